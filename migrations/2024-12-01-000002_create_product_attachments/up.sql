@@ -1,5 +1,7 @@
+-- Was 2024-12-01-000000, a version it shared with create_suppliers so only
+-- one of the two ever ran; IF NOT EXISTS lets it run again without harm
 -- Create product_attachments table for images and files
-CREATE TABLE product_attachments (
+CREATE TABLE IF NOT EXISTS product_attachments (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     product_id INTEGER NOT NULL,
     attachment_type TEXT NOT NULL CHECK (attachment_type IN ('image', 'document', 'manual', 'certificate')),
@@ -14,12 +16,12 @@ CREATE TABLE product_attachments (
 );
 
 -- Create index for efficient queries
-CREATE INDEX idx_product_attachments_product_id ON product_attachments(product_id);
-CREATE INDEX idx_product_attachments_type ON product_attachments(attachment_type);
-CREATE INDEX idx_product_attachments_primary ON product_attachments(is_primary);
+CREATE INDEX IF NOT EXISTS idx_product_attachments_product_id ON product_attachments(product_id);
+CREATE INDEX IF NOT EXISTS idx_product_attachments_type ON product_attachments(attachment_type);
+CREATE INDEX IF NOT EXISTS idx_product_attachments_primary ON product_attachments(is_primary);
 
 -- Create stock_audits table for stock audit functionality
-CREATE TABLE stock_audits (
+CREATE TABLE IF NOT EXISTS stock_audits (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     audit_name TEXT NOT NULL,
     audit_date DATE NOT NULL,
@@ -32,7 +34,7 @@ CREATE TABLE stock_audits (
 );
 
 -- Create stock_audit_items table for individual audit records
-CREATE TABLE stock_audit_items (
+CREATE TABLE IF NOT EXISTS stock_audit_items (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     audit_id INTEGER NOT NULL,
     product_id INTEGER NOT NULL,
@@ -48,7 +50,7 @@ CREATE TABLE stock_audit_items (
 );
 
 -- Create indexes for stock audit tables
-CREATE INDEX idx_stock_audits_date ON stock_audits(audit_date);
-CREATE INDEX idx_stock_audits_status ON stock_audits(status);
-CREATE INDEX idx_stock_audit_items_audit_id ON stock_audit_items(audit_id);
-CREATE INDEX idx_stock_audit_items_product_id ON stock_audit_items(product_id);
\ No newline at end of file
+CREATE INDEX IF NOT EXISTS idx_stock_audits_date ON stock_audits(audit_date);
+CREATE INDEX IF NOT EXISTS idx_stock_audits_status ON stock_audits(status);
+CREATE INDEX IF NOT EXISTS idx_stock_audit_items_audit_id ON stock_audit_items(audit_id);
+CREATE INDEX IF NOT EXISTS idx_stock_audit_items_product_id ON stock_audit_items(product_id);
\ No newline at end of file
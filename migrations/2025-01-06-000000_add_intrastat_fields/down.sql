@@ -0,0 +1,7 @@
+-- Remove customs details
+DROP INDEX IF EXISTS idx_deals_destination_country;
+DROP INDEX IF EXISTS idx_purchase_orders_dispatch_country;
+
+ALTER TABLE deals DROP COLUMN destination_country;
+ALTER TABLE purchase_orders DROP COLUMN incoterm;
+ALTER TABLE purchase_orders DROP COLUMN dispatch_country;
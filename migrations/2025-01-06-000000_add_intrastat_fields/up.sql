@@ -0,0 +1,7 @@
+-- Customs details for cross-border purchases and sales (Intrastat)
+ALTER TABLE purchase_orders ADD COLUMN dispatch_country TEXT;
+ALTER TABLE purchase_orders ADD COLUMN incoterm TEXT;
+ALTER TABLE deals ADD COLUMN destination_country TEXT;
+
+CREATE INDEX idx_purchase_orders_dispatch_country ON purchase_orders(dispatch_country);
+CREATE INDEX idx_deals_destination_country ON deals(destination_country);
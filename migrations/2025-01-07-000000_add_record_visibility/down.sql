@@ -0,0 +1,6 @@
+-- Remove record visibility
+DROP INDEX IF EXISTS idx_deals_owner_visibility;
+DROP INDEX IF EXISTS idx_leads_owner_visibility;
+
+ALTER TABLE deals DROP COLUMN visibility;
+ALTER TABLE leads DROP COLUMN visibility;
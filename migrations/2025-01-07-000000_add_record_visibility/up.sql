@@ -0,0 +1,6 @@
+-- Record visibility for ownership scoping (owner is assigned_to)
+ALTER TABLE leads ADD COLUMN visibility TEXT NOT NULL DEFAULT 'private' CHECK (visibility IN ('private', 'team', 'public'));
+ALTER TABLE deals ADD COLUMN visibility TEXT NOT NULL DEFAULT 'private' CHECK (visibility IN ('private', 'team', 'public'));
+
+CREATE INDEX idx_leads_owner_visibility ON leads(assigned_to, visibility);
+CREATE INDEX idx_deals_owner_visibility ON deals(assigned_to, visibility);
@@ -0,0 +1,2 @@
+-- Remove supplier lead time
+ALTER TABLE suppliers DROP COLUMN lead_time_days;
@@ -0,0 +1,2 @@
+-- Typical days from ordering to receipt, used for available-to-promise dates
+ALTER TABLE suppliers ADD COLUMN lead_time_days INTEGER;
@@ -0,0 +1,7 @@
+-- Remove access path indexes
+DROP INDEX IF EXISTS idx_transactions_account_date;
+DROP INDEX IF EXISTS idx_purchase_orders_status_expected;
+DROP INDEX IF EXISTS idx_activities_assigned_open;
+DROP INDEX IF EXISTS idx_deals_stage_close_date;
+DROP INDEX IF EXISTS idx_leads_status_created;
+DROP INDEX IF EXISTS idx_customers_status_name;
@@ -0,0 +1,7 @@
+-- Composite indexes for the filters and orderings used by list, search and report queries
+CREATE INDEX IF NOT EXISTS idx_customers_status_name ON customers(status, name);
+CREATE INDEX IF NOT EXISTS idx_leads_status_created ON leads(status, created_at);
+CREATE INDEX IF NOT EXISTS idx_deals_stage_close_date ON deals(stage, close_date);
+CREATE INDEX IF NOT EXISTS idx_activities_assigned_open ON activities(assigned_to, completed, activity_date);
+CREATE INDEX IF NOT EXISTS idx_purchase_orders_status_expected ON purchase_orders(status, expected_date);
+CREATE INDEX IF NOT EXISTS idx_transactions_account_date ON transactions(account_id, transaction_date);
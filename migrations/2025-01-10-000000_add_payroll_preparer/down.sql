@@ -0,0 +1,3 @@
+-- Remove payroll preparer and payer
+ALTER TABLE payrolls DROP COLUMN paid_by;
+ALTER TABLE payrolls DROP COLUMN prepared_by;
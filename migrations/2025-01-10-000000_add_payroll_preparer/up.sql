@@ -0,0 +1,3 @@
+-- Who prepared and who paid each payroll, for segregation of duties checks
+ALTER TABLE payrolls ADD COLUMN prepared_by INTEGER REFERENCES users(id);
+ALTER TABLE payrolls ADD COLUMN paid_by INTEGER REFERENCES users(id);
@@ -0,0 +1,5 @@
+-- Remove journal entries
+DROP INDEX IF EXISTS idx_transactions_journal_entry;
+ALTER TABLE transactions DROP COLUMN journal_entry_id;
+DROP INDEX IF EXISTS idx_journal_entries_date;
+DROP TABLE IF EXISTS journal_entries;
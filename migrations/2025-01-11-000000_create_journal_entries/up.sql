@@ -0,0 +1,17 @@
+-- Journal entries group the ledger lines of one posting; their lines net to zero
+CREATE TABLE journal_entries (
+    id INTEGER PRIMARY KEY NOT NULL,
+    entry_number TEXT NOT NULL UNIQUE,
+    entry_date DATE NOT NULL,
+    description TEXT NOT NULL,
+    reference TEXT,
+    reverses_entry_id INTEGER REFERENCES journal_entries (id),
+    created_by INTEGER REFERENCES users (id),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_journal_entries_date ON journal_entries(entry_date);
+
+-- Entry each transaction was posted in; earlier transactions have none
+ALTER TABLE transactions ADD COLUMN journal_entry_id INTEGER REFERENCES journal_entries (id);
+CREATE INDEX idx_transactions_journal_entry ON transactions(journal_entry_id);
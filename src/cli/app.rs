@@ -1,19 +1,24 @@
-use crate::cli::{commands::*, session::SessionManager};
+use crate::cli::{commands::*, prompt::{confirm_action, RiskLevel}, session::SessionManager, tui::pager};
 use crate::core::{
     auth::AuthService,
-    command::{CLIArgs, CLICommands, CommandRegistry},
+    command::{CLIArgs, CLICommands},
     config::CLIERPConfig,
     error::CLIERPError,
     logging,
     result::CLIERPResult,
 };
 use crate::database::{connection::{DatabaseManager, get_connection}, migrations};
-use clap::Parser;
+use crate::modules::system::audit::{changed_fields, print_changes, print_history, AuditService};
+use crate::modules::system::preferences::{PreferenceService, UserPreferences};
+use crate::utils::formatting::{for_output, format_currency};
+use crate::utils::i18n::{t, t_with};
+use crate::utils::table::TableView;
+use crate::outln;
+use std::fmt::Write as _;
 
 pub struct CLIApp {
     config: CLIERPConfig,
     auth_service: AuthService,
-    command_registry: CommandRegistry,
     session_manager: SessionManager,
 }
 
@@ -27,73 +32,138 @@ impl CLIApp {
         // Initialize logging
         logging::init_logging(&config)?;
 
+        // Initialize fiscal calendar
+        crate::utils::fiscal::FiscalCalendar::initialize(&config.fiscal)?;
+
+        // Initialize currency formatting
+        crate::utils::currency::CurrencyFormat::initialize(&config.currency)?;
+
+        // Initialize query cache
+        crate::utils::cache::QueryCache::initialize(&config.cache)?;
+
+        // Initialize automatic ledger postings
+        crate::modules::finance::PostingRules::initialize(&config.posting)?;
+
         // Initialize database
         DatabaseManager::initialize(&config)?;
 
-        // Run migrations
-        let mut conn = DatabaseManager::establish_connection(&config.database.url)?;
+        // Refuse a schema migrated by a newer release, then run migrations
+        let mut conn = DatabaseManager::establish_connection(&config.database.url, &config)?;
+        migrations::check_schema_version(&mut conn)?;
         migrations::run_migrations(&mut conn)?;
 
         // Initialize services
         let auth_service = AuthService::new(config.clone());
-        let command_registry = CommandRegistry::new();
         let session_manager = SessionManager::new(config.clone());
 
+        // Preferences of the logged-in user override the configuration
+        let preferences = match session_manager.get_current_user() {
+            Ok(Some(user)) => PreferenceService::load(&mut conn, user.id)?,
+            _ => UserPreferences::default(),
+        };
+
+        // Initialize message language
+        crate::utils::i18n::Language::initialize(preferences.locale.as_deref().unwrap_or(&config.lang))?;
+
+        // Initialize display timezone
+        crate::utils::timezone::DisplayTimezone::initialize(
+            preferences.timezone.as_deref().unwrap_or(&config.timezone),
+        )?;
+
+        // Initialize list page size and report format
+        if let Some(per_page) = preferences.per_page {
+            crate::utils::pagination::PaginationParams::set_default_per_page(per_page);
+        }
+        if let Some(format) = preferences.output_format {
+            crate::cli::commands::reports::set_default_format(format);
+        }
+
         // Create default admin user if needed
         auth_service.create_default_admin()?;
 
         Ok(Self {
             config,
             auth_service,
-            command_registry,
             session_manager,
         })
     }
 
-    pub async fn run(&mut self) -> CLIERPResult<()> {
-        let args = CLIArgs::parse();
-
-        // Register all commands
-        self.register_commands();
-
+    pub async fn run(&mut self, args: CLIArgs) -> CLIERPResult<()> {
         // Handle global flags
         if args.verbose {
             tracing::info!("Verbose mode enabled");
         }
+        if args.no_cache {
+            crate::utils::cache::QueryCache::current().set_enabled(false);
+        }
+        if args.yes {
+            crate::cli::prompt::set_assume_yes(true);
+        }
+        if args.no_pager {
+            crate::cli::tui::pager::set_pager_enabled(false);
+        }
+        crate::utils::formatting::configure_output(args.no_color, args.plain);
 
         // Execute command
         match args.command {
             Some(command) => self.execute_command(command).await,
             None => {
                 // Interactive mode or help
-                println!("CLIERP - CLI-based ERP System");
-                println!("Use --help for more information");
+                outln!("{}", t("app.banner"));
+                outln!("{}", t("app.help_hint"));
                 Ok(())
             }
         }
     }
 
-    fn register_commands(&mut self) {
-        // Register system commands
-        self.command_registry.register(SystemInitCommand::new());
-        self.command_registry.register(SystemStatusCommand::new());
-        self.command_registry.register(SystemMigrateCommand::new());
+    /// Run a command line and, when usage statistics are enabled, record
+    /// its subcommand path, duration and outcome
+    pub async fn run_recorded(&mut self, words: &[String], args: CLIArgs) -> CLIERPResult<()> {
+        use crate::database::NewCommandUsage;
+        use crate::modules::system::{command_path, UsageService};
+        use clap::CommandFactory;
 
-        // Register auth commands
-        self.command_registry
-            .register(AuthLoginCommand::new(self.auth_service.clone()));
-        self.command_registry.register(AuthLogoutCommand::new());
-        self.command_registry.register(AuthWhoamiCommand::new());
-
-        // Register HR commands
-        self.command_registry.register(HrDeptListCommand::new());
-        self.command_registry
-            .register(HrEmployeeListCommand::new(None));
+        if !self.config.usage.enabled {
+            return self.run(args).await;
+        }
+        let started = std::time::Instant::now();
+        let result = self.run(args).await;
+        let duration_ms = started.elapsed().as_millis() as i64;
 
-        // More commands will be registered as modules are implemented
+        let command = command_path(&CLIArgs::command(), words);
+        if !command.is_empty() {
+            let usage = NewCommandUsage {
+                command,
+                role: self.session_manager.get_current_user().ok().flatten().map(|user| user.role.to_string()),
+                duration_ms,
+                succeeded: result.is_ok(),
+                exit_code: result.as_ref().err().map(|e| e.exit_code()),
+            };
+            // Statistics must never make a command fail
+            if let Err(e) = get_connection().and_then(|mut conn| {
+                UsageService::record(&mut conn, &usage, self.config.usage.retention_days)
+            }) {
+                tracing::warn!("Could not record command usage: {}", e);
+            }
+        }
+        result
     }
 
     async fn execute_command(&mut self, command: CLICommands) -> CLIERPResult<()> {
+        // Attribute audited changes to the logged-in user; an expired session
+        // is reported by the command itself
+        if let Ok(Some(user)) = self.session_manager.get_current_user() {
+            AuditService::set_actor(user.id);
+        }
+
+        if let Some(permission) = crate::core::auth::required_permission(&command) {
+            let user = self
+                .session_manager
+                .get_current_user()?
+                .ok_or_else(|| CLIERPError::Authentication("Login required".to_string()))?;
+            crate::core::auth::require_permission(&user.role, permission)?;
+        }
+
         match command {
             CLICommands::System { action } => self.execute_system_command(action).await,
             CLICommands::Auth { action } => self.execute_auth_command(action).await,
@@ -103,6 +173,18 @@ impl CLIApp {
             CLICommands::Crm { action } => self.handle_crm_command(action).await,
             CLICommands::Sales { action } => self.execute_sales_command(action).await,
             CLICommands::Purchase { action } => self.execute_purchase_command(action).await,
+            CLICommands::Reports { save, action } => {
+                self.session_manager.get_current_user()?.ok_or_else(|| {
+                    CLIERPError::Authentication("Login required for reports".to_string())
+                })?;
+                execute_reports_command(action, save.as_deref())
+            }
+            CLICommands::Config { action } => self.execute_config_command(action).await,
+            CLICommands::Link { action } => self.execute_link_command(action).await,
+            CLICommands::Templates { action } => self.execute_email_template_command(action).await,
+            CLICommands::Events { action } => self.execute_event_command(action).await,
+            CLICommands::Export { action } => self.execute_export_command(action).await,
+            CLICommands::Shell => self.execute_shell().await,
         }
     }
 
@@ -114,7 +196,7 @@ impl CLIApp {
 
         match action {
             SystemCommands::Init => {
-                println!("Initializing CLIERP system...");
+                outln!("{}", t("system.initializing"));
 
                 // Initialize database
                 let mut conn = get_connection()?;
@@ -123,13 +205,13 @@ impl CLIApp {
                 // Create default admin
                 self.auth_service.create_default_admin()?;
 
-                println!("✓ System initialized successfully!");
-                println!("Default admin user created: username 'admin'");
-                println!("Please login and change the default password.");
+                outln!("{}", t("system.initialized"));
+                outln!("{}", t("system.default_admin"));
+                outln!("{}", t("system.change_password"));
                 Ok(())
             }
             SystemCommands::Status => {
-                println!("CLIERP System Status");
+                outln!("{}", t("system.status_title"));
                 println!("===================");
                 println!("Version: {}", crate::VERSION);
                 println!("Database: Connected");
@@ -137,22 +219,717 @@ impl CLIApp {
                 // Check database connection
                 let db_manager = DatabaseManager::new()?;
                 match db_manager.get_connection() {
-                    Ok(_) => println!("Database: ✓ Connected"),
-                    Err(e) => println!("Database: ✗ Error - {}", e),
+                    Ok(_) => outln!("{}", t("system.db_connected")),
+                    Err(e) => outln!("{}", t_with("system.db_error", &[("error", &e)])),
+                }
+
+                Ok(())
+            }
+            SystemCommands::Migrate { status: true, .. } => {
+                let mut conn = get_connection()?;
+                let status = migrations::migration_status(&mut conn)?;
+                println!("{:<10} {:<16} Migration", "State", "Version");
+                println!("{}", "-".repeat(70));
+                for migration in &status {
+                    println!(
+                        "{:<10} {:<16} {}",
+                        if migration.applied { "applied" } else { "pending" },
+                        migration.version,
+                        migration.name
+                    );
+                }
+                let pending = status.iter().filter(|m| !m.applied).count();
+                println!();
+                println!("{} applied, {} pending", status.len() - pending, pending);
+                Ok(())
+            }
+            SystemCommands::Migrate { rollback: Some(steps), .. } => {
+                use crate::database::models::UserRole;
+
+                let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+                    CLIERPError::Authentication("Login required to roll back migrations".to_string())
+                })?;
+                if !matches!(user.role, UserRole::Admin) {
+                    return Err(CLIERPError::Authorization("Admin role required".to_string()));
+                }
+                if !confirm_action(
+                    &format!(
+                        "Revert the last {} SQL migration(s)? Columns and tables they added are dropped with their data.",
+                        steps
+                    ),
+                    RiskLevel::High,
+                )? {
+                    println!("Cancelled.");
+                    return Ok(());
                 }
 
+                let mut conn = get_connection()?;
+                for version in migrations::rollback_sql_migrations(&mut conn, steps)? {
+                    outln!("↩️  Reverted {}", version);
+                }
                 Ok(())
             }
-            SystemCommands::Migrate => {
-                println!("Running database migrations...");
+            SystemCommands::Migrate { .. } => {
+                outln!("{}", t("system.migrating"));
                 let mut conn = get_connection()?;
                 migrations::run_migrations(&mut conn)?;
-                println!("✓ Migrations completed successfully!");
+                for version in migrations::run_sql_migrations(&mut conn)? {
+                    println!("  Applied {}", version);
+                }
+                outln!("{}", t("system.migrated"));
                 Ok(())
             }
             SystemCommands::CreateAdmin => {
                 self.auth_service.create_default_admin()?;
-                println!("✓ Default admin user created!");
+                outln!("{}", t("system.admin_created"));
+                Ok(())
+            }
+            SystemCommands::RefreshSummaries => {
+                use crate::modules::reporting::SummaryService;
+
+                let mut conn = get_connection()?;
+                let counts = SummaryService::rebuild(&mut conn)?;
+                crate::utils::cache::QueryCache::current().invalidate(&["inventory", "finance"]);
+                outln!(
+                    "✅ Summaries refreshed: {} product day(s), {} account month(s)",
+                    counts.product_days,
+                    counts.account_months
+                );
+                Ok(())
+            }
+            SystemCommands::Stats { top, no_save } => {
+                use crate::modules::system::StorageService;
+                use crate::utils::formatting::{format_bytes, format_datetime};
+
+                let mut conn = get_connection()?;
+                let report = StorageService::collect(&mut conn, top)?;
+                let signed = |value: i64| if value > 0 { format!("+{}", value) } else { value.to_string() };
+
+                outln!("💾 Database: {} ({} free)", format_bytes(report.database_bytes), format_bytes(report.free_bytes));
+                match (&report.previous, report.database_growth()) {
+                    (Some(previous), Some(growth)) => println!(
+                        "  Growth since {}: {}{}",
+                        format_datetime(&previous.taken_at),
+                        if growth > 0 { "+" } else { "" },
+                        format_bytes(growth)
+                    ),
+                    _ => println!("  No earlier stats run to compare with"),
+                }
+
+                println!();
+                outln!("📊 Tables:");
+                let mut view = TableView::new(&["Table", "Rows", "Growth", "Size"]);
+                for table in &report.tables {
+                    view.push(vec![
+                        table.table.clone(),
+                        table.rows.to_string(),
+                        table.growth.map(signed).unwrap_or_else(|| "-".to_string()),
+                        table.bytes.map(format_bytes).unwrap_or_else(|| "-".to_string()),
+                    ]);
+                }
+                println!("{}", view.render());
+
+                if report.page_sizes_available {
+                    outln!("🔍 Largest indexes:");
+                    let mut view = TableView::new(&["Index", "Table", "Size"]);
+                    for index in &report.indexes {
+                        view.push(vec![index.index.clone(), index.table.clone(), format_bytes(index.bytes)]);
+                    }
+                    println!("{}", view.render());
+                } else {
+                    println!("Table and index sizes need SQLite built with the dbstat extension.");
+                }
+
+                outln!("📎 Attachments: {} in total", format_bytes(report.attachment_bytes));
+                if !report.attachments.is_empty() {
+                    let mut view = TableView::new(&["Owner", "File", "Size"]);
+                    for attachment in &report.attachments {
+                        view.push(vec![attachment.owner.clone(), attachment.file_name.clone(), format_bytes(attachment.bytes)]);
+                    }
+                    println!("{}", view.render());
+                }
+
+                if !no_save {
+                    StorageService::save(&mut conn, &report)?;
+                }
+                Ok(())
+            }
+            SystemCommands::Usage { days, clear } => {
+                use crate::database::models::UserRole;
+                use crate::modules::system::UsageService;
+
+                let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+                    CLIERPError::Authentication("Login required for usage statistics".to_string())
+                })?;
+                if !matches!(user.role, UserRole::Admin | UserRole::Manager) {
+                    return Err(CLIERPError::Authorization("Admin or manager role required".to_string()));
+                }
+
+                let mut conn = get_connection()?;
+                if clear {
+                    let deleted = UsageService::clear(&mut conn)?;
+                    outln!("✅ Deleted {} recorded run(s)", deleted);
+                    return Ok(());
+                }
+                if !self.config.usage.enabled {
+                    println!("Usage statistics are off. Set usage.enabled = true (CLIERP_USAGE__ENABLED=true) to record runs on this machine.");
+                }
+
+                let since = chrono::Utc::now().naive_utc() - chrono::Duration::days(days);
+                let stats = UsageService::summarize(&mut conn, since)?;
+                if stats.is_empty() {
+                    println!("No runs recorded in the last {} day(s)", days);
+                    return Ok(());
+                }
+                outln!("📈 Command usage, last {} day(s), heaviest first:", days);
+                let mut view = TableView::new(&["Command", "Runs", "Errors", "Avg ms", "Max ms", "Total s", "Roles"]);
+                for command in &stats {
+                    let roles: Vec<String> = command.roles.iter().map(|(role, runs)| format!("{} {}", role, runs)).collect();
+                    view.push(vec![
+                        command.command.clone(),
+                        command.runs.to_string(),
+                        format!("{} ({:.1}%)", command.failures, command.error_rate()),
+                        command.average_ms().to_string(),
+                        command.max_ms.to_string(),
+                        format!("{:.1}", command.total_ms as f64 / 1000.0),
+                        roles.join(", "),
+                    ]);
+                }
+                println!("{}", view.render());
+                Ok(())
+            }
+            SystemCommands::Doctor => {
+                use crate::modules::system::{print_checks, CheckStatus, DoctorService};
+
+                let checks = DoctorService::run();
+                print_checks(&checks);
+                let failed = checks.iter().filter(|c| c.status == CheckStatus::Failed).count();
+                if failed > 0 {
+                    return Err(CLIERPError::BusinessLogic(format!("{} doctor check(s) failed", failed)));
+                }
+                Ok(())
+            }
+            SystemCommands::Analyze { slow_ms } => {
+                use crate::modules::system::AnalyzeService;
+
+                let mut conn = get_connection()?;
+                let report = AnalyzeService::run(&mut conn)?;
+
+                outln!("📊 Table Statistics:");
+                for table in &report.tables {
+                    println!("  {:<24} {:>10} rows  {} index(es)", table.table, table.rows, table.indexes.len());
+                }
+
+                println!();
+                outln!("🔍 Access Paths:");
+                let mut flagged = 0;
+                for check in &report.checks {
+                    let slow = check.elapsed_ms >= slow_ms;
+                    let marker = if check.full_scan || slow { "⚠️ " } else { "✅" };
+                    if check.full_scan || slow {
+                        flagged += 1;
+                    }
+                    outln!(
+                        "  {} {} ({}): {} rows in {:.2} ms",
+                        marker, check.name, check.table, check.rows_matched, check.elapsed_ms
+                    );
+                    for step in &check.plan {
+                        println!("      {}", step);
+                    }
+                    if check.full_scan {
+                        println!("      Full table scan; add an index on the filtered columns");
+                    } else if check.temp_sort {
+                        println!("      Sorted in a temporary b-tree; an index ending in the sort column avoids it");
+                    }
+                }
+                for name in &report.skipped {
+                    println!("  - {} (table not present)", name);
+                }
+
+                println!();
+                if flagged == 0 {
+                    outln!("✅ All {} access paths use an index", report.checks.len());
+                } else {
+                    outln!(
+                        "⚠️  {} of {} access paths scan a table or took over {} ms. Run 'clierp system migrate' and the SQL migrations to create missing indexes",
+                        flagged,
+                        report.checks.len(),
+                        slow_ms
+                    );
+                }
+                Ok(())
+            }
+            SystemCommands::Bench { iterations, json, output } => {
+                use crate::modules::system::BenchService;
+
+                let mut conn = get_connection()?;
+                let report = BenchService::run(&mut conn, iterations)?;
+                let rendered = serde_json::to_string_pretty(&report)?;
+                if let Some(path) = &output {
+                    std::fs::write(path, &rendered)?;
+                }
+                if json {
+                    println!("{}", rendered);
+                    return Ok(());
+                }
+
+                outln!(
+                    "⏱️  Benchmark ({} products, {} stock movements, {} runs each):",
+                    report.products, report.stock_movements, iterations
+                );
+                println!("  {:<24} {:>10} {:>10} {:>10} {:>12}", "Operation", "Mean ms", "p50 ms", "p95 ms", "Ops/s");
+                for result in &report.results {
+                    println!(
+                        "  {:<24} {:>10.3} {:>10.3} {:>10.3} {:>12.1}",
+                        result.name, result.mean_ms, result.p50_ms, result.p95_ms, result.ops_per_sec
+                    );
+                }
+                for reason in &report.skipped {
+                    println!("  - skipped {}", reason);
+                }
+                if let Some(path) = output {
+                    println!("\nResults written to {}", path);
+                }
+                Ok(())
+            }
+            SystemCommands::Check { fix } => {
+                use crate::database::models::UserRole;
+                use crate::modules::system::IntegrityService;
+
+                let mut fixed_by = None;
+                if fix {
+                    let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+                        CLIERPError::Authentication("Login required to repair data".to_string())
+                    })?;
+                    if !matches!(user.role, UserRole::Admin) {
+                        return Err(CLIERPError::Authorization("Admin role required".to_string()));
+                    }
+                    if !confirm_action(
+                        "Repair orphaned rows? Quarantined rows are moved to quarantined_rows.",
+                        RiskLevel::Medium,
+                    )? {
+                        println!("Cancelled.");
+                        return Ok(());
+                    }
+                    fixed_by = Some(user.id);
+                }
+
+                let mut conn = get_connection()?;
+                let findings = IntegrityService::run(&mut conn, fix, fixed_by)?;
+
+                outln!("🔎 Integrity Check:");
+                let mut problems = 0;
+                for finding in &findings {
+                    if finding.record_ids.is_empty() {
+                        outln!("  ✅ {}", finding.name);
+                        continue;
+                    }
+                    problems += finding.record_ids.len();
+                    let shown: Vec<String> = finding.record_ids.iter().take(10).map(|id| id.to_string()).collect();
+                    outln!(
+                        "  ⚠️  {}: {} row(s) in {} (ids {}{})",
+                        finding.name,
+                        finding.record_ids.len(),
+                        finding.table,
+                        shown.join(", "),
+                        if finding.record_ids.len() > shown.len() { ", ..." } else { "" }
+                    );
+                    if finding.repaired {
+                        println!("      Repaired: {}", finding.repair);
+                    } else {
+                        println!("      Fix: {}", finding.repair);
+                    }
+                }
+
+                println!();
+                if problems == 0 {
+                    outln!("✅ No integrity problems found");
+                } else if fix {
+                    outln!("✅ Repaired {} row(s)", problems);
+                } else {
+                    outln!("⚠️  {} row(s) need attention. Run 'clierp system check --fix' to repair them", problems);
+                }
+                Ok(())
+            }
+            SystemCommands::Archive { action } => {
+                use crate::core::command::ArchiveCommands;
+                use crate::database::models::UserRole;
+                use crate::modules::system::ArchiveService;
+                use crate::utils::formatting::{format_date, format_datetime};
+                use crate::utils::timezone::DisplayTimezone;
+
+                let mut conn = get_connection()?;
+                match action {
+                    ArchiveCommands::Run { before, dry_run } => {
+                        let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+                            CLIERPError::Authentication("Login required for archiving".to_string())
+                        })?;
+                        if !matches!(user.role, UserRole::Admin) {
+                            return Err(CLIERPError::Authorization("Admin role required".to_string()));
+                        }
+
+                        let before = before.unwrap_or_else(|| {
+                            DisplayTimezone::current().today()
+                                - chrono::Duration::days(self.config.archive.retention_days as i64)
+                        });
+                        if !dry_run
+                            && !confirm_action(
+                                &format!(
+                                    "Move stock movements and audit logs from before {} to the archive?",
+                                    format_date(&before)
+                                ),
+                                RiskLevel::Medium,
+                            )?
+                        {
+                            println!("Archive cancelled.");
+                            return Ok(());
+                        }
+
+                        let outcomes = ArchiveService::run(&mut conn, before, dry_run, Some(user.id))?;
+                        if dry_run {
+                            println!("Records before {} that would be archived:", format_date(&before));
+                        } else {
+                            outln!("✅ Archived records before {}:", format_date(&before));
+                        }
+                        for outcome in &outcomes {
+                            if outcome.summary_rows > 0 {
+                                println!(
+                                    "  {}: {} row(s), {} summary row(s) kept",
+                                    outcome.table_name, outcome.rows_archived, outcome.summary_rows
+                                );
+                            } else {
+                                println!("  {}: {} row(s)", outcome.table_name, outcome.rows_archived);
+                            }
+                        }
+                        Ok(())
+                    }
+                    ArchiveCommands::History { limit } => {
+                        let runs = ArchiveService::history(&mut conn, limit)?;
+                        if runs.is_empty() {
+                            println!("No archive runs recorded.");
+                            return Ok(());
+                        }
+                        println!("{:<5} {:<18} {:<12} {:>10} {:>10} {:<20}", "ID", "Table", "Before", "Archived", "Summary", "Run At");
+                        println!("{}", "-".repeat(80));
+                        for run in runs {
+                            println!(
+                                "{:<5} {:<18} {:<12} {:>10} {:>10} {:<20}",
+                                run.id,
+                                run.table_name,
+                                format_date(&DisplayTimezone::current().to_local(run.cutoff).date()),
+                                run.rows_archived,
+                                run.summary_rows,
+                                format_datetime(&run.run_at)
+                            );
+                        }
+                        Ok(())
+                    }
+                }
+            }
+            SystemCommands::Retention { action } => self.execute_retention_command(action),
+            SystemCommands::LegalHold { action } => self.execute_legal_hold_command(action),
+            SystemCommands::Delivery { action } => {
+                use crate::core::command::DeliveryCommands;
+                use crate::database::models::UserRole;
+                use crate::modules::system::DeliveryService;
+                use crate::utils::formatting::format_datetime;
+
+                let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+                    CLIERPError::Authentication("Login required for deliveries".to_string())
+                })?;
+                let require_admin = || {
+                    if matches!(user.role, UserRole::Admin) {
+                        Ok(())
+                    } else {
+                        Err(CLIERPError::Authorization("Admin role required".to_string()))
+                    }
+                };
+
+                let mut conn = get_connection()?;
+                match action {
+                    DeliveryCommands::Add {
+                        name,
+                        protocol,
+                        host,
+                        port,
+                        user: username,
+                        password_env,
+                        dir,
+                        export,
+                        template,
+                        locale,
+                        interval,
+                    } => {
+                        require_admin()?;
+                        let destination = DeliveryService::add(
+                            &mut conn,
+                            &name,
+                            protocol,
+                            &host,
+                            port,
+                            &username,
+                            password_env.as_deref(),
+                            dir.as_deref(),
+                            export,
+                            template.as_deref(),
+                            locale.as_deref(),
+                            interval,
+                            Some(user.id),
+                        )?;
+                        outln!(
+                            "✅ Delivery destination {} added: {} to {}://{}:{} every {} minute(s)",
+                            destination.name,
+                            destination.export,
+                            destination.protocol,
+                            destination.host,
+                            destination.port,
+                            destination.interval_minutes
+                        );
+                        Ok(())
+                    }
+                    DeliveryCommands::List => {
+                        let destinations = DeliveryService::list(&mut conn)?;
+                        if destinations.is_empty() {
+                            println!("No delivery destinations defined.");
+                            return Ok(());
+                        }
+                        println!(
+                            "{:<16} {:<13} {:<30} {:>8} {:<20} {:<10}",
+                            "Name", "Export", "Server", "Every", "Last Run", "Status"
+                        );
+                        println!("{}", "-".repeat(102));
+                        for destination in destinations {
+                            let status = match (destination.is_active, destination.last_status.as_deref()) {
+                                (false, _) => "paused".to_string(),
+                                (true, Some("failed")) => format!("failed x{}", destination.consecutive_failures),
+                                (true, status) => status.unwrap_or("pending").to_string(),
+                            };
+                            println!(
+                                "{:<16} {:<13} {:<30} {:>7}m {:<20} {:<10}",
+                                destination.name,
+                                destination.export,
+                                format!("{}://{}:{}", destination.protocol, destination.host, destination.port),
+                                destination.interval_minutes,
+                                destination.last_run_at.as_ref().map(format_datetime).unwrap_or_else(|| "-".to_string()),
+                                status
+                            );
+                        }
+                        Ok(())
+                    }
+                    DeliveryCommands::Pause { name } => {
+                        require_admin()?;
+                        DeliveryService::set_active(&mut conn, &name, false)?;
+                        outln!("✅ Deliveries to {} paused", name);
+                        Ok(())
+                    }
+                    DeliveryCommands::Resume { name } => {
+                        require_admin()?;
+                        DeliveryService::set_active(&mut conn, &name, true)?;
+                        outln!("✅ Deliveries to {} resumed", name);
+                        Ok(())
+                    }
+                    DeliveryCommands::Remove { name } => {
+                        require_admin()?;
+                        if !confirm_action(&format!("Remove delivery destination {} and its log?", name), RiskLevel::Medium)? {
+                            println!("Cancelled.");
+                            return Ok(());
+                        }
+                        if DeliveryService::remove(&mut conn, &name)? {
+                            outln!("✅ Delivery destination {} removed", name);
+                        } else {
+                            println!("No delivery destination named {}", name);
+                        }
+                        Ok(())
+                    }
+                    DeliveryCommands::Run { only } => {
+                        let outcomes = DeliveryService::run(&mut conn, only.as_deref())?;
+                        if outcomes.is_empty() {
+                            println!("No deliveries due.");
+                            return Ok(());
+                        }
+                        let mut failed = 0;
+                        for outcome in &outcomes {
+                            match &outcome.error {
+                                None => outln!(
+                                    "✅ {}: uploaded {} ({} bytes)",
+                                    outcome.destination, outcome.file_name, outcome.bytes
+                                ),
+                                Some(e) => {
+                                    failed += 1;
+                                    outln!("❌ {}: {}", outcome.destination, e);
+                                }
+                            }
+                        }
+                        // Fail the command so cron reports it
+                        if failed > 0 {
+                            return Err(CLIERPError::IoError(format!(
+                                "{} of {} deliveries failed",
+                                failed,
+                                outcomes.len()
+                            )));
+                        }
+                        Ok(())
+                    }
+                    DeliveryCommands::Log { name, limit } => {
+                        let transfers = DeliveryService::transfers(&mut conn, name.as_deref(), limit)?;
+                        if transfers.is_empty() {
+                            println!("No transfers logged.");
+                            return Ok(());
+                        }
+                        println!("{:<20} {:<16} {:<30} {:>9} {:<7} Error", "When", "Destination", "File", "Bytes", "Status");
+                        println!("{}", "-".repeat(100));
+                        for (transfer, destination) in transfers {
+                            println!(
+                                "{:<20} {:<16} {:<30} {:>9} {:<7} {}",
+                                format_datetime(&transfer.transferred_at),
+                                destination,
+                                transfer.file_name,
+                                transfer.bytes,
+                                transfer.status,
+                                transfer.message.unwrap_or_default()
+                            );
+                        }
+                        Ok(())
+                    }
+                }
+            }
+            SystemCommands::Serve { bind } => {
+                let server = crate::server::Server::new(&self.config, bind)?;
+                outln!("🌐 Serving portal pages on http://{}", server.bind_address());
+                if !self.config.server.tenants.is_empty() {
+                    let names: Vec<&str> = self.config.server.tenants.iter().map(|t| t.name.as_str()).collect();
+                    println!("Tenants: {}", names.join(", "));
+                }
+                println!("Links point to {}; press Ctrl+C to stop", self.config.server.base_url());
+                server.run()
+            }
+            SystemCommands::Dashboard { action } => {
+                use crate::core::command::DashboardCommands;
+                use crate::modules::reporting::DashboardService;
+                use crate::modules::system::portal::{PortalScope, PortalService};
+                use crate::utils::formatting::format_datetime;
+
+                let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+                    CLIERPError::Authentication("Login required for dashboard links".to_string())
+                })?;
+                let mut conn = get_connection()?;
+                match action {
+                    DashboardCommands::Share { days } => {
+                        let days = days.unwrap_or(self.config.server.link_ttl_days);
+                        let (link, token) =
+                            DashboardService::share(&mut conn, &self.config.auth.jwt_secret, user.id, days, Some(user.id))?;
+                        outln!("🔗 Dashboard link created (ID {}), valid until {}", link.id, format_datetime(&link.expires_at));
+                        println!("{}/dashboard/{}", self.config.server.base_url(), token);
+                        println!("It opens while 'clierp system serve' is running; keep it to yourself.");
+                    }
+                    DashboardCommands::Links => {
+                        let links = PortalService::list(&mut conn, PortalScope::Dashboard, user.id)?;
+                        if links.is_empty() {
+                            println!("No dashboard links.");
+                            return Ok(());
+                        }
+                        let now = chrono::Utc::now().naive_utc();
+                        let mut view = TableView::new(&["ID", "Created", "Expires", "Last Used", "State"]);
+                        for link in links {
+                            let state = if link.revoked_at.is_some() {
+                                "revoked"
+                            } else if link.expires_at <= now {
+                                "expired"
+                            } else {
+                                "active"
+                            };
+                            view.push(vec![
+                                link.id.to_string(),
+                                format_datetime(&link.created_at),
+                                format_datetime(&link.expires_at),
+                                link.last_used_at.map(|t| format_datetime(&t)).unwrap_or_else(|| "-".to_string()),
+                                state.to_string(),
+                            ]);
+                        }
+                        println!("{}", view.render());
+                    }
+                    DashboardCommands::Revoke { link_id } => {
+                        let owned = PortalService::list(&mut conn, PortalScope::Dashboard, user.id)?
+                            .iter()
+                            .any(|link| link.id == link_id);
+                        if !owned && !matches!(user.role, crate::database::models::UserRole::Admin) {
+                            return Err(CLIERPError::NotFound(format!("Dashboard link {} not found", link_id)));
+                        }
+                        let link = PortalService::revoke(&mut conn, PortalScope::Dashboard, link_id)?;
+                        outln!("✅ Dashboard link {} revoked", link.id);
+                    }
+                }
+                Ok(())
+            }
+            SystemCommands::Notifications { all } => {
+                use crate::modules::system::NotificationService;
+                use crate::utils::formatting::format_datetime;
+
+                let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+                    CLIERPError::Authentication("Login required for notifications".to_string())
+                })?;
+                let mut conn = get_connection()?;
+                let notifications = NotificationService::list(&mut conn, user.id, !all)?;
+                if notifications.is_empty() {
+                    println!("No {}notifications.", if all { "" } else { "unread " });
+                    return Ok(());
+                }
+
+                outln!("🔔 Notifications:");
+                for notification in &notifications {
+                    let marker = if notification.read_at.is_none() { "*" } else { " " };
+                    println!("{} {}  {}", marker, format_datetime(&notification.created_at), notification.subject);
+                    if let Some(body) = &notification.body {
+                        for line in body.lines() {
+                            println!("      {}", line);
+                        }
+                    }
+                }
+                let unread: Vec<i32> = notifications.iter().filter(|n| n.read_at.is_none()).map(|n| n.id).collect();
+                NotificationService::mark_read(&mut conn, &unread)?;
+                Ok(())
+            }
+            SystemCommands::EncryptDb { keep_plaintext } => {
+                use crate::database::encryption::{database_key, encrypt_database, DATABASE_KEY_ENV};
+                use crate::database::models::UserRole;
+
+                let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+                    CLIERPError::Authentication("Login required to encrypt the database".to_string())
+                })?;
+                if !matches!(user.role, UserRole::Admin) {
+                    return Err(CLIERPError::Authorization("Admin role required".to_string()));
+                }
+                if self.config.database.encrypted {
+                    return Err(CLIERPError::BusinessLogic(
+                        "database.encrypted is already set; the database is encrypted".to_string(),
+                    ));
+                }
+                let path = std::path::PathBuf::from(self.config.database.url.replace("sqlite:", ""));
+                let key = database_key(&self.config.database)?.ok_or_else(|| {
+                    CLIERPError::Validation(format!(
+                        "Set {} or database.key_command to the key to encrypt with",
+                        DATABASE_KEY_ENV
+                    ))
+                })?;
+                let prompt = match &keep_plaintext {
+                    Some(keep) => format!(
+                        "Encrypt {}? The plaintext database is moved to {}. Stop other CLIERP processes first.",
+                        path.display(),
+                        keep.display()
+                    ),
+                    None => format!(
+                        "Encrypt {}? The plaintext database is deleted; without the key the data cannot be read. Stop other CLIERP processes first.",
+                        path.display()
+                    ),
+                };
+                if !confirm_action(&prompt, RiskLevel::High)? {
+                    println!("Cancelled.");
+                    return Ok(());
+                }
+
+                let objects = encrypt_database(&path, &key, keep_plaintext.as_deref())?;
+                outln!("🔒 {} encrypted ({} tables, indexes and triggers)", path.display(), objects);
+                println!("Set database.encrypted = true (CLIERP_DATABASE__ENCRYPTED=true) and keep the key available to every CLIERP run.");
                 Ok(())
             }
         }
@@ -171,7 +948,7 @@ impl CLIApp {
                 } else {
                     // Prompt for password securely
                     use std::io::{self, Write};
-                    print!("Password: ");
+                    print!("{}", t("auth.password_prompt"));
                     io::stdout().flush().unwrap();
                     let mut input = String::new();
                     io::stdin().read_line(&mut input).unwrap();
@@ -182,10 +959,10 @@ impl CLIApp {
                     Ok(user) => {
                         let token = self.auth_service.generate_token(&user)?;
                         self.session_manager.save_session(&token)?;
-                        println!("✓ Login successful! Welcome, {}", user.username);
+                        outln!("{}", t_with("auth.login_success", &[("username", &user.username)]));
                     }
                     Err(e) => {
-                        println!("✗ Login failed: {}", e);
+                        outln!("{}", t_with("auth.login_failed", &[("error", &e)]));
                         return Err(e);
                     }
                 }
@@ -193,12 +970,12 @@ impl CLIApp {
             }
             AuthCommands::Logout => {
                 self.session_manager.clear_session()?;
-                println!("✓ Logged out successfully!");
+                outln!("{}", t("auth.logged_out"));
                 Ok(())
             }
             AuthCommands::Whoami => {
                 if let Some(user) = self.session_manager.get_current_user()? {
-                    println!("Current User:");
+                    outln!("{}", t("auth.current_user"));
                     println!("  Username: {}", user.username);
                     println!("  Email: {}", user.email);
                     println!("  Role: {}", user.role);
@@ -206,7 +983,7 @@ impl CLIApp {
                         println!("  Employee ID: {}", emp_id);
                     }
                 } else {
-                    println!("Not logged in");
+                    outln!("{}", t("auth.not_logged_in"));
                 }
                 Ok(())
             }
@@ -239,7 +1016,7 @@ impl CLIApp {
 
                 // Prompt for password
                 use std::io::{self, Write};
-                print!("Password for new user: ");
+                print!("{}", t("auth.new_password_prompt"));
                 io::stdout().flush().unwrap();
                 let mut password = String::new();
                 io::stdin().read_line(&mut password).unwrap();
@@ -252,369 +1029,4637 @@ impl CLIApp {
                     user_role,
                     employee_id,
                 )?;
-                println!("✓ User created successfully: {}", user.username);
+                outln!("{}", t_with("auth.user_created", &[("username", &user.username)]));
                 Ok(())
             }
+            AuthCommands::Scim { action } => self.execute_scim_command(action),
         }
     }
 
-    async fn execute_hr_command(
-        &mut self,
-        action: crate::core::command::HrCommands,
-    ) -> CLIERPResult<()> {
-        // Check authentication for HR commands
-        let _user = self.session_manager.get_current_user()?.ok_or_else(|| {
-            CLIERPError::Authentication("Login required for HR commands".to_string())
+    fn execute_retention_command(&self, action: crate::core::command::RetentionCommands) -> CLIERPResult<()> {
+        use crate::core::command::RetentionCommands;
+        use crate::database::models::UserRole;
+        use crate::modules::system::retention::{RetentionPolicy, RetentionService};
+        use crate::utils::formatting::{format_date, format_datetime};
+        use crate::utils::timezone::DisplayTimezone;
+
+        let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+            CLIERPError::Authentication("Login required for retention commands".to_string())
         })?;
+        let require_admin = || {
+            if matches!(user.role, UserRole::Admin) {
+                Ok(())
+            } else {
+                Err(CLIERPError::Authorization("Admin role required".to_string()))
+            }
+        };
+        let policies = RetentionPolicy::all_from_config(&self.config.retention)?;
+        let today = DisplayTimezone::current().today();
+        let mut conn = get_connection()?;
+
+        match action {
+            RetentionCommands::Run { preview } => {
+                if !preview {
+                    require_admin()?;
+                }
+                if policies.is_empty() {
+                    println!("No retention policies configured; add them under [retention] in the configuration.");
+                    return Ok(());
+                }
+                if !preview
+                    && !confirm_action(
+                        "Delete and anonymize records past their retention? This cannot be undone.",
+                        RiskLevel::High,
+                    )?
+                {
+                    println!("Retention run cancelled.");
+                    return Ok(());
+                }
 
-        println!("HR command executed: {:?}", action);
-        // HR command implementation will be added in Phase 2
+                let outcomes = RetentionService::run(&mut conn, &policies, today, preview, Some(user.id))?;
+                if preview {
+                    println!("Records a retention run would change:");
+                } else {
+                    outln!("✅ Retention applied:");
+                }
+                let mut view = TableView::new(&["Entity", "Action", "Before", "Records", "On Hold"]);
+                for outcome in &outcomes {
+                    view.push(vec![
+                        outcome.policy.entity.to_string(),
+                        outcome.policy.action.to_string(),
+                        format_date(&outcome.policy.keep_from(today)),
+                        outcome.affected.to_string(),
+                        outcome.held.to_string(),
+                    ]);
+                }
+                println!("{}", view.render());
+            }
+            RetentionCommands::Policies => {
+                if policies.is_empty() {
+                    println!("No retention policies configured.");
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["Entity", "Action", "After Days", "Keeps From"]);
+                for policy in &policies {
+                    view.push(vec![
+                        policy.entity.to_string(),
+                        policy.action.to_string(),
+                        policy.days.to_string(),
+                        format_date(&policy.keep_from(today)),
+                    ]);
+                }
+                println!("{}", view.render());
+            }
+            RetentionCommands::History { limit } => {
+                let runs = RetentionService::history(&mut conn, limit)?;
+                if runs.is_empty() {
+                    println!("No retention runs recorded.");
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["ID", "Entity", "Action", "Before", "Records", "On Hold", "Run At"]);
+                for run in runs {
+                    view.push(vec![
+                        run.id.to_string(),
+                        run.entity,
+                        run.action,
+                        format_date(&DisplayTimezone::current().to_local(run.cutoff).date()),
+                        run.rows_affected.to_string(),
+                        run.rows_held.to_string(),
+                        format_datetime(&run.run_at),
+                    ]);
+                }
+                println!("{}", view.render());
+            }
+        }
         Ok(())
     }
 
-    async fn execute_fin_command(
-        &mut self,
-        action: crate::core::command::FinCommands,
-    ) -> CLIERPResult<()> {
-        // Check authentication for Finance commands
-        let _user = self.session_manager.get_current_user()?.ok_or_else(|| {
-            CLIERPError::Authentication("Login required for Finance commands".to_string())
+    fn execute_legal_hold_command(&self, action: crate::core::command::LegalHoldCommands) -> CLIERPResult<()> {
+        use crate::core::command::LegalHoldCommands;
+        use crate::database::models::UserRole;
+        use crate::modules::system::legal_hold::{HoldEntity, LegalHoldService};
+        use crate::utils::formatting::format_datetime;
+
+        let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+            CLIERPError::Authentication("Login required for legal hold commands".to_string())
         })?;
+        let require_admin = || {
+            if matches!(user.role, UserRole::Admin) {
+                Ok(())
+            } else {
+                Err(CLIERPError::Authorization("Admin role required".to_string()))
+            }
+        };
+        let mut conn = get_connection()?;
 
-        println!("Finance command executed: {:?}", action);
-        // Finance command implementation will be added in Phase 2
+        match action {
+            LegalHoldCommands::Place { entity, record_id, reason } => {
+                require_admin()?;
+                let entity: HoldEntity = entity.parse()?;
+                let hold = LegalHoldService::place(&mut conn, entity, record_id, &reason, Some(user.id))?;
+                outln!("✅ Legal hold {} placed on {} {}", hold.id, hold.entity, hold.record_id);
+            }
+            LegalHoldCommands::Release { hold_id } => {
+                require_admin()?;
+                let hold = LegalHoldService::release(&mut conn, hold_id, Some(user.id))?;
+                outln!("✅ Legal hold {} on {} {} released", hold.id, hold.entity, hold.record_id);
+            }
+            LegalHoldCommands::Report { entity } => {
+                let entity = entity.map(|e| e.parse::<HoldEntity>()).transpose()?;
+                let records = LegalHoldService::report(&mut conn, entity)?;
+                if records.is_empty() {
+                    println!("No legal holds in force.");
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["ID", "Entity", "Record", "Name", "Reason", "Placed", "By"]);
+                for record in &records {
+                    view.push(vec![
+                        record.hold.id.to_string(),
+                        record.hold.entity.clone(),
+                        record.hold.record_id.to_string(),
+                        record.label.clone().unwrap_or_else(|| "-".to_string()),
+                        record.hold.reason.clone(),
+                        format_datetime(&record.hold.placed_at),
+                        record.hold.placed_by.map(|id| format!("user {}", id)).unwrap_or_default(),
+                    ]);
+                }
+                println!("{}", view.render());
+                println!("{} record(s) on legal hold", records.len());
+            }
+        }
         Ok(())
     }
 
-    async fn execute_inv_command(
+    fn execute_scim_command(&self, action: crate::core::command::ScimCommands) -> CLIERPResult<()> {
+        use crate::core::command::ScimCommands;
+        use crate::database::models::UserRole;
+        use crate::modules::system::portal::{PortalScope, PortalService};
+        use crate::utils::formatting::format_datetime;
+
+        let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+            CLIERPError::Authentication("Login required for SCIM commands".to_string())
+        })?;
+        if !matches!(user.role, UserRole::Admin) {
+            return Err(CLIERPError::Authorization("Admin role required".to_string()));
+        }
+        let mut conn = get_connection()?;
+
+        match action {
+            ScimCommands::Share { days } => {
+                let days = days.unwrap_or(self.config.server.link_ttl_days);
+                let (link, token) = PortalService::issue(
+                    &mut conn,
+                    &self.config.auth.jwt_secret,
+                    PortalScope::Scim,
+                    user.id,
+                    days,
+                    Some(user.id),
+                )?;
+                outln!("🔗 SCIM token created (ID {}), valid until {}", link.id, format_datetime(&link.expires_at));
+                println!("Base URL: {}/scim/v2", self.config.server.base_url());
+                println!("Token:    {}", token);
+                println!("Configure the identity provider to send it as 'Authorization: Bearer <token>' while");
+                println!("'clierp system serve' is running. Provisioned users log in through the directory.");
+            }
+            ScimCommands::Links => {
+                let links = PortalService::list(&mut conn, PortalScope::Scim, user.id)?;
+                if links.is_empty() {
+                    println!("No SCIM tokens.");
+                    return Ok(());
+                }
+                let now = chrono::Utc::now().naive_utc();
+                let mut view = TableView::new(&["ID", "Created", "Expires", "Last Used", "State"]);
+                for link in links {
+                    let state = if link.revoked_at.is_some() {
+                        "revoked"
+                    } else if link.expires_at <= now {
+                        "expired"
+                    } else {
+                        "active"
+                    };
+                    view.push(vec![
+                        link.id.to_string(),
+                        format_datetime(&link.created_at),
+                        format_datetime(&link.expires_at),
+                        link.last_used_at.map(|t| format_datetime(&t)).unwrap_or_else(|| "-".to_string()),
+                        state.to_string(),
+                    ]);
+                }
+                println!("{}", view.render());
+            }
+            ScimCommands::Revoke { link_id } => {
+                let link = PortalService::revoke(&mut conn, PortalScope::Scim, link_id)?;
+                outln!("✅ SCIM token {} revoked", link.id);
+            }
+        }
+        Ok(())
+    }
+
+    async fn execute_hr_command(
         &mut self,
-        action: crate::core::command::InvCommands,
+        action: crate::core::command::HrCommands,
     ) -> CLIERPResult<()> {
-        // Check authentication for Inventory commands
-        let _user = self.session_manager.get_current_user()?.ok_or_else(|| {
-            CLIERPError::Authentication("Login required for Inventory commands".to_string())
+        use crate::core::command::HrCommands;
+
+        // Check authentication for HR commands
+        let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+            CLIERPError::Authentication("Login required for HR commands".to_string())
         })?;
 
-        use crate::core::command::{InvCommands, ProductCommands, StockCommands};
-        use crate::modules::inventory::{CategoryService, ProductService};
+        match action {
+            HrCommands::Dept { action } => self.execute_dept_command(action, user),
+            HrCommands::Employee { action } => self.execute_employee_command(action, user),
+            HrCommands::Comp { action } => self.execute_comp_command(action, user),
+            HrCommands::Docs { action } => self.execute_docs_command(action, user),
+            HrCommands::Leave { action } => self.execute_leave_command(action, user),
+            HrCommands::Team { mine, manager } => self.execute_team_view(mine, manager, user),
+            HrCommands::Attendance { action } => self.execute_attendance_command(action, user),
+            HrCommands::Payroll { action } => self.execute_payroll_command(action, user),
+        }
+    }
+
+    fn execute_dept_command(
+        &self,
+        action: crate::core::command::DeptCommands,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::DeptCommands;
+        use crate::core::workflow::role_satisfies;
+
+        let require_manager = || {
+            if role_satisfies(&user.role, "manager") {
+                Ok(())
+            } else {
+                Err(CLIERPError::Authorization("Department changes require role: manager".to_string()))
+            }
+        };
 
         match action {
-            InvCommands::Product { action } => {
-                self.execute_product_command(action).await
+            DeptCommands::Add { name, description, manager_id } => {
+                require_manager()?;
+                HrDeptAddCommand::new(name, description, manager_id).execute(Some(&user))
             }
-            InvCommands::Stock { action } => {
-                self.execute_stock_command(action).await
+            DeptCommands::List { table } => HrDeptListCommand::new().with_table(table).execute(Some(&user)),
+            DeptCommands::Show { id } => HrDeptShowCommand::new(id).execute(Some(&user)),
+            DeptCommands::Update { id, name, description, manager_id } => {
+                require_manager()?;
+                HrDeptUpdateCommand::new(id, name, description, manager_id).execute(Some(&user))
             }
+            DeptCommands::Delete { id } => {
+                require_manager()?;
+                if !confirm_action(&format!("Delete department {}?", id), RiskLevel::Medium)? {
+                    println!("Cancelled.");
+                    return Ok(());
+                }
+                HrDeptDeleteCommand::new(id).execute(Some(&user))
+            }
+            DeptCommands::Export { format, output, template } => HrDeptExportCommand::new(format, output)
+                .with_template(template)
+                .execute(Some(&user)),
         }
     }
 
-    async fn execute_product_command(
-        &mut self,
-        action: crate::core::command::ProductCommands,
+    fn execute_employee_command(
+        &self,
+        action: crate::core::command::EmployeeCommands,
+        user: crate::core::auth::AuthenticatedUser,
     ) -> CLIERPResult<()> {
-        use crate::core::command::ProductCommands;
-        use crate::modules::inventory::ProductService;
-        use crate::utils::pagination::PaginationParams;
+        use crate::core::command::EmployeeCommands;
+        use crate::core::workflow::role_satisfies;
+        use crate::utils::timezone::DisplayTimezone;
 
-        let service = ProductService::new();
+        let require_manager = || {
+            if role_satisfies(&user.role, "manager") {
+                Ok(())
+            } else {
+                Err(CLIERPError::Authorization("Employee changes require role: manager".to_string()))
+            }
+        };
 
         match action {
-            ProductCommands::Add {
-                sku,
+            EmployeeCommands::Add {
+                code,
                 name,
-                category_id,
-                price,
-                cost_price,
-                stock,
-                min_stock,
-                max_stock,
-                unit,
-                description,
-                barcode,
+                email,
+                phone,
+                department_id,
+                position,
+                salary,
+                hire_date,
             } => {
-                let product = service.create_product(
-                    &sku,
-                    &name,
-                    description.as_deref(),
-                    category_id,
-                    price,
-                    cost_price.unwrap_or(0),
-                    stock.unwrap_or(0),
-                    min_stock.unwrap_or(0),
-                    max_stock,
-                    &unit.unwrap_or_else(|| "ea".to_string()),
-                    barcode.as_deref(),
-                )?;
-
-                println!("✅ Product created:");
-                println!("  ID: {}", product.id);
-                println!("  SKU: {}", product.sku);
-                println!("  Name: {}", product.name);
-                println!("  Category ID: {}", product.category_id);
-                println!("  Price: ¥{}", product.price as f64 / 100.0);
-                println!("  Stock: {} {}", product.current_stock, product.unit);
+                require_manager()?;
+                let hire_date = hire_date.unwrap_or_else(|| DisplayTimezone::current().today());
+                HrEmployeeAddCommand::new(name, email, phone, department_id, position, hire_date, salary)
+                    .with_code(code)
+                    .execute(Some(&user))
             }
-            ProductCommands::List {
-                category_id,
-                search,
-                low_stock,
-                active,
-                page,
-                per_page,
+            EmployeeCommands::List { department, status, filter, table } => HrEmployeeListCommand::new(department)
+                .with_status(status)
+                .with_filter(filter)
+                .with_table(table)
+                .execute(Some(&user)),
+            EmployeeCommands::Show { id, history } => HrEmployeeShowCommand::new(Some(id), None)
+                .with_history(history)
+                .execute(Some(&user)),
+            EmployeeCommands::Update {
+                id,
+                name,
+                email,
+                phone,
+                department_id,
+                position,
+                salary,
+                status,
             } => {
-                let pagination = PaginationParams::new(page.unwrap_or(1), per_page.unwrap_or(20));
-                let result = service.list_products(
-                    &pagination,
-                    category_id,
-                    active.unwrap_or(true),
-                    search.as_deref(),
-                    low_stock.unwrap_or(false),
-                )?;
-
-                if result.data.is_empty() {
-                    println!("No products found.");
+                require_manager()?;
+                HrEmployeeUpdateCommand::new(id, name, email, phone, department_id, position, salary, status)
+                    .execute(Some(&user))
+            }
+            EmployeeCommands::Search { query } => HrEmployeeSearchCommand::new(query).execute(Some(&user)),
+            EmployeeCommands::Delete { id } => {
+                require_manager()?;
+                if !confirm_action(&format!("Terminate employee {}?", id), RiskLevel::Medium)? {
+                    println!("Cancelled.");
                     return Ok(());
                 }
+                HrEmployeeDeleteCommand::new(id).execute(Some(&user))
+            }
+            EmployeeCommands::Export { format, output, department, template, filter } => {
+                HrEmployeeExportCommand::new(format, output, department)
+                    .with_template(template)
+                    .with_filter(filter)
+                    .execute(Some(&user))
+            }
+        }
+    }
 
-                println!("Products:");
-                for (i, prod_with_cat) in result.data.iter().enumerate() {
-                    let status = if prod_with_cat.product.current_stock <= prod_with_cat.product.min_stock_level {
-                        "[LOW STOCK]"
-                    } else if prod_with_cat.product.is_active {
-                        "[ACTIVE]"
-                    } else {
-                        "[INACTIVE]"
-                    };
+    fn execute_attendance_status(
+        &self,
+        employee_id: Option<i32>,
+        date: Option<chrono::NaiveDate>,
+    ) -> CLIERPResult<()> {
+        use crate::modules::hr::AttendanceService;
+        use crate::utils::timezone::DisplayTimezone;
+
+        let mut conn = get_connection()?;
+        let service = AttendanceService::new();
+        let date = date.unwrap_or_else(|| DisplayTimezone::current().today());
+        let time = |t: Option<chrono::NaiveTime>| t.map(|t| t.format("%H:%M").to_string()).unwrap_or_else(|| "-".to_string());
+
+        let records = match employee_id {
+            Some(employee_id) => service.get_attendance_by_date(&mut conn, employee_id, date)?.into_iter().collect(),
+            None => service.get_attendance_on(&mut conn, date)?,
+        };
+
+        println!("Attendance on {}:", date);
+        if records.is_empty() {
+            println!("  No attendance recorded.");
+            return Ok(());
+        }
+        println!("{:<10} {:<20} {:<8} {:<8} {:>8} {:<10}", "Code", "Name", "In", "Out", "OT hrs", "Status");
+        println!("{}", "-".repeat(69));
+        for record in &records {
+            println!(
+                "{:<10} {:<20} {:<8} {:<8} {:>8.1} {:<10}",
+                record.employee.employee_code,
+                record.employee.name,
+                time(record.attendance.check_in),
+                time(record.attendance.check_out),
+                record.attendance.overtime_hours.unwrap_or(0.0),
+                record.attendance.status
+            );
+        }
+        Ok(())
+    }
+
+    fn execute_payroll_command(
+        &self,
+        action: crate::core::command::PayrollCommands,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::PayrollCommands;
+        use crate::modules::hr::PayrollService;
+
+        let mut conn = get_connection()?;
+        let service = PayrollService::new();
+
+        match action {
+            PayrollCommands::Calculate { period, employee_id } => {
+                let calculations = match employee_id {
+                    Some(employee_id) => vec![service.calculate_payroll(&mut conn, employee_id, period.clone())?],
+                    None => service.calculate_period_payrolls(&mut conn, period.clone())?,
+                };
 
+                println!("Payroll calculation {} (preview, nothing is saved)", period);
+                println!(
+                    "{:<20} {:>15} {:>8} {:>15} {:>15} {:>15}",
+                    "Name", "Base", "OT hrs", "Overtime", "Deductions", "Net"
+                );
+                println!("{}", "-".repeat(93));
+                for calculation in &calculations {
                     println!(
-                        "  {}. {} ({}) - {} - ¥{} - {} {} {}",
-                        i + 1,
-                        prod_with_cat.product.name,
-                        prod_with_cat.product.sku,
-                        prod_with_cat.category.name,
-                        prod_with_cat.product.price as f64 / 100.0,
-                        prod_with_cat.product.current_stock,
-                        prod_with_cat.product.unit,
-                        status
+                        "{:<20} {:>15} {:>8.1} {:>15} {:>15} {:>15}",
+                        calculation.employee_name,
+                        format_currency(calculation.base_salary),
+                        calculation.overtime_hours,
+                        format_currency(calculation.overtime_pay),
+                        format_currency(calculation.total_deductions),
+                        format_currency(calculation.net_salary)
                     );
                 }
+                println!("{}", "-".repeat(93));
+                println!(
+                    "Net total: {}",
+                    format_currency(calculations.iter().map(|c| c.net_salary).sum::<i32>())
+                );
+                println!("Use 'clierp hr payroll run --period {} --commit' to save the payrolls", period);
+            }
+            PayrollCommands::Status { period } => {
+                let payrolls = service.get_payrolls_by_period(&mut conn, &period)?;
+                if payrolls.is_empty() {
+                    println!("No payrolls saved for {}.", period);
+                    return Ok(());
+                }
 
+                println!("Payrolls for {}:", period);
+                println!("{:<6} {:<10} {:<20} {:>15} {:<10} {:<12}", "ID", "Code", "Name", "Net", "Status", "Paid");
+                println!("{}", "-".repeat(78));
+                for entry in &payrolls {
+                    println!(
+                        "{:<6} {:<10} {:<20} {:>15} {:<10} {:<12}",
+                        entry.payroll.id,
+                        entry.employee.employee_code,
+                        entry.employee.name,
+                        format_currency(entry.payroll.net_salary),
+                        entry.payroll.status,
+                        entry.payroll.payment_date.map(|d| d.to_string()).unwrap_or_else(|| "-".to_string())
+                    );
+                }
+                println!("{}", "-".repeat(78));
                 println!(
-                    "\nPage {} of {} (Total: {} products)",
-                    result.current_page(), result.pagination.total_pages, result.pagination.total_count
+                    "{} payroll(s), net total {}",
+                    payrolls.len(),
+                    format_currency(payrolls.iter().map(|p| p.payroll.net_salary).sum::<i32>())
                 );
             }
-            ProductCommands::Show { id, sku } => {
-                let product = if let Some(id) = id {
-                    service.get_product_by_id(id)?
-                } else if let Some(sku) = sku {
-                    service.get_product_by_sku(&sku)?
-                        .ok_or_else(|| CLIERPError::NotFound(format!("Product with SKU '{}' not found", sku)))?
-                } else {
-                    return Err(CLIERPError::InvalidInput("Either --id or --sku must be provided".to_string()));
-                };
+            PayrollCommands::Run { period, commit } => return self.execute_payroll_run(&period, commit, user),
+        }
+        Ok(())
+    }
 
-                println!("Product Details:");
-                println!("  ID: {}", product.id);
-                println!("  SKU: {}", product.sku);
-                println!("  Name: {}", product.name);
-                println!("  Category ID: {}", product.category_id);
-                println!("  Price: ¥{}", product.price as f64 / 100.0);
-                println!("  Cost Price: ¥{}", product.cost_price as f64 / 100.0);
-                println!("  Current Stock: {} {}", product.current_stock, product.unit);
-                println!("  Min Stock Level: {}", product.min_stock_level);
-                if let Some(max_level) = product.max_stock_level {
-                    println!("  Max Stock Level: {}", max_level);
+    fn execute_comp_command(
+        &self,
+        action: crate::core::command::CompCommands,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::{BandCommands, CompCommands, RaiseCommands};
+        use crate::database::models::UserRole;
+        use crate::modules::hr::{BandStatus, CompensationService};
+        use crate::utils::formatting::{format_date, format_datetime};
+        use crate::utils::timezone::DisplayTimezone;
+
+        let require_admin = || {
+            if matches!(user.role, UserRole::Admin) {
+                Ok(())
+            } else {
+                Err(CLIERPError::Authorization("Admin role required".to_string()))
+            }
+        };
+        let mut conn = get_connection()?;
+
+        match action {
+            CompCommands::Band { action } => match action {
+                BandCommands::Set { position, level, min, max } => {
+                    require_admin()?;
+                    let band = CompensationService::set_band(&mut conn, &position, level.as_deref(), min, max, Some(user.id))?;
+                    outln!(
+                        "✅ Band {} for {} ({}): {} - {}",
+                        band.id,
+                        band.position,
+                        band.level.as_deref().unwrap_or("all levels"),
+                        format_currency(band.min_salary),
+                        format_currency(band.max_salary)
+                    );
+                }
+                BandCommands::List => {
+                    let bands = CompensationService::bands(&mut conn)?;
+                    if bands.is_empty() {
+                        println!("No salary bands defined.");
+                        return Ok(());
+                    }
+                    println!("{:<5} {:<24} {:<12} {:>15} {:>15}", "ID", "Position", "Level", "Minimum", "Maximum");
+                    println!("{}", "-".repeat(75));
+                    for band in bands {
+                        println!(
+                            "{:<5} {:<24} {:<12} {:>15} {:>15}",
+                            band.id,
+                            band.position,
+                            band.level.as_deref().unwrap_or("(all)"),
+                            format_currency(band.min_salary),
+                            format_currency(band.max_salary)
+                        );
+                    }
+                }
+                BandCommands::Remove { band_id } => {
+                    require_admin()?;
+                    CompensationService::remove_band(&mut conn, band_id)?;
+                    outln!("✅ Salary band {} removed", band_id);
+                }
+                BandCommands::Check { all } => {
+                    let checks = CompensationService::check_bands(&mut conn, !all)?;
+                    if checks.is_empty() {
+                        outln!("✅ Every employee is paid within their salary band");
+                        return Ok(());
+                    }
+                    println!(
+                        "{:<10} {:<20} {:<20} {:<10} {:>15} {:>31} {:<12}",
+                        "Code", "Name", "Position", "Level", "Salary", "Band", "Status"
+                    );
+                    println!("{}", "-".repeat(124));
+                    for check in &checks {
+                        let band = check
+                            .band
+                            .as_ref()
+                            .map(|b| format!("{} - {}", format_currency(b.min_salary), format_currency(b.max_salary)))
+                            .unwrap_or_else(|| "-".to_string());
+                        println!(
+                            "{:<10} {:<20} {:<20} {:<10} {:>15} {:>31} {:<12}",
+                            check.employee.employee_code,
+                            check.employee.name,
+                            check.employee.position,
+                            check.employee.level.as_deref().unwrap_or("-"),
+                            format_currency(check.employee.salary),
+                            band,
+                            check.status
+                        );
+                    }
+                    let outside = checks.iter().filter(|c| c.status != BandStatus::Within).count();
+                    if outside > 0 {
+                        outln!("\n⚠️  {} employee(s) outside their band or without one", outside);
+                    }
+                }
+            },
+            CompCommands::Level { employee_id, level } => {
+                require_admin()?;
+                let employee = CompensationService::set_level(&mut conn, employee_id, level.as_deref())?;
+                outln!(
+                    "✅ {} ({}) level set to {}",
+                    employee.name,
+                    employee.employee_code,
+                    employee.level.as_deref().unwrap_or("(none)")
+                );
+            }
+            CompCommands::Raise { action } => match action {
+                RaiseCommands::Propose { employee_id, salary, effective, reason } => {
+                    let (raise, band_status) =
+                        CompensationService::propose_raise(&mut conn, employee_id, salary, effective, &reason, Some(user.id))?;
+                    outln!("✅ Raise {} proposed, waiting for approval", raise.id);
+                    outln!(
+                        "  Salary: {} → {} from {}",
+                        format_currency(raise.current_salary),
+                        format_currency(raise.proposed_salary),
+                        format_date(&raise.effective_date)
+                    );
+                    if band_status != BandStatus::Within {
+                        outln!("⚠️  The proposed salary is {}", band_status);
+                    }
+                }
+                RaiseCommands::List { status, limit } => {
+                    let raises = CompensationService::raises(&mut conn, status, limit)?;
+                    if raises.is_empty() {
+                        println!("No raises found.");
+                        return Ok(());
+                    }
+                    println!(
+                        "{:<5} {:<10} {:<20} {:>15} {:>15} {:<12} {:<10} {:<19}",
+                        "ID", "Code", "Name", "Current", "Proposed", "Effective", "Status", "Proposed At"
+                    );
+                    println!("{}", "-".repeat(112));
+                    for (raise, employee) in raises {
+                        println!(
+                            "{:<5} {:<10} {:<20} {:>15} {:>15} {:<12} {:<10} {:<19}",
+                            raise.id,
+                            employee.employee_code,
+                            employee.name,
+                            format_currency(raise.current_salary),
+                            format_currency(raise.proposed_salary),
+                            format_date(&raise.effective_date),
+                            raise.status,
+                            format_datetime(&raise.proposed_at)
+                        );
+                    }
+                }
+                RaiseCommands::Approve { raise_id, note, override_sod } => {
+                    let raise = CompensationService::approve_raise(
+                        &mut conn,
+                        raise_id,
+                        &user,
+                        note.as_deref(),
+                        override_sod.as_deref(),
+                    )?;
+                    if raise.applied_at.is_some() {
+                        outln!(
+                            "✅ Raise {} approved; salary is now {}",
+                            raise.id,
+                            format_currency(raise.proposed_salary)
+                        );
+                    } else {
+                        outln!(
+                            "✅ Raise {} approved; it takes effect on {}",
+                            raise.id,
+                            format_date(&raise.effective_date)
+                        );
+                    }
+                }
+                RaiseCommands::Reject { raise_id, reason } => {
+                    let raise = CompensationService::reject_raise(&mut conn, raise_id, &user, &reason)?;
+                    println!("Raise {} rejected; the salary was not changed", raise.id);
+                }
+                RaiseCommands::ApplyDue => {
+                    require_admin()?;
+                    let applied = CompensationService::apply_due_raises(&mut conn, DisplayTimezone::current().today())?;
+                    if applied.is_empty() {
+                        println!("No approved raises are due.");
+                    } else {
+                        outln!("✅ Applied {} raise(s):", applied.len());
+                        for raise in applied {
+                            outln!(
+                                "  Raise {}: employee {} → {} from {}",
+                                raise.id,
+                                raise.employee_id,
+                                format_currency(raise.proposed_salary),
+                                format_date(&raise.effective_date)
+                            );
+                        }
+                    }
+                }
+            },
+            CompCommands::History { employee_id } => {
+                let changes = CompensationService::history(&mut conn, employee_id)?;
+                if changes.is_empty() {
+                    println!("No salary changes recorded for employee {}.", employee_id);
+                    return Ok(());
+                }
+                println!("{:<12} {:>15} {:>15} {:<8} {:<7} {:<19}", "Effective", "From", "To", "Source", "Raise", "Recorded");
+                println!("{}", "-".repeat(82));
+                for change in changes {
+                    println!(
+                        "{:<12} {:>15} {:>15} {:<8} {:<7} {:<19}",
+                        format_date(&change.effective_date),
+                        change.old_salary.map(format_currency).unwrap_or_else(|| "-".to_string()),
+                        format_currency(change.new_salary),
+                        change.source,
+                        change.raise_id.map(|id| id.to_string()).unwrap_or_else(|| "-".to_string()),
+                        format_datetime(&change.changed_at)
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_payroll_run(
+        &self,
+        period: &str,
+        commit: bool,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::workflow::role_satisfies;
+        use crate::modules::hr::{PayrollAccounts, PayrollService};
+
+        if !role_satisfies(&user.role, "manager") {
+            return Err(CLIERPError::Authorization(
+                "Payroll runs require role: manager".to_string(),
+            ));
+        }
+        // Check the posting accounts before calculating anything
+        let accounts = if commit {
+            Some(PayrollAccounts::from_config(&self.config.payroll)?)
+        } else {
+            None
+        };
+
+        let mut conn = get_connection()?;
+        let service = PayrollService::new();
+        let run = service.prepare_run(&mut conn, period)?;
+
+        println!("Payroll run {} ({} to {})", run.period, run.start_date, run.end_date);
+        println!(
+            "{:<10} {:<20} {:>15} {:>8} {:>15} {:>15} {:>15}",
+            "Code", "Name", "Base", "OT hrs", "Overtime", "Deductions", "Net"
+        );
+        println!("{}", "-".repeat(104));
+        for line in &run.lines {
+            let calculation = &line.calculation;
+            println!(
+                "{:<10} {:<20} {:>15} {:>8.1} {:>15} {:>15} {:>15}",
+                line.employee.employee_code,
+                line.employee.name,
+                format_currency(calculation.base_salary),
+                calculation.overtime_hours,
+                format_currency(calculation.overtime_pay),
+                format_currency(calculation.total_deductions),
+                format_currency(calculation.net_salary)
+            );
+        }
+        println!("{}", "-".repeat(104));
+        println!("Employees:  {}", run.lines.len());
+        println!("Gross:      {}", format_currency(run.gross_total()));
+        println!("Deductions: {}", format_currency(run.deduction_total()));
+        println!("Net:        {}", format_currency(run.net_total()));
+        if !run.skipped.is_empty() {
+            println!("\nSkipped:");
+            for skipped in &run.skipped {
+                println!("  {} {}: {}", skipped.employee.employee_code, skipped.employee.name, skipped.reason);
+            }
+        }
+
+        match accounts {
+            Some(accounts) => {
+                if !confirm_action(
+                    &format!(
+                        "Save {} payroll(s) and post {} to the ledger?",
+                        run.lines.len(),
+                        format_currency(run.gross_total())
+                    ),
+                    RiskLevel::Medium,
+                )? {
+                    println!("Payroll run cancelled; nothing was saved.");
+                    return Ok(());
+                }
+                let payrolls = service.commit_run(&mut conn, &run, &accounts, Some(user.id))?;
+                outln!(
+                    "\n✅ Saved {} payroll(s) and posted {} to {} (expense), {} (tax) and {} (payable)",
+                    payrolls.len(),
+                    format_currency(run.gross_total()),
+                    accounts.expense,
+                    accounts.tax,
+                    accounts.payable
+                );
+            }
+            None => println!("\nReview only: nothing was saved. Run again with --commit to save and post to the ledger."),
+        }
+        Ok(())
+    }
+
+    fn execute_attendance_command(
+        &self,
+        action: crate::core::command::AttendanceCommands,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::{AttendanceCommands, RemotePolicyCommands};
+        use crate::database::models::{Attendance, AttendancePunch, RemoteWorkPolicy, UserRole};
+        use crate::modules::hr::{PunchDetails, RemoteCheckinService, RemotePolicySettings};
+        use crate::utils::formatting::format_datetime;
+
+        let print_policy = |policy: &RemoteWorkPolicy| {
+            println!("Employee {}:", policy.employee_id);
+            println!("  Location required: {}", if policy.require_location { "yes" } else { "no" });
+            println!("  Device required:   {}", if policy.require_device { "yes" } else { "no" });
+            println!("  Registered device: {}", policy.registered_device.as_deref().unwrap_or("-"));
+            if let (Some(latitude), Some(longitude), Some(radius)) =
+                (policy.site_latitude, policy.site_longitude, policy.radius_meters)
+            {
+                println!("  Work site:         {:.6}, {:.6} within {} m", latitude, longitude, radius);
+            }
+        };
+        let print_punch = |attendance: &Attendance, punch: &AttendancePunch| {
+            outln!(
+                "✅ Employee {} checked {} at {}",
+                punch.employee_id,
+                punch.punch_type,
+                format_datetime(&punch.punched_at)
+            );
+            println!("  Status: {}", attendance.status);
+            if let Some(distance) = punch.distance_meters {
+                println!("  Distance from work site: {:.0} m", distance);
+            }
+        };
+        let mut conn = get_connection()?;
+
+        match action {
+            AttendanceCommands::Checkin { employee_id, lat, lon, device } => {
+                let punch = PunchDetails { latitude: lat, longitude: lon, device_fingerprint: device };
+                let (attendance, recorded) = RemoteCheckinService::check_in(&mut conn, employee_id, &punch)?;
+                print_punch(&attendance, &recorded);
+            }
+            AttendanceCommands::Checkout { employee_id, lat, lon, device } => {
+                let punch = PunchDetails { latitude: lat, longitude: lon, device_fingerprint: device };
+                let (attendance, recorded) = RemoteCheckinService::check_out(&mut conn, employee_id, &punch)?;
+                print_punch(&attendance, &recorded);
+            }
+            AttendanceCommands::Policy { action } => {
+                if !matches!(action, RemotePolicyCommands::Show { .. }) && !matches!(user.role, UserRole::Admin) {
+                    return Err(CLIERPError::Authorization("Admin role required".to_string()));
+                }
+                match action {
+                    RemotePolicyCommands::Set {
+                        employee_id,
+                        require_location,
+                        require_device,
+                        device,
+                        site_lat,
+                        site_lon,
+                        radius,
+                    } => {
+                        let settings = RemotePolicySettings {
+                            require_location,
+                            require_device,
+                            registered_device: device,
+                            site: site_lat.zip(site_lon).zip(radius).map(|((lat, lon), r)| (lat, lon, r)),
+                        };
+                        let policy = RemoteCheckinService::set_policy(&mut conn, employee_id, settings, Some(user.id))?;
+                        outln!("✅ Remote check-in policy saved");
+                        print_policy(&policy);
+                    }
+                    RemotePolicyCommands::Show { employee_id: Some(employee_id) } => {
+                        match RemoteCheckinService::policy(&mut conn, employee_id)? {
+                            Some(policy) => print_policy(&policy),
+                            None => println!("Employee {} has no remote check-in policy", employee_id),
+                        }
+                    }
+                    RemotePolicyCommands::Show { employee_id: None } => {
+                        let policies = RemoteCheckinService::policies(&mut conn)?;
+                        if policies.is_empty() {
+                            println!("No remote check-in policies");
+                        }
+                        for policy in &policies {
+                            print_policy(policy);
+                        }
+                    }
+                    RemotePolicyCommands::Clear { employee_id } => {
+                        RemoteCheckinService::remove_policy(&mut conn, employee_id)?;
+                        outln!("✅ Remote check-in policy removed for employee {}", employee_id);
+                    }
+                }
+            }
+            AttendanceCommands::Status { employee_id, date } => {
+                self.execute_attendance_status(employee_id, date)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_leave_command(
+        &self,
+        action: crate::core::command::LeaveCommands,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::LeaveCommands;
+        use crate::modules::hr::LeaveService;
+        use crate::utils::formatting::format_date;
+
+        let mut conn = get_connection()?;
+
+        match action {
+            LeaveCommands::Request { employee_id, from, to, reason } => {
+                let employee_id = employee_id.or(user.employee_id).ok_or_else(|| {
+                    CLIERPError::ValidationError(
+                        "Your user is not linked to an employee; pass --employee-id".to_string(),
+                    )
+                })?;
+                let request = LeaveService::request(&mut conn, employee_id, from, to, reason.as_deref(), &user)?;
+                outln!(
+                    "✅ Leave request {} submitted: {} to {} ({} working days)",
+                    request.id,
+                    format_date(&request.start_date),
+                    format_date(&request.end_date),
+                    request.days
+                );
+            }
+            LeaveCommands::List { employee_id, status } => {
+                let requests = LeaveService::requests(&mut conn, employee_id, status)?;
+                if requests.is_empty() {
+                    println!("No leave requests found.");
+                    return Ok(());
+                }
+                println!("{:<5} {:<9} {:<12} {:<12} {:>5} {:<9} {:<30}", "ID", "Employee", "From", "To", "Days", "Status", "Reason");
+                println!("{}", "-".repeat(86));
+                for request in requests {
+                    println!(
+                        "{:<5} {:<9} {:<12} {:<12} {:>5} {:<9} {:<30}",
+                        request.id,
+                        request.employee_id,
+                        format_date(&request.start_date),
+                        format_date(&request.end_date),
+                        request.days,
+                        request.status,
+                        request.reason.as_deref().unwrap_or("-")
+                    );
+                }
+            }
+            LeaveCommands::Approve { id, note } => {
+                let request = LeaveService::approve(&mut conn, id, &user, note.as_deref())?;
+                outln!(
+                    "✅ Leave request {} approved; {} days recorded as holiday",
+                    request.id, request.days
+                );
+            }
+            LeaveCommands::Reject { id, reason } => {
+                let request = LeaveService::reject(&mut conn, id, &user, &reason)?;
+                outln!("✅ Leave request {} rejected", request.id);
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_team_view(
+        &self,
+        mine: bool,
+        manager: Option<i32>,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::database::models::UserRole;
+        use crate::modules::hr::TeamService;
+        use crate::utils::formatting::format_date;
+        use crate::utils::timezone::DisplayTimezone;
+
+        let manager_id = if mine {
+            user.employee_id.ok_or_else(|| {
+                CLIERPError::ValidationError("Your user is not linked to an employee".to_string())
+            })?
+        } else {
+            let manager_id = manager.unwrap_or_default();
+            if user.employee_id != Some(manager_id) && !matches!(user.role, UserRole::Admin) {
+                return Err(CLIERPError::Authorization(
+                    "Admin role required to view another manager's team".to_string(),
+                ));
+            }
+            manager_id
+        };
+        let today = DisplayTimezone::current().today();
+        let mut conn = get_connection()?;
+        let team = TeamService::overview(&mut conn, manager_id, today)?;
+
+        outln!("👥 Team of employee {} on {}", manager_id, format_date(&today));
+        if team.is_empty() {
+            println!("No direct reports.");
+            return Ok(());
+        }
+        println!("{:<10} {:<20} {:<12} {:>6} {:>6} {:<12} {:<20}", "Code", "Name", "Today", "Leave", "Tasks", "Review", "Attention");
+        println!("{}", "-".repeat(92));
+        for member in &team {
+            let mut attention = Vec::new();
+            if member.overdue_tasks > 0 {
+                attention.push(format!("{} overdue", member.overdue_tasks));
+            }
+            if member.pending_raises > 0 {
+                attention.push("raise pending".to_string());
+            }
+            if member.review_upcoming(today) {
+                attention.push("review due".to_string());
+            }
+            println!(
+                "{:<10} {:<20} {:<12} {:>6} {:>6} {:<12} {:<20}",
+                member.employee.employee_code,
+                member.employee.name,
+                member.attendance.as_ref().map(|a| a.status.as_str()).unwrap_or("not in"),
+                member.pending_leave.len(),
+                member.open_tasks,
+                format_date(&member.next_review),
+                attention.join(", ")
+            );
+        }
+
+        let pending: Vec<_> = team.iter().flat_map(|m| m.pending_leave.iter().map(move |r| (m, r))).collect();
+        if !pending.is_empty() {
+            println!();
+            println!("Pending leave requests:");
+            for (member, request) in pending {
+                println!(
+                    "  #{} {}: {} to {} ({} days)",
+                    request.id,
+                    member.employee.name,
+                    format_date(&request.start_date),
+                    format_date(&request.end_date),
+                    request.days
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_docs_command(
+        &self,
+        action: crate::core::command::DocsCommands,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::DocsCommands;
+        use crate::database::models::UserRole;
+        use crate::modules::hr::{DocumentService, DocumentState};
+        use crate::utils::formatting::format_date;
+        use crate::utils::timezone::DisplayTimezone;
+
+        let require_admin = || {
+            if matches!(user.role, UserRole::Admin) {
+                Ok(())
+            } else {
+                Err(CLIERPError::Authorization("Admin role required".to_string()))
+            }
+        };
+        let optional_date = |date: Option<chrono::NaiveDate>| date.map(|d| format_date(&d)).unwrap_or_else(|| "-".to_string());
+        let today = DisplayTimezone::current().today();
+        let mut conn = get_connection()?;
+
+        match action {
+            DocsCommands::Add { employee_id, document_type, number, issued, expires, file, notes } => {
+                let document = DocumentService::add_document(
+                    &mut conn,
+                    employee_id,
+                    document_type,
+                    number.as_deref(),
+                    issued,
+                    expires,
+                    file.as_deref(),
+                    notes.as_deref(),
+                    Some(user.id),
+                )?;
+                outln!("✅ Document {} ({}) recorded for employee {}", document.id, document.document_type, employee_id);
+                if let Some(expiry) = document.expiry_date {
+                    println!("  Expires: {}", format_date(&expiry));
+                }
+            }
+            DocsCommands::List { employee_id } => {
+                let documents = DocumentService::documents(&mut conn, employee_id)?;
+                if documents.is_empty() {
+                    println!("No documents recorded for employee {}.", employee_id);
+                    return Ok(());
+                }
+                println!("{:<5} {:<14} {:<20} {:<12} {:<12} {:<8} {:<5}", "ID", "Type", "Number", "Issued", "Expires", "Status", "File");
+                println!("{}", "-".repeat(82));
+                for document in documents {
+                    println!(
+                        "{:<5} {:<14} {:<20} {:<12} {:<12} {:<8} {:<5}",
+                        document.id,
+                        document.document_type,
+                        document.document_number.as_deref().unwrap_or("-"),
+                        optional_date(document.issued_date),
+                        optional_date(document.expiry_date),
+                        if document.is_expired(today) { "expired" } else { "valid" },
+                        if document.file_path.is_some() { "yes" } else { "no" }
+                    );
+                }
+            }
+            DocsCommands::Renew { document_id, expires } => {
+                let document = DocumentService::renew(&mut conn, document_id, expires)?;
+                outln!(
+                    "✅ Document {} now expires {}",
+                    document.id,
+                    document.expiry_date.map(|d| format!("on {}", format_date(&d))).unwrap_or_else(|| "never".to_string())
+                );
+            }
+            DocsCommands::Remove { document_id } => {
+                if !confirm_action(
+                    &format!("Remove document {} and delete its stored file?", document_id),
+                    RiskLevel::High,
+                )? {
+                    println!("Cancelled.");
+                    return Ok(());
+                }
+                DocumentService::remove_document(&mut conn, document_id)?;
+                outln!("✅ Document {} removed", document_id);
+            }
+            DocsCommands::Expiring { days } => {
+                let expiring = DocumentService::expiring(&mut conn, today, days)?;
+                if expiring.is_empty() {
+                    println!("No documents expire in the next {} days.", days);
+                    return Ok(());
+                }
+                println!("{:<5} {:<10} {:<20} {:<14} {:<12} {:>6} {:<8}", "ID", "Code", "Name", "Type", "Expires", "Days", "Alerted");
+                println!("{}", "-".repeat(81));
+                for (document, employee) in expiring {
+                    let expiry = document.expiry_date.unwrap_or(today);
+                    println!(
+                        "{:<5} {:<10} {:<20} {:<14} {:<12} {:>6} {:<8}",
+                        document.id,
+                        employee.employee_code,
+                        employee.name,
+                        document.document_type,
+                        format_date(&expiry),
+                        (expiry - today).num_days(),
+                        if document.alerted_at.is_some() { "yes" } else { "no" }
+                    );
+                }
+            }
+            DocsCommands::Alerts { days } => {
+                let alerted = DocumentService::send_expiry_alerts(&mut conn, today, days)?;
+                if alerted.is_empty() {
+                    println!("No new document expiry alerts.");
+                    return Ok(());
+                }
+                outln!("⚠️  {} document expiry alert(s):", alerted.len());
+                for (document, employee) in alerted {
+                    let expiry = document.expiry_date.unwrap_or(today);
+                    let when = if expiry < today { "expired on" } else { "expires on" };
+                    println!(
+                        "  {} of {} ({}) {} {}",
+                        document.document_type,
+                        employee.name,
+                        employee.employee_code,
+                        when,
+                        format_date(&expiry)
+                    );
+                }
+            }
+            DocsCommands::Require { document_type, department_id } => {
+                require_admin()?;
+                DocumentService::require(&mut conn, document_type, department_id)?;
+                match department_id {
+                    Some(department_id) => outln!("✅ {} required in department {}", document_type, department_id),
+                    None => outln!("✅ {} required of every employee", document_type),
+                }
+            }
+            DocsCommands::Unrequire { document_type, department_id } => {
+                require_admin()?;
+                DocumentService::unrequire(&mut conn, document_type, department_id)?;
+                outln!("✅ {} is no longer required", document_type);
+            }
+            DocsCommands::Requirements => {
+                let requirements = DocumentService::requirements(&mut conn)?;
+                if requirements.is_empty() {
+                    println!("No document types are required.");
+                    return Ok(());
+                }
+                for requirement in requirements {
+                    println!(
+                        "  {:<14} {}",
+                        requirement.document_type,
+                        requirement
+                            .department_id
+                            .map(|d| format!("department {}", d))
+                            .unwrap_or_else(|| "all departments".to_string())
+                    );
+                }
+            }
+            DocsCommands::Compliance { department_id } => {
+                let gaps = DocumentService::compliance(&mut conn, today, department_id)?;
+                if gaps.is_empty() {
+                    outln!("✅ Every employee holds the required documents");
+                    return Ok(());
+                }
+                println!("{:<20} {:<10} {:<20} {:<14} {:<8} {:<12}", "Department", "Code", "Name", "Document", "Status", "Expired");
+                println!("{}", "-".repeat(89));
+                for gap in &gaps {
+                    println!(
+                        "{:<20} {:<10} {:<20} {:<14} {:<8} {:<12}",
+                        gap.department,
+                        gap.employee.employee_code,
+                        gap.employee.name,
+                        gap.document_type,
+                        gap.state,
+                        optional_date(gap.expired_on)
+                    );
+                }
+                let missing = gaps.iter().filter(|g| g.state == DocumentState::Missing).count();
+                outln!("\n⚠️  {} missing, {} expired", missing, gaps.len() - missing);
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_payment_command(
+        &self,
+        action: crate::core::command::PaymentCommands,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::PaymentCommands;
+        use crate::core::workflow::role_satisfies;
+        use crate::database::models::{PaymentBatchKind, UserRole};
+        use crate::modules::finance::{PaymentDebtor, PaymentService, ReceivableService, PAYEE_EMPLOYEE, PAYEE_SUPPLIER};
+        use crate::utils::formatting::{format_date, format_datetime};
+        use crate::utils::timezone::DisplayTimezone;
+
+        let require_manager = || {
+            if role_satisfies(&user.role, "manager") {
+                Ok(())
+            } else {
+                Err(CLIERPError::Authorization("Payments require role: manager".to_string()))
+            }
+        };
+        let today = DisplayTimezone::current().today();
+        let mut conn = get_connection()?;
+
+        match action {
+            PaymentCommands::SetAccount { employee, supplier, account, bic, holder } => {
+                if !matches!(user.role, UserRole::Admin) {
+                    return Err(CLIERPError::Authorization("Admin role required".to_string()));
+                }
+                let (payee_type, payee_id) = match (employee, supplier) {
+                    (Some(id), _) => (PAYEE_EMPLOYEE, id),
+                    (None, Some(id)) => (PAYEE_SUPPLIER, id),
+                    (None, None) => {
+                        return Err(CLIERPError::ValidationError("Give --employee or --supplier".to_string()))
+                    }
+                };
+                let account = PaymentService::set_bank_account(
+                    &mut conn,
+                    payee_type,
+                    payee_id,
+                    &account,
+                    bic.as_deref(),
+                    holder.as_deref(),
+                    Some(user.id),
+                )?;
+                outln!("✅ Bank account of {} {} set to {}", payee_type, payee_id, account.account_number);
+            }
+            PaymentCommands::Accounts => {
+                require_manager()?;
+                let accounts = PaymentService::bank_accounts(&mut conn)?;
+                if accounts.is_empty() {
+                    println!("No bank accounts recorded.");
+                    return Ok(());
+                }
+                println!("{:<10} {:<6} {:<34} {:<12} {:<20}", "Payee", "ID", "Account", "BIC", "Holder");
+                println!("{}", "-".repeat(86));
+                for account in accounts {
+                    println!(
+                        "{:<10} {:<6} {:<34} {:<12} {:<20}",
+                        account.payee_type,
+                        account.payee_id,
+                        account.account_number,
+                        account.bic.as_deref().unwrap_or("-"),
+                        account.account_holder.as_deref().unwrap_or("-")
+                    );
+                }
+            }
+            PaymentCommands::Export { kind, period, due_by, format, output, execution_date } => {
+                require_manager()?;
+                let debtor = PaymentDebtor::from_config(&self.config.bank)?;
+                let plan = match kind {
+                    PaymentBatchKind::Payroll => {
+                        let period = period.ok_or_else(|| {
+                            CLIERPError::ValidationError("--period is required for payroll payments".to_string())
+                        })?;
+                        PaymentService::plan_payroll(&mut conn, &period)?
+                    }
+                    PaymentBatchKind::Supplier => PaymentService::plan_suppliers(&mut conn, due_by.unwrap_or(today))?,
+                };
+                for (payee, reason) in &plan.skipped {
+                    outln!("⚠️  Skipped {}: {}", payee, reason);
+                }
+                let batch = PaymentService::export(
+                    &mut conn,
+                    kind,
+                    &plan,
+                    format,
+                    &output,
+                    execution_date.unwrap_or(today),
+                    &debtor,
+                    Some(user.id),
+                )?;
+                outln!(
+                    "✅ Batch {} ({}): {} payment(s) totalling {} written to {}",
+                    batch.id,
+                    batch.batch_reference,
+                    batch.item_count,
+                    format_currency(batch.total_amount),
+                    batch.file_path
+                );
+                println!(
+                    "Upload the file to the bank, then run 'clierp fin payments confirm {}' to mark the payments paid.",
+                    batch.id
+                );
+            }
+            PaymentCommands::Batches { limit } => {
+                require_manager()?;
+                let batches = PaymentService::batches(&mut conn, limit)?;
+                if batches.is_empty() {
+                    println!("No payment batches found.");
+                    return Ok(());
+                }
+                println!(
+                    "{:<5} {:<24} {:<9} {:<8} {:<12} {:>6} {:>15} {:<10} {:<19}",
+                    "ID", "Reference", "Kind", "Format", "Execution", "Items", "Total", "Status", "Created"
+                );
+                println!("{}", "-".repeat(116));
+                for batch in batches {
+                    println!(
+                        "{:<5} {:<24} {:<9} {:<8} {:<12} {:>6} {:>15} {:<10} {:<19}",
+                        batch.id,
+                        batch.batch_reference,
+                        batch.kind,
+                        batch.format,
+                        format_date(&batch.execution_date),
+                        batch.item_count,
+                        format_currency(batch.total_amount),
+                        batch.status,
+                        format_datetime(&batch.created_at)
+                    );
+                }
+            }
+            PaymentCommands::Show { batch_id } => {
+                require_manager()?;
+                let batch = PaymentService::find_batch(&mut conn, batch_id)?;
+                println!(
+                    "Batch {} ({}, {}) - {} - {}",
+                    batch.id, batch.batch_reference, batch.kind, batch.status, batch.file_path
+                );
+                println!("{:<24} {:<34} {:<12} {:>15} {:<24}", "Payee", "Account", "BIC", "Amount", "Remittance");
+                println!("{}", "-".repeat(113));
+                for item in PaymentService::items(&mut conn, batch_id)? {
+                    println!(
+                        "{:<24} {:<34} {:<12} {:>15} {:<24}",
+                        item.payee_name,
+                        item.account_number,
+                        item.bic.as_deref().unwrap_or("-"),
+                        format_currency(item.amount),
+                        item.remittance
+                    );
+                }
+            }
+            PaymentCommands::Confirm { batch_id, override_sod } => {
+                require_manager()?;
+                let batch = PaymentService::confirm(&mut conn, batch_id, &user, override_sod.as_deref())?;
+                outln!("✅ Batch {} confirmed; {} payment(s) marked paid", batch.id, batch.item_count);
+            }
+            PaymentCommands::Cancel { batch_id } => {
+                require_manager()?;
+                let batch = PaymentService::cancel(&mut conn, batch_id, user.id)?;
+                println!("Batch {} cancelled; its payments can be exported again", batch.id);
+            }
+            PaymentCommands::Receive { invoice_id, amount, date, reference } => {
+                require_manager()?;
+                let (invoice, payment) = ReceivableService::record_payment(
+                    &mut conn,
+                    invoice_id,
+                    amount,
+                    date.unwrap_or(today),
+                    reference.as_deref(),
+                    Some(user.id),
+                )?;
+                outln!(
+                    "✅ Payment {} of {} recorded on invoice {}; {} open ({})",
+                    payment.id,
+                    format_currency(payment.amount),
+                    invoice.invoice_number,
+                    format_currency(invoice.open_amount()),
+                    invoice.status
+                );
+            }
+            PaymentCommands::Return { payment_id, date, reason } => {
+                require_manager()?;
+                let (invoice, payment) =
+                    ReceivableService::return_payment(&mut conn, payment_id, date.unwrap_or(today), reason.as_deref())?;
+                outln!(
+                    "✅ Payment of {} returned; invoice {} is open again for {}",
+                    format_currency(payment.amount),
+                    invoice.invoice_number,
+                    format_currency(invoice.open_amount())
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_revenue_command(
+        &self,
+        action: crate::core::command::RevenueCommands,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::RevenueCommands;
+        use crate::core::workflow::role_satisfies;
+        use crate::modules::finance::{monthly_amounts, RecognitionAccounts, RecognitionService};
+        use crate::utils::formatting::format_date;
+        use crate::utils::timezone::DisplayTimezone;
+
+        let require_manager = || {
+            if role_satisfies(&user.role, "manager") {
+                Ok(())
+            } else {
+                Err(CLIERPError::Authorization("Revenue recognition requires role: manager".to_string()))
+            }
+        };
+        let today = DisplayTimezone::current().today();
+        let mut conn = get_connection()?;
+
+        match action {
+            RevenueCommands::Defer { invoice_id, months, start, amount, description } => {
+                require_manager()?;
+                let accounts = RecognitionAccounts::from_config(&self.config.revenue_recognition)?;
+                let schedule = RecognitionService::defer(
+                    &mut conn,
+                    &accounts,
+                    invoice_id,
+                    start,
+                    months,
+                    amount,
+                    description.as_deref(),
+                    Some(user.id),
+                )?;
+                let monthly = monthly_amounts(schedule.total_amount, schedule.months);
+                outln!(
+                    "✅ Schedule {}: {} deferred to {}, recognized at {} a month over {} months",
+                    schedule.id,
+                    format_currency(schedule.total_amount),
+                    schedule.deferred_account,
+                    format_currency(monthly[0]),
+                    schedule.months
+                );
+                println!("Run 'clierp fin revenue recognize' after each month end to post the revenue earned.");
+            }
+            RevenueCommands::Recognize { as_of, schedule, dry_run } => {
+                if !dry_run {
+                    require_manager()?;
+                }
+                let as_of = as_of.unwrap_or(today);
+                let pending = RecognitionService::pending(&mut conn, as_of, schedule)?;
+                if pending.is_empty() {
+                    println!("Nothing to recognize as of {}.", format_date(&as_of));
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["Schedule", "Invoice", "Month", "Posted On", "Amount"]);
+                for item in &pending {
+                    view.push(vec![
+                        item.schedule_id.to_string(),
+                        item.invoice_number.clone(),
+                        item.period.clone(),
+                        format_date(&item.recognize_on),
+                        format_currency(item.amount),
+                    ]);
+                }
+                println!("{}", view.render());
+                if dry_run {
+                    return Ok(());
+                }
+                let total: i32 = pending.iter().map(|p| p.amount).sum();
+                let count = RecognitionService::recognize(&mut conn, &pending, Some(user.id))?;
+                outln!("✅ {} month(s) totalling {} recognized as revenue", count, format_currency(total));
+            }
+            RevenueCommands::Balance { as_of, all } => {
+                let as_of = as_of.unwrap_or(today);
+                let balances = RecognitionService::balances(&mut conn, as_of, all)?;
+                if balances.is_empty() {
+                    println!("No deferred revenue as of {}.", format_date(&as_of));
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["Schedule", "Invoice", "Description", "Months", "Total", "Recognized", "Deferred"]);
+                for balance in &balances {
+                    view.push(vec![
+                        balance.schedule.id.to_string(),
+                        balance.invoice.invoice_number.clone(),
+                        balance.schedule.description.clone().unwrap_or_default(),
+                        format!(
+                            "{} - {}",
+                            balance.schedule.start_date.format("%Y-%m"),
+                            balance.end_period
+                        ),
+                        format_currency(balance.schedule.total_amount),
+                        format_currency(balance.recognized),
+                        format_currency(balance.deferred),
+                    ]);
+                }
+                println!("Deferred revenue as of {}", format_date(&as_of));
+                println!("{}", view.render());
+                let deferred: i32 = balances.iter().map(|b| b.deferred).sum();
+                println!("Total deferred: {}", format_currency(deferred));
+            }
+            RevenueCommands::Show { schedule_id } => {
+                let (schedule, invoice, recognitions) = RecognitionService::show(&mut conn, schedule_id)?;
+                println!("Schedule {} for invoice {}", schedule.id, invoice.invoice_number);
+                if let Some(description) = &schedule.description {
+                    println!("Description: {}", description);
+                }
+                println!("Amount: {}", format_currency(schedule.total_amount));
+                println!("Starts: {} ({} months)", schedule.start_date.format("%Y-%m"), schedule.months);
+                println!("Accounts: {} -> {}", schedule.deferred_account, schedule.revenue_account);
+                let recognized: i32 = recognitions.iter().map(|r| r.amount).sum();
+                println!("Recognized: {}", format_currency(recognized));
+                println!("Deferred: {}", format_currency(schedule.total_amount - recognized));
+                if !recognitions.is_empty() {
+                    let mut view = TableView::new(&["Month", "Posted On", "Amount"]);
+                    for recognition in recognitions {
+                        view.push(vec![
+                            recognition.period,
+                            format_date(&recognition.recognized_on),
+                            format_currency(recognition.amount),
+                        ]);
+                    }
+                    println!("{}", view.render());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_grni_command(
+        &self,
+        action: crate::core::command::GrniCommands,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::GrniCommands;
+        use crate::core::workflow::role_satisfies;
+        use crate::modules::finance::{AccrualAccounts, GrniService};
+        use crate::utils::dates::parse_period;
+        use crate::utils::formatting::format_date;
+        use crate::utils::timezone::DisplayTimezone;
+
+        let require_manager = || {
+            if role_satisfies(&user.role, "manager") {
+                Ok(())
+            } else {
+                Err(CLIERPError::Authorization("Purchase accruals require role: manager".to_string()))
+            }
+        };
+        let today = DisplayTimezone::current().today();
+        let mut conn = get_connection()?;
+
+        let print_lines = |lines: &[crate::modules::finance::GrniLine]| {
+            let mut view = TableView::new(&["PO", "Number", "Supplier", "Received", "Invoiced", "Uninvoiced"]);
+            for line in lines {
+                view.push(vec![
+                    line.po_id.to_string(),
+                    line.po_number.clone(),
+                    line.supplier.clone(),
+                    format_currency(line.received_amount),
+                    format_currency(line.invoiced_amount),
+                    format_currency(line.amount),
+                ]);
+            }
+            println!("{}", view.render());
+            let total: i32 = lines.iter().map(|l| l.amount).sum();
+            println!("Total uninvoiced: {}", format_currency(total));
+        };
+
+        match action {
+            GrniCommands::Report { as_of } => {
+                let as_of = as_of.unwrap_or(today);
+                let lines = GrniService::report(&mut conn, as_of)?;
+                if lines.is_empty() {
+                    println!("All receipts up to {} are invoiced.", format_date(&as_of));
+                    return Ok(());
+                }
+                println!("Goods received not invoiced as of {}", format_date(&as_of));
+                print_lines(&lines);
+            }
+            GrniCommands::Accrue { period, dry_run } => {
+                let (_, period_end) = parse_period(&period)?;
+                if dry_run {
+                    let lines = GrniService::report(&mut conn, period_end)?;
+                    println!("Would accrue for {} on {}:", period, format_date(&period_end));
+                    print_lines(&lines);
+                    return Ok(());
+                }
+                require_manager()?;
+                let accounts = AccrualAccounts::from_config(&self.config.purchase_accrual)?;
+                let (accrual, lines) = GrniService::accrue(&mut conn, &accounts, &period, period_end, Some(user.id))?;
+                if !lines.is_empty() {
+                    print_lines(&lines);
+                }
+                outln!(
+                    "✅ {} accrued for {} on {}, reversed on {}",
+                    format_currency(accrual.amount),
+                    accrual.period,
+                    format_date(&accrual.accrued_on),
+                    format_date(&accrual.reversed_on)
+                );
+            }
+            GrniCommands::Accruals { limit } => {
+                let accruals = GrniService::accruals(&mut conn, limit)?;
+                if accruals.is_empty() {
+                    println!("No purchase accruals yet.");
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["Period", "Accrued On", "Reversed On", "Orders", "Amount"]);
+                for accrual in accruals {
+                    let orders = GrniService::accrual_lines(&mut conn, accrual.id)?.len();
+                    view.push(vec![
+                        accrual.period,
+                        format_date(&accrual.accrued_on),
+                        format_date(&accrual.reversed_on),
+                        orders.to_string(),
+                        format_currency(accrual.amount),
+                    ]);
+                }
+                println!("{}", view.render());
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_invoice_command(
+        &self,
+        action: crate::core::command::InvoiceCommands,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::InvoiceCommands;
+        use crate::modules::finance::{invoice_state, CreateInvoiceRequest, InvoiceService};
+        use crate::utils::formatting::{format_date, format_datetime};
+        use crate::utils::timezone::DisplayTimezone;
+
+        let today = DisplayTimezone::current().today();
+        let mut conn = get_connection()?;
+
+        match action {
+            InvoiceCommands::Create { customer, lines, date, due, terms, description } => {
+                let invoice_date = date.unwrap_or(today);
+                let created = InvoiceService::create(
+                    &mut conn,
+                    CreateInvoiceRequest {
+                        customer_id: customer,
+                        invoice_date,
+                        due_date: Some(due.unwrap_or(invoice_date + chrono::Duration::days(terms))),
+                        description,
+                        lines,
+                    },
+                    Some(user.id),
+                )?;
+                outln!(
+                    "✅ Invoice {} drafted (ID {}) for {}",
+                    created.invoice.invoice_number,
+                    created.invoice.id,
+                    format_currency(created.invoice.total_amount)
+                );
+                println!("Run 'clierp fin invoice send {}' to issue it.", created.invoice.id);
+            }
+            InvoiceCommands::Send { invoice_id } => {
+                let invoice = InvoiceService::send(&mut conn, invoice_id)?;
+                outln!(
+                    "✅ Invoice {} sent, {} due {}",
+                    invoice.invoice_number,
+                    format_currency(invoice.open_amount()),
+                    invoice.due_date.map(|d| format_date(&d)).unwrap_or_else(|| "on receipt".to_string())
+                );
+            }
+            InvoiceCommands::Pay { invoice_id, amount, date, reference } => {
+                let (invoice, payment) = InvoiceService::apply_payment(
+                    &mut conn,
+                    invoice_id,
+                    amount,
+                    date.unwrap_or(today),
+                    reference.as_deref(),
+                    Some(user.id),
+                )?;
+                outln!(
+                    "✅ {} applied to invoice {}; {} open, status {}",
+                    format_currency(payment.amount),
+                    invoice.invoice_number,
+                    format_currency(invoice.open_amount()),
+                    invoice_state(&invoice, today)
+                );
+            }
+            InvoiceCommands::Cancel { invoice_id } => {
+                let invoice = InvoiceService::get(&mut conn, invoice_id)?.invoice;
+                if !confirm_action(
+                    &format!(
+                        "Cancel invoice {} for {}? Its number stays used.",
+                        invoice.invoice_number,
+                        format_currency(invoice.total_amount)
+                    ),
+                    RiskLevel::Medium,
+                )? {
+                    println!("Cancelled.");
+                    return Ok(());
+                }
+                let invoice = InvoiceService::cancel(&mut conn, invoice_id)?;
+                outln!("✅ Invoice {} cancelled", invoice.invoice_number);
+            }
+            InvoiceCommands::Show { invoice_id } => {
+                let shown = InvoiceService::get(&mut conn, invoice_id)?;
+                let invoice = &shown.invoice;
+                println!("Invoice {} ({})", invoice.invoice_number, invoice_state(invoice, today));
+                if let Some(customer_id) = invoice.customer_id {
+                    println!("Customer: {}", customer_id);
+                }
+                println!("Date: {}", format_date(&invoice.invoice_date));
+                if let Some(due_date) = invoice.due_date {
+                    println!("Due: {}", format_date(&due_date));
+                }
+                if let Some(sent_at) = invoice.sent_at {
+                    println!("Sent: {}", format_datetime(&sent_at));
+                }
+                if let Some(description) = &invoice.description {
+                    println!("Description: {}", description);
+                }
+                if !shown.lines.is_empty() {
+                    let mut view = TableView::new(&["#", "Description", "Quantity", "Unit Price", "Amount"]);
+                    for line in &shown.lines {
+                        view.push(vec![
+                            line.line_number.to_string(),
+                            line.description.clone(),
+                            line.quantity.to_string(),
+                            format_currency(line.unit_price),
+                            format_currency(line.amount),
+                        ]);
+                    }
+                    println!("{}", view.render());
+                }
+                println!("Total: {}", format_currency(invoice.total_amount));
+                println!("Paid: {}", format_currency(invoice.paid_amount));
+                println!("Open: {}", format_currency(invoice.open_amount()));
+                if !shown.payments.is_empty() {
+                    let mut view = TableView::new(&["Paid On", "Amount", "Reference"]);
+                    for payment in &shown.payments {
+                        view.push(vec![
+                            format_date(&payment.paid_on),
+                            format_currency(payment.amount),
+                            payment.reference.clone().unwrap_or_default(),
+                        ]);
+                    }
+                    println!("{}", view.render());
+                }
+            }
+            InvoiceCommands::List { customer, status, limit } => {
+                let invoices = InvoiceService::list(&mut conn, customer, status, today, limit)?;
+                if invoices.is_empty() {
+                    println!("No invoices found.");
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["ID", "Number", "Customer", "Date", "Due", "Total", "Open", "Status"]);
+                for invoice in &invoices {
+                    view.push(vec![
+                        invoice.id.to_string(),
+                        invoice.invoice_number.clone(),
+                        invoice.customer_id.map(|id| id.to_string()).unwrap_or_default(),
+                        format_date(&invoice.invoice_date),
+                        invoice.due_date.map(|d| format_date(&d)).unwrap_or_default(),
+                        format_currency(invoice.total_amount),
+                        format_currency(invoice.open_amount()),
+                        invoice_state(invoice, today).to_string(),
+                    ]);
+                }
+                println!("{}", view.render());
+            }
+            InvoiceCommands::Aging { as_of } => {
+                let as_of = as_of.unwrap_or(today);
+                let rows = InvoiceService::aging(&mut conn, as_of)?;
+                if rows.is_empty() {
+                    println!("No open receivables as of {}.", format_date(&as_of));
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["ID", "Customer", "Current", "1-30", "31-60", "61-90", "90+", "Total"]);
+                for row in &rows {
+                    view.push(vec![
+                        row.customer_id.to_string(),
+                        row.customer.clone(),
+                        format_currency(row.current),
+                        format_currency(row.days_1_30),
+                        format_currency(row.days_31_60),
+                        format_currency(row.days_61_90),
+                        format_currency(row.over_90),
+                        format_currency(row.total),
+                    ]);
+                }
+                println!("Receivables aging as of {}", format_date(&as_of));
+                println!("{}", view.render());
+                let total: i64 = rows.iter().map(|r| r.total).sum();
+                println!("Total open: {}", format_currency(total));
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_ap_command(
+        &self,
+        action: crate::core::command::ApCommands,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::ApCommands;
+        use crate::modules::finance::PayableService;
+        use crate::modules::inventory::PurchaseOrderService;
+        use crate::utils::formatting::format_date;
+        use crate::utils::timezone::DisplayTimezone;
+
+        let today = DisplayTimezone::current().today();
+        let mut conn = get_connection()?;
+
+        match action {
+            ApCommands::Bill { po_id, number, amount, date, due } => {
+                let bill = PurchaseOrderService::record_invoice(
+                    &mut conn,
+                    po_id,
+                    &number,
+                    date.unwrap_or(today),
+                    due,
+                    amount,
+                    Some(user.id),
+                )?;
+                outln!(
+                    "✅ Bill {} recorded (ID {}) for {}, due {}",
+                    bill.invoice_number,
+                    bill.id,
+                    format_currency(bill.total_amount),
+                    bill.due_date.map(|d| format_date(&d)).unwrap_or_else(|| "on receipt".to_string())
+                );
+            }
+            ApCommands::Pay { bill_id, amount, date, reference } => {
+                let (bill, payment) = PayableService::record_payment(
+                    &mut conn,
+                    bill_id,
+                    amount,
+                    date.unwrap_or(today),
+                    reference.as_deref(),
+                    Some(user.id),
+                )?;
+                outln!(
+                    "✅ {} paid on bill {}; {} open",
+                    format_currency(payment.amount),
+                    bill.invoice_number,
+                    format_currency(bill.open_amount())
+                );
+            }
+            ApCommands::Show { bill_id } => {
+                let shown = PayableService::get(&mut conn, bill_id)?;
+                let bill = &shown.bill;
+                println!("Bill {} from {} ({})", bill.invoice_number, shown.supplier, bill.status);
+                if let Some(description) = &bill.description {
+                    println!("Description: {}", description);
+                }
+                println!("Date: {}", format_date(&bill.invoice_date));
+                if let Some(due_date) = bill.due_date {
+                    println!("Due: {}", format_date(&due_date));
+                }
+                println!("Total: {}", format_currency(bill.total_amount));
+                println!("Paid: {}", format_currency(bill.paid_amount));
+                println!("Open: {}", format_currency(bill.open_amount()));
+                if !shown.payments.is_empty() {
+                    let mut view = TableView::new(&["Paid On", "Amount", "Reference"]);
+                    for payment in &shown.payments {
+                        view.push(vec![
+                            format_date(&payment.paid_on),
+                            format_currency(payment.amount),
+                            payment.reference.clone().unwrap_or_default(),
+                        ]);
+                    }
+                    println!("{}", view.render());
+                }
+            }
+            ApCommands::List { supplier, open, limit } => {
+                let bills = PayableService::list(&mut conn, supplier, open, limit)?;
+                if bills.is_empty() {
+                    println!("No bills found.");
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["ID", "Number", "Supplier", "PO", "Date", "Due", "Total", "Open", "Status"]);
+                for bill in &bills {
+                    view.push(vec![
+                        bill.id.to_string(),
+                        bill.invoice_number.clone(),
+                        bill.supplier_id.map(|id| id.to_string()).unwrap_or_default(),
+                        bill.po_id.map(|id| id.to_string()).unwrap_or_default(),
+                        format_date(&bill.invoice_date),
+                        bill.due_date.map(|d| format_date(&d)).unwrap_or_default(),
+                        format_currency(bill.total_amount),
+                        format_currency(bill.open_amount()),
+                        bill.status.clone(),
+                    ]);
+                }
+                println!("{}", view.render());
+            }
+            ApCommands::Aging { as_of } => {
+                let as_of = as_of.unwrap_or(today);
+                let rows = PayableService::aging(&mut conn, as_of)?;
+                if rows.is_empty() {
+                    println!("No open bills as of {}.", format_date(&as_of));
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["ID", "Supplier", "Overdue", "0-30", "31-60", "61-90", "Later", "Total"]);
+                for row in &rows {
+                    view.push(vec![
+                        row.supplier_id.to_string(),
+                        row.supplier.clone(),
+                        format_currency(row.overdue),
+                        format_currency(row.due_0_30),
+                        format_currency(row.due_31_60),
+                        format_currency(row.due_61_90),
+                        format_currency(row.later),
+                        format_currency(row.total),
+                    ]);
+                }
+                println!("Payables due as of {} (days until due)", format_date(&as_of));
+                println!("{}", view.render());
+                let total: i64 = rows.iter().map(|r| r.total).sum();
+                println!("Total open: {}", format_currency(total));
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_collection_command(
+        &self,
+        action: crate::core::command::CollectionCommands,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::CollectionCommands;
+        use crate::modules::finance::{CollectionService, PromiseRequest};
+        use crate::utils::formatting::{format_date, format_datetime};
+        use crate::utils::timezone::DisplayTimezone;
+
+        let today = DisplayTimezone::current().today();
+        let mut conn = get_connection()?;
+
+        match action {
+            CollectionCommands::Queue { limit } => {
+                let queue = CollectionService::queue(&mut conn, today, limit)?;
+                if queue.is_empty() {
+                    println!("No customers to call.");
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["ID", "Customer", "Phone", "Invoices", "Overdue", "Oldest (days)", "Escalation", "Last Call"]);
+                for entry in queue {
+                    view.push(vec![
+                        entry.customer.id.to_string(),
+                        entry.customer.name,
+                        entry.customer.phone.unwrap_or_default(),
+                        entry.overdue_invoices.to_string(),
+                        format_currency(entry.overdue_amount),
+                        entry.oldest_days_overdue.to_string(),
+                        entry.escalation.to_string(),
+                        entry
+                            .last_call
+                            .map(|call| format!("{} {}", format_datetime(&call.created_at), call.outcome))
+                            .unwrap_or_default(),
+                    ]);
+                }
+                println!("{}", view.render());
+            }
+            CollectionCommands::Log { customer_id, outcome, notes, promise_date, promise_amount } => {
+                let promise = match (promise_date, promise_amount) {
+                    (Some(date), amount) => Some(PromiseRequest { amount, date }),
+                    (None, Some(_)) => {
+                        return Err(CLIERPError::ValidationError("--promise-amount needs a --promise-date".to_string()))
+                    }
+                    (None, None) => None,
+                };
+                let (call, promise) =
+                    CollectionService::log_call(&mut conn, customer_id, outcome, notes.as_deref(), promise, today, Some(user.id))?;
+                match promise {
+                    Some(promise) => outln!(
+                        "✅ Call {} logged: {} promised by {}; the customer leaves the queue until then",
+                        call.id,
+                        format_currency(promise.amount),
+                        format_date(&promise.promised_date)
+                    ),
+                    None => outln!("✅ Call {} logged: {}", call.id, call.outcome),
+                }
+            }
+            CollectionCommands::History { customer_id } => {
+                let (calls, promises) = CollectionService::history(&mut conn, customer_id)?;
+                if calls.is_empty() {
+                    println!("No collections calls to customer {}.", customer_id);
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["ID", "When", "Outcome", "Promise", "Status", "Notes"]);
+                for call in calls {
+                    let promise = promises.get(&call.id);
+                    view.push(vec![
+                        call.id.to_string(),
+                        format_datetime(&call.created_at),
+                        call.outcome,
+                        promise
+                            .map(|p| format!("{} by {}", format_currency(p.amount), format_date(&p.promised_date)))
+                            .unwrap_or_default(),
+                        promise.map(|p| p.status.clone()).unwrap_or_default(),
+                        call.notes.unwrap_or_default(),
+                    ]);
+                }
+                println!("{}", view.render());
+            }
+            CollectionCommands::Promises { status, limit } => {
+                CollectionService::resolve_promises(&mut conn, today)?;
+                let promises = CollectionService::promises(&mut conn, status, limit)?;
+                if promises.is_empty() {
+                    println!("No promises to pay.");
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["ID", "Customer", "Amount", "Promised On", "Pay By", "Status"]);
+                for (promise, customer) in promises {
+                    view.push(vec![
+                        promise.id.to_string(),
+                        customer.name,
+                        format_currency(promise.amount),
+                        format_date(&promise.promised_on),
+                        format_date(&promise.promised_date),
+                        promise.status,
+                    ]);
+                }
+                println!("{}", view.render());
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_dunning_command(
+        &self,
+        action: crate::core::command::DunningCommands,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::DunningCommands;
+        use crate::core::workflow::role_satisfies;
+        use crate::modules::finance::{DunningAccounts, DunningPolicy, DunningService};
+        use crate::utils::formatting::format_date;
+        use crate::utils::timezone::DisplayTimezone;
+
+        let require_manager = || {
+            if role_satisfies(&user.role, "manager") {
+                Ok(())
+            } else {
+                Err(CLIERPError::Authorization("Dunning requires role: manager".to_string()))
+            }
+        };
+        let mut conn = get_connection()?;
+
+        match action {
+            DunningCommands::Run { as_of, invoice, dry_run } => {
+                require_manager()?;
+                let policy = DunningPolicy::from_config(&self.config.dunning)?;
+                if !policy.is_active() {
+                    println!("No late fee or interest rate configured; set dunning.late_fee or dunning.interest_rate.");
+                    return Ok(());
+                }
+                let as_of = as_of.unwrap_or_else(|| DisplayTimezone::current().today());
+                let run = DunningService::plan(&mut conn, &policy, as_of, invoice)?;
+
+                if run.charges.is_empty() {
+                    println!("Nothing to charge as of {}.", format_date(&as_of));
+                } else {
+                    let mut view = TableView::new(&["Invoice", "Customer", "Days Overdue", "Charge", "Period", "Amount"]);
+                    for charge in &run.charges {
+                        view.push(vec![
+                            charge.invoice_number.clone(),
+                            charge.customer_id.map(|id| id.to_string()).unwrap_or_default(),
+                            charge.days_overdue.to_string(),
+                            charge.kind.to_string(),
+                            match charge.accrued_from {
+                                Some(from) => format!("{} - {}", format_date(&from), format_date(&charge.accrued_to)),
+                                None => String::new(),
+                            },
+                            format_currency(charge.amount),
+                        ]);
+                    }
+                    println!("{}", view.render());
+                }
+                if run.exempt_invoices > 0 {
+                    println!("{} overdue invoice(s) of exempt customers skipped", run.exempt_invoices);
+                }
+                if dry_run || run.charges.is_empty() {
+                    return Ok(());
+                }
+
+                let accounts = DunningAccounts::from_config(&self.config.dunning)?;
+                let charged = DunningService::apply(&mut conn, &accounts, &run, Some(user.id))?;
+                outln!("✅ {} charge(s) totalling {} added to overdue invoices", charged, format_currency(run.total()));
+            }
+            DunningCommands::Charges { invoice, customer, limit } => {
+                let charges = DunningService::charges(&mut conn, invoice, customer, limit)?;
+                if charges.is_empty() {
+                    println!("No dunning charges.");
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["ID", "Charged", "Invoice", "Charge", "Days Overdue", "Amount"]);
+                for (charge, invoice) in charges {
+                    view.push(vec![
+                        charge.id.to_string(),
+                        format_date(&charge.accrued_to),
+                        invoice.invoice_number,
+                        charge.kind,
+                        charge.days_overdue.to_string(),
+                        format_currency(charge.amount),
+                    ]);
+                }
+                println!("{}", view.render());
+            }
+            DunningCommands::Exempt { customer_id, reason } => {
+                require_manager()?;
+                DunningService::exempt(&mut conn, customer_id, reason.as_deref(), Some(user.id))?;
+                outln!("✅ Customer {} will not be charged late fees or interest", customer_id);
+            }
+            DunningCommands::Unexempt { customer_id } => {
+                require_manager()?;
+                if DunningService::remove_exemption(&mut conn, customer_id)? {
+                    outln!("✅ Customer {} is charged late fees and interest again", customer_id);
+                } else {
+                    println!("Customer {} was not exempt.", customer_id);
+                }
+            }
+            DunningCommands::Exemptions => {
+                let exemptions = DunningService::exemptions(&mut conn)?;
+                if exemptions.is_empty() {
+                    println!("No exempt customers.");
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["ID", "Code", "Customer", "Since", "Reason"]);
+                for (exemption, customer) in exemptions {
+                    view.push(vec![
+                        customer.id.to_string(),
+                        customer.customer_code,
+                        customer.name,
+                        format_date(&exemption.created_at.date()),
+                        exemption.reason.unwrap_or_default(),
+                    ]);
+                }
+                println!("{}", view.render());
+            }
+        }
+        Ok(())
+    }
+
+    async fn execute_fin_command(
+        &mut self,
+        action: crate::core::command::FinCommands,
+    ) -> CLIERPResult<()> {
+        // Check authentication for Finance commands
+        let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+            CLIERPError::Authentication("Login required for Finance commands".to_string())
+        })?;
+
+        use crate::core::command::FinCommands;
+        use crate::utils::fiscal::FiscalCalendar;
+
+        match action {
+            FinCommands::Calendar { year } => {
+                let calendar = FiscalCalendar::current();
+                let fiscal_year = year.unwrap_or_else(|| {
+                    calendar.fiscal_year_of(crate::utils::timezone::DisplayTimezone::current().today())
+                });
+                let (start, end) = calendar.year_range(fiscal_year);
+
+                println!("Fiscal Year FY{} ({} to {}):", fiscal_year, start, end);
+                if let Some(pattern) = calendar.pattern() {
+                    println!("  Period pattern: {}", pattern);
+                }
+                for period in calendar.periods(fiscal_year) {
+                    println!(
+                        "  {} (Q{}): {} to {}",
+                        period.label(),
+                        period.quarter(),
+                        period.start_date,
+                        period.end_date
+                    );
+                }
+            }
+            FinCommands::Anomalies { action } => {
+                use crate::core::command::AnomalyCommands;
+                use crate::modules::system::{AnomalyService, STOCK_MOVEMENT_SOURCE};
+
+                let mut conn = get_connection()?;
+                let print_flags = |flags: &[crate::database::AnomalyFlag]| {
+                    println!(
+                        "{:<6} {:<15} {:<8} {:<16} {:>14} {:>7} {:<10}",
+                        "ID", "Source", "Record", "Account / SKU", "Value", "Score", "Status"
+                    );
+                    println!("{}", "-".repeat(82));
+                    for flag in flags {
+                        let value = if flag.source == STOCK_MOVEMENT_SOURCE {
+                            format!("{}", flag.value)
+                        } else {
+                            format_currency(flag.value as i64)
+                        };
+                        println!(
+                            "{:<6} {:<15} {:<8} {:<16} {:>14} {:>7.2} {:<10}",
+                            flag.id, flag.source, flag.record_id, flag.subject, value, flag.score, flag.status
+                        );
+                    }
+                };
+
+                match action {
+                    AnomalyCommands::Scan {
+                        source,
+                        method,
+                        threshold,
+                        since,
+                    } => {
+                        let outcome = AnomalyService::scan(&mut conn, source, method, threshold, since)?;
+                        println!("Scanned {} record(s).", outcome.records_scanned);
+                        if outcome.flagged.is_empty() {
+                            outln!("✅ No new anomalies found");
+                        } else {
+                            outln!("⚠️  {} new anomal(ies) flagged for review:", outcome.flagged.len());
+                            print_flags(&outcome.flagged);
+                        }
+                        if outcome.already_flagged > 0 {
+                            println!("{} outlier(s) were already flagged.", outcome.already_flagged);
+                        }
+                    }
+                    AnomalyCommands::List { status, limit } => {
+                        let flags = AnomalyService::list(&mut conn, Some(status), limit)?;
+                        if flags.is_empty() {
+                            println!("No {} anomalies.", status);
+                        } else {
+                            print_flags(&flags);
+                        }
+                    }
+                    AnomalyCommands::Review { id, status, note } => {
+                        let flag = AnomalyService::review(&mut conn, id, status, note.as_deref(), Some(user.id))?;
+                        outln!("✅ Anomaly flag {} marked {}", flag.id, flag.status);
+                    }
+                }
+            }
+            FinCommands::OpeningBalances { action } => {
+                use crate::core::command::OpeningBalanceCommands;
+                use crate::database::models::UserRole;
+                use crate::modules::finance::OpeningBalanceService;
+
+                if !matches!(user.role, UserRole::Admin) {
+                    return Err(CLIERPError::Authorization("Admin role required".to_string()));
+                }
+
+                match action {
+                    OpeningBalanceCommands::Import { file, as_of } => {
+                        let mut conn = get_connection()?;
+                        let summary = OpeningBalanceService::import_csv(&mut conn, &file, as_of, Some(user.id))?;
+                        outln!("✅ Opening balances as of {} imported", as_of);
+                        println!(
+                            "  Journal:     {} account(s), {} debit / {} credit",
+                            summary.accounts_posted,
+                            format_currency(summary.journal_total),
+                            format_currency(summary.journal_total)
+                        );
+                        println!(
+                            "  Stock:       {} product(s) worth {}",
+                            summary.products_stocked,
+                            format_currency(summary.stock_value)
+                        );
+                        println!(
+                            "  Receivables: {} open document(s), {}",
+                            summary.receivables,
+                            format_currency(summary.receivable_total)
+                        );
+                        println!(
+                            "  Payables:    {} open document(s), {}",
+                            summary.payables,
+                            format_currency(summary.payable_total)
+                        );
+                    }
+                }
+            }
+            FinCommands::Payments { action } => self.execute_payment_command(action, user)?,
+            FinCommands::Dunning { action } => self.execute_dunning_command(action, user)?,
+            FinCommands::Collections { action } => self.execute_collection_command(action, user)?,
+            FinCommands::Revenue { action } => self.execute_revenue_command(action, user)?,
+            FinCommands::Grni { action } => self.execute_grni_command(action, user)?,
+            FinCommands::Invoice { action } => self.execute_invoice_command(action, user)?,
+            FinCommands::Ap { action } => self.execute_ap_command(action, user)?,
+            FinCommands::Account { action } => self.execute_account_command(action, user)?,
+            FinCommands::Transaction { action } => self.execute_transaction_command(action, user)?,
+            FinCommands::Journal { action } => self.execute_journal_command(action, user)?,
+            FinCommands::Report { action } => self.execute_fin_report_command(action)?,
+        }
+        Ok(())
+    }
+
+    fn execute_account_command(
+        &self,
+        action: crate::core::command::AccountCommands,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::AccountCommands;
+        use crate::core::workflow::role_satisfies;
+        use crate::modules::finance::{AccountNode, AccountService, CreateAccountRequest, UpdateAccountRequest};
+
+        let require_manager = || {
+            if role_satisfies(&user.role, "manager") {
+                Ok(())
+            } else {
+                Err(CLIERPError::Authorization("Account changes require role: manager".to_string()))
+            }
+        };
+        let mut conn = get_connection()?;
+        let service = AccountService::new();
+
+        match action {
+            AccountCommands::Add { code, name, account_type, parent_id } => {
+                require_manager()?;
+                let account = service.create_account(
+                    &mut conn,
+                    CreateAccountRequest {
+                        account_code: code,
+                        account_name: name,
+                        account_type,
+                        parent_id,
+                    },
+                )?;
+                outln!("✅ Account {} {} created (ID {}, {})", account.account_code, account.account_name, account.id, account.account_type);
+            }
+            AccountCommands::List { account_type, tree } => {
+                if tree {
+                    fn print_nodes(nodes: &[AccountNode], depth: usize) {
+                        for node in nodes {
+                            println!(
+                                "{}{} {} ({}) {}",
+                                "  ".repeat(depth),
+                                node.account.account_code,
+                                node.account.account_name,
+                                node.account.account_type,
+                                format_currency(node.account.balance)
+                            );
+                            print_nodes(&node.children, depth + 1);
+                        }
+                    }
+                    print_nodes(&service.get_chart_of_accounts(&mut conn)?, 0);
+                    return Ok(());
+                }
+
+                let accounts = match account_type {
+                    Some(account_type) => service.list_accounts_by_type(&mut conn, &account_type)?,
+                    None => service.list_accounts(&mut conn)?,
+                };
+                if accounts.is_empty() {
+                    println!("No accounts found.");
+                    return Ok(());
+                }
+                println!("{:<5} {:<10} {:<30} {:<10} {:>15}", "ID", "Code", "Name", "Type", "Balance");
+                println!("{}", "-".repeat(74));
+                for account in &accounts {
+                    println!(
+                        "{:<5} {:<10} {:<30} {:<10} {:>15}",
+                        account.id,
+                        account.account_code,
+                        account.account_name,
+                        account.account_type,
+                        format_currency(account.balance)
+                    );
+                }
+            }
+            AccountCommands::Show { id } => {
+                use crate::modules::finance::TransactionService;
+
+                let account = service
+                    .get_account_by_id(&mut conn, id)?
+                    .ok_or_else(|| CLIERPError::NotFound(format!("Account {} not found", id)))?;
+                println!("Account Details:");
+                println!("  ID: {}", account.id);
+                println!("  Code: {}", account.account_code);
+                println!("  Name: {}", account.account_name);
+                println!("  Type: {}", account.account_type);
+                if let Some(parent_id) = account.parent_id {
+                    println!("  Parent ID: {}", parent_id);
+                }
+                println!("  Balance: {}", format_currency(account.balance));
+                println!("  Active: {}", if account.is_active { "Yes" } else { "No" });
+
+                let transactions = TransactionService::new().get_account_transactions(&mut conn, id, None, None)?;
+                if !transactions.is_empty() {
+                    println!("\nRecent transactions:");
+                    for transaction in transactions.iter().take(10) {
+                        println!(
+                            "  {} {:<6} {:>15}  {}",
+                            transaction.transaction_date,
+                            transaction.debit_credit,
+                            format_currency(transaction.amount),
+                            transaction.description
+                        );
+                    }
+                    if transactions.len() > 10 {
+                        println!("  ... {} more; see 'clierp fin transaction list --account-id {}'", transactions.len() - 10, id);
+                    }
+                }
+            }
+            AccountCommands::Update { id, code, name, account_type, parent_id } => {
+                require_manager()?;
+                let before = service
+                    .get_account_by_id(&mut conn, id)?
+                    .ok_or_else(|| CLIERPError::NotFound(format!("Account {} not found", id)))?;
+                let account = service.update_account(
+                    &mut conn,
+                    UpdateAccountRequest {
+                        id,
+                        account_code: code,
+                        account_name: name,
+                        account_type,
+                        parent_id,
+                    },
+                )?;
+                outln!("✅ Account {} updated", account.id);
+                print_changes(&changed_fields(&before, &account)?);
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_transaction_command(
+        &self,
+        action: crate::core::command::TransactionCommands,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::TransactionCommands;
+        use crate::modules::finance::{
+            JournalEntryRequest, JournalLine, JournalService, TransactionFilters, TransactionService,
+        };
+        use crate::utils::timezone::DisplayTimezone;
+
+        let mut conn = get_connection()?;
+        let service = TransactionService::new();
+
+        match action {
+            TransactionCommands::Add {
+                account_id,
+                amount,
+                transaction_type,
+                contra_account_id,
+                description,
+                date,
+                reference,
+            } => {
+                let lines = match transaction_type.to_lowercase().as_str() {
+                    "debit" => vec![JournalLine::debit(account_id, amount), JournalLine::credit(contra_account_id, amount)],
+                    "credit" => vec![JournalLine::credit(account_id, amount), JournalLine::debit(contra_account_id, amount)],
+                    _ => {
+                        return Err(CLIERPError::ValidationError(
+                            "Transaction must be either 'debit' or 'credit'".to_string(),
+                        ))
+                    }
+                };
+                let posted = JournalService::post_entry(
+                    &mut conn,
+                    JournalEntryRequest {
+                        entry_date: date.unwrap_or_else(|| DisplayTimezone::current().today()),
+                        description,
+                        reference,
+                        lines,
+                    },
+                    Some(user.id),
+                )?;
+                let transaction = &posted.lines[0];
+                outln!(
+                    "✅ Transaction {} recorded: {} {} on account {} (journal entry {})",
+                    transaction.id,
+                    transaction.debit_credit,
+                    format_currency(transaction.amount),
+                    transaction.account_id,
+                    posted.entry.entry_number
+                );
+            }
+            TransactionCommands::List { account_id, from, to } => {
+                let transactions = service.list_transactions(
+                    &mut conn,
+                    TransactionFilters {
+                        account_id,
+                        from_date: from,
+                        to_date: to,
+                        ..Default::default()
+                    },
+                )?;
+                if transactions.is_empty() {
+                    println!("No transactions found.");
+                    return Ok(());
+                }
+                println!(
+                    "{:<6} {:<10} {:<10} {:<6} {:>15}  Description",
+                    "ID", "Date", "Account", "Type", "Amount"
+                );
+                println!("{}", "-".repeat(80));
+                for entry in &transactions {
+                    println!(
+                        "{:<6} {:<10} {:<10} {:<6} {:>15}  {}",
+                        entry.transaction.id,
+                        entry.transaction.transaction_date,
+                        entry.account.account_code,
+                        entry.transaction.debit_credit,
+                        format_currency(entry.transaction.amount),
+                        entry.transaction.description
+                    );
+                }
+                println!("{}", "-".repeat(80));
+                println!("{} transaction(s)", transactions.len());
+            }
+            TransactionCommands::Show { id } => {
+                let entry = service
+                    .get_transaction_by_id(&mut conn, id)?
+                    .ok_or_else(|| CLIERPError::NotFound(format!("Transaction {} not found", id)))?;
+                println!("Transaction Details:");
+                println!("  ID: {}", entry.transaction.id);
+                println!("  Date: {}", entry.transaction.transaction_date);
+                println!("  Account: {} {}", entry.account.account_code, entry.account.account_name);
+                println!("  Type: {}", entry.transaction.debit_credit);
+                println!("  Amount: {}", format_currency(entry.transaction.amount));
+                println!("  Description: {}", entry.transaction.description);
+                if let Some(reference) = &entry.transaction.reference {
+                    println!("  Reference: {}", reference);
+                }
+                if let Some(journal_entry_id) = entry.transaction.journal_entry_id {
+                    println!("  Journal entry: {}", journal_entry_id);
+                }
+                if let Some(created_by) = entry.transaction.created_by {
+                    println!("  Created by: user {}", created_by);
+                }
+                {
+                    use crate::database::RecordType;
+                    use crate::modules::system::links::{print_related, LinkService, RecordRef};
+                    print_related(&LinkService::related(&mut conn, RecordRef::new(RecordType::Transaction, id))?);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_journal_command(
+        &self,
+        action: crate::core::command::JournalCommands,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::JournalCommands;
+        use crate::modules::finance::{JournalEntryRequest, JournalLine, JournalService};
+        use crate::utils::formatting::format_date;
+        use crate::utils::timezone::DisplayTimezone;
+
+        let mut conn = get_connection()?;
+        let today = DisplayTimezone::current().today();
+
+        match action {
+            JournalCommands::Post { description, debits, credits, date, reference } => {
+                let lines = debits
+                    .into_iter()
+                    .map(|(account_id, amount)| JournalLine::debit(account_id, amount))
+                    .chain(credits.into_iter().map(|(account_id, amount)| JournalLine::credit(account_id, amount)))
+                    .collect();
+                let posted = JournalService::post_entry(
+                    &mut conn,
+                    JournalEntryRequest {
+                        entry_date: date.unwrap_or(today),
+                        description,
+                        reference,
+                        lines,
+                    },
+                    Some(user.id),
+                )?;
+                outln!(
+                    "✅ Journal entry {} posted with {} line(s)",
+                    posted.entry.entry_number,
+                    posted.lines.len()
+                );
+            }
+            JournalCommands::List { from, to, limit } => {
+                let entries = JournalService::list_entries(&mut conn, from, to, limit)?;
+                if entries.is_empty() {
+                    println!("No journal entries found.");
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["ID", "Number", "Date", "Reference", "Description"]);
+                for entry in &entries {
+                    view.push(vec![
+                        entry.id.to_string(),
+                        entry.entry_number.clone(),
+                        format_date(&entry.entry_date),
+                        entry.reference.clone().unwrap_or_default(),
+                        entry.description.clone(),
+                    ]);
+                }
+                println!("{}", view.render());
+                println!("{} journal entr(ies)", entries.len());
+            }
+            JournalCommands::Show { id } => {
+                let posted = JournalService::get_entry(&mut conn, id)?;
+                println!("Journal entry {}", posted.entry.entry_number);
+                println!("  Date: {}", format_date(&posted.entry.entry_date));
+                println!("  Description: {}", posted.entry.description);
+                if let Some(reference) = &posted.entry.reference {
+                    println!("  Reference: {}", reference);
+                }
+                if let Some(reverses) = posted.entry.reverses_entry_id {
+                    println!("  Reverses entry: {}", reverses);
+                }
+                let mut view = TableView::new(&["Line", "Account", "Debit", "Credit", "Description"]);
+                for line in &posted.lines {
+                    let (debit, credit) = match line.debit_credit.as_str() {
+                        "debit" => (format_currency(line.amount), String::new()),
+                        _ => (String::new(), format_currency(line.amount)),
+                    };
+                    view.push(vec![
+                        line.id.to_string(),
+                        line.account_id.to_string(),
+                        debit,
+                        credit,
+                        line.description.clone(),
+                    ]);
+                }
+                println!("{}", view.render());
+            }
+            JournalCommands::Reverse { id, reason, date } => {
+                let original = JournalService::get_entry(&mut conn, id)?;
+                if !confirm_action(
+                    &format!("Reverse journal entry {}?", original.entry.entry_number),
+                    RiskLevel::Medium,
+                )? {
+                    println!("Cancelled.");
+                    return Ok(());
+                }
+                let reversal = JournalService::reverse_entry(&mut conn, id, date.unwrap_or(today), &reason, Some(user.id))?;
+                outln!(
+                    "✅ Journal entry {} reversed by {}",
+                    original.entry.entry_number, reversal.entry.entry_number
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_fin_report_command(&self, action: crate::core::command::ReportCommands) -> CLIERPResult<()> {
+        use crate::core::command::ReportCommands;
+        use crate::modules::finance::ReportService;
+        use crate::utils::timezone::DisplayTimezone;
+
+        let mut conn = get_connection()?;
+        let service = ReportService::new();
+        let today = DisplayTimezone::current().today();
+
+        match action {
+            ReportCommands::Balance { as_of } => {
+                let sheet = service.generate_balance_sheet(&mut conn, as_of.unwrap_or(today))?;
+                println!("Balance Sheet as of {}", sheet.as_of_date);
+                for (title, items, total) in [
+                    ("Assets", &sheet.asset_items, sheet.total_assets),
+                    ("Liabilities", &sheet.liability_items, sheet.total_liabilities),
+                    ("Equity", &sheet.equity_items, sheet.total_equity),
+                ] {
+                    println!("\n{}:", title);
+                    for item in items {
+                        println!("  {:<10} {:<30} {:>15}", item.account_code, item.account_name, format_currency(item.amount));
+                    }
+                    println!("  {:<41} {:>15}", format!("Total {}", title.to_lowercase()), format_currency(total));
+                }
+                println!(
+                    "\nLiabilities and equity: {}",
+                    format_currency(sheet.total_liabilities_and_equity)
+                );
+                if !sheet.is_balanced {
+                    outln!("⚠️  Assets do not equal liabilities and equity");
+                }
+            }
+            ReportCommands::Income { period } => {
+                let statement = service.generate_income_statement_for_period(&mut conn, &period)?;
+                println!("Income Statement {} ({} to {})", period, statement.from_date, statement.to_date);
+                for (title, items, total) in [
+                    ("Revenue", &statement.revenue_items, statement.total_revenue),
+                    ("Expenses", &statement.expense_items, statement.total_expenses),
+                ] {
+                    println!("\n{}:", title);
+                    for item in items {
+                        println!("  {:<10} {:<30} {:>15}", item.account_code, item.account_name, format_currency(item.amount));
+                    }
+                    println!("  {:<41} {:>15}", format!("Total {}", title.to_lowercase()), format_currency(total));
+                }
+                println!("\nNet income: {}", format_currency(statement.net_income));
+            }
+            ReportCommands::TrialBalance { as_of } => {
+                let report = service.generate_trial_balance(&mut conn, as_of.unwrap_or(today))?;
+                println!("Trial Balance as of {}", report.as_of_date);
+                println!("{:<10} {:<30} {:<10} {:>15} {:>15}", "Code", "Name", "Type", "Debit", "Credit");
+                println!("{}", "-".repeat(84));
+                for item in &report.items {
+                    println!(
+                        "{:<10} {:<30} {:<10} {:>15} {:>15}",
+                        item.account_code,
+                        item.account_name,
+                        item.account_type,
+                        format_currency(item.debit_amount),
+                        format_currency(item.credit_amount)
+                    );
+                }
+                println!("{}", "-".repeat(84));
+                println!(
+                    "{:<52} {:>15} {:>15}",
+                    "Total",
+                    format_currency(report.total_debits),
+                    format_currency(report.total_credits)
+                );
+                if !report.is_balanced {
+                    outln!("⚠️  Out of balance by {}", format_currency(report.difference));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn execute_inv_command(
+        &mut self,
+        action: crate::core::command::InvCommands,
+    ) -> CLIERPResult<()> {
+        // Check authentication for Inventory commands
+        let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+            CLIERPError::Authentication("Login required for Inventory commands".to_string())
+        })?;
+
+        use crate::core::command::InvCommands;
+
+        match action {
+            InvCommands::Category { action } => execute_category_command(action),
+            InvCommands::Audit { action } => execute_audit_command(action, Some(user.id)),
+            InvCommands::Bundle { action } => execute_bundle_command(action),
+            InvCommands::Product { action } => {
+                self.execute_product_command(action).await
+            }
+            InvCommands::Stock { action } => {
+                self.execute_stock_command(action).await
+            }
+            InvCommands::Browse { search } => {
+                use crate::cli::tui::browse::InventoryBrowser;
+
+                InventoryBrowser::new(search)?.run()
+            }
+            InvCommands::Intrastat { period, flow, output, template } => {
+                use crate::modules::inventory::IntrastatService;
+
+                let mut conn = get_connection()?;
+                let declaration = IntrastatService::generate_declaration(&mut conn, &period, flow)?;
+
+                println!("Intrastat Declaration ({}, {}):", declaration.period, declaration.flow);
+                if declaration.lines.is_empty() {
+                    outln!("{}", t("inv.no_cross_border"));
+                }
+                for line in &declaration.lines {
+                    println!(
+                        "  {} | {} | Origin: {} | Qty: {} | Net mass: {:.3} kg | Value: {}",
+                        line.commodity_code,
+                        line.partner_country,
+                        line.country_of_origin,
+                        line.quantity,
+                        line.net_mass_kg(),
+                        format_currency(line.invoice_value)
+                    );
+                }
+                println!(
+                    "Total: {} lines, {:.3} kg, {}",
+                    declaration.lines.len(),
+                    declaration.total_net_mass_kg(),
+                    format_currency(declaration.total_value())
+                );
+                for warning in &declaration.warnings {
+                    outln!("⚠️  {}", warning);
+                }
+
+                if let Some(output) = output {
+                    match template {
+                        Some(name) => {
+                            use crate::modules::system::{ExportEntity, ExportTemplateService};
+
+                            let mapping = ExportTemplateService::mapping(&mut conn, &name, ExportEntity::Intrastat)?;
+                            IntrastatService::export_csv_mapped(&declaration, &output, &mapping)?;
+                        }
+                        None => IntrastatService::export_csv(&declaration, &output)?,
+                    }
+                    outln!("{}", t_with("inv.declaration_exported", &[("path", &output)]));
+                }
+                Ok(())
+            }
+            InvCommands::Catalog { category, format, output, title, locale } => {
+                use crate::modules::inventory::CatalogService;
+
+                let mut conn = get_connection()?;
+                let entries = CatalogService::entries(&mut conn, category, locale.as_deref())?;
+                let output = output.unwrap_or_else(|| format!("catalog.{}", format.extension()));
+                let count = CatalogService::export(&entries, &title, format, &output)?;
+                let photos = entries.iter().filter(|e| e.photo.is_some()).count();
+                outln!("✅ Catalog of {} products ({} with photos) written to {}", count, photos, output);
+                Ok(())
+            }
+            InvCommands::WriteDown { action } => self.execute_write_down_command(action, user),
+        }
+    }
+
+    async fn execute_product_command(
+        &mut self,
+        action: crate::core::command::ProductCommands,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::ProductCommands;
+        use crate::modules::inventory::{AttachmentService, ProductService, TranslationService};
+        use crate::modules::system::links::{print_related, LinkService, RecordRef};
+        use crate::utils::formatting::format_datetime;
+        use crate::utils::pagination::PaginationParams;
+
+        let service = ProductService::new();
+
+        match action {
+            ProductCommands::Add {
+                sku,
+                name,
+                category_id,
+                price,
+                cost_price,
+                stock,
+                min_stock,
+                max_stock,
+                unit,
+                description,
+                barcode,
+                commodity_code,
+                origin_country,
+                net_weight,
+            } => {
+                let mut product = service.create_product(
+                    &sku,
+                    &name,
+                    description.as_deref(),
+                    category_id,
+                    price,
+                    cost_price.unwrap_or(0),
+                    stock.unwrap_or(0),
+                    min_stock.unwrap_or(0),
+                    max_stock,
+                    &unit.unwrap_or_else(|| "ea".to_string()),
+                    barcode.as_deref(),
+                )?;
+
+                if commodity_code.is_some() || origin_country.is_some() || net_weight.is_some() {
+                    product = service.update_customs_info(
+                        product.id,
+                        commodity_code.as_deref().map(Some),
+                        origin_country.as_deref().map(Some),
+                        net_weight.map(Some),
+                    )?;
+                }
+
+                outln!("{}", t("inv.product_created"));
+                println!("  ID: {}", product.id);
+                println!("  SKU: {}", product.sku);
+                println!("  Name: {}", product.name);
+                println!("  Category ID: {}", product.category_id);
+                println!("  Price: {}", format_currency(product.price));
+                println!("  Stock: {} {}", product.current_stock, product.unit);
+            }
+            ProductCommands::List {
+                category_id,
+                search,
+                low_stock,
+                active,
+                page,
+                per_page,
+                filter,
+                locale,
+                table,
+            } => {
+                let pagination = PaginationParams::new(page.unwrap_or(1), per_page.unwrap_or_else(|| PaginationParams::default_per_page(20)));
+                let mut result = service.list_products_where(
+                    &pagination,
+                    category_id,
+                    active.unwrap_or(true),
+                    search.as_deref(),
+                    low_stock.unwrap_or(false),
+                    filter.as_ref(),
+                )?;
+                if let Some(locale) = &locale {
+                    let mut conn = get_connection()?;
+                    TranslationService::localize_product_list(&mut conn, &mut result.data, locale)?;
+                }
+
+                if result.data.is_empty() {
+                    outln!("{}", t("inv.no_products"));
+                    return Ok(());
+                }
+
+                let mut view = TableView::new(&["SKU", "Name", "Category", "Price", "Stock", "Unit", "Status"]);
+                for prod_with_cat in &result.data {
+                    let status = if prod_with_cat.product.current_stock <= prod_with_cat.product.min_stock_level {
+                        "LOW STOCK"
+                    } else if prod_with_cat.product.is_active {
+                        "ACTIVE"
+                    } else {
+                        "INACTIVE"
+                    };
+
+                    view.push(vec![
+                        prod_with_cat.product.sku.clone(),
+                        prod_with_cat.product.name.clone(),
+                        prod_with_cat.category.name.clone(),
+                        format_currency(prod_with_cat.product.price),
+                        prod_with_cat.product.current_stock.to_string(),
+                        prod_with_cat.product.unit.clone(),
+                        status.to_string(),
+                    ]);
+                }
+                view.apply(&table)?;
+
+                let mut out = format!("{}\n{}\n", t("inv.products_title"), view.render());
+                let _ = writeln!(
+                    out,
+                    "\nPage {} of {} (Total: {} products)",
+                    result.current_page(), result.pagination.total_pages, result.pagination.total_count
+                );
+                pager::page(&out)?;
+            }
+            ProductCommands::Show { id, sku, history, locale } => {
+                let mut product = if let Some(id) = id {
+                    service.get_product_by_id(id)?
+                } else if let Some(sku) = sku {
+                    service.get_product_by_sku(&sku)?
+                        .ok_or_else(|| CLIERPError::NotFound(format!("Product with SKU '{}' not found", sku)))?
+                } else {
+                    return Err(CLIERPError::InvalidInput("Either --id or --sku must be provided".to_string()));
+                };
+                if let Some(locale) = &locale {
+                    let mut conn = get_connection()?;
+                    TranslationService::localize_products(&mut conn, std::slice::from_mut(&mut product), locale)?;
+                }
+
+                outln!("{}", t("inv.product_details"));
+                println!("  ID: {}", product.id);
+                println!("  SKU: {}", product.sku);
+                println!("  Name: {}", product.name);
+                println!("  Category ID: {}", product.category_id);
+                println!("  Price: {}", format_currency(product.price));
+                println!("  Cost Price: {}", format_currency(product.cost_price));
+                println!("  Current Stock: {} {}", product.current_stock, product.unit);
+                println!("  Min Stock Level: {}", product.min_stock_level);
+                if let Some(max_level) = product.max_stock_level {
+                    println!("  Max Stock Level: {}", max_level);
+                }
+                if let Some(desc) = &product.description {
+                    println!("  Description: {}", desc);
+                }
+                if let Some(barcode) = &product.barcode {
+                    println!("  Barcode: {}", barcode);
+                }
+                if let Some(code) = &product.commodity_code {
+                    println!("  Commodity Code: {}", code);
+                }
+                if let Some(country) = &product.country_of_origin {
+                    println!("  Country of Origin: {}", country);
+                }
+                if let Some(weight) = product.net_weight_grams {
+                    println!("  Net Weight: {} g", weight);
+                }
+                println!("  Active: {}", if product.is_active { "Yes" } else { "No" });
+                println!("  Created: {}", product.created_at.format("%Y-%m-%d %H:%M:%S"));
+                println!("  Updated: {}", product.updated_at.format("%Y-%m-%d %H:%M:%S"));
+
+                let mut conn = get_connection()?;
+                if let Some(line) = crate::modules::inventory::WriteDownService::provision(&mut conn, product.id)? {
+                    println!(
+                        "  Written Down: {}% of cost ({} stock, write-down {})",
+                        line.percent, line.reason, line.write_down_id
+                    );
+                }
+                print_related(&LinkService::related(
+                    &mut conn,
+                    RecordRef::new(crate::database::RecordType::Product, product.id),
+                )?);
+
+                if history {
+                    print_history(&AuditService::history(&mut conn, "products", product.id)?);
+
+                    let flags = crate::modules::system::AnomalyService::open_flags_for(
+                        &mut conn,
+                        crate::modules::system::STOCK_MOVEMENT_SOURCE,
+                        &product.sku,
+                    )?;
+                    if !flags.is_empty() {
+                        println!("\nOpen Anomalies:");
+                        for flag in flags {
+                            println!(
+                                "  #{}  stock movement {}  quantity {} (score {:.2}, {})",
+                                flag.id, flag.record_id, flag.value, flag.score, flag.method
+                            );
+                        }
+                    }
+                }
+            }
+            ProductCommands::Update {
+                id,
+                name,
+                category_id,
+                price,
+                cost_price,
+                min_stock,
+                max_stock,
+                unit,
+                description,
+                barcode,
+                active,
+            } => {
+                let before = service.get_product_by_id(id)?;
+                let product = service.update_product(
+                    id,
+                    name.as_deref(),
+                    description.as_deref().map(Some),
+                    category_id,
+                    price,
+                    cost_price,
+                    min_stock,
+                    max_stock.map(Some),
+                    unit.as_deref(),
+                    barcode.as_deref().map(Some),
+                    active,
+                )?;
+
+                println!("Product {} ({}):", product.id, product.sku);
+                print_changes(&changed_fields(&before, &product)?);
+            }
+            ProductCommands::Customs {
+                id,
+                commodity_code,
+                origin_country,
+                net_weight,
+            } => {
+                let product = service.update_customs_info(
+                    id,
+                    commodity_code.as_deref().map(Some),
+                    origin_country.as_deref().map(Some),
+                    net_weight.map(Some),
+                )?;
+
+                outln!("{}", t("inv.customs_updated"));
+                println!("  SKU: {}", product.sku);
+                println!("  Commodity Code: {}", product.commodity_code.as_deref().unwrap_or("-"));
+                println!("  Country of Origin: {}", product.country_of_origin.as_deref().unwrap_or("-"));
+                println!(
+                    "  Net Weight: {}",
+                    product.net_weight_grams.map(|w| format!("{} g", w)).unwrap_or_else(|| "-".to_string())
+                );
+            }
+            ProductCommands::Translate { id, locale, name, description } => {
+                let mut conn = get_connection()?;
+                let translation = TranslationService::set_product(
+                    &mut conn,
+                    id,
+                    &locale,
+                    name.as_deref(),
+                    description.as_deref(),
+                )?;
+                outln!("✅ Translation saved:");
+                println!("  Product ID: {}", translation.product_id);
+                println!("  Locale: {}", translation.locale);
+                println!("  Name: {}", translation.name);
+                if let Some(desc) = &translation.description {
+                    println!("  Description: {}", desc);
+                }
+            }
+            ProductCommands::Translations { id } => {
+                let mut conn = get_connection()?;
+                let product = service.get_product_by_id(id)?;
+                let translations = TranslationService::product_translations(&mut conn, id)?;
+                if translations.is_empty() {
+                    println!("No translations for product {} ({}).", product.id, product.sku);
+                    return Ok(());
+                }
+
+                let mut view = TableView::new(&["Locale", "Name", "Description", "Updated"]);
+                for translation in translations {
+                    view.push(vec![
+                        translation.locale,
+                        translation.name,
+                        translation.description.unwrap_or_else(|| "-".to_string()),
+                        format_datetime(&translation.updated_at),
+                    ]);
+                }
+                println!("Translations of {} ({}):", product.name, product.sku);
+                println!("{}", view.render());
+            }
+            ProductCommands::Untranslate { id, locale } => {
+                let mut conn = get_connection()?;
+                if TranslationService::remove_product(&mut conn, id, &locale)? {
+                    outln!("✅ Removed the {} translation of product {}", locale, id);
+                } else {
+                    println!("Product {} has no {} translation.", id, locale);
+                }
+            }
+            ProductCommands::Attach { id, file, kind, primary } => {
+                let attachments = AttachmentService::new();
+                let product = service.get_product_by_id(id)?;
+                let primary = primary || (kind == "image" && attachments.get_primary_image(product.id)?.is_none());
+                let attachment = attachments.add_attachment(product.id, &kind, std::path::Path::new(&file), primary)?;
+                outln!(
+                    "✅ Attached {} to {} as {} #{}{}",
+                    attachment.file_name,
+                    product.sku,
+                    attachment.attachment_type,
+                    attachment.id,
+                    if attachment.is_primary { " (primary)" } else { "" }
+                );
+            }
+            ProductCommands::Attachments { id } => {
+                let product = service.get_product_by_id(id)?;
+                let attachments = AttachmentService::new().list_attachments(product.id, None)?;
+                if attachments.is_empty() {
+                    println!("No attachments for product {} ({}).", product.id, product.sku);
+                    return Ok(());
+                }
+
+                let mut view = TableView::new(&["ID", "Type", "File", "Size", "Primary", "Added"]);
+                for attachment in attachments {
+                    view.push(vec![
+                        attachment.id.to_string(),
+                        attachment.attachment_type,
+                        attachment.file_name,
+                        format!("{} KB", (attachment.file_size + 1023) / 1024),
+                        if attachment.is_primary { "Yes" } else { "" }.to_string(),
+                        format_datetime(&attachment.created_at),
+                    ]);
+                }
+                println!("Attachments of {} ({}):", product.name, product.sku);
+                println!("{}", view.render());
+            }
+            ProductCommands::Photo { attachment } => {
+                let attachment = AttachmentService::new().set_primary_photo(attachment)?;
+                outln!("✅ {} is now the photo of product {}", attachment.file_name, attachment.product_id);
+            }
+            ProductCommands::Detach { attachment } => {
+                let attachments = AttachmentService::new();
+                let existing = attachments.get_attachment(attachment)?;
+                if !confirm_action(&format!("Delete attachment {}?", existing.file_name), RiskLevel::Medium)? {
+                    println!("Cancelled.");
+                    return Ok(());
+                }
+                attachments.delete_attachment(attachment)?;
+                outln!("✅ Deleted attachment {}", existing.file_name);
+            }
+            ProductCommands::BulkUpdate { filters, updates } => {
+                let matches = service.find_products_by_filters(&filters)?;
+                if matches.is_empty() {
+                    println!("No products match the filters.");
+                    return Ok(());
+                }
+
+                println!("{} product(s) will be updated:", matches.len());
+                for product in matches.iter().take(10) {
+                    println!("  {} - {}", product.sku, product.name);
+                }
+                if matches.len() > 10 {
+                    println!("  ... and {} more", matches.len() - 10);
+                }
+                if !confirm_action("Apply these changes?", RiskLevel::Medium)? {
+                    println!("Bulk update cancelled.");
+                    return Ok(());
+                }
+
+                let updated = service.bulk_update_products(&filters, &updates)?;
+                outln!("✅ {} product(s) updated", updated);
+            }
+            ProductCommands::Import { file, chunk_size, on_conflict, restart } => {
+                use crate::modules::inventory::ProductImportService;
+
+                let mut conn = get_connection()?;
+                let summary =
+                    ProductImportService::import_csv(&mut conn, &file, chunk_size, on_conflict, restart)?;
+
+                if summary.resumed_from > 0 {
+                    println!("Resumed after row {} of an interrupted import.", summary.resumed_from);
+                }
+                outln!("✅ {} product(s) imported ({} rows in file)", summary.imported, summary.total_rows);
+                if summary.updated > 0 {
+                    println!("   {} existing product(s) updated", summary.updated);
+                }
+                if summary.skipped > 0 {
+                    println!("   {} row(s) skipped because the SKU already exists", summary.skipped);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn execute_stock_command(
+        &mut self,
+        action: crate::core::command::StockCommands,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::StockCommands;
+        use crate::modules::inventory::{ProductService, ReservationService, StockApprovalService};
+
+        let service = ProductService::new();
+        let approval = self.config.stock_approval.clone();
+        // Large manual changes are queued instead of applied
+        let queue_if_large = |product_id: i32,
+                              movement_type: &str,
+                              quantity: i32,
+                              unit_cost: Option<i32>,
+                              reference: Option<&str>,
+                              notes: Option<&str>|
+         -> CLIERPResult<bool> {
+            let product = service.get_product_by_id(product_id)?;
+            let value = StockApprovalService::change_value(&product, quantity, unit_cost);
+            if !StockApprovalService::requires_approval(&approval, quantity, value) {
+                return Ok(false);
+            }
+
+            let mut conn = get_connection()?;
+            let request = StockApprovalService::request(
+                &mut conn,
+                &product,
+                movement_type,
+                quantity,
+                unit_cost,
+                reference,
+                notes,
+                AuditService::actor(),
+            )?;
+            outln!("⏳ Stock change queued for supervisor approval (request ID {})", request.id);
+            println!("  Product: {} ({})", product.name, product.sku);
+            println!("  Change: {:+} {} ({})", request.quantity, product.unit, request.movement_type);
+            println!("  Value: {}", format_currency(request.value));
+            println!("  Stock stays at {} {} until approved", product.current_stock, product.unit);
+            Ok(true)
+        };
+        let resolve_product_id = |product_id: Option<i32>, sku: Option<String>| -> CLIERPResult<i32> {
+            if let Some(id) = product_id {
+                Ok(id)
+            } else if let Some(sku) = sku {
+                let product = service.get_product_by_sku(&sku)?
+                    .ok_or_else(|| CLIERPError::NotFound(format!("Product with SKU '{}' not found", sku)))?;
+                Ok(product.id)
+            } else {
+                Err(CLIERPError::InvalidInput("Either --product-id or --sku must be provided".to_string()))
+            }
+        };
+
+        match action {
+            StockCommands::In {
+                product_id,
+                sku,
+                quantity,
+                unit_cost,
+                reference,
+                notes,
+            } => {
+                let product_id = if let Some(id) = product_id {
+                    id
+                } else if let Some(sku) = sku {
+                    let product = service.get_product_by_sku(&sku)?
+                        .ok_or_else(|| CLIERPError::NotFound(format!("Product with SKU '{}' not found", sku)))?;
+                    product.id
+                } else {
+                    return Err(CLIERPError::InvalidInput("Either --product-id or --sku must be provided".to_string()));
+                };
+
+                if queue_if_large(product_id, "in", quantity, unit_cost, reference.as_deref(), notes.as_deref())? {
+                    return Ok(());
+                }
+
+                let updated_product = service.update_stock(
+                    product_id,
+                    quantity,
+                    "in",
+                    unit_cost,
+                    reference.as_deref(),
+                    None,
+                    notes.as_deref(),
+                    None, // TODO: Add user context
+                )?;
+
+                outln!("{}", t("inv.stock_added"));
+                println!("  Product: {} ({})", updated_product.name, updated_product.sku);
+                println!("  Quantity Added: {} {}", quantity, updated_product.unit);
+                println!("  New Stock Level: {} {}", updated_product.current_stock, updated_product.unit);
+            }
+            StockCommands::Out {
+                product_id,
+                sku,
+                quantity,
+                reference,
+                notes,
+            } => {
+                let product_id = if let Some(id) = product_id {
+                    id
+                } else if let Some(sku) = sku {
+                    let product = service.get_product_by_sku(&sku)?
+                        .ok_or_else(|| CLIERPError::NotFound(format!("Product with SKU '{}' not found", sku)))?;
+                    product.id
+                } else {
+                    return Err(CLIERPError::InvalidInput("Either --product-id or --sku must be provided".to_string()));
+                };
+
+                if queue_if_large(product_id, "out", -quantity.abs(), None, reference.as_deref(), notes.as_deref())? {
+                    return Ok(());
+                }
+
+                let updated_product = service.update_stock(
+                    product_id,
+                    -quantity.abs(),
+                    "out",
+                    None,
+                    reference.as_deref(),
+                    None,
+                    notes.as_deref(),
+                    None, // TODO: Add user context
+                )?;
+
+                outln!("{}", t("inv.stock_removed"));
+                println!("  Product: {} ({})", updated_product.name, updated_product.sku);
+                println!("  Quantity Removed: {} {}", quantity, updated_product.unit);
+                println!("  New Stock Level: {} {}", updated_product.current_stock, updated_product.unit);
+            }
+            StockCommands::Availability { product_id, sku } => {
+                let product_id = resolve_product_id(product_id, sku)?;
+                let mut conn = get_connection()?;
+                let availability = ReservationService::availability(&mut conn, product_id)?;
+
+                println!("Stock Availability:");
+                println!("  Product: {} ({})", availability.name, availability.sku);
+                println!("  On Hand: {} {}", availability.on_hand, availability.unit);
+                println!("  Reserved: {} {}", availability.reserved, availability.unit);
+                println!("  Available: {} {}", availability.available, availability.unit);
+
+                let reservations = ReservationService::list_active(&mut conn, product_id)?;
+                if !reservations.is_empty() {
+                    println!("\n  Active Reservations:");
+                    for reservation in reservations {
+                        println!(
+                            "    #{} {} {} for {}{} ({})",
+                            reservation.id,
+                            reservation.quantity,
+                            availability.unit,
+                            reservation.reference_type,
+                            reservation.reference_id.map(|id| format!(" #{}", id)).unwrap_or_default(),
+                            reservation.created_at.format("%Y-%m-%d")
+                        );
+                    }
+                }
+            }
+            StockCommands::Reserve {
+                product_id,
+                sku,
+                quantity,
+                reference_type,
+                reference_id,
+                notes,
+            } => {
+                let product_id = resolve_product_id(product_id, sku)?;
+                let mut conn = get_connection()?;
+                let reservation = ReservationService::reserve(
+                    &mut conn,
+                    product_id,
+                    quantity,
+                    &reference_type,
+                    reference_id,
+                    notes.as_deref(),
+                    AuditService::actor(),
+                )?;
+                let availability = ReservationService::availability(&mut conn, product_id)?;
+
+                outln!("✅ Stock reserved:");
+                println!("  Reservation ID: {}", reservation.id);
+                println!("  Product: {} ({})", availability.name, availability.sku);
+                println!("  Quantity: {} {}", reservation.quantity, availability.unit);
+                println!("  Available Now: {} {}", availability.available, availability.unit);
+            }
+            StockCommands::Release { reservation_id } => {
+                let mut conn = get_connection()?;
+                let reservation = ReservationService::release(&mut conn, reservation_id)?;
+                outln!("✅ Reservation {} released ({} units available again)", reservation.id, reservation.quantity);
+            }
+            StockCommands::Fulfill { reservation_id } => {
+                let mut conn = get_connection()?;
+                let reservation = ReservationService::fulfill(&mut conn, reservation_id, AuditService::actor())?;
+                let product = service.get_product_by_id(reservation.product_id)?;
+                outln!("✅ Reservation {} fulfilled:", reservation.id);
+                println!("  Product: {} ({})", product.name, product.sku);
+                println!("  Quantity Shipped: {} {}", reservation.quantity, product.unit);
+                println!("  New Stock Level: {} {}", product.current_stock, product.unit);
+            }
+            StockCommands::Reverse { movement_id, reason } => {
+                let reversal = service.reverse_movement(movement_id, &reason, AuditService::actor())?;
+                let product = service.get_product_by_id(reversal.product_id)?;
+
+                outln!("{}", t("inv.movement_reversed"));
+                println!("  Original Movement: {}", movement_id);
+                println!("  Reversal Movement: {}", reversal.id);
+                println!("  Product: {} ({})", product.name, product.sku);
+                println!("  Quantity: {} {} ({})", reversal.quantity, product.unit, reversal.movement_type);
+                println!("  New Stock Level: {} {}", product.current_stock, product.unit);
+            }
+            StockCommands::Update { product_id, quantity, notes } => {
+                let product = service.get_product_by_id(product_id)?;
+                let change = quantity - product.current_stock;
+                if change == 0 {
+                    println!("Stock of {} ({}) is already {} {}", product.name, product.sku, quantity, product.unit);
+                    return Ok(());
+                }
+                if queue_if_large(product_id, "adjustment", change, None, None, notes.as_deref())? {
+                    return Ok(());
+                }
+
+                let updated_product = service.update_stock(
+                    product_id,
+                    change,
+                    "adjustment",
+                    None,
+                    None,
+                    None,
+                    notes.as_deref(),
+                    AuditService::actor(),
+                )?;
+
+                outln!("✅ Stock updated:");
+                println!("  Product: {} ({})", updated_product.name, updated_product.sku);
+                println!("  Change: {:+} {}", change, updated_product.unit);
+                println!("  New Stock Level: {} {}", updated_product.current_stock, updated_product.unit);
+            }
+            StockCommands::Pending { status, limit } => {
+                use crate::utils::formatting::format_datetime;
+
+                let mut conn = get_connection()?;
+                let requests = StockApprovalService::list(&mut conn, Some(status), limit)?;
+                if requests.is_empty() {
+                    println!("No {} stock adjustments.", status);
+                    return Ok(());
+                }
+
+                println!(
+                    "{:<6} {:<16} {:<11} {:>10} {:>14} {:<10} {:<19}",
+                    "ID", "SKU", "Type", "Change", "Value", "Status", "Requested"
+                );
+                println!("{}", "-".repeat(92));
+                for (request, product) in &requests {
+                    println!(
+                        "{:<6} {:<16} {:<11} {:>+10} {:>14} {:<10} {:<19}",
+                        request.id,
+                        product.sku,
+                        request.movement_type,
+                        request.quantity,
+                        format_currency(request.value),
+                        request.status,
+                        format_datetime(&request.requested_at)
+                    );
+                }
+            }
+            StockCommands::Approve { request_id, note, override_sod } => {
+                let approver = self.session_manager.get_current_user()?.ok_or_else(|| {
+                    CLIERPError::Authentication("Login required for Inventory commands".to_string())
+                })?;
+                let mut conn = get_connection()?;
+                let (request, product) =
+                    StockApprovalService::approve(
+                    &mut conn,
+                    request_id,
+                    approver,
+                    note.as_deref(),
+                    override_sod.as_deref(),
+                )?;
+
+                outln!("✅ Stock adjustment {} approved and applied:", request.id);
+                println!("  Product: {} ({})", product.name, product.sku);
+                println!("  Change: {:+} {} ({})", request.quantity, product.unit, request.movement_type);
+                if let Some(movement_id) = request.movement_id {
+                    println!("  Movement ID: {}", movement_id);
+                }
+                println!("  New Stock Level: {} {}", product.current_stock, product.unit);
+            }
+            StockCommands::Reject { request_id, reason } => {
+                let approver = self.session_manager.get_current_user()?.ok_or_else(|| {
+                    CLIERPError::Authentication("Login required for Inventory commands".to_string())
+                })?;
+                let mut conn = get_connection()?;
+                let request = StockApprovalService::reject(&mut conn, request_id, &approver, &reason)?;
+                println!("Stock adjustment {} rejected; stock was not changed", request.id);
+            }
+            StockCommands::Check { low_stock, out_of_stock, category_id, table } => {
+                if low_stock {
+                    let low_stock_products = service.get_low_stock_products()?;
+
+                    if low_stock_products.is_empty() {
+                        outln!("{}", t("inv.no_low_stock"));
+                        return Ok(());
+                    }
+
+                    let mut view = TableView::new(&["SKU", "Name", "Category", "Current", "Min", "Unit"]);
+                    for prod_with_cat in &low_stock_products {
+                        view.push(vec![
+                            prod_with_cat.product.sku.clone(),
+                            prod_with_cat.product.name.clone(),
+                            prod_with_cat.category.name.clone(),
+                            prod_with_cat.product.current_stock.to_string(),
+                            prod_with_cat.product.min_stock_level.to_string(),
+                            prod_with_cat.product.unit.clone(),
+                        ]);
+                    }
+                    view.apply(&table)?;
+                    pager::page(&format!("{}\n{}", t("inv.low_stock_title"), view.render()))?;
+                } else {
+                    print_stock_status(category_id, out_of_stock, &table)?;
+                }
+            }
+            StockCommands::History { product_id, sku, page, per_page, table } => {
+                let product_id = resolve_product_id(product_id, sku)?;
+                let product = service.get_product_by_id(product_id)?;
+                let per_page = per_page.unwrap_or_else(|| crate::utils::pagination::PaginationParams::default_per_page(20));
+                print_stock_history(&product, page.unwrap_or(1), per_page, &table)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_crm_command(
+        &mut self,
+        action: crate::core::command::CrmCommands,
+    ) -> CLIERPResult<()> {
+        // Check authentication for CRM commands
+        let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+            CLIERPError::Authentication("Login required for CRM commands".to_string())
+        })?;
+
+        let mut conn = get_connection()?;
+
+        use crate::cli::commands::crm_extended::{execute_crm_extended_command, CrmExtendedCommands, CrmExtendedAction};
+
+        match action {
+            crate::core::command::CrmCommands::Customer { action } => execute_crm_extended_command(
+                &mut conn,
+                CrmExtendedCommands { action: CrmExtendedAction::Customer { action } },
+                &user,
+            ),
+            crate::core::command::CrmCommands::Lead { action } => execute_crm_extended_command(
+                &mut conn,
+                CrmExtendedCommands { action: CrmExtendedAction::Lead { action } },
+                &user,
+            ),
+            crate::core::command::CrmCommands::Pipeline { action } => match action {
+                crate::core::command::PipelineCommands::Board { all } => {
+                    use crate::cli::tui::board::PipelineBoard;
+                    use crate::modules::crm::RecordScope;
+
+                    let scope = RecordScope::for_user(&mut conn, &user, all)?;
+                    let mut board = PipelineBoard::load(&mut conn, &scope)?;
+                    board.run(&mut conn, &scope)
+                }
+            },
+            crate::core::command::CrmCommands::Forecast { action } => execute_forecast_command(&mut conn, action, &user),
+            crate::core::command::CrmCommands::Survey { action } => execute_survey_command(&mut conn, action, &user),
+            crate::core::command::CrmCommands::Churn { action } => self.execute_churn_command(action),
+            crate::core::command::CrmCommands::Dropbox { action } => self.execute_dropbox_command(action),
+        }
+    }
+
+    fn execute_dropbox_command(&self, action: crate::core::command::DropboxCommands) -> CLIERPResult<()> {
+        use crate::core::command::DropboxCommands;
+        use crate::modules::crm::{dropbox_address, dropbox_token, DropboxService, IngestOutcome};
+
+        let token = dropbox_token(&self.config.auth.jwt_secret)?;
+        let describe = |outcome: &IngestOutcome| match outcome {
+            IngestOutcome::Logged(activities) => format!("logged as {} activit(ies)", activities.len()),
+            IngestOutcome::Unmatched => "no customer address, not logged".to_string(),
+            IngestOutcome::Duplicate => "already logged".to_string(),
+        };
+
+        match action {
+            DropboxCommands::Address => {
+                println!("{}", dropbox_address(&self.config)?);
+                println!(
+                    "Mail to it is logged when delivered to `clierp crm dropbox ingest`, fetched from {} or posted to {}/dropbox/{}",
+                    self.config.dropbox.maildir.as_deref().unwrap_or("the dropbox maildir"),
+                    self.config.server.base_url(),
+                    token
+                );
+            }
+            DropboxCommands::Ingest { file } => {
+                let raw = match file {
+                    Some(path) => std::fs::read(path)?,
+                    None => {
+                        use std::io::Read;
+                        let mut raw = Vec::new();
+                        std::io::stdin().read_to_end(&mut raw)?;
+                        raw
+                    }
+                };
+                let mut conn = get_connection()?;
+                let outcome = DropboxService::ingest(&mut conn, &token, &raw)?;
+                if let IngestOutcome::Logged(activities) = &outcome {
+                    let ids: Vec<String> = activities.iter().map(|a| a.id.to_string()).collect();
+                    outln!("✅ Email logged as activity {}", ids.join(", "));
+                } else {
+                    println!("Email {}", describe(&outcome));
+                }
+            }
+            DropboxCommands::Fetch { maildir } => {
+                let maildir = maildir
+                    .or_else(|| self.config.dropbox.maildir.as_ref().map(std::path::PathBuf::from))
+                    .ok_or_else(|| {
+                        CLIERPError::Configuration(config::ConfigError::Message(
+                            "dropbox.maildir is not set; pass --maildir".to_string(),
+                        ))
+                    })?;
+                let mut conn = get_connection()?;
+                let fetched = DropboxService::fetch(&mut conn, &token, &maildir)?;
+                if fetched.is_empty() {
+                    println!("No new emails in {}", maildir.display());
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["Message", "Result"]);
+                let mut failed = 0;
+                for (name, outcome) in &fetched {
+                    let result = match outcome {
+                        Ok(outcome) => describe(outcome),
+                        Err(e) => {
+                            failed += 1;
+                            format!("failed: {}", e)
+                        }
+                    };
+                    view.push(vec![name.clone(), result]);
+                }
+                println!("{}", view.render());
+                outln!("✅ {} email(s) processed, {} left in new/", fetched.len() - failed, failed);
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_churn_command(&self, action: crate::core::command::ChurnCommands) -> CLIERPResult<()> {
+        use crate::core::command::ChurnCommands;
+        use crate::modules::crm::{ChurnPolicy, ChurnService};
+        use crate::utils::formatting::format_date;
+        use crate::utils::timezone::DisplayTimezone;
+
+        let policy = ChurnPolicy::from_config(&self.config.churn)?;
+        let today = DisplayTimezone::current().today();
+        let mut conn = get_connection()?;
+
+        match action {
+            ChurnCommands::List { as_of } => {
+                let at_risk = ChurnService::at_risk(&mut conn, &policy, as_of.unwrap_or(today))?;
+                if at_risk.is_empty() {
+                    println!("No customers at risk of churning.");
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["Code", "Customer", "Owner", "Last Order", "Recent", "Before", "Signals", "Next Action"]);
+                for customer in &at_risk {
+                    view.push(vec![
+                        customer.customer.customer_code.clone(),
+                        customer.customer.name.clone(),
+                        customer.owner.as_ref().map(|o| o.1.clone()).unwrap_or_else(|| "unassigned".to_string()),
+                        customer.last_order.map(|d| format_date(&d)).unwrap_or_default(),
+                        format_currency(customer.recent_value),
+                        format_currency(customer.prior_value),
+                        customer.signals.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(", "),
+                        customer.next_action.to_string(),
+                    ]);
+                }
+                println!("{}", view.render());
+                println!(
+                    "{} customer(s) at risk; recent and before are order values of the last {} days and the {} before.",
+                    at_risk.len(),
+                    policy.window_days,
+                    policy.window_days
+                );
+            }
+            ChurnCommands::Alert { as_of } => {
+                let alerted = ChurnService::alert(&mut conn, &policy, as_of.unwrap_or(today))?;
+                let unassigned: Vec<&str> = alerted
+                    .iter()
+                    .filter(|(_, notified)| !notified)
+                    .map(|(customer, _)| customer.customer.name.as_str())
+                    .collect();
+                outln!(
+                    "✅ {} churn alert(s) raised, {} owner(s) notified",
+                    alerted.len(),
+                    alerted.len() - unassigned.len()
+                );
+                if !unassigned.is_empty() {
+                    println!("No owner with a user account for: {}", unassigned.join(", "));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_write_down_command(
+        &self,
+        action: crate::core::command::WriteDownCommands,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::WriteDownCommands;
+        use crate::modules::inventory::{WriteDownAccounts, WriteDownPolicy, WriteDownService};
+        use crate::utils::formatting::{format_date, format_datetime};
+        use crate::utils::timezone::DisplayTimezone;
+
+        let today = DisplayTimezone::current().today();
+        let mut conn = get_connection()?;
+
+        match action {
+            WriteDownCommands::Propose { as_of, dry_run } => {
+                let policy = WriteDownPolicy::from_config(&self.config.write_down)?;
+                let as_of = as_of.unwrap_or(today);
+                let (write_down, candidates) = if dry_run {
+                    (None, WriteDownService::candidates(&mut conn, &policy, as_of)?)
+                } else {
+                    let (write_down, candidates) = WriteDownService::propose(&mut conn, &policy, as_of, Some(user.id))?;
+                    (Some(write_down), candidates)
+                };
+                if candidates.is_empty() {
+                    println!("No slow-moving or dead stock to provision as of {}.", format_date(&as_of));
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["SKU", "Name", "Reason", "Quantity", "Unit Cost", "Provisioned", "To", "Amount"]);
+                for candidate in &candidates {
+                    view.push(vec![
+                        candidate.sku.clone(),
+                        candidate.name.clone(),
+                        candidate.reason.to_string(),
+                        candidate.quantity.to_string(),
+                        format_currency(candidate.unit_cost),
+                        format!("{}%", candidate.provisioned_percent),
+                        format!("{}%", candidate.percent),
+                        format_currency(candidate.amount),
+                    ]);
+                }
+                println!("{}", view.render());
+                match write_down {
+                    Some(write_down) => outln!(
+                        "✅ Write-down {} of {} proposed; a manager approves it with 'clierp inv write-down approve {}'",
+                        write_down.id,
+                        format_currency(write_down.amount),
+                        write_down.id
+                    ),
+                    None => {
+                        let total: i32 = candidates.iter().map(|c| c.amount).sum();
+                        println!("Total: {}", format_currency(total));
+                    }
+                }
+            }
+            WriteDownCommands::List { status, limit } => {
+                let write_downs = WriteDownService::list(&mut conn, status, limit)?;
+                if write_downs.is_empty() {
+                    println!("No stock write-downs.");
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["ID", "As Of", "Amount", "Status", "Proposed", "Decided"]);
+                for write_down in write_downs {
+                    view.push(vec![
+                        write_down.id.to_string(),
+                        format_date(&write_down.as_of),
+                        format_currency(write_down.amount),
+                        write_down.status,
+                        format_datetime(&write_down.proposed_at),
+                        write_down.decided_at.map(|t| format_datetime(&t)).unwrap_or_default(),
+                    ]);
+                }
+                println!("{}", view.render());
+            }
+            WriteDownCommands::Show { write_down_id } => {
+                let write_down = WriteDownService::find(&mut conn, write_down_id)?;
+                println!("Write-down {} as of {}", write_down.id, format_date(&write_down.as_of));
+                println!("Status: {}", write_down.status);
+                println!("Amount: {}", format_currency(write_down.amount));
+                if let Some(note) = &write_down.decision_note {
+                    println!("Decision Note: {}", note);
+                }
+                let mut view = TableView::new(&["SKU", "Name", "Reason", "Quantity", "Unit Cost", "Percent", "Amount"]);
+                for (line, product) in WriteDownService::lines(&mut conn, write_down.id)? {
+                    view.push(vec![
+                        product.sku,
+                        product.name,
+                        line.reason,
+                        line.quantity.to_string(),
+                        format_currency(line.unit_cost),
+                        format!("{}%", line.percent),
+                        format_currency(line.amount),
+                    ]);
+                }
+                println!("{}", view.render());
+            }
+            WriteDownCommands::Approve { write_down_id, note, override_sod } => {
+                let accounts = WriteDownAccounts::from_config(&self.config.write_down)?;
+                let write_down = WriteDownService::approve(
+                    &mut conn,
+                    write_down_id,
+                    &accounts,
+                    user,
+                    note.as_deref(),
+                    override_sod.as_deref(),
+                )?;
+                outln!(
+                    "✅ Write-down {} approved; {} posted to {} on {}",
+                    write_down.id,
+                    format_currency(write_down.amount),
+                    accounts.provision,
+                    format_date(&write_down.as_of)
+                );
+            }
+            WriteDownCommands::Reject { write_down_id, reason } => {
+                let write_down = WriteDownService::reject(&mut conn, write_down_id, &user, &reason)?;
+                println!("Write-down {} rejected; nothing was posted", write_down.id);
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_sales_order_command(
+        &self,
+        action: crate::core::command::SalesOrderCommands,
+        user: crate::core::auth::AuthenticatedUser,
+    ) -> CLIERPResult<()> {
+        use crate::core::command::SalesOrderCommands;
+        use crate::database::SalesOrderStatus;
+        use crate::modules::sales::SalesOrderService;
+        use crate::utils::formatting::{format_date, format_datetime};
+        use crate::utils::timezone::DisplayTimezone;
+
+        let mut conn = get_connection()?;
+
+        match action {
+            SalesOrderCommands::Create { deal_id, notes } => {
+                let order = SalesOrderService::create_from_deal(&mut conn, deal_id, notes.as_deref(), Some(user.id))?;
+                outln!(
+                    "✅ Sales order {} created from deal {} for {}",
+                    order.order_number,
+                    deal_id,
+                    format_currency(order.total_amount)
+                );
+                println!("Reserve its stock with 'sales order reserve {}'", order.id);
+            }
+            SalesOrderCommands::List { status } => {
+                let status = status.map(|s| s.parse::<SalesOrderStatus>()).transpose()?;
+                let orders = SalesOrderService::list_sales_orders(&mut conn, status)?;
+                if orders.is_empty() {
+                    println!("No sales orders.");
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["ID", "Number", "Date", "Deal", "Status", "Total"]);
+                for order in orders {
+                    view.push(vec![
+                        order.id.to_string(),
+                        order.order_number,
+                        format_date(&order.order_date),
+                        order.deal_id.map(|id| id.to_string()).unwrap_or_default(),
+                        order.status,
+                        format_currency(order.total_amount),
+                    ]);
+                }
+                println!("{}", view.render());
+            }
+            SalesOrderCommands::Show { order_id } => {
+                let details = SalesOrderService::get_sales_order_with_details(&mut conn, order_id)?;
+                let order = &details.sales_order;
+
+                println!("Order Number: {}", order.order_number);
+                if let Some(customer) = &details.customer {
+                    println!("Customer: {} ({})", customer.name, customer.customer_code);
+                }
+                if let Some(deal_id) = order.deal_id {
+                    println!("Deal: {}", deal_id);
+                }
+                println!("Order Date: {}", format_date(&order.order_date));
+                println!("Status: {}", order.status);
+                if order.discount_percent > 0 {
+                    println!("Discount: {}%", order.discount_percent);
+                }
+                println!("Total Amount: {}", format_currency(order.total_amount));
+                if let Some(shipped_at) = order.shipped_at {
+                    println!("Shipped: {}", format_datetime(&shipped_at));
+                }
+                if let Some(invoice_id) = order.invoice_id {
+                    println!("Invoice: {}", invoice_id);
+                }
+                if let Some(notes) = &order.notes {
+                    println!("Notes: {}", notes);
+                }
+                println!();
+
+                let mut view = TableView::new(&["SKU", "Product", "Quantity", "Unit Price", "Total", "Shipped", "Reservation"]);
+                for item in &details.items {
+                    view.push(vec![
+                        item.product_sku.clone(),
+                        item.product_name.clone(),
+                        format!("{} {}", item.sales_order_item.quantity, item.unit),
+                        format_currency(item.sales_order_item.unit_price),
+                        format_currency(item.sales_order_item.total_price),
+                        item.sales_order_item.shipped_quantity.to_string(),
+                        item.sales_order_item.reservation_id.map(|id| id.to_string()).unwrap_or_default(),
+                    ]);
+                }
+                println!("{}", view.render());
+            }
+            SalesOrderCommands::Reserve { order_id } => {
+                let order = SalesOrderService::reserve(&mut conn, order_id, Some(user.id))?;
+                outln!("✅ Stock reserved for sales order {}", order.order_number);
+            }
+            SalesOrderCommands::Ship { order_id } => {
+                let order = SalesOrderService::ship(&mut conn, order_id, Some(user.id))?;
+                outln!("✅ Sales order {} shipped", order.order_number);
+            }
+            SalesOrderCommands::Invoice { order_id, due_days } => {
+                let due_date = DisplayTimezone::current().today() + chrono::Duration::days(due_days);
+                let invoice = SalesOrderService::invoice(&mut conn, order_id, Some(due_date), Some(user.id))?;
+                outln!(
+                    "✅ Invoice {} for {} issued, due {}",
+                    invoice.invoice_number,
+                    format_currency(invoice.total_amount),
+                    format_date(&due_date)
+                );
+            }
+            SalesOrderCommands::Cancel { order_id } => {
+                let order = SalesOrderService::cancel(&mut conn, order_id)?;
+                println!("Sales order {} cancelled; its reservations were released", order.order_number);
+            }
+        }
+        Ok(())
+    }
+
+    async fn execute_sales_command(
+        &mut self,
+        action: crate::core::command::SalesCommands,
+    ) -> CLIERPResult<()> {
+        // Check authentication for sales commands
+        let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+            CLIERPError::Authentication("Login required for sales commands".to_string())
+        })?;
+
+        let mut conn = get_connection()?;
+
+        use crate::cli::commands::crm_extended::{execute_crm_extended_command, CrmExtendedCommands, CrmExtendedAction};
+        use crate::core::command::SalesCommands;
+
+        let extended_action = match action {
+            SalesCommands::Atp { product_id, sku, quantity } => {
+                use crate::modules::inventory::{AtpService, ProductService};
+
+                let product_id = match (product_id, sku) {
+                    (Some(id), _) => id,
+                    (None, Some(sku)) => ProductService::new()
+                        .get_product_by_sku(&sku)?
+                        .ok_or_else(|| CLIERPError::NotFound(format!("Product with SKU '{}' not found", sku)))?
+                        .id,
+                    (None, None) => {
+                        return Err(CLIERPError::InvalidInput("Either --product-id or --sku must be provided".to_string()))
+                    }
+                };
+                let atp = AtpService::promise(&mut conn, product_id, quantity)?;
+
+                println!("Available to Promise:");
+                println!("  Product: {} ({})", atp.name, atp.sku);
+                println!("  Requested: {} {}", atp.requested, atp.unit);
+                println!("  Available Now: {} {}", atp.available_now, atp.unit);
+                if !atp.incoming.is_empty() {
+                    println!("\n  Incoming:");
+                    for line in &atp.incoming {
+                        println!("    {}  {} {} ({})", line.expected_date, line.quantity, atp.unit, line.po_number);
+                    }
+                }
+                println!("\n  Promise Date: {}", atp.promise_date);
+                if atp.needs_replenishment {
+                    outln!(
+                        "  ⚠️  Open purchase orders do not cover this quantity; date assumes a new order today ({} day lead time)",
+                        atp.lead_time_days
+                    );
+                }
+                return Ok(());
+            }
+            SalesCommands::Order { action } => return self.execute_sales_order_command(action, user),
+            SalesCommands::Customer { action } => CrmExtendedAction::Customer { action },
+            SalesCommands::Lead { action } => CrmExtendedAction::Lead { action },
+            SalesCommands::Deal { action } => CrmExtendedAction::Deal { action },
+            SalesCommands::Campaign { action } => CrmExtendedAction::Campaign { action },
+            SalesCommands::Activity { action } => CrmExtendedAction::Activity { action },
+            SalesCommands::Dashboard => CrmExtendedAction::Dashboard,
+            SalesCommands::Pipeline => CrmExtendedAction::Pipeline,
+            SalesCommands::Performance => CrmExtendedAction::Performance,
+        };
+
+        execute_crm_extended_command(&mut conn, CrmExtendedCommands { action: extended_action }, &user)
+    }
+
+    async fn execute_shell(&mut self) -> CLIERPResult<()> {
+        use crate::cli::shell::{split_words, Completer, LineEditor};
+        use clap::{CommandFactory, Parser};
+
+        let mut editor = LineEditor::new(Completer::new(CLIArgs::command()), Some(LineEditor::default_history_file()));
+        outln!("CLIERP shell. Type commands without 'clierp', Tab to complete, 'exit' or Ctrl-D to leave.");
+        if !self.session_manager.is_authenticated() {
+            println!("Not logged in; run 'auth login <username>' once for the whole session.");
+        }
+
+        loop {
+            self.renew_session()?;
+            let prompt = match self.session_manager.load_session().ok().flatten() {
+                Some(session) => format!("clierp ({})> ", session.username),
+                None => "clierp> ".to_string(),
+            };
+            let Some(line) = editor.read_line(&prompt)? else {
+                break;
+            };
+            let words = match split_words(&line) {
+                Ok(words) => words,
+                Err(message) => {
+                    eprintln!("Error: {}", message);
+                    continue;
+                }
+            };
+            let Some(first) = words.first() else {
+                continue;
+            };
+            editor.add_history(&line)?;
+
+            match first.as_str() {
+                "exit" | "quit" => break,
+                "history" => {
+                    for (i, entry) in editor.history().iter().enumerate() {
+                        println!("{:>5}  {}", i + 1, entry);
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            let args = match CLIArgs::try_parse_from(std::iter::once("clierp".to_string()).chain(words.iter().cloned())) {
+                Ok(args) => args,
+                Err(e) => {
+                    // Help and version requests are "errors" that print to stdout
+                    let _ = e.print();
+                    continue;
+                }
+            };
+            if matches!(args.command, Some(CLICommands::Shell)) {
+                println!("Already in the shell.");
+                continue;
+            }
+            let error_format = args.error_format;
+            if let Err(e) = Box::pin(self.run_recorded(&words, args)).await {
+                e.print(error_format, "Error");
+            }
+        }
+        Ok(())
+    }
+
+    /// Issue a fresh token once the shell's session is past half its
+    /// lifetime, so a long session stays logged in while it is in use
+    fn renew_session(&mut self) -> CLIERPResult<()> {
+        let Some(session) = self.session_manager.load_session()? else {
+            return Ok(());
+        };
+        let remaining = session.expires_at - chrono::Utc::now().timestamp();
+        if remaining > self.config.auth.jwt_expiration as i64 / 2 {
+            return Ok(());
+        }
+        if let Some(user) = self.session_manager.get_current_user()? {
+            let token = self.auth_service.generate_token(&user)?;
+            self.session_manager.save_session(&token)?;
+        }
+        Ok(())
+    }
+
+    async fn execute_export_command(&mut self, action: crate::core::command::ExportCommands) -> CLIERPResult<()> {
+        use crate::core::command::ExportCommands;
+        use crate::database::models::UserRole;
+        use crate::modules::system::warehouse::WarehouseService;
+
+        let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+            CLIERPError::Authentication("Login required for exports".to_string())
+        })?;
+        if !matches!(user.role, UserRole::Admin | UserRole::Manager) {
+            return Err(CLIERPError::Authorization("Admin or manager role required".to_string()));
+        }
+
+        match action {
+            ExportCommands::Warehouse { output } => {
+                let mut conn = get_connection()?;
+                let files = WarehouseService::export(&mut conn, &output)?;
+                let mut view = TableView::new(&["File", "Rows"]);
+                for file in &files {
+                    view.push(vec![file.path.display().to_string(), file.rows.to_string()]);
+                }
+                println!("{}", view.render());
+                outln!("✅ Exported {} file(s) to {}", files.len(), output.display());
+            }
+        }
+        Ok(())
+    }
+
+    async fn execute_event_command(&mut self, action: crate::core::command::EventCommands) -> CLIERPResult<()> {
+        use crate::core::command::EventCommands;
+        use crate::database::models::UserRole;
+        use crate::modules::system::events::{event_json, EventService};
+        use crate::modules::system::portal::{PortalScope, PortalService};
+        use crate::utils::formatting::format_datetime;
+
+        let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+            CLIERPError::Authentication("Login required for event commands".to_string())
+        })?;
+        if !matches!(user.role, UserRole::Admin | UserRole::Auditor) {
+            return Err(CLIERPError::Authorization("Admin or auditor role required".to_string()));
+        }
+        let mut conn = get_connection()?;
+
+        match action {
+            EventCommands::Tail { since, entity, limit, follow } => {
+                let mut after = match since {
+                    Some(since) => since,
+                    None => (EventService::last_sequence(&mut conn)? - limit).max(0),
+                };
+                loop {
+                    let events = EventService::since(&mut conn, after, entity.as_deref(), limit)?;
+                    for event in &events {
+                        println!("{}", event_json(event));
+                        after = event.sequence;
+                    }
+                    if !follow {
+                        break;
+                    }
+                    if events.is_empty() {
+                        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                    }
+                }
+            }
+            EventCommands::Share { days } => {
+                if !matches!(user.role, UserRole::Admin) {
+                    return Err(CLIERPError::Authorization("Admin role required".to_string()));
                 }
-                if let Some(desc) = &product.description {
-                    println!("  Description: {}", desc);
+                let days = days.unwrap_or(self.config.server.link_ttl_days);
+                let (link, token) = PortalService::issue(
+                    &mut conn,
+                    &self.config.auth.jwt_secret,
+                    PortalScope::Events,
+                    user.id,
+                    days,
+                    Some(user.id),
+                )?;
+                outln!("🔗 Event stream link created (ID {}), valid until {}", link.id, format_datetime(&link.expires_at));
+                println!("{}/events/{}?since=0", self.config.server.base_url(), token);
+                println!("Subscribers read JSON lines from it while 'clierp system serve' is running, passing the last");
+                println!("sequence they saw as since=, and wait=<seconds> to hold the request until new events arrive.");
+            }
+            EventCommands::Links => {
+                let links = PortalService::list(&mut conn, PortalScope::Events, user.id)?;
+                if links.is_empty() {
+                    println!("No event stream links.");
+                    return Ok(());
                 }
-                if let Some(barcode) = &product.barcode {
-                    println!("  Barcode: {}", barcode);
+                let now = chrono::Utc::now().naive_utc();
+                let mut view = TableView::new(&["ID", "Created", "Expires", "Last Used", "State"]);
+                for link in links {
+                    let state = if link.revoked_at.is_some() {
+                        "revoked"
+                    } else if link.expires_at <= now {
+                        "expired"
+                    } else {
+                        "active"
+                    };
+                    view.push(vec![
+                        link.id.to_string(),
+                        format_datetime(&link.created_at),
+                        format_datetime(&link.expires_at),
+                        link.last_used_at.map(|t| format_datetime(&t)).unwrap_or_else(|| "-".to_string()),
+                        state.to_string(),
+                    ]);
                 }
-                println!("  Active: {}", if product.is_active { "Yes" } else { "No" });
-                println!("  Created: {}", product.created_at.format("%Y-%m-%d %H:%M:%S"));
-                println!("  Updated: {}", product.updated_at.format("%Y-%m-%d %H:%M:%S"));
+                println!("{}", view.render());
             }
-            _ => {
-                println!("Product command not yet implemented: {:?}", action);
+            EventCommands::Revoke { link_id } => {
+                let link = PortalService::revoke(&mut conn, PortalScope::Events, link_id)?;
+                outln!("✅ Event stream link {} revoked", link.id);
             }
         }
-
         Ok(())
     }
 
-    async fn execute_stock_command(
+    async fn execute_email_template_command(
         &mut self,
-        action: crate::core::command::StockCommands,
+        action: crate::core::command::EmailTemplateCommands,
     ) -> CLIERPResult<()> {
-        use crate::core::command::StockCommands;
-        use crate::modules::inventory::ProductService;
+        use crate::core::command::EmailTemplateCommands;
+        use crate::database::models::UserRole;
+        use crate::modules::system::email_templates::{
+            parse_template_text, template_text, EmailTemplateService, MergeSource, MERGE_FIELDS,
+        };
+        use crate::utils::timezone::DisplayTimezone;
 
-        let service = ProductService::new();
+        let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+            CLIERPError::Authentication("Login required for template commands".to_string())
+        })?;
+        let mut conn = get_connection()?;
+        let require_manager = || {
+            if matches!(user.role, UserRole::Admin | UserRole::Manager) {
+                Ok(())
+            } else {
+                Err(CLIERPError::Authorization("Manager role required".to_string()))
+            }
+        };
 
         match action {
-            StockCommands::In {
-                product_id,
-                sku,
-                quantity,
-                unit_cost,
-                reference,
-                notes,
-            } => {
-                let product_id = if let Some(id) = product_id {
-                    id
-                } else if let Some(sku) = sku {
-                    let product = service.get_product_by_sku(&sku)?
-                        .ok_or_else(|| CLIERPError::NotFound(format!("Product with SKU '{}' not found", sku)))?;
-                    product.id
-                } else {
-                    return Err(CLIERPError::InvalidInput("Either --product-id or --sku must be provided".to_string()));
-                };
-
-                let updated_product = service.update_stock(
-                    product_id,
-                    quantity,
-                    "in",
-                    unit_cost,
-                    reference.as_deref(),
-                    None,
-                    notes.as_deref(),
-                    None, // TODO: Add user context
-                )?;
-
-                println!("✅ Stock added:");
-                println!("  Product: {} ({})", updated_product.name, updated_product.sku);
-                println!("  Quantity Added: {} {}", quantity, updated_product.unit);
-                println!("  New Stock Level: {} {}", updated_product.current_stock, updated_product.unit);
+            EmailTemplateCommands::List => {
+                let mut view = TableView::new(&["Name", "Subject", "Edited"]);
+                for template in EmailTemplateService::list(&mut conn)? {
+                    view.push(vec![
+                        template.name,
+                        template.subject,
+                        if template.edited { "yes" } else { "" }.to_string(),
+                    ]);
+                }
+                println!("{}", view.render());
             }
-            StockCommands::Out {
-                product_id,
-                sku,
-                quantity,
-                reference,
-                notes,
-            } => {
-                let product_id = if let Some(id) = product_id {
-                    id
-                } else if let Some(sku) = sku {
-                    let product = service.get_product_by_sku(&sku)?
-                        .ok_or_else(|| CLIERPError::NotFound(format!("Product with SKU '{}' not found", sku)))?;
-                    product.id
-                } else {
-                    return Err(CLIERPError::InvalidInput("Either --product-id or --sku must be provided".to_string()));
-                };
-
-                let updated_product = service.update_stock(
-                    product_id,
-                    -quantity.abs(),
-                    "out",
-                    None,
-                    reference.as_deref(),
-                    None,
-                    notes.as_deref(),
-                    None, // TODO: Add user context
-                )?;
-
-                println!("✅ Stock removed:");
-                println!("  Product: {} ({})", updated_product.name, updated_product.sku);
-                println!("  Quantity Removed: {} {}", quantity, updated_product.unit);
-                println!("  New Stock Level: {} {}", updated_product.current_stock, updated_product.unit);
+            EmailTemplateCommands::Show { name } => {
+                let template = EmailTemplateService::get(&mut conn, &name)?;
+                print!("{}", template_text(&template.subject, &template.body));
             }
-            StockCommands::Check { low_stock } => {
-                if low_stock {
-                    let low_stock_products = service.get_low_stock_products()?;
-
-                    if low_stock_products.is_empty() {
-                        println!("No low stock products found.");
-                        return Ok(());
-                    }
-
-                    println!("Low Stock Products:");
-                    for (i, prod_with_cat) in low_stock_products.iter().enumerate() {
-                        println!(
-                            "  {}. {} ({}) - {} - Current: {} {} / Min: {}",
-                            i + 1,
-                            prod_with_cat.product.name,
-                            prod_with_cat.product.sku,
-                            prod_with_cat.category.name,
-                            prod_with_cat.product.current_stock,
-                            prod_with_cat.product.unit,
-                            prod_with_cat.product.min_stock_level
-                        );
+            EmailTemplateCommands::Fields => {
+                let mut view = TableView::new(&["Field", "Filled With"]);
+                for (field, description) in MERGE_FIELDS {
+                    view.push(vec![format!("{{{{{}}}}}", field), description.to_string()]);
+                }
+                println!("{}", view.render());
+            }
+            EmailTemplateCommands::Edit { name, file } => {
+                require_manager()?;
+                let text = match file {
+                    Some(path) => std::fs::read_to_string(path)?,
+                    None => {
+                        let initial = match EmailTemplateService::get(&mut conn, &name) {
+                            Ok(template) => template_text(&template.subject, &template.body),
+                            Err(CLIERPError::NotFound(_)) => template_text("", ""),
+                            Err(e) => return Err(e),
+                        };
+                        let edited = crate::cli::prompt::edit_text(&initial)?;
+                        if edited == initial {
+                            println!("No changes to email template {}", name);
+                            return Ok(());
+                        }
+                        edited
                     }
+                };
+                let (subject, body) = parse_template_text(&text)?;
+                let template = EmailTemplateService::save(&mut conn, &name, &subject, &body, Some(user.id))?;
+                outln!("✅ Email template {} saved", template.name);
+            }
+            EmailTemplateCommands::Preview { name, customer, deal, invoice } => {
+                let source = MergeSource { customer_id: customer, deal_id: deal, invoice_id: invoice };
+                let email = EmailTemplateService::render(&mut conn, &name, source, DisplayTimezone::current().today())?;
+                println!("To: {}", email.to.as_deref().unwrap_or("(no email address)"));
+                println!("Subject: {}", email.subject);
+                println!();
+                println!("{}", email.body);
+            }
+            EmailTemplateCommands::Reset { name } => {
+                require_manager()?;
+                if EmailTemplateService::reset(&mut conn, &name)? {
+                    outln!("✅ Email template {} reset", name);
                 } else {
-                    println!("General stock check not yet implemented");
+                    println!("Email template {} has no edits", name);
                 }
             }
-            _ => {
-                println!("Stock command not yet implemented: {:?}", action);
-            }
         }
-
         Ok(())
     }
 
-    async fn handle_crm_command(
-        &mut self,
-        action: crate::core::command::CrmCommands,
-    ) -> CLIERPResult<()> {
-        // Check authentication for CRM commands
-        let _user = self.session_manager.get_current_user()?.ok_or_else(|| {
-            CLIERPError::Authentication("Login required for CRM commands".to_string())
-        })?;
+    async fn execute_link_command(&mut self, action: crate::core::command::LinkCommands) -> CLIERPResult<()> {
+        use crate::core::command::LinkCommands;
+        use crate::modules::system::links::{print_related, LinkService};
 
+        let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+            CLIERPError::Authentication("Login required for link commands".to_string())
+        })?;
         let mut conn = get_connection()?;
 
         match action {
-            crate::core::command::CrmCommands::Customer { action } => {
-                println!("Customer command: {:?}", action);
-                println!("Full CRM functionality available through interactive mode");
-                Ok(())
+            LinkCommands::Add { source, target, relation, note } => {
+                let link = LinkService::add(&mut conn, source, target, relation.as_deref(), note.as_deref(), Some(user.id))?;
+                outln!("✅ Linked {} to {} ({})", source, target, link.relation);
             }
-            crate::core::command::CrmCommands::Lead { action } => {
-                println!("Lead command: {:?}", action);
-                println!("Full CRM functionality available through interactive mode");
-                Ok(())
+            LinkCommands::Remove { source, target } => {
+                LinkService::remove(&mut conn, source, target)?;
+                outln!("✅ Unlinked {} from {}", source, target);
+            }
+            LinkCommands::List { record } => {
+                let label = LinkService::label(&mut conn, record)?
+                    .ok_or_else(|| CLIERPError::NotFound(format!("{} not found", record)))?;
+                let related = LinkService::related(&mut conn, record)?;
+                println!("{} {}", record, label);
+                if related.is_empty() {
+                    println!("\nNo linked records.");
+                }
+                print_related(&related);
             }
         }
+        Ok(())
     }
 
-    async fn execute_sales_command(
+    async fn execute_config_command(
         &mut self,
-        action: crate::core::command::SalesCommands,
+        action: crate::core::command::ConfigCommands,
     ) -> CLIERPResult<()> {
-        // Check authentication for sales commands
-        let _user = self.session_manager.get_current_user()?.ok_or_else(|| {
-            CLIERPError::Authentication("Login required for sales commands".to_string())
+        use crate::core::command::{ConfigCommands, KpiCommands, PrefCommands, RuleCommands, SodCommands, TemplateCommands};
+        use crate::database::models::UserRole;
+        use crate::modules::reporting::{print_kpis, KpiService, BASE_METRICS};
+        use crate::modules::system::{ExportTemplateService, PreferenceKey, SodService, ValidationRuleService};
+
+        let user = self.session_manager.get_current_user()?.ok_or_else(|| {
+            CLIERPError::Authentication("Login required for config commands".to_string())
         })?;
 
         let mut conn = get_connection()?;
 
-        // Convert from simple command enum to the extended command structure
-        use crate::cli::commands::crm_extended::{execute_crm_extended_command, CrmExtendedCommands, CrmExtendedAction};
+        match action {
+            ConfigCommands::Show { key, json } => {
+                use crate::core::settings::{display_setting, redacted_settings};
 
-        let extended_action = match action {
-            crate::core::command::SalesCommands::Dashboard => CrmExtendedAction::Dashboard,
-            crate::core::command::SalesCommands::Pipeline => CrmExtendedAction::Pipeline,
-            crate::core::command::SalesCommands::Performance => CrmExtendedAction::Performance,
-            _ => {
-                println!("Sales command: {:?}", action);
-                println!("Full sales functionality available through interactive mode");
-                return Ok(());
+                let prefix = key.as_deref().unwrap_or("");
+                let settings: Vec<_> = redacted_settings(&self.config)?
+                    .into_iter()
+                    .filter(|(name, _)| prefix.is_empty() || name == prefix || name.starts_with(&format!("{}.", prefix)))
+                    .collect();
+                if settings.is_empty() {
+                    return Err(CLIERPError::NotFound(format!("No settings under '{}'", prefix)));
+                }
+                if json {
+                    let object: serde_json::Map<String, serde_json::Value> = settings.into_iter().collect();
+                    println!("{}", serde_json::to_string_pretty(&object)?);
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["Setting", "Value"]);
+                for (name, value) in &settings {
+                    view.push(vec![name.clone(), display_setting(value)]);
+                }
+                println!("{}", view.render());
+                Ok(())
             }
-        };
-
-        let extended_cmd = CrmExtendedCommands {
-            action: extended_action,
-        };
+            ConfigCommands::Set { key, value } => {
+                use crate::core::config::SETTINGS_FILE;
+                use crate::core::settings::{display_setting, env_var_for, set_setting, RELOADABLE_SETTINGS};
 
-        match execute_crm_extended_command(&mut conn, extended_cmd) {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                eprintln!("Sales command failed: {}", e);
-                Err(CLIERPError::Internal(format!("Sales command error: {}", e)))
+                if !matches!(user.role, UserRole::Admin) {
+                    return Err(CLIERPError::Authorization("Admin role required".to_string()));
+                }
+                let saved = set_setting(&key, &value)?;
+                outln!("✅ {} = {} saved to {}", key, display_setting(&saved), SETTINGS_FILE);
+                let env_var = env_var_for(&key);
+                if std::env::var(&env_var).is_ok() {
+                    outln!("⚠️  {} is set and overrides this setting", env_var);
+                }
+                if RELOADABLE_SETTINGS.contains(&key.as_str()) {
+                    println!("A running server applies it on SIGHUP or POST /reload.");
+                } else {
+                    println!("Takes effect on the next run; restart a running server to apply it.");
+                }
+                Ok(())
             }
+            ConfigCommands::Rules { action } => match action {
+                RuleCommands::Set {
+                    entity,
+                    field,
+                    rule_type,
+                    value,
+                    message,
+                } => {
+                    if !matches!(user.role, UserRole::Admin) {
+                        return Err(CLIERPError::Authorization("Admin role required".to_string()));
+                    }
+                    let rule = ValidationRuleService::set_rule(
+                        &mut conn,
+                        &entity,
+                        &field,
+                        rule_type,
+                        value.as_deref(),
+                        message.as_deref(),
+                    )?;
+                    outln!("✅ Validation rule saved:");
+                    println!("  ID: {}", rule.id);
+                    println!("  Rule: {}.{} {} {}", rule.entity, rule.field, rule.rule_type, rule.value.as_deref().unwrap_or(""));
+                    if let Some(message) = &rule.message {
+                        println!("  Message: {}", message);
+                    }
+                    Ok(())
+                }
+                RuleCommands::List { entity } => {
+                    let rules = ValidationRuleService::list_rules(&mut conn, entity.as_deref())?;
+                    if rules.is_empty() {
+                        println!("No validation rules configured.");
+                        return Ok(());
+                    }
+                    println!("Validation Rules:");
+                    println!("{:<5} {:<10} {:<18} {:<12} {:<25} {:<8}", "ID", "Entity", "Field", "Type", "Value", "Active");
+                    println!("{}", "-".repeat(82));
+                    for rule in rules {
+                        println!(
+                            "{:<5} {:<10} {:<18} {:<12} {:<25} {:<8}",
+                            rule.id,
+                            rule.entity,
+                            rule.field,
+                            rule.rule_type,
+                            rule.value.as_deref().unwrap_or("-"),
+                            if rule.is_active { "yes" } else { "no" }
+                        );
+                    }
+                    Ok(())
+                }
+                RuleCommands::Remove { id } => {
+                    if !matches!(user.role, UserRole::Admin) {
+                        return Err(CLIERPError::Authorization("Admin role required".to_string()));
+                    }
+                    ValidationRuleService::remove_rule(&mut conn, id)?;
+                    outln!("✅ Validation rule {} removed", id);
+                    Ok(())
+                }
+            },
+            ConfigCommands::Kpi { action } => match action {
+                KpiCommands::Set {
+                    name,
+                    formula,
+                    warning,
+                    critical,
+                    lower_is_better,
+                    description,
+                } => {
+                    if !matches!(user.role, UserRole::Admin) {
+                        return Err(CLIERPError::Authorization("Admin role required".to_string()));
+                    }
+                    let kpi = KpiService::set_kpi(
+                        &mut conn,
+                        &name,
+                        &formula,
+                        warning,
+                        critical,
+                        !lower_is_better,
+                        description.as_deref(),
+                        Some(user.id),
+                    )?;
+                    outln!("✅ KPI saved:");
+                    println!("  ID: {}", kpi.id);
+                    println!("  {} = {}", kpi.name, kpi.formula);
+                    println!(
+                        "  Warning {} {}, critical {} {}",
+                        if kpi.higher_is_better { "at or below" } else { "at or above" },
+                        kpi.warning_threshold,
+                        if kpi.higher_is_better { "at or below" } else { "at or above" },
+                        kpi.critical_threshold
+                    );
+                    Ok(())
+                }
+                KpiCommands::List => {
+                    let evaluations = KpiService::evaluate_all(&mut conn)?;
+                    if evaluations.is_empty() {
+                        println!("No KPIs defined. Use 'clierp config kpi set' to add one.");
+                        return Ok(());
+                    }
+                    println!("KPIs:");
+                    print_kpis(&evaluations);
+                    Ok(())
+                }
+                KpiCommands::Remove { name } => {
+                    if !matches!(user.role, UserRole::Admin) {
+                        return Err(CLIERPError::Authorization("Admin role required".to_string()));
+                    }
+                    KpiService::remove_kpi(&mut conn, &name)?;
+                    outln!("✅ KPI {} removed", name);
+                    Ok(())
+                }
+                KpiCommands::Metrics => {
+                    let values = KpiService::metric_values(&mut conn)?;
+                    println!("Base metrics:");
+                    for (name, description) in BASE_METRICS {
+                        println!(
+                            "  {:<22} {:>16.2}  {}",
+                            name,
+                            values.get(*name).copied().unwrap_or_default(),
+                            description
+                        );
+                    }
+                    Ok(())
+                }
+                KpiCommands::Check => {
+                    let evaluations = KpiService::check_all(&mut conn)?;
+                    if evaluations.is_empty() {
+                        println!("No KPIs defined.");
+                        return Ok(());
+                    }
+                    print_kpis(&evaluations);
+
+                    let crossed: Vec<_> = evaluations.iter().filter(|e| e.crossed()).collect();
+                    for evaluation in &crossed {
+                        eprintln!(
+                            "{}",
+                            for_output(&format!(
+                                "⚠️  {} is now {} ({:.2})",
+                                evaluation.kpi.name,
+                                evaluation.status.map(|s| s.to_string()).unwrap_or_default(),
+                                evaluation.value.unwrap_or_default()
+                            ))
+                        );
+                    }
+                    println!();
+                    if crossed.is_empty() {
+                        println!("No thresholds crossed since the last check.");
+                    } else {
+                        outln!("⚠️  {} KPI(s) crossed a threshold since the last check", crossed.len());
+                    }
+                    Ok(())
+                }
+            },
+            ConfigCommands::Sod { action } => match action {
+                SodCommands::List => {
+                    println!("Segregation of Duties Rules:");
+                    println!("{:<34} {:<10} Description", "Rule", "Enforced");
+                    println!("{}", "-".repeat(90));
+                    for (rule, enforcement) in SodService::rules(&mut conn)? {
+                        println!("{:<34} {:<10} {}", rule, enforcement, rule.description());
+                    }
+                    Ok(())
+                }
+                SodCommands::Set { rule, enforcement } => {
+                    if !matches!(user.role, UserRole::Admin) {
+                        return Err(CLIERPError::Authorization("Admin role required".to_string()));
+                    }
+                    SodService::set_enforcement(&mut conn, rule, enforcement, Some(user.id))?;
+                    outln!("✅ Segregation of duties rule {} set to {}", rule, enforcement);
+                    Ok(())
+                }
+                SodCommands::Overrides { limit } => {
+                    use crate::utils::formatting::format_datetime;
+
+                    let overrides = SodService::overrides(&mut conn, limit)?;
+                    if overrides.is_empty() {
+                        println!("No segregation of duties overrides logged.");
+                        return Ok(());
+                    }
+                    println!("{:<6} {:<19} {:<34} {:<8} {:<6} Reason", "ID", "When", "Rule", "Record", "User");
+                    println!("{}", "-".repeat(100));
+                    for entry in overrides {
+                        println!(
+                            "{:<6} {:<19} {:<34} {:<8} {:<6} {}",
+                            entry.id,
+                            format_datetime(&entry.created_at),
+                            entry.rule_name,
+                            entry.record_id,
+                            entry.user_id,
+                            entry.reason
+                        );
+                    }
+                    Ok(())
+                }
+            },
+            ConfigCommands::Prefs { action } => match action {
+                PrefCommands::Show => {
+                    let preferences = PreferenceService::load(&mut conn, user.id)?;
+                    let configured = |key: PreferenceKey| match key {
+                        PreferenceKey::PerPage => "per command".to_string(),
+                        PreferenceKey::OutputFormat => "text".to_string(),
+                        PreferenceKey::Timezone => self.config.timezone.clone(),
+                        PreferenceKey::Locale => self.config.lang.clone(),
+                    };
+                    println!("Preferences for {}:", user.username);
+                    println!("{:<15} {:<20} Source", "Name", "Value");
+                    println!("{}", "-".repeat(50));
+                    for key in PreferenceKey::ALL {
+                        match preferences.get(key) {
+                            Some(value) => println!("{:<15} {:<20} preference", key, value),
+                            None => println!("{:<15} {:<20} default", key, configured(key)),
+                        }
+                    }
+                    Ok(())
+                }
+                PrefCommands::Set { key, value } => {
+                    let value = PreferenceService::set(&mut conn, user.id, key, &value)?;
+                    outln!("✅ Preference {} set to {}; it applies from your next command", key, value);
+                    Ok(())
+                }
+                PrefCommands::Unset { key } => {
+                    if PreferenceService::unset(&mut conn, user.id, key)? {
+                        outln!("✅ Preference {} cleared", key);
+                    } else {
+                        println!("Preference {} was not set", key);
+                    }
+                    Ok(())
+                }
+            },
+            ConfigCommands::Template { action } => match action {
+                TemplateCommands::List => {
+                    let templates = ExportTemplateService::list(&mut conn)?;
+                    if templates.is_empty() {
+                        println!("No export templates defined.");
+                        return Ok(());
+                    }
+                    println!("{:<20} {:<12} {:<8} Dates", "Name", "Export", "Columns");
+                    println!("{}", "-".repeat(60));
+                    for template in templates {
+                        let mapping = ExportTemplateService::parse_mapping(&template)?;
+                        let columns = if mapping.columns.is_empty() {
+                            "all".to_string()
+                        } else {
+                            mapping.columns.len().to_string()
+                        };
+                        println!(
+                            "{:<20} {:<12} {:<8} {}",
+                            template.name,
+                            template.entity,
+                            columns,
+                            mapping.date_format.as_deref().unwrap_or("unchanged")
+                        );
+                    }
+                    Ok(())
+                }
+                TemplateCommands::Show { name } => {
+                    let template = ExportTemplateService::get(&mut conn, &name)?;
+                    let mapping = ExportTemplateService::parse_mapping(&template)?;
+                    println!("Export template {} ({} export)", template.name, template.entity);
+                    println!("Dates: {}", mapping.date_format.as_deref().unwrap_or("unchanged"));
+                    if mapping.columns.is_empty() {
+                        println!("Columns: all, under their own names");
+                    }
+                    for column in &mapping.columns {
+                        println!("  {:<20} -> {}", column.source, column.header);
+                    }
+                    Ok(())
+                }
+                TemplateCommands::Set { name, entity, columns, date_format } => {
+                    if !matches!(user.role, UserRole::Admin | UserRole::Manager) {
+                        return Err(CLIERPError::Authorization("Manager role required".to_string()));
+                    }
+                    let template = ExportTemplateService::save(
+                        &mut conn,
+                        &name,
+                        entity,
+                        columns,
+                        date_format.as_deref(),
+                        Some(user.id),
+                    )?;
+                    outln!("✅ Export template {} saved for {} exports", template.name, template.entity);
+                    Ok(())
+                }
+                TemplateCommands::Remove { name } => {
+                    if !matches!(user.role, UserRole::Admin | UserRole::Manager) {
+                        return Err(CLIERPError::Authorization("Manager role required".to_string()));
+                    }
+                    if ExportTemplateService::remove(&mut conn, &name)? {
+                        outln!("✅ Export template {} removed", name);
+                    } else {
+                        println!("No export template named {}", name);
+                    }
+                    Ok(())
+                }
+            },
         }
     }
 
@@ -623,12 +5668,15 @@ impl CLIApp {
         action: crate::core::command::PurchaseCommands,
     ) -> CLIERPResult<()> {
         use crate::core::command::{PurchaseCommands, SupplierCommands, PurchaseOrderCommands};
-        use crate::modules::inventory::{SupplierService, PurchaseOrderService, PurchaseOrderItem, ReceiveItemData};
+        use crate::modules::inventory::{SupplierService, PurchaseOrderService, PurchaseOrderItem, ReceiveItemData, SupplierPortalService};
+        use crate::modules::system::links::{print_related, LinkService, RecordRef};
+        use crate::modules::system::portal::{PortalScope, PortalService};
+        use crate::utils::formatting::format_datetime;
         use crate::utils::filters::FilterOptions;
         use crate::utils::pagination::PaginationParams;
 
         // Check authentication for purchase commands
-        let _user = self.session_manager.get_current_user()?.ok_or_else(|| {
+        let user = self.session_manager.get_current_user()?.ok_or_else(|| {
             CLIERPError::Authentication("Login required for purchase commands".to_string())
         })?;
 
@@ -657,7 +5705,7 @@ impl CLIApp {
                             payment_terms.as_deref(),
                         )?;
 
-                        println!("✅ Supplier created successfully!");
+                        outln!("{}", t("purchase.supplier_created"));
                         println!("ID: {}", supplier.id);
                         println!("Code: {}", supplier.supplier_code);
                         println!("Name: {}", supplier.name);
@@ -667,22 +5715,25 @@ impl CLIApp {
                         status,
                         page,
                         per_page,
+                        filter,
                     } => {
                         let filters = FilterOptions {
                             search,
                             status,
+                            where_clause: filter,
                             ..Default::default()
                         };
 
+                        let per_page = per_page.unwrap_or_else(|| PaginationParams::default_per_page(20) as u32);
                         let pagination = PaginationParams::new(page as usize, per_page as i64);
                         let result = SupplierService::list_suppliers(&mut conn, &filters, &pagination)?;
 
                         if result.data.is_empty() {
-                            println!("No suppliers found.");
+                            outln!("{}", t("purchase.no_suppliers"));
                             return Ok(());
                         }
 
-                        println!("Suppliers:");
+                        outln!("{}", t("purchase.suppliers_title"));
                         for (i, supplier) in result.data.iter().enumerate() {
                             println!(
                                 "  {}. {} ({}) - {} - {}",
@@ -693,7 +5744,17 @@ impl CLIApp {
                                 supplier.status
                             );
                         }
-                        println!("Page {} of {} ({} total)", result.pagination.current_page, result.pagination.total_pages, result.pagination.total_count);
+                        println!(
+                            "{}",
+                            t_with(
+                                "common.page",
+                                &[
+                                    ("page", &result.pagination.current_page),
+                                    ("total_pages", &result.pagination.total_pages),
+                                    ("total_count", &result.pagination.total_count),
+                                ],
+                            )
+                        );
                     }
                     SupplierCommands::Show { supplier_id } => {
                         let supplier = SupplierService::get_supplier_by_id(&mut conn, supplier_id)?
@@ -701,7 +5762,7 @@ impl CLIApp {
 
                         let stats = SupplierService::get_supplier_statistics(&mut conn, supplier_id)?;
 
-                        println!("Supplier Details:");
+                        outln!("{}", t("purchase.supplier_details"));
                         println!("ID: {}", supplier.id);
                         println!("Code: {}", supplier.supplier_code);
                         println!("Name: {}", supplier.name);
@@ -710,12 +5771,18 @@ impl CLIApp {
                         println!("Phone: {}", supplier.phone.unwrap_or_else(|| "-".to_string()));
                         println!("Address: {}", supplier.address.unwrap_or_else(|| "-".to_string()));
                         println!("Payment Terms: {}", supplier.payment_terms.unwrap_or_else(|| "-".to_string()));
+                        println!("Lead Time: {}", supplier.lead_time_days.map(|d| format!("{} days", d)).unwrap_or_else(|| "-".to_string()));
                         println!("Status: {}", supplier.status);
                         println!();
                         println!("Statistics:");
                         println!("Total Orders: {}", stats.total_orders);
                         println!("Pending Orders: {}", stats.pending_orders);
-                        println!("Total Amount: ₩{}", stats.total_amount);
+                        println!("Total Amount: {}", format_currency(stats.total_amount));
+
+                        print_related(&LinkService::related(
+                            &mut conn,
+                            RecordRef::new(crate::database::RecordType::Supplier, supplier.id),
+                        )?);
                     }
                     SupplierCommands::Update {
                         supplier_id,
@@ -726,6 +5793,7 @@ impl CLIApp {
                         address,
                         payment_terms,
                         status,
+                        lead_time_days,
                     } => {
                         let status_enum = status.map(|s| match s.as_str() {
                             "active" => crate::database::SupplierStatus::Active,
@@ -738,18 +5806,26 @@ impl CLIApp {
                             &mut conn,
                             supplier_id,
                             name.as_deref(),
-                            contact.as_deref(),
-                            email.as_deref(),
-                            phone.as_deref(),
-                            address.as_deref(),
-                            Some(payment_terms.as_deref()),
+                            // Omitted options keep their value
+                            contact.as_deref().map(Some),
+                            email.as_deref().map(Some),
+                            phone.as_deref().map(Some),
+                            address.as_deref().map(Some),
+                            payment_terms.as_deref().map(Some),
                             status_enum,
                         )?;
+                        let supplier = match lead_time_days {
+                            Some(days) => SupplierService::set_lead_time(&mut conn, supplier.id, Some(days))?,
+                            None => supplier,
+                        };
 
-                        println!("✅ Supplier updated successfully!");
+                        outln!("{}", t("purchase.supplier_updated"));
                         println!("ID: {}", supplier.id);
                         println!("Name: {}", supplier.name);
                         println!("Status: {}", supplier.status);
+                        if let Some(days) = supplier.lead_time_days {
+                            println!("Lead Time: {} days", days);
+                        }
                     }
                 }
             }
@@ -760,9 +5836,9 @@ impl CLIApp {
                         expected_date,
                         notes,
                         items,
+                        dispatch_country,
+                        incoterm,
                     } => {
-                        let expected_date = expected_date.map(|s| s.parse().unwrap());
-
                         // Parse items string
                         let items: Result<Vec<PurchaseOrderItem>, _> = items
                             .split(',')
@@ -782,9 +5858,9 @@ impl CLIApp {
                             .collect();
 
                         let items = items?;
-                        let current_user_id = Some(1); // TODO: Get from session
+                        let current_user_id = Some(user.id);
 
-                        let po_with_details = PurchaseOrderService::create_purchase_order(
+                        let mut po_with_details = PurchaseOrderService::create_purchase_order(
                             &mut conn,
                             supplier_id,
                             expected_date,
@@ -793,11 +5869,23 @@ impl CLIApp {
                             current_user_id,
                         )?;
 
-                        println!("✅ Purchase order created successfully!");
+                        if dispatch_country.is_some() || incoterm.is_some() {
+                            po_with_details.purchase_order = PurchaseOrderService::set_customs_details(
+                                &mut conn,
+                                po_with_details.purchase_order.id,
+                                dispatch_country.as_deref(),
+                                incoterm.as_deref(),
+                            )?;
+                        }
+
+                        outln!("{}", t("purchase.po_created"));
                         println!("PO Number: {}", po_with_details.purchase_order.po_number);
                         println!("Supplier: {}", po_with_details.supplier.name);
-                        println!("Total Amount: ₩{}", po_with_details.purchase_order.total_amount);
+                        println!("Total Amount: {}", format_currency(po_with_details.purchase_order.total_amount));
                         println!("Items: {} products", po_with_details.items.len());
+                        if let Some(country) = &po_with_details.purchase_order.dispatch_country {
+                            println!("Dispatch Country: {}", country);
+                        }
                     }
                     PurchaseOrderCommands::List {
                         search,
@@ -806,47 +5894,66 @@ impl CLIApp {
                         date_to,
                         page,
                         per_page,
+                        filter,
                     } => {
                         let filters = FilterOptions {
                             search,
                             status,
-                            date_from: date_from.map(|s| s.parse().unwrap()),
-                            date_to: date_to.map(|s| s.parse().unwrap()),
+                            date_from,
+                            date_to,
+                            where_clause: filter,
                             ..Default::default()
                         };
 
+                        let per_page = per_page.unwrap_or_else(|| PaginationParams::default_per_page(20) as u32);
                         let pagination = PaginationParams::new(page as usize, per_page as i64);
                         let result = PurchaseOrderService::list_purchase_orders(&mut conn, &filters, &pagination)?;
 
                         if result.data.is_empty() {
-                            println!("No purchase orders found.");
+                            outln!("{}", t("purchase.no_orders"));
                             return Ok(());
                         }
 
-                        println!("Purchase Orders:");
+                        outln!("{}", t("purchase.orders_title"));
                         for (i, po) in result.data.iter().enumerate() {
                             println!(
-                                "  {}. {} - {} - {} - {} items - ₩{}",
+                                "  {}. {} - {} - {} - {} items - {}",
                                 (page - 1) * per_page + i as u32 + 1,
                                 po.po_number,
                                 po.supplier_name,
                                 po.status,
                                 po.items_count,
-                                po.total_amount
+                                format_currency(po.total_amount)
                             );
                         }
-                        println!("Page {} of {} ({} total)", result.pagination.current_page, result.pagination.total_pages, result.pagination.total_count);
+                        println!(
+                            "{}",
+                            t_with(
+                                "common.page",
+                                &[
+                                    ("page", &result.pagination.current_page),
+                                    ("total_pages", &result.pagination.total_pages),
+                                    ("total_count", &result.pagination.total_count),
+                                ],
+                            )
+                        );
                     }
                     PurchaseOrderCommands::Show { po_id } => {
                         let po_details = PurchaseOrderService::get_purchase_order_with_details(&mut conn, po_id)?;
 
-                        println!("Purchase Order Details:");
+                        outln!("{}", t("purchase.order_details"));
                         println!("PO Number: {}", po_details.purchase_order.po_number);
                         println!("Supplier: {} ({})", po_details.supplier.name, po_details.supplier.supplier_code);
                         println!("Order Date: {}", po_details.purchase_order.order_date);
                         println!("Expected Date: {}", po_details.purchase_order.expected_date.map(|d| d.to_string()).unwrap_or_else(|| "-".to_string()));
                         println!("Status: {}", po_details.purchase_order.status);
-                        println!("Total Amount: ₩{}", po_details.purchase_order.total_amount);
+                        println!("Total Amount: {}", format_currency(po_details.purchase_order.total_amount));
+                        if let Some(country) = &po_details.purchase_order.dispatch_country {
+                            println!("Dispatch Country: {}", country);
+                        }
+                        if let Some(incoterm) = &po_details.purchase_order.incoterm {
+                            println!("Incoterm: {}", incoterm);
+                        }
                         if let Some(notes) = &po_details.purchase_order.notes {
                             println!("Notes: {}", notes);
                         }
@@ -855,24 +5962,119 @@ impl CLIApp {
                         println!("Items:");
                         for (i, item) in po_details.items.iter().enumerate() {
                             println!(
-                                "  {}. {} ({}) - Qty: {} - Cost: ₩{} each - Total: ₩{} - Received: {} - Status: {}",
+                                "  {}. {} ({}) - Qty: {} - Cost: {} each - Total: {} - Received: {} - Status: {}",
                                 i + 1,
                                 item.product_name,
                                 item.product_sku,
                                 item.purchase_item.quantity,
-                                item.purchase_item.unit_cost,
-                                item.purchase_item.total_cost,
+                                format_currency(item.purchase_item.unit_cost),
+                                format_currency(item.purchase_item.total_cost),
                                 item.purchase_item.received_quantity,
                                 item.purchase_item.status
                             );
                         }
+
+                        let responses = SupplierPortalService::responses(&mut conn, po_id)?;
+                        if !responses.is_empty() {
+                            println!();
+                            println!("Supplier Responses:");
+                            for response in &responses {
+                                let mut details = Vec::new();
+                                if let Some(date) = response.proposed_date {
+                                    details.push(format!("delivery {}", date));
+                                }
+                                if let Some(carrier) = &response.carrier {
+                                    details.push(carrier.clone());
+                                }
+                                if let Some(tracking) = &response.tracking_number {
+                                    details.push(format!("tracking {}", tracking));
+                                }
+                                if let Some(message) = &response.message {
+                                    details.push(format!("\"{}\"", message));
+                                }
+                                println!(
+                                    "  {} {:<14} {}",
+                                    format_datetime(&response.created_at),
+                                    response.kind,
+                                    details.join(", ")
+                                );
+                            }
+                        }
+
+                        let invoices = PurchaseOrderService::invoices(&mut conn, po_id)?;
+                        if !invoices.is_empty() {
+                            println!();
+                            println!("Supplier Invoices:");
+                            for invoice in &invoices {
+                                println!(
+                                    "  {} {:<16} {} - {}",
+                                    invoice.invoice_date,
+                                    invoice.invoice_number,
+                                    format_currency(invoice.total_amount),
+                                    invoice.status
+                                );
+                            }
+                        }
+
+                        print_related(&LinkService::related(
+                            &mut conn,
+                            RecordRef::new(crate::database::RecordType::PurchaseOrder, po_details.purchase_order.id),
+                        )?);
                     }
-                    PurchaseOrderCommands::Approve { po_id } => {
-                        let current_user_id = 1; // TODO: Get from session
+                    PurchaseOrderCommands::Share { po_id, days } => {
+                        let days = days.unwrap_or(self.config.server.link_ttl_days);
+                        let (link, token) = SupplierPortalService::share(
+                            &mut conn,
+                            &self.config.auth.jwt_secret,
+                            po_id,
+                            days,
+                            Some(user.id),
+                        )?;
 
-                        let purchase_order = PurchaseOrderService::approve_purchase_order(&mut conn, po_id, current_user_id)?;
+                        outln!("🔗 Supplier link created (ID {}), valid until {}", link.id, format_datetime(&link.expires_at));
+                        println!("{}/supplier/po/{}", self.config.server.base_url(), token);
+                        println!("The supplier can open it while 'clierp system serve' is running.");
+                    }
+                    PurchaseOrderCommands::Links { po_id } => {
+                        let links = PortalService::list(&mut conn, PortalScope::SupplierPo, po_id)?;
+                        if links.is_empty() {
+                            println!("No supplier links for this purchase order.");
+                            return Ok(());
+                        }
+                        let now = chrono::Utc::now().naive_utc();
+                        println!("{:<6} {:<20} {:<20} {:<20} State", "ID", "Created", "Expires", "Last Used");
+                        println!("{}", "-".repeat(80));
+                        for link in links {
+                            let state = if link.revoked_at.is_some() {
+                                "revoked"
+                            } else if link.expires_at <= now {
+                                "expired"
+                            } else {
+                                "active"
+                            };
+                            println!(
+                                "{:<6} {:<20} {:<20} {:<20} {}",
+                                link.id,
+                                format_datetime(&link.created_at),
+                                format_datetime(&link.expires_at),
+                                link.last_used_at.map(|t| format_datetime(&t)).unwrap_or_else(|| "-".to_string()),
+                                state
+                            );
+                        }
+                    }
+                    PurchaseOrderCommands::Revoke { link_id } => {
+                        let link = PortalService::revoke(&mut conn, PortalScope::SupplierPo, link_id)?;
+                        outln!("✅ Link {} revoked; it no longer opens purchase order {}", link.id, link.record_id);
+                    }
+                    PurchaseOrderCommands::Approve { po_id, override_sod } => {
+                        let purchase_order = PurchaseOrderService::approve_purchase_order(
+                            &mut conn,
+                            po_id,
+                            user.id,
+                            override_sod.as_deref(),
+                        )?;
 
-                        println!("✅ Purchase order approved successfully!");
+                        outln!("{}", t("purchase.po_approved"));
                         println!("PO Number: {}", purchase_order.po_number);
                         println!("Status: {}", purchase_order.status);
                     }
@@ -905,10 +6107,24 @@ impl CLIApp {
                             current_user_id,
                         )?;
 
-                        println!("✅ Purchase order items received successfully!");
+                        outln!("{}", t("purchase.items_received"));
                         println!("PO Number: {}", purchase_order.po_number);
                         println!("Status: {}", purchase_order.status);
                     }
+                    PurchaseOrderCommands::Invoice { po_id, number, amount, date, due } => {
+                        let date = date.unwrap_or_else(|| crate::utils::timezone::DisplayTimezone::current().today());
+                        let invoice = PurchaseOrderService::record_invoice(
+                            &mut conn,
+                            po_id,
+                            &number,
+                            date,
+                            due,
+                            amount,
+                            Some(user.id),
+                        )?;
+
+                        outln!("✅ Supplier invoice {} recorded (ID {}) for {}", invoice.invoice_number, invoice.id, format_currency(invoice.total_amount));
+                    }
                 }
             }
         }
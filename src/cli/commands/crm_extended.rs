@@ -1,15 +1,27 @@
 use clap::{Args, Subcommand};
 use chrono::{NaiveDate, NaiveDateTime};
-use crate::core::result::CLIERPResult;
+use crate::core::{auth::AuthenticatedUser, config::CLIERPConfig, result::CLIERPResult};
 use crate::database::{
     DatabaseConnection, CustomerType, CustomerStatus, LeadStatus, LeadPriority,
-    DealStage, CampaignType, CampaignStatus, ActivityType
+    DealStage, CampaignType, CampaignStatus, CampaignResponse, ActivityType, RecordVisibility, RecordType,
+    Campaign, LeadWithCustomer
 };
 use crate::modules::crm::{
-    CustomerService, LeadService, DealService, CampaignService, ActivityService
+    AttributionService, CreditScoreService, CustomerPortalService, CustomerService, LeadService, DealService, CampaignService, ActivityService, ActivityWithDetails, RecordScope
 };
+use crate::modules::system::audit::{changed_fields, print_changes, print_history, AuditService};
+use crate::modules::system::links::{print_related, LinkService, RecordRef};
+use crate::modules::system::portal::{PortalScope, PortalService};
+use crate::cli::prompt::{confirm_action, RiskLevel};
+use crate::cli::tui::pager;
 use crate::utils::pagination::PaginationParams;
-use crate::utils::filters::FilterOptions;
+use crate::utils::table::{TableArgs, TableView};
+use crate::utils::filter_expr::{parse_filter_expr_arg, FilterExpr};
+use crate::utils::filters::{parse_field_assignment_arg, FieldAssignment, FilterOptions};
+use crate::utils::dates::parse_date_arg;
+use crate::utils::timezone::DisplayTimezone;
+use crate::utils::formatting::{format_currency, format_datetime};
+use crate::outln;
 
 #[derive(Debug, Args)]
 pub struct CrmExtendedCommands {
@@ -69,8 +81,8 @@ pub enum CustomerAction {
     List {
         #[arg(long, default_value = "1")]
         page: i64,
-        #[arg(long, default_value = "20")]
-        per_page: i64,
+        #[arg(long)]
+        per_page: Option<i64>,
         #[arg(long)]
         search: Option<String>,
         #[arg(long)]
@@ -81,6 +93,11 @@ pub enum CustomerAction {
         sort_by: Option<String>,
         #[arg(long)]
         sort_desc: bool,
+        /// Only rows matching this expression, e.g. "status = active and id > 100"
+        #[arg(long = "where", value_parser = parse_filter_expr_arg)]
+        filter: Option<FilterExpr>,
+        #[command(flatten)]
+        table: TableArgs,
     },
     Show {
         #[arg(long)]
@@ -112,10 +129,40 @@ pub enum CustomerAction {
     Delete {
         id: i32,
     },
+    /// Update every customer matching the filters
+    BulkUpdate {
+        /// Filter as field=value (id, status, type, credit_limit); repeatable
+        #[arg(long = "filter", value_parser = parse_field_assignment_arg)]
+        filters: Vec<FieldAssignment>,
+        /// Change as field=value (status, type, credit_limit, notes); repeatable
+        #[arg(long = "set", value_parser = parse_field_assignment_arg, required = true)]
+        updates: Vec<FieldAssignment>,
+    },
     Search {
         query: String,
     },
     Stats,
+    /// Create a portal link where the customer can view invoices, statement and payments
+    Share {
+        id: i32,
+        /// Days the link stays valid; defaults to server.link_ttl_days
+        #[arg(long)]
+        days: Option<u32>,
+    },
+    /// Revoke a customer portal link before it expires
+    Unshare {
+        /// Link ID, as printed by `share`
+        link_id: i32,
+    },
+    /// Compare credit limits with those suggested by the customers' credit scores
+    CreditReview {
+        /// Only this customer
+        #[arg(long)]
+        id: Option<i32>,
+        /// Set the suggested limits after confirmation
+        #[arg(long)]
+        apply: bool,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -129,7 +176,7 @@ pub enum LeadAction {
         lead_source: String,
         #[arg(long)]
         estimated_value: i32,
-        #[arg(long)]
+        #[arg(long, value_parser = parse_date_arg)]
         expected_close_date: Option<NaiveDate>,
         #[arg(long, value_enum)]
         priority: LeadPriority,
@@ -143,8 +190,8 @@ pub enum LeadAction {
     List {
         #[arg(long, default_value = "1")]
         page: i64,
-        #[arg(long, default_value = "20")]
-        per_page: i64,
+        #[arg(long)]
+        per_page: Option<i64>,
         #[arg(long)]
         search: Option<String>,
         #[arg(long)]
@@ -153,17 +200,34 @@ pub enum LeadAction {
         priority: Option<String>,
         #[arg(long)]
         assigned_to: Option<i32>,
-        #[arg(long)]
+        #[arg(long, value_parser = parse_date_arg)]
         date_from: Option<NaiveDate>,
-        #[arg(long)]
+        #[arg(long, value_parser = parse_date_arg)]
         date_to: Option<NaiveDate>,
         #[arg(long)]
         sort_by: Option<String>,
         #[arg(long)]
         sort_desc: bool,
+        /// Include leads outside your own scope (managers and above)
+        #[arg(long)]
+        all: bool,
+        /// Only rows matching this expression, e.g. "status = active and id > 100"
+        #[arg(long = "where", value_parser = parse_filter_expr_arg)]
+        filter: Option<FilterExpr>,
+        #[command(flatten)]
+        table: TableArgs,
     },
     Show {
         id: i32,
+        /// Allow showing leads outside your own scope (managers and above)
+        #[arg(long)]
+        all: bool,
+    },
+    /// Set who besides the owner can see a lead
+    Visibility {
+        id: i32,
+        #[arg(value_enum)]
+        visibility: RecordVisibility,
     },
     UpdateStatus {
         id: i32,
@@ -182,7 +246,7 @@ pub enum LeadAction {
         lead_source: Option<String>,
         #[arg(long)]
         estimated_value: Option<i32>,
-        #[arg(long)]
+        #[arg(long, value_parser = parse_date_arg)]
         expected_close_date: Option<NaiveDate>,
         #[arg(long)]
         priority: Option<LeadPriority>,
@@ -216,7 +280,7 @@ pub enum DealAction {
         title: String,
         #[arg(long)]
         deal_value: i32,
-        #[arg(long)]
+        #[arg(long, value_parser = parse_date_arg)]
         expected_close_date: Option<NaiveDate>,
         #[arg(long)]
         assigned_to: Option<i32>,
@@ -224,29 +288,57 @@ pub enum DealAction {
         description: Option<String>,
         #[arg(long)]
         notes: Option<String>,
+        /// Destination country for cross-border sales (ISO 3166-1 alpha-2)
+        #[arg(long)]
+        destination_country: Option<String>,
     },
     List {
         #[arg(long, default_value = "1")]
         page: i64,
-        #[arg(long, default_value = "20")]
-        per_page: i64,
+        #[arg(long)]
+        per_page: Option<i64>,
         #[arg(long)]
         search: Option<String>,
         #[arg(long)]
         stage: Option<String>,
         #[arg(long)]
         assigned_to: Option<i32>,
-        #[arg(long)]
+        #[arg(long, value_parser = parse_date_arg)]
         date_from: Option<NaiveDate>,
-        #[arg(long)]
+        #[arg(long, value_parser = parse_date_arg)]
         date_to: Option<NaiveDate>,
         #[arg(long)]
         sort_by: Option<String>,
         #[arg(long)]
         sort_desc: bool,
+        /// Include deals outside your own scope (managers and above)
+        #[arg(long)]
+        all: bool,
+        /// Only rows matching this expression, e.g. "status = active and id > 100"
+        #[arg(long = "where", value_parser = parse_filter_expr_arg)]
+        filter: Option<FilterExpr>,
+        #[command(flatten)]
+        table: TableArgs,
     },
     Show {
         id: i32,
+        /// Allow showing deals outside your own scope (managers and above)
+        #[arg(long)]
+        all: bool,
+        /// Also show the deal's change history
+        #[arg(long)]
+        history: bool,
+    },
+    /// Set who besides the owner can see a deal
+    Visibility {
+        id: i32,
+        #[arg(value_enum)]
+        visibility: RecordVisibility,
+    },
+    /// Apply a discount percentage to a deal
+    Discount {
+        id: i32,
+        percent: i32,
     },
     UpdateStage {
         id: i32,
@@ -261,7 +353,7 @@ pub enum DealAction {
         title: Option<String>,
         #[arg(long)]
         deal_value: Option<i32>,
-        #[arg(long)]
+        #[arg(long, value_parser = parse_date_arg)]
         expected_close_date: Option<NaiveDate>,
         #[arg(long)]
         assigned_to: Option<i32>,
@@ -273,6 +365,18 @@ pub enum DealAction {
     Delete {
         id: i32,
     },
+    /// Update every deal in your scope matching the filters
+    BulkUpdate {
+        /// Filter as field=value (id, stage, assigned_to, lead_id, visibility); repeatable
+        #[arg(long = "filter", value_parser = parse_field_assignment_arg)]
+        filters: Vec<FieldAssignment>,
+        /// Change as field=value (assigned_to, close_date, stage, visibility); repeatable
+        #[arg(long = "set", value_parser = parse_field_assignment_arg, required = true)]
+        updates: Vec<FieldAssignment>,
+        /// Include deals outside your own scope (managers and above)
+        #[arg(long)]
+        all: bool,
+    },
     ByStage {
         #[arg(value_enum)]
         stage: DealStage,
@@ -289,9 +393,9 @@ pub enum CampaignAction {
         campaign_type: CampaignType,
         #[arg(long)]
         description: Option<String>,
-        #[arg(long)]
+        #[arg(long, value_parser = parse_date_arg)]
         start_date: NaiveDate,
-        #[arg(long)]
+        #[arg(long, value_parser = parse_date_arg)]
         end_date: Option<NaiveDate>,
         #[arg(long)]
         budget: Option<i32>,
@@ -303,22 +407,27 @@ pub enum CampaignAction {
     List {
         #[arg(long, default_value = "1")]
         page: i64,
-        #[arg(long, default_value = "20")]
-        per_page: i64,
+        #[arg(long)]
+        per_page: Option<i64>,
         #[arg(long)]
         search: Option<String>,
         #[arg(long)]
         status: Option<String>,
         #[arg(long)]
         campaign_type: Option<String>,
-        #[arg(long)]
+        #[arg(long, value_parser = parse_date_arg)]
         date_from: Option<NaiveDate>,
-        #[arg(long)]
+        #[arg(long, value_parser = parse_date_arg)]
         date_to: Option<NaiveDate>,
         #[arg(long)]
         sort_by: Option<String>,
         #[arg(long)]
         sort_desc: bool,
+        /// Only rows matching this expression, e.g. "status = active and id > 100"
+        #[arg(long = "where", value_parser = parse_filter_expr_arg)]
+        filter: Option<FilterExpr>,
+        #[command(flatten)]
+        table: TableArgs,
     },
     Show {
         #[arg(long)]
@@ -332,9 +441,9 @@ pub enum CampaignAction {
         name: Option<String>,
         #[arg(long)]
         description: Option<String>,
-        #[arg(long)]
+        #[arg(long, value_parser = parse_date_arg)]
         start_date: Option<NaiveDate>,
-        #[arg(long)]
+        #[arg(long, value_parser = parse_date_arg)]
         end_date: Option<NaiveDate>,
         #[arg(long)]
         budget: Option<i32>,
@@ -360,6 +469,23 @@ pub enum CampaignAction {
     Active,
     Performance,
     Stats,
+    /// Record that the campaign reached a lead, or the lead's response
+    Touch {
+        id: i32,
+        #[arg(long)]
+        lead: i32,
+        #[arg(long, value_enum)]
+        response: Option<CampaignResponse>,
+    },
+    /// Won revenue credited to campaigns and lead sources, first and last touch
+    Attribution {
+        /// Period deals closed in: this-quarter, FY2025-Q2, 2025-03, ...
+        #[arg(long, default_value = "this-quarter")]
+        period: String,
+        /// List each deal's first and last touch
+        #[arg(long)]
+        deals: bool,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -385,8 +511,8 @@ pub enum ActivityAction {
     List {
         #[arg(long, default_value = "1")]
         page: i64,
-        #[arg(long, default_value = "20")]
-        per_page: i64,
+        #[arg(long)]
+        per_page: Option<i64>,
         #[arg(long)]
         search: Option<String>,
         #[arg(long)]
@@ -397,14 +523,19 @@ pub enum ActivityAction {
         priority: Option<String>,
         #[arg(long)]
         assigned_to: Option<i32>,
-        #[arg(long)]
+        #[arg(long, value_parser = parse_date_arg)]
         date_from: Option<NaiveDate>,
-        #[arg(long)]
+        #[arg(long, value_parser = parse_date_arg)]
         date_to: Option<NaiveDate>,
         #[arg(long)]
         sort_by: Option<String>,
         #[arg(long)]
         sort_desc: bool,
+        /// Only rows matching this expression, e.g. "status = active and id > 100"
+        #[arg(long = "where", value_parser = parse_filter_expr_arg)]
+        filter: Option<FilterExpr>,
+        #[command(flatten)]
+        table: TableArgs,
     },
     Show {
         id: i32,
@@ -449,11 +580,12 @@ pub enum ActivityAction {
 pub fn execute_crm_extended_command(
     conn: &mut DatabaseConnection,
     cmd: CrmExtendedCommands,
+    user: &AuthenticatedUser,
 ) -> CLIERPResult<()> {
     match cmd.action {
-        CrmExtendedAction::Customer { action } => execute_customer_command(conn, action),
-        CrmExtendedAction::Lead { action } => execute_lead_command(conn, action),
-        CrmExtendedAction::Deal { action } => execute_deal_command(conn, action),
+        CrmExtendedAction::Customer { action } => execute_customer_command(conn, action, user),
+        CrmExtendedAction::Lead { action } => execute_lead_command(conn, action, user),
+        CrmExtendedAction::Deal { action } => execute_deal_command(conn, action, user),
         CrmExtendedAction::Campaign { action } => execute_campaign_command(conn, action),
         CrmExtendedAction::Activity { action } => execute_activity_command(conn, action),
         CrmExtendedAction::Dashboard => execute_dashboard_command(conn),
@@ -462,7 +594,11 @@ pub fn execute_crm_extended_command(
     }
 }
 
-fn execute_customer_command(conn: &mut DatabaseConnection, action: CustomerAction) -> CLIERPResult<()> {
+fn execute_customer_command(
+    conn: &mut DatabaseConnection,
+    action: CustomerAction,
+    user: &AuthenticatedUser,
+) -> CLIERPResult<()> {
     match action {
         CustomerAction::Create {
             name,
@@ -498,31 +634,38 @@ fn execute_customer_command(conn: &mut DatabaseConnection, action: CustomerActio
             customer_type,
             sort_by,
             sort_desc,
+            filter,
+            table,
         } => {
-            let pagination = PaginationParams::new(page as usize, per_page);
+            let pagination = PaginationParams::new(page as usize, per_page.unwrap_or_else(|| PaginationParams::default_per_page(20)));
             let filters = FilterOptions {
                 search,
                 status,
                 filter_type: customer_type,
                 sort_by,
                 sort_desc,
+                where_clause: filter,
                 ..Default::default()
             };
             let result = CustomerService::list_customers(conn, &filters, &pagination)?;
 
-            println!("Customers (Page {} of {}):", result.pagination.current_page, result.pagination.total_pages);
-            println!("Total: {} customers", result.pagination.total_count);
-            println!();
-
+            let mut view = TableView::new(&["ID", "Code", "Name", "Type", "Status"]);
             for customer in result.data {
-                println!("ID: {} | Code: {} | Name: {} | Type: {} | Status: {}",
-                    customer.id,
+                view.push(vec![
+                    customer.id.to_string(),
                     customer.customer_code,
                     customer.name,
                     customer.customer_type,
-                    customer.status
-                );
+                    customer.status,
+                ]);
             }
+            view.apply(&table)?;
+
+            pager::page(&format!(
+                "Customers (Page {} of {}):\nTotal: {} customers\n\n{}",
+                result.pagination.current_page, result.pagination.total_pages, result.pagination.total_count,
+                view.render()
+            ))?;
         }
         CustomerAction::Show { id, code } => {
             let customer = if let Some(id) = id {
@@ -561,6 +704,28 @@ fn execute_customer_command(conn: &mut DatabaseConnection, action: CustomerActio
                 println!("Total Leads: {}", customer_stats.total_leads);
                 println!("Active Deals: {}", customer_stats.active_deals);
                 println!("Total Deal Value: {}", customer_stats.total_deal_value);
+
+                let credit = CreditScoreService::score(conn, customer_stats.customer.id, DisplayTimezone::current().today())?;
+                let history = &credit.history;
+                println!();
+                println!("Credit:");
+                match (credit.score, credit.grade) {
+                    (Some(score), Some(grade)) => println!("Credit Score: {} ({})", score, grade),
+                    _ => println!("Credit Score: not enough invoices yet ({} issued)", history.invoices),
+                }
+                if let Some(days) = history.avg_days_to_pay {
+                    println!("Average Days to Pay: {:.0}", days);
+                }
+                println!("Overdue Invoices: {} of {}", history.overdue_invoices, history.invoices);
+                println!("Returned Payments: {}", history.returned_payments);
+                if let Some(limit) = credit.suggested_limit {
+                    println!("Suggested Credit Limit: {}", format_currency(limit));
+                }
+
+                print_related(&LinkService::related(
+                    conn,
+                    RecordRef::new(RecordType::Customer, customer_stats.customer.id),
+                )?);
             } else {
                 println!("Customer not found");
             }
@@ -577,6 +742,8 @@ fn execute_customer_command(conn: &mut DatabaseConnection, action: CustomerActio
             status,
             notes,
         } => {
+            let before = CustomerService::get_customer_by_id(conn, id)?
+                .ok_or_else(|| crate::core::error::CLIERPError::NotFound(format!("Customer with ID {} not found", id)))?;
             let customer = CustomerService::update_customer(
                 conn,
                 id,
@@ -590,8 +757,8 @@ fn execute_customer_command(conn: &mut DatabaseConnection, action: CustomerActio
                 status,
                 notes.as_deref().map(Some),
             )?;
-            println!("Customer updated successfully:");
-            println!("ID: {}, Name: {}", customer.id, customer.name);
+            println!("Customer {} ({}):", customer.id, customer.name);
+            print_changes(&changed_fields(&before, &customer)?);
         }
         CustomerAction::Delete { id } => {
             let deleted = CustomerService::delete_customer(conn, id)?;
@@ -601,6 +768,28 @@ fn execute_customer_command(conn: &mut DatabaseConnection, action: CustomerActio
                 println!("Customer not found");
             }
         }
+        CustomerAction::BulkUpdate { filters, updates } => {
+            let matches = CustomerService::find_customers_by_filters(conn, &filters)?;
+            if matches.is_empty() {
+                println!("No customers match the filters.");
+                return Ok(());
+            }
+
+            println!("{} customer(s) will be updated:", matches.len());
+            for customer in matches.iter().take(10) {
+                println!("  {} - {}", customer.customer_code, customer.name);
+            }
+            if matches.len() > 10 {
+                println!("  ... and {} more", matches.len() - 10);
+            }
+            if !confirm_action("Apply these changes?", RiskLevel::Medium)? {
+                println!("Bulk update cancelled.");
+                return Ok(());
+            }
+
+            let updated = CustomerService::bulk_update_customers(conn, &filters, &updates)?;
+            println!("{} customer(s) updated successfully", updated);
+        }
         CustomerAction::Search { query } => {
             let customers = CustomerService::search_customers(conn, &query)?;
             println!("Search results for '{}':", query);
@@ -613,6 +802,71 @@ fn execute_customer_command(conn: &mut DatabaseConnection, action: CustomerActio
                 );
             }
         }
+        CustomerAction::Share { id, days } => {
+            let config = CLIERPConfig::load().map_err(crate::core::error::CLIERPError::Configuration)?;
+            let days = days.unwrap_or(config.server.link_ttl_days);
+            let (link, token) = CustomerPortalService::share(conn, &config.auth.jwt_secret, id, days, Some(user.id))?;
+
+            outln!("🔗 Customer portal link created (ID {}), valid until {}", link.id, format_datetime(&link.expires_at));
+            println!("{}/customer/{}", config.server.base_url(), token);
+            println!("The customer can open it while 'clierp system serve' is running.");
+        }
+        CustomerAction::Unshare { link_id } => {
+            let link = PortalService::revoke(conn, PortalScope::Customer, link_id)?;
+            outln!("✅ Link {} revoked; it no longer opens the account of customer {}", link.id, link.record_id);
+        }
+        CustomerAction::CreditReview { id, apply } => {
+            let suggestions = CreditScoreService::suggestions(conn, id, DisplayTimezone::current().today())?;
+            if suggestions.is_empty() {
+                println!("All scored customers have the suggested credit limit.");
+                return Ok(());
+            }
+
+            let mut view = TableView::new(&["ID", "Code", "Name", "Score", "Avg Days to Pay", "Current Limit", "Suggested"]);
+            for suggestion in &suggestions {
+                let score = &suggestion.score;
+                view.push(vec![
+                    suggestion.customer.id.to_string(),
+                    suggestion.customer.customer_code.clone(),
+                    suggestion.customer.name.clone(),
+                    match (score.score, score.grade) {
+                        (Some(value), Some(grade)) => format!("{} ({})", value, grade),
+                        _ => String::new(),
+                    },
+                    score.history.avg_days_to_pay.map(|d| format!("{:.0}", d)).unwrap_or_default(),
+                    suggestion.customer.credit_limit.map(format_currency).unwrap_or_else(|| "None".to_string()),
+                    score.suggested_limit.map(format_currency).unwrap_or_default(),
+                ]);
+            }
+            println!("{}", view.render());
+            if !apply {
+                println!("Run again with --apply to set the suggested limits.");
+                return Ok(());
+            }
+            if !confirm_action(&format!("Set the credit limit of {} customer(s)?", suggestions.len()), RiskLevel::Medium)? {
+                println!("Credit review cancelled.");
+                return Ok(());
+            }
+
+            for suggestion in &suggestions {
+                let before = suggestion.customer.clone();
+                let customer = CustomerService::update_customer(
+                    conn,
+                    before.id,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    suggestion.score.suggested_limit,
+                    None,
+                    None,
+                )?;
+                println!("Customer {} ({}):", customer.id, customer.name);
+                print_changes(&changed_fields(&before, &customer)?);
+            }
+        }
         CustomerAction::Stats => {
             let stats = CustomerService::get_customer_statistics(conn)?;
             println!("Customer Statistics:");
@@ -626,7 +880,11 @@ fn execute_customer_command(conn: &mut DatabaseConnection, action: CustomerActio
     Ok(())
 }
 
-fn execute_deal_command(conn: &mut DatabaseConnection, action: DealAction) -> CLIERPResult<()> {
+fn execute_deal_command(
+    conn: &mut DatabaseConnection,
+    action: DealAction,
+    user: &AuthenticatedUser,
+) -> CLIERPResult<()> {
     match action {
         DealAction::Create {
             lead_id,
@@ -636,8 +894,9 @@ fn execute_deal_command(conn: &mut DatabaseConnection, action: DealAction) -> CL
             assigned_to,
             description,
             notes,
+            destination_country,
         } => {
-            let deal = DealService::create_deal(
+            let mut deal = DealService::create_deal(
                 conn,
                 lead_id,
                 &title,
@@ -647,6 +906,9 @@ fn execute_deal_command(conn: &mut DatabaseConnection, action: DealAction) -> CL
                 description.as_deref(),
                 notes.as_deref(),
             )?;
+            if destination_country.is_some() {
+                deal = DealService::set_destination_country(conn, deal.id, destination_country.as_deref())?;
+            }
             println!("Deal created successfully:");
             println!("ID: {}, Title: {}, Value: {}", deal.id, deal.deal_name, deal.deal_value);
         }
@@ -660,8 +922,12 @@ fn execute_deal_command(conn: &mut DatabaseConnection, action: DealAction) -> CL
             date_to,
             sort_by,
             sort_desc,
+            all,
+            filter,
+            table,
         } => {
-            let pagination = PaginationParams::new(page as usize, per_page);
+            let scope = RecordScope::for_user(conn, user, all)?;
+            let pagination = PaginationParams::new(page as usize, per_page.unwrap_or_else(|| PaginationParams::default_per_page(20)));
             let filters = FilterOptions {
                 search,
                 status: stage,
@@ -670,35 +936,41 @@ fn execute_deal_command(conn: &mut DatabaseConnection, action: DealAction) -> CL
                 date_to,
                 sort_by,
                 sort_desc,
+                where_clause: filter,
                 ..Default::default()
             };
-            let result = DealService::list_deals(conn, &filters, &pagination)?;
-
-            println!("Deals (Page {} of {}):", result.pagination.current_page, result.pagination.total_pages);
-            println!("Total: {} deals", result.pagination.total_count);
-            println!();
+            let result = DealService::list_deals(conn, &filters, &pagination, &scope)?;
 
+            let mut view = TableView::new(&["ID", "Title", "Value", "Stage", "Customer"]);
             for deal_details in result.data {
                 let customer_name = deal_details.customer
                     .as_ref()
-                    .map(|c| c.name.as_str())
-                    .unwrap_or("N/A");
-                println!("ID: {} | Title: {} | Value: {} | Stage: {} | Customer: {}",
-                    deal_details.deal.id,
+                    .map_or("N/A".to_string(), |c| c.name.clone());
+                view.push(vec![
+                    deal_details.deal.id.to_string(),
                     deal_details.deal.deal_name,
-                    deal_details.deal.deal_value,
+                    deal_details.deal.deal_value.to_string(),
                     deal_details.deal.stage,
-                    customer_name
-                );
+                    customer_name,
+                ]);
             }
+            view.apply(&table)?;
+
+            pager::page(&format!(
+                "Deals (Page {} of {}):\nTotal: {} deals\n\n{}",
+                result.pagination.current_page, result.pagination.total_pages, result.pagination.total_count,
+                view.render()
+            ))?;
         }
-        DealAction::Show { id } => {
-            if let Some(deal_details) = DealService::get_deal_with_details(conn, id)? {
+        DealAction::Show { id, all, history } => {
+            let scope = RecordScope::for_user(conn, user, all)?;
+            if let Some(deal_details) = DealService::get_deal_with_details(conn, id, &scope)? {
                 println!("Deal Details:");
                 println!("ID: {}", deal_details.deal.id);
                 println!("Title: {}", deal_details.deal.deal_name);
                 println!("Value: {}", deal_details.deal.deal_value);
                 println!("Stage: {}", deal_details.deal.stage);
+                println!("Visibility: {}", deal_details.deal.visibility);
                 println!("Probability: {}%", deal_details.deal.probability.map_or("N/A".to_string(), |p| p.to_string()));
 
                 if let Some(customer) = &deal_details.customer {
@@ -718,10 +990,31 @@ fn execute_deal_command(conn: &mut DatabaseConnection, action: DealAction) -> CL
                 if let Some(description) = &deal_details.deal.notes {
                     println!("Description: {}", description);
                 }
+
+                print_related(&LinkService::related(conn, RecordRef::new(RecordType::Deal, deal_details.deal.id))?);
+
+                if history {
+                    print_history(&AuditService::history(conn, "deals", deal_details.deal.id)?);
+                }
             } else {
                 println!("Deal not found");
             }
         }
+        DealAction::Visibility { id, visibility } => {
+            let deal = DealService::set_visibility(conn, id, visibility)?;
+            println!("Deal visibility updated:");
+            println!("ID: {}, Title: {}, Visibility: {}", deal.id, deal.deal_name, deal.visibility);
+        }
+        DealAction::Discount { id, percent } => {
+            let deal = DealService::set_discount(conn, id, percent)?;
+            println!("Deal discount applied:");
+            println!(
+                "ID: {}, Discount: {}%, Final Amount: {}",
+                deal.id,
+                percent,
+                format_currency(deal.final_amount.unwrap_or(deal.deal_value))
+            );
+        }
         DealAction::UpdateStage { id, stage, notes } => {
             let deal = DealService::update_deal_stage(conn, id, stage, notes.as_deref())?;
             println!("Deal stage updated successfully:");
@@ -736,6 +1029,8 @@ fn execute_deal_command(conn: &mut DatabaseConnection, action: DealAction) -> CL
             description,
             notes,
         } => {
+            let before = DealService::get_deal_by_id(conn, id)?
+                .ok_or_else(|| crate::core::error::CLIERPError::NotFound(format!("Deal with ID {} not found", id)))?;
             let deal = DealService::update_deal(
                 conn,
                 id,
@@ -746,8 +1041,8 @@ fn execute_deal_command(conn: &mut DatabaseConnection, action: DealAction) -> CL
                 description.as_deref().map(Some),
                 notes.as_deref().map(Some),
             )?;
-            println!("Deal updated successfully:");
-            println!("ID: {}, Title: {}", deal.id, deal.deal_name);
+            println!("Deal {} ({}):", deal.id, deal.deal_name);
+            print_changes(&changed_fields(&before, &deal)?);
         }
         DealAction::Delete { id } => {
             let deleted = DealService::delete_deal(conn, id)?;
@@ -757,9 +1052,32 @@ fn execute_deal_command(conn: &mut DatabaseConnection, action: DealAction) -> CL
                 println!("Deal not found");
             }
         }
+        DealAction::BulkUpdate { filters, updates, all } => {
+            let scope = RecordScope::for_user(conn, user, all)?;
+            let matches = DealService::find_deals_by_filters(conn, &filters, &scope)?;
+            if matches.is_empty() {
+                println!("No deals match the filters.");
+                return Ok(());
+            }
+
+            println!("{} deal(s) will be updated:", matches.len());
+            for deal in matches.iter().take(10) {
+                println!("  {} - {} ({})", deal.id, deal.deal_name, deal.stage);
+            }
+            if matches.len() > 10 {
+                println!("  ... and {} more", matches.len() - 10);
+            }
+            if !confirm_action("Apply these changes?", RiskLevel::Medium)? {
+                println!("Bulk update cancelled.");
+                return Ok(());
+            }
+
+            let updated = DealService::bulk_update_deals(conn, &filters, &updates, &scope)?;
+            println!("{} deal(s) updated successfully", updated);
+        }
         DealAction::ByStage { stage } => {
-            let deals = DealService::get_deals_by_stage(conn, stage)?;
-            println!("Deals in {} stage:", stage.to_string());
+            let deals = DealService::get_deals_by_stage(conn, stage.clone())?;
+            println!("Deals in {} stage:", stage);
             for deal_details in deals {
                 let customer_name = deal_details.customer
                     .as_ref()
@@ -789,7 +1107,11 @@ fn execute_deal_command(conn: &mut DatabaseConnection, action: DealAction) -> CL
     Ok(())
 }
 
-fn execute_lead_command(conn: &mut DatabaseConnection, action: LeadAction) -> CLIERPResult<()> {
+fn execute_lead_command(
+    conn: &mut DatabaseConnection,
+    action: LeadAction,
+    user: &AuthenticatedUser,
+) -> CLIERPResult<()> {
     match action {
         LeadAction::Create {
             title,
@@ -817,9 +1139,166 @@ fn execute_lead_command(conn: &mut DatabaseConnection, action: LeadAction) -> CL
             println!("Lead created successfully:");
             println!("ID: {}, Title: {}, Value: {}", lead.id, lead.title, lead.estimated_value.map_or("N/A".to_string(), |v| v.to_string()));
         }
-        _ => {
-            // TODO: Implement other lead actions
-            println!("Lead action not yet implemented");
+        LeadAction::Visibility { id, visibility } => {
+            let lead = LeadService::set_visibility(conn, id, visibility)?;
+            println!("Lead visibility updated:");
+            println!("ID: {}, Title: {}, Visibility: {}", lead.id, lead.title, lead.visibility);
+        }
+        LeadAction::List {
+            page,
+            per_page,
+            search,
+            status,
+            priority,
+            assigned_to,
+            date_from,
+            date_to,
+            sort_by,
+            sort_desc,
+            all,
+            filter,
+            table,
+        } => {
+            let scope = RecordScope::for_user(conn, user, all)?;
+            let pagination = PaginationParams::new(page as usize, per_page.unwrap_or_else(|| PaginationParams::default_per_page(20)));
+            let filters = FilterOptions {
+                search,
+                status,
+                priority,
+                assigned_to,
+                date_from,
+                date_to,
+                sort_by,
+                sort_desc,
+                where_clause: filter,
+                ..Default::default()
+            };
+            let result = LeadService::list_leads(conn, &filters, &pagination, &scope)?;
+
+            let mut view = TableView::new(&["ID", "Title", "Value", "Status", "Priority", "Customer"]);
+            for lead_details in result.data {
+                view.push(vec![
+                    lead_details.lead.id.to_string(),
+                    lead_details.lead.title,
+                    lead_details.lead.estimated_value.map_or("N/A".to_string(), |v| v.to_string()),
+                    lead_details.lead.status,
+                    lead_details.lead.priority,
+                    lead_details.customer.map_or("N/A".to_string(), |c| c.name),
+                ]);
+            }
+            view.apply(&table)?;
+
+            pager::page(&format!(
+                "Leads (Page {} of {}):\nTotal: {} leads\n\n{}",
+                result.pagination.current_page, result.pagination.total_pages, result.pagination.total_count,
+                view.render()
+            ))?;
+        }
+        LeadAction::Show { id, all } => {
+            let scope = RecordScope::for_user(conn, user, all)?;
+            if let Some(lead_details) = LeadService::get_lead_with_customer(conn, id, &scope)? {
+                let lead = &lead_details.lead;
+                println!("Lead Details:");
+                println!("ID: {}", lead.id);
+                println!("Title: {}", lead.title);
+                println!("Source: {}", lead.lead_source);
+                println!("Status: {}", lead.status);
+                println!("Priority: {}", lead.priority);
+                println!("Visibility: {}", lead.visibility);
+                println!("Estimated Value: {}", lead.estimated_value.map_or("N/A".to_string(), format_currency));
+                println!("Probability: {}%", lead.probability.map_or("N/A".to_string(), |p| p.to_string()));
+
+                if let Some(customer) = &lead_details.customer {
+                    println!("Customer: {} ({})", customer.name, customer.customer_code);
+                }
+
+                if let Some(assigned) = &lead_details.assigned_employee {
+                    println!("Assigned to: {}", assigned);
+                }
+
+                if let Some(close_date) = lead.expected_close_date {
+                    println!("Expected Close Date: {}", close_date);
+                }
+
+                if let Some(description) = &lead.description {
+                    println!("Description: {}", description);
+                }
+
+                if let Some(notes) = &lead.notes {
+                    println!("Notes: {}", notes);
+                }
+
+                print_related(&LinkService::related(conn, RecordRef::new(RecordType::Lead, lead.id))?);
+            } else {
+                println!("Lead not found");
+            }
+        }
+        LeadAction::UpdateStatus { id, status, notes } => {
+            let lead = LeadService::update_lead_status(conn, id, status, notes.as_deref())?;
+            println!("Lead status updated successfully:");
+            println!("ID: {}, Status: {}, Probability: {}%", lead.id, lead.status, lead.probability.map_or("N/A".to_string(), |p| p.to_string()));
+        }
+        LeadAction::Update {
+            id,
+            title,
+            customer_id,
+            lead_source,
+            estimated_value,
+            expected_close_date,
+            priority,
+            assigned_to,
+            description,
+            notes,
+        } => {
+            let before = LeadService::get_lead_by_id(conn, id)?
+                .ok_or_else(|| crate::core::error::CLIERPError::NotFound(format!("Lead with ID {} not found", id)))?;
+            let lead = LeadService::update_lead(
+                conn,
+                id,
+                title.as_deref(),
+                customer_id.map(Some),
+                lead_source.as_deref(),
+                estimated_value,
+                expected_close_date.map(Some),
+                priority,
+                assigned_to.map(Some),
+                description.as_deref().map(Some),
+                notes.as_deref().map(Some),
+            )?;
+            println!("Lead {} ({}):", lead.id, lead.title);
+            print_changes(&changed_fields(&before, &lead)?);
+        }
+        LeadAction::Assign { id, assigned_to } => {
+            let lead = LeadService::assign_lead(conn, id, assigned_to)?;
+            println!("Lead assigned successfully:");
+            println!("ID: {}, Title: {}, Assigned to: {}", lead.id, lead.title, assigned_to);
+        }
+        LeadAction::Delete { id } => {
+            let deleted = LeadService::delete_lead(conn, id)?;
+            if deleted {
+                println!("Lead deleted successfully");
+            } else {
+                println!("Lead not found");
+            }
+        }
+        LeadAction::ByStatus { status } => {
+            println!("Leads with {} status:", status);
+            let leads = LeadService::get_leads_by_status(conn, status)?;
+            for lead_details in leads {
+                println!("{}", lead_line(&lead_details));
+            }
+        }
+        LeadAction::Stats => {
+            let stats = LeadService::get_lead_statistics(conn)?;
+            println!("Lead Statistics:");
+            println!("Total Leads: {}", stats.total_leads);
+            println!("New Leads: {}", stats.new_leads);
+            println!("Qualified Leads: {}", stats.qualified_leads);
+            println!("Closed Won: {}", stats.closed_won);
+            println!("Closed Lost: {}", stats.closed_lost);
+            println!("Total Estimated Value: {}", stats.total_estimated_value);
+            println!("Average Deal Size: {:.2}", stats.average_deal_size);
+            println!("Conversion Rate: {:.1}%", stats.conversion_rate);
         }
     }
     Ok(())
@@ -875,8 +1354,209 @@ fn execute_campaign_command(conn: &mut DatabaseConnection, action: CampaignActio
                 println!();
             }
         }
-        _ => {
-            println!("Campaign action not yet implemented");
+        CampaignAction::List {
+            page,
+            per_page,
+            search,
+            status,
+            campaign_type,
+            date_from,
+            date_to,
+            sort_by,
+            sort_desc,
+            filter,
+            table,
+        } => {
+            let pagination = PaginationParams::new(page as usize, per_page.unwrap_or_else(|| PaginationParams::default_per_page(20)));
+            let filters = FilterOptions {
+                search,
+                status,
+                filter_type: campaign_type,
+                date_from,
+                date_to,
+                sort_by,
+                sort_desc,
+                where_clause: filter,
+                ..Default::default()
+            };
+            let result = CampaignService::list_campaigns(conn, &filters, &pagination)?;
+
+            let mut view = TableView::new(&["ID", "Name", "Type", "Status", "Start", "Budget"]);
+            for campaign in result.data {
+                view.push(vec![
+                    campaign.id.to_string(),
+                    campaign.name,
+                    campaign.campaign_type,
+                    campaign.status,
+                    campaign.start_date.map_or("N/A".to_string(), |d| d.to_string()),
+                    campaign.budget.map_or("N/A".to_string(), |b| b.to_string()),
+                ]);
+            }
+            view.apply(&table)?;
+
+            pager::page(&format!(
+                "Campaigns (Page {} of {}):\nTotal: {} campaigns\n\n{}",
+                result.pagination.current_page, result.pagination.total_pages, result.pagination.total_count,
+                view.render()
+            ))?;
+        }
+        CampaignAction::Show { id, code } => {
+            let campaign = if let Some(id) = id {
+                CampaignService::get_campaign_with_stats(conn, id)?
+            } else if let Some(code) = code {
+                if let Some(campaign) = CampaignService::get_campaign_by_code(conn, &code)? {
+                    CampaignService::get_campaign_with_stats(conn, campaign.id)?
+                } else {
+                    None
+                }
+            } else {
+                return Err(crate::core::error::CLIERPError::Validation(
+                    "Either --id or --code must be provided".to_string()
+                ));
+            };
+
+            if let Some(campaign_stats) = campaign {
+                let campaign = &campaign_stats.campaign;
+                println!("Campaign Details:");
+                println!("ID: {}", campaign.id);
+                println!("Name: {}", campaign.name);
+                println!("Type: {}", campaign.campaign_type);
+                println!("Status: {}", campaign.status);
+                if let Some(start_date) = campaign.start_date {
+                    println!("Start Date: {}", start_date);
+                }
+                if let Some(end_date) = campaign.end_date {
+                    println!("End Date: {}", end_date);
+                }
+                println!("Budget: {}", campaign.budget.map_or("None".to_string(), format_currency));
+                println!("Spent: {}", campaign.spent.map_or("None".to_string(), format_currency));
+                if let Some(audience) = &campaign.target_audience {
+                    println!("Target Audience: {}", audience);
+                }
+                if let Some(description) = &campaign.description {
+                    println!("Description: {}", description);
+                }
+                println!();
+                println!("Statistics:");
+                println!("Total Leads: {}", campaign_stats.total_leads);
+                println!("Converted Leads: {}", campaign_stats.converted_leads);
+                println!("Conversion Rate: {:.1}%", campaign_stats.conversion_rate);
+                println!("Total Revenue: {}", format_currency(campaign_stats.total_revenue));
+                println!("ROI: {:.1}%", campaign_stats.roi);
+
+                print_related(&LinkService::related(conn, RecordRef::new(RecordType::Campaign, campaign.id))?);
+            } else {
+                println!("Campaign not found");
+            }
+        }
+        CampaignAction::Update {
+            id,
+            name,
+            description,
+            start_date,
+            end_date,
+            budget,
+            actual_cost,
+            target_audience,
+            goals,
+        } => {
+            let before = CampaignService::get_campaign_by_id(conn, id)?
+                .ok_or_else(|| crate::core::error::CLIERPError::NotFound(format!("Campaign with ID {} not found", id)))?;
+            let campaign = CampaignService::update_campaign(
+                conn,
+                id,
+                name.as_deref(),
+                description.as_deref().map(Some),
+                start_date,
+                end_date.map(Some),
+                budget.map(Some),
+                actual_cost.map(Some),
+                target_audience.as_deref().map(Some),
+                goals.as_deref().map(Some),
+            )?;
+            println!("Campaign {} ({}):", campaign.id, campaign.name);
+            print_changes(&changed_fields(&before, &campaign)?);
+        }
+        CampaignAction::UpdateStatus { id, status } => {
+            let campaign = CampaignService::update_campaign_status(conn, id, status)?;
+            println!("Campaign status updated successfully:");
+            println!("ID: {}, Name: {}, Status: {}", campaign.id, campaign.name, campaign.status);
+        }
+        CampaignAction::Delete { id } => {
+            let deleted = CampaignService::delete_campaign(conn, id)?;
+            if deleted {
+                println!("Campaign deleted successfully");
+            } else {
+                println!("Campaign not found");
+            }
+        }
+        CampaignAction::ByStatus { status } => {
+            println!("Campaigns with {} status:", status);
+            let campaigns = CampaignService::get_campaigns_by_status(conn, status)?;
+            for campaign in campaigns {
+                println!("{}", campaign_line(&campaign));
+            }
+        }
+        CampaignAction::Active => {
+            let campaigns = CampaignService::get_active_campaigns(conn)?;
+            println!("Active Campaigns:");
+            for campaign in campaigns {
+                println!("{}", campaign_line(&campaign));
+            }
+        }
+        CampaignAction::Touch { id, lead, response } => {
+            let touch = AttributionService::record_touch(conn, id, lead, response)?;
+            outln!(
+                "✅ Lead {} recorded for campaign {}{}",
+                touch.lead_id,
+                touch.campaign_id,
+                touch.response.map(|r| format!(" ({})", r)).unwrap_or_default()
+            );
+        }
+        CampaignAction::Attribution { period, deals } => {
+            let (start, end) = crate::utils::dates::parse_period(&period)?;
+            if deals {
+                let deals = AttributionService::deals(conn, start, end)?;
+                if deals.is_empty() {
+                    println!("No deals won from {} to {}.", start, end);
+                    return Ok(());
+                }
+                let mut view = TableView::new(&["Deal", "Closed", "Revenue", "Touches", "First Touch", "Last Touch"]);
+                for deal in deals {
+                    view.push(vec![
+                        deal.deal_name,
+                        deal.closed_on.to_string(),
+                        format_currency(deal.revenue),
+                        deal.touches.to_string(),
+                        deal.first_touch.channel,
+                        deal.last_touch.channel,
+                    ]);
+                }
+                println!("{}", view.render());
+                return Ok(());
+            }
+
+            let channels = AttributionService::by_channel(conn, start, end)?;
+            if channels.is_empty() {
+                println!("No deals won from {} to {}.", start, end);
+                return Ok(());
+            }
+            let mut view = TableView::new(&[
+                "Campaign / Source", "Spent", "First-Touch Deals", "First-Touch Revenue", "Last-Touch Deals", "Last-Touch Revenue", "ROI",
+            ]);
+            for channel in channels {
+                view.push(vec![
+                    channel.channel.clone(),
+                    channel.spent.map(format_currency).unwrap_or_default(),
+                    channel.first_touch_deals.to_string(),
+                    format_currency(channel.first_touch_revenue),
+                    channel.last_touch_deals.to_string(),
+                    format_currency(channel.last_touch_revenue),
+                    channel.roi().map(|roi| format!("{:.0}%", roi)).unwrap_or_default(),
+                ]);
+            }
+            println!("Attribution of deals won from {} to {}", start, end);
+            println!("{}", view.render());
         }
     }
     Ok(())
@@ -892,8 +1572,14 @@ fn execute_activity_command(conn: &mut DatabaseConnection, action: ActivityActio
             lead_id,
             assigned_to,
             due_date,
-            priority,
+            // Activities have no priority column
+            priority: _,
         } => {
+            // Due dates are entered in the display timezone and stored in UTC;
+            // without one the activity is dated now
+            let activity_date = due_date
+                .map(|d| DisplayTimezone::current().to_utc(d))
+                .unwrap_or_else(|| chrono::Utc::now().naive_utc());
             let activity = ActivityService::create_activity(
                 conn,
                 activity_type,
@@ -901,9 +1587,10 @@ fn execute_activity_command(conn: &mut DatabaseConnection, action: ActivityActio
                 description.as_deref(),
                 customer_id,
                 lead_id,
-                assigned_to,
-                due_date,
-                priority.as_deref(),
+                None,
+                Some(assigned_to),
+                activity_date,
+                None,
             )?;
             println!("Activity created successfully:");
             println!("ID: {}, Title: {}, Type: {}", activity.id, activity.subject, activity.activity_type);
@@ -926,37 +1613,224 @@ fn execute_activity_command(conn: &mut DatabaseConnection, action: ActivityActio
             let activities = ActivityService::get_overdue_activities(conn)?;
             println!("Overdue Activities:");
             for activity_details in activities {
-                let entity_name = if let Some(customer) = &activity_details.customer {
+                println!("{}", activity_line(&activity_details));
+            }
+        }
+        ActivityAction::List {
+            page,
+            per_page,
+            search,
+            status,
+            activity_type,
+            priority,
+            assigned_to,
+            date_from,
+            date_to,
+            sort_by,
+            sort_desc,
+            filter,
+            table,
+        } => {
+            if priority.is_some() {
+                println!("Note: activities have no priority; --priority is ignored");
+            }
+            let pagination = PaginationParams::new(page as usize, per_page.unwrap_or_else(|| PaginationParams::default_per_page(20)));
+            let filters = FilterOptions {
+                search,
+                status,
+                filter_type: activity_type,
+                assigned_to,
+                date_from,
+                date_to,
+                sort_by,
+                sort_desc,
+                where_clause: filter,
+                ..Default::default()
+            };
+            let result = ActivityService::list_activities(conn, &filters, &pagination)?;
+
+            let mut view = TableView::new(&["ID", "Title", "Type", "Due", "Status", "Regarding"]);
+            for activity_details in result.data {
+                let regarding = if let Some(customer) = &activity_details.customer {
                     format!("Customer: {}", customer.name)
                 } else if let Some(lead) = &activity_details.lead {
                     format!("Lead: {}", lead.title)
                 } else {
                     "No entity".to_string()
                 };
+                view.push(vec![
+                    activity_details.activity.id.to_string(),
+                    activity_details.activity.subject.clone(),
+                    activity_details.activity.activity_type.clone(),
+                    DisplayTimezone::current()
+                        .to_local(activity_details.activity.activity_date)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string(),
+                    if activity_details.activity.completed { "completed" } else { "pending" }.to_string(),
+                    regarding,
+                ]);
+            }
+            view.apply(&table)?;
 
-                println!("ID: {} | Title: {} | Type: {} | Due: {} | {}",
-                    activity_details.activity.id,
-                    activity_details.activity.subject,
-                    activity_details.activity.activity_type,
-                    activity_details.activity.activity_date.format("%Y-%m-%d %H:%M").to_string(),
-                    entity_name
+            pager::page(&format!(
+                "Activities (Page {} of {}):\nTotal: {} activities\n\n{}",
+                result.pagination.current_page, result.pagination.total_pages, result.pagination.total_count,
+                view.render()
+            ))?;
+        }
+        ActivityAction::Show { id } => {
+            if let Some(activity_details) = ActivityService::get_activity_with_details(conn, id)? {
+                let activity = &activity_details.activity;
+                println!("Activity Details:");
+                println!("ID: {}", activity.id);
+                println!("Title: {}", activity.subject);
+                println!("Type: {}", activity.activity_type);
+                println!("Status: {}", if activity.completed { "completed" } else { "pending" });
+                println!(
+                    "Due: {}",
+                    DisplayTimezone::current().to_local(activity.activity_date).format("%Y-%m-%d %H:%M")
                 );
+                if let Some(duration) = activity.duration_minutes {
+                    println!("Duration: {} min", duration);
+                }
+                println!("Assigned to: {}", activity_details.assigned_employee);
+                if let Some(customer) = &activity_details.customer {
+                    println!("Customer: {} ({})", customer.name, customer.customer_code);
+                }
+                if let Some(lead) = &activity_details.lead {
+                    println!("Lead: {}", lead.title);
+                }
+                if let Some(description) = &activity.description {
+                    println!("Description: {}", description);
+                }
+                if let Some(outcome) = &activity.outcome {
+                    println!("Outcome: {}", outcome);
+                }
+            } else {
+                println!("Activity not found");
+            }
+        }
+        ActivityAction::Update {
+            id,
+            title,
+            description,
+            due_date,
+            priority,
+            assigned_to,
+        } => {
+            if priority.is_some() {
+                println!("Note: activities have no priority; --priority is ignored");
+            }
+            let before = ActivityService::get_activity_by_id(conn, id)?
+                .ok_or_else(|| crate::core::error::CLIERPError::NotFound(format!("Activity with ID {} not found", id)))?;
+            let activity = ActivityService::update_activity(
+                conn,
+                id,
+                title.as_deref(),
+                description.as_deref().map(Some),
+                due_date.map(|d| DisplayTimezone::current().to_utc(d)),
+                None,
+                assigned_to.map(Some),
+                None,
+            )?;
+            println!("Activity {} ({}):", activity.id, activity.subject);
+            print_changes(&changed_fields(&before, &activity)?);
+        }
+        ActivityAction::Complete { id, outcome } => {
+            let activity = ActivityService::complete_activity(conn, id, outcome.as_deref())?;
+            println!("Activity completed:");
+            println!("ID: {}, Title: {}", activity.id, activity.subject);
+        }
+        ActivityAction::Reopen { id } => {
+            let activity = ActivityService::reopen_activity(conn, id)?;
+            println!("Activity reopened:");
+            println!("ID: {}, Title: {}", activity.id, activity.subject);
+        }
+        ActivityAction::Delete { id } => {
+            let deleted = ActivityService::delete_activity(conn, id)?;
+            if deleted {
+                println!("Activity deleted successfully");
+            } else {
+                println!("Activity not found");
+            }
+        }
+        ActivityAction::ByCustomer { customer_id } => {
+            let activities = ActivityService::get_activities_by_customer(conn, customer_id)?;
+            println!("Activities for customer {}:", customer_id);
+            for activity_details in activities {
+                println!("{}", activity_line(&activity_details));
+            }
+        }
+        ActivityAction::ByLead { lead_id } => {
+            let activities = ActivityService::get_activities_by_lead(conn, lead_id)?;
+            println!("Activities for lead {}:", lead_id);
+            for activity_details in activities {
+                println!("{}", activity_line(&activity_details));
             }
         }
-        _ => {
-            println!("Activity action not yet implemented");
+        ActivityAction::ByEmployee { employee_id } => {
+            let activities = ActivityService::get_activities_by_employee(conn, employee_id)?;
+            println!("Activities assigned to employee {}:", employee_id);
+            for activity_details in activities {
+                println!("{}", activity_line(&activity_details));
+            }
         }
     }
     Ok(())
 }
 
+fn lead_line(lead_details: &LeadWithCustomer) -> String {
+    let customer_name = lead_details.customer
+        .as_ref()
+        .map(|c| c.name.as_str())
+        .unwrap_or("N/A");
+    format!("ID: {} | Title: {} | Value: {} | Status: {} | Priority: {} | Customer: {}",
+        lead_details.lead.id,
+        lead_details.lead.title,
+        lead_details.lead.estimated_value.map_or("N/A".to_string(), |v| v.to_string()),
+        lead_details.lead.status,
+        lead_details.lead.priority,
+        customer_name
+    )
+}
+
+fn campaign_line(campaign: &Campaign) -> String {
+    format!("ID: {} | Name: {} | Type: {} | Status: {} | Start: {} | Budget: {}",
+        campaign.id,
+        campaign.name,
+        campaign.campaign_type,
+        campaign.status,
+        campaign.start_date.map_or("N/A".to_string(), |d| d.to_string()),
+        campaign.budget.map_or("N/A".to_string(), |b| b.to_string())
+    )
+}
+
+fn activity_line(activity_details: &ActivityWithDetails) -> String {
+    let entity_name = if let Some(customer) = &activity_details.customer {
+        format!("Customer: {}", customer.name)
+    } else if let Some(lead) = &activity_details.lead {
+        format!("Lead: {}", lead.title)
+    } else {
+        "No entity".to_string()
+    };
+
+    format!("ID: {} | Title: {} | Type: {} | Due: {} | {} | {}",
+        activity_details.activity.id,
+        activity_details.activity.subject,
+        activity_details.activity.activity_type,
+        DisplayTimezone::current().to_local(activity_details.activity.activity_date).format("%Y-%m-%d %H:%M"),
+        if activity_details.activity.completed { "completed" } else { "pending" },
+        entity_name
+    )
+}
+
 fn execute_dashboard_command(conn: &mut DatabaseConnection) -> CLIERPResult<()> {
     println!("=== CRM Dashboard ===");
     println!();
 
     // Customer stats
     let customer_stats = CustomerService::get_customer_statistics(conn)?;
-    println!("📊 Customer Overview:");
+    outln!("📊 Customer Overview:");
     println!("  Total: {} | Active: {} | Business: {} | Individual: {}",
         customer_stats.total_customers,
         customer_stats.active_customers,
@@ -967,7 +1841,7 @@ fn execute_dashboard_command(conn: &mut DatabaseConnection) -> CLIERPResult<()>
 
     // Lead stats
     let lead_stats = LeadService::get_lead_statistics(conn)?;
-    println!("🎯 Lead Overview:");
+    outln!("🎯 Lead Overview:");
     println!("  Total: {} | New: {} | Qualified: {} | Won: {} | Lost: {}",
         lead_stats.total_leads,
         lead_stats.new_leads,
@@ -983,7 +1857,7 @@ fn execute_dashboard_command(conn: &mut DatabaseConnection) -> CLIERPResult<()>
 
     // Deal stats
     let deal_stats = DealService::get_deal_statistics(conn)?;
-    println!("💰 Deal Overview:");
+    outln!("💰 Deal Overview:");
     println!("  Total: {} | Active: {} | Won: {} | Lost: {}",
         deal_stats.total_deals,
         deal_stats.active_deals,
@@ -999,7 +1873,7 @@ fn execute_dashboard_command(conn: &mut DatabaseConnection) -> CLIERPResult<()>
 
     // Activity stats
     let activity_stats = ActivityService::get_activity_statistics(conn)?;
-    println!("📋 Activity Overview:");
+    outln!("📋 Activity Overview:");
     println!("  Total: {} | Pending: {} | Completed: {} | Overdue: {}",
         activity_stats.total_activities,
         activity_stats.pending_activities,
@@ -1007,6 +1881,14 @@ fn execute_dashboard_command(conn: &mut DatabaseConnection) -> CLIERPResult<()>
         activity_stats.overdue_activities
     );
 
+    // KPIs
+    let kpis = crate::modules::reporting::KpiService::evaluate_all(conn)?;
+    if !kpis.is_empty() {
+        println!();
+        outln!("📈 KPIs:");
+        crate::modules::reporting::print_kpis(&kpis);
+    }
+
     Ok(())
 }
 
@@ -1017,7 +1899,7 @@ fn execute_pipeline_command(conn: &mut DatabaseConnection) -> CLIERPResult<()> {
     let pipeline = DealService::get_sales_pipeline(conn)?;
 
     for stage in pipeline {
-        println!("📍 {}", stage.stage);
+        outln!("📍 {}", stage.stage);
         println!("   Deals: {} | Total Value: {} | Avg Value: {:.2}",
             stage.count,
             stage.total_value,
@@ -1036,9 +1918,9 @@ fn execute_performance_command(conn: &mut DatabaseConnection) -> CLIERPResult<()
     // Campaign performance
     let campaign_performance = CampaignService::get_campaign_performance(conn)?;
     if !campaign_performance.is_empty() {
-        println!("🎯 Top Campaigns by Lead Generation:");
+        outln!("🎯 Top Campaigns by Lead Generation:");
         let mut sorted_campaigns = campaign_performance;
-        sorted_campaigns.sort_by(|a, b| b.total_leads.cmp(&a.total_leads));
+        sorted_campaigns.sort_by_key(|perf| std::cmp::Reverse(perf.total_leads));
 
         for (i, perf) in sorted_campaigns.iter().take(5).enumerate() {
             println!("{}. {} - {} leads ({:.1}% conversion)",
@@ -1058,7 +1940,7 @@ fn execute_performance_command(conn: &mut DatabaseConnection) -> CLIERPResult<()
         .map(|stage| stage.total_value)
         .sum();
 
-    println!("💰 Pipeline Health:");
+    outln!("💰 Pipeline Health:");
     println!("   Active Pipeline Value: {}", total_pipeline_value);
 
     let qualification_deals = pipeline.iter()
@@ -1075,4 +1957,211 @@ fn execute_performance_command(conn: &mut DatabaseConnection) -> CLIERPResult<()
         qualification_deals, negotiation_deals);
 
     Ok(())
-}
\ No newline at end of file
+}
+pub fn execute_forecast_command(
+    conn: &mut DatabaseConnection,
+    action: crate::core::command::ForecastCommands,
+    user: &AuthenticatedUser,
+) -> CLIERPResult<()> {
+    use crate::core::command::ForecastCommands;
+    use crate::core::error::CLIERPError;
+    use crate::core::workflow::role_satisfies;
+    use crate::modules::crm::ForecastService;
+    use crate::utils::dates::parse_period;
+
+    match action {
+        ForecastCommands::Show { period, rep } => {
+            let (start, end) = parse_period(&period)?;
+            let forecast = ForecastService::forecast(conn, start, end, DisplayTimezone::current().today(), rep)?;
+            if forecast.reps.is_empty() {
+                println!("No deals to forecast for {} to {}.", start, end);
+                return Ok(());
+            }
+
+            let mut view = TableView::new(&[
+                "Rep", "Quota", "Booked", "Open", "Pipeline", "Weighted", "Run Rate", "Forecast", "Low - High", "Attainment",
+            ]);
+            let row = |rep: &crate::modules::crm::RepForecast| {
+                vec![
+                    rep.name.clone(),
+                    rep.quota.map(format_currency).unwrap_or_default(),
+                    format_currency(rep.booked),
+                    rep.open_deals.to_string(),
+                    format_currency(rep.pipeline),
+                    format_currency(rep.expected_pipeline),
+                    format_currency(rep.run_rate),
+                    format_currency(rep.forecast),
+                    format!("{} - {}", format_currency(rep.low), format_currency(rep.high)),
+                    rep.attainment().map(|a| format!("{:.0}%", a)).unwrap_or_default(),
+                ]
+            };
+            for rep in &forecast.reps {
+                view.push(row(rep));
+            }
+            if rep.is_none() {
+                view.push(row(&forecast.total));
+            }
+            println!("Sales forecast {} to {} as of {}", forecast.start, forecast.end, forecast.as_of);
+            println!("{}", view.render());
+            println!("Weighted blends stage probabilities with historical win rates; the band is 80% confidence.");
+        }
+        ForecastCommands::Quota { employee_id, period, amount } => {
+            if !role_satisfies(&user.role, "manager") {
+                return Err(CLIERPError::Authorization("Setting quotas requires role: manager".to_string()));
+            }
+            let (start, end) = parse_period(&period)?;
+            let quotas = ForecastService::set_quota(conn, employee_id, start, end, amount, Some(user.id))?;
+            outln!(
+                "✅ Quota of {} a month set for employee {} ({} month(s))",
+                format_currency(amount),
+                employee_id,
+                quotas.len()
+            );
+        }
+        ForecastCommands::Quotas { period } => {
+            let (start, end) = parse_period(&period)?;
+            let quotas = ForecastService::quotas(conn, start, end)?;
+            if quotas.is_empty() {
+                println!("No quotas set for {} to {}.", start, end);
+                return Ok(());
+            }
+            let mut view = TableView::new(&["Employee", "Month", "Quota"]);
+            for quota in quotas {
+                view.push(vec![quota.employee_id.to_string(), quota.period, format_currency(quota.amount)]);
+            }
+            println!("{}", view.render());
+        }
+    }
+    Ok(())
+}
+
+pub fn execute_survey_command(
+    conn: &mut DatabaseConnection,
+    action: crate::core::command::SurveyCommands,
+    user: &AuthenticatedUser,
+) -> CLIERPResult<()> {
+    use crate::core::command::SurveyCommands;
+    use crate::modules::crm::{NpsScore, SurveyService};
+    use crate::utils::dates::parse_period;
+
+    let nps_cell = |nps: &NpsScore| nps.score().map(|s| format!("{:+.0}", s)).unwrap_or_else(|| "-".to_string());
+    match action {
+        SurveyCommands::Record { customer_id, score, deal, channel, comment, date } => {
+            let date = date.unwrap_or_else(|| DisplayTimezone::current().today());
+            let survey = SurveyService::record(conn, customer_id, deal, score, comment.as_deref(), date, channel, Some(user.id))?;
+            outln!("✅ Survey {} recorded: customer {} scored {}", survey.id, survey.customer_id, survey.score);
+        }
+        SurveyCommands::List { customer, deal, limit } => {
+            let surveys = SurveyService::list(conn, customer, deal, limit)?;
+            if surveys.is_empty() {
+                println!("No surveys found.");
+                return Ok(());
+            }
+            let mut view = TableView::new(&["ID", "Date", "Customer", "Deal", "Score", "Channel", "Comment"]);
+            for survey in surveys {
+                view.push(vec![
+                    survey.id.to_string(),
+                    survey.survey_date.to_string(),
+                    survey.customer_id.to_string(),
+                    survey.deal_id.map(|id| id.to_string()).unwrap_or_default(),
+                    survey.score.to_string(),
+                    survey.channel,
+                    survey.comment.unwrap_or_default(),
+                ]);
+            }
+            println!("{}", view.render());
+        }
+        SurveyCommands::Nps { period } => {
+            let (start, end) = parse_period(&period)?;
+            let summary = SurveyService::nps(conn, start, end)?;
+            if summary.overall.responses == 0 {
+                println!("No surveys from {} to {}.", start, end);
+                return Ok(());
+            }
+            let row = |label: &str, nps: &NpsScore| {
+                vec![
+                    label.to_string(),
+                    nps.responses.to_string(),
+                    nps.promoters.to_string(),
+                    nps.passives.to_string(),
+                    nps.detractors.to_string(),
+                    nps_cell(nps),
+                ]
+            };
+            let headers = ["", "Responses", "Promoters", "Passives", "Detractors", "NPS"];
+            println!("Net Promoter Score from {} to {}: {}", start, end, nps_cell(&summary.overall));
+
+            let mut segments = TableView::new(&headers);
+            for (segment, nps) in &summary.by_segment {
+                segments.push(row(segment, nps));
+            }
+            println!("\nBy segment:\n{}", segments.render());
+
+            let mut months = TableView::new(&headers);
+            for (month, nps) in &summary.by_month {
+                months.push(row(month, nps));
+            }
+            println!("\nBy month:\n{}", months.render());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::UserRole;
+    use crate::testing::CompanyFixture;
+
+    #[test]
+    fn test_lead_actions() {
+        let company = CompanyFixture::new().with_customers(1).with_employees(1).build().unwrap();
+        let mut conn = company.connection().unwrap();
+        let user = AuthenticatedUser {
+            id: company.admin.id,
+            username: company.admin.username.clone(),
+            email: company.admin.email.clone(),
+            role: UserRole::Admin,
+            employee_id: None,
+        };
+        let customer = &company.customers[0];
+        let employee = &company.employees[0];
+
+        execute_lead_command(&mut conn, LeadAction::Create {
+            title: "Fit-out".to_string(),
+            customer_id: Some(customer.id),
+            lead_source: "referral".to_string(),
+            estimated_value: 50000,
+            expected_close_date: None,
+            priority: LeadPriority::High,
+            assigned_to: None,
+            description: None,
+            notes: Some("First call".to_string()),
+        }, &user).unwrap();
+        let lead_id = LeadService::get_leads_by_status(&mut conn, LeadStatus::New).unwrap()[0].lead.id;
+
+        execute_lead_command(&mut conn, LeadAction::UpdateStatus {
+            id: lead_id,
+            status: LeadStatus::Qualified,
+            notes: Some("Budget confirmed".to_string()),
+        }, &user).unwrap();
+        execute_lead_command(&mut conn, LeadAction::Assign { id: lead_id, assigned_to: employee.id }, &user).unwrap();
+        execute_lead_command(&mut conn, LeadAction::ByStatus { status: LeadStatus::Qualified }, &user).unwrap();
+        execute_lead_command(&mut conn, LeadAction::Show { id: lead_id, all: false }, &user).unwrap();
+
+        let lead = LeadService::get_lead_by_id(&mut conn, lead_id).unwrap().unwrap();
+        assert_eq!(lead.status, "qualified");
+        assert_eq!(lead.assigned_to, Some(employee.id));
+        assert_eq!(lead.notes.as_deref(), Some("First call\n---\nBudget confirmed"));
+        assert!(LeadService::get_leads_by_status(&mut conn, LeadStatus::New).unwrap().is_empty());
+
+        let scope = RecordScope::for_user(&mut conn, &user, false).unwrap();
+        let details = LeadService::get_lead_with_customer(&mut conn, lead_id, &scope).unwrap().unwrap();
+        assert_eq!(
+            lead_line(&details),
+            format!("ID: {} | Title: Fit-out | Value: 50000 | Status: qualified | Priority: high | Customer: {}", lead_id, customer.name)
+        );
+
+        assert!(execute_lead_command(&mut conn, LeadAction::Assign { id: lead_id, assigned_to: 9999 }, &user).is_err());
+    }
+}
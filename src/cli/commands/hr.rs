@@ -1,12 +1,19 @@
-use crate::core::{auth::AuthenticatedUser, command::Command, result::CLIERPResult};
-use crate::database::connection::{DatabaseManager, get_connection};
-use crate::modules::hr::department::{DepartmentService, DepartmentWithEmployeeCount};
-use crate::utils::formatting::format_table;
+use crate::core::{auth::AuthenticatedUser, result::CLIERPResult};
+use crate::database::connection::get_connection;
+use crate::modules::hr::department::{DepartmentService, DepartmentWithEmployeeCount, DEPARTMENT_CSV_HEADERS};
+use crate::modules::hr::employee::EMPLOYEE_CSV_HEADERS;
+use crate::modules::system::export_templates::{ExportEntity, ExportTemplateService};
+use crate::cli::tui::pager;
+use crate::utils::filter_expr::FilterExpr;
+use crate::utils::table::{TableArgs, TableView};
+use crate::outln;
 use chrono::NaiveDate;
 
 // Department Commands
 
-pub struct HrDeptListCommand;
+pub struct HrDeptListCommand {
+    pub table: TableArgs,
+}
 
 impl Default for HrDeptListCommand {
     fn default() -> Self {
@@ -16,16 +23,18 @@ impl Default for HrDeptListCommand {
 
 impl HrDeptListCommand {
     pub fn new() -> Self {
-        Self
+        Self {
+            table: TableArgs::default(),
+        }
+    }
+
+    /// Columns and sort order of the table
+    pub fn with_table(mut self, table: TableArgs) -> Self {
+        self.table = table;
+        self
     }
-}
 
-impl Command for HrDeptListCommand {
-    fn execute(
-        &self,
-        _args: &dyn std::any::Any,
-        user: Option<&AuthenticatedUser>,
-    ) -> CLIERPResult<()> {
+    pub fn execute(&self, user: Option<&AuthenticatedUser>) -> CLIERPResult<()> {
         let _user = user.ok_or_else(|| crate::core::error::CLIERPError::AuthenticationRequired)?;
 
         let mut conn = get_connection()?;
@@ -38,20 +47,52 @@ impl Command for HrDeptListCommand {
             return Ok(());
         }
 
-        display_departments_table(&departments);
-        Ok(())
+        display_departments_table(&departments, &self.table)
     }
+}
 
-    fn name(&self) -> &'static str {
-        "hr-dept-list"
-    }
+pub struct HrDeptShowCommand {
+    pub id: i32,
+}
 
-    fn description(&self) -> &'static str {
-        "List all departments with employee count"
+impl HrDeptShowCommand {
+    pub fn new(id: i32) -> Self {
+        Self { id }
     }
 
-    fn requires_auth(&self) -> bool {
-        true
+    pub fn execute(&self, user: Option<&AuthenticatedUser>) -> CLIERPResult<()> {
+        use crate::modules::hr::employee::EmployeeService;
+
+        let _user = user.ok_or_else(|| crate::core::error::CLIERPError::AuthenticationRequired)?;
+
+        let mut conn = get_connection()?;
+        let department = DepartmentService::new()
+            .get_department_by_id(&mut conn, self.id)?
+            .ok_or_else(|| {
+                crate::core::error::CLIERPError::NotFound(format!("Department with ID {} not found", self.id))
+            })?;
+
+        println!("Department Details:");
+        println!("ID: {}", department.id);
+        println!("Name: {}", department.name);
+        if let Some(desc) = &department.description {
+            println!("Description: {}", desc);
+        }
+        if let Some(manager_id) = department.manager_id {
+            println!("Manager ID: {}", manager_id);
+        }
+        println!(
+            "Created: {}",
+            department.created_at.format("%Y-%m-%d %H:%M:%S")
+        );
+
+        let employees = EmployeeService::new().list_employees_by_department(&mut conn, department.id)?;
+        println!("\nEmployees: {}", employees.len());
+        if !employees.is_empty() {
+            display_employees_table(&employees, &TableArgs::default())?;
+        }
+
+        Ok(())
     }
 }
 
@@ -69,14 +110,8 @@ impl HrDeptAddCommand {
             manager_id,
         }
     }
-}
 
-impl Command for HrDeptAddCommand {
-    fn execute(
-        &self,
-        _args: &dyn std::any::Any,
-        user: Option<&AuthenticatedUser>,
-    ) -> CLIERPResult<()> {
+    pub fn execute(&self, user: Option<&AuthenticatedUser>) -> CLIERPResult<()> {
         let _user = user.ok_or_else(|| crate::core::error::CLIERPError::AuthenticationRequired)?;
 
         let mut conn = get_connection()?;
@@ -89,7 +124,7 @@ impl Command for HrDeptAddCommand {
             self.manager_id,
         )?;
 
-        println!("✅ Department created successfully!");
+        outln!("✅ Department created successfully!");
         println!("ID: {}", department.id);
         println!("Name: {}", department.name);
         if let Some(desc) = &department.description {
@@ -102,18 +137,6 @@ impl Command for HrDeptAddCommand {
 
         Ok(())
     }
-
-    fn name(&self) -> &'static str {
-        "hr-dept-add"
-    }
-
-    fn description(&self) -> &'static str {
-        "Create a new department"
-    }
-
-    fn requires_auth(&self) -> bool {
-        true
-    }
 }
 
 pub struct HrDeptUpdateCommand {
@@ -137,14 +160,8 @@ impl HrDeptUpdateCommand {
             manager_id,
         }
     }
-}
 
-impl Command for HrDeptUpdateCommand {
-    fn execute(
-        &self,
-        _args: &dyn std::any::Any,
-        user: Option<&AuthenticatedUser>,
-    ) -> CLIERPResult<()> {
+    pub fn execute(&self, user: Option<&AuthenticatedUser>) -> CLIERPResult<()> {
         let _user = user.ok_or_else(|| crate::core::error::CLIERPError::AuthenticationRequired)?;
 
         let mut conn = get_connection()?;
@@ -158,7 +175,7 @@ impl Command for HrDeptUpdateCommand {
             self.manager_id,
         )?;
 
-        println!("✅ Department updated successfully!");
+        outln!("✅ Department updated successfully!");
         println!("ID: {}", department.id);
         println!("Name: {}", department.name);
         if let Some(desc) = &department.description {
@@ -171,18 +188,6 @@ impl Command for HrDeptUpdateCommand {
 
         Ok(())
     }
-
-    fn name(&self) -> &'static str {
-        "hr-dept-update"
-    }
-
-    fn description(&self) -> &'static str {
-        "Update an existing department"
-    }
-
-    fn requires_auth(&self) -> bool {
-        true
-    }
 }
 
 pub struct HrDeptDeleteCommand {
@@ -193,14 +198,8 @@ impl HrDeptDeleteCommand {
     pub fn new(id: i32) -> Self {
         Self { id }
     }
-}
 
-impl Command for HrDeptDeleteCommand {
-    fn execute(
-        &self,
-        _args: &dyn std::any::Any,
-        user: Option<&AuthenticatedUser>,
-    ) -> CLIERPResult<()> {
+    pub fn execute(&self, user: Option<&AuthenticatedUser>) -> CLIERPResult<()> {
         let _user = user.ok_or_else(|| crate::core::error::CLIERPError::AuthenticationRequired)?;
 
         let mut conn = get_connection()?;
@@ -208,42 +207,50 @@ impl Command for HrDeptDeleteCommand {
 
         dept_service.delete_department(&mut conn, self.id)?;
 
-        println!("✅ Department deleted successfully!");
+        outln!("✅ Department deleted successfully!");
 
         Ok(())
     }
-
-    fn name(&self) -> &'static str {
-        "hr-dept-delete"
-    }
-
-    fn description(&self) -> &'static str {
-        "Delete a department"
-    }
-
-    fn requires_auth(&self) -> bool {
-        true
-    }
 }
 
 // Employee Commands
 
 pub struct HrEmployeeListCommand {
     pub department_id: Option<i32>,
+    pub status: Option<String>,
+    pub filter: Option<FilterExpr>,
+    pub table: TableArgs,
 }
 
 impl HrEmployeeListCommand {
     pub fn new(department_id: Option<i32>) -> Self {
-        Self { department_id }
+        Self {
+            department_id,
+            status: None,
+            filter: None,
+            table: TableArgs::default(),
+        }
+    }
+
+    /// Only list employees with this status
+    pub fn with_status(mut self, status: Option<String>) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Only list employees matching a `--where` expression
+    pub fn with_filter(mut self, filter: Option<FilterExpr>) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Columns and sort order of the table
+    pub fn with_table(mut self, table: TableArgs) -> Self {
+        self.table = table;
+        self
     }
-}
 
-impl Command for HrEmployeeListCommand {
-    fn execute(
-        &self,
-        _args: &dyn std::any::Any,
-        user: Option<&AuthenticatedUser>,
-    ) -> CLIERPResult<()> {
+    pub fn execute(&self, user: Option<&AuthenticatedUser>) -> CLIERPResult<()> {
         use crate::modules::hr::employee::EmployeeService;
 
         let _user = user.ok_or_else(|| crate::core::error::CLIERPError::AuthenticationRequired)?;
@@ -251,34 +258,26 @@ impl Command for HrEmployeeListCommand {
         let mut conn = get_connection()?;
         let emp_service = EmployeeService::new();
 
-        let employees = match self.department_id {
-            Some(dept_id) => emp_service.list_employees_by_department(&mut conn, dept_id)?,
-            None => emp_service.list_employees(&mut conn)?,
+        let mut employees = match (&self.filter, self.department_id) {
+            (Some(filter), dept_id) => emp_service.list_employees_where(&mut conn, dept_id, filter)?,
+            (None, Some(dept_id)) => emp_service.list_employees_by_department(&mut conn, dept_id)?,
+            (None, None) => emp_service.list_employees(&mut conn)?,
         };
+        if let Some(status) = &self.status {
+            employees.retain(|e| e.employee.status.eq_ignore_ascii_case(status));
+        }
 
         if employees.is_empty() {
             println!("No employees found.");
             return Ok(());
         }
 
-        display_employees_table(&employees);
-        Ok(())
-    }
-
-    fn name(&self) -> &'static str {
-        "hr-employee-list"
-    }
-
-    fn description(&self) -> &'static str {
-        "List all employees"
-    }
-
-    fn requires_auth(&self) -> bool {
-        true
+        display_employees_table(&employees, &self.table)
     }
 }
 
 pub struct HrEmployeeAddCommand {
+    pub code: Option<String>,
     pub name: String,
     pub email: Option<String>,
     pub phone: Option<String>,
@@ -299,6 +298,7 @@ impl HrEmployeeAddCommand {
         salary: i32,
     ) -> Self {
         Self {
+            code: None,
             name,
             email,
             phone,
@@ -308,14 +308,14 @@ impl HrEmployeeAddCommand {
             salary,
         }
     }
-}
 
-impl Command for HrEmployeeAddCommand {
-    fn execute(
-        &self,
-        _args: &dyn std::any::Any,
-        user: Option<&AuthenticatedUser>,
-    ) -> CLIERPResult<()> {
+    /// Use this employee code instead of the next generated one
+    pub fn with_code(mut self, code: Option<String>) -> Self {
+        self.code = code;
+        self
+    }
+
+    pub fn execute(&self, user: Option<&AuthenticatedUser>) -> CLIERPResult<()> {
         use crate::modules::hr::employee::EmployeeService;
 
         let _user = user.ok_or_else(|| crate::core::error::CLIERPError::AuthenticationRequired)?;
@@ -324,6 +324,7 @@ impl Command for HrEmployeeAddCommand {
         let emp_service = EmployeeService::new();
 
         let request = crate::modules::hr::employee::CreateEmployeeRequest {
+            employee_code: self.code.clone(),
             name: self.name.clone(),
             email: self.email.clone(),
             phone: self.phone.clone(),
@@ -335,7 +336,7 @@ impl Command for HrEmployeeAddCommand {
 
         let employee = emp_service.create_employee(&mut conn, request)?;
 
-        println!("✅ Employee created successfully!");
+        outln!("✅ Employee created successfully!");
         println!("ID: {}", employee.id);
         println!("Code: {}", employee.employee_code);
         println!("Name: {}", employee.name);
@@ -355,37 +356,30 @@ impl Command for HrEmployeeAddCommand {
 
         Ok(())
     }
-
-    fn name(&self) -> &'static str {
-        "hr-employee-add"
-    }
-
-    fn description(&self) -> &'static str {
-        "Create a new employee"
-    }
-
-    fn requires_auth(&self) -> bool {
-        true
-    }
 }
 
 pub struct HrEmployeeShowCommand {
     pub id: Option<i32>,
     pub code: Option<String>,
+    pub history: bool,
 }
 
 impl HrEmployeeShowCommand {
     pub fn new(id: Option<i32>, code: Option<String>) -> Self {
-        Self { id, code }
+        Self {
+            id,
+            code,
+            history: false,
+        }
     }
-}
 
-impl Command for HrEmployeeShowCommand {
-    fn execute(
-        &self,
-        _args: &dyn std::any::Any,
-        user: Option<&AuthenticatedUser>,
-    ) -> CLIERPResult<()> {
+    /// Also print the employee's change history
+    pub fn with_history(mut self, history: bool) -> Self {
+        self.history = history;
+        self
+    }
+
+    pub fn execute(&self, user: Option<&AuthenticatedUser>) -> CLIERPResult<()> {
         use crate::modules::hr::employee::EmployeeService;
 
         let _user = user.ok_or_else(|| crate::core::error::CLIERPError::AuthenticationRequired)?;
@@ -406,6 +400,18 @@ impl Command for HrEmployeeShowCommand {
         match employee {
             Some(emp_with_dept) => {
                 display_employee_detail(&emp_with_dept);
+                {
+                    use crate::database::RecordType;
+                    use crate::modules::system::links::{print_related, LinkService, RecordRef};
+                    print_related(&LinkService::related(
+                        &mut conn,
+                        RecordRef::new(RecordType::Employee, emp_with_dept.employee.id),
+                    )?);
+                }
+                if self.history {
+                    use crate::modules::system::audit::{print_history, AuditService};
+                    print_history(&AuditService::history(&mut conn, "employees", emp_with_dept.employee.id)?);
+                }
             }
             None => {
                 println!("Employee not found.");
@@ -414,18 +420,6 @@ impl Command for HrEmployeeShowCommand {
 
         Ok(())
     }
-
-    fn name(&self) -> &'static str {
-        "hr-employee-show"
-    }
-
-    fn description(&self) -> &'static str {
-        "Show detailed employee information"
-    }
-
-    fn requires_auth(&self) -> bool {
-        true
-    }
 }
 
 pub struct HrEmployeeUpdateCommand {
@@ -440,6 +434,7 @@ pub struct HrEmployeeUpdateCommand {
 }
 
 impl HrEmployeeUpdateCommand {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         id: i32,
         name: Option<String>,
@@ -461,14 +456,8 @@ impl HrEmployeeUpdateCommand {
             status,
         }
     }
-}
 
-impl Command for HrEmployeeUpdateCommand {
-    fn execute(
-        &self,
-        _args: &dyn std::any::Any,
-        user: Option<&AuthenticatedUser>,
-    ) -> CLIERPResult<()> {
+    pub fn execute(&self, user: Option<&AuthenticatedUser>) -> CLIERPResult<()> {
         use crate::modules::hr::employee::EmployeeService;
 
         let _user = user.ok_or_else(|| crate::core::error::CLIERPError::AuthenticationRequired)?;
@@ -489,7 +478,7 @@ impl Command for HrEmployeeUpdateCommand {
 
         let employee = emp_service.update_employee(&mut conn, request)?;
 
-        println!("✅ Employee updated successfully!");
+        outln!("✅ Employee updated successfully!");
         println!("ID: {}", employee.id);
         println!("Code: {}", employee.employee_code);
         println!("Name: {}", employee.name);
@@ -505,18 +494,6 @@ impl Command for HrEmployeeUpdateCommand {
 
         Ok(())
     }
-
-    fn name(&self) -> &'static str {
-        "hr-employee-update"
-    }
-
-    fn description(&self) -> &'static str {
-        "Update an existing employee"
-    }
-
-    fn requires_auth(&self) -> bool {
-        true
-    }
 }
 
 pub struct HrEmployeeSearchCommand {
@@ -527,14 +504,8 @@ impl HrEmployeeSearchCommand {
     pub fn new(query: String) -> Self {
         Self { query }
     }
-}
 
-impl Command for HrEmployeeSearchCommand {
-    fn execute(
-        &self,
-        _args: &dyn std::any::Any,
-        user: Option<&AuthenticatedUser>,
-    ) -> CLIERPResult<()> {
+    pub fn execute(&self, user: Option<&AuthenticatedUser>) -> CLIERPResult<()> {
         use crate::modules::hr::employee::EmployeeService;
 
         let _user = user.ok_or_else(|| crate::core::error::CLIERPError::AuthenticationRequired)?;
@@ -550,20 +521,7 @@ impl Command for HrEmployeeSearchCommand {
         }
 
         println!("Search results for '{}':", self.query);
-        display_employees_table(&employees);
-        Ok(())
-    }
-
-    fn name(&self) -> &'static str {
-        "hr-employee-search"
-    }
-
-    fn description(&self) -> &'static str {
-        "Search employees by name, email, or code"
-    }
-
-    fn requires_auth(&self) -> bool {
-        true
+        display_employees_table(&employees, &TableArgs::default())
     }
 }
 
@@ -575,14 +533,8 @@ impl HrEmployeeDeleteCommand {
     pub fn new(id: i32) -> Self {
         Self { id }
     }
-}
 
-impl Command for HrEmployeeDeleteCommand {
-    fn execute(
-        &self,
-        _args: &dyn std::any::Any,
-        user: Option<&AuthenticatedUser>,
-    ) -> CLIERPResult<()> {
+    pub fn execute(&self, user: Option<&AuthenticatedUser>) -> CLIERPResult<()> {
         use crate::modules::hr::employee::EmployeeService;
 
         let _user = user.ok_or_else(|| crate::core::error::CLIERPError::AuthenticationRequired)?;
@@ -592,22 +544,10 @@ impl Command for HrEmployeeDeleteCommand {
 
         emp_service.delete_employee(&mut conn, self.id)?;
 
-        println!("✅ Employee terminated successfully!");
+        outln!("✅ Employee terminated successfully!");
 
         Ok(())
     }
-
-    fn name(&self) -> &'static str {
-        "hr-employee-delete"
-    }
-
-    fn description(&self) -> &'static str {
-        "Terminate an employee (soft delete)"
-    }
-
-    fn requires_auth(&self) -> bool {
-        true
-    }
 }
 
 // Export Commands
@@ -615,20 +555,25 @@ impl Command for HrEmployeeDeleteCommand {
 pub struct HrDeptExportCommand {
     pub format: String,
     pub output: Option<String>,
+    pub template: Option<String>,
 }
 
 impl HrDeptExportCommand {
     pub fn new(format: String, output: Option<String>) -> Self {
-        Self { format, output }
+        Self {
+            format,
+            output,
+            template: None,
+        }
     }
-}
 
-impl Command for HrDeptExportCommand {
-    fn execute(
-        &self,
-        _args: &dyn std::any::Any,
-        user: Option<&AuthenticatedUser>,
-    ) -> CLIERPResult<()> {
+    /// Write the CSV in this partner template's layout
+    pub fn with_template(mut self, template: Option<String>) -> Self {
+        self.template = template;
+        self
+    }
+
+    pub fn execute(&self, user: Option<&AuthenticatedUser>) -> CLIERPResult<()> {
         use crate::utils::export::ExportService;
 
         let _user = user.ok_or_else(|| crate::core::error::CLIERPError::AuthenticationRequired)?;
@@ -652,17 +597,17 @@ impl Command for HrDeptExportCommand {
         ExportService::prepare_file_path(&file_path)?;
 
         match self.format.to_lowercase().as_str() {
-            "csv" => {
-                let headers = &[
-                    "ID",
-                    "Name",
-                    "Description",
-                    "Manager ID",
-                    "Employee Count",
-                    "Created",
-                    "Updated",
-                ];
-                export_service.export_to_csv(&departments, headers, &file_path)?;
+            "csv" => match &self.template {
+                Some(name) => {
+                    let mapping = ExportTemplateService::mapping(&mut conn, name, ExportEntity::Departments)?;
+                    export_service.export_to_csv_mapped(&departments, &DEPARTMENT_CSV_HEADERS, &mapping, &file_path)?;
+                }
+                None => export_service.export_to_csv(&departments, &DEPARTMENT_CSV_HEADERS, &file_path)?,
+            },
+            _ if self.template.is_some() => {
+                return Err(crate::core::error::CLIERPError::ValidationError(
+                    "Export templates only apply to CSV exports.".to_string(),
+                ));
             }
             "json" => {
                 export_service.export_to_json(&departments, &file_path)?;
@@ -674,29 +619,19 @@ impl Command for HrDeptExportCommand {
             }
         }
 
-        println!("✅ Departments exported successfully to: {}", file_path);
-        println!("📊 Exported {} departments", departments.len());
+        outln!("✅ Departments exported successfully to: {}", file_path);
+        outln!("📊 Exported {} departments", departments.len());
 
         Ok(())
     }
-
-    fn name(&self) -> &'static str {
-        "hr-dept-export"
-    }
-
-    fn description(&self) -> &'static str {
-        "Export departments to CSV or JSON"
-    }
-
-    fn requires_auth(&self) -> bool {
-        true
-    }
 }
 
 pub struct HrEmployeeExportCommand {
     pub format: String,
     pub output: Option<String>,
     pub department_id: Option<i32>,
+    pub template: Option<String>,
+    pub filter: Option<FilterExpr>,
 }
 
 impl HrEmployeeExportCommand {
@@ -705,16 +640,24 @@ impl HrEmployeeExportCommand {
             format,
             output,
             department_id,
+            template: None,
+            filter: None,
         }
     }
-}
 
-impl Command for HrEmployeeExportCommand {
-    fn execute(
-        &self,
-        _args: &dyn std::any::Any,
-        user: Option<&AuthenticatedUser>,
-    ) -> CLIERPResult<()> {
+    /// Write the CSV in this partner template's layout
+    pub fn with_template(mut self, template: Option<String>) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Only export employees matching a `--where` expression
+    pub fn with_filter(mut self, filter: Option<FilterExpr>) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn execute(&self, user: Option<&AuthenticatedUser>) -> CLIERPResult<()> {
         use crate::modules::hr::employee::EmployeeService;
         use crate::utils::export::ExportService;
 
@@ -723,9 +666,10 @@ impl Command for HrEmployeeExportCommand {
         let mut conn = get_connection()?;
         let emp_service = EmployeeService::new();
 
-        let employees = match self.department_id {
-            Some(dept_id) => emp_service.list_employees_by_department(&mut conn, dept_id)?,
-            None => emp_service.list_employees(&mut conn)?,
+        let employees = match (&self.filter, self.department_id) {
+            (Some(filter), dept_id) => emp_service.list_employees_where(&mut conn, dept_id, filter)?,
+            (None, Some(dept_id)) => emp_service.list_employees_by_department(&mut conn, dept_id)?,
+            (None, None) => emp_service.list_employees(&mut conn)?,
         };
 
         if employees.is_empty() {
@@ -748,22 +692,17 @@ impl Command for HrEmployeeExportCommand {
         ExportService::prepare_file_path(&file_path)?;
 
         match self.format.to_lowercase().as_str() {
-            "csv" => {
-                let headers = &[
-                    "ID",
-                    "Code",
-                    "Name",
-                    "Email",
-                    "Phone",
-                    "Department",
-                    "Position",
-                    "Salary",
-                    "Status",
-                    "Hire Date",
-                    "Created",
-                    "Updated",
-                ];
-                export_service.export_to_csv(&employees, headers, &file_path)?;
+            "csv" => match &self.template {
+                Some(name) => {
+                    let mapping = ExportTemplateService::mapping(&mut conn, name, ExportEntity::Employees)?;
+                    export_service.export_to_csv_mapped(&employees, &EMPLOYEE_CSV_HEADERS, &mapping, &file_path)?;
+                }
+                None => export_service.export_to_csv(&employees, &EMPLOYEE_CSV_HEADERS, &file_path)?,
+            },
+            _ if self.template.is_some() => {
+                return Err(crate::core::error::CLIERPError::ValidationError(
+                    "Export templates only apply to CSV exports.".to_string(),
+                ));
             }
             "json" => {
                 export_service.export_to_json(&employees, &file_path)?;
@@ -775,28 +714,16 @@ impl Command for HrEmployeeExportCommand {
             }
         }
 
-        println!("✅ Employees exported successfully to: {}", file_path);
-        println!("📊 Exported {} employees", employees.len());
+        outln!("✅ Employees exported successfully to: {}", file_path);
+        outln!("📊 Exported {} employees", employees.len());
 
         Ok(())
     }
-
-    fn name(&self) -> &'static str {
-        "hr-employee-export"
-    }
-
-    fn description(&self) -> &'static str {
-        "Export employees to CSV or JSON"
-    }
-
-    fn requires_auth(&self) -> bool {
-        true
-    }
 }
 
 // Helper functions
 
-fn display_departments_table(departments: &[DepartmentWithEmployeeCount]) {
+fn display_departments_table(departments: &[DepartmentWithEmployeeCount], table: &TableArgs) -> CLIERPResult<()> {
     let headers = vec![
         "ID",
         "Name",
@@ -831,10 +758,16 @@ fn display_departments_table(departments: &[DepartmentWithEmployeeCount]) {
         })
         .collect();
 
-    format_table(&headers[..], &rows);
+    let mut view = TableView::new(&headers);
+    view.rows = rows;
+    view.apply(table)?;
+    pager::page(&view.render())
 }
 
-fn display_employees_table(employees: &[crate::modules::hr::employee::EmployeeWithDepartment]) {
+fn display_employees_table(
+    employees: &[crate::modules::hr::employee::EmployeeWithDepartment],
+    table: &TableArgs,
+) -> CLIERPResult<()> {
     let headers = vec![
         "ID",
         "Code",
@@ -871,12 +804,15 @@ fn display_employees_table(employees: &[crate::modules::hr::employee::EmployeeWi
         })
         .collect();
 
-    format_table(&headers[..], &rows);
+    let mut view = TableView::new(&headers);
+    view.rows = rows;
+    view.apply(table)?;
+    pager::page(&view.render())
 }
 
 fn display_employee_detail(emp_with_dept: &crate::modules::hr::employee::EmployeeWithDepartment) {
-    println!("📋 Employee Details");
-    println!("═══════════════════");
+    outln!("📋 Employee Details");
+    outln!("═══════════════════");
     println!("ID: {}", emp_with_dept.employee.id);
     println!("Code: {}", emp_with_dept.employee.employee_code);
     println!("Name: {}", emp_with_dept.employee.name);
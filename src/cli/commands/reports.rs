@@ -1,545 +1,908 @@
-use clap::{Arg, ArgMatches, Command};
-use chrono::NaiveDate;
+use clap::{Args, Subcommand};
+use once_cell::sync::OnceCell;
 use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
 
+use crate::cli::tui::pager;
+use crate::core::error::CLIERPError;
 use crate::core::result::CLIERPResult;
 use crate::modules::reporting::*;
-use crate::utils::formatting::format_date;
-
-pub fn reports_command() -> Command {
-    Command::new("reports")
-        .alias("report")
-        .about("Generate various reports")
-        .subcommand_required(true)
-        .subcommands([
-            hr_reports_commands(),
-            finance_reports_commands(),
-            inventory_reports_commands(),
-            crm_reports_commands(),
-        ])
+use crate::utils::dates::{parse_date, parse_period, parse_period_arg};
+use crate::utils::formatting::for_output;
+use crate::outln;
+
+#[derive(Debug, Subcommand)]
+pub enum ReportsCommands {
+    /// HR reports
+    Hr {
+        #[command(subcommand)]
+        action: HrReportCommands,
+    },
+    /// Finance reports
+    #[command(alias = "fin")]
+    Finance {
+        #[command(subcommand)]
+        action: FinanceReportCommands,
+    },
+    /// Inventory reports
+    #[command(alias = "inv")]
+    Inventory {
+        #[command(subcommand)]
+        action: InventoryReportCommands,
+    },
+    /// CRM reports
+    Crm {
+        #[command(subcommand)]
+        action: CrmReportCommands,
+    },
+    /// Generate the monthly report pack, running the reports concurrently
+    Pack {
+        /// Period (last-month, this-quarter, FY2025-Q2 or YYYY-MM)
+        #[arg(long)]
+        period: String,
+        #[command(flatten)]
+        output: FormatArgs,
+    },
+    /// List saved report snapshots
+    Snapshots {
+        /// Number of snapshots to show
+        #[arg(short, long, default_value = "20")]
+        limit: i64,
+    },
+    /// Compare the key metrics of two saved snapshots of the same report
+    Compare {
+        /// Earlier snapshot (name or ID)
+        #[arg(long)]
+        a: String,
+        /// Later snapshot (name or ID)
+        #[arg(long)]
+        b: String,
+    },
+    /// Render every report in every format against the seed dataset and
+    /// compare with the golden files (developer command)
+    #[cfg(feature = "test-support")]
+    VerifyGolden {
+        /// Directory of the golden files
+        #[arg(long, default_value = crate::testing::GOLDEN_DIR)]
+        dir: PathBuf,
+        /// Rewrite golden files that differ instead of failing
+        #[arg(long)]
+        update: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HrReportCommands {
+    /// Generate employee summary report
+    EmployeeSummary {
+        /// Filter by department ID
+        #[arg(long)]
+        department: Option<i32>,
+        #[command(flatten)]
+        dates: DateRangeArgs,
+        #[command(flatten)]
+        output: FormatArgs,
+    },
+    /// Month-end headcount by department (default: last 12 months)
+    Headcount {
+        #[command(flatten)]
+        period: HrPeriodArgs,
+    },
+    /// Joiners, leavers and turnover rate per month (default: last 12 months)
+    Turnover {
+        #[command(flatten)]
+        period: HrPeriodArgs,
+    },
+    /// Leave taken against leave earned, by department (default: last 12 months)
+    Leave {
+        #[command(flatten)]
+        period: HrPeriodArgs,
+        /// Leave days earned per year of employment (default 15)
+        #[arg(long)]
+        annual_leave_days: Option<u32>,
+    },
+    /// Employees missing required documents or holding expired ones, by department
+    Compliance {
+        /// Filter by department ID
+        #[arg(long)]
+        department: Option<i32>,
+        #[command(flatten)]
+        output: FormatArgs,
+    },
+    /// Attendance and lateness summary per month and employee (default: this month)
+    Attendance {
+        /// Filter by employee ID
+        #[arg(long)]
+        employee: Option<i32>,
+        /// Filter by department ID
+        #[arg(long)]
+        department: Option<i32>,
+        #[command(flatten)]
+        dates: DateRangeArgs,
+        #[command(flatten)]
+        output: FormatArgs,
+    },
+    /// Generate payroll report
+    Payroll {
+        /// Payroll period (YYYY-MM, this-month, last-month)
+        #[arg(long, value_parser = parse_period_arg)]
+        period: Option<String>,
+        /// Filter by department ID
+        #[arg(long)]
+        department: Option<i32>,
+        #[command(flatten)]
+        output: FormatArgs,
+    },
 }
 
-fn hr_reports_commands() -> Command {
-    Command::new("hr")
-        .about("HR reports")
-        .subcommand_required(true)
-        .subcommands([
-            Command::new("employee-summary")
-                .about("Generate employee summary report")
-                .args([
-                    Arg::new("department")
-                        .long("department")
-                        .value_parser(clap::value_parser!(i32))
-                        .help("Filter by department ID"),
-                    Arg::new("start-date")
-                        .long("start-date")
-                        .help("Start date (YYYY-MM-DD)"),
-                    Arg::new("end-date")
-                        .long("end-date")
-                        .help("End date (YYYY-MM-DD)"),
-                    Arg::new("format")
-                        .long("format")
-                        .value_parser(["json", "csv", "html", "text"])
-                        .default_value("text")
-                        .help("Output format"),
-                ]),
-            Command::new("attendance")
-                .about("Generate attendance report")
-                .args([
-                    Arg::new("employee")
-                        .long("employee")
-                        .value_parser(clap::value_parser!(i32))
-                        .help("Filter by employee ID"),
-                    Arg::new("department")
-                        .long("department")
-                        .value_parser(clap::value_parser!(i32))
-                        .help("Filter by department ID"),
-                    Arg::new("start-date")
-                        .long("start-date")
-                        .help("Start date (YYYY-MM-DD)"),
-                    Arg::new("end-date")
-                        .long("end-date")
-                        .help("End date (YYYY-MM-DD)"),
-                    Arg::new("format")
-                        .long("format")
-                        .value_parser(["json", "csv", "html", "text"])
-                        .default_value("text")
-                        .help("Output format"),
-                ]),
-            Command::new("payroll")
-                .about("Generate payroll report")
-                .args([
-                    Arg::new("period")
-                        .long("period")
-                        .help("Payroll period (YYYY-MM)"),
-                    Arg::new("department")
-                        .long("department")
-                        .value_parser(clap::value_parser!(i32))
-                        .help("Filter by department ID"),
-                    Arg::new("format")
-                        .long("format")
-                        .value_parser(["json", "csv", "html", "text"])
-                        .default_value("text")
-                        .help("Output format"),
-                ]),
-        ])
+#[derive(Debug, Subcommand)]
+pub enum FinanceReportCommands {
+    /// Generate income statement
+    IncomeStatement {
+        #[command(flatten)]
+        period: FiscalPeriodArgs,
+    },
+    /// Generate balance sheet
+    BalanceSheet {
+        /// As of date (YYYY-MM-DD, today, yesterday)
+        #[arg(long, visible_alias = "as-of")]
+        date: String,
+        #[command(flatten)]
+        output: FormatArgs,
+    },
+    /// Generate cash flow statement
+    CashFlow {
+        #[command(flatten)]
+        period: FiscalPeriodArgs,
+    },
+    /// Gross margin of shipped stock by product, category and customer
+    GrossMargin {
+        /// Costing method of the cost of goods sold
+        #[arg(long, value_parser = ["fifo", "lifo", "average", "standard"], default_value = "fifo")]
+        method: String,
+        /// Filter by category ID
+        #[arg(long)]
+        category: Option<i32>,
+        #[command(flatten)]
+        period: FiscalPeriodArgs,
+    },
 }
 
-fn finance_reports_commands() -> Command {
-    Command::new("finance")
-        .alias("fin")
-        .about("Finance reports")
-        .subcommand_required(true)
-        .subcommands([
-            Command::new("income-statement")
-                .about("Generate income statement")
-                .args([
-                    Arg::new("start-date")
-                        .long("start-date")
-                        .required(true)
-                        .help("Start date (YYYY-MM-DD)"),
-                    Arg::new("end-date")
-                        .long("end-date")
-                        .required(true)
-                        .help("End date (YYYY-MM-DD)"),
-                    Arg::new("format")
-                        .long("format")
-                        .value_parser(["json", "csv", "html", "text"])
-                        .default_value("text")
-                        .help("Output format"),
-                ]),
-            Command::new("balance-sheet")
-                .about("Generate balance sheet")
-                .args([
-                    Arg::new("date")
-                        .long("date")
-                        .required(true)
-                        .help("As of date (YYYY-MM-DD)"),
-                    Arg::new("format")
-                        .long("format")
-                        .value_parser(["json", "csv", "html", "text"])
-                        .default_value("text")
-                        .help("Output format"),
-                ]),
-            Command::new("cash-flow")
-                .about("Generate cash flow statement")
-                .args([
-                    Arg::new("start-date")
-                        .long("start-date")
-                        .required(true)
-                        .help("Start date (YYYY-MM-DD)"),
-                    Arg::new("end-date")
-                        .long("end-date")
-                        .required(true)
-                        .help("End date (YYYY-MM-DD)"),
-                    Arg::new("format")
-                        .long("format")
-                        .value_parser(["json", "csv", "html", "text"])
-                        .default_value("text")
-                        .help("Output format"),
-                ]),
-        ])
+#[derive(Debug, Subcommand)]
+pub enum InventoryReportCommands {
+    /// Generate stock levels report
+    StockLevels {
+        /// Filter by category ID
+        #[arg(long)]
+        category: Option<i32>,
+        /// Show only low stock items
+        #[arg(long)]
+        low_stock_only: bool,
+        #[command(flatten)]
+        output: FormatArgs,
+    },
+    /// Generate stock movement report
+    Movement {
+        /// Filter by product ID
+        #[arg(long)]
+        product: Option<i32>,
+        #[command(flatten)]
+        dates: DateRangeArgs,
+        #[command(flatten)]
+        output: FormatArgs,
+    },
+    /// Generate inventory valuation report
+    Valuation {
+        /// As of date (YYYY-MM-DD, yesterday, default: today)
+        #[arg(long, visible_alias = "as-of")]
+        date: Option<String>,
+        /// Valuation method
+        #[arg(long, value_parser = ["fifo", "lifo", "average"], default_value = "fifo")]
+        method: String,
+        #[command(flatten)]
+        output: FormatArgs,
+    },
+    /// Generate inventory aging report by receipt date
+    Aging {
+        /// Filter by category ID
+        #[arg(long)]
+        category: Option<i32>,
+        #[command(flatten)]
+        output: FormatArgs,
+    },
+    /// Match deals to their drop-ship and back-to-back purchase orders
+    Reconciliation {
+        #[command(flatten)]
+        dates: DateRangeArgs,
+        #[command(flatten)]
+        output: FormatArgs,
+    },
 }
 
-fn inventory_reports_commands() -> Command {
-    Command::new("inventory")
-        .alias("inv")
-        .about("Inventory reports")
-        .subcommand_required(true)
-        .subcommands([
-            Command::new("stock-levels")
-                .about("Generate stock levels report")
-                .args([
-                    Arg::new("category")
-                        .long("category")
-                        .value_parser(clap::value_parser!(i32))
-                        .help("Filter by category ID"),
-                    Arg::new("low-stock-only")
-                        .long("low-stock-only")
-                        .action(clap::ArgAction::SetTrue)
-                        .help("Show only low stock items"),
-                    Arg::new("format")
-                        .long("format")
-                        .value_parser(["json", "csv", "html", "text"])
-                        .default_value("text")
-                        .help("Output format"),
-                ]),
-            Command::new("movement")
-                .about("Generate stock movement report")
-                .args([
-                    Arg::new("product")
-                        .long("product")
-                        .value_parser(clap::value_parser!(i32))
-                        .help("Filter by product ID"),
-                    Arg::new("start-date")
-                        .long("start-date")
-                        .help("Start date (YYYY-MM-DD)"),
-                    Arg::new("end-date")
-                        .long("end-date")
-                        .help("End date (YYYY-MM-DD)"),
-                    Arg::new("format")
-                        .long("format")
-                        .value_parser(["json", "csv", "html", "text"])
-                        .default_value("text")
-                        .help("Output format"),
-                ]),
-            Command::new("valuation")
-                .about("Generate inventory valuation report")
-                .args([
-                    Arg::new("date")
-                        .long("date")
-                        .help("As of date (YYYY-MM-DD, default: today)"),
-                    Arg::new("method")
-                        .long("method")
-                        .value_parser(["fifo", "lifo", "average"])
-                        .default_value("fifo")
-                        .help("Valuation method"),
-                    Arg::new("format")
-                        .long("format")
-                        .value_parser(["json", "csv", "html", "text"])
-                        .default_value("text")
-                        .help("Output format"),
-                ]),
-        ])
+#[derive(Debug, Subcommand)]
+pub enum CrmReportCommands {
+    /// Generate sales performance report
+    SalesPerformance {
+        /// Filter by employee ID
+        #[arg(long)]
+        employee: Option<i32>,
+        #[command(flatten)]
+        dates: DateRangeArgs,
+        #[command(flatten)]
+        output: FormatArgs,
+    },
+    /// Generate sales pipeline report
+    Pipeline {
+        #[command(flatten)]
+        output: FormatArgs,
+    },
+    /// Generate customer analysis report
+    CustomerAnalysis {
+        /// Filter by customer type
+        #[arg(long, value_parser = ["individual", "business"])]
+        customer_type: Option<String>,
+        #[command(flatten)]
+        output: FormatArgs,
+    },
 }
 
-fn crm_reports_commands() -> Command {
-    Command::new("crm")
-        .about("CRM reports")
-        .subcommand_required(true)
-        .subcommands([
-            Command::new("sales-performance")
-                .about("Generate sales performance report")
-                .args([
-                    Arg::new("employee")
-                        .long("employee")
-                        .value_parser(clap::value_parser!(i32))
-                        .help("Filter by employee ID"),
-                    Arg::new("start-date")
-                        .long("start-date")
-                        .help("Start date (YYYY-MM-DD)"),
-                    Arg::new("end-date")
-                        .long("end-date")
-                        .help("End date (YYYY-MM-DD)"),
-                    Arg::new("format")
-                        .long("format")
-                        .value_parser(["json", "csv", "html", "text"])
-                        .default_value("text")
-                        .help("Output format"),
-                ]),
-            Command::new("pipeline")
-                .about("Generate sales pipeline report")
-                .args([
-                    Arg::new("format")
-                        .long("format")
-                        .value_parser(["json", "csv", "html", "text"])
-                        .default_value("text")
-                        .help("Output format"),
-                ]),
-            Command::new("customer-analysis")
-                .about("Generate customer analysis report")
-                .args([
-                    Arg::new("customer-type")
-                        .long("customer-type")
-                        .value_parser(["individual", "business"])
-                        .help("Filter by customer type"),
-                    Arg::new("format")
-                        .long("format")
-                        .value_parser(["json", "csv", "html", "text"])
-                        .default_value("text")
-                        .help("Output format"),
-                ]),
-        ])
+static DEFAULT_FORMAT: OnceCell<String> = OnceCell::new();
+
+/// Install the logged-in user's report format for the rest of the process
+pub fn set_default_format(format: String) {
+    let _ = DEFAULT_FORMAT.set(format);
 }
 
-pub fn handle_reports_command(matches: &ArgMatches) -> CLIERPResult<()> {
-    match matches.subcommand() {
-        Some(("hr", sub_matches)) => handle_hr_reports(sub_matches),
-        Some(("finance", sub_matches)) => handle_finance_reports(sub_matches),
-        Some(("inventory", sub_matches)) => handle_inventory_reports(sub_matches),
-        Some(("crm", sub_matches)) => handle_crm_reports(sub_matches),
-        _ => {
-            println!("Available report modules:");
-            println!("  hr        - Human Resources reports");
-            println!("  finance   - Financial reports");
-            println!("  inventory - Inventory reports");
-            println!("  crm       - Customer Relationship Management reports");
-            println!();
-            println!("Use 'clierp reports <module> --help' for more information");
-            Ok(())
+#[derive(Debug, Clone, Args)]
+pub struct FormatArgs {
+    /// Output format (default: your output_format preference, else text)
+    #[arg(long, value_parser = ["json", "csv", "html", "text", "pdf"])]
+    pub format: Option<String>,
+    /// Write the report to this file instead of the terminal; required for pdf
+    #[arg(long = "output", value_name = "FILE")]
+    pub output_file: Option<PathBuf>,
+}
+
+impl FormatArgs {
+    /// The format asked for, else the user's preference, else text
+    pub fn format(&self) -> String {
+        self.format
+            .clone()
+            .or_else(|| DEFAULT_FORMAT.get().cloned())
+            .unwrap_or_else(|| "text".to_string())
+    }
+
+    /// Refuse a PDF report without a file to write it to
+    fn check(&self) -> CLIERPResult<()> {
+        if self.format() == "pdf" && self.output_file.is_none() {
+            return Err(pdf_needs_file());
         }
+        Ok(())
     }
 }
 
-fn handle_hr_reports(matches: &ArgMatches) -> CLIERPResult<()> {
-    let mut conn = crate::database::get_connection()?;
-    let generator = HRReportsGenerator;
+#[derive(Debug, Clone, Args)]
+pub struct DateRangeArgs {
+    /// Start date (YYYY-MM-DD, "2 weeks ago", last monday)
+    #[arg(long, visible_alias = "from")]
+    pub start_date: Option<String>,
+    /// End date (YYYY-MM-DD, today, yesterday)
+    #[arg(long, visible_alias = "to")]
+    pub end_date: Option<String>,
+}
+
+/// Department, date range and format arguments of the workforce reports
+#[derive(Debug, Clone, Args)]
+pub struct HrPeriodArgs {
+    /// Filter by department ID
+    #[arg(long)]
+    pub department: Option<i32>,
+    /// Start date (YYYY-MM-DD, "2 weeks ago", last monday)
+    #[arg(long, visible_alias = "from", requires = "end_date")]
+    pub start_date: Option<String>,
+    /// End date (YYYY-MM-DD, today, yesterday)
+    #[arg(long, visible_alias = "to", requires = "start_date")]
+    pub end_date: Option<String>,
+    #[command(flatten)]
+    pub output: FormatArgs,
+}
+
+/// Date range or fiscal period of the finance statements
+#[derive(Debug, Clone, Args)]
+pub struct FiscalPeriodArgs {
+    /// Start date (YYYY-MM-DD, "2 weeks ago", last monday)
+    #[arg(long, visible_alias = "from", required_unless_present = "fiscal_period")]
+    pub start_date: Option<String>,
+    /// End date (YYYY-MM-DD, today, yesterday)
+    #[arg(long, visible_alias = "to", required_unless_present = "fiscal_period")]
+    pub end_date: Option<String>,
+    /// Period (last-month, this-quarter, ytd, FY2025-Q2, FY2025-P03 or YYYY-MM)
+    #[arg(long, visible_alias = "period", conflicts_with_all = ["start_date", "end_date"])]
+    pub fiscal_period: Option<String>,
+    #[command(flatten)]
+    pub output: FormatArgs,
+}
 
-    match matches.subcommand() {
-        Some(("employee-summary", sub_matches)) => {
-            let mut config = create_report_config("employee_summary", sub_matches)?;
-            let result = generator.generate_report(config)?;
-            display_report_result(&result, sub_matches)?;
+/// Generator id and report title of each report in the monthly pack
+const MONTHLY_PACK: &[(&str, &str)] = &[
+    ("finance", "income_statement"),
+    ("finance", "balance_sheet"),
+    ("finance", "cash_flow"),
+    ("hr", "payroll_report"),
+    ("inventory", "inventory_valuation"),
+    ("crm", "sales_pipeline"),
+];
+
+/// Filters and output options of one report run
+#[derive(Debug, Default)]
+struct ReportOptions {
+    department: Option<i32>,
+    employee: Option<i32>,
+    category: Option<i32>,
+    product: Option<i32>,
+    customer_type: Option<String>,
+    period: Option<String>,
+    method: Option<String>,
+    annual_leave_days: Option<u32>,
+    low_stock_only: bool,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    fiscal_period: Option<String>,
+    date: Option<String>,
+    format: String,
+    output_file: Option<PathBuf>,
+}
+
+impl ReportOptions {
+    fn formatted(output: FormatArgs) -> Self {
+        Self {
+            format: output.format(),
+            output_file: output.output_file,
+            ..Default::default()
         }
-        Some(("attendance", sub_matches)) => {
-            let mut config = create_report_config("attendance_report", sub_matches)?;
-            let result = generator.generate_report(config)?;
-            display_report_result(&result, sub_matches)?;
+    }
+
+    fn dated(dates: DateRangeArgs, output: FormatArgs) -> Self {
+        Self {
+            start_date: dates.start_date,
+            end_date: dates.end_date,
+            ..Self::formatted(output)
         }
-        Some(("payroll", sub_matches)) => {
-            let mut config = create_report_config("payroll_report", sub_matches)?;
-            let result = generator.generate_report(config)?;
-            display_report_result(&result, sub_matches)?;
+    }
+
+    fn hr_period(period: HrPeriodArgs) -> Self {
+        Self {
+            department: period.department,
+            start_date: period.start_date,
+            end_date: period.end_date,
+            ..Self::formatted(period.output)
         }
-        _ => {
-            println!("Available HR reports:");
-            println!("  employee-summary - Employee summary report");
-            println!("  attendance       - Attendance report");
-            println!("  payroll          - Payroll report");
+    }
+
+    fn fiscal(period: FiscalPeriodArgs) -> Self {
+        Self {
+            start_date: period.start_date,
+            end_date: period.end_date,
+            fiscal_period: period.fiscal_period,
+            ..Self::formatted(period.output)
         }
     }
-    Ok(())
 }
 
-fn handle_finance_reports(matches: &ArgMatches) -> CLIERPResult<()> {
-    let mut conn = crate::database::get_connection()?;
-    let generator = FinanceReportsGenerator;
-
-    match matches.subcommand() {
-        Some(("income-statement", sub_matches)) => {
-            let mut config = create_report_config("income_statement", sub_matches)?;
-            let result = generator.generate_report(config)?;
-            display_report_result(&result, sub_matches)?;
-        }
-        Some(("balance-sheet", sub_matches)) => {
-            let mut config = create_report_config("balance_sheet", sub_matches)?;
-            let result = generator.generate_report(config)?;
-            display_report_result(&result, sub_matches)?;
-        }
-        Some(("cash-flow", sub_matches)) => {
-            let mut config = create_report_config("cash_flow", sub_matches)?;
-            let result = generator.generate_report(config)?;
-            display_report_result(&result, sub_matches)?;
+pub fn execute_reports_command(action: ReportsCommands, save: Option<&str>) -> CLIERPResult<()> {
+    let (report_id, title, options) = match action {
+        ReportsCommands::Hr { action } => match action {
+            HrReportCommands::EmployeeSummary { department, dates, output } => (
+                "hr",
+                "employee_summary",
+                ReportOptions { department, ..ReportOptions::dated(dates, output) },
+            ),
+            HrReportCommands::Headcount { period } => ("hr", "headcount", ReportOptions::hr_period(period)),
+            HrReportCommands::Turnover { period } => ("hr", "turnover", ReportOptions::hr_period(period)),
+            HrReportCommands::Leave { period, annual_leave_days } => (
+                "hr",
+                "leave_utilization",
+                ReportOptions { annual_leave_days, ..ReportOptions::hr_period(period) },
+            ),
+            HrReportCommands::Compliance { department, output } => (
+                "hr",
+                "document_compliance",
+                ReportOptions { department, ..ReportOptions::formatted(output) },
+            ),
+            HrReportCommands::Attendance { employee, department, dates, output } => (
+                "hr",
+                "attendance_report",
+                ReportOptions { employee, department, ..ReportOptions::dated(dates, output) },
+            ),
+            HrReportCommands::Payroll { period, department, output } => (
+                "hr",
+                "payroll_report",
+                ReportOptions { period, department, ..ReportOptions::formatted(output) },
+            ),
+        },
+        ReportsCommands::Finance { action } => match action {
+            FinanceReportCommands::IncomeStatement { period } => {
+                ("finance", "income_statement", ReportOptions::fiscal(period))
+            }
+            FinanceReportCommands::BalanceSheet { date, output } => (
+                "finance",
+                "balance_sheet",
+                ReportOptions { date: Some(date), ..ReportOptions::formatted(output) },
+            ),
+            FinanceReportCommands::CashFlow { period } => ("finance", "cash_flow", ReportOptions::fiscal(period)),
+            FinanceReportCommands::GrossMargin { method, category, period } => (
+                "finance",
+                "gross_margin",
+                ReportOptions { method: Some(method), category, ..ReportOptions::fiscal(period) },
+            ),
+        },
+        ReportsCommands::Inventory { action } => match action {
+            InventoryReportCommands::StockLevels { category, low_stock_only, output } => (
+                "inventory",
+                "stock_levels",
+                ReportOptions { category, low_stock_only, ..ReportOptions::formatted(output) },
+            ),
+            InventoryReportCommands::Movement { product, dates, output } => (
+                "inventory",
+                "stock_movement",
+                ReportOptions { product, ..ReportOptions::dated(dates, output) },
+            ),
+            InventoryReportCommands::Valuation { date, method, output } => (
+                "inventory",
+                "inventory_valuation",
+                ReportOptions { date, method: Some(method), ..ReportOptions::formatted(output) },
+            ),
+            InventoryReportCommands::Aging { category, output } => (
+                "inventory",
+                "inventory_aging",
+                ReportOptions { category, ..ReportOptions::formatted(output) },
+            ),
+            InventoryReportCommands::Reconciliation { dates, output } => {
+                ("inventory", "order_reconciliation", ReportOptions::dated(dates, output))
+            }
+        },
+        ReportsCommands::Crm { action } => match action {
+            CrmReportCommands::SalesPerformance { employee, dates, output } => (
+                "crm",
+                "sales_performance",
+                ReportOptions { employee, ..ReportOptions::dated(dates, output) },
+            ),
+            CrmReportCommands::Pipeline { output } => ("crm", "sales_pipeline", ReportOptions::formatted(output)),
+            CrmReportCommands::CustomerAnalysis { customer_type, output } => (
+                "crm",
+                "customer_analysis",
+                ReportOptions { customer_type, ..ReportOptions::formatted(output) },
+            ),
+        },
+        ReportsCommands::Pack { period, output } => {
+            output.check()?;
+            return generate_report_pack(&period, &output.format(), output.output_file.as_deref(), save);
         }
-        _ => {
-            println!("Available Finance reports:");
-            println!("  income-statement - Income Statement");
-            println!("  balance-sheet    - Balance Sheet");
-            println!("  cash-flow        - Cash Flow Statement");
+        ReportsCommands::Snapshots { limit } => return list_report_snapshots(limit),
+        ReportsCommands::Compare { a, b } => return compare_report_snapshots(&a, &b),
+        #[cfg(feature = "test-support")]
+        ReportsCommands::VerifyGolden { dir, update } => return verify_golden_files(&dir, update),
+    };
+
+    if options.format == "pdf" && options.output_file.is_none() {
+        return Err(pdf_needs_file());
+    }
+    let output_file = options.output_file.clone();
+    let engine = ReportEngine::with_default_generators();
+    let config = create_report_config(title, options)?;
+    let result = engine.generate_report(report_id, config)?;
+    finish_report(report_id, &result, save, output_file.as_deref())
+}
+
+fn generate_report_pack(period: &str, format: &str, output_file: Option<&Path>, save: Option<&str>) -> CLIERPResult<()> {
+    let (start_date, end_date) = parse_period(period)?;
+    let format = report_format(format);
+
+    let requests: Vec<ReportRequest> = MONTHLY_PACK
+        .iter()
+        .map(|(report_id, title)| ReportRequest {
+            report_id: report_id.to_string(),
+            config: ReportConfig {
+                title: title.to_string(),
+                description: Some(format!("Generated {} report", title.replace('_', " "))),
+                date_range: Some(DateRange { start_date, end_date }),
+                filters: HashMap::from([("fiscal_period".to_string(), period.to_string())]),
+                format: format.clone(),
+                include_charts: false,
+                include_summary: true,
+            },
+        })
+        .collect();
+
+    let engine = ReportEngine::with_default_generators();
+    let started = std::time::Instant::now();
+    let pack = engine.generate_reports(requests);
+    let results = match tokio::runtime::Handle::try_current() {
+        Ok(handle) => tokio::task::block_in_place(|| handle.block_on(pack)),
+        Err(_) => tokio::runtime::Runtime::new()?.block_on(pack),
+    };
+
+    let mut failed = 0;
+    let mut reports = Vec::new();
+    for ((report_id, title), result) in MONTHLY_PACK.iter().zip(results) {
+        match result {
+            Ok(report) => {
+                if output_file.is_none() {
+                    display_report_result(&report, None)?;
+                }
+                // One snapshot per report, e.g. "2025-03.balance_sheet"
+                if let Some(name) = save {
+                    let mut conn = crate::database::get_connection()?;
+                    let actor = crate::modules::system::AuditService::actor();
+                    ReportSnapshotService::save(&mut conn, &format!("{}.{}", name, title), report_id, &report, actor)?;
+                }
+                reports.push(report);
+            }
+            Err(e) => {
+                failed += 1;
+                eprintln!("{}", for_output(&format!("❌ {} failed: {}", title.replace('_', " "), e)));
+            }
         }
+        println!();
+    }
+    // The whole pack goes into one file, one report after the other
+    if let Some(file) = output_file {
+        let contents = match format {
+            ReportFormat::Pdf => render_pdf(&reports.iter().collect::<Vec<_>>()),
+            _ => reports
+                .iter()
+                .map(render_report)
+                .collect::<CLIERPResult<Vec<_>>>()?
+                .join("\n")
+                .into_bytes(),
+        };
+        write_report_file(file, &contents)?;
     }
+    println!(
+        "Generated {} of {} reports in {} ms",
+        MONTHLY_PACK.len() - failed,
+        MONTHLY_PACK.len(),
+        started.elapsed().as_millis()
+    );
     Ok(())
 }
 
-fn handle_inventory_reports(matches: &ArgMatches) -> CLIERPResult<()> {
+fn list_report_snapshots(limit: i64) -> CLIERPResult<()> {
     let mut conn = crate::database::get_connection()?;
-    let generator = InventoryReportsGenerator;
+    let snapshots = ReportSnapshotService::list(&mut conn, limit)?;
 
-    match matches.subcommand() {
-        Some(("stock-levels", sub_matches)) => {
-            let mut config = create_report_config("stock_levels", sub_matches)?;
-            let result = generator.generate_report(config)?;
-            display_report_result(&result, sub_matches)?;
-        }
-        Some(("movement", sub_matches)) => {
-            let mut config = create_report_config("stock_movement", sub_matches)?;
-            let result = generator.generate_report(config)?;
-            display_report_result(&result, sub_matches)?;
-        }
-        Some(("valuation", sub_matches)) => {
-            let mut config = create_report_config("inventory_valuation", sub_matches)?;
-            let result = generator.generate_report(config)?;
-            display_report_result(&result, sub_matches)?;
-        }
-        _ => {
-            println!("Available Inventory reports:");
-            println!("  stock-levels - Current stock levels");
-            println!("  movement     - Stock movement history");
-            println!("  valuation    - Inventory valuation");
-        }
+    if snapshots.is_empty() {
+        println!("No report snapshots saved. Use --save <name> when running a report.");
+        return Ok(());
     }
+
+    use tabled::{settings::Style, builder::Builder};
+    let mut builder = Builder::default();
+    builder.push_record(["ID", "Name", "Report", "Saved"]);
+    for snapshot in &snapshots {
+        builder.push_record([
+            snapshot.id.to_string(),
+            snapshot.name.clone(),
+            format!("{} / {}", snapshot.report_id, snapshot.title.replace('_', " ")),
+            snapshot.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        ]);
+    }
+    let mut table = builder.build();
+    table.with(Style::modern());
+    println!("{}", table);
     Ok(())
 }
 
-fn handle_crm_reports(matches: &ArgMatches) -> CLIERPResult<()> {
+fn compare_report_snapshots(a: &str, b: &str) -> CLIERPResult<()> {
     let mut conn = crate::database::get_connection()?;
-    let generator = CRMReportsGenerator;
+    let comparison = ReportSnapshotService::compare(&mut conn, a, b)?;
 
-    match matches.subcommand() {
-        Some(("sales-performance", sub_matches)) => {
-            let mut config = create_report_config("sales_performance", sub_matches)?;
-            let result = generator.generate_report(config)?;
-            display_report_result(&result, sub_matches)?;
-        }
-        Some(("pipeline", sub_matches)) => {
-            let mut config = create_report_config("sales_pipeline", sub_matches)?;
-            let result = generator.generate_report(config)?;
-            display_report_result(&result, sub_matches)?;
-        }
-        Some(("customer-analysis", sub_matches)) => {
-            let mut config = create_report_config("customer_analysis", sub_matches)?;
-            let result = generator.generate_report(config)?;
-            display_report_result(&result, sub_matches)?;
-        }
-        _ => {
-            println!("Available CRM reports:");
-            println!("  sales-performance - Sales performance analysis");
-            println!("  pipeline          - Sales pipeline report");
-            println!("  customer-analysis - Customer analysis");
+    println!("=== {} ===", comparison.before.title.replace('_', " ").to_uppercase());
+    outln!(
+        "{} ({}) → {} ({})",
+        comparison.before.name,
+        comparison.before.created_at.format("%Y-%m-%d %H:%M"),
+        comparison.after.name,
+        comparison.after.created_at.format("%Y-%m-%d %H:%M")
+    );
+    println!();
+
+    if comparison.metrics.is_empty() {
+        println!("Neither snapshot has key metrics to compare.");
+        return Ok(());
+    }
+
+    use tabled::{settings::Style, builder::Builder};
+    let show = |value: &Option<MetricValue>| value.as_ref().map(format_metric_value).unwrap_or_else(|| "-".to_string());
+    let mut builder = Builder::default();
+    builder.push_record(["Metric", comparison.before.name.as_str(), comparison.after.name.as_str(), "Change", "%"]);
+    for delta in &comparison.metrics {
+        builder.push_record([
+            delta.metric.clone(),
+            show(&delta.before),
+            show(&delta.after),
+            show(&delta.change),
+            delta.change_pct.map(|p| format!("{:+.1}%", p)).unwrap_or_else(|| "-".to_string()),
+        ]);
+    }
+    let mut table = builder.build();
+    table.with(Style::modern());
+    println!("{}", table);
+    Ok(())
+}
+
+#[cfg(feature = "test-support")]
+fn verify_golden_files(dir: &Path, update: bool) -> CLIERPResult<()> {
+    use crate::testing::{verify_golden, GoldenStatus};
+
+    let outcomes = verify_golden(dir, update)?;
+    let mut changed = 0;
+    for outcome in &outcomes {
+        match &outcome.status {
+            GoldenStatus::Matched => {}
+            GoldenStatus::Missing => {
+                changed += 1;
+                outln!("❌ {} is missing", outcome.path.display());
+            }
+            GoldenStatus::Recorded => println!("  Recorded {}", outcome.path.display()),
+            GoldenStatus::Updated => println!("  Updated {}", outcome.path.display()),
+            GoldenStatus::Changed { line, expected, actual } => {
+                changed += 1;
+                outln!("❌ {} differs at line {}", outcome.path.display(), line);
+                println!("   expected: {}", expected);
+                println!("   actual:   {}", actual);
+            }
         }
     }
+
+    println!("Checked {} report outputs against {}", outcomes.len(), dir.display());
+    if changed > 0 {
+        return Err(CLIERPError::BusinessLogic(format!(
+            "{} report output(s) differ from or have no golden file; rerun with --update if the change is intended",
+            changed
+        )));
+    }
+    Ok(())
+}
+
+/// Show a generated report and save it as a snapshot when `--save` is given
+fn finish_report(report_id: &str, result: &ReportResult, save: Option<&str>, output_file: Option<&Path>) -> CLIERPResult<()> {
+    display_report_result(result, output_file)?;
+
+    if let Some(name) = save {
+        let mut conn = crate::database::get_connection()?;
+        let actor = crate::modules::system::AuditService::actor();
+        let snapshot = ReportSnapshotService::save(&mut conn, name, report_id, result, actor)?;
+        eprintln!("Saved as snapshot '{}' (ID {})", snapshot.name, snapshot.id);
+    }
     Ok(())
 }
 
-fn create_report_config(report_title: &str, matches: &ArgMatches) -> CLIERPResult<ReportConfig> {
+fn report_format(format: &str) -> ReportFormat {
+    match format {
+        "json" => ReportFormat::Json,
+        "csv" => ReportFormat::Csv,
+        "html" => ReportFormat::Html,
+        "pdf" => ReportFormat::Pdf,
+        _ => ReportFormat::Text,
+    }
+}
+
+fn create_report_config(report_title: &str, options: ReportOptions) -> CLIERPResult<ReportConfig> {
     let mut filters = HashMap::new();
 
     // Add common filters
-    if let Some(department) = matches.get_one::<i32>("department") {
+    if let Some(department) = options.department {
         filters.insert("department_id".to_string(), department.to_string());
     }
-    if let Some(employee) = matches.get_one::<i32>("employee") {
+    if let Some(employee) = options.employee {
         filters.insert("employee_id".to_string(), employee.to_string());
     }
-    if let Some(category) = matches.get_one::<i32>("category") {
+    if let Some(category) = options.category {
         filters.insert("category_id".to_string(), category.to_string());
     }
-    if let Some(product) = matches.get_one::<i32>("product") {
+    if let Some(product) = options.product {
         filters.insert("product_id".to_string(), product.to_string());
     }
-    if let Some(customer_type) = matches.get_one::<String>("customer-type") {
+    if let Some(customer_type) = &options.customer_type {
         filters.insert("customer_type".to_string(), customer_type.clone());
     }
-    if let Some(period) = matches.get_one::<String>("period") {
+    if let Some(period) = &options.period {
         filters.insert("period".to_string(), period.clone());
     }
-    if let Some(method) = matches.get_one::<String>("method") {
+    if let Some(method) = &options.method {
         filters.insert("valuation_method".to_string(), method.clone());
     }
-    if matches.get_flag("low-stock-only") {
+    if let Some(days) = options.annual_leave_days {
+        filters.insert("annual_leave_days".to_string(), days.to_string());
+    }
+    if options.low_stock_only {
         filters.insert("low_stock_only".to_string(), "true".to_string());
     }
 
     // Handle date range
-    let date_range = if let (Some(start_str), Some(end_str)) =
-        (matches.get_one::<String>("start-date"), matches.get_one::<String>("end-date")) {
-        let start_date = start_str.parse::<NaiveDate>()
-            .map_err(|_| crate::core::error::CLIERPError::ValidationError(
-                "Invalid start date format. Use YYYY-MM-DD".to_string()
-            ))?;
-        let end_date = end_str.parse::<NaiveDate>()
-            .map_err(|_| crate::core::error::CLIERPError::ValidationError(
-                "Invalid end date format. Use YYYY-MM-DD".to_string()
-            ))?;
+    let date_range = if let (Some(start_str), Some(end_str)) = (&options.start_date, &options.end_date) {
+        let start_date = parse_date(start_str)?;
+        let end_date = parse_date(end_str)?;
+        if start_date > end_date {
+            return Err(crate::core::error::CLIERPError::ValidationError(
+                "Start date must be on or before end date".to_string()
+            ));
+        }
         Some(DateRange { start_date, end_date })
-    } else if let Some(date_str) = matches.get_one::<String>("date") {
-        let date = date_str.parse::<NaiveDate>()
-            .map_err(|_| crate::core::error::CLIERPError::ValidationError(
-                "Invalid date format. Use YYYY-MM-DD".to_string()
-            ))?;
+    } else if let Some(period) = &options.fiscal_period {
+        let (start_date, end_date) = parse_period(period)?;
+        filters.insert("fiscal_period".to_string(), period.clone());
+        Some(DateRange { start_date, end_date })
+    } else if let Some(date_str) = &options.date {
+        let date = parse_date(date_str)?;
         Some(DateRange { start_date: date, end_date: date })
     } else {
         None
     };
 
-    // Determine format
-    let format = match matches.get_one::<String>("format").map(|s| s.as_str()) {
-        Some("json") => ReportFormat::Json,
-        Some("csv") => ReportFormat::Csv,
-        Some("html") => ReportFormat::Html,
-        _ => ReportFormat::Text,
-    };
-
     Ok(ReportConfig {
         title: report_title.to_string(),
         description: Some(format!("Generated {} report", report_title.replace('_', " "))),
         date_range,
         filters,
-        format,
+        format: report_format(&options.format),
         include_charts: false,
         include_summary: true,
     })
 }
 
-fn display_report_result(result: &ReportResult, matches: &ArgMatches) -> CLIERPResult<()> {
+/// Show a report in the pager, or write it to `output_file`
+fn display_report_result(result: &ReportResult, output_file: Option<&Path>) -> CLIERPResult<()> {
+    match (&result.config.format, output_file) {
+        (ReportFormat::Pdf, Some(file)) => write_report_file(file, &render_pdf(&[result])),
+        (ReportFormat::Pdf, None) => Err(pdf_needs_file()),
+        (_, Some(file)) => write_report_file(file, render_report(result)?.as_bytes()),
+        (_, None) => pager::page(&render_report(result)?),
+    }
+}
+
+fn write_report_file(file: &Path, contents: &[u8]) -> CLIERPResult<()> {
+    std::fs::write(file, contents)
+        .map_err(|e| CLIERPError::IoError(format!("Failed to write {}: {}", file.display(), e)))?;
+    eprintln!("Report written to {}", file.display());
+    Ok(())
+}
+
+fn pdf_needs_file() -> CLIERPError {
+    CLIERPError::ValidationError("PDF reports are written to a file; add --output FILE".to_string())
+}
+
+/// Report in its text format; PDF reports get the text layout here and
+/// their page layout from `render_pdf`. Maps are written in key order, so
+/// the same report always renders the same way.
+pub fn render_report(result: &ReportResult) -> CLIERPResult<String> {
+    let mut out = String::new();
     match result.config.format {
         ReportFormat::Json => {
-            let json = serde_json::to_string_pretty(result)?;
-            println!("{}", json);
+            let json = serde_json::to_string_pretty(&serde_json::to_value(result)?)?;
+            let _ = writeln!(out, "{}", json);
         }
         ReportFormat::Csv => {
             if let ReportData::Table(table_data) = &result.data {
-                // Print CSV headers
-                println!("{}", table_data.headers.join(","));
-                // Print CSV rows
+                // CSV headers, then rows
+                let _ = writeln!(out, "{}", table_data.headers.join(","));
                 for row in &table_data.rows {
-                    println!("{}", row.join(","));
+                    let _ = writeln!(out, "{}", row.join(","));
                 }
             } else {
-                println!("CSV format not supported for this report type");
+                let _ = writeln!(out, "CSV format not supported for this report type");
             }
         }
         ReportFormat::Html => {
-            println!("HTML format not yet implemented");
+            let _ = write!(out, "{}", render_html(result));
         }
-        ReportFormat::Text => {
-            println!("=== {} ===", result.config.title.replace('_', " ").to_uppercase());
-            println!("Generated: {}", result.generated_at.format("%Y-%m-%d %H:%M:%S"));
+        ReportFormat::Text | ReportFormat::Pdf => {
+            let _ = writeln!(out, "=== {} ===", result.config.title.replace('_', " ").to_uppercase());
+            let _ = writeln!(out, "Generated: {}", result.generated_at.format("%Y-%m-%d %H:%M:%S"));
 
             if let Some(date_range) = &result.config.date_range {
-                println!("Period: {} to {}", date_range.start_date, date_range.end_date);
+                let _ = writeln!(out, "Period: {} to {}", date_range.start_date, date_range.end_date);
             }
 
-            println!();
+            let _ = writeln!(out);
 
             match &result.data {
                 ReportData::Table(table_data) => {
-                    use tabled::{Table, settings::Style, builder::Builder};
-                    let mut builder = Builder::default();
-                    builder.push_record(&table_data.headers);
-                    for row in &table_data.rows {
-                        builder.push_record(row);
-                    }
-                    let mut table = builder.build();
-                    table.with(Style::modern());
-                    println!("{}", table);
+                    let _ = writeln!(out, "{}", render_table(table_data));
                 }
                 ReportData::Mixed(sections) => {
                     for section in sections {
-                        println!("## {}", section.title);
+                        let _ = writeln!(out, "## {}", section.title);
                         match &section.data {
                             ReportData::Table(table_data) => {
-                                use tabled::{Table, settings::Style, builder::Builder};
-                                let mut builder = Builder::default();
-                                builder.push_record(&table_data.headers);
-                                for row in &table_data.rows {
-                                    builder.push_record(row);
-                                }
-                                let mut table = builder.build();
-                                table.with(Style::modern());
-                                println!("{}", table);
+                                let _ = writeln!(out, "{}", render_table(table_data));
+                            }
+                            _ => {
+                                let _ = writeln!(out, "Content format not supported");
                             }
-                            _ => println!("Content format not supported"),
                         }
-                        println!();
+                        let _ = writeln!(out);
                     }
                 }
-                _ => println!("Report format not supported"),
+                _ => {
+                    let _ = writeln!(out, "Report format not supported");
+                }
             }
 
             if let Some(summary) = &result.summary {
-                println!("\n=== SUMMARY ===");
-                for (key, value) in &summary.key_metrics {
-                    println!("{}: {}", key, value);
+                let _ = writeln!(out, "\n=== SUMMARY ===");
+                for (key, value) in sorted_metrics(summary) {
+                    let _ = writeln!(out, "{}: {}", key, value);
                 }
                 if !summary.insights.is_empty() {
-                    println!("\nKey Insights:");
+                    let _ = writeln!(out, "\nKey Insights:");
                     for insight in &summary.insights {
-                        println!("• {}", insight);
+                        let _ = writeln!(out, "• {}", insight);
                     }
                 }
             }
         }
     }
-    Ok(())
-}
\ No newline at end of file
+    Ok(out)
+}
+
+fn render_html(result: &ReportResult) -> String {
+    use crate::utils::html::{escape_html, html_page, html_table};
+
+    let table = |data: &TableData| html_table(&data.headers, &data.rows, data.totals.as_deref());
+    let mut body = format!(
+        "<p>Generated: {}</p>\n",
+        escape_html(&result.generated_at.format("%Y-%m-%d %H:%M:%S").to_string())
+    );
+    if let Some(date_range) = &result.config.date_range {
+        let _ = writeln!(body, "<p>Period: {} to {}</p>", date_range.start_date, date_range.end_date);
+    }
+    match &result.data {
+        ReportData::Table(table_data) => body.push_str(&table(table_data)),
+        ReportData::Mixed(sections) => {
+            for section in sections {
+                let _ = writeln!(body, "<h2>{}</h2>", escape_html(&section.title));
+                match &section.data {
+                    ReportData::Table(table_data) => body.push_str(&table(table_data)),
+                    _ => body.push_str("<p>Content format not supported</p>\n"),
+                }
+            }
+        }
+        _ => body.push_str("<p>Report format not supported</p>\n"),
+    }
+    if let Some(summary) = &result.summary {
+        body.push_str("<h2>Summary</h2>\n<table>\n");
+        for (key, value) in sorted_metrics(summary) {
+            let _ = writeln!(body, "<tr><th>{}</th><td>{}</td></tr>", escape_html(key), escape_html(&value.to_string()));
+        }
+        body.push_str("</table>\n");
+        if !summary.insights.is_empty() {
+            body.push_str("<ul>\n");
+            for insight in &summary.insights {
+                let _ = writeln!(body, "<li>{}</li>", escape_html(insight));
+            }
+            body.push_str("</ul>\n");
+        }
+    }
+    html_page(&result.config.title.replace('_', " "), &body)
+}
+
+fn sorted_metrics(summary: &ReportSummary) -> Vec<(&String, &MetricValue)> {
+    let mut metrics: Vec<(&String, &MetricValue)> = summary.key_metrics.iter().collect();
+    metrics.sort_by(|a, b| a.0.cmp(b.0));
+    metrics
+}
+
+fn render_table(table_data: &TableData) -> String {
+    use tabled::{settings::Style, builder::Builder};
+    let mut builder = Builder::default();
+    builder.push_record(&table_data.headers);
+    for row in &table_data.rows {
+        builder.push_record(row);
+    }
+    let mut table = builder.build();
+    table.with(Style::modern());
+    table.to_string()
+}
@@ -1,97 +0,0 @@
-use crate::core::{auth::AuthenticatedUser, command::Command, result::CLIERPResult};
-
-#[derive(Default)]
-pub struct SystemInitCommand;
-
-impl SystemInitCommand {
-    pub fn new() -> Self {
-        Self
-    }
-}
-
-impl Command for SystemInitCommand {
-    fn execute(
-        &self,
-        _args: &dyn std::any::Any,
-        _user: Option<&AuthenticatedUser>,
-    ) -> CLIERPResult<()> {
-        println!("System initialization complete!");
-        Ok(())
-    }
-
-    fn name(&self) -> &'static str {
-        "system-init"
-    }
-
-    fn description(&self) -> &'static str {
-        "Initialize the CLIERP system"
-    }
-
-    fn requires_auth(&self) -> bool {
-        false
-    }
-}
-
-#[derive(Default)]
-pub struct SystemStatusCommand;
-
-impl SystemStatusCommand {
-    pub fn new() -> Self {
-        Self
-    }
-}
-
-impl Command for SystemStatusCommand {
-    fn execute(
-        &self,
-        _args: &dyn std::any::Any,
-        _user: Option<&AuthenticatedUser>,
-    ) -> CLIERPResult<()> {
-        println!("System status: OK");
-        Ok(())
-    }
-
-    fn name(&self) -> &'static str {
-        "system-status"
-    }
-
-    fn description(&self) -> &'static str {
-        "Show system status"
-    }
-
-    fn requires_auth(&self) -> bool {
-        false
-    }
-}
-
-#[derive(Default)]
-pub struct SystemMigrateCommand;
-
-impl SystemMigrateCommand {
-    pub fn new() -> Self {
-        Self
-    }
-}
-
-impl Command for SystemMigrateCommand {
-    fn execute(
-        &self,
-        _args: &dyn std::any::Any,
-        _user: Option<&AuthenticatedUser>,
-    ) -> CLIERPResult<()> {
-        println!("Database migrations completed!");
-        Ok(())
-    }
-
-    fn name(&self) -> &'static str {
-        "system-migrate"
-    }
-
-    fn description(&self) -> &'static str {
-        "Run database migrations"
-    }
-
-    fn requires_auth(&self) -> bool {
-        false
-    }
-}
@@ -10,6 +10,8 @@ use crate::core::{error::CLIERPError, result::CLIERPResult};
 use crate::cli::session::SessionManager;
 use crate::modules::inventory::{ProductService, SupplierService};
 use crate::database::connection::get_connection;
+use crate::utils::currency::CurrencyFormat;
+use crate::utils::formatting::format_currency;
 
 pub struct InteractiveMode {
     session_manager: SessionManager,
@@ -141,8 +143,8 @@ impl InteractiveMode {
         if let Some(desc) = &description {
             println!("Description: {}", desc);
         }
-        println!("Price: ¥{}", price as f64 / 100.0);
-        println!("Cost: ¥{}", cost_price as f64 / 100.0);
+        println!("Price: {}", format_currency(price));
+        println!("Cost: {}", format_currency(cost_price));
         println!("Initial Stock: {} {}", initial_stock, unit);
         println!("Min Stock: {}", min_stock);
 
@@ -254,7 +256,7 @@ impl InteractiveMode {
         let total_amount: i32 = items.iter()
             .map(|(_, qty, cost)| qty * cost)
             .sum();
-        println!("Total Amount: ¥{}", total_amount as f64 / 100.0);
+        println!("Total Amount: {}", format_currency(total_amount));
 
         if self.confirm("Create this purchase order?")? {
             // Here you would call the actual PurchaseOrderService
@@ -350,14 +352,14 @@ impl InteractiveMode {
 
     fn input_currency(&self, prompt: &str) -> CLIERPResult<i32> {
         loop {
-            print!("{} (¥): ", prompt);
+            print!("{} ({}): ", prompt, CurrencyFormat::current().symbol.trim());
             io::stdout().flush()?;
 
             let mut input = String::new();
             io::stdin().read_line(&mut input)?;
 
             match input.trim().parse::<f64>() {
-                Ok(amount) => return Ok((amount * 100.0) as i32),
+                Ok(amount) => return Ok(CurrencyFormat::current().to_minor_units(amount) as i32),
                 Err(_) => {
                     self.print_error("Please enter a valid amount")?;
                     continue;
@@ -1,3 +1,6 @@
 pub mod app;
 pub mod commands;
+pub mod prompt;
 pub mod session;
+pub mod shell;
+pub mod tui;
@@ -0,0 +1,114 @@
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::outln;
+
+/// Set by the global `--yes` flag for the rest of the process
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+
+/// How much a confirmed operation can break, which sets how it is asked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskLevel {
+    /// Changes data in a way that is easy to change back
+    Low,
+    /// Changes or removes many records, or posts to the ledger
+    Medium,
+    /// Deletes or moves data for good
+    High,
+}
+
+/// Answer yes to every confirmation, as `--yes` does
+pub fn set_assume_yes(assume_yes: bool) {
+    ASSUME_YES.store(assume_yes, Ordering::Relaxed);
+}
+
+pub fn assume_yes() -> bool {
+    ASSUME_YES.load(Ordering::Relaxed)
+}
+
+/// Ask a yes/no question on the terminal; anything but `y`/`yes` is a no
+pub fn confirm(prompt: &str) -> CLIERPResult<bool> {
+    print!("{} (y/N): ", prompt);
+    io::stdout().flush()?;
+
+    Ok(matches!(read_answer()?.as_str(), "y" | "yes"))
+}
+
+/// Confirm a destructive operation before running it.
+///
+/// `--yes` answers for the user. Without it, a non-interactive run is
+/// refused rather than read as a no, so scripts fail loudly instead of
+/// silently skipping the operation. High-risk operations must be confirmed
+/// by typing `yes` in full.
+pub fn confirm_action(prompt: &str, risk: RiskLevel) -> CLIERPResult<bool> {
+    if assume_yes() {
+        tracing::info!("Confirmed by --yes: {}", prompt);
+        return Ok(true);
+    }
+    if !io::stdin().is_terminal() {
+        return Err(CLIERPError::ValidationError(format!(
+            "{} Confirmation needed; pass --yes to run without a prompt",
+            prompt
+        )));
+    }
+
+    match risk {
+        RiskLevel::Low => confirm(prompt),
+        RiskLevel::Medium => confirm(&format!("⚠️  {}", prompt)),
+        RiskLevel::High => {
+            outln!("🛑 {}", prompt);
+            print!("This cannot be undone. Type 'yes' to continue: ");
+            io::stdout().flush()?;
+            Ok(read_answer()? == "yes")
+        }
+    }
+}
+
+fn read_answer() -> CLIERPResult<String> {
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_lowercase())
+}
+
+/// Let the user edit `initial` in `$VISUAL` or `$EDITOR` (vi if neither is
+/// set) and return the saved text
+pub fn edit_text(initial: &str) -> CLIERPResult<String> {
+    if !io::stdin().is_terminal() {
+        return Err(CLIERPError::ValidationError(
+            "Editing needs a terminal; pass the text in a file instead".to_string(),
+        ));
+    }
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+
+    let path = std::env::temp_dir().join(format!("clierp-{}.txt", uuid::Uuid::new_v4()));
+    std::fs::write(&path, initial)?;
+    let status = std::process::Command::new(program).args(words).arg(&path).status();
+    let text = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+
+    let status = status?;
+    if !status.success() {
+        return Err(CLIERPError::ValidationError(format!("{} exited with {}; nothing saved", editor, status)));
+    }
+    Ok(text?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assume_yes_confirms_without_prompt() {
+        set_assume_yes(true);
+        let confirmed = confirm_action("Delete everything?", RiskLevel::High);
+        set_assume_yes(false);
+        assert!(confirmed.unwrap());
+        assert!(!assume_yes());
+    }
+}
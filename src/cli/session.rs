@@ -109,6 +109,14 @@ impl SessionManager {
 
             // Get full user info from database
             let user_from_db = auth_service.get_user_by_id(user_id)?;
+            if !user_from_db.is_active {
+                // Deactivated since logging in, e.g. by the identity feed
+                self.clear_session()?;
+                return Err(CLIERPError::Authentication(format!(
+                    "User {} is deactivated",
+                    user_from_db.username
+                )));
+            }
 
             Ok(Some(AuthenticatedUser {
                 id: user_from_db.id,
@@ -0,0 +1,384 @@
+//! Line editing for `clierp shell`: history kept across sessions and tab
+//! completion of subcommands and flags from the clap command tree
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+
+use crossterm::{
+    cursor,
+    event::{KeyCode, KeyModifiers},
+    execute,
+    style::Print,
+    terminal::{self, ClearType},
+};
+
+use crate::cli::tui::read_key;
+use crate::core::result::CLIERPResult;
+
+/// Lines of history kept in the history file
+pub const HISTORY_LIMIT: usize = 1000;
+
+/// Words the shell handles itself rather than passing to the command parser
+pub const BUILTINS: &[&str] = &["exit", "quit", "history"];
+
+/// Raw mode without the alternate screen, so edited lines stay in the scrollback
+struct RawMode;
+
+impl RawMode {
+    fn enter() -> CLIERPResult<Self> {
+        terminal::enable_raw_mode()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Completes the word at the end of a line from a clap command tree
+pub struct Completer {
+    root: clap::Command,
+}
+
+impl Completer {
+    pub fn new(mut root: clap::Command) -> Self {
+        // Building propagates global flags to every subcommand
+        root.build();
+        Self { root }
+    }
+
+    /// Byte offset of the word being typed at the end of `line` and the
+    /// subcommands or flags it may complete to
+    pub fn complete(&self, line: &str) -> (usize, Vec<String>) {
+        let start = line.rfind(char::is_whitespace).map(|i| i + 1).unwrap_or(0);
+        let word = &line[start..];
+
+        let mut command = &self.root;
+        for done in line[..start].split_whitespace() {
+            if let Some(sub) = command
+                .get_subcommands()
+                .find(|sub| sub.get_name() == done || sub.get_all_aliases().any(|alias| alias == done))
+            {
+                command = sub;
+            }
+        }
+
+        let mut candidates: Vec<String> = if word.starts_with('-') {
+            command
+                .get_arguments()
+                .filter(|arg| !arg.is_hide_set())
+                .filter_map(|arg| arg.get_long().map(|long| format!("--{}", long)))
+                .filter(|flag| flag.starts_with(word))
+                .collect()
+        } else {
+            let mut names: Vec<String> = command
+                .get_subcommands()
+                .filter(|sub| !sub.is_hide_set())
+                .map(|sub| sub.get_name().to_string())
+                .collect();
+            if std::ptr::eq(command, &self.root) {
+                names.extend(BUILTINS.iter().map(|b| b.to_string()));
+            }
+            names.into_iter().filter(|name| name.starts_with(word)).collect()
+        };
+        candidates.sort();
+        candidates.dedup();
+        (start, candidates)
+    }
+}
+
+/// Longest prefix all candidates share
+pub fn common_prefix(candidates: &[String]) -> String {
+    let Some(first) = candidates.first() else {
+        return String::new();
+    };
+    let mut prefix = first.as_str();
+    for candidate in &candidates[1..] {
+        while !candidate.starts_with(prefix) {
+            prefix = &prefix[..prefix.char_indices().last().map(|(i, _)| i).unwrap_or(0)];
+        }
+    }
+    prefix.to_string()
+}
+
+/// Split a line into arguments the way a POSIX shell would for plain words,
+/// single and double quotes and backslash escapes
+pub fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("Unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err("Unterminated double quote".to_string()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("Unterminated double quote".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                match chars.next() {
+                    Some(c) => word.push(c),
+                    None => return Err("Trailing backslash".to_string()),
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// Reads lines with editing keys, history and completion on a terminal, and
+/// plain lines when input is piped
+pub struct LineEditor {
+    completer: Completer,
+    history: Vec<String>,
+    history_file: Option<PathBuf>,
+}
+
+impl LineEditor {
+    pub fn new(completer: Completer, history_file: Option<PathBuf>) -> Self {
+        let history = history_file
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|text| text.lines().filter(|line| !line.trim().is_empty()).map(String::from).collect())
+            .unwrap_or_default();
+        Self {
+            completer,
+            history,
+            history_file,
+        }
+    }
+
+    /// `~/.clierp_history`, or one in the temp directory without a home
+    pub fn default_history_file() -> PathBuf {
+        std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(std::env::temp_dir)
+            .join(".clierp_history")
+    }
+
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Remember a line, skipping repeats of the previous one
+    pub fn add_history(&mut self, line: &str) -> CLIERPResult<()> {
+        let line = line.trim();
+        if line.is_empty() || self.history.last().map(String::as_str) == Some(line) {
+            return Ok(());
+        }
+        self.history.push(line.to_string());
+        let Some(path) = &self.history_file else {
+            return Ok(());
+        };
+        if self.history.len() > HISTORY_LIMIT {
+            self.history.drain(..self.history.len() - HISTORY_LIMIT);
+            fs::write(path, self.history.join("\n") + "\n")?;
+        } else {
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    /// The next line, or `None` at end of input (Ctrl-D on an empty line)
+    pub fn read_line(&mut self, prompt: &str) -> CLIERPResult<Option<String>> {
+        if !io::stdin().is_terminal() {
+            let mut line = String::new();
+            return Ok(match io::stdin().lock().read_line(&mut line)? {
+                0 => None,
+                _ => Some(line.trim_end_matches(['\n', '\r']).to_string()),
+            });
+        }
+
+        let _raw = RawMode::enter()?;
+        let mut out = io::stdout();
+        let mut buffer: Vec<char> = Vec::new();
+        let mut cursor = 0;
+        // Position while browsing history, and the line being typed before
+        let mut recalled = self.history.len();
+        let mut draft: Vec<char> = Vec::new();
+        redraw(&mut out, prompt, &buffer, cursor)?;
+
+        loop {
+            let key = read_key()?;
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                KeyCode::Enter => {
+                    execute!(out, Print("\r\n"))?;
+                    return Ok(Some(buffer.iter().collect()));
+                }
+                KeyCode::Char('c') if ctrl => {
+                    execute!(out, Print("^C\r\n"))?;
+                    return Ok(Some(String::new()));
+                }
+                KeyCode::Char('d') if ctrl => {
+                    if buffer.is_empty() {
+                        execute!(out, Print("\r\n"))?;
+                        return Ok(None);
+                    }
+                    if cursor < buffer.len() {
+                        buffer.remove(cursor);
+                    }
+                }
+                KeyCode::Char('a') if ctrl => cursor = 0,
+                KeyCode::Char('e') if ctrl => cursor = buffer.len(),
+                KeyCode::Char('u') if ctrl => {
+                    buffer.drain(..cursor);
+                    cursor = 0;
+                }
+                KeyCode::Char('k') if ctrl => buffer.truncate(cursor),
+                KeyCode::Char('w') if ctrl => {
+                    let mut start = cursor;
+                    while start > 0 && buffer[start - 1].is_whitespace() {
+                        start -= 1;
+                    }
+                    while start > 0 && !buffer[start - 1].is_whitespace() {
+                        start -= 1;
+                    }
+                    buffer.drain(start..cursor);
+                    cursor = start;
+                }
+                KeyCode::Char('l') if ctrl => {
+                    execute!(out, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+                }
+                KeyCode::Char(c) if !ctrl => {
+                    buffer.insert(cursor, c);
+                    cursor += 1;
+                }
+                KeyCode::Backspace if cursor > 0 => {
+                    cursor -= 1;
+                    buffer.remove(cursor);
+                }
+                KeyCode::Delete if cursor < buffer.len() => {
+                    buffer.remove(cursor);
+                }
+                KeyCode::Left if cursor > 0 => cursor -= 1,
+                KeyCode::Right if cursor < buffer.len() => cursor += 1,
+                KeyCode::Home => cursor = 0,
+                KeyCode::End => cursor = buffer.len(),
+                KeyCode::Up if recalled > 0 => {
+                    if recalled == self.history.len() {
+                        draft = buffer.clone();
+                    }
+                    recalled -= 1;
+                    buffer = self.history[recalled].chars().collect();
+                    cursor = buffer.len();
+                }
+                KeyCode::Down if recalled < self.history.len() => {
+                    recalled += 1;
+                    buffer = match self.history.get(recalled) {
+                        Some(line) => line.chars().collect(),
+                        None => draft.clone(),
+                    };
+                    cursor = buffer.len();
+                }
+                KeyCode::Tab => {
+                    let before: String = buffer[..cursor].iter().collect();
+                    let (start, candidates) = self.completer.complete(&before);
+                    let word_start = before[..start].chars().count();
+                    let typed = &before[start..];
+                    let replacement = match candidates.as_slice() {
+                        [] => None,
+                        [only] => Some(format!("{} ", only)),
+                        _ => {
+                            let prefix = common_prefix(&candidates);
+                            if prefix.len() > typed.len() {
+                                Some(prefix)
+                            } else {
+                                execute!(out, Print("\r\n"), Print(candidates.join("  ")), Print("\r\n"))?;
+                                None
+                            }
+                        }
+                    };
+                    if let Some(replacement) = replacement {
+                        buffer.splice(word_start..cursor, replacement.chars());
+                        cursor = word_start + replacement.chars().count();
+                    }
+                }
+                _ => {}
+            }
+            redraw(&mut out, prompt, &buffer, cursor)?;
+        }
+    }
+}
+
+fn redraw(out: &mut io::Stdout, prompt: &str, buffer: &[char], cursor: usize) -> CLIERPResult<()> {
+    let line: String = buffer.iter().collect();
+    let column = prompt.chars().count() + cursor;
+    execute!(
+        out,
+        cursor::MoveToColumn(0),
+        terminal::Clear(ClearType::CurrentLine),
+        Print(prompt),
+        Print(line),
+        cursor::MoveToColumn(column as u16)
+    )?;
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_split_and_complete() {
+        assert_eq!(
+            split_words(r#"crm customer add --name "Acme Corp" --notes 'a "b"' x\ y"#).unwrap(),
+            ["crm", "customer", "add", "--name", "Acme Corp", "--notes", "a \"b\"", "x y"]
+        );
+        assert!(split_words("auth login 'admin").is_err());
+        assert_eq!(common_prefix(&["inventory".to_string(), "invoice".to_string()]), "inv");
+
+        let completer = Completer::new(crate::core::command::CLIArgs::command());
+        let (start, candidates) = completer.complete("sys");
+        assert_eq!(start, 0);
+        assert_eq!(candidates, ["system"]);
+        assert!(completer.complete("").1.contains(&"exit".to_string()));
+        let (start, candidates) = completer.complete("events tail --fo");
+        assert_eq!(start, 12);
+        assert_eq!(candidates, ["--follow"]);
+        assert!(completer.complete("system st").1.contains(&"stats".to_string()));
+    }
+}
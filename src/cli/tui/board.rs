@@ -0,0 +1,323 @@
+use std::io::{self, Write};
+
+use clap::ValueEnum;
+use crossterm::{
+    cursor::MoveTo,
+    event::{KeyCode, KeyModifiers},
+    queue,
+    style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor},
+    terminal::{self, Clear, ClearType},
+};
+
+use crate::cli::tui::{read_key, scroll_offset, spread, truncate, TerminalGuard};
+use crate::core::result::CLIERPResult;
+use crate::database::{DatabaseConnection, Deal, DealStage};
+use crate::modules::crm::{DealService, RecordScope};
+use crate::utils::formatting::format_currency;
+
+/// Narrowest column worth drawing; narrower terminals scroll horizontally
+const MIN_COLUMN_WIDTH: u16 = 20;
+
+/// Deals in one pipeline stage
+#[derive(Debug, Clone)]
+pub struct BoardColumn {
+    pub stage: DealStage,
+    pub deals: Vec<Deal>,
+}
+
+impl BoardColumn {
+    /// Total value of the column, using the discounted amount where set
+    pub fn total(&self) -> i64 {
+        self.deals
+            .iter()
+            .map(|deal| deal.final_amount.unwrap_or(deal.deal_value) as i64)
+            .sum()
+    }
+}
+
+/// Kanban view of the deal pipeline with one column per stage.
+///
+/// Arrow keys (or h/j/k/l) select a deal; Shift+←/→ (or `<`/`>`) move it to
+/// the previous or next stage through the regular stage update, so
+/// probability, close date and change history follow as they would from
+/// `deal update-stage`.
+pub struct PipelineBoard {
+    pub columns: Vec<BoardColumn>,
+    column: usize,
+    row: usize,
+    status: Option<String>,
+}
+
+impl PipelineBoard {
+    /// Group deals by stage in pipeline order, largest deals first.
+    /// Deals with an unrecognized stage are left off the board.
+    pub fn from_deals(deals: Vec<Deal>) -> Self {
+        let mut columns: Vec<BoardColumn> = DealStage::value_variants()
+            .iter()
+            .map(|stage| BoardColumn {
+                stage: stage.clone(),
+                deals: Vec::new(),
+            })
+            .collect();
+
+        for deal in deals {
+            if let Some(column) = columns.iter_mut().find(|c| c.stage.to_string() == deal.stage) {
+                column.deals.push(deal);
+            }
+        }
+        for column in &mut columns {
+            column.deals.sort_by(|a, b| b.deal_value.cmp(&a.deal_value).then(a.id.cmp(&b.id)));
+        }
+
+        Self {
+            columns,
+            column: 0,
+            row: 0,
+            status: None,
+        }
+    }
+
+    pub fn load(conn: &mut DatabaseConnection, scope: &RecordScope) -> CLIERPResult<Self> {
+        let deals = DealService::find_deals_by_filters(conn, &[], scope)?;
+        Ok(Self::from_deals(deals))
+    }
+
+    pub fn selected(&self) -> Option<&Deal> {
+        self.columns.get(self.column).and_then(|c| c.deals.get(self.row))
+    }
+
+    /// Move the cursor by whole columns or rows, clamped to the board
+    pub fn select(&mut self, column_delta: i32, row_delta: i32) {
+        let last_column = self.columns.len().saturating_sub(1) as i32;
+        self.column = (self.column as i32 + column_delta).clamp(0, last_column) as usize;
+
+        let rows = self.columns[self.column].deals.len() as i32;
+        self.row = (self.row as i32 + row_delta).clamp(0, (rows - 1).max(0)) as usize;
+    }
+
+    /// Put the cursor on a deal wherever it currently is
+    fn select_deal(&mut self, deal_id: i32) {
+        for (c, column) in self.columns.iter().enumerate() {
+            if let Some(r) = column.deals.iter().position(|d| d.id == deal_id) {
+                self.column = c;
+                self.row = r;
+                return;
+            }
+        }
+        self.select(0, 0);
+    }
+
+    /// Move the selected deal one stage left or right and reload the board
+    fn move_selected(
+        &mut self,
+        conn: &mut DatabaseConnection,
+        scope: &RecordScope,
+        delta: i32,
+    ) -> CLIERPResult<()> {
+        let deal_id = match self.selected() {
+            Some(deal) => deal.id,
+            None => return Ok(()),
+        };
+        let target = self.column as i32 + delta;
+        if target < 0 || target >= self.columns.len() as i32 {
+            return Ok(());
+        }
+        let stage = self.columns[target as usize].stage.clone();
+
+        let deal = DealService::update_deal_stage(conn, deal_id, stage, None)?;
+        let message = format!("Moved #{} {} to {}", deal.id, deal.deal_name, deal.stage);
+        self.reload(conn, scope)?;
+        self.select_deal(deal_id);
+        self.status = Some(message);
+        Ok(())
+    }
+
+    fn reload(&mut self, conn: &mut DatabaseConnection, scope: &RecordScope) -> CLIERPResult<()> {
+        let (column, row) = (self.column, self.row);
+        *self = Self::load(conn, scope)?;
+        self.column = column;
+        self.row = row;
+        self.select(0, 0);
+        Ok(())
+    }
+
+    /// Show the board until the user quits
+    pub fn run(&mut self, conn: &mut DatabaseConnection, scope: &RecordScope) -> CLIERPResult<()> {
+        let _guard = TerminalGuard::enter()?;
+
+        loop {
+            self.draw()?;
+            let key = read_key()?;
+            let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+
+            let result = match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => break,
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                KeyCode::Left if shift => self.move_selected(conn, scope, -1),
+                KeyCode::Right if shift => self.move_selected(conn, scope, 1),
+                KeyCode::Char('<') | KeyCode::Char('H') => self.move_selected(conn, scope, -1),
+                KeyCode::Char('>') | KeyCode::Char('L') => self.move_selected(conn, scope, 1),
+                KeyCode::Left | KeyCode::Char('h') => {
+                    self.select(-1, 0);
+                    Ok(())
+                }
+                KeyCode::Right | KeyCode::Char('l') => {
+                    self.select(1, 0);
+                    Ok(())
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.select(0, -1);
+                    Ok(())
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.select(0, 1);
+                    Ok(())
+                }
+                KeyCode::Char('r') => {
+                    let reloaded = self.reload(conn, scope);
+                    self.status = Some("Reloaded".to_string());
+                    reloaded
+                }
+                _ => Ok(()),
+            };
+
+            if let Err(e) = result {
+                self.status = Some(format!("Error: {}", e));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw(&self) -> CLIERPResult<()> {
+        let (width, height) = terminal::size()?;
+        let mut out = io::stdout();
+        queue!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+
+        // Show as many whole columns as fit, keeping the selected one in view
+        let visible = ((width / MIN_COLUMN_WIDTH).max(1) as usize).min(self.columns.len());
+        let first = self.column.saturating_sub(visible - 1).min(self.columns.len() - visible);
+        let column_width = (width as usize / visible).max(1);
+        let card_width = column_width.saturating_sub(2);
+        let card_rows = height.saturating_sub(6) as usize;
+
+        let pipeline_total: i64 = self.columns.iter().map(|c| c.total()).sum();
+        queue!(
+            out,
+            SetAttribute(Attribute::Bold),
+            Print(truncate(&format!("Deal Pipeline  —  {}", format_currency(pipeline_total)), width as usize)),
+            SetAttribute(Attribute::Reset)
+        )?;
+
+        for (slot, (index, column)) in self.columns.iter().enumerate().skip(first).take(visible).enumerate() {
+            let x = (slot * column_width) as u16;
+            let is_current = index == self.column;
+
+            let title = column.stage.to_string().replace('_', " ").to_uppercase();
+            queue!(out, MoveTo(x, 2))?;
+            if is_current {
+                queue!(out, SetForegroundColor(Color::Cyan))?;
+            }
+            queue!(
+                out,
+                SetAttribute(Attribute::Bold),
+                Print(spread(&title, &column.deals.len().to_string(), card_width)),
+                SetAttribute(Attribute::Reset),
+                MoveTo(x, 3),
+                Print(truncate(&format_currency(column.total()), card_width)),
+                ResetColor
+            )?;
+
+            // Scroll the selected column so its cursor stays on screen
+            let offset = if is_current { scroll_offset(self.row, card_rows) } else { 0 };
+            for (line, (row, deal)) in column.deals.iter().enumerate().skip(offset).take(card_rows).enumerate() {
+                let value = format_currency(deal.final_amount.unwrap_or(deal.deal_value));
+                let card = spread(&format!("#{} {}", deal.id, deal.deal_name), &value, card_width);
+                queue!(out, MoveTo(x, 5 + line as u16))?;
+                if is_current && row == self.row {
+                    queue!(
+                        out,
+                        SetAttribute(Attribute::Reverse),
+                        Print(card),
+                        SetAttribute(Attribute::Reset)
+                    )?;
+                } else {
+                    queue!(out, Print(card))?;
+                }
+            }
+        }
+
+        let help = "←/→ column  ↑/↓ deal  Shift+←/→ or </> move stage  r reload  q quit";
+        queue!(out, MoveTo(0, height.saturating_sub(1)), Print(truncate(help, width as usize)))?;
+        if let Some(status) = &self.status {
+            queue!(
+                out,
+                MoveTo(0, height.saturating_sub(2)),
+                SetForegroundColor(Color::Yellow),
+                Print(truncate(status, width as usize)),
+                ResetColor
+            )?;
+        }
+
+        out.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn deal(id: i32, stage: &str, value: i32) -> Deal {
+        let now = Utc::now().naive_utc();
+        Deal {
+            id,
+            lead_id: None,
+            deal_name: format!("Deal {}", id),
+            stage: stage.to_string(),
+            deal_value: value,
+            close_date: None,
+            probability: None,
+            assigned_to: None,
+            products: None,
+            discount_percent: None,
+            final_amount: None,
+            notes: None,
+            created_at: now,
+            updated_at: now,
+            destination_country: None,
+            visibility: "team".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_deals_grouped_by_stage() {
+        let mut discounted = deal(3, "proposal", 1000);
+        discounted.final_amount = Some(900);
+        let board = PipelineBoard::from_deals(vec![
+            deal(1, "prospecting", 500),
+            deal(2, "proposal", 2000),
+            discounted,
+            deal(4, "archived", 100),
+        ]);
+
+        assert_eq!(board.columns.len(), 8);
+        assert_eq!(board.columns[0].deals.len(), 1);
+        let proposal = &board.columns[3];
+        assert_eq!(proposal.deals.iter().map(|d| d.id).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(proposal.total(), 2900);
+    }
+
+    #[test]
+    fn test_selection_is_clamped() {
+        let mut board = PipelineBoard::from_deals(vec![deal(1, "prospecting", 500)]);
+        board.select(-1, 5);
+        assert_eq!(board.selected().map(|d| d.id), Some(1));
+
+        board.select(3, 0);
+        assert!(board.selected().is_none());
+        board.select(20, 0);
+        assert_eq!(board.column, 7);
+    }
+}
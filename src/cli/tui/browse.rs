@@ -0,0 +1,387 @@
+use std::io::{self, Write};
+
+use crossterm::{
+    cursor::MoveTo,
+    event::{KeyCode, KeyModifiers},
+    queue,
+    style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor},
+    terminal::{self, Clear, ClearType},
+};
+
+use crate::cli::tui::{read_key, scroll_offset, spread, truncate, TerminalGuard};
+use crate::core::result::CLIERPResult;
+use crate::database::connection::get_connection;
+use crate::modules::inventory::{AttachmentService, ProductService, ProductWithCategory, ReservationService};
+use crate::modules::system::audit::AuditService;
+use crate::utils::formatting::{format_currency, format_datetime};
+use crate::utils::pagination::PaginationParams;
+
+/// Products loaded per search; narrow the search to see others
+const MAX_PRODUCTS: i64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Details,
+    History,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Browse,
+    Search,
+    StockIn,
+    StockOut,
+}
+
+/// Full-screen product browser for the warehouse floor.
+///
+/// The left side lists products matching the search (name or SKU), the
+/// right side shows the selected product's details or its recent stock
+/// movements. Stock in/out goes through the regular stock update, so
+/// reservations and negative-stock checks apply as on the command line.
+pub struct InventoryBrowser {
+    service: ProductService,
+    attachments: AttachmentService,
+    products: Vec<ProductWithCategory>,
+    total_count: usize,
+    query: String,
+    selected: usize,
+    pane: Pane,
+    mode: Mode,
+    input: String,
+    status: Option<String>,
+}
+
+impl InventoryBrowser {
+    pub fn new(query: Option<String>) -> CLIERPResult<Self> {
+        let mut browser = Self {
+            service: ProductService::new(),
+            attachments: AttachmentService::new(),
+            products: Vec::new(),
+            total_count: 0,
+            query: query.unwrap_or_default(),
+            selected: 0,
+            pane: Pane::Details,
+            mode: Mode::Browse,
+            input: String::new(),
+            status: None,
+        };
+        browser.reload()?;
+        Ok(browser)
+    }
+
+    fn reload(&mut self) -> CLIERPResult<()> {
+        let selected_id = self.selected_product().map(|p| p.product.id);
+        let search = Some(self.query.trim()).filter(|q| !q.is_empty());
+        let result = self.service.list_products(
+            &PaginationParams::new(1, MAX_PRODUCTS),
+            None,
+            false,
+            search,
+            false,
+        )?;
+        self.total_count = result.total_items() as usize;
+        self.products = result.data;
+
+        self.selected = selected_id
+            .and_then(|id| self.products.iter().position(|p| p.product.id == id))
+            .unwrap_or(0);
+        Ok(())
+    }
+
+    fn selected_product(&self) -> Option<&ProductWithCategory> {
+        self.products.get(self.selected)
+    }
+
+    fn select(&mut self, delta: i32) {
+        let last = self.products.len().saturating_sub(1) as i32;
+        self.selected = (self.selected as i32 + delta).clamp(0, last) as usize;
+    }
+
+    /// Book the typed quantity as a stock in or out for the selected product
+    fn apply_stock_change(&mut self) -> CLIERPResult<()> {
+        let mode = self.mode;
+        self.mode = Mode::Browse;
+        let input = std::mem::take(&mut self.input);
+
+        let product_id = match self.selected_product() {
+            Some(p) => p.product.id,
+            None => return Ok(()),
+        };
+        let quantity: i32 = match input.trim().parse() {
+            Ok(q) if q > 0 => q,
+            _ => {
+                self.status = Some(format!("'{}' is not a positive quantity", input.trim()));
+                return Ok(());
+            }
+        };
+
+        let (change, movement_type, verb) = match mode {
+            Mode::StockIn => (quantity, "in", "Added"),
+            _ => (-quantity, "out", "Removed"),
+        };
+        let product = self.service.update_stock(
+            product_id,
+            change,
+            movement_type,
+            None,
+            Some("browse"),
+            None,
+            None,
+            AuditService::actor(),
+        )?;
+
+        self.status = Some(format!(
+            "{} {} {} of {}; now {} on hand",
+            verb, quantity, product.unit, product.sku, product.current_stock
+        ));
+        self.reload()
+    }
+
+    /// Show the browser until the user quits
+    pub fn run(&mut self) -> CLIERPResult<()> {
+        let _guard = TerminalGuard::enter()?;
+
+        loop {
+            self.draw()?;
+            let key = read_key()?;
+            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                break;
+            }
+
+            let result = match self.mode {
+                Mode::Search => match key.code {
+                    KeyCode::Enter => {
+                        self.mode = Mode::Browse;
+                        Ok(())
+                    }
+                    KeyCode::Esc => {
+                        self.mode = Mode::Browse;
+                        self.query.clear();
+                        self.reload()
+                    }
+                    KeyCode::Backspace => {
+                        self.query.pop();
+                        self.reload()
+                    }
+                    KeyCode::Char(c) => {
+                        self.query.push(c);
+                        self.reload()
+                    }
+                    _ => Ok(()),
+                },
+                Mode::StockIn | Mode::StockOut => match key.code {
+                    KeyCode::Enter => self.apply_stock_change(),
+                    KeyCode::Esc => {
+                        self.mode = Mode::Browse;
+                        self.input.clear();
+                        Ok(())
+                    }
+                    KeyCode::Backspace => {
+                        self.input.pop();
+                        Ok(())
+                    }
+                    KeyCode::Char(c) if c.is_ascii_digit() => {
+                        self.input.push(c);
+                        Ok(())
+                    }
+                    _ => Ok(()),
+                },
+                Mode::Browse => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => break,
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        self.select(-1);
+                        Ok(())
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        self.select(1);
+                        Ok(())
+                    }
+                    KeyCode::PageUp => {
+                        self.select(-10);
+                        Ok(())
+                    }
+                    KeyCode::PageDown => {
+                        self.select(10);
+                        Ok(())
+                    }
+                    KeyCode::Char('/') => {
+                        self.mode = Mode::Search;
+                        Ok(())
+                    }
+                    KeyCode::Tab | KeyCode::Char('h') => {
+                        self.pane = match self.pane {
+                            Pane::Details => Pane::History,
+                            Pane::History => Pane::Details,
+                        };
+                        Ok(())
+                    }
+                    KeyCode::Char('+') | KeyCode::Char('i') if self.selected_product().is_some() => {
+                        self.mode = Mode::StockIn;
+                        Ok(())
+                    }
+                    KeyCode::Char('-') | KeyCode::Char('o') if self.selected_product().is_some() => {
+                        self.mode = Mode::StockOut;
+                        Ok(())
+                    }
+                    KeyCode::Char('r') => {
+                        self.status = Some("Reloaded".to_string());
+                        self.reload()
+                    }
+                    _ => Ok(()),
+                },
+            };
+
+            if let Err(e) = result {
+                self.mode = Mode::Browse;
+                self.status = Some(format!("Error: {}", e));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw(&self) -> CLIERPResult<()> {
+        let (width, height) = terminal::size()?;
+        let mut out = io::stdout();
+        queue!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+
+        let list_width = (width as usize * 2 / 5).clamp(20, 48).min(width as usize);
+        let detail_x = (list_width + 2) as u16;
+        let detail_width = (width as usize).saturating_sub(list_width + 2);
+        let rows = height.saturating_sub(5) as usize;
+
+        let search = if self.mode == Mode::Search {
+            format!("Search: {}_", self.query)
+        } else if self.query.is_empty() {
+            "Search: (press / to search)".to_string()
+        } else {
+            format!("Search: {}", self.query)
+        };
+        let count = if self.total_count > self.products.len() {
+            format!("{} of {}", self.products.len(), self.total_count)
+        } else {
+            self.products.len().to_string()
+        };
+        queue!(
+            out,
+            SetAttribute(Attribute::Bold),
+            Print(spread("Inventory", &count, list_width)),
+            SetAttribute(Attribute::Reset),
+            MoveTo(0, 1),
+            Print(truncate(&search, list_width))
+        )?;
+
+        let offset = scroll_offset(self.selected, rows);
+        for (line, (index, item)) in self.products.iter().enumerate().skip(offset).take(rows).enumerate() {
+            let product = &item.product;
+            let stock = format!("{} {}", product.current_stock, product.unit);
+            let row = spread(&format!("{} {}", product.sku, product.name), &stock, list_width);
+
+            queue!(out, MoveTo(0, 3 + line as u16))?;
+            if !product.is_active {
+                queue!(out, SetForegroundColor(Color::DarkGrey))?;
+            } else if product.current_stock <= product.min_stock_level {
+                queue!(out, SetForegroundColor(Color::Red))?;
+            }
+            if index == self.selected {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+            queue!(out, Print(row), SetAttribute(Attribute::Reset), ResetColor)?;
+        }
+        if self.products.is_empty() {
+            queue!(out, MoveTo(0, 3), Print("No products found."))?;
+        }
+
+        if let Some(item) = self.selected_product() {
+            let lines = match self.pane {
+                Pane::Details => self.detail_lines(item)?,
+                Pane::History => self.history_lines(item, rows)?,
+            };
+            for (line, text) in lines.iter().take(rows + 2).enumerate() {
+                queue!(out, MoveTo(detail_x, line as u16), Print(truncate(text, detail_width)))?;
+            }
+        }
+
+        let help = match self.mode {
+            Mode::Search => "Type to filter  Enter done  Esc clear".to_string(),
+            Mode::StockIn => format!("Stock in quantity: {}_  (Enter book, Esc cancel)", self.input),
+            Mode::StockOut => format!("Stock out quantity: {}_  (Enter book, Esc cancel)", self.input),
+            Mode::Browse => "↑/↓ select  / search  Tab details/history  + stock in  - stock out  r reload  q quit".to_string(),
+        };
+        queue!(out, MoveTo(0, height.saturating_sub(1)), Print(truncate(&help, width as usize)))?;
+        if let Some(status) = &self.status {
+            queue!(
+                out,
+                MoveTo(0, height.saturating_sub(2)),
+                SetForegroundColor(Color::Yellow),
+                Print(truncate(status, width as usize)),
+                ResetColor
+            )?;
+        }
+
+        out.flush()?;
+        Ok(())
+    }
+
+    fn detail_lines(&self, item: &ProductWithCategory) -> CLIERPResult<Vec<String>> {
+        let product = &item.product;
+        let mut conn = get_connection()?;
+        let availability = ReservationService::availability(&mut conn, product.id)?;
+
+        let mut lines = vec![
+            format!("{} — {}", product.sku, product.name),
+            String::new(),
+            format!("Category: {}", item.category.name),
+            format!("Price: {}", format_currency(product.price)),
+            format!("Cost Price: {}", format_currency(product.cost_price)),
+            format!("On Hand: {} {}", availability.on_hand, product.unit),
+            format!("Reserved: {} {}", availability.reserved, product.unit),
+            format!("Available: {} {}", availability.available, product.unit),
+            format!(
+                "Stock Levels: min {} / max {}",
+                product.min_stock_level,
+                product.max_stock_level.map(|m| m.to_string()).unwrap_or_else(|| "-".to_string())
+            ),
+            format!("Barcode: {}", product.barcode.as_deref().unwrap_or("-")),
+            format!("Status: {}", if product.is_active { "Active" } else { "Inactive" }),
+            format!(
+                "Photo: {}",
+                self.attachments
+                    .get_primary_image(product.id)?
+                    .map(|photo| photo.file_name)
+                    .unwrap_or_else(|| "-".to_string())
+            ),
+        ];
+        if let Some(description) = &product.description {
+            lines.push(String::new());
+            lines.push(description.clone());
+        }
+        Ok(lines)
+    }
+
+    fn history_lines(&self, item: &ProductWithCategory, rows: usize) -> CLIERPResult<Vec<String>> {
+        let product = &item.product;
+        let movements = self
+            .service
+            .get_stock_movements(product.id, &PaginationParams::new(1, rows.max(1) as i64))?;
+
+        let mut lines = vec![
+            format!("{} — Stock Movements", product.sku),
+            String::new(),
+        ];
+        if movements.data.is_empty() {
+            lines.push("No stock movements recorded.".to_string());
+        }
+        for movement in movements.data {
+            lines.push(format!(
+                "{}  {:<10} {:>+6}  {}",
+                format_datetime(&movement.movement_date),
+                movement.movement_type,
+                movement.quantity,
+                movement.notes.or(movement.reference_type).unwrap_or_default()
+            ));
+        }
+        Ok(lines)
+    }
+}
@@ -0,0 +1,105 @@
+pub mod board;
+pub mod browse;
+pub mod pager;
+
+use std::io::{self, Write};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyEvent, KeyEventKind},
+    execute,
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+use crate::core::result::CLIERPResult;
+
+/// Raw mode on the alternate screen for as long as the guard lives.
+///
+/// Dropping the guard restores the terminal, so an early return or error in
+/// a full-screen view never leaves the user's shell in raw mode.
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    pub fn enter() -> CLIERPResult<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, cursor::Hide)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+        let _ = io::stdout().flush();
+    }
+}
+
+/// Block until the next key press, ignoring releases and other events
+pub fn read_key() -> CLIERPResult<KeyEvent> {
+    loop {
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                return Ok(key);
+            }
+        }
+    }
+}
+
+/// Cut `text` to at most `width` characters, marking the cut with `…`
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut cut: String = text.chars().take(width - 1).collect();
+    cut.push('…');
+    cut
+}
+
+/// Left-align `label` and right-align `value` within `width` characters
+pub fn spread(label: &str, value: &str, width: usize) -> String {
+    let value_width = value.chars().count();
+    if value_width + 1 >= width {
+        return truncate(label, width);
+    }
+    let label = truncate(label, width - value_width - 1);
+    let padding = width - label.chars().count() - value_width;
+    format!("{}{}{}", label, " ".repeat(padding), value)
+}
+
+/// First row to draw so that `selected` stays within `rows` visible rows
+pub fn scroll_offset(selected: usize, rows: usize) -> usize {
+    if rows == 0 {
+        selected
+    } else {
+        (selected + 1).saturating_sub(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("Pipeline", 20), "Pipeline");
+        assert_eq!(truncate("Enterprise renewal", 10), "Enterpris…");
+        assert_eq!(truncate("abc", 0), "");
+    }
+
+    #[test]
+    fn test_scroll_offset() {
+        assert_eq!(scroll_offset(3, 10), 0);
+        assert_eq!(scroll_offset(9, 10), 0);
+        assert_eq!(scroll_offset(12, 10), 3);
+    }
+
+    #[test]
+    fn test_spread() {
+        assert_eq!(spread("#1 Renewal", "₩500", 16), "#1 Renewal  ₩500");
+        assert_eq!(spread("#1 Enterprise renewal", "₩500", 12), "#1 Ent… ₩500");
+    }
+}
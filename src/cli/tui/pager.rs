@@ -0,0 +1,211 @@
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crossterm::{
+    cursor::MoveTo,
+    event::{KeyCode, KeyModifiers},
+    queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, Clear, ClearType},
+};
+
+use crate::cli::tui::{read_key, TerminalGuard};
+use crate::core::result::CLIERPResult;
+use crate::utils::formatting::for_output;
+
+/// Cleared by the global `--no-pager` flag for the rest of the process
+static PAGER_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Columns scrolled by one press of left or right
+const HORIZONTAL_STEP: usize = 8;
+
+pub fn set_pager_enabled(enabled: bool) {
+    PAGER_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Print `text`, through a pager when it does not fit on the screen.
+///
+/// Output that is piped, fits the terminal, or is printed with `--no-pager`
+/// goes straight to stdout. Otherwise `$PAGER` is used when set, and the
+/// built-in pager when it is not or cannot be started. The built-in pager
+/// cuts lines at the terminal width rather than wrapping them, so table rows
+/// stay aligned; left and right scroll to the cut columns. In `--plain`
+/// mode the text is made ASCII first.
+pub fn page(text: &str) -> CLIERPResult<()> {
+    let text = &*for_output(text);
+    let mut stdout = io::stdout();
+    let (columns, rows) = terminal::size().unwrap_or((80, 24));
+    let lines: Vec<&str> = text.lines().collect();
+
+    if !PAGER_ENABLED.load(Ordering::Relaxed) || !stdout.is_terminal() || lines.len() < rows as usize {
+        stdout.write_all(text.as_bytes())?;
+        if !text.ends_with('\n') {
+            writeln!(stdout)?;
+        }
+        return Ok(());
+    }
+
+    if let Some(pager) = std::env::var("PAGER").ok().filter(|p| !p.trim().is_empty()) {
+        match run_external(&pager, text) {
+            Ok(()) => return Ok(()),
+            Err(e) => tracing::warn!("Pager '{}' failed, using the built-in pager: {}", pager, e),
+        }
+    }
+
+    let lines: Vec<String> = lines.into_iter().map(strip_ansi).collect();
+    Pager::new(lines, columns as usize, rows as usize).run()
+}
+
+/// Feed `text` to `$PAGER`, split on whitespace into a program and arguments
+fn run_external(pager: &str, text: &str) -> io::Result<()> {
+    let mut parts = pager.split_whitespace();
+    let program = parts.next().unwrap_or_default();
+    let mut child = Command::new(program).args(parts).stdin(Stdio::piped()).spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager closing early (the user quit) is not an error
+        match stdin.write_all(text.as_bytes()) {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e),
+            _ => {}
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
+
+struct Pager {
+    lines: Vec<String>,
+    columns: usize,
+    /// Rows for text, leaving the last one for the status line
+    rows: usize,
+    top: usize,
+    left: usize,
+}
+
+impl Pager {
+    fn new(lines: Vec<String>, columns: usize, rows: usize) -> Self {
+        Self {
+            lines,
+            columns,
+            rows: rows.saturating_sub(1).max(1),
+            top: 0,
+            left: 0,
+        }
+    }
+
+    fn last_top(&self) -> usize {
+        self.lines.len().saturating_sub(self.rows)
+    }
+
+    fn widest(&self) -> usize {
+        self.lines.iter().map(|line| line.chars().count()).max().unwrap_or(0)
+    }
+
+    fn scroll(&mut self, delta: isize) {
+        self.top = self.top.saturating_add_signed(delta).min(self.last_top());
+    }
+
+    fn run(&mut self) -> CLIERPResult<()> {
+        let _guard = TerminalGuard::enter()?;
+
+        loop {
+            self.draw()?;
+            let key = read_key()?;
+            let page = self.rows as isize;
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => break,
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                KeyCode::Down | KeyCode::Enter | KeyCode::Char('j') => self.scroll(1),
+                KeyCode::Up | KeyCode::Char('k') => self.scroll(-1),
+                KeyCode::PageDown | KeyCode::Char(' ') | KeyCode::Char('f') => self.scroll(page),
+                KeyCode::PageUp | KeyCode::Char('b') => self.scroll(-page),
+                KeyCode::Home | KeyCode::Char('g') => self.top = 0,
+                KeyCode::End | KeyCode::Char('G') => self.top = self.last_top(),
+                KeyCode::Right | KeyCode::Char('l') => {
+                    let max_left = self.widest().saturating_sub(self.columns);
+                    self.left = (self.left + HORIZONTAL_STEP).min(max_left);
+                }
+                KeyCode::Left | KeyCode::Char('h') => self.left = self.left.saturating_sub(HORIZONTAL_STEP),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn draw(&self) -> CLIERPResult<()> {
+        let mut stdout = io::stdout();
+        for row in 0..self.rows {
+            queue!(stdout, MoveTo(0, row as u16), Clear(ClearType::CurrentLine))?;
+            if let Some(line) = self.lines.get(self.top + row) {
+                queue!(stdout, Print(window(line, self.left, self.columns)))?;
+            }
+        }
+
+        let shown = (self.top + self.rows).min(self.lines.len());
+        let status = format!(
+            " Lines {}-{} of {}{}  q quit  space/b page  ←/→ columns",
+            self.top + 1,
+            shown,
+            self.lines.len(),
+            if shown == self.lines.len() { " (END)" } else { "" }
+        );
+        queue!(
+            stdout,
+            MoveTo(0, self.rows as u16),
+            Clear(ClearType::CurrentLine),
+            SetAttribute(Attribute::Reverse),
+            Print(window(&status, 0, self.columns)),
+            SetAttribute(Attribute::Reset)
+        )?;
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+/// Columns `left..left + width` of `line`, with `…` where text is cut on the right
+pub fn window(line: &str, left: usize, width: usize) -> String {
+    let visible: String = line.chars().skip(left).collect();
+    crate::cli::tui::truncate(&visible, width)
+}
+
+/// Remove ANSI escape sequences such as colours, which the built-in pager
+/// would otherwise count as columns
+pub fn strip_ansi(line: &str) -> String {
+    let mut plain = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            plain.push(c);
+            continue;
+        }
+        if chars.peek() == Some(&'[') {
+            chars.next();
+            // Parameters run up to a final byte in '@'..='~'
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window() {
+        assert_eq!(window("│ SKU-001 │ Widget │", 0, 30), "│ SKU-001 │ Widget │");
+        assert_eq!(window("│ SKU-001 │ Widget │", 0, 10), "│ SKU-001…");
+        assert_eq!(window("│ SKU-001 │ Widget │", 10, 10), "│ Widget │");
+        assert_eq!(window("short", 8, 10), "");
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi("\u{1b}[1;32mActive\u{1b}[0m"), "Active");
+        assert_eq!(strip_ansi("plain ₩1,000"), "plain ₩1,000");
+    }
+}
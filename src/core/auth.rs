@@ -1,16 +1,34 @@
+use crate::cli::commands::crm_extended::{ActivityAction, CampaignAction, CustomerAction, DealAction, LeadAction};
+use crate::core::command::{
+    AccountCommands, AnomalyCommands, ApCommands, AttendanceCommands, AuditCommands, AuthCommands,
+    BandCommands, BundleCommands, CLICommands, CategoryCommands, CollectionCommands,
+    CompCommands, ConfigCommands, CrmCommands, DeptCommands, DocsCommands, DropboxCommands,
+    DunningCommands, EmailTemplateCommands, EmployeeCommands, FinCommands, ForecastCommands,
+    GrniCommands, HrCommands, InvCommands, InvoiceCommands, JournalCommands, KpiCommands, LeaveCommands, LegalHoldCommands, LinkCommands,
+    PaymentCommands, PayrollCommands, ProductCommands, PurchaseCommands, PurchaseOrderCommands,
+    RaiseCommands, RemotePolicyCommands, RevenueCommands, RuleCommands, SalesCommands,
+    SalesOrderCommands, SodCommands, StockCommands, SupplierCommands, SurveyCommands,
+    SystemCommands, TemplateCommands, TransactionCommands, WriteDownCommands,
+};
 use crate::core::{config::CLIERPConfig, error::CLIERPError, result::CLIERPResult};
 use crate::database::{
-    connection::{DatabaseManager, get_connection},
+    connection::get_connection,
     models::{NewUser, User, UserRole},
     schema::users,
 };
 use bcrypt::{hash, verify};
 use chrono::{Duration, Utc};
 use diesel::prelude::*;
+use crate::utils::ldap::{DirectoryLogin, DirectoryUser, LdapDirectory};
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
 
+/// Password hash of users created at their first directory login; no
+/// password matches it, so they cannot log in while the directory is away
+pub const DIRECTORY_PASSWORD_HASH: &str = "!directory";
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,      // Subject (user id)
@@ -84,8 +102,20 @@ impl AuthService {
             .map_err(CLIERPError::Database)
     }
 
-    /// Authenticate user with username and password
+    /// Authenticate user with username and password: against the directory
+    /// when `ldap.enabled`, otherwise, or when the directory does not know
+    /// the user or cannot be reached, against local users
     pub fn authenticate(&self, username: &str, password: &str) -> CLIERPResult<AuthenticatedUser> {
+        if self.config.ldap.enabled {
+            match LdapDirectory::new(&self.config.ldap).login(username, password) {
+                DirectoryLogin::Accepted(entry) => return self.authenticate_directory_user(username, entry),
+                DirectoryLogin::Rejected => {}
+                DirectoryLogin::Unavailable(reason) => {
+                    tracing::warn!("Directory unavailable, checking local users: {}", reason)
+                }
+            }
+        }
+
         let mut conn = get_connection()?;
 
         let user: User = users::table
@@ -94,7 +124,7 @@ impl AuthService {
             .first(&mut conn)
             .map_err(|_| CLIERPError::Authentication("Invalid username or password".to_string()))?;
 
-        if !self.verify_password(password, &user.password_hash)? {
+        if user.password_hash == DIRECTORY_PASSWORD_HASH || !self.verify_password(password, &user.password_hash)? {
             return Err(CLIERPError::Authentication(
                 "Invalid username or password".to_string(),
             ));
@@ -106,13 +136,62 @@ impl AuthService {
             .execute(&mut conn)
             .map_err(CLIERPError::Database)?;
 
-        let role = match user.role.as_str() {
-            "admin" => UserRole::Admin,
-            "manager" => UserRole::Manager,
-            "supervisor" => UserRole::Supervisor,
-            "employee" => UserRole::Employee,
-            "auditor" => UserRole::Auditor,
-            _ => UserRole::Employee,
+        let role = parse_role(&user.role).unwrap_or(UserRole::Employee);
+
+        Ok(AuthenticatedUser {
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            role,
+            employee_id: user.employee_id,
+        })
+    }
+
+    /// Local user for a directory login, created on first login; the role
+    /// follows the directory groups on every login
+    fn authenticate_directory_user(&self, username: &str, entry: DirectoryUser) -> CLIERPResult<AuthenticatedUser> {
+        let role = directory_role(&self.config.ldap.group_roles, self.config.ldap.default_role.as_deref(), &entry.groups)
+            .ok_or_else(|| CLIERPError::Authentication(format!(
+                "{} is in no directory group with access to CLIERP",
+                username
+            )))?;
+        let mut conn = get_connection()?;
+
+        let existing = users::table
+            .filter(users::username.eq(username))
+            .first::<User>(&mut conn)
+            .optional()?;
+        let user = match existing {
+            Some(user) if !user.is_active => {
+                return Err(CLIERPError::Authentication(format!("User {} is deactivated", username)));
+            }
+            Some(user) => {
+                diesel::update(users::table.find(user.id))
+                    .set((
+                        users::role.eq(role.to_string()),
+                        users::email.eq(entry.email.clone().unwrap_or(user.email.clone())),
+                        users::last_login.eq(Utc::now().naive_utc()),
+                        users::updated_at.eq(Utc::now().naive_utc()),
+                    ))
+                    .execute(&mut conn)?;
+                users::table.find(user.id).first::<User>(&mut conn)?
+            }
+            None => {
+                diesel::insert_into(users::table)
+                    .values(&NewUser {
+                        username: username.to_string(),
+                        email: entry.email.clone().unwrap_or_else(|| format!("{}@clierp.local", username)),
+                        password_hash: DIRECTORY_PASSWORD_HASH.to_string(),
+                        employee_id: None,
+                        role: role.to_string(),
+                        is_active: true,
+                    })
+                    .execute(&mut conn)?;
+                tracing::info!("Created user {} from directory entry {}", username, entry.dn);
+                users::table
+                    .filter(users::username.eq(username))
+                    .first::<User>(&mut conn)?
+            }
         };
 
         Ok(AuthenticatedUser {
@@ -166,25 +245,7 @@ impl AuthService {
 
     /// Check if user has required role
     pub fn check_permission(&self, user_role: &UserRole, required_role: &UserRole) -> bool {
-        use UserRole::*;
-
-        let user_level = match user_role {
-            Admin => 5,
-            Manager => 4,
-            Supervisor => 3,
-            Employee => 2,
-            Auditor => 1,
-        };
-
-        let required_level = match required_role {
-            Admin => 5,
-            Manager => 4,
-            Supervisor => 3,
-            Employee => 2,
-            Auditor => 1,
-        };
-
-        user_level >= required_level
+        role_level(user_role) >= role_level(required_role)
     }
 
     /// Create default admin user if none exists
@@ -220,3 +281,533 @@ impl AuthService {
         Ok(())
     }
 }
+
+fn role_level(role: &UserRole) -> u8 {
+    match role {
+        UserRole::Admin => 5,
+        UserRole::Manager => 4,
+        UserRole::Supervisor => 3,
+        UserRole::Employee => 2,
+        UserRole::Auditor => 1,
+    }
+}
+
+pub fn parse_role(role: &str) -> Option<UserRole> {
+    match role {
+        "admin" => Some(UserRole::Admin),
+        "manager" => Some(UserRole::Manager),
+        "supervisor" => Some(UserRole::Supervisor),
+        "employee" => Some(UserRole::Employee),
+        "auditor" => Some(UserRole::Auditor),
+        _ => None,
+    }
+}
+
+/// Highest role mapped to any of `groups`, matched by DN or common name
+/// without case, else `default_role`
+pub fn directory_role(
+    group_roles: &HashMap<String, String>,
+    default_role: Option<&str>,
+    groups: &[String],
+) -> Option<UserRole> {
+    let common_name = |dn: &str| {
+        dn.split(',')
+            .next()
+            .and_then(|rdn| rdn.split_once('='))
+            .filter(|(attribute, _)| attribute.trim().eq_ignore_ascii_case("cn"))
+            .map(|(_, value)| value.trim().to_string())
+    };
+
+    groups
+        .iter()
+        .flat_map(|group| {
+            group_roles.iter().filter(move |(name, _)| {
+                name.eq_ignore_ascii_case(group)
+                    || common_name(group).is_some_and(|cn| name.eq_ignore_ascii_case(&cn))
+            })
+        })
+        .filter_map(|(_, role)| parse_role(role))
+        .max_by_key(role_level)
+        .or_else(|| default_role.and_then(parse_role))
+}
+
+/// What a user's role allows, checked before a command runs. Handlers may
+/// narrow this further, e.g. to admins or to the user's own records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    HrRead,
+    HrWrite,
+    /// Approve or reject leave and raises
+    HrApprove,
+    PayrollRead,
+    /// Calculate and commit payroll
+    PayrollRun,
+    FinanceRead,
+    FinanceWrite,
+    InventoryRead,
+    InventoryWrite,
+    /// Approve stock adjustments and write-downs
+    InventoryApprove,
+    CrmRead,
+    CrmWrite,
+    SalesRead,
+    SalesWrite,
+    PurchaseRead,
+    PurchaseWrite,
+    PurchaseApprove,
+    ReportsRead,
+    /// The record change log and its subscriptions
+    AuditRead,
+    /// Validation rules, KPIs, SoD rules and templates
+    ConfigWrite,
+    /// Database maintenance, archiving, deliveries and statistics
+    SystemManage,
+}
+
+impl Permission {
+    pub const ALL: &'static [Permission] = &[
+        Permission::HrRead,
+        Permission::HrWrite,
+        Permission::HrApprove,
+        Permission::PayrollRead,
+        Permission::PayrollRun,
+        Permission::FinanceRead,
+        Permission::FinanceWrite,
+        Permission::InventoryRead,
+        Permission::InventoryWrite,
+        Permission::InventoryApprove,
+        Permission::CrmRead,
+        Permission::CrmWrite,
+        Permission::SalesRead,
+        Permission::SalesWrite,
+        Permission::PurchaseRead,
+        Permission::PurchaseWrite,
+        Permission::PurchaseApprove,
+        Permission::ReportsRead,
+        Permission::AuditRead,
+        Permission::ConfigWrite,
+        Permission::SystemManage,
+    ];
+
+    /// What the permission lets a user do, for denial messages
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Permission::HrRead => "view HR records",
+            Permission::HrWrite => "change HR records",
+            Permission::HrApprove => "approve leave or raises",
+            Permission::PayrollRead => "view payroll",
+            Permission::PayrollRun => "run payroll",
+            Permission::FinanceRead => "view finance records",
+            Permission::FinanceWrite => "change finance records",
+            Permission::InventoryRead => "view inventory",
+            Permission::InventoryWrite => "change inventory",
+            Permission::InventoryApprove => "approve stock adjustments or write-downs",
+            Permission::CrmRead => "view CRM records",
+            Permission::CrmWrite => "change CRM records",
+            Permission::SalesRead => "view sales records",
+            Permission::SalesWrite => "change sales records",
+            Permission::PurchaseRead => "view purchasing records",
+            Permission::PurchaseWrite => "change purchasing records",
+            Permission::PurchaseApprove => "approve purchase orders",
+            Permission::ReportsRead => "run reports",
+            Permission::AuditRead => "read the change log",
+            Permission::ConfigWrite => "change rules, KPIs or templates",
+            Permission::SystemManage => "run system maintenance",
+        }
+    }
+}
+
+/// Permissions each role has
+pub fn role_permissions(role: &UserRole) -> &'static [Permission] {
+    use Permission::*;
+
+    match role {
+        UserRole::Admin | UserRole::Manager => Permission::ALL,
+        UserRole::Supervisor => &[
+            HrRead,
+            HrApprove,
+            PayrollRead,
+            FinanceRead,
+            InventoryRead,
+            InventoryWrite,
+            InventoryApprove,
+            CrmRead,
+            CrmWrite,
+            SalesRead,
+            SalesWrite,
+            PurchaseRead,
+            PurchaseWrite,
+            ReportsRead,
+        ],
+        UserRole::Employee => &[
+            InventoryRead,
+            InventoryWrite,
+            CrmRead,
+            CrmWrite,
+            SalesRead,
+            SalesWrite,
+            PurchaseRead,
+            PurchaseWrite,
+        ],
+        UserRole::Auditor => &[
+            HrRead,
+            PayrollRead,
+            FinanceRead,
+            InventoryRead,
+            CrmRead,
+            SalesRead,
+            PurchaseRead,
+            ReportsRead,
+            AuditRead,
+        ],
+    }
+}
+
+pub fn has_permission(role: &UserRole, permission: Permission) -> bool {
+    role_permissions(role).contains(&permission)
+}
+
+/// Fail unless `role` has `permission`
+pub fn require_permission(role: &UserRole, permission: Permission) -> CLIERPResult<()> {
+    if has_permission(role, permission) {
+        Ok(())
+    } else {
+        Err(CLIERPError::Authorization(format!(
+            "The {} role is not allowed to {}",
+            role,
+            permission.describe()
+        )))
+    }
+}
+
+/// Permission a command needs; `None` for what every user may do, such as
+/// logging in, checking in or requesting leave, and for setting up a
+/// database before anyone can log in
+pub fn required_permission(command: &CLICommands) -> Option<Permission> {
+    use Permission::*;
+
+    let either = |read: bool, if_read: Permission, if_write: Permission| {
+        Some(if read { if_read } else { if_write })
+    };
+
+    match command {
+        CLICommands::Auth { action: AuthCommands::Scim { .. } } => Some(SystemManage),
+        CLICommands::Auth { .. } | CLICommands::Shell => None,
+        CLICommands::Hr { action } => match action {
+            HrCommands::Dept { action } => either(
+                matches!(action, DeptCommands::List { .. } | DeptCommands::Show { .. } | DeptCommands::Export { .. }),
+                HrRead,
+                HrWrite,
+            ),
+            HrCommands::Employee { action } => either(
+                matches!(
+                    action,
+                    EmployeeCommands::List { .. }
+                        | EmployeeCommands::Show { .. }
+                        | EmployeeCommands::Search { .. }
+                        | EmployeeCommands::Export { .. }
+                ),
+                HrRead,
+                HrWrite,
+            ),
+            HrCommands::Attendance { action } => match action {
+                AttendanceCommands::Policy { action: RemotePolicyCommands::Show { .. } } => Some(HrRead),
+                AttendanceCommands::Policy { .. } => Some(HrWrite),
+                _ => None,
+            },
+            HrCommands::Payroll { action } => {
+                either(matches!(action, PayrollCommands::Status { .. }), PayrollRead, PayrollRun)
+            }
+            HrCommands::Comp { action } => match action {
+                CompCommands::Band { action } => either(
+                    matches!(action, BandCommands::List | BandCommands::Check { .. }),
+                    HrRead,
+                    HrWrite,
+                ),
+                CompCommands::Level { .. } => Some(HrWrite),
+                CompCommands::Raise { action } => match action {
+                    RaiseCommands::Propose { .. } => Some(HrWrite),
+                    RaiseCommands::List { .. } => Some(HrRead),
+                    _ => Some(HrApprove),
+                },
+                CompCommands::History { .. } => Some(HrRead),
+            },
+            HrCommands::Docs { action } => either(
+                matches!(
+                    action,
+                    DocsCommands::List { .. }
+                        | DocsCommands::Expiring { .. }
+                        | DocsCommands::Alerts { .. }
+                        | DocsCommands::Requirements
+                        | DocsCommands::Compliance { .. }
+                ),
+                HrRead,
+                HrWrite,
+            ),
+            HrCommands::Leave { action } => match action {
+                LeaveCommands::Approve { .. } | LeaveCommands::Reject { .. } => Some(HrApprove),
+                _ => None,
+            },
+            // Scoped to the user's own team by the handler
+            HrCommands::Team { .. } => None,
+        },
+        CLICommands::Fin { action } => either(fin_reads(action), FinanceRead, FinanceWrite),
+        CLICommands::Inv { action } => inv_permission(action),
+        CLICommands::Crm { action } => either(crm_reads(action), CrmRead, CrmWrite),
+        CLICommands::Sales { action } => either(sales_reads(action), SalesRead, SalesWrite),
+        CLICommands::Purchase { action } => match action {
+            PurchaseCommands::Supplier { action } => either(
+                matches!(action, SupplierCommands::List { .. } | SupplierCommands::Show { .. }),
+                PurchaseRead,
+                PurchaseWrite,
+            ),
+            PurchaseCommands::Order { action } => match action {
+                PurchaseOrderCommands::List { .. }
+                | PurchaseOrderCommands::Show { .. }
+                | PurchaseOrderCommands::Links { .. } => Some(PurchaseRead),
+                PurchaseOrderCommands::Approve { .. } => Some(PurchaseApprove),
+                _ => Some(PurchaseWrite),
+            },
+        },
+        CLICommands::Reports { .. } | CLICommands::Export { .. } => Some(ReportsRead),
+        CLICommands::System { action } => match action {
+            // Rolling back reverts schema changes; migrating forward stays
+            // open so a new database can be set up
+            SystemCommands::Migrate { rollback: Some(_), .. } => Some(SystemManage),
+            SystemCommands::Init
+            | SystemCommands::Status
+            | SystemCommands::Migrate { .. }
+            | SystemCommands::CreateAdmin
+            | SystemCommands::Doctor
+            | SystemCommands::Serve { .. }
+            | SystemCommands::Notifications { .. } => None,
+            SystemCommands::Dashboard { .. } => Some(ReportsRead),
+            SystemCommands::LegalHold { action: LegalHoldCommands::Report { .. } } => Some(AuditRead),
+            _ => Some(SystemManage),
+        },
+        CLICommands::Config { action } => match action {
+            // Each user's own defaults
+            ConfigCommands::Prefs { .. } => None,
+            ConfigCommands::Rules { action: RuleCommands::List { .. } }
+            | ConfigCommands::Kpi { action: KpiCommands::List | KpiCommands::Metrics }
+            | ConfigCommands::Sod { action: SodCommands::List | SodCommands::Overrides { .. } }
+            | ConfigCommands::Template { action: TemplateCommands::List | TemplateCommands::Show { .. } } => {
+                Some(ReportsRead)
+            }
+            _ => Some(ConfigWrite),
+        },
+        CLICommands::Link { action } => either(matches!(action, LinkCommands::List { .. }), CrmRead, CrmWrite),
+        CLICommands::Templates { action } => match action {
+            EmailTemplateCommands::Edit { .. } | EmailTemplateCommands::Reset { .. } => Some(ConfigWrite),
+            _ => Some(CrmRead),
+        },
+        CLICommands::Events { .. } => Some(AuditRead),
+    }
+}
+
+fn fin_reads(action: &FinCommands) -> bool {
+    match action {
+        FinCommands::Account { action } => {
+            matches!(action, AccountCommands::List { .. } | AccountCommands::Show { .. })
+        }
+        FinCommands::Transaction { action } => {
+            matches!(action, TransactionCommands::List { .. } | TransactionCommands::Show { .. })
+        }
+        FinCommands::Journal { action } => {
+            matches!(action, JournalCommands::List { .. } | JournalCommands::Show { .. })
+        }
+        FinCommands::Report { .. } | FinCommands::Calendar { .. } => true,
+        FinCommands::Anomalies { action } => matches!(action, AnomalyCommands::List { .. }),
+        FinCommands::OpeningBalances { .. } => false,
+        FinCommands::Payments { action } => matches!(
+            action,
+            PaymentCommands::Accounts | PaymentCommands::Batches { .. } | PaymentCommands::Show { .. }
+        ),
+        FinCommands::Dunning { action } => {
+            matches!(action, DunningCommands::Charges { .. } | DunningCommands::Exemptions)
+        }
+        FinCommands::Collections { action } => !matches!(action, CollectionCommands::Log { .. }),
+        FinCommands::Revenue { action } => {
+            matches!(action, RevenueCommands::Balance { .. } | RevenueCommands::Show { .. })
+        }
+        FinCommands::Grni { action } => !matches!(action, GrniCommands::Accrue { .. }),
+        FinCommands::Invoice { action } => matches!(
+            action,
+            InvoiceCommands::Show { .. } | InvoiceCommands::List { .. } | InvoiceCommands::Aging { .. }
+        ),
+        FinCommands::Ap { action } => matches!(
+            action,
+            ApCommands::Show { .. } | ApCommands::List { .. } | ApCommands::Aging { .. }
+        ),
+    }
+}
+
+fn inv_permission(action: &InvCommands) -> Option<Permission> {
+    use Permission::*;
+
+    let reads = match action {
+        InvCommands::Category { action } => {
+            matches!(action, CategoryCommands::List { .. } | CategoryCommands::Tree)
+        }
+        InvCommands::Product { action } => matches!(
+            action,
+            ProductCommands::List { .. }
+                | ProductCommands::Show { .. }
+                | ProductCommands::Translations { .. }
+                | ProductCommands::Attachments { .. }
+        ),
+        InvCommands::Bundle { action } => matches!(
+            action,
+            BundleCommands::List { .. } | BundleCommands::Show { .. } | BundleCommands::Quote { .. }
+        ),
+        InvCommands::Stock { action } => match action {
+            StockCommands::Approve { .. } | StockCommands::Reject { .. } => return Some(InventoryApprove),
+            _ => matches!(
+                action,
+                StockCommands::Check { .. }
+                    | StockCommands::History { .. }
+                    | StockCommands::Availability { .. }
+                    | StockCommands::Pending { .. }
+            ),
+        },
+        InvCommands::Audit { action } => {
+            matches!(action, AuditCommands::List { .. } | AuditCommands::Items { .. })
+        }
+        InvCommands::Browse { .. } | InvCommands::Intrastat { .. } | InvCommands::Catalog { .. } => true,
+        InvCommands::WriteDown { action } => match action {
+            WriteDownCommands::Approve { .. } | WriteDownCommands::Reject { .. } => return Some(InventoryApprove),
+            _ => matches!(action, WriteDownCommands::List { .. } | WriteDownCommands::Show { .. }),
+        },
+    };
+    Some(if reads { InventoryRead } else { InventoryWrite })
+}
+
+fn crm_reads(action: &CrmCommands) -> bool {
+    match action {
+        CrmCommands::Customer { action } => customer_reads(action),
+        CrmCommands::Lead { action } => lead_reads(action),
+        CrmCommands::Pipeline { .. } | CrmCommands::Churn { .. } => true,
+        CrmCommands::Forecast { action } => !matches!(action, ForecastCommands::Quota { .. }),
+        CrmCommands::Survey { action } => !matches!(action, SurveyCommands::Record { .. }),
+        CrmCommands::Dropbox { action } => matches!(action, DropboxCommands::Address),
+    }
+}
+
+fn sales_reads(action: &SalesCommands) -> bool {
+    match action {
+        SalesCommands::Customer { action } => customer_reads(action),
+        SalesCommands::Lead { action } => lead_reads(action),
+        SalesCommands::Deal { action } => matches!(
+            action,
+            DealAction::List { .. } | DealAction::Show { .. } | DealAction::ByStage { .. } | DealAction::Stats
+        ),
+        SalesCommands::Campaign { action } => matches!(
+            action,
+            CampaignAction::List { .. }
+                | CampaignAction::Show { .. }
+                | CampaignAction::ByStatus { .. }
+                | CampaignAction::Active
+                | CampaignAction::Performance
+                | CampaignAction::Stats
+                | CampaignAction::Attribution { .. }
+        ),
+        SalesCommands::Activity { action } => matches!(
+            action,
+            ActivityAction::List { .. }
+                | ActivityAction::Show { .. }
+                | ActivityAction::ByCustomer { .. }
+                | ActivityAction::ByLead { .. }
+                | ActivityAction::ByEmployee { .. }
+                | ActivityAction::Overdue
+                | ActivityAction::Stats
+        ),
+        SalesCommands::Dashboard
+        | SalesCommands::Pipeline
+        | SalesCommands::Performance
+        | SalesCommands::Atp { .. } => true,
+        SalesCommands::Order { action } => {
+            matches!(action, SalesOrderCommands::List { .. } | SalesOrderCommands::Show { .. })
+        }
+    }
+}
+
+fn customer_reads(action: &CustomerAction) -> bool {
+    matches!(
+        action,
+        CustomerAction::List { .. }
+            | CustomerAction::Show { .. }
+            | CustomerAction::Search { .. }
+            | CustomerAction::Stats
+    )
+}
+
+fn lead_reads(action: &LeadAction) -> bool {
+    matches!(
+        action,
+        LeadAction::List { .. } | LeadAction::Show { .. } | LeadAction::ByStatus { .. } | LeadAction::Stats
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directory_role() {
+        let group_roles: HashMap<String, String> = [
+            ("erp-users", "employee"),
+            ("CN=ERP Admins,OU=Groups,DC=corp,DC=example,DC=com", "admin"),
+        ]
+        .into_iter()
+        .map(|(group, role)| (group.to_string(), role.to_string()))
+        .collect();
+        let groups = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+
+        let role = directory_role(&group_roles, None, &groups(&["cn=erp-users,ou=groups,dc=example,dc=com"]));
+        assert!(matches!(role, Some(UserRole::Employee)));
+        let role = directory_role(
+            &group_roles,
+            None,
+            &groups(&["cn=erp-users,dc=x", "cn=erp admins,ou=groups,dc=corp,dc=example,dc=com"]),
+        );
+        assert!(matches!(role, Some(UserRole::Admin)));
+        assert!(directory_role(&group_roles, None, &groups(&["cn=sales,dc=x"])).is_none());
+        assert!(matches!(
+            directory_role(&group_roles, Some("auditor"), &groups(&[])),
+            Some(UserRole::Auditor)
+        ));
+    }
+
+    #[test]
+    fn test_role_permissions() {
+        assert!(!has_permission(&UserRole::Employee, Permission::PurchaseApprove));
+        assert!(!has_permission(&UserRole::Employee, Permission::PayrollRun));
+        assert!(has_permission(&UserRole::Employee, Permission::InventoryWrite));
+        assert!(!has_permission(&UserRole::Supervisor, Permission::PayrollRun));
+        assert!(has_permission(&UserRole::Manager, Permission::PurchaseApprove));
+        assert!(Permission::ALL.iter().all(|p| has_permission(&UserRole::Admin, *p)));
+        assert!(!has_permission(&UserRole::Auditor, Permission::FinanceWrite));
+        assert!(require_permission(&UserRole::Employee, Permission::PayrollRun).is_err());
+        // The stock adjustment approval workflow routes to supervisors
+        assert!(has_permission(&UserRole::Supervisor, Permission::InventoryApprove));
+        assert!(!has_permission(&UserRole::Employee, Permission::InventoryApprove));
+    }
+
+    #[test]
+    fn test_required_permission() {
+        use clap::Parser;
+        let required = |args: &[&str]| {
+            let args = crate::core::command::CLIArgs::try_parse_from(["clierp"].iter().chain(args)).unwrap();
+            required_permission(&args.command.unwrap())
+        };
+
+        assert_eq!(required(&["system", "migrate"]), None);
+        assert_eq!(required(&["system", "migrate", "--status"]), None);
+        assert_eq!(required(&["system", "migrate", "--rollback", "1"]), Some(Permission::SystemManage));
+
+        let approve = required(&["inv", "stock", "approve", "3"]).unwrap();
+        assert_eq!(approve, Permission::InventoryApprove);
+        assert!(require_permission(&UserRole::Supervisor, approve).is_ok());
+        assert!(require_permission(&UserRole::Employee, approve).is_err());
+    }
+}
@@ -1,96 +1,15 @@
-use crate::core::{auth::AuthenticatedUser, error::CLIERPError, result::CLIERPResult};
+use crate::cli::commands::crm_extended::{ActivityAction, CampaignAction, CustomerAction, DealAction, LeadAction};
+use crate::cli::commands::reports::ReportsCommands;
+use crate::modules::finance::{parse_invoice_line_arg, parse_journal_amount_arg, InvoiceLineRequest};
+use crate::modules::inventory::{parse_bundle_component_arg, BundleComponent};
+use crate::modules::system::links::{parse_record_ref_arg, RecordRef};
+use crate::utils::dates::{parse_date_arg, parse_period_arg};
+use crate::utils::export::{parse_template_column_arg, TemplateColumn};
+use crate::utils::filter_expr::{parse_filter_expr_arg, FilterExpr};
+use crate::utils::filters::{parse_field_assignment_arg, FieldAssignment};
+use crate::utils::table::TableArgs;
+use chrono::NaiveDate;
 use clap::{Parser, Subcommand};
-use std::collections::HashMap;
-
-/// Trait for implementing CLI commands
-pub trait Command {
-    /// Execute the command with the given arguments
-    fn execute(
-        &self,
-        args: &dyn std::any::Any,
-        user: Option<&AuthenticatedUser>,
-    ) -> CLIERPResult<()>;
-
-    /// Get command name
-    fn name(&self) -> &'static str;
-
-    /// Get command description
-    fn description(&self) -> &'static str;
-
-    /// Check if command requires authentication
-    fn requires_auth(&self) -> bool {
-        true
-    }
-
-    /// Get required minimum role for this command
-    fn required_role(&self) -> Option<&'static str> {
-        None
-    }
-}
-
-/// Command registry for managing available commands
-pub struct CommandRegistry {
-    commands: HashMap<String, Box<dyn Command>>,
-}
-
-impl CommandRegistry {
-    pub fn new() -> Self {
-        Self {
-            commands: HashMap::new(),
-        }
-    }
-
-    /// Register a new command
-    pub fn register<C: Command + 'static>(&mut self, command: C) {
-        self.commands
-            .insert(command.name().to_string(), Box::new(command));
-    }
-
-    /// Get a command by name
-    pub fn get(&self, name: &str) -> Option<&dyn Command> {
-        self.commands.get(name).map(|boxed| boxed.as_ref())
-    }
-
-    /// Get all available commands
-    pub fn get_all(&self) -> &HashMap<String, Box<dyn Command>> {
-        &self.commands
-    }
-
-    /// Execute a command
-    pub fn execute(
-        &self,
-        name: &str,
-        args: &dyn std::any::Any,
-        user: Option<&AuthenticatedUser>,
-    ) -> CLIERPResult<()> {
-        let command = self
-            .get(name)
-            .ok_or_else(|| CLIERPError::NotFound(format!("Command '{}' not found", name)))?;
-
-        // Check authentication requirements
-        if command.requires_auth() && user.is_none() {
-            return Err(CLIERPError::AuthenticationRequired);
-        }
-
-        // Check role requirements
-        if let (Some(required_role), Some(user)) = (command.required_role(), user) {
-            // Implementation of role checking logic would go here
-            tracing::debug!(
-                "Checking role requirement: {} for user: {}",
-                required_role,
-                user.username
-            );
-        }
-
-        command.execute(args, user)
-    }
-}
-
-impl Default for CommandRegistry {
-    fn default() -> Self {
-        Self::new()
-    }
-}
 
 /// Base CLI application structure
 #[derive(Parser)]
@@ -108,6 +27,30 @@ pub struct CLIArgs {
     /// Configuration file path
     #[arg(short, long)]
     pub config: Option<String>,
+
+    /// Bypass cached dashboard statistics and report results
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+
+    /// Answer yes to confirmation prompts, e.g. in scripts
+    #[arg(short = 'y', long, global = true)]
+    pub yes: bool,
+
+    /// Print long lists and reports straight to the terminal instead of a pager
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+
+    /// Print without colors; also turned on by the NO_COLOR environment variable
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Print stable ASCII output without colors or emojis, for logs and scripts
+    #[arg(long, global = true)]
+    pub plain: bool,
+
+    /// How errors are printed to stderr
+    #[arg(long, value_enum, global = true, default_value_t = crate::core::error::ErrorFormat::Text)]
+    pub error_format: crate::core::error::ErrorFormat,
 }
 
 #[derive(Subcommand)]
@@ -147,11 +90,128 @@ pub enum CLICommands {
         #[command(subcommand)]
         action: PurchaseCommands,
     },
+    /// Reports across modules, with saved snapshots
+    #[command(alias = "report")]
+    Reports {
+        /// Save the report run as a snapshot under this name, for later comparison
+        #[arg(long, global = true)]
+        save: Option<String>,
+        #[command(subcommand)]
+        action: ReportsCommands,
+    },
     /// System commands
     System {
         #[command(subcommand)]
         action: SystemCommands,
     },
+    /// Deployment configuration commands
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommands,
+    },
+    /// Link related records across modules
+    Link {
+        #[command(subcommand)]
+        action: LinkCommands,
+    },
+    /// Email templates with merge fields from customer, deal and invoice data
+    Templates {
+        #[command(subcommand)]
+        action: EmailTemplateCommands,
+    },
+    /// The log of record changes external systems subscribe to
+    Events {
+        #[command(subcommand)]
+        action: EventCommands,
+    },
+    /// Exports for tools outside CLIERP
+    Export {
+        #[command(subcommand)]
+        action: ExportCommands,
+    },
+    /// Interactive shell that runs commands without the `clierp` prefix, with
+    /// history, tab completion and the login kept alive between commands
+    Shell,
+}
+
+#[derive(Subcommand)]
+pub enum ExportCommands {
+    /// Write stock movements, transactions, deals and invoices as Parquet fact
+    /// tables with a date dimension, for DuckDB or a BI tool
+    Warehouse {
+        /// Directory the .parquet files are written to
+        #[arg(short, long)]
+        output: std::path::PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum EventCommands {
+    /// Print events as JSON lines, oldest first
+    Tail {
+        /// Print events after this sequence number (defaults to the last --limit events)
+        #[arg(long)]
+        since: Option<i64>,
+        /// Only events of this entity, e.g. customers
+        #[arg(long)]
+        entity: Option<String>,
+        /// Most events to print
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: i64,
+        /// Keep printing new events as they are logged
+        #[arg(short, long)]
+        follow: bool,
+    },
+    /// Create a link a subscriber reads the event stream from in server mode
+    Share {
+        /// Days the link stays valid; defaults to server.link_ttl_days
+        #[arg(long)]
+        days: Option<u32>,
+    },
+    /// List the event stream links you created
+    Links,
+    /// Revoke an event stream link before it expires
+    Revoke {
+        /// Link ID, as shown by `links`
+        link_id: i32,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum EmailTemplateCommands {
+    /// List templates, built-in and custom
+    List,
+    /// Show a template's subject and body
+    Show {
+        /// Template name, e.g. invoice_reminder
+        name: String,
+    },
+    /// List the merge fields templates may use, written as {{customer.name}}
+    Fields,
+    /// Edit a template in $EDITOR, or create a custom one (managers and above)
+    Edit {
+        /// Template name
+        name: String,
+        /// Read the text from this file instead: a "Subject: ..." line, a blank line and the body
+        #[arg(short, long)]
+        file: Option<std::path::PathBuf>,
+    },
+    /// Fill a template from records, without sending it
+    Preview {
+        /// Template name
+        name: String,
+        #[arg(long)]
+        customer: Option<i32>,
+        #[arg(long)]
+        deal: Option<i32>,
+        #[arg(long)]
+        invoice: Option<i32>,
+    },
+    /// Discard edits to a built-in template, or delete a custom one (managers and above)
+    Reset {
+        /// Template name
+        name: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -184,6 +244,28 @@ pub enum AuthCommands {
         #[arg(long)]
         employee_id: Option<i32>,
     },
+    /// Tokens an identity provider or HR system provisions users with
+    Scim {
+        #[command(subcommand)]
+        action: ScimCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ScimCommands {
+    /// Create a bearer token for the SCIM endpoint in server mode (admin only)
+    Share {
+        /// Days the token stays valid; defaults to server.link_ttl_days
+        #[arg(long)]
+        days: Option<u32>,
+    },
+    /// List the SCIM tokens you created
+    Links,
+    /// Revoke a SCIM token before it expires
+    Revoke {
+        /// Link ID, as shown by `links`
+        link_id: i32,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -208,6 +290,265 @@ pub enum HrCommands {
         #[command(subcommand)]
         action: PayrollCommands,
     },
+    /// Salary bands, raises and compensation history
+    Comp {
+        #[command(subcommand)]
+        action: CompCommands,
+    },
+    /// Employee documents, expiry alerts and compliance
+    Docs {
+        #[command(subcommand)]
+        action: DocsCommands,
+    },
+    /// Leave requests and approval
+    Leave {
+        #[command(subcommand)]
+        action: LeaveCommands,
+    },
+    /// Today at a glance for a manager's direct reports
+    Team {
+        /// Show the team you manage
+        #[arg(long)]
+        mine: bool,
+        /// Show the team of this manager (employee ID)
+        #[arg(short, long, conflicts_with = "mine", required_unless_present = "mine")]
+        manager: Option<i32>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CompCommands {
+    /// Salary bands per position and level
+    Band {
+        #[command(subcommand)]
+        action: BandCommands,
+    },
+    /// Set or clear the job level that picks an employee's band
+    Level {
+        /// Employee ID
+        employee_id: i32,
+        /// Level, e.g. junior or L3 (omit to clear)
+        level: Option<String>,
+    },
+    /// Salary raise proposals and approvals
+    Raise {
+        #[command(subcommand)]
+        action: RaiseCommands,
+    },
+    /// Show an employee's salary changes
+    History {
+        /// Employee ID
+        employee_id: i32,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum BandCommands {
+    /// Create or replace a salary band (admin only)
+    Set {
+        /// Position the band applies to
+        #[arg(short, long)]
+        position: String,
+        /// Level within the position (omit for a band covering all levels)
+        #[arg(short, long)]
+        level: Option<String>,
+        /// Lowest salary in the band
+        #[arg(long)]
+        min: i32,
+        /// Highest salary in the band
+        #[arg(long)]
+        max: i32,
+    },
+    /// List salary bands
+    List,
+    /// Remove a salary band (admin only)
+    Remove {
+        /// Band ID
+        band_id: i32,
+    },
+    /// Flag employees paid outside their band or without one
+    Check {
+        /// Show every employee, including those within their band
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum RaiseCommands {
+    /// Propose a new salary for an employee
+    Propose {
+        /// Employee ID
+        #[arg(short, long)]
+        employee_id: i32,
+        /// Proposed salary
+        #[arg(short, long)]
+        salary: i32,
+        /// Date the new salary takes effect (YYYY-MM-DD, today, "in 1 month")
+        #[arg(long, value_parser = parse_date_arg)]
+        effective: NaiveDate,
+        /// Why the raise is proposed
+        #[arg(short, long)]
+        reason: String,
+    },
+    /// List raises
+    List {
+        /// Only raises with this status
+        #[arg(long, value_enum)]
+        status: Option<crate::database::SalaryRaiseStatus>,
+        /// Maximum number of raises to show
+        #[arg(short, long, default_value = "50")]
+        limit: i64,
+    },
+    /// Approve a proposed raise (managers and above)
+    Approve {
+        /// Raise ID
+        raise_id: i32,
+        /// Note recorded with the approval
+        #[arg(short, long)]
+        note: Option<String>,
+        /// Reason for approving your own proposal, where the SoD rule allows it
+        #[arg(long)]
+        override_sod: Option<String>,
+    },
+    /// Reject a proposed raise (managers and above)
+    Reject {
+        /// Raise ID
+        raise_id: i32,
+        /// Why the raise is declined
+        #[arg(long)]
+        reason: String,
+    },
+    /// Update salaries of approved raises whose effective date has arrived
+    ApplyDue,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum LeaveCommands {
+    /// Request leave
+    Request {
+        /// Employee ID (defaults to your own)
+        #[arg(short, long)]
+        employee_id: Option<i32>,
+        /// First day of leave
+        #[arg(long, value_parser = parse_date_arg)]
+        from: NaiveDate,
+        /// Last day of leave
+        #[arg(long, value_parser = parse_date_arg)]
+        to: NaiveDate,
+        /// Reason for the leave
+        #[arg(short, long)]
+        reason: Option<String>,
+    },
+    /// List leave requests
+    List {
+        /// Employee ID
+        #[arg(short, long)]
+        employee_id: Option<i32>,
+        /// Status filter
+        #[arg(short, long, value_enum)]
+        status: Option<crate::database::LeaveStatus>,
+    },
+    /// Approve a pending request
+    Approve {
+        /// Leave request ID
+        id: i32,
+        /// Note for the employee
+        #[arg(short, long)]
+        note: Option<String>,
+    },
+    /// Reject a pending request
+    Reject {
+        /// Leave request ID
+        id: i32,
+        /// Why the leave was rejected
+        #[arg(short, long)]
+        reason: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DocsCommands {
+    /// Record a document for an employee
+    Add {
+        /// Employee ID
+        #[arg(short, long)]
+        employee_id: i32,
+        /// Document type
+        #[arg(short = 't', long = "type", value_enum)]
+        document_type: crate::database::DocumentType,
+        /// Permit, passport or contract number
+        #[arg(short, long)]
+        number: Option<String>,
+        /// Date the document was issued or signed
+        #[arg(long, value_parser = parse_date_arg)]
+        issued: Option<NaiveDate>,
+        /// Date the document expires, e.g. a permit or fixed-term contract end
+        #[arg(long, value_parser = parse_date_arg)]
+        expires: Option<NaiveDate>,
+        /// Scanned copy to keep with the record
+        #[arg(short, long)]
+        file: Option<std::path::PathBuf>,
+        /// Notes
+        #[arg(long)]
+        notes: Option<String>,
+    },
+    /// List an employee's documents
+    List {
+        /// Employee ID
+        employee_id: i32,
+    },
+    /// Record a renewed expiry date for a document
+    Renew {
+        /// Document ID
+        document_id: i32,
+        /// New expiry date (omit if the document no longer expires)
+        #[arg(long, value_parser = parse_date_arg)]
+        expires: Option<NaiveDate>,
+    },
+    /// Remove a document and its stored copy
+    Remove {
+        /// Document ID
+        document_id: i32,
+    },
+    /// List documents of current employees that expire soon or have expired
+    Expiring {
+        /// Days ahead to look
+        #[arg(short, long, default_value = "30")]
+        days: i64,
+    },
+    /// Alert HR about documents expiring soon that have not been alerted yet
+    Alerts {
+        /// Days ahead to look
+        #[arg(short, long, default_value = "30")]
+        days: i64,
+    },
+    /// Require a document type of every employee, or of one department (admin only)
+    Require {
+        /// Document type
+        #[arg(value_enum)]
+        document_type: crate::database::DocumentType,
+        /// Department ID (omit for every department)
+        #[arg(short, long)]
+        department_id: Option<i32>,
+    },
+    /// Stop requiring a document type (admin only)
+    Unrequire {
+        /// Document type
+        #[arg(value_enum)]
+        document_type: crate::database::DocumentType,
+        /// Department ID (omit for the company-wide requirement)
+        #[arg(short, long)]
+        department_id: Option<i32>,
+    },
+    /// List required document types
+    Requirements,
+    /// Show employees missing a required document or holding an expired one
+    Compliance {
+        /// Only this department
+        #[arg(short, long)]
+        department_id: Option<i32>,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -220,9 +561,15 @@ pub enum DeptCommands {
         /// Department description
         #[arg(short, long)]
         description: Option<String>,
+        /// Manager (user ID)
+        #[arg(short, long)]
+        manager_id: Option<i32>,
     },
     /// List all departments
-    List,
+    List {
+        #[command(flatten)]
+        table: TableArgs,
+    },
     /// Show department details
     Show {
         /// Department ID
@@ -238,27 +585,45 @@ pub enum DeptCommands {
         /// New description
         #[arg(short, long)]
         description: Option<String>,
+        /// New manager (user ID)
+        #[arg(short, long)]
+        manager_id: Option<i32>,
     },
     /// Delete department
     Delete {
         /// Department ID
         id: i32,
     },
+    /// Export departments to CSV or JSON
+    Export {
+        /// Output format (csv, json)
+        #[arg(short, long, default_value = "csv")]
+        format: String,
+        /// Output file (default: departments_<timestamp>.<format>)
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Partner export template to lay the CSV out with (see `config template`)
+        #[arg(short, long)]
+        template: Option<String>,
+    },
 }
 
 #[derive(Debug, Subcommand)]
 pub enum EmployeeCommands {
     /// Add a new employee
     Add {
-        /// Employee code
+        /// Employee code (default: assigned automatically, e.g. EMP000042)
         #[arg(short, long)]
-        code: String,
+        code: Option<String>,
         /// Employee name
         #[arg(short, long)]
         name: String,
         /// Email
         #[arg(short, long)]
         email: Option<String>,
+        /// Phone
+        #[arg(long)]
+        phone: Option<String>,
         /// Department ID
         #[arg(short, long)]
         department_id: i32,
@@ -268,6 +633,9 @@ pub enum EmployeeCommands {
         /// Salary
         #[arg(short, long)]
         salary: i32,
+        /// Hire date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        hire_date: Option<NaiveDate>,
     },
     /// List employees
     List {
@@ -277,11 +645,19 @@ pub enum EmployeeCommands {
         /// Filter by status
         #[arg(short, long)]
         status: Option<String>,
+        /// Only employees matching this expression, e.g. "salary >= 3000000 and position like 'Eng*'"
+        #[arg(long = "where", value_parser = parse_filter_expr_arg)]
+        filter: Option<FilterExpr>,
+        #[command(flatten)]
+        table: TableArgs,
     },
     /// Show employee details
     Show {
         /// Employee ID
         id: i32,
+        /// Also show the employee's change history
+        #[arg(long)]
+        history: bool,
     },
     /// Update employee
     Update {
@@ -290,18 +666,53 @@ pub enum EmployeeCommands {
         /// New name
         #[arg(short, long)]
         name: Option<String>,
+        /// New email
+        #[arg(short, long)]
+        email: Option<String>,
+        /// New phone
+        #[arg(long)]
+        phone: Option<String>,
+        /// New department ID
+        #[arg(short, long)]
+        department_id: Option<i32>,
         /// New position
         #[arg(short, long)]
         position: Option<String>,
         /// New salary
         #[arg(short, long)]
         salary: Option<i32>,
+        /// New status (active, inactive, terminated)
+        #[arg(long)]
+        status: Option<String>,
+    },
+    /// Search employees by name, code or email
+    Search {
+        /// Search term
+        query: String,
     },
-    /// Delete employee
+    /// Terminate employee (the record is kept)
     Delete {
         /// Employee ID
         id: i32,
     },
+    /// Export employees to CSV or JSON
+    Export {
+        /// Output format (csv, json)
+        #[arg(short, long, default_value = "csv")]
+        format: String,
+        /// Output file (default: employees_<timestamp>.<format>)
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Only employees of this department
+        #[arg(short, long)]
+        department: Option<i32>,
+        /// Partner export template to lay the CSV out with (see `config template`)
+        #[arg(short, long)]
+        template: Option<String>,
+        /// Only employees matching this expression, e.g. "status = active and hired >= 2024-01-01"
+        #[arg(long = "where", value_parser = parse_filter_expr_arg)]
+        filter: Option<FilterExpr>,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -311,21 +722,84 @@ pub enum AttendanceCommands {
         /// Employee ID
         #[arg(short, long)]
         employee_id: i32,
+        /// Latitude of the punch (required by some remote policies)
+        #[arg(long, allow_hyphen_values = true)]
+        lat: Option<f64>,
+        /// Longitude of the punch
+        #[arg(long, allow_hyphen_values = true)]
+        lon: Option<f64>,
+        /// Device fingerprint of the punch
+        #[arg(long)]
+        device: Option<String>,
     },
     /// Check out
     Checkout {
         /// Employee ID
         #[arg(short, long)]
         employee_id: i32,
+        /// Latitude of the punch (required by some remote policies)
+        #[arg(long, allow_hyphen_values = true)]
+        lat: Option<f64>,
+        /// Longitude of the punch
+        #[arg(long, allow_hyphen_values = true)]
+        lon: Option<f64>,
+        /// Device fingerprint of the punch
+        #[arg(long)]
+        device: Option<String>,
+    },
+    /// Check-in policies for remote employees
+    Policy {
+        #[command(subcommand)]
+        action: RemotePolicyCommands,
     },
     /// Show attendance status
     Status {
         /// Employee ID
         #[arg(short, long)]
         employee_id: Option<i32>,
-        /// Date (YYYY-MM-DD)
+        /// Date (YYYY-MM-DD, today, yesterday, "3 days ago")
+        #[arg(short, long, value_parser = parse_date_arg)]
+        date: Option<NaiveDate>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum RemotePolicyCommands {
+    /// Designate an employee as remote and set what punches must carry
+    Set {
+        /// Employee ID
+        #[arg(short, long)]
+        employee_id: i32,
+        /// Require GPS coordinates on every punch
+        #[arg(long)]
+        require_location: bool,
+        /// Require a device fingerprint on every punch
+        #[arg(long)]
+        require_device: bool,
+        /// Only accept punches from this device
+        #[arg(long)]
+        device: Option<String>,
+        /// Latitude of the work site
+        #[arg(long, allow_hyphen_values = true, requires_all = ["site_lon", "radius"])]
+        site_lat: Option<f64>,
+        /// Longitude of the work site
+        #[arg(long, allow_hyphen_values = true, requires = "site_lat")]
+        site_lon: Option<f64>,
+        /// Allowed distance from the work site in meters
+        #[arg(long, requires = "site_lat")]
+        radius: Option<i32>,
+    },
+    /// Show the policy of an employee, or all policies
+    Show {
+        /// Employee ID
+        #[arg(short, long)]
+        employee_id: Option<i32>,
+    },
+    /// Stop treating an employee as remote
+    Clear {
+        /// Employee ID
         #[arg(short, long)]
-        date: Option<String>,
+        employee_id: i32,
     },
 }
 
@@ -333,8 +807,8 @@ pub enum AttendanceCommands {
 pub enum PayrollCommands {
     /// Calculate payroll
     Calculate {
-        /// Period (YYYY-MM)
-        #[arg(short, long)]
+        /// Period (YYYY-MM, FY2025-P03, this-month, last-month)
+        #[arg(short, long, value_parser = parse_period_arg)]
         period: String,
         /// Employee ID (optional, calculates for all if not provided)
         #[arg(short, long)]
@@ -342,10 +816,19 @@ pub enum PayrollCommands {
     },
     /// Show payroll status
     Status {
-        /// Period (YYYY-MM)
-        #[arg(short, long)]
+        /// Period (YYYY-MM, FY2025-P03, this-month, last-month)
+        #[arg(short, long, value_parser = parse_period_arg)]
         period: String,
     },
+    /// Calculate payroll for every active employee and show it for review
+    Run {
+        /// Period (YYYY-MM, FY2025-P03, this-month, last-month)
+        #[arg(short, long, value_parser = parse_period_arg)]
+        period: String,
+        /// Save the payrolls and post them to the general ledger (managers and above)
+        #[arg(long)]
+        commit: bool,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -360,29 +843,549 @@ pub enum FinCommands {
         #[command(subcommand)]
         action: TransactionCommands,
     },
+    /// Balanced journal entries
+    Journal {
+        #[command(subcommand)]
+        action: JournalCommands,
+    },
     /// Reports
     Report {
         #[command(subcommand)]
         action: ReportCommands,
     },
+    /// Show fiscal calendar periods
+    Calendar {
+        /// Fiscal year (defaults to the current fiscal year)
+        #[arg(long)]
+        year: Option<i32>,
+    },
+    /// Unusual transactions and stock movements flagged for review
+    Anomalies {
+        #[command(subcommand)]
+        action: AnomalyCommands,
+    },
+    /// Starting balances for go-live
+    OpeningBalances {
+        #[command(subcommand)]
+        action: OpeningBalanceCommands,
+    },
+    /// Bank payment files for salaries and supplier invoices
+    Payments {
+        #[command(subcommand)]
+        action: PaymentCommands,
+    },
+    /// Late fees and interest on overdue customer invoices
+    Dunning {
+        #[command(subcommand)]
+        action: DunningCommands,
+    },
+    /// Collections calls to customers with overdue invoices
+    Collections {
+        #[command(subcommand)]
+        action: CollectionCommands,
+    },
+    /// Revenue recognition schedules for prepaid licenses and contracts
+    Revenue {
+        #[command(subcommand)]
+        action: RevenueCommands,
+    },
+    /// Goods received not invoiced, accrued at period end
+    Grni {
+        #[command(subcommand)]
+        action: GrniCommands,
+    },
+    /// Customer invoices and receivables aging
+    Invoice {
+        #[command(subcommand)]
+        action: InvoiceCommands,
+    },
+    /// Supplier bills, payments on them and what falls due when
+    Ap {
+        #[command(subcommand)]
+        action: ApCommands,
+    },
 }
 
 #[derive(Debug, Subcommand)]
-pub enum AccountCommands {
-    /// Add account
-    Add {
-        /// Account code
+pub enum ApCommands {
+    /// Record a supplier bill for a purchase order
+    Bill {
+        /// Purchase order ID
+        po_id: i32,
+        /// Supplier's bill number
+        #[arg(long)]
+        number: String,
+        /// Bill total
+        #[arg(short, long)]
+        amount: i32,
+        /// Bill date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        date: Option<NaiveDate>,
+        /// Due date; defaults to the supplier's payment terms
+        #[arg(long, value_parser = parse_date_arg)]
+        due: Option<NaiveDate>,
+    },
+    /// Record a payment on a bill; partial payments leave the rest open
+    Pay {
+        /// Bill ID
+        bill_id: i32,
+        /// Amount paid; defaults to the open amount
+        #[arg(short, long)]
+        amount: Option<i32>,
+        /// Payment date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        date: Option<NaiveDate>,
+        /// Bank reference
+        #[arg(long)]
+        reference: Option<String>,
+    },
+    /// Show a bill with its payments
+    Show {
+        /// Bill ID
+        bill_id: i32,
+    },
+    /// List bills, earliest due first
+    List {
+        /// Only bills of this supplier
+        #[arg(long)]
+        supplier: Option<i32>,
+        /// Only bills with an open amount
+        #[arg(long)]
+        open: bool,
+        /// Maximum number of bills to show
+        #[arg(short, long, default_value = "50")]
+        limit: i64,
+    },
+    /// Open bills by supplier and days until due
+    Aging {
+        /// Count days from this date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        as_of: Option<NaiveDate>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum InvoiceCommands {
+    /// Draft an invoice; it is numbered straight away and sent separately
+    Create {
+        /// Customer ID
+        #[arg(short, long)]
+        customer: i32,
+        /// Line as DESCRIPTION:QUANTITY:UNIT_PRICE, repeatable
+        #[arg(short, long = "line", required = true, value_parser = parse_invoice_line_arg)]
+        lines: Vec<InvoiceLineRequest>,
+        /// Invoice date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        date: Option<NaiveDate>,
+        /// Due date; defaults to the invoice date plus --terms days
+        #[arg(long, value_parser = parse_date_arg, conflicts_with = "terms")]
+        due: Option<NaiveDate>,
+        /// Payment terms in days
+        #[arg(long, default_value = "30")]
+        terms: i64,
+        /// Description
+        #[arg(long)]
+        description: Option<String>,
+    },
+    /// Issue an invoice and record that it went to the customer
+    Send {
+        /// Invoice ID
+        invoice_id: i32,
+    },
+    /// Apply a customer payment to an invoice
+    Pay {
+        /// Invoice ID
+        invoice_id: i32,
+        /// Amount paid; defaults to the open amount
+        #[arg(short, long)]
+        amount: Option<i32>,
+        /// Payment date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        date: Option<NaiveDate>,
+        /// Bank or remittance reference
+        #[arg(long)]
+        reference: Option<String>,
+    },
+    /// Cancel an invoice nothing has been paid on
+    Cancel {
+        /// Invoice ID
+        invoice_id: i32,
+    },
+    /// Show an invoice with its lines and payments
+    Show {
+        /// Invoice ID
+        invoice_id: i32,
+    },
+    /// List customer invoices
+    List {
+        /// Only invoices of this customer
+        #[arg(long)]
+        customer: Option<i32>,
+        #[arg(long, value_enum)]
+        status: Option<crate::modules::finance::InvoiceState>,
+        /// Maximum number of invoices to show
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+    /// Open receivables by customer and days past due
+    Aging {
+        /// Age as of this date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        as_of: Option<NaiveDate>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum GrniCommands {
+    /// Purchase orders with receipts not yet invoiced by the supplier
+    Report {
+        /// Receipts and invoices up to this date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        as_of: Option<NaiveDate>,
+    },
+    /// Accrue the uninvoiced receipts of a period and post the reversal on
+    /// the next day; each period is accrued once, e.g. at month end from cron
+    Accrue {
+        /// Period (YYYY-MM, FY2025-P03, this-month, last-month)
+        #[arg(short, long, value_parser = parse_period_arg)]
+        period: String,
+        /// Show what would be accrued without posting it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Accruals made so far
+    Accruals {
+        /// Maximum number of periods to show
+        #[arg(short, long, default_value = "12")]
+        limit: i64,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum RevenueCommands {
+    /// Defer the revenue of an invoice and recognize it monthly
+    Defer {
+        /// Invoice ID
+        invoice_id: i32,
+        /// Number of months the revenue is earned over
+        #[arg(long)]
+        months: i32,
+        /// First month of the service (default: the invoice date)
+        #[arg(long, value_parser = parse_date_arg)]
+        start: Option<NaiveDate>,
+        /// Amount to defer; defaults to the invoice total
+        #[arg(short, long)]
+        amount: Option<i32>,
+        /// What the revenue is for, e.g. "Annual license 2025"
+        #[arg(long)]
+        description: Option<String>,
+    },
+    /// Recognize the months that have ended; safe to repeat, e.g. monthly from cron
+    Recognize {
+        /// Recognize months ended by this date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        as_of: Option<NaiveDate>,
+        /// Only this schedule
+        #[arg(long)]
+        schedule: Option<i32>,
+        /// Show what would be recognized without posting it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Deferred revenue balance per schedule
+    Balance {
+        /// Balance as of this date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        as_of: Option<NaiveDate>,
+        /// Include fully recognized schedules
+        #[arg(long)]
+        all: bool,
+    },
+    /// Show a schedule with the months recognized so far
+    Show {
+        /// Schedule ID
+        schedule_id: i32,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CollectionCommands {
+    /// Customers to call, escalated and longest-overdue amounts first
+    Queue {
+        /// Maximum number of customers to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+    /// Record the outcome of a call
+    Log {
+        /// Customer ID
+        customer_id: i32,
+        #[arg(long, value_enum)]
+        outcome: crate::database::CallOutcome,
+        /// What was said
+        #[arg(long)]
+        notes: Option<String>,
+        /// Date the customer promised to pay by (outcome promised)
+        #[arg(long, value_parser = parse_date_arg)]
+        promise_date: Option<NaiveDate>,
+        /// Amount promised; defaults to everything overdue
+        #[arg(long)]
+        promise_amount: Option<i32>,
+    },
+    /// Calls to a customer and the promises made on them
+    History {
+        /// Customer ID
+        customer_id: i32,
+    },
+    /// List promises to pay
+    Promises {
+        #[arg(long, value_enum)]
+        status: Option<crate::database::PromiseStatus>,
+        /// Maximum number of promises to show
+        #[arg(short, long, default_value = "50")]
+        limit: i64,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DunningCommands {
+    /// Charge the fees and interest due on overdue invoices; safe to repeat, e.g. daily from cron
+    Run {
+        /// Charge as of this date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        as_of: Option<NaiveDate>,
+        /// Only this invoice
+        #[arg(long)]
+        invoice: Option<i32>,
+        /// Show what would be charged without saving it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// List charges made
+    Charges {
+        /// Only charges on this invoice
+        #[arg(long)]
+        invoice: Option<i32>,
+        /// Only charges on invoices of this customer
+        #[arg(long)]
+        customer: Option<i32>,
+        /// Maximum number of charges to show
+        #[arg(short, long, default_value = "50")]
+        limit: i64,
+    },
+    /// Never charge a customer's overdue invoices
+    Exempt {
+        /// Customer ID
+        customer_id: i32,
+        /// Why the customer is exempt
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Charge a customer's overdue invoices again
+    Unexempt {
+        /// Customer ID
+        customer_id: i32,
+    },
+    /// List exempt customers
+    Exemptions,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PaymentCommands {
+    /// Record the bank account of an employee or supplier (admin only)
+    SetAccount {
+        /// Employee ID
+        #[arg(short, long, conflicts_with = "supplier", required_unless_present = "supplier")]
+        employee: Option<i32>,
+        /// Supplier ID
+        #[arg(short, long)]
+        supplier: Option<i32>,
+        /// IBAN or account number
+        #[arg(short, long)]
+        account: String,
+        /// BIC of the payee's bank
+        #[arg(short, long)]
+        bic: Option<String>,
+        /// Account holder, when not the payee's name
+        #[arg(long)]
+        holder: Option<String>,
+    },
+    /// List recorded bank accounts
+    Accounts,
+    /// Write a bank file with the payments that are due and record it as a batch
+    Export {
+        /// Payments to include
+        #[arg(short, long, value_enum)]
+        kind: crate::database::PaymentBatchKind,
+        /// Payroll period for salary payments (YYYY-MM, FY2025-P03, this-month, last-month)
+        #[arg(short, long, value_parser = parse_period_arg, required_if_eq("kind", "payroll"))]
+        period: Option<String>,
+        /// Include supplier invoices due on or before this date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        due_by: Option<NaiveDate>,
+        /// Bank file format
+        #[arg(short, long, value_enum, default_value = "pain001")]
+        format: crate::database::PaymentFileFormat,
+        /// File to write
+        #[arg(short, long)]
+        output: std::path::PathBuf,
+        /// Date the bank should execute the payments (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        execution_date: Option<NaiveDate>,
+    },
+    /// List payment batches
+    Batches {
+        /// Maximum number of batches to show
+        #[arg(short, long, default_value = "20")]
+        limit: i64,
+    },
+    /// Show the payments of a batch
+    Show {
+        /// Batch ID
+        batch_id: i32,
+    },
+    /// Mark the payments of a batch as paid once the bank has accepted the file
+    Confirm {
+        /// Batch ID
+        batch_id: i32,
+        /// Reason for paying a payroll you prepared, where the SoD rule allows it
+        #[arg(long)]
+        override_sod: Option<String>,
+    },
+    /// Discard a batch so its payments can be exported again
+    Cancel {
+        /// Batch ID
+        batch_id: i32,
+    },
+    /// Record a customer payment against a receivable invoice
+    Receive {
+        /// Invoice ID
+        invoice_id: i32,
+        /// Amount received; defaults to the open amount
+        #[arg(short, long)]
+        amount: Option<i32>,
+        /// Date the payment arrived (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        date: Option<NaiveDate>,
+        /// Bank reference or receipt number
+        #[arg(short, long)]
+        reference: Option<String>,
+    },
+    /// Record that the bank returned a customer payment, reopening its invoice
+    Return {
+        /// Payment ID
+        payment_id: i32,
+        /// Date the bank returned it (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        date: Option<NaiveDate>,
+        /// Why it was returned, e.g. insufficient funds
+        #[arg(long)]
+        reason: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum OpeningBalanceCommands {
+    /// Post the opening journal, stock and open AR/AP documents from a CSV
+    /// with type (account, stock, receivable, payable), code, quantity,
+    /// amount, document and due_date columns
+    Import {
+        /// CSV file path
+        #[arg(short, long)]
+        file: String,
+        /// Date the balances are valid at (YYYY-MM-DD)
+        #[arg(long, value_parser = parse_date_arg)]
+        as_of: NaiveDate,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AnomalyCommands {
+    /// Scan for outliers and flag new ones for review
+    Scan {
+        /// Records to scan
+        #[arg(short, long, value_enum, default_value = "all")]
+        source: crate::modules::system::AnomalySource,
+        /// Outlier test
+        #[arg(short, long, value_enum, default_value = "zscore")]
+        method: crate::utils::anomaly::AnomalyMethod,
+        /// Cut-off score (default 3 for zscore, 1.5 for iqr)
+        #[arg(short, long)]
+        threshold: Option<f64>,
+        /// Only consider records from this date on
+        #[arg(long, value_parser = parse_date_arg)]
+        since: Option<NaiveDate>,
+    },
+    /// List flagged anomalies
+    List {
+        /// Only show flags with this status (default: open)
+        #[arg(long, value_enum, default_value = "open")]
+        status: crate::database::AnomalyStatus,
+        /// Number of flags to show
+        #[arg(short, long, default_value = "50")]
+        limit: i64,
+    },
+    /// Confirm or dismiss a flagged anomaly
+    Review {
+        /// Flag ID
+        id: i32,
+        /// Review outcome
+        #[arg(value_enum)]
+        status: crate::database::AnomalyStatus,
+        /// Review note
+        #[arg(short, long)]
+        note: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AccountCommands {
+    /// Add account
+    Add {
+        /// Account code
         #[arg(short, long)]
         code: String,
         /// Account name
         #[arg(short, long)]
         name: String,
-        /// Account type
+        /// Account type (asset, liability, equity, revenue, expense)
         #[arg(short, long)]
         account_type: String,
+        /// Parent account ID
+        #[arg(short, long)]
+        parent_id: Option<i32>,
     },
     /// List accounts
-    List,
+    List {
+        /// Only accounts of this type
+        #[arg(short, long)]
+        account_type: Option<String>,
+        /// Show the chart of accounts as a tree
+        #[arg(long)]
+        tree: bool,
+    },
+    /// Show account details and recent transactions
+    Show {
+        /// Account ID
+        id: i32,
+    },
+    /// Update account
+    Update {
+        /// Account ID
+        id: i32,
+        /// New account code
+        #[arg(short, long)]
+        code: Option<String>,
+        /// New name
+        #[arg(short, long)]
+        name: Option<String>,
+        /// New account type
+        #[arg(short, long)]
+        account_type: Option<String>,
+        /// New parent account ID
+        #[arg(short, long)]
+        parent_id: Option<i32>,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -393,43 +1396,401 @@ pub enum TransactionCommands {
         #[arg(short, long)]
         account_id: i32,
         /// Amount
-        #[arg(short, long)]
+        #[arg(short = 'm', long)]
         amount: i32,
         /// Type (debit/credit)
         #[arg(short, long)]
         transaction_type: String,
+        /// Account ID posted the other side, so the entry balances
+        #[arg(long)]
+        contra_account_id: i32,
         /// Description
         #[arg(short, long)]
         description: String,
+        /// Transaction date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        date: Option<NaiveDate>,
+        /// Reference (invoice number, receipt, ...)
+        #[arg(short, long)]
+        reference: Option<String>,
     },
     /// List transactions
     List {
         /// Account ID filter
         #[arg(short, long)]
         account_id: Option<i32>,
+        /// Start date
+        #[arg(long, value_parser = parse_date_arg)]
+        from: Option<NaiveDate>,
+        /// End date
+        #[arg(long, value_parser = parse_date_arg)]
+        to: Option<NaiveDate>,
+    },
+    /// Show transaction details
+    Show {
+        /// Transaction ID
+        id: i32,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum JournalCommands {
+    /// Post an entry; debits and credits must net to zero
+    Post {
+        /// Description
+        #[arg(short, long)]
+        description: String,
+        /// Debit line ACCOUNT_ID:AMOUNT (repeatable)
+        #[arg(long = "debit", value_parser = parse_journal_amount_arg, required = true)]
+        debits: Vec<(i32, i32)>,
+        /// Credit line ACCOUNT_ID:AMOUNT (repeatable)
+        #[arg(long = "credit", value_parser = parse_journal_amount_arg, required = true)]
+        credits: Vec<(i32, i32)>,
+        /// Entry date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        date: Option<NaiveDate>,
+        /// Reference (invoice number, receipt, ...)
+        #[arg(short, long)]
+        reference: Option<String>,
+    },
+    /// List journal entries
+    List {
+        /// Start date
+        #[arg(long, value_parser = parse_date_arg)]
+        from: Option<NaiveDate>,
+        /// End date
+        #[arg(long, value_parser = parse_date_arg)]
+        to: Option<NaiveDate>,
+        /// Maximum number of entries
+        #[arg(short, long, default_value = "50")]
+        limit: i64,
+    },
+    /// Show an entry and its lines
+    Show {
+        /// Journal entry ID
+        id: i32,
+    },
+    /// Post the opposite entry
+    Reverse {
+        /// Journal entry ID
+        id: i32,
+        /// Reason for the reversal
+        #[arg(short, long)]
+        reason: String,
+        /// Reversal date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        date: Option<NaiveDate>,
     },
 }
 
 #[derive(Debug, Subcommand)]
 pub enum ReportCommands {
     /// Balance sheet
-    Balance,
+    Balance {
+        /// As of date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        as_of: Option<NaiveDate>,
+    },
     /// Income statement
-    Income,
+    Income {
+        /// Period (YYYY-MM, FY2025-Q2, FY2025-P03, this-month, last-month)
+        #[arg(short, long, value_parser = parse_period_arg, default_value = "this-month")]
+        period: String,
+    },
+    /// Trial balance of all accounts
+    TrialBalance {
+        /// As of date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        as_of: Option<NaiveDate>,
+    },
 }
 
 #[derive(Debug, Subcommand)]
 pub enum InvCommands {
+    /// Category management
+    #[command(alias = "cat")]
+    Category {
+        #[command(subcommand)]
+        action: CategoryCommands,
+    },
     /// Product management
     Product {
         #[command(subcommand)]
         action: ProductCommands,
     },
+    /// Sellable product bundles
+    Bundle {
+        #[command(subcommand)]
+        action: BundleCommands,
+    },
     /// Stock management
     Stock {
         #[command(subcommand)]
         action: StockCommands,
     },
+    /// Stock audits (physical counts)
+    Audit {
+        #[command(subcommand)]
+        action: AuditCommands,
+    },
+    /// Full-screen product browser with stock quick actions
+    Browse {
+        /// Initial search (name or SKU)
+        #[arg(short, long)]
+        search: Option<String>,
+    },
+    /// Monthly Intrastat declaration export
+    Intrastat {
+        /// Declaration period (YYYY-MM)
+        #[arg(short, long)]
+        period: String,
+        /// Flow to declare
+        #[arg(short, long, value_enum, default_value = "arrivals")]
+        flow: crate::modules::inventory::IntrastatFlow,
+        /// Output CSV file
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Partner export template to lay the CSV out with (see `config template`)
+        #[arg(short, long)]
+        template: Option<String>,
+    },
+    /// Printable product catalog with primary photos
+    Catalog {
+        /// Only this category and its subcategories
+        #[arg(short, long)]
+        category: Option<i32>,
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "html")]
+        format: crate::modules::inventory::CatalogFormat,
+        /// Output file (default: catalog.html or catalog.pdf)
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Heading printed on the catalog
+        #[arg(long, default_value = "Product Catalog")]
+        title: String,
+        /// Product names in this language (see `inv product translate`)
+        #[arg(long)]
+        locale: Option<String>,
+    },
+    /// Provisions for slow-moving and dead stock
+    WriteDown {
+        #[command(subcommand)]
+        action: WriteDownCommands,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum WriteDownCommands {
+    /// Propose a write-down of slow-moving and dead stock for approval
+    Propose {
+        /// Judge stock as of this date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        as_of: Option<NaiveDate>,
+        /// Show the candidates without proposing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// List write-downs
+    List {
+        /// Only write-downs with this status
+        #[arg(long, value_enum)]
+        status: Option<crate::database::WriteDownStatus>,
+        /// Maximum number of write-downs to show
+        #[arg(short, long, default_value = "20")]
+        limit: i64,
+    },
+    /// Show a write-down with its products
+    Show {
+        /// Write-down ID
+        write_down_id: i32,
+    },
+    /// Approve a proposed write-down and post it (managers and above)
+    Approve {
+        /// Write-down ID
+        write_down_id: i32,
+        /// Note recorded with the approval
+        #[arg(short, long)]
+        note: Option<String>,
+        /// Reason for approving your own proposal, where the SoD rule allows it
+        #[arg(long)]
+        override_sod: Option<String>,
+    },
+    /// Reject a proposed write-down (managers and above)
+    Reject {
+        /// Write-down ID
+        write_down_id: i32,
+        /// Why the write-down is declined
+        #[arg(long)]
+        reason: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CategoryCommands {
+    /// Add category
+    Add {
+        /// Category name
+        #[arg(short, long)]
+        name: String,
+        /// Description
+        #[arg(short, long)]
+        description: Option<String>,
+        /// Parent category ID
+        #[arg(short, long)]
+        parent_id: Option<i32>,
+    },
+    /// List categories
+    List {
+        /// Parent category ID filter
+        #[arg(short, long)]
+        parent_id: Option<i32>,
+        /// Include inactive categories
+        #[arg(short, long)]
+        all: bool,
+        /// Page number
+        #[arg(long)]
+        page: Option<usize>,
+        /// Items per page
+        #[arg(long)]
+        per_page: Option<i64>,
+        /// Show category names in this language (e.g. de, fr-CH)
+        #[arg(long)]
+        locale: Option<String>,
+        #[command(flatten)]
+        table: TableArgs,
+    },
+    /// Show the category tree with product counts and stock value
+    Tree,
+    /// Add or change a translated category name and description
+    Translate {
+        /// Category ID
+        #[arg(short, long)]
+        id: i32,
+        /// Language of the translation (e.g. de, fr-CH)
+        #[arg(short, long)]
+        locale: String,
+        /// Translated name; required for a new translation
+        #[arg(short, long)]
+        name: Option<String>,
+        /// Translated description
+        #[arg(short, long)]
+        description: Option<String>,
+    },
+    /// Remove a category translation
+    Untranslate {
+        /// Category ID
+        #[arg(short, long)]
+        id: i32,
+        /// Language of the translation
+        #[arg(short, long)]
+        locale: String,
+    },
+    /// Move a category and its subcategories under a new parent
+    Move {
+        /// Category ID
+        #[arg(short, long)]
+        id: i32,
+        /// New parent category ID; omit to move to the top level
+        #[arg(short, long)]
+        parent_id: Option<i32>,
+    },
+    /// Merge a category into another, moving its products and subcategories
+    Merge {
+        /// Category to merge away
+        #[arg(short, long)]
+        source: i32,
+        /// Category that receives the products and subcategories
+        #[arg(short, long)]
+        into: i32,
+    },
+    /// Update category
+    Update {
+        /// Category ID
+        #[arg(short, long)]
+        id: i32,
+        /// New name
+        #[arg(short, long)]
+        name: Option<String>,
+        /// New description
+        #[arg(short, long)]
+        description: Option<String>,
+        /// New parent category ID
+        #[arg(short, long)]
+        parent_id: Option<i32>,
+        /// Activate or deactivate the category
+        #[arg(long)]
+        active: Option<bool>,
+    },
+    /// Delete category
+    Delete {
+        /// Category ID
+        #[arg(short, long)]
+        id: i32,
+        /// Delete even if the category has subcategories or products
+        #[arg(short, long)]
+        force: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum BundleCommands {
+    /// Add bundle
+    Add {
+        /// Bundle SKU
+        #[arg(short, long)]
+        sku: String,
+        /// Bundle name
+        #[arg(short, long)]
+        name: String,
+        /// Description
+        #[arg(short, long)]
+        description: Option<String>,
+        /// Fixed bundle price (in cents); omit to price from the components
+        #[arg(short, long)]
+        price: Option<i32>,
+        /// Discount on the summed component prices, in percent
+        #[arg(long, default_value = "0")]
+        discount: i32,
+        /// Component as SKU:QUANTITY; repeatable
+        #[arg(short, long = "component", value_parser = parse_bundle_component_arg, required = true)]
+        components: Vec<BundleComponent>,
+    },
+    /// List bundles
+    List {
+        /// Include inactive bundles
+        #[arg(short, long)]
+        all: bool,
+        #[command(flatten)]
+        table: TableArgs,
+    },
+    /// Show bundle components
+    Show {
+        /// Bundle SKU
+        sku: String,
+    },
+    /// Price a quantity of a bundle and check component stock
+    Quote {
+        /// Bundle SKU
+        sku: String,
+        /// Number of bundles
+        #[arg(short, long, default_value = "1")]
+        quantity: i32,
+    },
+    /// Stop offering a bundle
+    Deactivate {
+        /// Bundle SKU
+        sku: String,
+    },
+    /// Offer a deactivated bundle again
+    Activate {
+        /// Bundle SKU
+        sku: String,
+    },
+    /// Delete bundle
+    Delete {
+        /// Bundle SKU
+        sku: String,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -469,6 +1830,15 @@ pub enum ProductCommands {
         /// Barcode
         #[arg(short, long)]
         barcode: Option<String>,
+        /// Commodity code (HS/CN)
+        #[arg(long)]
+        commodity_code: Option<String>,
+        /// Country of origin (ISO 3166-1 alpha-2)
+        #[arg(long)]
+        origin_country: Option<String>,
+        /// Net weight per unit (grams)
+        #[arg(long)]
+        net_weight: Option<i32>,
     },
     /// List products
     List {
@@ -490,6 +1860,14 @@ pub enum ProductCommands {
         /// Items per page
         #[arg(long)]
         per_page: Option<i64>,
+        /// Only products matching this expression, e.g. "price>=10000 and category in (3,5)"
+        #[arg(long = "where", value_parser = parse_filter_expr_arg)]
+        filter: Option<FilterExpr>,
+        /// Show product and category names in this language (e.g. de, fr-CH)
+        #[arg(long)]
+        locale: Option<String>,
+        #[command(flatten)]
+        table: TableArgs,
     },
     /// Show product details
     Show {
@@ -499,6 +1877,150 @@ pub enum ProductCommands {
         /// Product SKU
         #[arg(short, long)]
         sku: Option<String>,
+        /// Also show the product's change history
+        #[arg(long)]
+        history: bool,
+        /// Show the name and description in this language
+        #[arg(long)]
+        locale: Option<String>,
+    },
+    /// Add or change a translated product name and description
+    Translate {
+        /// Product ID
+        #[arg(short, long)]
+        id: i32,
+        /// Language of the translation (e.g. de, fr-CH)
+        #[arg(short, long)]
+        locale: String,
+        /// Translated name; required for a new translation
+        #[arg(short, long)]
+        name: Option<String>,
+        /// Translated description
+        #[arg(short, long)]
+        description: Option<String>,
+    },
+    /// List the translations of a product
+    Translations {
+        /// Product ID
+        #[arg(short, long)]
+        id: i32,
+    },
+    /// Remove a product translation
+    Untranslate {
+        /// Product ID
+        #[arg(short, long)]
+        id: i32,
+        /// Language of the translation
+        #[arg(short, long)]
+        locale: String,
+    },
+    /// Attach a file (photo, manual, certificate...) to a product
+    Attach {
+        /// Product ID
+        #[arg(short, long)]
+        id: i32,
+        /// File to attach; it is copied into the attachment storage
+        #[arg(short, long)]
+        file: String,
+        /// Kind of attachment
+        #[arg(short = 't', long = "type", default_value = "image", value_parser = ["image", "document", "manual", "certificate"])]
+        kind: String,
+        /// Make it the primary attachment of its kind (the first image always is)
+        #[arg(long)]
+        primary: bool,
+    },
+    /// List the attachments of a product
+    Attachments {
+        /// Product ID
+        #[arg(short, long)]
+        id: i32,
+    },
+    /// Make an image attachment the product's primary photo
+    Photo {
+        /// Attachment ID (see `inv product attachments`)
+        #[arg(short, long)]
+        attachment: i32,
+    },
+    /// Delete an attachment and its stored file
+    Detach {
+        /// Attachment ID
+        #[arg(short, long)]
+        attachment: i32,
+    },
+    /// Update product, showing the fields that changed
+    Update {
+        /// Product ID
+        #[arg(short, long)]
+        id: i32,
+        /// New name
+        #[arg(short, long)]
+        name: Option<String>,
+        /// New category ID
+        #[arg(short, long)]
+        category_id: Option<i32>,
+        /// New price (in cents)
+        #[arg(short, long)]
+        price: Option<i32>,
+        /// New cost price (in cents)
+        #[arg(long)]
+        cost_price: Option<i32>,
+        /// New minimum stock level
+        #[arg(long)]
+        min_stock: Option<i32>,
+        /// New maximum stock level
+        #[arg(long)]
+        max_stock: Option<i32>,
+        /// New unit of measurement
+        #[arg(short, long)]
+        unit: Option<String>,
+        /// New description
+        #[arg(short, long)]
+        description: Option<String>,
+        /// New barcode
+        #[arg(short, long)]
+        barcode: Option<String>,
+        /// Activate or deactivate the product
+        #[arg(long)]
+        active: Option<bool>,
+    },
+    /// Set customs (Intrastat) details
+    Customs {
+        /// Product ID
+        #[arg(short, long)]
+        id: i32,
+        /// Commodity code (HS/CN)
+        #[arg(long)]
+        commodity_code: Option<String>,
+        /// Country of origin (ISO 3166-1 alpha-2)
+        #[arg(long)]
+        origin_country: Option<String>,
+        /// Net weight per unit (grams)
+        #[arg(long)]
+        net_weight: Option<i32>,
+    },
+    /// Update every product matching the filters
+    BulkUpdate {
+        /// Filter as field=value (id, category, sku, unit, active, low_stock); repeatable
+        #[arg(long = "filter", value_parser = parse_field_assignment_arg)]
+        filters: Vec<FieldAssignment>,
+        /// Change as field=value (category, price, cost_price, min_stock, max_stock, unit, description, active); repeatable
+        #[arg(long = "set", value_parser = parse_field_assignment_arg, required = true)]
+        updates: Vec<FieldAssignment>,
+    },
+    /// Import products from CSV, resuming an interrupted import of the same file
+    Import {
+        /// CSV file (sku, name, category_id, price, cost_price, stock, min_stock, max_stock, unit, description, barcode)
+        #[arg(short, long)]
+        file: String,
+        /// Rows committed per transaction
+        #[arg(long, default_value_t = crate::modules::inventory::DEFAULT_IMPORT_CHUNK_SIZE)]
+        chunk_size: usize,
+        /// What to do with SKUs that already exist
+        #[arg(long, value_enum, default_value_t = crate::utils::import::ConflictStrategy::Fail)]
+        on_conflict: crate::utils::import::ConflictStrategy,
+        /// Ignore any checkpoint and import from the first row
+        #[arg(long)]
+        restart: bool,
     },
 }
 
@@ -548,6 +2070,31 @@ pub enum StockCommands {
         /// Show only low stock products
         #[arg(long)]
         low_stock: bool,
+        /// Show only products that are out of stock
+        #[arg(long)]
+        out_of_stock: bool,
+        /// Category ID filter
+        #[arg(long)]
+        category_id: Option<i32>,
+        #[command(flatten)]
+        table: TableArgs,
+    },
+    /// Show the stock movement history of a product
+    History {
+        /// Product ID
+        #[arg(long)]
+        product_id: Option<i32>,
+        /// Product SKU
+        #[arg(short, long)]
+        sku: Option<String>,
+        /// Page number
+        #[arg(long)]
+        page: Option<usize>,
+        /// Items per page
+        #[arg(long)]
+        per_page: Option<i64>,
+        #[command(flatten)]
+        table: TableArgs,
     },
     /// Update stock
     Update {
@@ -557,6 +2104,167 @@ pub enum StockCommands {
         /// Quantity
         #[arg(short, long)]
         quantity: i32,
+        /// Notes
+        #[arg(long)]
+        notes: Option<String>,
+    },
+    /// Show on-hand, reserved and available quantity
+    Availability {
+        /// Product ID
+        #[arg(long)]
+        product_id: Option<i32>,
+        /// Product SKU
+        #[arg(short, long)]
+        sku: Option<String>,
+    },
+    /// Reserve stock for an order
+    Reserve {
+        /// Product ID
+        #[arg(long)]
+        product_id: Option<i32>,
+        /// Product SKU
+        #[arg(short, long)]
+        sku: Option<String>,
+        /// Quantity to reserve
+        #[arg(short, long)]
+        quantity: i32,
+        /// What the stock is reserved for (e.g. deal, sales_order)
+        #[arg(long, default_value = "manual")]
+        reference_type: String,
+        /// ID of the reserving record
+        #[arg(long)]
+        reference_id: Option<i32>,
+        /// Notes
+        #[arg(long)]
+        notes: Option<String>,
+    },
+    /// Release a reservation without shipping it
+    Release {
+        /// Reservation ID
+        reservation_id: i32,
+    },
+    /// Ship a reservation as a stock out movement
+    Fulfill {
+        /// Reservation ID
+        reservation_id: i32,
+    },
+    /// Reverse a stock movement with a linked counter-movement
+    Reverse {
+        /// ID of the movement to reverse
+        #[arg(long)]
+        movement_id: i32,
+        /// Why the movement is being corrected
+        #[arg(long)]
+        reason: String,
+    },
+    /// List stock adjustments waiting for approval
+    Pending {
+        /// Show requests with this status instead
+        #[arg(long, value_enum, default_value_t = crate::database::StockAdjustmentStatus::Pending)]
+        status: crate::database::StockAdjustmentStatus,
+        /// Maximum number of requests to show
+        #[arg(short, long, default_value = "50")]
+        limit: i64,
+    },
+    /// Approve a queued stock adjustment and apply it (supervisors and above)
+    Approve {
+        /// Adjustment request ID
+        request_id: i32,
+        /// Note recorded with the approval
+        #[arg(short, long)]
+        note: Option<String>,
+        /// Reason for approving your own request, where the SoD rule allows it
+        #[arg(long)]
+        override_sod: Option<String>,
+    },
+    /// Reject a queued stock adjustment (supervisors and above)
+    Reject {
+        /// Adjustment request ID
+        request_id: i32,
+        /// Why the adjustment is declined
+        #[arg(long)]
+        reason: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AuditCommands {
+    /// Create a stock audit
+    Create {
+        /// Audit name
+        #[arg(short, long)]
+        name: String,
+        /// Audit date (default: today)
+        #[arg(short, long, value_parser = parse_date_arg)]
+        date: Option<NaiveDate>,
+        /// Notes
+        #[arg(long)]
+        notes: Option<String>,
+    },
+    /// List stock audits
+    List {
+        /// Status filter (pending, in_progress, completed, cancelled)
+        #[arg(short, long)]
+        status: Option<String>,
+        /// Page number
+        #[arg(long)]
+        page: Option<usize>,
+        /// Items per page
+        #[arg(long)]
+        per_page: Option<i64>,
+        #[command(flatten)]
+        table: TableArgs,
+    },
+    /// Start an audit, taking the expected quantity of every product
+    Start {
+        /// Audit ID
+        #[arg(short, long)]
+        id: i32,
+    },
+    /// Record the counted quantity of a product
+    Count {
+        /// Audit ID
+        #[arg(short, long)]
+        audit_id: i32,
+        /// Product SKU
+        #[arg(short, long)]
+        sku: String,
+        /// Quantity counted
+        #[arg(short, long)]
+        quantity: i32,
+        /// Notes
+        #[arg(short, long)]
+        notes: Option<String>,
+    },
+    /// Show the items of an audit
+    Items {
+        /// Audit ID
+        #[arg(short, long)]
+        id: i32,
+        /// Show only items whose count differs from the expected quantity
+        #[arg(long)]
+        variance_only: bool,
+        /// Page number
+        #[arg(long)]
+        page: Option<usize>,
+        /// Items per page
+        #[arg(long)]
+        per_page: Option<i64>,
+    },
+    /// Complete an audit
+    Complete {
+        /// Audit ID
+        #[arg(short, long)]
+        id: i32,
+        /// Adjust stock to the counted quantities
+        #[arg(long)]
+        apply_adjustments: bool,
+    },
+    /// Cancel an audit
+    Cancel {
+        /// Audit ID
+        #[arg(short, long)]
+        id: i32,
     },
 }
 
@@ -565,43 +2273,147 @@ pub enum CrmCommands {
     /// Customer management
     Customer {
         #[command(subcommand)]
-        action: CustomerCommands,
+        action: CustomerAction,
+    },
+    /// Lead management
+    Lead {
+        #[command(subcommand)]
+        action: LeadAction,
+    },
+    /// Deal pipeline views
+    Pipeline {
+        #[command(subcommand)]
+        action: PipelineCommands,
+    },
+    /// Sales forecast per rep against quota
+    Forecast {
+        #[command(subcommand)]
+        action: ForecastCommands,
+    },
+    /// Customer satisfaction surveys and Net Promoter Score
+    Survey {
+        #[command(subcommand)]
+        action: SurveyCommands,
+    },
+    /// Customers at risk of churning
+    Churn {
+        #[command(subcommand)]
+        action: ChurnCommands,
+    },
+    /// Log emails BCC'd to the dropbox address as activities
+    Dropbox {
+        #[command(subcommand)]
+        action: DropboxCommands,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DropboxCommands {
+    /// Show the address to BCC on emails to customers
+    Address,
+    /// Log one email given as a raw message (.eml), from a file or stdin
+    Ingest {
+        /// Message file (reads stdin when omitted)
+        file: Option<std::path::PathBuf>,
+    },
+    /// Log the emails an IMAP syncer delivered to the dropbox maildir
+    Fetch {
+        /// Maildir to read (defaults to dropbox.maildir)
+        #[arg(long)]
+        maildir: Option<std::path::PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ChurnCommands {
+    /// List customers showing churn signals, with the recommended next action
+    List {
+        /// Judge as of this date (defaults to today)
+        #[arg(long, value_parser = parse_date_arg)]
+        as_of: Option<NaiveDate>,
+    },
+    /// Notify account owners of customers at risk not alerted recently
+    Alert {
+        /// Judge as of this date (defaults to today)
+        #[arg(long, value_parser = parse_date_arg)]
+        as_of: Option<NaiveDate>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SurveyCommands {
+    /// Record a customer's answer (0-10) to "how likely are you to recommend us"
+    Record {
+        /// Customer ID
+        customer_id: i32,
+        #[arg(short, long)]
+        score: i32,
+        /// Deal the survey was about
+        #[arg(long)]
+        deal: Option<i32>,
+        #[arg(long, value_enum, default_value = "email")]
+        channel: crate::database::SurveyChannel,
+        #[arg(short, long)]
+        comment: Option<String>,
+        /// Date of the answer (defaults to today)
+        #[arg(long, value_parser = parse_date_arg)]
+        date: Option<NaiveDate>,
     },
-    /// Lead management
-    Lead {
-        #[command(subcommand)]
-        action: LeadCommands,
+    /// List surveys, latest first
+    List {
+        #[arg(long)]
+        customer: Option<i32>,
+        #[arg(long)]
+        deal: Option<i32>,
+        #[arg(short, long, default_value = "20")]
+        limit: i64,
+    },
+    /// Net Promoter Score of a period, per customer segment and month
+    Nps {
+        /// Period (this-year, this-quarter, FY2025-Q2 or YYYY-MM)
+        #[arg(long, default_value = "this-year")]
+        period: String,
     },
 }
 
 #[derive(Debug, Subcommand)]
-pub enum CustomerCommands {
-    /// Add customer
-    Add {
-        /// Customer name
-        #[arg(short, long)]
-        name: String,
-        /// Email
+pub enum ForecastCommands {
+    /// Forecast the revenue closed in a period, per rep and in total
+    Show {
+        /// Period (this-quarter, this-month, FY2025-Q2 or YYYY-MM)
+        #[arg(long, default_value = "this-quarter")]
+        period: String,
+        /// Only this rep (employee ID)
+        #[arg(long)]
+        rep: Option<i32>,
+    },
+    /// Set a rep's monthly quota for every month of a period (managers and above)
+    Quota {
+        /// Employee ID of the rep
+        employee_id: i32,
+        /// Period (this-quarter, this-year, FY2025-Q2 or YYYY-MM)
+        #[arg(long)]
+        period: String,
+        /// Quota per month
         #[arg(short, long)]
-        email: String,
+        amount: i32,
+    },
+    /// List the quotas of a period
+    Quotas {
+        /// Period (this-quarter, this-year, FY2025-Q2 or YYYY-MM)
+        #[arg(long, default_value = "this-quarter")]
+        period: String,
     },
-    /// List customers
-    List,
 }
 
 #[derive(Debug, Subcommand)]
-pub enum LeadCommands {
-    /// Add lead
-    Add {
-        /// Customer ID
-        #[arg(short, long)]
-        customer_id: i32,
-        /// Source
-        #[arg(short, long)]
-        source: String,
+pub enum PipelineCommands {
+    /// Interactive kanban board of deals by stage
+    Board {
+        /// Include deals outside your scope (managers and supervisors)
+        #[arg(long)]
+        all: bool,
     },
-    /// List leads
-    List,
 }
 
 #[derive(Debug, Subcommand)]
@@ -609,27 +2421,27 @@ pub enum SalesCommands {
     /// Customer management
     Customer {
         #[command(subcommand)]
-        action: SalesCustomerCommands,
+        action: CustomerAction,
     },
     /// Lead management
     Lead {
         #[command(subcommand)]
-        action: SalesLeadCommands,
+        action: LeadAction,
     },
     /// Deal management
     Deal {
         #[command(subcommand)]
-        action: DealCommands,
+        action: DealAction,
     },
     /// Campaign management
     Campaign {
         #[command(subcommand)]
-        action: CampaignCommands,
+        action: CampaignAction,
     },
     /// Activity management
     Activity {
         #[command(subcommand)]
-        action: ActivityCommands,
+        action: ActivityAction,
     },
     /// CRM Dashboard
     Dashboard,
@@ -637,118 +2449,70 @@ pub enum SalesCommands {
     Pipeline,
     /// Performance Overview
     Performance,
+    /// Earliest date a quantity can ship (available-to-promise)
+    Atp {
+        /// Product ID
+        #[arg(long)]
+        product_id: Option<i32>,
+        /// Product SKU
+        #[arg(short, long)]
+        sku: Option<String>,
+        /// Quantity to promise
+        #[arg(short, long)]
+        quantity: i32,
+    },
+    /// Sales order management
+    Order {
+        #[command(subcommand)]
+        action: SalesOrderCommands,
+    },
 }
 
 #[derive(Debug, Subcommand)]
-pub enum SalesCustomerCommands {
-    /// Create customer
-    Create,
-    /// List customers
-    List,
-    /// Show customer details
-    Show,
-    /// Update customer
-    Update,
-    /// Delete customer
-    Delete,
-    /// Search customers
-    Search,
-    /// Customer statistics
-    Stats,
-}
-
-#[derive(Debug, Subcommand)]
-pub enum SalesLeadCommands {
-    /// Create lead
-    Create,
-    /// List leads
-    List,
-    /// Show lead details
-    Show,
-    /// Update lead status
-    UpdateStatus,
-    /// Update lead
-    Update,
-    /// Assign lead
-    Assign,
-    /// Delete lead
-    Delete,
-    /// Leads by status
-    ByStatus,
-    /// Lead statistics
-    Stats,
-}
-
-#[derive(Debug, Subcommand)]
-pub enum DealCommands {
-    /// Create deal
-    Create,
-    /// List deals
-    List,
-    /// Show deal details
-    Show,
-    /// Update deal stage
-    UpdateStage,
-    /// Update deal
-    Update,
-    /// Delete deal
-    Delete,
-    /// Deals by stage
-    ByStage,
-    /// Deal statistics
-    Stats,
-}
-
-#[derive(Debug, Subcommand)]
-pub enum CampaignCommands {
-    /// Create campaign
-    Create,
-    /// List campaigns
-    List,
-    /// Show campaign details
-    Show,
-    /// Update campaign
-    Update,
-    /// Update campaign status
-    UpdateStatus,
-    /// Delete campaign
-    Delete,
-    /// Campaigns by status
-    ByStatus,
-    /// Active campaigns
-    Active,
-    /// Campaign performance
-    Performance,
-    /// Campaign statistics
-    Stats,
-}
-
-#[derive(Debug, Subcommand)]
-pub enum ActivityCommands {
-    /// Create activity
-    Create,
-    /// List activities
-    List,
-    /// Show activity details
-    Show,
-    /// Update activity
-    Update,
-    /// Complete activity
-    Complete,
-    /// Reopen activity
-    Reopen,
-    /// Delete activity
-    Delete,
-    /// Activities by customer
-    ByCustomer,
-    /// Activities by lead
-    ByLead,
-    /// Activities by employee
-    ByEmployee,
-    /// Overdue activities
-    Overdue,
-    /// Activity statistics
-    Stats,
+pub enum SalesOrderCommands {
+    /// Create the sales order for a won deal
+    Create {
+        /// Deal ID
+        #[arg(short, long)]
+        deal_id: i32,
+        /// Order notes
+        #[arg(short, long)]
+        notes: Option<String>,
+    },
+    /// List sales orders
+    List {
+        /// Status filter (pending, reserved, shipped, invoiced, cancelled)
+        #[arg(long)]
+        status: Option<String>,
+    },
+    /// Show sales order details
+    Show {
+        /// Sales order ID
+        order_id: i32,
+    },
+    /// Reserve stock for every line of the order
+    Reserve {
+        /// Sales order ID
+        order_id: i32,
+    },
+    /// Ship the reserved stock
+    Ship {
+        /// Sales order ID
+        order_id: i32,
+    },
+    /// Bill the customer for a shipped order
+    Invoice {
+        /// Sales order ID
+        order_id: i32,
+        /// Days until payment is due
+        #[arg(long, default_value = "30")]
+        due_days: i64,
+    },
+    /// Cancel the order and release its reservations
+    Cancel {
+        /// Sales order ID
+        order_id: i32,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -803,8 +2567,11 @@ pub enum SupplierCommands {
         #[arg(long, default_value = "1")]
         page: u32,
         /// Items per page
-        #[arg(long, default_value = "20")]
-        per_page: u32,
+        #[arg(long)]
+        per_page: Option<u32>,
+        /// Only suppliers matching this expression, e.g. "lead_time <= 14 and status = active"
+        #[arg(long = "where", value_parser = parse_filter_expr_arg)]
+        filter: Option<FilterExpr>,
     },
     /// Show supplier details
     Show {
@@ -836,6 +2603,9 @@ pub enum SupplierCommands {
         /// Status
         #[arg(long)]
         status: Option<String>,
+        /// Typical days from order to receipt
+        #[arg(long)]
+        lead_time_days: Option<i32>,
     },
 }
 
@@ -846,15 +2616,21 @@ pub enum PurchaseOrderCommands {
         /// Supplier ID
         #[arg(short, long)]
         supplier_id: i32,
-        /// Expected delivery date
-        #[arg(long)]
-        expected_date: Option<String>,
+        /// Expected delivery date (YYYY-MM-DD, "in 2 weeks")
+        #[arg(long, value_parser = parse_date_arg)]
+        expected_date: Option<NaiveDate>,
         /// Order notes
         #[arg(short, long)]
         notes: Option<String>,
         /// Items (format: product_id:quantity:unit_cost,...)
         #[arg(long)]
         items: String,
+        /// Dispatch country for cross-border orders (ISO 3166-1 alpha-2)
+        #[arg(long)]
+        dispatch_country: Option<String>,
+        /// Delivery terms (Incoterm, e.g. DAP)
+        #[arg(long)]
+        incoterm: Option<String>,
     },
     /// List purchase orders
     List {
@@ -864,18 +2640,21 @@ pub enum PurchaseOrderCommands {
         /// Status filter
         #[arg(long)]
         status: Option<String>,
-        /// Date from filter (YYYY-MM-DD)
-        #[arg(long)]
-        date_from: Option<String>,
-        /// Date to filter (YYYY-MM-DD)
-        #[arg(long)]
-        date_to: Option<String>,
+        /// Date from filter (YYYY-MM-DD, "2 weeks ago", last monday)
+        #[arg(long, alias = "from", value_parser = parse_date_arg)]
+        date_from: Option<NaiveDate>,
+        /// Date to filter (YYYY-MM-DD, today, yesterday)
+        #[arg(long, alias = "to", value_parser = parse_date_arg)]
+        date_to: Option<NaiveDate>,
         /// Page number
         #[arg(long, default_value = "1")]
         page: u32,
         /// Items per page
-        #[arg(long, default_value = "20")]
-        per_page: u32,
+        #[arg(long)]
+        per_page: Option<u32>,
+        /// Only orders matching this expression, e.g. "total > 1000000 and status not in (cancelled, received)"
+        #[arg(long = "where", value_parser = parse_filter_expr_arg)]
+        filter: Option<FilterExpr>,
     },
     /// Show purchase order details
     Show {
@@ -886,6 +2665,9 @@ pub enum PurchaseOrderCommands {
     Approve {
         /// Purchase order ID
         po_id: i32,
+        /// Reason for approving an order you created, where the SoD rule allows it
+        #[arg(long)]
+        override_sod: Option<String>,
     },
     /// Receive purchase order items
     Receive {
@@ -895,6 +2677,58 @@ pub enum PurchaseOrderCommands {
         #[arg(long)]
         items: String,
     },
+    /// Record the supplier's invoice for a purchase order
+    Invoice {
+        /// Purchase order ID
+        po_id: i32,
+        /// Supplier's invoice number
+        #[arg(long)]
+        number: String,
+        /// Invoice total
+        #[arg(short, long)]
+        amount: i32,
+        /// Invoice date (default: today)
+        #[arg(long, value_parser = parse_date_arg)]
+        date: Option<NaiveDate>,
+        /// Due date; defaults to the supplier's payment terms
+        #[arg(long, value_parser = parse_date_arg)]
+        due: Option<NaiveDate>,
+    },
+    /// Create a link the supplier can open to confirm, reschedule or ship the order
+    Share {
+        /// Purchase order ID
+        po_id: i32,
+        /// Days the link stays valid; defaults to server.link_ttl_days
+        #[arg(long)]
+        days: Option<u32>,
+    },
+    /// List the supplier links of a purchase order
+    Links {
+        /// Purchase order ID
+        po_id: i32,
+    },
+    /// Revoke a supplier link before it expires
+    Revoke {
+        /// Link ID, as shown by `links`
+        link_id: i32,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DashboardCommands {
+    /// Create a link that opens your dashboard, e.g. on your phone
+    Share {
+        /// Days the link stays valid; defaults to server.link_ttl_days
+        #[arg(long)]
+        days: Option<u32>,
+    },
+    /// List your dashboard links
+    Links,
+    /// Revoke a dashboard link before it expires
+    Revoke {
+        /// Link ID, as shown by `links`
+        link_id: i32,
+    },
 }
 
 #[derive(Subcommand)]
@@ -904,7 +2738,475 @@ pub enum SystemCommands {
     /// Show system status
     Status,
     /// Run database migrations
-    Migrate,
+    Migrate {
+        /// List SQL migrations and whether each is applied, without migrating
+        #[arg(long, conflicts_with = "rollback")]
+        status: bool,
+        /// Revert the last N applied SQL migrations with their down migrations
+        #[arg(long, value_name = "N")]
+        rollback: Option<usize>,
+    },
     /// Create default admin user
     CreateAdmin,
+    /// Refresh planner statistics and check common queries for missing indexes
+    Analyze {
+        /// Report queries taking at least this many milliseconds as slow
+        #[arg(long, default_value_t = 100.0)]
+        slow_ms: f64,
+    },
+    /// Recompute the daily product and monthly account summaries reports read,
+    /// e.g. after editing stock movements or transactions outside CLIERP
+    RefreshSummaries,
+    /// Show row counts, database and index sizes, the largest attachments and
+    /// growth since the last run, for planning archiving and backups
+    Stats {
+        /// Indexes and attachments listed
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+        /// Do not keep this run as the baseline growth is measured against
+        #[arg(long)]
+        no_save: bool,
+    },
+    /// Show which commands run most, take longest and fail most often, from
+    /// the usage statistics recorded when `usage.enabled` is set
+    Usage {
+        /// Days of runs included
+        #[arg(long, default_value_t = 30)]
+        days: i64,
+        /// Delete all recorded runs
+        #[arg(long)]
+        clear: bool,
+    },
+    /// Check configuration, the database and migrations, writable directories,
+    /// outbound connections and the clock, suggesting fixes. Runs without
+    /// logging in and before the usual startup, so it works when that fails.
+    Doctor,
+    /// Time stock movement inserts, the product list and a report against this database
+    Bench {
+        /// Runs of each operation
+        #[arg(short = 'n', long, default_value_t = 100)]
+        iterations: usize,
+        /// Print the results as JSON for comparing runs
+        #[arg(long)]
+        json: bool,
+        /// Also write the JSON results to this file
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Find orphaned rows and files that are gone
+    Check {
+        /// Repair what was found: clear dangling references, quarantine orphaned rows
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Move old stock movements and audit logs to archive tables
+    Archive {
+        #[command(subcommand)]
+        action: ArchiveCommands,
+    },
+    /// Delete or anonymize records past the configured retention, except those on legal hold
+    Retention {
+        #[command(subcommand)]
+        action: RetentionCommands,
+    },
+    /// Legal holds keeping customers, deals, documents and other records from deletion and archival
+    LegalHold {
+        #[command(subcommand)]
+        action: LegalHoldCommands,
+    },
+    /// Scheduled uploads of stock levels and price lists to partners' FTP/SFTP servers
+    Delivery {
+        #[command(subcommand)]
+        action: DeliveryCommands,
+    },
+    /// Serve the portal pages that shared links open
+    Serve {
+        /// Address to listen on; defaults to server.bind
+        #[arg(long)]
+        bind: Option<String>,
+    },
+    /// Links to the read-only dashboard `serve` shows managers
+    Dashboard {
+        #[command(subcommand)]
+        action: DashboardCommands,
+    },
+    /// Show your unread notifications and mark them read
+    Notifications {
+        /// Include notifications already read
+        #[arg(long)]
+        all: bool,
+    },
+    /// Encrypt the plaintext database with SQLCipher under the key from
+    /// CLIERP_DATABASE_KEY or database.key_command (admin only)
+    EncryptDb {
+        /// Move the plaintext database here instead of deleting it
+        #[arg(long)]
+        keep_plaintext: Option<std::path::PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DeliveryCommands {
+    /// Add a destination (admin only)
+    Add {
+        /// Destination name, usually the partner's
+        name: String,
+        #[arg(long, value_enum, default_value = "sftp")]
+        protocol: crate::utils::transfer::TransferProtocol,
+        /// Server host name or address
+        #[arg(long)]
+        host: String,
+        /// Server port (default: 21 for FTP, 22 for SFTP)
+        #[arg(long)]
+        port: Option<u16>,
+        /// Login name
+        #[arg(short, long)]
+        user: String,
+        /// Environment variable holding the FTP password; SFTP logs in with your SSH key
+        #[arg(long)]
+        password_env: Option<String>,
+        /// Directory on the server to upload to
+        #[arg(short, long)]
+        dir: Option<String>,
+        /// File to upload
+        #[arg(short, long, value_enum)]
+        export: crate::modules::system::DeliveryExport,
+        /// Export template (see `config template`) to write the file with
+        #[arg(short, long)]
+        template: Option<String>,
+        /// Write product names in this language (see `inv product translate`)
+        #[arg(long)]
+        locale: Option<String>,
+        /// Minutes between uploads
+        #[arg(short, long, default_value = "1440")]
+        interval: i32,
+    },
+    /// List destinations with their last upload
+    List,
+    /// Stop uploading to a destination without removing it
+    Pause {
+        name: String,
+    },
+    /// Resume uploads to a paused destination
+    Resume {
+        name: String,
+    },
+    /// Remove a destination and its transfer log (admin only)
+    Remove {
+        name: String,
+    },
+    /// Upload to every destination that is due; run this from cron
+    Run {
+        /// Upload to this destination now, whether due or not
+        #[arg(long)]
+        only: Option<String>,
+    },
+    /// Show the transfer log
+    Log {
+        /// Only this destination
+        #[arg(long)]
+        name: Option<String>,
+        /// Number of transfers to show
+        #[arg(short, long, default_value = "20")]
+        limit: i64,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RetentionCommands {
+    /// Apply the retention policies (admin only)
+    Run {
+        /// Only count the records that would be deleted or anonymized
+        #[arg(long)]
+        preview: bool,
+    },
+    /// Show the configured policies and the dates they keep records from
+    Policies,
+    /// Show past retention runs
+    History {
+        /// Number of runs to show
+        #[arg(short, long, default_value = "20")]
+        limit: i64,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum LegalHoldCommands {
+    /// Put a record on legal hold (admin only)
+    Place {
+        /// customers, deals, documents, activities, leads, employees, notifications or audit_logs
+        entity: String,
+        /// Record ID
+        record_id: i32,
+        /// Why the record is held, e.g. a case reference
+        #[arg(short, long)]
+        reason: String,
+    },
+    /// Release a legal hold (admin only)
+    Release {
+        /// Hold ID, as shown by `report`
+        hold_id: i32,
+    },
+    /// List every record on legal hold
+    Report {
+        /// Only holds on this entity
+        #[arg(short, long)]
+        entity: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ArchiveCommands {
+    /// Archive records from before a date (admin only)
+    Run {
+        /// Archive records before this date (YYYY-MM-DD); defaults to the configured retention
+        #[arg(long, value_parser = parse_date_arg)]
+        before: Option<NaiveDate>,
+        /// Only count the records that would be archived
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show past archive runs
+    History {
+        /// Number of runs to show
+        #[arg(short, long, default_value = "20")]
+        limit: i64,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum LinkCommands {
+    /// Link two records, e.g. `link add deal:12 po:3`
+    Add {
+        /// Record written as type:id
+        #[arg(value_parser = parse_record_ref_arg)]
+        source: RecordRef,
+        /// Record written as type:id
+        #[arg(value_parser = parse_record_ref_arg)]
+        target: RecordRef,
+        /// How the first record relates to the second, e.g. "fulfils"; drop_ship
+        /// or back_to_back tie a purchase order to the deal it was bought for
+        #[arg(short, long)]
+        relation: Option<String>,
+        /// Note on the link
+        #[arg(short, long)]
+        note: Option<String>,
+    },
+    /// Remove the link between two records
+    Remove {
+        #[arg(value_parser = parse_record_ref_arg)]
+        source: RecordRef,
+        #[arg(value_parser = parse_record_ref_arg)]
+        target: RecordRef,
+    },
+    /// List records linked to a record
+    List {
+        #[arg(value_parser = parse_record_ref_arg)]
+        record: RecordRef,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Show the effective configuration as dotted keys, secrets redacted
+    Show {
+        /// Only settings starting with this, e.g. `database` or `logging.level`
+        key: Option<String>,
+        /// Print a JSON object instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Save a setting to config/settings.json after validating it; a running
+    /// server picks up logging.level and cache.enabled on SIGHUP or POST /reload
+    Set {
+        /// Dotted key as `config show` lists it, e.g. `logging.level`
+        key: String,
+        /// New value; `null` unsets an optional setting
+        value: String,
+    },
+    /// Validation rules evaluated when records are created or updated
+    Rules {
+        #[command(subcommand)]
+        action: RuleCommands,
+    },
+    /// KPI definitions and threshold alerts
+    Kpi {
+        #[command(subcommand)]
+        action: KpiCommands,
+    },
+    /// Segregation of duties rules and their logged overrides
+    Sod {
+        #[command(subcommand)]
+        action: SodCommands,
+    },
+    /// Your own defaults, overriding the configuration file
+    Prefs {
+        #[command(subcommand)]
+        action: PrefCommands,
+    },
+    /// Export templates mapping CSV columns and dates to a partner's layout
+    Template {
+        #[command(subcommand)]
+        action: TemplateCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TemplateCommands {
+    /// List export templates
+    List,
+    /// Show a template's column mapping
+    Show {
+        /// Template name
+        name: String,
+    },
+    /// Create or replace a template, e.g.
+    /// `partnerx --entity employees --column Code=ItemCode --date-format DD/MM/YYYY`
+    Set {
+        /// Template name, usually the partner's
+        name: String,
+        /// Export the template is for
+        #[arg(short, long, value_enum)]
+        entity: crate::modules::system::ExportEntity,
+        /// Column to write, as OURS=THEIRS or OURS to keep the name; repeat in
+        /// the partner's order. Without any, all columns are written.
+        #[arg(short, long = "column", value_parser = parse_template_column_arg)]
+        columns: Vec<TemplateColumn>,
+        /// How dates are written, e.g. DD/MM/YYYY or %d.%m.%Y
+        #[arg(short, long)]
+        date_format: Option<String>,
+    },
+    /// Delete a template
+    Remove {
+        /// Template name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PrefCommands {
+    /// Show your preferences and the values in effect
+    Show,
+    /// Set a preference, e.g. `per_page 50`
+    Set {
+        /// Preference name
+        #[arg(value_enum)]
+        key: crate::modules::system::PreferenceKey,
+        /// New value
+        value: String,
+    },
+    /// Clear a preference so the configuration applies again
+    Unset {
+        /// Preference name
+        #[arg(value_enum)]
+        key: crate::modules::system::PreferenceKey,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SodCommands {
+    /// List rules with their enforcement
+    List,
+    /// Choose whether a rule blocks, allows logged overrides or is off (admin only)
+    Set {
+        /// Rule name
+        #[arg(value_enum)]
+        rule: crate::modules::system::SodRule,
+        /// Enforcement
+        #[arg(value_enum)]
+        enforcement: crate::database::SodEnforcement,
+    },
+    /// Show logged overrides, newest first
+    Overrides {
+        /// Number of overrides to show
+        #[arg(short, long, default_value = "20")]
+        limit: i64,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum KpiCommands {
+    /// Create or replace a KPI (admin only)
+    Set {
+        /// KPI name, e.g. gross_margin_pct
+        name: String,
+        /// Formula over base metrics, e.g. "(revenue - cogs) / revenue * 100"
+        formula: String,
+        /// Warning threshold
+        #[arg(long, allow_hyphen_values = true)]
+        warning: f64,
+        /// Critical threshold
+        #[arg(long, allow_hyphen_values = true)]
+        critical: f64,
+        /// Alert when the value rises to the thresholds instead of falling to them (e.g. DSO)
+        #[arg(long)]
+        lower_is_better: bool,
+        /// Description
+        #[arg(short, long)]
+        description: Option<String>,
+    },
+    /// List KPIs with their current values
+    List,
+    /// Remove a KPI (admin only)
+    Remove {
+        /// KPI name
+        name: String,
+    },
+    /// Show the base metrics formulas can use
+    Metrics,
+    /// Evaluate all KPIs, record their status and alert on crossed thresholds
+    Check,
+}
+
+#[derive(Subcommand)]
+pub enum RuleCommands {
+    /// Create or replace a validation rule (admin only)
+    Set {
+        /// Entity the rule applies to (product, customer, deal)
+        entity: String,
+        /// Field name, e.g. sku, tax_id, discount_percent
+        field: String,
+        /// Rule type
+        #[arg(value_enum)]
+        rule_type: crate::database::RuleType,
+        /// Regex, number or length limit (not needed for required)
+        value: Option<String>,
+        /// Custom error message shown when the rule fails
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+    /// List validation rules
+    List {
+        /// Only show rules for this entity
+        #[arg(short, long)]
+        entity: Option<String>,
+    },
+    /// Remove a validation rule (admin only)
+    Remove {
+        /// Rule ID
+        id: i32,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition() {
+        // Catches flags claimed twice, e.g. a subcommand's -y against the global --yes
+        CLIArgs::command().debug_assert();
+    }
+
+    #[test]
+    fn test_global_yes_after_subcommand() {
+        let args = CLIArgs::try_parse_from(["clierp", "fin", "calendar", "--year", "2025", "-y"]).unwrap();
+        assert!(args.yes);
+        assert!(matches!(
+            args.command,
+            Some(CLICommands::Fin { action: FinCommands::Calendar { year: Some(2025) } })
+        ));
+        assert!(!CLIArgs::try_parse_from(["clierp", "fin", "calendar"]).unwrap().yes);
+    }
 }
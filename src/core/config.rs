@@ -1,12 +1,37 @@
-use config::{Config, ConfigError, Environment, File};
+use config::{Config, ConfigError, Environment, File, FileFormat};
 use serde::{Deserialize, Serialize};
 use std::env;
 
+/// Settings saved by `clierp config set`, read after the configuration
+/// files and before the environment
+pub const SETTINGS_FILE: &str = "config/settings.json";
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct DatabaseConfig {
     pub url: String,
     pub max_connections: u32,
     pub timeout: u64,
+    /// SQLite journal mode ("wal", "delete", "truncate", "persist",
+    /// "memory", "off"); WAL lets readers carry on while another
+    /// invocation writes
+    pub journal_mode: String,
+    /// Milliseconds a connection waits on a locked database before failing
+    pub busy_timeout: u64,
+    /// SQLite synchronous setting ("off", "normal", "full", "extra");
+    /// "normal" is safe in WAL mode
+    pub synchronous: String,
+    /// Open the database with SQLCipher; needs a build with the `sqlcipher`
+    /// feature and the key in CLIERP_DATABASE_KEY or from `key_command`
+    pub encrypted: bool,
+    /// Command printing the database key, e.g. "secret-tool lookup service
+    /// clierp" to read it from the desktop keyring
+    pub key_command: Option<String>,
+    /// Read-only copy of the database, e.g. "sqlite:/replica/clierp.db"
+    /// kept current by Litestream or file replication, that reports and
+    /// the dashboard read so long queries do not hold up writes. It may lag
+    /// the primary; the primary is read when it is unset or unreachable.
+    /// Only SQLite files are supported, not a Postgres or other server.
+    pub replica_url: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -16,6 +41,37 @@ pub struct AuthConfig {
     pub password_rounds: u32,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LdapConfig {
+    /// Log in against the directory first; local users remain for accounts
+    /// not in the directory and for when it cannot be reached
+    pub enabled: bool,
+    /// Directory address, e.g. "ldaps://dc1.corp.example.com"; several
+    /// separated by spaces are tried in turn
+    pub url: Option<String>,
+    /// DN bound with the user's password; "{username}" is replaced, e.g.
+    /// "uid={username},ou=people,dc=example,dc=com" or for Active Directory
+    /// "{username}@corp.example.com"
+    pub bind_dn: String,
+    /// Where the user's entry is searched
+    pub search_base: String,
+    /// Filter finding the user's entry, e.g. "(sAMAccountName={username})"
+    pub user_filter: String,
+    pub email_attribute: String,
+    pub group_attribute: String,
+    /// Role given to members of a group, by group DN or common name; the
+    /// highest role of a user's groups wins
+    #[serde(default)]
+    pub group_roles: std::collections::HashMap<String, String>,
+    /// Role for directory users in none of the mapped groups; without one
+    /// they cannot log in
+    pub default_role: Option<String>,
+    /// Upgrade ldap:// connections with StartTLS
+    pub start_tls: bool,
+    /// Seconds to wait for the directory
+    pub timeout: u64,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct LoggingConfig {
     pub level: String,
@@ -23,11 +79,256 @@ pub struct LoggingConfig {
     pub file: Option<String>,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct FiscalConfig {
+    /// First month of the fiscal year (1-12)
+    pub year_start_month: u32,
+    /// Optional week-based period pattern ("4-4-5", "4-5-4" or "5-4-4")
+    pub period_pattern: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CurrencyConfig {
+    /// Locale preset supplying the defaults below ("ko-KR", "en-US", "de-DE", ...)
+    pub locale: String,
+    /// Currency symbol override
+    pub symbol: Option<String>,
+    /// Minor-unit digits carried by stored amounts (0 for KRW, 2 for USD)
+    pub decimal_places: Option<u32>,
+    pub thousands_separator: Option<String>,
+    pub decimal_separator: Option<String>,
+    /// Place the symbol after the amount, as in "1.234,56 €"
+    pub symbol_after: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CacheConfig {
+    /// Cache dashboard statistics and report runs; `--no-cache` overrides per run
+    pub enabled: bool,
+    pub ttl_seconds: u64,
+    /// Directory for an on-disk cache shared between runs; memory only if unset
+    pub dir: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ArchiveConfig {
+    /// Age in days after which stock movements and audit logs are archived
+    /// when `system archive run` is given no `--before` date
+    pub retention_days: u32,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+pub struct RetentionConfig {
+    /// What `system retention run` deletes or anonymizes, and after how long
+    #[serde(default)]
+    pub policies: Vec<RetentionPolicyConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RetentionPolicyConfig {
+    /// activities, leads, employees, notifications or audit_logs
+    pub entity: String,
+    /// "delete" or "anonymize"
+    pub action: String,
+    /// Age in days after which records are deleted or anonymized, e.g. 1095
+    /// for three years; terminated employees count from their termination
+    pub days: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct StockApprovalConfig {
+    /// Manual stock changes of more than this many units wait for supervisor approval
+    pub max_quantity: Option<u32>,
+    /// Manual stock changes worth more than this wait for supervisor approval
+    pub max_value: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct WriteDownConfig {
+    /// Stock held longer than this many days is slow moving
+    pub slow_days: i64,
+    /// Share of the cost of slow-moving stock provisioned, in percent
+    pub slow_percent: u32,
+    /// Products with no issue and no receipt within this many days are dead stock
+    pub dead_days: i64,
+    /// Share of the cost of dead stock provisioned, in percent
+    pub dead_percent: u32,
+    /// Account debited with the provision (inventory write-down expense)
+    pub expense_account: Option<String>,
+    /// Account credited with the provision (allowance against inventory)
+    pub provision_account: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ChurnConfig {
+    /// Customers with no order for this many days are inactive
+    pub inactive_days: i64,
+    /// Days of orders compared with the same number of days before them
+    pub window_days: i64,
+    /// Drop in order value between the two windows that counts as declining, in percent
+    pub decline_percent: u32,
+    /// Days before the owner of a customer still at risk is alerted again
+    pub renotify_days: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DropboxConfig {
+    /// Mail domain of the BCC dropbox address, e.g. "erp.example.com";
+    /// unset disables the dropbox
+    pub domain: Option<String>,
+    /// Maildir an IMAP syncer (fetchmail, mbsync) delivers the dropbox
+    /// mailbox to, read by `clierp crm dropbox fetch`
+    pub maildir: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PayrollConfig {
+    /// Account debited with gross pay when a payroll run is committed
+    pub expense_account: Option<String>,
+    /// Account credited with net pay owed to employees
+    pub payable_account: Option<String>,
+    /// Account credited with withheld tax (defaults to the payable account)
+    pub tax_account: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BankConfig {
+    /// Company name given as the payer in bank payment files
+    pub debtor_name: Option<String>,
+    /// IBAN or account number payments are made from
+    pub debtor_account: Option<String>,
+    pub debtor_bic: Option<String>,
+    /// ISO 4217 code of the currency amounts are paid in
+    pub currency: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DunningConfig {
+    /// Days past the due date before an invoice is charged anything
+    pub grace_days: u32,
+    /// Flat fee added once to an invoice overdue beyond the grace period
+    pub late_fee: i32,
+    /// Annual interest in percent, accrued daily on the open amount from
+    /// the end of the grace period
+    pub interest_rate: f64,
+    /// Interest below this is left to accrue until the next run
+    pub min_interest: i32,
+    /// Account debited with fees and interest (the receivables account)
+    pub receivable_account: Option<String>,
+    /// Account credited with fees and interest
+    pub revenue_account: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PurchaseAccrualConfig {
+    /// Account debited with goods received but not yet invoiced (inventory
+    /// or a receipts clearing account)
+    pub accrual_account: Option<String>,
+    /// Liability account credited with the accrual
+    pub liability_account: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PostingConfig {
+    /// Post journal entries for purchase order receipts and stock issues as
+    /// they happen
+    pub enabled: bool,
+    /// Inventory asset account, debited on receipt and credited on issue
+    pub inventory_account: Option<String>,
+    /// Accounts payable account credited with the value of goods received
+    pub payable_account: Option<String>,
+    /// Cost of goods sold account debited with the cost of stock issued
+    pub cogs_account: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RevenueRecognitionConfig {
+    /// Liability account holding revenue invoiced but not yet earned
+    pub deferred_account: Option<String>,
+    /// Account credited as deferred revenue is recognized
+    pub revenue_account: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct UsageConfig {
+    /// Record each command's name, duration and outcome in the local database
+    /// for `clierp system usage`; off unless an admin opts in
+    pub enabled: bool,
+    /// Days recorded runs are kept
+    pub retention_days: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ServerConfig {
+    /// Address `clierp system serve` listens on
+    pub bind: String,
+    /// Public address portal links point to, e.g. "https://erp.example.com";
+    /// defaults to http:// plus the bind address
+    pub base_url: Option<String>,
+    /// Days a portal link stays valid unless given with the link
+    pub link_ttl_days: u32,
+    /// Parent domain of tenant subdomains, e.g. "erp.example.com" so that
+    /// "acme.erp.example.com" serves the "acme" tenant
+    pub tenant_domain: Option<String>,
+    /// Bearer token for `POST /reload`, which reloads the configuration
+    /// like SIGHUP does; unset, the endpoint is not served
+    pub reload_token: Option<String>,
+    /// Company profiles served from one process; when empty the server
+    /// serves the configured database alone
+    #[serde(default)]
+    pub tenants: Vec<TenantConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TenantConfig {
+    /// Subdomain label and `X-CLIERP-Tenant` header value
+    pub name: String,
+    /// Database of this company, e.g. "sqlite:./acme.db"
+    pub database_url: String,
+    /// Secret the company's links are signed with; must differ between
+    /// tenants so a link of one company cannot open another's pages
+    pub jwt_secret: String,
+}
+
+impl ServerConfig {
+    pub fn base_url(&self) -> String {
+        self.base_url
+            .clone()
+            .unwrap_or_else(|| format!("http://{}", self.bind))
+            .trim_end_matches('/')
+            .to_string()
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct CLIERPConfig {
     pub database: DatabaseConfig,
     pub auth: AuthConfig,
+    pub ldap: LdapConfig,
     pub logging: LoggingConfig,
+    pub fiscal: FiscalConfig,
+    pub currency: CurrencyConfig,
+    pub cache: CacheConfig,
+    pub archive: ArchiveConfig,
+    pub stock_approval: StockApprovalConfig,
+    pub write_down: WriteDownConfig,
+    pub churn: ChurnConfig,
+    pub dropbox: DropboxConfig,
+    pub payroll: PayrollConfig,
+    pub bank: BankConfig,
+    pub dunning: DunningConfig,
+    pub revenue_recognition: RevenueRecognitionConfig,
+    pub purchase_accrual: PurchaseAccrualConfig,
+    pub posting: PostingConfig,
+    pub server: ServerConfig,
+    pub usage: UsageConfig,
+    /// Empty by default, and then left out when the defaults are serialized
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    /// Language for CLI messages ("en" or "ko"), overridable with CLIERP_LANG
+    pub lang: String,
+    /// Display timezone ("local", "UTC", "+09:00", "Asia/Seoul"); timestamps are stored in UTC
+    pub timezone: String,
     pub app_name: String,
     pub version: String,
 }
@@ -39,17 +340,126 @@ impl Default for CLIERPConfig {
                 url: "sqlite:./clierp.db".to_string(),
                 max_connections: 10,
                 timeout: 30,
+                journal_mode: "wal".to_string(),
+                busy_timeout: 5000,
+                synchronous: "normal".to_string(),
+                encrypted: false,
+                key_command: None,
+                replica_url: None,
             },
             auth: AuthConfig {
                 jwt_secret: "your-secret-key-change-this".to_string(),
                 jwt_expiration: 3600, // 1 hour
                 password_rounds: 12,
             },
+            ldap: LdapConfig {
+                enabled: false,
+                url: None,
+                bind_dn: "uid={username},ou=people,dc=example,dc=com".to_string(),
+                search_base: "dc=example,dc=com".to_string(),
+                user_filter: "(uid={username})".to_string(),
+                email_attribute: "mail".to_string(),
+                group_attribute: "memberOf".to_string(),
+                group_roles: std::collections::HashMap::new(),
+                default_role: None,
+                start_tls: false,
+                timeout: 5,
+            },
             logging: LoggingConfig {
                 level: "info".to_string(),
                 format: "pretty".to_string(),
                 file: None,
             },
+            fiscal: FiscalConfig {
+                year_start_month: 1,
+                period_pattern: None,
+            },
+            currency: CurrencyConfig {
+                locale: "ko-KR".to_string(),
+                symbol: None,
+                decimal_places: None,
+                thousands_separator: None,
+                decimal_separator: None,
+                symbol_after: None,
+            },
+            cache: CacheConfig {
+                enabled: true,
+                ttl_seconds: crate::utils::cache::DEFAULT_CACHE_TTL_SECONDS,
+                dir: None,
+            },
+            archive: ArchiveConfig {
+                retention_days: 730,
+            },
+            stock_approval: StockApprovalConfig {
+                max_quantity: None,
+                max_value: None,
+            },
+            write_down: WriteDownConfig {
+                slow_days: 180,
+                slow_percent: 50,
+                dead_days: 365,
+                dead_percent: 100,
+                expense_account: None,
+                provision_account: None,
+            },
+            churn: ChurnConfig {
+                inactive_days: 90,
+                window_days: 90,
+                decline_percent: 30,
+                renotify_days: 30,
+            },
+            dropbox: DropboxConfig {
+                domain: None,
+                maildir: None,
+            },
+            payroll: PayrollConfig {
+                expense_account: None,
+                payable_account: None,
+                tax_account: None,
+            },
+            bank: BankConfig {
+                debtor_name: None,
+                debtor_account: None,
+                debtor_bic: None,
+                currency: "KRW".to_string(),
+            },
+            dunning: DunningConfig {
+                grace_days: 7,
+                late_fee: 0,
+                interest_rate: 0.0,
+                min_interest: 100,
+                receivable_account: None,
+                revenue_account: None,
+            },
+            revenue_recognition: RevenueRecognitionConfig {
+                deferred_account: None,
+                revenue_account: None,
+            },
+            purchase_accrual: PurchaseAccrualConfig {
+                accrual_account: None,
+                liability_account: None,
+            },
+            posting: PostingConfig {
+                enabled: false,
+                inventory_account: None,
+                payable_account: None,
+                cogs_account: None,
+            },
+            server: ServerConfig {
+                bind: "127.0.0.1:8080".to_string(),
+                base_url: None,
+                link_ttl_days: 14,
+                tenant_domain: None,
+                reload_token: None,
+                tenants: Vec::new(),
+            },
+            usage: UsageConfig {
+                enabled: false,
+                retention_days: 180,
+            },
+            retention: RetentionConfig::default(),
+            lang: "en".to_string(),
+            timezone: "local".to_string(),
             app_name: crate::APP_NAME.to_string(),
             version: crate::VERSION.to_string(),
         }
@@ -67,6 +477,8 @@ impl CLIERPConfig {
             .add_source(File::with_name(&format!("config/{}", run_mode)).required(false))
             // Load local configuration file (gitignored)
             .add_source(File::with_name("config/local").required(false))
+            // Settings saved with `clierp config set`
+            .add_source(File::new(SETTINGS_FILE, FileFormat::Json).required(false))
             // Override with environment variables with CLIERP_ prefix
             .add_source(Environment::with_prefix("CLIERP"));
 
@@ -79,6 +491,15 @@ impl CLIERPConfig {
             eprintln!("Warning: Using default JWT secret. Please set CLIERP_AUTH__JWT_SECRET environment variable.");
         }
 
+        // Validate log level
+        if !crate::core::logging::LOG_LEVELS.contains(&self.logging.level.to_lowercase().as_str()) {
+            return Err(ConfigError::Message(format!(
+                "logging.level must be one of {}, got '{}'",
+                crate::core::logging::LOG_LEVELS.join(", "),
+                self.logging.level
+            )));
+        }
+
         // Validate database URL format
         if !self.database.url.starts_with("sqlite:")
             && !self.database.url.starts_with("postgres://")
@@ -88,6 +509,76 @@ impl CLIERPConfig {
             ));
         }
 
+        if let Some(replica_url) = &self.database.replica_url {
+            if !replica_url.starts_with("sqlite:") {
+                return Err(ConfigError::Message(
+                    "database.replica_url must start with 'sqlite:'".to_string(),
+                ));
+            }
+        }
+
+        // Validate SQLite connection settings
+        crate::database::SqlitePragmas::from_config(&self.database)
+            .map_err(|e| ConfigError::Message(e.to_string()))?;
+
+        // Validate fiscal calendar settings
+        crate::utils::fiscal::FiscalCalendar::from_config(&self.fiscal)
+            .map_err(|e| ConfigError::Message(e.to_string()))?;
+
+        // Validate currency formatting settings
+        crate::utils::currency::CurrencyFormat::from_config(&self.currency)
+            .map_err(|e| ConfigError::Message(e.to_string()))?;
+
+        // Validate display timezone
+        self.timezone
+            .parse::<crate::utils::timezone::DisplayTimezone>()
+            .map_err(|e| ConfigError::Message(e.to_string()))?;
+
+        // Validate directory login settings
+        if self.ldap.enabled {
+            if self.ldap.url.is_none() {
+                return Err(ConfigError::Message("ldap.url is required when ldap.enabled is set".to_string()));
+            }
+            for role in self.ldap.group_roles.values().chain(self.ldap.default_role.iter()) {
+                if crate::core::auth::parse_role(role).is_none() {
+                    return Err(ConfigError::Message(format!(
+                        "Unknown role '{}' in ldap settings; use admin, manager, supervisor, employee or auditor",
+                        role
+                    )));
+                }
+            }
+        }
+
+        // Validate retention policies
+        crate::modules::system::retention::RetentionPolicy::all_from_config(&self.retention)
+            .map_err(|e| ConfigError::Message(e.to_string()))?;
+
+        // Validate automatic ledger postings
+        crate::modules::finance::posting::PostingRules::from_config(&self.posting)
+            .map_err(|e| ConfigError::Message(e.to_string()))?;
+
+        // Validate message language
+        self.lang
+            .parse::<crate::utils::i18n::Language>()
+            .map_err(|e| ConfigError::Message(e.to_string()))?;
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_load_without_a_config_file() {
+        let config: CLIERPConfig = Config::builder()
+            .add_source(Config::try_from(&CLIERPConfig::default()).unwrap())
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        assert!(config.retention.policies.is_empty());
+        config.validate().unwrap();
+    }
+}
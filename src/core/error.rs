@@ -1,3 +1,5 @@
+use clap::ValueEnum;
+use serde::Serialize;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -89,3 +91,143 @@ pub enum CLIERPError {
     #[error("Invalid input: {0}")]
     InvalidInput(String),
 }
+
+/// Exit codes for each kind of error. They are part of the CLI's interface:
+/// scripts may rely on them, so existing codes must never be renumbered.
+pub mod exit_code {
+    /// Internal or otherwise unclassified error
+    pub const GENERAL: i32 = 1;
+    /// Bad command line arguments (also used by clap for parse errors)
+    pub const USAGE: i32 = 2;
+    /// Input failed validation or broke a business rule
+    pub const VALIDATION: i32 = 3;
+    /// The requested record does not exist
+    pub const NOT_FOUND: i32 = 4;
+    /// The record already exists or was changed concurrently
+    pub const CONFLICT: i32 = 5;
+    /// Not logged in, or the login could not be verified
+    pub const AUTHENTICATION: i32 = 6;
+    /// Logged in, but not allowed to do this
+    pub const PERMISSION_DENIED: i32 = 7;
+    /// The database failed or its data is inconsistent
+    pub const DATABASE: i32 = 8;
+    /// The configuration is invalid
+    pub const CONFIGURATION: i32 = 9;
+    /// Reading or writing a file failed
+    pub const IO: i32 = 10;
+}
+
+/// How errors are printed to stderr, set by `--error-format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ErrorFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// An error as printed by `--error-format json`
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorReport {
+    pub error: &'static str,
+    pub exit_code: i32,
+    pub message: String,
+}
+
+impl CLIERPError {
+    /// Stable, machine-readable name of the error's kind
+    pub fn kind(&self) -> &'static str {
+        match self.exit_code() {
+            exit_code::USAGE => "usage",
+            exit_code::VALIDATION => "validation",
+            exit_code::NOT_FOUND => "not_found",
+            exit_code::CONFLICT => "conflict",
+            exit_code::AUTHENTICATION => "authentication",
+            exit_code::PERMISSION_DENIED => "permission_denied",
+            exit_code::DATABASE => "database",
+            exit_code::CONFIGURATION => "configuration",
+            exit_code::IO => "io",
+            _ => "internal",
+        }
+    }
+
+    /// Process exit code for the error, one of the [`exit_code`] constants
+    pub fn exit_code(&self) -> i32 {
+        use diesel::result::{DatabaseErrorKind, Error as DieselError};
+
+        match self {
+            CLIERPError::CLI(_) | CLIERPError::InvalidInput(_) => exit_code::USAGE,
+            CLIERPError::ValidationError(_)
+            | CLIERPError::Validation(_)
+            | CLIERPError::BusinessRuleViolation(_)
+            | CLIERPError::BusinessLogic(_) => exit_code::VALIDATION,
+            CLIERPError::NotFound(_) | CLIERPError::Database(DieselError::NotFound) => exit_code::NOT_FOUND,
+            CLIERPError::AlreadyExists(_)
+            | CLIERPError::ConcurrencyError(_)
+            | CLIERPError::Database(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+                exit_code::CONFLICT
+            }
+            CLIERPError::Authentication(_) | CLIERPError::AuthenticationRequired | CLIERPError::Jwt(_) => {
+                exit_code::AUTHENTICATION
+            }
+            CLIERPError::Authorization(_)
+            | CLIERPError::InsufficientPrivileges(_)
+            | CLIERPError::PermissionDenied(_) => exit_code::PERMISSION_DENIED,
+            CLIERPError::Database(_)
+            | CLIERPError::DatabaseConnection(_)
+            | CLIERPError::DatabaseError(_)
+            | CLIERPError::Migration(_)
+            | CLIERPError::Transaction(_)
+            | CLIERPError::DataIntegrity(_) => exit_code::DATABASE,
+            CLIERPError::Configuration(_) => exit_code::CONFIGURATION,
+            CLIERPError::Io(_) | CLIERPError::IoError(_) => exit_code::IO,
+            CLIERPError::Serialization(_)
+            | CLIERPError::SerializationError(_)
+            | CLIERPError::BCrypt(_)
+            | CLIERPError::Uuid(_)
+            | CLIERPError::Internal(_) => exit_code::GENERAL,
+        }
+    }
+
+    pub fn report(&self) -> ErrorReport {
+        ErrorReport {
+            error: self.kind(),
+            exit_code: self.exit_code(),
+            message: self.to_string(),
+        }
+    }
+
+    /// Print the error to stderr in `format`, prefixed with `context` in text
+    pub fn print(&self, format: ErrorFormat, context: &str) {
+        match format {
+            ErrorFormat::Text => eprintln!("{}: {}", context, self),
+            ErrorFormat::Json => match serde_json::to_string(&self.report()) {
+                Ok(json) => eprintln!("{}", json),
+                Err(_) => eprintln!("{}: {}", context, self),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(CLIERPError::NotFound("x".into()).exit_code(), exit_code::NOT_FOUND);
+        assert_eq!(CLIERPError::Database(diesel::result::Error::NotFound).exit_code(), exit_code::NOT_FOUND);
+        assert_eq!(CLIERPError::Validation("x".into()).exit_code(), exit_code::VALIDATION);
+        assert_eq!(CLIERPError::Authorization("x".into()).exit_code(), exit_code::PERMISSION_DENIED);
+        assert_eq!(CLIERPError::AuthenticationRequired.exit_code(), exit_code::AUTHENTICATION);
+        assert_eq!(CLIERPError::Internal("x".into()).kind(), "internal");
+    }
+
+    #[test]
+    fn test_json_report() {
+        let report = CLIERPError::AlreadyExists("SKU P-1".into()).report();
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["error"], "conflict");
+        assert_eq!(json["exit_code"], 5);
+        assert_eq!(json["message"], "Already exists: SKU P-1");
+    }
+}
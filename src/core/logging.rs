@@ -1,22 +1,37 @@
-use crate::core::{config::CLIERPConfig, result::CLIERPResult};
+use crate::core::{config::CLIERPConfig, error::CLIERPError, result::CLIERPResult};
+use once_cell::sync::OnceCell;
 use tracing::Level;
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Registry};
+use tracing_subscriber::{fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry};
 
-pub fn init_logging(config: &CLIERPConfig) -> CLIERPResult<()> {
-    let level = match config.logging.level.as_str() {
+/// Values `logging.level` accepts
+pub const LOG_LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error"];
+
+/// Swaps the filter of the installed subscriber, see `set_log_level`
+static FILTER_HANDLE: OnceCell<reload::Handle<EnvFilter, Registry>> = OnceCell::new();
+
+fn parse_level(level: &str) -> Level {
+    match level.to_lowercase().as_str() {
         "trace" => Level::TRACE,
         "debug" => Level::DEBUG,
         "info" => Level::INFO,
         "warn" => Level::WARN,
         "error" => Level::ERROR,
         _ => Level::INFO,
-    };
+    }
+}
 
-    let env_filter = EnvFilter::from_default_env()
+fn env_filter(level: Level) -> EnvFilter {
+    EnvFilter::from_default_env()
         .add_directive(format!("clierp={}", level).parse().unwrap())
-        .add_directive("diesel=warn".parse().unwrap());
+        .add_directive("diesel=warn".parse().unwrap())
+}
 
-    let registry = Registry::default().with(env_filter);
+pub fn init_logging(config: &CLIERPConfig) -> CLIERPResult<()> {
+    let level = parse_level(&config.logging.level);
+    let (filter, handle) = reload::Layer::new(env_filter(level));
+    let _ = FILTER_HANDLE.set(handle);
+
+    let registry = Registry::default().with(filter);
 
     match config.logging.format.as_str() {
         "compact" => {
@@ -30,3 +45,17 @@ pub fn init_logging(config: &CLIERPConfig) -> CLIERPResult<()> {
     tracing::info!("Logging initialized with level: {}", level);
     Ok(())
 }
+
+/// Change the level of a running process, e.g. when the server reloads
+/// its configuration
+pub fn set_log_level(level: &str) -> CLIERPResult<()> {
+    let handle = FILTER_HANDLE
+        .get()
+        .ok_or_else(|| CLIERPError::Internal("Logging is not initialized".to_string()))?;
+    let level = parse_level(level);
+    handle
+        .reload(env_filter(level))
+        .map_err(|e| CLIERPError::Internal(format!("Failed to change the log level: {}", e)))?;
+    tracing::info!("Log level changed to {}", level);
+    Ok(())
+}
@@ -4,4 +4,5 @@ pub mod config;
 pub mod error;
 pub mod logging;
 pub mod result;
+pub mod settings;
 pub mod workflow;
@@ -0,0 +1,203 @@
+//! `clierp config show` and `clierp config set`: the effective configuration
+//! as dotted keys, and single settings saved to `config/settings.json`
+
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::core::config::{CLIERPConfig, SETTINGS_FILE};
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+
+/// Shown in place of secrets
+pub const REDACTED: &str = "<redacted>";
+
+/// Settings a running server applies on reload; everything else needs a
+/// restart
+pub const RELOADABLE_SETTINGS: &[&str] = &["cache.enabled", "logging.level"];
+
+fn is_secret(key: &str) -> bool {
+    let name = key.rsplit('.').next().unwrap_or(key);
+    ["secret", "password", "token"].iter().any(|word| name.contains(word))
+}
+
+fn redact(key: &str, value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (name, value) in map.iter_mut() {
+                redact(&format!("{}.{}", key, name), value);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| redact(key, item)),
+        Value::String(_) if is_secret(key) => *value = Value::String(REDACTED.to_string()),
+        _ => {}
+    }
+}
+
+/// Every setting as a dotted key and its value, sorted by key; lists such as
+/// `server.tenants` stay one value
+pub fn flatten_settings(config: &CLIERPConfig) -> CLIERPResult<Vec<(String, Value)>> {
+    fn walk(prefix: &str, value: Value, out: &mut Vec<(String, Value)>) {
+        match value {
+            Value::Object(map) if !map.is_empty() || prefix.is_empty() => {
+                for (name, value) in map {
+                    let key = if prefix.is_empty() { name } else { format!("{}.{}", prefix, name) };
+                    walk(&key, value, out);
+                }
+            }
+            value => out.push((prefix.to_string(), value)),
+        }
+    }
+
+    let mut settings = Vec::new();
+    walk("", serde_json::to_value(config)?, &mut settings);
+    settings.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(settings)
+}
+
+/// `flatten_settings` with secrets replaced by `REDACTED`
+pub fn redacted_settings(config: &CLIERPConfig) -> CLIERPResult<Vec<(String, Value)>> {
+    let mut settings = flatten_settings(config)?;
+    for (key, value) in settings.iter_mut() {
+        redact(key, value);
+    }
+    Ok(settings)
+}
+
+/// A setting as `config show` prints it: strings bare, unset as "-"
+pub fn display_setting(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Read `raw` as a value of the setting's current type; settings that are
+/// unset take JSON or else a string, and "null" or "" unsets an optional one
+pub fn parse_setting(current: &Value, raw: &str) -> Result<Value, String> {
+    let raw = raw.trim();
+    match current {
+        Value::Bool(_) => match raw.to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Ok(Value::Bool(true)),
+            "false" | "no" | "off" | "0" => Ok(Value::Bool(false)),
+            _ => Err(format!("Expected true or false, got '{}'", raw)),
+        },
+        Value::Number(n) if n.is_f64() => raw
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number)
+            .ok_or_else(|| format!("Expected a number, got '{}'", raw)),
+        Value::Number(_) => raw
+            .parse::<i64>()
+            .map(Value::from)
+            .map_err(|_| format!("Expected a whole number, got '{}'", raw)),
+        Value::String(_) => Ok(Value::String(raw.to_string())),
+        Value::Array(_) | Value::Object(_) => {
+            serde_json::from_str(raw).map_err(|e| format!("Expected JSON for this setting: {}", e))
+        }
+        Value::Null if raw.is_empty() || raw == "null" => Ok(Value::Null),
+        Value::Null => Ok(serde_json::from_str::<Value>(raw)
+            .ok()
+            .filter(|value| !value.is_string() && !value.is_object() && !value.is_array())
+            .unwrap_or_else(|| Value::String(raw.to_string()))),
+    }
+}
+
+/// Put `value` at the dotted `key` of a settings object, creating the
+/// objects on the way
+pub fn set_path(settings: &mut Value, key: &str, value: Value) {
+    let mut node = settings;
+    let mut parts = key.split('.').peekable();
+    while let Some(part) = parts.next() {
+        if !node.is_object() {
+            *node = Value::Object(Map::new());
+        }
+        let map = node.as_object_mut().expect("object");
+        if parts.peek().is_none() {
+            map.insert(part.to_string(), value);
+            return;
+        }
+        node = map.entry(part.to_string()).or_insert_with(|| Value::Object(Map::new()));
+    }
+}
+
+/// Environment variable overriding `key`, e.g. CLIERP_LOGGING__LEVEL
+pub fn env_var_for(key: &str) -> String {
+    format!("CLIERP_{}", key.replace('.', "__").to_uppercase())
+}
+
+/// Validate `raw` for `key` against the configuration loaded now and save
+/// it to `config/settings.json`. Returns the value saved.
+pub fn set_setting(key: &str, raw: &str) -> CLIERPResult<Value> {
+    let config = CLIERPConfig::load()?;
+    let current = flatten_settings(&config)?
+        .into_iter()
+        .find(|(name, _)| name == key)
+        .map(|(_, value)| value)
+        .ok_or_else(|| {
+            CLIERPError::NotFound(format!("Unknown setting '{}'; see `clierp config show` for the keys", key))
+        })?;
+    let mut value = parse_setting(&current, raw).map_err(|e| CLIERPError::Validation(format!("{}: {}", key, e)))?;
+
+    // The whole configuration has to stay loadable with the new value; an
+    // unset setting read as a number or flag may be text after all
+    let candidate = |value: &Value| -> CLIERPResult<CLIERPConfig> {
+        let mut candidate = serde_json::to_value(&config)?;
+        set_path(&mut candidate, key, value.clone());
+        serde_json::from_value(candidate).map_err(|e| CLIERPError::Validation(format!("{}: {}", key, e)))
+    };
+    let candidate = match candidate(&value) {
+        Err(_) if current.is_null() && !value.is_string() && !value.is_null() => {
+            value = Value::String(raw.trim().to_string());
+            candidate(&value)?
+        }
+        result => result?,
+    };
+    candidate.validate()?;
+
+    let path = Path::new(SETTINGS_FILE);
+    let mut settings = match std::fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Value::Object(Map::new()),
+        Err(e) => return Err(e.into()),
+    };
+    set_path(&mut settings, key, value.clone());
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&settings)? + "\n")?;
+
+    tracing::info!("Setting {} saved to {}", key, SETTINGS_FILE);
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_settings() {
+        let settings = redacted_settings(&CLIERPConfig::default()).unwrap();
+        let get = |key: &str| settings.iter().find(|(name, _)| name == key).map(|(_, v)| v.clone());
+        assert_eq!(get("database.busy_timeout"), Some(json!(5000)));
+        assert_eq!(get("auth.jwt_secret"), Some(json!(REDACTED)));
+        assert_eq!(get("server.tenants"), Some(json!([])));
+
+        assert_eq!(parse_setting(&json!(true), "off"), Ok(json!(false)));
+        assert_eq!(parse_setting(&json!(30), "45"), Ok(json!(45)));
+        assert!(parse_setting(&json!(30), "4.5").is_err());
+        assert_eq!(parse_setting(&json!(0.5), "2"), Ok(json!(2.0)));
+        assert_eq!(parse_setting(&Value::Null, "sqlite:./replica.db"), Ok(json!("sqlite:./replica.db")));
+        assert_eq!(parse_setting(&Value::Null, "12"), Ok(json!(12)));
+        assert_eq!(parse_setting(&Value::Null, "null"), Ok(Value::Null));
+
+        let mut file = json!({"logging": {"format": "compact"}});
+        set_path(&mut file, "logging.level", json!("debug"));
+        set_path(&mut file, "cache.enabled", json!(false));
+        assert_eq!(file, json!({"logging": {"format": "compact", "level": "debug"}, "cache": {"enabled": false}}));
+        assert_eq!(env_var_for("logging.level"), "CLIERP_LOGGING__LEVEL");
+    }
+}
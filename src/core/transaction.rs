@@ -104,6 +104,11 @@ impl UnitOfWorkOperation for StockUpdateOperation {
         diesel::insert_into(stock_movements::table)
             .values(&movement)
             .execute(conn)?;
+        crate::modules::reporting::summaries::SummaryService::refresh_product_day(
+            conn,
+            self.product_id,
+            Utc::now().date_naive(),
+        )?;
 
         Ok(())
     }
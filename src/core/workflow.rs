@@ -1,4 +1,5 @@
 use crate::core::{auth::AuthenticatedUser, error::CLIERPError, result::CLIERPResult};
+use crate::database::models::UserRole;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -109,7 +110,7 @@ impl WorkflowEngine {
         // Check if user has required role for this step
         if let Some(required_role) = &step.required_role {
             if let Some(user) = &context.user {
-                if user.role.to_string() != *required_role {
+                if !role_satisfies(&user.role, required_role) {
                     return Err(CLIERPError::Authorization(format!(
                         "Step '{}' requires role: {}",
                         step.name, required_role
@@ -174,6 +175,27 @@ impl WorkflowEngine {
     }
 }
 
+/// Whether `role` may perform a step that requires `required_role`.
+/// Admins, managers and supervisors also cover the steps of the roles
+/// below them; auditors only cover auditor steps.
+pub fn role_satisfies(role: &UserRole, required_role: &str) -> bool {
+    fn rank(role: &str) -> Option<u8> {
+        match role {
+            "employee" => Some(0),
+            "supervisor" => Some(1),
+            "manager" => Some(2),
+            "admin" => Some(3),
+            _ => None,
+        }
+    }
+
+    let role = role.to_string();
+    if role == required_role {
+        return true;
+    }
+    matches!((rank(&role), rank(required_role)), (Some(have), Some(need)) if have >= need)
+}
+
 impl Default for WorkflowEngine {
     fn default() -> Self {
         Self::new()
@@ -257,5 +279,33 @@ pub fn create_default_workflows() -> Vec<Workflow> {
             current_step: 0,
             status: WorkflowStatus::Draft,
         },
+        Workflow {
+            id: "stock_adjustment_approval".to_string(),
+            name: "Stock Adjustment Approval".to_string(),
+            description: "Approval of manual stock changes above the configured threshold".to_string(),
+            steps: vec![WorkflowStep {
+                id: "approve_stock_adjustment".to_string(),
+                name: "Approve Stock Adjustment".to_string(),
+                description: "Review the requested change and apply it to stock".to_string(),
+                required_role: Some("supervisor".to_string()),
+                auto_execute: false,
+            }],
+            current_step: 0,
+            status: WorkflowStatus::Draft,
+        },
+        Workflow {
+            id: "stock_write_down_approval".to_string(),
+            name: "Stock Write-Down Approval".to_string(),
+            description: "Approval of provisions for slow-moving and dead stock".to_string(),
+            steps: vec![WorkflowStep {
+                id: "approve_stock_write_down".to_string(),
+                name: "Approve Stock Write-Down".to_string(),
+                description: "Review the proposed provision and post it to the ledger".to_string(),
+                required_role: Some("manager".to_string()),
+                auto_execute: false,
+            }],
+            current_step: 0,
+            status: WorkflowStatus::Draft,
+        },
     ]
 }
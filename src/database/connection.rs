@@ -1,11 +1,17 @@
-use crate::core::{config::CLIERPConfig, error::CLIERPError, result::CLIERPResult};
+use crate::core::{
+    config::{CLIERPConfig, DatabaseConfig},
+    error::CLIERPError,
+    result::CLIERPResult,
+};
 use diesel::{
     connection::SimpleConnection,
     prelude::*,
-    r2d2::{ConnectionManager, Pool, PooledConnection},
+    r2d2::{ConnectionManager, CustomizeConnection, Pool, PooledConnection},
     sqlite::SqliteConnection,
 };
 use once_cell::sync::OnceCell;
+use std::cell::RefCell;
+use super::encryption::{database_key, key_pragma, sqlcipher_available, DATABASE_KEY_ENV};
 use std::sync::Arc;
 
 pub type SqlitePool = Pool<ConnectionManager<SqliteConnection>>;
@@ -13,6 +19,127 @@ pub type PooledSqliteConnection = PooledConnection<ConnectionManager<SqliteConne
 pub type DatabaseConnection = PooledSqliteConnection;
 
 static DATABASE_POOL: OnceCell<Arc<SqlitePool>> = OnceCell::new();
+static REPLICA_POOL: OnceCell<Arc<SqlitePool>> = OnceCell::new();
+
+/// Seconds to wait for a replica connection before reading the primary
+const REPLICA_TIMEOUT_SECS: u64 = 2;
+
+thread_local! {
+    /// Pool used on this thread instead of the global one, see `DatabaseManager::scope`
+    static SCOPED_POOL: RefCell<Option<Arc<SqlitePool>>> = const { RefCell::new(None) };
+}
+
+/// Restores the previous pool of the thread when dropped
+pub struct PoolScope {
+    previous: Option<Arc<SqlitePool>>,
+}
+
+impl Drop for PoolScope {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        SCOPED_POOL.with(|scoped| *scoped.borrow_mut() = previous);
+    }
+}
+
+const JOURNAL_MODES: &[&str] = &["delete", "truncate", "persist", "memory", "wal", "off"];
+const SYNCHRONOUS_MODES: &[&str] = &["off", "normal", "full", "extra"];
+
+/// Pragmas every connection is opened with, from the `database` settings
+#[derive(Clone, PartialEq, Eq)]
+pub struct SqlitePragmas {
+    /// SQLCipher key when `database.encrypted` is set
+    key: Option<String>,
+    journal_mode: String,
+    busy_timeout: u64,
+    synchronous: String,
+    /// Connections to the reporting replica only read
+    read_only: bool,
+}
+
+impl std::fmt::Debug for SqlitePragmas {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SqlitePragmas")
+            .field("key", &self.key.as_ref().map(|_| "<redacted>"))
+            .field("journal_mode", &self.journal_mode)
+            .field("busy_timeout", &self.busy_timeout)
+            .field("synchronous", &self.synchronous)
+            .field("read_only", &self.read_only)
+            .finish()
+    }
+}
+
+impl SqlitePragmas {
+    pub fn from_config(config: &DatabaseConfig) -> CLIERPResult<Self> {
+        let journal_mode = config.journal_mode.trim().to_lowercase();
+        if !JOURNAL_MODES.contains(&journal_mode.as_str()) {
+            return Err(CLIERPError::Configuration(config::ConfigError::Message(format!(
+                "database.journal_mode must be one of {}, got '{}'",
+                JOURNAL_MODES.join(", "),
+                config.journal_mode
+            ))));
+        }
+        let synchronous = config.synchronous.trim().to_lowercase();
+        if !SYNCHRONOUS_MODES.contains(&synchronous.as_str()) {
+            return Err(CLIERPError::Configuration(config::ConfigError::Message(format!(
+                "database.synchronous must be one of {}, got '{}'",
+                SYNCHRONOUS_MODES.join(", "),
+                config.synchronous
+            ))));
+        }
+        let key = if config.encrypted {
+            if !sqlcipher_available() {
+                return Err(CLIERPError::Configuration(config::ConfigError::Message(
+                    "database.encrypted is set but this build has no SQLCipher support; rebuild with `--features sqlcipher`".to_string(),
+                )));
+            }
+            Some(database_key(config)?.ok_or_else(|| {
+                CLIERPError::Configuration(config::ConfigError::Message(format!(
+                    "database.encrypted is set; put the key in {} or set database.key_command",
+                    DATABASE_KEY_ENV
+                )))
+            })?)
+        } else {
+            None
+        };
+        Ok(Self {
+            key,
+            journal_mode,
+            busy_timeout: config.busy_timeout,
+            synchronous,
+            read_only: false,
+        })
+    }
+
+    /// The same key and busy timeout, refusing writes; the journal mode is
+    /// left to whatever keeps the replica current
+    pub fn read_only(self) -> Self {
+        Self { read_only: true, ..self }
+    }
+
+    /// The key has to come first on an encrypted database. The busy timeout
+    /// follows, so switching the journal mode waits for another
+    /// invocation's lock instead of failing on it.
+    pub fn sql(&self) -> String {
+        let key = self.key.as_deref().map(|key| format!("{} ", key_pragma(key))).unwrap_or_default();
+        if self.read_only {
+            return format!("{}PRAGMA busy_timeout = {}; PRAGMA query_only = ON;", key, self.busy_timeout);
+        }
+        format!(
+            "{}PRAGMA busy_timeout = {}; PRAGMA journal_mode = {}; PRAGMA synchronous = {}; PRAGMA foreign_keys = ON;",
+            key, self.busy_timeout, self.journal_mode, self.synchronous
+        )
+    }
+
+    pub fn apply(&self, conn: &mut SqliteConnection) -> CLIERPResult<()> {
+        conn.batch_execute(&self.sql()).map_err(CLIERPError::Database)
+    }
+}
+
+impl CustomizeConnection<SqliteConnection, diesel::r2d2::Error> for SqlitePragmas {
+    fn on_acquire(&self, conn: &mut SqliteConnection) -> Result<(), diesel::r2d2::Error> {
+        conn.batch_execute(&self.sql()).map_err(diesel::r2d2::Error::QueryError)
+    }
+}
 
 pub struct DatabaseManager;
 
@@ -24,37 +151,115 @@ pub fn get_connection() -> CLIERPResult<DatabaseConnection> {
     })
 }
 
+/// Connection for reports and dashboards: the read-only replica when one
+/// is configured and reachable, otherwise the primary
+pub fn get_report_connection() -> CLIERPResult<DatabaseConnection> {
+    report_connection(DatabaseManager::replica_pool().as_deref())
+}
+
+fn report_connection(replica: Option<&SqlitePool>) -> CLIERPResult<DatabaseConnection> {
+    if let Some(replica) = replica {
+        match replica.get() {
+            Ok(conn) => return Ok(conn),
+            Err(e) => tracing::warn!("Replica unavailable, reading the primary: {}", e),
+        }
+    }
+    get_connection()
+}
+
 impl DatabaseManager {
     pub fn initialize(config: &CLIERPConfig) -> CLIERPResult<()> {
-        let database_url = &config.database.url.replace("sqlite:", "");
+        let pool = Self::build_pool(&config.database.url, config)?;
+
+        DATABASE_POOL
+            .set(Arc::new(pool))
+            .map_err(|_| CLIERPError::Internal("Database pool already initialized".to_string()))?;
+
+        tracing::info!("Database connection pool initialized");
+
+        if let Some(replica_url) = &config.database.replica_url {
+            match Self::build_replica_pool(replica_url, config) {
+                Ok(pool) => {
+                    let _ = REPLICA_POOL.set(Arc::new(pool));
+                    tracing::info!("Reports read from replica {}", replica_url);
+                }
+                Err(e) => tracing::warn!("Replica {} unavailable, reports read the primary: {}", replica_url, e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Read-only pool on the reporting replica, which must be a SQLite
+    /// file. Connections give up quickly, so reports fall back to the
+    /// primary instead of waiting on a replica that is gone.
+    pub fn build_replica_pool(replica_url: &str, config: &CLIERPConfig) -> CLIERPResult<SqlitePool> {
+        let path = replica_url.replace("sqlite:", "");
+        if !std::path::Path::new(&path).is_file() {
+            return Err(CLIERPError::NotFound(format!("Replica database {} not found", path)));
+        }
+        let pragmas = SqlitePragmas::from_config(&config.database)?.read_only();
+
+        let manager = ConnectionManager::<SqliteConnection>::new(format!("file:{}?mode=ro", path));
+        let pool = Pool::builder()
+            .max_size(config.database.max_connections)
+            .connection_timeout(std::time::Duration::from_secs(REPLICA_TIMEOUT_SECS))
+            .connection_customizer(Box::new(pragmas))
+            .build(manager)
+            .map_err(|e| {
+                CLIERPError::Internal(format!("Failed to create replica connection pool: {}", e))
+            })?;
+        Ok(pool)
+    }
+
+    /// The replica reports read from: none if not configured or it could
+    /// not be opened, and none inside a scope on another database, such as
+    /// a tenant's, whose reports must not come from the main replica
+    pub fn replica_pool() -> Option<Arc<SqlitePool>> {
+        let replica = REPLICA_POOL.get()?.clone();
+        let scoped = SCOPED_POOL.with(|scoped| scoped.borrow().clone());
+        match (scoped, DATABASE_POOL.get()) {
+            (Some(scoped), Some(primary)) if Arc::ptr_eq(&scoped, primary) => Some(replica),
+            (Some(_), _) => None,
+            (None, _) => Some(replica),
+        }
+    }
+
+    /// Pool for `database_url` sized, timed out and tuned like the main one
+    pub fn build_pool(database_url: &str, config: &CLIERPConfig) -> CLIERPResult<SqlitePool> {
+        let database_url = database_url.replace("sqlite:", "");
+        let pragmas = SqlitePragmas::from_config(&config.database)?;
 
         let manager = ConnectionManager::<SqliteConnection>::new(database_url);
         let pool = Pool::builder()
             .max_size(config.database.max_connections)
             .connection_timeout(std::time::Duration::from_secs(config.database.timeout))
+            // Foreign keys, busy timeout and journal mode on every connection
+            .connection_customizer(Box::new(pragmas))
             .build(manager)
             .map_err(|e| {
                 CLIERPError::Internal(format!("Failed to create connection pool: {}", e))
             })?;
 
         // Test the connection
-        let mut conn = pool.get().map_err(|e| {
+        pool.get().map_err(|e| {
             CLIERPError::DatabaseConnection(diesel::ConnectionError::BadConnection(e.to_string()))
         })?;
 
-        // Enable foreign key constraints for SQLite
-        conn.batch_execute("PRAGMA foreign_keys = ON;")
-            .map_err(CLIERPError::Database)?;
-
-        DATABASE_POOL
-            .set(Arc::new(pool))
-            .map_err(|_| CLIERPError::Internal("Database pool already initialized".to_string()))?;
+        Ok(pool)
+    }
 
-        tracing::info!("Database connection pool initialized");
-        Ok(())
+    /// Make `get_connection` on this thread use `pool` until the returned
+    /// scope is dropped, so services deep in a request of the multi-tenant
+    /// server reach the tenant's database and no other
+    pub fn scope(pool: Arc<SqlitePool>) -> PoolScope {
+        let previous = SCOPED_POOL.with(|scoped| scoped.borrow_mut().replace(pool));
+        PoolScope { previous }
     }
 
     pub fn get_pool() -> CLIERPResult<Arc<SqlitePool>> {
+        if let Some(pool) = SCOPED_POOL.with(|scoped| scoped.borrow().clone()) {
+            return Ok(pool);
+        }
         DATABASE_POOL
             .get()
             .cloned()
@@ -72,15 +277,122 @@ impl DatabaseManager {
         Ok(DatabaseManager)
     }
 
-    pub fn establish_connection(database_url: &str) -> CLIERPResult<SqliteConnection> {
+    /// Connection outside the pool, with the pragmas pooled connections get
+    pub fn establish_connection(database_url: &str, config: &CLIERPConfig) -> CLIERPResult<SqliteConnection> {
         let database_url = database_url.replace("sqlite:", "");
         let mut conn =
             SqliteConnection::establish(&database_url).map_err(CLIERPError::DatabaseConnection)?;
 
-        // Enable foreign key constraints for SQLite
-        conn.batch_execute("PRAGMA foreign_keys = ON;")
-            .map_err(CLIERPError::Database)?;
+        SqlitePragmas::from_config(&config.database)?.apply(&mut conn)?;
 
         Ok(conn)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqlite_pragmas_from_config() {
+        let mut config = CLIERPConfig::default().database;
+        assert_eq!(
+            SqlitePragmas::from_config(&config).unwrap().sql(),
+            "PRAGMA busy_timeout = 5000; PRAGMA journal_mode = wal; PRAGMA synchronous = normal; PRAGMA foreign_keys = ON;"
+        );
+
+        config.journal_mode = "WAL2".to_string();
+        assert!(SqlitePragmas::from_config(&config).is_err());
+
+        config.journal_mode = "Delete".to_string();
+        config.synchronous = "FULL".to_string();
+        config.busy_timeout = 0;
+        assert_eq!(
+            SqlitePragmas::from_config(&config).unwrap().sql(),
+            "PRAGMA busy_timeout = 0; PRAGMA journal_mode = delete; PRAGMA synchronous = full; PRAGMA foreign_keys = ON;"
+        );
+        assert_eq!(
+            SqlitePragmas::from_config(&config).unwrap().read_only().sql(),
+            "PRAGMA busy_timeout = 0; PRAGMA query_only = ON;"
+        );
+    }
+
+    /// A database file with the built-in tables and `products` of its own
+    fn database_file(path: &std::path::Path, products: &[&str]) {
+        let mut conn = SqliteConnection::establish(&path.to_string_lossy()).unwrap();
+        crate::database::migrations::run_sql_migrations(&mut conn).unwrap();
+        crate::database::migrations::run_migrations(&mut conn).unwrap();
+        for sku in products {
+            diesel::sql_query(format!(
+                "INSERT INTO products (sku, name, category_id, price, cost_price, current_stock, min_stock_level, unit) \
+                 VALUES ('{}', '{} widget', 1, 1000, 600, 10, 5, 'ea')",
+                sku, sku
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        }
+    }
+
+    fn product_count(conn: &mut SqliteConnection) -> i64 {
+        use crate::database::schema::products;
+        products::table.count().get_result(conn).unwrap()
+    }
+
+    #[test]
+    fn test_report_connection_falls_back_to_primary() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = CLIERPConfig::default();
+        config.database.max_connections = 1;
+        let missing = format!("sqlite:{}", dir.path().join("missing.db").display());
+        assert!(matches!(DatabaseManager::build_replica_pool(&missing, &config), Err(CLIERPError::NotFound(_))));
+
+        let replica_path = dir.path().join("replica.db");
+        database_file(&replica_path, &["REPLICA-1", "REPLICA-2"]);
+        let replica = DatabaseManager::build_replica_pool(&format!("sqlite:{}", replica_path.display()), &config).unwrap();
+
+        let _company = crate::testing::CompanyFixture::new().with_products(1).build().unwrap();
+        assert_eq!(product_count(&mut report_connection(Some(&replica)).unwrap()), 2);
+        // A replica that does not answer in time is skipped for the primary
+        let _busy = replica.get().unwrap();
+        assert_eq!(product_count(&mut report_connection(Some(&replica)).unwrap()), 1);
+        assert_eq!(product_count(&mut report_connection(None).unwrap()), 1);
+    }
+
+    #[test]
+    fn test_report_reads_the_replica() {
+        use crate::modules::reporting::{InventoryReportsGenerator, ReportConfig, ReportFormat, ReportGenerator};
+
+        // The replica is a copy taken before the primary got a second product
+        let dir = tempfile::tempdir().unwrap();
+        let primary_path = dir.path().join("primary.db");
+        let replica_path = dir.path().join("replica.db");
+        database_file(&primary_path, &["BOTH-1"]);
+        std::fs::copy(&primary_path, &replica_path).unwrap();
+        database_file(&primary_path, &["PRIMARY-2"]);
+
+        let mut config = CLIERPConfig::default();
+        config.database.url = format!("sqlite:{}", primary_path.display());
+        config.database.replica_url = Some(format!("sqlite:{}", replica_path.display()));
+        DatabaseManager::initialize(&config).unwrap();
+        assert_eq!(product_count(&mut get_connection().unwrap()), 2);
+
+        let report = InventoryReportsGenerator
+            .generate_report(ReportConfig {
+                title: "stock_status".to_string(),
+                description: None,
+                date_range: None,
+                filters: std::collections::HashMap::new(),
+                format: ReportFormat::Text,
+                include_charts: false,
+                include_summary: true,
+            })
+            .unwrap();
+        let text = crate::cli::commands::reports::render_report(&report).unwrap();
+        assert!(text.contains("BOTH-1"));
+        assert!(!text.contains("PRIMARY-2"));
+
+        // Report connections cannot write to the replica
+        let mut conn = get_report_connection().unwrap();
+        assert!(diesel::sql_query("DELETE FROM products").execute(&mut conn).is_err());
+    }
+}
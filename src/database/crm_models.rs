@@ -3,7 +3,7 @@ use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 use clap::ValueEnum;
 
-use super::schema::{customers, leads, deals, campaigns, campaign_leads, activities};
+use super::schema::{churn_alerts, customers, dropbox_messages, customer_surveys, leads, deals, campaigns, campaign_leads, activities};
 
 // Customer models
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
@@ -91,6 +91,7 @@ pub struct Lead {
     pub notes: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub visibility: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable)]
@@ -107,6 +108,7 @@ pub struct NewLead {
     pub title: String,
     pub description: Option<String>,
     pub notes: Option<String>,
+    pub visibility: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ValueEnum)]
@@ -153,6 +155,27 @@ impl std::fmt::Display for LeadPriority {
     }
 }
 
+/// Who besides the owner can see a lead or deal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum RecordVisibility {
+    /// Owner and their managers only
+    Private,
+    /// Everyone in the owner's department
+    Team,
+    /// All users
+    Public,
+}
+
+impl std::fmt::Display for RecordVisibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordVisibility::Private => write!(f, "private"),
+            RecordVisibility::Team => write!(f, "team"),
+            RecordVisibility::Public => write!(f, "public"),
+        }
+    }
+}
+
 // Deal models
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
 #[diesel(table_name = deals)]
@@ -171,6 +194,8 @@ pub struct Deal {
     pub notes: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub destination_country: Option<String>,
+    pub visibility: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable)]
@@ -187,6 +212,8 @@ pub struct NewDeal {
     pub discount_percent: Option<i32>,
     pub final_amount: Option<i32>,
     pub notes: Option<String>,
+    pub destination_country: Option<String>,
+    pub visibility: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ValueEnum)]
@@ -296,6 +323,136 @@ impl std::fmt::Display for CampaignStatus {
     }
 }
 
+// A lead reached by a campaign; one row per campaign and lead
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = campaign_leads)]
+pub struct CampaignLead {
+    pub id: i32,
+    pub campaign_id: i32,
+    pub lead_id: i32,
+    pub response: Option<String>,
+    pub response_date: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = campaign_leads)]
+pub struct NewCampaignLead {
+    pub campaign_id: i32,
+    pub lead_id: i32,
+    pub response: Option<String>,
+    pub response_date: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ValueEnum)]
+pub enum CampaignResponse {
+    Interested,
+    NotInterested,
+    NoResponse,
+}
+
+impl std::fmt::Display for CampaignResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CampaignResponse::Interested => write!(f, "interested"),
+            CampaignResponse::NotInterested => write!(f, "not_interested"),
+            CampaignResponse::NoResponse => write!(f, "no_response"),
+        }
+    }
+}
+
+// Survey models
+/// A customer's answer to "how likely are you to recommend us", 0 to 10
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = customer_surveys)]
+pub struct CustomerSurvey {
+    pub id: i32,
+    pub customer_id: i32,
+    pub deal_id: Option<i32>,
+    pub score: i32,
+    pub comment: Option<String>,
+    pub survey_date: NaiveDate,
+    pub channel: String,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = customer_surveys)]
+pub struct NewCustomerSurvey {
+    pub customer_id: i32,
+    pub deal_id: Option<i32>,
+    pub score: i32,
+    pub comment: Option<String>,
+    pub survey_date: NaiveDate,
+    pub channel: String,
+    pub created_by: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ValueEnum)]
+pub enum SurveyChannel {
+    Email,
+    Phone,
+    Web,
+    InPerson,
+}
+
+impl std::fmt::Display for SurveyChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SurveyChannel::Email => write!(f, "email"),
+            SurveyChannel::Phone => write!(f, "phone"),
+            SurveyChannel::Web => write!(f, "web"),
+            SurveyChannel::InPerson => write!(f, "in_person"),
+        }
+    }
+}
+
+/// An alert sent to the owner of a customer at risk of churning
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = churn_alerts)]
+pub struct ChurnAlert {
+    pub id: i32,
+    pub customer_id: i32,
+    pub user_id: Option<i32>,
+    /// Comma-separated signals the customer showed
+    pub signals: String,
+    pub alerted_on: NaiveDate,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = churn_alerts)]
+pub struct NewChurnAlert {
+    pub customer_id: i32,
+    pub user_id: Option<i32>,
+    pub signals: String,
+    pub alerted_on: NaiveDate,
+}
+
+/// An email received by the BCC dropbox, kept so a message delivered twice
+/// is logged once
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = dropbox_messages)]
+pub struct DropboxMessage {
+    pub id: i32,
+    pub message_id: String,
+    pub sender: String,
+    pub subject: String,
+    /// Activities logged from the message; 0 when no address matched
+    pub activities: i32,
+    pub received_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = dropbox_messages)]
+pub struct NewDropboxMessage {
+    pub message_id: String,
+    pub sender: String,
+    pub subject: String,
+    pub activities: i32,
+}
+
 // Activity models
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
 #[diesel(table_name = activities)]
@@ -0,0 +1,178 @@
+use std::path::{Path, PathBuf};
+
+use diesel::connection::SimpleConnection;
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+use once_cell::sync::OnceCell;
+
+use crate::core::config::DatabaseConfig;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+
+/// Environment variable holding the database key; it takes precedence over
+/// `database.key_command`
+pub const DATABASE_KEY_ENV: &str = "CLIERP_DATABASE_KEY";
+
+static DATABASE_KEY: OnceCell<Option<String>> = OnceCell::new();
+
+/// Whether this build links SQLCipher (the `sqlcipher` feature)
+pub fn sqlcipher_available() -> bool {
+    cfg!(feature = "sqlcipher")
+}
+
+/// The database key from `CLIERP_DATABASE_KEY`, or else printed by
+/// `database.key_command`, e.g. `secret-tool lookup service clierp` for the
+/// desktop keyring or `security find-generic-password -s clierp -w` on
+/// macOS. Looked up once per process; `None` if neither is set.
+pub fn database_key(config: &DatabaseConfig) -> CLIERPResult<Option<String>> {
+    DATABASE_KEY
+        .get_or_try_init(|| resolve_key(config))
+        .cloned()
+}
+
+fn resolve_key(config: &DatabaseConfig) -> CLIERPResult<Option<String>> {
+    if let Ok(key) = std::env::var(DATABASE_KEY_ENV) {
+        if !key.is_empty() {
+            return Ok(Some(key));
+        }
+    }
+    let Some(command) = config.key_command.as_deref().filter(|c| !c.trim().is_empty()) else {
+        return Ok(None);
+    };
+    let mut words = command.split_whitespace();
+    let program = words.next().unwrap_or_default();
+    let output = std::process::Command::new(program)
+        .args(words)
+        .output()
+        .map_err(|e| CLIERPError::Configuration(config::ConfigError::Message(format!(
+            "database.key_command '{}' could not be run: {}",
+            program, e
+        ))))?;
+    if !output.status.success() {
+        return Err(CLIERPError::Configuration(config::ConfigError::Message(format!(
+            "database.key_command '{}' exited with {}",
+            program, output.status
+        ))));
+    }
+    let key = String::from_utf8_lossy(&output.stdout).trim_end_matches(['\r', '\n']).to_string();
+    Ok(Some(key).filter(|key| !key.is_empty()))
+}
+
+/// `PRAGMA key` statement for `key`, which must run before anything else
+/// on a connection
+pub fn key_pragma(key: &str) -> String {
+    format!("PRAGMA key = {};", sql_literal(key))
+}
+
+fn sql_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Where `encrypt_database` writes the encrypted copy before it replaces
+/// the original
+pub fn encrypting_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".encrypting");
+    PathBuf::from(name)
+}
+
+#[derive(QueryableByName)]
+struct CountRow {
+    #[diesel(sql_type = BigInt)]
+    n: i64,
+}
+
+fn schema_objects(conn: &mut SqliteConnection) -> QueryResult<i64> {
+    diesel::sql_query("SELECT count(*) AS n FROM sqlite_master")
+        .get_result::<CountRow>(conn)
+        .map(|row| row.n)
+}
+
+/// Encrypt the plaintext database at `path` under `key` and put the
+/// encrypted copy in its place. The original is deleted, or moved to
+/// `keep_plaintext` if given. Returns the number of schema objects copied.
+///
+/// Nothing else should be writing to the database meanwhile; other CLIERP
+/// processes and the server are best stopped first.
+pub fn encrypt_database(path: &Path, key: &str, keep_plaintext: Option<&Path>) -> CLIERPResult<i64> {
+    if !sqlcipher_available() {
+        return Err(CLIERPError::Configuration(config::ConfigError::Message(
+            "This build has no SQLCipher support; rebuild with `--features sqlcipher`".to_string(),
+        )));
+    }
+    if key.is_empty() {
+        return Err(CLIERPError::Validation(format!(
+            "Set {} or database.key_command to the key to encrypt with",
+            DATABASE_KEY_ENV
+        )));
+    }
+    if !path.is_file() {
+        return Err(CLIERPError::NotFound(format!("Database {} not found", path.display())));
+    }
+    let target = encrypting_path(path);
+    if target.exists() {
+        std::fs::remove_file(&target)?;
+    }
+
+    let to_str = |path: &Path| {
+        path.to_str()
+            .map(str::to_string)
+            .ok_or_else(|| CLIERPError::Validation(format!("{} is not a valid UTF-8 path", path.display())))
+    };
+    let mut plain = SqliteConnection::establish(&to_str(path)?).map_err(CLIERPError::DatabaseConnection)?;
+    let objects = schema_objects(&mut plain).map_err(|_| {
+        CLIERPError::BusinessLogic(format!(
+            "{} is not a plaintext database; it may already be encrypted",
+            path.display()
+        ))
+    })?;
+    plain
+        .batch_execute(&format!(
+            "PRAGMA wal_checkpoint(TRUNCATE); ATTACH DATABASE {} AS encrypted KEY {}; SELECT sqlcipher_export('encrypted'); DETACH DATABASE encrypted;",
+            sql_literal(&to_str(&target)?),
+            sql_literal(key)
+        ))
+        .map_err(CLIERPError::Database)?;
+    drop(plain);
+
+    // Open the copy the way CLIERP will, before the original goes
+    let mut encrypted = SqliteConnection::establish(&to_str(&target)?).map_err(CLIERPError::DatabaseConnection)?;
+    encrypted.batch_execute(&key_pragma(key)).map_err(CLIERPError::Database)?;
+    let copied = schema_objects(&mut encrypted).map_err(CLIERPError::Database)?;
+    drop(encrypted);
+    if copied != objects {
+        let _ = std::fs::remove_file(&target);
+        return Err(CLIERPError::Internal(format!(
+            "The encrypted copy has {} schema objects, the original {}; {} is left as it was",
+            copied,
+            objects,
+            path.display()
+        )));
+    }
+
+    match keep_plaintext {
+        Some(keep) => std::fs::rename(path, keep)?,
+        None => std::fs::remove_file(path)?,
+    }
+    std::fs::rename(&target, path)?;
+    for suffix in ["-wal", "-shm"] {
+        let mut sidecar = path.as_os_str().to_os_string();
+        sidecar.push(suffix);
+        let _ = std::fs::remove_file(PathBuf::from(sidecar));
+    }
+
+    tracing::info!("Encrypted database {}", path.display());
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_pragma() {
+        assert_eq!(key_pragma("s3cret"), "PRAGMA key = 's3cret';");
+        assert_eq!(key_pragma("it's"), "PRAGMA key = 'it''s';");
+        assert_eq!(encrypting_path(Path::new("./clierp.db")), PathBuf::from("./clierp.db.encrypting"));
+    }
+}
@@ -1,5 +1,9 @@
+use crate::core::error::CLIERPError;
 use crate::core::result::CLIERPResult;
+use diesel::migration::MigrationSource;
 use diesel::prelude::*;
+use diesel::sqlite::Sqlite;
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 
 pub fn run_migrations(connection: &mut SqliteConnection) -> CLIERPResult<()> {
     tracing::info!("Running database migrations...");
@@ -35,6 +39,17 @@ pub fn run_migrations(connection: &mut SqliteConnection) -> CLIERPResult<()> {
         )"
     ).execute(connection)?;
 
+    // Termination date was added after the employees table shipped; earlier
+    // terminations are dated by their last update
+    add_column_if_missing(connection, "employees", "termination_date", "DATE")?;
+    diesel::sql_query(
+        "UPDATE employees SET termination_date = DATE(updated_at)
+         WHERE status = 'terminated' AND termination_date IS NULL",
+    )
+    .execute(connection)?;
+    // Job level, matched with salary bands
+    add_column_if_missing(connection, "employees", "level", "TEXT")?;
+
     // Create users table
     diesel::sql_query(
         "CREATE TABLE IF NOT EXISTS users (
@@ -97,7 +112,43 @@ pub fn run_migrations(connection: &mut SqliteConnection) -> CLIERPResult<()> {
             barcode TEXT,
             is_active BOOLEAN NOT NULL DEFAULT TRUE,
             created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            commodity_code TEXT,
+            country_of_origin TEXT,
+            net_weight_grams INTEGER
+        )",
+    )
+    .execute(connection)?;
+
+    // Customs fields were added after the products table shipped
+    add_column_if_missing(connection, "products", "commodity_code", "TEXT")?;
+    add_column_if_missing(connection, "products", "country_of_origin", "TEXT")?;
+    add_column_if_missing(connection, "products", "net_weight_grams", "INTEGER")?;
+
+    // Create product_translations table with per-locale product names and descriptions
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS product_translations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            product_id INTEGER NOT NULL REFERENCES products(id),
+            locale TEXT NOT NULL,
+            name TEXT NOT NULL,
+            description TEXT,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE(product_id, locale)
+        )",
+    )
+    .execute(connection)?;
+
+    // Create category_translations table with per-locale category names and descriptions
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS category_translations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            category_id INTEGER NOT NULL REFERENCES categories(id),
+            locale TEXT NOT NULL,
+            name TEXT NOT NULL,
+            description TEXT,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE(category_id, locale)
         )",
     )
     .execute(connection)?;
@@ -119,6 +170,1002 @@ pub fn run_migrations(connection: &mut SqliteConnection) -> CLIERPResult<()> {
     )
     .execute(connection)?;
 
+    // Create stock_reservations table for soft allocation of on-hand stock
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS stock_reservations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            product_id INTEGER NOT NULL REFERENCES products(id),
+            quantity INTEGER NOT NULL CHECK (quantity > 0),
+            reference_type TEXT NOT NULL,
+            reference_id INTEGER,
+            status TEXT NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'released', 'fulfilled')),
+            notes TEXT,
+            reserved_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create validation_rules table for deployment-specific field checks
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS validation_rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entity TEXT NOT NULL,
+            field TEXT NOT NULL,
+            rule_type TEXT NOT NULL CHECK (rule_type IN ('required', 'regex', 'min', 'max', 'max_length')),
+            value TEXT,
+            message TEXT,
+            is_active BOOLEAN NOT NULL DEFAULT TRUE,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (entity, field, rule_type)
+        )",
+    )
+    .execute(connection)?;
+
+    // Create import_checkpoints table so interrupted imports can resume
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS import_checkpoints (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            import_type TEXT NOT NULL,
+            source TEXT NOT NULL,
+            total_rows INTEGER NOT NULL DEFAULT 0,
+            rows_committed INTEGER NOT NULL DEFAULT 0,
+            last_key TEXT,
+            status TEXT NOT NULL DEFAULT 'running' CHECK (status IN ('running', 'completed')),
+            started_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (import_type, source)
+        )",
+    )
+    .execute(connection)?;
+
+    // Create archive tables; rows keep their original ids and carry no
+    // foreign keys so archived history survives later deletes
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS stock_movements_archive (
+            id INTEGER PRIMARY KEY,
+            product_id INTEGER NOT NULL,
+            movement_type TEXT NOT NULL,
+            quantity INTEGER NOT NULL,
+            unit_cost INTEGER,
+            reference_type TEXT,
+            reference_id INTEGER,
+            notes TEXT,
+            moved_by INTEGER,
+            movement_date DATETIME NOT NULL,
+            archived_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS audit_logs_archive (
+            id INTEGER PRIMARY KEY,
+            user_id INTEGER,
+            table_name TEXT NOT NULL,
+            record_id INTEGER NOT NULL,
+            action TEXT NOT NULL,
+            old_values TEXT,
+            new_values TEXT,
+            changed_at DATETIME NOT NULL,
+            archived_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create archive_runs table recording what each archive run moved
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS archive_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            table_name TEXT NOT NULL,
+            cutoff DATETIME NOT NULL,
+            rows_archived INTEGER NOT NULL DEFAULT 0,
+            summary_rows INTEGER NOT NULL DEFAULT 0,
+            run_by INTEGER REFERENCES users(id),
+            run_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create storage_snapshots table with the database size and row counts of each stats run
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS storage_snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            taken_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            database_bytes INTEGER NOT NULL,
+            row_counts TEXT NOT NULL
+        )",
+    )
+    .execute(connection)?;
+
+    // Create command_usage table with one row per command run when usage statistics are enabled
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS command_usage (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            command TEXT NOT NULL,
+            role TEXT,
+            duration_ms INTEGER NOT NULL,
+            succeeded BOOLEAN NOT NULL,
+            exit_code INTEGER,
+            used_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create sales_orders table for fulfilling won deals
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS sales_orders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            order_number TEXT NOT NULL UNIQUE,
+            deal_id INTEGER REFERENCES deals(id),
+            customer_id INTEGER REFERENCES customers(id),
+            order_date DATE NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'reserved', 'shipped', 'invoiced', 'cancelled')),
+            discount_percent INTEGER NOT NULL DEFAULT 0,
+            total_amount INTEGER NOT NULL,
+            invoice_id INTEGER REFERENCES invoices(id),
+            shipped_at DATETIME,
+            notes TEXT,
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create sales_order_items table with the products of each sales order and their reservations
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS sales_order_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            order_id INTEGER NOT NULL REFERENCES sales_orders(id) ON DELETE CASCADE,
+            product_id INTEGER NOT NULL REFERENCES products(id),
+            quantity INTEGER NOT NULL CHECK (quantity > 0),
+            unit_price INTEGER NOT NULL,
+            total_price INTEGER NOT NULL,
+            reservation_id INTEGER REFERENCES stock_reservations(id),
+            shipped_quantity INTEGER NOT NULL DEFAULT 0,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create legal_holds table with records exempt from retention while held
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS legal_holds (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entity TEXT NOT NULL,
+            record_id INTEGER NOT NULL,
+            reason TEXT NOT NULL,
+            placed_by INTEGER REFERENCES users(id),
+            placed_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            released_by INTEGER REFERENCES users(id),
+            released_at DATETIME
+        )",
+    )
+    .execute(connection)?;
+
+    // Create retention_runs table recording what each retention run removed
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS retention_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entity TEXT NOT NULL,
+            action TEXT NOT NULL CHECK (action IN ('delete', 'anonymize')),
+            cutoff DATETIME NOT NULL,
+            rows_affected INTEGER NOT NULL,
+            rows_held INTEGER NOT NULL,
+            run_by INTEGER REFERENCES users(id),
+            run_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create report_snapshots table holding saved report runs for comparison
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS report_snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT UNIQUE NOT NULL,
+            report_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            result_json TEXT NOT NULL,
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create kpis table with admin-defined KPI formulas and thresholds
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS kpis (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT UNIQUE NOT NULL,
+            description TEXT,
+            formula TEXT NOT NULL,
+            higher_is_better BOOLEAN NOT NULL DEFAULT TRUE,
+            warning_threshold REAL NOT NULL,
+            critical_threshold REAL NOT NULL,
+            last_value REAL,
+            last_status TEXT CHECK (last_status IN ('ok', 'warning', 'critical')),
+            last_evaluated_at DATETIME,
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create anomaly_flags table with unusual postings held for review
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS anomaly_flags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            source TEXT NOT NULL CHECK (source IN ('transaction', 'stock_movement')),
+            record_id INTEGER NOT NULL,
+            subject TEXT NOT NULL,
+            value REAL NOT NULL,
+            score REAL NOT NULL,
+            method TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'confirmed', 'dismissed')),
+            note TEXT,
+            flagged_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            reviewed_by INTEGER REFERENCES users(id),
+            reviewed_at DATETIME,
+            UNIQUE (source, record_id)
+        )",
+    )
+    .execute(connection)?;
+
+    // Create stock_adjustment_requests table with large adjustments awaiting approval
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS stock_adjustment_requests (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            product_id INTEGER NOT NULL REFERENCES products(id),
+            movement_type TEXT NOT NULL CHECK (movement_type IN ('in', 'out', 'adjustment')),
+            quantity INTEGER NOT NULL,
+            unit_cost INTEGER,
+            value INTEGER NOT NULL,
+            reference TEXT,
+            notes TEXT,
+            status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected')),
+            requested_by INTEGER REFERENCES users(id),
+            requested_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            decided_by INTEGER REFERENCES users(id),
+            decided_at DATETIME,
+            decision_note TEXT,
+            movement_id INTEGER REFERENCES stock_movements(id)
+        )",
+    )
+    .execute(connection)?;
+
+    // Create stock_write_downs table with proposed and approved provisions for slow and dead stock
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS stock_write_downs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            as_of DATE NOT NULL,
+            amount INTEGER NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected')),
+            proposed_by INTEGER REFERENCES users(id),
+            proposed_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            decided_by INTEGER REFERENCES users(id),
+            decided_at DATETIME,
+            decision_note TEXT
+        )",
+    )
+    .execute(connection)?;
+
+    // Create stock_write_down_lines table with the products each write-down provisions
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS stock_write_down_lines (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            write_down_id INTEGER NOT NULL REFERENCES stock_write_downs(id),
+            product_id INTEGER NOT NULL REFERENCES products(id),
+            reason TEXT NOT NULL CHECK (reason IN ('slow', 'dead')),
+            quantity INTEGER NOT NULL,
+            unit_cost INTEGER NOT NULL,
+            percent INTEGER NOT NULL,
+            amount INTEGER NOT NULL
+        )",
+    )
+    .execute(connection)?;
+
+    // Create sod_rules table with the enforcement chosen for each segregation of duties rule
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS sod_rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            enforcement TEXT NOT NULL CHECK (enforcement IN ('block', 'override', 'off')),
+            updated_by INTEGER REFERENCES users(id),
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create sod_overrides table logging approvals that went ahead despite a conflict
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS sod_overrides (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            rule_name TEXT NOT NULL,
+            record_id INTEGER NOT NULL,
+            first_actor INTEGER REFERENCES users(id),
+            user_id INTEGER NOT NULL REFERENCES users(id),
+            reason TEXT NOT NULL,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create user_preferences table with settings that override the configuration per user
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS user_preferences (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL REFERENCES users(id),
+            name TEXT NOT NULL,
+            value TEXT NOT NULL,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE(user_id, name)
+        )",
+    )
+    .execute(connection)?;
+
+    // Create delivery_destinations table with FTP/SFTP servers exports are uploaded to
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS delivery_destinations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            protocol TEXT NOT NULL,
+            host TEXT NOT NULL,
+            port INTEGER NOT NULL,
+            username TEXT NOT NULL,
+            password_env TEXT,
+            remote_dir TEXT,
+            export TEXT NOT NULL,
+            template TEXT,
+            locale TEXT,
+            interval_minutes INTEGER NOT NULL DEFAULT 1440,
+            is_active BOOLEAN NOT NULL DEFAULT 1,
+            last_run_at DATETIME,
+            last_status TEXT,
+            consecutive_failures INTEGER NOT NULL DEFAULT 0,
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Language of product names in delivered files, added after the table shipped
+    add_column_if_missing(connection, "delivery_destinations", "locale", "TEXT")?;
+
+    // Create delivery_transfers table logging every upload attempt
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS delivery_transfers (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            destination_id INTEGER NOT NULL REFERENCES delivery_destinations(id),
+            file_name TEXT NOT NULL,
+            bytes INTEGER NOT NULL DEFAULT 0,
+            status TEXT NOT NULL,
+            message TEXT,
+            transferred_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create events table with the append-only log of record changes for subscribers
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS events (
+            sequence INTEGER PRIMARY KEY AUTOINCREMENT,
+            entity TEXT NOT NULL,
+            entity_id INTEGER NOT NULL,
+            action TEXT NOT NULL CHECK (action IN ('created', 'updated', 'deleted')),
+            payload TEXT NOT NULL,
+            user_id INTEGER REFERENCES users(id),
+            occurred_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create summary_product_daily table with stock movement totals per product and UTC day
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS summary_product_daily (
+            product_id INTEGER NOT NULL REFERENCES products(id),
+            day DATE NOT NULL,
+            quantity_in INTEGER NOT NULL DEFAULT 0,
+            quantity_out INTEGER NOT NULL DEFAULT 0,
+            cost_out INTEGER NOT NULL DEFAULT 0,
+            uncosted_out INTEGER NOT NULL DEFAULT 0,
+            movements INTEGER NOT NULL DEFAULT 0,
+            refreshed_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (product_id, day)
+        )",
+    )
+    .execute(connection)?;
+
+    // Create summary_account_monthly table with debit and credit totals per account and month
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS summary_account_monthly (
+            account_id INTEGER NOT NULL REFERENCES accounts(id),
+            month TEXT NOT NULL,
+            debits INTEGER NOT NULL DEFAULT 0,
+            credits INTEGER NOT NULL DEFAULT 0,
+            entries INTEGER NOT NULL DEFAULT 0,
+            refreshed_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (account_id, month)
+        )",
+    )
+    .execute(connection)?;
+
+    // Create email_templates table with edited versions of the built-in email templates
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS email_templates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            subject TEXT NOT NULL,
+            body TEXT NOT NULL,
+            updated_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create export_templates table with per-partner column mappings for CSV exports
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS export_templates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            entity TEXT NOT NULL,
+            mapping TEXT NOT NULL,
+            date_format TEXT,
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create portal_links table with the signed links handed to suppliers and customers
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS portal_links (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            token_id TEXT NOT NULL UNIQUE,
+            scope TEXT NOT NULL,
+            record_id INTEGER NOT NULL,
+            expires_at DATETIME NOT NULL,
+            revoked_at DATETIME,
+            last_used_at DATETIME,
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create supplier_responses table with PO confirmations, date proposals and shipping notices
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS supplier_responses (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            po_id INTEGER NOT NULL REFERENCES purchase_orders(id),
+            link_id INTEGER REFERENCES portal_links(id),
+            kind TEXT NOT NULL CHECK (kind IN ('confirmed', 'date_proposed', 'shipped')),
+            proposed_date DATE,
+            carrier TEXT,
+            tracking_number TEXT,
+            message TEXT,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create notifications table with messages for users, e.g. supplier replies to their POs
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS notifications (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL REFERENCES users(id),
+            subject TEXT NOT NULL,
+            body TEXT,
+            read_at DATETIME,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create invoices table with open receivable and payable documents
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS invoices (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            invoice_number TEXT NOT NULL,
+            kind TEXT NOT NULL CHECK (kind IN ('receivable', 'payable')),
+            customer_id INTEGER REFERENCES customers(id),
+            supplier_id INTEGER REFERENCES suppliers(id),
+            invoice_date DATE NOT NULL,
+            due_date DATE,
+            total_amount INTEGER NOT NULL,
+            paid_amount INTEGER NOT NULL DEFAULT 0,
+            status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('draft', 'open', 'paid', 'cancelled')),
+            description TEXT,
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            CHECK ((kind = 'receivable' AND customer_id IS NOT NULL) OR (kind = 'payable' AND supplier_id IS NOT NULL))
+        )",
+    )
+    .execute(connection)?;
+
+    // Purchase order a supplier invoice bills, for matching invoices to receipts
+    add_column_if_missing(connection, "invoices", "po_id", "INTEGER REFERENCES purchase_orders(id)")?;
+
+    // When a receivable invoice was sent to the customer
+    add_column_if_missing(connection, "invoices", "sent_at", "DATETIME")?;
+
+    // Create invoice_lines table with the billed items of invoices raised in finance
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS invoice_lines (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            invoice_id INTEGER NOT NULL REFERENCES invoices(id),
+            line_number INTEGER NOT NULL,
+            description TEXT NOT NULL,
+            product_id INTEGER REFERENCES products(id),
+            quantity INTEGER NOT NULL CHECK (quantity > 0),
+            unit_price INTEGER NOT NULL CHECK (unit_price >= 0),
+            amount INTEGER NOT NULL,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (invoice_id, line_number)
+        )",
+    )
+    .execute(connection)?;
+
+    // Create invoice_sequences table with the last invoice number issued each year
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS invoice_sequences (
+            year INTEGER PRIMARY KEY,
+            last_number INTEGER NOT NULL
+        )",
+    )
+    .execute(connection)?;
+
+    // Create grni_accruals table with the period-end accruals of goods received not invoiced
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS grni_accruals (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            period TEXT NOT NULL UNIQUE,
+            accrued_on DATE NOT NULL,
+            reversed_on DATE NOT NULL,
+            amount INTEGER NOT NULL,
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create grni_accrual_lines table with the uninvoiced amount per purchase order
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS grni_accrual_lines (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            accrual_id INTEGER NOT NULL REFERENCES grni_accruals(id),
+            po_id INTEGER NOT NULL REFERENCES purchase_orders(id),
+            received_amount INTEGER NOT NULL,
+            invoiced_amount INTEGER NOT NULL,
+            amount INTEGER NOT NULL
+        )",
+    )
+    .execute(connection)?;
+
+    // Create dunning_charges table with late fees and interest added to overdue invoices
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS dunning_charges (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            invoice_id INTEGER NOT NULL REFERENCES invoices(id),
+            kind TEXT NOT NULL CHECK (kind IN ('fee', 'interest')),
+            amount INTEGER NOT NULL CHECK (amount > 0),
+            days_overdue INTEGER NOT NULL,
+            accrued_from DATE,
+            accrued_to DATE NOT NULL,
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create dunning_exemptions table with customers never charged fees or interest
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS dunning_exemptions (
+            customer_id INTEGER PRIMARY KEY REFERENCES customers(id),
+            reason TEXT,
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create collection_calls table with the outcome of each collections call
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS collection_calls (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            customer_id INTEGER NOT NULL REFERENCES customers(id),
+            outcome TEXT NOT NULL CHECK (outcome IN ('no_answer', 'left_message', 'promised', 'disputed', 'refused')),
+            notes TEXT,
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create payment_promises table with the payments customers promised on a call
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS payment_promises (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            customer_id INTEGER NOT NULL REFERENCES customers(id),
+            call_id INTEGER NOT NULL REFERENCES collection_calls(id),
+            amount INTEGER NOT NULL CHECK (amount > 0),
+            promised_on DATE NOT NULL,
+            promised_date DATE NOT NULL,
+            status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'kept', 'broken')),
+            resolved_on DATE,
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create customer_surveys table with satisfaction scores collected from customers
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS customer_surveys (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            customer_id INTEGER NOT NULL REFERENCES customers(id),
+            deal_id INTEGER REFERENCES deals(id),
+            score INTEGER NOT NULL CHECK (score BETWEEN 0 AND 10),
+            comment TEXT,
+            survey_date DATE NOT NULL,
+            channel TEXT NOT NULL CHECK (channel IN ('email', 'phone', 'web', 'in_person')),
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create churn_alerts table with the at-risk customers whose owners were alerted
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS churn_alerts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            customer_id INTEGER NOT NULL REFERENCES customers(id),
+            user_id INTEGER REFERENCES users(id),
+            signals TEXT NOT NULL,
+            alerted_on DATE NOT NULL,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create dropbox_messages table with the emails received by the BCC dropbox
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS dropbox_messages (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            message_id TEXT NOT NULL UNIQUE,
+            sender TEXT NOT NULL,
+            subject TEXT NOT NULL,
+            activities INTEGER NOT NULL DEFAULT 0,
+            received_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create sales_quotas table with each sales rep's monthly revenue target
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS sales_quotas (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL REFERENCES employees(id),
+            period TEXT NOT NULL,
+            amount INTEGER NOT NULL CHECK (amount >= 0),
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE(employee_id, period)
+        )",
+    )
+    .execute(connection)?;
+
+    // Create revenue_schedules table spreading invoice revenue over the months it is earned
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS revenue_schedules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            invoice_id INTEGER NOT NULL UNIQUE REFERENCES invoices(id),
+            description TEXT,
+            total_amount INTEGER NOT NULL CHECK (total_amount > 0),
+            start_date DATE NOT NULL,
+            months INTEGER NOT NULL CHECK (months > 0),
+            deferred_account TEXT NOT NULL,
+            revenue_account TEXT NOT NULL,
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create revenue_recognitions table with the month-end postings of each schedule
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS revenue_recognitions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            schedule_id INTEGER NOT NULL REFERENCES revenue_schedules(id),
+            period TEXT NOT NULL,
+            amount INTEGER NOT NULL CHECK (amount > 0),
+            recognized_on DATE NOT NULL,
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (schedule_id, period)
+        )",
+    )
+    .execute(connection)?;
+
+    // Create invoice_payments table with the payments applied to each invoice
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS invoice_payments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            invoice_id INTEGER NOT NULL REFERENCES invoices(id),
+            amount INTEGER NOT NULL CHECK (amount > 0),
+            paid_on DATE NOT NULL,
+            reference TEXT,
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Payments the bank returned (bounced cheques, reversed transfers)
+    add_column_if_missing(connection, "invoice_payments", "returned_on", "DATE")?;
+    add_column_if_missing(connection, "invoice_payments", "return_reason", "TEXT")?;
+
+    // Create salary bands table with the pay range of each position and level
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS salary_bands (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            position TEXT NOT NULL,
+            level TEXT,
+            min_salary INTEGER NOT NULL CHECK (min_salary >= 0),
+            max_salary INTEGER NOT NULL,
+            updated_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            CHECK (max_salary >= min_salary)
+        )",
+    )
+    .execute(connection)?;
+
+    // Create salary raises table with proposed raises and their approval
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS salary_raises (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL REFERENCES employees(id),
+            current_salary INTEGER NOT NULL,
+            proposed_salary INTEGER NOT NULL CHECK (proposed_salary > 0),
+            effective_date DATE NOT NULL,
+            reason TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected', 'applied')),
+            proposed_by INTEGER REFERENCES users(id),
+            proposed_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            decided_by INTEGER REFERENCES users(id),
+            decided_at DATETIME,
+            decision_note TEXT,
+            applied_at DATETIME
+        )",
+    )
+    .execute(connection)?;
+
+    // Create compensation history table with every salary change
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS compensation_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL REFERENCES employees(id),
+            old_salary INTEGER,
+            new_salary INTEGER NOT NULL,
+            effective_date DATE NOT NULL,
+            source TEXT NOT NULL CHECK (source IN ('hire', 'update', 'raise')),
+            raise_id INTEGER REFERENCES salary_raises(id),
+            changed_by INTEGER REFERENCES users(id),
+            changed_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create employee documents table with permits, contracts and their expiry
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS employee_documents (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL REFERENCES employees(id),
+            document_type TEXT NOT NULL,
+            document_number TEXT,
+            issued_date DATE,
+            expiry_date DATE,
+            file_path TEXT,
+            notes TEXT,
+            alerted_at DATETIME,
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create required documents table with the document types each department needs
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS required_documents (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            document_type TEXT NOT NULL,
+            department_id INTEGER REFERENCES departments(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (document_type, department_id)
+        )",
+    )
+    .execute(connection)?;
+
+    // Create payee bank accounts table with where employees and suppliers are paid
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS payee_bank_accounts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            payee_type TEXT NOT NULL CHECK (payee_type IN ('employee', 'supplier')),
+            payee_id INTEGER NOT NULL,
+            account_holder TEXT,
+            account_number TEXT NOT NULL,
+            bic TEXT,
+            updated_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (payee_type, payee_id)
+        )",
+    )
+    .execute(connection)?;
+
+    // Create payment batches table with the bank files generated for payment
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS payment_batches (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            batch_reference TEXT NOT NULL UNIQUE,
+            kind TEXT NOT NULL CHECK (kind IN ('payroll', 'supplier')),
+            format TEXT NOT NULL CHECK (format IN ('csv', 'pain001')),
+            file_path TEXT NOT NULL,
+            execution_date DATE NOT NULL,
+            item_count INTEGER NOT NULL,
+            total_amount INTEGER NOT NULL,
+            status TEXT NOT NULL DEFAULT 'generated' CHECK (status IN ('generated', 'confirmed', 'cancelled')),
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            decided_by INTEGER REFERENCES users(id),
+            decided_at DATETIME
+        )",
+    )
+    .execute(connection)?;
+
+    // Create payment batch items table with one payment per payroll or supplier invoice
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS payment_batch_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            batch_id INTEGER NOT NULL REFERENCES payment_batches(id),
+            payroll_id INTEGER REFERENCES payrolls(id),
+            invoice_id INTEGER REFERENCES invoices(id),
+            payee_name TEXT NOT NULL,
+            account_number TEXT NOT NULL,
+            bic TEXT,
+            amount INTEGER NOT NULL CHECK (amount > 0),
+            remittance TEXT NOT NULL,
+            CHECK ((payroll_id IS NULL) <> (invoice_id IS NULL))
+        )",
+    )
+    .execute(connection)?;
+
+    // Create remote work policies table with the check-in rules of remote employees
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS remote_work_policies (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL UNIQUE REFERENCES employees(id),
+            require_location BOOLEAN NOT NULL DEFAULT 0,
+            require_device BOOLEAN NOT NULL DEFAULT 0,
+            registered_device TEXT,
+            site_latitude REAL,
+            site_longitude REAL,
+            radius_meters INTEGER CHECK (radius_meters > 0),
+            updated_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            CHECK ((site_latitude IS NULL) = (site_longitude IS NULL))
+        )",
+    )
+    .execute(connection)?;
+
+    // Create attendance punches table with the location and device of each check-in and check-out
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS attendance_punches (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            attendance_id INTEGER NOT NULL REFERENCES attendances(id),
+            employee_id INTEGER NOT NULL REFERENCES employees(id),
+            punch_type TEXT NOT NULL CHECK (punch_type IN ('in', 'out')),
+            latitude REAL,
+            longitude REAL,
+            device_fingerprint TEXT,
+            distance_meters REAL,
+            punched_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create leave requests table with requested days off and their approval
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS leave_requests (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL REFERENCES employees(id),
+            start_date DATE NOT NULL,
+            end_date DATE NOT NULL CHECK (end_date >= start_date),
+            days INTEGER NOT NULL CHECK (days > 0),
+            reason TEXT,
+            status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected')),
+            requested_by INTEGER REFERENCES users(id),
+            requested_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            decided_by INTEGER REFERENCES users(id),
+            decided_at DATETIME,
+            decision_note TEXT
+        )",
+    )
+    .execute(connection)?;
+
+    // Create record links table relating any record to any other
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS record_links (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            source_type TEXT NOT NULL,
+            source_id INTEGER NOT NULL,
+            target_type TEXT NOT NULL,
+            target_id INTEGER NOT NULL,
+            relation TEXT NOT NULL DEFAULT 'related',
+            note TEXT,
+            created_by INTEGER REFERENCES users(id),
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE(source_type, source_id, target_type, target_id),
+            CHECK (source_type <> target_type OR source_id <> target_id)
+        )",
+    )
+    .execute(connection)?;
+
+    // Create quarantined rows table with orphaned rows moved out by the integrity check
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS quarantined_rows (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            table_name TEXT NOT NULL,
+            record_id INTEGER NOT NULL,
+            reason TEXT NOT NULL,
+            row_data TEXT NOT NULL,
+            quarantined_by INTEGER REFERENCES users(id),
+            quarantined_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(connection)?;
+
+    // Create product bundles table with sellable kits priced from their components
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS product_bundles (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            sku TEXT NOT NULL UNIQUE,
+            name TEXT NOT NULL,
+            description TEXT,
+            pricing TEXT NOT NULL DEFAULT 'sum' CHECK (pricing IN ('fixed', 'sum')),
+            fixed_price INTEGER,
+            discount_percent INTEGER NOT NULL DEFAULT 0 CHECK (discount_percent BETWEEN 0 AND 100),
+            is_active BOOLEAN NOT NULL DEFAULT 1,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            CHECK (pricing <> 'fixed' OR fixed_price IS NOT NULL)
+        )",
+    )
+    .execute(connection)?;
+
+    // Create product bundle items table with the components of each bundle
+    diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS product_bundle_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            bundle_id INTEGER NOT NULL REFERENCES product_bundles(id) ON DELETE CASCADE,
+            product_id INTEGER NOT NULL REFERENCES products(id),
+            quantity INTEGER NOT NULL CHECK (quantity > 0),
+            UNIQUE(bundle_id, product_id)
+        )",
+    )
+    .execute(connection)?;
+
     // Create indexes for better performance
     diesel::sql_query(
         "CREATE INDEX IF NOT EXISTS idx_employees_department_id ON employees(department_id)",
@@ -131,6 +1178,7 @@ pub fn run_migrations(connection: &mut SqliteConnection) -> CLIERPResult<()> {
     diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_users_email ON users(email)")
         .execute(connection)?;
     diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_audit_logs_table_record ON audit_logs(table_name, record_id)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_audit_logs_changed_at ON audit_logs(changed_at)").execute(connection)?;
 
     // Create indexes for inventory tables
     diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_categories_parent_id ON categories(parent_id)").execute(connection)?;
@@ -139,14 +1187,87 @@ pub fn run_migrations(connection: &mut SqliteConnection) -> CLIERPResult<()> {
     diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_products_stock_level ON products(current_stock, min_stock_level)").execute(connection)?;
     diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_stock_movements_product_id ON stock_movements(product_id)").execute(connection)?;
     diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_stock_movements_date ON stock_movements(movement_date)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_stock_movements_product_date ON stock_movements(product_id, movement_date)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_stock_movements_reference ON stock_movements(reference_type, reference_id)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_stock_reservations_product_status ON stock_reservations(product_id, status)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_stock_reservations_reference ON stock_reservations(reference_type, reference_id)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_validation_rules_entity ON validation_rules(entity, is_active)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_stock_movements_archive_product ON stock_movements_archive(product_id, movement_date)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_audit_logs_archive_table_record ON audit_logs_archive(table_name, record_id)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_report_snapshots_report ON report_snapshots(report_id, title, created_at)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_anomaly_flags_status ON anomaly_flags(status, flagged_at)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_stock_adjustment_requests_status ON stock_adjustment_requests(status, requested_at)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_stock_write_downs_status ON stock_write_downs(status, as_of)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_stock_write_down_lines_product ON stock_write_down_lines(product_id)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_customer_surveys_customer ON customer_surveys(customer_id, survey_date)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_customer_surveys_date ON customer_surveys(survey_date)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_churn_alerts_customer ON churn_alerts(customer_id, alerted_on)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_dropbox_messages_received ON dropbox_messages(received_at)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_events_entity ON events(entity, sequence)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_storage_snapshots_taken ON storage_snapshots(taken_at)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_command_usage_used ON command_usage(used_at)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_sales_orders_deal ON sales_orders(deal_id)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_sales_orders_status ON sales_orders(status, order_date)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_sales_order_items_order ON sales_order_items(order_id)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_legal_holds_record ON legal_holds(entity, record_id)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_retention_runs_run ON retention_runs(run_at)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_sod_overrides_created ON sod_overrides(created_at)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_invoices_kind_status_due ON invoices(kind, status, due_date)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_invoices_customer ON invoices(customer_id, invoice_date)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_invoices_po ON invoices(po_id)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_invoice_payments_invoice ON invoice_payments(invoice_id, paid_on)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_collection_calls_customer ON collection_calls(customer_id, created_at)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_payment_promises_customer ON payment_promises(customer_id, status)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_dunning_charges_invoice ON dunning_charges(invoice_id, kind, accrued_to)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_salary_bands_position ON salary_bands(position, level)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_salary_raises_status ON salary_raises(status, effective_date)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_compensation_history_employee ON compensation_history(employee_id, effective_date)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_employee_documents_employee ON employee_documents(employee_id, document_type)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_employee_documents_expiry ON employee_documents(expiry_date)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_payment_batch_items_batch ON payment_batch_items(batch_id)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_attendance_punches_attendance ON attendance_punches(attendance_id)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_leave_requests_employee ON leave_requests(employee_id, start_date)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_record_links_target ON record_links(target_type, target_id)").execute(connection)?;
+    diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_product_bundle_items_product ON product_bundle_items(product_id)").execute(connection)?;
 
     // Insert default data
     insert_default_data(connection)?;
 
+    // Summarize data recorded before the summary tables existed. The ledger
+    // comes from the SQL migrations, so a fresh database has nothing to
+    // summarize until 'system migrate' creates it and rebuilds the summaries.
+    if table_exists(connection, "transactions")? {
+        crate::modules::reporting::SummaryService::backfill(connection)?;
+    }
+
     tracing::info!("Database migrations completed successfully");
     Ok(())
 }
 
+#[derive(QueryableByName)]
+struct ColumnInfo {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    name: String,
+}
+
+/// SQLite has no `ADD COLUMN IF NOT EXISTS`, so check `table_info` first
+fn add_column_if_missing(
+    connection: &mut SqliteConnection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> CLIERPResult<()> {
+    let columns = diesel::sql_query(format!("PRAGMA table_info({})", table))
+        .load::<ColumnInfo>(connection)?;
+
+    if !columns.iter().any(|c| c.name == column) {
+        diesel::sql_query(format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+            .execute(connection)?;
+    }
+
+    Ok(())
+}
+
 fn insert_default_data(connection: &mut SqliteConnection) -> CLIERPResult<()> {
     tracing::info!("Inserting default data...");
 
@@ -175,3 +1296,167 @@ fn insert_default_data(connection: &mut SqliteConnection) -> CLIERPResult<()> {
     tracing::info!("Default data inserted successfully");
     Ok(())
 }
+
+/// SQL migrations from `migrations/`, tracked in `__diesel_schema_migrations`
+pub const SQL_MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+/// Table diesel records applied SQL migrations in
+const SQL_MIGRATIONS_TABLE: &str = "__diesel_schema_migrations";
+
+/// An SQL migration and whether it has been applied to the database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationState {
+    pub version: String,
+    pub name: String,
+    pub applied: bool,
+}
+
+#[derive(QueryableByName)]
+struct VersionRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    version: String,
+}
+
+fn migration_error(error: impl std::fmt::Display) -> CLIERPError {
+    CLIERPError::Migration(error.to_string())
+}
+
+fn table_exists(connection: &mut SqliteConnection, table: &str) -> CLIERPResult<bool> {
+    let found = diesel::sql_query(format!(
+        "SELECT name AS version FROM sqlite_master WHERE type = 'table' AND name = '{}'",
+        table
+    ))
+    .load::<VersionRow>(connection)?;
+    Ok(!found.is_empty())
+}
+
+/// Versions recorded as applied, without creating the tracking table
+fn applied_versions(connection: &mut SqliteConnection) -> CLIERPResult<Vec<String>> {
+    if !table_exists(connection, SQL_MIGRATIONS_TABLE)? {
+        return Ok(Vec::new());
+    }
+
+    Ok(diesel::sql_query(format!("SELECT version FROM {} ORDER BY version", SQL_MIGRATIONS_TABLE))
+        .load::<VersionRow>(connection)?
+        .into_iter()
+        .map(|row| row.version)
+        .collect())
+}
+
+/// Every SQL migration this binary knows, oldest first, with its state
+pub fn migration_status(connection: &mut SqliteConnection) -> CLIERPResult<Vec<MigrationState>> {
+    let applied = applied_versions(connection)?;
+    let mut migrations: Vec<MigrationState> = MigrationSource::<Sqlite>::migrations(&SQL_MIGRATIONS)
+        .map_err(migration_error)?
+        .iter()
+        .map(|migration| {
+            let version = migration.name().version().to_string();
+            MigrationState {
+                applied: applied.contains(&version),
+                name: migration.name().to_string(),
+                version,
+            }
+        })
+        .collect();
+    migrations.sort_by(|a, b| a.version.cmp(&b.version));
+    Ok(migrations)
+}
+
+/// Apply pending SQL migrations, returning the versions applied
+pub fn run_sql_migrations(connection: &mut SqliteConnection) -> CLIERPResult<Vec<String>> {
+    let applied = connection
+        .run_pending_migrations(SQL_MIGRATIONS)
+        .map_err(migration_error)?
+        .iter()
+        .map(|version| version.to_string())
+        .collect::<Vec<_>>();
+    for version in &applied {
+        tracing::info!("Applied SQL migration {}", version);
+    }
+    Ok(applied)
+}
+
+/// Revert the last `steps` applied SQL migrations with their `down.sql`,
+/// newest first, returning the versions reverted
+pub fn rollback_sql_migrations(connection: &mut SqliteConnection, steps: usize) -> CLIERPResult<Vec<String>> {
+    let applied = applied_versions(connection)?;
+    if steps > applied.len() {
+        return Err(CLIERPError::ValidationError(format!(
+            "Only {} SQL migration(s) are applied, cannot roll back {}",
+            applied.len(),
+            steps
+        )));
+    }
+
+    let mut reverted = Vec::with_capacity(steps);
+    for _ in 0..steps {
+        let version = connection
+            .revert_last_migration(SQL_MIGRATIONS)
+            .map_err(migration_error)?
+            .to_string();
+        tracing::warn!("Reverted SQL migration {}", version);
+        reverted.push(version);
+    }
+    Ok(reverted)
+}
+
+/// Refuse a database that has SQL migrations this binary does not know,
+/// meaning it was migrated by a newer release
+pub fn check_schema_version(connection: &mut SqliteConnection) -> CLIERPResult<()> {
+    let known: Vec<String> = MigrationSource::<Sqlite>::migrations(&SQL_MIGRATIONS)
+        .map_err(migration_error)?
+        .iter()
+        .map(|migration| migration.name().version().to_string())
+        .collect();
+    let unknown = newer_versions(&applied_versions(connection)?, &known);
+    if unknown.is_empty() {
+        return Ok(());
+    }
+
+    Err(CLIERPError::Migration(format!(
+        "the database schema is newer than this binary (unknown migration(s): {}); upgrade clierp or restore a matching backup",
+        unknown.join(", ")
+    )))
+}
+
+/// Applied versions missing from the known migrations
+fn newer_versions(applied: &[String], known: &[String]) -> Vec<String> {
+    applied.iter().filter(|version| !known.contains(version)).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newer_versions() {
+        let known = vec!["20240921000005".to_string(), "20250110000000".to_string()];
+        assert!(newer_versions(&known[..1], &known).is_empty());
+        assert_eq!(
+            newer_versions(&["20240921000005".to_string(), "20260101000000".to_string()], &known),
+            vec!["20260101000000".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_embedded_migrations_are_ordered_and_unique() {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        let status = migration_status(&mut connection).unwrap();
+        assert!(!status.is_empty());
+        assert!(status.iter().all(|m| !m.applied));
+        assert!(status.windows(2).all(|pair| pair[0].version < pair[1].version));
+    }
+
+    #[test]
+    fn test_built_in_migrations_on_fresh_database() {
+        // As on first start, before 'system migrate' has created the ledger
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        check_schema_version(&mut connection).unwrap();
+        run_migrations(&mut connection).unwrap();
+        assert!(!table_exists(&mut connection, "transactions").unwrap());
+
+        run_sql_migrations(&mut connection).unwrap();
+        run_migrations(&mut connection).unwrap();
+        assert!(table_exists(&mut connection, "transactions").unwrap());
+    }
+}
@@ -1,11 +1,14 @@
 pub mod connection;
+pub mod encryption;
 pub mod migrations;
 pub mod models;
 pub mod purchase_models;
+pub mod sales_models;
 pub mod crm_models;
 pub mod schema;
 
 pub use connection::*;
 pub use models::*;
 pub use purchase_models::*;
+pub use sales_models::*;
 pub use crm_models::*;
@@ -1,10 +1,12 @@
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use clap::ValueEnum;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use super::schema::{
-    accounts, attendances, audit_logs, categories, departments, employees, payrolls, products,
-    product_attachments, stock_movements, stock_audits, stock_audit_items, transactions, users,
+    accounts, anomaly_flags, archive_runs, attendance_punches, attendances, audit_logs, categories, category_translations, collection_calls, command_usage, compensation_history, delivery_destinations, delivery_transfers, departments, dunning_charges, dunning_exemptions, email_templates, employee_documents, employees, events, export_templates, grni_accrual_lines, grni_accruals, import_checkpoints, invoice_lines, invoice_payments, invoices, journal_entries, kpis, leave_requests, legal_holds, notifications, payee_bank_accounts, payment_batch_items, payment_batches, payment_promises, payrolls, portal_links, products, record_links, remote_work_policies,
+    product_attachments, product_bundle_items, product_bundles, product_translations, report_snapshots, required_documents, retention_runs, revenue_recognitions, revenue_schedules, salary_bands, salary_raises, sales_quotas, sod_overrides, sod_rules, stock_adjustment_requests, stock_movements, stock_reservations, stock_audits, stock_audit_items, stock_write_down_lines, stock_write_downs, storage_snapshots, transactions, user_preferences, users,
+    validation_rules,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
@@ -41,6 +43,9 @@ pub struct Employee {
     pub status: String,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub termination_date: Option<NaiveDate>,
+    /// Job level within the position, matched with salary bands
+    pub level: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable)]
@@ -213,6 +218,118 @@ impl std::fmt::Display for AttendanceStatus {
     }
 }
 
+/// Check-in rules for an employee who works remotely
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = remote_work_policies)]
+pub struct RemoteWorkPolicy {
+    pub id: i32,
+    pub employee_id: i32,
+    /// Punches must carry GPS coordinates
+    pub require_location: bool,
+    /// Punches must carry a device fingerprint
+    pub require_device: bool,
+    /// When set, punches must come from this device
+    pub registered_device: Option<String>,
+    /// Centre of the area punches must be made in, with `radius_meters`
+    pub site_latitude: Option<f64>,
+    pub site_longitude: Option<f64>,
+    pub radius_meters: Option<i32>,
+    pub updated_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = remote_work_policies)]
+pub struct NewRemoteWorkPolicy {
+    pub employee_id: i32,
+    pub require_location: bool,
+    pub require_device: bool,
+    pub registered_device: Option<String>,
+    pub site_latitude: Option<f64>,
+    pub site_longitude: Option<f64>,
+    pub radius_meters: Option<i32>,
+    pub updated_by: Option<i32>,
+}
+
+/// Location and device recorded with a check-in or check-out
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = attendance_punches)]
+pub struct AttendancePunch {
+    pub id: i32,
+    pub attendance_id: i32,
+    pub employee_id: i32,
+    /// "in" or "out"
+    pub punch_type: String,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub device_fingerprint: Option<String>,
+    /// Distance from the policy site, when both are known
+    pub distance_meters: Option<f64>,
+    pub punched_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = attendance_punches)]
+pub struct NewAttendancePunch {
+    pub attendance_id: i32,
+    pub employee_id: i32,
+    pub punch_type: String,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub device_fingerprint: Option<String>,
+    pub distance_meters: Option<f64>,
+}
+
+/// Days off requested by an employee; approved leave is recorded as
+/// `holiday` attendance
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = leave_requests)]
+pub struct LeaveRequest {
+    pub id: i32,
+    pub employee_id: i32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    /// Working days between the start and end date
+    pub days: i32,
+    pub reason: Option<String>,
+    pub status: String,
+    pub requested_by: Option<i32>,
+    pub requested_at: NaiveDateTime,
+    pub decided_by: Option<i32>,
+    pub decided_at: Option<NaiveDateTime>,
+    pub decision_note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = leave_requests)]
+pub struct NewLeaveRequest {
+    pub employee_id: i32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub days: i32,
+    pub reason: Option<String>,
+    pub requested_by: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum LeaveStatus {
+    /// Waiting for a manager
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl std::fmt::Display for LeaveStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LeaveStatus::Pending => write!(f, "pending"),
+            LeaveStatus::Approved => write!(f, "approved"),
+            LeaveStatus::Rejected => write!(f, "rejected"),
+        }
+    }
+}
+
 // Payroll models
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
 #[diesel(table_name = payrolls)]
@@ -229,6 +346,10 @@ pub struct Payroll {
     pub status: String,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    /// User who generated the payroll
+    pub prepared_by: Option<i32>,
+    /// User who released the payment
+    pub paid_by: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable)]
@@ -243,6 +364,7 @@ pub struct NewPayroll {
     pub net_salary: i32,
     pub payment_date: Option<NaiveDate>,
     pub status: String,
+    pub prepared_by: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -262,6 +384,269 @@ impl std::fmt::Display for PayrollStatus {
     }
 }
 
+// Compensation models
+/// Pay range of a position, optionally for one level of it
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = salary_bands)]
+pub struct SalaryBand {
+    pub id: i32,
+    pub position: String,
+    /// `None` for the band that covers every level of the position
+    pub level: Option<String>,
+    pub min_salary: i32,
+    pub max_salary: i32,
+    pub updated_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl SalaryBand {
+    pub fn contains(&self, salary: i32) -> bool {
+        salary >= self.min_salary && salary <= self.max_salary
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = salary_bands)]
+pub struct NewSalaryBand {
+    pub position: String,
+    pub level: Option<String>,
+    pub min_salary: i32,
+    pub max_salary: i32,
+    pub updated_by: Option<i32>,
+}
+
+/// Proposed salary change; the salary is updated once it is approved and
+/// its effective date has arrived
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = salary_raises)]
+pub struct SalaryRaise {
+    pub id: i32,
+    pub employee_id: i32,
+    /// Salary when the raise was proposed
+    pub current_salary: i32,
+    pub proposed_salary: i32,
+    pub effective_date: NaiveDate,
+    pub reason: String,
+    pub status: String,
+    pub proposed_by: Option<i32>,
+    pub proposed_at: NaiveDateTime,
+    pub decided_by: Option<i32>,
+    pub decided_at: Option<NaiveDateTime>,
+    pub decision_note: Option<String>,
+    pub applied_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = salary_raises)]
+pub struct NewSalaryRaise {
+    pub employee_id: i32,
+    pub current_salary: i32,
+    pub proposed_salary: i32,
+    pub effective_date: NaiveDate,
+    pub reason: String,
+    pub proposed_by: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum SalaryRaiseStatus {
+    /// Waiting for a manager
+    Pending,
+    /// Approved, waiting for its effective date
+    Approved,
+    Rejected,
+    /// The employee salary has been updated
+    Applied,
+}
+
+impl std::fmt::Display for SalaryRaiseStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SalaryRaiseStatus::Pending => write!(f, "pending"),
+            SalaryRaiseStatus::Approved => write!(f, "approved"),
+            SalaryRaiseStatus::Rejected => write!(f, "rejected"),
+            SalaryRaiseStatus::Applied => write!(f, "applied"),
+        }
+    }
+}
+
+/// One change of an employee salary
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = compensation_history)]
+pub struct CompensationChange {
+    pub id: i32,
+    pub employee_id: i32,
+    /// `None` for the starting salary
+    pub old_salary: Option<i32>,
+    pub new_salary: i32,
+    pub effective_date: NaiveDate,
+    /// `hire`, `update` or `raise`
+    pub source: String,
+    pub raise_id: Option<i32>,
+    pub changed_by: Option<i32>,
+    pub changed_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = compensation_history)]
+pub struct NewCompensationChange {
+    pub employee_id: i32,
+    pub old_salary: Option<i32>,
+    pub new_salary: i32,
+    pub effective_date: NaiveDate,
+    pub source: String,
+    pub raise_id: Option<i32>,
+    pub changed_by: Option<i32>,
+}
+
+// Employee document models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = employee_documents)]
+pub struct EmployeeDocument {
+    pub id: i32,
+    pub employee_id: i32,
+    pub document_type: String,
+    pub document_number: Option<String>,
+    pub issued_date: Option<NaiveDate>,
+    /// `None` for documents that do not expire
+    pub expiry_date: Option<NaiveDate>,
+    /// Copy of the scanned document in the document storage
+    pub file_path: Option<String>,
+    pub notes: Option<String>,
+    /// When HR was last alerted about the expiry; cleared when the expiry changes
+    pub alerted_at: Option<NaiveDateTime>,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl EmployeeDocument {
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        self.expiry_date.is_some_and(|expiry| expiry < today)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = employee_documents)]
+pub struct NewEmployeeDocument {
+    pub employee_id: i32,
+    pub document_type: String,
+    pub document_number: Option<String>,
+    pub issued_date: Option<NaiveDate>,
+    pub expiry_date: Option<NaiveDate>,
+    pub file_path: Option<String>,
+    pub notes: Option<String>,
+    pub created_by: Option<i32>,
+}
+
+/// Document type that every employee of a department, or of the whole
+/// company when `department_id` is `None`, must have on file
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = required_documents)]
+pub struct RequiredDocument {
+    pub id: i32,
+    pub document_type: String,
+    pub department_id: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = required_documents)]
+pub struct NewRequiredDocument {
+    pub document_type: String,
+    pub department_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum DocumentType {
+    #[value(name = "work_permit")]
+    WorkPermit,
+    Visa,
+    Passport,
+    /// National identity card
+    #[value(name = "id_card")]
+    IdCard,
+    Contract,
+    Certification,
+    Other,
+}
+
+impl std::fmt::Display for DocumentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DocumentType::WorkPermit => write!(f, "work_permit"),
+            DocumentType::Visa => write!(f, "visa"),
+            DocumentType::Passport => write!(f, "passport"),
+            DocumentType::IdCard => write!(f, "id_card"),
+            DocumentType::Contract => write!(f, "contract"),
+            DocumentType::Certification => write!(f, "certification"),
+            DocumentType::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// Explicit relation between two records of any module, e.g. a deal and
+/// the purchase order raised for it
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = record_links)]
+pub struct RecordLink {
+    pub id: i32,
+    pub source_type: String,
+    pub source_id: i32,
+    pub target_type: String,
+    pub target_id: i32,
+    /// How the source relates to the target, `related` unless given
+    pub relation: String,
+    pub note: Option<String>,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = record_links)]
+pub struct NewRecordLink {
+    pub source_type: String,
+    pub source_id: i32,
+    pub target_type: String,
+    pub target_id: i32,
+    pub relation: String,
+    pub note: Option<String>,
+    pub created_by: Option<i32>,
+}
+
+/// Kinds of records that can be linked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ValueEnum)]
+pub enum RecordType {
+    Employee,
+    Customer,
+    Lead,
+    Deal,
+    Campaign,
+    Product,
+    Supplier,
+    #[value(name = "purchase_order", alias = "po")]
+    PurchaseOrder,
+    Invoice,
+    Transaction,
+}
+
+impl std::fmt::Display for RecordType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordType::Employee => write!(f, "employee"),
+            RecordType::Customer => write!(f, "customer"),
+            RecordType::Lead => write!(f, "lead"),
+            RecordType::Deal => write!(f, "deal"),
+            RecordType::Campaign => write!(f, "campaign"),
+            RecordType::Product => write!(f, "product"),
+            RecordType::Supplier => write!(f, "supplier"),
+            RecordType::PurchaseOrder => write!(f, "purchase_order"),
+            RecordType::Invoice => write!(f, "invoice"),
+            RecordType::Transaction => write!(f, "transaction"),
+        }
+    }
+}
+
 // Account models for finance
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
 #[diesel(table_name = accounts)]
@@ -323,6 +708,7 @@ pub struct Transaction {
     pub created_by: Option<i32>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub journal_entry_id: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable)]
@@ -335,6 +721,32 @@ pub struct NewTransaction {
     pub description: String,
     pub reference: Option<String>,
     pub created_by: Option<i32>,
+    pub journal_entry_id: Option<i32>,
+}
+
+// Journal entry models: the balanced group of transactions of one posting
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = journal_entries)]
+pub struct JournalEntry {
+    pub id: i32,
+    pub entry_number: String,
+    pub entry_date: NaiveDate,
+    pub description: String,
+    pub reference: Option<String>,
+    pub reverses_entry_id: Option<i32>,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = journal_entries)]
+pub struct NewJournalEntry {
+    pub entry_number: String,
+    pub entry_date: NaiveDate,
+    pub description: String,
+    pub reference: Option<String>,
+    pub reverses_entry_id: Option<i32>,
+    pub created_by: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -352,93 +764,1287 @@ impl std::fmt::Display for TransactionType {
     }
 }
 
-// Category models for inventory
+/// Receivable or payable document; `paid_amount` of `total_amount` is settled
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = invoices)]
+pub struct Invoice {
+    pub id: i32,
+    pub invoice_number: String,
+    /// "receivable" (customer_id set) or "payable" (supplier_id set)
+    pub kind: String,
+    pub customer_id: Option<i32>,
+    pub supplier_id: Option<i32>,
+    pub invoice_date: NaiveDate,
+    pub due_date: Option<NaiveDate>,
+    pub total_amount: i32,
+    pub paid_amount: i32,
+    pub status: String,
+    pub description: Option<String>,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    /// Purchase order a supplier invoice bills
+    pub po_id: Option<i32>,
+    /// When a receivable invoice was sent to the customer
+    pub sent_at: Option<NaiveDateTime>,
+}
+
+impl Invoice {
+    pub fn open_amount(&self) -> i32 {
+        self.total_amount - self.paid_amount
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = invoices)]
+pub struct NewInvoice {
+    pub invoice_number: String,
+    pub kind: String,
+    pub customer_id: Option<i32>,
+    pub supplier_id: Option<i32>,
+    pub invoice_date: NaiveDate,
+    pub due_date: Option<NaiveDate>,
+    pub total_amount: i32,
+    pub paid_amount: i32,
+    pub status: String,
+    pub description: Option<String>,
+    pub created_by: Option<i32>,
+    pub po_id: Option<i32>,
+}
+
+/// Billed item of an invoice; `amount` is `quantity` at `unit_price`
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = invoice_lines)]
+pub struct InvoiceLine {
+    pub id: i32,
+    pub invoice_id: i32,
+    pub line_number: i32,
+    pub description: String,
+    pub product_id: Option<i32>,
+    pub quantity: i32,
+    pub unit_price: i32,
+    pub amount: i32,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = invoice_lines)]
+pub struct NewInvoiceLine {
+    pub invoice_id: i32,
+    pub line_number: i32,
+    pub description: String,
+    pub product_id: Option<i32>,
+    pub quantity: i32,
+    pub unit_price: i32,
+    pub amount: i32,
+}
+
+/// Payment received on (or, for a payable, made against) an invoice
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = invoice_payments)]
+pub struct InvoicePayment {
+    pub id: i32,
+    pub invoice_id: i32,
+    pub amount: i32,
+    pub paid_on: NaiveDate,
+    /// Bank reference or receipt number
+    pub reference: Option<String>,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    /// Set when the bank returned the payment; it no longer counts as paid
+    pub returned_on: Option<NaiveDate>,
+    pub return_reason: Option<String>,
+}
+
+impl InvoicePayment {
+    pub fn is_returned(&self) -> bool {
+        self.returned_on.is_some()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = invoice_payments)]
+pub struct NewInvoicePayment {
+    pub invoice_id: i32,
+    pub amount: i32,
+    pub paid_on: NaiveDate,
+    pub reference: Option<String>,
+    pub created_by: Option<i32>,
+}
+
+/// Late fee or interest added to an overdue receivable invoice
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = dunning_charges)]
+pub struct DunningCharge {
+    pub id: i32,
+    pub invoice_id: i32,
+    /// `fee` or `interest`
+    pub kind: String,
+    pub amount: i32,
+    /// Days past the due date when the charge was made
+    pub days_overdue: i32,
+    /// First day of the interest period (none for a fee)
+    pub accrued_from: Option<NaiveDate>,
+    /// Last day covered by the charge
+    pub accrued_to: NaiveDate,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = dunning_charges)]
+pub struct NewDunningCharge {
+    pub invoice_id: i32,
+    pub kind: String,
+    pub amount: i32,
+    pub days_overdue: i32,
+    pub accrued_from: Option<NaiveDate>,
+    pub accrued_to: NaiveDate,
+    pub created_by: Option<i32>,
+}
+
+/// Customer whose overdue invoices are never charged fees or interest
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = dunning_exemptions)]
+pub struct DunningExemption {
+    pub customer_id: i32,
+    pub reason: Option<String>,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = dunning_exemptions)]
+pub struct NewDunningExemption {
+    pub customer_id: i32,
+    pub reason: Option<String>,
+    pub created_by: Option<i32>,
+}
+
+// Collections models
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum CallOutcome {
+    NoAnswer,
+    LeftMessage,
+    /// The customer promised a payment by a date
+    Promised,
+    /// The customer disputes the amount owed
+    Disputed,
+    Refused,
+}
+
+impl std::fmt::Display for CallOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CallOutcome::NoAnswer => write!(f, "no_answer"),
+            CallOutcome::LeftMessage => write!(f, "left_message"),
+            CallOutcome::Promised => write!(f, "promised"),
+            CallOutcome::Disputed => write!(f, "disputed"),
+            CallOutcome::Refused => write!(f, "refused"),
+        }
+    }
+}
+
+/// Collections call made to a customer with overdue invoices
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = collection_calls)]
+pub struct CollectionCall {
+    pub id: i32,
+    pub customer_id: i32,
+    pub outcome: String,
+    pub notes: Option<String>,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = collection_calls)]
+pub struct NewCollectionCall {
+    pub customer_id: i32,
+    pub outcome: String,
+    pub notes: Option<String>,
+    pub created_by: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum PromiseStatus {
+    /// Not yet due
+    Open,
+    /// Paid in full by the promised date
+    Kept,
+    /// Not paid in full by the promised date
+    Broken,
+}
+
+impl std::fmt::Display for PromiseStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PromiseStatus::Open => write!(f, "open"),
+            PromiseStatus::Kept => write!(f, "kept"),
+            PromiseStatus::Broken => write!(f, "broken"),
+        }
+    }
+}
+
+/// Payment a customer promised on a collections call
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = payment_promises)]
+pub struct PaymentPromise {
+    pub id: i32,
+    pub customer_id: i32,
+    pub call_id: i32,
+    pub amount: i32,
+    /// Day the promise was made; payments from then on count towards it
+    pub promised_on: NaiveDate,
+    /// Day the customer promised to have paid by
+    pub promised_date: NaiveDate,
+    pub status: String,
+    pub resolved_on: Option<NaiveDate>,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = payment_promises)]
+pub struct NewPaymentPromise {
+    pub customer_id: i32,
+    pub call_id: i32,
+    pub amount: i32,
+    pub promised_on: NaiveDate,
+    pub promised_date: NaiveDate,
+    pub created_by: Option<i32>,
+}
+
+/// Revenue a sales rep is expected to close in a month
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = sales_quotas)]
+pub struct SalesQuota {
+    pub id: i32,
+    pub employee_id: i32,
+    /// Month as `YYYY-MM`
+    pub period: String,
+    pub amount: i32,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = sales_quotas)]
+pub struct NewSalesQuota {
+    pub employee_id: i32,
+    pub period: String,
+    pub amount: i32,
+    pub created_by: Option<i32>,
+}
+
+/// Period-end accrual of goods received not invoiced, reversed the next day
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = grni_accruals)]
+pub struct GrniAccrual {
+    pub id: i32,
+    pub period: String,
+    /// Last day of the period, the accrual posting date
+    pub accrued_on: NaiveDate,
+    /// First day of the next period, the reversal posting date
+    pub reversed_on: NaiveDate,
+    pub amount: i32,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = grni_accruals)]
+pub struct NewGrniAccrual {
+    pub period: String,
+    pub accrued_on: NaiveDate,
+    pub reversed_on: NaiveDate,
+    pub amount: i32,
+    pub created_by: Option<i32>,
+}
+
+/// Uninvoiced receipts of one purchase order in an accrual
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = grni_accrual_lines)]
+pub struct GrniAccrualLine {
+    pub id: i32,
+    pub accrual_id: i32,
+    pub po_id: i32,
+    pub received_amount: i32,
+    pub invoiced_amount: i32,
+    pub amount: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = grni_accrual_lines)]
+pub struct NewGrniAccrualLine {
+    pub accrual_id: i32,
+    pub po_id: i32,
+    pub received_amount: i32,
+    pub invoiced_amount: i32,
+    pub amount: i32,
+}
+
+/// Invoice revenue deferred and recognized in equal monthly parts
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = revenue_schedules)]
+pub struct RevenueSchedule {
+    pub id: i32,
+    pub invoice_id: i32,
+    pub description: Option<String>,
+    pub total_amount: i32,
+    /// Any day of the first month revenue is recognized in
+    pub start_date: NaiveDate,
+    pub months: i32,
+    pub deferred_account: String,
+    pub revenue_account: String,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = revenue_schedules)]
+pub struct NewRevenueSchedule {
+    pub invoice_id: i32,
+    pub description: Option<String>,
+    pub total_amount: i32,
+    pub start_date: NaiveDate,
+    pub months: i32,
+    pub deferred_account: String,
+    pub revenue_account: String,
+    pub created_by: Option<i32>,
+}
+
+/// Revenue of one month of a schedule, posted at the end of the month
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = revenue_recognitions)]
+pub struct RevenueRecognition {
+    pub id: i32,
+    pub schedule_id: i32,
+    /// `YYYY-MM`
+    pub period: String,
+    pub amount: i32,
+    pub recognized_on: NaiveDate,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = revenue_recognitions)]
+pub struct NewRevenueRecognition {
+    pub schedule_id: i32,
+    pub period: String,
+    pub amount: i32,
+    pub recognized_on: NaiveDate,
+    pub created_by: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InvoiceKind {
+    /// Owed to us by a customer
+    Receivable,
+    /// Owed by us to a supplier
+    Payable,
+}
+
+impl std::fmt::Display for InvoiceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvoiceKind::Receivable => write!(f, "receivable"),
+            InvoiceKind::Payable => write!(f, "payable"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InvoiceStatus {
+    Draft,
+    Open,
+    Paid,
+    Cancelled,
+}
+
+impl std::fmt::Display for InvoiceStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvoiceStatus::Draft => write!(f, "draft"),
+            InvoiceStatus::Open => write!(f, "open"),
+            InvoiceStatus::Paid => write!(f, "paid"),
+            InvoiceStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+// Payment models
+/// Bank account an employee or supplier is paid to
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = payee_bank_accounts)]
+pub struct PayeeBankAccount {
+    pub id: i32,
+    /// "employee" or "supplier"
+    pub payee_type: String,
+    pub payee_id: i32,
+    /// Name on the account when it differs from the payee's name
+    pub account_holder: Option<String>,
+    /// IBAN or domestic account number
+    pub account_number: String,
+    pub bic: Option<String>,
+    pub updated_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = payee_bank_accounts)]
+pub struct NewPayeeBankAccount {
+    pub payee_type: String,
+    pub payee_id: i32,
+    pub account_holder: Option<String>,
+    pub account_number: String,
+    pub bic: Option<String>,
+    pub updated_by: Option<i32>,
+}
+
+/// Bank file generated for a set of payments; the payments are marked paid
+/// once the batch is confirmed
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = payment_batches)]
+pub struct PaymentBatch {
+    pub id: i32,
+    pub batch_reference: String,
+    pub kind: String,
+    pub format: String,
+    pub file_path: String,
+    pub execution_date: NaiveDate,
+    pub item_count: i32,
+    pub total_amount: i32,
+    pub status: String,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    /// User who confirmed or cancelled the batch
+    pub decided_by: Option<i32>,
+    pub decided_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = payment_batches)]
+pub struct NewPaymentBatch {
+    pub batch_reference: String,
+    pub kind: String,
+    pub format: String,
+    pub file_path: String,
+    pub execution_date: NaiveDate,
+    pub item_count: i32,
+    pub total_amount: i32,
+    pub created_by: Option<i32>,
+}
+
+/// One payment of a batch, for either a payroll or a supplier invoice
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = payment_batch_items)]
+pub struct PaymentBatchItem {
+    pub id: i32,
+    pub batch_id: i32,
+    pub payroll_id: Option<i32>,
+    pub invoice_id: Option<i32>,
+    pub payee_name: String,
+    pub account_number: String,
+    pub bic: Option<String>,
+    pub amount: i32,
+    /// Text shown on the payee's bank statement
+    pub remittance: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = payment_batch_items)]
+pub struct NewPaymentBatchItem {
+    pub batch_id: i32,
+    pub payroll_id: Option<i32>,
+    pub invoice_id: Option<i32>,
+    pub payee_name: String,
+    pub account_number: String,
+    pub bic: Option<String>,
+    pub amount: i32,
+    pub remittance: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum PaymentBatchKind {
+    /// Net salaries of a payroll period
+    Payroll,
+    /// Open supplier invoices that are due
+    Supplier,
+}
+
+impl std::fmt::Display for PaymentBatchKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaymentBatchKind::Payroll => write!(f, "payroll"),
+            PaymentBatchKind::Supplier => write!(f, "supplier"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum PaymentFileFormat {
+    /// One payment per line
+    Csv,
+    /// ISO 20022 credit transfer initiation (pain.001.001.03)
+    Pain001,
+}
+
+impl std::fmt::Display for PaymentFileFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaymentFileFormat::Csv => write!(f, "csv"),
+            PaymentFileFormat::Pain001 => write!(f, "pain001"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaymentBatchStatus {
+    /// File written, waiting for confirmation that the bank took it
+    Generated,
+    /// Payments marked paid
+    Confirmed,
+    /// Discarded; its payments can go in a new batch
+    Cancelled,
+}
+
+impl std::fmt::Display for PaymentBatchStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaymentBatchStatus::Generated => write!(f, "generated"),
+            PaymentBatchStatus::Confirmed => write!(f, "confirmed"),
+            PaymentBatchStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+// Category models for inventory
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = categories)]
+pub struct Category {
+    pub id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub parent_id: Option<i32>,
+    pub is_active: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = categories)]
+pub struct NewCategory {
+    pub name: String,
+    pub description: Option<String>,
+    pub parent_id: Option<i32>,
+    pub is_active: bool,
+}
+
+// Product models for inventory
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = products)]
+pub struct Product {
+    pub id: i32,
+    pub sku: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub category_id: i32,
+    pub price: i32,
+    pub cost_price: i32,
+    pub current_stock: i32,
+    pub min_stock_level: i32,
+    pub max_stock_level: Option<i32>,
+    pub unit: String,
+    pub barcode: Option<String>,
+    pub is_active: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub commodity_code: Option<String>,
+    pub country_of_origin: Option<String>,
+    pub net_weight_grams: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = products)]
+pub struct NewProduct {
+    pub sku: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub category_id: i32,
+    pub price: i32,
+    pub cost_price: i32,
+    pub current_stock: i32,
+    pub min_stock_level: i32,
+    pub max_stock_level: Option<i32>,
+    pub unit: String,
+    pub barcode: Option<String>,
+    pub is_active: bool,
+    pub commodity_code: Option<String>,
+    pub country_of_origin: Option<String>,
+    pub net_weight_grams: Option<i32>,
+}
+
+// Translation models for localized product and category names
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = product_translations)]
+pub struct ProductTranslation {
+    pub id: i32,
+    pub product_id: i32,
+    pub locale: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = product_translations)]
+pub struct NewProductTranslation {
+    pub product_id: i32,
+    pub locale: String,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = category_translations)]
+pub struct CategoryTranslation {
+    pub id: i32,
+    pub category_id: i32,
+    pub locale: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = category_translations)]
+pub struct NewCategoryTranslation {
+    pub category_id: i32,
+    pub locale: String,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+// Stock movement models for inventory tracking
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = stock_movements)]
+pub struct StockMovement {
+    pub id: i32,
+    pub product_id: i32,
+    pub movement_type: String,
+    pub quantity: i32,
+    pub unit_cost: Option<i32>,
+    pub reference_type: Option<String>,
+    pub reference_id: Option<i32>,
+    pub notes: Option<String>,
+    pub moved_by: Option<i32>,
+    pub movement_date: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = stock_movements)]
+pub struct NewStockMovement {
+    pub product_id: i32,
+    pub movement_type: String,
+    pub quantity: i32,
+    pub unit_cost: Option<i32>,
+    pub reference_type: Option<String>,
+    pub reference_id: Option<i32>,
+    pub notes: Option<String>,
+    pub moved_by: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = stock_reservations)]
+pub struct StockReservation {
+    pub id: i32,
+    pub product_id: i32,
+    pub quantity: i32,
+    pub reference_type: String,
+    pub reference_id: Option<i32>,
+    pub status: String,
+    pub notes: Option<String>,
+    pub reserved_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = stock_reservations)]
+pub struct NewStockReservation {
+    pub product_id: i32,
+    pub quantity: i32,
+    pub reference_type: String,
+    pub reference_id: Option<i32>,
+    pub status: String,
+    pub notes: Option<String>,
+    pub reserved_by: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReservationStatus {
+    /// Quantity is held against on-hand stock
+    Active,
+    /// Hold was cancelled and the quantity is available again
+    Released,
+    /// Reserved quantity was shipped as a stock out movement
+    Fulfilled,
+}
+
+impl std::fmt::Display for ReservationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReservationStatus::Active => write!(f, "active"),
+            ReservationStatus::Released => write!(f, "released"),
+            ReservationStatus::Fulfilled => write!(f, "fulfilled"),
+        }
+    }
+}
+
+/// A sellable kit of products. Unlike a manufacturing bill of materials a
+/// bundle has no stock of its own; it is available as far as its components are.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = product_bundles)]
+pub struct ProductBundle {
+    pub id: i32,
+    pub sku: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub pricing: String,
+    /// Bundle price when `pricing` is `fixed`
+    pub fixed_price: Option<i32>,
+    /// Discount on the summed component prices when `pricing` is `sum`
+    pub discount_percent: i32,
+    pub is_active: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = product_bundles)]
+pub struct NewProductBundle {
+    pub sku: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub pricing: String,
+    pub fixed_price: Option<i32>,
+    pub discount_percent: i32,
+    pub is_active: bool,
+}
+
+/// Quantity of one product in each unit of a bundle
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = product_bundle_items)]
+pub struct ProductBundleItem {
+    pub id: i32,
+    pub bundle_id: i32,
+    pub product_id: i32,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = product_bundle_items)]
+pub struct NewProductBundleItem {
+    pub bundle_id: i32,
+    pub product_id: i32,
+    pub quantity: i32,
+}
+
+/// How a bundle's price is set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BundlePricing {
+    /// One price for the bundle regardless of its components
+    Fixed,
+    /// Component prices added up, less the bundle discount
+    Sum,
+}
+
+impl std::fmt::Display for BundlePricing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BundlePricing::Fixed => write!(f, "fixed"),
+            BundlePricing::Sum => write!(f, "sum"),
+        }
+    }
+}
+
+impl std::str::FromStr for BundlePricing {
+    type Err = crate::core::error::CLIERPError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fixed" => Ok(BundlePricing::Fixed),
+            "sum" => Ok(BundlePricing::Sum),
+            other => Err(crate::core::error::CLIERPError::Validation(format!(
+                "Unknown bundle pricing '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Enforcement an admin chose for a segregation of duties rule; rules
+/// without a row use their default
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = sod_rules)]
+pub struct SodRuleSetting {
+    pub id: i32,
+    pub name: String,
+    pub enforcement: String,
+    pub updated_by: Option<i32>,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = sod_rules)]
+pub struct NewSodRuleSetting {
+    pub name: String,
+    pub enforcement: String,
+    pub updated_by: Option<i32>,
+}
+
+/// A setting a user chose for themselves, e.g. `per_page`, stored as text
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = user_preferences)]
+pub struct UserPreference {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub value: String,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = user_preferences)]
+pub struct NewUserPreference {
+    pub user_id: i32,
+    pub name: String,
+    pub value: String,
+}
+
+/// Partner-specific layout of a CSV export: which internal columns to
+/// write, under which names, and how dates are written. `mapping` holds the
+/// columns as JSON.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = export_templates)]
+pub struct ExportTemplate {
+    pub id: i32,
+    pub name: String,
+    pub entity: String,
+    pub mapping: String,
+    pub date_format: Option<String>,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = export_templates)]
+pub struct NewExportTemplate {
+    pub name: String,
+    pub entity: String,
+    pub mapping: String,
+    pub date_format: Option<String>,
+    pub created_by: Option<i32>,
+}
+
+/// An edited email template; built-in templates without a row are used as
+/// shipped.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = email_templates)]
+pub struct EmailTemplate {
+    pub id: i32,
+    pub name: String,
+    pub subject: String,
+    pub body: String,
+    pub updated_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = email_templates)]
+pub struct NewEmailTemplate {
+    pub name: String,
+    pub subject: String,
+    pub body: String,
+    pub updated_by: Option<i32>,
+}
+
+/// One change to a record in the append-only event log. `sequence` only
+/// grows, so subscribers resume from the last one they saw.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = events)]
+pub struct ChangeEvent {
+    pub sequence: i64,
+    /// Table the record is in
+    pub entity: String,
+    pub entity_id: i32,
+    pub action: String,
+    /// The record as JSON after the change (before it, for deletions)
+    pub payload: String,
+    pub user_id: Option<i32>,
+    pub occurred_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = events)]
+pub struct NewChangeEvent {
+    pub entity: String,
+    pub entity_id: i32,
+    pub action: String,
+    pub payload: String,
+    pub user_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventAction {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl std::fmt::Display for EventAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventAction::Created => write!(f, "created"),
+            EventAction::Updated => write!(f, "updated"),
+            EventAction::Deleted => write!(f, "deleted"),
+        }
+    }
+}
+
+/// FTP or SFTP server an export is uploaded to every `interval_minutes`.
+/// The password, if any, is read from the environment variable named in
+/// `password_env` so it is never stored.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = delivery_destinations)]
+pub struct DeliveryDestination {
+    pub id: i32,
+    pub name: String,
+    pub protocol: String,
+    pub host: String,
+    pub port: i32,
+    pub username: String,
+    pub password_env: Option<String>,
+    pub remote_dir: Option<String>,
+    /// `stock_levels` or `price_list`
+    pub export: String,
+    /// Export template the file is written with
+    pub template: Option<String>,
+    /// Locale product names are translated to
+    pub locale: Option<String>,
+    pub interval_minutes: i32,
+    pub is_active: bool,
+    pub last_run_at: Option<NaiveDateTime>,
+    /// `ok` or `failed`
+    pub last_status: Option<String>,
+    pub consecutive_failures: i32,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = delivery_destinations)]
+pub struct NewDeliveryDestination {
+    pub name: String,
+    pub protocol: String,
+    pub host: String,
+    pub port: i32,
+    pub username: String,
+    pub password_env: Option<String>,
+    pub remote_dir: Option<String>,
+    pub export: String,
+    pub template: Option<String>,
+    pub locale: Option<String>,
+    pub interval_minutes: i32,
+    pub created_by: Option<i32>,
+}
+
+/// One upload attempt to a delivery destination
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = delivery_transfers)]
+pub struct DeliveryTransfer {
+    pub id: i32,
+    pub destination_id: i32,
+    pub file_name: String,
+    pub bytes: i32,
+    /// `ok` or `failed`
+    pub status: String,
+    /// Error of a failed upload
+    pub message: Option<String>,
+    pub transferred_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = delivery_transfers)]
+pub struct NewDeliveryTransfer {
+    pub destination_id: i32,
+    pub file_name: String,
+    pub bytes: i32,
+    pub status: String,
+    pub message: Option<String>,
+}
+
+/// Signed link issued to an outside party; the token carries its `token_id`
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = portal_links)]
+pub struct PortalLink {
+    pub id: i32,
+    pub token_id: String,
+    /// `supplier_po` or `customer`
+    pub scope: String,
+    /// Record the link opens, e.g. the purchase order ID
+    pub record_id: i32,
+    pub expires_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = portal_links)]
+pub struct NewPortalLink {
+    pub token_id: String,
+    pub scope: String,
+    pub record_id: i32,
+    pub expires_at: NaiveDateTime,
+    pub created_by: Option<i32>,
+}
+
+/// Message for a user, shown by `system notifications`
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = notifications)]
+pub struct Notification {
+    pub id: i32,
+    pub user_id: i32,
+    pub subject: String,
+    pub body: Option<String>,
+    pub read_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = notifications)]
+pub struct NewNotification {
+    pub user_id: i32,
+    pub subject: String,
+    pub body: Option<String>,
+}
+
+/// An approval that went ahead although the same user did the conflicting step
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = sod_overrides)]
+pub struct SodOverride {
+    pub id: i32,
+    pub rule_name: String,
+    /// Purchase order, payroll or stock adjustment request ID, per the rule
+    pub record_id: i32,
+    /// User who did the first step, e.g. created the purchase order
+    pub first_actor: Option<i32>,
+    pub user_id: i32,
+    pub reason: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = sod_overrides)]
+pub struct NewSodOverride {
+    pub rule_name: String,
+    pub record_id: i32,
+    pub first_actor: Option<i32>,
+    pub user_id: i32,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum SodEnforcement {
+    /// The same user can never do both steps
+    Block,
+    /// The same user can do both steps when giving a reason, which is logged
+    Override,
+    /// The rule is not checked
+    Off,
+}
+
+impl std::fmt::Display for SodEnforcement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SodEnforcement::Block => write!(f, "block"),
+            SodEnforcement::Override => write!(f, "override"),
+            SodEnforcement::Off => write!(f, "off"),
+        }
+    }
+}
+
+impl std::str::FromStr for SodEnforcement {
+    type Err = crate::core::error::CLIERPError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "block" => Ok(SodEnforcement::Block),
+            "override" => Ok(SodEnforcement::Override),
+            "off" => Ok(SodEnforcement::Off),
+            other => Err(crate::core::error::CLIERPError::Validation(format!(
+                "Unknown segregation of duties enforcement '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Manual stock change above the approval threshold, held until a
+/// supervisor approves or rejects it
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
-#[diesel(table_name = categories)]
-pub struct Category {
+#[diesel(table_name = stock_adjustment_requests)]
+pub struct StockAdjustmentRequest {
     pub id: i32,
-    pub name: String,
-    pub description: Option<String>,
-    pub parent_id: Option<i32>,
-    pub is_active: bool,
-    pub created_at: NaiveDateTime,
-    pub updated_at: NaiveDateTime,
+    pub product_id: i32,
+    pub movement_type: String,
+    /// Signed quantity change, as it will be recorded on the stock movement
+    pub quantity: i32,
+    pub unit_cost: Option<i32>,
+    /// Estimated value of the change at the unit cost, or the product cost price
+    pub value: i32,
+    pub reference: Option<String>,
+    pub notes: Option<String>,
+    pub status: String,
+    pub requested_by: Option<i32>,
+    pub requested_at: NaiveDateTime,
+    pub decided_by: Option<i32>,
+    pub decided_at: Option<NaiveDateTime>,
+    pub decision_note: Option<String>,
+    /// Stock movement recorded when the request was approved
+    pub movement_id: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable)]
-#[diesel(table_name = categories)]
-pub struct NewCategory {
-    pub name: String,
-    pub description: Option<String>,
-    pub parent_id: Option<i32>,
-    pub is_active: bool,
+#[diesel(table_name = stock_adjustment_requests)]
+pub struct NewStockAdjustmentRequest {
+    pub product_id: i32,
+    pub movement_type: String,
+    pub quantity: i32,
+    pub unit_cost: Option<i32>,
+    pub value: i32,
+    pub reference: Option<String>,
+    pub notes: Option<String>,
+    pub requested_by: Option<i32>,
 }
 
-// Product models for inventory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum StockAdjustmentStatus {
+    /// Waiting for a supervisor; stock is unchanged
+    Pending,
+    /// Applied to stock
+    Approved,
+    /// Declined; stock was never changed
+    Rejected,
+}
+
+impl std::fmt::Display for StockAdjustmentStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StockAdjustmentStatus::Pending => write!(f, "pending"),
+            StockAdjustmentStatus::Approved => write!(f, "approved"),
+            StockAdjustmentStatus::Rejected => write!(f, "rejected"),
+        }
+    }
+}
+
+/// Provision against slow-moving or dead stock, proposed from inventory
+/// aging and posted once approved
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
-#[diesel(table_name = products)]
-pub struct Product {
+#[diesel(table_name = stock_write_downs)]
+pub struct StockWriteDown {
     pub id: i32,
-    pub sku: String,
-    pub name: String,
-    pub description: Option<String>,
-    pub category_id: i32,
-    pub price: i32,
-    pub cost_price: i32,
-    pub current_stock: i32,
-    pub min_stock_level: i32,
-    pub max_stock_level: Option<i32>,
-    pub unit: String,
-    pub barcode: Option<String>,
-    pub is_active: bool,
-    pub created_at: NaiveDateTime,
-    pub updated_at: NaiveDateTime,
+    /// Aging date of the proposal, and the posting date once approved
+    pub as_of: NaiveDate,
+    pub amount: i32,
+    pub status: String,
+    pub proposed_by: Option<i32>,
+    pub proposed_at: NaiveDateTime,
+    pub decided_by: Option<i32>,
+    pub decided_at: Option<NaiveDateTime>,
+    pub decision_note: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable)]
-#[diesel(table_name = products)]
-pub struct NewProduct {
-    pub sku: String,
-    pub name: String,
-    pub description: Option<String>,
-    pub category_id: i32,
-    pub price: i32,
-    pub cost_price: i32,
-    pub current_stock: i32,
-    pub min_stock_level: i32,
-    pub max_stock_level: Option<i32>,
-    pub unit: String,
-    pub barcode: Option<String>,
-    pub is_active: bool,
+#[diesel(table_name = stock_write_downs)]
+pub struct NewStockWriteDown {
+    pub as_of: NaiveDate,
+    pub amount: i32,
+    pub proposed_by: Option<i32>,
 }
 
-// Stock movement models for inventory tracking
+/// One product of a write-down
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
-#[diesel(table_name = stock_movements)]
-pub struct StockMovement {
+#[diesel(table_name = stock_write_down_lines)]
+pub struct StockWriteDownLine {
     pub id: i32,
+    pub write_down_id: i32,
     pub product_id: i32,
-    pub movement_type: String,
+    pub reason: String,
+    /// Units the provision covers
     pub quantity: i32,
-    pub unit_cost: Option<i32>,
-    pub reference_type: Option<String>,
-    pub reference_id: Option<i32>,
-    pub notes: Option<String>,
-    pub moved_by: Option<i32>,
-    pub movement_date: NaiveDateTime,
+    pub unit_cost: i32,
+    /// Share of the cost provisioned in total, including earlier write-downs
+    pub percent: i32,
+    /// Provision added by this write-down
+    pub amount: i32,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable)]
-#[diesel(table_name = stock_movements)]
-pub struct NewStockMovement {
+#[diesel(table_name = stock_write_down_lines)]
+pub struct NewStockWriteDownLine {
+    pub write_down_id: i32,
     pub product_id: i32,
-    pub movement_type: String,
+    pub reason: String,
     pub quantity: i32,
-    pub unit_cost: Option<i32>,
-    pub reference_type: Option<String>,
-    pub reference_id: Option<i32>,
-    pub notes: Option<String>,
-    pub moved_by: Option<i32>,
+    pub unit_cost: i32,
+    pub percent: i32,
+    pub amount: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum WriteDownStatus {
+    /// Waiting for a manager; nothing is posted
+    Pending,
+    /// Posted to the ledger
+    Approved,
+    /// Declined; nothing was posted
+    Rejected,
+}
+
+impl std::fmt::Display for WriteDownStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WriteDownStatus::Pending => write!(f, "pending"),
+            WriteDownStatus::Approved => write!(f, "approved"),
+            WriteDownStatus::Rejected => write!(f, "rejected"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WriteDownReason {
+    /// Part of the stock has been held longer than the slow-moving threshold
+    Slow,
+    /// None of the stock has moved within the dead stock threshold
+    Dead,
+}
+
+impl std::fmt::Display for WriteDownReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WriteDownReason::Slow => write!(f, "slow"),
+            WriteDownReason::Dead => write!(f, "dead"),
+        }
+    }
 }
 
 // Enums for inventory management
@@ -599,3 +2205,401 @@ impl std::fmt::Display for AuditStatus {
         }
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = validation_rules)]
+pub struct ValidationRule {
+    pub id: i32,
+    pub entity: String,
+    pub field: String,
+    pub rule_type: String,
+    pub value: Option<String>,
+    pub message: Option<String>,
+    pub is_active: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = validation_rules)]
+pub struct NewValidationRule {
+    pub entity: String,
+    pub field: String,
+    pub rule_type: String,
+    pub value: Option<String>,
+    pub message: Option<String>,
+    pub is_active: bool,
+}
+
+/// Progress of a chunked import, keyed by import type and source file
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = import_checkpoints)]
+pub struct ImportCheckpoint {
+    pub id: i32,
+    pub import_type: String,
+    pub source: String,
+    pub total_rows: i32,
+    pub rows_committed: i32,
+    pub last_key: Option<String>,
+    pub status: String,
+    pub started_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = import_checkpoints)]
+pub struct NewImportCheckpoint {
+    pub import_type: String,
+    pub source: String,
+    pub total_rows: i32,
+    pub rows_committed: i32,
+    pub last_key: Option<String>,
+    pub status: String,
+    pub started_by: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportStatus {
+    /// Started or interrupted; the next run resumes after `rows_committed`
+    Running,
+    Completed,
+}
+
+impl std::fmt::Display for ImportStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportStatus::Running => write!(f, "running"),
+            ImportStatus::Completed => write!(f, "completed"),
+        }
+    }
+}
+
+/// A transaction or stock movement the anomaly detector found unusual
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = anomaly_flags)]
+pub struct AnomalyFlag {
+    pub id: i32,
+    /// "transaction" or "stock_movement"
+    pub source: String,
+    pub record_id: i32,
+    /// Account code or product SKU the record was compared within
+    pub subject: String,
+    pub value: f64,
+    pub score: f64,
+    pub method: String,
+    pub status: String,
+    pub note: Option<String>,
+    pub flagged_at: NaiveDateTime,
+    pub reviewed_by: Option<i32>,
+    pub reviewed_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = anomaly_flags)]
+pub struct NewAnomalyFlag {
+    pub source: String,
+    pub record_id: i32,
+    pub subject: String,
+    pub value: f64,
+    pub score: f64,
+    pub method: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum AnomalyStatus {
+    /// Waiting for review
+    Open,
+    /// Reviewed and found to be a real problem
+    Confirmed,
+    /// Reviewed and found to be legitimate
+    Dismissed,
+}
+
+impl std::fmt::Display for AnomalyStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnomalyStatus::Open => write!(f, "open"),
+            AnomalyStatus::Confirmed => write!(f, "confirmed"),
+            AnomalyStatus::Dismissed => write!(f, "dismissed"),
+        }
+    }
+}
+
+/// One table's share of an archive run
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = archive_runs)]
+pub struct ArchiveRun {
+    pub id: i32,
+    pub table_name: String,
+    /// Rows recorded before this UTC timestamp were moved to the archive table
+    pub cutoff: NaiveDateTime,
+    pub rows_archived: i32,
+    /// Summary rows left in the live table in place of the archived ones
+    pub summary_rows: i32,
+    pub run_by: Option<i32>,
+    pub run_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = archive_runs)]
+pub struct NewArchiveRun {
+    pub table_name: String,
+    pub cutoff: NaiveDateTime,
+    pub rows_archived: i32,
+    pub summary_rows: i32,
+    pub run_by: Option<i32>,
+}
+
+/// A record kept out of retention runs until the hold is released
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = legal_holds)]
+pub struct LegalHold {
+    pub id: i32,
+    /// Table the held record is in, e.g. activities
+    pub entity: String,
+    pub record_id: i32,
+    pub reason: String,
+    pub placed_by: Option<i32>,
+    pub placed_at: NaiveDateTime,
+    pub released_by: Option<i32>,
+    pub released_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = legal_holds)]
+pub struct NewLegalHold {
+    pub entity: String,
+    pub record_id: i32,
+    pub reason: String,
+    pub placed_by: Option<i32>,
+}
+
+/// One policy's share of a retention run
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = retention_runs)]
+pub struct RetentionRun {
+    pub id: i32,
+    pub entity: String,
+    pub action: String,
+    /// Records older than this UTC timestamp were deleted or anonymized
+    pub cutoff: NaiveDateTime,
+    pub rows_affected: i32,
+    /// Records old enough but left alone because of a legal hold
+    pub rows_held: i32,
+    pub run_by: Option<i32>,
+    pub run_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = retention_runs)]
+pub struct NewRetentionRun {
+    pub entity: String,
+    pub action: String,
+    pub cutoff: NaiveDateTime,
+    pub rows_affected: i32,
+    pub rows_held: i32,
+    pub run_by: Option<i32>,
+}
+
+/// Database size and row counts recorded by a `system stats` run
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = storage_snapshots)]
+pub struct StorageSnapshot {
+    pub id: i32,
+    pub taken_at: NaiveDateTime,
+    pub database_bytes: i64,
+    /// JSON object of table name to row count
+    pub row_counts: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = storage_snapshots)]
+pub struct NewStorageSnapshot {
+    pub database_bytes: i64,
+    pub row_counts: String,
+}
+
+/// One command run recorded while usage statistics are enabled. Only the
+/// subcommand path is kept, never argument values.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = command_usage)]
+pub struct CommandUsage {
+    pub id: i32,
+    /// e.g. `inv product list`
+    pub command: String,
+    /// Role of the logged-in user, if any
+    pub role: Option<String>,
+    pub duration_ms: i64,
+    pub succeeded: bool,
+    pub exit_code: Option<i32>,
+    pub used_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = command_usage)]
+pub struct NewCommandUsage {
+    pub command: String,
+    pub role: Option<String>,
+    pub duration_ms: i64,
+    pub succeeded: bool,
+    pub exit_code: Option<i32>,
+}
+
+/// KPI defined as a formula over base metrics, with alert thresholds
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = kpis)]
+pub struct Kpi {
+    pub id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub formula: String,
+    /// Whether values below the thresholds (rather than above) are the bad ones
+    pub higher_is_better: bool,
+    pub warning_threshold: f64,
+    pub critical_threshold: f64,
+    pub last_value: Option<f64>,
+    /// Status at the last recorded check, see `KpiStatus`
+    pub last_status: Option<String>,
+    pub last_evaluated_at: Option<NaiveDateTime>,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = kpis)]
+pub struct NewKpi {
+    pub name: String,
+    pub description: Option<String>,
+    pub formula: String,
+    pub higher_is_better: bool,
+    pub warning_threshold: f64,
+    pub critical_threshold: f64,
+    pub created_by: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum KpiStatus {
+    Ok,
+    Warning,
+    Critical,
+}
+
+impl KpiStatus {
+    /// Status of `value` against the thresholds. A value exactly at a
+    /// threshold counts as having crossed it.
+    pub fn classify(value: f64, warning: f64, critical: f64, higher_is_better: bool) -> Self {
+        let crossed = |threshold: f64| {
+            if higher_is_better {
+                value <= threshold
+            } else {
+                value >= threshold
+            }
+        };
+        if crossed(critical) {
+            KpiStatus::Critical
+        } else if crossed(warning) {
+            KpiStatus::Warning
+        } else {
+            KpiStatus::Ok
+        }
+    }
+}
+
+impl std::fmt::Display for KpiStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KpiStatus::Ok => write!(f, "ok"),
+            KpiStatus::Warning => write!(f, "warning"),
+            KpiStatus::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+impl std::str::FromStr for KpiStatus {
+    type Err = crate::core::error::CLIERPError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ok" => Ok(KpiStatus::Ok),
+            "warning" => Ok(KpiStatus::Warning),
+            "critical" => Ok(KpiStatus::Critical),
+            _ => Err(crate::core::error::CLIERPError::Validation(format!(
+                "Unknown KPI status: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// A saved report run, kept so later runs can be compared with it
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = report_snapshots)]
+pub struct ReportSnapshot {
+    pub id: i32,
+    pub name: String,
+    /// Generator id, e.g. "inventory"
+    pub report_id: String,
+    pub title: String,
+    /// The full `ReportResult` as JSON
+    pub result_json: String,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = report_snapshots)]
+pub struct NewReportSnapshot {
+    pub name: String,
+    pub report_id: String,
+    pub title: String,
+    pub result_json: String,
+    pub created_by: Option<i32>,
+}
+
+/// Check performed by a configured validation rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum RuleType {
+    /// Field must be present and non-empty
+    Required,
+    /// Field must match the regular expression in `value`
+    Regex,
+    /// Numeric field must be at least `value`
+    Min,
+    /// Numeric field must be at most `value`
+    Max,
+    /// Field must be at most `value` characters long
+    #[value(name = "max_length")]
+    MaxLength,
+}
+
+impl std::fmt::Display for RuleType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleType::Required => write!(f, "required"),
+            RuleType::Regex => write!(f, "regex"),
+            RuleType::Min => write!(f, "min"),
+            RuleType::Max => write!(f, "max"),
+            RuleType::MaxLength => write!(f, "max_length"),
+        }
+    }
+}
+
+impl std::str::FromStr for RuleType {
+    type Err = crate::core::error::CLIERPError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "required" => Ok(RuleType::Required),
+            "regex" => Ok(RuleType::Regex),
+            "min" => Ok(RuleType::Min),
+            "max" => Ok(RuleType::Max),
+            "max_length" => Ok(RuleType::MaxLength),
+            _ => Err(crate::core::error::CLIERPError::Validation(format!(
+                "Unknown rule type: {}",
+                s
+            ))),
+        }
+    }
+}
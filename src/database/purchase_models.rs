@@ -2,7 +2,7 @@ use chrono::{NaiveDate, NaiveDateTime};
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use super::schema::{suppliers, purchase_orders, purchase_items};
+use super::schema::{suppliers, purchase_orders, purchase_items, supplier_responses};
 
 // Supplier models
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
@@ -19,6 +19,7 @@ pub struct Supplier {
     pub status: String,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub lead_time_days: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable)]
@@ -32,6 +33,7 @@ pub struct NewSupplier {
     pub address: Option<String>,
     pub payment_terms: Option<String>,
     pub status: String,
+    pub lead_time_days: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,6 +70,8 @@ pub struct PurchaseOrder {
     pub approved_at: Option<NaiveDateTime>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub dispatch_country: Option<String>,
+    pub incoterm: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable)]
@@ -81,6 +85,8 @@ pub struct NewPurchaseOrder {
     pub total_amount: i32,
     pub notes: Option<String>,
     pub created_by: Option<i32>,
+    pub dispatch_country: Option<String>,
+    pub incoterm: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -88,6 +94,10 @@ pub enum PurchaseOrderStatus {
     Pending,
     Approved,
     Sent,
+    /// Supplier accepted the order, possibly with a new delivery date
+    Confirmed,
+    /// Supplier sent a shipping notice
+    Shipped,
     Received,
     Cancelled,
 }
@@ -98,6 +108,8 @@ impl std::fmt::Display for PurchaseOrderStatus {
             PurchaseOrderStatus::Pending => write!(f, "pending"),
             PurchaseOrderStatus::Approved => write!(f, "approved"),
             PurchaseOrderStatus::Sent => write!(f, "sent"),
+            PurchaseOrderStatus::Confirmed => write!(f, "confirmed"),
+            PurchaseOrderStatus::Shipped => write!(f, "shipped"),
             PurchaseOrderStatus::Received => write!(f, "received"),
             PurchaseOrderStatus::Cancelled => write!(f, "cancelled"),
         }
@@ -150,6 +162,34 @@ impl std::fmt::Display for PurchaseItemStatus {
     }
 }
 
+// Supplier portal models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = supplier_responses)]
+pub struct SupplierResponse {
+    pub id: i32,
+    pub po_id: i32,
+    pub link_id: Option<i32>,
+    /// `confirmed`, `date_proposed` or `shipped`
+    pub kind: String,
+    pub proposed_date: Option<NaiveDate>,
+    pub carrier: Option<String>,
+    pub tracking_number: Option<String>,
+    pub message: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = supplier_responses)]
+pub struct NewSupplierResponse {
+    pub po_id: i32,
+    pub link_id: Option<i32>,
+    pub kind: String,
+    pub proposed_date: Option<NaiveDate>,
+    pub carrier: Option<String>,
+    pub tracking_number: Option<String>,
+    pub message: Option<String>,
+}
+
 // DTOs for API responses
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PurchaseOrderWithItems {
@@ -0,0 +1,121 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::schema::{sales_order_items, sales_orders};
+use super::Customer;
+
+// Sales Order models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = sales_orders)]
+pub struct SalesOrder {
+    pub id: i32,
+    pub order_number: String,
+    pub deal_id: Option<i32>,
+    pub customer_id: Option<i32>,
+    pub order_date: NaiveDate,
+    pub status: String,
+    pub discount_percent: i32,
+    pub total_amount: i32,
+    pub invoice_id: Option<i32>,
+    pub shipped_at: Option<NaiveDateTime>,
+    pub notes: Option<String>,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = sales_orders)]
+pub struct NewSalesOrder {
+    pub order_number: String,
+    pub deal_id: Option<i32>,
+    pub customer_id: Option<i32>,
+    pub order_date: NaiveDate,
+    pub status: String,
+    pub discount_percent: i32,
+    pub total_amount: i32,
+    pub notes: Option<String>,
+    pub created_by: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SalesOrderStatus {
+    Pending,
+    /// Stock is held for every item
+    Reserved,
+    Shipped,
+    Invoiced,
+    Cancelled,
+}
+
+impl std::fmt::Display for SalesOrderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SalesOrderStatus::Pending => write!(f, "pending"),
+            SalesOrderStatus::Reserved => write!(f, "reserved"),
+            SalesOrderStatus::Shipped => write!(f, "shipped"),
+            SalesOrderStatus::Invoiced => write!(f, "invoiced"),
+            SalesOrderStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl std::str::FromStr for SalesOrderStatus {
+    type Err = crate::core::error::CLIERPError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pending" => Ok(SalesOrderStatus::Pending),
+            "reserved" => Ok(SalesOrderStatus::Reserved),
+            "shipped" => Ok(SalesOrderStatus::Shipped),
+            "invoiced" => Ok(SalesOrderStatus::Invoiced),
+            "cancelled" => Ok(SalesOrderStatus::Cancelled),
+            _ => Err(crate::core::error::CLIERPError::Validation(format!(
+                "Invalid sales order status '{}'; use pending, reserved, shipped, invoiced or cancelled",
+                s
+            ))),
+        }
+    }
+}
+
+// Sales Order Item models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = sales_order_items)]
+pub struct SalesOrderItem {
+    pub id: i32,
+    pub order_id: i32,
+    pub product_id: i32,
+    pub quantity: i32,
+    pub unit_price: i32,
+    pub total_price: i32,
+    pub reservation_id: Option<i32>,
+    pub shipped_quantity: i32,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = sales_order_items)]
+pub struct NewSalesOrderItem {
+    pub order_id: i32,
+    pub product_id: i32,
+    pub quantity: i32,
+    pub unit_price: i32,
+    pub total_price: i32,
+}
+
+// DTOs for API responses
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SalesOrderWithItems {
+    pub sales_order: SalesOrder,
+    pub items: Vec<SalesOrderItemWithProduct>,
+    pub customer: Option<Customer>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SalesOrderItemWithProduct {
+    pub sales_order_item: SalesOrderItem,
+    pub product_name: String,
+    pub product_sku: String,
+    pub unit: String,
+}
@@ -33,6 +33,49 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    anomaly_flags (id) {
+        id -> Integer,
+        source -> Text,
+        record_id -> Integer,
+        subject -> Text,
+        value -> Double,
+        score -> Double,
+        method -> Text,
+        status -> Text,
+        note -> Nullable<Text>,
+        flagged_at -> Timestamp,
+        reviewed_by -> Nullable<Integer>,
+        reviewed_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    archive_runs (id) {
+        id -> Integer,
+        table_name -> Text,
+        cutoff -> Timestamp,
+        rows_archived -> Integer,
+        summary_rows -> Integer,
+        run_by -> Nullable<Integer>,
+        run_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    attendance_punches (id) {
+        id -> Integer,
+        attendance_id -> Integer,
+        employee_id -> Integer,
+        punch_type -> Text,
+        latitude -> Nullable<Double>,
+        longitude -> Nullable<Double>,
+        device_fingerprint -> Nullable<Text>,
+        distance_meters -> Nullable<Double>,
+        punched_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     attendances (id) {
         id -> Integer,
@@ -49,226 +92,945 @@ diesel::table! {
 }
 
 diesel::table! {
-    audit_logs (id) {
+    audit_logs (id) {
+        id -> Integer,
+        user_id -> Nullable<Integer>,
+        table_name -> Text,
+        record_id -> Integer,
+        action -> Text,
+        old_values -> Nullable<Text>,
+        new_values -> Nullable<Text>,
+        changed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    audit_logs_archive (id) {
+        id -> Integer,
+        user_id -> Nullable<Integer>,
+        table_name -> Text,
+        record_id -> Integer,
+        action -> Text,
+        old_values -> Nullable<Text>,
+        new_values -> Nullable<Text>,
+        changed_at -> Timestamp,
+        archived_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    campaign_leads (id) {
+        id -> Integer,
+        campaign_id -> Integer,
+        lead_id -> Integer,
+        response -> Nullable<Text>,
+        response_date -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    campaigns (id) {
+        id -> Integer,
+        name -> Text,
+        description -> Nullable<Text>,
+        campaign_type -> Text,
+        start_date -> Nullable<Date>,
+        end_date -> Nullable<Date>,
+        budget -> Nullable<Integer>,
+        spent -> Nullable<Integer>,
+        target_audience -> Nullable<Text>,
+        status -> Text,
+        created_by -> Nullable<Integer>,
+        notes -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    categories (id) {
+        id -> Integer,
+        name -> Text,
+        description -> Nullable<Text>,
+        parent_id -> Nullable<Integer>,
+        is_active -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    category_translations (id) {
+        id -> Integer,
+        category_id -> Integer,
+        locale -> Text,
+        name -> Text,
+        description -> Nullable<Text>,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    churn_alerts (id) {
+        id -> Integer,
+        customer_id -> Integer,
+        user_id -> Nullable<Integer>,
+        signals -> Text,
+        alerted_on -> Date,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    collection_calls (id) {
+        id -> Integer,
+        customer_id -> Integer,
+        outcome -> Text,
+        notes -> Nullable<Text>,
+        created_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    command_usage (id) {
+        id -> Integer,
+        command -> Text,
+        role -> Nullable<Text>,
+        duration_ms -> BigInt,
+        succeeded -> Bool,
+        exit_code -> Nullable<Integer>,
+        used_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    compensation_history (id) {
+        id -> Integer,
+        employee_id -> Integer,
+        old_salary -> Nullable<Integer>,
+        new_salary -> Integer,
+        effective_date -> Date,
+        source -> Text,
+        raise_id -> Nullable<Integer>,
+        changed_by -> Nullable<Integer>,
+        changed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    customer_surveys (id) {
+        id -> Integer,
+        customer_id -> Integer,
+        deal_id -> Nullable<Integer>,
+        score -> Integer,
+        comment -> Nullable<Text>,
+        survey_date -> Date,
+        channel -> Text,
+        created_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    customers (id) {
+        id -> Integer,
+        customer_code -> Text,
+        name -> Text,
+        email -> Nullable<Text>,
+        phone -> Nullable<Text>,
+        address -> Nullable<Text>,
+        customer_type -> Text,
+        company_name -> Nullable<Text>,
+        tax_id -> Nullable<Text>,
+        credit_limit -> Nullable<Integer>,
+        status -> Text,
+        notes -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    deals (id) {
+        id -> Integer,
+        lead_id -> Nullable<Integer>,
+        deal_name -> Text,
+        stage -> Text,
+        deal_value -> Integer,
+        close_date -> Nullable<Date>,
+        probability -> Nullable<Integer>,
+        assigned_to -> Nullable<Integer>,
+        products -> Nullable<Text>,
+        discount_percent -> Nullable<Integer>,
+        final_amount -> Nullable<Integer>,
+        notes -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        destination_country -> Nullable<Text>,
+        visibility -> Text,
+    }
+}
+
+diesel::table! {
+    delivery_destinations (id) {
+        id -> Integer,
+        name -> Text,
+        protocol -> Text,
+        host -> Text,
+        port -> Integer,
+        username -> Text,
+        password_env -> Nullable<Text>,
+        remote_dir -> Nullable<Text>,
+        export -> Text,
+        template -> Nullable<Text>,
+        locale -> Nullable<Text>,
+        interval_minutes -> Integer,
+        is_active -> Bool,
+        last_run_at -> Nullable<Timestamp>,
+        last_status -> Nullable<Text>,
+        consecutive_failures -> Integer,
+        created_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    delivery_transfers (id) {
+        id -> Integer,
+        destination_id -> Integer,
+        file_name -> Text,
+        bytes -> Integer,
+        status -> Text,
+        message -> Nullable<Text>,
+        transferred_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    departments (id) {
+        id -> Integer,
+        name -> Text,
+        description -> Nullable<Text>,
+        manager_id -> Nullable<Integer>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dropbox_messages (id) {
+        id -> Integer,
+        message_id -> Text,
+        sender -> Text,
+        subject -> Text,
+        activities -> Integer,
+        received_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dunning_charges (id) {
+        id -> Integer,
+        invoice_id -> Integer,
+        kind -> Text,
+        amount -> Integer,
+        days_overdue -> Integer,
+        accrued_from -> Nullable<Date>,
+        accrued_to -> Date,
+        created_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dunning_exemptions (customer_id) {
+        customer_id -> Integer,
+        reason -> Nullable<Text>,
+        created_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    email_templates (id) {
+        id -> Integer,
+        name -> Text,
+        subject -> Text,
+        body -> Text,
+        updated_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    employee_documents (id) {
+        id -> Integer,
+        employee_id -> Integer,
+        document_type -> Text,
+        document_number -> Nullable<Text>,
+        issued_date -> Nullable<Date>,
+        expiry_date -> Nullable<Date>,
+        file_path -> Nullable<Text>,
+        notes -> Nullable<Text>,
+        alerted_at -> Nullable<Timestamp>,
+        created_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    employees (id) {
+        id -> Integer,
+        employee_code -> Text,
+        name -> Text,
+        email -> Nullable<Text>,
+        phone -> Nullable<Text>,
+        department_id -> Integer,
+        position -> Text,
+        hire_date -> Date,
+        salary -> Integer,
+        status -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        termination_date -> Nullable<Date>,
+        level -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    events (sequence) {
+        sequence -> BigInt,
+        entity -> Text,
+        entity_id -> Integer,
+        action -> Text,
+        payload -> Text,
+        user_id -> Nullable<Integer>,
+        occurred_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    export_templates (id) {
+        id -> Integer,
+        name -> Text,
+        entity -> Text,
+        mapping -> Text,
+        date_format -> Nullable<Text>,
+        created_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    grni_accrual_lines (id) {
+        id -> Integer,
+        accrual_id -> Integer,
+        po_id -> Integer,
+        received_amount -> Integer,
+        invoiced_amount -> Integer,
+        amount -> Integer,
+    }
+}
+
+diesel::table! {
+    grni_accruals (id) {
+        id -> Integer,
+        period -> Text,
+        accrued_on -> Date,
+        reversed_on -> Date,
+        amount -> Integer,
+        created_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    import_checkpoints (id) {
+        id -> Integer,
+        import_type -> Text,
+        source -> Text,
+        total_rows -> Integer,
+        rows_committed -> Integer,
+        last_key -> Nullable<Text>,
+        status -> Text,
+        started_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    invoice_lines (id) {
+        id -> Integer,
+        invoice_id -> Integer,
+        line_number -> Integer,
+        description -> Text,
+        product_id -> Nullable<Integer>,
+        quantity -> Integer,
+        unit_price -> Integer,
+        amount -> Integer,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    invoice_payments (id) {
+        id -> Integer,
+        invoice_id -> Integer,
+        amount -> Integer,
+        paid_on -> Date,
+        reference -> Nullable<Text>,
+        created_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+        returned_on -> Nullable<Date>,
+        return_reason -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    invoice_sequences (year) {
+        year -> Integer,
+        last_number -> Integer,
+    }
+}
+
+diesel::table! {
+    invoices (id) {
+        id -> Integer,
+        invoice_number -> Text,
+        kind -> Text,
+        customer_id -> Nullable<Integer>,
+        supplier_id -> Nullable<Integer>,
+        invoice_date -> Date,
+        due_date -> Nullable<Date>,
+        total_amount -> Integer,
+        paid_amount -> Integer,
+        status -> Text,
+        description -> Nullable<Text>,
+        created_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        po_id -> Nullable<Integer>,
+        sent_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    journal_entries (id) {
+        id -> Integer,
+        entry_number -> Text,
+        entry_date -> Date,
+        description -> Text,
+        reference -> Nullable<Text>,
+        reverses_entry_id -> Nullable<Integer>,
+        created_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    kpis (id) {
+        id -> Integer,
+        name -> Text,
+        description -> Nullable<Text>,
+        formula -> Text,
+        higher_is_better -> Bool,
+        warning_threshold -> Double,
+        critical_threshold -> Double,
+        last_value -> Nullable<Double>,
+        last_status -> Nullable<Text>,
+        last_evaluated_at -> Nullable<Timestamp>,
+        created_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    leads (id) {
+        id -> Integer,
+        customer_id -> Nullable<Integer>,
+        lead_source -> Text,
+        status -> Text,
+        priority -> Text,
+        estimated_value -> Nullable<Integer>,
+        probability -> Nullable<Integer>,
+        expected_close_date -> Nullable<Date>,
+        assigned_to -> Nullable<Integer>,
+        title -> Text,
+        description -> Nullable<Text>,
+        notes -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        visibility -> Text,
+    }
+}
+
+diesel::table! {
+    leave_requests (id) {
+        id -> Integer,
+        employee_id -> Integer,
+        start_date -> Date,
+        end_date -> Date,
+        days -> Integer,
+        reason -> Nullable<Text>,
+        status -> Text,
+        requested_by -> Nullable<Integer>,
+        requested_at -> Timestamp,
+        decided_by -> Nullable<Integer>,
+        decided_at -> Nullable<Timestamp>,
+        decision_note -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    legal_holds (id) {
+        id -> Integer,
+        entity -> Text,
+        record_id -> Integer,
+        reason -> Text,
+        placed_by -> Nullable<Integer>,
+        placed_at -> Timestamp,
+        released_by -> Nullable<Integer>,
+        released_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    notifications (id) {
+        id -> Integer,
+        user_id -> Integer,
+        subject -> Text,
+        body -> Nullable<Text>,
+        read_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    payee_bank_accounts (id) {
+        id -> Integer,
+        payee_type -> Text,
+        payee_id -> Integer,
+        account_holder -> Nullable<Text>,
+        account_number -> Text,
+        bic -> Nullable<Text>,
+        updated_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    payment_batch_items (id) {
+        id -> Integer,
+        batch_id -> Integer,
+        payroll_id -> Nullable<Integer>,
+        invoice_id -> Nullable<Integer>,
+        payee_name -> Text,
+        account_number -> Text,
+        bic -> Nullable<Text>,
+        amount -> Integer,
+        remittance -> Text,
+    }
+}
+
+diesel::table! {
+    payment_batches (id) {
+        id -> Integer,
+        batch_reference -> Text,
+        kind -> Text,
+        format -> Text,
+        file_path -> Text,
+        execution_date -> Date,
+        item_count -> Integer,
+        total_amount -> Integer,
+        status -> Text,
+        created_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+        decided_by -> Nullable<Integer>,
+        decided_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    payment_promises (id) {
+        id -> Integer,
+        customer_id -> Integer,
+        call_id -> Integer,
+        amount -> Integer,
+        promised_on -> Date,
+        promised_date -> Date,
+        status -> Text,
+        resolved_on -> Nullable<Date>,
+        created_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    payrolls (id) {
+        id -> Integer,
+        employee_id -> Integer,
+        period -> Text,
+        base_salary -> Integer,
+        overtime_pay -> Nullable<Integer>,
+        bonuses -> Nullable<Integer>,
+        deductions -> Nullable<Integer>,
+        net_salary -> Integer,
+        payment_date -> Nullable<Date>,
+        status -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        prepared_by -> Nullable<Integer>,
+        paid_by -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    portal_links (id) {
+        id -> Integer,
+        token_id -> Text,
+        scope -> Text,
+        record_id -> Integer,
+        expires_at -> Timestamp,
+        revoked_at -> Nullable<Timestamp>,
+        last_used_at -> Nullable<Timestamp>,
+        created_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    product_attachments (id) {
+        id -> Integer,
+        product_id -> Integer,
+        attachment_type -> Text,
+        file_name -> Text,
+        file_path -> Text,
+        file_size -> Integer,
+        mime_type -> Nullable<Text>,
+        is_primary -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    product_bundle_items (id) {
+        id -> Integer,
+        bundle_id -> Integer,
+        product_id -> Integer,
+        quantity -> Integer,
+    }
+}
+
+diesel::table! {
+    product_bundles (id) {
+        id -> Integer,
+        sku -> Text,
+        name -> Text,
+        description -> Nullable<Text>,
+        pricing -> Text,
+        fixed_price -> Nullable<Integer>,
+        discount_percent -> Integer,
+        is_active -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    product_translations (id) {
+        id -> Integer,
+        product_id -> Integer,
+        locale -> Text,
+        name -> Text,
+        description -> Nullable<Text>,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    products (id) {
+        id -> Integer,
+        sku -> Text,
+        name -> Text,
+        description -> Nullable<Text>,
+        category_id -> Integer,
+        price -> Integer,
+        cost_price -> Integer,
+        current_stock -> Integer,
+        min_stock_level -> Integer,
+        max_stock_level -> Nullable<Integer>,
+        unit -> Text,
+        barcode -> Nullable<Text>,
+        is_active -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        commodity_code -> Nullable<Text>,
+        country_of_origin -> Nullable<Text>,
+        net_weight_grams -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    purchase_items (id) {
+        id -> Integer,
+        po_id -> Integer,
+        product_id -> Integer,
+        quantity -> Integer,
+        unit_cost -> Integer,
+        total_cost -> Integer,
+        received_quantity -> Integer,
+        status -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    purchase_orders (id) {
+        id -> Integer,
+        po_number -> Text,
+        supplier_id -> Integer,
+        order_date -> Date,
+        expected_date -> Nullable<Date>,
+        status -> Text,
+        total_amount -> Integer,
+        notes -> Nullable<Text>,
+        created_by -> Nullable<Integer>,
+        approved_by -> Nullable<Integer>,
+        approved_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        dispatch_country -> Nullable<Text>,
+        incoterm -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    quarantined_rows (id) {
         id -> Integer,
-        user_id -> Nullable<Integer>,
         table_name -> Text,
         record_id -> Integer,
-        action -> Text,
-        old_values -> Nullable<Text>,
-        new_values -> Nullable<Text>,
-        changed_at -> Timestamp,
+        reason -> Text,
+        row_data -> Text,
+        quarantined_by -> Nullable<Integer>,
+        quarantined_at -> Timestamp,
     }
 }
 
 diesel::table! {
-    campaign_leads (id) {
+    record_links (id) {
         id -> Integer,
-        campaign_id -> Integer,
-        lead_id -> Integer,
-        response -> Nullable<Text>,
-        response_date -> Nullable<Timestamp>,
+        source_type -> Text,
+        source_id -> Integer,
+        target_type -> Text,
+        target_id -> Integer,
+        relation -> Text,
+        note -> Nullable<Text>,
+        created_by -> Nullable<Integer>,
         created_at -> Timestamp,
     }
 }
 
 diesel::table! {
-    campaigns (id) {
+    remote_work_policies (id) {
         id -> Integer,
-        name -> Text,
-        description -> Nullable<Text>,
-        campaign_type -> Text,
-        start_date -> Nullable<Date>,
-        end_date -> Nullable<Date>,
-        budget -> Nullable<Integer>,
-        spent -> Nullable<Integer>,
-        target_audience -> Nullable<Text>,
-        status -> Text,
-        created_by -> Nullable<Integer>,
-        notes -> Nullable<Text>,
+        employee_id -> Integer,
+        require_location -> Bool,
+        require_device -> Bool,
+        registered_device -> Nullable<Text>,
+        site_latitude -> Nullable<Double>,
+        site_longitude -> Nullable<Double>,
+        radius_meters -> Nullable<Integer>,
+        updated_by -> Nullable<Integer>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
     }
 }
 
 diesel::table! {
-    categories (id) {
+    report_snapshots (id) {
         id -> Integer,
         name -> Text,
-        description -> Nullable<Text>,
-        parent_id -> Nullable<Integer>,
-        is_active -> Bool,
+        report_id -> Text,
+        title -> Text,
+        result_json -> Text,
+        created_by -> Nullable<Integer>,
         created_at -> Timestamp,
-        updated_at -> Timestamp,
     }
 }
 
 diesel::table! {
-    customers (id) {
+    required_documents (id) {
         id -> Integer,
-        customer_code -> Text,
-        name -> Text,
-        email -> Nullable<Text>,
-        phone -> Nullable<Text>,
-        address -> Nullable<Text>,
-        customer_type -> Text,
-        company_name -> Nullable<Text>,
-        tax_id -> Nullable<Text>,
-        credit_limit -> Nullable<Integer>,
-        status -> Text,
-        notes -> Nullable<Text>,
+        document_type -> Text,
+        department_id -> Nullable<Integer>,
         created_at -> Timestamp,
-        updated_at -> Timestamp,
     }
 }
 
 diesel::table! {
-    deals (id) {
+    retention_runs (id) {
         id -> Integer,
-        lead_id -> Nullable<Integer>,
-        deal_name -> Text,
-        stage -> Text,
-        deal_value -> Integer,
-        close_date -> Nullable<Date>,
-        probability -> Nullable<Integer>,
-        assigned_to -> Nullable<Integer>,
-        products -> Nullable<Text>,
-        discount_percent -> Nullable<Integer>,
-        final_amount -> Nullable<Integer>,
-        notes -> Nullable<Text>,
+        entity -> Text,
+        action -> Text,
+        cutoff -> Timestamp,
+        rows_affected -> Integer,
+        rows_held -> Integer,
+        run_by -> Nullable<Integer>,
+        run_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    revenue_recognitions (id) {
+        id -> Integer,
+        schedule_id -> Integer,
+        period -> Text,
+        amount -> Integer,
+        recognized_on -> Date,
+        created_by -> Nullable<Integer>,
         created_at -> Timestamp,
-        updated_at -> Timestamp,
     }
 }
 
 diesel::table! {
-    departments (id) {
+    revenue_schedules (id) {
         id -> Integer,
-        name -> Text,
+        invoice_id -> Integer,
         description -> Nullable<Text>,
-        manager_id -> Nullable<Integer>,
+        total_amount -> Integer,
+        start_date -> Date,
+        months -> Integer,
+        deferred_account -> Text,
+        revenue_account -> Text,
+        created_by -> Nullable<Integer>,
         created_at -> Timestamp,
-        updated_at -> Timestamp,
     }
 }
 
 diesel::table! {
-    employees (id) {
+    salary_bands (id) {
         id -> Integer,
-        employee_code -> Text,
-        name -> Text,
-        email -> Nullable<Text>,
-        phone -> Nullable<Text>,
-        department_id -> Integer,
         position -> Text,
-        hire_date -> Date,
-        salary -> Integer,
-        status -> Text,
+        level -> Nullable<Text>,
+        min_salary -> Integer,
+        max_salary -> Integer,
+        updated_by -> Nullable<Integer>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
     }
 }
 
 diesel::table! {
-    leads (id) {
+    salary_raises (id) {
+        id -> Integer,
+        employee_id -> Integer,
+        current_salary -> Integer,
+        proposed_salary -> Integer,
+        effective_date -> Date,
+        reason -> Text,
+        status -> Text,
+        proposed_by -> Nullable<Integer>,
+        proposed_at -> Timestamp,
+        decided_by -> Nullable<Integer>,
+        decided_at -> Nullable<Timestamp>,
+        decision_note -> Nullable<Text>,
+        applied_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    sales_order_items (id) {
+        id -> Integer,
+        order_id -> Integer,
+        product_id -> Integer,
+        quantity -> Integer,
+        unit_price -> Integer,
+        total_price -> Integer,
+        reservation_id -> Nullable<Integer>,
+        shipped_quantity -> Integer,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    sales_orders (id) {
         id -> Integer,
+        order_number -> Text,
+        deal_id -> Nullable<Integer>,
         customer_id -> Nullable<Integer>,
-        lead_source -> Text,
+        order_date -> Date,
         status -> Text,
-        priority -> Text,
-        estimated_value -> Nullable<Integer>,
-        probability -> Nullable<Integer>,
-        expected_close_date -> Nullable<Date>,
-        assigned_to -> Nullable<Integer>,
-        title -> Text,
-        description -> Nullable<Text>,
+        discount_percent -> Integer,
+        total_amount -> Integer,
+        invoice_id -> Nullable<Integer>,
+        shipped_at -> Nullable<Timestamp>,
         notes -> Nullable<Text>,
+        created_by -> Nullable<Integer>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
     }
 }
 
 diesel::table! {
-    payrolls (id) {
+    sales_quotas (id) {
         id -> Integer,
         employee_id -> Integer,
         period -> Text,
-        base_salary -> Integer,
-        overtime_pay -> Nullable<Integer>,
-        bonuses -> Nullable<Integer>,
-        deductions -> Nullable<Integer>,
-        net_salary -> Integer,
-        payment_date -> Nullable<Date>,
-        status -> Text,
+        amount -> Integer,
+        created_by -> Nullable<Integer>,
         created_at -> Timestamp,
-        updated_at -> Timestamp,
     }
 }
 
 diesel::table! {
-    product_attachments (id) {
+    sod_overrides (id) {
         id -> Integer,
-        product_id -> Integer,
-        attachment_type -> Text,
-        file_name -> Text,
-        file_path -> Text,
-        file_size -> Integer,
-        mime_type -> Nullable<Text>,
-        is_primary -> Bool,
+        rule_name -> Text,
+        record_id -> Integer,
+        first_actor -> Nullable<Integer>,
+        user_id -> Integer,
+        reason -> Text,
         created_at -> Timestamp,
-        updated_at -> Timestamp,
     }
 }
 
 diesel::table! {
-    products (id) {
+    sod_rules (id) {
         id -> Integer,
-        sku -> Text,
         name -> Text,
-        description -> Nullable<Text>,
-        category_id -> Integer,
-        price -> Integer,
-        cost_price -> Integer,
-        current_stock -> Integer,
-        min_stock_level -> Integer,
-        max_stock_level -> Nullable<Integer>,
-        unit -> Text,
-        barcode -> Nullable<Text>,
-        is_active -> Bool,
-        created_at -> Timestamp,
+        enforcement -> Text,
+        updated_by -> Nullable<Integer>,
         updated_at -> Timestamp,
     }
 }
 
 diesel::table! {
-    purchase_items (id) {
+    stock_adjustment_requests (id) {
         id -> Integer,
-        po_id -> Integer,
         product_id -> Integer,
+        movement_type -> Text,
         quantity -> Integer,
-        unit_cost -> Integer,
-        total_cost -> Integer,
-        received_quantity -> Integer,
-        status -> Text,
-        created_at -> Timestamp,
-    }
-}
-
-diesel::table! {
-    purchase_orders (id) {
-        id -> Integer,
-        po_number -> Text,
-        supplier_id -> Integer,
-        order_date -> Date,
-        expected_date -> Nullable<Date>,
-        status -> Text,
-        total_amount -> Integer,
+        unit_cost -> Nullable<Integer>,
+        value -> Integer,
+        reference -> Nullable<Text>,
         notes -> Nullable<Text>,
-        created_by -> Nullable<Integer>,
-        approved_by -> Nullable<Integer>,
-        approved_at -> Nullable<Timestamp>,
-        created_at -> Timestamp,
-        updated_at -> Timestamp,
+        status -> Text,
+        requested_by -> Nullable<Integer>,
+        requested_at -> Timestamp,
+        decided_by -> Nullable<Integer>,
+        decided_at -> Nullable<Timestamp>,
+        decision_note -> Nullable<Text>,
+        movement_id -> Nullable<Integer>,
     }
 }
 
@@ -314,6 +1076,111 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    stock_movements_archive (id) {
+        id -> Integer,
+        product_id -> Integer,
+        movement_type -> Text,
+        quantity -> Integer,
+        unit_cost -> Nullable<Integer>,
+        reference_type -> Nullable<Text>,
+        reference_id -> Nullable<Integer>,
+        notes -> Nullable<Text>,
+        moved_by -> Nullable<Integer>,
+        movement_date -> Timestamp,
+        archived_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    stock_reservations (id) {
+        id -> Integer,
+        product_id -> Integer,
+        quantity -> Integer,
+        reference_type -> Text,
+        reference_id -> Nullable<Integer>,
+        status -> Text,
+        notes -> Nullable<Text>,
+        reserved_by -> Nullable<Integer>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    stock_write_down_lines (id) {
+        id -> Integer,
+        write_down_id -> Integer,
+        product_id -> Integer,
+        reason -> Text,
+        quantity -> Integer,
+        unit_cost -> Integer,
+        percent -> Integer,
+        amount -> Integer,
+    }
+}
+
+diesel::table! {
+    stock_write_downs (id) {
+        id -> Integer,
+        as_of -> Date,
+        amount -> Integer,
+        status -> Text,
+        proposed_by -> Nullable<Integer>,
+        proposed_at -> Timestamp,
+        decided_by -> Nullable<Integer>,
+        decided_at -> Nullable<Timestamp>,
+        decision_note -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    storage_snapshots (id) {
+        id -> Integer,
+        taken_at -> Timestamp,
+        database_bytes -> BigInt,
+        row_counts -> Text,
+    }
+}
+
+diesel::table! {
+    summary_account_monthly (account_id, month) {
+        account_id -> Integer,
+        month -> Text,
+        debits -> BigInt,
+        credits -> BigInt,
+        entries -> BigInt,
+        refreshed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    summary_product_daily (product_id, day) {
+        product_id -> Integer,
+        day -> Date,
+        quantity_in -> BigInt,
+        quantity_out -> BigInt,
+        cost_out -> BigInt,
+        uncosted_out -> BigInt,
+        movements -> BigInt,
+        refreshed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    supplier_responses (id) {
+        id -> Integer,
+        po_id -> Integer,
+        link_id -> Nullable<Integer>,
+        kind -> Text,
+        proposed_date -> Nullable<Date>,
+        carrier -> Nullable<Text>,
+        tracking_number -> Nullable<Text>,
+        message -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     suppliers (id) {
         id -> Integer,
@@ -327,6 +1194,7 @@ diesel::table! {
         status -> Text,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        lead_time_days -> Nullable<Integer>,
     }
 }
 
@@ -342,6 +1210,17 @@ diesel::table! {
         created_by -> Nullable<Integer>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        journal_entry_id -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    user_preferences (id) {
+        id -> Integer,
+        user_id -> Integer,
+        name -> Text,
+        value -> Text,
+        updated_at -> Timestamp,
     }
 }
 
@@ -364,18 +1243,60 @@ diesel::joinable!(activities -> employees (assigned_to));
 diesel::joinable!(activities -> deals (deal_id));
 diesel::joinable!(activities -> leads (lead_id));
 diesel::joinable!(activities -> customers (customer_id));
+diesel::joinable!(anomaly_flags -> users (reviewed_by));
+diesel::joinable!(archive_runs -> users (run_by));
+diesel::joinable!(attendance_punches -> attendances (attendance_id));
+diesel::joinable!(attendance_punches -> employees (employee_id));
 diesel::joinable!(attendances -> employees (employee_id));
 diesel::joinable!(audit_logs -> users (user_id));
 diesel::joinable!(campaign_leads -> leads (lead_id));
 diesel::joinable!(campaign_leads -> campaigns (campaign_id));
 diesel::joinable!(campaigns -> employees (created_by));
+diesel::joinable!(category_translations -> categories (category_id));
+diesel::joinable!(churn_alerts -> customers (customer_id));
+diesel::joinable!(churn_alerts -> users (user_id));
+diesel::joinable!(collection_calls -> customers (customer_id));
+diesel::joinable!(compensation_history -> employees (employee_id));
+diesel::joinable!(customer_surveys -> customers (customer_id));
+diesel::joinable!(customer_surveys -> deals (deal_id));
 diesel::joinable!(deals -> employees (assigned_to));
 diesel::joinable!(deals -> leads (lead_id));
+diesel::joinable!(delivery_destinations -> users (created_by));
+diesel::joinable!(delivery_transfers -> delivery_destinations (destination_id));
+diesel::joinable!(dunning_charges -> invoices (invoice_id));
+diesel::joinable!(dunning_exemptions -> customers (customer_id));
+diesel::joinable!(email_templates -> users (updated_by));
+diesel::joinable!(events -> users (user_id));
+diesel::joinable!(employee_documents -> employees (employee_id));
+diesel::joinable!(employee_documents -> users (created_by));
 diesel::joinable!(employees -> departments (department_id));
+diesel::joinable!(export_templates -> users (created_by));
+diesel::joinable!(grni_accrual_lines -> grni_accruals (accrual_id));
+diesel::joinable!(grni_accrual_lines -> purchase_orders (po_id));
+diesel::joinable!(import_checkpoints -> users (started_by));
+diesel::joinable!(invoice_lines -> invoices (invoice_id));
+diesel::joinable!(invoice_lines -> products (product_id));
+diesel::joinable!(invoice_payments -> invoices (invoice_id));
+diesel::joinable!(invoices -> customers (customer_id));
+diesel::joinable!(invoices -> purchase_orders (po_id));
+diesel::joinable!(invoices -> suppliers (supplier_id));
+diesel::joinable!(journal_entries -> users (created_by));
+diesel::joinable!(kpis -> users (created_by));
 diesel::joinable!(leads -> employees (assigned_to));
 diesel::joinable!(leads -> customers (customer_id));
+diesel::joinable!(leave_requests -> employees (employee_id));
+diesel::joinable!(notifications -> users (user_id));
+diesel::joinable!(payment_batch_items -> invoices (invoice_id));
+diesel::joinable!(payment_batch_items -> payment_batches (batch_id));
+diesel::joinable!(payment_batch_items -> payrolls (payroll_id));
+diesel::joinable!(payment_promises -> collection_calls (call_id));
+diesel::joinable!(payment_promises -> customers (customer_id));
 diesel::joinable!(payrolls -> employees (employee_id));
+diesel::joinable!(portal_links -> users (created_by));
 diesel::joinable!(product_attachments -> products (product_id));
+diesel::joinable!(product_bundle_items -> product_bundles (bundle_id));
+diesel::joinable!(product_bundle_items -> products (product_id));
+diesel::joinable!(product_translations -> products (product_id));
 diesel::joinable!(products -> categories (category_id));
 diesel::joinable!(purchase_items -> products (product_id));
 diesel::joinable!(purchase_items -> purchase_orders (po_id));
@@ -383,37 +1304,139 @@ diesel::joinable!(purchase_items -> purchase_orders (po_id));
 // Using one main relationship
 diesel::joinable!(purchase_orders -> users (created_by));
 diesel::joinable!(purchase_orders -> suppliers (supplier_id));
+diesel::joinable!(remote_work_policies -> employees (employee_id));
+diesel::joinable!(report_snapshots -> users (created_by));
+diesel::joinable!(required_documents -> departments (department_id));
+diesel::joinable!(retention_runs -> users (run_by));
+diesel::joinable!(revenue_recognitions -> revenue_schedules (schedule_id));
+diesel::joinable!(revenue_schedules -> invoices (invoice_id));
+diesel::joinable!(salary_bands -> users (updated_by));
+diesel::joinable!(salary_raises -> employees (employee_id));
+diesel::joinable!(sales_order_items -> products (product_id));
+diesel::joinable!(sales_order_items -> sales_orders (order_id));
+diesel::joinable!(sales_orders -> customers (customer_id));
+diesel::joinable!(sales_quotas -> employees (employee_id));
+diesel::joinable!(sod_overrides -> users (user_id));
+diesel::joinable!(sod_rules -> users (updated_by));
+diesel::joinable!(stock_adjustment_requests -> products (product_id));
 diesel::joinable!(stock_audit_items -> products (product_id));
 diesel::joinable!(stock_audit_items -> stock_audits (audit_id));
 diesel::joinable!(stock_audits -> users (conducted_by));
 diesel::joinable!(stock_movements -> users (moved_by));
 diesel::joinable!(stock_movements -> products (product_id));
+diesel::joinable!(stock_reservations -> products (product_id));
+diesel::joinable!(stock_write_down_lines -> products (product_id));
+diesel::joinable!(stock_write_down_lines -> stock_write_downs (write_down_id));
+diesel::joinable!(summary_account_monthly -> accounts (account_id));
+diesel::joinable!(summary_product_daily -> products (product_id));
+diesel::joinable!(supplier_responses -> purchase_orders (po_id));
 diesel::joinable!(transactions -> users (created_by));
 diesel::joinable!(transactions -> accounts (account_id));
+diesel::joinable!(transactions -> journal_entries (journal_entry_id));
+diesel::joinable!(user_preferences -> users (user_id));
 diesel::joinable!(users -> employees (employee_id));
 
+diesel::table! {
+    validation_rules (id) {
+        id -> Integer,
+        entity -> Text,
+        field -> Text,
+        rule_type -> Text,
+        value -> Nullable<Text>,
+        message -> Nullable<Text>,
+        is_active -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::allow_tables_to_appear_in_same_query!(
     accounts,
     activities,
+    anomaly_flags,
+    archive_runs,
+    attendance_punches,
     attendances,
     audit_logs,
+    audit_logs_archive,
     campaign_leads,
     campaigns,
     categories,
+    category_translations,
+    churn_alerts,
+    collection_calls,
+    command_usage,
+    compensation_history,
+    customer_surveys,
     customers,
     deals,
+    delivery_destinations,
+    delivery_transfers,
     departments,
+    dropbox_messages,
+    dunning_charges,
+    dunning_exemptions,
+    email_templates,
+    employee_documents,
     employees,
+    events,
+    export_templates,
+    grni_accrual_lines,
+    grni_accruals,
+    import_checkpoints,
+    invoice_lines,
+    invoice_payments,
+    invoice_sequences,
+    invoices,
+    journal_entries,
+    kpis,
     leads,
+    leave_requests,
+    legal_holds,
+    notifications,
+    payee_bank_accounts,
+    payment_batch_items,
+    payment_batches,
+    payment_promises,
     payrolls,
+    portal_links,
     product_attachments,
+    product_bundle_items,
+    product_bundles,
+    product_translations,
     products,
     purchase_items,
     purchase_orders,
+    quarantined_rows,
+    record_links,
+    remote_work_policies,
+    report_snapshots,
+    required_documents,
+    retention_runs,
+    revenue_recognitions,
+    revenue_schedules,
+    salary_bands,
+    salary_raises,
+    sales_order_items,
+    sales_orders,
+    sales_quotas,
+    sod_overrides,
+    sod_rules,
+    stock_adjustment_requests,
     stock_audit_items,
     stock_audits,
     stock_movements,
+    stock_movements_archive,
+    stock_reservations,
+    stock_write_down_lines,
+    stock_write_downs,
+    storage_snapshots,
+    summary_account_monthly,
+    summary_product_daily,
+    supplier_responses,
     suppliers,
     transactions,
+    user_preferences,
     users,
+    validation_rules,
 );
@@ -8,6 +8,9 @@ pub mod config;
 pub mod core;
 pub mod database;
 pub mod modules;
+pub mod server;
+#[cfg(any(test, feature = "test-support"))]
+pub mod testing;
 pub mod utils;
 
 // Re-export main components for easier access
@@ -1,19 +1,37 @@
+use clap::Parser;
 use clierp::cli::app::CLIApp;
+use clierp::core::command::{CLICommands, CLIArgs, SystemCommands};
+use clierp::modules::system::{print_checks, CheckStatus, DoctorService};
 use std::process;
 
 #[tokio::main]
 async fn main() {
+    // Parse arguments first so errors from startup honour --error-format
+    let args = CLIArgs::parse();
+    let error_format = args.error_format;
+
+    // The doctor diagnoses what makes startup fail, so it runs without it
+    if let Some(CLICommands::System { action: SystemCommands::Doctor }) = &args.command {
+        let checks = DoctorService::run();
+        print_checks(&checks);
+        if checks.iter().any(|c| c.status == CheckStatus::Failed) {
+            process::exit(1);
+        }
+        return;
+    }
+
     // Initialize and run the CLI application
     match CLIApp::new() {
         Ok(mut app) => {
-            if let Err(e) = app.run().await {
-                eprintln!("Error: {}", e);
-                process::exit(1);
+            let words: Vec<String> = std::env::args().skip(1).collect();
+            if let Err(e) = app.run_recorded(&words, args).await {
+                e.print(error_format, "Error");
+                process::exit(e.exit_code());
             }
         }
         Err(e) => {
-            eprintln!("Failed to initialize CLIERP: {}", e);
-            process::exit(1);
+            e.print(error_format, "Failed to initialize CLIERP");
+            process::exit(e.exit_code());
         }
     }
 }
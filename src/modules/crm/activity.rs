@@ -8,13 +8,31 @@ use crate::database::{
     DatabaseConnection, Activity, NewActivity, ActivityType, Customer, Lead, Employee
 };
 use crate::database::schema::{activities, customers, leads, employees};
+use crate::utils::cache::QueryCache;
 use crate::utils::validation::validate_required_string;
-use crate::utils::pagination::{Paginate, PaginationParams, PaginatedResult, PaginateResult};
+use crate::utils::pagination::{PaginationParams, PaginatedResult};
 use crate::utils::filters::FilterOptions;
+use crate::utils::filter_expr::FilterField;
+use crate::utils::timezone::DisplayTimezone;
+
+/// Fields `activity list --where` may use
+pub const ACTIVITY_WHERE_FIELDS: &[FilterField] = &[
+    FilterField::integer("id", "activities.id"),
+    FilterField::text("type", "activities.activity_type"),
+    FilterField::text("subject", "activities.subject"),
+    FilterField::integer("customer", "activities.customer_id"),
+    FilterField::integer("lead", "activities.lead_id"),
+    FilterField::integer("deal", "activities.deal_id"),
+    FilterField::integer("duration", "activities.duration_minutes"),
+    FilterField::integer("assigned_to", "activities.assigned_to"),
+    FilterField::boolean("completed", "activities.completed"),
+    FilterField::date("date", "activities.activity_date"),
+];
 
 pub struct ActivityService;
 
 impl ActivityService {
+    #[allow(clippy::too_many_arguments)]
     pub fn create_activity(
         conn: &mut DatabaseConnection,
         activity_type: ActivityType,
@@ -79,15 +97,12 @@ impl ActivityService {
         diesel::insert_into(activities::table)
             .values(&new_activity)
             .execute(conn)?;
+        QueryCache::current().invalidate(&["crm.activities"]);
 
-        // Get the inserted activity by searching for the most recent activity with matching criteria
+        // Get the inserted activity, the newest one; filtering on the
+        // customer, lead or assignee would miss it when they are NULL
         activities::table
-            .filter(activities::dsl::subject.eq(&new_activity.subject))
-            .filter(activities::dsl::activity_type.eq(&new_activity.activity_type))
-            .filter(activities::dsl::assigned_to.eq(new_activity.assigned_to))
-            .filter(activities::dsl::customer_id.eq(new_activity.customer_id))
-            .filter(activities::dsl::lead_id.eq(new_activity.lead_id))
-            .order(activities::dsl::created_at.desc())
+            .order(activities::dsl::id.desc())
             .first::<Activity>(conn)
             .map_err(Into::into)
     }
@@ -151,9 +166,9 @@ impl ActivityService {
         pagination: &PaginationParams,
     ) -> Result<PaginatedResult<ActivityWithDetails>> {
         let mut query = activities::table
-            .left_join(customers::table.on(customers::dsl::id.eq(activities::dsl::customer_id.nullable())))
-            .left_join(leads::table.on(leads::dsl::id.eq(activities::dsl::lead_id.nullable())))
-            .left_join(employees::table.on(employees::dsl::id.eq(activities::dsl::assigned_to.nullable())))
+            .left_join(customers::table.on(customers::dsl::id.nullable().eq(activities::dsl::customer_id)))
+            .left_join(leads::table.on(leads::dsl::id.nullable().eq(activities::dsl::lead_id)))
+            .left_join(employees::table.on(employees::dsl::id.nullable().eq(activities::dsl::assigned_to)))
             .select((
                 Activity::as_select(),
                 customers::all_columns.nullable(),
@@ -163,6 +178,10 @@ impl ActivityService {
             .into_boxed();
 
         // Apply filters
+        if let Some(expr) = &filters.where_clause {
+            query = query.filter(expr.predicate(ACTIVITY_WHERE_FIELDS)?);
+        }
+
         if let Some(search) = &filters.search {
             query = query.filter(
                 activities::dsl::subject.like(format!("%{}%", search))
@@ -187,14 +206,16 @@ impl ActivityService {
             query = query.filter(activities::dsl::assigned_to.eq(assigned_to));
         }
 
+        // Date filters are local business dates; activity dates are stored in UTC
+        let timezone = DisplayTimezone::current();
         if let Some(date_from) = filters.date_from {
-            let datetime_from = date_from.and_hms_opt(0, 0, 0).unwrap();
+            let datetime_from = timezone.day_start_utc(date_from);
             query = query.filter(activities::dsl::activity_date.ge(datetime_from));
         }
 
         if let Some(date_to) = filters.date_to {
-            let datetime_to = date_to.and_hms_opt(23, 59, 59).unwrap();
-            query = query.filter(activities::dsl::activity_date.le(datetime_to));
+            let datetime_to = timezone.day_start_utc(date_to + chrono::Duration::days(1));
+            query = query.filter(activities::dsl::activity_date.lt(datetime_to));
         }
 
         // Apply sorting
@@ -230,10 +251,10 @@ impl ActivityService {
             _ => query.order(activities::dsl::created_at.desc()),
         };
 
-        let results: Vec<(Activity, Option<Customer>, Option<Lead>, Option<String>)> = query
+        let results = query
             .offset(pagination.offset())
             .limit(pagination.limit())
-            .load(conn)?;
+            .load::<(Activity, Option<Customer>, Option<Lead>, Option<String>)>(conn)?;
 
         let total_items = activities::table.count().get_result::<i64>(conn)?;
 
@@ -250,6 +271,7 @@ impl ActivityService {
         Ok(PaginatedResult::new(activities_with_details, pagination, total_items))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn update_activity(
         conn: &mut DatabaseConnection,
         activity_id: i32,
@@ -282,12 +304,10 @@ impl ActivityService {
         }
 
         // Verify assigned employee exists if provided
-        if let Some(assigned_to_val) = assigned_to {
-            if let Some(employee_id) = assigned_to_val {
-                employees::table
-                    .find(employee_id)
-                    .first::<Employee>(conn)?;
-            }
+        if let Some(Some(employee_id)) = assigned_to {
+            employees::table
+                .find(employee_id)
+                .first::<Employee>(conn)?;
         }
 
         // Build update queries - apply updates individually for simplicity
@@ -333,6 +353,7 @@ impl ActivityService {
         diesel::update(activities::table.find(activity_id))
             .set(dsl::updated_at.eq(Utc::now().naive_utc()))
             .execute(conn)?;
+        QueryCache::current().invalidate(&["crm.activities"]);
 
         // Get the updated activity
         activities::table
@@ -353,6 +374,7 @@ impl ActivityService {
                 activities::dsl::updated_at.eq(Utc::now().naive_utc()),
             ))
             .execute(conn)?;
+        QueryCache::current().invalidate(&["crm.activities"]);
 
         // Get the updated activity
         activities::table
@@ -368,10 +390,11 @@ impl ActivityService {
         diesel::update(activities::table.find(activity_id))
             .set((
                 activities::dsl::completed.eq(false),
-                activities::dsl::outcome.eq(None::<Option<String>>),
+                activities::dsl::outcome.eq(None::<String>),
                 activities::dsl::updated_at.eq(Utc::now().naive_utc()),
             ))
             .execute(conn)?;
+        QueryCache::current().invalidate(&["crm.activities"]);
 
         // Get the updated activity
         activities::table
@@ -383,6 +406,7 @@ impl ActivityService {
     pub fn delete_activity(conn: &mut DatabaseConnection, activity_id: i32) -> Result<bool> {
         let deleted_rows = diesel::delete(activities::table.find(activity_id))
             .execute(conn)?;
+        QueryCache::current().invalidate(&["crm.activities"]);
 
         Ok(deleted_rows > 0)
     }
@@ -394,7 +418,7 @@ impl ActivityService {
         let results: Vec<(Activity, Option<Customer>, Option<Lead>, String)> = activities::table
             .left_join(customers::table)
             .left_join(leads::table)
-            .inner_join(employees::table.on(employees::dsl::id.eq(activities::dsl::assigned_to)))
+            .inner_join(employees::table.on(employees::dsl::id.nullable().eq(activities::dsl::assigned_to)))
             .filter(activities::dsl::customer_id.eq(customer_id))
             .select((
                 Activity::as_select(),
@@ -425,7 +449,7 @@ impl ActivityService {
         let results: Vec<(Activity, Option<Customer>, Option<Lead>, String)> = activities::table
             .left_join(customers::table)
             .left_join(leads::table)
-            .inner_join(employees::table.on(employees::dsl::id.eq(activities::dsl::assigned_to)))
+            .inner_join(employees::table.on(employees::dsl::id.nullable().eq(activities::dsl::assigned_to)))
             .filter(activities::dsl::lead_id.eq(lead_id))
             .select((
                 Activity::as_select(),
@@ -456,7 +480,7 @@ impl ActivityService {
         let results: Vec<(Activity, Option<Customer>, Option<Lead>, String)> = activities::table
             .left_join(customers::table)
             .left_join(leads::table)
-            .inner_join(employees::table.on(employees::dsl::id.eq(activities::dsl::assigned_to)))
+            .inner_join(employees::table.on(employees::dsl::id.nullable().eq(activities::dsl::assigned_to)))
             .filter(activities::dsl::assigned_to.eq(employee_id))
             .select((
                 Activity::as_select(),
@@ -464,7 +488,7 @@ impl ActivityService {
                 leads::all_columns.nullable(),
                 employees::name,
             ))
-            .order(activities::dsl::activity_date.asc().nulls_last())
+            .order(activities::dsl::activity_date.asc())
             .load(conn)?;
 
         let activities_with_details: Vec<ActivityWithDetails> = results
@@ -486,7 +510,7 @@ impl ActivityService {
         let results: Vec<(Activity, Option<Customer>, Option<Lead>, String)> = activities::table
             .left_join(customers::table)
             .left_join(leads::table)
-            .inner_join(employees::table.on(employees::dsl::id.eq(activities::dsl::assigned_to)))
+            .inner_join(employees::table.on(employees::dsl::id.nullable().eq(activities::dsl::assigned_to)))
             .filter(activities::dsl::completed.eq(false))
             .filter(activities::dsl::activity_date.lt(now))
             .select((
@@ -511,7 +535,12 @@ impl ActivityService {
         Ok(activities_with_details)
     }
 
+    /// Cached in the query cache until a change invalidates it or the TTL runs out
     pub fn get_activity_statistics(conn: &mut DatabaseConnection) -> Result<ActivityStatistics> {
+        QueryCache::current().get_or_compute("crm.activities.statistics", || Self::compute_activity_statistics(conn))
+    }
+
+    fn compute_activity_statistics(conn: &mut DatabaseConnection) -> Result<ActivityStatistics> {
         // Total activities count
         let total_activities = activities::table
             .count()
@@ -580,7 +609,7 @@ pub struct ActivityWithDetails {
     pub assigned_employee: String,
 }
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct ActivityStatistics {
     pub total_activities: i64,
     pub pending_activities: i64,
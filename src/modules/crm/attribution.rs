@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{campaign_leads, campaigns, deals, leads};
+use crate::database::{CampaignLead, CampaignResponse, DealStage, NewCampaignLead};
+use crate::utils::cache::QueryCache;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Where a lead came from: a campaign that reached it, or its lead source
+/// when no campaign did
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Touch {
+    pub campaign_id: Option<i32>,
+    /// Campaign name, or the lead source
+    pub channel: String,
+    pub touched_at: NaiveDateTime,
+}
+
+/// First and last touch of a won deal
+#[derive(Debug, Clone, Serialize)]
+pub struct DealAttribution {
+    pub deal_id: i32,
+    pub deal_name: String,
+    pub closed_on: NaiveDate,
+    pub revenue: i64,
+    pub touches: usize,
+    pub first_touch: Touch,
+    pub last_touch: Touch,
+}
+
+/// Won revenue credited to one campaign or lead source
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChannelAttribution {
+    pub campaign_id: Option<i32>,
+    pub channel: String,
+    /// Campaign spend; `None` for lead sources
+    pub spent: Option<i64>,
+    pub first_touch_deals: usize,
+    pub first_touch_revenue: i64,
+    pub last_touch_deals: usize,
+    pub last_touch_revenue: i64,
+}
+
+impl ChannelAttribution {
+    /// Last-touch revenue per unit of spend, as a percentage return
+    pub fn roi(&self) -> Option<f64> {
+        self.spent
+            .filter(|spent| *spent > 0)
+            .map(|spent| (self.last_touch_revenue - spent) as f64 / spent as f64 * 100.0)
+    }
+}
+
+/// Marketing attribution of won deals.
+///
+/// A lead is touched by its lead source when it is created (the campaign
+/// of that name, if there is one) and by every campaign it was added to
+/// (`campaign_leads`, at the response date when there is one). Touches
+/// after a deal closed do not count.
+pub struct AttributionService;
+
+impl AttributionService {
+    /// Record that a campaign reached a lead, or update its response
+    pub fn record_touch(
+        conn: &mut SqliteConnection,
+        campaign_id: i32,
+        lead_id: i32,
+        response: Option<CampaignResponse>,
+    ) -> Result<CampaignLead> {
+        let campaign_exists = campaigns::table
+            .find(campaign_id)
+            .count()
+            .get_result::<i64>(conn)?
+            > 0;
+        if !campaign_exists {
+            return Err(CLIERPError::NotFound(format!("Campaign {} not found", campaign_id)));
+        }
+        let lead_exists = leads::table.find(lead_id).count().get_result::<i64>(conn)? > 0;
+        if !lead_exists {
+            return Err(CLIERPError::NotFound(format!("Lead {} not found", lead_id)));
+        }
+
+        let response_date = response.as_ref().map(|_| Utc::now().naive_utc());
+        let response = response.map(|r| r.to_string());
+        let existing = campaign_leads::table
+            .filter(campaign_leads::campaign_id.eq(campaign_id))
+            .filter(campaign_leads::lead_id.eq(lead_id))
+            .first::<CampaignLead>(conn)
+            .optional()?;
+        match existing {
+            Some(existing) => {
+                if response.is_some() {
+                    diesel::update(campaign_leads::table.find(existing.id))
+                        .set((
+                            campaign_leads::response.eq(&response),
+                            campaign_leads::response_date.eq(response_date),
+                        ))
+                        .execute(conn)?;
+                }
+            }
+            None => {
+                diesel::insert_into(campaign_leads::table)
+                    .values(&NewCampaignLead { campaign_id, lead_id, response, response_date })
+                    .execute(conn)?;
+            }
+        }
+        QueryCache::current().invalidate(&["crm.campaigns"]);
+
+        Ok(campaign_leads::table
+            .filter(campaign_leads::campaign_id.eq(campaign_id))
+            .filter(campaign_leads::lead_id.eq(lead_id))
+            .first::<CampaignLead>(conn)?)
+    }
+
+    /// Deals won between `start` and `end` with their first and last touch,
+    /// latest first
+    pub fn deals(conn: &mut SqliteConnection, start: NaiveDate, end: NaiveDate) -> Result<Vec<DealAttribution>> {
+        let won = deals::table
+            .inner_join(leads::table)
+            .filter(deals::stage.eq(DealStage::ClosedWon.to_string()))
+            .select((
+                deals::id,
+                deals::deal_name,
+                deals::deal_value,
+                deals::final_amount,
+                deals::close_date,
+                deals::updated_at,
+                leads::id,
+                leads::lead_source,
+                leads::created_at,
+            ))
+            .load::<(i32, String, i32, Option<i32>, Option<NaiveDate>, NaiveDateTime, i32, String, NaiveDateTime)>(conn)?;
+        let won: Vec<_> = won
+            .into_iter()
+            .filter_map(|(id, name, value, final_amount, close_date, updated_at, lead_id, source, lead_created)| {
+                let closed_on = close_date.unwrap_or(updated_at.date());
+                (closed_on >= start && closed_on <= end)
+                    .then_some((id, name, i64::from(final_amount.unwrap_or(value)), closed_on, lead_id, source, lead_created))
+            })
+            .collect();
+
+        let lead_ids: Vec<i32> = won.iter().map(|deal| deal.4).collect();
+        let campaign_names: HashMap<String, i32> = campaigns::table
+            .select((campaigns::name, campaigns::id))
+            .load::<(String, i32)>(conn)?
+            .into_iter()
+            .collect();
+        let campaign_ids: HashMap<i32, String> = campaign_names.iter().map(|(name, id)| (*id, name.clone())).collect();
+        let mut touches: HashMap<i32, Vec<Touch>> = HashMap::new();
+        for touch in campaign_leads::table
+            .filter(campaign_leads::lead_id.eq_any(&lead_ids))
+            .load::<CampaignLead>(conn)?
+        {
+            let Some(name) = campaign_ids.get(&touch.campaign_id) else { continue };
+            touches.entry(touch.lead_id).or_default().push(Touch {
+                campaign_id: Some(touch.campaign_id),
+                channel: name.clone(),
+                touched_at: touch.response_date.unwrap_or(touch.created_at),
+            });
+        }
+
+        let mut attributed: Vec<DealAttribution> = won
+            .into_iter()
+            .filter_map(|(deal_id, deal_name, revenue, closed_on, lead_id, source, lead_created)| {
+                let mut lead_touches = touches.remove(&lead_id).unwrap_or_default();
+                let already_touched = lead_touches.iter().any(|t| t.channel == source);
+                if !already_touched {
+                    lead_touches.push(Touch {
+                        campaign_id: campaign_names.get(&source).copied(),
+                        channel: source,
+                        touched_at: lead_created,
+                    });
+                }
+                let closed_at = closed_on.succ_opt()?.and_hms_opt(0, 0, 0)?;
+                let (first_touch, last_touch, count) = first_and_last(&lead_touches, closed_at)?;
+                Some(DealAttribution { deal_id, deal_name, closed_on, revenue, touches: count, first_touch, last_touch })
+            })
+            .collect();
+        attributed.sort_by(|a, b| b.closed_on.cmp(&a.closed_on).then(b.deal_id.cmp(&a.deal_id)));
+        Ok(attributed)
+    }
+
+    /// Won revenue per campaign and lead source under both models, by
+    /// last-touch revenue
+    pub fn by_channel(conn: &mut SqliteConnection, start: NaiveDate, end: NaiveDate) -> Result<Vec<ChannelAttribution>> {
+        let deals = Self::deals(conn, start, end)?;
+        let spent: HashMap<i32, i64> = campaigns::table
+            .select((campaigns::id, campaigns::spent))
+            .load::<(i32, Option<i32>)>(conn)?
+            .into_iter()
+            .map(|(id, spent)| (id, i64::from(spent.unwrap_or(0))))
+            .collect();
+        let mut channels = credit_channels(&deals);
+        for channel in &mut channels {
+            channel.spent = channel.campaign_id.and_then(|id| spent.get(&id).copied());
+        }
+        Ok(channels)
+    }
+}
+
+/// Earliest and latest touch before `closed_at`, and how many touches there
+/// were; `None` when nothing touched the lead before the deal closed
+fn first_and_last(touches: &[Touch], closed_at: NaiveDateTime) -> Option<(Touch, Touch, usize)> {
+    let mut before: Vec<&Touch> = touches.iter().filter(|t| t.touched_at < closed_at).collect();
+    before.sort_by(|a, b| a.touched_at.cmp(&b.touched_at).then(a.channel.cmp(&b.channel)));
+    let first = (*before.first()?).clone();
+    let last = (*before.last()?).clone();
+    Some((first, last, before.len()))
+}
+
+/// Credit each deal's revenue to its first-touch and its last-touch channel
+fn credit_channels(deals: &[DealAttribution]) -> Vec<ChannelAttribution> {
+    let mut channels: HashMap<String, ChannelAttribution> = HashMap::new();
+    for deal in deals {
+        let first = channels.entry(deal.first_touch.channel.clone()).or_insert_with(|| ChannelAttribution {
+            campaign_id: deal.first_touch.campaign_id,
+            channel: deal.first_touch.channel.clone(),
+            ..Default::default()
+        });
+        first.first_touch_deals += 1;
+        first.first_touch_revenue += deal.revenue;
+
+        let last = channels.entry(deal.last_touch.channel.clone()).or_insert_with(|| ChannelAttribution {
+            campaign_id: deal.last_touch.campaign_id,
+            channel: deal.last_touch.channel.clone(),
+            ..Default::default()
+        });
+        last.last_touch_deals += 1;
+        last.last_touch_revenue += deal.revenue;
+    }
+    let mut channels: Vec<ChannelAttribution> = channels.into_values().collect();
+    channels.sort_by(|a, b| {
+        b.last_touch_revenue
+            .cmp(&a.last_touch_revenue)
+            .then(b.first_touch_revenue.cmp(&a.first_touch_revenue))
+            .then(a.channel.cmp(&b.channel))
+    });
+    channels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(campaign_id: Option<i32>, channel: &str, at: &str) -> Touch {
+        Touch {
+            campaign_id,
+            channel: channel.to_string(),
+            touched_at: NaiveDate::parse_from_str(at, "%Y-%m-%d").unwrap().and_hms_opt(9, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_first_and_last_touch_credit() {
+        let closed_at = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let touches = vec![
+            touch(Some(2), "Webinar", "2024-04-10"),
+            touch(None, "referral", "2024-03-01"),
+            touch(Some(3), "Trade Show", "2024-06-15"),
+        ];
+        let (first, last, count) = first_and_last(&touches, closed_at).unwrap();
+        assert_eq!(first.channel, "referral");
+        // The trade show came after the deal closed
+        assert_eq!(last.channel, "Webinar");
+        assert_eq!(count, 2);
+        assert!(first_and_last(&touches[2..], closed_at).is_none());
+
+        let deal = |id, revenue, first: &Touch, last: &Touch| DealAttribution {
+            deal_id: id,
+            deal_name: format!("Deal {}", id),
+            closed_on: closed_at.date(),
+            revenue,
+            touches: 2,
+            first_touch: first.clone(),
+            last_touch: last.clone(),
+        };
+        let channels = credit_channels(&[
+            deal(1, 1000, &touches[1], &touches[0]),
+            deal(2, 500, &touches[0], &touches[0]),
+        ]);
+        assert_eq!(channels[0].channel, "Webinar");
+        assert_eq!((channels[0].first_touch_revenue, channels[0].last_touch_revenue), (500, 1500));
+        assert_eq!((channels[1].first_touch_deals, channels[1].last_touch_deals), (1, 0));
+
+        let campaign = ChannelAttribution { spent: Some(500), last_touch_revenue: 1500, ..Default::default() };
+        assert_eq!(campaign.roi(), Some(200.0));
+    }
+}
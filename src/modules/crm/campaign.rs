@@ -7,14 +7,30 @@ type Result<T> = CLIERPResult<T>;
 use crate::database::{
     DatabaseConnection, Campaign, NewCampaign, CampaignStatus, CampaignType, CampaignWithStats
 };
-use crate::database::schema::{campaigns, leads, customers};
+use crate::database::schema::{campaigns, leads};
+use crate::utils::cache::QueryCache;
 use crate::utils::validation::validate_required_string;
-use crate::utils::pagination::{Paginate, PaginationParams, PaginatedResult, paginate_query};
+use crate::utils::pagination::{PaginationParams, PaginatedResult};
 use crate::utils::filters::FilterOptions;
+use crate::utils::filter_expr::FilterField;
+
+/// Fields `campaign list --where` may use
+pub const CAMPAIGN_WHERE_FIELDS: &[FilterField] = &[
+    FilterField::integer("id", "campaigns.id"),
+    FilterField::text("name", "campaigns.name"),
+    FilterField::text("type", "campaigns.campaign_type"),
+    FilterField::text("status", "campaigns.status"),
+    FilterField::integer("budget", "campaigns.budget"),
+    FilterField::integer("spent", "campaigns.spent"),
+    FilterField::date("start_date", "campaigns.start_date"),
+    FilterField::date("end_date", "campaigns.end_date"),
+    FilterField::date("created", "campaigns.created_at"),
+];
 
 pub struct CampaignService;
 
 impl CampaignService {
+    #[allow(clippy::too_many_arguments)]
     pub fn create_campaign(
         conn: &mut DatabaseConnection,
         name: &str,
@@ -24,7 +40,7 @@ impl CampaignService {
         end_date: Option<NaiveDate>,
         budget: Option<i32>,
         target_audience: Option<&str>,
-        goals: Option<&str>,
+        _goals: Option<&str>,
     ) -> Result<Campaign> {
         // Validate input
         validate_required_string(name, "name")?;
@@ -55,14 +71,11 @@ impl CampaignService {
             }
         }
 
-        // Generate campaign code
-        let campaign_code = Self::generate_campaign_code(conn)?;
-
         // Create new campaign
         let new_campaign = NewCampaign {
             name: name.to_string(),
             campaign_type: campaign_type.to_string(),
-            status: CampaignStatus::Draft.to_string(),
+            status: CampaignStatus::Planned.to_string(),
             description: description.map(|s| s.to_string()),
             start_date: Some(start_date),
             end_date,
@@ -76,6 +89,7 @@ impl CampaignService {
         diesel::insert_into(campaigns::table)
             .values(&new_campaign)
             .execute(conn)?;
+        QueryCache::current().invalidate(&["crm.campaigns"]);
 
         // Get the inserted campaign by name since SQLite doesn't support RETURNING
         campaigns::table
@@ -125,13 +139,6 @@ impl CampaignService {
                 0.0
             };
 
-            // Calculate cost per lead
-            let cost_per_lead = if total_leads > 0 && campaign.spent.unwrap_or(0) > 0 {
-                campaign.spent.unwrap_or(0) as f64 / total_leads as f64
-            } else {
-                0.0
-            };
-
             // Calculate ROI (placeholder - would need revenue tracking)
             let roi = 0.0; // TODO: Implement when revenue tracking is available
 
@@ -153,31 +160,8 @@ impl CampaignService {
         filters: &FilterOptions,
         pagination: &PaginationParams,
     ) -> Result<PaginatedResult<Campaign>> {
-        let mut query = campaigns::table.into_boxed();
-
-        // Apply filters
-        if let Some(search) = &filters.search {
-            query = query.filter(
-                campaigns::dsl::name.like(format!("%{}%", search))
-                    .or(campaigns::dsl::description.like(format!("%{}%", search)))
-            );
-        }
-
-        if let Some(status_filter) = &filters.status {
-            query = query.filter(campaigns::dsl::status.eq(status_filter));
-        }
-
-        if let Some(type_filter) = &filters.filter_type {
-            query = query.filter(campaigns::dsl::campaign_type.eq(type_filter));
-        }
-
-        if let Some(date_from) = filters.date_from {
-            query = query.filter(campaigns::dsl::start_date.ge(date_from));
-        }
-
-        if let Some(date_to) = filters.date_to {
-            query = query.filter(campaigns::dsl::end_date.le(date_to));
-        }
+        let total = filtered_campaigns(filters)?.count().get_result::<i64>(conn)?;
+        let mut query = filtered_campaigns(filters)?;
 
         // Apply sorting
         query = match filters.sort_by.as_deref() {
@@ -226,9 +210,15 @@ impl CampaignService {
             _ => query.order(campaigns::dsl::created_at.desc()),
         };
 
-        query.paginate_result(pagination, conn)
+        let data = query
+            .offset(pagination.offset())
+            .limit(pagination.limit())
+            .load::<Campaign>(conn)?;
+
+        Ok(PaginatedResult::new(data, pagination, total))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn update_campaign(
         conn: &mut DatabaseConnection,
         campaign_id: i32,
@@ -317,6 +307,7 @@ impl CampaignService {
         diesel::update(campaigns::table.find(campaign_id))
             .set(dsl::updated_at.eq(Utc::now().naive_utc()))
             .execute(conn)?;
+        QueryCache::current().invalidate(&["crm.campaigns"]);
 
         // Get the updated campaign
         campaigns::table
@@ -336,6 +327,7 @@ impl CampaignService {
                 campaigns::dsl::updated_at.eq(Utc::now().naive_utc()),
             ))
             .execute(conn)?;
+        QueryCache::current().invalidate(&["crm.campaigns"]);
 
         // Get the updated campaign
         campaigns::table
@@ -362,6 +354,7 @@ impl CampaignService {
 
         let deleted_rows = diesel::delete(campaigns::table.find(campaign_id))
             .execute(conn)?;
+        QueryCache::current().invalidate(&["crm.campaigns"]);
 
         Ok(deleted_rows > 0)
     }
@@ -392,7 +385,12 @@ impl CampaignService {
             .map_err(Into::into)
     }
 
+    /// Cached in the query cache until a change invalidates it or the TTL runs out
     pub fn get_campaign_performance(conn: &mut DatabaseConnection) -> Result<Vec<CampaignPerformance>> {
+        QueryCache::current().get_or_compute("crm.campaigns.performance", || Self::compute_campaign_performance(conn))
+    }
+
+    fn compute_campaign_performance(conn: &mut DatabaseConnection) -> Result<Vec<CampaignPerformance>> {
         let campaigns: Vec<Campaign> = campaigns::table
             .filter(campaigns::dsl::status.ne(CampaignStatus::Draft.to_string()))
             .order(campaigns::dsl::created_at.desc())
@@ -470,17 +468,44 @@ impl CampaignService {
             total_actual_cost: total_actual_cost.unwrap_or(0) as i32,
         })
     }
+}
 
-    fn generate_campaign_code(conn: &mut DatabaseConnection) -> Result<String> {
-        let count = campaigns::table
-            .count()
-            .get_result::<i64>(conn)?;
+/// Campaigns matching the list filters, in no particular order
+fn filtered_campaigns(filters: &FilterOptions) -> Result<campaigns::BoxedQuery<'_, diesel::sqlite::Sqlite>> {
+    let mut query = campaigns::table.into_boxed();
 
-        Ok(format!("CAMP{:06}", count + 1))
+    // Apply filters
+    if let Some(expr) = &filters.where_clause {
+        query = query.filter(expr.predicate(CAMPAIGN_WHERE_FIELDS)?);
     }
+
+    if let Some(search) = &filters.search {
+        query = query.filter(
+            campaigns::dsl::name.like(format!("%{}%", search))
+                .or(campaigns::dsl::description.like(format!("%{}%", search)))
+        );
+    }
+
+    if let Some(status_filter) = &filters.status {
+        query = query.filter(campaigns::dsl::status.eq(status_filter));
+    }
+
+    if let Some(type_filter) = &filters.filter_type {
+        query = query.filter(campaigns::dsl::campaign_type.eq(type_filter));
+    }
+
+    if let Some(date_from) = filters.date_from {
+        query = query.filter(campaigns::dsl::start_date.ge(date_from));
+    }
+
+    if let Some(date_to) = filters.date_to {
+        query = query.filter(campaigns::dsl::end_date.le(date_to));
+    }
+
+    Ok(query)
 }
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct CampaignPerformance {
     pub campaign_id: i32,
     pub campaign_code: String,
@@ -504,4 +529,40 @@ pub struct CampaignStatistics {
     pub completed_campaigns: i64,
     pub total_budget: i32,
     pub total_actual_cost: i32,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::CompanyFixture;
+    use crate::utils::filter_expr::parse_filter_expr_arg;
+
+    #[test]
+    fn test_list_campaigns() {
+        let company = CompanyFixture::new().build().unwrap();
+        let mut conn = company.connection().unwrap();
+        let start = NaiveDate::from_ymd_opt(2025, 1, 6).unwrap();
+        for (name, campaign_type, budget) in [
+            ("Spring mailing", CampaignType::Email, 500_000),
+            ("Trade fair", CampaignType::Event, 2_000_000),
+            ("Search ads", CampaignType::Advertising, 1_200_000),
+        ] {
+            CampaignService::create_campaign(&mut conn, name, campaign_type, None, start, None, Some(budget), None, None)
+                .unwrap();
+        }
+
+        let filters = FilterOptions { sort_by: Some("name".to_string()), ..Default::default() };
+        let page = CampaignService::list_campaigns(&mut conn, &filters, &PaginationParams::new(1, 2)).unwrap();
+        let names: Vec<&str> = page.data.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Search ads", "Spring mailing"]);
+        assert_eq!((page.pagination.total_count, page.pagination.total_pages), (3, 2));
+
+        let filters = FilterOptions {
+            where_clause: Some(parse_filter_expr_arg("budget >= 1000000").unwrap()),
+            ..filters
+        };
+        let page = CampaignService::list_campaigns(&mut conn, &filters, &PaginationParams::new(1, 20)).unwrap();
+        let names: Vec<&str> = page.data.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Search ads", "Trade fair"]);
+        assert_eq!(page.pagination.total_count, 2);
+    }
+}
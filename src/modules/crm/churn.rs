@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::core::config::ChurnConfig;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::models::{InvoiceKind, InvoiceStatus};
+use crate::database::schema::{activities, churn_alerts, customer_surveys, customers, deals, employees, invoices, leads, users};
+use crate::database::{Customer, CustomerStatus, NewChurnAlert};
+use crate::modules::system::notifications::NotificationService;
+use crate::utils::formatting::format_currency;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Survey scores at or below this are complaints until followed up
+const DETRACTOR_SCORE: i32 = 6;
+
+/// Thresholds a customer is judged against, from configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChurnPolicy {
+    pub inactive_days: i64,
+    pub window_days: i64,
+    pub decline_percent: u32,
+    pub renotify_days: i64,
+}
+
+impl ChurnPolicy {
+    pub fn from_config(config: &ChurnConfig) -> Result<Self> {
+        if config.inactive_days <= 0 || config.window_days <= 0 || config.renotify_days < 0 {
+            return Err(CLIERPError::Configuration(config::ConfigError::Message(
+                "churn.inactive_days and churn.window_days must be positive and churn.renotify_days not negative".to_string(),
+            )));
+        }
+        if config.decline_percent == 0 || config.decline_percent > 100 {
+            return Err(CLIERPError::Configuration(config::ConfigError::Message(
+                "churn.decline_percent must be between 1 and 100".to_string(),
+            )));
+        }
+        Ok(Self {
+            inactive_days: config.inactive_days,
+            window_days: config.window_days,
+            decline_percent: config.decline_percent,
+            renotify_days: config.renotify_days,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum ChurnSignal {
+    /// Days since the last order
+    Inactive(i64),
+    /// Drop in order value from the previous window, in percent
+    DecliningValue(u32),
+    /// Detractor survey answers not yet followed up
+    OpenComplaints(usize),
+}
+
+impl std::fmt::Display for ChurnSignal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChurnSignal::Inactive(days) => write!(f, "no orders in {} days", days),
+            ChurnSignal::DecliningValue(percent) => write!(f, "order value down {}%", percent),
+            ChurnSignal::OpenComplaints(count) => write!(f, "{} open complaint(s)", count),
+        }
+    }
+}
+
+/// A customer showing at least one churn signal
+#[derive(Debug, Clone, Serialize)]
+pub struct AtRiskCustomer {
+    pub customer: Customer,
+    /// Employee ID and name of the rep on the customer's latest deal or lead
+    pub owner: Option<(i32, String)>,
+    pub last_order: Option<NaiveDate>,
+    /// Order value in the latest window and the one before it
+    pub recent_value: i64,
+    pub prior_value: i64,
+    pub signals: Vec<ChurnSignal>,
+    pub next_action: &'static str,
+}
+
+/// Churn detection over active customers that have ordered before.
+///
+/// Orders are issued receivable invoices. Complaints are detractor survey
+/// answers (0-6) of the last window with no completed activity logged for
+/// the customer since.
+pub struct ChurnService;
+
+impl ChurnService {
+    /// Customers at risk as of a date, most signals and then largest
+    /// previous order value first
+    pub fn at_risk(conn: &mut SqliteConnection, policy: &ChurnPolicy, as_of: NaiveDate) -> Result<Vec<AtRiskCustomer>> {
+        let customers = customers::table
+            .filter(customers::status.eq(CustomerStatus::Active.to_string()))
+            .load::<Customer>(conn)?;
+        let ids: Vec<i32> = customers.iter().map(|c| c.id).collect();
+
+        let orders = invoices::table
+            .filter(invoices::customer_id.eq_any(&ids))
+            .filter(invoices::kind.eq(InvoiceKind::Receivable.to_string()))
+            .filter(invoices::status.ne_all(vec![
+                InvoiceStatus::Draft.to_string(),
+                InvoiceStatus::Cancelled.to_string(),
+            ]))
+            .filter(invoices::invoice_date.le(as_of))
+            .select((invoices::customer_id, invoices::invoice_date, invoices::total_amount))
+            .load::<(Option<i32>, NaiveDate, i32)>(conn)?;
+        let recent_from = as_of - Duration::days(policy.window_days);
+        let prior_from = recent_from - Duration::days(policy.window_days);
+        // (last order, recent value, prior value)
+        let mut history: HashMap<i32, (NaiveDate, i64, i64)> = HashMap::new();
+        for (customer_id, date, amount) in orders {
+            let Some(customer_id) = customer_id else { continue };
+            let entry = history.entry(customer_id).or_insert((date, 0, 0));
+            entry.0 = entry.0.max(date);
+            if date > recent_from {
+                entry.1 += i64::from(amount);
+            } else if date > prior_from {
+                entry.2 += i64::from(amount);
+            }
+        }
+
+        let complaints = Self::open_complaints(conn, &ids, recent_from, as_of)?;
+        let owners = Self::owners(conn, &ids)?;
+
+        let mut at_risk: Vec<AtRiskCustomer> = customers
+            .into_iter()
+            .filter_map(|customer| {
+                let (last_order, recent_value, prior_value) = *history.get(&customer.id)?;
+                let signals = signals(
+                    policy,
+                    as_of,
+                    last_order,
+                    recent_value,
+                    prior_value,
+                    complaints.get(&customer.id).copied().unwrap_or(0),
+                );
+                if signals.is_empty() {
+                    return None;
+                }
+                Some(AtRiskCustomer {
+                    owner: owners.get(&customer.id).cloned(),
+                    customer,
+                    last_order: Some(last_order),
+                    recent_value,
+                    prior_value,
+                    next_action: next_action(&signals),
+                    signals,
+                })
+            })
+            .collect();
+        at_risk.sort_by(|a, b| {
+            b.signals
+                .len()
+                .cmp(&a.signals.len())
+                .then(b.prior_value.cmp(&a.prior_value))
+                .then(a.customer.name.cmp(&b.customer.name))
+        });
+        Ok(at_risk)
+    }
+
+    /// Notify the owners of customers at risk, except those alerted within
+    /// the renotify period; returns the customers alerted and whether an
+    /// owner was notified
+    pub fn alert(conn: &mut SqliteConnection, policy: &ChurnPolicy, as_of: NaiveDate) -> Result<Vec<(AtRiskCustomer, bool)>> {
+        let at_risk = Self::at_risk(conn, policy, as_of)?;
+        let since = as_of - Duration::days(policy.renotify_days);
+        let recently_alerted: Vec<i32> = churn_alerts::table
+            .filter(churn_alerts::alerted_on.gt(since))
+            .select(churn_alerts::customer_id)
+            .load::<i32>(conn)?;
+        let employee_ids: Vec<i32> = at_risk.iter().filter_map(|c| c.owner.as_ref().map(|o| o.0)).collect();
+        let owner_users: HashMap<i32, i32> = users::table
+            .filter(users::employee_id.eq_any(employee_ids))
+            .filter(users::is_active.eq(true))
+            .select((users::employee_id.assume_not_null(), users::id))
+            .load::<(i32, i32)>(conn)?
+            .into_iter()
+            .collect();
+
+        let mut alerted = Vec::new();
+        for customer in at_risk {
+            if recently_alerted.contains(&customer.customer.id) {
+                continue;
+            }
+            let user_id = customer.owner.as_ref().and_then(|(employee_id, _)| owner_users.get(employee_id).copied());
+            let signals = customer.signals.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(", ");
+            conn.transaction::<_, CLIERPError, _>(|conn| {
+                diesel::insert_into(churn_alerts::table)
+                    .values(&NewChurnAlert {
+                        customer_id: customer.customer.id,
+                        user_id,
+                        signals: signals.clone(),
+                        alerted_on: as_of,
+                    })
+                    .execute(conn)?;
+                if let Some(user_id) = user_id {
+                    NotificationService::notify(
+                        conn,
+                        user_id,
+                        &format!("{} is at risk of churning", customer.customer.name),
+                        Some(&format!(
+                            "{} ({}): {}. Ordered {} in the last {} days against {} before. Next: {}.",
+                            customer.customer.name,
+                            customer.customer.customer_code,
+                            signals,
+                            format_currency(customer.recent_value),
+                            policy.window_days,
+                            format_currency(customer.prior_value),
+                            customer.next_action
+                        )),
+                    )?;
+                }
+                Ok(())
+            })?;
+            alerted.push((customer, user_id.is_some()));
+        }
+        tracing::info!("Raised {} churn alert(s) as of {}", alerted.len(), as_of);
+        Ok(alerted)
+    }
+
+    /// Detractor answers since `from` per customer with no completed
+    /// activity logged after them
+    fn open_complaints(
+        conn: &mut SqliteConnection,
+        customer_ids: &[i32],
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<HashMap<i32, usize>> {
+        let detractors = customer_surveys::table
+            .filter(customer_surveys::customer_id.eq_any(customer_ids))
+            .filter(customer_surveys::score.le(DETRACTOR_SCORE))
+            .filter(customer_surveys::survey_date.gt(from))
+            .filter(customer_surveys::survey_date.le(to))
+            .select((customer_surveys::customer_id, customer_surveys::survey_date))
+            .load::<(i32, NaiveDate)>(conn)?;
+        let follow_ups: HashMap<i32, NaiveDateTime> = activities::table
+            .filter(activities::customer_id.eq_any(customer_ids))
+            .filter(activities::completed.eq(true))
+            .group_by(activities::customer_id)
+            .select((activities::customer_id.assume_not_null(), diesel::dsl::max(activities::activity_date)))
+            .load::<(i32, Option<NaiveDateTime>)>(conn)?
+            .into_iter()
+            .filter_map(|(customer_id, latest)| Some((customer_id, latest?)))
+            .collect();
+
+        let mut open: HashMap<i32, usize> = HashMap::new();
+        for (customer_id, date) in detractors {
+            let followed_up = follow_ups.get(&customer_id).is_some_and(|latest| latest.date() >= date);
+            if !followed_up {
+                *open.entry(customer_id).or_default() += 1;
+            }
+        }
+        Ok(open)
+    }
+
+    /// Rep of each customer's latest deal, or of its latest lead when no
+    /// deal has one
+    fn owners(conn: &mut SqliteConnection, customer_ids: &[i32]) -> Result<HashMap<i32, (i32, String)>> {
+        let deal_reps = deals::table
+            .inner_join(leads::table)
+            .inner_join(employees::table)
+            .filter(leads::customer_id.eq_any(customer_ids))
+            .order(deals::updated_at.asc())
+            .select((leads::customer_id.assume_not_null(), employees::id, employees::name))
+            .load::<(i32, i32, String)>(conn)?;
+        let lead_reps = leads::table
+            .inner_join(employees::table.on(leads::assigned_to.eq(employees::id.nullable())))
+            .filter(leads::customer_id.eq_any(customer_ids))
+            .order(leads::updated_at.asc())
+            .select((leads::customer_id.assume_not_null(), employees::id, employees::name))
+            .load::<(i32, i32, String)>(conn)?;
+
+        let mut owners: HashMap<i32, (i32, String)> = HashMap::new();
+        for (customer_id, employee_id, name) in lead_reps {
+            owners.insert(customer_id, (employee_id, name));
+        }
+        // Latest last, so deal reps override lead reps and later deals earlier ones
+        for (customer_id, employee_id, name) in deal_reps {
+            owners.insert(customer_id, (employee_id, name));
+        }
+        Ok(owners)
+    }
+}
+
+/// Churn signals of a customer from its order history and complaints
+fn signals(
+    policy: &ChurnPolicy,
+    as_of: NaiveDate,
+    last_order: NaiveDate,
+    recent_value: i64,
+    prior_value: i64,
+    open_complaints: usize,
+) -> Vec<ChurnSignal> {
+    let mut signals = Vec::new();
+    let idle = (as_of - last_order).num_days();
+    if idle >= policy.inactive_days {
+        signals.push(ChurnSignal::Inactive(idle));
+    }
+    if prior_value > 0 && recent_value < prior_value {
+        let drop = ((prior_value - recent_value) * 100 / prior_value) as u32;
+        if drop >= policy.decline_percent {
+            signals.push(ChurnSignal::DecliningValue(drop));
+        }
+    }
+    if open_complaints > 0 {
+        signals.push(ChurnSignal::OpenComplaints(open_complaints));
+    }
+    signals
+}
+
+/// What the account owner should do first about a customer at risk
+fn next_action(signals: &[ChurnSignal]) -> &'static str {
+    if signals.iter().any(|s| matches!(s, ChurnSignal::OpenComplaints(_))) {
+        "call the customer to resolve the open complaints"
+    } else if signals.iter().any(|s| matches!(s, ChurnSignal::Inactive(_))) {
+        "schedule a check-in call and offer a re-order incentive"
+    } else {
+        "review the account and propose a volume or loyalty discount"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_churn_signals() {
+        let policy = ChurnPolicy { inactive_days: 90, window_days: 90, decline_percent: 30, renotify_days: 30 };
+        let as_of: NaiveDate = "2024-06-30".parse().unwrap();
+        let date = |d: &str| d.parse::<NaiveDate>().unwrap();
+
+        assert!(signals(&policy, as_of, date("2024-06-01"), 900, 1000, 0).is_empty());
+
+        let declining = signals(&policy, as_of, date("2024-06-01"), 600, 1000, 0);
+        assert_eq!(declining, vec![ChurnSignal::DecliningValue(40)]);
+        assert_eq!(next_action(&declining), "review the account and propose a volume or loyalty discount");
+
+        let lost = signals(&policy, as_of, date("2024-03-01"), 0, 1000, 2);
+        assert_eq!(
+            lost,
+            vec![ChurnSignal::Inactive(121), ChurnSignal::DecliningValue(100), ChurnSignal::OpenComplaints(2)]
+        );
+        assert_eq!(next_action(&lost), "call the customer to resolve the open complaints");
+        assert_eq!(lost[0].to_string(), "no orders in 121 days");
+    }
+}
@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::core::result::CLIERPResult;
+use crate::database::models::{Invoice, InvoiceKind, InvoicePayment, InvoiceStatus};
+use crate::database::schema::{customers, invoice_payments, invoices};
+use crate::database::{Customer, CustomerStatus};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Issued invoices a customer needs before it is scored
+const MIN_INVOICES: usize = 3;
+
+/// Suggested credit limits are rounded to this amount
+const LIMIT_ROUNDING: i32 = 10_000;
+
+/// How a customer has paid its receivable invoices
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PaymentHistory {
+    /// Issued (not draft or cancelled) receivable invoices
+    pub invoices: usize,
+    /// Invoices paid in full
+    pub paid_invoices: usize,
+    /// Average days from invoice date to full payment
+    pub avg_days_to_pay: Option<f64>,
+    /// Average days past the due date at full payment, 0 for those paid on time
+    pub avg_days_late: Option<f64>,
+    /// Invoices paid after their due date or still open past it
+    pub overdue_invoices: usize,
+    /// Payments the bank returned
+    pub returned_payments: usize,
+    /// Average amount invoiced per month over the last twelve months
+    pub monthly_invoiced: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum CreditGrade {
+    A,
+    B,
+    C,
+    D,
+}
+
+impl CreditGrade {
+    pub fn from_score(score: u32) -> Self {
+        match score {
+            80.. => CreditGrade::A,
+            60..=79 => CreditGrade::B,
+            40..=59 => CreditGrade::C,
+            _ => CreditGrade::D,
+        }
+    }
+
+    /// Months of average invoicing a customer of this grade may owe at once
+    pub fn months_of_credit(&self) -> i32 {
+        match self {
+            CreditGrade::A => 3,
+            CreditGrade::B => 2,
+            CreditGrade::C => 1,
+            CreditGrade::D => 0,
+        }
+    }
+}
+
+impl std::fmt::Display for CreditGrade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CreditGrade::A => write!(f, "A"),
+            CreditGrade::B => write!(f, "B"),
+            CreditGrade::C => write!(f, "C"),
+            CreditGrade::D => write!(f, "D"),
+        }
+    }
+}
+
+/// Internal credit score of a customer, 0 (worst) to 100
+#[derive(Debug, Clone, Serialize)]
+pub struct CreditScore {
+    pub customer_id: i32,
+    pub history: PaymentHistory,
+    /// `None` until the customer has enough invoices to judge
+    pub score: Option<u32>,
+    pub grade: Option<CreditGrade>,
+    /// Credit limit the score and invoicing volume support; 0 means
+    /// payment in advance
+    pub suggested_limit: Option<i32>,
+}
+
+/// A customer whose credit limit differs from the suggested one
+#[derive(Debug, Clone, Serialize)]
+pub struct CreditSuggestion {
+    pub customer: Customer,
+    pub score: CreditScore,
+}
+
+/// Credit scores of customers, computed from their invoices and payments
+/// whenever they are asked for, so they always reflect the latest payments
+pub struct CreditScoreService;
+
+impl CreditScoreService {
+    pub fn score(conn: &mut SqliteConnection, customer_id: i32, as_of: NaiveDate) -> Result<CreditScore> {
+        let mut scores = Self::scores(conn, &[customer_id], as_of)?;
+        Ok(scores.remove(&customer_id).unwrap_or_else(|| score_history(customer_id, PaymentHistory::default())))
+    }
+
+    /// Active customers (or just `customer_id`) whose credit limit differs
+    /// from the suggested one, by name
+    pub fn suggestions(
+        conn: &mut SqliteConnection,
+        customer_id: Option<i32>,
+        as_of: NaiveDate,
+    ) -> Result<Vec<CreditSuggestion>> {
+        let mut query = customers::table
+            .filter(customers::status.eq(CustomerStatus::Active.to_string()))
+            .into_boxed();
+        if let Some(customer_id) = customer_id {
+            query = query.filter(customers::id.eq(customer_id));
+        }
+        let customers = query.order(customers::name.asc()).load::<Customer>(conn)?;
+        let ids: Vec<i32> = customers.iter().map(|c| c.id).collect();
+        let mut scores = Self::scores(conn, &ids, as_of)?;
+
+        Ok(customers
+            .into_iter()
+            .filter_map(|customer| {
+                let score = scores.remove(&customer.id)?;
+                let suggested = score.suggested_limit?;
+                (customer.credit_limit != Some(suggested)).then_some(CreditSuggestion { customer, score })
+            })
+            .collect())
+    }
+
+    fn scores(conn: &mut SqliteConnection, customer_ids: &[i32], as_of: NaiveDate) -> Result<HashMap<i32, CreditScore>> {
+        let invoices = invoices::table
+            .filter(invoices::customer_id.eq_any(customer_ids))
+            .filter(invoices::kind.eq(InvoiceKind::Receivable.to_string()))
+            .filter(invoices::status.ne_all(vec![
+                InvoiceStatus::Draft.to_string(),
+                InvoiceStatus::Cancelled.to_string(),
+            ]))
+            .filter(invoices::invoice_date.le(as_of))
+            .load::<Invoice>(conn)?;
+        let ids: Vec<i32> = invoices.iter().map(|i| i.id).collect();
+        let mut payments: HashMap<i32, Vec<InvoicePayment>> = HashMap::new();
+        for payment in invoice_payments::table
+            .filter(invoice_payments::invoice_id.eq_any(ids))
+            .load::<InvoicePayment>(conn)?
+        {
+            payments.entry(payment.invoice_id).or_default().push(payment);
+        }
+
+        let mut by_customer: HashMap<i32, Vec<Invoice>> = HashMap::new();
+        for invoice in invoices {
+            if let Some(customer_id) = invoice.customer_id {
+                by_customer.entry(customer_id).or_default().push(invoice);
+            }
+        }
+        Ok(by_customer
+            .into_iter()
+            .map(|(customer_id, invoices)| {
+                let history = payment_history(&invoices, &payments, as_of);
+                (customer_id, score_history(customer_id, history))
+            })
+            .collect())
+    }
+}
+
+/// Summarize the invoices of one customer and their payments as of a date
+fn payment_history(invoices: &[Invoice], payments: &HashMap<i32, Vec<InvoicePayment>>, as_of: NaiveDate) -> PaymentHistory {
+    let no_payments = Vec::new();
+    let year_ago = as_of - Duration::days(365);
+    let mut history = PaymentHistory { invoices: invoices.len(), ..Default::default() };
+    let (mut days_to_pay, mut days_late) = (0i64, 0i64);
+    let mut invoiced = 0i64;
+
+    for invoice in invoices {
+        let invoice_payments = payments.get(&invoice.id).unwrap_or(&no_payments);
+        history.returned_payments += invoice_payments.iter().filter(|p| p.is_returned()).count();
+        if invoice.invoice_date > year_ago {
+            invoiced += i64::from(invoice.total_amount);
+        }
+
+        // An invoice is settled on the day of the last payment that stuck
+        let settled_on = invoice_payments.iter().filter(|p| !p.is_returned()).map(|p| p.paid_on).max();
+        match settled_on {
+            Some(paid_on) if invoice.status == InvoiceStatus::Paid.to_string() => {
+                history.paid_invoices += 1;
+                days_to_pay += (paid_on - invoice.invoice_date).num_days().max(0);
+                let late = invoice.due_date.map_or(0, |due| (paid_on - due).num_days().max(0));
+                days_late += late;
+                if late > 0 {
+                    history.overdue_invoices += 1;
+                }
+            }
+            _ => {
+                if invoice.status == InvoiceStatus::Open.to_string() && invoice.due_date.is_some_and(|due| due < as_of) {
+                    history.overdue_invoices += 1;
+                }
+            }
+        }
+    }
+
+    if history.paid_invoices > 0 {
+        history.avg_days_to_pay = Some(days_to_pay as f64 / history.paid_invoices as f64);
+        history.avg_days_late = Some(days_late as f64 / history.paid_invoices as f64);
+    }
+    history.monthly_invoiced = (invoiced / 12) as i32;
+    history
+}
+
+/// Score a payment history: 100 less up to 40 points for average lateness
+/// (one per day), up to 40 for the share of invoices that went overdue and
+/// 10 per returned payment, up to 30
+fn score_history(customer_id: i32, history: PaymentHistory) -> CreditScore {
+    let score = (history.invoices >= MIN_INVOICES).then(|| {
+        let lateness = history.avg_days_late.unwrap_or(0.0).min(40.0);
+        let overdue = history.overdue_invoices as f64 / history.invoices as f64 * 40.0;
+        let returned = (history.returned_payments as f64 * 10.0).min(30.0);
+        (100.0 - lateness - overdue - returned).round().max(0.0) as u32
+    });
+    let grade = score.map(CreditGrade::from_score);
+    let suggested_limit = grade.map(|grade| {
+        let limit = history.monthly_invoiced * grade.months_of_credit();
+        (limit + LIMIT_ROUNDING / 2) / LIMIT_ROUNDING * LIMIT_ROUNDING
+    });
+    CreditScore { customer_id, history, score, grade, suggested_limit }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn invoice(id: i32, date: &str, due: &str, total: i32, status: &str) -> Invoice {
+        let now = Utc::now().naive_utc();
+        Invoice {
+            id,
+            invoice_number: format!("INV-{}", id),
+            kind: "receivable".to_string(),
+            customer_id: Some(1),
+            supplier_id: None,
+            invoice_date: date.parse().unwrap(),
+            due_date: Some(due.parse().unwrap()),
+            total_amount: total,
+            paid_amount: 0,
+            status: status.to_string(),
+            description: None,
+            created_by: None,
+            created_at: now,
+            updated_at: now,
+            po_id: None,
+            sent_at: None,
+        }
+    }
+
+    fn payment(invoice_id: i32, date: &str, returned_on: Option<&str>) -> InvoicePayment {
+        InvoicePayment {
+            id: invoice_id,
+            invoice_id,
+            amount: 1,
+            paid_on: date.parse().unwrap(),
+            reference: None,
+            created_by: None,
+            created_at: Utc::now().naive_utc(),
+            returned_on: returned_on.map(|d| d.parse().unwrap()),
+            return_reason: None,
+        }
+    }
+
+    #[test]
+    fn test_score_from_history() {
+        let as_of: NaiveDate = "2024-06-30".parse().unwrap();
+        let invoices = vec![
+            invoice(1, "2024-01-01", "2024-01-31", 1_200_000, "paid"),
+            invoice(2, "2024-02-01", "2024-03-02", 1_200_000, "paid"),
+            invoice(3, "2024-03-01", "2024-03-31", 1_200_000, "paid"),
+            invoice(4, "2024-05-01", "2024-05-31", 1_200_000, "open"),
+        ];
+        let payments = HashMap::from([
+            (1, vec![payment(1, "2024-01-31", None)]),
+            // Paid 10 days late, after a first payment bounced
+            (2, vec![payment(2, "2024-03-01", Some("2024-03-05")), payment(2, "2024-03-12", None)]),
+            (3, vec![payment(3, "2024-03-21", None)]),
+        ]);
+
+        let history = payment_history(&invoices, &payments, as_of);
+        assert_eq!(history.invoices, 4);
+        assert_eq!(history.paid_invoices, 3);
+        assert_eq!(history.avg_days_to_pay, Some((30.0 + 40.0 + 20.0) / 3.0));
+        assert_eq!(history.avg_days_late, Some(10.0 / 3.0));
+        // One paid late, one still open past its due date
+        assert_eq!(history.overdue_invoices, 2);
+        assert_eq!(history.returned_payments, 1);
+        assert_eq!(history.monthly_invoiced, 400_000);
+
+        // 100 - 3.3 (lateness) - 20 (half overdue) - 10 (one returned)
+        let score = score_history(1, history);
+        assert_eq!(score.score, Some(67));
+        assert_eq!(score.grade, Some(CreditGrade::B));
+        assert_eq!(score.suggested_limit, Some(800_000));
+
+        let new_customer = score_history(2, payment_history(&invoices[..2], &payments, as_of));
+        assert_eq!(new_customer.score, None);
+        assert_eq!(new_customer.suggested_limit, None);
+    }
+}
@@ -1,6 +1,6 @@
 use diesel::prelude::*;
 use chrono::Utc;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use crate::core::result::CLIERPResult;
 
 // Type alias for convenience
@@ -10,13 +10,34 @@ use crate::database::{
     CustomerSummary
 };
 use crate::database::schema::{customers, leads, deals};
+use crate::database::EventAction;
+use crate::modules::system::events::EventService;
+use crate::modules::system::legal_hold::{HoldEntity, LegalHoldService};
+use crate::modules::system::rules::ValidationRuleService;
+use crate::utils::cache::QueryCache;
 use crate::utils::validation::{validate_email, validate_required_string};
-use crate::utils::pagination::{Paginate, PaginationParams, PaginatedResult};
-use crate::utils::filters::FilterOptions;
+use crate::utils::pagination::{PaginationParams, PaginatedResult};
+use crate::utils::filters::{FieldAssignment, FilterOptions};
+use crate::utils::filter_expr::FilterField;
+
+/// Fields `customer list --where` may use
+pub const CUSTOMER_WHERE_FIELDS: &[FilterField] = &[
+    FilterField::integer("id", "customers.id"),
+    FilterField::text("code", "customers.customer_code"),
+    FilterField::text("name", "customers.name"),
+    FilterField::text("email", "customers.email"),
+    FilterField::text("phone", "customers.phone"),
+    FilterField::text("type", "customers.customer_type"),
+    FilterField::text("company", "customers.company_name"),
+    FilterField::integer("credit_limit", "customers.credit_limit"),
+    FilterField::text("status", "customers.status"),
+    FilterField::date("created", "customers.created_at"),
+];
 
 pub struct CustomerService;
 
 impl CustomerService {
+    #[allow(clippy::too_many_arguments)]
     pub fn create_customer(
         conn: &mut DatabaseConnection,
         name: &str,
@@ -59,6 +80,23 @@ impl CustomerService {
             }
         }
 
+        // Deployment-specific rules, e.g. fields a company requires
+        let credit_limit_value = credit_limit.map(|c| c.to_string());
+        ValidationRuleService::validate(
+            conn,
+            "customer",
+            &[
+                ("name", Some(name)),
+                ("email", email),
+                ("phone", phone),
+                ("address", address),
+                ("company_name", company_name),
+                ("tax_id", tax_id),
+                ("credit_limit", credit_limit_value.as_deref()),
+                ("notes", notes),
+            ],
+        )?;
+
         // Generate customer code
         let customer_code = Self::generate_customer_code(conn)?;
 
@@ -72,7 +110,7 @@ impl CustomerService {
             customer_type: customer_type.to_string(),
             company_name: company_name.map(|s| s.to_string()),
             tax_id: tax_id.map(|s| s.to_string()),
-            credit_limit: credit_limit,
+            credit_limit,
             status: CustomerStatus::Active.to_string(),
             notes: notes.map(|s| s.to_string()),
         };
@@ -80,12 +118,14 @@ impl CustomerService {
         diesel::insert_into(customers::table)
             .values(&new_customer)
             .execute(conn)?;
+        QueryCache::current().invalidate(&["crm.customers"]);
 
         // Get the inserted customer by customer code since SQLite doesn't support RETURNING
-        customers::table
+        let customer = customers::table
             .filter(customers::customer_code.eq(&new_customer.customer_code))
-            .first::<Customer>(conn)
-            .map_err(Into::into)
+            .first::<Customer>(conn)?;
+        EventService::record(conn, "customers", customer.id, EventAction::Created, &customer)?;
+        Ok(customer)
     }
 
     pub fn get_customer_by_id(conn: &mut DatabaseConnection, customer_id: i32) -> Result<Option<Customer>> {
@@ -153,6 +193,10 @@ impl CustomerService {
         let mut query = customers::table.into_boxed();
 
         // Apply filters
+        if let Some(expr) = &filters.where_clause {
+            query = query.filter(expr.predicate(CUSTOMER_WHERE_FIELDS)?);
+        }
+
         if let Some(search) = &filters.search {
             query = query.filter(
                 customers::name.like(format!("%{}%", search))
@@ -216,7 +260,7 @@ impl CustomerService {
 
     pub fn get_customer_summaries(
         conn: &mut DatabaseConnection,
-        filters: &FilterOptions,
+        _filters: &FilterOptions,
         pagination: &PaginationParams,
     ) -> Result<PaginatedResult<CustomerSummary>> {
         let customer_query = customers::table
@@ -239,10 +283,10 @@ impl CustomerService {
                 customers::status,
             ));
 
-        let results: Vec<(i32, String, String, String, i64, Option<i64>, String)> = customer_query
+        let results = customer_query
             .offset(pagination.offset())
             .limit(pagination.limit())
-            .load(conn)?;
+            .load::<(i32, String, String, String, i64, Option<i64>, String)>(conn)?;
 
         let total_items = customers::table.count().get_result::<i64>(conn)?;
 
@@ -264,6 +308,7 @@ impl CustomerService {
         Ok(PaginatedResult::new(summaries, pagination, total_items))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn update_customer(
         conn: &mut DatabaseConnection,
         customer_id: i32,
@@ -278,11 +323,35 @@ impl CustomerService {
         notes: Option<Option<&str>>,
     ) -> Result<Customer> {
         // Check if customer exists
-        let _customer = Self::get_customer_by_id(conn, customer_id)?
+        let customer = Self::get_customer_by_id(conn, customer_id)?
             .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
                 format!("Customer with ID {} not found", customer_id)
             ))?;
 
+        // Values equal to the current ones are not written, so an update
+        // that changes nothing leaves the customer untouched
+        let name = name.filter(|n| *n != customer.name);
+        let email = email.filter(|e| *e != customer.email.as_deref());
+        let phone = phone.filter(|p| *p != customer.phone.as_deref());
+        let address = address.filter(|a| *a != customer.address.as_deref());
+        let company_name = company_name.filter(|c| *c != customer.company_name.as_deref());
+        let tax_id = tax_id.filter(|t| *t != customer.tax_id.as_deref());
+        let credit_limit = credit_limit.filter(|c| Some(*c) != customer.credit_limit);
+        let status = status.filter(|s| s.to_string() != customer.status);
+        let notes = notes.filter(|n| *n != customer.notes.as_deref());
+        if name.is_none()
+            && email.is_none()
+            && phone.is_none()
+            && address.is_none()
+            && company_name.is_none()
+            && tax_id.is_none()
+            && credit_limit.is_none()
+            && status.is_none()
+            && notes.is_none()
+        {
+            return Ok(customer);
+        }
+
         // Validate input
         if let Some(name) = name {
             validate_required_string(name, "name")?;
@@ -302,6 +371,29 @@ impl CustomerService {
             validate_email(email)?;
         }
 
+        // Only the fields being changed are checked against configured rules
+        let credit_limit_value = credit_limit.map(|c| c.to_string());
+        let mut fields: Vec<(&str, Option<&str>)> = Vec::new();
+        if let Some(name) = name {
+            fields.push(("name", Some(name)));
+        }
+        for (field, value) in [
+            ("email", email),
+            ("phone", phone),
+            ("address", address),
+            ("company_name", company_name),
+            ("tax_id", tax_id),
+            ("notes", notes),
+        ] {
+            if let Some(value) = value {
+                fields.push((field, value));
+            }
+        }
+        if let Some(credit_limit) = credit_limit_value.as_deref() {
+            fields.push(("credit_limit", Some(credit_limit)));
+        }
+        ValidationRuleService::validate(conn, "customer", &fields)?;
+
         // Build update query - update each field individually
         let current_time = Utc::now().naive_utc();
 
@@ -363,15 +455,86 @@ impl CustomerService {
         diesel::update(customers::table.find(customer_id))
             .set(customers::updated_at.eq(current_time))
             .execute(conn)?;
+        QueryCache::current().invalidate(&["crm.customers"]);
 
         // Get the updated customer
-        customers::table
+        let customer = customers::table
             .find(customer_id)
-            .first::<Customer>(conn)
-            .map_err(Into::into)
+            .first::<Customer>(conn)?;
+        EventService::record(conn, "customers", customer.id, EventAction::Updated, &customer)?;
+        Ok(customer)
+    }
+
+    /// Customers matching every `field=value` filter
+    pub fn find_customers_by_filters(
+        conn: &mut DatabaseConnection,
+        filters: &[FieldAssignment],
+    ) -> Result<Vec<Customer>> {
+        let customers = filtered_customers(filters)?.load::<Customer>(conn)?;
+        Ok(customers)
+    }
+
+    /// Apply `updates` to every customer matching `filters` in a single
+    /// transaction, with the same validation as a single update
+    pub fn bulk_update_customers(
+        conn: &mut DatabaseConnection,
+        filters: &[FieldAssignment],
+        updates: &[FieldAssignment],
+    ) -> Result<usize> {
+        if updates.is_empty() {
+            return Err(crate::core::error::CLIERPError::Validation(
+                "At least one --set is required".to_string()
+            ));
+        }
+
+        let mut credit_limit = None;
+        let mut status = None;
+        let mut customer_type = None;
+        let mut notes = None;
+        for update in updates {
+            match update.field.as_str() {
+                "credit_limit" => credit_limit = Some(update.as_i32()?),
+                "status" => status = Some(update.as_enum::<CustomerStatus>()?),
+                "type" | "customer_type" => customer_type = Some(update.as_enum::<CustomerType>()?),
+                "notes" => notes = Some(update.as_optional()),
+                _ => return Err(update.unsupported(CUSTOMER_UPDATE_FIELDS)),
+            }
+        }
+
+        let updated = conn.transaction::<_, crate::core::error::CLIERPError, _>(|conn| {
+            let matches = filtered_customers(filters)?.load::<Customer>(conn)?;
+
+            for customer in &matches {
+                Self::update_customer(
+                    conn,
+                    customer.id,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    credit_limit,
+                    status.clone(),
+                    notes,
+                )?;
+                if let Some(customer_type) = &customer_type {
+                    diesel::update(customers::table.find(customer.id))
+                        .set(customers::customer_type.eq(customer_type.to_string()))
+                        .execute(conn)?;
+                }
+            }
+
+            Ok(matches.len())
+        })?;
+
+        tracing::info!("Bulk updated {} customers", updated);
+        Ok(updated)
     }
 
     pub fn delete_customer(conn: &mut DatabaseConnection, customer_id: i32) -> Result<bool> {
+        LegalHoldService::ensure_not_held(conn, HoldEntity::Customers, customer_id, "delete")?;
+
         // Check if customer has any leads or deals
         let has_leads = leads::table
             .filter(leads::customer_id.eq(customer_id))
@@ -385,8 +548,16 @@ impl CustomerService {
             ));
         }
 
+        let customer = customers::table
+            .find(customer_id)
+            .first::<Customer>(conn)
+            .optional()?;
         let deleted_rows = diesel::delete(customers::table.find(customer_id))
             .execute(conn)?;
+        QueryCache::current().invalidate(&["crm.customers"]);
+        if let Some(customer) = customer.filter(|_| deleted_rows > 0) {
+            EventService::record(conn, "customers", customer_id, EventAction::Deleted, &customer)?;
+        }
 
         Ok(deleted_rows > 0)
     }
@@ -406,7 +577,12 @@ impl CustomerService {
             .map_err(Into::into)
     }
 
+    /// Cached in the query cache until a change invalidates it or the TTL runs out
     pub fn get_customer_statistics(conn: &mut DatabaseConnection) -> Result<CustomerStatistics> {
+        QueryCache::current().get_or_compute("crm.customers.statistics", || Self::compute_customer_statistics(conn))
+    }
+
+    fn compute_customer_statistics(conn: &mut DatabaseConnection) -> Result<CustomerStatistics> {
         // Total customers count
         let total_customers = customers::table
             .count()
@@ -455,7 +631,33 @@ impl CustomerService {
     }
 }
 
-#[derive(Debug, Serialize)]
+const CUSTOMER_FILTER_FIELDS: &[&str] = &["id", "status", "type", "credit_limit"];
+const CUSTOMER_UPDATE_FIELDS: &[&str] = &["status", "type", "credit_limit", "notes"];
+
+fn filtered_customers(
+    filters: &[FieldAssignment],
+) -> Result<customers::BoxedQuery<'static, diesel::sqlite::Sqlite>> {
+    let mut query = customers::table.into_boxed();
+
+    for filter in filters {
+        query = match filter.field.as_str() {
+            "id" => query.filter(customers::id.eq(filter.as_i32()?)),
+            "status" => query.filter(customers::status.eq(filter.as_enum::<CustomerStatus>()?.to_string())),
+            "type" | "customer_type" => {
+                query.filter(customers::customer_type.eq(filter.as_enum::<CustomerType>()?.to_string()))
+            }
+            "credit_limit" => match filter.as_optional_i32()? {
+                Some(limit) => query.filter(customers::credit_limit.eq(limit)),
+                None => query.filter(customers::credit_limit.is_null()),
+            },
+            _ => return Err(filter.unsupported(CUSTOMER_FILTER_FIELDS)),
+        };
+    }
+
+    Ok(query.order(customers::id.asc()))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CustomerStatistics {
     pub total_customers: i64,
     pub active_customers: i64,
@@ -0,0 +1,49 @@
+use diesel::prelude::*;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::customers;
+use crate::database::{Customer, CustomerStatus, DatabaseConnection, PortalLink};
+use crate::modules::system::portal::{PortalScope, PortalService};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Read-only portal links through which customers look up their invoices,
+/// statement and payments instead of asking for copies
+pub struct CustomerPortalService;
+
+impl CustomerPortalService {
+    /// Issue a portal link for a customer
+    pub fn share(
+        conn: &mut DatabaseConnection,
+        secret: &str,
+        customer_id: i32,
+        ttl_days: u32,
+        shared_by: Option<i32>,
+    ) -> Result<(PortalLink, String)> {
+        let customer = Self::find(conn, customer_id)?;
+        if customer.status != CustomerStatus::Active.to_string() {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Customer {} is {}; only active customers get a portal link",
+                customer.customer_code, customer.status
+            )));
+        }
+        PortalService::issue(conn, secret, PortalScope::Customer, customer_id, ttl_days, shared_by)
+    }
+
+    /// The customer a portal link opens
+    pub fn open(conn: &mut DatabaseConnection, secret: &str, token: &str) -> Result<(PortalLink, Customer)> {
+        let link = PortalService::verify(conn, secret, token, PortalScope::Customer)?;
+        let customer = Self::find(conn, link.record_id)?;
+        Ok((link, customer))
+    }
+
+    fn find(conn: &mut DatabaseConnection, customer_id: i32) -> Result<Customer> {
+        customers::table
+            .find(customer_id)
+            .first::<Customer>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Customer with ID {} not found", customer_id)))
+    }
+}
@@ -5,16 +5,40 @@ use crate::core::result::CLIERPResult;
 // Type alias for convenience
 type Result<T> = CLIERPResult<T>;
 use crate::database::{
-    DatabaseConnection, Deal, NewDeal, DealStage, Lead, Customer, Employee
+    DatabaseConnection, Deal, NewDeal, DealStage, EventAction, Lead, Customer, Employee, RecordVisibility
 };
+use crate::modules::crm::visibility::RecordScope;
+use crate::modules::system::audit::AuditService;
+use crate::modules::system::events::EventService;
+use crate::modules::system::legal_hold::{HoldEntity, LegalHoldService};
+use crate::modules::system::rules::ValidationRuleService;
 use crate::database::schema::{deals, leads, customers, employees};
-use crate::utils::validation::validate_required_string;
-use crate::utils::pagination::{Paginate, PaginationParams, PaginatedResult};
-use crate::utils::filters::FilterOptions;
+use crate::utils::cache::QueryCache;
+use crate::utils::validation::{validate_country_code, validate_required_string};
+use crate::utils::pagination::{PaginationParams, PaginatedResult};
+use crate::utils::dates::parse_date;
+use crate::utils::filters::{FieldAssignment, FilterOptions};
+use crate::utils::filter_expr::FilterField;
+
+/// Fields `deal list --where` may use
+pub const DEAL_WHERE_FIELDS: &[FilterField] = &[
+    FilterField::integer("id", "deals.id"),
+    FilterField::text("name", "deals.deal_name"),
+    FilterField::integer("lead", "deals.lead_id"),
+    FilterField::text("stage", "deals.stage"),
+    FilterField::integer("value", "deals.deal_value"),
+    FilterField::integer("final_amount", "deals.final_amount"),
+    FilterField::integer("probability", "deals.probability"),
+    FilterField::date("close_date", "deals.close_date"),
+    FilterField::integer("assigned_to", "deals.assigned_to"),
+    FilterField::text("country", "deals.destination_country"),
+    FilterField::date("created", "deals.created_at"),
+];
 
 pub struct DealService;
 
 impl DealService {
+    #[allow(clippy::too_many_arguments)]
     pub fn create_deal(
         conn: &mut DatabaseConnection,
         lead_id: i32,
@@ -22,7 +46,7 @@ impl DealService {
         deal_value: i32,
         expected_close_date: Option<NaiveDate>,
         assigned_to: Option<i32>,
-        description: Option<&str>,
+        _description: Option<&str>,
         notes: Option<&str>,
     ) -> Result<Deal> {
         // Validate input
@@ -43,8 +67,19 @@ impl DealService {
             ));
         }
 
+        let deal_value_str = deal_value.to_string();
+        ValidationRuleService::validate(
+            conn,
+            "deal",
+            &[
+                ("title", Some(title)),
+                ("deal_value", Some(&deal_value_str)),
+                ("notes", notes),
+            ],
+        )?;
+
         // Verify lead exists
-        let lead = leads::table
+        leads::table
             .find(lead_id)
             .first::<Lead>(conn)?;
 
@@ -68,20 +103,24 @@ impl DealService {
             discount_percent: Some(0),
             final_amount: None,
             notes: notes.map(|s| s.to_string()),
+            destination_country: None,
+            visibility: RecordVisibility::Private.to_string(),
         };
 
         diesel::insert_into(deals::table)
             .values(&new_deal)
             .execute(conn)?;
+        QueryCache::current().invalidate(&["crm.deals"]);
 
         // Get the inserted deal by searching for the most recent deal with matching criteria
-        deals::table
+        let deal = deals::table
             .filter(deals::dsl::deal_name.eq(&new_deal.deal_name))
             .filter(deals::dsl::lead_id.eq(new_deal.lead_id))
             .filter(deals::dsl::deal_value.eq(new_deal.deal_value))
             .order(deals::dsl::created_at.desc())
-            .first::<Deal>(conn)
-            .map_err(Into::into)
+            .first::<Deal>(conn)?;
+        EventService::record(conn, "deals", deal.id, EventAction::Created, &deal)?;
+        Ok(deal)
     }
 
     pub fn get_deal_by_id(conn: &mut DatabaseConnection, deal_id: i32) -> Result<Option<Deal>> {
@@ -92,8 +131,14 @@ impl DealService {
             .map_err(Into::into)
     }
 
-    pub fn get_deal_with_details(conn: &mut DatabaseConnection, deal_id: i32) -> Result<Option<DealWithDetails>> {
-        let deal = Self::get_deal_by_id(conn, deal_id)?;
+    /// Load a deal with its lead and customer; deals outside `scope` are reported as not found
+    pub fn get_deal_with_details(
+        conn: &mut DatabaseConnection,
+        deal_id: i32,
+        scope: &RecordScope,
+    ) -> Result<Option<DealWithDetails>> {
+        let deal = Self::get_deal_by_id(conn, deal_id)?
+            .filter(|deal| scope.can_see(deal.assigned_to, &deal.visibility));
 
         if let Some(deal) = deal {
             // Get lead and customer info
@@ -133,6 +178,7 @@ impl DealService {
         conn: &mut DatabaseConnection,
         filters: &FilterOptions,
         pagination: &PaginationParams,
+        scope: &RecordScope,
     ) -> Result<PaginatedResult<DealWithDetails>> {
         let mut query = deals::table
             .inner_join(leads::table.on(leads::dsl::id.eq(deals::dsl::lead_id.assume_not_null())))
@@ -145,8 +191,31 @@ impl DealService {
                 employees::name.nullable(),
             ))
             .into_boxed();
+        let mut count_query = deals::table.into_boxed();
+
+        // Restrict to records the user may see
+        if let Some(owners) = &scope.owners {
+            query = query.filter(
+                deals::dsl::visibility.eq(RecordVisibility::Public.to_string())
+                    .or(deals::dsl::assigned_to.eq_any(owners.clone()))
+                    .or(deals::dsl::visibility.eq(RecordVisibility::Team.to_string())
+                        .and(deals::dsl::assigned_to.eq_any(scope.team.clone())))
+            );
+            count_query = count_query.filter(
+                deals::dsl::visibility.eq(RecordVisibility::Public.to_string())
+                    .or(deals::dsl::assigned_to.eq_any(owners.clone()))
+                    .or(deals::dsl::visibility.eq(RecordVisibility::Team.to_string())
+                        .and(deals::dsl::assigned_to.eq_any(scope.team.clone())))
+            );
+        }
 
         // Apply filters
+        if let Some(expr) = &filters.where_clause {
+            let predicate = expr.predicate(DEAL_WHERE_FIELDS)?;
+            query = query.filter(predicate.clone());
+            count_query = count_query.filter(predicate);
+        }
+
         if let Some(search) = &filters.search {
             query = query.filter(
                 deals::dsl::deal_name.like(format!("%{}%", search))
@@ -216,7 +285,7 @@ impl DealService {
             .limit(pagination.limit())
             .load(conn)?;
 
-        let total_items = deals::table.count().get_result::<i64>(conn)?;
+        let total_items = count_query.count().get_result::<i64>(conn)?;
 
         let deals_with_details: Vec<DealWithDetails> = results
             .into_iter()
@@ -248,6 +317,7 @@ impl DealService {
             .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
                 format!("Deal with ID {} not found", deal_id)
             ))?;
+        let before = deal.clone();
 
         // Calculate new probability based on stage
         let new_probability = Self::calculate_probability_for_stage(&new_stage);
@@ -262,12 +332,6 @@ impl DealService {
             deal.notes
         };
 
-        // Set actual close date if deal is closed - note: using close_date field
-        let actual_close_date = match new_stage {
-            DealStage::ClosedWon | DealStage::ClosedLost => Some(Utc::now().naive_utc().date()),
-            _ => deal.close_date,
-        };
-
         diesel::update(deals::table.find(deal_id))
             .set((
                 deals::dsl::stage.eq(new_stage.to_string()),
@@ -278,12 +342,16 @@ impl DealService {
             .execute(conn)?;
 
         // Get the updated deal
-        deals::table
+        let updated_deal = deals::table
             .find(deal_id)
-            .first::<Deal>(conn)
-            .map_err(Into::into)
+            .first::<Deal>(conn)?;
+        AuditService::record_update(conn, "deals", deal_id, &before, &updated_deal)?;
+        QueryCache::current().invalidate(&["crm.deals"]);
+
+        Ok(updated_deal)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn update_deal(
         conn: &mut DatabaseConnection,
         deal_id: i32,
@@ -291,15 +359,31 @@ impl DealService {
         deal_value: Option<i32>,
         expected_close_date: Option<Option<NaiveDate>>,
         assigned_to: Option<Option<i32>>,
-        description: Option<Option<&str>>,
+        _description: Option<Option<&str>>,
         notes: Option<Option<&str>>,
     ) -> Result<Deal> {
         // Check if deal exists
-        let _deal = Self::get_deal_by_id(conn, deal_id)?
+        let before = Self::get_deal_by_id(conn, deal_id)?
             .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
                 format!("Deal with ID {} not found", deal_id)
             ))?;
 
+        // Values equal to the current ones are not written, so an update
+        // that changes nothing leaves the deal untouched
+        let title = title.filter(|t| *t != before.deal_name);
+        let deal_value = deal_value.filter(|v| *v != before.deal_value);
+        let expected_close_date = expected_close_date.filter(|d| *d != before.close_date);
+        let assigned_to = assigned_to.filter(|a| *a != before.assigned_to);
+        let notes = notes.filter(|n| *n != before.notes.as_deref());
+        if title.is_none()
+            && deal_value.is_none()
+            && expected_close_date.is_none()
+            && assigned_to.is_none()
+            && notes.is_none()
+        {
+            return Ok(before);
+        }
+
         // Validate input
         if let Some(title) = title {
             validate_required_string(title, "title")?;
@@ -323,6 +407,20 @@ impl DealService {
             }
         }
 
+        // Only the fields being changed are checked against configured rules
+        let deal_value_str = deal_value.map(|v| v.to_string());
+        let mut fields: Vec<(&str, Option<&str>)> = Vec::new();
+        if let Some(title) = title {
+            fields.push(("title", Some(title)));
+        }
+        if let Some(deal_value) = deal_value_str.as_deref() {
+            fields.push(("deal_value", Some(deal_value)));
+        }
+        if let Some(notes) = notes {
+            fields.push(("notes", notes));
+        }
+        ValidationRuleService::validate(conn, "deal", &fields)?;
+
         // Perform individual updates for each provided field
         if let Some(title_val) = title {
             diesel::update(deals::table.find(deal_id))
@@ -356,15 +454,177 @@ impl DealService {
             .execute(conn)?;
 
         // Get the updated deal
-        deals::table
+        let updated_deal = deals::table
             .find(deal_id)
-            .first::<Deal>(conn)
-            .map_err(Into::into)
+            .first::<Deal>(conn)?;
+        AuditService::record_update(conn, "deals", deal_id, &before, &updated_deal)?;
+        QueryCache::current().invalidate(&["crm.deals"]);
+
+        Ok(updated_deal)
+    }
+
+    /// Set the country goods are shipped to for a cross-border sale.
+    pub fn set_destination_country(
+        conn: &mut DatabaseConnection,
+        deal_id: i32,
+        destination_country: Option<&str>,
+    ) -> Result<Deal> {
+        let before = Self::get_deal_by_id(conn, deal_id)?
+            .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
+                format!("Deal with ID {} not found", deal_id)
+            ))?;
+
+        if let Some(country) = destination_country {
+            validate_country_code(country)?;
+        }
+
+        diesel::update(deals::table.find(deal_id))
+            .set((
+                deals::dsl::destination_country.eq(destination_country),
+                deals::dsl::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+
+        let updated_deal = deals::table
+            .find(deal_id)
+            .first::<Deal>(conn)?;
+        AuditService::record_update(conn, "deals", deal_id, &before, &updated_deal)?;
+
+        Ok(updated_deal)
+    }
+
+    /// Change who besides the owner can see a deal
+    pub fn set_visibility(
+        conn: &mut DatabaseConnection,
+        deal_id: i32,
+        visibility: RecordVisibility,
+    ) -> Result<Deal> {
+        let before = Self::get_deal_by_id(conn, deal_id)?
+            .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
+                format!("Deal with ID {} not found", deal_id)
+            ))?;
+
+        diesel::update(deals::table.find(deal_id))
+            .set((
+                deals::dsl::visibility.eq(visibility.to_string()),
+                deals::dsl::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+
+        let updated_deal = deals::table
+            .find(deal_id)
+            .first::<Deal>(conn)?;
+        AuditService::record_update(conn, "deals", deal_id, &before, &updated_deal)?;
+
+        Ok(updated_deal)
+    }
+
+    /// Apply a discount and recalculate the deal's final amount. The maximum
+    /// allowed discount comes from the `deal.discount_percent` rules.
+    pub fn set_discount(
+        conn: &mut DatabaseConnection,
+        deal_id: i32,
+        discount_percent: i32,
+    ) -> Result<Deal> {
+        let before = Self::get_deal_by_id(conn, deal_id)?
+            .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
+                format!("Deal with ID {} not found", deal_id)
+            ))?;
+
+        if !(0..=100).contains(&discount_percent) {
+            return Err(crate::core::error::CLIERPError::Validation(
+                "Discount must be between 0 and 100 percent".to_string()
+            ));
+        }
+        let discount_value = discount_percent.to_string();
+        ValidationRuleService::validate(conn, "deal", &[("discount_percent", Some(&discount_value))])?;
+
+        let final_amount = (before.deal_value as i64 * (100 - discount_percent) as i64 / 100) as i32;
+        diesel::update(deals::table.find(deal_id))
+            .set((
+                deals::dsl::discount_percent.eq(Some(discount_percent)),
+                deals::dsl::final_amount.eq(Some(final_amount)),
+                deals::dsl::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+
+        let updated_deal = deals::table
+            .find(deal_id)
+            .first::<Deal>(conn)?;
+        AuditService::record_update(conn, "deals", deal_id, &before, &updated_deal)?;
+        QueryCache::current().invalidate(&["crm.deals"]);
+
+        Ok(updated_deal)
+    }
+
+    /// Deals in the user's scope matching every `field=value` filter
+    pub fn find_deals_by_filters(
+        conn: &mut DatabaseConnection,
+        filters: &[FieldAssignment],
+        scope: &RecordScope,
+    ) -> Result<Vec<Deal>> {
+        let deals = filtered_deals(filters, scope)?.load::<Deal>(conn)?;
+        Ok(deals)
+    }
+
+    /// Apply `updates` to every matching deal in the user's scope in a single
+    /// transaction. Each change goes through the single-deal update so
+    /// validation rules and change history still apply.
+    pub fn bulk_update_deals(
+        conn: &mut DatabaseConnection,
+        filters: &[FieldAssignment],
+        updates: &[FieldAssignment],
+        scope: &RecordScope,
+    ) -> Result<usize> {
+        if updates.is_empty() {
+            return Err(crate::core::error::CLIERPError::Validation(
+                "At least one --set is required".to_string()
+            ));
+        }
+
+        let mut assigned_to = None;
+        let mut close_date = None;
+        let mut stage = None;
+        let mut visibility = None;
+        for update in updates {
+            match update.field.as_str() {
+                "assigned_to" => assigned_to = Some(update.as_optional_i32()?),
+                "close_date" => {
+                    close_date = Some(update.as_optional().map(parse_date).transpose()?)
+                }
+                "stage" => stage = Some(update.as_enum::<DealStage>()?),
+                "visibility" => visibility = Some(update.as_enum::<RecordVisibility>()?),
+                _ => return Err(update.unsupported(DEAL_UPDATE_FIELDS)),
+            }
+        }
+
+        let updated = conn.transaction::<_, crate::core::error::CLIERPError, _>(|conn| {
+            let matches = filtered_deals(filters, scope)?.load::<Deal>(conn)?;
+
+            for deal in &matches {
+                if assigned_to.is_some() || close_date.is_some() {
+                    Self::update_deal(conn, deal.id, None, None, close_date, assigned_to, None, None)?;
+                }
+                if let Some(stage) = &stage {
+                    Self::update_deal_stage(conn, deal.id, stage.clone(), None)?;
+                }
+                if let Some(visibility) = visibility {
+                    Self::set_visibility(conn, deal.id, visibility)?;
+                }
+            }
+
+            Ok(matches.len())
+        })?;
+
+        tracing::info!("Bulk updated {} deals", updated);
+        Ok(updated)
     }
 
     pub fn delete_deal(conn: &mut DatabaseConnection, deal_id: i32) -> Result<bool> {
+        LegalHoldService::ensure_not_held(conn, HoldEntity::Deals, deal_id, "delete")?;
         let deleted_rows = diesel::delete(deals::table.find(deal_id))
             .execute(conn)?;
+        QueryCache::current().invalidate(&["crm.deals"]);
 
         Ok(deleted_rows > 0)
     }
@@ -400,7 +660,12 @@ impl DealService {
         Ok(deals_with_details)
     }
 
+    /// Cached in the query cache until a change invalidates it or the TTL runs out
     pub fn get_sales_pipeline(conn: &mut DatabaseConnection) -> Result<Vec<PipelineStage>> {
+        QueryCache::current().get_or_compute("crm.deals.pipeline", || Self::compute_sales_pipeline(conn))
+    }
+
+    fn compute_sales_pipeline(conn: &mut DatabaseConnection) -> Result<Vec<PipelineStage>> {
         use crate::database::DealStage as DS;
 
         let stages = vec![
@@ -440,7 +705,12 @@ impl DealService {
         Ok(pipeline)
     }
 
+    /// Cached in the query cache until a change invalidates it or the TTL runs out
     pub fn get_deal_statistics(conn: &mut DatabaseConnection) -> Result<DealStatistics> {
+        QueryCache::current().get_or_compute("crm.deals.statistics", || Self::compute_deal_statistics(conn))
+    }
+
+    fn compute_deal_statistics(conn: &mut DatabaseConnection) -> Result<DealStatistics> {
         // Total deals count
         let total_deals = deals::table
             .count()
@@ -525,6 +795,43 @@ impl DealService {
     }
 }
 
+const DEAL_FILTER_FIELDS: &[&str] = &["id", "stage", "assigned_to", "lead_id", "visibility"];
+const DEAL_UPDATE_FIELDS: &[&str] = &["assigned_to", "close_date", "stage", "visibility"];
+
+fn filtered_deals(
+    filters: &[FieldAssignment],
+    scope: &RecordScope,
+) -> Result<deals::BoxedQuery<'static, diesel::sqlite::Sqlite>> {
+    let mut query = deals::table.into_boxed();
+
+    if let Some(owners) = &scope.owners {
+        query = query.filter(
+            deals::dsl::visibility.eq(RecordVisibility::Public.to_string())
+                .or(deals::dsl::assigned_to.eq_any(owners.clone()))
+                .or(deals::dsl::visibility.eq(RecordVisibility::Team.to_string())
+                    .and(deals::dsl::assigned_to.eq_any(scope.team.clone())))
+        );
+    }
+
+    for filter in filters {
+        query = match filter.field.as_str() {
+            "id" => query.filter(deals::dsl::id.eq(filter.as_i32()?)),
+            "stage" => query.filter(deals::dsl::stage.eq(filter.as_enum::<DealStage>()?.to_string())),
+            "assigned_to" => match filter.as_optional_i32()? {
+                Some(employee_id) => query.filter(deals::dsl::assigned_to.eq(employee_id)),
+                None => query.filter(deals::dsl::assigned_to.is_null()),
+            },
+            "lead_id" => query.filter(deals::dsl::lead_id.eq(filter.as_i32()?)),
+            "visibility" => {
+                query.filter(deals::dsl::visibility.eq(filter.as_enum::<RecordVisibility>()?.to_string()))
+            }
+            _ => return Err(filter.unsupported(DEAL_FILTER_FIELDS)),
+        };
+    }
+
+    Ok(query.order(deals::dsl::id.asc()))
+}
+
 #[derive(Debug, serde::Serialize)]
 pub struct DealWithDetails {
     pub deal: Deal,
@@ -533,7 +840,7 @@ pub struct DealWithDetails {
     pub assigned_employee: Option<String>,
 }
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct PipelineStage {
     pub stage: String,
     pub count: i64,
@@ -541,7 +848,7 @@ pub struct PipelineStage {
     pub average_value: f64,
 }
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct DealStatistics {
     pub total_deals: i64,
     pub active_deals: i64,
@@ -0,0 +1,528 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use jsonwebtoken::{Algorithm, EncodingKey};
+
+use crate::core::config::CLIERPConfig;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{activities, customers, deals, dropbox_messages, employees, leads, users};
+use crate::database::{Activity, ActivityType, DealStage, LeadStatus, NewActivity, NewDropboxMessage};
+use crate::utils::cache::QueryCache;
+use crate::utils::html::base64_decode;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Characters of the signature kept in the dropbox address
+const TOKEN_LENGTH: usize = 16;
+/// Longest activity subject accepted, in bytes
+const MAX_SUBJECT: usize = 200;
+/// Longest email body kept as the activity description, in characters
+const MAX_DESCRIPTION: usize = 4000;
+
+/// Secret part of the dropbox address. Derived from the JWT secret, so it
+/// is the same for every install of a deployment and changes when the
+/// secret is rotated.
+pub fn dropbox_token(secret: &str) -> Result<String> {
+    let signature = jsonwebtoken::crypto::sign(
+        b"clierp-dropbox",
+        &EncodingKey::from_secret(secret.as_bytes()),
+        Algorithm::HS256,
+    )?;
+    Ok(signature
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .take(TOKEN_LENGTH)
+        .collect::<String>()
+        .to_lowercase())
+}
+
+/// The address users BCC, `dropbox-<token>@<dropbox.domain>`
+pub fn dropbox_address(config: &CLIERPConfig) -> Result<String> {
+    let domain = config.dropbox.domain.as_deref().filter(|d| !d.trim().is_empty()).ok_or_else(|| {
+        CLIERPError::Configuration(config::ConfigError::Message(
+            "dropbox.domain is not set; the email dropbox is disabled".to_string(),
+        ))
+    })?;
+    Ok(format!("dropbox-{}@{}", dropbox_token(&config.auth.jwt_secret)?, domain.trim()))
+}
+
+/// The parts of an email the dropbox logs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailMessage {
+    pub message_id: Option<String>,
+    /// Address of the sender, lowercase
+    pub from: Option<String>,
+    /// Addresses in To, Cc and Bcc, lowercase
+    pub recipients: Vec<String>,
+    pub subject: String,
+    /// When the email was sent, in UTC
+    pub date: Option<NaiveDateTime>,
+    /// First text/plain part, decoded. Text is assumed to be UTF-8.
+    pub body: Option<String>,
+}
+
+impl EmailMessage {
+    /// Parse an RFC 5322 message as delivered to the mailbox
+    pub fn parse(raw: &str) -> Result<Self> {
+        let raw = raw.replace("\r\n", "\n");
+        let (headers, body) = split_message(&raw);
+
+        let from = header(&headers, "from").and_then(|value| addresses(value).into_iter().next());
+        let mut recipients = Vec::new();
+        for (name, value) in &headers {
+            if matches!(name.as_str(), "to" | "cc" | "bcc") {
+                for address in addresses(value) {
+                    if !recipients.contains(&address) {
+                        recipients.push(address);
+                    }
+                }
+            }
+        }
+        if from.is_none() && recipients.is_empty() {
+            return Err(CLIERPError::ValidationError(
+                "Not an email message: it has no From or To address".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            message_id: header(&headers, "message-id")
+                .map(|id| id.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+                .filter(|id| !id.is_empty()),
+            from,
+            recipients,
+            subject: header(&headers, "subject").map(decode_words).unwrap_or_default().trim().to_string(),
+            date: header(&headers, "date").and_then(parse_date),
+            body: text_body(&headers, body),
+        })
+    }
+}
+
+/// What became of one email
+#[derive(Debug, Clone)]
+pub enum IngestOutcome {
+    /// One activity per matching customer
+    Logged(Vec<Activity>),
+    /// No address belongs to a customer
+    Unmatched,
+    /// The message was received before
+    Duplicate,
+}
+
+/// Emails BCC'd to the deployment's dropbox address, logged as email
+/// activities against the customers they were exchanged with.
+///
+/// Every address of a message other than the dropbox's is matched against
+/// customer email addresses; each customer found gets one completed email
+/// activity, attached to its latest open lead and that lead's latest open
+/// deal. The activity is assigned to the employee whose user or employee
+/// email is on the message, the sender first.
+pub struct DropboxService;
+
+impl DropboxService {
+    /// Log one raw message delivered to the dropbox with `token`
+    pub fn ingest(conn: &mut SqliteConnection, token: &str, raw: &[u8]) -> Result<IngestOutcome> {
+        let raw = String::from_utf8_lossy(raw);
+        let message = EmailMessage::parse(&raw)?;
+        let message_id = message
+            .message_id
+            .clone()
+            .unwrap_or_else(|| format!("{:016x}@dropbox.clierp", fingerprint(raw.as_bytes())));
+        let seen = dropbox_messages::table
+            .filter(dropbox_messages::message_id.eq(&message_id))
+            .count()
+            .get_result::<i64>(conn)?
+            > 0;
+        if seen {
+            return Ok(IngestOutcome::Duplicate);
+        }
+
+        let dropbox = format!("dropbox-{}", token);
+        let addresses: Vec<&String> = message
+            .from
+            .iter()
+            .chain(&message.recipients)
+            .filter(|address| address.split('@').next() != Some(dropbox.as_str()))
+            .collect();
+
+        let customer_emails = customers::table
+            .filter(customers::email.is_not_null())
+            .select((customers::id, customers::email.assume_not_null()))
+            .load::<(i32, String)>(conn)?;
+        let mut customer_ids = Vec::new();
+        for address in &addresses {
+            for (id, email) in &customer_emails {
+                if email.trim().eq_ignore_ascii_case(address) && !customer_ids.contains(id) {
+                    customer_ids.push(*id);
+                }
+            }
+        }
+
+        let staff = Self::staff_addresses(conn)?;
+        let sender_is_staff = message
+            .from
+            .as_ref()
+            .is_some_and(|from| staff.iter().any(|(email, _)| email == from));
+        let assigned_to = addresses
+            .iter()
+            .find_map(|address| staff.iter().find(|(email, _)| email == *address).map(|(_, id)| *id));
+
+        let subject = activity_subject(&message.subject);
+        let description = message.body.as_ref().map(|body| body.chars().take(MAX_DESCRIPTION).collect::<String>());
+        let activity_date = message.date.unwrap_or_else(|| Utc::now().naive_utc());
+        let outcome = if sender_is_staff { "sent" } else { "received" };
+
+        let logged = conn.transaction::<_, CLIERPError, _>(|conn| {
+            let mut logged = Vec::new();
+            for customer_id in &customer_ids {
+                let lead_id = leads::table
+                    .filter(leads::customer_id.eq(customer_id))
+                    .filter(leads::status.ne_all([LeadStatus::ClosedWon.to_string(), LeadStatus::ClosedLost.to_string()]))
+                    .order((leads::created_at.desc(), leads::id.desc()))
+                    .select(leads::id)
+                    .first::<i32>(conn)
+                    .optional()?;
+                let deal_id = match lead_id {
+                    Some(lead_id) => deals::table
+                        .filter(deals::lead_id.eq(lead_id))
+                        .filter(deals::stage.ne_all([DealStage::ClosedWon.to_string(), DealStage::ClosedLost.to_string()]))
+                        .order((deals::created_at.desc(), deals::id.desc()))
+                        .select(deals::id)
+                        .first::<i32>(conn)
+                        .optional()?,
+                    None => None,
+                };
+
+                diesel::insert_into(activities::table)
+                    .values(&NewActivity {
+                        customer_id: Some(*customer_id),
+                        lead_id,
+                        deal_id,
+                        activity_type: ActivityType::Email.to_string(),
+                        subject: subject.clone(),
+                        description: description.clone(),
+                        activity_date,
+                        duration_minutes: None,
+                        outcome: Some(outcome.to_string()),
+                        assigned_to,
+                        completed: true,
+                    })
+                    .execute(conn)?;
+                logged.push(activities::table.order(activities::id.desc()).first::<Activity>(conn)?);
+            }
+
+            diesel::insert_into(dropbox_messages::table)
+                .values(&NewDropboxMessage {
+                    message_id: message_id.clone(),
+                    sender: message.from.clone().unwrap_or_default(),
+                    subject: subject.clone(),
+                    activities: logged.len() as i32,
+                })
+                .execute(conn)?;
+            Ok(logged)
+        })?;
+
+        tracing::info!("Dropbox message {} logged as {} activit(ies)", message_id, logged.len());
+        if logged.is_empty() {
+            Ok(IngestOutcome::Unmatched)
+        } else {
+            QueryCache::current().invalidate(&["crm.activities"]);
+            Ok(IngestOutcome::Logged(logged))
+        }
+    }
+
+    /// Ingest the messages an IMAP syncer delivered to `<maildir>/new`,
+    /// moving each one to `cur` once it is logged. Messages that fail stay
+    /// in `new` and are tried again on the next fetch.
+    pub fn fetch(
+        conn: &mut SqliteConnection,
+        token: &str,
+        maildir: &Path,
+    ) -> Result<Vec<(String, Result<IngestOutcome>)>> {
+        let new_dir = maildir.join("new");
+        let cur_dir = maildir.join("cur");
+        let entries = fs::read_dir(&new_dir)
+            .map_err(|e| CLIERPError::IoError(format!("Cannot read {}: {}", new_dir.display(), e)))?;
+        let mut files: Vec<_> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().map(|t| t.is_file()).unwrap_or(false))
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|name| !name.starts_with('.'))
+            .collect();
+        files.sort();
+
+        let mut fetched = Vec::new();
+        for name in files {
+            let path = new_dir.join(&name);
+            let outcome = fs::read(&path)
+                .map_err(|e| CLIERPError::IoError(format!("Cannot read {}: {}", path.display(), e)))
+                .and_then(|raw| Self::ingest(conn, token, &raw));
+            if outcome.is_ok() {
+                // Maildir marks messages that were moved out of new/ as seen
+                let seen_name = if name.contains(':') { name.clone() } else { format!("{}:2,S", name) };
+                fs::create_dir_all(&cur_dir)?;
+                fs::rename(&path, cur_dir.join(seen_name))?;
+            }
+            fetched.push((name, outcome));
+        }
+        Ok(fetched)
+    }
+
+    /// Lowercase user and employee email addresses with the employee they
+    /// belong to, users first
+    fn staff_addresses(conn: &mut SqliteConnection) -> Result<Vec<(String, i32)>> {
+        let mut staff: Vec<(String, i32)> = users::table
+            .filter(users::employee_id.is_not_null())
+            .filter(users::is_active.eq(true))
+            .select((users::email, users::employee_id.assume_not_null()))
+            .load::<(String, i32)>(conn)?;
+        staff.extend(
+            employees::table
+                .filter(employees::email.is_not_null())
+                .select((employees::email.assume_not_null(), employees::id))
+                .load::<(String, i32)>(conn)?,
+        );
+        for (email, _) in &mut staff {
+            *email = email.trim().to_lowercase();
+        }
+        Ok(staff)
+    }
+}
+
+/// Subject cut to what an activity holds, or a placeholder when there is none
+fn activity_subject(subject: &str) -> String {
+    if subject.chars().count() < 2 {
+        return "(no subject)".to_string();
+    }
+    let mut end = subject.len().min(MAX_SUBJECT);
+    while !subject.is_char_boundary(end) {
+        end -= 1;
+    }
+    subject[..end].to_string()
+}
+
+/// Headers (lowercase names, folded lines joined) and the body after them
+fn split_message(raw: &str) -> (Vec<(String, String)>, &str) {
+    let (head, body) = if let Some(body) = raw.strip_prefix('\n') {
+        ("", body)
+    } else {
+        match raw.find("\n\n") {
+            Some(at) => (&raw[..at], &raw[at + 2..]),
+            None => (raw, ""),
+        }
+    };
+
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in head.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    (headers, body)
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter().find(|(n, _)| n == name).map(|(_, value)| value.as_str())
+}
+
+/// `name=value` parameter of a header such as Content-Type
+fn parameter(value: &str, name: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// The first text/plain part of a body, searching multipart bodies in order
+fn text_body(headers: &[(String, String)], body: &str) -> Option<String> {
+    let content_type = header(headers, "content-type").unwrap_or("text/plain");
+    let mime = content_type.split(';').next().unwrap_or_default().trim().to_lowercase();
+    if mime.starts_with("multipart/") {
+        let delimiter = format!("--{}", parameter(content_type, "boundary")?);
+        return body
+            .split(delimiter.as_str())
+            .skip(1)
+            .take_while(|part| !part.starts_with("--"))
+            .find_map(|part| {
+                // The rest of the delimiter line is padding
+                let (_, part) = part.split_once('\n')?;
+                let (part_headers, part_body) = split_message(part);
+                text_body(&part_headers, part_body)
+            });
+    }
+    if mime != "text/plain" {
+        return None;
+    }
+
+    let encoding = header(headers, "content-transfer-encoding").unwrap_or_default().trim().to_lowercase();
+    let bytes = match encoding.as_str() {
+        "quoted-printable" => decode_quoted_printable(body),
+        "base64" => base64_decode(body)?,
+        _ => body.as_bytes().to_vec(),
+    };
+    let text = String::from_utf8_lossy(&bytes).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Addresses of a To/From/Cc list, lowercase
+fn addresses(value: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut mailbox = String::new();
+    let mut quoted = false;
+    for c in value.chars().chain(std::iter::once(',')) {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                mailbox.push(c);
+            }
+            ',' if !quoted => {
+                let spec = match (mailbox.rfind('<'), mailbox.rfind('>')) {
+                    (Some(start), Some(end)) if start < end => &mailbox[start + 1..end],
+                    _ => mailbox.as_str(),
+                };
+                let spec = spec.trim();
+                if spec.contains('@') && !spec.contains(char::is_whitespace) {
+                    found.push(spec.to_lowercase());
+                }
+                mailbox.clear();
+            }
+            _ => mailbox.push(c),
+        }
+    }
+    found
+}
+
+/// Date header in UTC; a trailing comment such as "(KST)" is ignored
+fn parse_date(value: &str) -> Option<NaiveDateTime> {
+    let value = value.split(" (").next()?.trim();
+    DateTime::parse_from_rfc2822(value).ok().map(|date| date.naive_utc())
+}
+
+/// Header text with RFC 2047 encoded words (`=?utf-8?B?...?=`) decoded.
+/// Whitespace between two encoded words is dropped.
+fn decode_words(value: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        match decode_word(&rest[start + 2..]) {
+            Some((text, remainder)) => {
+                let between = &rest[..start];
+                if !(after_word && between.trim().is_empty()) {
+                    decoded.push_str(between);
+                }
+                decoded.push_str(&text);
+                rest = remainder;
+                after_word = true;
+            }
+            None => {
+                decoded.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+                after_word = false;
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// One encoded word after its `=?`, and the text after it
+fn decode_word(word: &str) -> Option<(String, &str)> {
+    let (_charset, word) = word.split_once('?')?;
+    let (encoding, word) = word.split_once('?')?;
+    let end = word.find("?=")?;
+    let bytes = match encoding {
+        "B" | "b" => base64_decode(&word[..end])?,
+        "Q" | "q" => decode_quoted_printable(&word[..end].replace('_', " ")),
+        _ => return None,
+    };
+    Some((String::from_utf8_lossy(&bytes).into_owned(), &word[end + 2..]))
+}
+
+fn decode_quoted_printable(input: &str) -> Vec<u8> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'=' {
+            // Soft line break
+            if bytes.get(i + 1) == Some(&b'\n') {
+                i += 2;
+                continue;
+            }
+            if let Some(byte) = input.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    decoded
+}
+
+/// FNV-1a, to identify a message that has no Message-ID
+fn fingerprint(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_email() {
+        let raw = "Message-ID: <abc123@mail.example.com>\r\n\
+            From: \"Kim, Minji\" <Minji.Kim@Example.com>\r\n\
+            To: buyer@customer.co.kr,\r\n\x20\"Lee\" <lee@customer.co.kr>\r\n\
+            Bcc: dropbox-0123456789abcdef@erp.example.com\r\n\
+            Subject: =?UTF-8?B?6rKs7KCB?= =?UTF-8?Q?_follow-up?=\r\n\
+            Date: Tue, 5 Mar 2024 14:30:00 +0900 (KST)\r\n\
+            Content-Type: multipart/alternative; boundary=\"b1\"\r\n\
+            \r\n\
+            preamble\r\n\
+            --b1\r\n\
+            Content-Type: text/plain; charset=utf-8\r\n\
+            Content-Transfer-Encoding: quoted-printable\r\n\
+            \r\n\
+            Price is 1,000 =3D final.=\r\n\
+            \x20Thanks\r\n\
+            --b1\r\n\
+            Content-Type: text/html\r\n\
+            \r\n\
+            <p>ignored</p>\r\n\
+            --b1--\r\n";
+        let message = EmailMessage::parse(raw).unwrap();
+        assert_eq!(message.message_id.as_deref(), Some("abc123@mail.example.com"));
+        assert_eq!(message.from.as_deref(), Some("minji.kim@example.com"));
+        assert_eq!(
+            message.recipients,
+            ["buyer@customer.co.kr", "lee@customer.co.kr", "dropbox-0123456789abcdef@erp.example.com"]
+        );
+        assert_eq!(message.subject, "견적 follow-up");
+        assert_eq!(message.date.unwrap().to_string(), "2024-03-05 05:30:00");
+        assert_eq!(message.body.as_deref(), Some("Price is 1,000 = final. Thanks"));
+
+        let plain = EmailMessage::parse("To: a@b.com\n\nSGVsbG8=\n").unwrap();
+        assert_eq!(plain.from, None);
+        assert_eq!(plain.subject, "");
+        assert_eq!(plain.body.as_deref(), Some("SGVsbG8="));
+        assert_eq!(base64_decode("SGVs\nbG8=").unwrap(), b"Hello");
+        assert!(EmailMessage::parse("Subject: hi\n\nbody").is_err());
+
+        assert_eq!(activity_subject(""), "(no subject)");
+        assert_eq!(activity_subject(&"가".repeat(100)).len(), 198);
+        let token = dropbox_token("secret").unwrap();
+        assert_eq!(token.len(), TOKEN_LENGTH);
+        assert_ne!(token, dropbox_token("other secret").unwrap());
+    }
+}
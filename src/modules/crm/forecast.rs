@@ -0,0 +1,484 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::models::{NewSalesQuota, SalesQuota};
+use crate::database::schema::{customers, deals, employees, leads, sales_quotas};
+use crate::database::{DatabaseConnection, DealStage};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Share of the remaining forecast taken from the open pipeline; the rest
+/// comes from the seasonal run rate
+const PIPELINE_WEIGHT: f64 = 0.7;
+
+/// Closed deals a rep or segment needs before its own win rate is used
+const MIN_CLOSED_DEALS: usize = 3;
+
+/// Standard deviations either side of the forecast in its confidence band (80%)
+const BAND_Z: f64 = 1.28;
+
+/// Forecast of the revenue one sales rep (or the whole team) closes in a period
+#[derive(Debug, Clone, Serialize)]
+pub struct RepForecast {
+    /// `None` for deals with no rep, and for the team total
+    pub employee_id: Option<i32>,
+    pub name: String,
+    /// Sum of the monthly quotas in the period
+    pub quota: Option<i64>,
+    /// Deals already won in the period
+    pub booked: i64,
+    pub open_deals: usize,
+    /// Open deals expected to close in the period, at full value
+    pub pipeline: i64,
+    /// Pipeline weighted by the deals' stage probabilities
+    pub stage_weighted: i64,
+    /// Pipeline weighted by stage probability blended with historical win rates
+    pub expected_pipeline: i64,
+    /// Historical monthly wins over the rest of the period, adjusted for seasonality
+    pub run_rate: i64,
+    pub forecast: i64,
+    pub low: i64,
+    pub high: i64,
+    #[serde(skip)]
+    variance: f64,
+}
+
+impl RepForecast {
+    /// Forecast as a percentage of quota
+    pub fn attainment(&self) -> Option<f64> {
+        self.quota
+            .filter(|quota| *quota > 0)
+            .map(|quota| self.forecast as f64 / quota as f64 * 100.0)
+    }
+
+    fn new(employee_id: Option<i32>) -> Self {
+        Self {
+            employee_id,
+            name: "Unassigned".to_string(),
+            quota: None,
+            booked: 0,
+            open_deals: 0,
+            pipeline: 0,
+            stage_weighted: 0,
+            expected_pipeline: 0,
+            run_rate: 0,
+            forecast: 0,
+            low: 0,
+            high: 0,
+            variance: 0.0,
+        }
+    }
+
+    /// Fill in the forecast and its band from the components
+    fn finish(&mut self) {
+        let (forecast, low, high) = band(self.booked, self.expected_pipeline, self.run_rate, self.variance);
+        self.forecast = forecast;
+        self.low = low;
+        self.high = high;
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SalesForecast {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub as_of: NaiveDate,
+    /// Reps by forecast, largest first
+    pub reps: Vec<RepForecast>,
+    pub total: RepForecast,
+}
+
+/// A deal as the forecast sees it
+#[derive(Debug, Clone)]
+struct ForecastDeal {
+    rep: Option<i32>,
+    segment: String,
+    stage: String,
+    value: i64,
+    probability: Option<i32>,
+    /// Expected close date of an open deal, or when a closed deal closed
+    closed_on: Option<NaiveDate>,
+}
+
+/// Historical win rates by rep and customer segment
+#[derive(Debug, Default)]
+pub struct WinRates {
+    by_rep_segment: HashMap<(Option<i32>, String), (usize, usize)>,
+    by_rep: HashMap<Option<i32>, (usize, usize)>,
+    overall: (usize, usize),
+}
+
+impl WinRates {
+    /// Count closed deals as (rep, segment, won)
+    pub fn from_closed<'a>(closed: impl IntoIterator<Item = (Option<i32>, &'a str, bool)>) -> Self {
+        let mut rates = Self::default();
+        for (rep, segment, won) in closed {
+            for counts in [
+                rates.by_rep_segment.entry((rep, segment.to_string())).or_default(),
+                rates.by_rep.entry(rep).or_default(),
+                &mut rates.overall,
+            ] {
+                counts.0 += usize::from(won);
+                counts.1 += 1;
+            }
+        }
+        rates
+    }
+
+    /// Win rate of deals of `rep` with `segment` customers, falling back to
+    /// the rep's overall and then the team's rate where there is too little history
+    pub fn rate(&self, rep: Option<i32>, segment: &str) -> Option<f64> {
+        let rate = |(won, closed): (usize, usize)| (closed >= MIN_CLOSED_DEALS).then(|| won as f64 / closed as f64);
+        self.by_rep_segment
+            .get(&(rep, segment.to_string()))
+            .copied()
+            .and_then(rate)
+            .or_else(|| self.by_rep.get(&rep).copied().and_then(rate))
+            .or_else(|| rate(self.overall))
+    }
+}
+
+/// Sales forecasts per rep, blending the weighted pipeline with historical
+/// win rates and seasonal run rates.
+///
+/// An open deal counts at its value times the average of its stage
+/// probability and the historical win rate of its rep with customers of its
+/// segment over the last twelve months. What is not yet booked is forecast
+/// as 70% pipeline and 30% seasonal run rate (the rep's average monthly wins
+/// scaled by how the months of the period compared with the average month
+/// over the last two years). The confidence band treats each open deal as
+/// an independent win or loss at its blended probability.
+pub struct ForecastService;
+
+impl ForecastService {
+    /// Set `employee_id`'s quota to `amount` for every month from `start` to `end`
+    pub fn set_quota(
+        conn: &mut DatabaseConnection,
+        employee_id: i32,
+        start: NaiveDate,
+        end: NaiveDate,
+        amount: i32,
+        created_by: Option<i32>,
+    ) -> Result<Vec<SalesQuota>> {
+        if amount < 0 {
+            return Err(CLIERPError::ValidationError("A quota cannot be negative".to_string()));
+        }
+        let exists = employees::table
+            .find(employee_id)
+            .select(employees::id)
+            .first::<i32>(conn)
+            .optional()?;
+        if exists.is_none() {
+            return Err(CLIERPError::NotFound(format!("Employee {} not found", employee_id)));
+        }
+
+        let periods: Vec<String> = months(start, end).iter().map(|(month, _)| month_label(*month)).collect();
+        conn.transaction::<_, CLIERPError, _>(|conn| {
+            for period in &periods {
+                let updated = diesel::update(
+                    sales_quotas::table
+                        .filter(sales_quotas::employee_id.eq(employee_id))
+                        .filter(sales_quotas::period.eq(period)),
+                )
+                .set((sales_quotas::amount.eq(amount), sales_quotas::created_by.eq(created_by)))
+                .execute(conn)?;
+                if updated == 0 {
+                    diesel::insert_into(sales_quotas::table)
+                        .values(&NewSalesQuota {
+                            employee_id,
+                            period: period.clone(),
+                            amount,
+                            created_by,
+                        })
+                        .execute(conn)?;
+                }
+            }
+            Ok(())
+        })?;
+
+        tracing::info!("Set quota of employee {} to {} for {} month(s)", employee_id, amount, periods.len());
+        Self::quotas(conn, start, end)
+            .map(|quotas| quotas.into_iter().filter(|q| q.employee_id == employee_id).collect())
+    }
+
+    /// Quotas of the months from `start` to `end`, by rep and month
+    pub fn quotas(conn: &mut DatabaseConnection, start: NaiveDate, end: NaiveDate) -> Result<Vec<SalesQuota>> {
+        let periods: Vec<String> = months(start, end).iter().map(|(month, _)| month_label(*month)).collect();
+        sales_quotas::table
+            .filter(sales_quotas::period.eq_any(periods))
+            .order((sales_quotas::employee_id.asc(), sales_quotas::period.asc()))
+            .load::<SalesQuota>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Forecast the revenue closed from `start` to `end`, seen from `as_of`
+    pub fn forecast(
+        conn: &mut DatabaseConnection,
+        start: NaiveDate,
+        end: NaiveDate,
+        as_of: NaiveDate,
+        rep: Option<i32>,
+    ) -> Result<SalesForecast> {
+        if end < start {
+            return Err(CLIERPError::ValidationError("The forecast period ends before it starts".to_string()));
+        }
+        let deals = Self::deals(conn)?;
+        let won = DealStage::ClosedWon.to_string();
+        let lost = DealStage::ClosedLost.to_string();
+        let is_closed = |deal: &ForecastDeal| deal.stage == won || deal.stage == lost;
+
+        let year_ago = as_of - Duration::days(365);
+        let win_rates = WinRates::from_closed(
+            deals
+                .iter()
+                .filter(|d| is_closed(d) && d.closed_on.is_some_and(|on| on > year_ago && on <= as_of))
+                .map(|d| (d.rep, d.segment.as_str(), d.stage == won)),
+        );
+
+        // Monthly wins over the last two years, for seasonality
+        let two_years_ago = month_start(as_of) - Months::new(24);
+        let mut monthly_won: HashMap<NaiveDate, i64> = HashMap::new();
+        for deal in deals.iter().filter(|d| d.stage == won) {
+            if let Some(on) = deal.closed_on.filter(|on| *on >= two_years_ago && *on < month_start(as_of)) {
+                *monthly_won.entry(month_start(on)).or_default() += deal.value;
+            }
+        }
+        let season = seasonality(&monthly_won, two_years_ago, 24);
+        // Months of the period still ahead, weighted by their season
+        let remaining_months: f64 = months(start.max(as_of), end)
+            .iter()
+            .map(|(month, share)| share * season[month.month0() as usize])
+            .sum();
+
+        let mut quotas: HashMap<i32, i64> = HashMap::new();
+        for quota in Self::quotas(conn, start, end)? {
+            *quotas.entry(quota.employee_id).or_default() += i64::from(quota.amount);
+        }
+
+        let mut by_rep: HashMap<Option<i32>, RepForecast> = HashMap::new();
+        let mut trailing_won: HashMap<Option<i32>, i64> = HashMap::new();
+        for deal in deals.iter().filter(|d| rep.is_none() || d.rep == rep) {
+            let entry = by_rep.entry(deal.rep).or_insert_with(|| RepForecast::new(deal.rep));
+            let in_period = deal.closed_on.is_some_and(|on| on >= start && on <= end);
+            if deal.stage == won {
+                if in_period && deal.closed_on.is_some_and(|on| on <= as_of) {
+                    entry.booked += deal.value;
+                }
+                if deal.closed_on.is_some_and(|on| on > year_ago && on <= as_of) {
+                    *trailing_won.entry(deal.rep).or_default() += deal.value;
+                }
+            } else if !is_closed(deal) && in_period {
+                let stage_probability = f64::from(deal.probability.unwrap_or(0).clamp(0, 100)) / 100.0;
+                let probability = blend_probability(stage_probability, win_rates.rate(deal.rep, &deal.segment));
+                entry.open_deals += 1;
+                entry.pipeline += deal.value;
+                entry.stage_weighted += (deal.value as f64 * stage_probability).round() as i64;
+                entry.expected_pipeline += (deal.value as f64 * probability).round() as i64;
+                entry.variance += (deal.value as f64).powi(2) * probability * (1.0 - probability);
+            }
+        }
+
+        let names: HashMap<i32, String> = employees::table
+            .filter(employees::id.eq_any(by_rep.keys().flatten().copied().collect::<Vec<_>>()))
+            .select((employees::id, employees::name))
+            .load::<(i32, String)>(conn)?
+            .into_iter()
+            .collect();
+        let mut reps: Vec<RepForecast> = by_rep
+            .into_values()
+            .filter(|forecast| forecast.booked > 0 || forecast.open_deals > 0 || trailing_won.contains_key(&forecast.employee_id))
+            .map(|mut forecast| {
+                if let Some(id) = forecast.employee_id {
+                    forecast.name = names.get(&id).cloned().unwrap_or_else(|| format!("Employee {}", id));
+                    forecast.quota = quotas.get(&id).copied();
+                }
+                let monthly = trailing_won.get(&forecast.employee_id).copied().unwrap_or(0) as f64 / 12.0;
+                forecast.run_rate = (monthly * remaining_months).round() as i64;
+                forecast.finish();
+                forecast
+            })
+            .collect();
+        reps.sort_by(|a, b| b.forecast.cmp(&a.forecast).then_with(|| a.name.cmp(&b.name)));
+
+        let mut total = RepForecast::new(None);
+        total.name = "Total".to_string();
+        for forecast in &reps {
+            total.quota = match (total.quota, forecast.quota) {
+                (None, None) => None,
+                (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+            };
+            total.booked += forecast.booked;
+            total.open_deals += forecast.open_deals;
+            total.pipeline += forecast.pipeline;
+            total.stage_weighted += forecast.stage_weighted;
+            total.expected_pipeline += forecast.expected_pipeline;
+            total.run_rate += forecast.run_rate;
+            total.variance += forecast.variance;
+        }
+        total.finish();
+
+        Ok(SalesForecast { start, end, as_of, reps, total })
+    }
+
+    fn deals(conn: &mut DatabaseConnection) -> Result<Vec<ForecastDeal>> {
+        let rows = deals::table
+            .left_join(leads::table.left_join(customers::table))
+            .select((
+                deals::stage,
+                deals::deal_value,
+                deals::final_amount,
+                deals::close_date,
+                deals::probability,
+                deals::assigned_to,
+                deals::updated_at,
+                customers::customer_type.nullable(),
+            ))
+            .load::<(String, i32, Option<i32>, Option<NaiveDate>, Option<i32>, Option<i32>, NaiveDateTime, Option<String>)>(
+                conn,
+            )?;
+        let won = DealStage::ClosedWon.to_string();
+        let lost = DealStage::ClosedLost.to_string();
+        Ok(rows
+            .into_iter()
+            .map(|(stage, value, final_amount, close_date, probability, rep, updated_at, segment)| {
+                // Closed deals without a close date closed when last updated
+                let closed_on = if stage == won || stage == lost {
+                    close_date.or(Some(updated_at.date()))
+                } else {
+                    close_date
+                };
+                ForecastDeal {
+                    rep,
+                    segment: segment.unwrap_or_else(|| "unknown".to_string()),
+                    stage,
+                    value: i64::from(final_amount.unwrap_or(value)),
+                    probability,
+                    closed_on,
+                }
+            })
+            .collect())
+    }
+}
+
+/// Probability of winning an open deal: the average of its stage
+/// probability and the historical win rate, or the stage probability alone
+pub fn blend_probability(stage_probability: f64, win_rate: Option<f64>) -> f64 {
+    match win_rate {
+        Some(rate) => (stage_probability + rate) / 2.0,
+        None => stage_probability,
+    }
+}
+
+/// (forecast, low, high) from booked revenue, the expected pipeline, the
+/// seasonal run rate and the variance of the pipeline
+pub fn band(booked: i64, expected_pipeline: i64, run_rate: i64, variance: f64) -> (i64, i64, i64) {
+    let remaining = PIPELINE_WEIGHT * expected_pipeline as f64 + (1.0 - PIPELINE_WEIGHT) * run_rate as f64;
+    let spread = BAND_Z * PIPELINE_WEIGHT * variance.sqrt();
+    let forecast = booked as f64 + remaining;
+    (
+        forecast.round() as i64,
+        (forecast - spread).max(booked as f64).round() as i64,
+        (forecast + spread).round() as i64,
+    )
+}
+
+/// Seasonal factor of each calendar month: its average wins over the
+/// `months` months from `from`, relative to the average month. All 1.0
+/// without history.
+pub fn seasonality(monthly_won: &HashMap<NaiveDate, i64>, from: NaiveDate, months: u32) -> [f64; 12] {
+    let total: i64 = monthly_won.values().sum();
+    if total <= 0 || months == 0 {
+        return [1.0; 12];
+    }
+    let average = total as f64 / months as f64;
+    let mut sums = [0i64; 12];
+    let mut counts = [0u32; 12];
+    for offset in 0..months {
+        let month = from + Months::new(offset);
+        sums[month.month0() as usize] += monthly_won.get(&month).copied().unwrap_or(0);
+        counts[month.month0() as usize] += 1;
+    }
+    let mut factors = [1.0; 12];
+    for month in 0..12 {
+        if counts[month] > 0 {
+            factors[month] = sums[month] as f64 / counts[month] as f64 / average;
+        }
+    }
+    factors
+}
+
+/// Months overlapping `start..=end`, as (first day, share of the month in the range)
+fn months(start: NaiveDate, end: NaiveDate) -> Vec<(NaiveDate, f64)> {
+    let mut months = Vec::new();
+    let mut month = month_start(start);
+    while month <= end {
+        let next = month + Months::new(1);
+        let days = (next - month).num_days() as f64;
+        let from = start.max(month);
+        let to = end.min(next - Duration::days(1));
+        months.push((month, ((to - from).num_days() + 1) as f64 / days));
+        month = next;
+    }
+    months
+}
+
+fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+fn month_label(date: NaiveDate) -> String {
+    date.format("%Y-%m").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_forecast_components() {
+        let rates = WinRates::from_closed([
+            (Some(1), "enterprise", true),
+            (Some(1), "enterprise", true),
+            (Some(1), "enterprise", false),
+            (Some(1), "smb", false),
+            (Some(2), "smb", true),
+        ]);
+        assert_eq!(rates.rate(Some(1), "enterprise"), Some(2.0 / 3.0));
+        // Too few SMB deals for rep 1: the rep's overall rate
+        assert_eq!(rates.rate(Some(1), "smb"), Some(0.5));
+        // Nothing for rep 2 or 3 on their own: the team rate
+        assert_eq!(rates.rate(Some(3), "smb"), Some(3.0 / 5.0));
+        assert_eq!(WinRates::default().rate(Some(1), "smb"), None);
+        assert_eq!(blend_probability(0.6, Some(0.4)), 0.5);
+        assert_eq!(blend_probability(0.6, None), 0.6);
+
+        // December sells well above the average month of 115
+        let from = date("2023-01-01");
+        let mut won = HashMap::new();
+        for offset in 0..24 {
+            let month = from + Months::new(offset);
+            won.insert(month, if month.month() == 12 { 280 } else { 100 });
+        }
+        let season = seasonality(&won, from, 24);
+        assert!((season[11] - 280.0 / 115.0).abs() < 1e-9);
+        assert!((season[0] - 100.0 / 115.0).abs() < 1e-9);
+        assert_eq!(seasonality(&HashMap::new(), from, 24), [1.0; 12]);
+
+        assert_eq!(months(date("2025-06-16"), date("2025-07-31")), vec![(date("2025-06-01"), 0.5), (date("2025-07-01"), 1.0)]);
+
+        // 100 booked + 0.7 * 1000 + 0.3 * 500, band of 1.28 * 0.7 * 100
+        assert_eq!(band(100, 1000, 500, 10_000.0), (950, 860, 1040));
+        // The low end never drops below what is booked
+        assert_eq!(band(100, 0, 0, 1_000_000.0).1, 100);
+    }
+}
@@ -5,16 +5,37 @@ use crate::core::result::CLIERPResult;
 // Type alias for convenience
 type Result<T> = CLIERPResult<T>;
 use crate::database::{
-    DatabaseConnection, Lead, NewLead, LeadStatus, LeadPriority, LeadWithCustomer, Customer
+    DatabaseConnection, Lead, NewLead, LeadStatus, LeadPriority, LeadWithCustomer, Customer, RecordVisibility
 };
+use crate::modules::crm::visibility::RecordScope;
 use crate::database::schema::{leads, customers, employees};
+use crate::database::EventAction;
+use crate::modules::system::events::EventService;
+use crate::utils::cache::QueryCache;
 use crate::utils::validation::validate_required_string;
-use crate::utils::pagination::{Paginate, PaginationParams, PaginatedResult};
+use crate::utils::pagination::{PaginationParams, PaginatedResult};
 use crate::utils::filters::FilterOptions;
+use crate::utils::filter_expr::FilterField;
+
+/// Fields `lead list --where` may use
+pub const LEAD_WHERE_FIELDS: &[FilterField] = &[
+    FilterField::integer("id", "leads.id"),
+    FilterField::text("title", "leads.title"),
+    FilterField::integer("customer", "leads.customer_id"),
+    FilterField::text("source", "leads.lead_source"),
+    FilterField::text("status", "leads.status"),
+    FilterField::text("priority", "leads.priority"),
+    FilterField::integer("value", "leads.estimated_value"),
+    FilterField::integer("probability", "leads.probability"),
+    FilterField::date("close_date", "leads.expected_close_date"),
+    FilterField::integer("assigned_to", "leads.assigned_to"),
+    FilterField::date("created", "leads.created_at"),
+];
 
 pub struct LeadService;
 
 impl LeadService {
+    #[allow(clippy::too_many_arguments)]
     pub fn create_lead(
         conn: &mut DatabaseConnection,
         title: &str,
@@ -78,9 +99,9 @@ impl LeadService {
             title: title.to_string(),
             description: description.map(|s| s.to_string()),
             notes: notes.map(|s| s.to_string()),
+            visibility: RecordVisibility::Private.to_string(),
         };
 
-        let current_time = Utc::now().naive_utc();
         let new_lead_with_time = NewLead {
             customer_id: new_lead.customer_id,
             lead_source: new_lead.lead_source.clone(),
@@ -93,20 +114,21 @@ impl LeadService {
             title: new_lead.title.clone(),
             description: new_lead.description.clone(),
             notes: new_lead.notes.clone(),
+            visibility: new_lead.visibility.clone(),
         };
 
         diesel::insert_into(leads::table)
             .values(&new_lead_with_time)
             .execute(conn)?;
-
-        // Get the inserted lead by searching for the most recent lead with matching criteria
-        leads::table
-            .filter(leads::title.eq(&new_lead.title))
-            .filter(leads::lead_source.eq(&new_lead.lead_source))
-            .filter(leads::customer_id.eq(&new_lead.customer_id))
-            .order(leads::created_at.desc())
-            .first::<Lead>(conn)
-            .map_err(Into::into)
+        QueryCache::current().invalidate(&["crm.leads", "crm.campaigns"]);
+
+        // Get the inserted lead, the newest one; filtering on `customer_id`
+        // would miss it when it is NULL
+        let lead = leads::table
+            .order(leads::id.desc())
+            .first::<Lead>(conn)?;
+        EventService::record(conn, "leads", lead.id, EventAction::Created, &lead)?;
+        Ok(lead)
     }
 
     pub fn get_lead_by_id(conn: &mut DatabaseConnection, lead_id: i32) -> Result<Option<Lead>> {
@@ -117,8 +139,14 @@ impl LeadService {
             .map_err(Into::into)
     }
 
-    pub fn get_lead_with_customer(conn: &mut DatabaseConnection, lead_id: i32) -> Result<Option<LeadWithCustomer>> {
-        let lead = Self::get_lead_by_id(conn, lead_id)?;
+    /// Load a lead with its customer; leads outside `scope` are reported as not found
+    pub fn get_lead_with_customer(
+        conn: &mut DatabaseConnection,
+        lead_id: i32,
+        scope: &RecordScope,
+    ) -> Result<Option<LeadWithCustomer>> {
+        let lead = Self::get_lead_by_id(conn, lead_id)?
+            .filter(|lead| scope.can_see(lead.assigned_to, &lead.visibility));
 
         if let Some(lead) = lead {
             // Get customer info if available
@@ -156,6 +184,7 @@ impl LeadService {
         conn: &mut DatabaseConnection,
         filters: &FilterOptions,
         pagination: &PaginationParams,
+        scope: &RecordScope,
     ) -> Result<PaginatedResult<LeadWithCustomer>> {
         let mut query = leads::table
             .left_join(customers::table)
@@ -166,8 +195,31 @@ impl LeadService {
                 employees::name.nullable(),
             ))
             .into_boxed();
+        let mut count_query = leads::table.into_boxed();
+
+        // Restrict to records the user may see
+        if let Some(owners) = &scope.owners {
+            query = query.filter(
+                leads::visibility.eq(RecordVisibility::Public.to_string())
+                    .or(leads::assigned_to.eq_any(owners.clone()))
+                    .or(leads::visibility.eq(RecordVisibility::Team.to_string())
+                        .and(leads::assigned_to.eq_any(scope.team.clone())))
+            );
+            count_query = count_query.filter(
+                leads::visibility.eq(RecordVisibility::Public.to_string())
+                    .or(leads::assigned_to.eq_any(owners.clone()))
+                    .or(leads::visibility.eq(RecordVisibility::Team.to_string())
+                        .and(leads::assigned_to.eq_any(scope.team.clone())))
+            );
+        }
 
         // Apply filters
+        if let Some(expr) = &filters.where_clause {
+            let predicate = expr.predicate(LEAD_WHERE_FIELDS)?;
+            query = query.filter(predicate.clone());
+            count_query = count_query.filter(predicate);
+        }
+
         if let Some(search) = &filters.search {
             query = query.filter(
                 leads::title.like(format!("%{}%", search))
@@ -248,7 +300,7 @@ impl LeadService {
             .limit(pagination.limit())
             .load(conn)?;
 
-        let total_items = leads::table.count().get_result::<i64>(conn)?;
+        let total_items = count_query.count().get_result::<i64>(conn)?;
 
         let leads_with_customer: Vec<LeadWithCustomer> = results
             .into_iter()
@@ -294,6 +346,7 @@ impl LeadService {
                 leads::updated_at.eq(Utc::now().naive_utc()),
             ))
             .execute(conn)?;
+        QueryCache::current().invalidate(&["crm.leads", "crm.campaigns"]);
 
         // Get the updated lead
         leads::table
@@ -302,6 +355,7 @@ impl LeadService {
             .map_err(Into::into)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn update_lead(
         conn: &mut DatabaseConnection,
         lead_id: i32,
@@ -414,6 +468,7 @@ impl LeadService {
         diesel::update(leads::table.find(lead_id))
             .set(leads::updated_at.eq(current_time))
             .execute(conn)?;
+        QueryCache::current().invalidate(&["crm.leads", "crm.campaigns"]);
 
         // Get the updated lead
         leads::table
@@ -422,6 +477,30 @@ impl LeadService {
             .map_err(Into::into)
     }
 
+    /// Change who besides the owner can see a lead
+    pub fn set_visibility(
+        conn: &mut DatabaseConnection,
+        lead_id: i32,
+        visibility: RecordVisibility,
+    ) -> Result<Lead> {
+        Self::get_lead_by_id(conn, lead_id)?
+            .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
+                format!("Lead with ID {} not found", lead_id)
+            ))?;
+
+        diesel::update(leads::table.find(lead_id))
+            .set((
+                leads::visibility.eq(visibility.to_string()),
+                leads::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+
+        leads::table
+            .find(lead_id)
+            .first::<Lead>(conn)
+            .map_err(Into::into)
+    }
+
     pub fn assign_lead(
         conn: &mut DatabaseConnection,
         lead_id: i32,
@@ -463,6 +542,7 @@ impl LeadService {
 
         let deleted_rows = diesel::delete(leads::table.find(lead_id))
             .execute(conn)?;
+        QueryCache::current().invalidate(&["crm.leads", "crm.campaigns"]);
 
         Ok(deleted_rows > 0)
     }
@@ -495,7 +575,12 @@ impl LeadService {
         Ok(leads_with_customer)
     }
 
+    /// Cached in the query cache until a change invalidates it or the TTL runs out
     pub fn get_lead_statistics(conn: &mut DatabaseConnection) -> Result<LeadStatistics> {
+        QueryCache::current().get_or_compute("crm.leads.statistics", || Self::compute_lead_statistics(conn))
+    }
+
+    fn compute_lead_statistics(conn: &mut DatabaseConnection) -> Result<LeadStatistics> {
         // Total leads count
         let total_leads = leads::table
             .count()
@@ -571,7 +656,7 @@ impl LeadService {
     }
 }
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct LeadStatistics {
     pub total_leads: i64,
     pub new_leads: i64,
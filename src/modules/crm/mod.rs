@@ -1,11 +1,27 @@
+pub mod attribution;
+pub mod churn;
+pub mod credit;
 pub mod customer;
+pub mod customer_portal;
+pub mod dropbox;
+pub mod forecast;
 pub mod lead;
 pub mod deal;
 pub mod campaign;
+pub mod survey;
 pub mod activity;
+pub mod visibility;
 
+pub use attribution::*;
+pub use churn::*;
+pub use credit::*;
 pub use customer::*;
+pub use customer_portal::*;
+pub use dropbox::*;
+pub use forecast::*;
 pub use lead::*;
 pub use deal::*;
 pub use campaign::*;
+pub use survey::*;
 pub use activity::*;
+pub use visibility::*;
@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{customer_surveys, customers, deals, leads};
+use crate::database::{CustomerSurvey, NewCustomerSurvey, SurveyChannel};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Net Promoter Score of a set of survey answers: the percentage of
+/// promoters (9-10) less the percentage of detractors (0-6)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct NpsScore {
+    pub responses: usize,
+    pub promoters: usize,
+    pub passives: usize,
+    pub detractors: usize,
+}
+
+impl NpsScore {
+    pub fn from_scores(scores: impl IntoIterator<Item = i32>) -> Self {
+        let mut nps = NpsScore::default();
+        for score in scores {
+            nps.add(score);
+        }
+        nps
+    }
+
+    fn add(&mut self, score: i32) {
+        self.responses += 1;
+        match score {
+            9.. => self.promoters += 1,
+            7..=8 => self.passives += 1,
+            _ => self.detractors += 1,
+        }
+    }
+
+    /// -100 to 100; `None` without responses
+    pub fn score(&self) -> Option<f64> {
+        (self.responses > 0)
+            .then(|| (self.promoters as f64 - self.detractors as f64) * 100.0 / self.responses as f64)
+    }
+}
+
+/// NPS of a period overall, per customer segment and per month
+#[derive(Debug, Clone, Serialize)]
+pub struct NpsSummary {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub overall: NpsScore,
+    /// By customer type
+    pub by_segment: BTreeMap<String, NpsScore>,
+    /// By `YYYY-MM`, oldest first
+    pub by_month: BTreeMap<String, NpsScore>,
+}
+
+/// Customer satisfaction surveys, each attached to a customer and
+/// optionally to the deal it was about
+pub struct SurveyService;
+
+impl SurveyService {
+    #[allow(clippy::too_many_arguments)]
+    pub fn record(
+        conn: &mut SqliteConnection,
+        customer_id: i32,
+        deal_id: Option<i32>,
+        score: i32,
+        comment: Option<&str>,
+        survey_date: NaiveDate,
+        channel: SurveyChannel,
+        created_by: Option<i32>,
+    ) -> Result<CustomerSurvey> {
+        if !(0..=10).contains(&score) {
+            return Err(CLIERPError::ValidationError("Survey score must be between 0 and 10".to_string()));
+        }
+        let customer_exists = customers::table.find(customer_id).count().get_result::<i64>(conn)? > 0;
+        if !customer_exists {
+            return Err(CLIERPError::NotFound(format!("Customer {} not found", customer_id)));
+        }
+        if let Some(deal_id) = deal_id {
+            // The deal must be the customer's, through the lead it came from
+            let deal_customer = deals::table
+                .left_join(leads::table)
+                .filter(deals::id.eq(deal_id))
+                .select(leads::customer_id.nullable())
+                .first::<Option<i32>>(conn)
+                .optional()?
+                .ok_or_else(|| CLIERPError::NotFound(format!("Deal {} not found", deal_id)))?;
+            if deal_customer.is_some_and(|id| id != customer_id) {
+                return Err(CLIERPError::ValidationError(format!(
+                    "Deal {} belongs to another customer",
+                    deal_id
+                )));
+            }
+        }
+
+        diesel::insert_into(customer_surveys::table)
+            .values(&NewCustomerSurvey {
+                customer_id,
+                deal_id,
+                score,
+                comment: comment.map(str::to_string),
+                survey_date,
+                channel: channel.to_string(),
+                created_by,
+            })
+            .execute(conn)?;
+        Ok(customer_surveys::table
+            .order(customer_surveys::id.desc())
+            .first::<CustomerSurvey>(conn)?)
+    }
+
+    /// Surveys of a customer or deal (or all), latest first
+    pub fn list(
+        conn: &mut SqliteConnection,
+        customer_id: Option<i32>,
+        deal_id: Option<i32>,
+        limit: i64,
+    ) -> Result<Vec<CustomerSurvey>> {
+        let mut query = customer_surveys::table.into_boxed();
+        if let Some(customer_id) = customer_id {
+            query = query.filter(customer_surveys::customer_id.eq(customer_id));
+        }
+        if let Some(deal_id) = deal_id {
+            query = query.filter(customer_surveys::deal_id.eq(deal_id));
+        }
+        Ok(query
+            .order((customer_surveys::survey_date.desc(), customer_surveys::id.desc()))
+            .limit(limit)
+            .load::<CustomerSurvey>(conn)?)
+    }
+
+    /// NPS of the surveys taken between `start` and `end`
+    pub fn nps(conn: &mut SqliteConnection, start: NaiveDate, end: NaiveDate) -> Result<NpsSummary> {
+        let answers = customer_surveys::table
+            .inner_join(customers::table)
+            .filter(customer_surveys::survey_date.between(start, end))
+            .select((customer_surveys::score, customer_surveys::survey_date, customers::customer_type))
+            .load::<(i32, NaiveDate, String)>(conn)?;
+        Ok(summarize(start, end, &answers))
+    }
+}
+
+fn summarize(start: NaiveDate, end: NaiveDate, answers: &[(i32, NaiveDate, String)]) -> NpsSummary {
+    let mut summary = NpsSummary {
+        start,
+        end,
+        overall: NpsScore::default(),
+        by_segment: BTreeMap::new(),
+        by_month: BTreeMap::new(),
+    };
+    for (score, date, segment) in answers {
+        summary.overall.add(*score);
+        summary.by_segment.entry(segment.clone()).or_default().add(*score);
+        summary.by_month.entry(date.format("%Y-%m").to_string()).or_default().add(*score);
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nps() {
+        let nps = NpsScore::from_scores([10, 9, 9, 8, 7, 6, 0, 10, 3, 9]);
+        assert_eq!((nps.promoters, nps.passives, nps.detractors), (5, 2, 3));
+        assert_eq!(nps.score(), Some(20.0));
+        assert_eq!(NpsScore::default().score(), None);
+
+        let date = |d: &str| d.parse::<NaiveDate>().unwrap();
+        let answers = vec![
+            (10, date("2024-01-15"), "business".to_string()),
+            (4, date("2024-01-20"), "individual".to_string()),
+            (9, date("2024-02-03"), "business".to_string()),
+        ];
+        let summary = summarize(date("2024-01-01"), date("2024-03-31"), &answers);
+        assert_eq!(summary.overall.score().map(f64::round), Some(33.0));
+        assert_eq!(summary.by_segment["business"].score(), Some(100.0));
+        assert_eq!(summary.by_segment["individual"].score(), Some(-100.0));
+        assert_eq!(summary.by_month.keys().collect::<Vec<_>>(), ["2024-01", "2024-02"]);
+        assert_eq!(summary.by_month["2024-01"].score(), Some(0.0));
+    }
+}
@@ -0,0 +1,144 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::core::auth::AuthenticatedUser;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{departments, employees};
+use crate::database::{DatabaseConnection, RecordVisibility, UserRole};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Which leads and deals a user may see.
+///
+/// The owner of a record is its `assigned_to` employee. Reps see their own
+/// records plus team-visible records from their department, managers also
+/// see everything owned by the departments they manage, and admins and
+/// auditors see everything. Public records are visible to all users.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecordScope {
+    /// Owners whose records are fully visible; `None` means unrestricted
+    pub owners: Option<Vec<i32>>,
+    /// Owners whose team-visible records are visible
+    pub team: Vec<i32>,
+}
+
+impl RecordScope {
+    pub fn unrestricted() -> Self {
+        Self {
+            owners: None,
+            team: Vec::new(),
+        }
+    }
+
+    /// Build the scope for the logged-in user. `all` lifts the restriction
+    /// for managers and supervisors and is rejected for other roles.
+    pub fn for_user(conn: &mut DatabaseConnection, user: &AuthenticatedUser, all: bool) -> Result<Self> {
+        let privileged = matches!(user.role, UserRole::Manager | UserRole::Supervisor);
+
+        if matches!(user.role, UserRole::Admin | UserRole::Auditor) || (all && privileged) {
+            return Ok(Self::unrestricted());
+        }
+        if all {
+            return Err(crate::core::error::CLIERPError::Authorization(
+                "--all requires a manager, supervisor or admin role".to_string(),
+            ));
+        }
+
+        // Users without an employee record own nothing and only see public records
+        let employee_id = match user.employee_id {
+            Some(id) => id,
+            None => {
+                return Ok(Self {
+                    owners: Some(Vec::new()),
+                    team: Vec::new(),
+                })
+            }
+        };
+
+        let department_id = employees::table
+            .find(employee_id)
+            .select(employees::department_id)
+            .first::<i32>(conn)
+            .optional()?;
+
+        let team = match department_id {
+            Some(department_id) => employees::table
+                .filter(employees::department_id.eq(department_id))
+                .select(employees::id)
+                .load::<i32>(conn)?,
+            None => Vec::new(),
+        };
+
+        let mut owners = vec![employee_id];
+        if privileged {
+            let managed_departments = departments::table
+                .filter(departments::manager_id.eq(employee_id))
+                .select(departments::id)
+                .load::<i32>(conn)?;
+            let reports = employees::table
+                .filter(employees::department_id.eq_any(&managed_departments))
+                .select(employees::id)
+                .load::<i32>(conn)?;
+            owners.extend(reports.into_iter().filter(|id| *id != employee_id));
+        }
+
+        Ok(Self {
+            owners: Some(owners),
+            team,
+        })
+    }
+
+    pub fn is_unrestricted(&self) -> bool {
+        self.owners.is_none()
+    }
+
+    /// Whether a record with the given owner and visibility is in scope
+    pub fn can_see(&self, owner: Option<i32>, visibility: &str) -> bool {
+        let owners = match &self.owners {
+            Some(owners) => owners,
+            None => return true,
+        };
+        if visibility == RecordVisibility::Public.to_string() {
+            return true;
+        }
+        match owner {
+            Some(owner) => {
+                owners.contains(&owner)
+                    || (visibility == RecordVisibility::Team.to_string() && self.team.contains(&owner))
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rep_scope() -> RecordScope {
+        RecordScope {
+            owners: Some(vec![1]),
+            team: vec![1, 2, 3],
+        }
+    }
+
+    #[test]
+    fn test_rep_sees_own_and_team_records() {
+        let scope = rep_scope();
+        assert!(scope.can_see(Some(1), "private"));
+        assert!(scope.can_see(Some(2), "team"));
+        assert!(!scope.can_see(Some(2), "private"));
+        assert!(!scope.can_see(Some(9), "team"));
+        assert!(scope.can_see(Some(9), "public"));
+        assert!(!scope.can_see(None, "private"));
+    }
+
+    #[test]
+    fn test_unrestricted_scope_sees_everything() {
+        let scope = RecordScope::unrestricted();
+        assert!(scope.is_unrestricted());
+        assert!(scope.can_see(None, "private"));
+        assert!(scope.can_see(Some(42), "private"));
+    }
+}
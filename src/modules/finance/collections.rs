@@ -0,0 +1,461 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::models::{
+    CallOutcome, CollectionCall, Invoice, InvoiceKind, InvoiceStatus, NewCollectionCall, NewPaymentPromise,
+    PaymentPromise, PromiseStatus,
+};
+use crate::database::schema::{collection_calls, customers, invoice_payments, invoices, payment_promises};
+use crate::database::Customer;
+use crate::modules::system::notifications::NotificationService;
+use crate::utils::formatting::format_currency;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// How far a customer has been escalated, from the promises it broke since
+/// it last kept one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Escalation {
+    None,
+    /// One broken promise: call again
+    FollowUp,
+    /// Two: a manager takes over
+    Manager,
+    /// Three or more: hand over to legal or a collection agency
+    Legal,
+}
+
+impl Escalation {
+    pub fn from_broken_promises(count: usize) -> Self {
+        match count {
+            0 => Escalation::None,
+            1 => Escalation::FollowUp,
+            2 => Escalation::Manager,
+            _ => Escalation::Legal,
+        }
+    }
+}
+
+impl std::fmt::Display for Escalation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Escalation::None => write!(f, "-"),
+            Escalation::FollowUp => write!(f, "follow-up"),
+            Escalation::Manager => write!(f, "manager"),
+            Escalation::Legal => write!(f, "legal"),
+        }
+    }
+}
+
+/// A customer to call, with what it owes
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueEntry {
+    pub customer: Customer,
+    pub overdue_invoices: usize,
+    pub overdue_amount: i32,
+    pub oldest_days_overdue: i64,
+    /// Open amount times days overdue, summed over the overdue invoices
+    pub priority: i64,
+    pub escalation: Escalation,
+    pub last_call: Option<CollectionCall>,
+}
+
+/// Promise to pay given on a call
+#[derive(Debug, Clone)]
+pub struct PromiseRequest {
+    /// Defaults to everything the customer has overdue
+    pub amount: Option<i32>,
+    pub date: NaiveDate,
+}
+
+/// Collections worklist of customers with overdue invoices.
+///
+/// The queue ranks customers by how much they owe and for how long. A
+/// customer who promised to pay leaves the queue until the promised date;
+/// a promise not paid in full by then is marked broken, which puts the
+/// customer back in the queue one escalation level higher and notifies
+/// whoever took the promise.
+pub struct CollectionService;
+
+impl CollectionService {
+    /// Customers to call, most urgent first; resolves due promises first
+    pub fn queue(conn: &mut SqliteConnection, as_of: NaiveDate, limit: usize) -> Result<Vec<QueueEntry>> {
+        Self::resolve_promises(conn, as_of)?;
+
+        let overdue = invoices::table
+            .filter(invoices::kind.eq(InvoiceKind::Receivable.to_string()))
+            .filter(invoices::status.eq(InvoiceStatus::Open.to_string()))
+            .filter(invoices::due_date.lt(as_of))
+            .filter(invoices::customer_id.is_not_null())
+            .load::<Invoice>(conn)?;
+        let mut by_customer: HashMap<i32, Vec<Invoice>> = HashMap::new();
+        for invoice in overdue {
+            if let Some(customer_id) = invoice.customer_id {
+                by_customer.entry(customer_id).or_default().push(invoice);
+            }
+        }
+        let ids: Vec<i32> = by_customer.keys().copied().collect();
+
+        let mut promises: HashMap<i32, Vec<PaymentPromise>> = HashMap::new();
+        for promise in payment_promises::table
+            .filter(payment_promises::customer_id.eq_any(&ids))
+            .order(payment_promises::id.asc())
+            .load::<PaymentPromise>(conn)?
+        {
+            promises.entry(promise.customer_id).or_default().push(promise);
+        }
+        let mut last_calls: HashMap<i32, CollectionCall> = HashMap::new();
+        for call in collection_calls::table
+            .filter(collection_calls::customer_id.eq_any(&ids))
+            .order(collection_calls::id.asc())
+            .load::<CollectionCall>(conn)?
+        {
+            last_calls.insert(call.customer_id, call);
+        }
+        let customers: HashMap<i32, Customer> = customers::table
+            .filter(customers::id.eq_any(&ids))
+            .load::<Customer>(conn)?
+            .into_iter()
+            .map(|c| (c.id, c))
+            .collect();
+
+        let mut queue = Vec::new();
+        for (customer_id, invoices) in by_customer {
+            let customer_promises = promises.get(&customer_id).map(Vec::as_slice).unwrap_or_default();
+            if customer_promises.iter().any(|p| p.status == PromiseStatus::Open.to_string()) {
+                continue;
+            }
+            let Some(customer) = customers.get(&customer_id) else { continue };
+            queue.push(queue_entry(
+                customer.clone(),
+                &invoices,
+                customer_promises,
+                last_calls.remove(&customer_id),
+                as_of,
+            ));
+        }
+        rank(&mut queue);
+        queue.truncate(limit);
+        Ok(queue)
+    }
+
+    /// Record a call and, for a `promised` outcome, the promise made on it
+    pub fn log_call(
+        conn: &mut SqliteConnection,
+        customer_id: i32,
+        outcome: CallOutcome,
+        notes: Option<&str>,
+        promise: Option<PromiseRequest>,
+        today: NaiveDate,
+        created_by: Option<i32>,
+    ) -> Result<(CollectionCall, Option<PaymentPromise>)> {
+        customers::table
+            .find(customer_id)
+            .first::<Customer>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Customer {} not found", customer_id)))?;
+        let promise = match (outcome, promise) {
+            (CallOutcome::Promised, Some(promise)) => {
+                if promise.date < today {
+                    return Err(CLIERPError::Validation("The promised date cannot be in the past".to_string()));
+                }
+                let amount = match promise.amount {
+                    Some(amount) => amount,
+                    None => Self::overdue_amount(conn, customer_id, today)?,
+                };
+                if amount <= 0 {
+                    return Err(CLIERPError::Validation("The promised amount must be positive".to_string()));
+                }
+                Some((amount, promise.date))
+            }
+            (CallOutcome::Promised, None) => {
+                return Err(CLIERPError::Validation("A promised outcome needs a --promise-date".to_string()))
+            }
+            (_, Some(_)) => {
+                return Err(CLIERPError::Validation("Only a promised outcome takes a promise".to_string()))
+            }
+            (_, None) => None,
+        };
+
+        conn.transaction::<_, CLIERPError, _>(|conn| {
+            diesel::insert_into(collection_calls::table)
+                .values(&NewCollectionCall {
+                    customer_id,
+                    outcome: outcome.to_string(),
+                    notes: notes.map(str::trim).filter(|n| !n.is_empty()).map(str::to_string),
+                    created_by,
+                })
+                .execute(conn)?;
+            let call = collection_calls::table
+                .filter(collection_calls::customer_id.eq(customer_id))
+                .order(collection_calls::id.desc())
+                .first::<CollectionCall>(conn)?;
+
+            let promise = match promise {
+                Some((amount, promised_date)) => {
+                    diesel::insert_into(payment_promises::table)
+                        .values(&NewPaymentPromise {
+                            customer_id,
+                            call_id: call.id,
+                            amount,
+                            promised_on: today,
+                            promised_date,
+                            created_by,
+                        })
+                        .execute(conn)?;
+                    Some(
+                        payment_promises::table
+                            .filter(payment_promises::call_id.eq(call.id))
+                            .first::<PaymentPromise>(conn)?,
+                    )
+                }
+                None => None,
+            };
+            tracing::info!("Logged {} collections call to customer {}", call.outcome, customer_id);
+            Ok((call, promise))
+        })
+    }
+
+    /// Mark open promises kept once paid in full, or broken when their date
+    /// has passed without that; returns the promises just broken
+    pub fn resolve_promises(conn: &mut SqliteConnection, as_of: NaiveDate) -> Result<Vec<PaymentPromise>> {
+        let open = payment_promises::table
+            .filter(payment_promises::status.eq(PromiseStatus::Open.to_string()))
+            .load::<PaymentPromise>(conn)?;
+
+        let mut broken = Vec::new();
+        for promise in open {
+            let paid = Self::paid_between(conn, promise.customer_id, promise.promised_on, promise.promised_date)?;
+            let Some(status) = promise_outcome(&promise, paid, as_of) else { continue };
+            diesel::update(payment_promises::table.find(promise.id))
+                .set((
+                    payment_promises::status.eq(status.to_string()),
+                    payment_promises::resolved_on.eq(Some(as_of)),
+                ))
+                .execute(conn)?;
+            if status == PromiseStatus::Broken {
+                Self::notify_broken(conn, &promise, paid)?;
+                broken.push(promise);
+            }
+        }
+        Ok(broken)
+    }
+
+    /// Calls to a customer and the promises made on them, newest first
+    pub fn history(
+        conn: &mut SqliteConnection,
+        customer_id: i32,
+    ) -> Result<(Vec<CollectionCall>, HashMap<i32, PaymentPromise>)> {
+        let calls = collection_calls::table
+            .filter(collection_calls::customer_id.eq(customer_id))
+            .order(collection_calls::id.desc())
+            .load::<CollectionCall>(conn)?;
+        let promises = payment_promises::table
+            .filter(payment_promises::customer_id.eq(customer_id))
+            .load::<PaymentPromise>(conn)?
+            .into_iter()
+            .map(|p| (p.call_id, p))
+            .collect();
+        Ok((calls, promises))
+    }
+
+    /// Promises by promised date, optionally only those with `status`
+    pub fn promises(
+        conn: &mut SqliteConnection,
+        status: Option<PromiseStatus>,
+        limit: i64,
+    ) -> Result<Vec<(PaymentPromise, Customer)>> {
+        let mut query = payment_promises::table.inner_join(customers::table).into_boxed();
+        if let Some(status) = status {
+            query = query.filter(payment_promises::status.eq(status.to_string()));
+        }
+        Ok(query
+            .order((payment_promises::promised_date.asc(), payment_promises::id.asc()))
+            .limit(limit)
+            .load::<(PaymentPromise, Customer)>(conn)?)
+    }
+
+    fn overdue_amount(conn: &mut SqliteConnection, customer_id: i32, as_of: NaiveDate) -> Result<i32> {
+        Ok(invoices::table
+            .filter(invoices::customer_id.eq(customer_id))
+            .filter(invoices::kind.eq(InvoiceKind::Receivable.to_string()))
+            .filter(invoices::status.eq(InvoiceStatus::Open.to_string()))
+            .filter(invoices::due_date.lt(as_of))
+            .load::<Invoice>(conn)?
+            .iter()
+            .map(Invoice::open_amount)
+            .sum())
+    }
+
+    /// Payments of a customer that stuck, received between two days
+    fn paid_between(conn: &mut SqliteConnection, customer_id: i32, from: NaiveDate, to: NaiveDate) -> Result<i32> {
+        let amounts = invoice_payments::table
+            .inner_join(invoices::table)
+            .filter(invoices::customer_id.eq(customer_id))
+            .filter(invoices::kind.eq(InvoiceKind::Receivable.to_string()))
+            .filter(invoice_payments::returned_on.is_null())
+            .filter(invoice_payments::paid_on.between(from, to))
+            .select(invoice_payments::amount)
+            .load::<i32>(conn)?;
+        Ok(amounts.into_iter().sum())
+    }
+
+    fn notify_broken(conn: &mut SqliteConnection, promise: &PaymentPromise, paid: i32) -> Result<()> {
+        let Some(user_id) = promise.created_by else { return Ok(()) };
+        let customer = customers::table.find(promise.customer_id).first::<Customer>(conn)?;
+        NotificationService::notify(
+            conn,
+            user_id,
+            &format!("{} broke a promise to pay", customer.name),
+            Some(&format!(
+                "{} promised to pay {} by {} but paid {}; the customer is back in the collections queue.",
+                customer.name,
+                format_currency(promise.amount),
+                promise.promised_date,
+                format_currency(paid)
+            )),
+        )
+    }
+}
+
+/// New status of an open promise as of a date, or `None` while it stands
+fn promise_outcome(promise: &PaymentPromise, paid: i32, as_of: NaiveDate) -> Option<PromiseStatus> {
+    if paid >= promise.amount {
+        Some(PromiseStatus::Kept)
+    } else if as_of > promise.promised_date {
+        Some(PromiseStatus::Broken)
+    } else {
+        None
+    }
+}
+
+fn queue_entry(
+    customer: Customer,
+    invoices: &[Invoice],
+    promises: &[PaymentPromise],
+    last_call: Option<CollectionCall>,
+    as_of: NaiveDate,
+) -> QueueEntry {
+    let days_overdue = |invoice: &Invoice| invoice.due_date.map_or(0, |due| (as_of - due).num_days().max(0));
+    // Only promises broken since the last one kept count against the customer
+    let broken = promises
+        .iter()
+        .rev()
+        .take_while(|p| p.status != PromiseStatus::Kept.to_string())
+        .filter(|p| p.status == PromiseStatus::Broken.to_string())
+        .count();
+    QueueEntry {
+        customer,
+        overdue_invoices: invoices.len(),
+        overdue_amount: invoices.iter().map(Invoice::open_amount).sum(),
+        oldest_days_overdue: invoices.iter().map(days_overdue).max().unwrap_or(0),
+        priority: invoices.iter().map(|i| i64::from(i.open_amount()) * days_overdue(i)).sum(),
+        escalation: Escalation::from_broken_promises(broken),
+        last_call,
+    }
+}
+
+/// Escalated customers first, then by overdue amount weighted by age
+fn rank(queue: &mut [QueueEntry]) {
+    queue.sort_by(|a, b| {
+        b.escalation
+            .cmp(&a.escalation)
+            .then(b.priority.cmp(&a.priority))
+            .then(a.customer.id.cmp(&b.customer.id))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn customer(id: i32) -> Customer {
+        let now = Utc::now().naive_utc();
+        Customer {
+            id,
+            customer_code: format!("C{}", id),
+            name: format!("Customer {}", id),
+            email: None,
+            phone: None,
+            address: None,
+            customer_type: "business".to_string(),
+            company_name: None,
+            tax_id: None,
+            credit_limit: None,
+            status: "active".to_string(),
+            notes: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn invoice(id: i32, due: &str, total: i32) -> Invoice {
+        let now = Utc::now().naive_utc();
+        Invoice {
+            id,
+            invoice_number: format!("INV-{}", id),
+            kind: "receivable".to_string(),
+            customer_id: Some(1),
+            supplier_id: None,
+            invoice_date: "2024-01-01".parse().unwrap(),
+            due_date: Some(due.parse().unwrap()),
+            total_amount: total,
+            paid_amount: 0,
+            status: "open".to_string(),
+            description: None,
+            created_by: None,
+            created_at: now,
+            updated_at: now,
+            po_id: None,
+            sent_at: None,
+        }
+    }
+
+    fn promise(id: i32, status: &str) -> PaymentPromise {
+        PaymentPromise {
+            id,
+            customer_id: 1,
+            call_id: id,
+            amount: 500,
+            promised_on: "2024-03-01".parse().unwrap(),
+            promised_date: "2024-03-10".parse().unwrap(),
+            status: status.to_string(),
+            resolved_on: None,
+            created_by: None,
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+
+    #[test]
+    fn test_promises_and_ranking() {
+        let open = promise(1, "open");
+        assert_eq!(promise_outcome(&open, 200, "2024-03-10".parse().unwrap()), None);
+        assert_eq!(promise_outcome(&open, 500, "2024-03-05".parse().unwrap()), Some(PromiseStatus::Kept));
+        assert_eq!(promise_outcome(&open, 200, "2024-03-11".parse().unwrap()), Some(PromiseStatus::Broken));
+
+        let as_of: NaiveDate = "2024-04-01".parse().unwrap();
+        // Large but recent against small but old: 1000 x 10 days < 300 x 60 days
+        let recent = queue_entry(customer(1), &[invoice(1, "2024-03-22", 1000)], &[], None, as_of);
+        let old = queue_entry(customer(2), &[invoice(2, "2024-02-01", 300)], &[], None, as_of);
+        assert_eq!(recent.priority, 10_000);
+        assert_eq!(old.oldest_days_overdue, 60);
+
+        // A kept promise clears the promises broken before it
+        let promises = [promise(1, "broken"), promise(2, "kept"), promise(3, "broken")];
+        let escalated = queue_entry(customer(3), &[invoice(3, "2024-03-31", 10)], &promises, None, as_of);
+        assert_eq!(escalated.escalation, Escalation::FollowUp);
+
+        let mut queue = vec![recent, old, escalated];
+        rank(&mut queue);
+        let order: Vec<i32> = queue.iter().map(|e| e.customer.id).collect();
+        assert_eq!(order, vec![3, 2, 1]);
+    }
+}
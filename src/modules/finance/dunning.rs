@@ -0,0 +1,437 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::core::config::DunningConfig;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::models::{
+    DunningCharge, DunningExemption, Invoice, InvoiceKind, InvoiceStatus, NewDunningCharge, NewDunningExemption,
+};
+use crate::database::Customer;
+use crate::database::schema::{accounts, customers, dunning_charges, dunning_exemptions, invoices};
+use crate::modules::finance::{JournalEntryRequest, JournalLine, JournalService};
+use crate::utils::cache::QueryCache;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// `transactions.reference` of the postings for an invoice's charges
+pub fn dunning_reference(invoice_number: &str) -> String {
+    format!("dunning:{}", invoice_number)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChargeKind {
+    Fee,
+    Interest,
+}
+
+impl std::fmt::Display for ChargeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChargeKind::Fee => write!(f, "fee"),
+            ChargeKind::Interest => write!(f, "interest"),
+        }
+    }
+}
+
+/// Fees and interest charged on overdue invoices, from configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct DunningPolicy {
+    pub grace_days: i64,
+    pub late_fee: i32,
+    /// Annual rate in percent
+    pub interest_rate: f64,
+    pub min_interest: i32,
+}
+
+impl DunningPolicy {
+    pub fn from_config(config: &DunningConfig) -> Result<Self> {
+        if config.late_fee < 0 || config.interest_rate < 0.0 || config.min_interest < 0 {
+            return Err(CLIERPError::Configuration(config::ConfigError::Message(
+                "dunning.late_fee, dunning.interest_rate and dunning.min_interest cannot be negative".to_string(),
+            )));
+        }
+        Ok(Self {
+            grace_days: config.grace_days as i64,
+            late_fee: config.late_fee,
+            interest_rate: config.interest_rate,
+            min_interest: config.min_interest,
+        })
+    }
+
+    /// Whether the policy charges anything at all
+    pub fn is_active(&self) -> bool {
+        self.late_fee > 0 || self.interest_rate > 0.0
+    }
+}
+
+/// Account codes fees and interest are posted to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DunningAccounts {
+    pub receivable: String,
+    pub revenue: String,
+}
+
+impl DunningAccounts {
+    pub fn from_config(config: &DunningConfig) -> Result<Self> {
+        match (&config.receivable_account, &config.revenue_account) {
+            (Some(receivable), Some(revenue)) => Ok(Self {
+                receivable: receivable.clone(),
+                revenue: revenue.clone(),
+            }),
+            _ => Err(CLIERPError::Configuration(config::ConfigError::Message(
+                "Set dunning.receivable_account and dunning.revenue_account (CLIERP_DUNNING__RECEIVABLE_ACCOUNT, CLIERP_DUNNING__REVENUE_ACCOUNT) to charge overdue invoices".to_string(),
+            ))),
+        }
+    }
+}
+
+/// A fee or interest amount due on an invoice, not yet saved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlannedCharge {
+    pub invoice_id: i32,
+    pub invoice_number: String,
+    pub customer_id: Option<i32>,
+    pub kind: ChargeKind,
+    pub amount: i32,
+    pub days_overdue: i32,
+    pub accrued_from: Option<NaiveDate>,
+    pub accrued_to: NaiveDate,
+}
+
+/// Charges due on overdue invoices as of a date
+#[derive(Debug, Clone, Serialize)]
+pub struct DunningRun {
+    pub as_of: NaiveDate,
+    pub charges: Vec<PlannedCharge>,
+    /// Overdue invoices left alone because their customer is exempt
+    pub exempt_invoices: usize,
+}
+
+impl DunningRun {
+    pub fn total(&self) -> i32 {
+        self.charges.iter().map(|c| c.amount).sum()
+    }
+}
+
+/// Late fees and interest on overdue receivable invoices.
+///
+/// Each invoice is charged the flat fee once and interest for the days
+/// since it was last charged, so a run can be repeated (e.g. daily from
+/// cron) without charging anything twice. Charges are added to the invoice
+/// total and posted as revenue against receivables.
+pub struct DunningService;
+
+impl DunningService {
+    /// Charges due as of `as_of`, on one invoice or on every overdue invoice
+    pub fn plan(
+        conn: &mut SqliteConnection,
+        policy: &DunningPolicy,
+        as_of: NaiveDate,
+        invoice_id: Option<i32>,
+    ) -> Result<DunningRun> {
+        let mut query = invoices::table
+            .filter(invoices::kind.eq(InvoiceKind::Receivable.to_string()))
+            .filter(invoices::status.eq(InvoiceStatus::Open.to_string()))
+            .filter(invoices::due_date.lt(as_of - Duration::days(policy.grace_days)))
+            .into_boxed();
+        if let Some(invoice_id) = invoice_id {
+            query = query.filter(invoices::id.eq(invoice_id));
+        }
+        let overdue = query
+            .order((invoices::due_date.asc(), invoices::id.asc()))
+            .load::<Invoice>(conn)?;
+
+        let exempt: HashSet<i32> = dunning_exemptions::table
+            .select(dunning_exemptions::customer_id)
+            .load::<i32>(conn)?
+            .into_iter()
+            .collect();
+        let ids: Vec<i32> = overdue.iter().map(|i| i.id).collect();
+        let mut existing: HashMap<i32, Vec<DunningCharge>> = HashMap::new();
+        for charge in dunning_charges::table
+            .filter(dunning_charges::invoice_id.eq_any(&ids))
+            .load::<DunningCharge>(conn)?
+        {
+            existing.entry(charge.invoice_id).or_default().push(charge);
+        }
+
+        let mut run = DunningRun {
+            as_of,
+            charges: Vec::new(),
+            exempt_invoices: 0,
+        };
+        for invoice in &overdue {
+            if invoice.customer_id.is_some_and(|id| exempt.contains(&id)) {
+                run.exempt_invoices += 1;
+                continue;
+            }
+            let charges = existing.get(&invoice.id).map(Vec::as_slice).unwrap_or_default();
+            run.charges.extend(plan_invoice(invoice, charges, policy, as_of));
+        }
+        Ok(run)
+    }
+
+    /// Save the charges of a run, add them to their invoices and post them
+    pub fn apply(
+        conn: &mut SqliteConnection,
+        accounts: &DunningAccounts,
+        run: &DunningRun,
+        created_by: Option<i32>,
+    ) -> Result<usize> {
+        if run.charges.is_empty() {
+            return Ok(0);
+        }
+        let receivable_id = account_id(conn, &accounts.receivable)?;
+        let revenue_id = account_id(conn, &accounts.revenue)?;
+
+        conn.transaction::<_, CLIERPError, _>(|conn| {
+            let now = Utc::now().naive_utc();
+            for charge in &run.charges {
+                diesel::insert_into(dunning_charges::table)
+                    .values(&NewDunningCharge {
+                        invoice_id: charge.invoice_id,
+                        kind: charge.kind.to_string(),
+                        amount: charge.amount,
+                        days_overdue: charge.days_overdue,
+                        accrued_from: charge.accrued_from,
+                        accrued_to: charge.accrued_to,
+                        created_by,
+                    })
+                    .execute(conn)?;
+                diesel::update(invoices::table.find(charge.invoice_id))
+                    .set((
+                        invoices::total_amount.eq(invoices::total_amount + charge.amount),
+                        invoices::updated_at.eq(now),
+                    ))
+                    .execute(conn)?;
+
+                let description = match charge.kind {
+                    ChargeKind::Fee => format!("Late fee, invoice {}", charge.invoice_number),
+                    ChargeKind::Interest => format!("Late interest, invoice {}", charge.invoice_number),
+                };
+                JournalService::post_entry(
+                    conn,
+                    JournalEntryRequest {
+                        entry_date: run.as_of,
+                        description,
+                        reference: Some(dunning_reference(&charge.invoice_number)),
+                        lines: vec![JournalLine::debit(receivable_id, charge.amount), JournalLine::credit(revenue_id, charge.amount)],
+                    },
+                    created_by,
+                )?;
+            }
+            Ok(())
+        })?;
+        QueryCache::current().invalidate(&["finance"]);
+
+        tracing::info!(
+            "Charged {} dunning charge(s) totalling {} as of {}",
+            run.charges.len(),
+            run.total(),
+            run.as_of
+        );
+        Ok(run.charges.len())
+    }
+
+    /// Charges made, newest first, optionally of one invoice or customer
+    pub fn charges(
+        conn: &mut SqliteConnection,
+        invoice_id: Option<i32>,
+        customer_id: Option<i32>,
+        limit: i64,
+    ) -> Result<Vec<(DunningCharge, Invoice)>> {
+        let mut query = dunning_charges::table.inner_join(invoices::table).into_boxed();
+        if let Some(invoice_id) = invoice_id {
+            query = query.filter(dunning_charges::invoice_id.eq(invoice_id));
+        }
+        if let Some(customer_id) = customer_id {
+            query = query.filter(invoices::customer_id.eq(customer_id));
+        }
+        Ok(query
+            .order(dunning_charges::id.desc())
+            .limit(limit)
+            .load::<(DunningCharge, Invoice)>(conn)?)
+    }
+
+    /// Stop charging a customer's overdue invoices
+    pub fn exempt(
+        conn: &mut SqliteConnection,
+        customer_id: i32,
+        reason: Option<&str>,
+        created_by: Option<i32>,
+    ) -> Result<DunningExemption> {
+        customers::table
+            .find(customer_id)
+            .first::<Customer>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Customer with ID {} not found", customer_id)))?;
+
+        diesel::replace_into(dunning_exemptions::table)
+            .values(&NewDunningExemption {
+                customer_id,
+                reason: reason.map(str::trim).filter(|r| !r.is_empty()).map(str::to_string),
+                created_by,
+            })
+            .execute(conn)?;
+        tracing::info!("Customer {} exempted from dunning charges", customer_id);
+        Ok(dunning_exemptions::table.find(customer_id).first::<DunningExemption>(conn)?)
+    }
+
+    /// Charge a customer again, returning whether it was exempt
+    pub fn remove_exemption(conn: &mut SqliteConnection, customer_id: i32) -> Result<bool> {
+        let deleted = diesel::delete(dunning_exemptions::table.find(customer_id)).execute(conn)?;
+        Ok(deleted > 0)
+    }
+
+    pub fn exemptions(conn: &mut SqliteConnection) -> Result<Vec<(DunningExemption, Customer)>> {
+        Ok(dunning_exemptions::table
+            .inner_join(customers::table)
+            .order(customers::name.asc())
+            .load::<(DunningExemption, Customer)>(conn)?)
+    }
+}
+
+fn account_id(conn: &mut SqliteConnection, code: &str) -> Result<i32> {
+    accounts::table
+        .filter(accounts::account_code.eq(code))
+        .select(accounts::id)
+        .first::<i32>(conn)
+        .optional()?
+        .ok_or_else(|| CLIERPError::NotFound(format!("Dunning account '{}' not found", code)))
+}
+
+/// Fee and interest due on one overdue invoice given its earlier charges
+fn plan_invoice(
+    invoice: &Invoice,
+    charges: &[DunningCharge],
+    policy: &DunningPolicy,
+    as_of: NaiveDate,
+) -> Vec<PlannedCharge> {
+    let Some(due_date) = invoice.due_date else {
+        return Vec::new();
+    };
+    let days_overdue = (as_of - due_date).num_days();
+    if days_overdue <= policy.grace_days {
+        return Vec::new();
+    }
+    let planned = |kind, amount, accrued_from| PlannedCharge {
+        invoice_id: invoice.id,
+        invoice_number: invoice.invoice_number.clone(),
+        customer_id: invoice.customer_id,
+        kind,
+        amount,
+        days_overdue: days_overdue as i32,
+        accrued_from,
+        accrued_to: as_of,
+    };
+
+    let mut planned_charges = Vec::new();
+    let charged_fee = charges.iter().any(|c| c.kind == ChargeKind::Fee.to_string());
+    if policy.late_fee > 0 && !charged_fee {
+        planned_charges.push(planned(ChargeKind::Fee, policy.late_fee, None));
+    }
+
+    if policy.interest_rate > 0.0 {
+        // Interest runs on what is open of the invoice itself, not on earlier charges
+        let charged: i32 = charges.iter().map(|c| c.amount).sum();
+        let principal = (invoice.total_amount - charged - invoice.paid_amount).max(0);
+        let from = charges
+            .iter()
+            .filter(|c| c.kind == ChargeKind::Interest.to_string())
+            .map(|c| c.accrued_to)
+            .max()
+            .unwrap_or(due_date + Duration::days(policy.grace_days))
+            + Duration::days(1);
+        let days = (as_of - from).num_days() + 1;
+        if days > 0 {
+            let interest = (principal as f64 * policy.interest_rate / 100.0 * days as f64 / 365.0).round() as i32;
+            if interest > 0 && interest >= policy.min_interest {
+                planned_charges.push(planned(ChargeKind::Interest, interest, Some(from)));
+            }
+        }
+    }
+    planned_charges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    fn charge(kind: ChargeKind, amount: i32, accrued_to: &str) -> DunningCharge {
+        DunningCharge {
+            id: 1,
+            invoice_id: 1,
+            kind: kind.to_string(),
+            amount,
+            days_overdue: 0,
+            accrued_from: None,
+            accrued_to: date(accrued_to),
+            created_by: None,
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+
+    #[test]
+    fn test_plan_invoice() {
+        let now = Utc::now().naive_utc();
+        let mut invoice = Invoice {
+            id: 1,
+            invoice_number: "INV-1".to_string(),
+            kind: "receivable".to_string(),
+            customer_id: Some(1),
+            supplier_id: None,
+            invoice_date: date("2024-01-01"),
+            due_date: Some(date("2024-01-31")),
+            total_amount: 365_000,
+            paid_amount: 0,
+            status: "open".to_string(),
+            description: None,
+            created_by: None,
+            created_at: now,
+            updated_at: now,
+            po_id: None,
+            sent_at: None,
+        };
+        let policy = DunningPolicy {
+            grace_days: 5,
+            late_fee: 5_000,
+            interest_rate: 10.0,
+            min_interest: 100,
+        };
+
+        // Within the grace period nothing is due
+        assert!(plan_invoice(&invoice, &[], &policy, date("2024-02-05")).is_empty());
+
+        // Fee plus interest from the end of the grace period: Feb 6-15 is 10 days
+        let first = plan_invoice(&invoice, &[], &policy, date("2024-02-15"));
+        assert_eq!(first.len(), 2);
+        assert_eq!((first[0].kind, first[0].amount), (ChargeKind::Fee, 5_000));
+        assert_eq!((first[1].kind, first[1].amount), (ChargeKind::Interest, 1_000));
+        assert_eq!(first[1].accrued_from, Some(date("2024-02-06")));
+        assert_eq!(first[1].days_overdue, 15);
+
+        // A later run charges only the interest since the last charge, on the
+        // principal without the earlier charges
+        invoice.total_amount += 6_000;
+        let charges = [charge(ChargeKind::Fee, 5_000, "2024-02-15"), charge(ChargeKind::Interest, 1_000, "2024-02-15")];
+        let second = plan_invoice(&invoice, &charges, &policy, date("2024-02-20"));
+        assert_eq!(second.len(), 1);
+        assert_eq!((second[0].kind, second[0].amount), (ChargeKind::Interest, 500));
+        assert_eq!(second[0].accrued_from, Some(date("2024-02-16")));
+
+        // Running again on the same day charges nothing; tiny interest waits
+        assert!(plan_invoice(&invoice, &charges, &policy, date("2024-02-15")).is_empty());
+        invoice.paid_amount = 360_000;
+        assert!(plan_invoice(&invoice, &charges, &policy, date("2024-02-20")).is_empty());
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Duration, NaiveDate};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::core::config::PurchaseAccrualConfig;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::models::{
+    GrniAccrual, GrniAccrualLine, InvoiceKind, InvoiceStatus, NewGrniAccrual, NewGrniAccrualLine, StockMovementType,
+};
+use crate::database::schema::{accounts, grni_accrual_lines, grni_accruals, invoices, purchase_orders, stock_movements, suppliers};
+use crate::modules::finance::{JournalEntryRequest, JournalLine, JournalService, PostingRules};
+use crate::utils::cache::QueryCache;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// `transactions.reference` of the postings of a period's accrual
+pub fn grni_reference(period: &str) -> String {
+    format!("grni:{}", period)
+}
+
+/// Accounts the accrual is posted to, from configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccrualAccounts {
+    pub accrual: String,
+    pub liability: String,
+}
+
+impl AccrualAccounts {
+    pub fn from_config(config: &PurchaseAccrualConfig) -> Result<Self> {
+        match (&config.accrual_account, &config.liability_account) {
+            (Some(accrual), Some(liability)) => Ok(Self {
+                accrual: accrual.clone(),
+                liability: liability.clone(),
+            }),
+            _ => Err(CLIERPError::Configuration(config::ConfigError::Message(
+                "Set purchase_accrual.accrual_account and purchase_accrual.liability_account (CLIERP_PURCHASE_ACCRUAL__ACCRUAL_ACCOUNT, CLIERP_PURCHASE_ACCRUAL__LIABILITY_ACCOUNT) to accrue uninvoiced receipts".to_string(),
+            ))),
+        }
+    }
+}
+
+/// Receipts of a purchase order not yet covered by supplier invoices
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GrniLine {
+    pub po_id: i32,
+    pub po_number: String,
+    pub supplier: String,
+    /// Value of the goods received, at the order's unit costs
+    pub received_amount: i32,
+    pub invoiced_amount: i32,
+    pub amount: i32,
+}
+
+/// Goods received not invoiced (GRNI).
+///
+/// Unless automatic posting is switched on, receiving a purchase order
+/// does not post anything to the ledger; the liability only appears once
+/// the supplier invoice arrives. At period end the value of receipts
+/// without an invoice is accrued on the last day and reversed on the first
+/// day of the next period, so the statements show the liability without it
+/// being counted twice once the invoice is booked.
+pub struct GrniService;
+
+impl GrniService {
+    /// Purchase orders with uninvoiced receipts as of the end of `as_of`,
+    /// largest first
+    pub fn report(conn: &mut SqliteConnection, as_of: NaiveDate) -> Result<Vec<GrniLine>> {
+        let end = (as_of + Duration::days(1)).and_hms_opt(0, 0, 0).unwrap_or_default();
+        let receipts = stock_movements::table
+            .filter(stock_movements::movement_type.eq(StockMovementType::In.to_string()))
+            .filter(stock_movements::reference_type.eq("purchase_order"))
+            .filter(stock_movements::movement_date.lt(end))
+            .select((stock_movements::reference_id, stock_movements::quantity, stock_movements::unit_cost))
+            .load::<(Option<i32>, i32, Option<i32>)>(conn)?;
+        let mut received: HashMap<i32, i64> = HashMap::new();
+        for (po_id, quantity, unit_cost) in receipts {
+            if let Some(po_id) = po_id {
+                *received.entry(po_id).or_default() += i64::from(quantity) * i64::from(unit_cost.unwrap_or(0));
+            }
+        }
+
+        let billed = invoices::table
+            .filter(invoices::kind.eq(InvoiceKind::Payable.to_string()))
+            .filter(invoices::status.ne_all(vec![
+                InvoiceStatus::Draft.to_string(),
+                InvoiceStatus::Cancelled.to_string(),
+            ]))
+            .filter(invoices::invoice_date.le(as_of))
+            .select((invoices::po_id, invoices::total_amount))
+            .load::<(Option<i32>, i32)>(conn)?;
+        let mut invoiced: HashMap<i32, i64> = HashMap::new();
+        for (po_id, amount) in billed {
+            if let Some(po_id) = po_id {
+                *invoiced.entry(po_id).or_default() += i64::from(amount);
+            }
+        }
+
+        let open = uninvoiced(&received, &invoiced);
+        let ids: Vec<i32> = open.keys().copied().collect();
+        let orders: HashMap<i32, (String, String)> = purchase_orders::table
+            .inner_join(suppliers::table)
+            .filter(purchase_orders::id.eq_any(&ids))
+            .select((purchase_orders::id, purchase_orders::po_number, suppliers::name))
+            .load::<(i32, String, String)>(conn)?
+            .into_iter()
+            .map(|(id, number, supplier)| (id, (number, supplier)))
+            .collect();
+
+        let mut lines: Vec<GrniLine> = open
+            .into_iter()
+            .filter_map(|(po_id, (received_amount, invoiced_amount, amount))| {
+                let (po_number, supplier) = orders.get(&po_id)?.clone();
+                Some(GrniLine { po_id, po_number, supplier, received_amount, invoiced_amount, amount })
+            })
+            .collect();
+        lines.sort_by(|a, b| b.amount.cmp(&a.amount).then(a.po_id.cmp(&b.po_id)));
+        Ok(lines)
+    }
+
+    /// Accrue the uninvoiced receipts of a period on its last day and post
+    /// the reversal on the next day; each period is accrued once
+    pub fn accrue(
+        conn: &mut SqliteConnection,
+        accounts: &AccrualAccounts,
+        period: &str,
+        period_end: NaiveDate,
+        created_by: Option<i32>,
+    ) -> Result<(GrniAccrual, Vec<GrniLine>)> {
+        if PostingRules::current().is_some() {
+            return Err(CLIERPError::BusinessLogic(
+                "Receipts are already posted to accounts payable as they arrive; accruing them again would count the liability twice".to_string(),
+            ));
+        }
+        let existing = grni_accruals::table
+            .filter(grni_accruals::period.eq(period))
+            .first::<GrniAccrual>(conn)
+            .optional()?;
+        if let Some(existing) = existing {
+            return Err(CLIERPError::AlreadyExists(format!(
+                "Period {} was already accrued on {} ({})",
+                period, existing.accrued_on, existing.amount
+            )));
+        }
+        let lines = Self::report(conn, period_end)?;
+        let total: i32 = lines.iter().map(|l| l.amount).sum();
+        let accrual_id = account_id(conn, &accounts.accrual)?;
+        let liability_id = account_id(conn, &accounts.liability)?;
+        let reversed_on = period_end + Duration::days(1);
+
+        let accrual = conn.transaction::<_, CLIERPError, _>(|conn| {
+            diesel::insert_into(grni_accruals::table)
+                .values(&NewGrniAccrual {
+                    period: period.to_string(),
+                    accrued_on: period_end,
+                    reversed_on,
+                    amount: total,
+                    created_by,
+                })
+                .execute(conn)?;
+            let accrual = grni_accruals::table
+                .filter(grni_accruals::period.eq(period))
+                .first::<GrniAccrual>(conn)?;
+            let rows: Vec<NewGrniAccrualLine> = lines
+                .iter()
+                .map(|line| NewGrniAccrualLine {
+                    accrual_id: accrual.id,
+                    po_id: line.po_id,
+                    received_amount: line.received_amount,
+                    invoiced_amount: line.invoiced_amount,
+                    amount: line.amount,
+                })
+                .collect();
+            diesel::insert_into(grni_accrual_lines::table).values(&rows).execute(conn)?;
+
+            if total > 0 {
+                let entries = [
+                    (period_end, "Goods received not invoiced", accrual_id, liability_id),
+                    (reversed_on, "Reversal of goods received not invoiced", liability_id, accrual_id),
+                ];
+                for (date, description, debit_id, credit_id) in entries {
+                    JournalService::post_entry(
+                        conn,
+                        JournalEntryRequest {
+                            entry_date: date,
+                            description: format!("{} {}", description, period),
+                            reference: Some(grni_reference(period)),
+                            lines: vec![JournalLine::debit(debit_id, total), JournalLine::credit(credit_id, total)],
+                        },
+                        created_by,
+                    )?;
+                }
+            }
+            Ok(accrual)
+        })?;
+        QueryCache::current().invalidate(&["finance"]);
+
+        tracing::info!("Accrued {} of goods received not invoiced for {}", total, period);
+        Ok((accrual, lines))
+    }
+
+    /// Accruals made, latest period first
+    pub fn accruals(conn: &mut SqliteConnection, limit: i64) -> Result<Vec<GrniAccrual>> {
+        Ok(grni_accruals::table
+            .order(grni_accruals::accrued_on.desc())
+            .limit(limit)
+            .load::<GrniAccrual>(conn)?)
+    }
+
+    /// Purchase order lines of an accrual, by amount
+    pub fn accrual_lines(conn: &mut SqliteConnection, accrual_id: i32) -> Result<Vec<(GrniAccrualLine, String)>> {
+        Ok(grni_accrual_lines::table
+            .inner_join(purchase_orders::table)
+            .filter(grni_accrual_lines::accrual_id.eq(accrual_id))
+            .order(grni_accrual_lines::amount.desc())
+            .select((GrniAccrualLine::as_select(), purchase_orders::po_number))
+            .load::<(GrniAccrualLine, String)>(conn)?)
+    }
+}
+
+fn account_id(conn: &mut SqliteConnection, code: &str) -> Result<i32> {
+    accounts::table
+        .filter(accounts::account_code.eq(code))
+        .select(accounts::id)
+        .first::<i32>(conn)
+        .optional()?
+        .ok_or_else(|| CLIERPError::NotFound(format!("Accrual account '{}' not found", code)))
+}
+
+/// (received, invoiced, uninvoiced) per purchase order still short of
+/// invoices; orders invoiced in full or beyond are left out
+fn uninvoiced(received: &HashMap<i32, i64>, invoiced: &HashMap<i32, i64>) -> BTreeMap<i32, (i32, i32, i32)> {
+    received
+        .iter()
+        .filter_map(|(&po_id, &received)| {
+            let invoiced = invoiced.get(&po_id).copied().unwrap_or(0);
+            (received > invoiced).then(|| (po_id, (received as i32, invoiced as i32, (received - invoiced) as i32)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uninvoiced_receipts() {
+        let received = HashMap::from([(1, 1000), (2, 500), (3, 800)]);
+        let invoiced = HashMap::from([(1, 400), (2, 500), (3, 900), (4, 100)]);
+        let open = uninvoiced(&received, &invoiced);
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[&1], (1000, 400, 600));
+
+        let open = uninvoiced(&received, &HashMap::new());
+        assert_eq!(open.values().map(|v| v.2).sum::<i32>(), 2300);
+    }
+}
@@ -0,0 +1,485 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use super::receivables::ReceivableService;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::models::{
+    Invoice, InvoiceKind, InvoiceLine, InvoicePayment, InvoiceStatus, NewInvoice, NewInvoiceLine,
+};
+use crate::database::schema::{customers, invoice_lines, invoice_payments, invoice_sequences, invoices, products};
+use crate::utils::cache::QueryCache;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Where a receivable invoice stands. Stored statuses are draft, open,
+/// paid and cancelled; an open invoice counts as sent once it went to the
+/// customer and as overdue once its due date has passed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, clap::ValueEnum)]
+pub enum InvoiceState {
+    Draft,
+    /// Issued but not sent, e.g. billed from a sales order
+    Open,
+    Sent,
+    Overdue,
+    Paid,
+    Cancelled,
+}
+
+impl std::fmt::Display for InvoiceState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvoiceState::Draft => write!(f, "draft"),
+            InvoiceState::Open => write!(f, "open"),
+            InvoiceState::Sent => write!(f, "sent"),
+            InvoiceState::Overdue => write!(f, "overdue"),
+            InvoiceState::Paid => write!(f, "paid"),
+            InvoiceState::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+/// State of an invoice on `today`
+pub fn invoice_state(invoice: &Invoice, today: NaiveDate) -> InvoiceState {
+    match invoice.status.as_str() {
+        "draft" => InvoiceState::Draft,
+        "paid" => InvoiceState::Paid,
+        "cancelled" => InvoiceState::Cancelled,
+        _ if invoice.due_date.is_some_and(|due| due < today) => InvoiceState::Overdue,
+        _ if invoice.sent_at.is_some() => InvoiceState::Sent,
+        _ => InvoiceState::Open,
+    }
+}
+
+/// Invoice numbers run per year without gaps: INV-2025-00001, INV-2025-00002, ...
+pub fn format_invoice_number(year: i32, number: i32) -> String {
+    format!("INV-{}-{:05}", year, number)
+}
+
+/// Clap value parser for `--line DESCRIPTION:QUANTITY:UNIT_PRICE`; the
+/// description may itself contain colons
+pub fn parse_invoice_line_arg(s: &str) -> std::result::Result<InvoiceLineRequest, String> {
+    let mut parts = s.rsplitn(3, ':');
+    let (Some(unit_price), Some(quantity), Some(description)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(format!("Expected DESCRIPTION:QUANTITY:UNIT_PRICE, got '{}'", s));
+    };
+    let quantity = quantity
+        .trim()
+        .parse::<i32>()
+        .map_err(|_| format!("Invalid quantity in '{}'", s))?;
+    let unit_price = unit_price
+        .trim()
+        .parse::<i32>()
+        .map_err(|_| format!("Invalid unit price in '{}'", s))?;
+    Ok(InvoiceLineRequest {
+        description: description.trim().to_string(),
+        product_id: None,
+        quantity,
+        unit_price,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvoiceLineRequest {
+    pub description: String,
+    pub product_id: Option<i32>,
+    pub quantity: i32,
+    pub unit_price: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateInvoiceRequest {
+    pub customer_id: i32,
+    pub invoice_date: NaiveDate,
+    pub due_date: Option<NaiveDate>,
+    pub description: Option<String>,
+    pub lines: Vec<InvoiceLineRequest>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InvoiceWithLines {
+    pub invoice: Invoice,
+    pub lines: Vec<InvoiceLine>,
+    pub payments: Vec<InvoicePayment>,
+}
+
+/// Open receivables of one customer by days past due
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AgingRow {
+    pub customer_id: i32,
+    pub customer: String,
+    /// Not yet due, or without a due date
+    pub current: i64,
+    pub days_1_30: i64,
+    pub days_31_60: i64,
+    pub days_61_90: i64,
+    pub over_90: i64,
+    pub total: i64,
+}
+
+impl AgingRow {
+    fn add(&mut self, days_overdue: i64, amount: i64) {
+        match days_overdue {
+            i64::MIN..=0 => self.current += amount,
+            1..=30 => self.days_1_30 += amount,
+            31..=60 => self.days_31_60 += amount,
+            61..=90 => self.days_61_90 += amount,
+            _ => self.over_90 += amount,
+        }
+        self.total += amount;
+    }
+}
+
+/// Customer invoices raised in finance: drafted with their lines, numbered
+/// from the yearly sequence, sent, paid through `ReceivableService` and
+/// aged while open
+pub struct InvoiceService;
+
+impl InvoiceService {
+    /// Draft an invoice for the total of its lines. The number is taken
+    /// from the sequence of the invoice year straight away, so numbers
+    /// follow the order invoices were raised in.
+    pub fn create(
+        conn: &mut SqliteConnection,
+        request: CreateInvoiceRequest,
+        created_by: Option<i32>,
+    ) -> Result<InvoiceWithLines> {
+        customers::table
+            .find(request.customer_id)
+            .select(customers::id)
+            .first::<i32>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Customer {} not found", request.customer_id)))?;
+        if request.lines.is_empty() {
+            return Err(CLIERPError::Validation("An invoice needs at least one line".to_string()));
+        }
+        if let Some(due_date) = request.due_date {
+            if due_date < request.invoice_date {
+                return Err(CLIERPError::Validation(format!(
+                    "Due date {} is before the invoice date {}",
+                    due_date, request.invoice_date
+                )));
+            }
+        }
+        let mut total: i64 = 0;
+        for line in &request.lines {
+            if line.description.trim().is_empty() {
+                return Err(CLIERPError::Validation("Invoice lines need a description".to_string()));
+            }
+            if line.quantity <= 0 || line.unit_price < 0 {
+                return Err(CLIERPError::Validation(format!(
+                    "Line '{}' needs a positive quantity and a unit price of at least 0",
+                    line.description
+                )));
+            }
+            if let Some(product_id) = line.product_id {
+                products::table
+                    .find(product_id)
+                    .select(products::id)
+                    .first::<i32>(conn)
+                    .optional()?
+                    .ok_or_else(|| CLIERPError::NotFound(format!("Product {} not found", product_id)))?;
+            }
+            total += line.quantity as i64 * line.unit_price as i64;
+        }
+        let total_amount = i32::try_from(total)
+            .map_err(|_| CLIERPError::Validation(format!("Invoice total {} is too large", total)))?;
+
+        let invoice_id = conn.transaction::<_, CLIERPError, _>(|conn| {
+            let invoice_number = Self::next_number(conn, request.invoice_date.year())?;
+            diesel::insert_into(invoices::table)
+                .values(&NewInvoice {
+                    invoice_number: invoice_number.clone(),
+                    kind: InvoiceKind::Receivable.to_string(),
+                    customer_id: Some(request.customer_id),
+                    supplier_id: None,
+                    invoice_date: request.invoice_date,
+                    due_date: request.due_date,
+                    total_amount,
+                    paid_amount: 0,
+                    status: InvoiceStatus::Draft.to_string(),
+                    description: request.description.clone(),
+                    created_by,
+                    po_id: None,
+                })
+                .execute(conn)?;
+            let invoice_id = invoices::table
+                .filter(invoices::kind.eq(InvoiceKind::Receivable.to_string()))
+                .filter(invoices::invoice_number.eq(&invoice_number))
+                .select(invoices::id)
+                .first::<i32>(conn)?;
+
+            let lines: Vec<NewInvoiceLine> = request
+                .lines
+                .iter()
+                .enumerate()
+                .map(|(index, line)| NewInvoiceLine {
+                    invoice_id,
+                    line_number: index as i32 + 1,
+                    description: line.description.trim().to_string(),
+                    product_id: line.product_id,
+                    quantity: line.quantity,
+                    unit_price: line.unit_price,
+                    amount: line.quantity * line.unit_price,
+                })
+                .collect();
+            diesel::insert_into(invoice_lines::table).values(&lines).execute(conn)?;
+            Ok(invoice_id)
+        })?;
+        QueryCache::current().invalidate(&["finance"]);
+
+        let created = Self::get(conn, invoice_id)?;
+        tracing::info!("Drafted invoice {} for {}", created.invoice.invoice_number, total_amount);
+        Ok(created)
+    }
+
+    /// Issue a draft, or an issued invoice that has not gone out yet, and
+    /// record that it was sent
+    pub fn send(conn: &mut SqliteConnection, invoice_id: i32) -> Result<Invoice> {
+        let invoice = Self::receivable(conn, invoice_id)?;
+        let status: &str = &invoice.status;
+        if status != InvoiceStatus::Draft.to_string() && status != InvoiceStatus::Open.to_string() {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Invoice {} is {} and cannot be sent",
+                invoice.invoice_number, invoice.status
+            )));
+        }
+        if let Some(sent_at) = invoice.sent_at {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Invoice {} was already sent on {}",
+                invoice.invoice_number,
+                sent_at.format("%Y-%m-%d")
+            )));
+        }
+
+        let now = Utc::now().naive_utc();
+        diesel::update(invoices::table.find(invoice_id))
+            .set((
+                invoices::status.eq(InvoiceStatus::Open.to_string()),
+                invoices::sent_at.eq(Some(now)),
+                invoices::updated_at.eq(now),
+            ))
+            .execute(conn)?;
+        QueryCache::current().invalidate(&["finance"]);
+
+        tracing::info!("Sent invoice {}", invoice.invoice_number);
+        Ok(invoices::table.find(invoice_id).first::<Invoice>(conn)?)
+    }
+
+    /// Apply a customer payment, by default the whole open amount
+    pub fn apply_payment(
+        conn: &mut SqliteConnection,
+        invoice_id: i32,
+        amount: Option<i32>,
+        paid_on: NaiveDate,
+        reference: Option<&str>,
+        created_by: Option<i32>,
+    ) -> Result<(Invoice, InvoicePayment)> {
+        let invoice = Self::receivable(conn, invoice_id)?;
+        if invoice.status == InvoiceStatus::Draft.to_string() {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Invoice {} is a draft; send it before recording payments",
+                invoice.invoice_number
+            )));
+        }
+        ReceivableService::record_payment(conn, invoice_id, amount, paid_on, reference, created_by)
+    }
+
+    /// Cancel an invoice nothing has been paid on
+    pub fn cancel(conn: &mut SqliteConnection, invoice_id: i32) -> Result<Invoice> {
+        let invoice = Self::receivable(conn, invoice_id)?;
+        if invoice.status == InvoiceStatus::Paid.to_string() || invoice.status == InvoiceStatus::Cancelled.to_string() {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Invoice {} is {}",
+                invoice.invoice_number, invoice.status
+            )));
+        }
+        if invoice.paid_amount > 0 {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "{} has been paid on invoice {}; record the payment's return first",
+                invoice.paid_amount, invoice.invoice_number
+            )));
+        }
+
+        diesel::update(invoices::table.find(invoice_id))
+            .set((
+                invoices::status.eq(InvoiceStatus::Cancelled.to_string()),
+                invoices::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+        QueryCache::current().invalidate(&["finance"]);
+
+        tracing::info!("Cancelled invoice {}", invoice.invoice_number);
+        Ok(invoices::table.find(invoice_id).first::<Invoice>(conn)?)
+    }
+
+    pub fn get(conn: &mut SqliteConnection, invoice_id: i32) -> Result<InvoiceWithLines> {
+        let invoice = Self::receivable(conn, invoice_id)?;
+        let lines = invoice_lines::table
+            .filter(invoice_lines::invoice_id.eq(invoice_id))
+            .order(invoice_lines::line_number.asc())
+            .load::<InvoiceLine>(conn)?;
+        let payments = invoice_payments::table
+            .filter(invoice_payments::invoice_id.eq(invoice_id))
+            .order((invoice_payments::paid_on.asc(), invoice_payments::id.asc()))
+            .load::<InvoicePayment>(conn)?;
+        Ok(InvoiceWithLines { invoice, lines, payments })
+    }
+
+    /// Receivable invoices, newest first, optionally of one customer or in
+    /// one state on `today`
+    pub fn list(
+        conn: &mut SqliteConnection,
+        customer_id: Option<i32>,
+        state: Option<InvoiceState>,
+        today: NaiveDate,
+        limit: usize,
+    ) -> Result<Vec<Invoice>> {
+        let mut query = invoices::table
+            .filter(invoices::kind.eq(InvoiceKind::Receivable.to_string()))
+            .into_boxed();
+        if let Some(customer_id) = customer_id {
+            query = query.filter(invoices::customer_id.eq(customer_id));
+        }
+        Ok(query
+            .order((invoices::invoice_date.desc(), invoices::id.desc()))
+            .load::<Invoice>(conn)?
+            .into_iter()
+            .filter(|invoice| state.is_none_or(|state| invoice_state(invoice, today) == state))
+            .take(limit)
+            .collect())
+    }
+
+    /// Open amounts of issued receivable invoices by customer, aged by days
+    /// past due on `as_of`; invoices dated after `as_of` are left out
+    pub fn aging(conn: &mut SqliteConnection, as_of: NaiveDate) -> Result<Vec<AgingRow>> {
+        let open = invoices::table
+            .inner_join(customers::table)
+            .filter(invoices::kind.eq(InvoiceKind::Receivable.to_string()))
+            .filter(invoices::status.eq(InvoiceStatus::Open.to_string()))
+            .filter(invoices::invoice_date.le(as_of))
+            .select((Invoice::as_select(), customers::name))
+            .load::<(Invoice, String)>(conn)?;
+        Ok(build_aging(&open, as_of))
+    }
+
+    fn receivable(conn: &mut SqliteConnection, invoice_id: i32) -> Result<Invoice> {
+        let invoice = invoices::table
+            .find(invoice_id)
+            .first::<Invoice>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Invoice {} not found", invoice_id)))?;
+        if invoice.kind != InvoiceKind::Receivable.to_string() {
+            return Err(CLIERPError::Validation(format!(
+                "Invoice {} is a supplier invoice",
+                invoice.invoice_number
+            )));
+        }
+        Ok(invoice)
+    }
+
+    /// Take the next number of the year's sequence; call inside the
+    /// transaction that saves the invoice so an unsaved number is not used up
+    fn next_number(conn: &mut SqliteConnection, year: i32) -> Result<String> {
+        let last = invoice_sequences::table
+            .find(year)
+            .select(invoice_sequences::last_number)
+            .first::<i32>(conn)
+            .optional()?;
+        let number = last.unwrap_or(0) + 1;
+        match last {
+            Some(_) => {
+                diesel::update(invoice_sequences::table.find(year))
+                    .set(invoice_sequences::last_number.eq(number))
+                    .execute(conn)?;
+            }
+            None => {
+                diesel::insert_into(invoice_sequences::table)
+                    .values((invoice_sequences::year.eq(year), invoice_sequences::last_number.eq(number)))
+                    .execute(conn)?;
+            }
+        }
+        Ok(format_invoice_number(year, number))
+    }
+}
+
+/// One row per customer with open amounts, largest total first
+fn build_aging(open: &[(Invoice, String)], as_of: NaiveDate) -> Vec<AgingRow> {
+    let mut rows: BTreeMap<i32, AgingRow> = BTreeMap::new();
+    for (invoice, customer) in open {
+        let Some(customer_id) = invoice.customer_id else {
+            continue;
+        };
+        let row = rows.entry(customer_id).or_insert_with(|| AgingRow {
+            customer_id,
+            customer: customer.clone(),
+            ..Default::default()
+        });
+        let days_overdue = invoice.due_date.map_or(0, |due| (as_of - due).num_days());
+        row.add(days_overdue, invoice.open_amount() as i64);
+    }
+    let mut rows: Vec<AgingRow> = rows.into_values().filter(|row| row.total != 0).collect();
+    rows.sort_by(|a, b| b.total.cmp(&a.total).then(a.customer_id.cmp(&b.customer_id)));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invoice(id: i32, customer_id: i32, due: Option<&str>, total: i32, paid: i32) -> Invoice {
+        let now = Utc::now().naive_utc();
+        Invoice {
+            id,
+            invoice_number: format_invoice_number(2025, id),
+            kind: "receivable".to_string(),
+            customer_id: Some(customer_id),
+            supplier_id: None,
+            invoice_date: "2025-01-01".parse().unwrap(),
+            due_date: due.map(|d| d.parse().unwrap()),
+            total_amount: total,
+            paid_amount: paid,
+            status: "open".to_string(),
+            description: None,
+            created_by: None,
+            created_at: now,
+            updated_at: now,
+            po_id: None,
+            sent_at: None,
+        }
+    }
+
+    #[test]
+    fn test_invoice_aging() {
+        let as_of: NaiveDate = "2025-04-30".parse().unwrap();
+        let open = vec![
+            (invoice(1, 1, Some("2025-05-15"), 1000, 0), "Acme".to_string()),
+            (invoice(2, 1, Some("2025-04-10"), 800, 300), "Acme".to_string()),
+            (invoice(3, 2, Some("2025-01-15"), 400, 0), "Globex".to_string()),
+            (invoice(4, 2, None, 100, 0), "Globex".to_string()),
+        ];
+        let rows = build_aging(&open, as_of);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].customer, "Acme");
+        assert_eq!((rows[0].current, rows[0].days_1_30, rows[0].total), (1000, 500, 1500));
+        assert_eq!((rows[1].current, rows[1].over_90, rows[1].total), (100, 400, 500));
+
+        let mut sent = open[0].0.clone();
+        assert_eq!(invoice_state(&sent, as_of), InvoiceState::Open);
+        sent.sent_at = Some(Utc::now().naive_utc());
+        assert_eq!(invoice_state(&sent, as_of), InvoiceState::Sent);
+        assert_eq!(invoice_state(&sent, "2025-05-16".parse().unwrap()), InvoiceState::Overdue);
+        sent.status = "paid".to_string();
+        assert_eq!(invoice_state(&sent, "2025-05-16".parse().unwrap()), InvoiceState::Paid);
+
+        assert_eq!(format_invoice_number(2025, 42), "INV-2025-00042");
+        let line = parse_invoice_line_arg("Support: March:3:15000").unwrap();
+        assert_eq!((line.description.as_str(), line.quantity, line.unit_price), ("Support: March", 3, 15000));
+        assert!(parse_invoice_line_arg("Support:3").is_err());
+        assert!(parse_invoice_line_arg("Support:three:15000").is_err());
+    }
+}
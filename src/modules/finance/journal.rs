@@ -0,0 +1,298 @@
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use serde::Serialize;
+
+use super::transaction::{CreateTransactionRequest, TransactionService};
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::models::{JournalEntry, NewJournalEntry, Transaction};
+use crate::database::schema::{journal_entries, transactions};
+use crate::utils::cache::QueryCache;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// One debit or credit line of a journal entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalLine {
+    pub account_id: i32,
+    pub amount: i32,
+    /// "debit" or "credit"
+    pub debit_credit: String,
+    /// Falls back to the entry's description
+    pub description: Option<String>,
+}
+
+impl JournalLine {
+    pub fn debit(account_id: i32, amount: i32) -> Self {
+        Self::new(account_id, amount, "debit")
+    }
+
+    pub fn credit(account_id: i32, amount: i32) -> Self {
+        Self::new(account_id, amount, "credit")
+    }
+
+    fn new(account_id: i32, amount: i32, debit_credit: &str) -> Self {
+        Self {
+            account_id,
+            amount,
+            debit_credit: debit_credit.to_string(),
+            description: None,
+        }
+    }
+
+    pub fn described(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+/// Clap value parser for `--debit ACCOUNT_ID:AMOUNT` and `--credit ACCOUNT_ID:AMOUNT`
+pub fn parse_journal_amount_arg(s: &str) -> std::result::Result<(i32, i32), String> {
+    let (account_id, amount) = s
+        .split_once(':')
+        .ok_or_else(|| format!("Expected ACCOUNT_ID:AMOUNT, got '{}'", s))?;
+    let account_id = account_id
+        .trim()
+        .parse::<i32>()
+        .map_err(|_| format!("Invalid account ID in '{}'", s))?;
+    let amount = amount
+        .trim()
+        .parse::<i32>()
+        .map_err(|_| format!("Invalid amount in '{}'", s))?;
+    Ok((account_id, amount))
+}
+
+#[derive(Debug, Clone)]
+pub struct JournalEntryRequest {
+    pub entry_date: NaiveDate,
+    pub description: String,
+    pub reference: Option<String>,
+    pub lines: Vec<JournalLine>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JournalEntryWithLines {
+    pub entry: JournalEntry,
+    pub lines: Vec<Transaction>,
+}
+
+/// Journal entries: the only way lines reach the ledger.
+///
+/// An entry's debits and credits must net to zero, and its lines are
+/// posted together or not at all, so the books stay balanced.
+pub struct JournalService;
+
+impl JournalService {
+    /// Post a balanced entry and all of its lines in one database transaction
+    pub fn post_entry(
+        conn: &mut SqliteConnection,
+        request: JournalEntryRequest,
+        created_by: Option<i32>,
+    ) -> Result<JournalEntryWithLines> {
+        Self::post(conn, request, None, created_by)
+    }
+
+    pub fn get_entry(conn: &mut SqliteConnection, entry_id: i32) -> Result<JournalEntryWithLines> {
+        let entry = journal_entries::table
+            .find(entry_id)
+            .first::<JournalEntry>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Journal entry {} not found", entry_id)))?;
+        let lines = transactions::table
+            .filter(transactions::journal_entry_id.eq(entry.id))
+            .order(transactions::id.asc())
+            .load::<Transaction>(conn)?;
+        Ok(JournalEntryWithLines { entry, lines })
+    }
+
+    /// Entries dated within the range, newest first
+    pub fn list_entries(
+        conn: &mut SqliteConnection,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        limit: i64,
+    ) -> Result<Vec<JournalEntry>> {
+        let mut query = journal_entries::table.into_boxed();
+        if let Some(from) = from {
+            query = query.filter(journal_entries::entry_date.ge(from));
+        }
+        if let Some(to) = to {
+            query = query.filter(journal_entries::entry_date.le(to));
+        }
+        Ok(query
+            .order((journal_entries::entry_date.desc(), journal_entries::id.desc()))
+            .limit(limit)
+            .load::<JournalEntry>(conn)?)
+    }
+
+    /// Post an entry with every line of `entry_id` on the opposite side.
+    /// An entry is reversed once, and reversals are not reversed again.
+    pub fn reverse_entry(
+        conn: &mut SqliteConnection,
+        entry_id: i32,
+        entry_date: NaiveDate,
+        reason: &str,
+        created_by: Option<i32>,
+    ) -> Result<JournalEntryWithLines> {
+        let original = Self::get_entry(conn, entry_id)?;
+        if original.entry.reverses_entry_id.is_some() {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Journal entry {} is itself a reversal",
+                original.entry.entry_number
+            )));
+        }
+        let reversed_by = journal_entries::table
+            .filter(journal_entries::reverses_entry_id.eq(entry_id))
+            .select(journal_entries::entry_number)
+            .first::<String>(conn)
+            .optional()?;
+        if let Some(number) = reversed_by {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Journal entry {} is already reversed by {}",
+                original.entry.entry_number, number
+            )));
+        }
+
+        let lines = original
+            .lines
+            .iter()
+            .map(|line| JournalLine {
+                account_id: line.account_id,
+                amount: line.amount,
+                debit_credit: if line.debit_credit == "debit" { "credit" } else { "debit" }.to_string(),
+                description: Some(format!("REVERSAL: {} - {}", line.description, reason)),
+            })
+            .collect();
+        Self::post(
+            conn,
+            JournalEntryRequest {
+                entry_date,
+                description: format!("REVERSAL: {} - {}", original.entry.description, reason),
+                reference: Some(format!("REV-{}", original.entry.entry_number)),
+                lines,
+            },
+            Some(entry_id),
+            created_by,
+        )
+    }
+
+    fn post(
+        conn: &mut SqliteConnection,
+        request: JournalEntryRequest,
+        reverses_entry_id: Option<i32>,
+        created_by: Option<i32>,
+    ) -> Result<JournalEntryWithLines> {
+        check_entry_balanced(&request.lines)?;
+
+        let posted = conn.transaction::<_, CLIERPError, _>(|conn| {
+            let entry_number = Self::generate_entry_number(conn, request.entry_date)?;
+            diesel::insert_into(journal_entries::table)
+                .values(&NewJournalEntry {
+                    entry_number: entry_number.clone(),
+                    entry_date: request.entry_date,
+                    description: request.description.clone(),
+                    reference: request.reference.clone(),
+                    reverses_entry_id,
+                    created_by,
+                })
+                .execute(conn)?;
+            let entry = journal_entries::table
+                .filter(journal_entries::entry_number.eq(&entry_number))
+                .first::<JournalEntry>(conn)?;
+
+            let service = TransactionService::new();
+            let mut lines = Vec::with_capacity(request.lines.len());
+            for line in request.lines {
+                lines.push(service.post_line(
+                    conn,
+                    CreateTransactionRequest {
+                        account_id: line.account_id,
+                        transaction_date: request.entry_date,
+                        amount: line.amount,
+                        debit_credit: line.debit_credit,
+                        description: line.description.unwrap_or_else(|| request.description.clone()),
+                        reference: request.reference.clone(),
+                    },
+                    entry.id,
+                    created_by,
+                )?);
+            }
+            Ok(JournalEntryWithLines { entry, lines })
+        })?;
+        QueryCache::current().invalidate(&["finance"]);
+
+        tracing::info!(
+            "Posted journal entry {} with {} line(s)",
+            posted.entry.entry_number,
+            posted.lines.len()
+        );
+        Ok(posted)
+    }
+
+    fn generate_entry_number(conn: &mut SqliteConnection, entry_date: NaiveDate) -> Result<String> {
+        let count = journal_entries::table.count().get_result::<i64>(conn)?;
+        Ok(format!("JE{}{:06}", entry_date.format("%Y%m%d"), count + 1))
+    }
+}
+
+/// Check an entry has at least two lines of positive amounts, with debits
+/// and credits netting to zero
+pub fn check_entry_balanced(lines: &[JournalLine]) -> Result<()> {
+    if lines.len() < 2 {
+        return Err(CLIERPError::ValidationError(
+            "A journal entry needs at least one debit and one credit line".to_string(),
+        ));
+    }
+
+    let mut net: i64 = 0;
+    for line in lines {
+        if line.amount <= 0 {
+            return Err(CLIERPError::ValidationError(format!(
+                "Journal line amounts must be positive, got {} on account {}",
+                line.amount, line.account_id
+            )));
+        }
+        match line.debit_credit.as_str() {
+            "debit" => net += line.amount as i64,
+            "credit" => net -= line.amount as i64,
+            other => {
+                return Err(CLIERPError::ValidationError(format!(
+                    "Journal lines must be either 'debit' or 'credit', got '{}'",
+                    other
+                )))
+            }
+        }
+    }
+    if net != 0 {
+        return Err(CLIERPError::ValidationError(format!(
+            "Journal entry does not balance: debits and credits differ by {}",
+            net.abs()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_entry_balanced() {
+        let lines = vec![JournalLine::debit(1, 1500), JournalLine::credit(2, 1000), JournalLine::credit(3, 500)];
+        assert!(check_entry_balanced(&lines).is_ok());
+
+        assert!(check_entry_balanced(&lines[..2]).is_err());
+        assert!(check_entry_balanced(&lines[..1]).is_err());
+        assert!(check_entry_balanced(&[JournalLine::debit(1, 0), JournalLine::credit(2, 0)]).is_err());
+        assert!(check_entry_balanced(&[JournalLine::debit(1, -5), JournalLine::debit(2, 5)]).is_err());
+
+        let mut odd = lines.clone();
+        odd[0].debit_credit = "both".to_string();
+        assert!(check_entry_balanced(&odd).is_err());
+
+        assert_eq!(parse_journal_amount_arg("12:5000"), Ok((12, 5000)));
+        assert!(parse_journal_amount_arg("12").is_err());
+        assert!(parse_journal_amount_arg("cash:10").is_err());
+    }
+}
@@ -1,7 +1,29 @@
 pub mod account;
+pub mod collections;
+pub mod dunning;
+pub mod grni;
+pub mod invoice;
+pub mod journal;
+pub mod opening;
+pub mod payables;
+pub mod payments;
+pub mod posting;
+pub mod receivables;
+pub mod recognition;
 pub mod report;
 pub mod transaction;
 
 pub use account::*;
+pub use collections::*;
+pub use dunning::*;
+pub use grni::*;
+pub use invoice::*;
+pub use journal::*;
+pub use opening::*;
+pub use payables::*;
+pub use payments::*;
+pub use posting::*;
+pub use receivables::*;
+pub use recognition::*;
 pub use report::*;
 pub use transaction::*;
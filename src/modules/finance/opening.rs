@@ -0,0 +1,417 @@
+use chrono::{NaiveDate, Utc};
+use diesel::prelude::*;
+use std::collections::HashSet;
+
+use super::journal::{JournalEntryRequest, JournalLine, JournalService};
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{
+    accounts, customers, invoices, products, stock_movements, suppliers, transactions,
+};
+use crate::database::{
+    DatabaseConnection, InvoiceKind, InvoiceStatus, NewInvoice, NewStockMovement, Product,
+};
+use crate::modules::reporting::summaries::SummaryService;
+use crate::utils::cache::QueryCache;
+use crate::utils::import::CsvTable;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// `transactions.reference` of the opening journal and
+/// `stock_movements.reference_type` of opening stock
+pub const OPENING_BALANCE_REFERENCE: &str = "opening_balance";
+pub const OPENING_BALANCE_REQUIRED_COLUMNS: &[&str] = &["type", "code", "amount"];
+
+/// One row of an opening balances file
+#[derive(Debug, Clone, PartialEq)]
+enum OpeningLine {
+    /// Ledger balance, debit positive and credit negative
+    Account { code: String, amount: i64 },
+    /// On-hand quantity of a product and its total value
+    Stock { sku: String, quantity: i32, value: i64 },
+    /// Open document owed by a customer or to a supplier
+    Document {
+        kind: InvoiceKind,
+        party_code: String,
+        number: String,
+        due_date: Option<NaiveDate>,
+        amount: i64,
+    },
+}
+
+/// What an opening balances import posted
+#[derive(Debug, Clone, Default)]
+pub struct OpeningBalanceSummary {
+    pub accounts_posted: usize,
+    /// Total of the debit (and, the journal being balanced, credit) side
+    pub journal_total: i64,
+    pub products_stocked: usize,
+    pub stock_value: i64,
+    pub receivables: usize,
+    pub receivable_total: i64,
+    pub payables: usize,
+    pub payable_total: i64,
+}
+
+/// Starting balances for a new deployment.
+///
+/// The CSV has `type`, `code` and `amount` columns plus `quantity`,
+/// `document` and `due_date` where the type needs them:
+///
+/// - `account`: ledger account code; amount is the balance, debit positive
+///   and credit negative. Account rows must net to zero.
+/// - `stock`: product SKU with `quantity` on hand; amount is the total value.
+/// - `receivable` / `payable`: customer or supplier code with the open
+///   `document` number, optional `due_date` and the open amount.
+///
+/// Everything is posted in one database transaction dated `as_of`, and only
+/// once: a second import is refused while an opening journal exists.
+pub struct OpeningBalanceService;
+
+impl OpeningBalanceService {
+    pub fn import_csv(
+        conn: &mut DatabaseConnection,
+        file_path: &str,
+        as_of: NaiveDate,
+        created_by: Option<i32>,
+    ) -> Result<OpeningBalanceSummary> {
+        let table = CsvTable::read(file_path)?;
+        table.require_columns(OPENING_BALANCE_REQUIRED_COLUMNS)?;
+        let lines = parse_lines(&table)?;
+        let journal_total = check_balanced(&lines)?;
+
+        let already_posted = transactions::table
+            .filter(transactions::reference.eq(OPENING_BALANCE_REFERENCE))
+            .count()
+            .get_result::<i64>(conn)?;
+        if already_posted > 0 {
+            return Err(CLIERPError::AlreadyExists(
+                "Opening balances were already imported".to_string(),
+            ));
+        }
+
+        let summary = conn.transaction::<_, CLIERPError, _>(|conn| {
+            let mut summary = OpeningBalanceSummary {
+                journal_total,
+                ..Default::default()
+            };
+            let mut journal = Vec::new();
+            for (index, line) in lines.iter().enumerate() {
+                post_line(conn, line, as_of, created_by, &mut journal, &mut summary)
+                    .map_err(|e| CLIERPError::Validation(format!("Row {}: {}", index + 2, e)))?;
+            }
+            if !journal.is_empty() {
+                JournalService::post_entry(
+                    conn,
+                    JournalEntryRequest {
+                        entry_date: as_of,
+                        description: "Opening balance".to_string(),
+                        reference: Some(OPENING_BALANCE_REFERENCE.to_string()),
+                        lines: journal,
+                    },
+                    created_by,
+                )?;
+            }
+            Ok(summary)
+        })?;
+
+        if summary.products_stocked > 0 {
+            QueryCache::current().invalidate(&["inventory"]);
+        }
+        tracing::info!(
+            "Imported opening balances as of {}: {} accounts, {} products, {} receivables, {} payables",
+            as_of,
+            summary.accounts_posted,
+            summary.products_stocked,
+            summary.receivables,
+            summary.payables
+        );
+        Ok(summary)
+    }
+}
+
+/// Post a stock, receivable or payable row; account rows are collected into
+/// `journal`, posted as one entry once every row is read
+fn post_line(
+    conn: &mut SqliteConnection,
+    line: &OpeningLine,
+    as_of: NaiveDate,
+    created_by: Option<i32>,
+    journal: &mut Vec<JournalLine>,
+    summary: &mut OpeningBalanceSummary,
+) -> Result<()> {
+    match line {
+        OpeningLine::Account { code, amount } => {
+            let account_id = accounts::table
+                .filter(accounts::account_code.eq(code))
+                .select(accounts::id)
+                .first::<i32>(conn)
+                .optional()?
+                .ok_or_else(|| CLIERPError::NotFound(format!("Account '{}' not found", code)))?;
+            let balance = to_amount(amount.abs())?;
+            journal.push(if *amount > 0 {
+                JournalLine::debit(account_id, balance)
+            } else {
+                JournalLine::credit(account_id, balance)
+            });
+            summary.accounts_posted += 1;
+        }
+        OpeningLine::Stock { sku, quantity, value } => {
+            let product = products::table
+                .filter(products::sku.eq(sku))
+                .first::<Product>(conn)
+                .optional()?
+                .ok_or_else(|| CLIERPError::NotFound(format!("Product with SKU '{}' not found", sku)))?;
+            let unit_cost = if *quantity > 0 {
+                to_amount(value / *quantity as i64)?
+            } else {
+                product.cost_price
+            };
+
+            let change = quantity - product.current_stock;
+            if change != 0 {
+                diesel::insert_into(stock_movements::table)
+                    .values(&NewStockMovement {
+                        product_id: product.id,
+                        movement_type: "adjustment".to_string(),
+                        quantity: change,
+                        unit_cost: Some(unit_cost),
+                        reference_type: Some(OPENING_BALANCE_REFERENCE.to_string()),
+                        reference_id: None,
+                        notes: Some(format!("Opening stock as of {}", as_of)),
+                        moved_by: created_by,
+                    })
+                    .execute(conn)?;
+                SummaryService::refresh_product_day(conn, product.id, Utc::now().date_naive())?;
+            }
+            diesel::update(products::table.find(product.id))
+                .set((
+                    products::current_stock.eq(quantity),
+                    products::cost_price.eq(unit_cost),
+                    products::updated_at.eq(Utc::now().naive_utc()),
+                ))
+                .execute(conn)?;
+            summary.products_stocked += 1;
+            summary.stock_value += value;
+        }
+        OpeningLine::Document {
+            kind,
+            party_code,
+            number,
+            due_date,
+            amount,
+        } => {
+            let (customer_id, supplier_id) = match kind {
+                InvoiceKind::Receivable => {
+                    let id = customers::table
+                        .filter(customers::customer_code.eq(party_code))
+                        .select(customers::id)
+                        .first::<i32>(conn)
+                        .optional()?
+                        .ok_or_else(|| CLIERPError::NotFound(format!("Customer '{}' not found", party_code)))?;
+                    (Some(id), None)
+                }
+                InvoiceKind::Payable => {
+                    let id = suppliers::table
+                        .filter(suppliers::supplier_code.eq(party_code))
+                        .select(suppliers::id)
+                        .first::<i32>(conn)
+                        .optional()?
+                        .ok_or_else(|| CLIERPError::NotFound(format!("Supplier '{}' not found", party_code)))?;
+                    (None, Some(id))
+                }
+            };
+
+            diesel::insert_into(invoices::table)
+                .values(&NewInvoice {
+                    invoice_number: number.clone(),
+                    kind: kind.to_string(),
+                    customer_id,
+                    supplier_id,
+                    invoice_date: as_of,
+                    due_date: *due_date,
+                    total_amount: to_amount(*amount)?,
+                    paid_amount: 0,
+                    status: InvoiceStatus::Open.to_string(),
+                    description: Some("Opening balance".to_string()),
+                    created_by,
+                    po_id: None,
+                })
+                .execute(conn)?;
+            match kind {
+                InvoiceKind::Receivable => {
+                    summary.receivables += 1;
+                    summary.receivable_total += amount;
+                }
+                InvoiceKind::Payable => {
+                    summary.payables += 1;
+                    summary.payable_total += amount;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn to_amount(value: i64) -> Result<i32> {
+    i32::try_from(value).map_err(|_| CLIERPError::Validation(format!("Amount {} is too large", value)))
+}
+
+fn parse_lines(table: &CsvTable) -> Result<Vec<OpeningLine>> {
+    let mut documents = HashSet::new();
+    let mut skus = HashSet::new();
+
+    table
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(index, row)| {
+            let row_error = |message: String| CLIERPError::Validation(format!("Row {}: {}", index + 2, message));
+            let text = |column: &str| table.get(row, column).map(|s| s.to_string());
+            let number = |column: &str| -> Result<Option<i64>> {
+                table
+                    .get(row, column)
+                    .map(|value| {
+                        value
+                            .parse::<i64>()
+                            .map_err(|_| row_error(format!("{} must be a whole number, got '{}'", column, value)))
+                    })
+                    .transpose()
+            };
+
+            let code = text("code").ok_or_else(|| row_error("code is required".to_string()))?;
+            let amount = number("amount")?.ok_or_else(|| row_error("amount is required".to_string()))?;
+            let line_type = text("type").unwrap_or_default().to_lowercase();
+
+            match line_type.as_str() {
+                "account" => {
+                    if amount == 0 {
+                        return Err(row_error(format!("account {} has a zero balance", code)));
+                    }
+                    Ok(OpeningLine::Account { code, amount })
+                }
+                "stock" => {
+                    let quantity = number("quantity")?
+                        .ok_or_else(|| row_error("quantity is required for stock rows".to_string()))?;
+                    let quantity = i32::try_from(quantity)
+                        .ok()
+                        .filter(|q| *q >= 0)
+                        .ok_or_else(|| row_error(format!("quantity {} is out of range", quantity)))?;
+                    if amount < 0 {
+                        return Err(row_error("stock value cannot be negative".to_string()));
+                    }
+                    if !skus.insert(code.clone()) {
+                        return Err(row_error(format!("SKU {} appears more than once", code)));
+                    }
+                    Ok(OpeningLine::Stock {
+                        sku: code,
+                        quantity,
+                        value: amount,
+                    })
+                }
+                "receivable" | "payable" => {
+                    let kind = if line_type == "receivable" {
+                        InvoiceKind::Receivable
+                    } else {
+                        InvoiceKind::Payable
+                    };
+                    let number = text("document")
+                        .ok_or_else(|| row_error(format!("document is required for {} rows", line_type)))?;
+                    let due_date = text("due_date")
+                        .map(|value| {
+                            NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+                                .map_err(|_| row_error(format!("due_date must be YYYY-MM-DD, got '{}'", value)))
+                        })
+                        .transpose()?;
+                    if amount <= 0 {
+                        return Err(row_error(format!("open amount of {} must be positive", number)));
+                    }
+                    if !documents.insert((kind.to_string(), code.clone(), number.clone())) {
+                        return Err(row_error(format!("{} document {} of {} appears more than once", line_type, number, code)));
+                    }
+                    Ok(OpeningLine::Document {
+                        kind,
+                        party_code: code,
+                        number,
+                        due_date,
+                        amount,
+                    })
+                }
+                other => Err(row_error(format!(
+                    "type must be account, stock, receivable or payable, got '{}'",
+                    other
+                ))),
+            }
+        })
+        .collect()
+}
+
+/// Debit total of the account rows, which must equal their credit total
+fn check_balanced(lines: &[OpeningLine]) -> Result<i64> {
+    let (debit, credit) = lines.iter().fold((0i64, 0i64), |(debit, credit), line| match line {
+        OpeningLine::Account { amount, .. } if *amount > 0 => (debit + amount, credit),
+        OpeningLine::Account { amount, .. } => (debit, credit - amount),
+        _ => (debit, credit),
+    });
+    if debit != credit {
+        return Err(CLIERPError::Validation(format!(
+            "Opening journal is out of balance: debits {} and credits {} differ by {}",
+            debit,
+            credit,
+            debit - credit
+        )));
+    }
+    Ok(debit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_balance() {
+        let table = CsvTable::parse(
+            "type,code,quantity,amount,document,due_date\n\
+             account,1100,,500000,,\n\
+             account,1300,,250000,,\n\
+             account,3000,,-750000,,\n\
+             stock,SKU-1,10,250000,,\n\
+             receivable,C001,,120000,INV-0042,2025-02-15\n\
+             payable,S001,,80000,BILL-7,\n",
+        )
+        .unwrap();
+        let lines = parse_lines(&table).unwrap();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(
+            lines[3],
+            OpeningLine::Stock {
+                sku: "SKU-1".to_string(),
+                quantity: 10,
+                value: 250000
+            }
+        );
+        assert!(matches!(
+            &lines[4],
+            OpeningLine::Document { kind: InvoiceKind::Receivable, due_date: Some(_), .. }
+        ));
+        assert_eq!(check_balanced(&lines).unwrap(), 750000);
+
+        let unbalanced = CsvTable::parse("type,code,amount\naccount,1100,100\naccount,3000,-90\n").unwrap();
+        assert!(check_balanced(&parse_lines(&unbalanced).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for content in [
+            "type,code,amount\nledger,1100,100\n",
+            "type,code,amount\nstock,SKU-1,100\n",
+            "type,code,amount\nreceivable,C001,100\n",
+            "type,code,amount,document\npayable,S001,-5,B-1\n",
+            "type,code,amount,document\nreceivable,C001,5,I-1\nreceivable,C001,7,I-1\n",
+        ] {
+            let table = CsvTable::parse(content).unwrap();
+            assert!(parse_lines(&table).is_err(), "{}", content);
+        }
+    }
+}
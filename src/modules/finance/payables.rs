@@ -0,0 +1,292 @@
+use std::collections::BTreeMap;
+
+use chrono::{Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use super::payments::PaymentService;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::models::{Invoice, InvoiceKind, InvoicePayment, InvoiceStatus, NewInvoicePayment};
+use crate::database::schema::{invoice_payments, invoices, suppliers};
+use crate::utils::cache::QueryCache;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Days until payment from a supplier's payment terms: "Net 30", "30 days"
+/// and "2/10 Net 45" (the net days count) are read, as are "COD" and "Due
+/// on receipt"; anything else is `None`
+pub fn payment_terms_days(terms: &str) -> Option<i64> {
+    let terms = terms.trim().to_lowercase();
+    if matches!(terms.as_str(), "cod" | "cash on delivery" | "due on receipt" | "on receipt" | "immediate") {
+        return Some(0);
+    }
+    terms
+        .split(|c: char| !c.is_ascii_digit())
+        .rfind(|part| !part.is_empty())
+        .and_then(|days| days.parse().ok())
+}
+
+/// Due date of a bill dated `bill_date` under `terms`
+pub fn bill_due_date(bill_date: NaiveDate, terms: &str) -> Result<NaiveDate> {
+    payment_terms_days(terms)
+        .map(|days| bill_date + Duration::days(days))
+        .ok_or_else(|| {
+            CLIERPError::Validation(format!(
+                "Cannot read a number of days from payment terms '{}'; give the due date",
+                terms
+            ))
+        })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BillWithPayments {
+    pub bill: Invoice,
+    pub supplier: String,
+    pub payments: Vec<InvoicePayment>,
+}
+
+/// Open bills of one supplier by when they fall due
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PayablesDueRow {
+    pub supplier_id: i32,
+    pub supplier: String,
+    /// Past its due date
+    pub overdue: i64,
+    /// Due within 30 days, or without a due date
+    pub due_0_30: i64,
+    pub due_31_60: i64,
+    pub due_61_90: i64,
+    pub later: i64,
+    pub total: i64,
+}
+
+impl PayablesDueRow {
+    fn add(&mut self, days_until_due: i64, amount: i64) {
+        match days_until_due {
+            i64::MIN..=-1 => self.overdue += amount,
+            0..=30 => self.due_0_30 += amount,
+            31..=60 => self.due_31_60 += amount,
+            61..=90 => self.due_61_90 += amount,
+            _ => self.later += amount,
+        }
+        self.total += amount;
+    }
+}
+
+/// Supplier bills, the payable invoices recorded against purchase orders,
+/// and the payments made on them outside payment batches
+pub struct PayableService;
+
+impl PayableService {
+    /// Record a payment (by default the whole open amount) on an open
+    /// bill, marking it paid once nothing is left open. Bills waiting in a
+    /// generated payment batch are paid by the batch instead.
+    pub fn record_payment(
+        conn: &mut SqliteConnection,
+        bill_id: i32,
+        amount: Option<i32>,
+        paid_on: NaiveDate,
+        reference: Option<&str>,
+        created_by: Option<i32>,
+    ) -> Result<(Invoice, InvoicePayment)> {
+        let bill = Self::bill(conn, bill_id)?;
+        if bill.status != InvoiceStatus::Open.to_string() {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Bill {} is {}",
+                bill.invoice_number, bill.status
+            )));
+        }
+        if PaymentService::batched_ids(conn)?.1.contains(&bill_id) {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Bill {} is in a payment batch; confirm or cancel the batch first",
+                bill.invoice_number
+            )));
+        }
+        let amount = amount.unwrap_or(bill.open_amount());
+        if amount <= 0 {
+            return Err(CLIERPError::Validation("Payment amount must be positive".to_string()));
+        }
+        if amount > bill.open_amount() {
+            return Err(CLIERPError::Validation(format!(
+                "Payment of {} exceeds the open amount {} of bill {}",
+                amount,
+                bill.open_amount(),
+                bill.invoice_number
+            )));
+        }
+
+        let paid_amount = bill.paid_amount + amount;
+        let status = if paid_amount == bill.total_amount { InvoiceStatus::Paid } else { InvoiceStatus::Open };
+        let payment = conn.transaction::<_, CLIERPError, _>(|conn| {
+            diesel::insert_into(invoice_payments::table)
+                .values(&NewInvoicePayment {
+                    invoice_id: bill_id,
+                    amount,
+                    paid_on,
+                    reference: reference.map(str::trim).filter(|r| !r.is_empty()).map(str::to_string),
+                    created_by,
+                })
+                .execute(conn)?;
+            diesel::update(invoices::table.find(bill_id))
+                .set((
+                    invoices::paid_amount.eq(paid_amount),
+                    invoices::status.eq(status.to_string()),
+                    invoices::updated_at.eq(Utc::now().naive_utc()),
+                ))
+                .execute(conn)?;
+            Ok(invoice_payments::table
+                .filter(invoice_payments::invoice_id.eq(bill_id))
+                .order(invoice_payments::id.desc())
+                .first::<InvoicePayment>(conn)?)
+        })?;
+        QueryCache::current().invalidate(&["finance"]);
+
+        tracing::info!("Recorded payment of {} on bill {}", amount, bill.invoice_number);
+        Ok((invoices::table.find(bill_id).first::<Invoice>(conn)?, payment))
+    }
+
+    pub fn get(conn: &mut SqliteConnection, bill_id: i32) -> Result<BillWithPayments> {
+        let bill = Self::bill(conn, bill_id)?;
+        let supplier = match bill.supplier_id {
+            Some(supplier_id) => suppliers::table
+                .find(supplier_id)
+                .select(suppliers::name)
+                .first::<String>(conn)
+                .optional()?
+                .unwrap_or_else(|| format!("Supplier #{}", supplier_id)),
+            None => "-".to_string(),
+        };
+        let payments = invoice_payments::table
+            .filter(invoice_payments::invoice_id.eq(bill_id))
+            .order((invoice_payments::paid_on.asc(), invoice_payments::id.asc()))
+            .load::<InvoicePayment>(conn)?;
+        Ok(BillWithPayments { bill, supplier, payments })
+    }
+
+    /// Bills, earliest due first, optionally of one supplier or only open ones
+    pub fn list(
+        conn: &mut SqliteConnection,
+        supplier_id: Option<i32>,
+        open_only: bool,
+        limit: i64,
+    ) -> Result<Vec<Invoice>> {
+        let mut query = invoices::table
+            .filter(invoices::kind.eq(InvoiceKind::Payable.to_string()))
+            .into_boxed();
+        if let Some(supplier_id) = supplier_id {
+            query = query.filter(invoices::supplier_id.eq(supplier_id));
+        }
+        if open_only {
+            query = query.filter(invoices::status.eq(InvoiceStatus::Open.to_string()));
+        }
+        Ok(query
+            .order((invoices::due_date.asc(), invoices::id.asc()))
+            .limit(limit)
+            .load::<Invoice>(conn)?)
+    }
+
+    /// Open amounts of bills by supplier and days until due on `as_of`;
+    /// bills dated after `as_of` are left out
+    pub fn aging(conn: &mut SqliteConnection, as_of: NaiveDate) -> Result<Vec<PayablesDueRow>> {
+        let open = invoices::table
+            .inner_join(suppliers::table)
+            .filter(invoices::kind.eq(InvoiceKind::Payable.to_string()))
+            .filter(invoices::status.eq(InvoiceStatus::Open.to_string()))
+            .filter(invoices::invoice_date.le(as_of))
+            .select((Invoice::as_select(), suppliers::name))
+            .load::<(Invoice, String)>(conn)?;
+        Ok(build_payables_due(&open, as_of))
+    }
+
+    fn bill(conn: &mut SqliteConnection, bill_id: i32) -> Result<Invoice> {
+        let bill = invoices::table
+            .find(bill_id)
+            .first::<Invoice>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Bill {} not found", bill_id)))?;
+        if bill.kind != InvoiceKind::Payable.to_string() {
+            return Err(CLIERPError::Validation(format!(
+                "Invoice {} is a customer invoice",
+                bill.invoice_number
+            )));
+        }
+        Ok(bill)
+    }
+}
+
+/// One row per supplier with open amounts, soonest due first
+fn build_payables_due(open: &[(Invoice, String)], as_of: NaiveDate) -> Vec<PayablesDueRow> {
+    let mut rows: BTreeMap<i32, PayablesDueRow> = BTreeMap::new();
+    for (bill, supplier) in open {
+        let Some(supplier_id) = bill.supplier_id else {
+            continue;
+        };
+        let row = rows.entry(supplier_id).or_insert_with(|| PayablesDueRow {
+            supplier_id,
+            supplier: supplier.clone(),
+            ..Default::default()
+        });
+        let days_until_due = bill.due_date.map_or(0, |due| (due - as_of).num_days());
+        row.add(days_until_due, bill.open_amount() as i64);
+    }
+    let mut rows: Vec<PayablesDueRow> = rows.into_values().filter(|row| row.total != 0).collect();
+    rows.sort_by(|a, b| {
+        (b.overdue, b.due_0_30, b.due_31_60, b.due_61_90)
+            .cmp(&(a.overdue, a.due_0_30, a.due_31_60, a.due_61_90))
+            .then(a.supplier_id.cmp(&b.supplier_id))
+    });
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bill(id: i32, supplier_id: i32, due: &str, total: i32, paid: i32) -> Invoice {
+        let now = Utc::now().naive_utc();
+        Invoice {
+            id,
+            invoice_number: format!("SUP-{}", id),
+            kind: "payable".to_string(),
+            customer_id: None,
+            supplier_id: Some(supplier_id),
+            invoice_date: "2025-03-01".parse().unwrap(),
+            due_date: Some(due.parse().unwrap()),
+            total_amount: total,
+            paid_amount: paid,
+            status: "open".to_string(),
+            description: None,
+            created_by: None,
+            created_at: now,
+            updated_at: now,
+            po_id: None,
+            sent_at: None,
+        }
+    }
+
+    #[test]
+    fn test_payables_due() {
+        assert_eq!(payment_terms_days("Net 30"), Some(30));
+        assert_eq!(payment_terms_days("45 days"), Some(45));
+        assert_eq!(payment_terms_days("2/10 Net 60"), Some(60));
+        assert_eq!(payment_terms_days("COD"), Some(0));
+        assert_eq!(payment_terms_days("end of month"), None);
+        assert_eq!(bill_due_date("2025-01-31".parse().unwrap(), "Net 30").unwrap(), "2025-03-02".parse().unwrap());
+        assert!(bill_due_date("2025-01-31".parse().unwrap(), "EOM").is_err());
+
+        let as_of: NaiveDate = "2025-04-01".parse().unwrap();
+        let open = vec![
+            (bill(1, 1, "2025-03-25", 500, 0), "Parts Co".to_string()),
+            (bill(2, 1, "2025-04-20", 1000, 400), "Parts Co".to_string()),
+            (bill(3, 2, "2025-05-15", 700, 0), "Tools Ltd".to_string()),
+            (bill(4, 2, "2025-08-01", 300, 0), "Tools Ltd".to_string()),
+        ];
+        let rows = build_payables_due(&open, as_of);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].supplier, "Parts Co");
+        assert_eq!((rows[0].overdue, rows[0].due_0_30, rows[0].total), (500, 600, 1100));
+        assert_eq!((rows[1].due_31_60, rows[1].later, rows[1].total), (700, 300, 1000));
+    }
+}
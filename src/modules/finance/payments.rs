@@ -0,0 +1,639 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::Path;
+
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use diesel::prelude::*;
+
+use crate::core::auth::AuthenticatedUser;
+use crate::core::config::BankConfig;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::models::{
+    Invoice, InvoiceKind, InvoiceStatus, NewInvoicePayment, NewPayeeBankAccount, NewPaymentBatch, NewPaymentBatchItem,
+    PayeeBankAccount, PaymentBatch, PaymentBatchItem, PaymentBatchKind, PaymentBatchStatus,
+    PaymentFileFormat, Payroll, PayrollStatus,
+};
+use crate::database::schema::{
+    employees, invoice_payments, invoices, payee_bank_accounts, payment_batch_items, payment_batches, payrolls, suppliers,
+};
+use crate::modules::hr::PayrollService;
+use crate::utils::cache::QueryCache;
+use crate::utils::currency::CurrencyFormat;
+use crate::utils::export::escape_csv_value;
+use crate::utils::validation::validate_required_string;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+pub const PAYEE_EMPLOYEE: &str = "employee";
+pub const PAYEE_SUPPLIER: &str = "supplier";
+
+/// The company account payments are made from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentDebtor {
+    pub name: String,
+    pub account: String,
+    pub bic: Option<String>,
+    pub currency: String,
+}
+
+impl PaymentDebtor {
+    pub fn from_config(config: &BankConfig) -> Result<Self> {
+        match (&config.debtor_name, &config.debtor_account) {
+            (Some(name), Some(account)) => Ok(Self {
+                name: name.clone(),
+                account: account.clone(),
+                bic: config.debtor_bic.clone(),
+                currency: config.currency.clone(),
+            }),
+            _ => Err(CLIERPError::Configuration(config::ConfigError::Message(
+                "Set bank.debtor_name and bank.debtor_account (CLIERP_BANK__DEBTOR_NAME, CLIERP_BANK__DEBTOR_ACCOUNT) to export payment files".to_string(),
+            ))),
+        }
+    }
+}
+
+/// A payment ready to go in a batch, before it is saved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentLine {
+    pub payroll_id: Option<i32>,
+    pub invoice_id: Option<i32>,
+    pub payee_name: String,
+    pub account_number: String,
+    pub bic: Option<String>,
+    pub amount: i32,
+    pub remittance: String,
+}
+
+/// Payments that can be exported now, and those held back with the reason
+#[derive(Debug, Clone, Default)]
+pub struct PaymentPlan {
+    pub lines: Vec<PaymentLine>,
+    pub skipped: Vec<(String, String)>,
+}
+
+impl PaymentPlan {
+    pub fn total(&self) -> i64 {
+        self.lines.iter().map(|line| line.amount as i64).sum()
+    }
+}
+
+/// Bank payment files for net salaries and due supplier invoices.
+///
+/// Exporting writes the file and records it as a batch; the payroll or
+/// invoice behind each payment stays unpaid, and out of any other batch,
+/// until the batch is confirmed once the bank has accepted the file.
+/// Cancelling a batch releases its payments for a new export. Payrolls are
+/// paid through `PayrollService::pay_payroll`, so the `payroll_prepare_pay`
+/// segregation of duties rule applies at confirmation.
+pub struct PaymentService;
+
+impl PaymentService {
+    /// Create or replace the bank account of an employee or supplier
+    pub fn set_bank_account(
+        conn: &mut SqliteConnection,
+        payee_type: &str,
+        payee_id: i32,
+        account_number: &str,
+        bic: Option<&str>,
+        account_holder: Option<&str>,
+        updated_by: Option<i32>,
+    ) -> Result<PayeeBankAccount> {
+        validate_required_string(account_number, "Account number")?;
+        Self::payee_name(conn, payee_type, payee_id)?;
+        let account_number: String = account_number.chars().filter(|c| !c.is_whitespace()).collect();
+        let bic = bic.map(str::trim).filter(|b| !b.is_empty()).map(|b| b.to_uppercase());
+        let account_holder = account_holder.map(str::trim).filter(|h| !h.is_empty()).map(|h| h.to_string());
+
+        let updated = diesel::update(
+            payee_bank_accounts::table
+                .filter(payee_bank_accounts::payee_type.eq(payee_type))
+                .filter(payee_bank_accounts::payee_id.eq(payee_id)),
+        )
+        .set((
+            payee_bank_accounts::account_number.eq(&account_number),
+            payee_bank_accounts::bic.eq(&bic),
+            payee_bank_accounts::account_holder.eq(&account_holder),
+            payee_bank_accounts::updated_by.eq(updated_by),
+            payee_bank_accounts::updated_at.eq(Utc::now().naive_utc()),
+        ))
+        .execute(conn)?;
+        if updated == 0 {
+            diesel::insert_into(payee_bank_accounts::table)
+                .values(&NewPayeeBankAccount {
+                    payee_type: payee_type.to_string(),
+                    payee_id,
+                    account_holder,
+                    account_number,
+                    bic,
+                    updated_by,
+                })
+                .execute(conn)?;
+        }
+
+        tracing::info!("Bank account of {} {} updated", payee_type, payee_id);
+        Self::bank_account(conn, payee_type, payee_id)?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Bank account of {} {} not found", payee_type, payee_id)))
+    }
+
+    pub fn bank_account(
+        conn: &mut SqliteConnection,
+        payee_type: &str,
+        payee_id: i32,
+    ) -> Result<Option<PayeeBankAccount>> {
+        payee_bank_accounts::table
+            .filter(payee_bank_accounts::payee_type.eq(payee_type))
+            .filter(payee_bank_accounts::payee_id.eq(payee_id))
+            .first::<PayeeBankAccount>(conn)
+            .optional()
+            .map_err(Into::into)
+    }
+
+    pub fn bank_accounts(conn: &mut SqliteConnection) -> Result<Vec<PayeeBankAccount>> {
+        payee_bank_accounts::table
+            .order((payee_bank_accounts::payee_type.asc(), payee_bank_accounts::payee_id.asc()))
+            .load::<PayeeBankAccount>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Net salaries of the unpaid payrolls of `period`
+    pub fn plan_payroll(conn: &mut SqliteConnection, period: &str) -> Result<PaymentPlan> {
+        let pending = payrolls::table
+            .inner_join(employees::table)
+            .filter(payrolls::period.eq(period))
+            .filter(payrolls::status.ne(PayrollStatus::Paid.to_string()))
+            .order(employees::employee_code.asc())
+            .select((Payroll::as_select(), employees::employee_code, employees::name))
+            .load::<(Payroll, String, String)>(conn)?;
+        let batched = Self::batched_ids(conn)?;
+        let accounts = Self::accounts_of(conn, PAYEE_EMPLOYEE)?;
+
+        let mut plan = PaymentPlan::default();
+        for (payroll, code, name) in pending {
+            let payee = format!("{} ({})", name, code);
+            if batched.0.contains(&payroll.id) {
+                plan.skipped.push((payee, "already in a payment batch".to_string()));
+            } else if payroll.net_salary <= 0 {
+                plan.skipped.push((payee, "no net salary to pay".to_string()));
+            } else if let Some(account) = accounts.get(&payroll.employee_id) {
+                plan.lines.push(PaymentLine {
+                    payroll_id: Some(payroll.id),
+                    invoice_id: None,
+                    payee_name: account.account_holder.clone().unwrap_or(name),
+                    account_number: account.account_number.clone(),
+                    bic: account.bic.clone(),
+                    amount: payroll.net_salary,
+                    remittance: format!("Salary {}", period),
+                });
+            } else {
+                plan.skipped.push((payee, "no bank account on file".to_string()));
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Open amounts of supplier invoices due on or before `due_by`
+    pub fn plan_suppliers(conn: &mut SqliteConnection, due_by: NaiveDate) -> Result<PaymentPlan> {
+        let due = invoices::table
+            .filter(invoices::kind.eq(InvoiceKind::Payable.to_string()))
+            .filter(invoices::status.eq(InvoiceStatus::Open.to_string()))
+            .filter(invoices::due_date.le(due_by))
+            .order((invoices::due_date.asc(), invoices::id.asc()))
+            .load::<Invoice>(conn)?;
+        let supplier_names: HashMap<i32, String> = suppliers::table
+            .select((suppliers::id, suppliers::name))
+            .load::<(i32, String)>(conn)?
+            .into_iter()
+            .collect();
+        let batched = Self::batched_ids(conn)?;
+        let accounts = Self::accounts_of(conn, PAYEE_SUPPLIER)?;
+
+        let mut plan = PaymentPlan::default();
+        for invoice in due.into_iter().filter(|invoice| invoice.open_amount() > 0) {
+            let Some(supplier_id) = invoice.supplier_id else {
+                continue;
+            };
+            let name = supplier_names
+                .get(&supplier_id)
+                .cloned()
+                .unwrap_or_else(|| format!("Supplier #{}", supplier_id));
+            let payee = format!("{} ({})", name, invoice.invoice_number);
+            if batched.1.contains(&invoice.id) {
+                plan.skipped.push((payee, "already in a payment batch".to_string()));
+            } else if let Some(account) = accounts.get(&supplier_id) {
+                plan.lines.push(PaymentLine {
+                    payroll_id: None,
+                    invoice_id: Some(invoice.id),
+                    payee_name: account.account_holder.clone().unwrap_or(name),
+                    account_number: account.account_number.clone(),
+                    bic: account.bic.clone(),
+                    amount: invoice.open_amount(),
+                    remittance: format!("Invoice {}", invoice.invoice_number),
+                });
+            } else {
+                plan.skipped.push((payee, "no bank account on file".to_string()));
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Write `plan` to `output` in `format` and record it as a generated batch
+    #[allow(clippy::too_many_arguments)]
+    pub fn export(
+        conn: &mut SqliteConnection,
+        kind: PaymentBatchKind,
+        plan: &PaymentPlan,
+        format: PaymentFileFormat,
+        output: &Path,
+        execution_date: NaiveDate,
+        debtor: &PaymentDebtor,
+        created_by: Option<i32>,
+    ) -> Result<PaymentBatch> {
+        if plan.lines.is_empty() {
+            return Err(CLIERPError::ValidationError("There are no payments to export".to_string()));
+        }
+        let total = i32::try_from(plan.total())
+            .map_err(|_| CLIERPError::ValidationError("Batch total is too large".to_string()))?;
+        let now = Utc::now().naive_utc();
+        let reference = format!("{}-{}", kind.to_string().to_uppercase(), now.format("%Y%m%d%H%M%S"));
+        let contents = match format {
+            PaymentFileFormat::Csv => render_csv(&reference, execution_date, debtor, &plan.lines),
+            PaymentFileFormat::Pain001 => render_pain001(&reference, now, execution_date, kind, debtor, &plan.lines),
+        };
+
+        let batch = conn.transaction::<_, CLIERPError, _>(|conn| {
+            diesel::insert_into(payment_batches::table)
+                .values(&NewPaymentBatch {
+                    batch_reference: reference.clone(),
+                    kind: kind.to_string(),
+                    format: format.to_string(),
+                    file_path: output.to_string_lossy().to_string(),
+                    execution_date,
+                    item_count: plan.lines.len() as i32,
+                    total_amount: total,
+                    created_by,
+                })
+                .execute(conn)?;
+            let batch = payment_batches::table
+                .order(payment_batches::id.desc())
+                .first::<PaymentBatch>(conn)?;
+            let items: Vec<NewPaymentBatchItem> = plan
+                .lines
+                .iter()
+                .map(|line| NewPaymentBatchItem {
+                    batch_id: batch.id,
+                    payroll_id: line.payroll_id,
+                    invoice_id: line.invoice_id,
+                    payee_name: line.payee_name.clone(),
+                    account_number: line.account_number.clone(),
+                    bic: line.bic.clone(),
+                    amount: line.amount,
+                    remittance: line.remittance.clone(),
+                })
+                .collect();
+            diesel::insert_into(payment_batch_items::table)
+                .values(&items)
+                .execute(conn)?;
+
+            // Written last so a failed insert leaves no file behind
+            if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(output, &contents)?;
+            Ok(batch)
+        })?;
+
+        tracing::info!(
+            "Exported {} payment batch {} ({} payments, total {}) to {}",
+            kind,
+            batch.batch_reference,
+            batch.item_count,
+            batch.total_amount,
+            batch.file_path
+        );
+        Ok(batch)
+    }
+
+    /// Mark every payment of a generated batch as paid: payrolls through
+    /// payroll payment, supplier invoices settled in full
+    pub fn confirm(
+        conn: &mut SqliteConnection,
+        batch_id: i32,
+        user: &AuthenticatedUser,
+        sod_override: Option<&str>,
+    ) -> Result<PaymentBatch> {
+        let batch = Self::generated_batch(conn, batch_id)?;
+        let items = Self::items(conn, batch_id)?;
+        let payroll_service = PayrollService::new();
+
+        conn.transaction::<_, CLIERPError, _>(|conn| {
+            let now = Utc::now().naive_utc();
+            for item in &items {
+                if let Some(payroll_id) = item.payroll_id {
+                    payroll_service.pay_payroll(conn, payroll_id, user.id, sod_override)?;
+                }
+                if let Some(invoice_id) = item.invoice_id {
+                    diesel::insert_into(invoice_payments::table)
+                        .values(&NewInvoicePayment {
+                            invoice_id,
+                            amount: item.amount,
+                            paid_on: now.date(),
+                            reference: Some(batch.batch_reference.clone()),
+                            created_by: Some(user.id),
+                        })
+                        .execute(conn)?;
+                    diesel::update(invoices::table.find(invoice_id))
+                        .set((
+                            invoices::paid_amount.eq(invoices::total_amount),
+                            invoices::status.eq(InvoiceStatus::Paid.to_string()),
+                            invoices::updated_at.eq(now),
+                        ))
+                        .execute(conn)?;
+                }
+            }
+            Self::decide(conn, batch.id, PaymentBatchStatus::Confirmed, user.id)
+        })?;
+        QueryCache::current().invalidate(&["finance", "hr"]);
+
+        tracing::info!("Payment batch {} confirmed by user {}", batch.batch_reference, user.id);
+        Self::find_batch(conn, batch_id)
+    }
+
+    /// Discard a generated batch so its payments can be exported again
+    pub fn cancel(conn: &mut SqliteConnection, batch_id: i32, cancelled_by: i32) -> Result<PaymentBatch> {
+        let batch = Self::generated_batch(conn, batch_id)?;
+        Self::decide(conn, batch.id, PaymentBatchStatus::Cancelled, cancelled_by)?;
+
+        tracing::info!("Payment batch {} cancelled by user {}", batch.batch_reference, cancelled_by);
+        Self::find_batch(conn, batch_id)
+    }
+
+    /// Batches, newest first
+    pub fn batches(conn: &mut SqliteConnection, limit: i64) -> Result<Vec<PaymentBatch>> {
+        payment_batches::table
+            .order(payment_batches::id.desc())
+            .limit(limit)
+            .load::<PaymentBatch>(conn)
+            .map_err(Into::into)
+    }
+
+    pub fn find_batch(conn: &mut SqliteConnection, batch_id: i32) -> Result<PaymentBatch> {
+        payment_batches::table
+            .find(batch_id)
+            .first::<PaymentBatch>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Payment batch {} not found", batch_id)))
+    }
+
+    pub fn items(conn: &mut SqliteConnection, batch_id: i32) -> Result<Vec<PaymentBatchItem>> {
+        payment_batch_items::table
+            .filter(payment_batch_items::batch_id.eq(batch_id))
+            .order(payment_batch_items::id.asc())
+            .load::<PaymentBatchItem>(conn)
+            .map_err(Into::into)
+    }
+
+    fn generated_batch(conn: &mut SqliteConnection, batch_id: i32) -> Result<PaymentBatch> {
+        let batch = Self::find_batch(conn, batch_id)?;
+        if batch.status != PaymentBatchStatus::Generated.to_string() {
+            return Err(CLIERPError::ValidationError(format!(
+                "Payment batch {} is already {}",
+                batch.batch_reference, batch.status
+            )));
+        }
+        Ok(batch)
+    }
+
+    fn decide(conn: &mut SqliteConnection, batch_id: i32, status: PaymentBatchStatus, user_id: i32) -> Result<()> {
+        diesel::update(payment_batches::table.find(batch_id))
+            .set((
+                payment_batches::status.eq(status.to_string()),
+                payment_batches::decided_by.eq(Some(user_id)),
+                payment_batches::decided_at.eq(Some(Utc::now().naive_utc())),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Payroll and invoice IDs in batches that are generated but not yet confirmed
+    pub(crate) fn batched_ids(conn: &mut SqliteConnection) -> Result<(HashSet<i32>, HashSet<i32>)> {
+        let items = payment_batch_items::table
+            .inner_join(payment_batches::table)
+            .filter(payment_batches::status.eq(PaymentBatchStatus::Generated.to_string()))
+            .select((payment_batch_items::payroll_id, payment_batch_items::invoice_id))
+            .load::<(Option<i32>, Option<i32>)>(conn)?;
+        Ok((
+            items.iter().filter_map(|(payroll, _)| *payroll).collect(),
+            items.iter().filter_map(|(_, invoice)| *invoice).collect(),
+        ))
+    }
+
+    fn accounts_of(conn: &mut SqliteConnection, payee_type: &str) -> Result<HashMap<i32, PayeeBankAccount>> {
+        Ok(payee_bank_accounts::table
+            .filter(payee_bank_accounts::payee_type.eq(payee_type))
+            .load::<PayeeBankAccount>(conn)?
+            .into_iter()
+            .map(|account| (account.payee_id, account))
+            .collect())
+    }
+
+    fn payee_name(conn: &mut SqliteConnection, payee_type: &str, payee_id: i32) -> Result<String> {
+        let name = match payee_type {
+            PAYEE_EMPLOYEE => employees::table
+                .find(payee_id)
+                .select(employees::name)
+                .first::<String>(conn)
+                .optional()?,
+            PAYEE_SUPPLIER => suppliers::table
+                .find(payee_id)
+                .select(suppliers::name)
+                .first::<String>(conn)
+                .optional()?,
+            _ => {
+                return Err(CLIERPError::ValidationError(format!(
+                    "Unknown payee type '{}'",
+                    payee_type
+                )))
+            }
+        };
+        name.ok_or_else(|| CLIERPError::NotFound(format!("{} {} not found", payee_type, payee_id)))
+    }
+}
+
+/// One payment per line with the batch reference and execution date
+fn render_csv(reference: &str, execution_date: NaiveDate, debtor: &PaymentDebtor, lines: &[PaymentLine]) -> String {
+    let currency = CurrencyFormat::current();
+    let mut csv = String::from("batch_reference,execution_date,debtor_account,payee_name,account_number,bic,amount,currency,remittance\n");
+    for line in lines {
+        let fields = [
+            reference.to_string(),
+            execution_date.to_string(),
+            debtor.account.clone(),
+            line.payee_name.clone(),
+            line.account_number.clone(),
+            line.bic.clone().unwrap_or_default(),
+            currency.format_plain(line.amount as i64),
+            debtor.currency.clone(),
+            line.remittance.clone(),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| escape_csv_value(f)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// ISO 20022 `pain.001.001.03` customer credit transfer initiation with one
+/// payment information block for the whole batch
+fn render_pain001(
+    reference: &str,
+    created_at: NaiveDateTime,
+    execution_date: NaiveDate,
+    kind: PaymentBatchKind,
+    debtor: &PaymentDebtor,
+    lines: &[PaymentLine],
+) -> String {
+    let currency = CurrencyFormat::current();
+    let control_sum = currency.format_plain(lines.iter().map(|l| l.amount as i64).sum());
+    let purpose = match kind {
+        PaymentBatchKind::Payroll => "SALA",
+        PaymentBatchKind::Supplier => "SUPP",
+    };
+
+    let mut xml = String::new();
+    let _ = writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(xml, r#"<Document xmlns="urn:iso:std:iso:20022:tech:xsd:pain.001.001.03">"#);
+    let _ = writeln!(xml, "  <CstmrCdtTrfInitn>");
+    let _ = writeln!(xml, "    <GrpHdr>");
+    let _ = writeln!(xml, "      <MsgId>{}</MsgId>", xml_escape(reference));
+    let _ = writeln!(xml, "      <CreDtTm>{}</CreDtTm>", created_at.format("%Y-%m-%dT%H:%M:%S"));
+    let _ = writeln!(xml, "      <NbOfTxs>{}</NbOfTxs>", lines.len());
+    let _ = writeln!(xml, "      <CtrlSum>{}</CtrlSum>", control_sum);
+    let _ = writeln!(xml, "      <InitgPty><Nm>{}</Nm></InitgPty>", xml_escape(&debtor.name));
+    let _ = writeln!(xml, "    </GrpHdr>");
+    let _ = writeln!(xml, "    <PmtInf>");
+    let _ = writeln!(xml, "      <PmtInfId>{}</PmtInfId>", xml_escape(reference));
+    let _ = writeln!(xml, "      <PmtMtd>TRF</PmtMtd>");
+    let _ = writeln!(xml, "      <NbOfTxs>{}</NbOfTxs>", lines.len());
+    let _ = writeln!(xml, "      <CtrlSum>{}</CtrlSum>", control_sum);
+    let _ = writeln!(xml, "      <PmtTpInf><CtgyPurp><Cd>{}</Cd></CtgyPurp></PmtTpInf>", purpose);
+    let _ = writeln!(xml, "      <ReqdExctnDt>{}</ReqdExctnDt>", execution_date);
+    let _ = writeln!(xml, "      <Dbtr><Nm>{}</Nm></Dbtr>", xml_escape(&debtor.name));
+    let _ = writeln!(xml, "      <DbtrAcct>{}</DbtrAcct>", account_id(&debtor.account));
+    let _ = writeln!(xml, "      <DbtrAgt>{}</DbtrAgt>", agent(debtor.bic.as_deref()));
+    for (index, line) in lines.iter().enumerate() {
+        let _ = writeln!(xml, "      <CdtTrfTxInf>");
+        let _ = writeln!(xml, "        <PmtId><EndToEndId>{}-{}</EndToEndId></PmtId>", xml_escape(reference), index + 1);
+        let _ = writeln!(
+            xml,
+            r#"        <Amt><InstdAmt Ccy="{}">{}</InstdAmt></Amt>"#,
+            xml_escape(&debtor.currency),
+            currency.format_plain(line.amount as i64)
+        );
+        if line.bic.is_some() {
+            let _ = writeln!(xml, "        <CdtrAgt>{}</CdtrAgt>", agent(line.bic.as_deref()));
+        }
+        let _ = writeln!(xml, "        <Cdtr><Nm>{}</Nm></Cdtr>", xml_escape(&line.payee_name));
+        let _ = writeln!(xml, "        <CdtrAcct>{}</CdtrAcct>", account_id(&line.account_number));
+        let _ = writeln!(xml, "        <RmtInf><Ustrd>{}</Ustrd></RmtInf>", xml_escape(&line.remittance));
+        let _ = writeln!(xml, "      </CdtTrfTxInf>");
+    }
+    let _ = writeln!(xml, "    </PmtInf>");
+    let _ = writeln!(xml, "  </CstmrCdtTrfInitn>");
+    let _ = writeln!(xml, "</Document>");
+    xml
+}
+
+/// IBANs start with a country code and two check digits; anything else is
+/// sent as a domestic account number
+fn account_id(account: &str) -> String {
+    let bytes = account.as_bytes();
+    let is_iban = bytes.len() >= 15
+        && bytes[..2].iter().all(u8::is_ascii_uppercase)
+        && bytes[2..4].iter().all(u8::is_ascii_digit);
+    if is_iban {
+        format!("<Id><IBAN>{}</IBAN></Id>", account)
+    } else {
+        format!("<Id><Othr><Id>{}</Id></Othr></Id>", xml_escape(account))
+    }
+}
+
+fn agent(bic: Option<&str>) -> String {
+    match bic {
+        Some(bic) => format!("<FinInstnId><BIC>{}</BIC></FinInstnId>", xml_escape(bic)),
+        None => "<FinInstnId><Othr><Id>NOTPROVIDED</Id></Othr></FinInstnId>".to_string(),
+    }
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn debtor() -> PaymentDebtor {
+        PaymentDebtor {
+            name: "Acme & Co".to_string(),
+            account: "DE89370400440532013000".to_string(),
+            bic: None,
+            currency: "EUR".to_string(),
+        }
+    }
+
+    fn line(amount: i32, bic: Option<&str>) -> PaymentLine {
+        PaymentLine {
+            payroll_id: Some(1),
+            invoice_id: None,
+            payee_name: "Kim, Minji".to_string(),
+            account_number: "110-234-567890".to_string(),
+            bic: bic.map(|b| b.to_string()),
+            amount,
+            remittance: "Salary 2025-01".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render_csv() {
+        let date = NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+        let csv = render_csv("PAYROLL-1", date, &debtor(), &[line(2_500_000, Some("SHBKKRSE"))]);
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].starts_with("batch_reference,execution_date"));
+        assert_eq!(
+            rows[1],
+            "PAYROLL-1,2025-01-31,DE89370400440532013000,\"Kim, Minji\",110-234-567890,SHBKKRSE,2500000,EUR,Salary 2025-01"
+        );
+    }
+
+    #[test]
+    fn test_render_pain001() {
+        let created = NaiveDate::from_ymd_opt(2025, 1, 30).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+        let xml = render_pain001(
+            "PAYROLL-1",
+            created,
+            date,
+            PaymentBatchKind::Payroll,
+            &debtor(),
+            &[line(1_000, None), line(2_000, Some("SHBKKRSE"))],
+        );
+
+        assert!(xml.contains("<NbOfTxs>2</NbOfTxs>"));
+        assert!(xml.contains("<CtrlSum>3000</CtrlSum>"));
+        assert!(xml.contains("<Cd>SALA</Cd>"));
+        assert!(xml.contains("<Dbtr><Nm>Acme &amp; Co</Nm></Dbtr>"));
+        assert!(xml.contains("<DbtrAcct><Id><IBAN>DE89370400440532013000</IBAN></Id></DbtrAcct>"));
+        assert!(xml.contains("<CdtrAcct><Id><Othr><Id>110-234-567890</Id></Othr></Id></CdtrAcct>"));
+        assert!(xml.contains("<EndToEndId>PAYROLL-1-2</EndToEndId>"));
+        assert_eq!(xml.matches("<CdtrAgt>").count(), 1);
+        assert_eq!(xml.matches("<CdtTrfTxInf>").count(), 2);
+    }
+}
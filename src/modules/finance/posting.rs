@@ -0,0 +1,234 @@
+use std::cell::RefCell;
+
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use once_cell::sync::OnceCell;
+
+use super::journal::{JournalEntryRequest, JournalEntryWithLines, JournalLine, JournalService};
+use crate::core::config::PostingConfig;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{accounts, journal_entries};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+static POSTING_RULES: OnceCell<Option<PostingRules>> = OnceCell::new();
+
+thread_local! {
+    /// Rules used on this thread instead of the configured ones, see `PostingRules::scope`
+    static SCOPED_RULES: RefCell<Option<Option<PostingRules>>> = const { RefCell::new(None) };
+}
+
+/// Restores the previous rules of the thread when dropped
+pub struct PostingScope {
+    previous: Option<Option<PostingRules>>,
+}
+
+impl Drop for PostingScope {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        SCOPED_RULES.with(|scoped| *scoped.borrow_mut() = previous);
+    }
+}
+
+/// `transactions.reference` of the entry posted for a purchase order receipt
+pub fn receipt_reference(po_number: &str) -> String {
+    format!("receipt:{}", po_number)
+}
+
+/// `transactions.reference` of the entry posted for a stock issue
+pub fn issue_reference(movement_id: i32) -> String {
+    format!("issue:{}", movement_id)
+}
+
+/// Value of `quantity` units at `unit_cost`, as a ledger amount
+pub fn stock_value(quantity: i32, unit_cost: i32) -> Result<i32> {
+    i32::try_from(quantity.unsigned_abs() as i64 * unit_cost as i64).map_err(|_| {
+        CLIERPError::Validation(format!(
+            "{} units at {} is too large to post to the ledger",
+            quantity, unit_cost
+        ))
+    })
+}
+
+/// Accounts stock events are posted to, from configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostingRules {
+    pub inventory: String,
+    pub payable: String,
+    pub cogs: String,
+}
+
+impl PostingRules {
+    /// The rules, or `None` while automatic posting is switched off
+    pub fn from_config(config: &PostingConfig) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        match (&config.inventory_account, &config.payable_account, &config.cogs_account) {
+            (Some(inventory), Some(payable), Some(cogs)) => Ok(Some(Self {
+                inventory: inventory.clone(),
+                payable: payable.clone(),
+                cogs: cogs.clone(),
+            })),
+            _ => Err(CLIERPError::Configuration(config::ConfigError::Message(
+                "Set posting.inventory_account, posting.payable_account and posting.cogs_account (CLIERP_POSTING__INVENTORY_ACCOUNT, CLIERP_POSTING__PAYABLE_ACCOUNT, CLIERP_POSTING__COGS_ACCOUNT) to post stock events automatically".to_string(),
+            ))),
+        }
+    }
+
+    /// Install the rules from configuration for the rest of the process
+    pub fn initialize(config: &PostingConfig) -> Result<()> {
+        let rules = Self::from_config(config)?;
+        let _ = POSTING_RULES.set(rules);
+        Ok(())
+    }
+
+    /// The configured rules; none if not initialized or switched off
+    pub fn current() -> Option<PostingRules> {
+        if let Some(rules) = SCOPED_RULES.with(|scoped| scoped.borrow().clone()) {
+            return rules;
+        }
+        POSTING_RULES.get().cloned().flatten()
+    }
+
+    /// Post under `rules` (`None` for no posting) on this thread until the
+    /// returned scope is dropped, whatever is configured
+    pub fn scope(rules: Option<PostingRules>) -> PostingScope {
+        let previous = SCOPED_RULES.with(|scoped| scoped.borrow_mut().replace(rules));
+        PostingScope { previous }
+    }
+}
+
+/// Posts the ledger side of stock events under the configured rules:
+///
+/// - receiving a purchase order debits inventory and credits accounts
+///   payable with the received quantity at the order's unit cost;
+/// - issuing stock debits cost of goods sold and credits inventory at the
+///   product's cost, and reversing the issue reverses that entry.
+///
+/// Callers post inside the transaction that records the stock movement, so
+/// the movement and its entry are saved together. With posting switched
+/// off nothing is posted.
+pub struct PostingService;
+
+impl PostingService {
+    pub fn post_receipt(
+        conn: &mut SqliteConnection,
+        po_number: &str,
+        amount: i32,
+        date: NaiveDate,
+        created_by: Option<i32>,
+    ) -> Result<Option<JournalEntryWithLines>> {
+        let Some(rules) = PostingRules::current() else {
+            return Ok(None);
+        };
+        let inventory_id = account_id(conn, &rules.inventory)?;
+        let payable_id = account_id(conn, &rules.payable)?;
+        Self::post(
+            conn,
+            JournalEntryRequest {
+                entry_date: date,
+                description: format!("Goods received, PO {}", po_number),
+                reference: Some(receipt_reference(po_number)),
+                lines: vec![JournalLine::debit(inventory_id, amount), JournalLine::credit(payable_id, amount)],
+            },
+            created_by,
+        )
+    }
+
+    pub fn post_issue(
+        conn: &mut SqliteConnection,
+        movement_id: i32,
+        description: &str,
+        amount: i32,
+        date: NaiveDate,
+        created_by: Option<i32>,
+    ) -> Result<Option<JournalEntryWithLines>> {
+        let Some(rules) = PostingRules::current() else {
+            return Ok(None);
+        };
+        let cogs_id = account_id(conn, &rules.cogs)?;
+        let inventory_id = account_id(conn, &rules.inventory)?;
+        Self::post(
+            conn,
+            JournalEntryRequest {
+                entry_date: date,
+                description: format!("Cost of goods issued, {}", description),
+                reference: Some(issue_reference(movement_id)),
+                lines: vec![JournalLine::debit(cogs_id, amount), JournalLine::credit(inventory_id, amount)],
+            },
+            created_by,
+        )
+    }
+
+    /// Reverse the entry posted for the stock issue `movement_id`, returning
+    /// its cost to inventory. Issues posted nothing for, say while posting
+    /// was off, have nothing to reverse.
+    pub fn reverse_issue(
+        conn: &mut SqliteConnection,
+        movement_id: i32,
+        reason: &str,
+        date: NaiveDate,
+        created_by: Option<i32>,
+    ) -> Result<Option<JournalEntryWithLines>> {
+        let entry_id = journal_entries::table
+            .filter(journal_entries::reference.eq(issue_reference(movement_id)))
+            .filter(journal_entries::reverses_entry_id.is_null())
+            .select(journal_entries::id)
+            .first::<i32>(conn)
+            .optional()?;
+        match entry_id {
+            Some(entry_id) => JournalService::reverse_entry(conn, entry_id, date, reason, created_by).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Stock moved at no cost has nothing to post
+    fn post(
+        conn: &mut SqliteConnection,
+        request: JournalEntryRequest,
+        created_by: Option<i32>,
+    ) -> Result<Option<JournalEntryWithLines>> {
+        if request.lines.iter().all(|line| line.amount == 0) {
+            return Ok(None);
+        }
+        JournalService::post_entry(conn, request, created_by).map(Some)
+    }
+}
+
+fn account_id(conn: &mut SqliteConnection, code: &str) -> Result<i32> {
+    accounts::table
+        .filter(accounts::account_code.eq(code))
+        .select(accounts::id)
+        .first::<i32>(conn)
+        .optional()?
+        .ok_or_else(|| CLIERPError::NotFound(format!("Posting account '{}' not found", code)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_posting_rules_from_config() {
+        let mut config = PostingConfig {
+            enabled: false,
+            inventory_account: Some("1300".to_string()),
+            payable_account: None,
+            cogs_account: Some("5000".to_string()),
+        };
+        assert_eq!(PostingRules::from_config(&config).unwrap(), None);
+
+        config.enabled = true;
+        assert!(PostingRules::from_config(&config).is_err());
+
+        config.payable_account = Some("2100".to_string());
+        let rules = PostingRules::from_config(&config).unwrap().unwrap();
+        assert_eq!(rules.payable, "2100");
+
+        assert_eq!(stock_value(-4, 2500).unwrap(), 10_000);
+        assert!(stock_value(100_000, 100_000).is_err());
+    }
+}
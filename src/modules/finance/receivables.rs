@@ -0,0 +1,415 @@
+use chrono::{NaiveDate, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::models::{
+    DunningCharge, Invoice, InvoiceKind, InvoicePayment, InvoiceStatus, NewInvoicePayment,
+};
+use crate::database::schema::{dunning_charges, invoice_payments, invoices};
+use crate::utils::cache::QueryCache;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// One line of a customer statement
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StatementLine {
+    pub date: NaiveDate,
+    pub description: String,
+    /// Amount invoiced
+    pub debit: i32,
+    /// Amount paid
+    pub credit: i32,
+    /// Amount owed after this line
+    pub balance: i32,
+}
+
+/// Invoices and payments of a customer over a period, with balances
+#[derive(Debug, Clone, Serialize)]
+pub struct Statement {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub opening_balance: i32,
+    pub lines: Vec<StatementLine>,
+    pub closing_balance: i32,
+}
+
+/// Receivable invoices of customers and the payments applied to them
+pub struct ReceivableService;
+
+impl ReceivableService {
+    /// Apply a customer payment (by default the whole open amount) to an
+    /// open receivable invoice, marking it paid once nothing is left open
+    pub fn record_payment(
+        conn: &mut SqliteConnection,
+        invoice_id: i32,
+        amount: Option<i32>,
+        paid_on: NaiveDate,
+        reference: Option<&str>,
+        created_by: Option<i32>,
+    ) -> Result<(Invoice, InvoicePayment)> {
+        let invoice = invoices::table
+            .find(invoice_id)
+            .first::<Invoice>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Invoice {} not found", invoice_id)))?;
+        if invoice.kind != InvoiceKind::Receivable.to_string() {
+            return Err(CLIERPError::Validation(format!(
+                "Invoice {} is a supplier invoice; supplier payments go through payment batches",
+                invoice.invoice_number
+            )));
+        }
+        if invoice.status != InvoiceStatus::Open.to_string() {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Invoice {} is {}",
+                invoice.invoice_number, invoice.status
+            )));
+        }
+        let amount = amount.unwrap_or(invoice.open_amount());
+        if amount <= 0 {
+            return Err(CLIERPError::Validation("Payment amount must be positive".to_string()));
+        }
+        if amount > invoice.open_amount() {
+            return Err(CLIERPError::Validation(format!(
+                "Payment of {} exceeds the open amount {} of invoice {}",
+                amount,
+                invoice.open_amount(),
+                invoice.invoice_number
+            )));
+        }
+
+        let paid_amount = invoice.paid_amount + amount;
+        let status = if paid_amount == invoice.total_amount { InvoiceStatus::Paid } else { InvoiceStatus::Open };
+        let payment = conn.transaction::<_, CLIERPError, _>(|conn| {
+            diesel::insert_into(invoice_payments::table)
+                .values(&NewInvoicePayment {
+                    invoice_id,
+                    amount,
+                    paid_on,
+                    reference: reference.map(str::trim).filter(|r| !r.is_empty()).map(str::to_string),
+                    created_by,
+                })
+                .execute(conn)?;
+            diesel::update(invoices::table.find(invoice_id))
+                .set((
+                    invoices::paid_amount.eq(paid_amount),
+                    invoices::status.eq(status.to_string()),
+                    invoices::updated_at.eq(Utc::now().naive_utc()),
+                ))
+                .execute(conn)?;
+            Ok(invoice_payments::table
+                .filter(invoice_payments::invoice_id.eq(invoice_id))
+                .order(invoice_payments::id.desc())
+                .first::<InvoicePayment>(conn)?)
+        })?;
+        QueryCache::current().invalidate(&["finance"]);
+
+        tracing::info!("Recorded payment of {} on invoice {}", amount, invoice.invoice_number);
+        Ok((invoices::table.find(invoice_id).first::<Invoice>(conn)?, payment))
+    }
+
+    /// Record that the bank returned a payment (a bounced cheque or reversed
+    /// transfer), reopening its invoice for the amount
+    pub fn return_payment(
+        conn: &mut SqliteConnection,
+        payment_id: i32,
+        returned_on: NaiveDate,
+        reason: Option<&str>,
+    ) -> Result<(Invoice, InvoicePayment)> {
+        let payment = invoice_payments::table
+            .find(payment_id)
+            .first::<InvoicePayment>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Payment {} not found", payment_id)))?;
+        if let Some(returned_on) = payment.returned_on {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Payment {} was already returned on {}",
+                payment_id, returned_on
+            )));
+        }
+        if returned_on < payment.paid_on {
+            return Err(CLIERPError::Validation(format!(
+                "Payment {} was received on {}; it cannot be returned before",
+                payment_id, payment.paid_on
+            )));
+        }
+        let invoice = invoices::table.find(payment.invoice_id).first::<Invoice>(conn)?;
+        if invoice.kind != InvoiceKind::Receivable.to_string() {
+            return Err(CLIERPError::Validation(format!(
+                "Invoice {} is a supplier invoice",
+                invoice.invoice_number
+            )));
+        }
+        if invoice.status == InvoiceStatus::Cancelled.to_string() {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Invoice {} is cancelled",
+                invoice.invoice_number
+            )));
+        }
+
+        conn.transaction::<_, CLIERPError, _>(|conn| {
+            diesel::update(invoice_payments::table.find(payment_id))
+                .set((
+                    invoice_payments::returned_on.eq(Some(returned_on)),
+                    invoice_payments::return_reason
+                        .eq(reason.map(str::trim).filter(|r| !r.is_empty()).map(str::to_string)),
+                ))
+                .execute(conn)?;
+            diesel::update(invoices::table.find(invoice.id))
+                .set((
+                    invoices::paid_amount.eq(invoice.paid_amount - payment.amount),
+                    invoices::status.eq(InvoiceStatus::Open.to_string()),
+                    invoices::updated_at.eq(Utc::now().naive_utc()),
+                ))
+                .execute(conn)?;
+            Ok(())
+        })?;
+        QueryCache::current().invalidate(&["finance"]);
+
+        tracing::info!("Payment {} on invoice {} returned", payment_id, invoice.invoice_number);
+        Ok((
+            invoices::table.find(invoice.id).first::<Invoice>(conn)?,
+            invoice_payments::table.find(payment_id).first::<InvoicePayment>(conn)?,
+        ))
+    }
+
+    /// Open receivable invoices of a customer, earliest due first
+    pub fn open_invoices(conn: &mut SqliteConnection, customer_id: i32) -> Result<Vec<Invoice>> {
+        Ok(Self::customer_invoices(conn, customer_id)?
+            .into_iter()
+            .filter(|i| i.status == InvoiceStatus::Open.to_string())
+            .collect())
+    }
+
+    /// Payments of a customer with the invoice each was applied to, newest first
+    pub fn payments(conn: &mut SqliteConnection, customer_id: i32) -> Result<Vec<(InvoicePayment, Invoice)>> {
+        Ok(invoice_payments::table
+            .inner_join(invoices::table)
+            .filter(invoices::customer_id.eq(customer_id))
+            .filter(invoices::kind.eq(InvoiceKind::Receivable.to_string()))
+            .order((invoice_payments::paid_on.desc(), invoice_payments::id.desc()))
+            .load::<(InvoicePayment, Invoice)>(conn)?)
+    }
+
+    /// A receivable invoice of the customer by number, with its payments
+    pub fn customer_invoice(
+        conn: &mut SqliteConnection,
+        customer_id: i32,
+        invoice_number: &str,
+    ) -> Result<(Invoice, Vec<InvoicePayment>)> {
+        let invoice = invoices::table
+            .filter(invoices::customer_id.eq(customer_id))
+            .filter(invoices::kind.eq(InvoiceKind::Receivable.to_string()))
+            .filter(invoices::invoice_number.eq(invoice_number))
+            .filter(invoices::status.ne(InvoiceStatus::Draft.to_string()))
+            .first::<Invoice>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Invoice {} not found", invoice_number)))?;
+        let payments = invoice_payments::table
+            .filter(invoice_payments::invoice_id.eq(invoice.id))
+            .order(invoice_payments::paid_on.asc())
+            .load::<InvoicePayment>(conn)?;
+        Ok((invoice, payments))
+    }
+
+    /// Statement of a customer's account from `from` to `to`
+    pub fn statement(conn: &mut SqliteConnection, customer_id: i32, from: NaiveDate, to: NaiveDate) -> Result<Statement> {
+        let invoices = Self::customer_invoices(conn, customer_id)?;
+        let payments: Vec<(InvoicePayment, Invoice)> = Self::payments(conn, customer_id)?
+            .into_iter()
+            .filter(|(_, invoice)| invoice.status != InvoiceStatus::Cancelled.to_string())
+            .collect();
+        let ids: Vec<i32> = invoices.iter().map(|i| i.id).collect();
+        let charges = dunning_charges::table
+            .filter(dunning_charges::invoice_id.eq_any(ids))
+            .load::<DunningCharge>(conn)?;
+        Ok(build_statement(&invoices, &payments, &charges, from, to))
+    }
+
+    /// Receivable invoices of a customer that were issued (not drafts or
+    /// cancelled), by due date
+    fn customer_invoices(conn: &mut SqliteConnection, customer_id: i32) -> Result<Vec<Invoice>> {
+        Ok(invoices::table
+            .filter(invoices::customer_id.eq(customer_id))
+            .filter(invoices::kind.eq(InvoiceKind::Receivable.to_string()))
+            .filter(invoices::status.ne_all(vec![
+                InvoiceStatus::Draft.to_string(),
+                InvoiceStatus::Cancelled.to_string(),
+            ]))
+            .order((invoices::due_date.asc(), invoices::invoice_date.asc(), invoices::id.asc()))
+            .load::<Invoice>(conn)?)
+    }
+}
+
+/// Statement lines in date order, invoices before charges before payments
+/// on the same day. Late fees and interest, which are included in the
+/// invoice totals, get lines of their own on the day they were charged,
+/// and a returned payment is charged back on the day it was returned.
+fn build_statement(
+    invoices: &[Invoice],
+    payments: &[(InvoicePayment, Invoice)],
+    charges: &[DunningCharge],
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Statement {
+    let mut entries: Vec<(NaiveDate, u8, String, i32, i32)> = Vec::new();
+    let mut opening_balance = 0;
+    for invoice in invoices.iter().filter(|i| i.invoice_date <= to) {
+        let charged: i32 = charges.iter().filter(|c| c.invoice_id == invoice.id).map(|c| c.amount).sum();
+        let amount = invoice.total_amount - charged;
+        if invoice.invoice_date < from {
+            opening_balance += amount;
+        } else {
+            entries.push((invoice.invoice_date, 0, format!("Invoice {}", invoice.invoice_number), amount, 0));
+        }
+        for charge in charges.iter().filter(|c| c.invoice_id == invoice.id && c.accrued_to <= to) {
+            if charge.accrued_to < from {
+                opening_balance += charge.amount;
+            } else {
+                let kind = if charge.kind == "fee" { "Late fee" } else { "Late interest" };
+                entries.push((charge.accrued_to, 1, format!("{}, invoice {}", kind, invoice.invoice_number), charge.amount, 0));
+            }
+        }
+    }
+    for (payment, invoice) in payments.iter().filter(|(p, _)| p.paid_on <= to) {
+        if payment.paid_on < from {
+            opening_balance -= payment.amount;
+        } else {
+            let mut description = format!("Payment, invoice {}", invoice.invoice_number);
+            if let Some(reference) = &payment.reference {
+                description.push_str(&format!(" ({})", reference));
+            }
+            entries.push((payment.paid_on, 2, description, 0, payment.amount));
+        }
+        match payment.returned_on {
+            Some(returned_on) if returned_on < from => opening_balance += payment.amount,
+            Some(returned_on) if returned_on <= to => entries.push((
+                returned_on,
+                3,
+                format!("Returned payment, invoice {}", invoice.invoice_number),
+                payment.amount,
+                0,
+            )),
+            _ => {}
+        }
+    }
+    entries.sort_by_key(|entry| (entry.0, entry.1));
+
+    let mut balance = opening_balance;
+    let lines = entries
+        .into_iter()
+        .map(|(date, _, description, debit, credit)| {
+            balance += debit - credit;
+            StatementLine { date, description, debit, credit, balance }
+        })
+        .collect();
+    Statement { from, to, opening_balance, lines, closing_balance: balance }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invoice(id: i32, number: &str, date: &str, total: i32) -> Invoice {
+        let now = Utc::now().naive_utc();
+        Invoice {
+            id,
+            invoice_number: number.to_string(),
+            kind: "receivable".to_string(),
+            customer_id: Some(1),
+            supplier_id: None,
+            invoice_date: date.parse().unwrap(),
+            due_date: None,
+            total_amount: total,
+            paid_amount: 0,
+            status: "open".to_string(),
+            description: None,
+            created_by: None,
+            created_at: now,
+            updated_at: now,
+            po_id: None,
+            sent_at: None,
+        }
+    }
+
+    fn payment(invoice: &Invoice, date: &str, amount: i32) -> (InvoicePayment, Invoice) {
+        let payment = InvoicePayment {
+            id: 1,
+            invoice_id: invoice.id,
+            amount,
+            paid_on: date.parse().unwrap(),
+            reference: Some("BANK-1".to_string()),
+            created_by: None,
+            created_at: Utc::now().naive_utc(),
+            returned_on: None,
+            return_reason: None,
+        };
+        (payment, invoice.clone())
+    }
+
+    #[test]
+    fn test_statement_balances() {
+        let old = invoice(1, "INV-1", "2024-01-10", 1000);
+        let new = invoice(2, "INV-2", "2024-02-05", 500);
+        let later = invoice(3, "INV-3", "2024-04-01", 700);
+        let payments = vec![payment(&old, "2024-01-20", 400), payment(&old, "2024-02-05", 600)];
+
+        let statement = build_statement(
+            &[old.clone(), new, later],
+            &payments,
+            &[],
+            "2024-02-01".parse().unwrap(),
+            "2024-02-29".parse().unwrap(),
+        );
+        assert_eq!(statement.opening_balance, 600);
+        assert_eq!(statement.lines.len(), 2);
+        assert_eq!(statement.lines[0].description, "Invoice INV-2");
+        assert_eq!(statement.lines[0].balance, 1100);
+        assert_eq!(statement.lines[1].description, "Payment, invoice INV-1 (BANK-1)");
+        assert_eq!(statement.lines[1].credit, 600);
+        assert_eq!(statement.closing_balance, 500);
+
+        // A late fee included in the invoice total is shown when it was charged
+        let mut charged = old;
+        charged.total_amount += 50;
+        let fee = DunningCharge {
+            id: 1,
+            invoice_id: charged.id,
+            kind: "fee".to_string(),
+            amount: 50,
+            days_overdue: 20,
+            accrued_from: None,
+            accrued_to: "2024-02-10".parse().unwrap(),
+            created_by: None,
+            created_at: Utc::now().naive_utc(),
+        };
+        let statement = build_statement(
+            &[charged],
+            &payments,
+            &[fee],
+            "2024-02-01".parse().unwrap(),
+            "2024-02-29".parse().unwrap(),
+        );
+        assert_eq!(statement.opening_balance, 600);
+        assert_eq!(statement.lines[0].description, "Payment, invoice INV-1 (BANK-1)");
+        assert_eq!(statement.lines[1].description, "Late fee, invoice INV-1");
+        assert_eq!(statement.closing_balance, 50);
+
+        // A payment returned after the period still counts within it
+        let mut payments = payments;
+        payments[1].0.returned_on = Some("2024-03-04".parse().unwrap());
+        let statement = build_statement(
+            &[payments[1].1.clone()],
+            &payments,
+            &[],
+            "2024-02-01".parse().unwrap(),
+            "2024-03-31".parse().unwrap(),
+        );
+        assert_eq!(statement.lines.len(), 2);
+        assert_eq!(statement.lines[1].description, "Returned payment, invoice INV-1");
+        assert_eq!(statement.lines[1].debit, 600);
+        assert_eq!(statement.closing_balance, 600);
+    }
+}
@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::core::config::RevenueRecognitionConfig;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::models::{
+    Invoice, InvoiceKind, InvoiceStatus, NewRevenueRecognition, NewRevenueSchedule, RevenueRecognition,
+    RevenueSchedule,
+};
+use crate::database::schema::{accounts, invoices, revenue_recognitions, revenue_schedules};
+use crate::modules::finance::{JournalEntryRequest, JournalLine, JournalService};
+use crate::utils::cache::QueryCache;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// `transactions.reference` of the postings of a revenue schedule
+pub fn recognition_reference(schedule_id: i32) -> String {
+    format!("revrec:{}", schedule_id)
+}
+
+/// Accounts a new schedule defers to and recognizes into, from configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecognitionAccounts {
+    pub deferred: String,
+    pub revenue: String,
+}
+
+impl RecognitionAccounts {
+    pub fn from_config(config: &RevenueRecognitionConfig) -> Result<Self> {
+        match (&config.deferred_account, &config.revenue_account) {
+            (Some(deferred), Some(revenue)) => Ok(Self {
+                deferred: deferred.clone(),
+                revenue: revenue.clone(),
+            }),
+            _ => Err(CLIERPError::Configuration(config::ConfigError::Message(
+                "Set revenue_recognition.deferred_account and revenue_recognition.revenue_account (CLIERP_REVENUE_RECOGNITION__DEFERRED_ACCOUNT, CLIERP_REVENUE_RECOGNITION__REVENUE_ACCOUNT) to defer revenue".to_string(),
+            ))),
+        }
+    }
+}
+
+/// One month of a schedule that is due to be recognized
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PendingRecognition {
+    pub schedule_id: i32,
+    pub invoice_number: String,
+    pub period: String,
+    pub amount: i32,
+    /// Last day of the month, the posting date
+    pub recognize_on: NaiveDate,
+}
+
+/// Recognized and still deferred revenue of a schedule as of a date
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleBalance {
+    pub schedule: RevenueSchedule,
+    pub invoice: Invoice,
+    pub recognized: i32,
+    pub deferred: i32,
+    /// Last month of the schedule
+    pub end_period: String,
+}
+
+/// Revenue recognition schedules for annual licenses and service contracts.
+///
+/// Deferring an invoice moves its revenue to the deferred revenue account;
+/// each month that has ended is then recognized back into revenue, in
+/// equal parts with any rounding remainder in the last month. Recognizing
+/// is safe to repeat, e.g. monthly from cron: months already posted are
+/// skipped.
+pub struct RecognitionService;
+
+impl RecognitionService {
+    /// Defer the revenue of a receivable invoice (by default its whole total)
+    /// over `months` months starting with the month of `start_date`
+    #[allow(clippy::too_many_arguments)]
+    pub fn defer(
+        conn: &mut SqliteConnection,
+        accounts: &RecognitionAccounts,
+        invoice_id: i32,
+        start_date: Option<NaiveDate>,
+        months: i32,
+        amount: Option<i32>,
+        description: Option<&str>,
+        created_by: Option<i32>,
+    ) -> Result<RevenueSchedule> {
+        let invoice = invoices::table
+            .find(invoice_id)
+            .first::<Invoice>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Invoice {} not found", invoice_id)))?;
+        if invoice.kind != InvoiceKind::Receivable.to_string() {
+            return Err(CLIERPError::Validation(format!(
+                "Invoice {} is a supplier invoice; only customer revenue is deferred",
+                invoice.invoice_number
+            )));
+        }
+        if invoice.status == InvoiceStatus::Draft.to_string() || invoice.status == InvoiceStatus::Cancelled.to_string() {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Invoice {} is {}",
+                invoice.invoice_number, invoice.status
+            )));
+        }
+        if !(1..=120).contains(&months) {
+            return Err(CLIERPError::Validation("A schedule runs for 1 to 120 months".to_string()));
+        }
+        let amount = amount.unwrap_or(invoice.total_amount);
+        if amount <= 0 || amount > invoice.total_amount {
+            return Err(CLIERPError::Validation(format!(
+                "The deferred amount must be between 1 and the invoice total {}",
+                invoice.total_amount
+            )));
+        }
+        let existing = revenue_schedules::table
+            .filter(revenue_schedules::invoice_id.eq(invoice_id))
+            .count()
+            .get_result::<i64>(conn)?;
+        if existing > 0 {
+            return Err(CLIERPError::AlreadyExists(format!(
+                "Invoice {} already has a revenue schedule",
+                invoice.invoice_number
+            )));
+        }
+        let deferred_id = account_id(conn, &accounts.deferred)?;
+        let revenue_id = account_id(conn, &accounts.revenue)?;
+
+        let schedule = conn.transaction::<_, CLIERPError, _>(|conn| {
+            diesel::insert_into(revenue_schedules::table)
+                .values(&NewRevenueSchedule {
+                    invoice_id,
+                    description: description.map(str::trim).filter(|d| !d.is_empty()).map(str::to_string),
+                    total_amount: amount,
+                    start_date: start_date.unwrap_or(invoice.invoice_date),
+                    months,
+                    deferred_account: accounts.deferred.clone(),
+                    revenue_account: accounts.revenue.clone(),
+                    created_by,
+                })
+                .execute(conn)?;
+            let schedule = revenue_schedules::table
+                .filter(revenue_schedules::invoice_id.eq(invoice_id))
+                .first::<RevenueSchedule>(conn)?;
+
+            JournalService::post_entry(
+                conn,
+                JournalEntryRequest {
+                    entry_date: invoice.invoice_date,
+                    description: format!("Deferred revenue, invoice {}", invoice.invoice_number),
+                    reference: Some(recognition_reference(schedule.id)),
+                    lines: vec![JournalLine::debit(revenue_id, amount), JournalLine::credit(deferred_id, amount)],
+                },
+                created_by,
+            )?;
+            Ok(schedule)
+        })?;
+        QueryCache::current().invalidate(&["finance"]);
+
+        tracing::info!(
+            "Deferred {} of invoice {} over {} months",
+            amount,
+            invoice.invoice_number,
+            months
+        );
+        Ok(schedule)
+    }
+
+    /// Months that have ended by `as_of` and are not yet recognized, on one
+    /// schedule or all of them
+    pub fn pending(
+        conn: &mut SqliteConnection,
+        as_of: NaiveDate,
+        schedule_id: Option<i32>,
+    ) -> Result<Vec<PendingRecognition>> {
+        let mut query = revenue_schedules::table.inner_join(invoices::table).into_boxed();
+        if let Some(schedule_id) = schedule_id {
+            query = query.filter(revenue_schedules::id.eq(schedule_id));
+        }
+        let schedules = query
+            .order(revenue_schedules::id.asc())
+            .load::<(RevenueSchedule, Invoice)>(conn)?;
+        let mut done = Self::recognitions(conn, &schedules.iter().map(|(s, _)| s.id).collect::<Vec<_>>())?;
+
+        Ok(schedules
+            .iter()
+            .flat_map(|(schedule, invoice)| {
+                let recognized = done.remove(&schedule.id).unwrap_or_default();
+                due_months(schedule, &recognized, as_of)
+                    .into_iter()
+                    .map(|(period, amount, recognize_on)| PendingRecognition {
+                        schedule_id: schedule.id,
+                        invoice_number: invoice.invoice_number.clone(),
+                        period,
+                        amount,
+                        recognize_on,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect())
+    }
+
+    /// Post pending months from deferred revenue to revenue
+    pub fn recognize(
+        conn: &mut SqliteConnection,
+        pending: &[PendingRecognition],
+        created_by: Option<i32>,
+    ) -> Result<usize> {
+        if pending.is_empty() {
+            return Ok(0);
+        }
+        conn.transaction::<_, CLIERPError, _>(|conn| {
+            for item in pending {
+                let schedule = revenue_schedules::table.find(item.schedule_id).first::<RevenueSchedule>(conn)?;
+                let deferred_id = account_id(conn, &schedule.deferred_account)?;
+                let revenue_id = account_id(conn, &schedule.revenue_account)?;
+                diesel::insert_into(revenue_recognitions::table)
+                    .values(&NewRevenueRecognition {
+                        schedule_id: schedule.id,
+                        period: item.period.clone(),
+                        amount: item.amount,
+                        recognized_on: item.recognize_on,
+                        created_by,
+                    })
+                    .execute(conn)?;
+                JournalService::post_entry(
+                    conn,
+                    JournalEntryRequest {
+                        entry_date: item.recognize_on,
+                        description: format!("Recognized revenue {}, invoice {}", item.period, item.invoice_number),
+                        reference: Some(recognition_reference(schedule.id)),
+                        lines: vec![JournalLine::debit(deferred_id, item.amount), JournalLine::credit(revenue_id, item.amount)],
+                    },
+                    created_by,
+                )?;
+            }
+            Ok(())
+        })?;
+        QueryCache::current().invalidate(&["finance"]);
+
+        tracing::info!("Recognized {} month(s) of deferred revenue", pending.len());
+        Ok(pending.len())
+    }
+
+    /// Deferred revenue balance per schedule as of a date; fully recognized
+    /// schedules are left out unless `include_completed`
+    pub fn balances(
+        conn: &mut SqliteConnection,
+        as_of: NaiveDate,
+        include_completed: bool,
+    ) -> Result<Vec<ScheduleBalance>> {
+        let schedules = revenue_schedules::table
+            .inner_join(invoices::table)
+            .filter(invoices::invoice_date.le(as_of))
+            .order(revenue_schedules::start_date.asc())
+            .load::<(RevenueSchedule, Invoice)>(conn)?;
+        let mut done = Self::recognitions(conn, &schedules.iter().map(|(s, _)| s.id).collect::<Vec<_>>())?;
+
+        Ok(schedules
+            .into_iter()
+            .map(|(schedule, invoice)| {
+                let recognized: i32 = done
+                    .remove(&schedule.id)
+                    .unwrap_or_default()
+                    .iter()
+                    .filter(|r| r.recognized_on <= as_of)
+                    .map(|r| r.amount)
+                    .sum();
+                let end_period = month_label(month_start(schedule.start_date, schedule.months - 1));
+                ScheduleBalance {
+                    deferred: schedule.total_amount - recognized,
+                    recognized,
+                    end_period,
+                    schedule,
+                    invoice,
+                }
+            })
+            .filter(|balance| include_completed || balance.deferred > 0)
+            .collect())
+    }
+
+    /// A schedule with its invoice and the months recognized so far
+    pub fn show(
+        conn: &mut SqliteConnection,
+        schedule_id: i32,
+    ) -> Result<(RevenueSchedule, Invoice, Vec<RevenueRecognition>)> {
+        let (schedule, invoice) = revenue_schedules::table
+            .inner_join(invoices::table)
+            .filter(revenue_schedules::id.eq(schedule_id))
+            .first::<(RevenueSchedule, Invoice)>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Revenue schedule {} not found", schedule_id)))?;
+        let recognitions = revenue_recognitions::table
+            .filter(revenue_recognitions::schedule_id.eq(schedule_id))
+            .order(revenue_recognitions::period.asc())
+            .load::<RevenueRecognition>(conn)?;
+        Ok((schedule, invoice, recognitions))
+    }
+
+    fn recognitions(conn: &mut SqliteConnection, schedule_ids: &[i32]) -> Result<HashMap<i32, Vec<RevenueRecognition>>> {
+        let mut by_schedule: HashMap<i32, Vec<RevenueRecognition>> = HashMap::new();
+        for recognition in revenue_recognitions::table
+            .filter(revenue_recognitions::schedule_id.eq_any(schedule_ids))
+            .load::<RevenueRecognition>(conn)?
+        {
+            by_schedule.entry(recognition.schedule_id).or_default().push(recognition);
+        }
+        Ok(by_schedule)
+    }
+}
+
+fn account_id(conn: &mut SqliteConnection, code: &str) -> Result<i32> {
+    accounts::table
+        .filter(accounts::account_code.eq(code))
+        .select(accounts::id)
+        .first::<i32>(conn)
+        .optional()?
+        .ok_or_else(|| CLIERPError::NotFound(format!("Revenue recognition account '{}' not found", code)))
+}
+
+/// Equal monthly parts of `total`, the rounding remainder in the last
+pub fn monthly_amounts(total: i32, months: i32) -> Vec<i32> {
+    let base = total / months;
+    let mut amounts = vec![base; months as usize];
+    if let Some(last) = amounts.last_mut() {
+        *last += total - base * months;
+    }
+    amounts
+}
+
+/// First day of the month `offset` months after the month of `date`
+fn month_start(date: NaiveDate, offset: i32) -> NaiveDate {
+    let first = date.with_day(1).unwrap_or(date);
+    first + Months::new(offset.max(0) as u32)
+}
+
+fn month_label(date: NaiveDate) -> String {
+    date.format("%Y-%m").to_string()
+}
+
+/// Months of a schedule ended by `as_of` and not yet recognized, as
+/// (period, amount, last day of the month)
+fn due_months(
+    schedule: &RevenueSchedule,
+    recognized: &[RevenueRecognition],
+    as_of: NaiveDate,
+) -> Vec<(String, i32, NaiveDate)> {
+    monthly_amounts(schedule.total_amount, schedule.months)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, amount)| {
+            let start = month_start(schedule.start_date, index as i32);
+            let end = start + Months::new(1) - Duration::days(1);
+            let period = month_label(start);
+            let pending = end <= as_of && amount > 0 && !recognized.iter().any(|r| r.period == period);
+            pending.then_some((period, amount, end))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_monthly_schedule() {
+        assert_eq!(monthly_amounts(1000, 3), vec![333, 333, 334]);
+        assert_eq!(monthly_amounts(1_200_000, 12).iter().sum::<i32>(), 1_200_000);
+
+        let schedule = RevenueSchedule {
+            id: 1,
+            invoice_id: 1,
+            description: None,
+            total_amount: 1000,
+            start_date: "2024-01-15".parse().unwrap(),
+            months: 3,
+            deferred_account: "2400".to_string(),
+            revenue_account: "4000".to_string(),
+            created_by: None,
+            created_at: Utc::now().naive_utc(),
+        };
+        let january = RevenueRecognition {
+            id: 1,
+            schedule_id: 1,
+            period: "2024-01".to_string(),
+            amount: 333,
+            recognized_on: "2024-01-31".parse().unwrap(),
+            created_by: None,
+            created_at: Utc::now().naive_utc(),
+        };
+
+        // February has not ended on the 28th of a leap year
+        let due = due_months(&schedule, std::slice::from_ref(&january), "2024-02-28".parse().unwrap());
+        assert!(due.is_empty());
+        let due = due_months(&schedule, &[january], "2024-03-31".parse().unwrap());
+        assert_eq!(
+            due,
+            vec![
+                ("2024-02".to_string(), 333, "2024-02-29".parse().unwrap()),
+                ("2024-03".to_string(), 334, "2024-03-31".parse().unwrap()),
+            ]
+        );
+    }
+}
@@ -4,10 +4,12 @@ use serde::{Deserialize, Serialize};
 
 use super::account::AccountService;
 use super::transaction::TransactionService;
+use crate::modules::reporting::summaries::SummaryService;
 use crate::core::error::CLIERPError;
 use crate::core::result::CLIERPResult;
 use crate::database::models::Account;
 use crate::database::schema::accounts;
+use crate::utils::fiscal::FiscalCalendar;
 
 pub struct ReportService;
 
@@ -24,7 +26,6 @@ impl ReportService {
         to_date: NaiveDate,
     ) -> CLIERPResult<IncomeStatement> {
         let account_service = AccountService::new();
-        let transaction_service = TransactionService::new();
 
         // Get revenue accounts
         let revenue_accounts = account_service.list_accounts_by_type(conn, "revenue")?;
@@ -32,20 +33,8 @@ impl ReportService {
         let mut total_revenue = 0;
 
         for account in revenue_accounts {
-            let transactions = transaction_service.get_account_transactions(
-                conn,
-                account.id,
-                Some(from_date),
-                Some(to_date),
-            )?;
-            let period_balance = transactions
-                .iter()
-                .map(|t| match t.debit_credit.as_str() {
-                    "credit" => t.amount,
-                    "debit" => -t.amount,
-                    _ => 0,
-                })
-                .sum::<i32>();
+            let (debits, credits) = SummaryService::account_totals(conn, account.id, Some(from_date), Some(to_date))?;
+            let period_balance = (credits - debits) as i32;
 
             if period_balance != 0 {
                 revenue_items.push(IncomeStatementItem {
@@ -63,20 +52,8 @@ impl ReportService {
         let mut total_expenses = 0;
 
         for account in expense_accounts {
-            let transactions = transaction_service.get_account_transactions(
-                conn,
-                account.id,
-                Some(from_date),
-                Some(to_date),
-            )?;
-            let period_balance = transactions
-                .iter()
-                .map(|t| match t.debit_credit.as_str() {
-                    "debit" => t.amount,
-                    "credit" => -t.amount,
-                    _ => 0,
-                })
-                .sum::<i32>();
+            let (debits, credits) = SummaryService::account_totals(conn, account.id, Some(from_date), Some(to_date))?;
+            let period_balance = (debits - credits) as i32;
 
             if period_balance != 0 {
                 expense_items.push(IncomeStatementItem {
@@ -101,6 +78,17 @@ impl ReportService {
         })
     }
 
+    /// Generate Income Statement for a period label (YYYY-MM, FY2025, FY2025-Q2, FY2025-P03)
+    /// resolved against the configured fiscal calendar
+    pub fn generate_income_statement_for_period(
+        &self,
+        conn: &mut SqliteConnection,
+        period: &str,
+    ) -> CLIERPResult<IncomeStatement> {
+        let (from_date, to_date) = FiscalCalendar::current().resolve(period)?;
+        self.generate_income_statement(conn, from_date, to_date)
+    }
+
     /// Generate Balance Sheet
     pub fn generate_balance_sheet(
         &self,
@@ -108,7 +96,6 @@ impl ReportService {
         as_of_date: NaiveDate,
     ) -> CLIERPResult<BalanceSheet> {
         let account_service = AccountService::new();
-        let transaction_service = TransactionService::new();
 
         // Get asset accounts
         let asset_accounts = account_service.list_accounts_by_type(conn, "asset")?;
@@ -116,20 +103,8 @@ impl ReportService {
         let mut total_assets = 0;
 
         for account in asset_accounts {
-            let transactions = transaction_service.get_account_transactions(
-                conn,
-                account.id,
-                None,
-                Some(as_of_date),
-            )?;
-            let balance = transactions
-                .iter()
-                .map(|t| match t.debit_credit.as_str() {
-                    "debit" => t.amount,
-                    "credit" => -t.amount,
-                    _ => 0,
-                })
-                .sum::<i32>();
+            let (debits, credits) = SummaryService::account_totals(conn, account.id, None, Some(as_of_date))?;
+            let balance = (debits - credits) as i32;
 
             if balance != 0 {
                 asset_items.push(BalanceSheetItem {
@@ -147,20 +122,8 @@ impl ReportService {
         let mut total_liabilities = 0;
 
         for account in liability_accounts {
-            let transactions = transaction_service.get_account_transactions(
-                conn,
-                account.id,
-                None,
-                Some(as_of_date),
-            )?;
-            let balance = transactions
-                .iter()
-                .map(|t| match t.debit_credit.as_str() {
-                    "credit" => t.amount,
-                    "debit" => -t.amount,
-                    _ => 0,
-                })
-                .sum::<i32>();
+            let (debits, credits) = SummaryService::account_totals(conn, account.id, None, Some(as_of_date))?;
+            let balance = (credits - debits) as i32;
 
             if balance != 0 {
                 liability_items.push(BalanceSheetItem {
@@ -178,20 +141,8 @@ impl ReportService {
         let mut total_equity = 0;
 
         for account in equity_accounts {
-            let transactions = transaction_service.get_account_transactions(
-                conn,
-                account.id,
-                None,
-                Some(as_of_date),
-            )?;
-            let balance = transactions
-                .iter()
-                .map(|t| match t.debit_credit.as_str() {
-                    "credit" => t.amount,
-                    "debit" => -t.amount,
-                    _ => 0,
-                })
-                .sum::<i32>();
+            let (debits, credits) = SummaryService::account_totals(conn, account.id, None, Some(as_of_date))?;
+            let balance = (credits - debits) as i32;
 
             if balance != 0 {
                 equity_items.push(BalanceSheetItem {
@@ -226,7 +177,6 @@ impl ReportService {
         as_of_date: NaiveDate,
     ) -> CLIERPResult<TrialBalanceReport> {
         let account_service = AccountService::new();
-        let transaction_service = TransactionService::new();
 
         let all_accounts = account_service.list_accounts(conn)?;
         let mut trial_balance_items = Vec::new();
@@ -234,26 +184,8 @@ impl ReportService {
         let mut total_credits = 0;
 
         for account in all_accounts {
-            let transactions = transaction_service.get_account_transactions(
-                conn,
-                account.id,
-                None,
-                Some(as_of_date),
-            )?;
-
-            let debit_balance = transactions
-                .iter()
-                .filter(|t| t.debit_credit == "debit")
-                .map(|t| t.amount)
-                .sum::<i32>();
-
-            let credit_balance = transactions
-                .iter()
-                .filter(|t| t.debit_credit == "credit")
-                .map(|t| t.amount)
-                .sum::<i32>();
-
-            let net_balance = debit_balance - credit_balance;
+            let (debits, credits) = SummaryService::account_totals(conn, account.id, None, Some(as_of_date))?;
+            let net_balance = (debits - credits) as i32;
 
             if net_balance != 0 {
                 let (debit_amount, credit_amount) = if net_balance > 0 {
@@ -1,4 +1,4 @@
-use chrono::{Local, NaiveDate};
+use chrono::NaiveDate;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -7,6 +7,7 @@ use crate::core::error::CLIERPError;
 use crate::core::result::CLIERPResult;
 use crate::database::models::{Account, NewTransaction, Transaction};
 use crate::database::schema::{accounts, transactions};
+use crate::modules::reporting::summaries::SummaryService;
 
 pub struct TransactionService;
 
@@ -15,11 +16,14 @@ impl TransactionService {
         Self
     }
 
-    /// Create a new transaction
-    pub fn create_transaction(
+    /// Post one line of a journal entry and update the account balance.
+    /// Only `JournalService` posts lines, so every line belongs to an entry
+    /// that nets to zero.
+    pub(super) fn post_line(
         &self,
         conn: &mut SqliteConnection,
         request: CreateTransactionRequest,
+        journal_entry_id: i32,
         created_by: Option<i32>,
     ) -> CLIERPResult<Transaction> {
         // Validate account exists
@@ -57,6 +61,7 @@ impl TransactionService {
             description: request.description,
             reference: request.reference,
             created_by,
+            journal_entry_id: Some(journal_entry_id),
         };
 
         diesel::insert_into(transactions::table)
@@ -64,14 +69,14 @@ impl TransactionService {
             .execute(conn)?;
 
         let transaction = transactions::table
+            .filter(transactions::journal_entry_id.eq(journal_entry_id))
             .filter(transactions::account_id.eq(request.account_id))
-            .filter(transactions::transaction_date.eq(request.transaction_date))
-            .filter(transactions::amount.eq(request.amount))
             .order(transactions::id.desc())
             .first::<Transaction>(conn)?;
 
         // Update account balance
         self.update_account_balance(conn, &account, &transaction)?;
+        SummaryService::refresh_account_month(conn, transaction.account_id, transaction.transaction_date)?;
 
         Ok(transaction)
     }
@@ -242,46 +247,6 @@ impl TransactionService {
         Ok(())
     }
 
-    /// Reverse a transaction
-    pub fn reverse_transaction(
-        &self,
-        conn: &mut SqliteConnection,
-        transaction_id: i32,
-        reason: String,
-        created_by: Option<i32>,
-    ) -> CLIERPResult<Transaction> {
-        let original_transaction = transactions::table
-            .find(transaction_id)
-            .first::<Transaction>(conn)
-            .optional()?
-            .ok_or_else(|| CLIERPError::NotFound("Transaction not found".to_string()))?;
-
-        // Create reverse transaction
-        let reverse_debit_credit = match original_transaction.debit_credit.as_str() {
-            "debit" => "credit",
-            "credit" => "debit",
-            _ => {
-                return Err(CLIERPError::ValidationError(
-                    "Invalid original transaction type".to_string(),
-                ))
-            }
-        };
-
-        let reverse_transaction_request = CreateTransactionRequest {
-            account_id: original_transaction.account_id,
-            transaction_date: Local::now().date_naive(),
-            amount: original_transaction.amount,
-            debit_credit: reverse_debit_credit.to_string(),
-            description: format!(
-                "REVERSAL: {} - {}",
-                original_transaction.description, reason
-            ),
-            reference: Some(format!("REV-{}", original_transaction.id)),
-        };
-
-        self.create_transaction(conn, reverse_transaction_request, created_by)
-    }
-
     /// Get transaction summary for a period
     pub fn get_transaction_summary(
         &self,
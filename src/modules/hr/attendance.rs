@@ -1,4 +1,4 @@
-use chrono::{Local, NaiveDate, NaiveTime, Timelike};
+use chrono::{NaiveDate, NaiveTime, Timelike};
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -6,6 +6,8 @@ use crate::core::error::CLIERPError;
 use crate::core::result::CLIERPResult;
 use crate::database::models::{Attendance, Employee, NewAttendance};
 use crate::database::schema::{attendances, employees};
+use crate::utils::cache::QueryCache;
+use crate::utils::timezone::DisplayTimezone;
 
 pub struct AttendanceService;
 
@@ -14,14 +16,18 @@ impl AttendanceService {
         Self
     }
 
-    /// Check in an employee for today
+    /// Check in an employee for today.
+    ///
+    /// The attendance date and check-in/out times are wall-clock values in the
+    /// display timezone, so lateness rules follow the office's working day.
     pub fn check_in(
         &self,
         conn: &mut SqliteConnection,
         employee_id: i32,
     ) -> CLIERPResult<Attendance> {
-        let today = Local::now().date_naive();
-        let now = Local::now().time();
+        let local_now = DisplayTimezone::current().now();
+        let today = local_now.date();
+        let now = local_now.time();
 
         // Check if already checked in today
         let existing_attendance = attendances::table
@@ -50,6 +56,7 @@ impl AttendanceService {
                 .filter(attendances::id.eq(attendance.id))
                 .first::<Attendance>(conn)?;
 
+            QueryCache::current().invalidate(&["hr"]);
             Ok(updated_attendance)
         } else {
             // Create new attendance record
@@ -58,8 +65,8 @@ impl AttendanceService {
                 date: today,
                 check_in: Some(now),
                 check_out: None,
-                break_time: 0,
-                overtime_hours: 0.0,
+                break_time: Some(0),
+                overtime_hours: Some(0.0),
                 status: if now.hour() > 9 {
                     "late".to_string()
                 } else {
@@ -77,6 +84,7 @@ impl AttendanceService {
                 .filter(attendances::date.eq(today))
                 .first::<Attendance>(conn)?;
 
+            QueryCache::current().invalidate(&["hr"]);
             Ok(attendance)
         }
     }
@@ -87,8 +95,9 @@ impl AttendanceService {
         conn: &mut SqliteConnection,
         employee_id: i32,
     ) -> CLIERPResult<Attendance> {
-        let today = Local::now().date_naive();
-        let now = Local::now().time();
+        let local_now = DisplayTimezone::current().now();
+        let today = local_now.date();
+        let now = local_now.time();
 
         let attendance = attendances::table
             .filter(attendances::employee_id.eq(employee_id))
@@ -113,7 +122,7 @@ impl AttendanceService {
 
         // Calculate overtime hours if applicable
         let check_in_time = attendance.check_in.unwrap();
-        let work_hours = Self::calculate_work_hours(check_in_time, now, attendance.break_time);
+        let work_hours = Self::calculate_work_hours(check_in_time, now, attendance.break_time.unwrap_or(0));
         let overtime_hours = if work_hours > 8.0 {
             work_hours - 8.0
         } else {
@@ -137,6 +146,7 @@ impl AttendanceService {
             .filter(attendances::id.eq(attendance.id))
             .first::<Attendance>(conn)?;
 
+        QueryCache::current().invalidate(&["hr"]);
         Ok(updated_attendance)
     }
 
@@ -166,12 +176,20 @@ impl AttendanceService {
         &self,
         conn: &mut SqliteConnection,
     ) -> CLIERPResult<Vec<AttendanceWithEmployee>> {
-        let today = Local::now().date_naive();
+        self.get_attendance_on(conn, DisplayTimezone::current().today())
+    }
 
+    /// Get the attendance of all employees on a date
+    pub fn get_attendance_on(
+        &self,
+        conn: &mut SqliteConnection,
+        date: NaiveDate,
+    ) -> CLIERPResult<Vec<AttendanceWithEmployee>> {
         let results = attendances::table
             .inner_join(employees::table)
-            .filter(attendances::date.eq(today))
+            .filter(attendances::date.eq(date))
             .select((Attendance::as_select(), Employee::as_select()))
+            .order(employees::employee_code.asc())
             .load::<(Attendance, Employee)>(conn)?;
 
         Ok(results
@@ -246,7 +264,7 @@ impl AttendanceService {
             .count() as i32;
         let late_days = attendances.iter().filter(|a| a.status == "late").count() as i32;
         let absent_days = attendances.iter().filter(|a| a.status == "absent").count() as i32;
-        let total_overtime = attendances.iter().map(|a| a.overtime_hours).sum::<f32>();
+        let total_overtime = attendances.iter().filter_map(|a| a.overtime_hours).sum::<f32>();
 
         Ok(AttendanceStats {
             total_days,
@@ -285,6 +303,7 @@ impl AttendanceService {
             let updated = attendances::table
                 .filter(attendances::id.eq(attendance.id))
                 .first::<Attendance>(conn)?;
+            QueryCache::current().invalidate(&["hr"]);
             Ok(updated)
         } else {
             // Create new absence record
@@ -293,8 +312,8 @@ impl AttendanceService {
                 date,
                 check_in: None,
                 check_out: None,
-                break_time: 0,
-                overtime_hours: 0.0,
+                break_time: Some(0),
+                overtime_hours: Some(0.0),
                 status: "absent".to_string(),
                 notes,
             };
@@ -308,6 +327,7 @@ impl AttendanceService {
                 .filter(attendances::date.eq(date))
                 .first::<Attendance>(conn)?;
 
+            QueryCache::current().invalidate(&["hr"]);
             Ok(attendance)
         }
     }
@@ -0,0 +1,514 @@
+use chrono::{NaiveDate, Utc};
+use diesel::prelude::*;
+
+use crate::core::auth::AuthenticatedUser;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::core::workflow::role_satisfies;
+use crate::database::models::{
+    CompensationChange, Employee, NewCompensationChange, NewSalaryBand, NewSalaryRaise, SalaryBand,
+    SalaryRaise, SalaryRaiseStatus,
+};
+use crate::database::schema::{compensation_history, employees, salary_bands, salary_raises};
+use crate::database::DatabaseConnection;
+use crate::modules::system::audit::AuditService;
+use crate::modules::system::sod::{SodRule, SodService};
+use crate::utils::cache::QueryCache;
+use crate::utils::timezone::DisplayTimezone;
+use crate::utils::validation::validate_required_string;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Role needed to approve or reject a raise
+const RAISE_APPROVER_ROLE: &str = "manager";
+
+/// Where a salary sits relative to the band of the employee's position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BandStatus {
+    Within,
+    Below,
+    Above,
+    /// No band is defined for the position
+    NoBand,
+}
+
+impl std::fmt::Display for BandStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BandStatus::Within => write!(f, "within band"),
+            BandStatus::Below => write!(f, "below band"),
+            BandStatus::Above => write!(f, "above band"),
+            BandStatus::NoBand => write!(f, "no band"),
+        }
+    }
+}
+
+/// An employee checked against their salary band
+#[derive(Debug, Clone)]
+pub struct BandCheck {
+    pub employee: Employee,
+    pub band: Option<SalaryBand>,
+    pub status: BandStatus,
+}
+
+/// The band of `position` at `level`, falling back to the band that covers
+/// every level of the position
+pub fn find_band<'a>(bands: &'a [SalaryBand], position: &str, level: Option<&str>) -> Option<&'a SalaryBand> {
+    let of_position = |band: &&SalaryBand| band.position.eq_ignore_ascii_case(position);
+    level
+        .and_then(|level| {
+            bands
+                .iter()
+                .filter(of_position)
+                .find(|band| band.level.as_deref().is_some_and(|l| l.eq_ignore_ascii_case(level)))
+        })
+        .or_else(|| bands.iter().filter(of_position).find(|band| band.level.is_none()))
+}
+
+pub fn band_status(band: Option<&SalaryBand>, salary: i32) -> BandStatus {
+    match band {
+        None => BandStatus::NoBand,
+        Some(band) if salary < band.min_salary => BandStatus::Below,
+        Some(band) if salary > band.max_salary => BandStatus::Above,
+        Some(_) => BandStatus::Within,
+    }
+}
+
+/// Salary bands, raises and the compensation history of employees.
+///
+/// A raise is proposed with the new salary and the date it takes effect,
+/// then approved or rejected by a manager; approving one's own proposal is
+/// subject to the `raise_propose_approve` segregation of duties rule. An
+/// approved raise updates the salary on its effective date, either at
+/// approval or on a later `apply_due_raises`. Every salary change, whether
+/// from a raise, an employee update or hiring, is kept in the compensation
+/// history.
+pub struct CompensationService;
+
+impl CompensationService {
+    /// Create or replace the band of `position` (at `level` when given)
+    pub fn set_band(
+        conn: &mut DatabaseConnection,
+        position: &str,
+        level: Option<&str>,
+        min_salary: i32,
+        max_salary: i32,
+        updated_by: Option<i32>,
+    ) -> Result<SalaryBand> {
+        validate_required_string(position, "Position")?;
+        if min_salary < 0 || max_salary < min_salary {
+            return Err(CLIERPError::ValidationError(
+                "Band maximum must be at least its minimum, and the minimum cannot be negative".to_string(),
+            ));
+        }
+        let position = position.trim();
+        let level = level.map(str::trim).filter(|l| !l.is_empty());
+
+        let existing = Self::bands(conn)?
+            .into_iter()
+            .find(|band| {
+                band.position.eq_ignore_ascii_case(position)
+                    && band.level.as_deref().map(str::to_lowercase) == level.map(str::to_lowercase)
+            });
+        let band_id = match existing {
+            Some(band) => {
+                diesel::update(salary_bands::table.find(band.id))
+                    .set((
+                        salary_bands::min_salary.eq(min_salary),
+                        salary_bands::max_salary.eq(max_salary),
+                        salary_bands::updated_by.eq(updated_by),
+                        salary_bands::updated_at.eq(Utc::now().naive_utc()),
+                    ))
+                    .execute(conn)?;
+                band.id
+            }
+            None => {
+                diesel::insert_into(salary_bands::table)
+                    .values(&NewSalaryBand {
+                        position: position.to_string(),
+                        level: level.map(|l| l.to_string()),
+                        min_salary,
+                        max_salary,
+                        updated_by,
+                    })
+                    .execute(conn)?;
+                salary_bands::table
+                    .select(salary_bands::id)
+                    .order(salary_bands::id.desc())
+                    .first::<i32>(conn)?
+            }
+        };
+
+        let band = salary_bands::table.find(band_id).first::<SalaryBand>(conn)?;
+        tracing::info!(
+            "Salary band for {}{} set to {}..{}",
+            band.position,
+            band.level.as_deref().map(|l| format!(" {}", l)).unwrap_or_default(),
+            band.min_salary,
+            band.max_salary
+        );
+        Ok(band)
+    }
+
+    pub fn bands(conn: &mut DatabaseConnection) -> Result<Vec<SalaryBand>> {
+        salary_bands::table
+            .order((salary_bands::position.asc(), salary_bands::level.asc()))
+            .load::<SalaryBand>(conn)
+            .map_err(Into::into)
+    }
+
+    pub fn remove_band(conn: &mut DatabaseConnection, band_id: i32) -> Result<()> {
+        let deleted = diesel::delete(salary_bands::table.find(band_id)).execute(conn)?;
+        if deleted == 0 {
+            return Err(CLIERPError::NotFound(format!("Salary band {} not found", band_id)));
+        }
+        tracing::info!("Salary band {} removed", band_id);
+        Ok(())
+    }
+
+    /// Set the job level that picks an employee's salary band
+    pub fn set_level(conn: &mut DatabaseConnection, employee_id: i32, level: Option<&str>) -> Result<Employee> {
+        let before = Self::employee(conn, employee_id)?;
+        let level = level.map(str::trim).filter(|l| !l.is_empty());
+        diesel::update(employees::table.find(employee_id))
+            .set((
+                employees::level.eq(level),
+                employees::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+
+        let after = Self::employee(conn, employee_id)?;
+        AuditService::record_update(conn, "employees", employee_id, &before, &after)?;
+        Ok(after)
+    }
+
+    /// Current employees checked against their bands; with `outside_only`,
+    /// only those below or above their band or without one
+    pub fn check_bands(conn: &mut DatabaseConnection, outside_only: bool) -> Result<Vec<BandCheck>> {
+        let bands = Self::bands(conn)?;
+        let employees = employees::table
+            .filter(employees::status.ne("terminated"))
+            .order(employees::employee_code.asc())
+            .load::<Employee>(conn)?;
+
+        Ok(employees
+            .into_iter()
+            .map(|employee| {
+                let band = find_band(&bands, &employee.position, employee.level.as_deref()).cloned();
+                let status = band_status(band.as_ref(), employee.salary);
+                BandCheck { employee, band, status }
+            })
+            .filter(|check| !outside_only || check.status != BandStatus::Within)
+            .collect())
+    }
+
+    /// Propose a new salary for an employee from `effective_date`. The band
+    /// status of the proposed salary is returned so it can be shown.
+    pub fn propose_raise(
+        conn: &mut DatabaseConnection,
+        employee_id: i32,
+        proposed_salary: i32,
+        effective_date: NaiveDate,
+        reason: &str,
+        proposed_by: Option<i32>,
+    ) -> Result<(SalaryRaise, BandStatus)> {
+        validate_required_string(reason, "Reason")?;
+        let employee = Self::employee(conn, employee_id)?;
+        if employee.status == "terminated" {
+            return Err(CLIERPError::ValidationError(format!(
+                "Employee {} is terminated",
+                employee.employee_code
+            )));
+        }
+        if proposed_salary <= 0 || proposed_salary == employee.salary {
+            return Err(CLIERPError::ValidationError(format!(
+                "Proposed salary must be positive and differ from the current salary of {}",
+                employee.salary
+            )));
+        }
+        let open = salary_raises::table
+            .filter(salary_raises::employee_id.eq(employee_id))
+            .filter(salary_raises::status.eq_any([
+                SalaryRaiseStatus::Pending.to_string(),
+                SalaryRaiseStatus::Approved.to_string(),
+            ]))
+            .select(salary_raises::id)
+            .first::<i32>(conn)
+            .optional()?;
+        if let Some(open) = open {
+            return Err(CLIERPError::ValidationError(format!(
+                "Employee {} already has raise {} waiting to take effect",
+                employee.employee_code, open
+            )));
+        }
+
+        diesel::insert_into(salary_raises::table)
+            .values(&NewSalaryRaise {
+                employee_id,
+                current_salary: employee.salary,
+                proposed_salary,
+                effective_date,
+                reason: reason.trim().to_string(),
+                proposed_by,
+            })
+            .execute(conn)?;
+        let raise = salary_raises::table
+            .order(salary_raises::id.desc())
+            .first::<SalaryRaise>(conn)?;
+
+        let bands = Self::bands(conn)?;
+        let status = band_status(
+            find_band(&bands, &employee.position, employee.level.as_deref()),
+            proposed_salary,
+        );
+        tracing::info!(
+            "Raise {} proposed for {}: {} -> {} from {}",
+            raise.id,
+            employee.employee_code,
+            employee.salary,
+            proposed_salary,
+            effective_date
+        );
+        Ok((raise, status))
+    }
+
+    /// Raises with their employees, newest first, optionally only those with `status`
+    pub fn raises(
+        conn: &mut DatabaseConnection,
+        status: Option<SalaryRaiseStatus>,
+        limit: i64,
+    ) -> Result<Vec<(SalaryRaise, Employee)>> {
+        let mut query = salary_raises::table.inner_join(employees::table).into_boxed();
+        if let Some(status) = status {
+            query = query.filter(salary_raises::status.eq(status.to_string()));
+        }
+        query
+            .order((salary_raises::proposed_at.desc(), salary_raises::id.desc()))
+            .limit(limit)
+            .load::<(SalaryRaise, Employee)>(conn)
+            .map_err(Into::into)
+    }
+
+    pub fn find_raise(conn: &mut DatabaseConnection, raise_id: i32) -> Result<SalaryRaise> {
+        salary_raises::table
+            .find(raise_id)
+            .first::<SalaryRaise>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Raise {} not found", raise_id)))
+    }
+
+    /// Approve a pending raise, applying it right away when its effective
+    /// date has arrived. `sod_override` is the reason for approving one's
+    /// own proposal.
+    pub fn approve_raise(
+        conn: &mut DatabaseConnection,
+        raise_id: i32,
+        approver: &AuthenticatedUser,
+        note: Option<&str>,
+        sod_override: Option<&str>,
+    ) -> Result<SalaryRaise> {
+        let raise = Self::pending_for_decision(conn, raise_id, approver)?;
+        SodService::check(
+            conn,
+            SodRule::RaiseProposeApprove,
+            raise.id,
+            raise.proposed_by,
+            approver.id,
+            sod_override,
+        )?;
+
+        diesel::update(salary_raises::table.find(raise.id))
+            .set((
+                salary_raises::status.eq(SalaryRaiseStatus::Approved.to_string()),
+                salary_raises::decided_by.eq(Some(approver.id)),
+                salary_raises::decided_at.eq(Some(Utc::now().naive_utc())),
+                salary_raises::decision_note.eq(note),
+            ))
+            .execute(conn)?;
+        tracing::info!("Raise {} approved", raise.id);
+
+        let today = DisplayTimezone::current().today();
+        if raise.effective_date <= today {
+            Self::apply_due_raises(conn, today)?;
+        }
+        Self::find_raise(conn, raise.id)
+    }
+
+    /// Decline a pending raise; the salary is left as it is
+    pub fn reject_raise(
+        conn: &mut DatabaseConnection,
+        raise_id: i32,
+        approver: &AuthenticatedUser,
+        reason: &str,
+    ) -> Result<SalaryRaise> {
+        validate_required_string(reason, "Reason")?;
+        let raise = Self::pending_for_decision(conn, raise_id, approver)?;
+
+        diesel::update(salary_raises::table.find(raise.id))
+            .set((
+                salary_raises::status.eq(SalaryRaiseStatus::Rejected.to_string()),
+                salary_raises::decided_by.eq(Some(approver.id)),
+                salary_raises::decided_at.eq(Some(Utc::now().naive_utc())),
+                salary_raises::decision_note.eq(Some(reason)),
+            ))
+            .execute(conn)?;
+
+        tracing::info!("Raise {} rejected", raise.id);
+        Self::find_raise(conn, raise.id)
+    }
+
+    /// Update the salaries of approved raises effective on or before `today`
+    pub fn apply_due_raises(conn: &mut DatabaseConnection, today: NaiveDate) -> Result<Vec<SalaryRaise>> {
+        let due = salary_raises::table
+            .filter(salary_raises::status.eq(SalaryRaiseStatus::Approved.to_string()))
+            .filter(salary_raises::effective_date.le(today))
+            .order((salary_raises::effective_date.asc(), salary_raises::id.asc()))
+            .load::<SalaryRaise>(conn)?;
+        if due.is_empty() {
+            return Ok(due);
+        }
+
+        let ids: Vec<i32> = due.iter().map(|raise| raise.id).collect();
+        conn.transaction::<_, CLIERPError, _>(|conn| {
+            for raise in &due {
+                let before = employees::table.find(raise.employee_id).first::<Employee>(conn)?;
+                diesel::update(employees::table.find(raise.employee_id))
+                    .set((
+                        employees::salary.eq(raise.proposed_salary),
+                        employees::updated_at.eq(Utc::now().naive_utc()),
+                    ))
+                    .execute(conn)?;
+                let after = employees::table.find(raise.employee_id).first::<Employee>(conn)?;
+                AuditService::record_update(conn, "employees", raise.employee_id, &before, &after)?;
+                Self::record_change(
+                    conn,
+                    raise.employee_id,
+                    Some(before.salary),
+                    raise.proposed_salary,
+                    raise.effective_date,
+                    "raise",
+                    Some(raise.id),
+                )?;
+
+                diesel::update(salary_raises::table.find(raise.id))
+                    .set((
+                        salary_raises::status.eq(SalaryRaiseStatus::Applied.to_string()),
+                        salary_raises::applied_at.eq(Some(Utc::now().naive_utc())),
+                    ))
+                    .execute(conn)?;
+            }
+            Ok(())
+        })?;
+
+        QueryCache::current().invalidate(&["hr"]);
+        tracing::info!("Applied {} raise(s) effective on or before {}", due.len(), today);
+        salary_raises::table
+            .filter(salary_raises::id.eq_any(&ids))
+            .order((salary_raises::effective_date.asc(), salary_raises::id.asc()))
+            .load::<SalaryRaise>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Salary changes of an employee, oldest first
+    pub fn history(conn: &mut DatabaseConnection, employee_id: i32) -> Result<Vec<CompensationChange>> {
+        Self::employee(conn, employee_id)?;
+        compensation_history::table
+            .filter(compensation_history::employee_id.eq(employee_id))
+            .order((compensation_history::effective_date.asc(), compensation_history::id.asc()))
+            .load::<CompensationChange>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Add a salary change to the compensation history, attributed to the
+    /// current audit actor
+    pub(crate) fn record_change(
+        conn: &mut SqliteConnection,
+        employee_id: i32,
+        old_salary: Option<i32>,
+        new_salary: i32,
+        effective_date: NaiveDate,
+        source: &str,
+        raise_id: Option<i32>,
+    ) -> Result<()> {
+        diesel::insert_into(compensation_history::table)
+            .values(&NewCompensationChange {
+                employee_id,
+                old_salary,
+                new_salary,
+                effective_date,
+                source: source.to_string(),
+                raise_id,
+                changed_by: AuditService::actor(),
+            })
+            .execute(conn)?;
+        Ok(())
+    }
+
+    fn employee(conn: &mut DatabaseConnection, employee_id: i32) -> Result<Employee> {
+        employees::table
+            .find(employee_id)
+            .first::<Employee>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Employee with ID {} not found", employee_id)))
+    }
+
+    /// Load a raise that `approver` may decide on
+    fn pending_for_decision(
+        conn: &mut DatabaseConnection,
+        raise_id: i32,
+        approver: &AuthenticatedUser,
+    ) -> Result<SalaryRaise> {
+        if !role_satisfies(&approver.role, RAISE_APPROVER_ROLE) {
+            return Err(CLIERPError::Authorization(format!(
+                "Deciding on raises requires role: {}",
+                RAISE_APPROVER_ROLE
+            )));
+        }
+        let raise = Self::find_raise(conn, raise_id)?;
+        if raise.status != SalaryRaiseStatus::Pending.to_string() {
+            return Err(CLIERPError::ValidationError(format!(
+                "Raise {} is already {}",
+                raise.id, raise.status
+            )));
+        }
+        Ok(raise)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn band(id: i32, position: &str, level: Option<&str>, min_salary: i32, max_salary: i32) -> SalaryBand {
+        SalaryBand {
+            id,
+            position: position.to_string(),
+            level: level.map(|l| l.to_string()),
+            min_salary,
+            max_salary,
+            updated_by: None,
+            created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+        }
+    }
+
+    #[test]
+    fn test_find_band_and_status() {
+        let bands = vec![
+            band(1, "Engineer", None, 3_000_000, 5_000_000),
+            band(2, "Engineer", Some("Senior"), 5_000_000, 7_000_000),
+        ];
+
+        assert_eq!(find_band(&bands, "engineer", Some("senior")).map(|b| b.id), Some(2));
+        assert_eq!(find_band(&bands, "Engineer", Some("Junior")).map(|b| b.id), Some(1));
+        assert_eq!(find_band(&bands, "Engineer", None).map(|b| b.id), Some(1));
+        assert!(find_band(&bands, "Designer", None).is_none());
+
+        let senior = find_band(&bands, "Engineer", Some("Senior"));
+        assert_eq!(band_status(senior, 4_900_000), BandStatus::Below);
+        assert_eq!(band_status(senior, 7_000_000), BandStatus::Within);
+        assert_eq!(band_status(senior, 7_000_001), BandStatus::Above);
+        assert_eq!(band_status(None, 1), BandStatus::NoBand);
+    }
+}
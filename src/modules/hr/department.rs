@@ -245,6 +245,17 @@ pub struct DepartmentWithEmployeeCount {
     pub employee_count: i64,
 }
 
+/// Columns of the department CSV export, in `to_csv_row` order
+pub const DEPARTMENT_CSV_HEADERS: [&str; 7] = [
+    "ID",
+    "Name",
+    "Description",
+    "Manager ID",
+    "Employee Count",
+    "Created",
+    "Updated",
+];
+
 impl crate::utils::export::CsvSerializable for DepartmentWithEmployeeCount {
     fn to_csv_row(&self) -> Vec<String> {
         use crate::utils::export::escape_csv_value;
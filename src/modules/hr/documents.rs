@@ -0,0 +1,479 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::models::{
+    Department, DocumentType, Employee, EmployeeDocument, NewEmployeeDocument, NewRequiredDocument,
+    RequiredDocument,
+};
+use crate::database::schema::{departments, employee_documents, employees, required_documents};
+use crate::database::DatabaseConnection;
+use crate::modules::system::legal_hold::{HoldEntity, LegalHoldService};
+use crate::utils::cache::QueryCache;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Where scanned copies of employee documents are kept
+const DOCUMENT_STORAGE_PATH: &str = "./storage/documents";
+
+/// Whether an employee holds a valid document of a required type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentState {
+    /// A document of the type is on file and has not expired
+    Valid,
+    /// Every document of the type on file has expired
+    Expired,
+    /// No document of the type is on file
+    Missing,
+}
+
+impl std::fmt::Display for DocumentState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DocumentState::Valid => write!(f, "valid"),
+            DocumentState::Expired => write!(f, "expired"),
+            DocumentState::Missing => write!(f, "missing"),
+        }
+    }
+}
+
+/// State of the documents of one type an employee holds, with the latest
+/// expiry among them. A document without an expiry date never expires.
+pub fn document_state(documents: &[&EmployeeDocument], today: NaiveDate) -> (DocumentState, Option<NaiveDate>) {
+    if documents.is_empty() {
+        return (DocumentState::Missing, None);
+    }
+    if documents.iter().any(|d| d.expiry_date.is_none()) {
+        return (DocumentState::Valid, None);
+    }
+    let latest = documents.iter().filter_map(|d| d.expiry_date).max();
+    match latest {
+        Some(expiry) if expiry < today => (DocumentState::Expired, latest),
+        _ => (DocumentState::Valid, latest),
+    }
+}
+
+/// A required document an employee is missing or only holds expired
+#[derive(Debug, Clone)]
+pub struct ComplianceGap {
+    pub employee: Employee,
+    pub department: String,
+    pub document_type: String,
+    pub state: DocumentState,
+    /// Latest expiry of the expired documents of the type
+    pub expired_on: Option<NaiveDate>,
+}
+
+/// Documents kept per employee, such as work permits, visas and contracts.
+///
+/// Documents may carry an expiry date; `send_expiry_alerts` warns HR once
+/// about each document expiring within the notice period, and again after
+/// the document is renewed. Admins mark document types as required for the
+/// whole company or for a department, and `compliance` lists current
+/// employees who are missing a required document or only hold expired ones.
+pub struct DocumentService;
+
+impl DocumentService {
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_document(
+        conn: &mut DatabaseConnection,
+        employee_id: i32,
+        document_type: DocumentType,
+        document_number: Option<&str>,
+        issued_date: Option<NaiveDate>,
+        expiry_date: Option<NaiveDate>,
+        file: Option<&Path>,
+        notes: Option<&str>,
+        created_by: Option<i32>,
+    ) -> Result<EmployeeDocument> {
+        let employee = Self::employee(conn, employee_id)?;
+        if let (Some(issued), Some(expiry)) = (issued_date, expiry_date) {
+            if expiry < issued {
+                return Err(CLIERPError::ValidationError(
+                    "Expiry date cannot be before the issue date".to_string(),
+                ));
+            }
+        }
+        let file_path = file
+            .map(|file| store_file(Path::new(DOCUMENT_STORAGE_PATH), employee_id, file))
+            .transpose()?;
+
+        diesel::insert_into(employee_documents::table)
+            .values(&NewEmployeeDocument {
+                employee_id,
+                document_type: document_type.to_string(),
+                document_number: document_number.map(str::trim).filter(|n| !n.is_empty()).map(|n| n.to_string()),
+                issued_date,
+                expiry_date,
+                file_path: file_path.map(|p| p.to_string_lossy().to_string()),
+                notes: notes.map(|n| n.to_string()),
+                created_by,
+            })
+            .execute(conn)?;
+        let document = employee_documents::table
+            .order(employee_documents::id.desc())
+            .first::<EmployeeDocument>(conn)?;
+        QueryCache::current().invalidate(&["hr"]);
+
+        tracing::info!(
+            "Added {} document {} for employee {}",
+            document.document_type,
+            document.id,
+            employee.employee_code
+        );
+        Ok(document)
+    }
+
+    pub fn documents(conn: &mut DatabaseConnection, employee_id: i32) -> Result<Vec<EmployeeDocument>> {
+        Self::employee(conn, employee_id)?;
+        employee_documents::table
+            .filter(employee_documents::employee_id.eq(employee_id))
+            .order((employee_documents::document_type.asc(), employee_documents::expiry_date.desc()))
+            .load::<EmployeeDocument>(conn)
+            .map_err(Into::into)
+    }
+
+    pub fn find_document(conn: &mut DatabaseConnection, document_id: i32) -> Result<EmployeeDocument> {
+        employee_documents::table
+            .find(document_id)
+            .first::<EmployeeDocument>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Document {} not found", document_id)))
+    }
+
+    /// Record a new expiry date after a document is renewed; the document
+    /// will be alerted about again when the new date comes near
+    pub fn renew(
+        conn: &mut DatabaseConnection,
+        document_id: i32,
+        expiry_date: Option<NaiveDate>,
+    ) -> Result<EmployeeDocument> {
+        let document = Self::find_document(conn, document_id)?;
+        diesel::update(employee_documents::table.find(document_id))
+            .set((
+                employee_documents::expiry_date.eq(expiry_date),
+                employee_documents::alerted_at.eq(None::<chrono::NaiveDateTime>),
+                employee_documents::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+        QueryCache::current().invalidate(&["hr"]);
+
+        tracing::info!(
+            "Document {} renewed: expiry {:?} -> {:?}",
+            document_id,
+            document.expiry_date,
+            expiry_date
+        );
+        Self::find_document(conn, document_id)
+    }
+
+    /// Delete a document record and its stored copy
+    pub fn remove_document(conn: &mut DatabaseConnection, document_id: i32) -> Result<()> {
+        let document = Self::find_document(conn, document_id)?;
+        LegalHoldService::ensure_not_held(conn, HoldEntity::Documents, document_id, "remove")?;
+        if let Some(file_path) = &document.file_path {
+            let path = Path::new(file_path);
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+        diesel::delete(employee_documents::table.find(document_id)).execute(conn)?;
+        QueryCache::current().invalidate(&["hr"]);
+
+        tracing::info!(
+            "Removed {} document {} of employee {}",
+            document.document_type,
+            document_id,
+            document.employee_id
+        );
+        Ok(())
+    }
+
+    /// Documents of current employees that expire within `within_days` of
+    /// `today` or have already expired, soonest first
+    pub fn expiring(
+        conn: &mut DatabaseConnection,
+        today: NaiveDate,
+        within_days: i64,
+    ) -> Result<Vec<(EmployeeDocument, Employee)>> {
+        let horizon = today + Duration::days(within_days.max(0));
+        employee_documents::table
+            .inner_join(employees::table)
+            .filter(employees::status.ne("terminated"))
+            .filter(employee_documents::expiry_date.le(horizon))
+            .order((employee_documents::expiry_date.asc(), employee_documents::id.asc()))
+            .select((EmployeeDocument::as_select(), Employee::as_select()))
+            .load::<(EmployeeDocument, Employee)>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Warn HR about each expiring document that has not been alerted about
+    /// yet and mark it alerted; returns the documents alerted about
+    pub fn send_expiry_alerts(
+        conn: &mut DatabaseConnection,
+        today: NaiveDate,
+        within_days: i64,
+    ) -> Result<Vec<(EmployeeDocument, Employee)>> {
+        let pending: Vec<(EmployeeDocument, Employee)> = Self::expiring(conn, today, within_days)?
+            .into_iter()
+            .filter(|(document, _)| document.alerted_at.is_none())
+            .collect();
+        if pending.is_empty() {
+            return Ok(pending);
+        }
+
+        let ids: Vec<i32> = pending.iter().map(|(document, _)| document.id).collect();
+        diesel::update(employee_documents::table.filter(employee_documents::id.eq_any(&ids)))
+            .set(employee_documents::alerted_at.eq(Some(Utc::now().naive_utc())))
+            .execute(conn)?;
+
+        for (document, employee) in &pending {
+            let expiry = document.expiry_date.unwrap_or(today);
+            if expiry < today {
+                tracing::warn!(
+                    "Document alert: {} of {} ({}) expired on {}",
+                    document.document_type,
+                    employee.name,
+                    employee.employee_code,
+                    expiry
+                );
+            } else {
+                tracing::warn!(
+                    "Document alert: {} of {} ({}) expires on {} ({} days)",
+                    document.document_type,
+                    employee.name,
+                    employee.employee_code,
+                    expiry,
+                    (expiry - today).num_days()
+                );
+            }
+        }
+        Ok(pending)
+    }
+
+    /// Require `document_type` of every employee of `department_id`, or of
+    /// every employee when no department is given
+    pub fn require(
+        conn: &mut DatabaseConnection,
+        document_type: DocumentType,
+        department_id: Option<i32>,
+    ) -> Result<RequiredDocument> {
+        if let Some(department_id) = department_id {
+            departments::table
+                .find(department_id)
+                .select(departments::id)
+                .first::<i32>(conn)
+                .optional()?
+                .ok_or_else(|| CLIERPError::NotFound(format!("Department {} not found", department_id)))?;
+        }
+        if let Some(existing) = Self::find_requirement(conn, document_type, department_id)? {
+            return Err(CLIERPError::AlreadyExists(format!(
+                "{} is already required (requirement {})",
+                document_type, existing.id
+            )));
+        }
+
+        diesel::insert_into(required_documents::table)
+            .values(&NewRequiredDocument {
+                document_type: document_type.to_string(),
+                department_id,
+            })
+            .execute(conn)?;
+        let requirement = required_documents::table
+            .order(required_documents::id.desc())
+            .first::<RequiredDocument>(conn)?;
+        QueryCache::current().invalidate(&["hr"]);
+
+        tracing::info!("Document type {} required for department {:?}", document_type, department_id);
+        Ok(requirement)
+    }
+
+    pub fn unrequire(
+        conn: &mut DatabaseConnection,
+        document_type: DocumentType,
+        department_id: Option<i32>,
+    ) -> Result<()> {
+        let requirement = Self::find_requirement(conn, document_type, department_id)?
+            .ok_or_else(|| CLIERPError::NotFound(format!("{} is not required there", document_type)))?;
+        diesel::delete(required_documents::table.find(requirement.id)).execute(conn)?;
+        QueryCache::current().invalidate(&["hr"]);
+
+        tracing::info!("Document type {} no longer required for department {:?}", document_type, department_id);
+        Ok(())
+    }
+
+    /// Required document types, company-wide ones first
+    pub fn requirements(conn: &mut DatabaseConnection) -> Result<Vec<RequiredDocument>> {
+        required_documents::table
+            .order((required_documents::department_id.asc(), required_documents::document_type.asc()))
+            .load::<RequiredDocument>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Current employees, of `department_id` when given, who are missing a
+    /// required document or hold only expired ones, by department and employee
+    pub fn compliance(
+        conn: &mut DatabaseConnection,
+        today: NaiveDate,
+        department_id: Option<i32>,
+    ) -> Result<Vec<ComplianceGap>> {
+        let requirements = Self::requirements(conn)?;
+        if requirements.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut query = employees::table
+            .filter(employees::status.ne("terminated"))
+            .into_boxed();
+        if let Some(department_id) = department_id {
+            query = query.filter(employees::department_id.eq(department_id));
+        }
+        let employees = query.order(employees::employee_code.asc()).load::<Employee>(conn)?;
+        let department_names: HashMap<i32, String> = departments::table
+            .load::<Department>(conn)?
+            .into_iter()
+            .map(|d| (d.id, d.name))
+            .collect();
+
+        let employee_ids: Vec<i32> = employees.iter().map(|e| e.id).collect();
+        let documents = employee_documents::table
+            .filter(employee_documents::employee_id.eq_any(&employee_ids))
+            .load::<EmployeeDocument>(conn)?;
+
+        let mut gaps = Vec::new();
+        for employee in employees {
+            let department = department_names
+                .get(&employee.department_id)
+                .cloned()
+                .unwrap_or_else(|| format!("#{}", employee.department_id));
+            let mut required: Vec<&str> = requirements
+                .iter()
+                .filter(|r| r.department_id.is_none_or(|d| d == employee.department_id))
+                .map(|r| r.document_type.as_str())
+                .collect();
+            required.sort();
+            required.dedup();
+
+            for document_type in required {
+                let held: Vec<&EmployeeDocument> = documents
+                    .iter()
+                    .filter(|d| d.employee_id == employee.id && d.document_type == document_type)
+                    .collect();
+                let (state, expired_on) = document_state(&held, today);
+                if state != DocumentState::Valid {
+                    gaps.push(ComplianceGap {
+                        employee: employee.clone(),
+                        department: department.clone(),
+                        document_type: document_type.to_string(),
+                        state,
+                        expired_on,
+                    });
+                }
+            }
+        }
+        gaps.sort_by(|a, b| {
+            (&a.department, &a.employee.employee_code).cmp(&(&b.department, &b.employee.employee_code))
+        });
+        Ok(gaps)
+    }
+
+    fn find_requirement(
+        conn: &mut DatabaseConnection,
+        document_type: DocumentType,
+        department_id: Option<i32>,
+    ) -> Result<Option<RequiredDocument>> {
+        let mut query = required_documents::table
+            .filter(required_documents::document_type.eq(document_type.to_string()))
+            .into_boxed();
+        query = match department_id {
+            Some(department_id) => query.filter(required_documents::department_id.eq(department_id)),
+            None => query.filter(required_documents::department_id.is_null()),
+        };
+        query.first::<RequiredDocument>(conn).optional().map_err(Into::into)
+    }
+
+    fn employee(conn: &mut DatabaseConnection, employee_id: i32) -> Result<Employee> {
+        employees::table
+            .find(employee_id)
+            .first::<Employee>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Employee {} not found", employee_id)))
+    }
+}
+
+/// Copy `file` into the storage directory of the employee under a unique name
+fn store_file(storage: &Path, employee_id: i32, file: &Path) -> Result<PathBuf> {
+    if !file.is_file() {
+        return Err(CLIERPError::ValidationError(format!(
+            "Document file {} does not exist",
+            file.display()
+        )));
+    }
+    let directory = storage.join(format!("employee_{}", employee_id));
+    fs::create_dir_all(&directory)?;
+    let file_name = match file.extension().and_then(|ext| ext.to_str()) {
+        Some(extension) => format!("{}.{}", Uuid::new_v4(), extension),
+        None => Uuid::new_v4().to_string(),
+    };
+    let destination = directory.join(file_name);
+    fs::copy(file, &destination)?;
+    Ok(destination)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(id: i32, expiry_date: Option<NaiveDate>) -> EmployeeDocument {
+        EmployeeDocument {
+            id,
+            employee_id: 1,
+            document_type: DocumentType::WorkPermit.to_string(),
+            document_number: None,
+            issued_date: None,
+            expiry_date,
+            file_path: None,
+            notes: None,
+            alerted_at: None,
+            created_by: None,
+            created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+        }
+    }
+
+    #[test]
+    fn test_document_state() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let expired = document(1, NaiveDate::from_ymd_opt(2024, 5, 31));
+        let renewed = document(2, NaiveDate::from_ymd_opt(2025, 5, 31));
+        let permanent = document(3, None);
+
+        assert_eq!(document_state(&[], today), (DocumentState::Missing, None));
+        assert_eq!(document_state(&[&expired], today), (DocumentState::Expired, expired.expiry_date));
+        assert_eq!(document_state(&[&expired, &renewed], today), (DocumentState::Valid, renewed.expiry_date));
+        assert_eq!(document_state(&[&expired, &permanent], today), (DocumentState::Valid, None));
+        // Valid through its expiry date
+        let last_day = document(4, Some(today));
+        assert_eq!(document_state(&[&last_day], today).0, DocumentState::Valid);
+    }
+
+    #[test]
+    fn test_store_file() {
+        let storage = tempfile::TempDir::new().unwrap();
+        let source = storage.path().join("permit.pdf");
+        fs::write(&source, b"scan").unwrap();
+
+        let stored = store_file(storage.path(), 7, &source).unwrap();
+        assert!(stored.starts_with(storage.path().join("employee_7")));
+        assert_eq!(stored.extension().and_then(|e| e.to_str()), Some("pdf"));
+        assert_eq!(fs::read(&stored).unwrap(), b"scan");
+        assert!(store_file(storage.path(), 7, &storage.path().join("missing.pdf")).is_err());
+    }
+}
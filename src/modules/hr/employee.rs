@@ -2,14 +2,39 @@ use crate::core::error::CLIERPError;
 use crate::core::result::CLIERPResult;
 use crate::database::{
     connection::DatabaseConnection,
-    models::{Department, Employee, NewEmployee},
+    models::{Department, Employee, EventAction, NewEmployee},
     schema::{departments, employees},
 };
+use crate::modules::hr::compensation::CompensationService;
+use crate::modules::system::audit::AuditService;
+use crate::modules::system::events::EventService;
+use crate::utils::cache::QueryCache;
+use crate::utils::filter_expr::{FilterExpr, FilterField};
+use crate::utils::timezone::DisplayTimezone;
+use crate::utils::validation::validate_required_string;
 use chrono::{NaiveDate, Utc};
 use diesel::prelude::*;
 
+/// Fields `employee list --where` may use
+pub const EMPLOYEE_WHERE_FIELDS: &[FilterField] = &[
+    FilterField::integer("id", "employees.id"),
+    FilterField::text("code", "employees.employee_code"),
+    FilterField::text("name", "employees.name"),
+    FilterField::text("email", "employees.email"),
+    FilterField::integer("department", "employees.department_id"),
+    FilterField::text("department_name", "departments.name"),
+    FilterField::text("position", "employees.position"),
+    FilterField::text("level", "employees.level"),
+    FilterField::integer("salary", "employees.salary"),
+    FilterField::text("status", "employees.status"),
+    FilterField::date("hired", "employees.hire_date"),
+    FilterField::date("terminated", "employees.termination_date"),
+];
+
 #[derive(Debug)]
 pub struct CreateEmployeeRequest {
+    /// Generated when not given
+    pub employee_code: Option<String>,
     pub name: String,
     pub email: Option<String>,
     pub phone: Option<String>,
@@ -91,8 +116,24 @@ impl EmployeeService {
             }
         }
 
-        // Generate employee code
-        let employee_code_val = self.generate_employee_code(conn)?;
+        // Use the given employee code, or generate one
+        let employee_code_val = match request.employee_code.as_deref().map(str::trim) {
+            Some(code_val) => {
+                validate_required_string(code_val, "Employee code")?;
+                let existing = employees
+                    .filter(employee_code.eq(code_val))
+                    .first::<Employee>(conn)
+                    .optional()?;
+                if existing.is_some() {
+                    return Err(CLIERPError::ValidationError(format!(
+                        "Employee with code '{}' already exists",
+                        code_val
+                    )));
+                }
+                code_val.to_string()
+            }
+            None => self.generate_employee_code(conn)?,
+        };
 
         let new_emp = NewEmployee {
             employee_code: employee_code_val.clone(),
@@ -114,6 +155,17 @@ impl EmployeeService {
         let employee = employees
             .filter(employee_code.eq(&employee_code_val))
             .first::<Employee>(conn)?;
+        CompensationService::record_change(
+            conn,
+            employee.id,
+            None,
+            employee.salary,
+            employee.hire_date,
+            "hire",
+            None,
+        )?;
+        EventService::record(conn, "employees", employee.id, EventAction::Created, &employee)?;
+        QueryCache::current().invalidate(&["hr"]);
 
         Ok(employee)
     }
@@ -142,6 +194,35 @@ impl EmployeeService {
         Ok(result)
     }
 
+    /// List employees matching a `--where` expression, optionally in one department
+    pub fn list_employees_where(
+        &self,
+        conn: &mut DatabaseConnection,
+        dept_id: Option<i32>,
+        filter: &FilterExpr,
+    ) -> CLIERPResult<Vec<EmployeeWithDepartment>> {
+        let mut query = employees::table
+            .inner_join(departments::table)
+            .filter(filter.predicate(EMPLOYEE_WHERE_FIELDS)?)
+            .select((Employee::as_select(), Department::as_select()))
+            .order(employees::name.asc())
+            .into_boxed();
+        if let Some(dept_id) = dept_id {
+            query = query.filter(employees::department_id.eq(dept_id));
+        }
+
+        let result = query
+            .load::<(Employee, Department)>(conn)?
+            .into_iter()
+            .map(|(emp, dept)| EmployeeWithDepartment {
+                employee: emp,
+                department: dept,
+            })
+            .collect();
+
+        Ok(result)
+    }
+
     /// List employees by department
     pub fn list_employees_by_department(
         &self,
@@ -314,6 +395,11 @@ impl EmployeeService {
             changeset.salary = Some(new_sal);
         }
         if let Some(new_st) = request.status {
+            if new_st == "terminated" && emp.employee.status != "terminated" {
+                changeset.termination_date = Some(Some(DisplayTimezone::current().today()));
+            } else if new_st != "terminated" {
+                changeset.termination_date = Some(None);
+            }
             changeset.status = Some(new_st);
         }
         changeset.updated_at = Some(Utc::now().naive_utc());
@@ -326,6 +412,19 @@ impl EmployeeService {
         let updated_emp = employees
             .filter(id.eq(request.id))
             .first::<Employee>(conn)?;
+        AuditService::record_update(conn, "employees", request.id, &emp.employee, &updated_emp)?;
+        if updated_emp.salary != emp.employee.salary {
+            CompensationService::record_change(
+                conn,
+                updated_emp.id,
+                Some(emp.employee.salary),
+                updated_emp.salary,
+                DisplayTimezone::current().today(),
+                "update",
+                None,
+            )?;
+        }
+        QueryCache::current().invalidate(&["hr"]);
 
         Ok(updated_emp)
     }
@@ -335,17 +434,22 @@ impl EmployeeService {
         use crate::database::schema::employees::dsl::*;
 
         // Check if employee exists
-        let _emp = self.get_employee_by_id(conn, emp_id)?.ok_or_else(|| {
+        let emp = self.get_employee_by_id(conn, emp_id)?.ok_or_else(|| {
             CLIERPError::NotFound(format!("Employee with ID {} not found", emp_id))
         })?;
+        if emp.employee.status == "terminated" {
+            return Ok(());
+        }
 
         // Soft delete - set status to terminated
         diesel::update(employees.filter(id.eq(emp_id)))
             .set((
                 status.eq("terminated"),
+                termination_date.eq(Some(DisplayTimezone::current().today())),
                 updated_at.eq(Utc::now().naive_utc()),
             ))
             .execute(conn)?;
+        QueryCache::current().invalidate(&["hr"]);
 
         Ok(())
     }
@@ -386,6 +490,22 @@ pub struct EmployeeWithDepartment {
     pub department: Department,
 }
 
+/// Columns of the employee CSV export, in `to_csv_row` order
+pub const EMPLOYEE_CSV_HEADERS: [&str; 12] = [
+    "ID",
+    "Code",
+    "Name",
+    "Email",
+    "Phone",
+    "Department",
+    "Position",
+    "Salary",
+    "Status",
+    "Hire Date",
+    "Created",
+    "Updated",
+];
+
 impl crate::utils::export::CsvSerializable for EmployeeWithDepartment {
     fn to_csv_row(&self) -> Vec<String> {
         use crate::utils::export::escape_csv_value;
@@ -398,7 +518,7 @@ impl crate::utils::export::CsvSerializable for EmployeeWithDepartment {
             escape_csv_value(&self.employee.phone.clone().unwrap_or_default()),
             escape_csv_value(&self.department.name),
             escape_csv_value(&self.employee.position),
-            self.employee.salary.to_string(),
+            crate::utils::formatting::format_plain_amount(self.employee.salary),
             escape_csv_value(&self.employee.status),
             self.employee.hire_date.format("%Y-%m-%d").to_string(),
             self.employee
@@ -430,5 +550,35 @@ struct EmployeeChangeset {
     pub position: Option<String>,
     pub salary: Option<i32>,
     pub status: Option<String>,
+    pub termination_date: Option<Option<chrono::NaiveDate>>,
     pub updated_at: Option<chrono::NaiveDateTime>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::CompanyFixture;
+
+    #[test]
+    fn test_create_employee_with_code() {
+        let company = CompanyFixture::new().with_employees(1).build().unwrap();
+        let mut conn = company.connection().unwrap();
+        let request = |code: Option<&str>| CreateEmployeeRequest {
+            employee_code: code.map(str::to_string),
+            name: "Kim Minji".to_string(),
+            email: None,
+            phone: None,
+            department_id: company.employees[0].department_id,
+            position: "Analyst".to_string(),
+            hire_date: NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            salary: 3_500_000,
+        };
+        let service = EmployeeService::new();
+
+        let employee = service.create_employee(&mut conn, request(Some(" HR-0100 "))).unwrap();
+        assert_eq!(employee.employee_code, "HR-0100");
+        assert!(service.create_employee(&mut conn, request(Some("HR-0100"))).is_err());
+        assert!(service.create_employee(&mut conn, request(Some(""))).is_err());
+        assert!(service.create_employee(&mut conn, request(None)).unwrap().employee_code.starts_with("EMP"));
+    }
+}
@@ -0,0 +1,266 @@
+use chrono::{Datelike, Duration, NaiveDate, Utc, Weekday};
+use diesel::prelude::*;
+
+use crate::core::auth::AuthenticatedUser;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::core::workflow::role_satisfies;
+use crate::database::models::{AttendanceStatus, LeaveRequest, LeaveStatus, NewAttendance, NewLeaveRequest};
+use crate::database::schema::{attendances, employees, leave_requests};
+use crate::database::DatabaseConnection;
+use crate::utils::cache::QueryCache;
+use crate::utils::validation::validate_required_string;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Role needed to approve or reject leave, or to request it for someone else
+pub const LEAVE_APPROVER_ROLE: &str = "manager";
+
+/// Weekdays from `start` to `end`, both included
+pub fn working_days(start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+    let mut days = Vec::new();
+    let mut day = start;
+    while day <= end {
+        if !matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+            days.push(day);
+        }
+        day += Duration::days(1);
+    }
+    days
+}
+
+/// Leave requests and their approval.
+///
+/// A request covers the working days between its start and end date.
+/// Approving it records each of those days as `holiday` attendance, which
+/// is what attendance summaries and the leave utilization report count.
+pub struct LeaveService;
+
+impl LeaveService {
+    /// Request leave for an employee. Employees request their own leave;
+    /// requesting it for someone else needs the approver role.
+    pub fn request(
+        conn: &mut DatabaseConnection,
+        employee_id: i32,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        reason: Option<&str>,
+        requester: &AuthenticatedUser,
+    ) -> Result<LeaveRequest> {
+        if requester.employee_id != Some(employee_id) && !role_satisfies(&requester.role, LEAVE_APPROVER_ROLE) {
+            return Err(CLIERPError::Authorization(format!(
+                "Requesting leave for another employee requires role: {}",
+                LEAVE_APPROVER_ROLE
+            )));
+        }
+        employees::table
+            .find(employee_id)
+            .select(employees::id)
+            .first::<i32>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Employee with ID {} not found", employee_id)))?;
+        if end_date < start_date {
+            return Err(CLIERPError::ValidationError(
+                "Leave cannot end before it starts".to_string(),
+            ));
+        }
+        let days = working_days(start_date, end_date).len() as i32;
+        if days == 0 {
+            return Err(CLIERPError::ValidationError(
+                "Leave must include at least one working day".to_string(),
+            ));
+        }
+
+        let overlapping = leave_requests::table
+            .filter(leave_requests::employee_id.eq(employee_id))
+            .filter(leave_requests::status.ne(LeaveStatus::Rejected.to_string()))
+            .filter(leave_requests::start_date.le(end_date))
+            .filter(leave_requests::end_date.ge(start_date))
+            .select(leave_requests::id)
+            .first::<i32>(conn)
+            .optional()?;
+        if let Some(existing) = overlapping {
+            return Err(CLIERPError::AlreadyExists(format!(
+                "Leave request {} already covers part of these dates",
+                existing
+            )));
+        }
+
+        diesel::insert_into(leave_requests::table)
+            .values(&NewLeaveRequest {
+                employee_id,
+                start_date,
+                end_date,
+                days,
+                reason: reason.map(str::trim).filter(|r| !r.is_empty()).map(|r| r.to_string()),
+                requested_by: Some(requester.id),
+            })
+            .execute(conn)?;
+        let request = leave_requests::table
+            .order(leave_requests::id.desc())
+            .first::<LeaveRequest>(conn)?;
+
+        tracing::info!(
+            "Leave request {} for employee {}: {} to {} ({} days)",
+            request.id,
+            employee_id,
+            start_date,
+            end_date,
+            days
+        );
+        Ok(request)
+    }
+
+    pub fn requests(
+        conn: &mut DatabaseConnection,
+        employee_id: Option<i32>,
+        status: Option<LeaveStatus>,
+    ) -> Result<Vec<LeaveRequest>> {
+        let mut query = leave_requests::table.into_boxed();
+        if let Some(employee_id) = employee_id {
+            query = query.filter(leave_requests::employee_id.eq(employee_id));
+        }
+        if let Some(status) = status {
+            query = query.filter(leave_requests::status.eq(status.to_string()));
+        }
+        query
+            .order((leave_requests::start_date.asc(), leave_requests::id.asc()))
+            .load::<LeaveRequest>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Pending requests of the given employees
+    pub fn pending_for(conn: &mut DatabaseConnection, employee_ids: &[i32]) -> Result<Vec<LeaveRequest>> {
+        leave_requests::table
+            .filter(leave_requests::employee_id.eq_any(employee_ids))
+            .filter(leave_requests::status.eq(LeaveStatus::Pending.to_string()))
+            .order(leave_requests::start_date.asc())
+            .load::<LeaveRequest>(conn)
+            .map_err(Into::into)
+    }
+
+    pub fn find_request(conn: &mut DatabaseConnection, request_id: i32) -> Result<LeaveRequest> {
+        leave_requests::table
+            .find(request_id)
+            .first::<LeaveRequest>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Leave request {} not found", request_id)))
+    }
+
+    /// Approve a pending request and record its working days as `holiday`
+    /// attendance. Days that already have attendance are left as they are.
+    pub fn approve(
+        conn: &mut DatabaseConnection,
+        request_id: i32,
+        approver: &AuthenticatedUser,
+        note: Option<&str>,
+    ) -> Result<LeaveRequest> {
+        let request = Self::pending_for_decision(conn, request_id, approver)?;
+
+        conn.transaction::<_, CLIERPError, _>(|conn| {
+            diesel::update(leave_requests::table.find(request.id))
+                .set((
+                    leave_requests::status.eq(LeaveStatus::Approved.to_string()),
+                    leave_requests::decided_by.eq(Some(approver.id)),
+                    leave_requests::decided_at.eq(Some(Utc::now().naive_utc())),
+                    leave_requests::decision_note.eq(note),
+                ))
+                .execute(conn)?;
+
+            let recorded: Vec<NaiveDate> = attendances::table
+                .filter(attendances::employee_id.eq(request.employee_id))
+                .filter(attendances::date.between(request.start_date, request.end_date))
+                .select(attendances::date)
+                .load(conn)?;
+            let leave_days: Vec<NewAttendance> = working_days(request.start_date, request.end_date)
+                .into_iter()
+                .filter(|day| !recorded.contains(day))
+                .map(|date| NewAttendance {
+                    employee_id: request.employee_id,
+                    date,
+                    check_in: None,
+                    check_out: None,
+                    break_time: None,
+                    overtime_hours: None,
+                    status: AttendanceStatus::Holiday.to_string(),
+                    notes: Some(format!("Leave request {}", request.id)),
+                })
+                .collect();
+            diesel::insert_into(attendances::table)
+                .values(&leave_days)
+                .execute(conn)?;
+            Ok(())
+        })?;
+
+        QueryCache::current().invalidate(&["hr"]);
+        tracing::info!("Leave request {} approved", request.id);
+        Self::find_request(conn, request.id)
+    }
+
+    pub fn reject(
+        conn: &mut DatabaseConnection,
+        request_id: i32,
+        approver: &AuthenticatedUser,
+        reason: &str,
+    ) -> Result<LeaveRequest> {
+        validate_required_string(reason, "Reason")?;
+        let request = Self::pending_for_decision(conn, request_id, approver)?;
+
+        diesel::update(leave_requests::table.find(request.id))
+            .set((
+                leave_requests::status.eq(LeaveStatus::Rejected.to_string()),
+                leave_requests::decided_by.eq(Some(approver.id)),
+                leave_requests::decided_at.eq(Some(Utc::now().naive_utc())),
+                leave_requests::decision_note.eq(Some(reason)),
+            ))
+            .execute(conn)?;
+
+        tracing::info!("Leave request {} rejected", request.id);
+        Self::find_request(conn, request.id)
+    }
+
+    /// Load a request that `approver` may decide on
+    fn pending_for_decision(
+        conn: &mut DatabaseConnection,
+        request_id: i32,
+        approver: &AuthenticatedUser,
+    ) -> Result<LeaveRequest> {
+        if !role_satisfies(&approver.role, LEAVE_APPROVER_ROLE) {
+            return Err(CLIERPError::Authorization(format!(
+                "Deciding on leave requires role: {}",
+                LEAVE_APPROVER_ROLE
+            )));
+        }
+        let request = Self::find_request(conn, request_id)?;
+        if request.status != LeaveStatus::Pending.to_string() {
+            return Err(CLIERPError::ValidationError(format!(
+                "Leave request {} is already {}",
+                request.id, request.status
+            )));
+        }
+        if approver.employee_id == Some(request.employee_id) {
+            return Err(CLIERPError::Authorization(
+                "Leave cannot be approved or rejected by the employee taking it".to_string(),
+            ));
+        }
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_working_days() {
+        let date = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        // Thursday 2 to Tuesday 7 skips the weekend of the 4th and 5th
+        assert_eq!(
+            working_days(date(2), date(7)),
+            vec![date(2), date(3), date(6), date(7)]
+        );
+        assert!(working_days(date(4), date(5)).is_empty());
+        assert_eq!(working_days(date(6), date(6)), vec![date(6)]);
+    }
+}
@@ -1,9 +1,19 @@
 pub mod attendance;
+pub mod compensation;
 pub mod department;
+pub mod documents;
 pub mod employee;
+pub mod leave;
 pub mod payroll;
+pub mod remote;
+pub mod team;
 
 pub use attendance::*;
+pub use compensation::*;
 pub use department::*;
+pub use documents::*;
 pub use employee::*;
+pub use leave::*;
 pub use payroll::*;
+pub use remote::*;
+pub use team::*;
@@ -1,11 +1,23 @@
-use chrono::{Local, NaiveDate};
+use chrono::NaiveDate;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::core::config::PayrollConfig;
 use crate::core::error::CLIERPError;
 use crate::core::result::CLIERPResult;
 use crate::database::models::{Attendance, Employee, NewPayroll, Payroll, PayrollStatus};
-use crate::database::schema::{attendances, employees, payrolls};
+use crate::database::schema::{accounts, attendances, employees, payrolls};
+use crate::modules::finance::{JournalEntryRequest, JournalLine, JournalService};
+use crate::modules::system::sod::{SodRule, SodService};
+use crate::utils::fiscal::FiscalCalendar;
+use crate::utils::timezone::DisplayTimezone;
+use crate::utils::cache::QueryCache;
+use crate::utils::formatting::format_currency;
+
+/// Reference of the journal posted for a committed payroll run
+pub fn payroll_run_reference(period: &str) -> String {
+    format!("payroll:{}", period)
+}
 
 pub struct PayrollService;
 
@@ -27,39 +39,8 @@ impl PayrollService {
             .optional()?
             .ok_or_else(|| CLIERPError::NotFound("Employee not found".to_string()))?;
 
-        // Parse period (YYYY-MM format)
-        let parts: Vec<&str> = period.split('-').collect();
-        if parts.len() != 2 {
-            return Err(CLIERPError::ValidationError(
-                "Period must be in YYYY-MM format".to_string(),
-            ));
-        }
-
-        let year: i32 = parts[0]
-            .parse()
-            .map_err(|_| CLIERPError::ValidationError("Invalid year in period".to_string()))?;
-        let month: u32 = parts[1]
-            .parse()
-            .map_err(|_| CLIERPError::ValidationError("Invalid month in period".to_string()))?;
-
-        if month < 1 || month > 12 {
-            return Err(CLIERPError::ValidationError(
-                "Month must be between 1 and 12".to_string(),
-            ));
-        }
-
-        // Get attendance data for the period
-        let start_date = NaiveDate::from_ymd_opt(year, month, 1)
-            .ok_or_else(|| CLIERPError::ValidationError("Invalid date".to_string()))?;
-
-        let end_date = if month == 12 {
-            NaiveDate::from_ymd_opt(year + 1, 1, 1)
-        } else {
-            NaiveDate::from_ymd_opt(year, month + 1, 1)
-        }
-        .ok_or_else(|| CLIERPError::ValidationError("Invalid date".to_string()))?
-        .pred_opt()
-        .ok_or_else(|| CLIERPError::ValidationError("Invalid date".to_string()))?;
+        // Resolve period (YYYY-MM or fiscal period such as FY2025-P03)
+        let (start_date, end_date) = FiscalCalendar::current().resolve(&period)?;
 
         let attendances = attendances::table
             .filter(attendances::employee_id.eq(employee_id))
@@ -68,7 +49,7 @@ impl PayrollService {
             .load::<Attendance>(conn)?;
 
         // Calculate overtime hours
-        let total_overtime_hours: f32 = attendances.iter().map(|a| a.overtime_hours).sum();
+        let total_overtime_hours: f32 = attendances.iter().filter_map(|a| a.overtime_hours).sum();
 
         // Calculate overtime pay (assuming 1.5x hourly rate)
         let daily_rate = employee.salary / 30; // Approximate daily rate
@@ -104,6 +85,7 @@ impl PayrollService {
         calculation: PayrollCalculation,
         bonuses: Option<i32>,
         additional_deductions: Option<i32>,
+        prepared_by: Option<i32>,
     ) -> CLIERPResult<Payroll> {
         // Check if payroll already exists for this period
         let existing = payrolls::table
@@ -128,12 +110,13 @@ impl PayrollService {
             employee_id: calculation.employee_id,
             period: calculation.period,
             base_salary: calculation.base_salary,
-            overtime_pay: calculation.overtime_pay,
-            bonuses: final_bonuses,
-            deductions: total_deductions,
+            overtime_pay: Some(calculation.overtime_pay),
+            bonuses: Some(final_bonuses),
+            deductions: Some(total_deductions),
             net_salary,
             payment_date: None,
             status: PayrollStatus::Pending.to_string(),
+            prepared_by,
         };
 
         diesel::insert_into(payrolls::table)
@@ -166,19 +149,38 @@ impl PayrollService {
         Ok(payroll)
     }
 
-    /// Pay payroll (mark as paid and set payment date)
+    /// Pay payroll (mark as paid and set payment date). `sod_override` is the
+    /// reason for letting the preparer release the payment, where the SoD
+    /// rule allows it.
     pub fn pay_payroll(
         &self,
         conn: &mut SqliteConnection,
         payroll_id: i32,
+        paid_by: i32,
+        sod_override: Option<&str>,
     ) -> CLIERPResult<Payroll> {
-        let today = Local::now().date_naive();
+        let payroll = payrolls::table
+            .find(payroll_id)
+            .first::<Payroll>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Payroll with ID {} not found", payroll_id)))?;
+        SodService::check(
+            conn,
+            SodRule::PayrollPreparePay,
+            payroll.id,
+            payroll.prepared_by,
+            paid_by,
+            sod_override,
+        )?;
+
+        let today = DisplayTimezone::current().today();
 
         diesel::update(payrolls::table)
             .filter(payrolls::id.eq(payroll_id))
             .set((
                 payrolls::status.eq(PayrollStatus::Paid.to_string()),
                 payrolls::payment_date.eq(Some(today)),
+                payrolls::paid_by.eq(Some(paid_by)),
             ))
             .execute(conn)?;
 
@@ -281,6 +283,111 @@ impl PayrollService {
         Ok(calculations)
     }
 
+    /// Calculate the payroll of every active employee for `period` without
+    /// saving anything, so the run can be reviewed before it is committed.
+    /// Employees who already have a payroll for the period, or who joined
+    /// after it ended, are listed as skipped.
+    pub fn prepare_run(&self, conn: &mut SqliteConnection, period: &str) -> CLIERPResult<PayrollRun> {
+        let (start_date, end_date) = FiscalCalendar::current().resolve(period)?;
+        let employees = employees::table
+            .filter(employees::status.eq("active"))
+            .order(employees::employee_code.asc())
+            .load::<Employee>(conn)?;
+        let existing: Vec<i32> = payrolls::table
+            .filter(payrolls::period.eq(period))
+            .select(payrolls::employee_id)
+            .load::<i32>(conn)?;
+
+        let mut run = PayrollRun {
+            period: period.to_string(),
+            start_date,
+            end_date,
+            lines: Vec::new(),
+            skipped: Vec::new(),
+        };
+        for employee in employees {
+            let reason = if existing.contains(&employee.id) {
+                Some("payroll already exists for the period".to_string())
+            } else if employee.hire_date > end_date {
+                Some(format!("hired after the period ({})", employee.hire_date))
+            } else {
+                None
+            };
+            if let Some(reason) = reason {
+                run.skipped.push(SkippedPayroll { employee, reason });
+                continue;
+            }
+            match self.calculate_payroll(conn, employee.id, period.to_string()) {
+                Ok(calculation) => run.lines.push(PayrollRunLine { employee, calculation }),
+                Err(e) => run.skipped.push(SkippedPayroll { employee, reason: e.to_string() }),
+            }
+        }
+        Ok(run)
+    }
+
+    /// Save the payrolls of a reviewed run and post its journal: gross pay
+    /// to the expense account, withheld tax and net pay to the tax and
+    /// payable accounts. Nothing is saved if any step fails.
+    pub fn commit_run(
+        &self,
+        conn: &mut SqliteConnection,
+        run: &PayrollRun,
+        accounts: &PayrollAccounts,
+        prepared_by: Option<i32>,
+    ) -> CLIERPResult<Vec<Payroll>> {
+        if run.lines.is_empty() {
+            return Err(CLIERPError::ValidationError(format!(
+                "No payrolls to commit for period {}",
+                run.period
+            )));
+        }
+
+        let payrolls = conn.transaction::<_, CLIERPError, _>(|conn| {
+            let payrolls = run
+                .lines
+                .iter()
+                .map(|line| self.generate_payroll(conn, line.calculation.clone(), None, None, prepared_by))
+                .collect::<CLIERPResult<Vec<Payroll>>>()?;
+
+            let mut lines = Vec::new();
+            for (code, amount, debit_credit) in accounts.journal(run.gross_total(), run.deduction_total(), run.net_total()) {
+                if amount == 0 {
+                    continue;
+                }
+                let account_id = accounts::table
+                    .filter(accounts::account_code.eq(code))
+                    .select(accounts::id)
+                    .first::<i32>(conn)
+                    .optional()?
+                    .ok_or_else(|| CLIERPError::NotFound(format!("Payroll account '{}' not found", code)))?;
+                lines.push(match debit_credit {
+                    "debit" => JournalLine::debit(account_id, amount),
+                    _ => JournalLine::credit(account_id, amount),
+                });
+            }
+            JournalService::post_entry(
+                conn,
+                JournalEntryRequest {
+                    entry_date: run.end_date,
+                    description: format!("Payroll {}", run.period),
+                    reference: Some(payroll_run_reference(&run.period)),
+                    lines,
+                },
+                prepared_by,
+            )?;
+            Ok(payrolls)
+        })?;
+        QueryCache::current().invalidate(&["hr", "finance"]);
+
+        tracing::info!(
+            "Committed payroll run {}: {} payrolls, net {}",
+            run.period,
+            payrolls.len(),
+            run.net_total()
+        );
+        Ok(payrolls)
+    }
+
     /// Generate payslip data
     pub fn generate_payslip(
         &self,
@@ -300,12 +407,12 @@ impl PayrollService {
             position: payroll_with_employee.employee.position,
             period: payroll_with_employee.payroll.period,
             base_salary: payroll_with_employee.payroll.base_salary,
-            overtime_pay: payroll_with_employee.payroll.overtime_pay,
-            bonuses: payroll_with_employee.payroll.bonuses,
+            overtime_pay: payroll_with_employee.payroll.overtime_pay.unwrap_or(0),
+            bonuses: payroll_with_employee.payroll.bonuses.unwrap_or(0),
             gross_salary: payroll_with_employee.payroll.base_salary
-                + payroll_with_employee.payroll.overtime_pay
-                + payroll_with_employee.payroll.bonuses,
-            deductions: payroll_with_employee.payroll.deductions,
+                + payroll_with_employee.payroll.overtime_pay.unwrap_or(0)
+                + payroll_with_employee.payroll.bonuses.unwrap_or(0),
+            deductions: payroll_with_employee.payroll.deductions.unwrap_or(0),
             net_salary: payroll_with_employee.payroll.net_salary,
             payment_date: payroll_with_employee.payroll.payment_date,
             status: payroll_with_employee.payroll.status,
@@ -362,6 +469,93 @@ pub struct Payslip {
     pub status: String,
 }
 
+impl std::fmt::Display for Payslip {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Payslip - {}", self.period)?;
+        writeln!(f, "  Employee: {} ({})", self.employee_name, self.employee_code)?;
+        writeln!(f, "  Position: {}", self.position)?;
+        writeln!(f, "  Base Salary: {}", format_currency(self.base_salary))?;
+        writeln!(f, "  Overtime Pay: {}", format_currency(self.overtime_pay))?;
+        writeln!(f, "  Bonuses: {}", format_currency(self.bonuses))?;
+        writeln!(f, "  Gross Salary: {}", format_currency(self.gross_salary))?;
+        writeln!(f, "  Deductions: {}", format_currency(self.deductions))?;
+        writeln!(f, "  Net Salary: {}", format_currency(self.net_salary))?;
+        match self.payment_date {
+            Some(date) => write!(f, "  Status: {} (paid {})", self.status, date),
+            None => write!(f, "  Status: {}", self.status),
+        }
+    }
+}
+
+/// Payrolls of every active employee for one period, calculated but not saved
+#[derive(Debug, Clone)]
+pub struct PayrollRun {
+    pub period: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub lines: Vec<PayrollRunLine>,
+    pub skipped: Vec<SkippedPayroll>,
+}
+
+impl PayrollRun {
+    pub fn gross_total(&self) -> i32 {
+        self.lines.iter().map(|l| l.calculation.gross_salary).sum()
+    }
+
+    pub fn deduction_total(&self) -> i32 {
+        self.lines.iter().map(|l| l.calculation.total_deductions).sum()
+    }
+
+    pub fn net_total(&self) -> i32 {
+        self.lines.iter().map(|l| l.calculation.net_salary).sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PayrollRunLine {
+    pub employee: Employee,
+    pub calculation: PayrollCalculation,
+}
+
+/// Active employee left out of a payroll run, and why
+#[derive(Debug, Clone)]
+pub struct SkippedPayroll {
+    pub employee: Employee,
+    pub reason: String,
+}
+
+/// Account codes a committed payroll run is posted to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayrollAccounts {
+    pub expense: String,
+    pub payable: String,
+    pub tax: String,
+}
+
+impl PayrollAccounts {
+    pub fn from_config(config: &PayrollConfig) -> CLIERPResult<Self> {
+        match (&config.expense_account, &config.payable_account) {
+            (Some(expense), Some(payable)) => Ok(Self {
+                expense: expense.clone(),
+                payable: payable.clone(),
+                tax: config.tax_account.clone().unwrap_or_else(|| payable.clone()),
+            }),
+            _ => Err(CLIERPError::Configuration(config::ConfigError::Message(
+                "Set payroll.expense_account and payroll.payable_account (CLIERP_PAYROLL__EXPENSE_ACCOUNT, CLIERP_PAYROLL__PAYABLE_ACCOUNT) to commit payroll runs".to_string(),
+            ))),
+        }
+    }
+
+    /// `(account code, amount, debit/credit)` legs of the payroll journal
+    fn journal(&self, gross: i32, deductions: i32, net: i32) -> [(&str, i32, &'static str); 3] {
+        [
+            (self.expense.as_str(), gross, "debit"),
+            (self.tax.as_str(), deductions, "credit"),
+            (self.payable.as_str(), net, "credit"),
+        ]
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GeneratePayrollRequest {
     pub employee_id: i32,
@@ -374,3 +568,26 @@ pub struct GeneratePayrollRequest {
 pub struct ProcessPayrollRequest {
     pub payroll_id: i32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payroll_accounts() {
+        let mut config = PayrollConfig {
+            expense_account: Some("5100".to_string()),
+            payable_account: None,
+            tax_account: None,
+        };
+        assert!(PayrollAccounts::from_config(&config).is_err());
+
+        config.payable_account = Some("2100".to_string());
+        let accounts = PayrollAccounts::from_config(&config).unwrap();
+        assert_eq!(accounts.tax, "2100");
+        assert_eq!(
+            accounts.journal(1_100, 100, 1_000),
+            [("5100", 1_100, "debit"), ("2100", 100, "credit"), ("2100", 1_000, "credit")]
+        );
+    }
+}
@@ -0,0 +1,327 @@
+use diesel::prelude::*;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::models::{
+    Attendance, AttendancePunch, NewAttendancePunch, NewRemoteWorkPolicy, RemoteWorkPolicy,
+};
+use crate::database::schema::{attendance_punches, employees, remote_work_policies};
+use crate::modules::hr::AttendanceService;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+const EARTH_RADIUS_METERS: f64 = 6_371_000.0;
+
+/// Location and device sent with a check-in or check-out
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PunchDetails {
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub device_fingerprint: Option<String>,
+}
+
+impl PunchDetails {
+    fn coordinates(&self) -> Result<Option<(f64, f64)>> {
+        match (self.latitude, self.longitude) {
+            (None, None) => Ok(None),
+            (Some(latitude), Some(longitude))
+                if (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude) =>
+            {
+                Ok(Some((latitude, longitude)))
+            }
+            (Some(_), Some(_)) => Err(CLIERPError::ValidationError(
+                "Latitude must be within ±90 and longitude within ±180".to_string(),
+            )),
+            _ => Err(CLIERPError::ValidationError(
+                "Give both latitude and longitude".to_string(),
+            )),
+        }
+    }
+
+    fn device(&self) -> Option<&str> {
+        self.device_fingerprint.as_deref().map(str::trim).filter(|d| !d.is_empty())
+    }
+}
+
+/// Rules to store for a remote employee; `site` is the latitude, longitude
+/// and radius in meters of the area punches must be made in
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RemotePolicySettings {
+    pub require_location: bool,
+    pub require_device: bool,
+    pub registered_device: Option<String>,
+    pub site: Option<(f64, f64, i32)>,
+}
+
+/// Great-circle distance in meters between two coordinates
+pub fn distance_meters(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lon1) = (from.0.to_radians(), from.1.to_radians());
+    let (lat2, lon2) = (to.0.to_radians(), to.1.to_radians());
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_METERS * a.sqrt().asin()
+}
+
+/// Check a punch against the employee's policy, if any. Returns the
+/// distance from the policy site when both it and the punch location are known.
+pub fn check_punch(policy: Option<&RemoteWorkPolicy>, punch: &PunchDetails) -> Result<Option<f64>> {
+    let coordinates = punch.coordinates()?;
+    let Some(policy) = policy else {
+        return Ok(None);
+    };
+
+    let site = match (policy.site_latitude, policy.site_longitude) {
+        (Some(latitude), Some(longitude)) => Some((latitude, longitude)),
+        _ => None,
+    };
+    if (policy.require_location || site.is_some()) && coordinates.is_none() {
+        return Err(CLIERPError::ValidationError(
+            "This employee's check-in policy requires GPS coordinates".to_string(),
+        ));
+    }
+    let device = punch.device();
+    if (policy.require_device || policy.registered_device.is_some()) && device.is_none() {
+        return Err(CLIERPError::ValidationError(
+            "This employee's check-in policy requires a device fingerprint".to_string(),
+        ));
+    }
+    if let (Some(registered), Some(device)) = (policy.registered_device.as_deref(), device) {
+        if registered != device {
+            return Err(CLIERPError::ValidationError(
+                "Punch is not from the employee's registered device".to_string(),
+            ));
+        }
+    }
+
+    let distance = site.zip(coordinates).map(|(site, at)| distance_meters(site, at));
+    if let (Some(distance), Some(radius)) = (distance, policy.radius_meters) {
+        if distance > radius as f64 {
+            return Err(CLIERPError::ValidationError(format!(
+                "Punch is {:.0} m from the work site, outside the allowed {} m",
+                distance, radius
+            )));
+        }
+    }
+    Ok(distance)
+}
+
+/// Check-in and check-out with the GPS coordinates and device of each punch.
+///
+/// Coordinates and device fingerprint are optional and recorded when given.
+/// Employees designated as remote get a policy that can require either, bind
+/// punches to one registered device, or limit them to a radius around a
+/// work site; punches that break the policy are refused.
+pub struct RemoteCheckinService;
+
+impl RemoteCheckinService {
+    /// Create or replace the check-in policy of an employee
+    pub fn set_policy(
+        conn: &mut SqliteConnection,
+        employee_id: i32,
+        settings: RemotePolicySettings,
+        updated_by: Option<i32>,
+    ) -> Result<RemoteWorkPolicy> {
+        employees::table
+            .find(employee_id)
+            .select(employees::id)
+            .first::<i32>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Employee {} not found", employee_id)))?;
+        if let Some((latitude, longitude, radius)) = settings.site {
+            PunchDetails {
+                latitude: Some(latitude),
+                longitude: Some(longitude),
+                device_fingerprint: None,
+            }
+            .coordinates()?;
+            if radius <= 0 {
+                return Err(CLIERPError::ValidationError("Radius must be positive".to_string()));
+            }
+        }
+        let registered_device = settings
+            .registered_device
+            .as_deref()
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .map(|d| d.to_string());
+
+        diesel::delete(remote_work_policies::table.filter(remote_work_policies::employee_id.eq(employee_id)))
+            .execute(conn)?;
+        diesel::insert_into(remote_work_policies::table)
+            .values(&NewRemoteWorkPolicy {
+                employee_id,
+                require_location: settings.require_location,
+                require_device: settings.require_device,
+                registered_device,
+                site_latitude: settings.site.map(|s| s.0),
+                site_longitude: settings.site.map(|s| s.1),
+                radius_meters: settings.site.map(|s| s.2),
+                updated_by,
+            })
+            .execute(conn)?;
+
+        tracing::info!("Remote check-in policy set for employee {}", employee_id);
+        Self::policy(conn, employee_id)?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Policy of employee {} not found", employee_id)))
+    }
+
+    pub fn policy(conn: &mut SqliteConnection, employee_id: i32) -> Result<Option<RemoteWorkPolicy>> {
+        remote_work_policies::table
+            .filter(remote_work_policies::employee_id.eq(employee_id))
+            .first::<RemoteWorkPolicy>(conn)
+            .optional()
+            .map_err(Into::into)
+    }
+
+    pub fn policies(conn: &mut SqliteConnection) -> Result<Vec<RemoteWorkPolicy>> {
+        remote_work_policies::table
+            .order(remote_work_policies::employee_id.asc())
+            .load::<RemoteWorkPolicy>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Stop treating an employee as remote
+    pub fn remove_policy(conn: &mut SqliteConnection, employee_id: i32) -> Result<()> {
+        let removed = diesel::delete(
+            remote_work_policies::table.filter(remote_work_policies::employee_id.eq(employee_id)),
+        )
+        .execute(conn)?;
+        if removed == 0 {
+            return Err(CLIERPError::NotFound(format!(
+                "Employee {} has no remote check-in policy",
+                employee_id
+            )));
+        }
+        tracing::info!("Remote check-in policy removed for employee {}", employee_id);
+        Ok(())
+    }
+
+    pub fn check_in(
+        conn: &mut SqliteConnection,
+        employee_id: i32,
+        punch: &PunchDetails,
+    ) -> Result<(Attendance, AttendancePunch)> {
+        Self::punch(conn, employee_id, punch, "in")
+    }
+
+    pub fn check_out(
+        conn: &mut SqliteConnection,
+        employee_id: i32,
+        punch: &PunchDetails,
+    ) -> Result<(Attendance, AttendancePunch)> {
+        Self::punch(conn, employee_id, punch, "out")
+    }
+
+    /// Punches recorded for an attendance record, in order
+    pub fn punches(conn: &mut SqliteConnection, attendance_id: i32) -> Result<Vec<AttendancePunch>> {
+        attendance_punches::table
+            .filter(attendance_punches::attendance_id.eq(attendance_id))
+            .order(attendance_punches::punched_at.asc())
+            .load::<AttendancePunch>(conn)
+            .map_err(Into::into)
+    }
+
+    fn punch(
+        conn: &mut SqliteConnection,
+        employee_id: i32,
+        punch: &PunchDetails,
+        punch_type: &str,
+    ) -> Result<(Attendance, AttendancePunch)> {
+        let policy = Self::policy(conn, employee_id)?;
+        let distance = check_punch(policy.as_ref(), punch)?;
+
+        conn.transaction::<_, CLIERPError, _>(|conn| {
+            let service = AttendanceService::new();
+            let attendance = if punch_type == "in" {
+                service.check_in(conn, employee_id)?
+            } else {
+                service.check_out(conn, employee_id)?
+            };
+            diesel::insert_into(attendance_punches::table)
+                .values(&NewAttendancePunch {
+                    attendance_id: attendance.id,
+                    employee_id,
+                    punch_type: punch_type.to_string(),
+                    latitude: punch.latitude,
+                    longitude: punch.longitude,
+                    device_fingerprint: punch.device().map(|d| d.to_string()),
+                    distance_meters: distance,
+                })
+                .execute(conn)?;
+            let recorded = attendance_punches::table
+                .order(attendance_punches::id.desc())
+                .first::<AttendancePunch>(conn)?;
+
+            tracing::info!(
+                "Employee {} punched {} for attendance {} (location: {}, device: {})",
+                employee_id,
+                punch_type,
+                attendance.id,
+                recorded.latitude.is_some(),
+                recorded.device_fingerprint.is_some()
+            );
+            Ok((attendance, recorded))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn policy(require_location: bool, registered_device: Option<&str>, site: Option<(f64, f64, i32)>) -> RemoteWorkPolicy {
+        RemoteWorkPolicy {
+            id: 1,
+            employee_id: 1,
+            require_location,
+            require_device: false,
+            registered_device: registered_device.map(|d| d.to_string()),
+            site_latitude: site.map(|s| s.0),
+            site_longitude: site.map(|s| s.1),
+            radius_meters: site.map(|s| s.2),
+            updated_by: None,
+            created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+        }
+    }
+
+    fn punch(latitude: Option<f64>, longitude: Option<f64>, device: Option<&str>) -> PunchDetails {
+        PunchDetails {
+            latitude,
+            longitude,
+            device_fingerprint: device.map(|d| d.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_distance_meters() {
+        // Seoul City Hall to Gangnam Station is about 8.9 km
+        let distance = distance_meters((37.5663, 126.9779), (37.4979, 127.0276));
+        assert!((distance - 8_900.0).abs() < 200.0, "{}", distance);
+        assert_eq!(distance_meters((37.5, 127.0), (37.5, 127.0)), 0.0);
+    }
+
+    #[test]
+    fn test_check_punch() {
+        // Without a policy anything valid goes, and nothing is required
+        assert!(check_punch(None, &PunchDetails::default()).unwrap().is_none());
+        assert!(check_punch(None, &punch(Some(91.0), Some(0.0), None)).is_err());
+        assert!(check_punch(None, &punch(Some(37.5), None, None)).is_err());
+
+        let located = policy(true, None, None);
+        assert!(check_punch(Some(&located), &PunchDetails::default()).is_err());
+        assert!(check_punch(Some(&located), &punch(Some(37.5), Some(127.0), None)).is_ok());
+
+        let bound = policy(false, Some("laptop-42"), None);
+        assert!(check_punch(Some(&bound), &punch(None, None, Some("laptop-42"))).is_ok());
+        assert!(check_punch(Some(&bound), &punch(None, None, Some("phone-7"))).is_err());
+        assert!(check_punch(Some(&bound), &PunchDetails::default()).is_err());
+
+        let fenced = policy(false, None, Some((37.5663, 126.9779, 500)));
+        let near = check_punch(Some(&fenced), &punch(Some(37.5670), Some(126.9785), None)).unwrap();
+        assert!(near.is_some_and(|d| d < 500.0));
+        assert!(check_punch(Some(&fenced), &punch(Some(37.4979), Some(127.0276), None)).is_err());
+        assert!(check_punch(Some(&fenced), &PunchDetails::default()).is_err());
+    }
+}
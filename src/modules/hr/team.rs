@@ -0,0 +1,151 @@
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::models::{Attendance, Employee, LeaveRequest, SalaryRaiseStatus};
+use crate::database::schema::{activities, attendances, departments, employees, salary_raises};
+use crate::database::DatabaseConnection;
+use crate::modules::hr::LeaveService;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Days ahead in which an annual review counts as upcoming
+pub const REVIEW_HORIZON_DAYS: i64 = 30;
+
+/// Next work anniversary on or after `today`, when the annual review is due.
+/// Employees hired on 29 February are reviewed on 28 February in other years.
+pub fn next_review_date(hire_date: NaiveDate, today: NaiveDate) -> NaiveDate {
+    let anniversary = |year: i32| {
+        NaiveDate::from_ymd_opt(year, hire_date.month(), hire_date.day())
+            .or_else(|| NaiveDate::from_ymd_opt(year, 2, 28))
+            .expect("valid anniversary")
+    };
+    let this_year = anniversary(today.year());
+    if this_year >= today {
+        this_year
+    } else {
+        anniversary(today.year() + 1)
+    }
+}
+
+/// Today at a glance for one direct report
+#[derive(Debug, Clone)]
+pub struct TeamMemberStatus {
+    pub employee: Employee,
+    /// Today's attendance, if any was recorded
+    pub attendance: Option<Attendance>,
+    pub pending_leave: Vec<LeaveRequest>,
+    /// Uncompleted activities assigned to the employee
+    pub open_tasks: i64,
+    /// Open tasks dated before today
+    pub overdue_tasks: i64,
+    pub next_review: NaiveDate,
+    /// Raises proposed for the employee that still need a decision
+    pub pending_raises: i64,
+}
+
+impl TeamMemberStatus {
+    pub fn review_upcoming(&self, today: NaiveDate) -> bool {
+        (self.next_review - today).num_days() <= REVIEW_HORIZON_DAYS
+    }
+}
+
+/// Team views for managers.
+///
+/// A manager's direct reports are the active employees of the departments
+/// they manage, as set by `departments.manager_id`.
+pub struct TeamService;
+
+impl TeamService {
+    pub fn direct_reports(conn: &mut DatabaseConnection, manager_id: i32) -> Result<Vec<Employee>> {
+        let managed: Vec<i32> = departments::table
+            .filter(departments::manager_id.eq(manager_id))
+            .select(departments::id)
+            .load(conn)?;
+        if managed.is_empty() {
+            return Err(CLIERPError::NotFound(format!(
+                "Employee {} does not manage any department",
+                manager_id
+            )));
+        }
+
+        employees::table
+            .filter(employees::department_id.eq_any(&managed))
+            .filter(employees::status.eq("active"))
+            .filter(employees::id.ne(manager_id))
+            .order(employees::name.asc())
+            .load::<Employee>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Attendance, pending leave, open tasks and reviews of a manager's
+    /// direct reports on `today`
+    pub fn overview(
+        conn: &mut DatabaseConnection,
+        manager_id: i32,
+        today: NaiveDate,
+    ) -> Result<Vec<TeamMemberStatus>> {
+        let reports = Self::direct_reports(conn, manager_id)?;
+        let ids: Vec<i32> = reports.iter().map(|e| e.id).collect();
+
+        let attendance: Vec<Attendance> = attendances::table
+            .filter(attendances::employee_id.eq_any(&ids))
+            .filter(attendances::date.eq(today))
+            .load(conn)?;
+        let pending_leave = LeaveService::pending_for(conn, &ids)?;
+        let open_tasks: Vec<(Option<i32>, NaiveDate)> = activities::table
+            .filter(activities::assigned_to.eq_any(&ids))
+            .filter(activities::completed.eq(false))
+            .select((activities::assigned_to, activities::activity_date))
+            .load::<(Option<i32>, NaiveDateTime)>(conn)?
+            .into_iter()
+            .map(|(assignee, at)| (assignee, at.date()))
+            .collect();
+        let pending_raises: Vec<i32> = salary_raises::table
+            .filter(salary_raises::employee_id.eq_any(&ids))
+            .filter(salary_raises::status.eq(SalaryRaiseStatus::Pending.to_string()))
+            .select(salary_raises::employee_id)
+            .load(conn)?;
+
+        Ok(reports
+            .into_iter()
+            .map(|employee| {
+                let tasks: Vec<NaiveDate> = open_tasks
+                    .iter()
+                    .filter(|(assignee, _)| *assignee == Some(employee.id))
+                    .map(|(_, date)| *date)
+                    .collect();
+                TeamMemberStatus {
+                    attendance: attendance.iter().find(|a| a.employee_id == employee.id).cloned(),
+                    pending_leave: pending_leave
+                        .iter()
+                        .filter(|r| r.employee_id == employee.id)
+                        .cloned()
+                        .collect(),
+                    open_tasks: tasks.len() as i64,
+                    overdue_tasks: tasks.iter().filter(|date| **date < today).count() as i64,
+                    next_review: next_review_date(employee.hire_date, today),
+                    pending_raises: pending_raises.iter().filter(|id| **id == employee.id).count() as i64,
+                    employee,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_review_date() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(next_review_date(date(2020, 3, 15), date(2025, 1, 10)), date(2025, 3, 15));
+        assert_eq!(next_review_date(date(2020, 3, 15), date(2025, 3, 15)), date(2025, 3, 15));
+        assert_eq!(next_review_date(date(2020, 3, 15), date(2025, 3, 16)), date(2026, 3, 15));
+        assert_eq!(next_review_date(date(2020, 2, 29), date(2025, 1, 1)), date(2025, 2, 28));
+        assert_eq!(next_review_date(date(2020, 2, 29), date(2027, 12, 1)), date(2028, 2, 29));
+    }
+}
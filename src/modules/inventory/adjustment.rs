@@ -0,0 +1,326 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use std::collections::HashMap;
+
+use super::product::ProductService;
+use crate::core::auth::AuthenticatedUser;
+use crate::core::config::StockApprovalConfig;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::core::workflow::{
+    create_default_workflows, role_satisfies, WorkflowAction, WorkflowContext, WorkflowEngine,
+};
+use crate::database::schema::{products, stock_adjustment_requests, stock_movements};
+use crate::database::{
+    get_connection, DatabaseConnection, NewStockAdjustmentRequest, Product, StockAdjustmentRequest,
+    StockAdjustmentStatus,
+};
+use crate::modules::system::sod::{SodRule, SodService};
+use crate::utils::validation::validate_required_string;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Workflow that large manual stock changes go through
+pub const STOCK_ADJUSTMENT_WORKFLOW: &str = "stock_adjustment_approval";
+/// Step of `STOCK_ADJUSTMENT_WORKFLOW` that applies the change
+const APPROVE_STEP: &str = "approve_stock_adjustment";
+/// `stock_movements.reference_type` of movements recorded by an approval;
+/// `reference_id` is the adjustment request
+pub const APPROVED_ADJUSTMENT_REFERENCE: &str = "stock_adjustment_request";
+
+/// Holds manual stock changes above the configured quantity or value until
+/// a supervisor approves them.
+///
+/// A queued request leaves stock untouched. Approval runs the
+/// `stock_adjustment_approval` workflow, whose approve step records the
+/// stock movement. Approving one's own request is subject to the
+/// `stock_adjustment_request_approve` segregation of duties rule.
+pub struct StockApprovalService;
+
+impl StockApprovalService {
+    /// Estimated value of changing `product` by `quantity` units, at
+    /// `unit_cost` when given and the product cost price otherwise
+    pub fn change_value(product: &Product, quantity: i32, unit_cost: Option<i32>) -> i64 {
+        quantity.unsigned_abs() as i64 * unit_cost.unwrap_or(product.cost_price) as i64
+    }
+
+    /// Whether a change of `quantity` units worth `value` is over a configured limit
+    pub fn requires_approval(config: &StockApprovalConfig, quantity: i32, value: i64) -> bool {
+        config
+            .max_quantity
+            .is_some_and(|max| quantity.unsigned_abs() > max)
+            || config.max_value.is_some_and(|max| value.abs() > max)
+    }
+
+    /// Queue a stock change for approval. `quantity` is signed the way
+    /// `ProductService::update_stock` expects it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn request(
+        conn: &mut DatabaseConnection,
+        product: &Product,
+        movement_type: &str,
+        quantity: i32,
+        unit_cost: Option<i32>,
+        reference: Option<&str>,
+        notes: Option<&str>,
+        requested_by: Option<i32>,
+    ) -> Result<StockAdjustmentRequest> {
+        if !["in", "out", "adjustment"].contains(&movement_type) || quantity == 0 {
+            return Err(CLIERPError::ValidationError(
+                "An adjustment request needs a non-zero in, out or adjustment quantity".to_string(),
+            ));
+        }
+        // Refuse requests that could not be applied even now
+        if product.current_stock + quantity < 0 {
+            return Err(CLIERPError::ValidationError(format!(
+                "Only {} {} of {} on hand",
+                product.current_stock, product.unit, product.sku
+            )));
+        }
+
+        let value = Self::change_value(product, quantity, unit_cost);
+        diesel::insert_into(stock_adjustment_requests::table)
+            .values(&NewStockAdjustmentRequest {
+                product_id: product.id,
+                movement_type: movement_type.to_string(),
+                quantity,
+                unit_cost,
+                value: i32::try_from(value).unwrap_or(i32::MAX),
+                reference: reference.map(|s| s.to_string()),
+                notes: notes.map(|s| s.to_string()),
+                requested_by,
+            })
+            .execute(conn)?;
+
+        let request = stock_adjustment_requests::table
+            .order(stock_adjustment_requests::id.desc())
+            .first::<StockAdjustmentRequest>(conn)?;
+        tracing::info!(
+            "Queued stock adjustment request {} for {} ({} {}) awaiting approval",
+            request.id,
+            product.sku,
+            movement_type,
+            quantity
+        );
+        Ok(request)
+    }
+
+    /// Requests with their products, oldest first, optionally only those with `status`
+    pub fn list(
+        conn: &mut DatabaseConnection,
+        status: Option<StockAdjustmentStatus>,
+        limit: i64,
+    ) -> Result<Vec<(StockAdjustmentRequest, Product)>> {
+        let mut query = stock_adjustment_requests::table
+            .inner_join(products::table)
+            .into_boxed();
+        if let Some(status) = status {
+            query = query.filter(stock_adjustment_requests::status.eq(status.to_string()));
+        }
+        query
+            .order(stock_adjustment_requests::requested_at.asc())
+            .then_order_by(stock_adjustment_requests::id.asc())
+            .limit(limit)
+            .load::<(StockAdjustmentRequest, Product)>(conn)
+            .map_err(Into::into)
+    }
+
+    pub fn find(conn: &mut DatabaseConnection, request_id: i32) -> Result<StockAdjustmentRequest> {
+        stock_adjustment_requests::table
+            .find(request_id)
+            .first::<StockAdjustmentRequest>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Stock adjustment request {} not found", request_id)))
+    }
+
+    /// Approve a pending request and apply it to stock through the approval
+    /// workflow. `sod_override` is the reason for approving one's own request.
+    pub fn approve(
+        conn: &mut DatabaseConnection,
+        request_id: i32,
+        approver: AuthenticatedUser,
+        note: Option<&str>,
+        sod_override: Option<&str>,
+    ) -> Result<(StockAdjustmentRequest, Product)> {
+        let request = Self::pending_for_decision(conn, request_id, &approver)?;
+        SodService::check(
+            conn,
+            SodRule::StockAdjustmentRequestApprove,
+            request.id,
+            request.requested_by,
+            approver.id,
+            sod_override,
+        )?;
+
+        let mut engine = WorkflowEngine::new();
+        for workflow in create_default_workflows() {
+            engine.register_workflow(workflow);
+        }
+        engine.register_action(ApplyStockAdjustment);
+
+        engine.start_workflow(
+            STOCK_ADJUSTMENT_WORKFLOW,
+            WorkflowContext {
+                user: None,
+                data: HashMap::new(),
+            },
+        )?;
+        let mut context = WorkflowContext {
+            user: Some(approver),
+            data: HashMap::from([("request_id".to_string(), serde_json::json!(request.id))]),
+        };
+        while engine.execute_next_step(STOCK_ADJUSTMENT_WORKFLOW, &mut context)? {}
+
+        let movement_id = context
+            .data
+            .get("movement_id")
+            .and_then(|id| id.as_i64())
+            .map(|id| id as i32);
+        let approver_id = context.user.as_ref().map(|user| user.id);
+        diesel::update(stock_adjustment_requests::table.find(request.id))
+            .set((
+                stock_adjustment_requests::status.eq(StockAdjustmentStatus::Approved.to_string()),
+                stock_adjustment_requests::decided_by.eq(approver_id),
+                stock_adjustment_requests::decided_at.eq(Some(Utc::now().naive_utc())),
+                stock_adjustment_requests::decision_note.eq(note),
+                stock_adjustment_requests::movement_id.eq(movement_id),
+            ))
+            .execute(conn)?;
+
+        let request = Self::find(conn, request.id)?;
+        let product = products::table.find(request.product_id).first::<Product>(conn)?;
+        tracing::info!(
+            "Stock adjustment request {} approved and applied to {}",
+            request.id,
+            product.sku
+        );
+        Ok((request, product))
+    }
+
+    /// Decline a pending request; stock is left as it is
+    pub fn reject(
+        conn: &mut DatabaseConnection,
+        request_id: i32,
+        approver: &AuthenticatedUser,
+        reason: &str,
+    ) -> Result<StockAdjustmentRequest> {
+        validate_required_string(reason, "Reason")?;
+        let request = Self::pending_for_decision(conn, request_id, approver)?;
+
+        diesel::update(stock_adjustment_requests::table.find(request.id))
+            .set((
+                stock_adjustment_requests::status.eq(StockAdjustmentStatus::Rejected.to_string()),
+                stock_adjustment_requests::decided_by.eq(Some(approver.id)),
+                stock_adjustment_requests::decided_at.eq(Some(Utc::now().naive_utc())),
+                stock_adjustment_requests::decision_note.eq(Some(reason)),
+            ))
+            .execute(conn)?;
+
+        tracing::info!("Stock adjustment request {} rejected", request.id);
+        Self::find(conn, request.id)
+    }
+
+    /// Load a request that `approver` may decide on
+    fn pending_for_decision(
+        conn: &mut DatabaseConnection,
+        request_id: i32,
+        approver: &AuthenticatedUser,
+    ) -> Result<StockAdjustmentRequest> {
+        let request = Self::find(conn, request_id)?;
+        if request.status != StockAdjustmentStatus::Pending.to_string() {
+            return Err(CLIERPError::ValidationError(format!(
+                "Stock adjustment request {} is already {}",
+                request.id, request.status
+            )));
+        }
+        let required_role = create_default_workflows()
+            .into_iter()
+            .find(|workflow| workflow.id == STOCK_ADJUSTMENT_WORKFLOW)
+            .and_then(|workflow| workflow.steps.into_iter().find(|step| step.id == APPROVE_STEP))
+            .and_then(|step| step.required_role);
+        if let Some(required_role) = required_role {
+            if !role_satisfies(&approver.role, &required_role) {
+                return Err(CLIERPError::Authorization(format!(
+                    "Deciding on stock adjustments requires role: {}",
+                    required_role
+                )));
+            }
+        }
+        Ok(request)
+    }
+}
+
+/// Approve step of the stock adjustment workflow: records the requested
+/// movement and puts its ID into the context as `movement_id`
+struct ApplyStockAdjustment;
+
+impl WorkflowAction for ApplyStockAdjustment {
+    fn execute(&self, context: &mut WorkflowContext) -> CLIERPResult<()> {
+        let request_id = context
+            .data
+            .get("request_id")
+            .and_then(|id| id.as_i64())
+            .ok_or_else(|| CLIERPError::Internal("Workflow context has no request_id".to_string()))?
+            as i32;
+
+        let mut conn = get_connection()?;
+        let request = StockApprovalService::find(&mut conn, request_id)?;
+        let notes = match (request.reference.as_deref(), request.notes.as_deref()) {
+            (Some(reference), Some(notes)) => Some(format!("{}: {}", reference, notes)),
+            (Some(reference), None) => Some(reference.to_string()),
+            (None, notes) => notes.map(|s| s.to_string()),
+        };
+
+        ProductService::new().update_stock(
+            request.product_id,
+            request.quantity,
+            &request.movement_type,
+            request.unit_cost,
+            Some(APPROVED_ADJUSTMENT_REFERENCE),
+            Some(request.id),
+            notes.as_deref(),
+            request.requested_by,
+        )?;
+
+        let movement_id = stock_movements::table
+            .filter(stock_movements::reference_type.eq(APPROVED_ADJUSTMENT_REFERENCE))
+            .filter(stock_movements::reference_id.eq(request.id))
+            .select(stock_movements::id)
+            .first::<i32>(&mut conn)?;
+        context
+            .data
+            .insert("movement_id".to_string(), serde_json::json!(movement_id));
+        Ok(())
+    }
+
+    fn can_execute(&self, context: &WorkflowContext) -> bool {
+        context.data.contains_key("request_id")
+    }
+
+    fn name(&self) -> &str {
+        APPROVE_STEP
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requires_approval() {
+        let config = StockApprovalConfig {
+            max_quantity: Some(100),
+            max_value: Some(1_000_000),
+        };
+        assert!(!StockApprovalService::requires_approval(&config, 100, 500_000));
+        assert!(StockApprovalService::requires_approval(&config, -101, 0));
+        assert!(StockApprovalService::requires_approval(&config, 10, 1_000_001));
+
+        let disabled = StockApprovalConfig {
+            max_quantity: None,
+            max_value: None,
+        };
+        assert!(!StockApprovalService::requires_approval(&disabled, 1_000_000, i64::MAX));
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::core::result::CLIERPResult;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+use crate::database::schema::{categories, products, stock_movements};
+use crate::database::{DatabaseConnection, Product, StockMovementType};
+use crate::utils::timezone::DisplayTimezone;
+
+/// Age buckets of the aging report, by upper bound in days; the last is open ended
+pub const AGING_BUCKETS: [(&str, Option<i64>); 4] = [
+    ("0-30", Some(30)),
+    ("31-60", Some(60)),
+    ("61-90", Some(90)),
+    ("90+", None),
+];
+
+/// How long the on-hand stock of one product has been held
+#[derive(Debug, Clone, Serialize)]
+pub struct ProductAging {
+    pub product_id: i32,
+    pub sku: String,
+    pub name: String,
+    pub category: String,
+    pub on_hand: i32,
+    pub unit_cost: i32,
+    /// On-hand quantity in each of `AGING_BUCKETS`
+    pub quantities: [i32; 4],
+    pub oldest_receipt: Option<NaiveDate>,
+}
+
+impl ProductAging {
+    /// Stock value at cost in each of `AGING_BUCKETS`
+    pub fn values(&self) -> [i64; 4] {
+        self.quantities.map(|quantity| quantity as i64 * self.unit_cost as i64)
+    }
+
+    pub fn total_value(&self) -> i64 {
+        self.values().iter().sum()
+    }
+
+    /// Value of the stock held more than 60 days
+    pub fn aged_value(&self) -> i64 {
+        let values = self.values();
+        values[2] + values[3]
+    }
+}
+
+/// Inventory aging by receipt date.
+///
+/// Stock has no lots, so on-hand quantity is matched to receipts first in,
+/// first out: what is left is taken to be the most recent receipts. Stock
+/// that no receipt accounts for, such as an opening balance, is aged from
+/// the product's creation.
+pub struct InventoryAgingService;
+
+impl InventoryAgingService {
+    pub fn aging(conn: &mut DatabaseConnection, as_of: NaiveDate, category_id: Option<i32>) -> Result<Vec<ProductAging>> {
+        let mut query = products::table
+            .inner_join(categories::table)
+            .filter(products::is_active.eq(true))
+            .filter(products::current_stock.gt(0))
+            .into_boxed();
+        if let Some(category_id) = category_id {
+            query = query.filter(products::category_id.eq(category_id));
+        }
+        let stocked: Vec<(Product, String)> = query
+            .select((Product::as_select(), categories::name))
+            .order(products::sku.asc())
+            .load(conn)?;
+
+        let products: Vec<&Product> = stocked.iter().map(|(product, _)| product).collect();
+        let mut layers = Self::layers(conn, &products)?;
+
+        Ok(stocked
+            .into_iter()
+            .map(|(product, category)| {
+                let layers = layers.remove(&product.id).unwrap_or_default();
+                ProductAging {
+                    product_id: product.id,
+                    sku: product.sku,
+                    name: product.name,
+                    category,
+                    on_hand: product.current_stock,
+                    unit_cost: product.cost_price,
+                    quantities: bucket_quantities(&layers, as_of),
+                    oldest_receipt: layers.last().map(|(date, _)| *date),
+                }
+            })
+            .collect())
+    }
+
+    /// On-hand stock of each product split into the receipts it came from,
+    /// newest first, as `on_hand_layers` does it
+    pub fn layers(conn: &mut DatabaseConnection, products: &[&Product]) -> Result<HashMap<i32, Vec<(NaiveDate, i32)>>> {
+        let product_ids: Vec<i32> = products.iter().map(|product| product.id).collect();
+        let timezone = DisplayTimezone::current();
+        let mut receipts: HashMap<i32, Vec<(NaiveDate, i32)>> = HashMap::new();
+        let rows: Vec<(i32, i32, chrono::NaiveDateTime)> = stock_movements::table
+            .filter(stock_movements::product_id.eq_any(&product_ids))
+            .filter(stock_movements::movement_type.eq(StockMovementType::In.to_string()))
+            .filter(stock_movements::quantity.gt(0))
+            .select((stock_movements::product_id, stock_movements::quantity, stock_movements::movement_date))
+            .order(stock_movements::movement_date.desc())
+            .load(conn)?;
+        for (product_id, quantity, moved_at) in rows {
+            receipts
+                .entry(product_id)
+                .or_default()
+                .push((timezone.to_local(moved_at).date(), quantity));
+        }
+
+        Ok(products
+            .iter()
+            .map(|product| {
+                let opened = timezone.to_local(product.created_at).date();
+                let layers = on_hand_layers(
+                    product.current_stock,
+                    receipts.get(&product.id).map(Vec::as_slice).unwrap_or_default(),
+                    opened,
+                );
+                (product.id, layers)
+            })
+            .collect())
+    }
+}
+
+/// Split `on_hand` into the receipts it came from, newest first. `receipts`
+/// must be newest first; anything they do not cover is dated `opened`.
+pub fn on_hand_layers(on_hand: i32, receipts: &[(NaiveDate, i32)], opened: NaiveDate) -> Vec<(NaiveDate, i32)> {
+    let mut remaining = on_hand.max(0);
+    let mut layers = Vec::new();
+    for &(date, quantity) in receipts {
+        if remaining == 0 {
+            break;
+        }
+        let taken = quantity.min(remaining);
+        layers.push((date, taken));
+        remaining -= taken;
+    }
+    if remaining > 0 {
+        layers.push((opened, remaining));
+    }
+    layers
+}
+
+/// Quantity of `layers` in each of `AGING_BUCKETS` on `as_of`
+pub fn bucket_quantities(layers: &[(NaiveDate, i32)], as_of: NaiveDate) -> [i32; 4] {
+    let mut quantities = [0; 4];
+    for &(date, quantity) in layers {
+        let age = (as_of - date).num_days().max(0);
+        let bucket = AGING_BUCKETS
+            .iter()
+            .position(|(_, upper)| upper.is_none_or(|upper| age <= upper))
+            .unwrap_or(AGING_BUCKETS.len() - 1);
+        quantities[bucket] += quantity;
+    }
+    quantities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_on_hand_layers() {
+        let receipts = [(date("2025-03-01"), 10), (date("2025-01-15"), 20)];
+        let opened = date("2024-06-01");
+
+        // The newest receipts are still on hand
+        assert_eq!(
+            on_hand_layers(15, &receipts, opened),
+            vec![(date("2025-03-01"), 10), (date("2025-01-15"), 5)]
+        );
+        // Stock beyond the receipts is aged from when the product was created
+        assert_eq!(
+            on_hand_layers(35, &receipts, opened),
+            vec![(date("2025-03-01"), 10), (date("2025-01-15"), 20), (opened, 5)]
+        );
+        assert!(on_hand_layers(0, &receipts, opened).is_empty());
+    }
+
+    #[test]
+    fn test_bucket_quantities() {
+        let as_of = date("2025-03-31");
+        let layers = [
+            (date("2025-03-31"), 1),
+            (date("2025-03-01"), 2),
+            (date("2025-02-28"), 4),
+            (date("2025-01-01"), 8),
+            (date("2024-12-30"), 16),
+        ];
+        // 0, 30, 31, 89 and 91 days old
+        assert_eq!(bucket_quantities(&layers, as_of), [3, 4, 8, 16]);
+    }
+}
@@ -0,0 +1,198 @@
+use chrono::{Duration, NaiveDate};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::core::result::CLIERPResult;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+use crate::database::schema::{purchase_items, purchase_orders, suppliers};
+use crate::database::{DatabaseConnection, PurchaseOrderStatus};
+use crate::modules::inventory::ReservationService;
+use crate::utils::timezone::DisplayTimezone;
+
+/// Lead time assumed for a new purchase order when no supplier of the
+/// product has one configured
+pub const DEFAULT_LEAD_TIME_DAYS: i32 = 14;
+
+/// Quantity still to be received on an open purchase order line
+#[derive(Debug, Clone, Serialize)]
+pub struct IncomingSupply {
+    pub po_number: String,
+    pub expected_date: NaiveDate,
+    pub quantity: i32,
+}
+
+/// Earliest date a quantity of one product can ship
+#[derive(Debug, Clone, Serialize)]
+pub struct AtpResult {
+    pub product_id: i32,
+    pub sku: String,
+    pub name: String,
+    pub unit: String,
+    pub requested: i32,
+    pub available_now: i32,
+    pub incoming: Vec<IncomingSupply>,
+    pub promise_date: NaiveDate,
+    /// True when open purchase orders do not cover the quantity and the
+    /// date assumes a new order placed today
+    pub needs_replenishment: bool,
+    pub lead_time_days: i32,
+}
+
+/// Available-to-promise calculation.
+///
+/// Starts from unreserved on-hand stock, then adds open purchase order lines
+/// in the order they are expected. A line without an expected date is due
+/// its supplier's lead time after the order date. Whatever is still short
+/// is promised one lead time from today.
+pub struct AtpService;
+
+impl AtpService {
+    pub fn promise(conn: &mut DatabaseConnection, product_id: i32, quantity: i32) -> Result<AtpResult> {
+        if quantity <= 0 {
+            return Err(crate::core::error::CLIERPError::Validation(
+                "Quantity must be greater than zero".to_string()
+            ));
+        }
+
+        let availability = ReservationService::availability(conn, product_id)?;
+        let incoming = Self::incoming_supply(conn, product_id)?;
+        let lead_time_days = Self::replenishment_lead_time(conn, product_id)?;
+
+        let today = DisplayTimezone::current().today();
+        let supply: Vec<(NaiveDate, i32)> = incoming
+            .iter()
+            .map(|line| (line.expected_date, line.quantity))
+            .collect();
+        let (promise_date, needs_replenishment) = earliest_promise_date(
+            today,
+            availability.available,
+            quantity,
+            &supply,
+            lead_time_days,
+        );
+
+        Ok(AtpResult {
+            product_id,
+            sku: availability.sku,
+            name: availability.name,
+            unit: availability.unit,
+            requested: quantity,
+            available_now: availability.available,
+            incoming,
+            promise_date,
+            needs_replenishment,
+            lead_time_days,
+        })
+    }
+
+    /// Unreceived quantities on approved purchase orders not yet received, earliest first
+    pub fn incoming_supply(conn: &mut DatabaseConnection, product_id: i32) -> Result<Vec<IncomingSupply>> {
+        let open_statuses = vec![
+            PurchaseOrderStatus::Approved.to_string(),
+            PurchaseOrderStatus::Sent.to_string(),
+            PurchaseOrderStatus::Confirmed.to_string(),
+            PurchaseOrderStatus::Shipped.to_string(),
+        ];
+
+        let lines = purchase_items::table
+            .inner_join(purchase_orders::table.inner_join(suppliers::table))
+            .filter(purchase_items::product_id.eq(product_id))
+            .filter(purchase_orders::status.eq_any(open_statuses))
+            .select((
+                purchase_orders::po_number,
+                purchase_orders::order_date,
+                purchase_orders::expected_date,
+                suppliers::lead_time_days,
+                purchase_items::quantity,
+                purchase_items::received_quantity,
+            ))
+            .load::<(String, NaiveDate, Option<NaiveDate>, Option<i32>, i32, i32)>(conn)?;
+
+        let mut incoming: Vec<IncomingSupply> = lines
+            .into_iter()
+            .filter(|(_, _, _, _, ordered, received)| ordered > received)
+            .map(|(po_number, order_date, expected_date, lead_time, ordered, received)| {
+                let expected_date = expected_date.unwrap_or_else(|| {
+                    order_date + Duration::days(lead_time.unwrap_or(DEFAULT_LEAD_TIME_DAYS) as i64)
+                });
+                IncomingSupply {
+                    po_number,
+                    expected_date,
+                    quantity: ordered - received,
+                }
+            })
+            .collect();
+        incoming.sort_by_key(|receipt| receipt.expected_date);
+
+        Ok(incoming)
+    }
+
+    /// Longest lead time among suppliers the product has been ordered from
+    fn replenishment_lead_time(conn: &mut DatabaseConnection, product_id: i32) -> Result<i32> {
+        let lead_time = purchase_items::table
+            .inner_join(purchase_orders::table.inner_join(suppliers::table))
+            .filter(purchase_items::product_id.eq(product_id))
+            .select(diesel::dsl::max(suppliers::lead_time_days))
+            .first::<Option<i32>>(conn)?;
+
+        Ok(lead_time.unwrap_or(DEFAULT_LEAD_TIME_DAYS))
+    }
+}
+
+/// Walk `supply` in date order until `available` plus receipts cover
+/// `quantity`. Receipts that are overdue count from `today`. Returns the
+/// promise date and whether a new order of `lead_time_days` was needed.
+pub fn earliest_promise_date(
+    today: NaiveDate,
+    available: i32,
+    quantity: i32,
+    supply: &[(NaiveDate, i32)],
+    lead_time_days: i32,
+) -> (NaiveDate, bool) {
+    let mut covered = available.max(0);
+    if covered >= quantity {
+        return (today, false);
+    }
+
+    let mut supply = supply.to_vec();
+    supply.sort_by_key(|(date, _)| *date);
+    for (date, received) in supply {
+        covered += received;
+        if covered >= quantity {
+            return (date.max(today), false);
+        }
+    }
+
+    (today + Duration::days(lead_time_days as i64), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_promise_from_stock_on_hand() {
+        assert_eq!(earliest_promise_date(date(3, 1), 80, 50, &[], 14), (date(3, 1), false));
+    }
+
+    #[test]
+    fn test_promise_waits_for_open_purchase_orders() {
+        let supply = [(date(3, 20), 30), (date(3, 10), 10)];
+        assert_eq!(earliest_promise_date(date(3, 1), 20, 30, &supply, 14), (date(3, 10), false));
+        assert_eq!(earliest_promise_date(date(3, 1), 20, 50, &supply, 14), (date(3, 20), false));
+        // Overdue receipts are promised from today
+        assert_eq!(earliest_promise_date(date(3, 15), 20, 30, &supply, 14), (date(3, 15), false));
+    }
+
+    #[test]
+    fn test_promise_falls_back_to_lead_time() {
+        let supply = [(date(3, 10), 10)];
+        assert_eq!(earliest_promise_date(date(3, 1), -5, 50, &supply, 21), (date(3, 22), true));
+    }
+}
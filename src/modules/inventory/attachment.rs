@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use chrono::Utc;
@@ -8,7 +9,10 @@ use crate::core::result::CLIERPResult;
 use crate::database::connection::get_connection;
 use crate::database::models::{ProductAttachment, NewProductAttachment};
 use crate::database::schema::product_attachments;
-use crate::utils::validation::{validate_required_string, ValidationResult};
+use crate::utils::validation::validate_required_string;
+
+/// Directory product attachments are copied to
+pub const ATTACHMENTS_DIR: &str = "./storage/attachments";
 
 #[derive(Debug, Clone)]
 pub struct AttachmentService {
@@ -17,7 +21,7 @@ pub struct AttachmentService {
 
 impl AttachmentService {
     pub fn new() -> Self {
-        let storage_path = PathBuf::from("./storage/attachments");
+        let storage_path = PathBuf::from(ATTACHMENTS_DIR);
         Self { storage_path }
     }
 
@@ -274,6 +278,31 @@ impl AttachmentService {
         Ok(attachment)
     }
 
+    /// Make an image the product's primary photo, used by catalogs and the browser
+    pub fn set_primary_photo(&self, id: i32) -> CLIERPResult<ProductAttachment> {
+        let attachment = self.get_attachment(id)?;
+        if attachment.attachment_type != "image" {
+            return Err(crate::core::error::CLIERPError::ValidationError(format!(
+                "Attachment {} is a {}, not an image",
+                id, attachment.attachment_type
+            )));
+        }
+        self.set_primary_attachment(id)
+    }
+
+    /// Primary photos of the given products, keyed by product ID
+    pub fn get_primary_images(&self, product_ids: &[i32]) -> CLIERPResult<HashMap<i32, ProductAttachment>> {
+        let mut connection = get_connection()?;
+
+        let attachments = product_attachments::table
+            .filter(product_attachments::product_id.eq_any(product_ids))
+            .filter(product_attachments::attachment_type.eq("image"))
+            .filter(product_attachments::is_primary.eq(true))
+            .load::<ProductAttachment>(&mut connection)?;
+
+        Ok(attachments.into_iter().map(|a| (a.product_id, a)).collect())
+    }
+
     pub fn get_storage_path(&self) -> &Path {
         &self.storage_path
     }
@@ -320,6 +349,12 @@ impl AttachmentService {
     }
 }
 
+impl Default for AttachmentService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -5,8 +5,9 @@ use crate::core::result::CLIERPResult;
 use crate::database::connection::get_connection;
 use crate::database::models::{StockAudit, NewStockAudit, StockAuditItem, NewStockAuditItem, Product};
 use crate::database::schema::{stock_audits, stock_audit_items, products, categories};
+use crate::modules::inventory::reservation::ReservationService;
 use crate::utils::pagination::{PaginationParams, PaginationResult};
-use crate::utils::validation::{validate_required_string, ValidationResult};
+use crate::utils::validation::validate_required_string;
 
 #[derive(Debug, Clone)]
 pub struct StockAuditService;
@@ -292,8 +293,25 @@ impl StockAuditService {
             .map(|item| item.variance.unwrap_or(0))
             .sum::<i32>();
 
-        // Apply stock adjustments if requested
+        // Apply stock adjustments if requested, once every count is known
+        // to leave reserved stock on hand
         if apply_adjustments {
+            for audit_item in &audit_items {
+                if let (Some(actual), Some(variance)) = (audit_item.actual_quantity, audit_item.variance) {
+                    if variance < 0 {
+                        ReservationService::ensure_unreserved(&mut connection, audit_item.product_id, actual)
+                            .map_err(|e| match e {
+                                crate::core::error::CLIERPError::Validation(message) => {
+                                    crate::core::error::CLIERPError::ValidationError(format!(
+                                        "Product {}: {}; release reservations before applying the count",
+                                        audit_item.product_id, message
+                                    ))
+                                }
+                                e => e,
+                            })?;
+                    }
+                }
+            }
             for audit_item in &audit_items {
                 if let Some(variance) = audit_item.variance {
                     if variance != 0 {
@@ -408,6 +426,12 @@ impl StockAuditService {
     }
 }
 
+impl Default for StockAuditService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct StockAuditItemWithProduct {
     pub audit_item: StockAuditItem,
@@ -431,9 +455,8 @@ mod tests {
 
     #[test]
     fn test_audit_service_creation() {
-        let service = StockAuditService::new();
         // Basic instantiation test
-        assert!(true);
+        let _service = StockAuditService::new();
     }
 
     #[test]
@@ -453,4 +476,26 @@ mod tests {
         assert_eq!(summary.total_variance, -5);
         assert!(summary.adjustments_applied);
     }
+
+    #[test]
+    fn test_count_below_reserved_is_not_applied() {
+        use crate::modules::inventory::reservation::ReservationService;
+        use crate::testing::CompanyFixture;
+
+        let company = CompanyFixture::new().with_products(1).build().unwrap();
+        let product_id = company.products[0].id;
+        let mut conn = company.connection().unwrap();
+        ReservationService::reserve(&mut conn, product_id, 8, "sales_order", Some(1), None, None).unwrap();
+
+        let service = StockAuditService::new();
+        let audit = service.create_audit("Year end", NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(), None, None).unwrap();
+        service.start_audit(audit.id).unwrap();
+        service.record_audit_count(audit.id, product_id, 5, None).unwrap();
+
+        let err = service.complete_audit(audit.id, true).unwrap_err();
+        assert!(err.to_string().contains("release reservations"), "{}", err);
+        let product = products::table.find(product_id).first::<Product>(&mut conn).unwrap();
+        assert_eq!(product.current_stock, 10);
+        assert_eq!(service.get_audit(audit.id).unwrap().status, "in_progress");
+    }
 }
\ No newline at end of file
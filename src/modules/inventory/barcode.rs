@@ -221,6 +221,12 @@ impl BarcodeService {
     }
 }
 
+impl Default for BarcodeService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -244,6 +250,9 @@ mod tests {
             is_active: true,
             created_at: Utc::now().naive_utc(),
             updated_at: Utc::now().naive_utc(),
+            commodity_code: None,
+            country_of_origin: None,
+            net_weight_grams: None,
         }
     }
 
@@ -0,0 +1,361 @@
+use std::collections::HashSet;
+
+use chrono::Utc;
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+use crate::database::schema::{product_bundle_items, product_bundles, products};
+use crate::database::{
+    BundlePricing, DatabaseConnection, NewProductBundle, NewProductBundleItem, Product, ProductBundle,
+    ProductBundleItem,
+};
+use crate::modules::inventory::ReservationService;
+use crate::utils::cache::QueryCache;
+use crate::utils::validation::validate_required_string;
+
+/// A component given on the command line as `SKU:QUANTITY`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleComponent {
+    pub sku: String,
+    pub quantity: i32,
+}
+
+impl std::str::FromStr for BundleComponent {
+    type Err = CLIERPError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (sku, quantity) = match s.rsplit_once(':') {
+            Some((sku, quantity)) => (sku.trim(), quantity.trim()),
+            None => (s.trim(), "1"),
+        };
+        let quantity = quantity
+            .parse::<i32>()
+            .map_err(|_| CLIERPError::Validation(format!("Invalid component quantity in '{}'", s)))?;
+        if sku.is_empty() {
+            return Err(CLIERPError::Validation(format!("Missing component SKU in '{}'", s)));
+        }
+        if quantity <= 0 {
+            return Err(CLIERPError::Validation(format!("Component quantity must be positive in '{}'", s)));
+        }
+        Ok(Self {
+            sku: sku.to_string(),
+            quantity,
+        })
+    }
+}
+
+/// Clap value parser for `--component SKU:QUANTITY`
+pub fn parse_bundle_component_arg(s: &str) -> std::result::Result<BundleComponent, String> {
+    s.parse().map_err(|e: CLIERPError| e.to_string())
+}
+
+/// One component line of a bundle quote
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleComponentQuote {
+    pub product_id: i32,
+    pub sku: String,
+    pub name: String,
+    pub unit: String,
+    pub per_bundle: i32,
+    pub required: i32,
+    pub available: i32,
+    pub unit_price: i32,
+}
+
+impl BundleComponentQuote {
+    pub fn shortfall(&self) -> i32 {
+        (self.required - self.available).max(0)
+    }
+}
+
+/// Price and availability of a quantity of one bundle
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleQuote {
+    pub bundle_id: i32,
+    pub sku: String,
+    pub name: String,
+    pub pricing: BundlePricing,
+    pub quantity: i32,
+    /// Sum of the component list prices for one bundle
+    pub component_total: i64,
+    pub unit_price: i64,
+    pub total_price: i64,
+    /// Bundles the unreserved component stock covers
+    pub available: i32,
+    pub components: Vec<BundleComponentQuote>,
+}
+
+impl BundleQuote {
+    pub fn in_stock(&self) -> bool {
+        self.available >= self.quantity
+    }
+}
+
+/// Sellable product bundles.
+///
+/// A bundle holds no stock of its own. Its availability is how many
+/// complete kits the unreserved stock of its components makes up, and its
+/// price is either fixed or the component prices less a discount. Selling
+/// a bundle moves stock of the components.
+pub struct BundleService;
+
+impl BundleService {
+    pub fn create_bundle(
+        conn: &mut DatabaseConnection,
+        sku: &str,
+        name: &str,
+        description: Option<&str>,
+        fixed_price: Option<i32>,
+        discount_percent: i32,
+        components: &[BundleComponent],
+    ) -> Result<ProductBundle> {
+        validate_required_string(sku, "sku")?;
+        validate_required_string(name, "name")?;
+        if components.is_empty() {
+            return Err(CLIERPError::Validation("A bundle needs at least one component".to_string()));
+        }
+        if !(0..=100).contains(&discount_percent) {
+            return Err(CLIERPError::Validation("Discount must be between 0 and 100 percent".to_string()));
+        }
+        if fixed_price.is_some_and(|price| price < 0) {
+            return Err(CLIERPError::Validation("Bundle price cannot be negative".to_string()));
+        }
+        if fixed_price.is_some() && discount_percent != 0 {
+            return Err(CLIERPError::Validation(
+                "A discount only applies to bundles priced from their components".to_string(),
+            ));
+        }
+
+        // Bundles and products are quoted by SKU, so the two must not overlap
+        let sku_taken = Self::get_bundle_by_sku(conn, sku)?.is_some()
+            || products::table
+                .filter(products::sku.eq(sku))
+                .count()
+                .get_result::<i64>(conn)?
+                > 0;
+        if sku_taken {
+            return Err(CLIERPError::Validation(format!("SKU '{}' is already in use", sku)));
+        }
+
+        let mut items = Vec::with_capacity(components.len());
+        let mut seen = HashSet::new();
+        for component in components {
+            let product = products::table
+                .filter(products::sku.eq(&component.sku))
+                .first::<Product>(conn)
+                .optional()?
+                .ok_or_else(|| CLIERPError::NotFound(format!("Product with SKU '{}' not found", component.sku)))?;
+            if !seen.insert(product.id) {
+                return Err(CLIERPError::Validation(format!(
+                    "Component '{}' is listed more than once",
+                    component.sku
+                )));
+            }
+            items.push((product.id, component.quantity));
+        }
+
+        let pricing = if fixed_price.is_some() { BundlePricing::Fixed } else { BundlePricing::Sum };
+        let bundle = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::insert_into(product_bundles::table)
+                .values(&NewProductBundle {
+                    sku: sku.to_string(),
+                    name: name.to_string(),
+                    description: description.map(|s| s.to_string()),
+                    pricing: pricing.to_string(),
+                    fixed_price,
+                    discount_percent,
+                    is_active: true,
+                })
+                .execute(conn)?;
+            let bundle = product_bundles::table
+                .order(product_bundles::id.desc())
+                .first::<ProductBundle>(conn)?;
+
+            let new_items: Vec<NewProductBundleItem> = items
+                .iter()
+                .map(|&(product_id, quantity)| NewProductBundleItem {
+                    bundle_id: bundle.id,
+                    product_id,
+                    quantity,
+                })
+                .collect();
+            diesel::insert_into(product_bundle_items::table)
+                .values(&new_items)
+                .execute(conn)?;
+            Ok(bundle)
+        })?;
+        QueryCache::current().invalidate(&["inventory"]);
+
+        tracing::info!("Created bundle {} with {} components", bundle.sku, items.len());
+        Ok(bundle)
+    }
+
+    pub fn get_bundle_by_sku(conn: &mut DatabaseConnection, sku: &str) -> Result<Option<ProductBundle>> {
+        product_bundles::table
+            .filter(product_bundles::sku.eq(sku))
+            .first::<ProductBundle>(conn)
+            .optional()
+            .map_err(Into::into)
+    }
+
+    pub fn list_bundles(conn: &mut DatabaseConnection, active_only: bool) -> Result<Vec<ProductBundle>> {
+        let mut query = product_bundles::table.into_boxed();
+        if active_only {
+            query = query.filter(product_bundles::is_active.eq(true));
+        }
+        query
+            .order(product_bundles::sku.asc())
+            .load::<ProductBundle>(conn)
+            .map_err(Into::into)
+    }
+
+    /// The components of a bundle with their products
+    pub fn components(conn: &mut DatabaseConnection, bundle_id: i32) -> Result<Vec<(ProductBundleItem, Product)>> {
+        product_bundle_items::table
+            .inner_join(products::table)
+            .filter(product_bundle_items::bundle_id.eq(bundle_id))
+            .select((ProductBundleItem::as_select(), Product::as_select()))
+            .order(products::sku.asc())
+            .load(conn)
+            .map_err(Into::into)
+    }
+
+    /// Price `quantity` of a bundle and check the component stock for it
+    pub fn quote(conn: &mut DatabaseConnection, bundle: &ProductBundle, quantity: i32) -> Result<BundleQuote> {
+        if quantity <= 0 {
+            return Err(CLIERPError::Validation("Quantity must be greater than zero".to_string()));
+        }
+        if !bundle.is_active {
+            return Err(CLIERPError::BusinessLogic(format!("Bundle '{}' is inactive", bundle.sku)));
+        }
+        let pricing: BundlePricing = bundle.pricing.parse()?;
+
+        let mut components = Vec::new();
+        for (item, product) in Self::components(conn, bundle.id)? {
+            let availability = ReservationService::availability(conn, product.id)?;
+            components.push(BundleComponentQuote {
+                product_id: product.id,
+                sku: product.sku,
+                name: product.name,
+                unit: product.unit,
+                per_bundle: item.quantity,
+                required: item.quantity * quantity,
+                available: availability.available,
+                unit_price: product.price,
+            });
+        }
+
+        let component_total = components
+            .iter()
+            .map(|c| c.unit_price as i64 * c.per_bundle as i64)
+            .sum::<i64>();
+        let unit_price = bundle_unit_price(pricing, bundle.fixed_price, bundle.discount_percent, component_total);
+        let available = buildable_quantity(components.iter().map(|c| (c.per_bundle, c.available)));
+
+        Ok(BundleQuote {
+            bundle_id: bundle.id,
+            sku: bundle.sku.clone(),
+            name: bundle.name.clone(),
+            pricing,
+            quantity,
+            component_total,
+            unit_price,
+            total_price: unit_price * quantity as i64,
+            available,
+            components,
+        })
+    }
+
+    pub fn set_active(conn: &mut DatabaseConnection, bundle_id: i32, is_active: bool) -> Result<ProductBundle> {
+        let updated = diesel::update(product_bundles::table.find(bundle_id))
+            .set((
+                product_bundles::is_active.eq(is_active),
+                product_bundles::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+        if updated == 0 {
+            return Err(CLIERPError::NotFound(format!("Bundle with ID {} not found", bundle_id)));
+        }
+        QueryCache::current().invalidate(&["inventory"]);
+
+        product_bundles::table
+            .find(bundle_id)
+            .first::<ProductBundle>(conn)
+            .map_err(Into::into)
+    }
+
+    pub fn delete_bundle(conn: &mut DatabaseConnection, bundle_id: i32) -> Result<bool> {
+        let deleted = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::delete(product_bundle_items::table.filter(product_bundle_items::bundle_id.eq(bundle_id)))
+                .execute(conn)?;
+            diesel::delete(product_bundles::table.find(bundle_id)).execute(conn)
+        })?;
+        QueryCache::current().invalidate(&["inventory"]);
+
+        Ok(deleted > 0)
+    }
+}
+
+/// Price of one bundle. A summed price takes the discount off the component
+/// total, rounding half up to the minor unit.
+pub fn bundle_unit_price(
+    pricing: BundlePricing,
+    fixed_price: Option<i32>,
+    discount_percent: i32,
+    component_total: i64,
+) -> i64 {
+    match (pricing, fixed_price) {
+        (BundlePricing::Fixed, Some(price)) => price as i64,
+        _ => (component_total * (100 - discount_percent as i64) + 50) / 100,
+    }
+}
+
+/// Complete bundles that `(per_bundle, available)` component stock makes up
+pub fn buildable_quantity(components: impl IntoIterator<Item = (i32, i32)>) -> i32 {
+    components
+        .into_iter()
+        .map(|(per_bundle, available)| available.max(0) / per_bundle.max(1))
+        .min()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_unit_price() {
+        assert_eq!(bundle_unit_price(BundlePricing::Fixed, Some(9_900), 0, 12_000), 9_900);
+        assert_eq!(bundle_unit_price(BundlePricing::Sum, None, 0, 12_000), 12_000);
+        assert_eq!(bundle_unit_price(BundlePricing::Sum, None, 10, 12_000), 10_800);
+        // 999 less 15% is 849.15
+        assert_eq!(bundle_unit_price(BundlePricing::Sum, None, 15, 999), 849);
+        assert_eq!(bundle_unit_price(BundlePricing::Sum, None, 100, 999), 0);
+    }
+
+    #[test]
+    fn test_buildable_quantity() {
+        assert_eq!(buildable_quantity([(2, 10), (1, 3)]), 3);
+        assert_eq!(buildable_quantity([(4, 10)]), 2);
+        // Over-reserved stock counts as none
+        assert_eq!(buildable_quantity([(1, -5), (1, 10)]), 0);
+        assert_eq!(buildable_quantity(Vec::new()), 0);
+    }
+
+    #[test]
+    fn test_parse_bundle_component() {
+        assert_eq!(
+            "KB-01:2".parse::<BundleComponent>().unwrap(),
+            BundleComponent { sku: "KB-01".to_string(), quantity: 2 }
+        );
+        assert_eq!("MOUSE".parse::<BundleComponent>().unwrap().quantity, 1);
+        assert!("KB-01:0".parse::<BundleComponent>().is_err());
+        assert!("KB-01:two".parse::<BundleComponent>().is_err());
+        assert!(":3".parse::<BundleComponent>().is_err());
+    }
+}
@@ -0,0 +1,233 @@
+use std::io::Cursor;
+
+use diesel::prelude::*;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{categories, products};
+use crate::database::{Category, Product, ProductAttachment};
+use crate::modules::inventory::attachment::AttachmentService;
+use crate::modules::inventory::category::CategoryService;
+use crate::modules::inventory::product::ProductWithCategory;
+use crate::modules::inventory::translation::TranslationService;
+use crate::utils::formatting::format_currency;
+use crate::utils::html::{data_uri, escape_html, html_page};
+use crate::utils::pdf::{wrap_text, PdfDocument, PdfFont, A4_HEIGHT, A4_WIDTH};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Longest side of the photo thumbnails embedded in a catalog, in pixels
+const THUMBNAIL_SIZE: u32 = 320;
+
+/// PDF layout: a grid of product cards on A4, in points
+const MARGIN: f32 = 36.0;
+const GUTTER: f32 = 12.0;
+const COLUMNS: usize = 3;
+const ROWS: usize = 4;
+const CARD_HEIGHT: f32 = 170.0;
+const PHOTO_HEIGHT: f32 = 90.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CatalogFormat {
+    /// Self-contained page with the photos embedded; print it from a browser
+    Html,
+    /// A4 pages, four rows of three products each
+    Pdf,
+}
+
+impl CatalogFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CatalogFormat::Html => "html",
+            CatalogFormat::Pdf => "pdf",
+        }
+    }
+}
+
+/// One product of a catalog with its primary photo
+#[derive(Debug, Clone)]
+pub struct CatalogEntry {
+    pub product: Product,
+    pub category: Category,
+    pub photo: Option<ProductAttachment>,
+}
+
+/// JPEG thumbnail of a product photo
+struct Thumbnail {
+    jpeg: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+/// Printable product catalogs built from active products and their
+/// primary photos
+pub struct CatalogService;
+
+impl CatalogService {
+    /// Active products of `category_id` and its subcategories (all products
+    /// when `None`), by category and name, translated to `locale` if given
+    pub fn entries(
+        conn: &mut SqliteConnection,
+        category_id: Option<i32>,
+        locale: Option<&str>,
+    ) -> Result<Vec<CatalogEntry>> {
+        let mut query = products::table
+            .inner_join(categories::table)
+            .filter(products::is_active.eq(true))
+            .into_boxed();
+        if let Some(category_id) = category_id {
+            let ids = CategoryService::new().subtree_ids(category_id)?;
+            query = query.filter(products::category_id.eq_any(ids));
+        }
+        let mut rows: Vec<ProductWithCategory> = query
+            .order((categories::name.asc(), products::name.asc()))
+            .load::<(Product, Category)>(conn)?
+            .into_iter()
+            .map(|(product, category)| ProductWithCategory { product, category })
+            .collect();
+        if let Some(locale) = locale {
+            TranslationService::localize_product_list(conn, &mut rows, locale)?;
+        }
+
+        let ids: Vec<i32> = rows.iter().map(|r| r.product.id).collect();
+        let mut photos = AttachmentService::new().get_primary_images(&ids)?;
+        Ok(rows
+            .into_iter()
+            .map(|row| CatalogEntry {
+                photo: photos.remove(&row.product.id),
+                product: row.product,
+                category: row.category,
+            })
+            .collect())
+    }
+
+    /// Write the catalog to `path`, returning the number of products
+    pub fn export(entries: &[CatalogEntry], title: &str, format: CatalogFormat, path: &str) -> Result<usize> {
+        let contents = match format {
+            CatalogFormat::Html => Self::render_html(entries, title).into_bytes(),
+            CatalogFormat::Pdf => Self::render_pdf(entries, title),
+        };
+        std::fs::write(path, contents)
+            .map_err(|e| CLIERPError::IoError(format!("Failed to write {}: {}", path, e)))?;
+        tracing::info!("Exported catalog of {} products to {}", entries.len(), path);
+        Ok(entries.len())
+    }
+
+    pub fn render_html(entries: &[CatalogEntry], title: &str) -> String {
+        let mut body = String::new();
+        let mut current_category = None;
+        for entry in entries {
+            if current_category != Some(entry.category.id) {
+                if current_category.is_some() {
+                    body.push_str("</div>\n");
+                }
+                body.push_str(&format!("<h2>{}</h2>\n<div class=\"catalog\">\n", escape_html(&entry.category.name)));
+                current_category = Some(entry.category.id);
+            }
+
+            let product = &entry.product;
+            body.push_str("<div class=\"item\">\n");
+            match entry.photo.as_ref().and_then(thumbnail) {
+                Some(thumb) => body.push_str(&format!(
+                    "<img src=\"{}\" alt=\"{}\">\n",
+                    data_uri("image/jpeg", &thumb.jpeg),
+                    escape_html(&product.name)
+                )),
+                None => body.push_str("<div class=\"photo\"></div>\n"),
+            }
+            body.push_str(&format!("<div><strong>{}</strong></div>\n", escape_html(&product.name)));
+            body.push_str(&format!(
+                "<div class=\"muted\">{} &middot; {}</div>\n",
+                escape_html(&product.sku),
+                escape_html(&product.unit)
+            ));
+            body.push_str(&format!("<div class=\"price\">{}</div>\n", escape_html(&format_currency(product.price))));
+            if let Some(description) = &product.description {
+                body.push_str(&format!("<p>{}</p>\n", escape_html(description)));
+            }
+            body.push_str("</div>\n");
+        }
+        if current_category.is_some() {
+            body.push_str("</div>\n");
+        } else {
+            body.push_str("<p>No products.</p>\n");
+        }
+        html_page(title, &body)
+    }
+
+    pub fn render_pdf(entries: &[CatalogEntry], title: &str) -> Vec<u8> {
+        let card_width = (A4_WIDTH - 2.0 * MARGIN - (COLUMNS - 1) as f32 * GUTTER) / COLUMNS as f32;
+        let per_page = COLUMNS * ROWS;
+        let page_count = entries.len().div_ceil(per_page).max(1);
+
+        let mut doc = PdfDocument::new();
+        let thumbnails: Vec<Option<Thumbnail>> = entries.iter().map(|e| e.photo.as_ref().and_then(thumbnail)).collect();
+        let image_ids: Vec<Option<usize>> = thumbnails
+            .iter()
+            .map(|thumb| thumb.as_ref().map(|t| doc.add_jpeg(t.jpeg.clone(), t.width, t.height)))
+            .collect();
+
+        for page_index in 0..page_count {
+            let page = doc.new_page();
+            let top = A4_HEIGHT - MARGIN;
+            page.text(MARGIN, top - 16.0, 16.0, PdfFont::Bold, title);
+            page.text(
+                A4_WIDTH - MARGIN - 60.0,
+                top - 16.0,
+                9.0,
+                PdfFont::Regular,
+                &format!("Page {} of {}", page_index + 1, page_count),
+            );
+            page.line(MARGIN, top - 24.0, A4_WIDTH - MARGIN, top - 24.0);
+
+            let start = page_index * per_page;
+            for (slot, index) in (start..entries.len().min(start + per_page)).enumerate() {
+                let entry = &entries[index];
+                let x = MARGIN + (slot % COLUMNS) as f32 * (card_width + GUTTER);
+                let y = top - 36.0 - (slot / COLUMNS + 1) as f32 * (CARD_HEIGHT + GUTTER);
+                page.rect(x, y, card_width, CARD_HEIGHT);
+
+                // Photo centred in the top of the card, keeping its aspect ratio
+                let photo_top = y + CARD_HEIGHT - 6.0;
+                if let (Some(image), Some(thumb)) = (image_ids[index], &thumbnails[index]) {
+                    let box_width = card_width - 12.0;
+                    let scale = (box_width / thumb.width as f32).min(PHOTO_HEIGHT / thumb.height as f32);
+                    let (w, h) = (thumb.width as f32 * scale, thumb.height as f32 * scale);
+                    page.image(image, x + (card_width - w) / 2.0, photo_top - PHOTO_HEIGHT + (PHOTO_HEIGHT - h) / 2.0, w, h);
+                }
+
+                let mut line_y = photo_top - PHOTO_HEIGHT - 14.0;
+                for line in wrap_text(&entry.product.name, 9.0, card_width - 12.0).iter().take(2) {
+                    page.text(x + 6.0, line_y, 9.0, PdfFont::Bold, line);
+                    line_y -= 11.0;
+                }
+                page.text(
+                    x + 6.0,
+                    line_y,
+                    7.5,
+                    PdfFont::Regular,
+                    &format!("{}  {}  /{}", entry.product.sku, entry.category.name, entry.product.unit),
+                );
+                page.text(x + 6.0, y + 8.0, 10.0, PdfFont::Bold, &format_currency(entry.product.price));
+            }
+        }
+        doc.to_bytes()
+    }
+}
+
+/// Scaled-down JPEG of a photo, or `None` (with a warning) when the file is
+/// missing or not an image the decoder understands
+fn thumbnail(photo: &ProductAttachment) -> Option<Thumbnail> {
+    let image = image::open(&photo.file_path)
+        .map_err(|e| tracing::warn!("Skipping photo {} of product {}: {}", photo.file_path, photo.product_id, e))
+        .ok()?;
+    let rgb = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgb8();
+    let (width, height) = rgb.dimensions();
+    let mut jpeg = Vec::new();
+    image::DynamicImage::ImageRgb8(rgb)
+        .write_to(&mut Cursor::new(&mut jpeg), image::ImageOutputFormat::Jpeg(80))
+        .map_err(|e| tracing::warn!("Could not encode thumbnail of {}: {}", photo.file_path, e))
+        .ok()?;
+    Some(Thumbnail { jpeg, width, height })
+}
@@ -1,12 +1,16 @@
+use std::collections::HashMap;
+
 use chrono::Utc;
 use diesel::prelude::*;
 
+use crate::core::error::CLIERPError;
 use crate::core::result::CLIERPResult;
-use crate::database::connection::get_connection;
+use crate::database::connection::{get_connection, DatabaseConnection};
 use crate::database::models::{Category, NewCategory};
-use crate::database::schema::categories;
+use crate::database::schema::{categories, products};
+use crate::utils::cache::QueryCache;
 use crate::utils::pagination::{PaginationParams, PaginationResult};
-use crate::utils::validation::{validate_required_string, ValidationResult};
+use crate::utils::validation::validate_required_string;
 
 #[derive(Debug, Clone)]
 pub struct CategoryService;
@@ -134,7 +138,19 @@ impl CategoryService {
             .order_by(categories::name.asc())
             .load::<Category>(&mut connection)?;
 
-        let tree = self.build_category_tree(&all_categories, None);
+        // Product count and stock value (at cost) of the active products in each category
+        let mut totals: HashMap<i32, (i64, i64)> = HashMap::new();
+        let stock = products::table
+            .filter(products::is_active.eq(true))
+            .select((products::category_id, products::current_stock, products::cost_price))
+            .load::<(i32, i32, i32)>(&mut connection)?;
+        for (category_id, current_stock, cost_price) in stock {
+            let entry = totals.entry(category_id).or_default();
+            entry.0 += 1;
+            entry.1 += current_stock as i64 * cost_price as i64;
+        }
+
+        let tree = self.build_category_tree(&all_categories, None, &totals);
         Ok(tree)
     }
 
@@ -149,7 +165,7 @@ impl CategoryService {
         let mut connection = get_connection()?;
 
         // Check if category exists
-        let existing_category = self.get_category_by_id(id)?;
+        self.get_category_by_id(id)?;
 
         // Validate name if provided
         if let Some(name) = name {
@@ -169,14 +185,9 @@ impl CategoryService {
             }
         }
 
-        // Check if parent category exists
+        // Check that the new parent exists and is not inside this category's subtree
         if let Some(Some(parent_id)) = parent_id {
-            if parent_id == id {
-                return Err(crate::core::error::CLIERPError::ValidationError(
-                    "Category cannot be its own parent".to_string(),
-                ));
-            }
-            self.get_category_by_id(parent_id)?;
+            self.check_new_parent(&mut connection, id, parent_id)?;
         }
 
         // Build update changeset
@@ -245,12 +256,10 @@ impl CategoryService {
                 .execute(&mut connection)?;
 
             // Move products to "기타" category (assuming it exists as default)
-            if let Ok(default_category) = self.get_category_by_name("기타") {
-                if let Some(default_cat) = default_category {
-                    diesel::update(products::table.filter(products::category_id.eq(id)))
-                        .set(products::category_id.eq(default_cat.id))
-                        .execute(&mut connection)?;
-                }
+            if let Ok(Some(default_cat)) = self.get_category_by_name("기타") {
+                diesel::update(products::table.filter(products::category_id.eq(id)))
+                    .set(products::category_id.eq(default_cat.id))
+                    .execute(&mut connection)?;
             }
         }
 
@@ -261,18 +270,123 @@ impl CategoryService {
         Ok(())
     }
 
+    /// Move a category, with everything below it, under a new parent (`None` for the top level)
+    pub fn move_category(&self, id: i32, new_parent_id: Option<i32>) -> CLIERPResult<Category> {
+        let mut connection = get_connection()?;
+        let category = self.get_category_by_id(id)?;
+
+        if let Some(parent_id) = new_parent_id {
+            self.check_new_parent(&mut connection, id, parent_id)?;
+        }
+
+        diesel::update(categories::table.find(id))
+            .set((
+                categories::parent_id.eq(new_parent_id),
+                categories::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(&mut connection)?;
+        QueryCache::current().invalidate(&["inventory"]);
+
+        tracing::info!(
+            "Moved category {} from parent {:?} to {:?}",
+            category.name,
+            category.parent_id,
+            new_parent_id
+        );
+        self.get_category_by_id(id)
+    }
+
+    /// Merge `source_id` into `target_id`: its products and subcategories move
+    /// to the target and the source category is deleted
+    pub fn merge_categories(&self, source_id: i32, target_id: i32) -> CLIERPResult<CategoryMergeSummary> {
+        if source_id == target_id {
+            return Err(CLIERPError::ValidationError(
+                "Cannot merge a category into itself".to_string(),
+            ));
+        }
+
+        let mut connection = get_connection()?;
+        let source = self.get_category_by_id(source_id)?;
+        let target = self.get_category_by_id(target_id)?;
+
+        let parents = load_parents(&mut connection)?;
+        if is_descendant(&parents, target_id, source_id) {
+            return Err(CLIERPError::ValidationError(format!(
+                "Cannot merge '{}' into its own subcategory '{}'",
+                source.name, target.name
+            )));
+        }
+
+        let now = Utc::now().naive_utc();
+        let summary = connection.transaction::<_, diesel::result::Error, _>(|conn| {
+            let products_moved = diesel::update(products::table.filter(products::category_id.eq(source_id)))
+                .set((products::category_id.eq(target_id), products::updated_at.eq(now)))
+                .execute(conn)?;
+            let subcategories_moved = diesel::update(categories::table.filter(categories::parent_id.eq(source_id)))
+                .set((categories::parent_id.eq(target_id), categories::updated_at.eq(now)))
+                .execute(conn)?;
+            diesel::delete(categories::table.find(source_id)).execute(conn)?;
+
+            Ok(CategoryMergeSummary {
+                products_moved,
+                subcategories_moved,
+            })
+        })?;
+        QueryCache::current().invalidate(&["inventory"]);
+
+        tracing::info!(
+            "Merged category {} into {}: {} products, {} subcategories moved",
+            source.name,
+            target.name,
+            summary.products_moved,
+            summary.subcategories_moved
+        );
+        Ok(summary)
+    }
+
+    /// `id` and the IDs of every category below it
+    pub fn subtree_ids(&self, id: i32) -> CLIERPResult<Vec<i32>> {
+        self.get_category_by_id(id)?;
+        let mut connection = get_connection()?;
+        let parents = load_parents(&mut connection)?;
+        let mut ids: Vec<i32> = parents.keys().copied().filter(|&c| is_descendant(&parents, c, id)).collect();
+        ids.sort_unstable();
+        Ok(ids)
+    }
+
+    fn check_new_parent(&self, connection: &mut DatabaseConnection, id: i32, parent_id: i32) -> CLIERPResult<()> {
+        if parent_id == id {
+            return Err(CLIERPError::ValidationError(
+                "Category cannot be its own parent".to_string(),
+            ));
+        }
+        self.get_category_by_id(parent_id)?;
+
+        if is_descendant(&load_parents(connection)?, parent_id, id) {
+            return Err(CLIERPError::ValidationError(format!(
+                "Category {} is inside category {}; moving would create a cycle",
+                parent_id, id
+            )));
+        }
+        Ok(())
+    }
+
     fn build_category_tree(
         &self,
         all_categories: &[Category],
         parent_id: Option<i32>,
+        totals: &HashMap<i32, (i64, i64)>,
     ) -> Vec<CategoryTreeNode> {
         all_categories
             .iter()
             .filter(|cat| cat.parent_id == parent_id)
             .map(|cat| {
-                let children = self.build_category_tree(all_categories, Some(cat.id));
+                let children = self.build_category_tree(all_categories, Some(cat.id), totals);
+                let (product_count, stock_value) = totals.get(&cat.id).copied().unwrap_or_default();
                 CategoryTreeNode {
                     category: cat.clone(),
+                    product_count,
+                    stock_value,
                     children,
                 }
             })
@@ -280,12 +394,68 @@ impl CategoryService {
     }
 }
 
+impl Default for CategoryService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parent of every category, by id
+fn load_parents(connection: &mut DatabaseConnection) -> CLIERPResult<HashMap<i32, Option<i32>>> {
+    Ok(categories::table
+        .select((categories::id, categories::parent_id))
+        .load::<(i32, Option<i32>)>(connection)?
+        .into_iter()
+        .collect())
+}
+
+/// Whether `id` is `ancestor_id` or somewhere below it. Stops on a cycle
+/// already in the data rather than looping forever.
+fn is_descendant(parents: &HashMap<i32, Option<i32>>, id: i32, ancestor_id: i32) -> bool {
+    let mut current = Some(id);
+    let mut steps = 0;
+    while let Some(category_id) = current {
+        if category_id == ancestor_id {
+            return true;
+        }
+        steps += 1;
+        if steps > parents.len() {
+            return false;
+        }
+        current = parents.get(&category_id).copied().flatten();
+    }
+    false
+}
+
 #[derive(Debug, Clone)]
 pub struct CategoryTreeNode {
     pub category: Category,
+    /// Active products directly in this category
+    pub product_count: i64,
+    /// Stock value at cost of those products
+    pub stock_value: i64,
     pub children: Vec<CategoryTreeNode>,
 }
 
+impl CategoryTreeNode {
+    /// Product count of this category and everything below it
+    pub fn total_product_count(&self) -> i64 {
+        self.product_count + self.children.iter().map(|c| c.total_product_count()).sum::<i64>()
+    }
+
+    /// Stock value of this category and everything below it
+    pub fn total_stock_value(&self) -> i64 {
+        self.stock_value + self.children.iter().map(|c| c.total_stock_value()).sum::<i64>()
+    }
+}
+
+/// What `merge_categories` moved to the target category
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CategoryMergeSummary {
+    pub products_moved: usize,
+    pub subcategories_moved: usize,
+}
+
 #[derive(AsChangeset, Default)]
 #[diesel(table_name = categories)]
 struct CategoryUpdateChangeset {
@@ -302,8 +472,23 @@ mod tests {
 
     #[test]
     fn test_category_service_creation() {
-        let service = CategoryService::new();
         // Basic instantiation test
-        assert!(true);
+        let _service = CategoryService::new();
+    }
+
+    #[test]
+    fn test_is_descendant() {
+        // 1 -> 2 -> 3, and 4 on its own
+        let parents: HashMap<i32, Option<i32>> =
+            [(1, None), (2, Some(1)), (3, Some(2)), (4, None)].into_iter().collect();
+
+        assert!(is_descendant(&parents, 3, 1));
+        assert!(is_descendant(&parents, 2, 2));
+        assert!(!is_descendant(&parents, 1, 3));
+        assert!(!is_descendant(&parents, 4, 1));
+
+        // A cycle already in the data ends the walk
+        let cyclic: HashMap<i32, Option<i32>> = [(1, Some(2)), (2, Some(1))].into_iter().collect();
+        assert!(!is_descendant(&cyclic, 1, 5));
     }
 }
\ No newline at end of file
@@ -0,0 +1,198 @@
+use diesel::prelude::*;
+
+use crate::core::result::CLIERPResult;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+use crate::database::{DatabaseConnection, NewProduct};
+use crate::modules::inventory::ProductService;
+use crate::modules::system::{AuditService, ImportCheckpointService};
+use crate::utils::import::{ConflictStrategy, CsvTable, DEFAULT_BATCH_SIZE};
+use crate::utils::progress::progress_bar;
+
+/// Checkpoint `import_type` of product imports
+pub const PRODUCT_IMPORT: &str = "products";
+pub const PRODUCT_IMPORT_REQUIRED_COLUMNS: &[&str] = &["sku", "name", "category_id", "price"];
+pub const DEFAULT_IMPORT_CHUNK_SIZE: usize = DEFAULT_BATCH_SIZE;
+
+/// Outcome of a product import run
+#[derive(Debug, Clone)]
+pub struct ImportSummary {
+    pub total_rows: usize,
+    /// Rows already imported by an earlier, interrupted run
+    pub resumed_from: usize,
+    pub imported: usize,
+    /// Existing products overwritten under `ConflictStrategy::Update`
+    pub updated: usize,
+    /// Rows left out under `ConflictStrategy::Skip`
+    pub skipped: usize,
+}
+
+/// Create products from a CSV file in chunks of `chunk_size` rows.
+///
+/// Columns: `sku`, `name`, `category_id`, `price` (required) and
+/// `cost_price`, `stock`, `min_stock`, `max_stock`, `unit`, `description`,
+/// `barcode`. Each chunk is written with one batched insert and commits
+/// together with its checkpoint; a failing row rolls back only its own
+/// chunk, and running the import again on the same file continues from the
+/// first row that is not in. SKUs that already exist are handled by the
+/// conflict strategy.
+pub struct ProductImportService;
+
+impl ProductImportService {
+    pub fn import_csv(
+        conn: &mut DatabaseConnection,
+        file_path: &str,
+        chunk_size: usize,
+        on_conflict: ConflictStrategy,
+        restart: bool,
+    ) -> Result<ImportSummary> {
+        let table = CsvTable::read(file_path)?;
+        table.require_columns(PRODUCT_IMPORT_REQUIRED_COLUMNS)?;
+
+        let source = std::fs::canonicalize(file_path)
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|_| file_path.to_string());
+        let total_rows = table.rows.len();
+        let checkpoint = ImportCheckpointService::begin(
+            conn,
+            PRODUCT_IMPORT,
+            &source,
+            total_rows as i32,
+            restart,
+            AuditService::actor(),
+        )?;
+
+        // Only resume if the rows already imported are still where they were
+        let resumed_from = checkpoint.rows_committed.max(0) as usize;
+        if resumed_from > 0 {
+            let last_sku = table
+                .rows
+                .get(resumed_from - 1)
+                .and_then(|row| table.get(row, "sku"));
+            if resumed_from > total_rows || last_sku != checkpoint.last_key.as_deref() {
+                return Err(crate::core::error::CLIERPError::Validation(format!(
+                    "{} changed since the interrupted import ({} rows were imported). Use --restart to import it from the beginning",
+                    file_path, resumed_from
+                )));
+            }
+        }
+
+        let service = ProductService::new();
+        let bar = progress_bar(total_rows as u64, "Importing products");
+        bar.set_position(resumed_from as u64);
+
+        let mut committed = resumed_from;
+        let mut totals = crate::modules::inventory::BulkInsertSummary::default();
+        for chunk in table.rows[resumed_from..].chunks(chunk_size.max(1)) {
+            let chunk_start = committed;
+            // Line numbers count the header as line 1
+            let first_line = chunk_start + 2;
+            let result = conn.transaction::<_, crate::core::error::CLIERPError, _>(|conn| {
+                let products = chunk
+                    .iter()
+                    .enumerate()
+                    .map(|(offset, row)| {
+                        Self::parse_row(&table, row).map_err(|e| {
+                            crate::core::error::CLIERPError::Validation(format!(
+                                "Row {}: {}. Rows before line {} are imported; fix the file and rerun to resume",
+                                first_line + offset,
+                                e,
+                                first_line
+                            ))
+                        })
+                    })
+                    .collect::<Result<Vec<NewProduct>>>()?;
+
+                let batch = service
+                    .bulk_create_products(conn, &products, on_conflict, AuditService::actor())
+                    .map_err(|e| {
+                        crate::core::error::CLIERPError::Validation(format!(
+                            "Rows {}-{}: {}. Rows before line {} are imported; fix the file and rerun to resume",
+                            first_line,
+                            first_line + chunk.len() - 1,
+                            e,
+                            first_line
+                        ))
+                    })?;
+
+                let last_sku = chunk.last().and_then(|row| table.get(row, "sku"));
+                ImportCheckpointService::advance(conn, checkpoint.id, (chunk_start + chunk.len()) as i32, last_sku)?;
+                Ok(batch)
+            });
+
+            match result {
+                Ok(batch) => {
+                    totals.inserted += batch.inserted;
+                    totals.updated += batch.updated;
+                    totals.skipped += batch.skipped;
+                }
+                Err(e) => {
+                    bar.abandon();
+                    return Err(e);
+                }
+            }
+            committed += chunk.len();
+            bar.set_position(committed as u64);
+        }
+
+        ImportCheckpointService::complete(conn, checkpoint.id)?;
+        bar.finish_and_clear();
+
+        tracing::info!(
+            "Imported {} products from {} ({} updated, {} skipped, resumed at row {})",
+            totals.inserted,
+            file_path,
+            totals.updated,
+            totals.skipped,
+            resumed_from
+        );
+        Ok(ImportSummary {
+            total_rows,
+            resumed_from,
+            imported: totals.inserted,
+            updated: totals.updated,
+            skipped: totals.skipped,
+        })
+    }
+
+    fn parse_row(table: &CsvTable, row: &[String]) -> Result<NewProduct> {
+        let int = |column: &str| -> Result<Option<i32>> {
+            table
+                .get(row, column)
+                .map(|value| {
+                    value.parse::<i32>().map_err(|_| {
+                        crate::core::error::CLIERPError::Validation(format!(
+                            "{} must be a whole number, got '{}'",
+                            column, value
+                        ))
+                    })
+                })
+                .transpose()
+        };
+        let required = |column: &str| -> Result<i32> {
+            int(column)?.ok_or_else(|| {
+                crate::core::error::CLIERPError::Validation(format!("{} is required", column))
+            })
+        };
+        let text = |column: &str| table.get(row, column).map(|s| s.to_string());
+
+        Ok(NewProduct {
+            sku: text("sku").unwrap_or_default(),
+            name: text("name").unwrap_or_default(),
+            description: text("description"),
+            category_id: required("category_id")?,
+            price: required("price")?,
+            cost_price: int("cost_price")?.unwrap_or(0),
+            current_stock: int("stock")?.unwrap_or(0),
+            min_stock_level: int("min_stock")?.unwrap_or(0),
+            max_stock_level: int("max_stock")?,
+            unit: text("unit").unwrap_or_else(|| "ea".to_string()),
+            barcode: text("barcode"),
+            is_active: true,
+            commodity_code: None,
+            country_of_origin: None,
+            net_weight_grams: None,
+        })
+    }
+}
@@ -0,0 +1,368 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Duration, NaiveDate};
+use clap::ValueEnum;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{deals, products, purchase_orders, stock_movements};
+use crate::database::{DatabaseConnection, Deal, DealProduct, DealStage, Product, PurchaseOrder, StockMovement};
+use crate::utils::export::{escape_csv_value, CsvSerializable, ExportMapping, ExportService};
+use crate::utils::formatting::format_plain_amount;
+use crate::utils::timezone::DisplayTimezone;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Direction of goods movement for an Intrastat declaration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum IntrastatFlow {
+    /// Goods received from another country (cross-border purchase orders)
+    Arrivals,
+    /// Goods shipped to another country (cross-border sales)
+    Dispatches,
+}
+
+impl std::fmt::Display for IntrastatFlow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntrastatFlow::Arrivals => write!(f, "arrivals"),
+            IntrastatFlow::Dispatches => write!(f, "dispatches"),
+        }
+    }
+}
+
+/// One aggregated declaration line (commodity code x partner country x origin)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntrastatLine {
+    pub commodity_code: String,
+    pub partner_country: String,
+    pub country_of_origin: String,
+    pub quantity: i64,
+    pub net_mass_grams: i64,
+    pub invoice_value: i64,
+}
+
+impl IntrastatLine {
+    pub fn net_mass_kg(&self) -> f64 {
+        self.net_mass_grams as f64 / 1000.0
+    }
+}
+
+impl CsvSerializable for IntrastatLine {
+    fn to_csv_row(&self) -> Vec<String> {
+        vec![
+            escape_csv_value(&self.commodity_code),
+            escape_csv_value(&self.partner_country),
+            escape_csv_value(&self.country_of_origin),
+            self.quantity.to_string(),
+            format!("{:.3}", self.net_mass_kg()),
+            format_plain_amount(self.invoice_value),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntrastatDeclaration {
+    pub period: String,
+    pub flow: IntrastatFlow,
+    pub lines: Vec<IntrastatLine>,
+    /// Products that moved cross-border but are missing customs data
+    pub warnings: Vec<String>,
+}
+
+impl IntrastatDeclaration {
+    pub fn total_value(&self) -> i64 {
+        self.lines.iter().map(|l| l.invoice_value).sum()
+    }
+
+    pub fn total_net_mass_kg(&self) -> f64 {
+        self.lines.iter().map(|l| l.net_mass_kg()).sum()
+    }
+}
+
+pub const INTRASTAT_CSV_HEADERS: [&str; 6] = [
+    "commodity_code",
+    "partner_country",
+    "country_of_origin",
+    "quantity",
+    "net_mass_kg",
+    "invoice_value",
+];
+
+/// A single cross-border movement before aggregation
+#[derive(Debug, Clone)]
+struct CustomsEntry {
+    product_id: i32,
+    partner_country: String,
+    quantity: i64,
+    value: i64,
+}
+
+pub struct IntrastatService;
+
+impl IntrastatService {
+    /// Build the monthly declaration for the given flow. `period` is `YYYY-MM`.
+    pub fn generate_declaration(
+        conn: &mut DatabaseConnection,
+        period: &str,
+        flow: IntrastatFlow,
+    ) -> Result<IntrastatDeclaration> {
+        let (start, end) = Self::parse_period(period)?;
+
+        let entries = match flow {
+            IntrastatFlow::Arrivals => Self::collect_arrivals(conn, start, end)?,
+            IntrastatFlow::Dispatches => Self::collect_dispatches(conn, start, end)?,
+        };
+
+        let product_ids: Vec<i32> = entries.iter().map(|e| e.product_id).collect();
+        let product_map: HashMap<i32, Product> = products::table
+            .filter(products::id.eq_any(&product_ids))
+            .load::<Product>(conn)?
+            .into_iter()
+            .map(|p| (p.id, p))
+            .collect();
+
+        let (lines, warnings) = aggregate_lines(&entries, &product_map);
+
+        tracing::info!(
+            "Generated Intrastat {} declaration for {}: {} lines",
+            flow,
+            period,
+            lines.len()
+        );
+
+        Ok(IntrastatDeclaration {
+            period: period.to_string(),
+            flow,
+            lines,
+            warnings,
+        })
+    }
+
+    /// Write the declaration lines to CSV
+    pub fn export_csv(declaration: &IntrastatDeclaration, file_path: &str) -> Result<()> {
+        ExportService::prepare_file_path(file_path)?;
+        ExportService::new().export_to_csv(&declaration.lines, &INTRASTAT_CSV_HEADERS, file_path)
+    }
+
+    /// Write the declaration lines to CSV in a partner template's layout
+    pub fn export_csv_mapped(declaration: &IntrastatDeclaration, file_path: &str, mapping: &ExportMapping) -> Result<()> {
+        ExportService::prepare_file_path(file_path)?;
+        ExportService::new().export_to_csv_mapped(&declaration.lines, &INTRASTAT_CSV_HEADERS, mapping, file_path)
+    }
+
+    fn parse_period(period: &str) -> Result<(NaiveDate, NaiveDate)> {
+        let start = NaiveDate::parse_from_str(&format!("{}-01", period), "%Y-%m-%d").map_err(|_| {
+            crate::core::error::CLIERPError::Validation(format!(
+                "Invalid period '{}', expected YYYY-MM",
+                period
+            ))
+        })?;
+        let end = start
+            .checked_add_months(chrono::Months::new(1))
+            .ok_or_else(|| crate::core::error::CLIERPError::Validation("Period out of range".to_string()))?;
+        Ok((start, end))
+    }
+
+    /// Goods received during the period against POs with a dispatch country
+    fn collect_arrivals(
+        conn: &mut DatabaseConnection,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<CustomsEntry>> {
+        // `end` is exclusive; movement timestamps are stored in UTC
+        let (from, to) = DisplayTimezone::current().date_range_utc(start, end - Duration::days(1));
+        let movements = stock_movements::table
+            .filter(stock_movements::reference_type.eq("purchase_order"))
+            .filter(stock_movements::movement_date.ge(from))
+            .filter(stock_movements::movement_date.lt(to))
+            .load::<StockMovement>(conn)?;
+
+        let po_ids: Vec<i32> = movements.iter().filter_map(|m| m.reference_id).collect();
+        let cross_border_pos: HashMap<i32, String> = purchase_orders::table
+            .filter(purchase_orders::id.eq_any(&po_ids))
+            .filter(purchase_orders::dispatch_country.is_not_null())
+            .load::<PurchaseOrder>(conn)?
+            .into_iter()
+            .filter_map(|po| po.dispatch_country.map(|country| (po.id, country)))
+            .collect();
+
+        Ok(movements
+            .into_iter()
+            .filter_map(|m| {
+                let country = cross_border_pos.get(&m.reference_id?)?;
+                Some(CustomsEntry {
+                    product_id: m.product_id,
+                    partner_country: country.clone(),
+                    quantity: m.quantity as i64,
+                    value: m.quantity as i64 * m.unit_cost.unwrap_or(0) as i64,
+                })
+            })
+            .collect())
+    }
+
+    /// Won deals closed during the period with a destination country
+    fn collect_dispatches(
+        conn: &mut DatabaseConnection,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<CustomsEntry>> {
+        let won_deals = deals::table
+            .filter(deals::stage.eq(DealStage::ClosedWon.to_string()))
+            .filter(deals::close_date.ge(start))
+            .filter(deals::close_date.lt(end))
+            .filter(deals::destination_country.is_not_null())
+            .load::<Deal>(conn)?;
+
+        let mut entries = Vec::new();
+        for deal in won_deals {
+            let country = match &deal.destination_country {
+                Some(country) => country.clone(),
+                None => continue,
+            };
+            let items: Vec<DealProduct> = match &deal.products {
+                Some(json) => serde_json::from_str(json)?,
+                None => {
+                    tracing::warn!("Deal {} has no product lines, skipping for Intrastat", deal.id);
+                    continue;
+                }
+            };
+            for item in items {
+                entries.push(CustomsEntry {
+                    product_id: item.product_id,
+                    partner_country: country.clone(),
+                    quantity: item.quantity as i64,
+                    value: item.total_price() as i64,
+                });
+            }
+        }
+
+        Ok(entries)
+    }
+}
+
+/// Group entries by commodity code, partner country and origin
+fn aggregate_lines(
+    entries: &[CustomsEntry],
+    product_map: &HashMap<i32, Product>,
+) -> (Vec<IntrastatLine>, Vec<String>) {
+    let mut grouped: BTreeMap<(String, String, String), IntrastatLine> = BTreeMap::new();
+    let mut warnings = Vec::new();
+
+    for entry in entries {
+        let product = match product_map.get(&entry.product_id) {
+            Some(product) => product,
+            None => continue,
+        };
+        let commodity_code = match &product.commodity_code {
+            Some(code) => code.clone(),
+            None => {
+                let warning = format!("Product {} ({}) has no commodity code", product.sku, product.name);
+                if !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+                continue;
+            }
+        };
+        if product.net_weight_grams.is_none() {
+            let warning = format!("Product {} ({}) has no net weight", product.sku, product.name);
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        }
+        let origin = product.country_of_origin.clone().unwrap_or_else(|| "QU".to_string());
+
+        let line = grouped
+            .entry((commodity_code.clone(), entry.partner_country.clone(), origin.clone()))
+            .or_insert_with(|| IntrastatLine {
+                commodity_code,
+                partner_country: entry.partner_country.clone(),
+                country_of_origin: origin,
+                quantity: 0,
+                net_mass_grams: 0,
+                invoice_value: 0,
+            });
+        line.quantity += entry.quantity;
+        line.net_mass_grams += entry.quantity * product.net_weight_grams.unwrap_or(0) as i64;
+        line.invoice_value += entry.value;
+    }
+
+    (grouped.into_values().collect(), warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn product(id: i32, code: Option<&str>, origin: Option<&str>, weight: Option<i32>) -> Product {
+        Product {
+            id,
+            sku: format!("SKU{}", id),
+            name: format!("Product {}", id),
+            description: None,
+            category_id: 1,
+            price: 1000,
+            cost_price: 500,
+            current_stock: 0,
+            min_stock_level: 0,
+            max_stock_level: None,
+            unit: "ea".to_string(),
+            barcode: None,
+            is_active: true,
+            created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+            commodity_code: code.map(|s| s.to_string()),
+            country_of_origin: origin.map(|s| s.to_string()),
+            net_weight_grams: weight,
+        }
+    }
+
+    fn entry(product_id: i32, country: &str, quantity: i64, value: i64) -> CustomsEntry {
+        CustomsEntry {
+            product_id,
+            partner_country: country.to_string(),
+            quantity,
+            value,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_groups_by_code_and_country() {
+        let mut products = HashMap::new();
+        products.insert(1, product(1, Some("84713000"), Some("CN"), Some(1500)));
+        products.insert(2, product(2, Some("84713000"), Some("CN"), Some(500)));
+
+        let entries = vec![entry(1, "DE", 2, 2000), entry(2, "DE", 4, 800), entry(1, "FR", 1, 1000)];
+        let (lines, warnings) = aggregate_lines(&entries, &products);
+
+        assert!(warnings.is_empty());
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].partner_country, "DE");
+        assert_eq!(lines[0].quantity, 6);
+        assert_eq!(lines[0].net_mass_grams, 2 * 1500 + 4 * 500);
+        assert_eq!(lines[0].invoice_value, 2800);
+        assert_eq!(lines[1].partner_country, "FR");
+    }
+
+    #[test]
+    fn test_aggregate_skips_products_without_commodity_code() {
+        let mut products = HashMap::new();
+        products.insert(1, product(1, None, None, None));
+
+        let (lines, warnings) = aggregate_lines(&[entry(1, "DE", 1, 100), entry(1, "DE", 1, 100)], &products);
+
+        assert!(lines.is_empty());
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_period() {
+        let (start, end) = IntrastatService::parse_period("2024-12").unwrap();
+        assert_eq!(start, NaiveDate::from_ymd_opt(2024, 12, 1).unwrap());
+        assert_eq!(end, NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
+        assert!(IntrastatService::parse_period("2024-13").is_err());
+    }
+}
@@ -1,15 +1,39 @@
 pub mod category;
 pub mod product;
 pub mod attachment;
+pub mod catalog;
 pub mod barcode;
 pub mod audit;
 pub mod supplier;
 pub mod purchase_order;
+pub mod intrastat;
+pub mod reservation;
+pub mod atp;
+pub mod import;
+pub mod adjustment;
+pub mod bundle;
+pub mod aging;
+pub mod reconciliation;
+pub mod translation;
+pub mod supplier_portal;
+pub mod write_down;
 
 pub use category::*;
 pub use product::*;
 pub use attachment::*;
+pub use catalog::*;
 pub use barcode::*;
 pub use audit::*;
 pub use supplier::*;
 pub use purchase_order::*;
+pub use intrastat::*;
+pub use reservation::*;
+pub use atp::*;
+pub use import::*;
+pub use adjustment::*;
+pub use bundle::*;
+pub use aging::*;
+pub use reconciliation::*;
+pub use translation::*;
+pub use supplier_portal::*;
+pub use write_down::*;
@@ -1,12 +1,58 @@
 use chrono::Utc;
 use diesel::prelude::*;
+use std::collections::HashSet;
 
 use crate::core::result::CLIERPResult;
-use crate::database::connection::get_connection;
-use crate::database::models::{Product, NewProduct, StockMovement, NewStockMovement, Category};
+use crate::database::connection::{get_connection, DatabaseConnection};
+use crate::database::models::{Product, NewProduct, StockMovement, NewStockMovement, Category, EventAction, ValidationRule};
 use crate::database::schema::{products, stock_movements, categories};
+use crate::modules::finance::{stock_value, PostingService};
+use crate::modules::inventory::reservation::ReservationService;
+use crate::modules::reporting::summaries::SummaryService;
+use crate::modules::system::audit::AuditService;
+use crate::modules::system::events::EventService;
+use crate::modules::system::rules::ValidationRuleService;
+use crate::utils::cache::QueryCache;
+use crate::utils::filter_expr::{FilterExpr, FilterField};
+use crate::utils::filters::FieldAssignment;
+use crate::utils::import::{plan_batch, BatchAction, ConflictStrategy};
 use crate::utils::pagination::{PaginationParams, PaginationResult};
-use crate::utils::validation::{validate_required_string, ValidationResult};
+use crate::utils::timezone::DisplayTimezone;
+use crate::utils::validation::{
+    apply_rules, validate_commodity_code, validate_country_code, validate_required_string,
+};
+
+/// `reference_type` of a counter-movement; its `reference_id` is the reversed movement
+pub const REVERSAL_REFERENCE: &str = "reversal";
+/// `reference_type` of the movement carrying the net quantity of archived movements
+pub const ARCHIVE_SUMMARY_REFERENCE: &str = "archive_summary";
+
+/// Fields `product list --where` may use
+pub const PRODUCT_WHERE_FIELDS: &[FilterField] = &[
+    FilterField::integer("id", "products.id"),
+    FilterField::text("sku", "products.sku"),
+    FilterField::text("name", "products.name"),
+    FilterField::integer("category", "products.category_id"),
+    FilterField::text("category_name", "categories.name"),
+    FilterField::integer("price", "products.price"),
+    FilterField::integer("cost", "products.cost_price"),
+    FilterField::integer("stock", "products.current_stock"),
+    FilterField::integer("min_stock", "products.min_stock_level"),
+    FilterField::integer("max_stock", "products.max_stock_level"),
+    FilterField::text("unit", "products.unit"),
+    FilterField::text("barcode", "products.barcode"),
+    FilterField::boolean("active", "products.is_active"),
+    FilterField::text("origin", "products.country_of_origin"),
+    FilterField::date("created", "products.created_at"),
+];
+
+/// Outcome of a batched product insert
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BulkInsertSummary {
+    pub inserted: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
 
 #[derive(Debug, Clone)]
 pub struct ProductService;
@@ -16,6 +62,7 @@ impl ProductService {
         Self
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_product(
         &self,
         sku: &str,
@@ -30,54 +77,72 @@ impl ProductService {
         unit: &str,
         barcode: Option<&str>,
     ) -> CLIERPResult<Product> {
-        // Validate inputs
-        validate_required_string(sku, "SKU")?;
-        validate_required_string(name, "Product name")?;
-        validate_required_string(unit, "Unit")?;
-
-        if price < 0 {
-            return Err(crate::core::error::CLIERPError::ValidationError(
-                "Price cannot be negative".to_string(),
-            ));
-        }
-
-        if cost_price < 0 {
-            return Err(crate::core::error::CLIERPError::ValidationError(
-                "Cost price cannot be negative".to_string(),
-            ));
-        }
-
-        if initial_stock < 0 {
-            return Err(crate::core::error::CLIERPError::ValidationError(
-                "Initial stock cannot be negative".to_string(),
-            ));
-        }
-
-        if min_stock_level < 0 {
-            return Err(crate::core::error::CLIERPError::ValidationError(
-                "Minimum stock level cannot be negative".to_string(),
-            ));
-        }
+        let mut connection = get_connection()?;
+        self.create_product_with_connection(
+            &mut connection,
+            sku,
+            name,
+            description,
+            category_id,
+            price,
+            cost_price,
+            initial_stock,
+            min_stock_level,
+            max_stock_level,
+            unit,
+            barcode,
+        )
+    }
 
-        if let Some(max_level) = max_stock_level {
-            if max_level < min_stock_level {
-                return Err(crate::core::error::CLIERPError::ValidationError(
-                    "Maximum stock level cannot be less than minimum stock level".to_string(),
-                ));
-            }
-        }
+    /// `create_product` on a caller's connection, so imports can create
+    /// many products in one transaction
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_product_with_connection(
+        &self,
+        connection: &mut DatabaseConnection,
+        sku: &str,
+        name: &str,
+        description: Option<&str>,
+        category_id: i32,
+        price: i32,
+        cost_price: i32,
+        initial_stock: i32,
+        min_stock_level: i32,
+        max_stock_level: Option<i32>,
+        unit: &str,
+        barcode: Option<&str>,
+    ) -> CLIERPResult<Product> {
+        let new_product = NewProduct {
+            sku: sku.to_string(),
+            name: name.to_string(),
+            description: description.map(|s| s.to_string()),
+            category_id,
+            price,
+            cost_price,
+            current_stock: initial_stock,
+            min_stock_level,
+            max_stock_level,
+            unit: unit.to_string(),
+            barcode: barcode.map(|s| s.to_string()),
+            is_active: true,
+            commodity_code: None,
+            country_of_origin: None,
+            net_weight_grams: None,
+        };
 
-        let mut connection = get_connection()?;
+        // Built-in checks plus deployment-specific rules, e.g. a company SKU format
+        let rules = ValidationRuleService::rules_for(connection, "product")?;
+        check_new_product(&new_product, &rules)?;
 
         // Check if category exists
         categories::table
             .find(category_id)
-            .first::<Category>(&mut connection)?;
+            .first::<Category>(connection)?;
 
         // Check for duplicate SKU
         let existing = products::table
             .filter(products::sku.eq(sku))
-            .first::<Product>(&mut connection)
+            .first::<Product>(connection)
             .optional()?;
 
         if existing.is_some() {
@@ -86,28 +151,13 @@ impl ProductService {
             ));
         }
 
-        let new_product = NewProduct {
-            sku: sku.to_string(),
-            name: name.to_string(),
-            description: description.map(|s| s.to_string()),
-            category_id,
-            price,
-            cost_price,
-            current_stock: initial_stock,
-            min_stock_level,
-            max_stock_level,
-            unit: unit.to_string(),
-            barcode: barcode.map(|s| s.to_string()),
-            is_active: true,
-        };
-
         diesel::insert_into(products::table)
             .values(&new_product)
-            .execute(&mut connection)?;
+            .execute(connection)?;
 
         let product = products::table
             .order(products::id.desc())
-            .first::<Product>(&mut connection)?;
+            .first::<Product>(connection)?;
 
         // Create initial stock movement if stock > 0
         if initial_stock > 0 {
@@ -124,13 +174,146 @@ impl ProductService {
 
             diesel::insert_into(stock_movements::table)
                 .values(&stock_movement)
-                .execute(&mut connection)?;
+                .execute(connection)?;
+            SummaryService::refresh_product_day(connection, product.id, Utc::now().date_naive())?;
         }
+        EventService::record(connection, "products", product.id, EventAction::Created, &product)?;
 
+        QueryCache::current().invalidate(&["inventory"]);
         tracing::info!("Created product: {} (SKU: {})", product.name, product.sku);
         Ok(product)
     }
 
+    /// Create many products with a single insert statement. SKUs that
+    /// already exist, or repeat within `products`, are handled by
+    /// `on_conflict`.
+    ///
+    /// Updates overwrite the existing product's catalogue fields but never
+    /// its stock. New products with stock get an initial stock movement as
+    /// in `create_product`. Call it inside a transaction so a failing batch
+    /// leaves nothing behind.
+    pub fn bulk_create_products(
+        &self,
+        connection: &mut DatabaseConnection,
+        products_batch: &[NewProduct],
+        on_conflict: ConflictStrategy,
+        moved_by: Option<i32>,
+    ) -> CLIERPResult<BulkInsertSummary> {
+        let rules = ValidationRuleService::rules_for(connection, "product")?;
+        for product in products_batch {
+            check_new_product(product, &rules).map_err(|e| {
+                crate::core::error::CLIERPError::ValidationError(format!("SKU {}: {}", product.sku, e))
+            })?;
+        }
+
+        let mut category_ids: Vec<i32> = products_batch.iter().map(|p| p.category_id).collect();
+        category_ids.sort_unstable();
+        category_ids.dedup();
+        let known_categories = categories::table
+            .filter(categories::id.eq_any(&category_ids))
+            .select(categories::id)
+            .load::<i32>(connection)?;
+        if let Some(missing) = category_ids.iter().find(|id| !known_categories.contains(id)) {
+            return Err(crate::core::error::CLIERPError::NotFound(format!(
+                "Category with ID {} not found",
+                missing
+            )));
+        }
+
+        let skus: Vec<&str> = products_batch.iter().map(|p| p.sku.as_str()).collect();
+        let existing: HashSet<String> = products::table
+            .filter(products::sku.eq_any(&skus))
+            .select(products::sku)
+            .load::<String>(connection)?
+            .into_iter()
+            .collect();
+        let actions = plan_batch("SKU", &skus, &existing, on_conflict)?;
+
+        let inserts: Vec<&NewProduct> = products_batch
+            .iter()
+            .zip(&actions)
+            .filter(|(_, action)| **action == BatchAction::Insert)
+            .map(|(product, _)| product)
+            .collect();
+        if !inserts.is_empty() {
+            diesel::insert_into(products::table)
+                .values(inserts.iter().map(|p| (*p).clone()).collect::<Vec<_>>())
+                .execute(connection)?;
+
+            let inserted_skus: Vec<&str> = inserts.iter().map(|p| p.sku.as_str()).collect();
+            let initial_stock: Vec<NewStockMovement> = products::table
+                .filter(products::sku.eq_any(&inserted_skus))
+                .filter(products::current_stock.gt(0))
+                .load::<Product>(connection)?
+                .into_iter()
+                .map(|product| NewStockMovement {
+                    product_id: product.id,
+                    movement_type: "in".to_string(),
+                    quantity: product.current_stock,
+                    unit_cost: Some(product.cost_price),
+                    reference_type: Some("initial_stock".to_string()),
+                    reference_id: None,
+                    notes: Some("Initial stock entry".to_string()),
+                    moved_by,
+                })
+                .collect();
+            if !initial_stock.is_empty() {
+                diesel::insert_into(stock_movements::table)
+                    .values(&initial_stock)
+                    .execute(connection)?;
+                let today = Utc::now().date_naive();
+                for movement in &initial_stock {
+                    SummaryService::refresh_product_day(connection, movement.product_id, today)?;
+                }
+            }
+        }
+
+        // Updates run after the inserts so later rows win over earlier ones
+        let mut updated = 0;
+        for (product, _) in products_batch
+            .iter()
+            .zip(&actions)
+            .filter(|(_, action)| **action == BatchAction::Update)
+        {
+            let before = products::table
+                .filter(products::sku.eq(&product.sku))
+                .first::<Product>(connection)?;
+            let changeset = ProductUpdateChangeset {
+                name: Some(product.name.clone()),
+                description: Some(product.description.clone()),
+                category_id: Some(product.category_id),
+                price: Some(product.price),
+                cost_price: Some(product.cost_price),
+                min_stock_level: Some(product.min_stock_level),
+                max_stock_level: Some(product.max_stock_level),
+                unit: Some(product.unit.clone()),
+                barcode: Some(product.barcode.clone()),
+                updated_at: Some(Utc::now().naive_utc()),
+                ..Default::default()
+            };
+            diesel::update(products::table.find(before.id))
+                .set(&changeset)
+                .execute(connection)?;
+            let after = products::table.find(before.id).first::<Product>(connection)?;
+            AuditService::record_update(connection, "products", before.id, &before, &after)?;
+            updated += 1;
+        }
+
+        let summary = BulkInsertSummary {
+            inserted: inserts.len(),
+            updated,
+            skipped: actions.iter().filter(|a| **a == BatchAction::Skip).count(),
+        };
+        QueryCache::current().invalidate(&["inventory"]);
+        tracing::info!(
+            "Bulk created {} products ({} updated, {} skipped)",
+            summary.inserted,
+            summary.updated,
+            summary.skipped
+        );
+        Ok(summary)
+    }
+
     pub fn get_product_by_id(&self, id: i32) -> CLIERPResult<Product> {
         let mut connection = get_connection()?;
 
@@ -159,8 +342,22 @@ impl ProductService {
         active_only: bool,
         search_term: Option<&str>,
         low_stock_only: bool,
+    ) -> CLIERPResult<PaginationResult<ProductWithCategory>> {
+        self.list_products_where(pagination, category_id, active_only, search_term, low_stock_only, None)
+    }
+
+    /// `list_products`, further narrowed by a `--where` expression
+    pub fn list_products_where(
+        &self,
+        pagination: &PaginationParams,
+        category_id: Option<i32>,
+        active_only: bool,
+        search_term: Option<&str>,
+        low_stock_only: bool,
+        filter: Option<&FilterExpr>,
     ) -> CLIERPResult<PaginationResult<ProductWithCategory>> {
         let mut connection = get_connection()?;
+        let predicate = filter.map(|expr| expr.predicate(PRODUCT_WHERE_FIELDS)).transpose()?;
 
         let mut query = products::table
             .inner_join(categories::table)
@@ -193,6 +390,10 @@ impl ProductService {
             query = query.filter(products::current_stock.le(products::min_stock_level));
         }
 
+        if let Some(predicate) = &predicate {
+            query = query.filter(predicate.clone());
+        }
+
         // Get total count
         let total_count = {
             let mut count_query = products::table
@@ -214,6 +415,9 @@ impl ProductService {
             if low_stock_only {
                 count_query = count_query.filter(products::current_stock.le(products::min_stock_level));
             }
+            if let Some(predicate) = predicate {
+                count_query = count_query.filter(predicate);
+            }
 
             count_query.count().get_result::<i64>(&mut connection)? as usize
         };
@@ -236,6 +440,7 @@ impl ProductService {
         Ok(PaginationResult::new_simple(products_with_category, total_count, pagination))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn update_product(
         &self,
         id: i32,
@@ -255,6 +460,32 @@ impl ProductService {
         // Check if product exists
         let existing_product = self.get_product_by_id(id)?;
 
+        // Values equal to the current ones are not written, so an update
+        // that changes nothing leaves the product untouched
+        let name = name.filter(|n| *n != existing_product.name);
+        let description = description.filter(|d| *d != existing_product.description.as_deref());
+        let category_id = category_id.filter(|c| *c != existing_product.category_id);
+        let price = price.filter(|p| *p != existing_product.price);
+        let cost_price = cost_price.filter(|c| *c != existing_product.cost_price);
+        let min_stock_level = min_stock_level.filter(|m| *m != existing_product.min_stock_level);
+        let max_stock_level = max_stock_level.filter(|m| *m != existing_product.max_stock_level);
+        let unit = unit.filter(|u| *u != existing_product.unit);
+        let barcode = barcode.filter(|b| *b != existing_product.barcode.as_deref());
+        let is_active = is_active.filter(|a| *a != existing_product.is_active);
+        if name.is_none()
+            && description.is_none()
+            && category_id.is_none()
+            && price.is_none()
+            && cost_price.is_none()
+            && min_stock_level.is_none()
+            && max_stock_level.is_none()
+            && unit.is_none()
+            && barcode.is_none()
+            && is_active.is_none()
+        {
+            return Ok(existing_product);
+        }
+
         // Validate inputs
         if let Some(name) = name {
             validate_required_string(name, "Product name")?;
@@ -335,11 +566,61 @@ impl ProductService {
             .execute(&mut connection)?;
 
         let updated_product = self.get_product_by_id(id)?;
+        AuditService::record_update(&mut connection, "products", id, &existing_product, &updated_product)?;
 
+        QueryCache::current().invalidate(&["inventory"]);
         tracing::info!("Updated product: {} (SKU: {})", updated_product.name, updated_product.sku);
         Ok(updated_product)
     }
 
+    /// Set the customs fields used for Intrastat declarations. Passing
+    /// `None` leaves a field unchanged; `Some(None)` clears it.
+    pub fn update_customs_info(
+        &self,
+        id: i32,
+        commodity_code: Option<Option<&str>>,
+        country_of_origin: Option<Option<&str>>,
+        net_weight_grams: Option<Option<i32>>,
+    ) -> CLIERPResult<Product> {
+        let mut connection = get_connection()?;
+
+        // Check if product exists
+        let existing_product = self.get_product_by_id(id)?;
+
+        if let Some(Some(code)) = commodity_code {
+            validate_commodity_code(code)?;
+        }
+        if let Some(Some(country)) = country_of_origin {
+            validate_country_code(country)?;
+        }
+        if let Some(Some(weight)) = net_weight_grams {
+            if weight < 0 {
+                return Err(crate::core::error::CLIERPError::ValidationError(
+                    "Net weight cannot be negative".to_string(),
+                ));
+            }
+        }
+
+        let changeset = ProductUpdateChangeset {
+            commodity_code: commodity_code.map(|c| c.map(|s| s.to_string())),
+            country_of_origin: country_of_origin.map(|c| c.map(|s| s.to_string())),
+            net_weight_grams,
+            updated_at: Some(Utc::now().naive_utc()),
+            ..Default::default()
+        };
+
+        diesel::update(products::table.find(id))
+            .set(&changeset)
+            .execute(&mut connection)?;
+
+        let updated_product = self.get_product_by_id(id)?;
+        AuditService::record_update(&mut connection, "products", id, &existing_product, &updated_product)?;
+
+        tracing::info!("Updated customs info for product: {} (SKU: {})", updated_product.name, updated_product.sku);
+        Ok(updated_product)
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn update_stock(
         &self,
         product_id: i32,
@@ -381,6 +662,9 @@ impl ProductService {
             ));
         }
 
+        // Stock outs and adjustments may not dip into quantity held for orders
+        ReservationService::ensure_unreserved(&mut connection, product_id, new_stock)?;
+
         // Create stock movement record
         let stock_movement = NewStockMovement {
             product_id,
@@ -397,7 +681,7 @@ impl ProductService {
         product.current_stock = new_stock;
 
         // Execute in transaction
-        connection.transaction::<_, diesel::result::Error, _>(|conn| {
+        connection.transaction::<_, crate::core::error::CLIERPError, _>(|conn| {
             // Insert stock movement
             diesel::insert_into(stock_movements::table)
                 .values(&stock_movement)
@@ -411,12 +695,37 @@ impl ProductService {
                 ))
                 .execute(conn)?;
 
+            // Issued stock leaves inventory at cost
+            if movement_type == "out" {
+                let movement_id = stock_movements::table
+                    .filter(stock_movements::product_id.eq(product_id))
+                    .order(stock_movements::id.desc())
+                    .select(stock_movements::id)
+                    .first::<i32>(conn)?;
+                PostingService::post_issue(
+                    conn,
+                    movement_id,
+                    &format!("{} x {}", quantity_change.abs(), product.sku),
+                    stock_value(quantity_change, unit_cost.unwrap_or(product.cost_price))?,
+                    DisplayTimezone::current().today(),
+                    moved_by,
+                )?;
+            }
+
             Ok(())
         })?;
 
         // Reload product to get updated data
         product = self.get_product_by_id(product_id)?;
+        let movement = stock_movements::table
+            .filter(stock_movements::product_id.eq(product_id))
+            .order(stock_movements::id.desc())
+            .first::<StockMovement>(&mut connection)?;
+        SummaryService::refresh_product_day(&mut connection, product_id, movement.movement_date.date())?;
+        EventService::record(&mut connection, "stock_movements", movement.id, EventAction::Created, &movement)?;
+        EventService::record(&mut connection, "products", product.id, EventAction::Updated, &product)?;
 
+        QueryCache::current().invalidate(&["inventory"]);
         tracing::info!(
             "Updated stock for product {} ({}): {} -> {}",
             product.name,
@@ -428,6 +737,121 @@ impl ProductService {
         Ok(product)
     }
 
+    /// Undo a stock movement by recording a linked counter-movement. The
+    /// original row is kept untouched so the audit trail shows both.
+    /// Reversing an issue also reverses the entry posted for it.
+    pub fn reverse_movement(
+        &self,
+        movement_id: i32,
+        reason: &str,
+        moved_by: Option<i32>,
+    ) -> CLIERPResult<StockMovement> {
+        validate_required_string(reason, "Reason")?;
+        let mut connection = get_connection()?;
+
+        let original = stock_movements::table
+            .find(movement_id)
+            .first::<StockMovement>(&mut connection)
+            .optional()?
+            .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
+                format!("Stock movement with ID {} not found", movement_id),
+            ))?;
+
+        if original.reference_type.as_deref() == Some(REVERSAL_REFERENCE) {
+            return Err(crate::core::error::CLIERPError::ValidationError(
+                "A reversal cannot itself be reversed; record a new movement instead".to_string(),
+            ));
+        }
+        if original.reference_type.as_deref() == Some(ARCHIVE_SUMMARY_REFERENCE) {
+            return Err(crate::core::error::CLIERPError::ValidationError(
+                "An archive summary cannot be reversed; record an adjustment instead".to_string(),
+            ));
+        }
+
+        let existing_reversal = stock_movements::table
+            .filter(stock_movements::reference_type.eq(REVERSAL_REFERENCE))
+            .filter(stock_movements::reference_id.eq(movement_id))
+            .first::<StockMovement>(&mut connection)
+            .optional()?;
+        if let Some(reversal) = existing_reversal {
+            return Err(crate::core::error::CLIERPError::ValidationError(format!(
+                "Stock movement {} was already reversed by movement {}",
+                movement_id, reversal.id
+            )));
+        }
+
+        // Out movements are stored as negative quantities, in movements as
+        // positive ones and adjustments as a signed delta
+        let (movement_type, quantity) = match original.movement_type.as_str() {
+            "in" => ("out", -original.quantity.abs()),
+            "out" => ("in", original.quantity.abs()),
+            _ => ("adjustment", -original.quantity),
+        };
+
+        let product = self.get_product_by_id(original.product_id)?;
+        let new_stock = product.current_stock + quantity;
+        if new_stock < 0 {
+            return Err(crate::core::error::CLIERPError::ValidationError(format!(
+                "Reversing movement {} would leave {} with negative stock ({})",
+                movement_id, product.sku, new_stock
+            )));
+        }
+        ReservationService::ensure_unreserved(&mut connection, original.product_id, new_stock)?;
+
+        let counter_movement = NewStockMovement {
+            product_id: original.product_id,
+            movement_type: movement_type.to_string(),
+            quantity,
+            unit_cost: original.unit_cost,
+            reference_type: Some(REVERSAL_REFERENCE.to_string()),
+            reference_id: Some(movement_id),
+            notes: Some(format!("Reversal of movement #{}: {}", movement_id, reason)),
+            moved_by,
+        };
+
+        let reversal = connection.transaction::<_, crate::core::error::CLIERPError, _>(|conn| {
+            diesel::insert_into(stock_movements::table)
+                .values(&counter_movement)
+                .execute(conn)?;
+
+            diesel::update(products::table.find(original.product_id))
+                .set((
+                    products::current_stock.eq(new_stock),
+                    products::updated_at.eq(Utc::now().naive_utc()),
+                ))
+                .execute(conn)?;
+
+            // Stock back from an issue takes its cost off cost of goods sold
+            if original.movement_type == "out" {
+                PostingService::reverse_issue(
+                    conn,
+                    movement_id,
+                    reason,
+                    DisplayTimezone::current().today(),
+                    moved_by,
+                )?;
+            }
+
+            Ok(stock_movements::table
+                .filter(stock_movements::reference_type.eq(REVERSAL_REFERENCE))
+                .filter(stock_movements::reference_id.eq(movement_id))
+                .first::<StockMovement>(conn)?)
+        })?;
+        SummaryService::refresh_product_day(&mut connection, reversal.product_id, reversal.movement_date.date())?;
+
+        QueryCache::current().invalidate(&["inventory"]);
+        tracing::info!(
+            "Reversed stock movement {} with movement {} for product {} ({}): {} -> {}",
+            movement_id,
+            reversal.id,
+            product.name,
+            product.sku,
+            product.current_stock,
+            new_stock
+        );
+        Ok(reversal)
+    }
+
     pub fn get_stock_movements(
         &self,
         product_id: i32,
@@ -495,14 +919,167 @@ impl ProductService {
         if force {
             diesel::delete(stock_movements::table.filter(stock_movements::product_id.eq(id)))
                 .execute(&mut connection)?;
+            SummaryService::refresh_product(&mut connection, id)?;
         }
 
         // Delete the product
         diesel::delete(products::table.find(id)).execute(&mut connection)?;
+        EventService::record(&mut connection, "products", id, EventAction::Deleted, &product)?;
 
+        QueryCache::current().invalidate(&["inventory"]);
         tracing::info!("Deleted product: {} (SKU: {})", product.name, product.sku);
         Ok(())
     }
+
+    /// Products matching every `field=value` filter. A trailing `*` on `sku`
+    /// matches by prefix.
+    pub fn find_products_by_filters(&self, filters: &[FieldAssignment]) -> CLIERPResult<Vec<Product>> {
+        let mut connection = get_connection()?;
+        let products = filtered_products(filters)?.load::<Product>(&mut connection)?;
+        Ok(products)
+    }
+
+    /// Apply `updates` to every product matching `filters` in a single
+    /// transaction. Any failing record rolls back the whole batch.
+    pub fn bulk_update_products(
+        &self,
+        filters: &[FieldAssignment],
+        updates: &[FieldAssignment],
+    ) -> CLIERPResult<usize> {
+        if updates.is_empty() {
+            return Err(crate::core::error::CLIERPError::ValidationError(
+                "At least one --set is required".to_string(),
+            ));
+        }
+
+        let mut connection = get_connection()?;
+        let mut changeset = ProductUpdateChangeset::default();
+        let mut rule_values: Vec<(&str, Option<String>)> = Vec::new();
+
+        for update in updates {
+            match update.field.as_str() {
+                "category" | "category_id" => {
+                    let category_id = update.as_i32()?;
+                    categories::table
+                        .find(category_id)
+                        .first::<Category>(&mut connection)?;
+                    changeset.category_id = Some(category_id);
+                }
+                "price" | "cost_price" => {
+                    let amount = update.as_i32()?;
+                    if amount < 0 {
+                        return Err(crate::core::error::CLIERPError::ValidationError(
+                            format!("{} cannot be negative", update.field),
+                        ));
+                    }
+                    if update.field == "price" {
+                        changeset.price = Some(amount);
+                        rule_values.push(("price", Some(amount.to_string())));
+                    } else {
+                        changeset.cost_price = Some(amount);
+                        rule_values.push(("cost_price", Some(amount.to_string())));
+                    }
+                }
+                "min_stock" | "min_stock_level" => {
+                    let level = update.as_i32()?;
+                    if level < 0 {
+                        return Err(crate::core::error::CLIERPError::ValidationError(
+                            "Minimum stock level cannot be negative".to_string(),
+                        ));
+                    }
+                    changeset.min_stock_level = Some(level);
+                }
+                "max_stock" | "max_stock_level" => {
+                    changeset.max_stock_level = Some(update.as_optional_i32()?);
+                }
+                "unit" => {
+                    validate_required_string(&update.value, "Unit")?;
+                    changeset.unit = Some(update.value.clone());
+                    rule_values.push(("unit", Some(update.value.clone())));
+                }
+                "description" => {
+                    changeset.description = Some(update.as_optional().map(|s| s.to_string()));
+                    rule_values.push(("description", update.as_optional().map(|s| s.to_string())));
+                }
+                "active" | "is_active" => {
+                    changeset.is_active = Some(update.as_bool()?);
+                }
+                _ => return Err(update.unsupported(PRODUCT_UPDATE_FIELDS)),
+            }
+        }
+        changeset.updated_at = Some(Utc::now().naive_utc());
+
+        let rule_fields: Vec<(&str, Option<&str>)> =
+            rule_values.iter().map(|(field, value)| (*field, value.as_deref())).collect();
+        ValidationRuleService::validate(&mut connection, "product", &rule_fields)?;
+
+        let updated = connection.transaction::<_, crate::core::error::CLIERPError, _>(|conn| {
+            let matches = filtered_products(filters)?.load::<Product>(conn)?;
+
+            for before in &matches {
+                diesel::update(products::table.find(before.id))
+                    .set(&changeset)
+                    .execute(conn)?;
+                let after = products::table.find(before.id).first::<Product>(conn)?;
+
+                if let Some(max_level) = after.max_stock_level {
+                    if max_level < after.min_stock_level {
+                        return Err(crate::core::error::CLIERPError::ValidationError(format!(
+                            "Maximum stock level cannot be less than minimum stock level (SKU: {})",
+                            after.sku
+                        )));
+                    }
+                }
+                AuditService::record_update(conn, "products", before.id, before, &after)?;
+            }
+
+            Ok(matches.len())
+        })?;
+
+        QueryCache::current().invalidate(&["inventory"]);
+        tracing::info!("Bulk updated {} products", updated);
+        Ok(updated)
+    }
+}
+
+impl Default for ProductService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const PRODUCT_FILTER_FIELDS: &[&str] = &["id", "category", "sku", "unit", "active", "low_stock"];
+const PRODUCT_UPDATE_FIELDS: &[&str] = &[
+    "category", "price", "cost_price", "min_stock", "max_stock", "unit", "description", "active",
+];
+
+fn filtered_products(
+    filters: &[FieldAssignment],
+) -> CLIERPResult<products::BoxedQuery<'static, diesel::sqlite::Sqlite>> {
+    let mut query = products::table.into_boxed();
+
+    for filter in filters {
+        query = match filter.field.as_str() {
+            "id" => query.filter(products::id.eq(filter.as_i32()?)),
+            "category" | "category_id" => query.filter(products::category_id.eq(filter.as_i32()?)),
+            "sku" => match filter.value.strip_suffix('*') {
+                Some(prefix) => query.filter(products::sku.like(format!("{}%", prefix))),
+                None => query.filter(products::sku.eq(filter.value.clone())),
+            },
+            "unit" => query.filter(products::unit.eq(filter.value.clone())),
+            "active" | "is_active" => query.filter(products::is_active.eq(filter.as_bool()?)),
+            "low_stock" => {
+                if filter.as_bool()? {
+                    query.filter(products::current_stock.le(products::min_stock_level))
+                } else {
+                    query.filter(products::current_stock.gt(products::min_stock_level))
+                }
+            }
+            _ => return Err(filter.unsupported(PRODUCT_FILTER_FIELDS)),
+        };
+    }
+
+    Ok(query.order(products::id.asc()))
 }
 
 #[derive(Debug, Clone)]
@@ -511,6 +1088,60 @@ pub struct ProductWithCategory {
     pub category: Category,
 }
 
+/// Built-in and configured rule checks for a product about to be created
+fn check_new_product(product: &NewProduct, rules: &[ValidationRule]) -> CLIERPResult<()> {
+    validate_required_string(&product.sku, "SKU")?;
+    validate_required_string(&product.name, "Product name")?;
+    validate_required_string(&product.unit, "Unit")?;
+
+    if product.price < 0 {
+        return Err(crate::core::error::CLIERPError::ValidationError(
+            "Price cannot be negative".to_string(),
+        ));
+    }
+
+    if product.cost_price < 0 {
+        return Err(crate::core::error::CLIERPError::ValidationError(
+            "Cost price cannot be negative".to_string(),
+        ));
+    }
+
+    if product.current_stock < 0 {
+        return Err(crate::core::error::CLIERPError::ValidationError(
+            "Initial stock cannot be negative".to_string(),
+        ));
+    }
+
+    if product.min_stock_level < 0 {
+        return Err(crate::core::error::CLIERPError::ValidationError(
+            "Minimum stock level cannot be negative".to_string(),
+        ));
+    }
+
+    if let Some(max_level) = product.max_stock_level {
+        if max_level < product.min_stock_level {
+            return Err(crate::core::error::CLIERPError::ValidationError(
+                "Maximum stock level cannot be less than minimum stock level".to_string(),
+            ));
+        }
+    }
+
+    let price_value = product.price.to_string();
+    let cost_price_value = product.cost_price.to_string();
+    apply_rules(
+        rules,
+        &[
+            ("sku", Some(&product.sku)),
+            ("name", Some(&product.name)),
+            ("description", product.description.as_deref()),
+            ("unit", Some(&product.unit)),
+            ("barcode", product.barcode.as_deref()),
+            ("price", Some(&price_value)),
+            ("cost_price", Some(&cost_price_value)),
+        ],
+    )
+}
+
 #[derive(AsChangeset, Default)]
 #[diesel(table_name = products)]
 struct ProductUpdateChangeset {
@@ -524,17 +1155,98 @@ struct ProductUpdateChangeset {
     unit: Option<String>,
     barcode: Option<Option<String>>,
     is_active: Option<bool>,
+    commodity_code: Option<Option<String>>,
+    country_of_origin: Option<Option<String>>,
+    net_weight_grams: Option<Option<i32>>,
     updated_at: Option<chrono::NaiveDateTime>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::CompanyFixture;
 
     #[test]
     fn test_product_service_creation() {
-        let service = ProductService::new();
         // Basic instantiation test
-        assert!(true);
+        let _service = ProductService::new();
+    }
+
+    fn last_movement(product_id: i32) -> StockMovement {
+        stock_movements::table
+            .filter(stock_movements::product_id.eq(product_id))
+            .order(stock_movements::id.desc())
+            .first::<StockMovement>(&mut get_connection().unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_reverse_movement() {
+        let company = CompanyFixture::new().with_products(1).build().unwrap();
+        let service = ProductService::new();
+        let product = &company.products[0];
+
+        service.update_stock(product.id, 5, "in", None, None, None, None, None).unwrap();
+        let receipt = last_movement(product.id);
+        let reversal = service.reverse_movement(receipt.id, "Keyed twice", Some(company.admin.id)).unwrap();
+        assert_eq!((reversal.movement_type.as_str(), reversal.quantity), ("out", -5));
+        assert_eq!(reversal.reference_type.as_deref(), Some(REVERSAL_REFERENCE));
+        assert_eq!(reversal.reference_id, Some(receipt.id));
+        assert_eq!(service.get_product_by_id(product.id).unwrap().current_stock, product.current_stock);
+
+        assert!(service.reverse_movement(receipt.id, "Again", None).is_err());
+        assert!(service.reverse_movement(reversal.id, "Undo the undo", None).is_err());
+        assert!(service.reverse_movement(receipt.id, " ", None).is_err());
+
+        service.update_stock(product.id, -3, "out", None, None, None, None, None).unwrap();
+        let issue = last_movement(product.id);
+        let reversal = service.reverse_movement(issue.id, "Returned", None).unwrap();
+        assert_eq!((reversal.movement_type.as_str(), reversal.quantity), ("in", 3));
+        assert_eq!(service.get_product_by_id(product.id).unwrap().current_stock, product.current_stock);
+    }
+
+    #[test]
+    fn test_reversed_issue_ties_out_ledger() {
+        use crate::database::schema::accounts;
+        use crate::modules::finance::{AccountService, CreateAccountRequest, PostingRules};
+
+        let company = CompanyFixture::new().with_products(1).build().unwrap();
+        let mut conn = company.connection().unwrap();
+        for (code, name, kind) in [("1300", "Inventory", "asset"), ("2100", "Payables", "liability"), ("5000", "COGS", "expense")] {
+            AccountService::new()
+                .create_account(
+                    &mut conn,
+                    CreateAccountRequest {
+                        account_code: code.to_string(),
+                        account_name: name.to_string(),
+                        account_type: kind.to_string(),
+                        parent_id: None,
+                    },
+                )
+                .unwrap();
+        }
+        let _posting = PostingRules::scope(Some(PostingRules {
+            inventory: "1300".to_string(),
+            payable: "2100".to_string(),
+            cogs: "5000".to_string(),
+        }));
+        let mut balance = |code: &str| {
+            accounts::table
+                .filter(accounts::account_code.eq(code))
+                .select(accounts::balance)
+                .first::<i32>(&mut conn)
+                .unwrap()
+        };
+
+        // Product 1 costs 600
+        let service = ProductService::new();
+        let product = &company.products[0];
+        service.update_stock(product.id, -4, "out", None, None, None, None, None).unwrap();
+        let issue = last_movement(product.id);
+        assert_eq!((balance("5000"), balance("1300")), (2_400, -2_400));
+
+        service.reverse_movement(issue.id, "Returned unopened", None).unwrap();
+        assert_eq!((balance("5000"), balance("1300")), (0, 0));
+        assert_eq!(service.get_product_by_id(product.id).unwrap().current_stock, product.current_stock);
     }
 }
\ No newline at end of file
@@ -7,12 +7,30 @@ type Result<T> = CLIERPResult<T>;
 use crate::database::{
     DatabaseConnection, PurchaseOrder, NewPurchaseOrder, PurchaseItem, NewPurchaseItem,
     PurchaseOrderStatus, PurchaseItemStatus, PurchaseOrderWithItems, PurchaseItemWithProduct,
-    PurchaseOrderSummary, Supplier, Product
+    PurchaseOrderSummary, Supplier, Product, Invoice, InvoiceKind, InvoiceStatus, NewInvoice
 };
 use crate::database::schema::{purchase_orders, purchase_items, suppliers, products};
-use crate::utils::validation::validate_required_string;
-use crate::utils::pagination::{Paginate, PaginationParams, PaginatedResult};
+use crate::modules::finance::bill_due_date;
+use crate::modules::system::sod::{SodRule, SodService};
+use crate::utils::timezone::DisplayTimezone;
+use crate::utils::validation::{validate_country_code, validate_required_string};
+use crate::utils::pagination::{PaginationParams, PaginatedResult};
 use crate::utils::filters::FilterOptions;
+use crate::utils::filter_expr::FilterField;
+
+/// Fields `purchase-order list --where` may use
+pub const PURCHASE_ORDER_WHERE_FIELDS: &[FilterField] = &[
+    FilterField::integer("id", "purchase_orders.id"),
+    FilterField::text("po_number", "purchase_orders.po_number"),
+    FilterField::integer("supplier_id", "purchase_orders.supplier_id"),
+    FilterField::text("supplier", "suppliers.name"),
+    FilterField::date("date", "purchase_orders.order_date"),
+    FilterField::date("expected", "purchase_orders.expected_date"),
+    FilterField::text("status", "purchase_orders.status"),
+    FilterField::integer("total", "purchase_orders.total_amount"),
+    FilterField::text("country", "purchase_orders.dispatch_country"),
+    FilterField::text("incoterm", "purchase_orders.incoterm"),
+];
 
 pub struct PurchaseOrderService;
 
@@ -82,6 +100,8 @@ impl PurchaseOrderService {
                 total_amount,
                 notes: notes.map(|s| s.to_string()),
                 created_by,
+                dispatch_country: None,
+                incoterm: None,
             };
 
             diesel::insert_into(purchase_orders::table)
@@ -125,7 +145,7 @@ impl PurchaseOrderService {
             Ok((purchase_order, created_items))
         })
         .map_err(|e| crate::core::error::CLIERPError::DatabaseError(e.to_string()))
-        .and_then(|(po, items)| {
+        .and_then(|(po, _items)| {
             Self::get_purchase_order_with_details(conn, po.id)
         })
     }
@@ -203,6 +223,10 @@ impl PurchaseOrderService {
             .into_boxed();
 
         // Apply filters
+        if let Some(expr) = &filters.where_clause {
+            query = query.filter(expr.predicate(PURCHASE_ORDER_WHERE_FIELDS)?);
+        }
+
         if let Some(search) = &filters.search {
             query = query.filter(
                 purchase_orders::po_number.like(format!("%{}%", search))
@@ -291,13 +315,16 @@ impl PurchaseOrderService {
             })
             .collect();
 
-        Ok(PaginatedResult::new(summaries, &pagination, total_items))
+        Ok(PaginatedResult::new(summaries, pagination, total_items))
     }
 
+    /// Approve a pending order. `sod_override` is the reason for letting the
+    /// creator approve their own order, where the SoD rule allows it.
     pub fn approve_purchase_order(
         conn: &mut DatabaseConnection,
         po_id: i32,
         approved_by: i32,
+        sod_override: Option<&str>,
     ) -> Result<PurchaseOrder> {
         let purchase_order = Self::get_purchase_order_by_id(conn, po_id)?
             .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
@@ -310,6 +337,15 @@ impl PurchaseOrderService {
             ));
         }
 
+        SodService::check(
+            conn,
+            SodRule::PoCreateApprove,
+            purchase_order.id,
+            purchase_order.created_by,
+            approved_by,
+            sod_override,
+        )?;
+
         diesel::update(purchase_orders::table.find(po_id))
             .set((
                 purchase_orders::status.eq(PurchaseOrderStatus::Approved.to_string()),
@@ -323,7 +359,6 @@ impl PurchaseOrderService {
             .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
                 format!("Purchase order with ID {} not found after update", po_id)
             ))
-            .map_err(Into::into)
     }
 
     pub fn receive_purchase_items(
@@ -337,14 +372,21 @@ impl PurchaseOrderService {
                 format!("Purchase order with ID {} not found", po_id)
             ))?;
 
-        if purchase_order.status != PurchaseOrderStatus::Approved.to_string()
-            && purchase_order.status != PurchaseOrderStatus::Sent.to_string() {
+        let receivable = [
+            PurchaseOrderStatus::Approved,
+            PurchaseOrderStatus::Sent,
+            PurchaseOrderStatus::Confirmed,
+            PurchaseOrderStatus::Shipped,
+        ];
+        if !receivable.iter().any(|s| purchase_order.status == s.to_string()) {
             return Err(crate::core::error::CLIERPError::BusinessLogic(
-                "Only approved or sent purchase orders can be received".to_string()
+                "Only approved, sent, confirmed or shipped purchase orders can be received".to_string()
             ));
         }
 
-        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        let mut received_products = Vec::new();
+        conn.transaction::<_, crate::core::error::CLIERPError, _>(|conn| {
+            let mut received_value = 0;
             for receive_data in received_items {
                 // Get current item
                 let current_item = purchase_items::table
@@ -352,12 +394,18 @@ impl PurchaseOrderService {
                     .first::<PurchaseItem>(conn)?;
 
                 if current_item.po_id != po_id {
-                    return Err(diesel::result::Error::RollbackTransaction);
+                    return Err(crate::core::error::CLIERPError::Validation(format!(
+                        "Item {} is not on purchase order {}",
+                        receive_data.item_id, purchase_order.po_number
+                    )));
                 }
 
                 let new_received = current_item.received_quantity + receive_data.quantity;
                 if new_received > current_item.quantity {
-                    return Err(diesel::result::Error::RollbackTransaction);
+                    return Err(crate::core::error::CLIERPError::Validation(format!(
+                        "Receiving {} of item {} would exceed the {} ordered ({} already received)",
+                        receive_data.quantity, receive_data.item_id, current_item.quantity, current_item.received_quantity
+                    )));
                 }
 
                 // Update item status
@@ -398,6 +446,8 @@ impl PurchaseOrderService {
                 diesel::insert_into(stock_movements::table)
                     .values(&stock_movement)
                     .execute(conn)?;
+                received_value += crate::modules::finance::stock_value(receive_data.quantity, current_item.unit_cost)?;
+                received_products.push(current_item.product_id);
             }
 
             // Check if all items are fully received
@@ -424,14 +474,141 @@ impl PurchaseOrderService {
                     .execute(conn)?;
             }
 
+            crate::modules::finance::PostingService::post_receipt(
+                conn,
+                &purchase_order.po_number,
+                received_value,
+                DisplayTimezone::current().today(),
+                received_by,
+            )?;
             Ok(())
-        })
-        .map_err(|e| crate::core::error::CLIERPError::DatabaseError(e.to_string()))?;
+        })?;
+
+        let today = Utc::now().date_naive();
+        for product_id in received_products {
+            crate::modules::reporting::summaries::SummaryService::refresh_product_day(conn, product_id, today)?;
+        }
 
         Self::get_purchase_order_by_id(conn, po_id)?
             .ok_or_else(|| crate::core::error::CLIERPError::NotFound("Purchase order not found".to_string()))
     }
 
+    /// Record the customs details of a cross-border purchase order.
+    /// A PO without a dispatch country is treated as domestic.
+    pub fn set_customs_details(
+        conn: &mut DatabaseConnection,
+        po_id: i32,
+        dispatch_country: Option<&str>,
+        incoterm: Option<&str>,
+    ) -> Result<PurchaseOrder> {
+        Self::get_purchase_order_by_id(conn, po_id)?
+            .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
+                format!("Purchase order with ID {} not found", po_id)
+            ))?;
+
+        if let Some(country) = dispatch_country {
+            validate_country_code(country)?;
+        }
+        if let Some(incoterm) = incoterm {
+            validate_required_string(incoterm, "incoterm")?;
+        }
+
+        diesel::update(purchase_orders::table.find(po_id))
+            .set((
+                purchase_orders::dispatch_country.eq(dispatch_country),
+                purchase_orders::incoterm.eq(incoterm.map(|s| s.to_uppercase())),
+                purchase_orders::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+
+        purchase_orders::table
+            .find(po_id)
+            .first::<PurchaseOrder>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Record the supplier's invoice (bill) for a purchase order, so
+    /// receipts it bills are no longer accrued as goods received not
+    /// invoiced. Without a due date it falls due under the supplier's
+    /// payment terms.
+    pub fn record_invoice(
+        conn: &mut DatabaseConnection,
+        po_id: i32,
+        invoice_number: &str,
+        invoice_date: NaiveDate,
+        due_date: Option<NaiveDate>,
+        amount: i32,
+        created_by: Option<i32>,
+    ) -> Result<Invoice> {
+        use crate::database::schema::invoices;
+
+        let purchase_order = Self::get_purchase_order_by_id(conn, po_id)?
+            .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
+                format!("Purchase order with ID {} not found", po_id)
+            ))?;
+        validate_required_string(invoice_number, "invoice number")?;
+        if amount <= 0 {
+            return Err(crate::core::error::CLIERPError::Validation(
+                "Invoice amount must be positive".to_string()
+            ));
+        }
+        let duplicate = invoices::table
+            .filter(invoices::kind.eq(InvoiceKind::Payable.to_string()))
+            .filter(invoices::supplier_id.eq(purchase_order.supplier_id))
+            .filter(invoices::invoice_number.eq(invoice_number.trim()))
+            .count()
+            .get_result::<i64>(conn)?;
+        if duplicate > 0 {
+            return Err(crate::core::error::CLIERPError::AlreadyExists(
+                format!("Supplier invoice {} is already recorded", invoice_number.trim())
+            ));
+        }
+        let due_date = match due_date {
+            Some(due_date) => Some(due_date),
+            None => suppliers::table
+                .find(purchase_order.supplier_id)
+                .select(suppliers::payment_terms)
+                .first::<Option<String>>(conn)?
+                .map(|terms| bill_due_date(invoice_date, &terms))
+                .transpose()?,
+        };
+
+        diesel::insert_into(invoices::table)
+            .values(&NewInvoice {
+                invoice_number: invoice_number.trim().to_string(),
+                kind: InvoiceKind::Payable.to_string(),
+                customer_id: None,
+                supplier_id: Some(purchase_order.supplier_id),
+                invoice_date,
+                due_date,
+                total_amount: amount,
+                paid_amount: 0,
+                status: InvoiceStatus::Open.to_string(),
+                description: Some(format!("PO {}", purchase_order.po_number)),
+                created_by,
+                po_id: Some(po_id),
+            })
+            .execute(conn)?;
+
+        tracing::info!("Recorded supplier invoice {} for PO {}", invoice_number.trim(), purchase_order.po_number);
+        invoices::table
+            .filter(invoices::po_id.eq(po_id))
+            .order(invoices::id.desc())
+            .first::<Invoice>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Supplier invoices recorded for a purchase order
+    pub fn invoices(conn: &mut DatabaseConnection, po_id: i32) -> Result<Vec<Invoice>> {
+        use crate::database::schema::invoices;
+
+        invoices::table
+            .filter(invoices::po_id.eq(po_id))
+            .order(invoices::invoice_date.asc())
+            .load::<Invoice>(conn)
+            .map_err(Into::into)
+    }
+
     fn generate_po_number(conn: &mut DatabaseConnection) -> Result<String> {
         let count = purchase_orders::table
             .count()
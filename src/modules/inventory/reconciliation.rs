@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::core::result::CLIERPResult;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+use crate::database::schema::{deals, products, purchase_items, purchase_orders, record_links};
+use crate::database::{
+    DatabaseConnection, Deal, DealProduct, Product, PurchaseItem, PurchaseOrder, PurchaseOrderStatus, RecordLink,
+    RecordType,
+};
+
+/// Link relation of a purchase order shipped by the supplier straight to the customer
+pub const DROP_SHIP_RELATION: &str = "drop_ship";
+/// Link relation of a purchase order bought in for one deal and shipped from stock
+pub const BACK_TO_BACK_RELATION: &str = "back_to_back";
+
+/// How a sale and a purchase are matched up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ReconciliationStatus {
+    /// Bought what was sold and received all of it
+    Matched,
+    /// Bought what was sold but not all of it has arrived
+    AwaitingReceipt,
+    /// Sold without a purchase order line to cover it
+    Unsourced,
+    /// Bought more than the deal sold, or a product the deal does not list
+    Excess,
+}
+
+impl std::fmt::Display for ReconciliationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReconciliationStatus::Matched => write!(f, "matched"),
+            ReconciliationStatus::AwaitingReceipt => write!(f, "awaiting receipt"),
+            ReconciliationStatus::Unsourced => write!(f, "unsourced"),
+            ReconciliationStatus::Excess => write!(f, "excess"),
+        }
+    }
+}
+
+/// A deal line set against the purchase order line bought for it. One of
+/// the two sides may be missing.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconciledLine {
+    pub deal_id: i32,
+    pub deal_name: String,
+    pub po_id: Option<i32>,
+    pub po_number: Option<String>,
+    /// `DROP_SHIP_RELATION` or `BACK_TO_BACK_RELATION`
+    pub fulfillment: String,
+    pub product_id: i32,
+    pub sku: String,
+    pub product_name: String,
+    pub sold: i32,
+    pub purchased: i32,
+    pub received: i32,
+    /// Sales value of `sold`, after the deal discount
+    pub revenue: i64,
+    /// Purchase cost of `purchased`
+    pub cost: i64,
+}
+
+impl ReconciledLine {
+    /// Sold less purchased: positive is a shortfall, negative an excess
+    pub fn unmatched(&self) -> i32 {
+        self.sold - self.purchased
+    }
+
+    pub fn margin(&self) -> i64 {
+        self.revenue - self.cost
+    }
+
+    pub fn status(&self) -> ReconciliationStatus {
+        if self.po_id.is_none() || self.unmatched() > 0 {
+            ReconciliationStatus::Unsourced
+        } else if self.unmatched() < 0 {
+            ReconciliationStatus::Excess
+        } else if self.received < self.purchased {
+            ReconciliationStatus::AwaitingReceipt
+        } else {
+            ReconciliationStatus::Matched
+        }
+    }
+}
+
+/// Sales matched to the purchase orders raised for them.
+///
+/// A purchase order is tied to a deal by a record link whose relation is
+/// `drop_ship` or `back_to_back`, made in either direction. Each deal line
+/// is covered by the lines of the same product on its linked orders, in the
+/// order the links were made; cancelled orders are left out.
+pub struct OrderReconciliationService;
+
+impl OrderReconciliationService {
+    /// Reconcile every linked deal, or only orders placed between `from` and `to`
+    pub fn reconcile(
+        conn: &mut DatabaseConnection,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<Vec<ReconciledLine>> {
+        let deal_type = RecordType::Deal.to_string();
+        let po_type = RecordType::PurchaseOrder.to_string();
+        let links = record_links::table
+            .filter(record_links::relation.eq_any([DROP_SHIP_RELATION, BACK_TO_BACK_RELATION]))
+            .order(record_links::id.asc())
+            .load::<RecordLink>(conn)?;
+
+        // Linked orders of each deal, in link order
+        let mut deal_ids = Vec::new();
+        let mut linked: HashMap<i32, Vec<(i32, String)>> = HashMap::new();
+        for link in links {
+            let pair = if link.source_type == deal_type && link.target_type == po_type {
+                (link.source_id, link.target_id)
+            } else if link.source_type == po_type && link.target_type == deal_type {
+                (link.target_id, link.source_id)
+            } else {
+                continue;
+            };
+            if !linked.contains_key(&pair.0) {
+                deal_ids.push(pair.0);
+            }
+            linked.entry(pair.0).or_default().push((pair.1, link.relation));
+        }
+
+        let po_ids: Vec<i32> = linked.values().flatten().map(|(po_id, _)| *po_id).collect();
+        let mut po_query = purchase_orders::table
+            .filter(purchase_orders::id.eq_any(&po_ids))
+            .filter(purchase_orders::status.ne(PurchaseOrderStatus::Cancelled.to_string()))
+            .into_boxed();
+        if let Some(from) = from {
+            po_query = po_query.filter(purchase_orders::order_date.ge(from));
+        }
+        if let Some(to) = to {
+            po_query = po_query.filter(purchase_orders::order_date.le(to));
+        }
+        let orders: HashMap<i32, PurchaseOrder> = po_query
+            .load::<PurchaseOrder>(conn)?
+            .into_iter()
+            .map(|po| (po.id, po))
+            .collect();
+        let mut po_lines: HashMap<i32, Vec<PurchaseItem>> = HashMap::new();
+        for item in purchase_items::table
+            .filter(purchase_items::po_id.eq_any(orders.keys().copied().collect::<Vec<_>>()))
+            .order(purchase_items::id.asc())
+            .load::<PurchaseItem>(conn)?
+        {
+            po_lines.entry(item.po_id).or_default().push(item);
+        }
+
+        let deal_map: HashMap<i32, Deal> = deals::table
+            .filter(deals::id.eq_any(&deal_ids))
+            .load::<Deal>(conn)?
+            .into_iter()
+            .map(|deal| (deal.id, deal))
+            .collect();
+
+        let mut lines = Vec::new();
+        for deal_id in deal_ids {
+            let Some(deal) = deal_map.get(&deal_id) else {
+                tracing::warn!("Deal {} is linked to purchase orders but no longer exists", deal_id);
+                continue;
+            };
+            let sold: Vec<DealProduct> = match &deal.products {
+                Some(json) => serde_json::from_str(json)?,
+                None => Vec::new(),
+            };
+            let discount = deal.discount_percent.unwrap_or(0).clamp(0, 100) as i64;
+            let net = |quantity: i32, unit_price: i32| quantity as i64 * unit_price as i64 * (100 - discount) / 100;
+            let bought: Vec<(&PurchaseOrder, &str, &PurchaseItem)> = linked[&deal_id]
+                .iter()
+                .filter_map(|(po_id, relation)| Some((orders.get(po_id)?, relation.as_str())))
+                .flat_map(|(po, relation)| {
+                    po_lines.get(&po.id).into_iter().flatten().map(move |item| (po, relation, item))
+                })
+                .collect();
+            if bought.is_empty() && (from.is_some() || to.is_some()) {
+                // No order of this deal falls in the period
+                continue;
+            }
+
+            let sold_quantities: Vec<(i32, i32)> = sold.iter().map(|line| (line.product_id, line.quantity)).collect();
+            let purchased: Vec<(i32, i32)> = bought.iter().map(|(_, _, item)| (item.product_id, item.quantity)).collect();
+            let (allocated, unsourced) = allocate(&sold_quantities, &purchased);
+            let unit_price = |product_id: i32| {
+                sold.iter().find(|line| line.product_id == product_id).map_or(0, |line| line.unit_price)
+            };
+            let default_fulfillment = linked[&deal_id][0].1.clone();
+
+            for ((po, relation, item), sold_quantity) in bought.iter().zip(allocated) {
+                lines.push(ReconciledLine {
+                    deal_id,
+                    deal_name: deal.deal_name.clone(),
+                    po_id: Some(po.id),
+                    po_number: Some(po.po_number.clone()),
+                    fulfillment: relation.to_string(),
+                    product_id: item.product_id,
+                    sku: String::new(),
+                    product_name: String::new(),
+                    sold: sold_quantity,
+                    purchased: item.quantity,
+                    received: item.received_quantity,
+                    revenue: net(sold_quantity, unit_price(item.product_id)),
+                    cost: item.total_cost as i64,
+                });
+            }
+            for (product_id, quantity) in unsourced {
+                lines.push(ReconciledLine {
+                    deal_id,
+                    deal_name: deal.deal_name.clone(),
+                    po_id: None,
+                    po_number: None,
+                    fulfillment: default_fulfillment.clone(),
+                    product_id,
+                    sku: String::new(),
+                    product_name: String::new(),
+                    sold: quantity,
+                    purchased: 0,
+                    received: 0,
+                    revenue: net(quantity, unit_price(product_id)),
+                    cost: 0,
+                });
+            }
+        }
+
+        let product_ids: Vec<i32> = lines.iter().map(|line| line.product_id).collect();
+        let product_map: HashMap<i32, Product> = products::table
+            .filter(products::id.eq_any(&product_ids))
+            .load::<Product>(conn)?
+            .into_iter()
+            .map(|product| (product.id, product))
+            .collect();
+        for line in &mut lines {
+            match product_map.get(&line.product_id) {
+                Some(product) => {
+                    line.sku = product.sku.clone();
+                    line.product_name = product.name.clone();
+                }
+                None => line.sku = format!("#{}", line.product_id),
+            }
+        }
+
+        Ok(lines)
+    }
+}
+
+/// Allocate sold quantities to purchase lines, both `(product id, quantity)`.
+///
+/// Each purchase line takes as much of its product's sold quantity as it
+/// holds, in order. Returns the quantity allocated to each purchase line and
+/// the sold quantity no purchase line covers, per product in sale order.
+pub fn allocate(sold: &[(i32, i32)], purchased: &[(i32, i32)]) -> (Vec<i32>, Vec<(i32, i32)>) {
+    let mut remaining: Vec<(i32, i32)> = Vec::new();
+    for &(product_id, quantity) in sold {
+        match remaining.iter_mut().find(|(id, _)| *id == product_id) {
+            Some(entry) => entry.1 += quantity,
+            None => remaining.push((product_id, quantity)),
+        }
+    }
+
+    let allocated = purchased
+        .iter()
+        .map(|&(product_id, quantity)| match remaining.iter_mut().find(|(id, _)| *id == product_id) {
+            Some(entry) => {
+                let taken = entry.1.min(quantity).max(0);
+                entry.1 -= taken;
+                taken
+            }
+            None => 0,
+        })
+        .collect();
+    remaining.retain(|(_, quantity)| *quantity > 0);
+
+    (allocated, remaining)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(sold: i32, purchased: i32, received: i32, po_id: Option<i32>) -> ReconciledLine {
+        ReconciledLine {
+            deal_id: 1,
+            deal_name: "Deal".to_string(),
+            po_id,
+            po_number: None,
+            fulfillment: DROP_SHIP_RELATION.to_string(),
+            product_id: 1,
+            sku: "SKU".to_string(),
+            product_name: "Product".to_string(),
+            sold,
+            purchased,
+            received,
+            revenue: 0,
+            cost: 0,
+        }
+    }
+
+    #[test]
+    fn test_allocate() {
+        // Product 1 sold 10, bought 6 + 6; product 2 sold 5, not bought; product 3 bought, not sold
+        let (allocated, unsourced) = allocate(&[(1, 10), (2, 5)], &[(1, 6), (1, 6), (3, 2)]);
+        assert_eq!(allocated, vec![6, 4, 0]);
+        assert_eq!(unsourced, vec![(2, 5)]);
+
+        let (allocated, unsourced) = allocate(&[(1, 4)], &[(1, 3)]);
+        assert_eq!(allocated, vec![3]);
+        assert_eq!(unsourced, vec![(1, 1)]);
+    }
+
+    #[test]
+    fn test_reconciled_line_status() {
+        assert_eq!(line(5, 5, 5, Some(1)).status(), ReconciliationStatus::Matched);
+        assert_eq!(line(5, 5, 2, Some(1)).status(), ReconciliationStatus::AwaitingReceipt);
+        assert_eq!(line(4, 6, 6, Some(1)).status(), ReconciliationStatus::Excess);
+        assert_eq!(line(0, 2, 0, Some(1)).status(), ReconciliationStatus::Excess);
+        assert_eq!(line(3, 0, 0, None).status(), ReconciliationStatus::Unsourced);
+        assert_eq!(line(5, 5, 5, Some(1)).unmatched(), 0);
+        assert_eq!(line(4, 6, 6, Some(1)).unmatched(), -2);
+    }
+}
@@ -0,0 +1,340 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::core::result::CLIERPResult;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+use crate::database::schema::{products, stock_movements, stock_reservations};
+use crate::database::{
+    DatabaseConnection, NewStockMovement, NewStockReservation, Product, ReservationStatus,
+    StockMovementType, StockReservation,
+};
+use crate::modules::finance::{stock_value, PostingService};
+use crate::modules::reporting::summaries::SummaryService;
+use crate::utils::timezone::DisplayTimezone;
+use crate::utils::validation::validate_required_string;
+
+/// On-hand, reserved and available quantity of one product
+#[derive(Debug, Clone, Serialize)]
+pub struct StockAvailability {
+    pub product_id: i32,
+    pub sku: String,
+    pub name: String,
+    pub unit: String,
+    pub on_hand: i32,
+    pub reserved: i32,
+    pub available: i32,
+}
+
+/// Soft allocation of on-hand stock to orders.
+///
+/// A reservation does not move stock; it lowers the quantity other orders
+/// can claim (`available = on_hand - reserved`) until it is fulfilled with
+/// a stock out movement or released.
+pub struct ReservationService;
+
+impl ReservationService {
+    /// Total quantity held by active reservations for a product
+    pub fn reserved_quantity(conn: &mut SqliteConnection, product_id: i32) -> Result<i32> {
+        let reserved = stock_reservations::table
+            .filter(stock_reservations::product_id.eq(product_id))
+            .filter(stock_reservations::status.eq(ReservationStatus::Active.to_string()))
+            .select(diesel::dsl::sum(stock_reservations::quantity))
+            .first::<Option<i64>>(conn)?;
+
+        Ok(reserved.unwrap_or(0) as i32)
+    }
+
+    /// Fail unless lowering the stock of `product_id` to `new_stock` leaves
+    /// what active reservations hold on hand. Every movement that takes
+    /// stock out checks this first, except fulfilling a reservation, which
+    /// uses up its own hold.
+    pub fn ensure_unreserved(conn: &mut SqliteConnection, product_id: i32, new_stock: i32) -> Result<()> {
+        let on_hand = products::table
+            .find(product_id)
+            .select(products::current_stock)
+            .first::<i32>(conn)?;
+        if new_stock >= on_hand {
+            return Ok(());
+        }
+        let reserved = Self::reserved_quantity(conn, product_id)?;
+        if new_stock < reserved {
+            return Err(crate::core::error::CLIERPError::Validation(format!(
+                "Only {} available; {} of the {} on hand is reserved for orders",
+                (on_hand - reserved).max(0),
+                reserved,
+                on_hand
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn availability(conn: &mut DatabaseConnection, product_id: i32) -> Result<StockAvailability> {
+        let product = products::table
+            .find(product_id)
+            .first::<Product>(conn)
+            .optional()?
+            .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
+                format!("Product with ID {} not found", product_id)
+            ))?;
+        let reserved = Self::reserved_quantity(conn, product_id)?;
+
+        Ok(StockAvailability {
+            product_id,
+            sku: product.sku,
+            name: product.name,
+            unit: product.unit,
+            on_hand: product.current_stock,
+            reserved,
+            available: product.current_stock - reserved,
+        })
+    }
+
+    /// Hold `quantity` for an order. Fails if less than that is available.
+    pub fn reserve(
+        conn: &mut DatabaseConnection,
+        product_id: i32,
+        quantity: i32,
+        reference_type: &str,
+        reference_id: Option<i32>,
+        notes: Option<&str>,
+        reserved_by: Option<i32>,
+    ) -> Result<StockReservation> {
+        validate_required_string(reference_type, "reference_type")?;
+        if quantity <= 0 {
+            return Err(crate::core::error::CLIERPError::Validation(
+                "Reserved quantity must be greater than zero".to_string()
+            ));
+        }
+
+        let reservation = conn.transaction::<_, crate::core::error::CLIERPError, _>(|conn| {
+            let availability = Self::availability(conn, product_id)?;
+            if availability.available < quantity {
+                return Err(crate::core::error::CLIERPError::Validation(format!(
+                    "Only {} {} of {} available ({} on hand, {} reserved)",
+                    availability.available,
+                    availability.unit,
+                    availability.sku,
+                    availability.on_hand,
+                    availability.reserved
+                )));
+            }
+
+            let new_reservation = NewStockReservation {
+                product_id,
+                quantity,
+                reference_type: reference_type.to_string(),
+                reference_id,
+                status: ReservationStatus::Active.to_string(),
+                notes: notes.map(|s| s.to_string()),
+                reserved_by,
+            };
+            diesel::insert_into(stock_reservations::table)
+                .values(&new_reservation)
+                .execute(conn)?;
+
+            let reservation = stock_reservations::table
+                .order(stock_reservations::id.desc())
+                .first::<StockReservation>(conn)?;
+            Ok(reservation)
+        })?;
+
+        tracing::info!(
+            "Reserved {} of product {} for {} {:?}",
+            quantity,
+            product_id,
+            reference_type,
+            reference_id
+        );
+        Ok(reservation)
+    }
+
+    /// Cancel an active reservation, making its quantity available again
+    pub fn release(conn: &mut DatabaseConnection, reservation_id: i32) -> Result<StockReservation> {
+        let reservation = Self::get_active(conn, reservation_id)?;
+
+        diesel::update(stock_reservations::table.find(reservation.id))
+            .set((
+                stock_reservations::status.eq(ReservationStatus::Released.to_string()),
+                stock_reservations::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+
+        tracing::info!("Released stock reservation {}", reservation_id);
+        stock_reservations::table
+            .find(reservation_id)
+            .first::<StockReservation>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Ship a reservation: record the stock out movement and close the hold
+    pub fn fulfill(
+        conn: &mut DatabaseConnection,
+        reservation_id: i32,
+        moved_by: Option<i32>,
+    ) -> Result<StockReservation> {
+        let reservation = Self::get_active(conn, reservation_id)?;
+
+        conn.transaction::<_, crate::core::error::CLIERPError, _>(|conn| {
+            let product = products::table
+                .find(reservation.product_id)
+                .first::<Product>(conn)?;
+            if product.current_stock < reservation.quantity {
+                return Err(crate::core::error::CLIERPError::Validation(format!(
+                    "Only {} {} of {} on hand, cannot ship {}",
+                    product.current_stock, product.unit, product.sku, reservation.quantity
+                )));
+            }
+
+            let movement = NewStockMovement {
+                product_id: reservation.product_id,
+                movement_type: StockMovementType::Out.to_string(),
+                quantity: -reservation.quantity,
+                unit_cost: None,
+                reference_type: Some(reservation.reference_type.clone()),
+                reference_id: reservation.reference_id,
+                notes: Some(format!("Fulfilled reservation #{}", reservation.id)),
+                moved_by,
+            };
+            diesel::insert_into(stock_movements::table)
+                .values(&movement)
+                .execute(conn)?;
+            let movement_id = stock_movements::table
+                .filter(stock_movements::product_id.eq(reservation.product_id))
+                .order(stock_movements::id.desc())
+                .select(stock_movements::id)
+                .first::<i32>(conn)?;
+            SummaryService::refresh_product_day(conn, reservation.product_id, Utc::now().date_naive())?;
+            PostingService::post_issue(
+                conn,
+                movement_id,
+                &format!("{} x {} for reservation #{}", reservation.quantity, product.sku, reservation.id),
+                stock_value(reservation.quantity, product.cost_price)?,
+                DisplayTimezone::current().today(),
+                moved_by,
+            )?;
+
+            diesel::update(products::table.find(reservation.product_id))
+                .set((
+                    products::current_stock.eq(products::current_stock - reservation.quantity),
+                    products::updated_at.eq(Utc::now().naive_utc()),
+                ))
+                .execute(conn)?;
+
+            diesel::update(stock_reservations::table.find(reservation.id))
+                .set((
+                    stock_reservations::status.eq(ReservationStatus::Fulfilled.to_string()),
+                    stock_reservations::updated_at.eq(Utc::now().naive_utc()),
+                ))
+                .execute(conn)?;
+
+            Ok(())
+        })?;
+
+        tracing::info!("Fulfilled stock reservation {}", reservation_id);
+        stock_reservations::table
+            .find(reservation_id)
+            .first::<StockReservation>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Active reservations for a product, oldest first
+    pub fn list_active(conn: &mut DatabaseConnection, product_id: i32) -> Result<Vec<StockReservation>> {
+        let reservations = stock_reservations::table
+            .filter(stock_reservations::product_id.eq(product_id))
+            .filter(stock_reservations::status.eq(ReservationStatus::Active.to_string()))
+            .order(stock_reservations::created_at.asc())
+            .load::<StockReservation>(conn)?;
+        Ok(reservations)
+    }
+
+    fn get_active(conn: &mut DatabaseConnection, reservation_id: i32) -> Result<StockReservation> {
+        let reservation = stock_reservations::table
+            .find(reservation_id)
+            .first::<StockReservation>(conn)
+            .optional()?
+            .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
+                format!("Stock reservation with ID {} not found", reservation_id)
+            ))?;
+
+        if reservation.status != ReservationStatus::Active.to_string() {
+            return Err(crate::core::error::CLIERPError::Validation(format!(
+                "Stock reservation {} is already {}",
+                reservation_id, reservation.status
+            )));
+        }
+        Ok(reservation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::inventory::ProductService;
+    use crate::testing::{Company, CompanyFixture};
+
+    /// A product with 10 on hand, `reserved` of them held for an order
+    fn company_with_reservation(reserved: i32) -> (Company, i32) {
+        let company = CompanyFixture::new().with_products(1).build().unwrap();
+        let product_id = company.products[0].id;
+        let mut conn = company.connection().unwrap();
+        ReservationService::reserve(&mut conn, product_id, reserved, "sales_order", Some(1), None, None).unwrap();
+        (company, product_id)
+    }
+
+    fn on_hand(product_id: i32) -> i32 {
+        ProductService::new().get_product_by_id(product_id).unwrap().current_stock
+    }
+
+    #[test]
+    fn test_stock_out_keeps_reserved() {
+        let (_company, product_id) = company_with_reservation(8);
+        let service = ProductService::new();
+        let err = service.update_stock(product_id, -3, "out", None, None, None, None, None).unwrap_err();
+        assert!(err.to_string().contains("Only 2 available; 8 of the 10 on hand is reserved"), "{}", err);
+        service.update_stock(product_id, -2, "out", None, None, None, None, None).unwrap();
+        assert_eq!(on_hand(product_id), 8);
+    }
+
+    #[test]
+    fn test_adjustment_keeps_reserved() {
+        let (_company, product_id) = company_with_reservation(8);
+        let service = ProductService::new();
+        assert!(service.update_stock(product_id, 0, "adjustment", None, None, None, None, None).is_err());
+        assert!(service.update_stock(product_id, -3, "adjustment", None, None, None, None, None).is_err());
+        assert_eq!(on_hand(product_id), 10);
+        service.update_stock(product_id, 4, "adjustment", None, None, None, None, None).unwrap();
+        assert_eq!(on_hand(product_id), 14);
+    }
+
+    #[test]
+    fn test_reversal_keeps_reserved() {
+        let (company, product_id) = company_with_reservation(8);
+        let service = ProductService::new();
+        service.update_stock(product_id, 5, "in", None, None, None, None, None).unwrap();
+        let mut conn = company.connection().unwrap();
+        ReservationService::reserve(&mut conn, product_id, 4, "sales_order", Some(2), None, None).unwrap();
+        let receipt = stock_movements::table
+            .filter(stock_movements::product_id.eq(product_id))
+            .order(stock_movements::id.desc())
+            .select(stock_movements::id)
+            .first::<i32>(&mut conn)
+            .unwrap();
+
+        let err = service.reverse_movement(receipt, "Keyed twice", None).unwrap_err();
+        assert!(err.to_string().contains("Only 3 available; 12 of the 15 on hand is reserved"), "{}", err);
+        assert_eq!(on_hand(product_id), 15);
+    }
+
+    #[test]
+    fn test_fulfill_uses_its_own_hold() {
+        let (company, product_id) = company_with_reservation(8);
+        let mut conn = company.connection().unwrap();
+        let reservation = ReservationService::list_active(&mut conn, product_id).unwrap().remove(0);
+        ReservationService::fulfill(&mut conn, reservation.id, None).unwrap();
+        let availability = ReservationService::availability(&mut conn, product_id).unwrap();
+        assert_eq!((availability.on_hand, availability.reserved, availability.available), (2, 0, 2));
+    }
+}
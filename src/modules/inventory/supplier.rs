@@ -1,5 +1,5 @@
 use diesel::prelude::*;
-use chrono::{Utc, NaiveDate};
+use chrono::Utc;
 use crate::core::result::CLIERPResult;
 
 // Type alias for convenience
@@ -7,12 +7,27 @@ type Result<T> = CLIERPResult<T>;
 use crate::database::{DatabaseConnection, Supplier, NewSupplier, SupplierStatus};
 use crate::database::schema::suppliers;
 use crate::utils::validation::{validate_email, validate_required_string};
-use crate::utils::pagination::{Paginate, PaginationParams, PaginatedResult};
+use crate::utils::pagination::{PaginationParams, PaginatedResult};
 use crate::utils::filters::FilterOptions;
+use crate::utils::filter_expr::FilterField;
+
+/// Fields `supplier list --where` may use
+pub const SUPPLIER_WHERE_FIELDS: &[FilterField] = &[
+    FilterField::integer("id", "suppliers.id"),
+    FilterField::text("code", "suppliers.supplier_code"),
+    FilterField::text("name", "suppliers.name"),
+    FilterField::text("contact", "suppliers.contact_person"),
+    FilterField::text("email", "suppliers.email"),
+    FilterField::text("payment_terms", "suppliers.payment_terms"),
+    FilterField::integer("lead_time", "suppliers.lead_time_days"),
+    FilterField::text("status", "suppliers.status"),
+    FilterField::date("created", "suppliers.created_at"),
+];
 
 pub struct SupplierService;
 
 impl SupplierService {
+    #[allow(clippy::too_many_arguments)]
     pub fn create_supplier(
         conn: &mut DatabaseConnection,
         supplier_code: &str,
@@ -73,6 +88,7 @@ impl SupplierService {
             address: address.map(|s| s.to_string()),
             payment_terms: payment_terms.map(|s| s.to_string()),
             status: SupplierStatus::Active.to_string(),
+            lead_time_days: None,
         };
 
         diesel::insert_into(suppliers::table)
@@ -107,20 +123,8 @@ impl SupplierService {
         filters: &FilterOptions,
         pagination: &PaginationParams,
     ) -> Result<PaginatedResult<Supplier>> {
-        let mut query = suppliers::table.into_boxed();
-
-        // Apply filters
-        if let Some(search) = &filters.search {
-            query = query.filter(
-                suppliers::name.like(format!("%{}%", search))
-                    .or(suppliers::supplier_code.like(format!("%{}%", search)))
-                    .or(suppliers::contact_person.like(format!("%{}%", search)))
-            );
-        }
-
-        if let Some(status_filter) = &filters.status {
-            query = query.filter(suppliers::status.eq(status_filter));
-        }
+        let total = filtered_suppliers(filters)?.count().get_result::<i64>(conn)?;
+        let mut query = filtered_suppliers(filters)?;
 
         // Apply sorting
         query = match filters.sort_by.as_deref() {
@@ -148,9 +152,15 @@ impl SupplierService {
             _ => query.order(suppliers::created_at.desc()),
         };
 
-        query.paginate_result(pagination, conn)
+        let data = query
+            .offset(pagination.offset())
+            .limit(pagination.limit())
+            .load::<Supplier>(conn)?;
+
+        Ok(PaginatedResult::new(data, pagination, total))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn update_supplier(
         conn: &mut DatabaseConnection,
         supplier_id: i32,
@@ -163,7 +173,7 @@ impl SupplierService {
         status: Option<SupplierStatus>,
     ) -> Result<Supplier> {
         // Check if supplier exists
-        let supplier = Self::get_supplier_by_id(conn, supplier_id)?
+        Self::get_supplier_by_id(conn, supplier_id)?
             .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
                 format!("Supplier with ID {} not found", supplier_id)
             ))?;
@@ -188,7 +198,8 @@ impl SupplierService {
         }
 
         // Build update query dynamically
-        use crate::database::schema::suppliers::dsl::*;
+        use crate::database::schema::suppliers::dsl as s;
+        use crate::database::schema::suppliers::dsl::suppliers;
 
         let current_time = Utc::now().naive_utc();
 
@@ -198,49 +209,49 @@ impl SupplierService {
         // For simplicity, let's update each field individually when provided
         if let Some(name_val) = name {
             diesel::update(suppliers.find(supplier_id))
-                .set(name.eq(name_val))
+                .set(s::name.eq(name_val))
                 .execute(conn)?;
         }
 
         if let Some(contact_val) = contact_person {
             diesel::update(suppliers.find(supplier_id))
-                .set(contact_person.eq(contact_val.map(|s| s.to_string())))
+                .set(s::contact_person.eq(contact_val.map(|s| s.to_string())))
                 .execute(conn)?;
         }
 
         if let Some(email_val) = email {
             diesel::update(suppliers.find(supplier_id))
-                .set(email.eq(email_val.map(|s| s.to_string())))
+                .set(s::email.eq(email_val.map(|s| s.to_string())))
                 .execute(conn)?;
         }
 
         if let Some(phone_val) = phone {
             diesel::update(suppliers.find(supplier_id))
-                .set(phone.eq(phone_val.map(|s| s.to_string())))
+                .set(s::phone.eq(phone_val.map(|s| s.to_string())))
                 .execute(conn)?;
         }
 
         if let Some(address_val) = address {
             diesel::update(suppliers.find(supplier_id))
-                .set(address.eq(address_val.map(|s| s.to_string())))
+                .set(s::address.eq(address_val.map(|s| s.to_string())))
                 .execute(conn)?;
         }
 
         if let Some(payment_val) = payment_terms {
             diesel::update(suppliers.find(supplier_id))
-                .set(payment_terms.eq(payment_val.map(|s| s.to_string())))
+                .set(s::payment_terms.eq(payment_val.map(|s| s.to_string())))
                 .execute(conn)?;
         }
 
         if let Some(status_val) = status {
             diesel::update(suppliers.find(supplier_id))
-                .set(status.eq(status_val.to_string()))
+                .set(s::status.eq(status_val.to_string()))
                 .execute(conn)?;
         }
 
         // Always update the timestamp
         diesel::update(suppliers.find(supplier_id))
-            .set(updated_at.eq(current_time))
+            .set(s::updated_at.eq(current_time))
             .execute(conn)?;
 
         // Get the updated supplier
@@ -250,9 +261,41 @@ impl SupplierService {
             .map_err(Into::into)
     }
 
+    /// Set or clear the supplier's typical lead time in days
+    pub fn set_lead_time(
+        conn: &mut DatabaseConnection,
+        supplier_id: i32,
+        lead_time_days: Option<i32>,
+    ) -> Result<Supplier> {
+        Self::get_supplier_by_id(conn, supplier_id)?
+            .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
+                format!("Supplier with ID {} not found", supplier_id)
+            ))?;
+
+        if let Some(days) = lead_time_days {
+            if !(0..=365).contains(&days) {
+                return Err(crate::core::error::CLIERPError::Validation(
+                    "Lead time must be between 0 and 365 days".to_string()
+                ));
+            }
+        }
+
+        diesel::update(suppliers::table.find(supplier_id))
+            .set((
+                suppliers::lead_time_days.eq(lead_time_days),
+                suppliers::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+
+        suppliers::table
+            .find(supplier_id)
+            .first::<Supplier>(conn)
+            .map_err(Into::into)
+    }
+
     pub fn delete_supplier(conn: &mut DatabaseConnection, supplier_id: i32) -> Result<bool> {
         // Check if supplier exists
-        let supplier = Self::get_supplier_by_id(conn, supplier_id)?
+        Self::get_supplier_by_id(conn, supplier_id)?
             .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
                 format!("Supplier with ID {} not found", supplier_id)
             ))?;
@@ -300,7 +343,7 @@ impl SupplierService {
     }
 
     pub fn get_supplier_statistics(conn: &mut DatabaseConnection, supplier_id: i32) -> Result<SupplierStatistics> {
-        use crate::database::schema::{purchase_orders, purchase_items};
+        use crate::database::schema::purchase_orders;
 
         // Get total purchase orders count
         let total_orders = purchase_orders::table
@@ -329,9 +372,108 @@ impl SupplierService {
     }
 }
 
+/// Suppliers matching the list filters, in no particular order
+fn filtered_suppliers(filters: &FilterOptions) -> Result<suppliers::BoxedQuery<'_, diesel::sqlite::Sqlite>> {
+    let mut query = suppliers::table.into_boxed();
+
+    // Apply filters
+    if let Some(expr) = &filters.where_clause {
+        query = query.filter(expr.predicate(SUPPLIER_WHERE_FIELDS)?);
+    }
+
+    if let Some(search) = &filters.search {
+        query = query.filter(
+            suppliers::name.like(format!("%{}%", search))
+                .or(suppliers::supplier_code.like(format!("%{}%", search)))
+                .or(suppliers::contact_person.like(format!("%{}%", search)))
+        );
+    }
+
+    if let Some(status_filter) = &filters.status {
+        query = query.filter(suppliers::status.eq(status_filter));
+    }
+
+    Ok(query)
+}
+
 #[derive(Debug, serde::Serialize)]
 pub struct SupplierStatistics {
     pub total_orders: i64,
     pub pending_orders: i64,
     pub total_amount: i32,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::CompanyFixture;
+    use crate::utils::filter_expr::parse_filter_expr_arg;
+
+    #[test]
+    fn test_list_suppliers() {
+        let company = CompanyFixture::new().with_suppliers(3).build().unwrap();
+        let mut conn = company.connection().unwrap();
+
+        let filters = FilterOptions { sort_by: Some("code".to_string()), ..Default::default() };
+        let page = SupplierService::list_suppliers(&mut conn, &filters, &PaginationParams::new(1, 2)).unwrap();
+        let codes: Vec<&str> = page.data.iter().map(|s| s.supplier_code.as_str()).collect();
+        assert_eq!(codes, vec!["FIX-S001", "FIX-S002"]);
+        assert_eq!((page.pagination.total_count, page.pagination.total_pages), (3, 2));
+        let page = SupplierService::list_suppliers(&mut conn, &filters, &PaginationParams::new(2, 2)).unwrap();
+        assert_eq!(page.data.len(), 1);
+
+        let filters = FilterOptions {
+            where_clause: Some(parse_filter_expr_arg("code != FIX-S002 and status = active").unwrap()),
+            ..filters
+        };
+        let page = SupplierService::list_suppliers(&mut conn, &filters, &PaginationParams::new(1, 20)).unwrap();
+        let codes: Vec<&str> = page.data.iter().map(|s| s.supplier_code.as_str()).collect();
+        assert_eq!(codes, vec!["FIX-S001", "FIX-S003"]);
+        assert_eq!(page.pagination.total_count, 2);
+    }
+
+    #[test]
+    fn test_update_keeps_fields_not_given() {
+        let company = CompanyFixture::new().with_suppliers(1).build().unwrap();
+        let mut conn = company.connection().unwrap();
+        let id = company.suppliers[0].id;
+        SupplierService::update_supplier(
+            &mut conn,
+            id,
+            None,
+            Some(Some("Kim Minji")),
+            None,
+            Some(Some("02-555-0100")),
+            Some(Some("Seoul")),
+            None,
+            None,
+        )
+        .unwrap();
+
+        // Only --name given, as the CLI passes it
+        let supplier = SupplierService::update_supplier(
+            &mut conn,
+            id,
+            Some("Renamed Supplier"),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(supplier.name, "Renamed Supplier");
+        assert_eq!(supplier.contact_person.as_deref(), Some("Kim Minji"));
+        assert_eq!(supplier.email.as_deref(), Some("supplier1@example.com"));
+        assert_eq!(supplier.phone.as_deref(), Some("02-555-0100"));
+        assert_eq!(supplier.address.as_deref(), Some("Seoul"));
+        assert_eq!(supplier.payment_terms.as_deref(), Some("Net 30"));
+        assert_eq!(supplier.status, "active");
+
+        // An explicit None clears the field
+        let supplier =
+            SupplierService::update_supplier(&mut conn, id, None, None, None, Some(None), None, None, None).unwrap();
+        assert_eq!(supplier.phone, None);
+        assert_eq!(supplier.address.as_deref(), Some("Seoul"));
+    }
+}
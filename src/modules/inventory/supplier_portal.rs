@@ -0,0 +1,202 @@
+use chrono::{NaiveDate, Utc};
+use diesel::prelude::*;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{purchase_orders, supplier_responses};
+use crate::database::{
+    DatabaseConnection, NewSupplierResponse, PortalLink, PurchaseOrder, PurchaseOrderStatus, PurchaseOrderWithItems, SupplierResponse,
+};
+use crate::modules::inventory::purchase_order::PurchaseOrderService;
+use crate::modules::system::notifications::NotificationService;
+use crate::modules::system::portal::{PortalScope, PortalService};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// What a supplier can answer on a purchase order link
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SupplierReply {
+    /// Accept the order as it is
+    Confirm { message: Option<String> },
+    /// Accept the order for delivery on another date
+    ProposeDate { date: NaiveDate, message: Option<String> },
+    /// Goods are on their way
+    Ship {
+        carrier: Option<String>,
+        tracking_number: Option<String>,
+        message: Option<String>,
+    },
+}
+
+impl SupplierReply {
+    fn kind(&self) -> &'static str {
+        match self {
+            SupplierReply::Confirm { .. } => "confirmed",
+            SupplierReply::ProposeDate { .. } => "date_proposed",
+            SupplierReply::Ship { .. } => "shipped",
+        }
+    }
+}
+
+/// Purchase orders shared with suppliers through portal links.
+///
+/// A supplier's answer is kept as a response, moves the order to confirmed
+/// or shipped and notifies the buyer who created (or else approved) it.
+pub struct SupplierPortalService;
+
+impl SupplierPortalService {
+    /// Issue a link to an approved purchase order, marking it sent
+    pub fn share(
+        conn: &mut DatabaseConnection,
+        secret: &str,
+        po_id: i32,
+        ttl_days: u32,
+        shared_by: Option<i32>,
+    ) -> Result<(PortalLink, String)> {
+        let po = Self::find(conn, po_id)?;
+        if !Self::is_open(&po) {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Purchase order {} is {}; only approved orders can be shared with the supplier",
+                po.po_number, po.status
+            )));
+        }
+
+        let issued = PortalService::issue(conn, secret, PortalScope::SupplierPo, po_id, ttl_days, shared_by)?;
+        if po.status == PurchaseOrderStatus::Approved.to_string() {
+            diesel::update(purchase_orders::table.find(po_id))
+                .set((
+                    purchase_orders::status.eq(PurchaseOrderStatus::Sent.to_string()),
+                    purchase_orders::updated_at.eq(Utc::now().naive_utc()),
+                ))
+                .execute(conn)?;
+        }
+        Ok(issued)
+    }
+
+    /// The purchase order a supplier link opens
+    pub fn open(conn: &mut DatabaseConnection, secret: &str, token: &str) -> Result<(PortalLink, PurchaseOrderWithItems)> {
+        let link = PortalService::verify(conn, secret, token, PortalScope::SupplierPo)?;
+        let po = PurchaseOrderService::get_purchase_order_with_details(conn, link.record_id)?;
+        Ok((link, po))
+    }
+
+    /// Record a supplier's answer given through `link`
+    pub fn respond(conn: &mut DatabaseConnection, link: &PortalLink, reply: SupplierReply) -> Result<SupplierResponse> {
+        let po = Self::find(conn, link.record_id)?;
+        if !Self::is_open(&po) {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Purchase order {} is {} and can no longer be changed",
+                po.po_number, po.status
+            )));
+        }
+        if po.status == PurchaseOrderStatus::Shipped.to_string() && !matches!(reply, SupplierReply::Ship { .. }) {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Purchase order {} has already been shipped",
+                po.po_number
+            )));
+        }
+
+        let now = Utc::now().naive_utc();
+        let mut response = NewSupplierResponse {
+            po_id: po.id,
+            link_id: Some(link.id),
+            kind: reply.kind().to_string(),
+            proposed_date: None,
+            carrier: None,
+            tracking_number: None,
+            message: None,
+        };
+        let (subject, status) = match reply {
+            SupplierReply::Confirm { message } => {
+                response.message = clean(message);
+                (format!("{} confirmed by the supplier", po.po_number), PurchaseOrderStatus::Confirmed)
+            }
+            SupplierReply::ProposeDate { date, message } => {
+                if date < now.date() {
+                    return Err(CLIERPError::Validation("The delivery date cannot be in the past".to_string()));
+                }
+                response.proposed_date = Some(date);
+                response.message = clean(message);
+                (
+                    format!("{} confirmed for delivery on {}", po.po_number, date),
+                    PurchaseOrderStatus::Confirmed,
+                )
+            }
+            SupplierReply::Ship { carrier, tracking_number, message } => {
+                response.carrier = clean(carrier);
+                response.tracking_number = clean(tracking_number);
+                response.message = clean(message);
+                (format!("{} shipped by the supplier", po.po_number), PurchaseOrderStatus::Shipped)
+            }
+        };
+
+        let saved = conn.transaction::<_, CLIERPError, _>(|conn| {
+            diesel::insert_into(supplier_responses::table)
+                .values(&response)
+                .execute(conn)?;
+            diesel::update(purchase_orders::table.find(po.id))
+                .set((
+                    purchase_orders::status.eq(status.to_string()),
+                    purchase_orders::expected_date.eq(response.proposed_date.or(po.expected_date)),
+                    purchase_orders::updated_at.eq(now),
+                ))
+                .execute(conn)?;
+
+            if let Some(buyer) = po.created_by.or(po.approved_by) {
+                let mut body = Vec::new();
+                if let Some(carrier) = &response.carrier {
+                    body.push(format!("Carrier: {}", carrier));
+                }
+                if let Some(tracking) = &response.tracking_number {
+                    body.push(format!("Tracking number: {}", tracking));
+                }
+                if let Some(message) = &response.message {
+                    body.push(message.clone());
+                }
+                let body = (!body.is_empty()).then(|| body.join("\n"));
+                NotificationService::notify(conn, buyer, &subject, body.as_deref())?;
+            }
+
+            Ok(supplier_responses::table
+                .filter(supplier_responses::po_id.eq(po.id))
+                .order(supplier_responses::id.desc())
+                .first::<SupplierResponse>(conn)?)
+        })?;
+
+        tracing::info!("Supplier response '{}' recorded for {}", saved.kind, po.po_number);
+        Ok(saved)
+    }
+
+    /// Supplier responses to a purchase order, oldest first
+    pub fn responses(conn: &mut DatabaseConnection, po_id: i32) -> Result<Vec<SupplierResponse>> {
+        Ok(supplier_responses::table
+            .filter(supplier_responses::po_id.eq(po_id))
+            .order(supplier_responses::created_at.asc())
+            .load::<SupplierResponse>(conn)?)
+    }
+
+    fn find(conn: &mut DatabaseConnection, po_id: i32) -> Result<PurchaseOrder> {
+        purchase_orders::table
+            .find(po_id)
+            .first::<PurchaseOrder>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Purchase order with ID {} not found", po_id)))
+    }
+
+    /// Approved and not yet received or cancelled
+    fn is_open(po: &PurchaseOrder) -> bool {
+        [
+            PurchaseOrderStatus::Approved,
+            PurchaseOrderStatus::Sent,
+            PurchaseOrderStatus::Confirmed,
+            PurchaseOrderStatus::Shipped,
+        ]
+        .iter()
+        .any(|s| po.status == s.to_string())
+    }
+}
+
+fn clean(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
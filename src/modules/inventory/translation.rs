@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use diesel::prelude::*;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{categories, category_translations, product_translations, products};
+use crate::database::{
+    Category, CategoryTranslation, NewCategoryTranslation, NewProductTranslation, Product, ProductTranslation,
+};
+use crate::modules::inventory::product::ProductWithCategory;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Lowercase a locale tag such as `de_CH` to `de-ch`, rejecting anything
+/// that is not a language code with an optional region or script
+pub fn normalize_locale(locale: &str) -> Result<String> {
+    let normalized = locale.trim().replace('_', "-").to_lowercase();
+    let mut parts = normalized.split('-');
+    let language = parts.next().unwrap_or_default();
+    let region = parts.next();
+    let valid = (2..=3).contains(&language.len())
+        && language.chars().all(|c| c.is_ascii_lowercase())
+        && region.is_none_or(|r| (2..=8).contains(&r.len()) && r.chars().all(|c| c.is_ascii_alphanumeric()))
+        && parts.next().is_none();
+    if valid {
+        Ok(normalized)
+    } else {
+        Err(CLIERPError::Validation(format!(
+            "Invalid locale '{}'; use a language code such as de or de-CH",
+            locale
+        )))
+    }
+}
+
+/// Locales tried for `locale`, most specific first: `de-ch` falls back to `de`
+pub fn fallback_chain(locale: &str) -> Vec<String> {
+    let mut chain = vec![locale.to_string()];
+    if let Some((language, _)) = locale.split_once('-') {
+        chain.push(language.to_string());
+    }
+    chain
+}
+
+/// Translated names and descriptions of products and categories.
+///
+/// Translations replace the name and description when a list, show or
+/// export is run with a locale; products without one for that locale keep
+/// their own text, so a partial translation never hides a product.
+pub struct TranslationService;
+
+impl TranslationService {
+    /// Add or change the translation of a product. A new translation needs
+    /// a name; an existing one keeps whatever is not given.
+    pub fn set_product(
+        conn: &mut SqliteConnection,
+        product_id: i32,
+        locale: &str,
+        name: Option<&str>,
+        description: Option<&str>,
+    ) -> Result<ProductTranslation> {
+        let locale = normalize_locale(locale)?;
+        products::table.find(product_id).first::<Product>(conn)?;
+        let name = non_empty(name, "Translated name")?;
+
+        let existing = product_translations::table
+            .filter(product_translations::product_id.eq(product_id))
+            .filter(product_translations::locale.eq(&locale))
+            .first::<ProductTranslation>(conn)
+            .optional()?;
+
+        match existing {
+            Some(translation) => {
+                diesel::update(product_translations::table.find(translation.id))
+                    .set((
+                        product_translations::name.eq(name.unwrap_or(translation.name.as_str())),
+                        product_translations::description
+                            .eq(description.map(str::to_string).or(translation.description.clone())),
+                        product_translations::updated_at.eq(Utc::now().naive_utc()),
+                    ))
+                    .execute(conn)?;
+            }
+            None => {
+                let name = name.ok_or_else(|| {
+                    CLIERPError::Validation(format!("--name is required for a new {} translation", locale))
+                })?;
+                diesel::insert_into(product_translations::table)
+                    .values(&NewProductTranslation {
+                        product_id,
+                        locale: locale.clone(),
+                        name: name.to_string(),
+                        description: description.map(str::to_string),
+                    })
+                    .execute(conn)?;
+            }
+        }
+
+        tracing::info!("Saved {} translation of product {}", locale, product_id);
+        Ok(product_translations::table
+            .filter(product_translations::product_id.eq(product_id))
+            .filter(product_translations::locale.eq(&locale))
+            .first::<ProductTranslation>(conn)?)
+    }
+
+    /// Delete a product translation, returning whether it existed
+    pub fn remove_product(conn: &mut SqliteConnection, product_id: i32, locale: &str) -> Result<bool> {
+        let locale = normalize_locale(locale)?;
+        let deleted = diesel::delete(
+            product_translations::table
+                .filter(product_translations::product_id.eq(product_id))
+                .filter(product_translations::locale.eq(&locale)),
+        )
+        .execute(conn)?;
+        Ok(deleted > 0)
+    }
+
+    pub fn product_translations(conn: &mut SqliteConnection, product_id: i32) -> Result<Vec<ProductTranslation>> {
+        Ok(product_translations::table
+            .filter(product_translations::product_id.eq(product_id))
+            .order(product_translations::locale.asc())
+            .load::<ProductTranslation>(conn)?)
+    }
+
+    /// Add or change the translation of a category, like [`Self::set_product`]
+    pub fn set_category(
+        conn: &mut SqliteConnection,
+        category_id: i32,
+        locale: &str,
+        name: Option<&str>,
+        description: Option<&str>,
+    ) -> Result<CategoryTranslation> {
+        let locale = normalize_locale(locale)?;
+        categories::table.find(category_id).first::<Category>(conn)?;
+        let name = non_empty(name, "Translated name")?;
+
+        let existing = category_translations::table
+            .filter(category_translations::category_id.eq(category_id))
+            .filter(category_translations::locale.eq(&locale))
+            .first::<CategoryTranslation>(conn)
+            .optional()?;
+
+        match existing {
+            Some(translation) => {
+                diesel::update(category_translations::table.find(translation.id))
+                    .set((
+                        category_translations::name.eq(name.unwrap_or(translation.name.as_str())),
+                        category_translations::description
+                            .eq(description.map(str::to_string).or(translation.description.clone())),
+                        category_translations::updated_at.eq(Utc::now().naive_utc()),
+                    ))
+                    .execute(conn)?;
+            }
+            None => {
+                let name = name.ok_or_else(|| {
+                    CLIERPError::Validation(format!("--name is required for a new {} translation", locale))
+                })?;
+                diesel::insert_into(category_translations::table)
+                    .values(&NewCategoryTranslation {
+                        category_id,
+                        locale: locale.clone(),
+                        name: name.to_string(),
+                        description: description.map(str::to_string),
+                    })
+                    .execute(conn)?;
+            }
+        }
+
+        tracing::info!("Saved {} translation of category {}", locale, category_id);
+        Ok(category_translations::table
+            .filter(category_translations::category_id.eq(category_id))
+            .filter(category_translations::locale.eq(&locale))
+            .first::<CategoryTranslation>(conn)?)
+    }
+
+    /// Delete a category translation, returning whether it existed
+    pub fn remove_category(conn: &mut SqliteConnection, category_id: i32, locale: &str) -> Result<bool> {
+        let locale = normalize_locale(locale)?;
+        let deleted = diesel::delete(
+            category_translations::table
+                .filter(category_translations::category_id.eq(category_id))
+                .filter(category_translations::locale.eq(&locale)),
+        )
+        .execute(conn)?;
+        Ok(deleted > 0)
+    }
+
+    /// Replace product names and descriptions with their `locale` translations
+    pub fn localize_products(conn: &mut SqliteConnection, products: &mut [Product], locale: &str) -> Result<()> {
+        let ids: Vec<i32> = products.iter().map(|p| p.id).collect();
+        let translations = Self::product_lookup(conn, &ids, locale)?;
+        for product in products.iter_mut() {
+            if let Some(translation) = translations.get(&product.id) {
+                product.name = translation.name.clone();
+                if translation.description.is_some() {
+                    product.description = translation.description.clone();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Localize both the products and their categories of a product list
+    pub fn localize_product_list(
+        conn: &mut SqliteConnection,
+        rows: &mut [ProductWithCategory],
+        locale: &str,
+    ) -> Result<()> {
+        let product_ids: Vec<i32> = rows.iter().map(|r| r.product.id).collect();
+        let products = Self::product_lookup(conn, &product_ids, locale)?;
+        let category_ids: Vec<i32> = rows.iter().map(|r| r.category.id).collect();
+        let categories = Self::category_lookup(conn, &category_ids, locale)?;
+
+        for row in rows.iter_mut() {
+            if let Some(translation) = products.get(&row.product.id) {
+                row.product.name = translation.name.clone();
+                if translation.description.is_some() {
+                    row.product.description = translation.description.clone();
+                }
+            }
+            if let Some(translation) = categories.get(&row.category.id) {
+                row.category.name = translation.name.clone();
+                if translation.description.is_some() {
+                    row.category.description = translation.description.clone();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace category names and descriptions with their `locale` translations
+    pub fn localize_categories(conn: &mut SqliteConnection, categories: &mut [Category], locale: &str) -> Result<()> {
+        let ids: Vec<i32> = categories.iter().map(|c| c.id).collect();
+        let translations = Self::category_lookup(conn, &ids, locale)?;
+        for category in categories.iter_mut() {
+            if let Some(translation) = translations.get(&category.id) {
+                category.name = translation.name.clone();
+                if translation.description.is_some() {
+                    category.description = translation.description.clone();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Best translation per product, following [`fallback_chain`]
+    fn product_lookup(
+        conn: &mut SqliteConnection,
+        ids: &[i32],
+        locale: &str,
+    ) -> Result<HashMap<i32, ProductTranslation>> {
+        let chain = fallback_chain(&normalize_locale(locale)?);
+        let rows = product_translations::table
+            .filter(product_translations::product_id.eq_any(ids))
+            .filter(product_translations::locale.eq_any(&chain))
+            .load::<ProductTranslation>(conn)?;
+        Ok(best_by_chain(rows, &chain, |t| (t.product_id, t.locale.as_str())))
+    }
+
+    fn category_lookup(
+        conn: &mut SqliteConnection,
+        ids: &[i32],
+        locale: &str,
+    ) -> Result<HashMap<i32, CategoryTranslation>> {
+        let chain = fallback_chain(&normalize_locale(locale)?);
+        let rows = category_translations::table
+            .filter(category_translations::category_id.eq_any(ids))
+            .filter(category_translations::locale.eq_any(&chain))
+            .load::<CategoryTranslation>(conn)?;
+        Ok(best_by_chain(rows, &chain, |t| (t.category_id, t.locale.as_str())))
+    }
+}
+
+/// `None` for a missing value, an error for a blank one
+fn non_empty<'a>(value: Option<&'a str>, field: &str) -> Result<Option<&'a str>> {
+    match value.map(str::trim) {
+        Some("") => Err(CLIERPError::Validation(format!("{} cannot be empty", field))),
+        value => Ok(value),
+    }
+}
+
+/// Keep, per record, the translation whose locale comes first in `chain`
+fn best_by_chain<T>(rows: Vec<T>, chain: &[String], key: impl Fn(&T) -> (i32, &str)) -> HashMap<i32, T> {
+    let rank = |locale: &str| chain.iter().position(|l| l == locale).unwrap_or(usize::MAX);
+    let mut best: HashMap<i32, T> = HashMap::new();
+    for row in rows {
+        let (id, locale) = key(&row);
+        let better = best.get(&id).is_none_or(|current| rank(locale) < rank(key(current).1));
+        if better {
+            best.insert(id, row);
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_fallback() {
+        assert_eq!(normalize_locale("de_CH").unwrap(), "de-ch");
+        assert_eq!(normalize_locale(" FR ").unwrap(), "fr");
+        assert!(normalize_locale("german").is_err());
+        assert!(normalize_locale("de-ch-x").is_err());
+
+        assert_eq!(fallback_chain("de-ch"), vec!["de-ch".to_string(), "de".to_string()]);
+        assert_eq!(fallback_chain("fr"), vec!["fr".to_string()]);
+
+        let chain = fallback_chain("de-ch");
+        let rows = vec![(1, "de"), (1, "de-ch"), (2, "de")];
+        let best = best_by_chain(rows, &chain, |r| (r.0, r.1));
+        assert_eq!(best[&1].1, "de-ch");
+        assert_eq!(best[&2].1, "de");
+    }
+}
@@ -0,0 +1,539 @@
+use chrono::{NaiveDate, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::aging::InventoryAgingService;
+use crate::core::auth::AuthenticatedUser;
+use crate::core::config::WriteDownConfig;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::core::workflow::{
+    create_default_workflows, role_satisfies, WorkflowAction, WorkflowContext, WorkflowEngine,
+};
+use crate::database::schema::{accounts, products, stock_movements, stock_write_down_lines, stock_write_downs};
+use crate::database::{
+    get_connection, DatabaseConnection, NewStockWriteDown, NewStockWriteDownLine, Product, StockMovementType,
+    StockWriteDown, StockWriteDownLine, WriteDownReason, WriteDownStatus,
+};
+use crate::modules::finance::{JournalEntryRequest, JournalLine, JournalService};
+use crate::modules::system::sod::{SodRule, SodService};
+use crate::utils::timezone::DisplayTimezone;
+use crate::utils::validation::validate_required_string;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Workflow that write-down proposals go through
+pub const STOCK_WRITE_DOWN_WORKFLOW: &str = "stock_write_down_approval";
+/// Step of `STOCK_WRITE_DOWN_WORKFLOW` that posts the provision
+const APPROVE_STEP: &str = "approve_stock_write_down";
+
+/// `transactions.reference` of the postings of a write-down
+pub fn write_down_reference(write_down_id: i32) -> String {
+    format!("writedown:{}", write_down_id)
+}
+
+/// Thresholds that make stock slow moving or dead, from configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteDownPolicy {
+    pub slow_days: i64,
+    pub slow_percent: u32,
+    pub dead_days: i64,
+    pub dead_percent: u32,
+}
+
+impl WriteDownPolicy {
+    pub fn from_config(config: &WriteDownConfig) -> Result<Self> {
+        let valid_percent = |percent: u32| (1..=100).contains(&percent);
+        if config.slow_days <= 0 || config.dead_days <= config.slow_days {
+            return Err(CLIERPError::Configuration(config::ConfigError::Message(
+                "write_down.dead_days must be longer than write_down.slow_days, which must be positive".to_string(),
+            )));
+        }
+        if !valid_percent(config.slow_percent) || !valid_percent(config.dead_percent) {
+            return Err(CLIERPError::Configuration(config::ConfigError::Message(
+                "write_down.slow_percent and write_down.dead_percent must be between 1 and 100".to_string(),
+            )));
+        }
+        Ok(Self {
+            slow_days: config.slow_days,
+            slow_percent: config.slow_percent,
+            dead_days: config.dead_days,
+            dead_percent: config.dead_percent,
+        })
+    }
+}
+
+/// Accounts an approved write-down is posted to, from configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteDownAccounts {
+    pub expense: String,
+    pub provision: String,
+}
+
+impl WriteDownAccounts {
+    pub fn from_config(config: &WriteDownConfig) -> Result<Self> {
+        match (&config.expense_account, &config.provision_account) {
+            (Some(expense), Some(provision)) => Ok(Self {
+                expense: expense.clone(),
+                provision: provision.clone(),
+            }),
+            _ => Err(CLIERPError::Configuration(config::ConfigError::Message(
+                "Set write_down.expense_account and write_down.provision_account (CLIERP_WRITE_DOWN__EXPENSE_ACCOUNT, CLIERP_WRITE_DOWN__PROVISION_ACCOUNT) to approve stock write-downs".to_string(),
+            ))),
+        }
+    }
+}
+
+/// A product whose stock should be provisioned further
+#[derive(Debug, Clone, Serialize)]
+pub struct WriteDownCandidate {
+    pub product_id: i32,
+    pub sku: String,
+    pub name: String,
+    pub reason: WriteDownReason,
+    pub quantity: i32,
+    pub unit_cost: i32,
+    /// Share of the cost that should be provisioned
+    pub percent: u32,
+    /// Share already provisioned by approved write-downs
+    pub provisioned_percent: u32,
+    pub amount: i32,
+}
+
+/// Provisions for slow-moving and dead stock.
+///
+/// Stock has no lots or expiry dates, so it is judged by the receipt
+/// layers of the aging report and the last time the product was issued.
+/// A proposal leaves the ledger untouched until a manager approves it
+/// through the `stock_write_down_approval` workflow, whose approve step
+/// posts the provision. Approved lines tag their products: a product stays
+/// provisioned at the percentage of its latest approved write-down, and
+/// later proposals only add what is missing.
+pub struct WriteDownService;
+
+impl WriteDownService {
+    /// Products that need a provision as of `as_of`, largest amount first
+    pub fn candidates(
+        conn: &mut DatabaseConnection,
+        policy: &WriteDownPolicy,
+        as_of: NaiveDate,
+    ) -> Result<Vec<WriteDownCandidate>> {
+        let stocked = products::table
+            .filter(products::is_active.eq(true))
+            .filter(products::current_stock.gt(0))
+            .order(products::sku.asc())
+            .load::<Product>(conn)?;
+        let refs: Vec<&Product> = stocked.iter().collect();
+        let mut layers = InventoryAgingService::layers(conn, &refs)?;
+
+        let product_ids: Vec<i32> = stocked.iter().map(|product| product.id).collect();
+        let timezone = DisplayTimezone::current();
+        let mut last_issues: HashMap<i32, NaiveDate> = HashMap::new();
+        let issues: Vec<(i32, chrono::NaiveDateTime)> = stock_movements::table
+            .filter(stock_movements::product_id.eq_any(&product_ids))
+            .filter(stock_movements::movement_type.eq(StockMovementType::Out.to_string()))
+            .select((stock_movements::product_id, stock_movements::movement_date))
+            .load(conn)?;
+        for (product_id, moved_at) in issues {
+            let date = timezone.to_local(moved_at).date();
+            let last = last_issues.entry(product_id).or_insert(date);
+            *last = (*last).max(date);
+        }
+        let provisioned = Self::provisioned_percents(conn, &product_ids)?;
+
+        let mut candidates: Vec<WriteDownCandidate> = stocked
+            .into_iter()
+            .filter_map(|product| {
+                let layers = layers.remove(&product.id).unwrap_or_default();
+                let (reason, quantity, percent) =
+                    assess(&layers, last_issues.get(&product.id).copied(), as_of, policy)?;
+                let provisioned_percent = provisioned.get(&product.id).copied().unwrap_or(0);
+                let amount = provision_amount(quantity, product.cost_price, percent, provisioned_percent);
+                (amount > 0).then_some(WriteDownCandidate {
+                    product_id: product.id,
+                    sku: product.sku,
+                    name: product.name,
+                    reason,
+                    quantity,
+                    unit_cost: product.cost_price,
+                    percent,
+                    provisioned_percent,
+                    amount,
+                })
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.amount.cmp(&a.amount).then_with(|| a.sku.cmp(&b.sku)));
+        Ok(candidates)
+    }
+
+    /// Propose a write-down of the current candidates for approval. Only
+    /// one proposal may be pending at a time, so none is provisioned twice.
+    pub fn propose(
+        conn: &mut DatabaseConnection,
+        policy: &WriteDownPolicy,
+        as_of: NaiveDate,
+        proposed_by: Option<i32>,
+    ) -> Result<(StockWriteDown, Vec<WriteDownCandidate>)> {
+        let pending = stock_write_downs::table
+            .filter(stock_write_downs::status.eq(WriteDownStatus::Pending.to_string()))
+            .select(stock_write_downs::id)
+            .first::<i32>(conn)
+            .optional()?;
+        if let Some(pending) = pending {
+            return Err(CLIERPError::AlreadyExists(format!(
+                "Write-down {} is still pending; approve or reject it first",
+                pending
+            )));
+        }
+        let candidates = Self::candidates(conn, policy, as_of)?;
+        if candidates.is_empty() {
+            return Err(CLIERPError::ValidationError(format!(
+                "No slow-moving or dead stock to provision as of {}",
+                as_of
+            )));
+        }
+        let amount: i32 = candidates.iter().map(|c| c.amount).sum();
+
+        let write_down = conn.transaction::<_, CLIERPError, _>(|conn| {
+            diesel::insert_into(stock_write_downs::table)
+                .values(&NewStockWriteDown { as_of, amount, proposed_by })
+                .execute(conn)?;
+            let write_down = stock_write_downs::table
+                .order(stock_write_downs::id.desc())
+                .first::<StockWriteDown>(conn)?;
+            let lines: Vec<NewStockWriteDownLine> = candidates
+                .iter()
+                .map(|candidate| NewStockWriteDownLine {
+                    write_down_id: write_down.id,
+                    product_id: candidate.product_id,
+                    reason: candidate.reason.to_string(),
+                    quantity: candidate.quantity,
+                    unit_cost: candidate.unit_cost,
+                    percent: candidate.percent as i32,
+                    amount: candidate.amount,
+                })
+                .collect();
+            diesel::insert_into(stock_write_down_lines::table)
+                .values(&lines)
+                .execute(conn)?;
+            Ok(write_down)
+        })?;
+
+        tracing::info!(
+            "Proposed stock write-down {} of {} over {} products",
+            write_down.id,
+            amount,
+            candidates.len()
+        );
+        Ok((write_down, candidates))
+    }
+
+    /// Write-downs, newest first, optionally only those with `status`
+    pub fn list(
+        conn: &mut DatabaseConnection,
+        status: Option<WriteDownStatus>,
+        limit: i64,
+    ) -> Result<Vec<StockWriteDown>> {
+        let mut query = stock_write_downs::table.into_boxed();
+        if let Some(status) = status {
+            query = query.filter(stock_write_downs::status.eq(status.to_string()));
+        }
+        query
+            .order(stock_write_downs::id.desc())
+            .limit(limit)
+            .load::<StockWriteDown>(conn)
+            .map_err(Into::into)
+    }
+
+    pub fn find(conn: &mut DatabaseConnection, write_down_id: i32) -> Result<StockWriteDown> {
+        stock_write_downs::table
+            .find(write_down_id)
+            .first::<StockWriteDown>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Stock write-down {} not found", write_down_id)))
+    }
+
+    /// Lines of a write-down with their products, largest first
+    pub fn lines(conn: &mut DatabaseConnection, write_down_id: i32) -> Result<Vec<(StockWriteDownLine, Product)>> {
+        stock_write_down_lines::table
+            .inner_join(products::table)
+            .filter(stock_write_down_lines::write_down_id.eq(write_down_id))
+            .order(stock_write_down_lines::amount.desc())
+            .select((StockWriteDownLine::as_select(), Product::as_select()))
+            .load::<(StockWriteDownLine, Product)>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Latest approved write-down line of a product, the provision it carries
+    pub fn provision(conn: &mut DatabaseConnection, product_id: i32) -> Result<Option<StockWriteDownLine>> {
+        stock_write_down_lines::table
+            .inner_join(stock_write_downs::table)
+            .filter(stock_write_down_lines::product_id.eq(product_id))
+            .filter(stock_write_downs::status.eq(WriteDownStatus::Approved.to_string()))
+            .order(stock_write_down_lines::id.desc())
+            .select(StockWriteDownLine::as_select())
+            .first::<StockWriteDownLine>(conn)
+            .optional()
+            .map_err(Into::into)
+    }
+
+    /// Approve a pending write-down and post it through the approval
+    /// workflow. `sod_override` is the reason for approving one's own proposal.
+    pub fn approve(
+        conn: &mut DatabaseConnection,
+        write_down_id: i32,
+        accounts: &WriteDownAccounts,
+        approver: AuthenticatedUser,
+        note: Option<&str>,
+        sod_override: Option<&str>,
+    ) -> Result<StockWriteDown> {
+        let write_down = Self::pending_for_decision(conn, write_down_id, &approver)?;
+        SodService::check(
+            conn,
+            SodRule::WriteDownProposeApprove,
+            write_down.id,
+            write_down.proposed_by,
+            approver.id,
+            sod_override,
+        )?;
+
+        let mut engine = WorkflowEngine::new();
+        for workflow in create_default_workflows() {
+            engine.register_workflow(workflow);
+        }
+        engine.register_action(PostStockWriteDown);
+
+        engine.start_workflow(
+            STOCK_WRITE_DOWN_WORKFLOW,
+            WorkflowContext {
+                user: None,
+                data: HashMap::new(),
+            },
+        )?;
+        let mut context = WorkflowContext {
+            user: Some(approver),
+            data: HashMap::from([
+                ("write_down_id".to_string(), serde_json::json!(write_down.id)),
+                ("expense_account".to_string(), serde_json::json!(accounts.expense)),
+                ("provision_account".to_string(), serde_json::json!(accounts.provision)),
+            ]),
+        };
+        while engine.execute_next_step(STOCK_WRITE_DOWN_WORKFLOW, &mut context)? {}
+
+        let approver_id = context.user.as_ref().map(|user| user.id);
+        diesel::update(stock_write_downs::table.find(write_down.id))
+            .set((
+                stock_write_downs::status.eq(WriteDownStatus::Approved.to_string()),
+                stock_write_downs::decided_by.eq(approver_id),
+                stock_write_downs::decided_at.eq(Some(Utc::now().naive_utc())),
+                stock_write_downs::decision_note.eq(note),
+            ))
+            .execute(conn)?;
+
+        tracing::info!("Stock write-down {} approved and posted", write_down.id);
+        Self::find(conn, write_down.id)
+    }
+
+    /// Decline a pending write-down; nothing is posted
+    pub fn reject(
+        conn: &mut DatabaseConnection,
+        write_down_id: i32,
+        approver: &AuthenticatedUser,
+        reason: &str,
+    ) -> Result<StockWriteDown> {
+        validate_required_string(reason, "Reason")?;
+        let write_down = Self::pending_for_decision(conn, write_down_id, approver)?;
+
+        diesel::update(stock_write_downs::table.find(write_down.id))
+            .set((
+                stock_write_downs::status.eq(WriteDownStatus::Rejected.to_string()),
+                stock_write_downs::decided_by.eq(Some(approver.id)),
+                stock_write_downs::decided_at.eq(Some(Utc::now().naive_utc())),
+                stock_write_downs::decision_note.eq(Some(reason)),
+            ))
+            .execute(conn)?;
+
+        tracing::info!("Stock write-down {} rejected", write_down.id);
+        Self::find(conn, write_down.id)
+    }
+
+    /// Percent provisioned per product by its latest approved write-down
+    fn provisioned_percents(conn: &mut DatabaseConnection, product_ids: &[i32]) -> Result<HashMap<i32, u32>> {
+        let rows: Vec<(i32, i32)> = stock_write_down_lines::table
+            .inner_join(stock_write_downs::table)
+            .filter(stock_write_down_lines::product_id.eq_any(product_ids))
+            .filter(stock_write_downs::status.eq(WriteDownStatus::Approved.to_string()))
+            .order(stock_write_down_lines::id.asc())
+            .select((stock_write_down_lines::product_id, stock_write_down_lines::percent))
+            .load(conn)?;
+        // Later lines overwrite earlier ones
+        Ok(rows
+            .into_iter()
+            .map(|(product_id, percent)| (product_id, percent.max(0) as u32))
+            .collect())
+    }
+
+    /// Load a write-down that `approver` may decide on
+    fn pending_for_decision(
+        conn: &mut DatabaseConnection,
+        write_down_id: i32,
+        approver: &AuthenticatedUser,
+    ) -> Result<StockWriteDown> {
+        let write_down = Self::find(conn, write_down_id)?;
+        if write_down.status != WriteDownStatus::Pending.to_string() {
+            return Err(CLIERPError::ValidationError(format!(
+                "Stock write-down {} is already {}",
+                write_down.id, write_down.status
+            )));
+        }
+        let required_role = create_default_workflows()
+            .into_iter()
+            .find(|workflow| workflow.id == STOCK_WRITE_DOWN_WORKFLOW)
+            .and_then(|workflow| workflow.steps.into_iter().find(|step| step.id == APPROVE_STEP))
+            .and_then(|step| step.required_role);
+        if let Some(required_role) = required_role {
+            if !role_satisfies(&approver.role, &required_role) {
+                return Err(CLIERPError::Authorization(format!(
+                    "Deciding on stock write-downs requires role: {}",
+                    required_role
+                )));
+            }
+        }
+        Ok(write_down)
+    }
+}
+
+/// Approve step of the stock write-down workflow: posts the provision,
+/// debiting the expense and crediting the provision account on the
+/// write-down date
+struct PostStockWriteDown;
+
+impl WorkflowAction for PostStockWriteDown {
+    fn execute(&self, context: &mut WorkflowContext) -> CLIERPResult<()> {
+        let value = |key: &str| {
+            context
+                .data
+                .get(key)
+                .cloned()
+                .ok_or_else(|| CLIERPError::Internal(format!("Workflow context has no {}", key)))
+        };
+        let write_down_id = value("write_down_id")?.as_i64().unwrap_or_default() as i32;
+        let expense = value("expense_account")?.as_str().unwrap_or_default().to_string();
+        let provision = value("provision_account")?.as_str().unwrap_or_default().to_string();
+        let created_by = context.user.as_ref().map(|user| user.id);
+
+        let mut conn = get_connection()?;
+        let write_down = WriteDownService::find(&mut conn, write_down_id)?;
+        let expense_id = account_id(&mut conn, &expense)?;
+        let provision_id = account_id(&mut conn, &provision)?;
+        JournalService::post_entry(
+            &mut conn,
+            JournalEntryRequest {
+                entry_date: write_down.as_of,
+                description: format!("Write-down of slow-moving and dead stock {}", write_down.id),
+                reference: Some(write_down_reference(write_down.id)),
+                lines: vec![
+                    JournalLine::debit(expense_id, write_down.amount),
+                    JournalLine::credit(provision_id, write_down.amount),
+                ],
+            },
+            created_by,
+        )?;
+        Ok(())
+    }
+
+    fn can_execute(&self, context: &WorkflowContext) -> bool {
+        context.data.contains_key("write_down_id")
+    }
+
+    fn name(&self) -> &str {
+        APPROVE_STEP
+    }
+}
+
+fn account_id(conn: &mut DatabaseConnection, code: &str) -> Result<i32> {
+    accounts::table
+        .filter(accounts::account_code.eq(code))
+        .select(accounts::id)
+        .first::<i32>(conn)
+        .optional()?
+        .ok_or_else(|| CLIERPError::NotFound(format!("Write-down account '{}' not found", code)))
+}
+
+/// Judge the on-hand `layers` of a product (newest first): dead when
+/// nothing was received or issued within `dead_days`, slow when some of it
+/// has been held longer than `slow_days`. Returns the reason, the quantity
+/// concerned and the percent of its cost to provision.
+pub fn assess(
+    layers: &[(NaiveDate, i32)],
+    last_issue: Option<NaiveDate>,
+    as_of: NaiveDate,
+    policy: &WriteDownPolicy,
+) -> Option<(WriteDownReason, i32, u32)> {
+    let newest = layers.first()?.0;
+    let age = |date: NaiveDate| (as_of - date).num_days();
+    let on_hand: i32 = layers.iter().map(|(_, quantity)| quantity).sum();
+    if age(newest) > policy.dead_days && last_issue.is_none_or(|date| age(date) > policy.dead_days) {
+        return Some((WriteDownReason::Dead, on_hand, policy.dead_percent));
+    }
+    let slow: i32 = layers
+        .iter()
+        .filter(|(date, _)| age(*date) > policy.slow_days)
+        .map(|(_, quantity)| quantity)
+        .sum();
+    (slow > 0).then_some((WriteDownReason::Slow, slow, policy.slow_percent))
+}
+
+/// Provision still to add to bring `quantity` units at `unit_cost` from
+/// `provisioned` to `percent` of their cost
+pub fn provision_amount(quantity: i32, unit_cost: i32, percent: u32, provisioned: u32) -> i32 {
+    let missing = percent.saturating_sub(provisioned) as i64;
+    (quantity as i64 * unit_cost as i64 * missing / 100) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_assess_stock() {
+        let policy = WriteDownPolicy {
+            slow_days: 180,
+            slow_percent: 50,
+            dead_days: 365,
+            dead_percent: 100,
+        };
+        let as_of = date("2025-06-30");
+        let layers = [(date("2025-05-01"), 10), (date("2024-11-01"), 4), (date("2024-03-01"), 6)];
+
+        // Recent receipts, but 10 units held more than 180 days
+        assert_eq!(
+            assess(&layers, Some(date("2025-06-01")), as_of, &policy),
+            Some((WriteDownReason::Slow, 10, 50))
+        );
+        // Nothing received or issued for more than a year
+        let old = [(date("2024-03-01"), 6)];
+        assert_eq!(assess(&old, None, as_of, &policy), Some((WriteDownReason::Dead, 6, 100)));
+        assert_eq!(
+            assess(&old, Some(date("2024-06-01")), as_of, &policy),
+            Some((WriteDownReason::Dead, 6, 100))
+        );
+        // Still issued from, so only slow
+        assert_eq!(
+            assess(&old, Some(date("2025-01-01")), as_of, &policy),
+            Some((WriteDownReason::Slow, 6, 50))
+        );
+        assert_eq!(assess(&[(date("2025-05-01"), 3)], None, as_of, &policy), None);
+        assert_eq!(assess(&[], None, as_of, &policy), None);
+
+        assert_eq!(provision_amount(6, 1_000, 100, 0), 6_000);
+        assert_eq!(provision_amount(6, 1_000, 100, 50), 3_000);
+        assert_eq!(provision_amount(6, 1_000, 50, 50), 0);
+    }
+}
@@ -3,3 +3,5 @@ pub mod finance;
 pub mod hr;
 pub mod inventory;
 pub mod reporting;
+pub mod sales;
+pub mod system;
@@ -1,8 +1,20 @@
 use chrono::Utc;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use crate::core::result::CLIERPResult;
+use crate::database::get_report_connection;
+use crate::modules::crm::{
+    AttributionService, CampaignPerformance, CampaignService, ForecastService, NpsScore, RepForecast, SurveyService,
+};
+use crate::utils::dates::parse_period;
+use crate::utils::formatting::format_currency as format_amount;
+use crate::utils::timezone::DisplayTimezone;
+use super::data_source::*;
 use super::engine::*;
 
+/// Lead statuses counted as qualified and as opportunities in the funnel
+const QUALIFIED_STATUSES: &[&str] = &["qualified", "proposal", "negotiation", "closed_won"];
+const OPPORTUNITY_STATUSES: &[&str] = &["proposal", "negotiation", "closed_won"];
+
 pub struct CRMReportsGenerator;
 
 impl ReportGenerator for CRMReportsGenerator {
@@ -14,6 +26,7 @@ impl ReportGenerator for CRMReportsGenerator {
             "campaign_performance" => self.generate_campaign_performance_report(config),
             "sales_activity" => self.generate_sales_activity_report(config),
             "revenue_forecast" => self.generate_revenue_forecast_report(config),
+            "marketing_attribution" => self.generate_marketing_attribution_report(config),
             _ => Err(crate::core::error::CLIERPError::NotFound(
                 format!("CRM report '{}' not found", config.title)
             )),
@@ -29,9 +42,8 @@ impl ReportGenerator for CRMReportsGenerator {
                 required: false,
                 default_value: None,
                 options: Some(vec![
-                    FilterOption { value: "enterprise".to_string(), label: "Enterprise".to_string() },
-                    FilterOption { value: "smb".to_string(), label: "Small & Medium Business".to_string() },
                     FilterOption { value: "individual".to_string(), label: "Individual".to_string() },
+                    FilterOption { value: "business".to_string(), label: "Business".to_string() },
                 ]),
             },
             FilterDefinition {
@@ -49,10 +61,12 @@ impl ReportGenerator for CRMReportsGenerator {
                 required: false,
                 default_value: None,
                 options: Some(vec![
+                    FilterOption { value: "prospecting".to_string(), label: "Prospecting".to_string() },
                     FilterOption { value: "qualification".to_string(), label: "Qualification".to_string() },
                     FilterOption { value: "needs_analysis".to_string(), label: "Needs Analysis".to_string() },
                     FilterOption { value: "proposal".to_string(), label: "Proposal".to_string() },
                     FilterOption { value: "negotiation".to_string(), label: "Negotiation".to_string() },
+                    FilterOption { value: "closing".to_string(), label: "Closing".to_string() },
                     FilterOption { value: "closed_won".to_string(), label: "Closed Won".to_string() },
                     FilterOption { value: "closed_lost".to_string(), label: "Closed Lost".to_string() },
                 ]),
@@ -65,9 +79,10 @@ impl ReportGenerator for CRMReportsGenerator {
                 default_value: None,
                 options: Some(vec![
                     FilterOption { value: "email".to_string(), label: "Email Marketing".to_string() },
+                    FilterOption { value: "phone".to_string(), label: "Phone".to_string() },
                     FilterOption { value: "social".to_string(), label: "Social Media".to_string() },
-                    FilterOption { value: "webinar".to_string(), label: "Webinar".to_string() },
                     FilterOption { value: "event".to_string(), label: "Event".to_string() },
+                    FilterOption { value: "advertising".to_string(), label: "Advertising".to_string() },
                 ]),
             },
         ]
@@ -84,6 +99,7 @@ impl ReportGenerator for CRMReportsGenerator {
                 ReportFormat::Csv,
                 ReportFormat::Html,
                 ReportFormat::Text,
+                ReportFormat::Pdf,
             ],
         }
     }
@@ -95,492 +111,560 @@ impl CRMReportsGenerator {
     }
 
     fn generate_customer_analysis_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
-        let sections = vec![
+        let start_time = std::time::Instant::now();
+        let filters = ReportFilters::from_config(&config)?;
+        let (nps_start, nps_end) = filters.period_or(parse_period("this-year")?);
+        let mut conn = get_report_connection()?;
+        let customers = ReportDataSource::customers(&mut conn, &filters)?;
+        let won: Vec<DealRow> = ReportDataSource::deals(&mut conn, &filters)?
+            .into_iter()
+            .filter(|deal| deal.stage == "closed_won")
+            .collect();
+        let nps = SurveyService::nps(&mut conn, nps_start, nps_end)?;
+        let nps_row = |label: &str, score: &NpsScore| {
+            vec![
+                label.to_string(),
+                score.responses.to_string(),
+                score.promoters.to_string(),
+                score.passives.to_string(),
+                score.detractors.to_string(),
+                score.score().map(|s| format!("{:+.0}", s)).unwrap_or_default(),
+            ]
+        };
+
+        // Customers and won revenue per customer type
+        #[derive(Default)]
+        struct Segment {
+            customers: i64,
+            active: i64,
+            new: i64,
+            revenue: i64,
+            buyers: BTreeSet<i32>,
+        }
+        let mut segments: BTreeMap<String, Segment> = BTreeMap::new();
+        for customer in &customers {
+            let segment = segments.entry(customer.customer_type.clone()).or_default();
+            segment.customers += 1;
+            if customer.status == "active" {
+                segment.active += 1;
+            }
+            if filters.contains(customer.created_at) {
+                segment.new += 1;
+            }
+        }
+        for deal in &won {
+            let segment = segments
+                .entry(deal.customer_type.clone().unwrap_or_else(|| "no customer".to_string()))
+                .or_default();
+            segment.revenue += i64::from(deal.value);
+            if let Some(customer_id) = deal.customer_id {
+                segment.buyers.insert(customer_id);
+            }
+        }
+        let total_revenue: i64 = segments.values().map(|s| s.revenue).sum();
+        let total_buyers: usize = segments.values().map(|s| s.buyers.len()).sum();
+        let per_buyer = |revenue: i64, buyers: usize| if buyers == 0 { 0 } else { revenue / buyers as i64 };
+        let value_row = |label: String, customers: i64, active: i64, new: i64, buyers: usize, revenue: i64| {
+            vec![
+                label,
+                customers.to_string(),
+                active.to_string(),
+                new.to_string(),
+                buyers.to_string(),
+                format_amount(revenue),
+                format_amount(per_buyer(revenue, buyers)),
+                format_percentage(share(revenue, total_revenue)),
+            ]
+        };
+        let value_rows = segments
+            .iter()
+            .map(|(name, s)| value_row(title_case(name), s.customers, s.active, s.new, s.buyers.len(), s.revenue))
+            .collect();
+        let active_customers = customers.iter().filter(|c| c.status == "active").count() as i64;
+        let new_customers: Vec<&CustomerRow> = customers.iter().filter(|c| filters.contains(c.created_at)).collect();
+
+        // New customers per month, over the last year without a period
+        let trend_filters = filters.or_recent(365);
+        let timezone = DisplayTimezone::current();
+        let mut acquisitions: BTreeMap<String, f64> = BTreeMap::new();
+        for customer in customers.iter().filter(|c| trend_filters.contains(c.created_at)) {
+            *acquisitions
+                .entry(timezone.to_local(customer.created_at).format("%Y-%m").to_string())
+                .or_default() += 1.0;
+        }
+
+        let mut sections = vec![
             ReportSection {
                 title: "Customer Segmentation".to_string(),
                 section_type: SectionType::Analysis,
                 data: ReportData::Chart(create_pie_chart(
-                    vec![
-                        "Enterprise".to_string(),
-                        "Small Business".to_string(),
-                        "Individual".to_string(),
-                        "Government".to_string(),
-                    ],
-                    vec![45.0, 30.0, 20.0, 5.0],
+                    segments.iter().filter(|(_, s)| s.customers > 0).map(|(name, _)| title_case(name)).collect(),
+                    segments.values().filter(|s| s.customers > 0).map(|s| s.customers as f64).collect(),
                 )),
             },
             ReportSection {
                 title: "Customer Value Analysis".to_string(),
                 section_type: SectionType::Detail,
                 data: ReportData::Table(TableData {
-                    headers: vec![
-                        "Segment".to_string(),
-                        "Customer Count".to_string(),
-                        "Total Revenue".to_string(),
-                        "Avg Revenue/Customer".to_string(),
-                        "Growth Rate".to_string(),
-                        "Churn Rate".to_string(),
-                        "CLV".to_string(),
-                    ],
-                    rows: vec![
-                        vec![
-                            "Enterprise".to_string(),
-                            "125".to_string(),
-                            "₩1,850,000,000".to_string(),
-                            "₩14,800,000".to_string(),
-                            "+12.5%".to_string(),
-                            "2.1%".to_string(),
-                            "₩45,600,000".to_string(),
-                        ],
-                        vec![
-                            "Small Business".to_string(),
-                            "340".to_string(),
-                            "₩980,000,000".to_string(),
-                            "₩2,882,353".to_string(),
-                            "+8.7%".to_string(),
-                            "5.8%".to_string(),
-                            "₩12,450,000".to_string(),
-                        ],
-                        vec![
-                            "Individual".to_string(),
-                            "1,250".to_string(),
-                            "₩380,000,000".to_string(),
-                            "₩304,000".to_string(),
-                            "+15.2%".to_string(),
-                            "12.4%".to_string(),
-                            "₩1,850,000".to_string(),
-                        ],
-                        vec![
-                            "Government".to_string(),
-                            "18".to_string(),
-                            "₩650,000,000".to_string(),
-                            "₩36,111,111".to_string(),
-                            "+3.2%".to_string(),
-                            "0.5%".to_string(),
-                            "₩95,200,000".to_string(),
-                        ],
-                    ],
-                    totals: Some(vec![
-                        "Total".to_string(),
-                        "1,733".to_string(),
-                        "₩3,860,000,000".to_string(),
-                        "₩2,227,866".to_string(),
-                        "+9.9%".to_string(),
-                        "5.2%".to_string(),
-                        "₩15,725,000".to_string(),
+                    headers: header_row(&[
+                        "Segment", "Customers", "Active", "New", "Buying Customers", "Won Revenue", "Avg Revenue/Buyer",
+                        "% of Revenue",
                     ]),
+                    rows: value_rows,
+                    totals: Some(value_row(
+                        "Total".to_string(),
+                        customers.len() as i64,
+                        active_customers,
+                        new_customers.len() as i64,
+                        total_buyers,
+                        total_revenue,
+                    )),
                 }),
             },
             ReportSection {
                 title: "Customer Acquisition Trends".to_string(),
                 section_type: SectionType::Chart,
                 data: ReportData::Chart(create_line_chart(
-                    vec![
-                        "Jan".to_string(),
-                        "Feb".to_string(),
-                        "Mar".to_string(),
-                        "Apr".to_string(),
-                        "May".to_string(),
-                        "Jun".to_string(),
-                    ],
-                    vec![
-                        Dataset {
-                            label: "New Customers".to_string(),
-                            data: vec![45.0, 52.0, 48.0, 61.0, 58.0, 67.0],
-                            color: Some("#10B981".to_string()),
-                        },
-                        Dataset {
-                            label: "Churn".to_string(),
-                            data: vec![12.0, 8.0, 15.0, 9.0, 11.0, 7.0],
-                            color: Some("#EF4444".to_string()),
-                        },
-                    ],
+                    acquisitions.keys().cloned().collect(),
+                    vec![Dataset {
+                        label: "New Customers".to_string(),
+                        data: acquisitions.values().copied().collect(),
+                        color: Some("#10B981".to_string()),
+                    }],
                 )),
             },
         ];
+        if nps.overall.responses > 0 {
+            let rows = nps.by_segment.iter().map(|(segment, score)| nps_row(segment, score)).collect();
+            sections.push(ReportSection {
+                title: format!("Net Promoter Score by Segment, {} to {}", nps.start, nps.end),
+                section_type: SectionType::Analysis,
+                data: ReportData::Table(TableData {
+                    headers: ["Segment", "Responses", "Promoters", "Passives", "Detractors", "NPS"]
+                        .iter()
+                        .map(|h| h.to_string())
+                        .collect(),
+                    rows,
+                    totals: Some(nps_row("Total", &nps.overall)),
+                }),
+            });
+            sections.push(ReportSection {
+                title: "Net Promoter Score Trend".to_string(),
+                section_type: SectionType::Chart,
+                data: ReportData::Chart(create_line_chart(
+                    nps.by_month.keys().cloned().collect(),
+                    vec![Dataset {
+                        label: "NPS".to_string(),
+                        data: nps.by_month.values().map(|score| score.score().unwrap_or(0.0)).collect(),
+                        color: Some("#3B82F6".to_string()),
+                    }],
+                )),
+            });
+        }
 
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("total_customers".to_string(), MetricValue::Count(1733));
-        key_metrics.insert("customer_growth_rate".to_string(), MetricValue::Percentage(9.9));
-        key_metrics.insert("average_clv".to_string(), MetricValue::Currency(15725000));
-        key_metrics.insert("customer_satisfaction".to_string(), MetricValue::Number(4.3));
-        key_metrics.insert("net_promoter_score".to_string(), MetricValue::Number(67.0));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "Enterprise segment drives 47.9% of total revenue with highest CLV".to_string(),
-                "Individual segment shows strongest growth at 15.2% but highest churn".to_string(),
-                "Government segment has lowest churn rate at 0.5%".to_string(),
-                "Customer acquisition accelerated in Q2 with 67 new customers in June".to_string(),
-                "Net Promoter Score of 67 indicates strong customer loyalty".to_string(),
-            ],
-            recommendations: vec![
-                "Focus retention efforts on Individual segment to reduce 12.4% churn".to_string(),
-                "Develop enterprise upselling programs to maximize CLV".to_string(),
-                "Implement referral programs leveraging high NPS".to_string(),
-                "Create targeted small business growth initiatives".to_string(),
-            ],
-        };
+        key_metrics.insert("total_customers".to_string(), MetricValue::Count(customers.len() as i64));
+        key_metrics.insert("active_customers".to_string(), MetricValue::Count(active_customers));
+        key_metrics.insert("new_customers".to_string(), MetricValue::Count(new_customers.len() as i64));
+        key_metrics.insert("won_revenue".to_string(), MetricValue::Text(format_amount(total_revenue)));
+        key_metrics.insert(
+            "average_revenue_per_buyer".to_string(),
+            MetricValue::Text(format_amount(per_buyer(total_revenue, total_buyers))),
+        );
+        key_metrics.insert("survey_responses".to_string(), MetricValue::Count(nps.overall.responses as i64));
+        if let Some(score) = nps.overall.score() {
+            key_metrics.insert("net_promoter_score".to_string(), MetricValue::Number(score));
+        }
 
-        let metadata = ReportMetadata {
-            total_records: 1733,
-            processing_time_ms: 280,
-            filters_applied: vec!["active_customers".to_string(), "revenue_data".to_string()],
-            data_sources: vec!["customers".to_string(), "deals".to_string(), "customer_surveys".to_string()],
-        };
+        let mut insights = vec![format!(
+            "{} customers, {} active; {} new in the period",
+            customers.len(),
+            active_customers,
+            new_customers.len()
+        )];
+        if let Some((name, segment)) = segments.iter().max_by_key(|(_, s)| s.revenue).filter(|(_, s)| s.revenue > 0) {
+            insights.push(format!(
+                "{} customers bring {} of won revenue ({})",
+                title_case(name),
+                format_percentage(share(segment.revenue, total_revenue)),
+                format_amount(segment.revenue)
+            ));
+        }
+        let mut recommendations = Vec::new();
+        let inactive = customers.len() as i64 - active_customers;
+        if inactive > 0 {
+            recommendations.push(format!("Review {} inactive or suspended customer(s) for win-back", inactive));
+        }
+        match nps.overall.score() {
+            Some(score) => {
+                insights.push(format!(
+                    "Net Promoter Score of {:+.0} from {} survey responses",
+                    score, nps.overall.responses
+                ));
+                if score >= 30.0 {
+                    recommendations.push("Implement referral programs leveraging high NPS".to_string());
+                } else {
+                    recommendations.push("Follow up with detractors to find and fix their main complaints".to_string());
+                }
+            }
+            None => recommendations.push("Record customer surveys with `crm survey record` to track NPS".to_string()),
+        }
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
             data: ReportData::Mixed(sections),
-            summary: Some(summary),
-            metadata,
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: customers.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["customers".to_string(), "deals".to_string(), "customer_surveys".to_string()],
+            },
         })
     }
 
     fn generate_sales_pipeline_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let filters = ReportFilters::from_config(&config)?;
+        let mut conn = get_report_connection()?;
+        let deals = ReportDataSource::deals(&mut conn, &filters)?;
+        let timezone = DisplayTimezone::current();
+        let today = timezone.today();
+
+        let weighted = |deal: &DealRow| i64::from(deal.value) * i64::from(deal.probability.unwrap_or(0)) / 100;
+        let open: Vec<&DealRow> = deals.iter().filter(|deal| deal.is_open()).collect();
+        let stage_row = |label: String, deals: &[&DealRow]| {
+            let total: i64 = deals.iter().map(|deal| i64::from(deal.value)).sum();
+            let probability = if deals.is_empty() {
+                0.0
+            } else {
+                deals.iter().map(|deal| f64::from(deal.probability.unwrap_or(0))).sum::<f64>() / deals.len() as f64
+            };
+            vec![
+                label,
+                deals.len().to_string(),
+                format_amount(total),
+                format_amount(if deals.is_empty() { 0 } else { total / deals.len() as i64 }),
+                format_percentage(probability),
+                format_amount(deals.iter().map(|deal| weighted(deal)).sum::<i64>()),
+            ]
+        };
+
+        let by_stage: Vec<(&str, Vec<&DealRow>)> = DEAL_STAGES
+            .iter()
+            .map(|stage| (*stage, open.iter().copied().filter(|deal| deal.stage == *stage).collect::<Vec<_>>()))
+            .filter(|(_, deals)| !deals.is_empty())
+            .collect();
+        let average_age = |deals: &[&DealRow]| {
+            deals
+                .iter()
+                .map(|deal| (today - timezone.to_local(deal.created_at).date()).num_days() as f64)
+                .sum::<f64>()
+                / deals.len().max(1) as f64
+        };
+
+        // Win rate of closed deals by the month they were opened
+        let mut by_month: BTreeMap<String, (i64, i64)> = BTreeMap::new();
+        for deal in deals.iter().filter(|deal| !deal.is_open()) {
+            let entry = by_month
+                .entry(timezone.to_local(deal.created_at).format("%Y-%m").to_string())
+                .or_default();
+            if deal.stage == "closed_won" {
+                entry.0 += 1;
+            }
+            entry.1 += 1;
+        }
+        let won: Vec<&DealRow> = deals.iter().filter(|deal| deal.stage == "closed_won").collect();
+        let closed = deals.len() - open.len();
+        let win_rate = share(won.len() as i64, closed as i64);
+        let cycle_days: Vec<i64> = won
+            .iter()
+            .filter_map(|deal| deal.close_date.map(|close| (close - timezone.to_local(deal.created_at).date()).num_days()))
+            .collect();
+        let pipeline_value: i64 = open.iter().map(|deal| i64::from(deal.value)).sum();
+        let weighted_value: i64 = open.iter().map(|deal| weighted(deal)).sum();
+
         let sections = vec![
             ReportSection {
                 title: "Pipeline by Stage".to_string(),
                 section_type: SectionType::Detail,
                 data: ReportData::Table(TableData {
-                    headers: vec![
-                        "Stage".to_string(),
-                        "Deal Count".to_string(),
-                        "Total Value".to_string(),
-                        "Avg Deal Size".to_string(),
-                        "Probability".to_string(),
-                        "Weighted Value".to_string(),
-                        "Avg Age (Days)".to_string(),
-                    ],
-                    rows: vec![
-                        vec![
-                            "Qualification".to_string(),
-                            "45".to_string(),
-                            "₩1,250,000,000".to_string(),
-                            "₩27,777,778".to_string(),
-                            "20%".to_string(),
-                            "₩250,000,000".to_string(),
-                            "12".to_string(),
-                        ],
-                        vec![
-                            "Needs Analysis".to_string(),
-                            "32".to_string(),
-                            "₩980,000,000".to_string(),
-                            "₩30,625,000".to_string(),
-                            "40%".to_string(),
-                            "₩392,000,000".to_string(),
-                            "28".to_string(),
-                        ],
-                        vec![
-                            "Proposal".to_string(),
-                            "28".to_string(),
-                            "₩850,000,000".to_string(),
-                            "₩30,357,143".to_string(),
-                            "60%".to_string(),
-                            "₩510,000,000".to_string(),
-                            "45".to_string(),
-                        ],
-                        vec![
-                            "Negotiation".to_string(),
-                            "18".to_string(),
-                            "₩720,000,000".to_string(),
-                            "₩40,000,000".to_string(),
-                            "80%".to_string(),
-                            "₩576,000,000".to_string(),
-                            "67".to_string(),
-                        ],
-                    ],
-                    totals: Some(vec![
-                        "Total Pipeline".to_string(),
-                        "123".to_string(),
-                        "₩3,800,000,000".to_string(),
-                        "₩30,894,309".to_string(),
-                        "45.5%".to_string(),
-                        "₩1,728,000,000".to_string(),
-                        "38".to_string(),
+                    headers: header_row(&[
+                        "Stage", "Deal Count", "Total Value", "Avg Deal Size", "Avg Probability", "Weighted Value",
                     ]),
+                    rows: by_stage.iter().map(|(stage, deals)| stage_row(title_case(stage), deals)).collect(),
+                    totals: Some(stage_row("Total Pipeline".to_string(), &open)),
                 }),
             },
             ReportSection {
-                title: "Pipeline Velocity".to_string(),
+                title: "Average Age of Open Deals (days)".to_string(),
                 section_type: SectionType::Chart,
                 data: ReportData::Chart(create_bar_chart(
-                    vec![
-                        "Qualification".to_string(),
-                        "Needs Analysis".to_string(),
-                        "Proposal".to_string(),
-                        "Negotiation".to_string(),
-                    ],
-                    vec![12.0, 28.0, 45.0, 67.0],
-                    "Average Days in Stage",
+                    by_stage.iter().map(|(stage, _)| title_case(stage)).collect(),
+                    by_stage.iter().map(|(_, deals)| average_age(deals)).collect(),
+                    "Days Open",
                 )),
             },
             ReportSection {
                 title: "Win Rate Analysis".to_string(),
-                section_type: SectionType::Chart,
+                section_type: SectionType::Analysis,
                 data: ReportData::Chart(create_line_chart(
-                    vec![
-                        "Q1 2023".to_string(),
-                        "Q2 2023".to_string(),
-                        "Q3 2023".to_string(),
-                        "Q4 2023".to_string(),
-                        "Q1 2024".to_string(),
-                        "Q2 2024".to_string(),
-                    ],
-                    vec![
-                        Dataset {
-                            label: "Win Rate %".to_string(),
-                            data: vec![23.5, 26.8, 29.1, 31.2, 28.7, 33.4],
-                            color: Some("#10B981".to_string()),
-                        },
-                        Dataset {
-                            label: "Average Deal Size (M)".to_string(),
-                            data: vec![25.2, 27.8, 29.5, 31.8, 28.9, 30.9],
-                            color: Some("#3B82F6".to_string()),
-                        },
-                    ],
+                    by_month.keys().cloned().collect(),
+                    vec![Dataset {
+                        label: "Win Rate %".to_string(),
+                        data: by_month.values().map(|(won, closed)| share(*won, *closed)).collect(),
+                        color: Some("#10B981".to_string()),
+                    }],
                 )),
             },
         ];
 
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("total_pipeline_value".to_string(), MetricValue::Currency(3800000000));
-        key_metrics.insert("weighted_pipeline".to_string(), MetricValue::Currency(1728000000));
-        key_metrics.insert("average_deal_size".to_string(), MetricValue::Currency(30894309));
-        key_metrics.insert("win_rate".to_string(), MetricValue::Percentage(33.4));
-        key_metrics.insert("sales_cycle_length".to_string(), MetricValue::Number(152.0));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "Total pipeline value of ₩3.8 billion across 123 active deals".to_string(),
-                "Weighted pipeline of ₩1.73 billion considering stage probabilities".to_string(),
-                "Win rate improved to 33.4% in Q2 2024, up from 28.7% in Q1".to_string(),
-                "Negotiation stage deals have highest average value at ₩40M".to_string(),
-                "Average sales cycle length of 152 days remains stable".to_string(),
-            ],
-            recommendations: vec![
-                "Accelerate qualification process to reduce early-stage deal age".to_string(),
-                "Focus on proposal stage optimization to improve conversion".to_string(),
-                "Implement sales coaching for negotiation stage deals".to_string(),
-                "Develop competitive battle cards for proposal stage".to_string(),
-            ],
-        };
+        key_metrics.insert("open_deals".to_string(), MetricValue::Count(open.len() as i64));
+        key_metrics.insert("total_pipeline_value".to_string(), MetricValue::Text(format_amount(pipeline_value)));
+        key_metrics.insert("weighted_pipeline".to_string(), MetricValue::Text(format_amount(weighted_value)));
+        key_metrics.insert(
+            "average_deal_size".to_string(),
+            MetricValue::Text(format_amount(if open.is_empty() { 0 } else { pipeline_value / open.len() as i64 })),
+        );
+        key_metrics.insert("win_rate".to_string(), MetricValue::Percentage(win_rate));
+        if !cycle_days.is_empty() {
+            key_metrics.insert(
+                "sales_cycle_length".to_string(),
+                MetricValue::Number(cycle_days.iter().sum::<i64>() as f64 / cycle_days.len() as f64),
+            );
+        }
 
-        let metadata = ReportMetadata {
-            total_records: 123,
-            processing_time_ms: 195,
-            filters_applied: vec!["active_deals".to_string(), "current_quarter".to_string()],
-            data_sources: vec!["deals".to_string(), "leads".to_string(), "sales_activities".to_string()],
-        };
+        let mut insights = vec![format!(
+            "{} open deals worth {}, {} weighted by probability",
+            open.len(),
+            format_amount(pipeline_value),
+            format_amount(weighted_value)
+        )];
+        if let Some((stage, deals)) = by_stage
+            .iter()
+            .max_by_key(|(_, deals)| deals.iter().map(|deal| i64::from(deal.value)).sum::<i64>())
+        {
+            insights.push(format!("{} holds the most pipeline value across {} deal(s)", title_case(stage), deals.len()));
+        }
+        if closed > 0 {
+            insights.push(format!("{} of {} closed deals were won", format_percentage(win_rate), closed));
+        }
+        let mut recommendations = Vec::new();
+        let overdue = open.iter().filter(|deal| deal.close_date.is_some_and(|close| close < today)).count();
+        if overdue > 0 {
+            recommendations.push(format!("Update {} open deal(s) whose expected close date has passed", overdue));
+        }
+        if closed >= 5 && win_rate < 25.0 {
+            recommendations.push("Win rate is below 25%; tighten qualification before the proposal stage".to_string());
+        }
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
             data: ReportData::Mixed(sections),
-            summary: Some(summary),
-            metadata,
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: deals.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["deals".to_string(), "leads".to_string(), "customers".to_string()],
+            },
         })
     }
 
     fn generate_lead_conversion_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
-        let table_data = TableData {
-            headers: vec![
-                "Lead Source".to_string(),
-                "Leads Generated".to_string(),
-                "Qualified Leads".to_string(),
-                "Opportunities".to_string(),
-                "Closed Won".to_string(),
-                "Conversion Rate".to_string(),
-                "Avg Time to Close".to_string(),
-            ],
-            rows: vec![
-                vec![
-                    "Website".to_string(),
-                    "245".to_string(),
-                    "189".to_string(),
-                    "89".to_string(),
-                    "28".to_string(),
-                    "11.4%".to_string(),
-                    "67 days".to_string(),
-                ],
-                vec![
-                    "Referral".to_string(),
-                    "156".to_string(),
-                    "142".to_string(),
-                    "98".to_string(),
-                    "45".to_string(),
-                    "28.8%".to_string(),
-                    "45 days".to_string(),
-                ],
-                vec![
-                    "Trade Show".to_string(),
-                    "89".to_string(),
-                    "72".to_string(),
-                    "45".to_string(),
-                    "18".to_string(),
-                    "20.2%".to_string(),
-                    "89 days".to_string(),
-                ],
-                vec![
-                    "Cold Outreach".to_string(),
-                    "324".to_string(),
-                    "145".to_string(),
-                    "67".to_string(),
-                    "15".to_string(),
-                    "4.6%".to_string(),
-                    "112 days".to_string(),
-                ],
-                vec![
-                    "Social Media".to_string(),
-                    "189".to_string(),
-                    "98".to_string(),
-                    "34".to_string(),
-                    "12".to_string(),
-                    "6.3%".to_string(),
-                    "78 days".to_string(),
-                ],
-                vec![
-                    "Partner Channel".to_string(),
-                    "98".to_string(),
-                    "87".to_string(),
-                    "56".to_string(),
-                    "22".to_string(),
-                    "22.4%".to_string(),
-                    "56 days".to_string(),
-                ],
-            ],
-            totals: Some(vec![
-                "Total".to_string(),
-                "1,101".to_string(),
-                "733".to_string(),
-                "389".to_string(),
-                "140".to_string(),
-                "12.7%".to_string(),
-                "74 days".to_string(),
-            ]),
+        let start_time = std::time::Instant::now();
+        let filters = ReportFilters::from_config(&config)?;
+        let mut conn = get_report_connection()?;
+        let leads = ReportDataSource::leads(&mut conn, &filters)?;
+
+        let count = |leads: &[&LeadRow], statuses: &[&str]| {
+            leads.iter().filter(|lead| statuses.contains(&lead.status.as_str())).count() as i64
+        };
+        let source_row = |label: String, leads: &[&LeadRow]| {
+            vec![
+                label,
+                leads.len().to_string(),
+                count(leads, QUALIFIED_STATUSES).to_string(),
+                count(leads, OPPORTUNITY_STATUSES).to_string(),
+                count(leads, &["closed_won"]).to_string(),
+                count(leads, &["closed_lost"]).to_string(),
+                format_percentage(share(count(leads, &["closed_won"]), leads.len() as i64)),
+                format_amount(leads.iter().map(|lead| i64::from(lead.estimated_value.unwrap_or(0))).sum::<i64>()),
+            ]
         };
 
+        let mut by_source: BTreeMap<&str, Vec<&LeadRow>> = BTreeMap::new();
+        for lead in &leads {
+            by_source.entry(lead.source.as_str()).or_default().push(lead);
+        }
+        let all: Vec<&LeadRow> = leads.iter().collect();
+        let total = leads.len() as i64;
+        let funnel = [
+            ("Leads", total),
+            ("Qualified", count(&all, QUALIFIED_STATUSES)),
+            ("Opportunities", count(&all, OPPORTUNITY_STATUSES)),
+            ("Closed Won", count(&all, &["closed_won"])),
+        ];
+        let open_value: i64 = leads
+            .iter()
+            .filter(|lead| lead.status != "closed_won" && lead.status != "closed_lost")
+            .map(|lead| i64::from(lead.estimated_value.unwrap_or(0)))
+            .sum();
+
+        let sections = vec![
+            ReportSection {
+                title: "Conversion by Lead Source".to_string(),
+                section_type: SectionType::Detail,
+                data: ReportData::Table(TableData {
+                    headers: header_row(&[
+                        "Lead Source", "Leads", "Qualified", "Opportunities", "Closed Won", "Closed Lost", "Conversion Rate",
+                        "Estimated Value",
+                    ]),
+                    rows: by_source.iter().map(|(source, leads)| source_row(source.to_string(), leads)).collect(),
+                    totals: Some(source_row("Total".to_string(), &all)),
+                }),
+            },
+            ReportSection {
+                title: "Lead Funnel".to_string(),
+                section_type: SectionType::Chart,
+                data: ReportData::Chart(create_bar_chart(
+                    funnel.iter().map(|(label, _)| label.to_string()).collect(),
+                    funnel.iter().map(|(_, count)| *count as f64).collect(),
+                    "Leads",
+                )),
+            },
+        ];
+
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("total_leads".to_string(), MetricValue::Count(1101));
-        key_metrics.insert("qualification_rate".to_string(), MetricValue::Percentage(66.6));
-        key_metrics.insert("opportunity_rate".to_string(), MetricValue::Percentage(35.3));
-        key_metrics.insert("overall_conversion_rate".to_string(), MetricValue::Percentage(12.7));
-        key_metrics.insert("average_time_to_close".to_string(), MetricValue::Number(74.0));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "Referral leads show highest conversion rate at 28.8%".to_string(),
-                "Website generates most leads (245) but lower conversion (11.4%)".to_string(),
-                "Partner channel demonstrates strong quality with 22.4% conversion".to_string(),
-                "Cold outreach has lowest ROI with 4.6% conversion rate".to_string(),
-                "Referral leads also close fastest at 45 days average".to_string(),
-            ],
-            recommendations: vec![
-                "Increase investment in referral program development".to_string(),
-                "Optimize website lead qualification process".to_string(),
-                "Expand partner channel relationships".to_string(),
-                "Review and improve cold outreach messaging".to_string(),
-                "Implement lead scoring to prioritize high-quality leads".to_string(),
-            ],
-        };
+        key_metrics.insert("total_leads".to_string(), MetricValue::Count(total));
+        key_metrics.insert("qualification_rate".to_string(), MetricValue::Percentage(share(funnel[1].1, total)));
+        key_metrics.insert("opportunity_rate".to_string(), MetricValue::Percentage(share(funnel[2].1, total)));
+        key_metrics.insert("overall_conversion_rate".to_string(), MetricValue::Percentage(share(funnel[3].1, total)));
+        key_metrics.insert("open_lead_value".to_string(), MetricValue::Text(format_amount(open_value)));
 
-        let metadata = ReportMetadata {
-            total_records: 1101,
-            processing_time_ms: 165,
-            filters_applied: vec!["lead_sources".to_string(), "conversion_funnel".to_string()],
-            data_sources: vec!["leads".to_string(), "deals".to_string(), "lead_sources".to_string()],
-        };
+        let mut insights = vec![format!(
+            "{} leads: {} qualified, {} won ({})",
+            total,
+            funnel[1].1,
+            funnel[3].1,
+            format_percentage(share(funnel[3].1, total))
+        )];
+        let conversion = |leads: &[&LeadRow]| share(count(leads, &["closed_won"]), leads.len() as i64);
+        if let Some((source, leads)) = by_source
+            .iter()
+            .filter(|(_, leads)| count(leads, &["closed_won"]) > 0)
+            .max_by(|a, b| conversion(a.1).total_cmp(&conversion(b.1)))
+        {
+            insights.push(format!("{} converts best at {}", source, format_percentage(conversion(leads))));
+        }
+        let mut recommendations = Vec::new();
+        let untouched = count(&all, &["new"]);
+        if untouched > 0 {
+            recommendations.push(format!("Contact {} lead(s) still in status new", untouched));
+        }
+        if let Some((source, leads)) = by_source.iter().max_by_key(|(_, leads)| leads.len()) {
+            if leads.len() >= 10 && count(leads, &["closed_won"]) == 0 {
+                recommendations.push(format!(
+                    "{} brings the most leads but has converted none; review its targeting",
+                    source
+                ));
+            }
+        }
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
-            data: ReportData::Table(table_data),
-            summary: Some(summary),
-            metadata,
+            data: ReportData::Mixed(sections),
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: total,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["leads".to_string(), "customers".to_string()],
+            },
         })
     }
 
     fn generate_campaign_performance_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let filters = ReportFilters::from_config(&config)?;
+        let mut conn = get_report_connection()?;
+        let campaign_ids: BTreeSet<i32> = ReportDataSource::campaigns(&mut conn, &filters)?
+            .iter()
+            .map(|campaign| campaign.id)
+            .collect();
+        let performance: Vec<CampaignPerformance> = CampaignService::get_campaign_performance(&mut conn)?
+            .into_iter()
+            .filter(|campaign| campaign_ids.contains(&campaign.campaign_id))
+            .collect();
+        let names: Vec<String> = performance.iter().map(|campaign| campaign.campaign_name.clone()).collect();
+        let revenue = ReportDataSource::campaign_revenue(&mut conn, &names)?;
+
+        let revenue_of = |campaign: &CampaignPerformance| revenue.get(&campaign.campaign_name).copied().unwrap_or(0);
+        let roi = |revenue: i64, spend: i64| (spend > 0).then(|| (revenue - spend) as f64 * 100.0 / spend as f64);
+        let rows = performance
+            .iter()
+            .map(|campaign| {
+                vec![
+                    campaign.campaign_name.clone(),
+                    title_case(&campaign.campaign_type),
+                    title_case(&campaign.status),
+                    format_amount(i64::from(campaign.budget)),
+                    format_amount(i64::from(campaign.actual_cost)),
+                    campaign.total_leads.to_string(),
+                    format_amount(campaign.cost_per_lead as i64),
+                    format_percentage(campaign.conversion_rate),
+                    format_amount(revenue_of(campaign)),
+                    roi(revenue_of(campaign), i64::from(campaign.actual_cost))
+                        .map(format_percentage)
+                        .unwrap_or_else(|| "-".to_string()),
+                ]
+            })
+            .collect();
+
+        let budget: i64 = performance.iter().map(|c| i64::from(c.budget)).sum();
+        let spend: i64 = performance.iter().map(|c| i64::from(c.actual_cost)).sum();
+        let leads: i64 = performance.iter().map(|c| c.total_leads).sum();
+        let qualified: i64 = performance.iter().map(|c| c.qualified_leads).sum();
+        let total_revenue: i64 = performance.iter().map(revenue_of).sum();
+        let cost_per_lead = if leads == 0 { 0 } else { spend / leads };
+
         let sections = vec![
             ReportSection {
                 title: "Campaign Results".to_string(),
                 section_type: SectionType::Detail,
                 data: ReportData::Table(TableData {
-                    headers: vec![
-                        "Campaign".to_string(),
-                        "Type".to_string(),
-                        "Budget".to_string(),
-                        "Spend".to_string(),
-                        "Leads".to_string(),
-                        "Cost/Lead".to_string(),
-                        "Conversion".to_string(),
-                        "ROI".to_string(),
-                    ],
-                    rows: vec![
-                        vec![
-                            "Q2 Product Launch".to_string(),
-                            "Integrated".to_string(),
-                            "₩50,000,000".to_string(),
-                            "₩48,500,000".to_string(),
-                            "189".to_string(),
-                            "₩256,614".to_string(),
-                            "24.3%".to_string(),
-                            "340%".to_string(),
-                        ],
-                        vec![
-                            "Summer Email Series".to_string(),
-                            "Email".to_string(),
-                            "₩8,000,000".to_string(),
-                            "₩7,200,000".to_string(),
-                            "145".to_string(),
-                            "₩49,655".to_string(),
-                            "18.6%".to_string(),
-                            "285%".to_string(),
-                        ],
-                        vec![
-                            "LinkedIn Ads".to_string(),
-                            "Social".to_string(),
-                            "₩15,000,000".to_string(),
-                            "₩14,800,000".to_string(),
-                            "78".to_string(),
-                            "₩189,744".to_string(),
-                            "12.8%".to_string(),
-                            "156%".to_string(),
-                        ],
-                        vec![
-                            "Trade Show Booth".to_string(),
-                            "Event".to_string(),
-                            "₩25,000,000".to_string(),
-                            "₩23,600,000".to_string(),
-                            "67".to_string(),
-                            "₩352,239".to_string(),
-                            "29.9%".to_string(),
-                            "420%".to_string(),
-                        ],
-                        vec![
-                            "Webinar Series".to_string(),
-                            "Digital".to_string(),
-                            "₩12,000,000".to_string(),
-                            "₩11,500,000".to_string(),
-                            "156".to_string(),
-                            "₩73,718".to_string(),
-                            "22.4%".to_string(),
-                            "310%".to_string(),
-                        ],
-                    ],
+                    headers: header_row(&[
+                        "Campaign", "Type", "Status", "Budget", "Spend", "Leads", "Cost/Lead", "Conversion", "Won Revenue",
+                        "ROI",
+                    ]),
+                    rows,
                     totals: Some(vec![
                         "Total".to_string(),
-                        "".to_string(),
-                        "₩110,000,000".to_string(),
-                        "₩105,600,000".to_string(),
-                        "635".to_string(),
-                        "₩166,299".to_string(),
-                        "21.6%".to_string(),
-                        "322%".to_string(),
+                        String::new(),
+                        String::new(),
+                        format_amount(budget),
+                        format_amount(spend),
+                        leads.to_string(),
+                        format_amount(cost_per_lead),
+                        format_percentage(share(qualified, leads)),
+                        format_amount(total_revenue),
+                        roi(total_revenue, spend).map(format_percentage).unwrap_or_else(|| "-".to_string()),
                     ]),
                 }),
             },
@@ -588,404 +672,541 @@ impl CRMReportsGenerator {
                 title: "Campaign ROI Comparison".to_string(),
                 section_type: SectionType::Chart,
                 data: ReportData::Chart(create_bar_chart(
-                    vec![
-                        "Product Launch".to_string(),
-                        "Email Series".to_string(),
-                        "LinkedIn Ads".to_string(),
-                        "Trade Show".to_string(),
-                        "Webinar Series".to_string(),
-                    ],
-                    vec![340.0, 285.0, 156.0, 420.0, 310.0],
+                    performance.iter().map(|c| c.campaign_name.clone()).collect(),
+                    performance
+                        .iter()
+                        .map(|c| roi(revenue_of(c), i64::from(c.actual_cost)).unwrap_or(0.0))
+                        .collect(),
                     "ROI %",
                 )),
             },
         ];
 
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("total_campaign_spend".to_string(), MetricValue::Currency(105600000));
-        key_metrics.insert("total_leads_generated".to_string(), MetricValue::Count(635));
-        key_metrics.insert("average_cost_per_lead".to_string(), MetricValue::Currency(166299));
-        key_metrics.insert("average_conversion_rate".to_string(), MetricValue::Percentage(21.6));
-        key_metrics.insert("average_campaign_roi".to_string(), MetricValue::Percentage(322.0));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "Trade Show Booth achieved highest ROI at 420% despite high cost per lead".to_string(),
-                "Email campaigns deliver best cost efficiency at ₩49,655 per lead".to_string(),
-                "Q2 Product Launch generated most leads (189) with strong 24.3% conversion".to_string(),
-                "All campaigns exceeded 150% ROI threshold indicating positive performance".to_string(),
-                "Digital channels (Email, Webinar) show consistent performance".to_string(),
-            ],
-            recommendations: vec![
-                "Increase budget allocation to high-ROI Trade Show activities".to_string(),
-                "Scale email marketing programs for cost-effective lead generation".to_string(),
-                "Optimize LinkedIn Ads targeting to improve conversion rates".to_string(),
-                "Replicate Q2 Product Launch success formula for future launches".to_string(),
-                "Develop integrated campaign approach combining best-performing channels".to_string(),
-            ],
-        };
+        key_metrics.insert("campaigns".to_string(), MetricValue::Count(performance.len() as i64));
+        key_metrics.insert("total_campaign_spend".to_string(), MetricValue::Text(format_amount(spend)));
+        key_metrics.insert("total_leads_generated".to_string(), MetricValue::Count(leads));
+        key_metrics.insert("average_cost_per_lead".to_string(), MetricValue::Text(format_amount(cost_per_lead)));
+        key_metrics.insert("average_conversion_rate".to_string(), MetricValue::Percentage(share(qualified, leads)));
+        key_metrics.insert("campaign_revenue".to_string(), MetricValue::Text(format_amount(total_revenue)));
+        if let Some(roi) = roi(total_revenue, spend) {
+            key_metrics.insert("overall_campaign_roi".to_string(), MetricValue::Percentage(roi));
+        }
+
+        let mut insights = vec![format!(
+            "{} campaign(s) spent {} of {} budgeted and brought {} leads",
+            performance.len(),
+            format_amount(spend),
+            format_amount(budget),
+            leads
+        )];
+        if let Some(best) = performance
+            .iter()
+            .filter_map(|c| roi(revenue_of(c), i64::from(c.actual_cost)).map(|roi| (c, roi)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+        {
+            insights.push(format!("{} has the best ROI at {}", best.0.campaign_name, format_percentage(best.1)));
+        }
+        let mut recommendations = Vec::new();
+        let over_budget: Vec<&str> = performance
+            .iter()
+            .filter(|c| c.budget > 0 && c.actual_cost > c.budget)
+            .map(|c| c.campaign_name.as_str())
+            .collect();
+        if !over_budget.is_empty() {
+            recommendations.push(format!("Review spending of over-budget campaign(s): {}", over_budget.join(", ")));
+        }
+        let no_leads = performance.iter().filter(|c| c.actual_cost > 0 && c.total_leads == 0).count();
+        if no_leads > 0 {
+            recommendations.push(format!("{} campaign(s) have spent without bringing a lead", no_leads));
+        }
 
-        let metadata = ReportMetadata {
-            total_records: 5,
-            processing_time_ms: 125,
-            filters_applied: vec!["completed_campaigns".to_string(), "q2_2024".to_string()],
-            data_sources: vec!["campaigns".to_string(), "leads".to_string(), "campaign_costs".to_string()],
+        Ok(ReportResult {
+            config: config.clone(),
+            generated_at: Utc::now().naive_utc(),
+            data: ReportData::Mixed(sections),
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: performance.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["campaigns".to_string(), "leads".to_string(), "deals".to_string()],
+            },
+        })
+    }
+
+    fn generate_marketing_attribution_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let (start, end) = match &config.date_range {
+            Some(range) => (range.start_date, range.end_date),
+            None => parse_period("this-quarter")?,
         };
+        let mut conn = get_report_connection()?;
+        let channels = AttributionService::by_channel(&mut conn, start, end)?;
+        let deals = AttributionService::deals(&mut conn, start, end)?;
+
+        let won_revenue: i64 = deals.iter().map(|deal| deal.revenue).sum();
+        let channel_rows = channels
+            .iter()
+            .map(|channel| {
+                vec![
+                    channel.channel.clone(),
+                    channel.spent.map(format_amount).unwrap_or_default(),
+                    channel.first_touch_deals.to_string(),
+                    format_amount(channel.first_touch_revenue),
+                    channel.last_touch_deals.to_string(),
+                    format_amount(channel.last_touch_revenue),
+                    format_percentage(share(channel.last_touch_revenue, won_revenue)),
+                    channel.roi().map(format_percentage).unwrap_or_default(),
+                ]
+            })
+            .collect();
+        let deal_rows = deals
+            .iter()
+            .map(|deal| {
+                vec![
+                    deal.deal_name.clone(),
+                    deal.closed_on.to_string(),
+                    format_amount(deal.revenue),
+                    deal.touches.to_string(),
+                    deal.first_touch.channel.clone(),
+                    deal.last_touch.channel.clone(),
+                ]
+            })
+            .collect();
+        let headers = |names: &[&str]| names.iter().map(|h| h.to_string()).collect::<Vec<_>>();
+
+        let sections = vec![
+            ReportSection {
+                title: format!("Revenue by Campaign and Source, {} to {}", start, end),
+                section_type: SectionType::Summary,
+                data: ReportData::Table(TableData {
+                    headers: headers(&[
+                        "Campaign / Source",
+                        "Spent",
+                        "First-Touch Deals",
+                        "First-Touch Revenue",
+                        "Last-Touch Deals",
+                        "Last-Touch Revenue",
+                        "Share",
+                        "ROI",
+                    ]),
+                    rows: channel_rows,
+                    totals: Some(vec![
+                        "Total".to_string(),
+                        String::new(),
+                        deals.len().to_string(),
+                        format_amount(won_revenue),
+                        deals.len().to_string(),
+                        format_amount(won_revenue),
+                        String::new(),
+                        String::new(),
+                    ]),
+                }),
+            },
+            ReportSection {
+                title: "Won Deals by Touch".to_string(),
+                section_type: SectionType::Detail,
+                data: ReportData::Table(TableData {
+                    headers: headers(&["Deal", "Closed", "Revenue", "Touches", "First Touch", "Last Touch"]),
+                    rows: deal_rows,
+                    totals: None,
+                }),
+            },
+        ];
+
+        let mut key_metrics = HashMap::new();
+        key_metrics.insert("won_revenue".to_string(), MetricValue::Text(format_amount(won_revenue)));
+        key_metrics.insert("won_deals".to_string(), MetricValue::Count(deals.len() as i64));
+        let campaign_revenue: i64 = channels
+            .iter()
+            .filter(|channel| channel.campaign_id.is_some())
+            .map(|channel| channel.last_touch_revenue)
+            .sum();
+        key_metrics.insert(
+            "campaign_sourced_share".to_string(),
+            MetricValue::Percentage(share(campaign_revenue, won_revenue)),
+        );
+
+        let mut insights = Vec::new();
+        let by_first = channels.iter().max_by_key(|channel| channel.first_touch_revenue);
+        if let (Some(first), Some(last)) = (by_first, channels.first()) {
+            insights.push(format!(
+                "{} opened the most won revenue ({}); {} closed the most ({})",
+                first.channel,
+                format_amount(first.first_touch_revenue),
+                last.channel,
+                format_amount(last.last_touch_revenue)
+            ));
+        }
+        let mut recommendations = Vec::new();
+        let unproductive: Vec<&str> = channels
+            .iter()
+            .filter(|channel| channel.roi().is_some_and(|roi| roi < 0.0))
+            .map(|channel| channel.channel.as_str())
+            .collect();
+        if !unproductive.is_empty() {
+            recommendations.push(format!(
+                "Won revenue does not cover the spend of {}; review their targeting",
+                unproductive.join(", ")
+            ));
+        }
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
             data: ReportData::Mixed(sections),
-            summary: Some(summary),
-            metadata,
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: deals.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec![
+                    "deals".to_string(),
+                    "leads".to_string(),
+                    "campaign_leads".to_string(),
+                    "campaigns".to_string(),
+                ],
+            },
         })
     }
 
     fn generate_sales_activity_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let filters = ReportFilters::from_config(&config)?;
+        let mut conn = get_report_connection()?;
+        let activities = ReportDataSource::activities(&mut conn, &filters)?;
+        let deals = ReportDataSource::deals(&mut conn, &filters)?;
+        let now = Utc::now().naive_utc();
+        let timezone = DisplayTimezone::current();
+
+        let overdue = |activity: &ActivityRow| !activity.completed && activity.activity_date < now;
+        let type_row = |label: String, activities: &[&ActivityRow]| {
+            let completed = activities.iter().filter(|a| a.completed).count();
+            let late = activities.iter().filter(|a| overdue(a)).count();
+            let durations: Vec<i32> = activities.iter().filter_map(|a| a.duration_minutes).collect();
+            vec![
+                label,
+                activities.len().to_string(),
+                completed.to_string(),
+                (activities.len() - completed - late).to_string(),
+                late.to_string(),
+                format_percentage(share(completed as i64, activities.len() as i64)),
+                if durations.is_empty() {
+                    "-".to_string()
+                } else {
+                    format!("{} min", durations.iter().sum::<i32>() / durations.len() as i32)
+                },
+            ]
+        };
+
+        let mut by_type: BTreeMap<&str, Vec<&ActivityRow>> = BTreeMap::new();
+        for activity in &activities {
+            by_type.entry(activity.activity_type.as_str()).or_default().push(activity);
+        }
+        let all: Vec<&ActivityRow> = activities.iter().collect();
+
+        // Activities and deals per rep, matched by name
+        #[derive(Default)]
+        struct Rep {
+            activities: i64,
+            calls: i64,
+            meetings: i64,
+            completed: i64,
+            pipeline: i64,
+            won: i64,
+        }
+        let rep_name = |name: &Option<String>| name.clone().unwrap_or_else(|| "Unassigned".to_string());
+        let mut reps: BTreeMap<String, Rep> = BTreeMap::new();
+        for activity in &activities {
+            let rep = reps.entry(rep_name(&activity.sales_rep)).or_default();
+            rep.activities += 1;
+            match activity.activity_type.as_str() {
+                "call" => rep.calls += 1,
+                "meeting" => rep.meetings += 1,
+                _ => {}
+            }
+            if activity.completed {
+                rep.completed += 1;
+            }
+        }
+        for deal in &deals {
+            let rep = reps.entry(rep_name(&deal.sales_rep)).or_default();
+            if deal.is_open() {
+                rep.pipeline += i64::from(deal.value);
+            } else if deal.stage == "closed_won" {
+                rep.won += 1;
+            }
+        }
+        let rep_row = |name: String, rep: &Rep| {
+            vec![
+                name,
+                rep.activities.to_string(),
+                rep.calls.to_string(),
+                rep.meetings.to_string(),
+                format_percentage(share(rep.completed, rep.activities)),
+                format_amount(rep.pipeline),
+                rep.won.to_string(),
+            ]
+        };
+        let team = reps.values().fold(Rep::default(), |mut team, rep| {
+            team.activities += rep.activities;
+            team.calls += rep.calls;
+            team.meetings += rep.meetings;
+            team.completed += rep.completed;
+            team.pipeline += rep.pipeline;
+            team.won += rep.won;
+            team
+        });
+
+        // Calls, meetings and emails per month
+        let mut by_month: BTreeMap<String, [f64; 3]> = BTreeMap::new();
+        for activity in &activities {
+            let counts = by_month
+                .entry(timezone.to_local(activity.activity_date).format("%Y-%m").to_string())
+                .or_default();
+            match activity.activity_type.as_str() {
+                "call" => counts[0] += 1.0,
+                "meeting" => counts[1] += 1.0,
+                "email" => counts[2] += 1.0,
+                _ => {}
+            }
+        }
+        let trend = |label: &str, index: usize, color: &str| Dataset {
+            label: label.to_string(),
+            data: by_month.values().map(|counts| counts[index]).collect(),
+            color: Some(color.to_string()),
+        };
+
         let sections = vec![
             ReportSection {
                 title: "Activity Summary by Type".to_string(),
                 section_type: SectionType::Detail,
                 data: ReportData::Table(TableData {
-                    headers: vec![
-                        "Activity Type".to_string(),
-                        "Total Count".to_string(),
-                        "Completed".to_string(),
-                        "Pending".to_string(),
-                        "Overdue".to_string(),
-                        "Completion Rate".to_string(),
-                        "Avg Duration".to_string(),
-                    ],
-                    rows: vec![
-                        vec![
-                            "Calls".to_string(),
-                            "1,245".to_string(),
-                            "1,156".to_string(),
-                            "67".to_string(),
-                            "22".to_string(),
-                            "92.9%".to_string(),
-                            "18 min".to_string(),
-                        ],
-                        vec![
-                            "Emails".to_string(),
-                            "2,890".to_string(),
-                            "2,834".to_string(),
-                            "56".to_string(),
-                            "0".to_string(),
-                            "98.1%".to_string(),
-                            "5 min".to_string(),
-                        ],
-                        vec![
-                            "Meetings".to_string(),
-                            "456".to_string(),
-                            "398".to_string(),
-                            "45".to_string(),
-                            "13".to_string(),
-                            "87.3%".to_string(),
-                            "52 min".to_string(),
-                        ],
-                        vec![
-                            "Demos".to_string(),
-                            "189".to_string(),
-                            "167".to_string(),
-                            "18".to_string(),
-                            "4".to_string(),
-                            "88.4%".to_string(),
-                            "45 min".to_string(),
-                        ],
-                        vec![
-                            "Follow-ups".to_string(),
-                            "678".to_string(),
-                            "612".to_string(),
-                            "54".to_string(),
-                            "12".to_string(),
-                            "90.3%".to_string(),
-                            "12 min".to_string(),
-                        ],
-                    ],
-                    totals: Some(vec![
-                        "Total".to_string(),
-                        "5,458".to_string(),
-                        "5,167".to_string(),
-                        "240".to_string(),
-                        "51".to_string(),
-                        "94.7%".to_string(),
-                        "26 min".to_string(),
+                    headers: header_row(&[
+                        "Activity Type", "Total Count", "Completed", "Pending", "Overdue", "Completion Rate", "Avg Duration",
                     ]),
+                    rows: by_type.iter().map(|(kind, activities)| type_row(title_case(kind), activities)).collect(),
+                    totals: Some(type_row("Total".to_string(), &all)),
                 }),
             },
             ReportSection {
                 title: "Sales Rep Performance".to_string(),
-                section_type: SectionType::Detail,
+                section_type: SectionType::Analysis,
                 data: ReportData::Table(TableData {
-                    headers: vec![
-                        "Sales Rep".to_string(),
-                        "Activities".to_string(),
-                        "Calls".to_string(),
-                        "Meetings".to_string(),
-                        "Completion %".to_string(),
-                        "Pipeline Value".to_string(),
-                        "Closed Deals".to_string(),
-                    ],
-                    rows: vec![
-                        vec![
-                            "김영수".to_string(),
-                            "456".to_string(),
-                            "145".to_string(),
-                            "34".to_string(),
-                            "96.2%".to_string(),
-                            "₩450,000,000".to_string(),
-                            "8".to_string(),
-                        ],
-                        vec![
-                            "박민지".to_string(),
-                            "398".to_string(),
-                            "122".to_string(),
-                            "28".to_string(),
-                            "94.1%".to_string(),
-                            "₩380,000,000".to_string(),
-                            "6".to_string(),
-                        ],
-                        vec![
-                            "이창호".to_string(),
-                            "512".to_string(),
-                            "167".to_string(),
-                            "42".to_string(),
-                            "97.8%".to_string(),
-                            "₩520,000,000".to_string(),
-                            "9".to_string(),
-                        ],
-                        vec![
-                            "정수연".to_string(),
-                            "423".to_string(),
-                            "134".to_string(),
-                            "31".to_string(),
-                            "93.6%".to_string(),
-                            "₩410,000,000".to_string(),
-                            "7".to_string(),
-                        ],
-                    ],
-                    totals: Some(vec![
-                        "Team Total".to_string(),
-                        "1,789".to_string(),
-                        "568".to_string(),
-                        "135".to_string(),
-                        "95.4%".to_string(),
-                        "₩1,760,000,000".to_string(),
-                        "30".to_string(),
+                    headers: header_row(&[
+                        "Sales Rep", "Activities", "Calls", "Meetings", "Completion %", "Open Pipeline", "Closed Won",
                     ]),
+                    rows: reps.iter().map(|(name, rep)| rep_row(name.clone(), rep)).collect(),
+                    totals: Some(rep_row("Team Total".to_string(), &team)),
                 }),
             },
             ReportSection {
                 title: "Activity Trends".to_string(),
                 section_type: SectionType::Chart,
                 data: ReportData::Chart(create_line_chart(
+                    by_month.keys().cloned().collect(),
                     vec![
-                        "Week 1".to_string(),
-                        "Week 2".to_string(),
-                        "Week 3".to_string(),
-                        "Week 4".to_string(),
-                    ],
-                    vec![
-                        Dataset {
-                            label: "Calls".to_string(),
-                            data: vec![285.0, 320.0, 295.0, 345.0],
-                            color: Some("#3B82F6".to_string()),
-                        },
-                        Dataset {
-                            label: "Meetings".to_string(),
-                            data: vec![98.0, 115.0, 108.0, 135.0],
-                            color: Some("#10B981".to_string()),
-                        },
-                        Dataset {
-                            label: "Emails".to_string(),
-                            data: vec![645.0, 720.0, 678.0, 847.0],
-                            color: Some("#F59E0B".to_string()),
-                        },
+                        trend("Calls", 0, "#3B82F6"),
+                        trend("Meetings", 1, "#10B981"),
+                        trend("Emails", 2, "#F59E0B"),
                     ],
                 )),
             },
         ];
 
+        let completed = activities.iter().filter(|a| a.completed).count() as i64;
+        let late = activities.iter().filter(|a| overdue(a)).count() as i64;
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("total_activities".to_string(), MetricValue::Count(5458));
-        key_metrics.insert("completion_rate".to_string(), MetricValue::Percentage(94.7));
-        key_metrics.insert("overdue_activities".to_string(), MetricValue::Count(51));
-        key_metrics.insert("average_activities_per_rep".to_string(), MetricValue::Number(447.3));
-        key_metrics.insert("team_pipeline_value".to_string(), MetricValue::Currency(1760000000));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "Sales team completed 5,167 activities with 94.7% completion rate".to_string(),
-                "이창호 leads team with 512 activities and ₩520M pipeline value".to_string(),
-                "Email activities have highest completion rate at 98.1%".to_string(),
-                "Only 51 activities are overdue, indicating good time management".to_string(),
-                "Activity volume increased 21% in Week 4 compared to Week 1".to_string(),
-            ],
-            recommendations: vec![
-                "Provide time management coaching for reps with overdue activities".to_string(),
-                "Share best practices from top performers like 이창호".to_string(),
-                "Implement activity automation for routine follow-ups".to_string(),
-                "Set team goals for meeting-to-call ratios".to_string(),
-                "Review and optimize activity scheduling processes".to_string(),
-            ],
-        };
+        key_metrics.insert("total_activities".to_string(), MetricValue::Count(activities.len() as i64));
+        key_metrics.insert(
+            "completion_rate".to_string(),
+            MetricValue::Percentage(share(completed, activities.len() as i64)),
+        );
+        key_metrics.insert("overdue_activities".to_string(), MetricValue::Count(late));
+        key_metrics.insert(
+            "activities_per_rep".to_string(),
+            MetricValue::Number(activities.len() as f64 / reps.values().filter(|r| r.activities > 0).count().max(1) as f64),
+        );
 
-        let metadata = ReportMetadata {
-            total_records: 5458,
-            processing_time_ms: 320,
-            filters_applied: vec!["current_month".to_string(), "sales_team".to_string()],
-            data_sources: vec!["activities".to_string(), "employees".to_string(), "deals".to_string()],
-        };
+        let mut insights = vec![format!(
+            "{} activities, {} completed ({})",
+            activities.len(),
+            completed,
+            format_percentage(share(completed, activities.len() as i64))
+        )];
+        if let Some((name, rep)) = reps.iter().max_by_key(|(_, rep)| rep.activities).filter(|(_, rep)| rep.activities > 0) {
+            insights.push(format!("{} logged the most activities: {}", name, rep.activities));
+        }
+        let mut recommendations = Vec::new();
+        if late > 0 {
+            recommendations.push(format!("Complete or reschedule {} overdue activities", late));
+        }
+        let idle: Vec<&str> = reps
+            .iter()
+            .filter(|(_, rep)| rep.pipeline > 0 && rep.activities == 0)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if !idle.is_empty() {
+            recommendations.push(format!("No activities logged against open pipeline of {}", idle.join(", ")));
+        }
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
             data: ReportData::Mixed(sections),
-            summary: Some(summary),
-            metadata,
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: activities.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["activities".to_string(), "deals".to_string(), "employees".to_string()],
+            },
         })
     }
 
     fn generate_revenue_forecast_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let (start, end) = match &config.date_range {
+            Some(range) => (range.start_date, range.end_date),
+            None => parse_period("this-quarter")?,
+        };
+        let mut conn = get_report_connection()?;
+        let forecast = ForecastService::forecast(
+            &mut conn,
+            start,
+            end,
+            DisplayTimezone::current().today(),
+            filter_i32(&config, "employee_id")?,
+        )?;
+
+        let rep_cells = |rep: &RepForecast| {
+            vec![
+                rep.name.clone(),
+                rep.quota.map(format_amount).unwrap_or_default(),
+                format_amount(rep.booked),
+                format_amount(rep.pipeline),
+                format_amount(rep.expected_pipeline),
+                format_amount(rep.run_rate),
+                format_amount(rep.forecast),
+                format_amount(rep.low),
+                format_amount(rep.high),
+                rep.attainment().map(format_percentage).unwrap_or_default(),
+            ]
+        };
+        let rep_rows = forecast.reps.iter().map(rep_cells).collect();
+        let total = &forecast.total;
+
         let sections = vec![
             ReportSection {
-                title: "Quarterly Revenue Forecast".to_string(),
+                title: format!("Forecast by Rep, {} to {}", forecast.start, forecast.end),
                 section_type: SectionType::Detail,
                 data: ReportData::Table(TableData {
-                    headers: vec![
-                        "Quarter".to_string(),
-                        "Pipeline".to_string(),
-                        "Probability".to_string(),
-                        "Forecast".to_string(),
-                        "Upside".to_string(),
-                        "Conservative".to_string(),
-                        "Actual/Target".to_string(),
-                    ],
-                    rows: vec![
-                        vec![
-                            "Q2 2024".to_string(),
-                            "₩1,850,000,000".to_string(),
-                            "65%".to_string(),
-                            "₩1,202,500,000".to_string(),
-                            "₩1,387,500,000".to_string(),
-                            "₩925,000,000".to_string(),
-                            "₩1,145,000,000".to_string(),
-                        ],
-                        vec![
-                            "Q3 2024".to_string(),
-                            "₩2,100,000,000".to_string(),
-                            "58%".to_string(),
-                            "₩1,218,000,000".to_string(),
-                            "₩1,470,000,000".to_string(),
-                            "₩1,050,000,000".to_string(),
-                            "₩1,200,000,000".to_string(),
-                        ],
-                        vec![
-                            "Q4 2024".to_string(),
-                            "₩2,400,000,000".to_string(),
-                            "52%".to_string(),
-                            "₩1,248,000,000".to_string(),
-                            "₩1,560,000,000".to_string(),
-                            "₩1,080,000,000".to_string(),
-                            "₩1,250,000,000".to_string(),
-                        ],
-                        vec![
-                            "Q1 2025".to_string(),
-                            "₩2,200,000,000".to_string(),
-                            "45%".to_string(),
-                            "₩990,000,000".to_string(),
-                            "₩1,320,000,000".to_string(),
-                            "₩880,000,000".to_string(),
-                            "₩1,100,000,000".to_string(),
-                        ],
-                    ],
-                    totals: Some(vec![
-                        "FY 2024".to_string(),
-                        "₩8,550,000,000".to_string(),
-                        "55%".to_string(),
-                        "₩4,658,500,000".to_string(),
-                        "₩5,737,500,000".to_string(),
-                        "₩3,935,000,000".to_string(),
-                        "₩4,695,000,000".to_string(),
-                    ]),
+                    headers: [
+                        "Rep", "Quota", "Booked", "Pipeline", "Weighted", "Run Rate", "Forecast", "Low", "High", "Attainment",
+                    ]
+                    .iter()
+                    .map(|h| h.to_string())
+                    .collect(),
+                    rows: rep_rows,
+                    totals: Some(rep_cells(total)),
                 }),
             },
             ReportSection {
-                title: "Revenue Trend & Forecast".to_string(),
+                title: "Forecast against Quota".to_string(),
                 section_type: SectionType::Chart,
-                data: ReportData::Chart(create_line_chart(
-                    vec![
-                        "Q1 2023".to_string(),
-                        "Q2 2023".to_string(),
-                        "Q3 2023".to_string(),
-                        "Q4 2023".to_string(),
-                        "Q1 2024".to_string(),
-                        "Q2 2024".to_string(),
-                        "Q3 2024".to_string(),
-                        "Q4 2024".to_string(),
-                    ],
-                    vec![
+                data: ReportData::Chart(ChartData {
+                    chart_type: ChartType::Bar,
+                    labels: forecast.reps.iter().map(|rep| rep.name.clone()).collect(),
+                    datasets: vec![
                         Dataset {
-                            label: "Actual Revenue".to_string(),
-                            data: vec![890.0, 1050.0, 980.0, 1180.0, 1100.0, 1145.0, 0.0, 0.0],
-                            color: Some("#10B981".to_string()),
+                            label: "Quota".to_string(),
+                            data: forecast.reps.iter().map(|rep| rep.quota.unwrap_or(0) as f64).collect(),
+                            color: Some("#F59E0B".to_string()),
                         },
                         Dataset {
                             label: "Forecast".to_string(),
-                            data: vec![0.0, 0.0, 0.0, 0.0, 0.0, 1202.5, 1218.0, 1248.0],
+                            data: forecast.reps.iter().map(|rep| rep.forecast as f64).collect(),
                             color: Some("#3B82F6".to_string()),
                         },
                         Dataset {
-                            label: "Conservative".to_string(),
-                            data: vec![0.0, 0.0, 0.0, 0.0, 0.0, 925.0, 1050.0, 1080.0],
-                            color: Some("#F59E0B".to_string()),
+                            label: "Booked".to_string(),
+                            data: forecast.reps.iter().map(|rep| rep.booked as f64).collect(),
+                            color: Some("#10B981".to_string()),
                         },
                     ],
-                )),
+                }),
             },
         ];
 
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("fy2024_forecast".to_string(), MetricValue::Currency(4658500000));
-        key_metrics.insert("forecast_accuracy".to_string(), MetricValue::Percentage(94.2));
-        key_metrics.insert("revenue_growth_yoy".to_string(), MetricValue::Percentage(12.8));
-        key_metrics.insert("pipeline_coverage".to_string(), MetricValue::Number(1.8));
-        key_metrics.insert("forecast_confidence".to_string(), MetricValue::Percentage(85.0));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "FY 2024 revenue forecast of ₩4.66 billion represents 12.8% YoY growth".to_string(),
-                "Q2 2024 achieved 95.2% of forecast, indicating strong predictability".to_string(),
-                "Pipeline coverage ratio of 1.8x provides healthy forecast buffer".to_string(),
-                "Conservative scenario still achieves ₩3.94 billion, exceeding previous year".to_string(),
-                "Forecast accuracy improved to 94.2% with enhanced methodology".to_string(),
-            ],
-            recommendations: vec![
-                "Maintain focus on Q3/Q4 pipeline development for stronger coverage".to_string(),
-                "Implement weekly forecast reviews with sales team".to_string(),
-                "Develop contingency plans for conservative scenario achievement".to_string(),
-                "Invest in sales enablement to improve close rates".to_string(),
-                "Consider accelerating Q1 2025 pipeline development".to_string(),
-            ],
-        };
+        key_metrics.insert("forecast".to_string(), MetricValue::Text(format_amount(total.forecast)));
+        key_metrics.insert("forecast_low".to_string(), MetricValue::Text(format_amount(total.low)));
+        key_metrics.insert("forecast_high".to_string(), MetricValue::Text(format_amount(total.high)));
+        key_metrics.insert("booked".to_string(), MetricValue::Text(format_amount(total.booked)));
+        key_metrics.insert("open_pipeline".to_string(), MetricValue::Text(format_amount(total.pipeline)));
+        if let Some(attainment) = total.attainment() {
+            key_metrics.insert("quota_attainment".to_string(), MetricValue::Percentage(attainment));
+        }
 
-        let metadata = ReportMetadata {
-            total_records: 4,
-            processing_time_ms: 145,
-            filters_applied: vec!["forecast_model".to_string(), "quarterly_view".to_string()],
-            data_sources: vec!["deals".to_string(), "historical_actuals".to_string(), "pipeline_analysis".to_string()],
-        };
+        let mut insights = vec![format!(
+            "Forecast of {} ({} to {}, 80% confidence) with {} already booked",
+            format_amount(total.forecast),
+            format_amount(total.low),
+            format_amount(total.high),
+            format_amount(total.booked)
+        )];
+        if total.stage_weighted != total.expected_pipeline {
+            insights.push(format!(
+                "Historical win rates move the weighted pipeline from {} to {}",
+                format_amount(total.stage_weighted),
+                format_amount(total.expected_pipeline)
+            ));
+        }
+        let mut recommendations = Vec::new();
+        let behind: Vec<&str> = forecast
+            .reps
+            .iter()
+            .filter(|rep| rep.quota.is_some_and(|quota| rep.high < quota))
+            .map(|rep| rep.name.as_str())
+            .collect();
+        if !behind.is_empty() {
+            recommendations.push(format!(
+                "Even the high forecast misses quota for {}; review their pipeline",
+                behind.join(", ")
+            ));
+        }
+        if forecast.reps.iter().all(|rep| rep.quota.is_none()) {
+            recommendations.push("Set quotas with `crm forecast quota` to track attainment".to_string());
+        }
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
             data: ReportData::Mixed(sections),
-            summary: Some(summary),
-            metadata,
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: forecast.reps.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["deals".to_string(), "sales_quotas".to_string(), "employees".to_string()],
+            },
         })
     }
 }
@@ -994,4 +1215,17 @@ impl Default for CRMReportsGenerator {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+/// `needs_analysis` as `Needs Analysis`
+fn title_case(value: &str) -> String {
+    value
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|first| first.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
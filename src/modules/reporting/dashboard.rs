@@ -0,0 +1,93 @@
+use diesel::prelude::*;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::users;
+use crate::database::{DatabaseConnection, PortalLink, User};
+use crate::modules::system::portal::{PortalScope, PortalService};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Roles a dashboard link may be issued to and keeps working for
+const DASHBOARD_ROLES: &[&str] = &["admin", "manager"];
+
+/// Links to the read-only dashboard `clierp system serve` renders, so
+/// managers can check KPIs, the pipeline and low stock from a phone. A
+/// link belongs to the user it was issued to and stops working when that
+/// user is deactivated or no longer a manager.
+pub struct DashboardService;
+
+impl DashboardService {
+    /// Issue a dashboard link for a user
+    pub fn share(
+        conn: &mut DatabaseConnection,
+        secret: &str,
+        user_id: i32,
+        ttl_days: u32,
+        shared_by: Option<i32>,
+    ) -> Result<(PortalLink, String)> {
+        let user = Self::find(conn, user_id)?;
+        if !Self::may_view(&user) {
+            return Err(CLIERPError::Authorization(format!(
+                "{} is not an active manager or admin; only they get a dashboard link",
+                user.username
+            )));
+        }
+        PortalService::issue(conn, secret, PortalScope::Dashboard, user_id, ttl_days, shared_by)
+    }
+
+    /// The user a dashboard link was issued to
+    pub fn open(conn: &mut DatabaseConnection, secret: &str, token: &str) -> Result<(PortalLink, User)> {
+        let link = PortalService::verify(conn, secret, token, PortalScope::Dashboard)?;
+        let user = Self::find(conn, link.record_id)?;
+        if !Self::may_view(&user) {
+            return Err(CLIERPError::Authentication("This link is invalid or has expired".to_string()));
+        }
+        Ok((link, user))
+    }
+
+    fn may_view(user: &User) -> bool {
+        user.is_active && DASHBOARD_ROLES.contains(&user.role.as_str())
+    }
+
+    fn find(conn: &mut DatabaseConnection, user_id: i32) -> Result<User> {
+        users::table
+            .find(user_id)
+            .first::<User>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("User with ID {} not found", user_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::CompanyFixture;
+
+    const SECRET: &str = "dashboard-test-secret";
+
+    #[test]
+    fn test_link_follows_the_users_role() {
+        let company = CompanyFixture::new().build().unwrap();
+        let mut conn = company.connection().unwrap();
+        let admin = company.admin.id;
+
+        let (_, token) = DashboardService::share(&mut conn, SECRET, admin, 7, Some(admin)).unwrap();
+        let (link, user) = DashboardService::open(&mut conn, SECRET, &token).unwrap();
+        assert_eq!(link.record_id, admin);
+        assert_eq!(user.id, admin);
+        assert!(DashboardService::open(&mut conn, "another-secret", &token).is_err());
+
+        // A demoted user keeps the link but can no longer open it, nor get a new one
+        diesel::update(users::table.find(admin)).set(users::role.eq("employee")).execute(&mut conn).unwrap();
+        assert!(matches!(DashboardService::open(&mut conn, SECRET, &token), Err(CLIERPError::Authentication(_))));
+        assert!(matches!(DashboardService::share(&mut conn, SECRET, admin, 7, None), Err(CLIERPError::Authorization(_))));
+
+        diesel::update(users::table.find(admin))
+            .set((users::role.eq("manager"), users::is_active.eq(false)))
+            .execute(&mut conn)
+            .unwrap();
+        assert!(DashboardService::open(&mut conn, SECRET, &token).is_err());
+    }
+}
@@ -0,0 +1,813 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{
+    accounts, activities, campaigns, categories, customers, deals, employees, leads, products, purchase_items,
+    purchase_orders, stock_movements, suppliers,
+};
+use crate::database::{Account, Campaign};
+use crate::utils::dates::parse_period;
+use crate::utils::timezone::DisplayTimezone;
+use super::engine::{filter_i32, DateRange, ReportConfig};
+use super::summaries::SummaryService;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+pub const MOVEMENT_TYPES: &[&str] = &["in", "out", "adjustment"];
+pub const ACCOUNT_TYPES: &[&str] = &["asset", "liability", "equity", "revenue", "expense"];
+pub const CUSTOMER_SEGMENTS: &[&str] = &["individual", "business"];
+pub const CAMPAIGN_TYPES: &[&str] = &["email", "phone", "social", "event", "advertising"];
+pub const DEAL_STAGES: &[&str] = &[
+    "prospecting",
+    "qualification",
+    "needs_analysis",
+    "proposal",
+    "negotiation",
+    "closing",
+    "closed_won",
+    "closed_lost",
+];
+
+/// Stock position of a product against its minimum and maximum levels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum StockLevel {
+    Normal,
+    Low,
+    Out,
+    Overstocked,
+}
+
+impl StockLevel {
+    pub fn of(current: i32, min: i32, max: Option<i32>) -> Self {
+        if current <= 0 {
+            StockLevel::Out
+        } else if current <= min {
+            StockLevel::Low
+        } else if max.is_some_and(|max| current > max) {
+            StockLevel::Overstocked
+        } else {
+            StockLevel::Normal
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            StockLevel::Normal => "Normal",
+            StockLevel::Low => "Low Stock",
+            StockLevel::Out => "Out of Stock",
+            StockLevel::Overstocked => "Overstocked",
+        }
+    }
+}
+
+impl std::str::FromStr for StockLevel {
+    type Err = CLIERPError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "normal" => Ok(StockLevel::Normal),
+            "low" => Ok(StockLevel::Low),
+            "out" => Ok(StockLevel::Out),
+            "overstocked" => Ok(StockLevel::Overstocked),
+            _ => Err(CLIERPError::ValidationError(format!(
+                "Filter stock_level must be all, normal, low, out or overstocked, got '{}'",
+                s
+            ))),
+        }
+    }
+}
+
+/// The filters of a report config, parsed and checked once so every query
+/// applies them the same way
+#[derive(Debug, Clone, Default)]
+pub struct ReportFilters {
+    /// From `date_range`, else `fiscal_period`, else the `period` preset
+    pub range: Option<DateRange>,
+    pub category_id: Option<i32>,
+    pub supplier_id: Option<i32>,
+    /// Employee deals, leads and activities are assigned to
+    pub sales_rep: Option<i32>,
+    /// `None` for all levels
+    pub stock_level: Option<StockLevel>,
+    pub movement_type: Option<String>,
+    /// Empty for every account type
+    pub account_types: Vec<String>,
+    /// Empty for every stage
+    pub deal_stages: Vec<String>,
+    /// Customer type: `individual` or `business`
+    pub customer_segment: Option<String>,
+    pub campaign_type: Option<String>,
+    /// Add a previous-period column to finance statements
+    pub comparison: bool,
+}
+
+impl ReportFilters {
+    pub fn from_config(config: &ReportConfig) -> Result<Self> {
+        let text = |name: &str| config.filters.get(name).map(|v| v.trim().to_lowercase()).filter(|v| !v.is_empty());
+        let range = match (&config.date_range, text("fiscal_period"), text("period")) {
+            (Some(range), _, _) => Some(range.clone()),
+            (None, Some(label), _) => Some(Self::resolve(&label)?),
+            (None, None, Some(preset)) => Some(Self::resolve(match preset.as_str() {
+                "monthly" => "this-month",
+                "quarterly" => "this-quarter",
+                "yearly" => "this-year",
+                other => other,
+            })?),
+            (None, None, None) => None,
+        };
+
+        Ok(Self {
+            range,
+            category_id: filter_i32(config, "category_id")?,
+            supplier_id: filter_i32(config, "supplier_id")?,
+            sales_rep: filter_i32(config, "sales_rep")?,
+            stock_level: match text("stock_level") {
+                None => None,
+                Some(level) if level == "all" => None,
+                Some(level) => Some(level.parse()?),
+            },
+            movement_type: one_of(text("movement_type"), "movement_type", MOVEMENT_TYPES)?,
+            account_types: list_of(text("account_type"), "account_type", ACCOUNT_TYPES)?,
+            deal_stages: list_of(text("deal_stage"), "deal_stage", DEAL_STAGES)?,
+            customer_segment: one_of(text("customer_segment"), "customer_segment", CUSTOMER_SEGMENTS)?,
+            campaign_type: one_of(text("campaign_type"), "campaign_type", CAMPAIGN_TYPES)?,
+            comparison: matches!(text("comparison").as_deref(), Some("true" | "yes" | "1")),
+        })
+    }
+
+    fn resolve(label: &str) -> Result<DateRange> {
+        let (start_date, end_date) = parse_period(label)?;
+        Ok(DateRange { start_date, end_date })
+    }
+
+    /// The filtered period, or `default` when the config names none
+    pub fn period_or(&self, default: (NaiveDate, NaiveDate)) -> (NaiveDate, NaiveDate) {
+        self.range
+            .as_ref()
+            .map(|range| (range.start_date, range.end_date))
+            .unwrap_or(default)
+    }
+
+    /// These filters with the period defaulting to the last `days` days,
+    /// for figures such as turnover that need some history
+    pub fn or_recent(&self, days: i64) -> ReportFilters {
+        let mut filters = self.clone();
+        if filters.range.is_none() {
+            let today = DisplayTimezone::current().today();
+            filters.range = Some(DateRange {
+                start_date: today - Duration::days(days - 1),
+                end_date: today,
+            });
+        }
+        filters
+    }
+
+    /// Whether a stored UTC timestamp falls in the period; always without one
+    pub fn contains(&self, at: NaiveDateTime) -> bool {
+        self.utc_bounds().is_none_or(|(start, end)| at >= start && at < end)
+    }
+
+    fn utc_bounds(&self) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        self.range.as_ref().map(DateRange::utc_bounds)
+    }
+}
+
+fn one_of(value: Option<String>, name: &str, allowed: &[&str]) -> Result<Option<String>> {
+    match value {
+        Some(value) if !allowed.contains(&value.as_str()) => Err(CLIERPError::ValidationError(format!(
+            "Filter {} must be one of {}, got '{}'",
+            name,
+            allowed.join(", "),
+            value
+        ))),
+        value => Ok(value),
+    }
+}
+
+/// A comma-separated multi-select filter
+fn list_of(value: Option<String>, name: &str, allowed: &[&str]) -> Result<Vec<String>> {
+    value
+        .map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(|v| one_of(Some(v.to_string()), name, allowed).map(|v| v.unwrap_or_default()))
+                .collect()
+        })
+        .unwrap_or_else(|| Ok(Vec::new()))
+}
+
+#[derive(Debug, Clone)]
+pub struct ProductRow {
+    pub id: i32,
+    pub sku: String,
+    pub name: String,
+    pub category: String,
+    pub current_stock: i32,
+    pub min_stock_level: i32,
+    pub max_stock_level: Option<i32>,
+    pub cost_price: i32,
+    pub price: i32,
+}
+
+impl ProductRow {
+    /// Stock on hand at cost
+    pub fn value(&self) -> i64 {
+        i64::from(self.current_stock.max(0)) * i64::from(self.cost_price)
+    }
+
+    pub fn level(&self) -> StockLevel {
+        StockLevel::of(self.current_stock, self.min_stock_level, self.max_stock_level)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MovementRow {
+    pub moved_at: NaiveDateTime,
+    pub product_id: i32,
+    pub sku: String,
+    pub product: String,
+    pub category: String,
+    pub movement_type: String,
+    /// Signed: receipts positive, issues negative
+    pub quantity: i32,
+    /// The movement's unit cost, else the product's cost price
+    pub unit_cost: i32,
+    pub reference: Option<String>,
+}
+
+impl MovementRow {
+    pub fn value(&self) -> i64 {
+        i64::from(self.quantity.abs()) * i64::from(self.unit_cost)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DealRow {
+    pub id: i32,
+    pub name: String,
+    pub stage: String,
+    /// Final amount after discount, else the deal value
+    pub value: i32,
+    pub probability: Option<i32>,
+    pub close_date: Option<NaiveDate>,
+    pub created_at: NaiveDateTime,
+    pub sales_rep: Option<String>,
+    pub customer_id: Option<i32>,
+    pub customer: Option<String>,
+    pub customer_type: Option<String>,
+}
+
+impl DealRow {
+    pub fn is_open(&self) -> bool {
+        self.stage != "closed_won" && self.stage != "closed_lost"
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CustomerRow {
+    pub id: i32,
+    pub name: String,
+    pub customer_type: String,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone)]
+pub struct LeadRow {
+    pub source: String,
+    pub status: String,
+    pub estimated_value: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub sales_rep: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ActivityRow {
+    pub activity_type: String,
+    pub activity_date: NaiveDateTime,
+    pub duration_minutes: Option<i32>,
+    pub completed: bool,
+    pub deal_id: Option<i32>,
+    pub sales_rep: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PurchaseOrderRow {
+    pub po_number: String,
+    pub supplier_id: i32,
+    pub supplier: String,
+    pub payment_terms: Option<String>,
+    pub order_date: NaiveDate,
+    pub expected_date: Option<NaiveDate>,
+    pub status: String,
+    pub total_amount: i32,
+    /// Day of the last receipt against the order
+    pub received_on: Option<NaiveDate>,
+}
+
+impl PurchaseOrderRow {
+    /// Whether the order arrived by its expected date; `None` until it has
+    /// been received or when no date was expected
+    pub fn on_time(&self) -> Option<bool> {
+        match (self.received_on, self.expected_date) {
+            (Some(received), Some(expected)) => Some(received <= expected),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountRow {
+    pub account_code: String,
+    pub account_name: String,
+    pub account_type: String,
+    pub debits: i64,
+    pub credits: i64,
+}
+
+impl AccountRow {
+    /// Balance on the account's normal side: debits for assets and
+    /// expenses, credits for the rest
+    pub fn balance(&self) -> i64 {
+        match self.account_type.as_str() {
+            "asset" | "expense" => self.debits - self.credits,
+            _ => self.credits - self.debits,
+        }
+    }
+}
+
+/// Queries the report generators read from, each applying the filters
+/// that concern its table
+pub struct ReportDataSource;
+
+impl ReportDataSource {
+    /// Active products with their category, by SKU
+    pub fn products(conn: &mut SqliteConnection, filters: &ReportFilters) -> Result<Vec<ProductRow>> {
+        let mut query = products::table
+            .inner_join(categories::table)
+            .filter(products::is_active.eq(true))
+            .select((
+                products::id,
+                products::sku,
+                products::name,
+                categories::name,
+                products::current_stock,
+                products::min_stock_level,
+                products::max_stock_level,
+                products::cost_price,
+                products::price,
+            ))
+            .order(products::sku.asc())
+            .into_boxed();
+        if let Some(category_id) = filters.category_id {
+            query = query.filter(products::category_id.eq(category_id));
+        }
+        if let Some(supplier_id) = filters.supplier_id {
+            query = query.filter(products::id.eq_any(Self::supplied_products(conn, supplier_id)?));
+        }
+
+        let rows = query
+            .load::<(i32, String, String, String, i32, i32, Option<i32>, i32, i32)>(conn)?
+            .into_iter()
+            .map(
+                |(id, sku, name, category, current_stock, min_stock_level, max_stock_level, cost_price, price)| {
+                    ProductRow {
+                        id,
+                        sku,
+                        name,
+                        category,
+                        current_stock,
+                        min_stock_level,
+                        max_stock_level,
+                        cost_price,
+                        price,
+                    }
+                },
+            )
+            .filter(|row| filters.stock_level.is_none_or(|level| row.level() == level))
+            .collect();
+        Ok(rows)
+    }
+
+    /// Stock movements in the period, newest first
+    pub fn stock_movements(conn: &mut SqliteConnection, filters: &ReportFilters) -> Result<Vec<MovementRow>> {
+        let mut query = stock_movements::table
+            .inner_join(products::table.inner_join(categories::table))
+            .select((
+                stock_movements::movement_date,
+                products::id,
+                products::sku,
+                products::name,
+                categories::name,
+                stock_movements::movement_type,
+                stock_movements::quantity,
+                stock_movements::unit_cost,
+                products::cost_price,
+                stock_movements::reference_type,
+                stock_movements::reference_id,
+            ))
+            .order((stock_movements::movement_date.desc(), stock_movements::id.desc()))
+            .into_boxed();
+        if let Some((start, end)) = filters.utc_bounds() {
+            query = query
+                .filter(stock_movements::movement_date.ge(start))
+                .filter(stock_movements::movement_date.lt(end));
+        }
+        if let Some(movement_type) = &filters.movement_type {
+            query = query.filter(stock_movements::movement_type.eq(movement_type.clone()));
+        }
+        if let Some(category_id) = filters.category_id {
+            query = query.filter(products::category_id.eq(category_id));
+        }
+        if let Some(supplier_id) = filters.supplier_id {
+            query = query.filter(products::id.eq_any(Self::supplied_products(conn, supplier_id)?));
+        }
+
+        let rows = query
+            .load::<(
+                NaiveDateTime,
+                i32,
+                String,
+                String,
+                String,
+                String,
+                i32,
+                Option<i32>,
+                i32,
+                Option<String>,
+                Option<i32>,
+            )>(conn)?
+            .into_iter()
+            .map(
+                |(moved_at, product_id, sku, product, category, movement_type, quantity, unit_cost, cost_price, ref_type, ref_id)| {
+                    MovementRow {
+                        moved_at,
+                        product_id,
+                        sku,
+                        product,
+                        category,
+                        quantity: match movement_type.as_str() {
+                            "in" => quantity.abs(),
+                            "out" => -quantity.abs(),
+                            _ => quantity,
+                        },
+                        movement_type,
+                        unit_cost: unit_cost.unwrap_or(cost_price),
+                        reference: match (ref_type, ref_id) {
+                            (Some(kind), Some(id)) => Some(format!("{} #{}", kind, id)),
+                            (Some(kind), None) => Some(kind),
+                            _ => None,
+                        },
+                    }
+                },
+            )
+            .collect();
+        Ok(rows)
+    }
+
+    /// Deals opened in the period, with their customer and sales rep
+    pub fn deals(conn: &mut SqliteConnection, filters: &ReportFilters) -> Result<Vec<DealRow>> {
+        let mut query = deals::table
+            .left_join(leads::table.on(leads::id.nullable().eq(deals::lead_id)))
+            .left_join(customers::table.on(customers::id.nullable().eq(leads::customer_id)))
+            .left_join(employees::table.on(employees::id.nullable().eq(deals::assigned_to)))
+            .select((
+                deals::id,
+                deals::deal_name,
+                deals::stage,
+                deals::deal_value,
+                deals::final_amount,
+                deals::probability,
+                deals::close_date,
+                deals::created_at,
+                employees::name.nullable(),
+                customers::id.nullable(),
+                customers::name.nullable(),
+                customers::customer_type.nullable(),
+            ))
+            .order(deals::created_at.desc())
+            .into_boxed();
+        if let Some((start, end)) = filters.utc_bounds() {
+            query = query.filter(deals::created_at.ge(start)).filter(deals::created_at.lt(end));
+        }
+        if !filters.deal_stages.is_empty() {
+            query = query.filter(deals::stage.eq_any(filters.deal_stages.clone()));
+        }
+        if let Some(rep) = filters.sales_rep {
+            query = query.filter(deals::assigned_to.eq(rep));
+        }
+        if let Some(segment) = &filters.customer_segment {
+            query = query.filter(customers::customer_type.eq(segment.clone()));
+        }
+
+        let rows = query
+            .load::<(
+                i32,
+                String,
+                String,
+                i32,
+                Option<i32>,
+                Option<i32>,
+                Option<NaiveDate>,
+                NaiveDateTime,
+                Option<String>,
+                Option<i32>,
+                Option<String>,
+                Option<String>,
+            )>(conn)?
+            .into_iter()
+            .map(
+                |(id, name, stage, deal_value, final_amount, probability, close_date, created_at, sales_rep, customer_id, customer, customer_type)| {
+                    DealRow {
+                        id,
+                        name,
+                        stage,
+                        value: final_amount.unwrap_or(deal_value),
+                        probability,
+                        close_date,
+                        created_at,
+                        sales_rep,
+                        customer_id,
+                        customer,
+                        customer_type,
+                    }
+                },
+            )
+            .collect();
+        Ok(rows)
+    }
+
+    /// Customers of the filtered segment, whenever they were created
+    pub fn customers(conn: &mut SqliteConnection, filters: &ReportFilters) -> Result<Vec<CustomerRow>> {
+        let mut query = customers::table
+            .select((
+                customers::id,
+                customers::name,
+                customers::customer_type,
+                customers::status,
+                customers::created_at,
+            ))
+            .order(customers::name.asc())
+            .into_boxed();
+        if let Some(segment) = &filters.customer_segment {
+            query = query.filter(customers::customer_type.eq(segment.clone()));
+        }
+        Ok(query
+            .load::<(i32, String, String, String, NaiveDateTime)>(conn)?
+            .into_iter()
+            .map(|(id, name, customer_type, status, created_at)| CustomerRow {
+                id,
+                name,
+                customer_type,
+                status,
+                created_at,
+            })
+            .collect())
+    }
+
+    /// Leads created in the period
+    pub fn leads(conn: &mut SqliteConnection, filters: &ReportFilters) -> Result<Vec<LeadRow>> {
+        let mut query = leads::table
+            .left_join(customers::table)
+            .left_join(employees::table)
+            .select((
+                leads::lead_source,
+                leads::status,
+                leads::estimated_value,
+                leads::created_at,
+                employees::name.nullable(),
+            ))
+            .order(leads::created_at.desc())
+            .into_boxed();
+        if let Some((start, end)) = filters.utc_bounds() {
+            query = query.filter(leads::created_at.ge(start)).filter(leads::created_at.lt(end));
+        }
+        if let Some(rep) = filters.sales_rep {
+            query = query.filter(leads::assigned_to.eq(rep));
+        }
+        if let Some(segment) = &filters.customer_segment {
+            query = query.filter(customers::customer_type.eq(segment.clone()));
+        }
+        Ok(query
+            .load::<(String, String, Option<i32>, NaiveDateTime, Option<String>)>(conn)?
+            .into_iter()
+            .map(|(source, status, estimated_value, created_at, sales_rep)| LeadRow {
+                source,
+                status,
+                estimated_value,
+                created_at,
+                sales_rep,
+            })
+            .collect())
+    }
+
+    /// Activities dated in the period
+    pub fn activities(conn: &mut SqliteConnection, filters: &ReportFilters) -> Result<Vec<ActivityRow>> {
+        let mut query = activities::table
+            .left_join(employees::table)
+            .select((
+                activities::activity_type,
+                activities::activity_date,
+                activities::duration_minutes,
+                activities::completed,
+                activities::deal_id,
+                employees::name.nullable(),
+            ))
+            .order(activities::activity_date.desc())
+            .into_boxed();
+        if let Some((start, end)) = filters.utc_bounds() {
+            query = query
+                .filter(activities::activity_date.ge(start))
+                .filter(activities::activity_date.lt(end));
+        }
+        if let Some(rep) = filters.sales_rep {
+            query = query.filter(activities::assigned_to.eq(rep));
+        }
+        Ok(query
+            .load::<(String, NaiveDateTime, Option<i32>, bool, Option<i32>, Option<String>)>(conn)?
+            .into_iter()
+            .map(|(activity_type, activity_date, duration_minutes, completed, deal_id, sales_rep)| ActivityRow {
+                activity_type,
+                activity_date,
+                duration_minutes,
+                completed,
+                deal_id,
+                sales_rep,
+            })
+            .collect())
+    }
+
+    /// Campaigns of the filtered type running at any time in the period
+    pub fn campaigns(conn: &mut SqliteConnection, filters: &ReportFilters) -> Result<Vec<Campaign>> {
+        let mut query = campaigns::table.select(Campaign::as_select()).order(campaigns::name.asc()).into_boxed();
+        if let Some(campaign_type) = &filters.campaign_type {
+            query = query.filter(campaigns::campaign_type.eq(campaign_type.clone()));
+        }
+        if let Some(range) = &filters.range {
+            query = query
+                .filter(campaigns::start_date.is_null().or(campaigns::start_date.le(range.end_date)))
+                .filter(campaigns::end_date.is_null().or(campaigns::end_date.ge(range.start_date)));
+        }
+        Ok(query.load::<Campaign>(conn)?)
+    }
+
+    /// Value of won deals per campaign name, from leads whose source is the
+    /// campaign, the way campaign lead counts are matched
+    pub fn campaign_revenue(conn: &mut SqliteConnection, names: &[String]) -> Result<HashMap<String, i64>> {
+        let won = deals::table
+            .inner_join(leads::table.on(leads::id.nullable().eq(deals::lead_id)))
+            .filter(deals::stage.eq("closed_won"))
+            .filter(leads::lead_source.eq_any(names))
+            .select((leads::lead_source, deals::deal_value, deals::final_amount))
+            .load::<(String, i32, Option<i32>)>(conn)?;
+        let mut revenue = HashMap::new();
+        for (source, deal_value, final_amount) in won {
+            *revenue.entry(source).or_default() += i64::from(final_amount.unwrap_or(deal_value));
+        }
+        Ok(revenue)
+    }
+
+    /// Purchase orders dated in the period, with the day each was last received
+    pub fn purchase_orders(conn: &mut SqliteConnection, filters: &ReportFilters) -> Result<Vec<PurchaseOrderRow>> {
+        let mut query = purchase_orders::table
+            .inner_join(suppliers::table)
+            .select((
+                purchase_orders::id,
+                purchase_orders::po_number,
+                suppliers::id,
+                suppliers::name,
+                suppliers::payment_terms,
+                purchase_orders::order_date,
+                purchase_orders::expected_date,
+                purchase_orders::status,
+                purchase_orders::total_amount,
+            ))
+            .order(purchase_orders::order_date.asc())
+            .into_boxed();
+        if let Some(range) = &filters.range {
+            query = query
+                .filter(purchase_orders::order_date.ge(range.start_date))
+                .filter(purchase_orders::order_date.le(range.end_date));
+        }
+        if let Some(supplier_id) = filters.supplier_id {
+            query = query.filter(purchase_orders::supplier_id.eq(supplier_id));
+        }
+        let orders = query
+            .load::<(i32, String, i32, String, Option<String>, NaiveDate, Option<NaiveDate>, String, i32)>(conn)?;
+
+        let receipts: HashMap<i32, NaiveDateTime> = stock_movements::table
+            .filter(stock_movements::reference_type.eq("purchase_order"))
+            .filter(stock_movements::reference_id.eq_any(orders.iter().map(|order| order.0).collect::<Vec<_>>()))
+            .group_by(stock_movements::reference_id)
+            .select((stock_movements::reference_id, diesel::dsl::max(stock_movements::movement_date)))
+            .load::<(Option<i32>, Option<NaiveDateTime>)>(conn)?
+            .into_iter()
+            .filter_map(|(id, received)| Some((id?, received?)))
+            .collect();
+
+        Ok(orders
+            .into_iter()
+            .map(
+                |(id, po_number, supplier_id, supplier, payment_terms, order_date, expected_date, status, total_amount)| {
+                    PurchaseOrderRow {
+                        po_number,
+                        supplier_id,
+                        supplier,
+                        payment_terms,
+                        order_date,
+                        expected_date,
+                        status,
+                        total_amount,
+                        received_on: receipts.get(&id).map(|at| at.date()),
+                    }
+                },
+            )
+            .collect())
+    }
+
+    /// Debits and credits of active accounts of the filtered types between
+    /// two dates, inclusive; `None` is unbounded. Accounts with no postings
+    /// are left out.
+    pub fn account_totals(
+        conn: &mut SqliteConnection,
+        filters: &ReportFilters,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<Vec<AccountRow>> {
+        let mut query = accounts::table
+            .filter(accounts::is_active.eq(true))
+            .select(Account::as_select())
+            .order(accounts::account_code.asc())
+            .into_boxed();
+        if !filters.account_types.is_empty() {
+            query = query.filter(accounts::account_type.eq_any(filters.account_types.clone()));
+        }
+
+        let mut rows = Vec::new();
+        for account in query.load::<Account>(conn)? {
+            let (debits, credits) = SummaryService::account_totals(conn, account.id, from, to)?;
+            if debits != 0 || credits != 0 {
+                rows.push(AccountRow {
+                    account_code: account.account_code,
+                    account_name: account.account_name,
+                    account_type: account.account_type,
+                    debits,
+                    credits,
+                });
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Products ever ordered from a supplier
+    fn supplied_products(conn: &mut SqliteConnection, supplier_id: i32) -> Result<Vec<i32>> {
+        Ok(purchase_items::table
+            .inner_join(purchase_orders::table)
+            .filter(purchase_orders::supplier_id.eq(supplier_id))
+            .select(purchase_items::product_id)
+            .distinct()
+            .load::<i32>(conn)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filters_and_stock_level() {
+        assert_eq!(StockLevel::of(0, 5, Some(50)), StockLevel::Out);
+        assert_eq!(StockLevel::of(5, 5, Some(50)), StockLevel::Low);
+        assert_eq!(StockLevel::of(20, 5, Some(50)), StockLevel::Normal);
+        assert_eq!(StockLevel::of(60, 5, Some(50)), StockLevel::Overstocked);
+        assert_eq!(StockLevel::of(60, 5, None), StockLevel::Normal);
+
+        let mut config = ReportConfig {
+            title: "stock_status".to_string(),
+            description: None,
+            date_range: None,
+            filters: HashMap::new(),
+            format: super::super::engine::ReportFormat::Text,
+            include_charts: false,
+            include_summary: true,
+        };
+        config.filters.insert("stock_level".to_string(), "all".to_string());
+        config.filters.insert("deal_stage".to_string(), "proposal, closed_won".to_string());
+        config.filters.insert("category_id".to_string(), "3".to_string());
+        let filters = ReportFilters::from_config(&config).unwrap();
+        assert_eq!(filters.stock_level, None);
+        assert_eq!(filters.deal_stages, ["proposal", "closed_won"]);
+        assert_eq!(filters.category_id, Some(3));
+        assert!(filters.range.is_none());
+
+        config.filters.insert("movement_type".to_string(), "transfer".to_string());
+        assert!(ReportFilters::from_config(&config).is_err());
+    }
+}
@@ -1,7 +1,11 @@
 use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 use crate::core::result::CLIERPResult;
+use crate::database::DatabaseManager;
+use crate::utils::cache::{QueryCache, REPORTS_PREFIX};
+use crate::utils::pdf::{text_width, wrap_text, PdfDocument, PdfFont, PdfPage, A4_HEIGHT, A4_WIDTH};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReportConfig {
@@ -20,6 +24,8 @@ pub enum ReportFormat {
     Csv,
     Html,
     Text,
+    /// Printable A4 pages; written to a file, not the terminal
+    Pdf,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +34,14 @@ pub struct DateRange {
     pub end_date: NaiveDate,
 }
 
+impl DateRange {
+    /// Half-open UTC timestamp bounds for filtering stored timestamps, with
+    /// the inclusive dates interpreted in the display timezone
+    pub fn utc_bounds(&self) -> (chrono::NaiveDateTime, chrono::NaiveDateTime) {
+        crate::utils::timezone::DisplayTimezone::current().date_range_utc(self.start_date, self.end_date)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReportResult {
     pub config: ReportConfig,
@@ -108,7 +122,7 @@ impl std::fmt::Display for MetricValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             MetricValue::Number(n) => write!(f, "{:.2}", n),
-            MetricValue::Currency(c) => write!(f, "${:.2}", *c as f64 / 100.0),
+            MetricValue::Currency(c) => write!(f, "{}", format_currency(*c)),
             MetricValue::Percentage(p) => write!(f, "{:.1}%", p),
             MetricValue::Count(c) => write!(f, "{}", c),
             MetricValue::Text(t) => write!(f, "{}", t),
@@ -124,7 +138,7 @@ pub struct ReportMetadata {
     pub data_sources: Vec<String>,
 }
 
-pub trait ReportGenerator {
+pub trait ReportGenerator: Send + Sync {
     fn generate_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult>;
     fn get_available_filters(&self) -> Vec<FilterDefinition>;
     fn get_report_info(&self) -> ReportInfo;
@@ -166,7 +180,14 @@ pub struct ReportInfo {
 }
 
 pub struct ReportEngine {
-    generators: HashMap<String, Box<dyn ReportGenerator>>,
+    generators: HashMap<String, Arc<dyn ReportGenerator>>,
+}
+
+/// One report of a pack: the generator id and the config passed to it
+#[derive(Debug, Clone)]
+pub struct ReportRequest {
+    pub report_id: String,
+    pub config: ReportConfig,
 }
 
 impl ReportEngine {
@@ -176,17 +197,63 @@ impl ReportEngine {
         }
     }
 
+    /// Engine with the HR, finance, inventory and CRM generators registered
+    pub fn with_default_generators() -> Self {
+        let mut engine = Self::new();
+        engine.register_generator("hr".to_string(), super::HRReportsGenerator);
+        engine.register_generator("finance".to_string(), super::FinanceReportsGenerator);
+        engine.register_generator("inventory".to_string(), super::InventoryReportsGenerator);
+        engine.register_generator("crm".to_string(), super::CRMReportsGenerator);
+        engine
+    }
+
     pub fn register_generator<T: ReportGenerator + 'static>(&mut self, id: String, generator: T) {
-        self.generators.insert(id, Box::new(generator));
+        self.generators.insert(id, Arc::new(generator));
     }
 
+    /// Generate a report, reusing a cached run with the same config
     pub fn generate_report(&self, report_id: &str, config: ReportConfig) -> CLIERPResult<ReportResult> {
-        let generator = self.generators.get(report_id)
-            .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
-                format!("Report generator '{}' not found", report_id)
-            ))?;
+        let generator = self.generator(report_id)?;
+        let key = report_cache_key(report_id, &config);
+        QueryCache::current().get_or_compute(&key, || generator.generate_report(config))
+    }
+
+    /// Generate several reports concurrently on the blocking thread pool.
+    ///
+    /// Diesel is synchronous, so each report runs in `spawn_blocking` with
+    /// its own pooled connection. Results come back in request order; one
+    /// failing report does not stop the others.
+    pub async fn generate_reports(&self, requests: Vec<ReportRequest>) -> Vec<CLIERPResult<ReportResult>> {
+        // Generators run on blocking threads, which see the pool of a
+        // database scope on this thread only if it is passed along
+        let pool = DatabaseManager::get_pool().ok();
+        let mut handles = Vec::with_capacity(requests.len());
+        for request in requests {
+            let handle = self.generator(&request.report_id).map(|generator| {
+                let pool = pool.clone();
+                tokio::task::spawn_blocking(move || {
+                    let _scope = pool.map(DatabaseManager::scope);
+                    let key = report_cache_key(&request.report_id, &request.config);
+                    QueryCache::current().get_or_compute(&key, || generator.generate_report(request.config))
+                })
+            });
+            handles.push(handle);
+        }
 
-        generator.generate_report(config)
+        let mut results = Vec::with_capacity(handles.len());
+        for handle in handles {
+            let result = match handle {
+                Ok(handle) => handle.await.unwrap_or_else(|e| {
+                    Err(crate::core::error::CLIERPError::Internal(format!(
+                        "Report generation task failed: {}",
+                        e
+                    )))
+                }),
+                Err(e) => Err(e),
+            };
+            results.push(result);
+        }
+        results
     }
 
     pub fn list_available_reports(&self) -> Vec<ReportInfo> {
@@ -199,6 +266,21 @@ impl ReportEngine {
         self.generators.get(report_id)
             .map(|generator| generator.get_available_filters())
     }
+
+    fn generator(&self, report_id: &str) -> CLIERPResult<Arc<dyn ReportGenerator>> {
+        self.generators.get(report_id)
+            .cloned()
+            .ok_or_else(|| crate::core::error::CLIERPError::NotFound(
+                format!("Report generator '{}' not found", report_id)
+            ))
+    }
+}
+
+/// Query cache key of a report run. Going through `serde_json::Value` sorts
+/// the filter map, so equal configs always produce the same key.
+fn report_cache_key(report_id: &str, config: &ReportConfig) -> String {
+    let config = serde_json::to_value(config).unwrap_or_default();
+    format!("{}{}.{}", REPORTS_PREFIX, report_id, config)
 }
 
 impl Default for ReportEngine {
@@ -207,6 +289,63 @@ impl Default for ReportEngine {
     }
 }
 
+/// Builds one report section, typically from its own queries
+pub type SectionTask<'a> = Box<dyn FnOnce() -> CLIERPResult<ReportSection> + Send + 'a>;
+
+/// Run independent section builders on parallel threads and return the
+/// sections in the order given. Each builder should take its own pooled
+/// connection; the first error is returned once all builders finish.
+pub fn build_sections(tasks: Vec<SectionTask<'_>>) -> CLIERPResult<Vec<ReportSection>> {
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = tasks.into_iter().map(|task| scope.spawn(task)).collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle.join().unwrap_or_else(|_| {
+                    Err(crate::core::error::CLIERPError::Internal(
+                        "Report section builder panicked".to_string(),
+                    ))
+                })
+            })
+            .collect()
+    })
+}
+
+/// A numeric report filter, if given
+pub fn filter_i32(config: &ReportConfig, name: &str) -> CLIERPResult<Option<i32>> {
+    config
+        .filters
+        .get(name)
+        .map(|value| {
+            value.parse::<i32>().map_err(|_| {
+                crate::core::error::CLIERPError::ValidationError(format!(
+                    "Filter {} must be a number, got '{}'",
+                    name, value
+                ))
+            })
+        })
+        .transpose()
+}
+
+/// Filters and date range of a report, as `name=value` for its metadata
+pub fn applied_filters(config: &ReportConfig) -> Vec<String> {
+    let mut filters: Vec<String> = config
+        .filters
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect();
+    if let Some(range) = &config.date_range {
+        filters.push(format!("date_range={}..{}", range.start_date, range.end_date));
+    }
+    filters.sort();
+    filters
+}
+
+/// Table headers from column names
+pub fn header_row(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
 // Helper functions for report formatting
 pub fn format_table_data(
     headers: Vec<String>,
@@ -234,13 +373,22 @@ pub fn format_table_data(
 }
 
 pub fn format_currency(amount: i32) -> String {
-    format!("₩{}", amount)
+    crate::utils::formatting::format_currency(amount)
 }
 
 pub fn format_percentage(value: f64) -> String {
     format!("{:.1}%", value)
 }
 
+/// `part` as a percentage of `whole`, 0 when there is nothing
+pub fn share(part: i64, whole: i64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / whole as f64
+    }
+}
+
 pub fn format_metric_value(metric: &MetricValue) -> String {
     match metric {
         MetricValue::Number(n) => format!("{:.2}", n),
@@ -282,4 +430,348 @@ pub fn create_line_chart(labels: Vec<String>, datasets: Vec<Dataset>) -> ChartDa
         labels,
         datasets,
     }
-}
\ No newline at end of file
+}
+
+// PDF layout, in points
+const PDF_MARGIN: f32 = 40.0;
+const PDF_TEXT_SIZE: f32 = 8.0;
+const PDF_LINE_HEIGHT: f32 = 11.0;
+const PDF_CELL_PADDING: f32 = 6.0;
+
+/// Reports laid out on A4 pages for printing, each starting on a new page.
+/// Charts are printed as tables of their values.
+pub fn render_pdf(results: &[&ReportResult]) -> Vec<u8> {
+    let mut doc = PdfDocument::new();
+    for result in results {
+        let mut writer = PdfWriter::new(&mut doc, result.config.title.replace('_', " "));
+        writer.report(result);
+    }
+    doc.to_bytes()
+}
+
+/// Cursor running down the pages of one report
+struct PdfWriter<'a> {
+    doc: &'a mut PdfDocument,
+    title: String,
+    y: f32,
+}
+
+impl<'a> PdfWriter<'a> {
+    fn new(doc: &'a mut PdfDocument, title: String) -> Self {
+        let mut writer = Self { doc, title, y: 0.0 };
+        writer.new_page();
+        writer
+    }
+
+    fn new_page(&mut self) {
+        let page_number = self.doc.page_count() + 1;
+        let page = self.doc.new_page();
+        let top = A4_HEIGHT - PDF_MARGIN;
+        page.text(PDF_MARGIN, top - 14.0, 14.0, PdfFont::Bold, &self.title);
+        page.line(PDF_MARGIN, top - 22.0, A4_WIDTH - PDF_MARGIN, top - 22.0);
+        page.text(A4_WIDTH - PDF_MARGIN - 40.0, PDF_MARGIN / 2.0, 7.0, PdfFont::Regular, &format!("Page {}", page_number));
+        self.y = top - 40.0;
+    }
+
+    /// Start a new page unless `height` more fits on this one
+    fn reserve(&mut self, height: f32) {
+        if self.y - height < PDF_MARGIN {
+            self.new_page();
+        }
+    }
+
+    fn page(&mut self) -> &mut PdfPage {
+        self.doc.last_page().expect("writer starts with a page")
+    }
+
+    fn report(&mut self, result: &ReportResult) {
+        let mut subtitle = format!("Generated {}", result.generated_at.format("%Y-%m-%d %H:%M"));
+        if let Some(date_range) = &result.config.date_range {
+            subtitle.push_str(&format!("  ·  Period {} to {}", date_range.start_date, date_range.end_date));
+        }
+        self.paragraph(&subtitle, PdfFont::Regular);
+        if let Some(description) = &result.config.description {
+            self.paragraph(description, PdfFont::Regular);
+        }
+        self.y -= PDF_LINE_HEIGHT;
+
+        match &result.data {
+            ReportData::Mixed(sections) => {
+                for section in sections {
+                    self.heading(&section.title);
+                    self.data(&section.data);
+                }
+            }
+            data => self.data(data),
+        }
+
+        if let Some(summary) = &result.summary {
+            self.heading("Summary");
+            let mut metrics: Vec<(&String, &MetricValue)> = summary.key_metrics.iter().collect();
+            metrics.sort_by(|a, b| a.0.cmp(b.0));
+            if !metrics.is_empty() {
+                self.table(&TableData {
+                    headers: header_row(&["Metric", "Value"]),
+                    rows: metrics
+                        .into_iter()
+                        .map(|(key, value)| vec![key.replace('_', " "), format_metric_value(value)])
+                        .collect(),
+                    totals: None,
+                });
+            }
+            for (label, lines) in [("Key Insights", &summary.insights), ("Recommendations", &summary.recommendations)] {
+                if lines.is_empty() {
+                    continue;
+                }
+                self.reserve(2.0 * PDF_LINE_HEIGHT);
+                self.paragraph(label, PdfFont::Bold);
+                for line in lines {
+                    self.paragraph(&format!("• {}", line), PdfFont::Regular);
+                }
+            }
+        }
+    }
+
+    fn heading(&mut self, text: &str) {
+        // Keep a heading with at least the first lines below it
+        self.reserve(5.0 * PDF_LINE_HEIGHT);
+        self.y -= 4.0;
+        let y = self.y;
+        self.page().text(PDF_MARGIN, y, 11.0, PdfFont::Bold, text);
+        self.y -= PDF_LINE_HEIGHT + 6.0;
+    }
+
+    fn paragraph(&mut self, text: &str, font: PdfFont) {
+        for line in wrap_text(text, PDF_TEXT_SIZE, A4_WIDTH - 2.0 * PDF_MARGIN) {
+            self.reserve(PDF_LINE_HEIGHT);
+            let y = self.y;
+            self.page().text(PDF_MARGIN, y, PDF_TEXT_SIZE, font, &line);
+            self.y -= PDF_LINE_HEIGHT;
+        }
+    }
+
+    fn data(&mut self, data: &ReportData) {
+        match data {
+            ReportData::Table(table) => self.table(table),
+            ReportData::Chart(chart) => {
+                let mut headers = vec![String::new()];
+                headers.extend(chart.datasets.iter().map(|d| d.label.clone()));
+                let rows = chart
+                    .labels
+                    .iter()
+                    .enumerate()
+                    .map(|(i, label)| {
+                        let mut row = vec![label.clone()];
+                        row.extend(chart.datasets.iter().map(|d| d.data.get(i).map(|v| format!("{:.2}", v)).unwrap_or_default()));
+                        row
+                    })
+                    .collect();
+                self.table(&TableData { headers, rows, totals: None });
+            }
+            ReportData::Mixed(sections) => {
+                for section in sections {
+                    self.heading(&section.title);
+                    self.data(&section.data);
+                }
+            }
+        }
+        self.y -= PDF_LINE_HEIGHT;
+    }
+
+    /// Table with its header repeated on every page it runs onto
+    fn table(&mut self, table: &TableData) {
+        if table.headers.is_empty() {
+            return;
+        }
+        let natural: Vec<f32> = (0..table.headers.len())
+            .map(|column| {
+                std::iter::once(&table.headers)
+                    .chain(&table.rows)
+                    .chain(&table.totals)
+                    .filter_map(|row| row.get(column))
+                    .map(|cell| text_width(cell, PDF_TEXT_SIZE) + PDF_CELL_PADDING)
+                    .fold(0.0, f32::max)
+            })
+            .collect();
+        let widths = pdf_column_widths(&natural, A4_WIDTH - 2.0 * PDF_MARGIN);
+        let right = PDF_MARGIN + widths.iter().sum::<f32>();
+
+        self.reserve(3.0 * PDF_LINE_HEIGHT);
+        self.table_header(&table.headers, &widths, right);
+        for row in &table.rows {
+            if self.y - PDF_LINE_HEIGHT < PDF_MARGIN {
+                self.new_page();
+                self.table_header(&table.headers, &widths, right);
+            }
+            self.table_row(row, &widths, PdfFont::Regular);
+        }
+        if let Some(totals) = &table.totals {
+            self.reserve(PDF_LINE_HEIGHT);
+            let y = self.y + PDF_LINE_HEIGHT - 3.0;
+            self.page().line(PDF_MARGIN, y, right, y);
+            self.table_row(totals, &widths, PdfFont::Bold);
+        }
+    }
+
+    fn table_header(&mut self, headers: &[String], widths: &[f32], right: f32) {
+        self.table_row(headers, widths, PdfFont::Bold);
+        let y = self.y + PDF_LINE_HEIGHT - 3.0;
+        self.page().line(PDF_MARGIN, y, right, y);
+    }
+
+    fn table_row(&mut self, cells: &[String], widths: &[f32], font: PdfFont) {
+        let y = self.y;
+        let mut x = PDF_MARGIN;
+        let page = self.page();
+        for (cell, width) in cells.iter().zip(widths) {
+            let text = fit_text(cell, PDF_TEXT_SIZE, width - PDF_CELL_PADDING);
+            let offset = if is_numeric_cell(cell) {
+                width - PDF_CELL_PADDING / 2.0 - text_width(&text, PDF_TEXT_SIZE)
+            } else {
+                PDF_CELL_PADDING / 2.0
+            };
+            page.text(x + offset, y, PDF_TEXT_SIZE, font, &text);
+            x += width;
+        }
+        self.y -= PDF_LINE_HEIGHT;
+    }
+}
+
+/// Column widths in points: the natural widths when they fit, else the
+/// wide columns capped at the width that makes the table fit
+pub fn pdf_column_widths(natural: &[f32], available: f32) -> Vec<f32> {
+    if natural.iter().sum::<f32>() <= available {
+        return natural.to_vec();
+    }
+    let mut sorted = natural.to_vec();
+    sorted.sort_by(f32::total_cmp);
+    let mut remaining = available;
+    let mut cap = available / sorted.len() as f32;
+    for (i, width) in sorted.iter().enumerate() {
+        let fair = remaining / (sorted.len() - i) as f32;
+        if *width > fair {
+            cap = fair;
+            break;
+        }
+        remaining -= width;
+    }
+    natural.iter().map(|width| width.min(cap)).collect()
+}
+
+/// `text` cut with an ellipsis to fit `width`
+fn fit_text(text: &str, size: f32, width: f32) -> String {
+    if text_width(text, size) <= width {
+        return text.to_string();
+    }
+    let mut fitted = String::new();
+    for c in text.chars() {
+        if text_width(&fitted, size) + text_width("…", size) * 2.0 > width {
+            break;
+        }
+        fitted.push(c);
+    }
+    fitted.push('…');
+    fitted
+}
+
+/// Amounts, counts and percentages are right-aligned
+fn is_numeric_cell(cell: &str) -> bool {
+    let cell = cell.trim();
+    // A dash past the sign makes it a date or a range
+    cell.chars().any(|c| c.is_ascii_digit())
+        && !cell.chars().skip(1).any(|c| c == '-')
+        && cell.chars().all(|c| c.is_ascii_digit() || matches!(c, ',' | '.' | '-' | '+' | '%' | '₩' | '$' | '€' | ' '))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::CompanyFixture;
+
+    fn config(title: &str) -> ReportConfig {
+        ReportConfig {
+            title: title.to_string(),
+            description: None,
+            date_range: None,
+            filters: HashMap::new(),
+            format: ReportFormat::Text,
+            include_charts: false,
+            include_summary: true,
+        }
+    }
+
+    fn section(title: &str) -> ReportSection {
+        ReportSection {
+            title: title.to_string(),
+            section_type: SectionType::Summary,
+            data: ReportData::Table(TableData {
+                headers: Vec::new(),
+                rows: Vec::new(),
+                totals: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_build_sections_keeps_order() {
+        let sections = build_sections(vec![
+            Box::new(|| {
+                std::thread::sleep(std::time::Duration::from_millis(20));
+                Ok(section("first"))
+            }),
+            Box::new(|| Ok(section("second"))),
+        ])
+        .unwrap();
+        let titles: Vec<&str> = sections.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, vec!["first", "second"]);
+
+        let failed = build_sections(vec![
+            Box::new(|| Ok(section("ok"))),
+            Box::new(|| Err(crate::core::error::CLIERPError::Internal("boom".to_string()))),
+        ]);
+        assert!(failed.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_generate_reports_in_request_order() {
+        // The finance reports read the ledger
+        let _company = CompanyFixture::new().build().unwrap();
+        let engine = ReportEngine::with_default_generators();
+        let results = engine
+            .generate_reports(vec![
+                ReportRequest { report_id: "finance".to_string(), config: config("balance_sheet") },
+                ReportRequest { report_id: "missing".to_string(), config: config("anything") },
+                ReportRequest { report_id: "finance".to_string(), config: config("income_statement") },
+            ])
+            .await;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().config.title, "balance_sheet");
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap().config.title, "income_statement");
+    }
+
+    #[test]
+    fn test_pdf_column_widths() {
+        assert_eq!(pdf_column_widths(&[50.0, 100.0], 300.0), vec![50.0, 100.0]);
+        // The narrow column keeps its width, the two wide ones share the rest
+        assert_eq!(pdf_column_widths(&[50.0, 400.0, 300.0], 450.0), vec![50.0, 200.0, 200.0]);
+        assert_eq!(pdf_column_widths(&[300.0, 300.0], 300.0), vec![150.0, 150.0]);
+        assert!(is_numeric_cell("₩1,250,000") && is_numeric_cell("-12.5%"));
+        assert!(!is_numeric_cell("2025-03-01") && !is_numeric_cell("Widget"));
+    }
+
+    #[test]
+    fn test_report_cache_key_ignores_filter_order() {
+        let mut first = config("sales_pipeline");
+        first.filters.insert("region".to_string(), "north".to_string());
+        first.filters.insert("owner".to_string(), "7".to_string());
+        let mut second = config("sales_pipeline");
+        second.filters.insert("owner".to_string(), "7".to_string());
+        second.filters.insert("region".to_string(), "north".to_string());
+
+        assert_eq!(report_cache_key("crm", &first), report_cache_key("crm", &second));
+        assert_ne!(report_cache_key("crm", &first), report_cache_key("hr", &first));
+        assert!(report_cache_key("crm", &first).starts_with(REPORTS_PREFIX));
+    }
+}
@@ -1,7 +1,20 @@
-use chrono::Utc;
-use std::collections::HashMap;
+use chrono::{Datelike, Duration, Months, NaiveDate, Utc};
+use std::collections::{BTreeMap, HashMap};
 use crate::core::result::CLIERPResult;
+use crate::database::get_report_connection;
+use crate::modules::finance::{CashFlowItem, ReportService};
+use crate::utils::dates::parse_period;
+use crate::utils::formatting::format_currency as format_amount;
+use crate::utils::timezone::DisplayTimezone;
+use super::data_source::*;
 use super::engine::*;
+use super::margin::{CostingMethod, GrossMarginService, MarginRow};
+
+/// Lowest-margin products listed under margin watch
+const MARGIN_WATCH_PRODUCTS: usize = 10;
+
+/// Cash movements listed under largest cash movements
+const LARGEST_CASH_MOVEMENTS: usize = 10;
 
 pub struct FinanceReportsGenerator;
 
@@ -13,6 +26,7 @@ impl ReportGenerator for FinanceReportsGenerator {
             "cash_flow" => self.generate_cash_flow_statement(config),
             "budget_vs_actual" => self.generate_budget_vs_actual_report(config),
             "financial_analytics" => self.generate_financial_analytics(config),
+            "gross_margin" => self.generate_gross_margin_report(config),
             _ => Err(crate::core::error::CLIERPError::NotFound(
                 format!("Finance report '{}' not found", config.title)
             )),
@@ -33,6 +47,14 @@ impl ReportGenerator for FinanceReportsGenerator {
                     FilterOption { value: "yearly".to_string(), label: "Yearly".to_string() },
                 ]),
             },
+            FilterDefinition {
+                name: "fiscal_period".to_string(),
+                label: "Fiscal Period (FY2025, FY2025-Q2, FY2025-P03)".to_string(),
+                filter_type: FilterType::Text,
+                required: false,
+                default_value: None,
+                options: None,
+            },
             FilterDefinition {
                 name: "account_type".to_string(),
                 label: "Account Type".to_string(),
@@ -47,6 +69,19 @@ impl ReportGenerator for FinanceReportsGenerator {
                     FilterOption { value: "expense".to_string(), label: "Expenses".to_string() },
                 ]),
             },
+            FilterDefinition {
+                name: "valuation_method".to_string(),
+                label: "Costing Method (gross margin)".to_string(),
+                filter_type: FilterType::Select,
+                required: false,
+                default_value: Some("fifo".to_string()),
+                options: Some(vec![
+                    FilterOption { value: "fifo".to_string(), label: "FIFO".to_string() },
+                    FilterOption { value: "lifo".to_string(), label: "LIFO".to_string() },
+                    FilterOption { value: "average".to_string(), label: "Weighted Average".to_string() },
+                    FilterOption { value: "standard".to_string(), label: "Standard Cost".to_string() },
+                ]),
+            },
             FilterDefinition {
                 name: "comparison".to_string(),
                 label: "Compare with Previous Period".to_string(),
@@ -69,6 +104,7 @@ impl ReportGenerator for FinanceReportsGenerator {
                 ReportFormat::Csv,
                 ReportFormat::Html,
                 ReportFormat::Text,
+                ReportFormat::Pdf,
             ],
         }
     }
@@ -80,568 +116,529 @@ impl FinanceReportsGenerator {
     }
 
     fn generate_income_statement(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let filters = ReportFilters::from_config(&config)?;
+        let (start, end) = filters.period_or(parse_period("this-month")?);
+        let mut conn = get_report_connection()?;
+        let current = ReportDataSource::account_totals(&mut conn, &filters, Some(start), Some(end))?;
+        let previous = match filters.comparison {
+            true => {
+                let (from, to) = previous_period(start, end);
+                Some(ReportDataSource::account_totals(&mut conn, &filters, Some(from), Some(to))?)
+            }
+            false => None,
+        };
+
+        let revenue_lines = account_lines(&["revenue"], &current, previous.as_deref());
+        let expense_lines = account_lines(&["expense"], &current, previous.as_deref());
+        let totals = Totals::of(&current);
+        let previous_totals = previous.as_deref().map(Totals::of);
         let sections = vec![
             ReportSection {
                 title: "Revenue".to_string(),
                 section_type: SectionType::Detail,
-                data: ReportData::Table(TableData {
-                    headers: vec![
-                        "Account".to_string(),
-                        "Current Period".to_string(),
-                        "Previous Period".to_string(),
-                        "Change".to_string(),
-                        "% Change".to_string(),
-                    ],
-                    rows: vec![
-                        vec![
-                            "Sales Revenue".to_string(),
-                            "₩850,000,000".to_string(),
-                            "₩780,000,000".to_string(),
-                            "₩70,000,000".to_string(),
-                            "+9.0%".to_string(),
-                        ],
-                        vec![
-                            "Service Revenue".to_string(),
-                            "₩245,000,000".to_string(),
-                            "₩220,000,000".to_string(),
-                            "₩25,000,000".to_string(),
-                            "+11.4%".to_string(),
-                        ],
-                        vec![
-                            "Other Income".to_string(),
-                            "₩15,000,000".to_string(),
-                            "₩12,000,000".to_string(),
-                            "₩3,000,000".to_string(),
-                            "+25.0%".to_string(),
-                        ],
-                    ],
-                    totals: Some(vec![
-                        "Total Revenue".to_string(),
-                        "₩1,110,000,000".to_string(),
-                        "₩1,012,000,000".to_string(),
-                        "₩98,000,000".to_string(),
-                        "+9.7%".to_string(),
-                    ]),
-                }),
+                data: ReportData::Table(statement_table(&revenue_lines, "Total Revenue", filters.comparison)),
             },
             ReportSection {
                 title: "Expenses".to_string(),
                 section_type: SectionType::Detail,
-                data: ReportData::Table(TableData {
-                    headers: vec![
-                        "Account".to_string(),
-                        "Current Period".to_string(),
-                        "Previous Period".to_string(),
-                        "Change".to_string(),
-                        "% Change".to_string(),
-                    ],
-                    rows: vec![
-                        vec![
-                            "Cost of Goods Sold".to_string(),
-                            "₩455,000,000".to_string(),
-                            "₩420,000,000".to_string(),
-                            "₩35,000,000".to_string(),
-                            "+8.3%".to_string(),
-                        ],
-                        vec![
-                            "Salaries & Benefits".to_string(),
-                            "₩320,000,000".to_string(),
-                            "₩305,000,000".to_string(),
-                            "₩15,000,000".to_string(),
-                            "+4.9%".to_string(),
-                        ],
-                        vec![
-                            "Operating Expenses".to_string(),
-                            "₩180,000,000".to_string(),
-                            "₩175,000,000".to_string(),
-                            "₩5,000,000".to_string(),
-                            "+2.9%".to_string(),
-                        ],
-                        vec![
-                            "Depreciation".to_string(),
-                            "₩25,000,000".to_string(),
-                            "₩25,000,000".to_string(),
-                            "₩0".to_string(),
-                            "0.0%".to_string(),
-                        ],
-                    ],
-                    totals: Some(vec![
-                        "Total Expenses".to_string(),
-                        "₩980,000,000".to_string(),
-                        "₩925,000,000".to_string(),
-                        "₩55,000,000".to_string(),
-                        "+5.9%".to_string(),
-                    ]),
-                }),
+                data: ReportData::Table(statement_table(&expense_lines, "Total Expenses", filters.comparison)),
             },
         ];
 
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("total_revenue".to_string(), MetricValue::Currency(1110000000));
-        key_metrics.insert("total_expenses".to_string(), MetricValue::Currency(980000000));
-        key_metrics.insert("net_income".to_string(), MetricValue::Currency(130000000));
-        key_metrics.insert("gross_margin".to_string(), MetricValue::Percentage(59.0));
-        key_metrics.insert("net_margin".to_string(), MetricValue::Percentage(11.7));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "Revenue increased by 9.7% compared to previous period".to_string(),
-                "Gross margin improved due to better cost management".to_string(),
-                "Operating efficiency increased with controlled expense growth".to_string(),
-                "Service revenue showed strongest growth at 11.4%".to_string(),
-            ],
-            recommendations: vec![
-                "Continue focus on high-margin service offerings".to_string(),
-                "Optimize cost of goods sold through supplier negotiations".to_string(),
-                "Monitor salary growth relative to revenue growth".to_string(),
-            ],
-        };
+        key_metrics.insert("total_revenue".to_string(), MetricValue::Text(format_amount(totals.revenue)));
+        key_metrics.insert("total_expenses".to_string(), MetricValue::Text(format_amount(totals.expenses)));
+        key_metrics.insert("net_income".to_string(), MetricValue::Text(format_amount(totals.net_income())));
+        key_metrics.insert(
+            "net_margin".to_string(),
+            MetricValue::Percentage(share(totals.net_income(), totals.revenue)),
+        );
 
-        let metadata = ReportMetadata {
-            total_records: 245,
-            processing_time_ms: 180,
-            filters_applied: vec!["current_month".to_string(), "comparison_enabled".to_string()],
-            data_sources: vec!["accounts".to_string(), "transactions".to_string()],
-        };
+        let mut insights = vec![format!(
+            "Net income of {} on revenue of {}, {} to {}",
+            format_amount(totals.net_income()),
+            format_amount(totals.revenue),
+            start,
+            end
+        )];
+        if let Some(previous) = previous_totals {
+            insights.push(format!(
+                "Revenue {} and expenses {} against the previous period",
+                change(totals.revenue, previous.revenue),
+                change(totals.expenses, previous.expenses)
+            ));
+        }
+        if let Some((label, amount, _)) = expense_lines.iter().max_by_key(|line| line.1).filter(|line| line.1 > 0) {
+            insights.push(format!(
+                "{} is the largest expense at {} of the total",
+                label,
+                format_percentage(share(*amount, totals.expenses))
+            ));
+        }
+        let mut recommendations = Vec::new();
+        if totals.net_income() < 0 {
+            recommendations.push(format!(
+                "Expenses exceed revenue by {}; review the largest expense accounts",
+                format_amount(-totals.net_income())
+            ));
+        }
+        if let Some(previous) = previous_totals {
+            if growth(totals.expenses, previous.expenses) > growth(totals.revenue, previous.revenue) + 5.0 {
+                recommendations.push("Expenses are growing faster than revenue".to_string());
+            }
+        }
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
             data: ReportData::Mixed(sections),
-            summary: Some(summary),
-            metadata,
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: current.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["accounts".to_string(), "transactions".to_string(), "summary_account_monthly".to_string()],
+            },
         })
     }
 
     fn generate_balance_sheet(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let filters = ReportFilters::from_config(&config)?;
+        let (start, end) = filters.period_or(parse_period("this-month")?);
+        let as_of = end.min(DisplayTimezone::current().today());
+        let mut conn = get_report_connection()?;
+        let current = ReportDataSource::account_totals(&mut conn, &filters, None, Some(as_of))?;
+        let previous = match filters.comparison {
+            true => {
+                let (_, previous_end) = previous_period(start, end);
+                Some(ReportDataSource::account_totals(&mut conn, &filters, None, Some(previous_end))?)
+            }
+            false => None,
+        };
+
+        let totals = Totals::of(&current);
+        let previous_totals = previous.as_deref().map(Totals::of);
+        let asset_lines = account_lines(&["asset"], &current, previous.as_deref());
+        let mut funding_lines = account_lines(&["liability", "equity"], &current, previous.as_deref());
+        // Revenue less expenses not yet closed to an equity account
+        if totals.net_income() != 0 || previous_totals.is_some_and(|p| p.net_income() != 0) {
+            funding_lines.push((
+                "Current Earnings".to_string(),
+                totals.net_income(),
+                previous_totals.map(|p| p.net_income()),
+            ));
+        }
+        let equity = totals.equity + totals.net_income();
+        let difference = totals.assets - totals.liabilities - equity;
+
         let sections = vec![
             ReportSection {
-                title: "Assets".to_string(),
+                title: format!("Assets as of {}", as_of),
                 section_type: SectionType::Detail,
-                data: ReportData::Table(TableData {
-                    headers: vec![
-                        "Account".to_string(),
-                        "Current".to_string(),
-                        "Previous".to_string(),
-                        "Change".to_string(),
-                    ],
-                    rows: vec![
-                        vec![
-                            "Cash and Cash Equivalents".to_string(),
-                            "₩450,000,000".to_string(),
-                            "₩380,000,000".to_string(),
-                            "₩70,000,000".to_string(),
-                        ],
-                        vec![
-                            "Accounts Receivable".to_string(),
-                            "₩320,000,000".to_string(),
-                            "₩295,000,000".to_string(),
-                            "₩25,000,000".to_string(),
-                        ],
-                        vec![
-                            "Inventory".to_string(),
-                            "₩180,000,000".to_string(),
-                            "₩165,000,000".to_string(),
-                            "₩15,000,000".to_string(),
-                        ],
-                        vec![
-                            "Property, Plant & Equipment".to_string(),
-                            "₩850,000,000".to_string(),
-                            "₩875,000,000".to_string(),
-                            "-₩25,000,000".to_string(),
-                        ],
-                    ],
-                    totals: Some(vec![
-                        "Total Assets".to_string(),
-                        "₩1,800,000,000".to_string(),
-                        "₩1,715,000,000".to_string(),
-                        "₩85,000,000".to_string(),
-                    ]),
-                }),
+                data: ReportData::Table(statement_table(&asset_lines, "Total Assets", filters.comparison)),
             },
             ReportSection {
                 title: "Liabilities & Equity".to_string(),
                 section_type: SectionType::Detail,
-                data: ReportData::Table(TableData {
-                    headers: vec![
-                        "Account".to_string(),
-                        "Current".to_string(),
-                        "Previous".to_string(),
-                        "Change".to_string(),
-                    ],
-                    rows: vec![
-                        vec![
-                            "Accounts Payable".to_string(),
-                            "₩185,000,000".to_string(),
-                            "₩175,000,000".to_string(),
-                            "₩10,000,000".to_string(),
-                        ],
-                        vec![
-                            "Short-term Debt".to_string(),
-                            "₩120,000,000".to_string(),
-                            "₩150,000,000".to_string(),
-                            "-₩30,000,000".to_string(),
-                        ],
-                        vec![
-                            "Long-term Debt".to_string(),
-                            "₩450,000,000".to_string(),
-                            "₩480,000,000".to_string(),
-                            "-₩30,000,000".to_string(),
-                        ],
-                        vec![
-                            "Retained Earnings".to_string(),
-                            "₩780,000,000".to_string(),
-                            "₩650,000,000".to_string(),
-                            "₩130,000,000".to_string(),
-                        ],
-                        vec![
-                            "Share Capital".to_string(),
-                            "₩265,000,000".to_string(),
-                            "₩260,000,000".to_string(),
-                            "₩5,000,000".to_string(),
-                        ],
-                    ],
-                    totals: Some(vec![
-                        "Total Liabilities & Equity".to_string(),
-                        "₩1,800,000,000".to_string(),
-                        "₩1,715,000,000".to_string(),
-                        "₩85,000,000".to_string(),
-                    ]),
-                }),
+                data: ReportData::Table(statement_table(
+                    &funding_lines,
+                    "Total Liabilities & Equity",
+                    filters.comparison,
+                )),
             },
         ];
 
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("total_assets".to_string(), MetricValue::Currency(1800000000));
-        key_metrics.insert("total_liabilities".to_string(), MetricValue::Currency(755000000));
-        key_metrics.insert("total_equity".to_string(), MetricValue::Currency(1045000000));
-        key_metrics.insert("debt_to_equity_ratio".to_string(), MetricValue::Number(0.72));
-        key_metrics.insert("current_ratio".to_string(), MetricValue::Number(3.1));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "Asset base grew by ₩85 million driven by cash and receivables".to_string(),
-                "Debt levels decreased by ₩60 million improving financial position".to_string(),
-                "Current ratio of 3.1 indicates strong liquidity".to_string(),
-                "Retained earnings increased by ₩130 million from profitable operations".to_string(),
-            ],
-            recommendations: vec![
-                "Consider investing excess cash in growth opportunities".to_string(),
-                "Maintain current debt reduction strategy".to_string(),
-                "Optimize accounts receivable collection processes".to_string(),
-            ],
-        };
+        key_metrics.insert("total_assets".to_string(), MetricValue::Text(format_amount(totals.assets)));
+        key_metrics.insert("total_liabilities".to_string(), MetricValue::Text(format_amount(totals.liabilities)));
+        key_metrics.insert("total_equity".to_string(), MetricValue::Text(format_amount(equity)));
+        if equity != 0 {
+            key_metrics.insert(
+                "debt_to_equity_ratio".to_string(),
+                MetricValue::Number(totals.liabilities as f64 / equity as f64),
+            );
+        }
 
-        let metadata = ReportMetadata {
-            total_records: 58,
-            processing_time_ms: 95,
-            filters_applied: vec!["balance_sheet_accounts".to_string()],
-            data_sources: vec!["accounts".to_string(), "transactions".to_string()],
-        };
+        let mut insights = vec![format!(
+            "Assets of {} funded by {} of liabilities and {} of equity",
+            format_amount(totals.assets),
+            format_amount(totals.liabilities),
+            format_amount(equity)
+        )];
+        if let Some(previous) = previous_totals {
+            insights.push(format!("Assets {} against the previous period", change(totals.assets, previous.assets)));
+        }
+        let mut recommendations = Vec::new();
+        if difference != 0 && filters.account_types.is_empty() {
+            recommendations.push(format!(
+                "Assets and liabilities plus equity differ by {}; check for unbalanced postings",
+                format_amount(difference)
+            ));
+        }
+        if equity < 0 {
+            recommendations.push("Equity is negative; liabilities exceed assets".to_string());
+        }
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
             data: ReportData::Mixed(sections),
-            summary: Some(summary),
-            metadata,
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: current.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["accounts".to_string(), "transactions".to_string(), "summary_account_monthly".to_string()],
+            },
         })
     }
 
     fn generate_cash_flow_statement(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
-        let table_data = TableData {
-            headers: vec![
-                "Cash Flow Category".to_string(),
-                "Current Month".to_string(),
-                "Previous Month".to_string(),
-                "YTD".to_string(),
-            ],
-            rows: vec![
-                vec![
-                    "Operating Activities".to_string(),
-                    "₩145,000,000".to_string(),
-                    "₩125,000,000".to_string(),
-                    "₩890,000,000".to_string(),
-                ],
-                vec![
-                    "  Net Income".to_string(),
-                    "₩130,000,000".to_string(),
-                    "₩115,000,000".to_string(),
-                    "₩780,000,000".to_string(),
-                ],
-                vec![
-                    "  Depreciation".to_string(),
-                    "₩25,000,000".to_string(),
-                    "₩25,000,000".to_string(),
-                    "₩300,000,000".to_string(),
-                ],
-                vec![
-                    "  Working Capital Changes".to_string(),
-                    "-₩10,000,000".to_string(),
-                    "-₩15,000,000".to_string(),
-                    "-₩190,000,000".to_string(),
-                ],
-                vec![
-                    "Investing Activities".to_string(),
-                    "-₩45,000,000".to_string(),
-                    "-₩35,000,000".to_string(),
-                    "-₩320,000,000".to_string(),
-                ],
-                vec![
-                    "  Capital Expenditures".to_string(),
-                    "-₩50,000,000".to_string(),
-                    "-₩40,000,000".to_string(),
-                    "-₩350,000,000".to_string(),
-                ],
-                vec![
-                    "  Asset Disposals".to_string(),
-                    "₩5,000,000".to_string(),
-                    "₩5,000,000".to_string(),
-                    "₩30,000,000".to_string(),
-                ],
-                vec![
-                    "Financing Activities".to_string(),
-                    "-₩30,000,000".to_string(),
-                    "-₩25,000,000".to_string(),
-                    "-₩200,000,000".to_string(),
-                ],
-                vec![
-                    "  Debt Payments".to_string(),
-                    "-₩25,000,000".to_string(),
-                    "-₩20,000,000".to_string(),
-                    "-₩180,000,000".to_string(),
-                ],
-                vec![
-                    "  Dividend Payments".to_string(),
-                    "-₩5,000,000".to_string(),
-                    "-₩5,000,000".to_string(),
-                    "-₩20,000,000".to_string(),
-                ],
-            ],
-            totals: Some(vec![
-                "Net Cash Flow".to_string(),
-                "₩70,000,000".to_string(),
-                "₩65,000,000".to_string(),
-                "₩370,000,000".to_string(),
-            ]),
+        let start_time = std::time::Instant::now();
+        let filters = ReportFilters::from_config(&config)?;
+        let (start, end) = filters.period_or(parse_period("this-month")?);
+        let mut conn = get_report_connection()?;
+        let service = ReportService::new();
+        let statement = service.generate_cash_flow_statement(&mut conn, start, end)?;
+        let previous = match filters.comparison {
+            true => {
+                let (from, to) = previous_period(start, end);
+                Some(service.generate_cash_flow_statement(&mut conn, from, to)?)
+            }
+            false => None,
         };
 
-        let mut key_metrics = HashMap::new();
-        key_metrics.insert("operating_cash_flow".to_string(), MetricValue::Currency(145000000));
-        key_metrics.insert("free_cash_flow".to_string(), MetricValue::Currency(95000000));
-        key_metrics.insert("cash_conversion_cycle".to_string(), MetricValue::Number(45.2));
-        key_metrics.insert("operating_margin".to_string(), MetricValue::Percentage(13.1));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "Operating cash flow increased by 16% month-over-month".to_string(),
-                "Free cash flow of ₩95M indicates strong cash generation".to_string(),
-                "Capital expenditures focused on growth initiatives".to_string(),
-                "Debt reduction continues as planned".to_string(),
-            ],
-            recommendations: vec![
-                "Continue focus on operating cash flow optimization".to_string(),
-                "Evaluate ROI of recent capital investments".to_string(),
-                "Consider increasing dividend based on strong cash position".to_string(),
-            ],
+        // Cash in and out per month
+        let mut by_month: BTreeMap<String, (i64, i64)> = BTreeMap::new();
+        for item in &statement.items {
+            let entry = by_month.entry(item.date.format("%Y-%m").to_string()).or_default();
+            if item.amount > 0 {
+                entry.0 += i64::from(item.amount);
+            } else {
+                entry.1 += i64::from(-item.amount);
+            }
+        }
+        let flow_row = |label: String, cash_in: i64, cash_out: i64| {
+            vec![label, format_amount(cash_in), format_amount(cash_out), format_amount(cash_in - cash_out)]
         };
+        let mut totals = vec![flow_row(
+            format!("{} to {}", start, end),
+            i64::from(statement.cash_inflows),
+            i64::from(statement.cash_outflows),
+        )];
+        if let Some(previous) = &previous {
+            totals.push(flow_row(
+                format!("Previous: {} to {}", previous.from_date, previous.to_date),
+                i64::from(previous.cash_inflows),
+                i64::from(previous.cash_outflows),
+            ));
+        }
 
-        let metadata = ReportMetadata {
-            total_records: 156,
-            processing_time_ms: 145,
-            filters_applied: vec!["cash_flow_accounts".to_string()],
-            data_sources: vec!["transactions".to_string(), "accounts".to_string()],
-        };
+        let mut largest: Vec<&CashFlowItem> = statement.items.iter().collect();
+        largest.sort_by_key(|item| std::cmp::Reverse(item.amount.abs()));
+        let sections = vec![
+            ReportSection {
+                title: "Cash Flow by Month".to_string(),
+                section_type: SectionType::Detail,
+                data: ReportData::Table(TableData {
+                    headers: header_row(&["Month", "Cash In", "Cash Out", "Net Cash Flow"]),
+                    rows: by_month.iter().map(|(month, (cash_in, cash_out))| flow_row(month.clone(), *cash_in, *cash_out)).collect(),
+                    totals: totals.first().cloned(),
+                }),
+            },
+            ReportSection {
+                title: "Period Comparison".to_string(),
+                section_type: SectionType::Summary,
+                data: ReportData::Table(TableData {
+                    headers: header_row(&["Period", "Cash In", "Cash Out", "Net Cash Flow"]),
+                    rows: totals,
+                    totals: None,
+                }),
+            },
+            ReportSection {
+                title: "Largest Cash Movements".to_string(),
+                section_type: SectionType::Analysis,
+                data: ReportData::Table(TableData {
+                    headers: header_row(&["Date", "Description", "Account", "Amount"]),
+                    rows: largest
+                        .iter()
+                        .take(LARGEST_CASH_MOVEMENTS)
+                        .map(|item| {
+                            vec![
+                                item.date.to_string(),
+                                item.description.clone(),
+                                item.account_name.clone(),
+                                format_amount(item.amount),
+                            ]
+                        })
+                        .collect(),
+                    totals: None,
+                }),
+            },
+        ];
+
+        let net = i64::from(statement.net_cash_flow);
+        let mut key_metrics = HashMap::new();
+        key_metrics.insert("cash_inflows".to_string(), MetricValue::Text(format_amount(statement.cash_inflows)));
+        key_metrics.insert("cash_outflows".to_string(), MetricValue::Text(format_amount(statement.cash_outflows)));
+        key_metrics.insert("net_cash_flow".to_string(), MetricValue::Text(format_amount(net)));
+        key_metrics.insert("cash_movements".to_string(), MetricValue::Count(statement.items.len() as i64));
+
+        let mut insights = vec![format!(
+            "{} in and {} out of cash accounts, net {}",
+            format_amount(statement.cash_inflows),
+            format_amount(statement.cash_outflows),
+            format_amount(net)
+        )];
+        if let Some(previous) = &previous {
+            insights.push(format!(
+                "Net cash flow was {} in the previous period",
+                format_amount(previous.net_cash_flow)
+            ));
+        }
+        let mut recommendations = Vec::new();
+        if net < 0 {
+            recommendations.push("Cash outflows exceed inflows; review payment timing and collections".to_string());
+        }
+        if statement.items.is_empty() {
+            recommendations.push("No postings to cash accounts (codes starting 1000) in the period".to_string());
+        }
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
-            data: ReportData::Table(table_data),
-            summary: Some(summary),
-            metadata,
+            data: ReportData::Mixed(sections),
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: statement.items.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["accounts".to_string(), "transactions".to_string()],
+            },
         })
     }
 
+    /// CLIERP keeps no budgets, so actuals are compared with the same period
+    /// a year earlier
     fn generate_budget_vs_actual_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let filters = ReportFilters::from_config(&config)?;
+        let (start, end) = filters.period_or(parse_period("this-month")?);
+        let (baseline_start, baseline_end) = (year_earlier(start), year_earlier(end));
+        let mut conn = get_report_connection()?;
+        let actual = ReportDataSource::account_totals(&mut conn, &filters, Some(start), Some(end))?;
+        let baseline = ReportDataSource::account_totals(&mut conn, &filters, Some(baseline_start), Some(baseline_end))?;
+
+        let variance_table = |lines: &[(String, i64, Option<i64>)], total_label: &str| {
+            let row = |label: String, actual: i64, baseline: i64| {
+                vec![
+                    label,
+                    format_amount(baseline),
+                    format_amount(actual),
+                    format_amount(actual - baseline),
+                    change(actual, baseline),
+                ]
+            };
+            TableData {
+                headers: header_row(&["Account", "Baseline (Prior Year)", "Actual", "Variance", "Variance %"]),
+                rows: lines
+                    .iter()
+                    .map(|(label, actual, baseline)| row(label.clone(), *actual, baseline.unwrap_or(0)))
+                    .collect(),
+                totals: Some(row(
+                    total_label.to_string(),
+                    lines.iter().map(|line| line.1).sum(),
+                    lines.iter().map(|line| line.2.unwrap_or(0)).sum(),
+                )),
+            }
+        };
+        let revenue_lines = account_lines(&["revenue"], &actual, Some(&baseline));
+        let expense_lines = account_lines(&["expense"], &actual, Some(&baseline));
+        let totals = Totals::of(&actual);
+        let baseline_totals = Totals::of(&baseline);
+
         let sections = vec![
             ReportSection {
                 title: "Revenue Analysis".to_string(),
                 section_type: SectionType::Detail,
-                data: ReportData::Table(TableData {
-                    headers: vec![
-                        "Revenue Stream".to_string(),
-                        "Budget".to_string(),
-                        "Actual".to_string(),
-                        "Variance".to_string(),
-                        "% Variance".to_string(),
-                    ],
-                    rows: vec![
-                        vec![
-                            "Product Sales".to_string(),
-                            "₩800,000,000".to_string(),
-                            "₩850,000,000".to_string(),
-                            "₩50,000,000".to_string(),
-                            "+6.3%".to_string(),
-                        ],
-                        vec![
-                            "Services".to_string(),
-                            "₩200,000,000".to_string(),
-                            "₩245,000,000".to_string(),
-                            "₩45,000,000".to_string(),
-                            "+22.5%".to_string(),
-                        ],
-                    ],
-                    totals: Some(vec![
-                        "Total Revenue".to_string(),
-                        "₩1,000,000,000".to_string(),
-                        "₩1,095,000,000".to_string(),
-                        "₩95,000,000".to_string(),
-                        "+9.5%".to_string(),
-                    ]),
-                }),
+                data: ReportData::Table(variance_table(&revenue_lines, "Total Revenue")),
             },
             ReportSection {
                 title: "Expense Analysis".to_string(),
                 section_type: SectionType::Detail,
-                data: ReportData::Table(TableData {
-                    headers: vec![
-                        "Expense Category".to_string(),
-                        "Budget".to_string(),
-                        "Actual".to_string(),
-                        "Variance".to_string(),
-                        "% Variance".to_string(),
-                    ],
-                    rows: vec![
-                        vec![
-                            "Personnel".to_string(),
-                            "₩300,000,000".to_string(),
-                            "₩320,000,000".to_string(),
-                            "₩20,000,000".to_string(),
-                            "+6.7%".to_string(),
-                        ],
-                        vec![
-                            "Operations".to_string(),
-                            "₩450,000,000".to_string(),
-                            "₩435,000,000".to_string(),
-                            "-₩15,000,000".to_string(),
-                            "-3.3%".to_string(),
-                        ],
-                        vec![
-                            "Marketing".to_string(),
-                            "₩80,000,000".to_string(),
-                            "₩85,000,000".to_string(),
-                            "₩5,000,000".to_string(),
-                            "+6.3%".to_string(),
-                        ],
-                    ],
-                    totals: Some(vec![
-                        "Total Expenses".to_string(),
-                        "₩830,000,000".to_string(),
-                        "₩840,000,000".to_string(),
-                        "₩10,000,000".to_string(),
-                        "+1.2%".to_string(),
-                    ]),
-                }),
+                data: ReportData::Table(variance_table(&expense_lines, "Total Expenses")),
             },
             ReportSection {
-                title: "Budget Performance Chart".to_string(),
+                title: "Baseline Performance Chart".to_string(),
                 section_type: SectionType::Chart,
-                data: ReportData::Chart(create_bar_chart(
-                    vec![
-                        "Q1".to_string(),
-                        "Q2".to_string(),
-                        "Q3".to_string(),
-                        "Q4".to_string(),
+                data: ReportData::Chart(ChartData {
+                    chart_type: ChartType::Bar,
+                    labels: vec!["Revenue".to_string(), "Expenses".to_string(), "Net Income".to_string()],
+                    datasets: vec![
+                        Dataset {
+                            label: "Baseline".to_string(),
+                            data: vec![
+                                baseline_totals.revenue as f64,
+                                baseline_totals.expenses as f64,
+                                baseline_totals.net_income() as f64,
+                            ],
+                            color: Some("#F59E0B".to_string()),
+                        },
+                        Dataset {
+                            label: "Actual".to_string(),
+                            data: vec![totals.revenue as f64, totals.expenses as f64, totals.net_income() as f64],
+                            color: Some("#3B82F6".to_string()),
+                        },
                     ],
-                    vec![105.2, 98.7, 109.5, 102.1],
-                    "Budget Achievement %",
-                )),
+                }),
             },
         ];
 
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("revenue_variance".to_string(), MetricValue::Currency(95000000));
-        key_metrics.insert("expense_variance".to_string(), MetricValue::Currency(10000000));
-        key_metrics.insert("net_variance".to_string(), MetricValue::Currency(85000000));
-        key_metrics.insert("budget_achievement".to_string(), MetricValue::Percentage(105.2));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "Revenue exceeded budget by 9.5% driven by strong service growth".to_string(),
-                "Expenses remained well-controlled with only 1.2% over budget".to_string(),
-                "Service revenue performed exceptionally with 22.5% above budget".to_string(),
-                "Operational efficiency improved with cost savings in operations".to_string(),
-            ],
-            recommendations: vec![
-                "Revise service revenue targets upward for next period".to_string(),
-                "Investigate personnel cost increases for sustainability".to_string(),
-                "Consider reallocating marketing budget to high-performing channels".to_string(),
-            ],
-        };
+        key_metrics.insert(
+            "revenue_variance".to_string(),
+            MetricValue::Text(format_amount(totals.revenue - baseline_totals.revenue)),
+        );
+        key_metrics.insert(
+            "expense_variance".to_string(),
+            MetricValue::Text(format_amount(totals.expenses - baseline_totals.expenses)),
+        );
+        key_metrics.insert(
+            "net_income_variance".to_string(),
+            MetricValue::Text(format_amount(totals.net_income() - baseline_totals.net_income())),
+        );
 
-        let metadata = ReportMetadata {
-            total_records: 89,
-            processing_time_ms: 125,
-            filters_applied: vec!["current_period".to_string(), "budget_comparison".to_string()],
-            data_sources: vec!["budget".to_string(), "actuals".to_string()],
-        };
+        let mut insights = vec![
+            format!(
+                "No budgets are recorded; {} to {} is compared with {} to {}",
+                start, end, baseline_start, baseline_end
+            ),
+            format!(
+                "Revenue {} and expenses {} year over year",
+                change(totals.revenue, baseline_totals.revenue),
+                change(totals.expenses, baseline_totals.expenses)
+            ),
+        ];
+        let mut recommendations = Vec::new();
+        if let Some((label, actual, baseline)) = expense_lines
+            .iter()
+            .max_by_key(|(_, actual, baseline)| actual - baseline.unwrap_or(0))
+            .filter(|(_, actual, baseline)| *actual > baseline.unwrap_or(0))
+        {
+            insights.push(format!(
+                "{} grew the most, by {}",
+                label,
+                format_amount(actual - baseline.unwrap_or(0))
+            ));
+            if growth(*actual, baseline.unwrap_or(0)) > 20.0 {
+                recommendations.push(format!("Review the growth of {}", label));
+            }
+        }
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
             data: ReportData::Mixed(sections),
-            summary: Some(summary),
-            metadata,
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: actual.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["accounts".to_string(), "transactions".to_string(), "summary_account_monthly".to_string()],
+            },
         })
     }
 
     fn generate_financial_analytics(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let filters = ReportFilters::from_config(&config)?;
+        let (start, end) = filters.period_or(parse_period("this-month")?);
+        let (previous_start, previous_end) = previous_period(start, end);
+        let mut conn = get_report_connection()?;
+
+        // Ratios need every account type whatever the filter says
+        let all_accounts = ReportFilters {
+            account_types: Vec::new(),
+            ..filters.clone()
+        };
+        let mut ratios_for = |from: NaiveDate, to: NaiveDate| -> CLIERPResult<Ratios> {
+            let flows = Totals::of(&ReportDataSource::account_totals(&mut conn, &all_accounts, Some(from), Some(to))?);
+            let position = Totals::of(&ReportDataSource::account_totals(&mut conn, &all_accounts, None, Some(to))?);
+            Ok(Ratios::of(flows, position))
+        };
+        let current = ratios_for(start, end)?;
+        let previous = ratios_for(previous_start, previous_end)?;
+
+        let ratio_row = |label: &str, current: Option<f64>, previous: Option<f64>, higher_is_better: bool, percent: bool| {
+            let show = |value: Option<f64>| match value {
+                Some(value) if percent => format_percentage(value),
+                Some(value) => format!("{:.2}", value),
+                None => "-".to_string(),
+            };
+            let status = match (current, previous) {
+                (Some(current), Some(previous)) if (current - previous).abs() < 0.005 => "Steady",
+                (Some(current), Some(previous)) if (current > previous) == higher_is_better => "Improving",
+                (Some(_), Some(_)) => "Declining",
+                _ => "-",
+            };
+            vec![label.to_string(), show(current), show(previous), status.to_string()]
+        };
+        let rows = vec![
+            ratio_row("Net Margin", current.net_margin, previous.net_margin, true, true),
+            ratio_row("Expense Ratio", current.expense_ratio, previous.expense_ratio, false, true),
+            ratio_row("ROE", current.roe, previous.roe, true, true),
+            ratio_row("ROA", current.roa, previous.roa, true, true),
+            ratio_row("Debt-to-Equity", current.debt_to_equity, previous.debt_to_equity, false, false),
+        ];
+
+        // Revenue and net margin for the twelve months up to the period end
+        let mut labels = Vec::new();
+        let mut revenue_trend = Vec::new();
+        let mut margin_trend = Vec::new();
+        for month in (0..12).rev() {
+            let Some(month_start) = end.with_day(1).and_then(|first| first.checked_sub_months(Months::new(month))) else {
+                continue;
+            };
+            let month_end = month_start
+                .checked_add_months(Months::new(1))
+                .and_then(|next| next.pred_opt())
+                .unwrap_or(month_start);
+            let totals = Totals::of(&ReportDataSource::account_totals(
+                &mut conn,
+                &all_accounts,
+                Some(month_start),
+                Some(month_end),
+            )?);
+            labels.push(month_start.format("%Y-%m").to_string());
+            revenue_trend.push(totals.revenue as f64);
+            margin_trend.push(share(totals.net_income(), totals.revenue));
+        }
+
         let sections = vec![
             ReportSection {
-                title: "Financial Ratios".to_string(),
+                title: format!("Financial Ratios, {} to {}", start, end),
                 section_type: SectionType::Analysis,
                 data: ReportData::Table(TableData {
-                    headers: vec![
-                        "Ratio".to_string(),
-                        "Current".to_string(),
-                        "Previous".to_string(),
-                        "Industry Avg".to_string(),
-                        "Status".to_string(),
-                    ],
-                    rows: vec![
-                        vec![
-                            "Current Ratio".to_string(),
-                            "3.1".to_string(),
-                            "2.8".to_string(),
-                            "2.5".to_string(),
-                            "Above Average".to_string(),
-                        ],
-                        vec![
-                            "Debt-to-Equity".to_string(),
-                            "0.72".to_string(),
-                            "0.84".to_string(),
-                            "0.65".to_string(),
-                            "Improving".to_string(),
-                        ],
-                        vec![
-                            "ROE".to_string(),
-                            "12.4%".to_string(),
-                            "11.2%".to_string(),
-                            "10.5%".to_string(),
-                            "Above Average".to_string(),
-                        ],
-                        vec![
-                            "ROA".to_string(),
-                            "7.2%".to_string(),
-                            "6.7%".to_string(),
-                            "6.8%".to_string(),
-                            "Above Average".to_string(),
-                        ],
-                    ],
+                    headers: header_row(&["Ratio", "Current", "Previous", "Status"]),
+                    rows,
                     totals: None,
                 }),
             },
@@ -649,24 +646,17 @@ impl FinanceReportsGenerator {
                 title: "Trend Analysis".to_string(),
                 section_type: SectionType::Chart,
                 data: ReportData::Chart(create_line_chart(
-                    vec![
-                        "Jan".to_string(),
-                        "Feb".to_string(),
-                        "Mar".to_string(),
-                        "Apr".to_string(),
-                        "May".to_string(),
-                        "Jun".to_string(),
-                    ],
+                    labels,
                     vec![
                         Dataset {
-                            label: "Revenue Growth %".to_string(),
-                            data: vec![8.5, 9.2, 7.8, 11.3, 9.7, 10.1],
-                            color: Some("#10B981".to_string()),
+                            label: "Revenue".to_string(),
+                            data: revenue_trend,
+                            color: Some("#3B82F6".to_string()),
                         },
                         Dataset {
-                            label: "Profit Margin %".to_string(),
-                            data: vec![11.2, 11.8, 10.9, 12.1, 11.7, 12.4],
-                            color: Some("#3B82F6".to_string()),
+                            label: "Net Margin %".to_string(),
+                            data: margin_trend,
+                            color: Some("#10B981".to_string()),
                         },
                     ],
                 )),
@@ -674,46 +664,341 @@ impl FinanceReportsGenerator {
         ];
 
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("current_ratio".to_string(), MetricValue::Number(3.1));
-        key_metrics.insert("roe".to_string(), MetricValue::Percentage(12.4));
-        key_metrics.insert("roa".to_string(), MetricValue::Percentage(7.2));
-        key_metrics.insert("debt_to_equity".to_string(), MetricValue::Number(0.72));
-        key_metrics.insert("financial_health_score".to_string(), MetricValue::Number(8.7));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "Financial health improved across all key metrics".to_string(),
-                "Liquidity position strengthened with current ratio of 3.1".to_string(),
-                "Profitability trends show consistent improvement".to_string(),
-                "Debt management strategy yielding positive results".to_string(),
-            ],
-            recommendations: vec![
-                "Maintain current financial discipline".to_string(),
-                "Consider strategic investments to deploy excess liquidity".to_string(),
-                "Continue debt reduction to reach industry benchmark".to_string(),
-            ],
+        for (name, value) in [
+            ("net_margin", current.net_margin),
+            ("expense_ratio", current.expense_ratio),
+            ("roe", current.roe),
+            ("roa", current.roa),
+        ] {
+            if let Some(value) = value {
+                key_metrics.insert(name.to_string(), MetricValue::Percentage(value));
+            }
+        }
+        if let Some(debt_to_equity) = current.debt_to_equity {
+            key_metrics.insert("debt_to_equity".to_string(), MetricValue::Number(debt_to_equity));
+        }
+
+        let mut insights = Vec::new();
+        let mut recommendations = Vec::new();
+        match (current.net_margin, previous.net_margin) {
+            (Some(margin), Some(before)) => insights.push(format!(
+                "Net margin {} against {} in the previous period",
+                format_percentage(margin),
+                format_percentage(before)
+            )),
+            (Some(margin), None) => insights.push(format!("Net margin {}", format_percentage(margin))),
+            (None, _) => recommendations.push("No revenue posted in the period; ratios need revenue accounts".to_string()),
+        }
+        if current.debt_to_equity.is_some_and(|ratio| ratio > 2.0) {
+            recommendations.push("Debt-to-equity is above 2; review borrowing and payables".to_string());
+        }
+        if current.net_margin.is_some_and(|margin| margin < 0.0) {
+            recommendations.push("The period ran at a loss; see the income statement for the largest expenses".to_string());
+        }
+
+        Ok(ReportResult {
+            config: config.clone(),
+            generated_at: Utc::now().naive_utc(),
+            data: ReportData::Mixed(sections),
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: 12,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["accounts".to_string(), "transactions".to_string(), "summary_account_monthly".to_string()],
+            },
+        })
+    }
+
+    fn generate_gross_margin_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let (start, end) = match &config.date_range {
+            Some(range) => (range.start_date, range.end_date),
+            None => {
+                let today = DisplayTimezone::current().today();
+                (today.with_day(1).unwrap_or(today), today)
+            }
+        };
+        let method = match config.filters.get("valuation_method") {
+            Some(method) => method.parse::<CostingMethod>()?,
+            None => CostingMethod::Fifo,
         };
+        let mut conn = get_report_connection()?;
+        let analysis = GrossMarginService::analyze(&mut conn, start, end, method, filter_i32(&config, "category_id")?)?;
 
-        let metadata = ReportMetadata {
-            total_records: 24,
-            processing_time_ms: 95,
-            filters_applied: vec!["financial_ratios".to_string()],
-            data_sources: vec!["balance_sheet".to_string(), "income_statement".to_string()],
+        let total = analysis.total();
+        let by_product = analysis.by_product();
+        let by_customer = analysis.by_customer();
+        let margin_table = |label: &str, rows: &[MarginRow]| TableData {
+            headers: vec![
+                label.to_string(),
+                "Quantity".to_string(),
+                "Revenue".to_string(),
+                "COGS".to_string(),
+                "Gross Margin".to_string(),
+                "Margin %".to_string(),
+            ],
+            rows: rows.iter().map(margin_cells).collect(),
+            totals: Some(margin_cells(&total)),
         };
 
+        let mut watch: Vec<&MarginRow> = by_product.iter().filter(|row| row.revenue > 0).collect();
+        watch.sort_by(|a, b| a.margin_percent().total_cmp(&b.margin_percent()));
+        let watch_rows = watch
+            .iter()
+            .take(MARGIN_WATCH_PRODUCTS)
+            .map(|row| margin_cells(row))
+            .collect();
+
+        let mut key_metrics = HashMap::new();
+        key_metrics.insert("revenue".to_string(), MetricValue::Text(format_amount(total.revenue)));
+        key_metrics.insert("cogs".to_string(), MetricValue::Text(format_amount(total.cogs)));
+        key_metrics.insert("gross_margin".to_string(), MetricValue::Text(format_amount(total.margin())));
+        key_metrics.insert("gross_margin_percent".to_string(), MetricValue::Percentage(total.margin_percent()));
+        key_metrics.insert("shipments".to_string(), MetricValue::Count(analysis.lines.len() as i64));
+
+        let mut insights = vec![format!(
+            "Gross margin of {} on {} revenue ({}), costed {}",
+            format_amount(total.margin()),
+            format_amount(total.revenue),
+            format_percentage(total.margin_percent()),
+            method
+        )];
+        if let Some(best) = by_customer.iter().find(|row| row.label != super::margin::UNASSIGNED_CUSTOMER) {
+            insights.push(format!("{} contributed the most margin at {}", best.label, format_amount(best.margin())));
+        }
+        let mut recommendations = Vec::new();
+        let losses: Vec<&str> = by_product
+            .iter()
+            .filter(|row| row.margin() < 0)
+            .map(|row| row.label.as_str())
+            .collect();
+        if !losses.is_empty() {
+            recommendations.push(format!("Review pricing of products sold below cost: {}", losses.join(", ")));
+        }
+        if by_customer.iter().any(|row| row.label == super::margin::UNASSIGNED_CUSTOMER) {
+            recommendations.push(
+                "Ship against deals so that sales are priced and attributed to a customer".to_string(),
+            );
+        }
+
+        let sections = vec![
+            ReportSection {
+                title: format!("Gross Margin by Product, {} to {}", start, end),
+                section_type: SectionType::Detail,
+                data: ReportData::Table(margin_table("Product", &by_product)),
+            },
+            ReportSection {
+                title: "Gross Margin by Category".to_string(),
+                section_type: SectionType::Summary,
+                data: ReportData::Table(margin_table("Category", &analysis.by_category())),
+            },
+            ReportSection {
+                title: "Gross Margin by Customer".to_string(),
+                section_type: SectionType::Summary,
+                data: ReportData::Table(margin_table("Customer", &by_customer)),
+            },
+            ReportSection {
+                title: "Margin Watch: Lowest-Margin Products".to_string(),
+                section_type: SectionType::Analysis,
+                data: ReportData::Table(TableData {
+                    rows: watch_rows,
+                    totals: None,
+                    ..margin_table("Product", &[])
+                }),
+            },
+        ];
+
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
             data: ReportData::Mixed(sections),
-            summary: Some(summary),
-            metadata,
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: analysis.lines.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec![
+                    "stock_movements".to_string(),
+                    "products".to_string(),
+                    "deals".to_string(),
+                    "customers".to_string(),
+                ],
+            },
         })
     }
 }
 
+/// Table cells of one gross margin row
+fn margin_cells(row: &MarginRow) -> Vec<String> {
+    vec![
+        row.label.clone(),
+        row.quantity.to_string(),
+        format_amount(row.revenue),
+        format_amount(row.cogs),
+        format_amount(row.margin()),
+        format_percentage(row.margin_percent()),
+    ]
+}
+
 impl Default for FinanceReportsGenerator {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+/// Balances of account rows by account type
+#[derive(Debug, Clone, Copy, Default)]
+struct Totals {
+    revenue: i64,
+    expenses: i64,
+    assets: i64,
+    liabilities: i64,
+    equity: i64,
+}
+
+impl Totals {
+    fn of(rows: &[AccountRow]) -> Self {
+        let mut totals = Self::default();
+        for row in rows {
+            let total = match row.account_type.as_str() {
+                "revenue" => &mut totals.revenue,
+                "expense" => &mut totals.expenses,
+                "asset" => &mut totals.assets,
+                "liability" => &mut totals.liabilities,
+                _ => &mut totals.equity,
+            };
+            *total += row.balance();
+        }
+        totals
+    }
+
+    fn net_income(&self) -> i64 {
+        self.revenue - self.expenses
+    }
+}
+
+/// Ratios of a period's flows and the position at its end, `None` where
+/// the denominator is zero
+#[derive(Debug, Clone, Copy)]
+struct Ratios {
+    net_margin: Option<f64>,
+    expense_ratio: Option<f64>,
+    roe: Option<f64>,
+    roa: Option<f64>,
+    debt_to_equity: Option<f64>,
+}
+
+impl Ratios {
+    fn of(flows: Totals, position: Totals) -> Self {
+        let ratio = |part: i64, whole: i64| (whole != 0).then(|| part as f64 * 100.0 / whole as f64);
+        let equity = position.equity + position.net_income();
+        Self {
+            net_margin: ratio(flows.net_income(), flows.revenue),
+            expense_ratio: ratio(flows.expenses, flows.revenue),
+            roe: ratio(flows.net_income(), equity),
+            roa: ratio(flows.net_income(), position.assets),
+            debt_to_equity: ratio(position.liabilities, equity).map(|percent| percent / 100.0),
+        }
+    }
+}
+
+/// Statement lines of the accounts of `types`: label, balance and the
+/// previous balance when comparing
+fn account_lines(
+    types: &[&str],
+    current: &[AccountRow],
+    previous: Option<&[AccountRow]>,
+) -> Vec<(String, i64, Option<i64>)> {
+    let label = |row: &AccountRow| format!("{} {}", row.account_code, row.account_name);
+    let mut lines: BTreeMap<String, (i64, Option<i64>)> = BTreeMap::new();
+    for row in current.iter().filter(|row| types.contains(&row.account_type.as_str())) {
+        lines.entry(label(row)).or_insert((0, previous.map(|_| 0))).0 += row.balance();
+    }
+    for row in previous.unwrap_or_default().iter().filter(|row| types.contains(&row.account_type.as_str())) {
+        let line = lines.entry(label(row)).or_insert((0, Some(0)));
+        line.1 = Some(line.1.unwrap_or(0) + row.balance());
+    }
+    lines.into_iter().map(|(label, (amount, previous))| (label, amount, previous)).collect()
+}
+
+fn statement_table(lines: &[(String, i64, Option<i64>)], total_label: &str, comparison: bool) -> TableData {
+    let row = |label: String, amount: i64, previous: i64| match comparison {
+        true => vec![label, format_amount(amount), format_amount(previous), change(amount, previous)],
+        false => vec![label, format_amount(amount)],
+    };
+    TableData {
+        headers: match comparison {
+            true => header_row(&["Account", "Current Period", "Previous Period", "Change"]),
+            false => header_row(&["Account", "Amount"]),
+        },
+        rows: lines
+            .iter()
+            .map(|(label, amount, previous)| row(label.clone(), *amount, previous.unwrap_or(0)))
+            .collect(),
+        totals: Some(row(
+            total_label.to_string(),
+            lines.iter().map(|line| line.1).sum(),
+            lines.iter().map(|line| line.2.unwrap_or(0)).sum(),
+        )),
+    }
+}
+
+/// Percentage growth from `previous` to `current`, 0 from nothing
+fn growth(current: i64, previous: i64) -> f64 {
+    if previous == 0 {
+        0.0
+    } else {
+        (current - previous) as f64 * 100.0 / previous.abs() as f64
+    }
+}
+
+fn change(current: i64, previous: i64) -> String {
+    if previous == 0 {
+        "-".to_string()
+    } else {
+        format!("{:+.1}%", growth(current, previous))
+    }
+}
+
+/// The period of the same length just before `start..=end`: the same number
+/// of whole months when the period is whole months, else of days
+fn previous_period(start: NaiveDate, end: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let before = start.pred_opt().unwrap_or(start);
+    match end.succ_opt() {
+        Some(next) if start.day() == 1 && next.day() == 1 => {
+            let months = (next.year() - start.year()) * 12 + next.month() as i32 - start.month() as i32;
+            let from = start.checked_sub_months(Months::new(months.max(1) as u32)).unwrap_or(start);
+            (from, before)
+        }
+        _ => (before - Duration::days((end - start).num_days()), before),
+    }
+}
+
+/// The same day a year earlier, the 28th for the 29th of February
+fn year_earlier(date: NaiveDate) -> NaiveDate {
+    date.checked_sub_months(Months::new(12)).unwrap_or(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_previous_period() {
+        let date = |s: &str| s.parse::<NaiveDate>().unwrap();
+        assert_eq!(previous_period(date("2024-03-01"), date("2024-03-31")), (date("2024-02-01"), date("2024-02-29")));
+        assert_eq!(previous_period(date("2024-04-01"), date("2024-06-30")), (date("2024-01-01"), date("2024-03-31")));
+        assert_eq!(previous_period(date("2024-03-10"), date("2024-03-16")), (date("2024-03-03"), date("2024-03-09")));
+        assert_eq!(year_earlier(date("2024-02-29")), date("2023-02-28"));
+        assert_eq!(change(120, 100), "+20.0%");
+        assert_eq!(change(5, 0), "-");
+    }
+}
@@ -1,20 +1,31 @@
-use chrono::{Utc, NaiveDate};
+use chrono::{Datelike, NaiveDate, Utc};
 use diesel::prelude::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use crate::core::result::CLIERPResult;
-use crate::database::{DatabaseConnection, Employee, Attendance, Payroll};
+use crate::database::{get_report_connection, Attendance, DatabaseConnection, Department, Employee, Payroll};
 use crate::database::schema::{employees, attendances, payrolls, departments};
+use crate::modules::hr::{DocumentService, DocumentState};
+use crate::utils::timezone::DisplayTimezone;
 use super::engine::*;
 
+/// Leave days a full-time employee is entitled to per year when the
+/// `annual_leave_days` filter is not given
+pub const DEFAULT_ANNUAL_LEAVE_DAYS: u32 = 15;
+
+/// Attendance status recorded for a day of leave
+const LEAVE_STATUS: &str = "holiday";
+
 pub struct HRReportsGenerator;
 
 impl ReportGenerator for HRReportsGenerator {
     fn generate_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
-        let start_time = std::time::Instant::now();
-
         match config.title.as_str() {
             "employee_summary" => self.generate_employee_summary_report(config),
+            "headcount" => self.generate_headcount_report(config),
+            "turnover" => self.generate_turnover_report(config),
             "attendance_report" => self.generate_attendance_report(config),
+            "leave_utilization" => self.generate_leave_utilization_report(config),
+            "document_compliance" => self.generate_document_compliance_report(config),
             "payroll_report" => self.generate_payroll_report(config),
             "hr_analytics" => self.generate_hr_analytics_report(config),
             _ => Err(crate::core::error::CLIERPError::NotFound(
@@ -38,13 +49,21 @@ impl ReportGenerator for HRReportsGenerator {
                 label: "Employee Status".to_string(),
                 filter_type: FilterType::Select,
                 required: false,
-                default_value: Some("active".to_string()),
+                default_value: None,
                 options: Some(vec![
                     FilterOption { value: "active".to_string(), label: "Active".to_string() },
                     FilterOption { value: "inactive".to_string(), label: "Inactive".to_string() },
                     FilterOption { value: "terminated".to_string(), label: "Terminated".to_string() },
                 ]),
             },
+            FilterDefinition {
+                name: "employee_id".to_string(),
+                label: "Employee".to_string(),
+                filter_type: FilterType::Number,
+                required: false,
+                default_value: None,
+                options: None,
+            },
             FilterDefinition {
                 name: "date_range".to_string(),
                 label: "Date Range".to_string(),
@@ -53,6 +72,14 @@ impl ReportGenerator for HRReportsGenerator {
                 default_value: None,
                 options: None,
             },
+            FilterDefinition {
+                name: "annual_leave_days".to_string(),
+                label: "Annual Leave Entitlement (days)".to_string(),
+                filter_type: FilterType::Number,
+                required: false,
+                default_value: Some(DEFAULT_ANNUAL_LEAVE_DAYS.to_string()),
+                options: None,
+            },
         ]
     }
 
@@ -60,26 +87,228 @@ impl ReportGenerator for HRReportsGenerator {
         ReportInfo {
             id: "hr_reports".to_string(),
             name: "HR Reports".to_string(),
-            description: "Comprehensive HR reporting including employee, attendance, and payroll reports".to_string(),
+            description: "Headcount, turnover, attendance, leave, document compliance and payroll reports".to_string(),
             category: "Human Resources".to_string(),
             supported_formats: vec![
                 ReportFormat::Json,
                 ReportFormat::Csv,
                 ReportFormat::Html,
                 ReportFormat::Text,
+                ReportFormat::Pdf,
             ],
         }
     }
 }
 
+/// Employees and departments a report works on, after the department filter
+struct Workforce {
+    employees: Vec<Employee>,
+    /// Names of the departments those employees belong to
+    departments: BTreeMap<i32, String>,
+}
+
+impl Workforce {
+    fn load(conn: &mut DatabaseConnection, config: &ReportConfig) -> CLIERPResult<Self> {
+        let mut query = employees::table.into_boxed();
+        if let Some(department_id) = filter_i32(config, "department_id")? {
+            query = query.filter(employees::department_id.eq(department_id));
+        }
+        if let Some(employee_id) = filter_i32(config, "employee_id")? {
+            query = query.filter(employees::id.eq(employee_id));
+        }
+        let employees = query
+            .order(employees::employee_code.asc())
+            .load::<Employee>(conn)?;
+
+        let departments = departments::table
+            .order(departments::name.asc())
+            .load::<Department>(conn)?
+            .into_iter()
+            .filter(|d| employees.iter().any(|e| e.department_id == d.id))
+            .map(|d| (d.id, d.name))
+            .collect();
+        Ok(Self { employees, departments })
+    }
+
+    fn department_name(&self, department_id: i32) -> String {
+        self.departments
+            .get(&department_id)
+            .cloned()
+            .unwrap_or_else(|| format!("#{}", department_id))
+    }
+
+    /// Department IDs ordered by department name
+    fn department_ids(&self) -> Vec<i32> {
+        let mut ids: Vec<(&String, i32)> = self.departments.iter().map(|(id, name)| (name, *id)).collect();
+        ids.sort();
+        ids.into_iter().map(|(_, id)| id).collect()
+    }
+
+    fn headcount_at(&self, date: NaiveDate, department_id: Option<i32>) -> i64 {
+        self.employees
+            .iter()
+            .filter(|e| department_id.is_none_or(|d| e.department_id == d))
+            .filter(|e| employed_on(e, date))
+            .count() as i64
+    }
+}
+
+/// Whether `employee` was on the payroll on `date`; inactive employees are
+/// still employed, terminated ones are not from their termination date on
+fn employed_on(employee: &Employee, date: NaiveDate) -> bool {
+    employee.hire_date <= date && termination_date(employee).is_none_or(|left| left > date)
+}
+
+fn termination_date(employee: &Employee) -> Option<NaiveDate> {
+    match employee.termination_date {
+        Some(date) => Some(date),
+        None if employee.status == "terminated" => Some(employee.updated_at.date()),
+        None => None,
+    }
+}
+
+/// Leavers per 100 employees of the average of the opening and closing headcount
+fn turnover_rate(leavers: i64, opening: i64, closing: i64) -> f64 {
+    let average = (opening + closing) as f64 / 2.0;
+    if average > 0.0 {
+        leavers as f64 / average * 100.0
+    } else {
+        0.0
+    }
+}
+
+fn percentage(part: i64, whole: i64) -> f64 {
+    if whole > 0 {
+        part as f64 / whole as f64 * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// First and last day of each calendar month overlapping `start..=end`,
+/// clipped to the range
+fn month_ranges(start: NaiveDate, end: NaiveDate) -> Vec<(NaiveDate, NaiveDate)> {
+    let mut ranges = Vec::new();
+    let mut month_start = start;
+    while month_start <= end {
+        let next_month = if month_start.month() == 12 {
+            NaiveDate::from_ymd_opt(month_start.year() + 1, 1, 1)
+        } else {
+            NaiveDate::from_ymd_opt(month_start.year(), month_start.month() + 1, 1)
+        }
+        .expect("first of month is a valid date");
+        let month_end = next_month.pred_opt().expect("month end is a valid date").min(end);
+        ranges.push((month_start, month_end));
+        month_start = next_month;
+    }
+    ranges
+}
+
+fn month_label(date: NaiveDate) -> String {
+    date.format("%Y-%m").to_string()
+}
+
+/// The configured date range, or the twelve months up to today
+fn range_or_last_year(config: &ReportConfig) -> (NaiveDate, NaiveDate) {
+    match &config.date_range {
+        Some(range) => (range.start_date, range.end_date),
+        None => {
+            let today = DisplayTimezone::current().today();
+            let months = today.year() * 12 + today.month0() as i32 - 11;
+            let start = NaiveDate::from_ymd_opt(months.div_euclid(12), months.rem_euclid(12) as u32 + 1, 1)
+                .unwrap_or(today);
+            (start, today)
+        }
+    }
+}
+
+/// The configured date range, or the current month up to today
+fn range_or_this_month(config: &ReportConfig) -> (NaiveDate, NaiveDate) {
+    match &config.date_range {
+        Some(range) => (range.start_date, range.end_date),
+        None => {
+            let today = DisplayTimezone::current().today();
+            (today.with_day(1).unwrap_or(today), today)
+        }
+    }
+}
+
+fn to_currency(amount: i64) -> i32 {
+    i32::try_from(amount).unwrap_or(if amount < 0 { i32::MIN } else { i32::MAX })
+}
+
+/// Attendance counts of one group of records
+#[derive(Debug, Default, Clone, PartialEq)]
+struct AttendanceTally {
+    records: i64,
+    present: i64,
+    late: i64,
+    early_leave: i64,
+    absent: i64,
+    leave: i64,
+    overtime_hours: f64,
+}
+
+impl AttendanceTally {
+    fn add(&mut self, attendance: &Attendance) {
+        self.records += 1;
+        match attendance.status.as_str() {
+            "present" => self.present += 1,
+            "late" => self.late += 1,
+            "early_leave" => self.early_leave += 1,
+            "absent" => self.absent += 1,
+            LEAVE_STATUS => self.leave += 1,
+            _ => {}
+        }
+        self.overtime_hours += attendance.overtime_hours.unwrap_or(0.0) as f64;
+    }
+
+    fn attended(&self) -> i64 {
+        self.present + self.late + self.early_leave
+    }
+
+    /// Days attended out of the days that were expected at work (leave excluded)
+    fn attendance_rate(&self) -> f64 {
+        percentage(self.attended(), self.attended() + self.absent)
+    }
+
+    /// Late arrivals out of the days attended
+    fn lateness_rate(&self) -> f64 {
+        percentage(self.late, self.attended())
+    }
+
+    fn row(&self, label: String) -> Vec<String> {
+        vec![
+            label,
+            self.records.to_string(),
+            self.present.to_string(),
+            self.late.to_string(),
+            self.early_leave.to_string(),
+            self.absent.to_string(),
+            self.leave.to_string(),
+            format_percentage(self.attendance_rate()),
+            format_percentage(self.lateness_rate()),
+            format!("{:.1}", self.overtime_hours),
+        ]
+    }
+}
+
 impl HRReportsGenerator {
     pub fn new() -> Self {
         Self
     }
 
     fn generate_employee_summary_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
-        // This would need a database connection in a real implementation
-        // For now, we'll create a mock report structure
+        let start_time = std::time::Instant::now();
+        let mut conn = get_report_connection()?;
+        let workforce = Workforce::load(&mut conn, &config)?;
+
+        let status_filter = config.filters.get("employee_status");
+        let listed: Vec<&Employee> = workforce
+            .employees
+            .iter()
+            .filter(|e| status_filter.is_none_or(|s| &e.status == s))
+            .collect();
 
         let headers = vec![
             "Employee ID".to_string(),
@@ -90,167 +319,626 @@ impl HRReportsGenerator {
             "Hire Date".to_string(),
             "Salary".to_string(),
         ];
+        let rows = listed
+            .iter()
+            .map(|e| {
+                vec![
+                    e.employee_code.clone(),
+                    e.name.clone(),
+                    workforce.department_name(e.department_id),
+                    e.position.clone(),
+                    e.status.clone(),
+                    e.hire_date.to_string(),
+                    format_currency(e.salary),
+                ]
+            })
+            .collect();
 
-        let rows = vec![
-            vec![
-                "EMP001".to_string(),
-                "John Doe".to_string(),
-                "Engineering".to_string(),
-                "Software Engineer".to_string(),
-                "Active".to_string(),
-                "2023-01-15".to_string(),
-                "₩5,000,000".to_string(),
-            ],
-            vec![
-                "EMP002".to_string(),
-                "Jane Smith".to_string(),
-                "Marketing".to_string(),
-                "Marketing Manager".to_string(),
-                "Active".to_string(),
-                "2022-06-01".to_string(),
-                "₩4,500,000".to_string(),
-            ],
+        let active: Vec<&&Employee> = listed.iter().filter(|e| e.status == "active").collect();
+        let average_salary = if active.is_empty() {
+            0
+        } else {
+            active.iter().map(|e| e.salary as i64).sum::<i64>() / active.len() as i64
+        };
+
+        let mut key_metrics = HashMap::new();
+        key_metrics.insert("total_employees".to_string(), MetricValue::Count(listed.len() as i64));
+        key_metrics.insert("active_employees".to_string(), MetricValue::Count(active.len() as i64));
+        key_metrics.insert("average_salary".to_string(), MetricValue::Currency(to_currency(average_salary)));
+        key_metrics.insert("departments".to_string(), MetricValue::Count(workforce.departments.len() as i64));
+
+        let mut insights = Vec::new();
+        if let Some(range) = &config.date_range {
+            let joiners = listed
+                .iter()
+                .filter(|e| e.hire_date >= range.start_date && e.hire_date <= range.end_date)
+                .count();
+            let leavers = listed
+                .iter()
+                .filter(|e| termination_date(e).is_some_and(|d| d >= range.start_date && d <= range.end_date))
+                .count();
+            insights.push(format!(
+                "{} joined and {} left between {} and {}",
+                joiners, leavers, range.start_date, range.end_date
+            ));
+        }
+        let mut by_department: BTreeMap<String, (i64, i64)> = BTreeMap::new();
+        for employee in &active {
+            let entry = by_department.entry(workforce.department_name(employee.department_id)).or_default();
+            entry.0 += employee.salary as i64;
+            entry.1 += 1;
+        }
+        if let Some((name, (total, count))) = by_department
+            .iter()
+            .max_by_key(|(_, (total, count))| total / count)
+        {
+            insights.push(format!(
+                "{} has the highest average salary ({})",
+                name,
+                format_currency(to_currency(total / count))
+            ));
+        }
+
+        Ok(ReportResult {
+            config: config.clone(),
+            generated_at: Utc::now().naive_utc(),
+            data: ReportData::Table(TableData { headers, rows, totals: None }),
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations: Vec::new(),
+            }),
+            metadata: ReportMetadata {
+                total_records: listed.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["employees".to_string(), "departments".to_string()],
+            },
+        })
+    }
+
+    /// Month-end headcount by department
+    fn generate_headcount_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let mut conn = get_report_connection()?;
+        let workforce = Workforce::load(&mut conn, &config)?;
+        let (start, end) = range_or_last_year(&config);
+        let months = month_ranges(start, end);
+        let department_ids = workforce.department_ids();
+
+        let mut headers = vec!["Month".to_string()];
+        headers.extend(department_ids.iter().map(|id| workforce.department_name(*id)));
+        headers.push("Total".to_string());
+
+        let mut totals_by_month = Vec::new();
+        let rows = months
+            .iter()
+            .map(|(_, month_end)| {
+                let mut row = vec![month_label(*month_end)];
+                row.extend(
+                    department_ids
+                        .iter()
+                        .map(|id| workforce.headcount_at(*month_end, Some(*id)).to_string()),
+                );
+                let total = workforce.headcount_at(*month_end, None);
+                totals_by_month.push(total);
+                row.push(total.to_string());
+                row
+            })
+            .collect();
+
+        let opening = workforce.headcount_at(start.pred_opt().unwrap_or(start), None);
+        let closing = totals_by_month.last().copied().unwrap_or(opening);
+        let mut key_metrics = HashMap::new();
+        key_metrics.insert("headcount".to_string(), MetricValue::Count(closing));
+        key_metrics.insert("headcount_change".to_string(), MetricValue::Count(closing - opening));
+        key_metrics.insert("departments".to_string(), MetricValue::Count(department_ids.len() as i64));
+
+        let mut insights = vec![format!(
+            "Headcount went from {} to {} between {} and {}",
+            opening, closing, start, end
+        )];
+        if let Some(largest) = department_ids
+            .iter()
+            .max_by_key(|id| workforce.headcount_at(end, Some(**id)))
+        {
+            insights.push(format!(
+                "{} is the largest department with {} employees",
+                workforce.department_name(*largest),
+                workforce.headcount_at(end, Some(*largest))
+            ));
+        }
+
+        let chart_labels: Vec<String> = months.iter().map(|(_, month_end)| month_label(*month_end)).collect();
+        let sections = vec![
+            ReportSection {
+                title: "Headcount by Department".to_string(),
+                section_type: SectionType::Summary,
+                data: ReportData::Table(TableData { headers, rows, totals: None }),
+            },
+            ReportSection {
+                title: "Headcount Trend".to_string(),
+                section_type: SectionType::Chart,
+                data: ReportData::Chart(create_line_chart(
+                    chart_labels,
+                    vec![Dataset {
+                        label: "Headcount".to_string(),
+                        data: totals_by_month.iter().map(|c| *c as f64).collect(),
+                        color: Some("#3B82F6".to_string()),
+                    }],
+                )),
+            },
+        ];
+
+        Ok(ReportResult {
+            config: config.clone(),
+            generated_at: Utc::now().naive_utc(),
+            data: ReportData::Mixed(sections),
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations: Vec::new(),
+            }),
+            metadata: ReportMetadata {
+                total_records: workforce.employees.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["employees".to_string(), "departments".to_string()],
+            },
+        })
+    }
+
+    /// Joiners, leavers and turnover rate per month
+    fn generate_turnover_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let mut conn = get_report_connection()?;
+        let workforce = Workforce::load(&mut conn, &config)?;
+        let (start, end) = range_or_last_year(&config);
+
+        let headers = vec![
+            "Month".to_string(),
+            "Opening".to_string(),
+            "Joiners".to_string(),
+            "Leavers".to_string(),
+            "Closing".to_string(),
+            "Turnover Rate".to_string(),
         ];
 
-        let table_data = TableData {
-            headers,
-            rows,
-            totals: None,
+        let count_between = |from: NaiveDate, to: NaiveDate, date_of: &dyn Fn(&Employee) -> Option<NaiveDate>| {
+            workforce
+                .employees
+                .iter()
+                .filter(|e| date_of(e).is_some_and(|d| d >= from && d <= to))
+                .count() as i64
         };
+        let hired = |e: &Employee| Some(e.hire_date);
+        let left = |e: &Employee| termination_date(e);
+
+        let rows = month_ranges(start, end)
+            .into_iter()
+            .map(|(month_start, month_end)| {
+                let opening = workforce.headcount_at(month_start.pred_opt().unwrap_or(month_start), None);
+                let closing = workforce.headcount_at(month_end, None);
+                let leavers = count_between(month_start, month_end, &left);
+                vec![
+                    month_label(month_start),
+                    opening.to_string(),
+                    count_between(month_start, month_end, &hired).to_string(),
+                    leavers.to_string(),
+                    closing.to_string(),
+                    format_percentage(turnover_rate(leavers, opening, closing)),
+                ]
+            })
+            .collect();
+
+        let opening = workforce.headcount_at(start.pred_opt().unwrap_or(start), None);
+        let closing = workforce.headcount_at(end, None);
+        let joiners = count_between(start, end, &hired);
+        let leavers = count_between(start, end, &left);
+        let rate = turnover_rate(leavers, opening, closing);
 
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("total_employees".to_string(), MetricValue::Count(125));
-        key_metrics.insert("active_employees".to_string(), MetricValue::Count(118));
-        key_metrics.insert("average_salary".to_string(), MetricValue::Currency(4750000));
-        key_metrics.insert("departments".to_string(), MetricValue::Count(8));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "Employee headcount increased by 12% compared to last quarter".to_string(),
-                "Engineering department has the highest average salary".to_string(),
-                "New hire retention rate is 94% after 6 months".to_string(),
-            ],
-            recommendations: vec![
-                "Consider salary adjustment for Marketing department".to_string(),
-                "Implement mentorship program for new hires".to_string(),
-            ],
-        };
+        key_metrics.insert("joiners".to_string(), MetricValue::Count(joiners));
+        key_metrics.insert("leavers".to_string(), MetricValue::Count(leavers));
+        key_metrics.insert("turnover_rate".to_string(), MetricValue::Percentage(rate));
 
-        let metadata = ReportMetadata {
-            total_records: 125,
-            processing_time_ms: 45,
-            filters_applied: vec!["active_employees".to_string()],
-            data_sources: vec!["employees".to_string(), "departments".to_string()],
-        };
+        let mut insights = vec![format!(
+            "{} joined and {} left between {} and {}, a turnover rate of {}",
+            joiners,
+            leavers,
+            start,
+            end,
+            format_percentage(rate)
+        )];
+        let recent_leavers = workforce
+            .employees
+            .iter()
+            .filter(|e| termination_date(e).is_some_and(|d| d >= start && d <= end))
+            .filter(|e| {
+                termination_date(e).is_some_and(|d| (d - e.hire_date).num_days() < 365)
+            })
+            .count();
+        if recent_leavers > 0 {
+            insights.push(format!(
+                "{} of the leavers left within their first year",
+                recent_leavers
+            ));
+        }
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
-            data: ReportData::Table(table_data),
-            summary: Some(summary),
-            metadata,
+            data: ReportData::Table(TableData {
+                headers,
+                rows,
+                totals: Some(vec![
+                    "Total".to_string(),
+                    opening.to_string(),
+                    joiners.to_string(),
+                    leavers.to_string(),
+                    closing.to_string(),
+                    format_percentage(rate),
+                ]),
+            }),
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations: Vec::new(),
+            }),
+            metadata: ReportMetadata {
+                total_records: workforce.employees.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["employees".to_string()],
+            },
         })
     }
 
+    /// Attendance and lateness per month and per employee
     fn generate_attendance_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let mut conn = get_report_connection()?;
+        let workforce = Workforce::load(&mut conn, &config)?;
+        let (start, end) = range_or_this_month(&config);
+
+        let employee_ids: Vec<i32> = workforce.employees.iter().map(|e| e.id).collect();
+        let records = attendances::table
+            .filter(attendances::employee_id.eq_any(&employee_ids))
+            .filter(attendances::date.ge(start))
+            .filter(attendances::date.le(end))
+            .order(attendances::date.asc())
+            .load::<Attendance>(&mut conn)?;
+
+        let mut by_month: BTreeMap<String, AttendanceTally> = BTreeMap::new();
+        let mut by_employee: HashMap<i32, AttendanceTally> = HashMap::new();
+        let mut total = AttendanceTally::default();
+        for record in &records {
+            by_month.entry(month_label(record.date)).or_default().add(record);
+            by_employee.entry(record.employee_id).or_default().add(record);
+            total.add(record);
+        }
+
+        let headers = vec![
+            "Records".to_string(),
+            "Present".to_string(),
+            "Late".to_string(),
+            "Early Leave".to_string(),
+            "Absent".to_string(),
+            "Leave".to_string(),
+            "Attendance Rate".to_string(),
+            "Lateness Rate".to_string(),
+            "Overtime Hours".to_string(),
+        ];
+        let with_first = |first: &str| {
+            let mut all = vec![first.to_string()];
+            all.extend(headers.iter().cloned());
+            all
+        };
+
+        let mut employees_by_lateness: Vec<&Employee> = workforce
+            .employees
+            .iter()
+            .filter(|e| by_employee.contains_key(&e.id))
+            .collect();
+        employees_by_lateness.sort_by(|a, b| {
+            let (a_tally, b_tally) = (&by_employee[&a.id], &by_employee[&b.id]);
+            b_tally.late.cmp(&a_tally.late).then(b_tally.absent.cmp(&a_tally.absent))
+        });
+
         let sections = vec![
             ReportSection {
                 title: "Attendance Summary".to_string(),
                 section_type: SectionType::Summary,
                 data: ReportData::Table(TableData {
-                    headers: vec![
-                        "Month".to_string(),
-                        "Total Working Days".to_string(),
-                        "Average Attendance".to_string(),
-                        "Late Arrivals".to_string(),
-                        "Early Departures".to_string(),
-                        "Overtime Hours".to_string(),
-                    ],
-                    rows: vec![
-                        vec![
-                            "January 2024".to_string(),
-                            "22".to_string(),
-                            "96.5%".to_string(),
-                            "15".to_string(),
-                            "8".to_string(),
-                            "245".to_string(),
-                        ],
-                        vec![
-                            "February 2024".to_string(),
-                            "20".to_string(),
-                            "97.2%".to_string(),
-                            "12".to_string(),
-                            "5".to_string(),
-                            "198".to_string(),
-                        ],
-                    ],
-                    totals: Some(vec![
-                        "Total".to_string(),
-                        "42".to_string(),
-                        "96.9%".to_string(),
-                        "27".to_string(),
-                        "13".to_string(),
-                        "443".to_string(),
-                    ]),
+                    headers: with_first("Month"),
+                    rows: by_month.iter().map(|(month, tally)| tally.row(month.clone())).collect(),
+                    totals: Some(total.row("Total".to_string())),
                 }),
             },
             ReportSection {
-                title: "Attendance Trends".to_string(),
-                section_type: SectionType::Chart,
-                data: ReportData::Chart(create_line_chart(
-                    vec!["Jan".to_string(), "Feb".to_string(), "Mar".to_string()],
-                    vec![
-                        Dataset {
-                            label: "Attendance Rate".to_string(),
-                            data: vec![96.5, 97.2, 95.8],
-                            color: Some("#10B981".to_string()),
-                        },
-                        Dataset {
-                            label: "Overtime Hours".to_string(),
-                            data: vec![245.0, 198.0, 287.0],
-                            color: Some("#F59E0B".to_string()),
-                        },
-                    ],
-                )),
+                title: "Attendance by Employee".to_string(),
+                section_type: SectionType::Detail,
+                data: ReportData::Table(TableData {
+                    headers: with_first("Employee"),
+                    rows: employees_by_lateness
+                        .iter()
+                        .map(|e| by_employee[&e.id].row(format!("{} {}", e.employee_code, e.name)))
+                        .collect(),
+                    totals: None,
+                }),
             },
         ];
 
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("average_attendance_rate".to_string(), MetricValue::Percentage(96.9));
-        key_metrics.insert("total_late_arrivals".to_string(), MetricValue::Count(27));
-        key_metrics.insert("total_overtime_hours".to_string(), MetricValue::Number(443.0));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "Attendance rate improved by 0.7% in February".to_string(),
-                "Late arrivals decreased by 20% month-over-month".to_string(),
-                "Overtime hours vary significantly by department".to_string(),
-            ],
-            recommendations: vec![
-                "Implement flexible working hours to reduce late arrivals".to_string(),
-                "Monitor overtime patterns to prevent burnout".to_string(),
-            ],
-        };
+        key_metrics.insert("average_attendance_rate".to_string(), MetricValue::Percentage(total.attendance_rate()));
+        key_metrics.insert("lateness_rate".to_string(), MetricValue::Percentage(total.lateness_rate()));
+        key_metrics.insert("total_late_arrivals".to_string(), MetricValue::Count(total.late));
+        key_metrics.insert("total_absences".to_string(), MetricValue::Count(total.absent));
+        key_metrics.insert("total_overtime_hours".to_string(), MetricValue::Number(total.overtime_hours));
 
-        let metadata = ReportMetadata {
-            total_records: 2580,
-            processing_time_ms: 120,
-            filters_applied: vec!["date_range".to_string()],
-            data_sources: vec!["attendances".to_string(), "employees".to_string()],
-        };
+        let mut insights = vec![format!(
+            "{} attendance records between {} and {}",
+            total.records, start, end
+        )];
+        if let Some(most_late) = employees_by_lateness.first().filter(|e| by_employee[&e.id].late > 0) {
+            insights.push(format!(
+                "{} was late most often ({} times)",
+                most_late.name, by_employee[&most_late.id].late
+            ));
+        }
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
             data: ReportData::Mixed(sections),
-            summary: Some(summary),
-            metadata,
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations: Vec::new(),
+            }),
+            metadata: ReportMetadata {
+                total_records: records.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["attendances".to_string(), "employees".to_string()],
+            },
+        })
+    }
+
+    /// Leave taken against the entitlement earned over the range, per department.
+    ///
+    /// Each employee earns `annual_leave_days` per year, prorated by the days
+    /// they were employed within the range; leave is attendance recorded as
+    /// `holiday`.
+    fn generate_leave_utilization_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let annual_days = filter_i32(&config, "annual_leave_days")?
+            .map(|days| days.max(0) as f64)
+            .unwrap_or(DEFAULT_ANNUAL_LEAVE_DAYS as f64);
+        let mut conn = get_report_connection()?;
+        let workforce = Workforce::load(&mut conn, &config)?;
+        let (start, end) = range_or_last_year(&config);
+
+        let employee_ids: Vec<i32> = workforce.employees.iter().map(|e| e.id).collect();
+        let leave_days: HashMap<i32, i64> = attendances::table
+            .filter(attendances::employee_id.eq_any(&employee_ids))
+            .filter(attendances::status.eq(LEAVE_STATUS))
+            .filter(attendances::date.ge(start))
+            .filter(attendances::date.le(end))
+            .group_by(attendances::employee_id)
+            .select((attendances::employee_id, diesel::dsl::count_star()))
+            .load::<(i32, i64)>(&mut conn)?
+            .into_iter()
+            .collect();
+
+        // (employees, entitlement, taken) per department
+        let mut by_department: BTreeMap<i32, (i64, f64, i64)> = BTreeMap::new();
+        for employee in &workforce.employees {
+            let first = employee.hire_date.max(start);
+            let last = termination_date(employee)
+                .and_then(|d| d.pred_opt())
+                .map_or(end, |d| d.min(end));
+            if first > last {
+                continue;
+            }
+            let employed_days = (last - first).num_days() + 1;
+            let entry = by_department.entry(employee.department_id).or_default();
+            entry.0 += 1;
+            entry.1 += annual_days * employed_days as f64 / 365.0;
+            entry.2 += leave_days.get(&employee.id).copied().unwrap_or(0);
+        }
+
+        let headers = vec![
+            "Department".to_string(),
+            "Employees".to_string(),
+            "Entitlement (days)".to_string(),
+            "Taken (days)".to_string(),
+            "Remaining (days)".to_string(),
+            "Utilization".to_string(),
+        ];
+        let row = |label: String, (count, entitled, taken): (i64, f64, i64)| {
+            vec![
+                label,
+                count.to_string(),
+                format!("{:.1}", entitled),
+                taken.to_string(),
+                format!("{:.1}", (entitled - taken as f64).max(0.0)),
+                format_percentage(utilization(taken, entitled)),
+            ]
+        };
+
+        let mut rows = Vec::new();
+        let mut total = (0i64, 0f64, 0i64);
+        for id in workforce.department_ids() {
+            if let Some(values) = by_department.get(&id) {
+                rows.push(row(workforce.department_name(id), *values));
+                total = (total.0 + values.0, total.1 + values.1, total.2 + values.2);
+            }
+        }
+
+        let mut key_metrics = HashMap::new();
+        key_metrics.insert("leave_days_taken".to_string(), MetricValue::Count(total.2));
+        key_metrics.insert("leave_days_entitled".to_string(), MetricValue::Number(total.1));
+        key_metrics.insert("leave_utilization".to_string(), MetricValue::Percentage(utilization(total.2, total.1)));
+
+        let mut insights = vec![format!(
+            "{} leave days taken of {:.1} earned between {} and {} ({} days a year)",
+            total.2, total.1, start, end, annual_days
+        )];
+        if let Some((id, _)) = by_department
+            .iter()
+            .filter(|(_, (_, entitled, _))| *entitled > 0.0)
+            .min_by(|(_, a), (_, b)| utilization(a.2, a.1).total_cmp(&utilization(b.2, b.1)))
+        {
+            insights.push(format!(
+                "{} has the lowest leave utilization",
+                workforce.department_name(*id)
+            ));
+        }
+
+        Ok(ReportResult {
+            config: config.clone(),
+            generated_at: Utc::now().naive_utc(),
+            data: ReportData::Table(TableData {
+                headers,
+                rows,
+                totals: Some(row("Total".to_string(), total)),
+            }),
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations: Vec::new(),
+            }),
+            metadata: ReportMetadata {
+                total_records: workforce.employees.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["attendances".to_string(), "employees".to_string()],
+            },
+        })
+    }
+
+    /// Required documents current employees are missing or hold only
+    /// expired, listed by department
+    fn generate_document_compliance_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let today = DisplayTimezone::current().today();
+        let mut conn = get_report_connection()?;
+        let gaps = DocumentService::compliance(&mut conn, today, filter_i32(&config, "department_id")?)?;
+
+        let headers = vec![
+            "Department".to_string(),
+            "Employee ID".to_string(),
+            "Name".to_string(),
+            "Document".to_string(),
+            "Status".to_string(),
+            "Expired On".to_string(),
+        ];
+        let rows = gaps
+            .iter()
+            .map(|gap| {
+                vec![
+                    gap.department.clone(),
+                    gap.employee.employee_code.clone(),
+                    gap.employee.name.clone(),
+                    gap.document_type.clone(),
+                    gap.state.to_string(),
+                    gap.expired_on.map(|d| d.to_string()).unwrap_or_default(),
+                ]
+            })
+            .collect();
+
+        // (missing, expired) per department
+        let mut by_department: BTreeMap<&str, (i64, i64)> = BTreeMap::new();
+        for gap in &gaps {
+            let entry = by_department.entry(gap.department.as_str()).or_default();
+            match gap.state {
+                DocumentState::Missing => entry.0 += 1,
+                _ => entry.1 += 1,
+            }
+        }
+        let missing: i64 = by_department.values().map(|(missing, _)| missing).sum();
+        let expired: i64 = by_department.values().map(|(_, expired)| expired).sum();
+        let mut employees: Vec<i32> = gaps.iter().map(|gap| gap.employee.id).collect();
+        employees.dedup();
+
+        let mut key_metrics = HashMap::new();
+        key_metrics.insert("missing_documents".to_string(), MetricValue::Count(missing));
+        key_metrics.insert("expired_documents".to_string(), MetricValue::Count(expired));
+        key_metrics.insert("non_compliant_employees".to_string(), MetricValue::Count(employees.len() as i64));
+
+        let mut insights: Vec<String> = by_department
+            .iter()
+            .map(|(department, (missing, expired))| {
+                format!("{}: {} missing, {} expired", department, missing, expired)
+            })
+            .collect();
+        if gaps.is_empty() {
+            insights.push(format!("Every current employee holds the required documents as of {}", today));
+        }
+
+        Ok(ReportResult {
+            config: config.clone(),
+            generated_at: Utc::now().naive_utc(),
+            data: ReportData::Table(TableData { headers, rows, totals: None }),
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations: Vec::new(),
+            }),
+            metadata: ReportMetadata {
+                total_records: gaps.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec![
+                    "employee_documents".to_string(),
+                    "required_documents".to_string(),
+                    "employees".to_string(),
+                ],
+            },
         })
     }
 
+    /// Payroll totals per department for one period, by default the latest
     fn generate_payroll_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let mut conn = get_report_connection()?;
+        let workforce = Workforce::load(&mut conn, &config)?;
+
+        let period = match config.filters.get("period") {
+            Some(period) => Some(period.clone()),
+            None => payrolls::table
+                .select(payrolls::period)
+                .order(payrolls::period.desc())
+                .first::<String>(&mut conn)
+                .optional()?,
+        };
+        let employee_ids: Vec<i32> = workforce.employees.iter().map(|e| e.id).collect();
+        let records = match &period {
+            Some(period) => payrolls::table
+                .filter(payrolls::period.eq(period))
+                .filter(payrolls::employee_id.eq_any(&employee_ids))
+                .load::<Payroll>(&mut conn)?,
+            None => Vec::new(),
+        };
+
+        let department_of: HashMap<i32, i32> = workforce
+            .employees
+            .iter()
+            .map(|e| (e.id, e.department_id))
+            .collect();
+        // (employees, base, overtime, deductions, net) per department
+        let mut by_department: BTreeMap<i32, [i64; 5]> = BTreeMap::new();
+        for payroll in &records {
+            let department_id = department_of.get(&payroll.employee_id).copied().unwrap_or_default();
+            let entry = by_department.entry(department_id).or_default();
+            entry[0] += 1;
+            entry[1] += payroll.base_salary as i64 + payroll.bonuses.unwrap_or(0) as i64;
+            entry[2] += payroll.overtime_pay.unwrap_or(0) as i64;
+            entry[3] += payroll.deductions.unwrap_or(0) as i64;
+            entry[4] += payroll.net_salary as i64;
+        }
+
         let headers = vec![
             "Department".to_string(),
             "Employee Count".to_string(),
@@ -260,148 +948,160 @@ impl HRReportsGenerator {
             "Net Pay".to_string(),
             "Average per Employee".to_string(),
         ];
-
-        let rows = vec![
-            vec![
-                "Engineering".to_string(),
-                "45".to_string(),
-                "₩225,000,000".to_string(),
-                "₩15,600,000".to_string(),
-                "₩48,000,000".to_string(),
-                "₩192,600,000".to_string(),
-                "₩4,280,000".to_string(),
-            ],
+        let row = |label: String, values: [i64; 5]| {
+            let average = if values[0] > 0 { values[4] / values[0] } else { 0 };
             vec![
-                "Marketing".to_string(),
-                "25".to_string(),
-                "₩100,000,000".to_string(),
-                "₩5,200,000".to_string(),
-                "₩21,000,000".to_string(),
-                "₩84,200,000".to_string(),
-                "₩3,368,000".to_string(),
-            ],
-            vec![
-                "Sales".to_string(),
-                "30".to_string(),
-                "₩135,000,000".to_string(),
-                "₩8,100,000".to_string(),
-                "₩28,600,000".to_string(),
-                "₩114,500,000".to_string(),
-                "₩3,817,000".to_string(),
-            ],
-        ];
-
-        let totals = Some(vec![
-            "Total".to_string(),
-            "100".to_string(),
-            "₩460,000,000".to_string(),
-            "₩28,900,000".to_string(),
-            "₩97,600,000".to_string(),
-            "₩391,300,000".to_string(),
-            "₩3,913,000".to_string(),
-        ]);
-
-        let table_data = TableData {
-            headers,
-            rows,
-            totals,
+                label,
+                values[0].to_string(),
+                format_currency(to_currency(values[1])),
+                format_currency(to_currency(values[2])),
+                format_currency(to_currency(values[3])),
+                format_currency(to_currency(values[4])),
+                format_currency(to_currency(average)),
+            ]
         };
 
+        let mut total = [0i64; 5];
+        let mut rows = Vec::new();
+        for id in workforce.department_ids() {
+            if let Some(values) = by_department.get(&id) {
+                rows.push(row(workforce.department_name(id), *values));
+                for (sum, value) in total.iter_mut().zip(values) {
+                    *sum += value;
+                }
+            }
+        }
+        let average = if total[0] > 0 { total[4] / total[0] } else { 0 };
+        let gross = total[1] + total[2];
+
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("total_payroll".to_string(), MetricValue::Currency(391300000));
-        key_metrics.insert("total_overtime".to_string(), MetricValue::Currency(28900000));
-        key_metrics.insert("average_salary".to_string(), MetricValue::Currency(3913000));
-        key_metrics.insert("payroll_growth".to_string(), MetricValue::Percentage(5.2));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "Engineering department has the highest average salary".to_string(),
-                "Overtime pay represents 6.3% of total payroll".to_string(),
-                "Total payroll increased by 5.2% compared to last month".to_string(),
-            ],
-            recommendations: vec![
-                "Review overtime policies to control costs".to_string(),
-                "Consider salary benchmarking for competitive positioning".to_string(),
-            ],
-        };
+        key_metrics.insert("total_payroll".to_string(), MetricValue::Currency(to_currency(total[4])));
+        key_metrics.insert("total_overtime".to_string(), MetricValue::Currency(to_currency(total[2])));
+        key_metrics.insert("average_salary".to_string(), MetricValue::Currency(to_currency(average)));
 
-        let metadata = ReportMetadata {
-            total_records: 100,
-            processing_time_ms: 85,
-            filters_applied: vec!["current_month".to_string()],
-            data_sources: vec!["payrolls".to_string(), "employees".to_string(), "departments".to_string()],
-        };
+        let mut insights = Vec::new();
+        match &period {
+            Some(period) => insights.push(format!(
+                "{} payroll(s) in period {}",
+                records.len(),
+                period
+            )),
+            None => insights.push("No payrolls have been generated yet".to_string()),
+        }
+        if gross > 0 {
+            insights.push(format!(
+                "Overtime pay is {} of gross payroll",
+                format_percentage(percentage(total[2], gross))
+            ));
+        }
+
+        let mut filters_applied = applied_filters(&config);
+        if let (Some(period), false) = (&period, config.filters.contains_key("period")) {
+            filters_applied.push(format!("period={} (latest)", period));
+        }
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
-            data: ReportData::Table(table_data),
-            summary: Some(summary),
-            metadata,
+            data: ReportData::Table(TableData {
+                headers,
+                rows,
+                totals: Some(row("Total".to_string(), total)),
+            }),
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations: Vec::new(),
+            }),
+            metadata: ReportMetadata {
+                total_records: records.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied,
+                data_sources: vec!["payrolls".to_string(), "employees".to_string(), "departments".to_string()],
+            },
         })
     }
 
     fn generate_hr_analytics_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let mut conn = get_report_connection()?;
+        let workforce = Workforce::load(&mut conn, &config)?;
+        let (start, end) = range_or_last_year(&config);
+        let department_ids = workforce.department_ids();
+
+        let current: Vec<&Employee> = workforce
+            .employees
+            .iter()
+            .filter(|e| employed_on(e, end))
+            .collect();
+
+        const SALARY_BAND_LIMITS: [i32; 4] = [3_000_000, 4_000_000, 5_000_000, 6_000_000];
+        let mut band_labels = vec![format!("< {}", format_currency(SALARY_BAND_LIMITS[0]))];
+        band_labels.extend(SALARY_BAND_LIMITS.windows(2).map(|pair| {
+            format!("{} - {}", format_currency(pair[0]), format_currency(pair[1]))
+        }));
+        band_labels.push(format!("≥ {}", format_currency(SALARY_BAND_LIMITS[3])));
+        let mut band_counts = vec![0f64; band_labels.len()];
+        for employee in &current {
+            let band = SALARY_BAND_LIMITS
+                .iter()
+                .position(|limit| employee.salary < *limit)
+                .unwrap_or(SALARY_BAND_LIMITS.len());
+            band_counts[band] += 1.0;
+        }
+
+        let opening = workforce.headcount_at(start.pred_opt().unwrap_or(start), None);
+        let closing = current.len() as i64;
+        let leavers = workforce
+            .employees
+            .iter()
+            .filter(|e| termination_date(e).is_some_and(|d| d >= start && d <= end))
+            .count() as i64;
+        let rate = turnover_rate(leavers, opening, closing);
+
+        let employee_ids: Vec<i32> = workforce.employees.iter().map(|e| e.id).collect();
+        let mut attendance = AttendanceTally::default();
+        for record in attendances::table
+            .filter(attendances::employee_id.eq_any(&employee_ids))
+            .filter(attendances::date.ge(start))
+            .filter(attendances::date.le(end))
+            .load::<Attendance>(&mut conn)?
+        {
+            attendance.add(&record);
+        }
+        let absenteeism = percentage(attendance.absent, attendance.attended() + attendance.absent);
+
         let sections = vec![
             ReportSection {
                 title: "Workforce Analytics".to_string(),
                 section_type: SectionType::Analysis,
                 data: ReportData::Chart(create_pie_chart(
-                    vec![
-                        "Engineering".to_string(),
-                        "Marketing".to_string(),
-                        "Sales".to_string(),
-                        "HR".to_string(),
-                        "Finance".to_string(),
-                    ],
-                    vec![45.0, 25.0, 30.0, 12.0, 15.0],
+                    department_ids.iter().map(|id| workforce.department_name(*id)).collect(),
+                    department_ids
+                        .iter()
+                        .map(|id| workforce.headcount_at(end, Some(*id)) as f64)
+                        .collect(),
                 )),
             },
             ReportSection {
                 title: "Salary Distribution".to_string(),
                 section_type: SectionType::Chart,
                 data: ReportData::Chart(create_bar_chart(
-                    vec![
-                        "< ₩3M".to_string(),
-                        "₩3-4M".to_string(),
-                        "₩4-5M".to_string(),
-                        "₩5-6M".to_string(),
-                        "> ₩6M".to_string(),
-                    ],
-                    vec![15.0, 35.0, 28.0, 18.0, 4.0],
+                    band_labels,
+                    band_counts,
                     "Employee Count",
                 )),
             },
             ReportSection {
-                title: "Performance Metrics".to_string(),
+                title: "Workforce Metrics".to_string(),
                 section_type: SectionType::Summary,
                 data: ReportData::Table(TableData {
-                    headers: vec![
-                        "Metric".to_string(),
-                        "Current Quarter".to_string(),
-                        "Previous Quarter".to_string(),
-                        "Change".to_string(),
-                    ],
+                    headers: vec!["Metric".to_string(), "Value".to_string()],
                     rows: vec![
-                        vec![
-                            "Employee Satisfaction".to_string(),
-                            "4.2/5.0".to_string(),
-                            "4.0/5.0".to_string(),
-                            "+5.0%".to_string(),
-                        ],
-                        vec![
-                            "Turnover Rate".to_string(),
-                            "3.2%".to_string(),
-                            "4.1%".to_string(),
-                            "-22.0%".to_string(),
-                        ],
-                        vec![
-                            "Absenteeism Rate".to_string(),
-                            "2.8%".to_string(),
-                            "3.5%".to_string(),
-                            "-20.0%".to_string(),
-                        ],
+                        vec!["Headcount".to_string(), closing.to_string()],
+                        vec!["Turnover Rate".to_string(), format_percentage(rate)],
+                        vec!["Absenteeism Rate".to_string(), format_percentage(absenteeism)],
+                        vec!["Lateness Rate".to_string(), format_percentage(attendance.lateness_rate())],
                     ],
                     totals: None,
                 }),
@@ -409,50 +1109,108 @@ impl HRReportsGenerator {
         ];
 
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("employee_satisfaction".to_string(), MetricValue::Number(4.2));
-        key_metrics.insert("turnover_rate".to_string(), MetricValue::Percentage(3.2));
-        key_metrics.insert("absenteeism_rate".to_string(), MetricValue::Percentage(2.8));
-        key_metrics.insert("training_hours".to_string(), MetricValue::Number(1250.0));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "Employee satisfaction improved significantly this quarter".to_string(),
-                "Turnover rate is below industry average of 4.5%".to_string(),
-                "Absenteeism decreased due to wellness programs".to_string(),
-                "Training investment yielded positive engagement results".to_string(),
-            ],
-            recommendations: vec![
-                "Continue wellness program initiatives".to_string(),
-                "Implement mentorship program for junior staff".to_string(),
-                "Consider performance bonus structure review".to_string(),
-            ],
-        };
-
-        let metadata = ReportMetadata {
-            total_records: 127,
-            processing_time_ms: 250,
-            filters_applied: vec!["current_quarter".to_string()],
-            data_sources: vec![
-                "employees".to_string(),
-                "attendances".to_string(),
-                "payrolls".to_string(),
-                "performance_reviews".to_string(),
-            ],
-        };
+        key_metrics.insert("headcount".to_string(), MetricValue::Count(closing));
+        key_metrics.insert("turnover_rate".to_string(), MetricValue::Percentage(rate));
+        key_metrics.insert("absenteeism_rate".to_string(), MetricValue::Percentage(absenteeism));
+        key_metrics.insert("lateness_rate".to_string(), MetricValue::Percentage(attendance.lateness_rate()));
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
             data: ReportData::Mixed(sections),
-            summary: Some(summary),
-            metadata,
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights: vec![format!(
+                    "Between {} and {}: headcount {} → {}, {} leaver(s)",
+                    start, end, opening, closing, leavers
+                )],
+                recommendations: Vec::new(),
+            }),
+            metadata: ReportMetadata {
+                total_records: workforce.employees.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec![
+                    "employees".to_string(),
+                    "attendances".to_string(),
+                    "departments".to_string(),
+                ],
+            },
         })
     }
 }
 
+/// Leave taken as a percentage of the leave earned
+fn utilization(taken: i64, entitled: f64) -> f64 {
+    if entitled > 0.0 {
+        taken as f64 / entitled * 100.0
+    } else {
+        0.0
+    }
+}
+
 impl Default for HRReportsGenerator {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn employee(hire_date: &str, status: &str, termination_date: Option<&str>) -> Employee {
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        Employee {
+            id: 1,
+            employee_code: "EMP001".to_string(),
+            name: "Kim".to_string(),
+            email: None,
+            phone: None,
+            department_id: 1,
+            position: "Engineer".to_string(),
+            hire_date: date(hire_date),
+            salary: 4_000_000,
+            status: status.to_string(),
+            created_at: date("2024-01-01").and_hms_opt(0, 0, 0).unwrap(),
+            updated_at: date("2024-06-30").and_hms_opt(9, 0, 0).unwrap(),
+            termination_date: termination_date.map(date),
+            level: None,
+        }
+    }
+
+    #[test]
+    fn test_employed_on() {
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        let leaver = employee("2024-02-01", "terminated", Some("2024-05-15"));
+        assert!(!employed_on(&leaver, date("2024-01-31")));
+        assert!(employed_on(&leaver, date("2024-02-01")));
+        assert!(employed_on(&leaver, date("2024-05-14")));
+        assert!(!employed_on(&leaver, date("2024-05-15")));
+
+        // Terminated before termination dates were recorded: last update
+        let legacy = employee("2024-02-01", "terminated", None);
+        assert!(employed_on(&legacy, date("2024-06-29")));
+        assert!(!employed_on(&legacy, date("2024-06-30")));
+
+        assert!(employed_on(&employee("2024-02-01", "inactive", None), date("2025-01-01")));
+    }
+
+    #[test]
+    fn test_month_ranges_and_rates() {
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        let months = month_ranges(date("2024-11-15"), date("2025-01-10"));
+        assert_eq!(
+            months,
+            vec![
+                (date("2024-11-15"), date("2024-11-30")),
+                (date("2024-12-01"), date("2024-12-31")),
+                (date("2025-01-01"), date("2025-01-10")),
+            ]
+        );
+
+        assert_eq!(turnover_rate(2, 18, 22), 10.0);
+        assert_eq!(turnover_rate(0, 0, 0), 0.0);
+        assert_eq!(utilization(5, 10.0), 50.0);
+    }
+}
@@ -1,8 +1,21 @@
 use chrono::Utc;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use crate::core::result::CLIERPResult;
+use crate::database::get_report_connection;
+use crate::modules::inventory::{
+    InventoryAgingService, OrderReconciliationService, ReconciledLine, ReconciliationStatus, AGING_BUCKETS,
+};
+use crate::utils::formatting::format_currency as format_amount;
+use crate::utils::timezone::DisplayTimezone;
+use super::data_source::*;
 use super::engine::*;
 
+/// Products listed under aging high-value stock
+const AGED_STOCK_HIGHLIGHTS: usize = 10;
+
+/// Cumulative shares of issue value that close classes A and B
+const ABC_CLASS_LIMITS: (f64, f64) = (80.0, 95.0);
+
 pub struct InventoryReportsGenerator;
 
 impl ReportGenerator for InventoryReportsGenerator {
@@ -14,6 +27,8 @@ impl ReportGenerator for InventoryReportsGenerator {
             "purchase_analysis" => self.generate_purchase_analysis_report(config),
             "supplier_performance" => self.generate_supplier_performance_report(config),
             "abc_analysis" => self.generate_abc_analysis_report(config),
+            "inventory_aging" => self.generate_inventory_aging_report(config),
+            "order_reconciliation" => self.generate_order_reconciliation_report(config),
             _ => Err(crate::core::error::CLIERPError::NotFound(
                 format!("Inventory report '{}' not found", config.title)
             )),
@@ -38,6 +53,7 @@ impl ReportGenerator for InventoryReportsGenerator {
                 default_value: None,
                 options: Some(vec![
                     FilterOption { value: "all".to_string(), label: "All Levels".to_string() },
+                    FilterOption { value: "normal".to_string(), label: "Normal".to_string() },
                     FilterOption { value: "low".to_string(), label: "Low Stock".to_string() },
                     FilterOption { value: "out".to_string(), label: "Out of Stock".to_string() },
                     FilterOption { value: "overstocked".to_string(), label: "Overstocked".to_string() },
@@ -61,7 +77,6 @@ impl ReportGenerator for InventoryReportsGenerator {
                     FilterOption { value: "in".to_string(), label: "Stock In".to_string() },
                     FilterOption { value: "out".to_string(), label: "Stock Out".to_string() },
                     FilterOption { value: "adjustment".to_string(), label: "Adjustments".to_string() },
-                    FilterOption { value: "transfer".to_string(), label: "Transfers".to_string() },
                 ]),
             },
         ]
@@ -78,6 +93,7 @@ impl ReportGenerator for InventoryReportsGenerator {
                 ReportFormat::Csv,
                 ReportFormat::Html,
                 ReportFormat::Text,
+                ReportFormat::Pdf,
             ],
         }
     }
@@ -89,72 +105,51 @@ impl InventoryReportsGenerator {
     }
 
     fn generate_stock_status_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let filters = ReportFilters::from_config(&config)?;
+        let mut conn = get_report_connection()?;
+        let products = ReportDataSource::products(&mut conn, &filters)?;
+
+        let mut by_level: BTreeMap<StockLevel, i64> = BTreeMap::new();
+        let rows = products
+            .iter()
+            .map(|product| {
+                *by_level.entry(product.level()).or_default() += 1;
+                vec![
+                    product.sku.clone(),
+                    product.name.clone(),
+                    product.category.clone(),
+                    product.current_stock.to_string(),
+                    product.min_stock_level.to_string(),
+                    product.max_stock_level.map(|max| max.to_string()).unwrap_or_else(|| "-".to_string()),
+                    format_amount(product.value()),
+                    product.level().label().to_string(),
+                ]
+            })
+            .collect();
+        let total_value: i64 = products.iter().map(ProductRow::value).sum();
+        let total_units: i64 = products.iter().map(|p| i64::from(p.current_stock.max(0))).sum();
+        let categories: BTreeSet<&str> = products.iter().map(|p| p.category.as_str()).collect();
+        let count = |level: StockLevel| by_level.get(&level).copied().unwrap_or(0);
+
         let sections = vec![
             ReportSection {
                 title: "Current Stock Levels".to_string(),
                 section_type: SectionType::Detail,
                 data: ReportData::Table(TableData {
-                    headers: vec![
-                        "SKU".to_string(),
-                        "Product Name".to_string(),
-                        "Category".to_string(),
-                        "Current Stock".to_string(),
-                        "Min Stock".to_string(),
-                        "Max Stock".to_string(),
-                        "Value".to_string(),
-                        "Status".to_string(),
-                    ],
-                    rows: vec![
-                        vec![
-                            "PRD001".to_string(),
-                            "Laptop Computer".to_string(),
-                            "Electronics".to_string(),
-                            "45".to_string(),
-                            "20".to_string(),
-                            "100".to_string(),
-                            "₩67,500,000".to_string(),
-                            "Normal".to_string(),
-                        ],
-                        vec![
-                            "PRD002".to_string(),
-                            "Office Chair".to_string(),
-                            "Furniture".to_string(),
-                            "8".to_string(),
-                            "15".to_string(),
-                            "50".to_string(),
-                            "₩2,400,000".to_string(),
-                            "Low Stock".to_string(),
-                        ],
-                        vec![
-                            "PRD003".to_string(),
-                            "Printer Paper".to_string(),
-                            "Office Supplies".to_string(),
-                            "0".to_string(),
-                            "100".to_string(),
-                            "500".to_string(),
-                            "₩0".to_string(),
-                            "Out of Stock".to_string(),
-                        ],
-                        vec![
-                            "PRD004".to_string(),
-                            "Desk Lamp".to_string(),
-                            "Furniture".to_string(),
-                            "85".to_string(),
-                            "10".to_string(),
-                            "30".to_string(),
-                            "₩4,250,000".to_string(),
-                            "Overstocked".to_string(),
-                        ],
-                    ],
+                    headers: header_row(&[
+                        "SKU", "Product Name", "Category", "Current Stock", "Min Stock", "Max Stock", "Value", "Status",
+                    ]),
+                    rows,
                     totals: Some(vec![
                         "Total".to_string(),
-                        "".to_string(),
-                        "4 Categories".to_string(),
-                        "138 Units".to_string(),
-                        "".to_string(),
-                        "".to_string(),
-                        "₩74,150,000".to_string(),
-                        "".to_string(),
+                        format!("{} Products", products.len()),
+                        format!("{} Categories", categories.len()),
+                        format!("{} Units", total_units),
+                        String::new(),
+                        String::new(),
+                        format_amount(total_value),
+                        String::new(),
                     ]),
                 }),
             },
@@ -162,261 +157,249 @@ impl InventoryReportsGenerator {
                 title: "Stock Distribution".to_string(),
                 section_type: SectionType::Chart,
                 data: ReportData::Chart(create_pie_chart(
-                    vec![
-                        "Normal Stock".to_string(),
-                        "Low Stock".to_string(),
-                        "Out of Stock".to_string(),
-                        "Overstocked".to_string(),
-                    ],
-                    vec![65.0, 20.0, 8.0, 7.0],
+                    by_level.keys().map(|level| level.label().to_string()).collect(),
+                    by_level.values().map(|count| *count as f64).collect(),
                 )),
             },
         ];
 
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("total_products".to_string(), MetricValue::Count(1250));
-        key_metrics.insert("total_stock_value".to_string(), MetricValue::Currency(2840000000));
-        key_metrics.insert("low_stock_items".to_string(), MetricValue::Count(85));
-        key_metrics.insert("out_of_stock_items".to_string(), MetricValue::Count(12));
-        key_metrics.insert("overstocked_items".to_string(), MetricValue::Count(28));
-        key_metrics.insert("stock_turnover_ratio".to_string(), MetricValue::Number(6.8));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "65% of products are at normal stock levels".to_string(),
-                "20% of products require immediate restocking".to_string(),
-                "Total inventory value of ₩2.84 billion".to_string(),
-                "Stock turnover ratio of 6.8 indicates healthy inventory movement".to_string(),
-                "Electronics category has highest inventory value".to_string(),
-            ],
-            recommendations: vec![
-                "Immediate reorder needed for 12 out-of-stock items".to_string(),
-                "Review max stock levels for overstocked items".to_string(),
-                "Implement automated reorder points for critical items".to_string(),
-                "Consider markdown strategy for slow-moving inventory".to_string(),
-            ],
-        };
+        key_metrics.insert("total_products".to_string(), MetricValue::Count(products.len() as i64));
+        key_metrics.insert("total_stock_value".to_string(), MetricValue::Text(format_amount(total_value)));
+        key_metrics.insert("low_stock_items".to_string(), MetricValue::Count(count(StockLevel::Low)));
+        key_metrics.insert("out_of_stock_items".to_string(), MetricValue::Count(count(StockLevel::Out)));
+        key_metrics.insert("overstocked_items".to_string(), MetricValue::Count(count(StockLevel::Overstocked)));
 
-        let metadata = ReportMetadata {
-            total_records: 1250,
-            processing_time_ms: 280,
-            filters_applied: vec!["active_products".to_string()],
-            data_sources: vec!["products".to_string(), "stock_movements".to_string(), "categories".to_string()],
-        };
+        let mut insights = vec![format!(
+            "{} of {} products ({}) are at normal stock levels, worth {} in total",
+            count(StockLevel::Normal),
+            products.len(),
+            format_percentage(share(count(StockLevel::Normal), products.len() as i64)),
+            format_amount(total_value)
+        )];
+        if let Some(top) = products.iter().max_by_key(|p| p.value()).filter(|p| p.value() > 0) {
+            insights.push(format!("{} ({}) holds the most stock value at {}", top.name, top.sku, format_amount(top.value())));
+        }
+        let mut recommendations = Vec::new();
+        if count(StockLevel::Out) > 0 {
+            recommendations.push(format!("Reorder {} out-of-stock product(s)", count(StockLevel::Out)));
+        }
+        if count(StockLevel::Low) > 0 {
+            recommendations.push(format!("Restock {} product(s) at or below their minimum level", count(StockLevel::Low)));
+        }
+        if count(StockLevel::Overstocked) > 0 {
+            recommendations.push(format!(
+                "Review maximum levels or run promotions for {} overstocked product(s)",
+                count(StockLevel::Overstocked)
+            ));
+        }
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
             data: ReportData::Mixed(sections),
-            summary: Some(summary),
-            metadata,
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: products.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["products".to_string(), "categories".to_string()],
+            },
         })
     }
 
     fn generate_stock_movement_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
-        let table_data = TableData {
-            headers: vec![
-                "Date".to_string(),
-                "SKU".to_string(),
-                "Product".to_string(),
-                "Movement Type".to_string(),
-                "Quantity".to_string(),
-                "Unit Cost".to_string(),
-                "Total Value".to_string(),
-                "Reference".to_string(),
-            ],
-            rows: vec![
-                vec![
-                    "2024-03-15".to_string(),
-                    "PRD001".to_string(),
-                    "Laptop Computer".to_string(),
-                    "Stock In".to_string(),
-                    "+20".to_string(),
-                    "₩1,500,000".to_string(),
-                    "₩30,000,000".to_string(),
-                    "PO-2024-001".to_string(),
-                ],
-                vec![
-                    "2024-03-14".to_string(),
-                    "PRD002".to_string(),
-                    "Office Chair".to_string(),
-                    "Stock Out".to_string(),
-                    "-5".to_string(),
-                    "₩300,000".to_string(),
-                    "₩1,500,000".to_string(),
-                    "SO-2024-028".to_string(),
-                ],
-                vec![
-                    "2024-03-13".to_string(),
-                    "PRD003".to_string(),
-                    "Printer Paper".to_string(),
-                    "Stock Out".to_string(),
-                    "-100".to_string(),
-                    "₩5,000".to_string(),
-                    "₩500,000".to_string(),
-                    "SO-2024-027".to_string(),
-                ],
-                vec![
-                    "2024-03-12".to_string(),
-                    "PRD004".to_string(),
-                    "Desk Lamp".to_string(),
-                    "Adjustment".to_string(),
-                    "+2".to_string(),
-                    "₩50,000".to_string(),
-                    "₩100,000".to_string(),
-                    "ADJ-2024-003".to_string(),
-                ],
+        let start_time = std::time::Instant::now();
+        let filters = ReportFilters::from_config(&config)?;
+        let mut conn = get_report_connection()?;
+        let movements = ReportDataSource::stock_movements(&mut conn, &filters)?;
+        let timezone = DisplayTimezone::current();
+
+        let rows = movements
+            .iter()
+            .map(|movement| {
                 vec![
-                    "2024-03-11".to_string(),
-                    "PRD001".to_string(),
-                    "Laptop Computer".to_string(),
-                    "Stock Out".to_string(),
-                    "-3".to_string(),
-                    "₩1,500,000".to_string(),
-                    "₩4,500,000".to_string(),
-                    "SO-2024-026".to_string(),
-                ],
-            ],
-            totals: Some(vec![
-                "Totals".to_string(),
-                "".to_string(),
-                "".to_string(),
-                "".to_string(),
-                "Net: -86".to_string(),
-                "".to_string(),
-                "₩36,600,000".to_string(),
-                "5 Movements".to_string(),
-            ]),
+                    timezone.to_local(movement.moved_at).date().to_string(),
+                    movement.sku.clone(),
+                    movement.product.clone(),
+                    movement_label(&movement.movement_type).to_string(),
+                    format!("{:+}", movement.quantity),
+                    format_amount(movement.unit_cost),
+                    format_amount(movement.value()),
+                    movement.reference.clone().unwrap_or_default(),
+                ]
+            })
+            .collect();
+
+        let value_of = |kind: &str| -> i64 {
+            movements.iter().filter(|m| m.movement_type == kind).map(MovementRow::value).sum()
+        };
+        let in_value = value_of("in");
+        let out_value = value_of("out");
+        let net_quantity: i64 = movements.iter().map(|m| i64::from(m.quantity)).sum();
+        let net_value: i64 = movements.iter().map(|m| m.value() * i64::from(m.quantity.signum())).sum();
+        let days = match (&filters.range, movements.last(), movements.first()) {
+            (Some(range), _, _) => (range.end_date - range.start_date).num_days() + 1,
+            (None, Some(oldest), Some(newest)) => (newest.moved_at.date() - oldest.moved_at.date()).num_days() + 1,
+            _ => 1,
         };
 
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("total_movements".to_string(), MetricValue::Count(847));
-        key_metrics.insert("stock_in_value".to_string(), MetricValue::Currency(1250000000));
-        key_metrics.insert("stock_out_value".to_string(), MetricValue::Currency(980000000));
-        key_metrics.insert("net_movement_value".to_string(), MetricValue::Currency(270000000));
-        key_metrics.insert("average_daily_movements".to_string(), MetricValue::Number(28.2));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "847 stock movements recorded in the selected period".to_string(),
-                "Net positive stock movement of ₩270 million".to_string(),
-                "Stock inflows primarily from purchase orders".to_string(),
-                "Average of 28.2 movements per day indicates active inventory".to_string(),
-                "Electronics category shows highest movement frequency".to_string(),
-            ],
-            recommendations: vec![
-                "Review high-frequency movement items for automation opportunities".to_string(),
-                "Implement real-time tracking for high-value items".to_string(),
-                "Optimize reorder quantities based on movement patterns".to_string(),
-                "Consider cycle counting for fast-moving items".to_string(),
-            ],
-        };
+        key_metrics.insert("total_movements".to_string(), MetricValue::Count(movements.len() as i64));
+        key_metrics.insert("stock_in_value".to_string(), MetricValue::Text(format_amount(in_value)));
+        key_metrics.insert("stock_out_value".to_string(), MetricValue::Text(format_amount(out_value)));
+        key_metrics.insert("net_movement_value".to_string(), MetricValue::Text(format_amount(net_value)));
+        key_metrics.insert(
+            "average_daily_movements".to_string(),
+            MetricValue::Number(movements.len() as f64 / days.max(1) as f64),
+        );
 
-        let metadata = ReportMetadata {
-            total_records: 847,
-            processing_time_ms: 195,
-            filters_applied: vec!["date_range".to_string(), "movement_types".to_string()],
-            data_sources: vec!["stock_movements".to_string(), "products".to_string()],
-        };
+        // Products moved most often
+        let mut frequency: HashMap<(&str, &str), i64> = HashMap::new();
+        for movement in &movements {
+            *frequency.entry((movement.sku.as_str(), movement.product.as_str())).or_default() += 1;
+        }
+        let busiest = frequency.iter().max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)));
+
+        let mut insights = vec![format!(
+            "{} stock movements: {} received, {} issued, net {:+} units",
+            movements.len(),
+            format_amount(in_value),
+            format_amount(out_value),
+            net_quantity
+        )];
+        if let Some(((sku, name), count)) = busiest {
+            insights.push(format!("{} ({}) moved most often, {} times", name, sku, count));
+        }
+        let adjustments = movements.iter().filter(|m| m.movement_type == "adjustment").count();
+        let mut recommendations = Vec::new();
+        if adjustments * 10 > movements.len() && adjustments > 0 {
+            recommendations.push(format!(
+                "{} of {} movements are adjustments; schedule stock counts to find the cause",
+                adjustments,
+                movements.len()
+            ));
+        }
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
-            data: ReportData::Table(table_data),
-            summary: Some(summary),
-            metadata,
+            data: ReportData::Table(TableData {
+                headers: header_row(&[
+                    "Date", "SKU", "Product", "Movement Type", "Quantity", "Unit Cost", "Total Value", "Reference",
+                ]),
+                rows,
+                totals: Some(vec![
+                    "Totals".to_string(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    format!("Net: {:+}", net_quantity),
+                    String::new(),
+                    format_amount(net_value),
+                    format!("{} Movements", movements.len()),
+                ]),
+            }),
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: movements.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["stock_movements".to_string(), "products".to_string()],
+            },
         })
     }
 
     fn generate_inventory_valuation_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let filters = ReportFilters::from_config(&config)?;
+        let mut conn = get_report_connection()?;
+        let products = ReportDataSource::products(&mut conn, &filters)?;
+        let usage_filters = filters.or_recent(365);
+        let movements = ReportDataSource::stock_movements(&mut conn, &usage_filters)?;
+
+        // Product count, units and value per category
+        let mut by_category: BTreeMap<&str, (i64, i64, i64)> = BTreeMap::new();
+        for product in &products {
+            let entry = by_category.entry(product.category.as_str()).or_default();
+            entry.0 += 1;
+            entry.1 += i64::from(product.current_stock.max(0));
+            entry.2 += product.value();
+        }
+        let total_value: i64 = by_category.values().map(|c| c.2).sum();
+        let total_units: i64 = by_category.values().map(|c| c.1).sum();
+        let average_cost = |value: i64, units: i64| if units == 0 { 0 } else { value / units };
+        let rows = by_category
+            .iter()
+            .map(|(category, (count, units, value))| {
+                vec![
+                    category.to_string(),
+                    count.to_string(),
+                    units.to_string(),
+                    format_amount(average_cost(*value, *units)),
+                    format_amount(*value),
+                    format_percentage(share(*value, total_value)),
+                ]
+            })
+            .collect();
+
+        // Receipts and issues at cost per month of the usage period
+        let mut by_month: BTreeMap<String, (f64, f64)> = BTreeMap::new();
+        for movement in &movements {
+            let entry = by_month.entry(movement.moved_at.format("%Y-%m").to_string()).or_default();
+            match movement.movement_type.as_str() {
+                "in" => entry.0 += movement.value() as f64,
+                "out" => entry.1 += movement.value() as f64,
+                _ => {}
+            }
+        }
+        let (period_start, period_end) = usage_filters.period_or((DisplayTimezone::current().today(), DisplayTimezone::current().today()));
+        let days = (period_end - period_start).num_days() + 1;
+        let out_value: i64 = movements.iter().filter(|m| m.movement_type == "out").map(MovementRow::value).sum();
+        let annual_cost_of_sales = out_value as f64 * 365.0 / days.max(1) as f64;
+        let turnover = if total_value == 0 { 0.0 } else { annual_cost_of_sales / total_value as f64 };
+
         let sections = vec![
             ReportSection {
                 title: "Valuation by Category".to_string(),
                 section_type: SectionType::Detail,
                 data: ReportData::Table(TableData {
-                    headers: vec![
-                        "Category".to_string(),
-                        "Product Count".to_string(),
-                        "Total Quantity".to_string(),
-                        "Average Cost".to_string(),
-                        "Total Value".to_string(),
-                        "% of Total".to_string(),
-                    ],
-                    rows: vec![
-                        vec![
-                            "Electronics".to_string(),
-                            "125".to_string(),
-                            "1,245".to_string(),
-                            "₩1,250,000".to_string(),
-                            "₩1,556,250,000".to_string(),
-                            "54.8%".to_string(),
-                        ],
-                        vec![
-                            "Furniture".to_string(),
-                            "85".to_string(),
-                            "890".to_string(),
-                            "₩450,000".to_string(),
-                            "₩400,500,000".to_string(),
-                            "14.1%".to_string(),
-                        ],
-                        vec![
-                            "Office Supplies".to_string(),
-                            "250".to_string(),
-                            "5,670".to_string(),
-                            "₩15,000".to_string(),
-                            "₩85,050,000".to_string(),
-                            "3.0%".to_string(),
-                        ],
-                        vec![
-                            "Manufacturing".to_string(),
-                            "180".to_string(),
-                            "2,340".to_string(),
-                            "₩320,000".to_string(),
-                            "₩748,800,000".to_string(),
-                            "26.4%".to_string(),
-                        ],
-                        vec![
-                            "Safety Equipment".to_string(),
-                            "65".to_string(),
-                            "780".to_string(),
-                            "₩65,000".to_string(),
-                            "₩50,700,000".to_string(),
-                            "1.8%".to_string(),
-                        ],
-                    ],
+                    headers: header_row(&[
+                        "Category", "Product Count", "Total Quantity", "Average Cost", "Total Value", "% of Total",
+                    ]),
+                    rows,
                     totals: Some(vec![
                         "Total".to_string(),
-                        "705".to_string(),
-                        "10,925".to_string(),
-                        "₩259,864".to_string(),
-                        "₩2,841,300,000".to_string(),
-                        "100.0%".to_string(),
+                        products.len().to_string(),
+                        total_units.to_string(),
+                        format_amount(average_cost(total_value, total_units)),
+                        format_amount(total_value),
+                        format_percentage(if total_value == 0 { 0.0 } else { 100.0 }),
                     ]),
                 }),
             },
             ReportSection {
-                title: "Valuation Trends".to_string(),
+                title: format!("Movement Value by Month, {} to {}", period_start, period_end),
                 section_type: SectionType::Chart,
                 data: ReportData::Chart(create_line_chart(
-                    vec![
-                        "Jan".to_string(),
-                        "Feb".to_string(),
-                        "Mar".to_string(),
-                        "Apr".to_string(),
-                        "May".to_string(),
-                        "Jun".to_string(),
-                    ],
+                    by_month.keys().cloned().collect(),
                     vec![
                         Dataset {
-                            label: "Total Inventory Value (Billions)".to_string(),
-                            data: vec![2.65, 2.72, 2.84, 2.91, 2.88, 2.84],
+                            label: "Received at Cost".to_string(),
+                            data: by_month.values().map(|m| m.0).collect(),
                             color: Some("#3B82F6".to_string()),
                         },
                         Dataset {
-                            label: "Monthly Movement Value (Millions)".to_string(),
-                            data: vec![450.0, 520.0, 480.0, 580.0, 610.0, 590.0],
+                            label: "Issued at Cost".to_string(),
+                            data: by_month.values().map(|m| m.1).collect(),
                             color: Some("#10B981".to_string()),
                         },
                     ],
@@ -425,301 +408,371 @@ impl InventoryReportsGenerator {
         ];
 
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("total_inventory_value".to_string(), MetricValue::Currency(2841300000));
-        key_metrics.insert("average_unit_cost".to_string(), MetricValue::Currency(259864));
-        key_metrics.insert("inventory_turnover".to_string(), MetricValue::Number(6.8));
-        key_metrics.insert("days_in_inventory".to_string(), MetricValue::Number(53.7));
-        key_metrics.insert("obsolete_inventory_risk".to_string(), MetricValue::Percentage(2.3));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "Total inventory value of ₩2.84 billion across 705 products".to_string(),
-                "Electronics represent 54.8% of total inventory value".to_string(),
-                "Inventory turnover of 6.8 indicates efficient stock management".to_string(),
-                "Average inventory holding period of 53.7 days".to_string(),
-                "Low obsolete inventory risk at 2.3%".to_string(),
-            ],
-            recommendations: vec![
-                "Monitor electronics category closely due to high value concentration".to_string(),
-                "Optimize reorder points for high-value, slow-moving items".to_string(),
-                "Consider just-in-time delivery for electronics components".to_string(),
-                "Implement ABC analysis for better inventory classification".to_string(),
-            ],
-        };
+        key_metrics.insert("total_inventory_value".to_string(), MetricValue::Text(format_amount(total_value)));
+        key_metrics.insert(
+            "average_unit_cost".to_string(),
+            MetricValue::Text(format_amount(average_cost(total_value, total_units))),
+        );
+        key_metrics.insert("inventory_turnover".to_string(), MetricValue::Number(turnover));
+        if turnover > 0.0 {
+            key_metrics.insert("days_in_inventory".to_string(), MetricValue::Number(365.0 / turnover));
+        }
 
-        let metadata = ReportMetadata {
-            total_records: 705,
-            processing_time_ms: 340,
-            filters_applied: vec!["valuation_method_fifo".to_string()],
-            data_sources: vec!["products".to_string(), "stock_movements".to_string(), "categories".to_string()],
-        };
+        let mut insights = vec![format!(
+            "Inventory worth {} across {} products in {} categories",
+            format_amount(total_value),
+            products.len(),
+            by_category.len()
+        )];
+        if let Some((category, (_, _, value))) = by_category.iter().max_by_key(|(_, c)| c.2).filter(|(_, c)| c.2 > 0) {
+            insights.push(format!(
+                "{} holds {} of inventory value",
+                category,
+                format_percentage(share(*value, total_value))
+            ));
+        }
+        insights.push(format!(
+            "Stock issued at {} a year turns inventory over {:.1} times",
+            format_amount(annual_cost_of_sales as i64),
+            turnover
+        ));
+        let mut recommendations = Vec::new();
+        if total_value > 0 && turnover < 4.0 {
+            recommendations.push("Turnover is below 4; review reorder quantities of slow-moving categories".to_string());
+        }
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
             data: ReportData::Mixed(sections),
-            summary: Some(summary),
-            metadata,
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: products.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["products".to_string(), "stock_movements".to_string(), "categories".to_string()],
+            },
         })
     }
 
     fn generate_purchase_analysis_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
-        let table_data = TableData {
-            headers: vec![
-                "Month".to_string(),
-                "Purchase Orders".to_string(),
-                "Total Value".to_string(),
-                "Average PO Value".to_string(),
-                "Supplier Count".to_string(),
-                "On-Time Delivery".to_string(),
-                "Quality Issues".to_string(),
-            ],
-            rows: vec![
-                vec![
-                    "January 2024".to_string(),
-                    "45".to_string(),
-                    "₩1,250,000,000".to_string(),
-                    "₩27,777,778".to_string(),
-                    "18".to_string(),
-                    "92.3%".to_string(),
-                    "2".to_string(),
-                ],
-                vec![
-                    "February 2024".to_string(),
-                    "38".to_string(),
-                    "₩1,180,000,000".to_string(),
-                    "₩31,052,632".to_string(),
-                    "15".to_string(),
-                    "94.7%".to_string(),
-                    "1".to_string(),
-                ],
-                vec![
-                    "March 2024".to_string(),
-                    "52".to_string(),
-                    "₩1,450,000,000".to_string(),
-                    "₩27,884,615".to_string(),
-                    "22".to_string(),
-                    "89.1%".to_string(),
-                    "4".to_string(),
-                ],
-                vec![
-                    "April 2024".to_string(),
-                    "41".to_string(),
-                    "₩1,320,000,000".to_string(),
-                    "₩32,195,122".to_string(),
-                    "19".to_string(),
-                    "95.1%".to_string(),
-                    "1".to_string(),
-                ],
-            ],
-            totals: Some(vec![
-                "Total/Average".to_string(),
-                "176".to_string(),
-                "₩5,200,000,000".to_string(),
-                "₩29,545,455".to_string(),
-                "74".to_string(),
-                "92.8%".to_string(),
-                "8".to_string(),
-            ]),
+        let start_time = std::time::Instant::now();
+        let filters = ReportFilters::from_config(&config)?;
+        let mut conn = get_report_connection()?;
+        let orders: Vec<PurchaseOrderRow> = ReportDataSource::purchase_orders(&mut conn, &filters)?
+            .into_iter()
+            .filter(|order| order.status != "cancelled")
+            .collect();
+
+        let on_time_rate = |orders: &[&PurchaseOrderRow]| {
+            let rated: Vec<bool> = orders.iter().filter_map(|order| order.on_time()).collect();
+            (!rated.is_empty()).then(|| share(rated.iter().filter(|on_time| **on_time).count() as i64, rated.len() as i64))
+        };
+        let row = |label: String, orders: &[&PurchaseOrderRow]| {
+            let total: i64 = orders.iter().map(|order| i64::from(order.total_amount)).sum();
+            let suppliers: BTreeSet<i32> = orders.iter().map(|order| order.supplier_id).collect();
+            vec![
+                label,
+                orders.len().to_string(),
+                format_amount(total),
+                format_amount(if orders.is_empty() { 0 } else { total / orders.len() as i64 }),
+                suppliers.len().to_string(),
+                on_time_rate(orders).map(format_percentage).unwrap_or_else(|| "-".to_string()),
+                orders.iter().filter(|order| order.received_on.is_some()).count().to_string(),
+            ]
         };
 
+        let mut by_month: BTreeMap<String, Vec<&PurchaseOrderRow>> = BTreeMap::new();
+        for order in &orders {
+            by_month.entry(order.order_date.format("%Y-%m").to_string()).or_default().push(order);
+        }
+        let rows = by_month.iter().map(|(month, orders)| row(month.clone(), orders)).collect();
+        let all: Vec<&PurchaseOrderRow> = orders.iter().collect();
+        let total: i64 = orders.iter().map(|order| i64::from(order.total_amount)).sum();
+        let suppliers: BTreeSet<i32> = orders.iter().map(|order| order.supplier_id).collect();
+        let on_time = on_time_rate(&all);
+
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("total_purchase_value".to_string(), MetricValue::Currency(5200000000));
-        key_metrics.insert("average_po_value".to_string(), MetricValue::Currency(29545455));
-        key_metrics.insert("supplier_diversity".to_string(), MetricValue::Count(74));
-        key_metrics.insert("on_time_delivery_rate".to_string(), MetricValue::Percentage(92.8));
-        key_metrics.insert("cost_savings_achieved".to_string(), MetricValue::Currency(156000000));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "Total purchase value of ₩5.2 billion across 176 orders".to_string(),
-                "Average purchase order value increased to ₩29.5 million".to_string(),
-                "On-time delivery rate of 92.8% meets target threshold".to_string(),
-                "Quality issues remain minimal at 4.5% of orders".to_string(),
-                "Cost savings of ₩156 million achieved through negotiations".to_string(),
-            ],
-            recommendations: vec![
-                "Focus on improving delivery performance with underperforming suppliers".to_string(),
-                "Implement supplier scorecards for continuous improvement".to_string(),
-                "Consider consolidating orders to achieve better pricing".to_string(),
-                "Develop backup suppliers for critical components".to_string(),
-            ],
-        };
+        key_metrics.insert("total_purchase_value".to_string(), MetricValue::Text(format_amount(total)));
+        key_metrics.insert("purchase_orders".to_string(), MetricValue::Count(orders.len() as i64));
+        key_metrics.insert(
+            "average_po_value".to_string(),
+            MetricValue::Text(format_amount(if orders.is_empty() { 0 } else { total / orders.len() as i64 })),
+        );
+        key_metrics.insert("supplier_count".to_string(), MetricValue::Count(suppliers.len() as i64));
+        if let Some(rate) = on_time {
+            key_metrics.insert("on_time_delivery_rate".to_string(), MetricValue::Percentage(rate));
+        }
 
-        let metadata = ReportMetadata {
-            total_records: 176,
-            processing_time_ms: 165,
-            filters_applied: vec!["completed_orders".to_string(), "date_range".to_string()],
-            data_sources: vec!["purchase_orders".to_string(), "purchase_items".to_string(), "suppliers".to_string()],
-        };
+        let mut insights = vec![format!(
+            "{} purchase orders worth {} from {} supplier(s)",
+            orders.len(),
+            format_amount(total),
+            suppliers.len()
+        )];
+        if let Some(rate) = on_time {
+            insights.push(format!("{} of received orders with an expected date arrived on time", format_percentage(rate)));
+        }
+        let mut recommendations = Vec::new();
+        if on_time.is_some_and(|rate| rate < 90.0) {
+            recommendations.push("Review late suppliers in the supplier performance report".to_string());
+        }
+        let awaiting = orders.iter().filter(|order| order.received_on.is_none()).count();
+        if awaiting > 0 {
+            recommendations.push(format!("Follow up on {} order(s) not yet received", awaiting));
+        }
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
-            data: ReportData::Table(table_data),
-            summary: Some(summary),
-            metadata,
+            data: ReportData::Table(TableData {
+                headers: header_row(&[
+                    "Month", "Purchase Orders", "Total Value", "Average PO Value", "Supplier Count", "On-Time Delivery", "Received",
+                ]),
+                rows,
+                totals: Some(row("Total/Average".to_string(), &all)),
+            }),
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: orders.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec![
+                    "purchase_orders".to_string(),
+                    "suppliers".to_string(),
+                    "stock_movements".to_string(),
+                ],
+            },
         })
     }
 
     fn generate_supplier_performance_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
-        let table_data = TableData {
-            headers: vec![
-                "Supplier".to_string(),
-                "Orders".to_string(),
-                "Total Value".to_string(),
-                "On-Time %".to_string(),
-                "Quality Score".to_string(),
-                "Payment Terms".to_string(),
-                "Performance Rating".to_string(),
-            ],
-            rows: vec![
-                vec![
-                    "TechSupply Co.".to_string(),
-                    "25".to_string(),
-                    "₩1,850,000,000".to_string(),
-                    "96.0%".to_string(),
-                    "4.8/5.0".to_string(),
-                    "Net 30".to_string(),
-                    "Excellent".to_string(),
-                ],
-                vec![
-                    "Global Electronics Ltd.".to_string(),
-                    "18".to_string(),
-                    "₩1,200,000,000".to_string(),
-                    "89.0%".to_string(),
-                    "4.2/5.0".to_string(),
-                    "Net 45".to_string(),
-                    "Good".to_string(),
-                ],
-                vec![
-                    "Office Solutions Inc.".to_string(),
-                    "32".to_string(),
-                    "₩650,000,000".to_string(),
-                    "94.0%".to_string(),
-                    "4.6/5.0".to_string(),
-                    "Net 30".to_string(),
-                    "Excellent".to_string(),
-                ],
-                vec![
-                    "Manufacturing Parts Co.".to_string(),
-                    "22".to_string(),
-                    "₩980,000,000".to_string(),
-                    "87.0%".to_string(),
-                    "3.9/5.0".to_string(),
-                    "Net 60".to_string(),
-                    "Fair".to_string(),
-                ],
-                vec![
-                    "Safety First Supplies".to_string(),
-                    "15".to_string(),
-                    "₩380,000,000".to_string(),
-                    "98.0%".to_string(),
-                    "4.9/5.0".to_string(),
-                    "Net 15".to_string(),
-                    "Excellent".to_string(),
-                ],
-            ],
-            totals: Some(vec![
-                "Total/Average".to_string(),
-                "112".to_string(),
-                "₩5,060,000,000".to_string(),
-                "92.8%".to_string(),
-                "4.5/5.0".to_string(),
-                "Avg: 36 days".to_string(),
-                "".to_string(),
-            ]),
+        let start_time = std::time::Instant::now();
+        let filters = ReportFilters::from_config(&config)?;
+        let mut conn = get_report_connection()?;
+        let orders: Vec<PurchaseOrderRow> = ReportDataSource::purchase_orders(&mut conn, &filters)?
+            .into_iter()
+            .filter(|order| order.status != "cancelled")
+            .collect();
+
+        let mut by_supplier: BTreeMap<&str, Vec<&PurchaseOrderRow>> = BTreeMap::new();
+        for order in &orders {
+            by_supplier.entry(order.supplier.as_str()).or_default().push(order);
+        }
+        let total: i64 = orders.iter().map(|order| i64::from(order.total_amount)).sum();
+
+        struct SupplierStats {
+            orders: usize,
+            value: i64,
+            on_time: Option<f64>,
+            lead_days: Option<f64>,
+            payment_terms: String,
+        }
+        let mut stats: Vec<(&str, SupplierStats)> = by_supplier
+            .iter()
+            .map(|(supplier, orders)| {
+                let rated: Vec<bool> = orders.iter().filter_map(|order| order.on_time()).collect();
+                let lead_times: Vec<i64> = orders
+                    .iter()
+                    .filter_map(|order| order.received_on.map(|received| (received - order.order_date).num_days()))
+                    .collect();
+                (
+                    *supplier,
+                    SupplierStats {
+                        orders: orders.len(),
+                        value: orders.iter().map(|order| i64::from(order.total_amount)).sum(),
+                        on_time: (!rated.is_empty())
+                            .then(|| share(rated.iter().filter(|on_time| **on_time).count() as i64, rated.len() as i64)),
+                        lead_days: (!lead_times.is_empty())
+                            .then(|| lead_times.iter().sum::<i64>() as f64 / lead_times.len() as f64),
+                        payment_terms: orders[0].payment_terms.clone().unwrap_or_else(|| "-".to_string()),
+                    },
+                )
+            })
+            .collect();
+        stats.sort_by(|a, b| b.1.value.cmp(&a.1.value).then_with(|| a.0.cmp(b.0)));
+
+        let rating = |on_time: Option<f64>| match on_time {
+            Some(rate) if rate >= 95.0 => "Excellent",
+            Some(rate) if rate >= 85.0 => "Good",
+            Some(_) => "Needs Improvement",
+            None => "Not Rated",
         };
+        let rows = stats
+            .iter()
+            .map(|(supplier, s)| {
+                vec![
+                    supplier.to_string(),
+                    s.orders.to_string(),
+                    format_amount(s.value),
+                    format_percentage(share(s.value, total)),
+                    s.on_time.map(format_percentage).unwrap_or_else(|| "-".to_string()),
+                    s.lead_days.map(|days| format!("{:.1}", days)).unwrap_or_else(|| "-".to_string()),
+                    s.payment_terms.clone(),
+                    rating(s.on_time).to_string(),
+                ]
+            })
+            .collect();
 
+        let top_share = stats.first().map(|(_, s)| share(s.value, total)).unwrap_or(0.0);
         let mut key_metrics = HashMap::new();
-        key_metrics.insert("top_suppliers".to_string(), MetricValue::Count(5));
-        key_metrics.insert("supplier_concentration_risk".to_string(), MetricValue::Percentage(36.6));
-        key_metrics.insert("average_quality_score".to_string(), MetricValue::Number(4.5));
-        key_metrics.insert("payment_terms_average".to_string(), MetricValue::Number(36.0));
-        key_metrics.insert("supplier_performance_index".to_string(), MetricValue::Number(8.7));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "Top 5 suppliers represent 36.6% of total purchase value".to_string(),
-                "Average supplier quality score of 4.5/5.0 indicates good relationships".to_string(),
-                "TechSupply Co. and Safety First Supplies show excellent performance".to_string(),
-                "Manufacturing Parts Co. requires attention for delivery improvement".to_string(),
-                "Payment terms average 36 days, supporting cash flow management".to_string(),
-            ],
-            recommendations: vec![
-                "Develop improvement plan for Manufacturing Parts Co.".to_string(),
-                "Consider preferred supplier agreements with top performers".to_string(),
-                "Diversify supplier base to reduce concentration risk".to_string(),
-                "Implement quarterly business reviews with key suppliers".to_string(),
-            ],
-        };
+        key_metrics.insert("suppliers".to_string(), MetricValue::Count(stats.len() as i64));
+        key_metrics.insert("supplier_concentration_risk".to_string(), MetricValue::Percentage(top_share));
+        key_metrics.insert("total_purchase_value".to_string(), MetricValue::Text(format_amount(total)));
 
-        let metadata = ReportMetadata {
-            total_records: 5,
-            processing_time_ms: 85,
-            filters_applied: vec!["top_suppliers".to_string(), "active_status".to_string()],
-            data_sources: vec!["suppliers".to_string(), "purchase_orders".to_string(), "supplier_evaluations".to_string()],
-        };
+        let mut insights = Vec::new();
+        if let Some((supplier, _)) = stats.first() {
+            insights.push(format!("{} supplies {} of purchase value", supplier, format_percentage(top_share)));
+        }
+        let late: Vec<&str> = stats
+            .iter()
+            .filter(|(_, s)| s.on_time.is_some_and(|rate| rate < 85.0))
+            .map(|(supplier, _)| *supplier)
+            .collect();
+        let mut recommendations = Vec::new();
+        if !late.is_empty() {
+            insights.push(format!("{} supplier(s) deliver on time less than 85% of the time", late.len()));
+            recommendations.push(format!("Agree delivery improvement plans with {}", late.join(", ")));
+        }
+        if top_share > 50.0 {
+            recommendations.push("Qualify a second supplier to reduce dependence on the largest one".to_string());
+        }
 
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
-            data: ReportData::Table(table_data),
-            summary: Some(summary),
-            metadata,
+            data: ReportData::Table(TableData {
+                headers: header_row(&[
+                    "Supplier", "Orders", "Total Value", "% of Value", "On-Time %", "Avg Lead Days", "Payment Terms",
+                    "Performance Rating",
+                ]),
+                rows,
+                totals: Some(vec![
+                    "Total".to_string(),
+                    orders.len().to_string(),
+                    format_amount(total),
+                    format_percentage(if total == 0 { 0.0 } else { 100.0 }),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                ]),
+            }),
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: stats.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec![
+                    "suppliers".to_string(),
+                    "purchase_orders".to_string(),
+                    "stock_movements".to_string(),
+                ],
+            },
         })
     }
 
     fn generate_abc_analysis_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let filters = ReportFilters::from_config(&config)?;
+        let mut conn = get_report_connection()?;
+        let products = ReportDataSource::products(&mut conn, &filters)?;
+        let usage_filters = filters.or_recent(365);
+        let movements = ReportDataSource::stock_movements(&mut conn, &usage_filters)?;
+        let (period_start, period_end) = usage_filters.period_or((DisplayTimezone::current().today(), DisplayTimezone::current().today()));
+
+        // Quantity and value issued per product in the usage period
+        let mut usage: HashMap<i32, (i64, i64)> = HashMap::new();
+        for movement in movements.iter().filter(|m| m.movement_type == "out") {
+            let entry = usage.entry(movement.product_id).or_default();
+            entry.0 += i64::from(movement.quantity.abs());
+            entry.1 += movement.value();
+        }
+        let mut ranked: Vec<(&ProductRow, i64, i64)> = products
+            .iter()
+            .map(|product| {
+                let (quantity, value) = usage.get(&product.id).copied().unwrap_or_default();
+                (product, quantity, value)
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.sku.cmp(&b.0.sku)));
+        let classes = abc_classes(&ranked.iter().map(|r| r.2).collect::<Vec<_>>());
+        let total_value: i64 = ranked.iter().map(|r| r.2).sum();
+
+        let strategy = |class: char| match class {
+            'A' => "Tight Control",
+            'B' => "Moderate Control",
+            _ => "Simple Control",
+        };
+        let class_stats = |class: char| {
+            let members: Vec<usize> = (0..ranked.len()).filter(|i| classes[*i] == class).collect();
+            (members.len() as i64, members.iter().map(|i| ranked[*i].2).sum::<i64>())
+        };
+        let rows = ['A', 'B', 'C']
+            .iter()
+            .map(|class| {
+                let (count, value) = class_stats(*class);
+                vec![
+                    format!("Class {}", class),
+                    count.to_string(),
+                    format_percentage(share(count, ranked.len() as i64)),
+                    format_amount(value),
+                    format_percentage(share(value, total_value)),
+                    strategy(*class).to_string(),
+                ]
+            })
+            .collect();
+
+        let mut cumulative = 0;
+        let top_rows = ranked
+            .iter()
+            .zip(&classes)
+            .take_while(|(_, class)| **class == 'A')
+            .enumerate()
+            .map(|(rank, ((product, quantity, value), _))| {
+                cumulative += value;
+                vec![
+                    (rank + 1).to_string(),
+                    product.sku.clone(),
+                    product.name.clone(),
+                    quantity.to_string(),
+                    format_amount(if *quantity == 0 { 0 } else { value / quantity }),
+                    format_amount(*value),
+                    format_percentage(share(cumulative, total_value)),
+                ]
+            })
+            .collect();
+
+        let (a_count, a_value) = class_stats('A');
+        let (c_count, c_value) = class_stats('C');
         let sections = vec![
             ReportSection {
-                title: "ABC Classification Results".to_string(),
+                title: format!("ABC Classification by Issue Value, {} to {}", period_start, period_end),
                 section_type: SectionType::Analysis,
                 data: ReportData::Table(TableData {
-                    headers: vec![
-                        "Class".to_string(),
-                        "Product Count".to_string(),
-                        "% of Products".to_string(),
-                        "Total Value".to_string(),
-                        "% of Value".to_string(),
-                        "Management Strategy".to_string(),
-                    ],
-                    rows: vec![
-                        vec![
-                            "Class A".to_string(),
-                            "141".to_string(),
-                            "20.0%".to_string(),
-                            "₩2,273,040,000".to_string(),
-                            "80.0%".to_string(),
-                            "Tight Control".to_string(),
-                        ],
-                        vec![
-                            "Class B".to_string(),
-                            "141".to_string(),
-                            "20.0%".to_string(),
-                            "₩568,260,000".to_string(),
-                            "15.0%".to_string(),
-                            "Moderate Control".to_string(),
-                        ],
-                        vec![
-                            "Class C".to_string(),
-                            "423".to_string(),
-                            "60.0%".to_string(),
-                            "₩142,065,000".to_string(),
-                            "5.0%".to_string(),
-                            "Simple Control".to_string(),
-                        ],
-                    ],
+                    headers: header_row(&[
+                        "Class", "Product Count", "% of Products", "Total Value", "% of Value", "Management Strategy",
+                    ]),
+                    rows,
                     totals: Some(vec![
                         "Total".to_string(),
-                        "705".to_string(),
-                        "100.0%".to_string(),
-                        "₩2,983,365,000".to_string(),
-                        "100.0%".to_string(),
-                        "".to_string(),
+                        ranked.len().to_string(),
+                        format_percentage(if ranked.is_empty() { 0.0 } else { 100.0 }),
+                        format_amount(total_value),
+                        format_percentage(if total_value == 0 { 0.0 } else { 100.0 }),
+                        String::new(),
                     ]),
                 }),
             },
@@ -728,94 +781,381 @@ impl InventoryReportsGenerator {
                 section_type: SectionType::Chart,
                 data: ReportData::Chart(create_bar_chart(
                     vec!["Class A".to_string(), "Class B".to_string(), "Class C".to_string()],
-                    vec![141.0, 141.0, 423.0],
+                    ['A', 'B', 'C'].iter().map(|class| class_stats(*class).0 as f64).collect(),
                     "Product Count",
                 )),
             },
             ReportSection {
-                title: "Top Class A Items".to_string(),
+                title: "Class A Items".to_string(),
                 section_type: SectionType::Detail,
+                data: ReportData::Table(TableData {
+                    headers: header_row(&[
+                        "Rank", "SKU", "Product Name", "Quantity Issued", "Unit Cost", "Issue Value", "Cumulative %",
+                    ]),
+                    rows: top_rows,
+                    totals: None,
+                }),
+            },
+        ];
+
+        let mut key_metrics = HashMap::new();
+        key_metrics.insert("class_a_items".to_string(), MetricValue::Count(a_count));
+        key_metrics.insert("class_a_value_percentage".to_string(), MetricValue::Percentage(share(a_value, total_value)));
+        key_metrics.insert("class_c_items".to_string(), MetricValue::Count(c_count));
+        key_metrics.insert("issue_value".to_string(), MetricValue::Text(format_amount(total_value)));
+
+        let insights = vec![
+            format!(
+                "{} of {} products ({}) account for {} of issue value",
+                a_count,
+                ranked.len(),
+                format_percentage(share(a_count, ranked.len() as i64)),
+                format_percentage(share(a_value, total_value))
+            ),
+            format!(
+                "{} Class C products contribute {} of issue value",
+                c_count,
+                format_percentage(share(c_value, total_value))
+            ),
+        ];
+        let mut recommendations = Vec::new();
+        if a_count > 0 {
+            recommendations.push("Count Class A items often and keep their reorder points under review".to_string());
+        }
+        if c_count > 0 {
+            recommendations.push("Order Class C items in bulk with simple min/max rules".to_string());
+        }
+
+        Ok(ReportResult {
+            config: config.clone(),
+            generated_at: Utc::now().naive_utc(),
+            data: ReportData::Mixed(sections),
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: ranked.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["products".to_string(), "stock_movements".to_string()],
+            },
+        })
+    }
+
+    fn generate_inventory_aging_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let as_of = DisplayTimezone::current().today();
+        let mut conn = get_report_connection()?;
+        let aging = InventoryAgingService::aging(&mut conn, as_of, filter_i32(&config, "category_id")?)?;
+
+        let bucket_headers = || AGING_BUCKETS.iter().map(|(label, _)| format!("{} days", label));
+        let mut headers = vec!["SKU".to_string(), "Product".to_string(), "Category".to_string()];
+        headers.extend(bucket_headers());
+        headers.push("Value".to_string());
+        headers.push("Oldest Receipt".to_string());
+
+        let mut bucket_totals = [0i64; 4];
+        let mut value_totals = [0i64; 4];
+        // Stock value in each bucket, per category
+        let mut by_category: BTreeMap<&str, [i64; 4]> = BTreeMap::new();
+        let rows = aging
+            .iter()
+            .map(|item| {
+                let values = item.values();
+                let entry = by_category.entry(item.category.as_str()).or_default();
+                for bucket in 0..AGING_BUCKETS.len() {
+                    bucket_totals[bucket] += item.quantities[bucket] as i64;
+                    value_totals[bucket] += values[bucket];
+                    entry[bucket] += values[bucket];
+                }
+
+                let mut row = vec![item.sku.clone(), item.name.clone(), item.category.clone()];
+                row.extend(item.quantities.iter().map(|q| q.to_string()));
+                row.push(format_amount(item.total_value()));
+                row.push(item.oldest_receipt.map(|d| d.to_string()).unwrap_or_default());
+                row
+            })
+            .collect();
+        let total_value: i64 = value_totals.iter().sum();
+
+        let mut totals = vec!["Total".to_string(), String::new(), String::new()];
+        totals.extend(bucket_totals.iter().map(|q| q.to_string()));
+        totals.push(format_amount(total_value));
+        totals.push(String::new());
+
+        let mut category_headers = vec!["Category".to_string()];
+        category_headers.extend(bucket_headers());
+        category_headers.push("Value".to_string());
+        category_headers.push("Over 60 Days".to_string());
+        let category_rows = by_category
+            .iter()
+            .map(|(category, values)| {
+                let mut row = vec![category.to_string()];
+                row.extend(values.iter().map(|v| format_amount(*v)));
+                row.push(format_amount(values.iter().sum::<i64>()));
+                row.push(format_percentage(share(values[2] + values[3], values.iter().sum())));
+                row
+            })
+            .collect();
+
+        let mut aged: Vec<_> = aging.iter().filter(|item| item.aged_value() > 0).collect();
+        aged.sort_by_key(|item| std::cmp::Reverse(item.aged_value()));
+        let highlight_rows = aged
+            .iter()
+            .take(AGED_STOCK_HIGHLIGHTS)
+            .map(|item| {
+                vec![
+                    item.sku.clone(),
+                    item.name.clone(),
+                    (item.quantities[2] + item.quantities[3]).to_string(),
+                    format_amount(item.aged_value()),
+                    format_amount(item.values()[3]),
+                ]
+            })
+            .collect();
+
+        let aged_value = value_totals[2] + value_totals[3];
+        let mut key_metrics = HashMap::new();
+        key_metrics.insert("inventory_value".to_string(), MetricValue::Text(format_amount(total_value)));
+        key_metrics.insert("value_over_60_days".to_string(), MetricValue::Text(format_amount(aged_value)));
+        key_metrics.insert("value_over_90_days".to_string(), MetricValue::Text(format_amount(value_totals[3])));
+        key_metrics.insert("aged_share".to_string(), MetricValue::Percentage(share(aged_value, total_value)));
+        key_metrics.insert("products_in_stock".to_string(), MetricValue::Count(aging.len() as i64));
+
+        let mut insights = vec![format!(
+            "{} of {} in stock ({}) has been on hand more than 60 days",
+            format_amount(aged_value),
+            format_amount(total_value),
+            format_percentage(share(aged_value, total_value))
+        )];
+        if let Some(oldest) = aged.first() {
+            insights.push(format!(
+                "{} ({}) holds the most aged value at {}",
+                oldest.name,
+                oldest.sku,
+                format_amount(oldest.aged_value())
+            ));
+        }
+        let mut recommendations = Vec::new();
+        if value_totals[3] > 0 {
+            recommendations.push(
+                "Review stock held over 90 days for markdown, return to supplier or write-down".to_string(),
+            );
+        }
+
+        let sections = vec![
+            ReportSection {
+                title: format!("Aging by Product as of {}", as_of),
+                section_type: SectionType::Detail,
+                data: ReportData::Table(TableData { headers, rows, totals: Some(totals) }),
+            },
+            ReportSection {
+                title: "Aging Value by Category".to_string(),
+                section_type: SectionType::Summary,
+                data: ReportData::Table(TableData {
+                    headers: category_headers,
+                    rows: category_rows,
+                    totals: None,
+                }),
+            },
+            ReportSection {
+                title: "Aging High-Value Stock".to_string(),
+                section_type: SectionType::Analysis,
                 data: ReportData::Table(TableData {
                     headers: vec![
-                        "Rank".to_string(),
                         "SKU".to_string(),
-                        "Product Name".to_string(),
-                        "Annual Usage".to_string(),
-                        "Unit Cost".to_string(),
-                        "Annual Value".to_string(),
-                        "Cumulative %".to_string(),
-                    ],
-                    rows: vec![
-                        vec![
-                            "1".to_string(),
-                            "PRD001".to_string(),
-                            "High-End Laptop".to_string(),
-                            "240".to_string(),
-                            "₩2,500,000".to_string(),
-                            "₩600,000,000".to_string(),
-                            "20.1%".to_string(),
-                        ],
-                        vec![
-                            "2".to_string(),
-                            "PRD105".to_string(),
-                            "Server Hardware".to_string(),
-                            "180".to_string(),
-                            "₩3,000,000".to_string(),
-                            "₩540,000,000".to_string(),
-                            "38.2%".to_string(),
-                        ],
-                        vec![
-                            "3".to_string(),
-                            "PRD087".to_string(),
-                            "Industrial Printer".to_string(),
-                            "150".to_string(),
-                            "₩1,800,000".to_string(),
-                            "₩270,000,000".to_string(),
-                            "47.2%".to_string(),
-                        ],
+                        "Product".to_string(),
+                        "Qty Over 60 Days".to_string(),
+                        "Value Over 60 Days".to_string(),
+                        "Value Over 90 Days".to_string(),
                     ],
+                    rows: highlight_rows,
                     totals: None,
                 }),
             },
         ];
 
-        let mut key_metrics = HashMap::new();
-        key_metrics.insert("class_a_items".to_string(), MetricValue::Count(141));
-        key_metrics.insert("class_a_value_percentage".to_string(), MetricValue::Percentage(80.0));
-        key_metrics.insert("pareto_efficiency".to_string(), MetricValue::Number(0.95));
-        key_metrics.insert("inventory_concentration".to_string(), MetricValue::Number(8.2));
-
-        let summary = ReportSummary {
-            key_metrics,
-            insights: vec![
-                "Classic Pareto distribution: 20% of items represent 80% of value".to_string(),
-                "141 Class A items require intensive management attention".to_string(),
-                "60% of products (Class C) contribute only 5% of total value".to_string(),
-                "High inventory concentration in electronics and servers".to_string(),
-                "ABC classification helps optimize inventory management resources".to_string(),
-            ],
-            recommendations: vec![
-                "Implement daily monitoring for all Class A items".to_string(),
-                "Use JIT delivery for high-value, predictable Class A items".to_string(),
-                "Apply bulk ordering strategies for Class C items".to_string(),
-                "Review Class B items monthly for reclassification".to_string(),
-                "Establish separate approval processes by ABC class".to_string(),
-            ],
+        Ok(ReportResult {
+            config: config.clone(),
+            generated_at: Utc::now().naive_utc(),
+            data: ReportData::Mixed(sections),
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: aging.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec!["products".to_string(), "stock_movements".to_string(), "categories".to_string()],
+            },
+        })
+    }
+
+    fn generate_order_reconciliation_report(&self, config: ReportConfig) -> CLIERPResult<ReportResult> {
+        let start_time = std::time::Instant::now();
+        let mut conn = get_report_connection()?;
+        let (from, to) = match &config.date_range {
+            Some(range) => (Some(range.start_date), Some(range.end_date)),
+            None => (None, None),
         };
+        let lines = OrderReconciliationService::reconcile(&mut conn, from, to)?;
 
-        let metadata = ReportMetadata {
-            total_records: 705,
-            processing_time_ms: 420,
-            filters_applied: vec!["annual_usage_data".to_string(), "value_calculation".to_string()],
-            data_sources: vec!["products".to_string(), "stock_movements".to_string(), "usage_history".to_string()],
+        let line_cells = |line: &ReconciledLine| {
+            vec![
+                line.deal_name.clone(),
+                line.po_number.clone().unwrap_or_else(|| "-".to_string()),
+                line.fulfillment.replace('_', "-"),
+                line.sku.clone(),
+                line.sold.to_string(),
+                line.purchased.to_string(),
+                line.received.to_string(),
+                line.unmatched().to_string(),
+                format_amount(line.margin()),
+                line.status().to_string(),
+            ]
+        };
+        let line_headers = || {
+            [
+                "Deal", "PO", "Fulfillment", "SKU", "Sold", "Purchased", "Received", "Unmatched", "Margin", "Status",
+            ]
+            .iter()
+            .map(|h| h.to_string())
+            .collect::<Vec<_>>()
         };
 
+        // Revenue, cost and open gaps of each linked pair of deal and order
+        let mut pairs: BTreeMap<(String, String), (i64, i64, usize)> = BTreeMap::new();
+        for line in &lines {
+            let key = (line.deal_name.clone(), line.po_number.clone().unwrap_or_else(|| "-".to_string()));
+            let entry = pairs.entry(key).or_default();
+            entry.0 += line.revenue;
+            entry.1 += line.cost;
+            if line.status() != ReconciliationStatus::Matched {
+                entry.2 += 1;
+            }
+        }
+        let pair_rows = pairs
+            .iter()
+            .map(|((deal, po), (revenue, cost, gaps))| {
+                vec![
+                    deal.clone(),
+                    po.clone(),
+                    format_amount(*revenue),
+                    format_amount(*cost),
+                    format_amount(revenue - cost),
+                    format_percentage(share(revenue - cost, *revenue)),
+                    gaps.to_string(),
+                ]
+            })
+            .collect();
+
+        let gaps: Vec<&ReconciledLine> = lines
+            .iter()
+            .filter(|line| line.status() != ReconciliationStatus::Matched)
+            .collect();
+        let count = |status: ReconciliationStatus| lines.iter().filter(|line| line.status() == status).count();
+        let unsourced: i64 = lines.iter().map(|line| line.unmatched().max(0) as i64).sum();
+        let excess: i64 = lines.iter().map(|line| (-line.unmatched()).max(0) as i64).sum();
+        let revenue: i64 = lines.iter().map(|line| line.revenue).sum();
+        let margin: i64 = lines.iter().map(ReconciledLine::margin).sum();
+
+        let mut key_metrics = HashMap::new();
+        key_metrics.insert("linked_pairs".to_string(), MetricValue::Count(pairs.len() as i64));
+        key_metrics.insert("lines".to_string(), MetricValue::Count(lines.len() as i64));
+        key_metrics.insert("lines_with_gaps".to_string(), MetricValue::Count(gaps.len() as i64));
+        key_metrics.insert("unsourced_quantity".to_string(), MetricValue::Count(unsourced));
+        key_metrics.insert("excess_quantity".to_string(), MetricValue::Count(excess));
+        key_metrics.insert("linked_margin".to_string(), MetricValue::Text(format_amount(margin)));
+        key_metrics.insert("linked_margin_percent".to_string(), MetricValue::Percentage(share(margin, revenue)));
+
+        let mut insights = vec![format!(
+            "{} of {} linked lines matched; {} unsourced, {} excess, {} awaiting receipt",
+            count(ReconciliationStatus::Matched),
+            lines.len(),
+            count(ReconciliationStatus::Unsourced),
+            count(ReconciliationStatus::Excess),
+            count(ReconciliationStatus::AwaitingReceipt)
+        )];
+        if lines.is_empty() {
+            insights.push(format!(
+                "No deals are linked to purchase orders; link them with relation {} or {}",
+                crate::modules::inventory::DROP_SHIP_RELATION,
+                crate::modules::inventory::BACK_TO_BACK_RELATION
+            ));
+        }
+        let mut recommendations = Vec::new();
+        if unsourced > 0 {
+            recommendations.push(format!("Raise purchase orders for {} sold units not yet bought", unsourced));
+        }
+        if excess > 0 {
+            recommendations.push(format!("Review {} units bought beyond what the deals sold", excess));
+        }
+        if lines.iter().any(|line| line.margin() < 0 && line.po_id.is_some()) {
+            recommendations.push("Check supplier costs on pairs with a negative margin".to_string());
+        }
+
+        let sections = vec![
+            ReportSection {
+                title: "Linked Order Lines".to_string(),
+                section_type: SectionType::Detail,
+                data: ReportData::Table(TableData {
+                    headers: line_headers(),
+                    rows: lines.iter().map(line_cells).collect(),
+                    totals: None,
+                }),
+            },
+            ReportSection {
+                title: "Margin per Linked Pair".to_string(),
+                section_type: SectionType::Summary,
+                data: ReportData::Table(TableData {
+                    headers: vec![
+                        "Deal".to_string(),
+                        "PO".to_string(),
+                        "Revenue".to_string(),
+                        "Cost".to_string(),
+                        "Margin".to_string(),
+                        "Margin %".to_string(),
+                        "Gaps".to_string(),
+                    ],
+                    rows: pair_rows,
+                    totals: None,
+                }),
+            },
+            ReportSection {
+                title: "Fulfillment Gaps".to_string(),
+                section_type: SectionType::Analysis,
+                data: ReportData::Table(TableData {
+                    headers: line_headers(),
+                    rows: gaps.iter().map(|line| line_cells(line)).collect(),
+                    totals: None,
+                }),
+            },
+        ];
+
         Ok(ReportResult {
-            config,
+            config: config.clone(),
             generated_at: Utc::now().naive_utc(),
             data: ReportData::Mixed(sections),
-            summary: Some(summary),
-            metadata,
+            summary: Some(ReportSummary {
+                key_metrics,
+                insights,
+                recommendations,
+            }),
+            metadata: ReportMetadata {
+                total_records: lines.len() as i64,
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                filters_applied: applied_filters(&config),
+                data_sources: vec![
+                    "record_links".to_string(),
+                    "deals".to_string(),
+                    "purchase_orders".to_string(),
+                    "purchase_items".to_string(),
+                ],
+            },
         })
     }
 }
@@ -824,4 +1164,48 @@ impl Default for InventoryReportsGenerator {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+fn movement_label(movement_type: &str) -> &str {
+    match movement_type {
+        "in" => "Stock In",
+        "out" => "Stock Out",
+        "adjustment" => "Adjustment",
+        other => other,
+    }
+}
+
+/// ABC class of each value in a list sorted from highest to lowest, by the
+/// cumulative share of the total reached once the item is included; the
+/// top item is always class A when it has any value
+fn abc_classes(values: &[i64]) -> Vec<char> {
+    let total: i64 = values.iter().sum();
+    let mut cumulative = 0;
+    values
+        .iter()
+        .map(|value| {
+            cumulative += value;
+            let reached = share(cumulative, total);
+            if *value > 0 && (reached <= ABC_CLASS_LIMITS.0 || cumulative == *value) {
+                'A'
+            } else if *value > 0 && reached <= ABC_CLASS_LIMITS.1 {
+                'B'
+            } else {
+                'C'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abc_classes() {
+        assert_eq!(abc_classes(&[700, 100, 100, 50, 30, 20, 0]), ['A', 'A', 'B', 'B', 'C', 'C', 'C']);
+        assert_eq!(abc_classes(&[900, 100]), ['A', 'C']);
+        assert_eq!(abc_classes(&[0, 0]), ['C', 'C']);
+        assert!(abc_classes(&[]).is_empty());
+    }
+}
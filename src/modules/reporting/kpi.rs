@@ -0,0 +1,316 @@
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::Double;
+use std::collections::HashMap;
+
+use crate::core::result::CLIERPResult;
+use crate::database::schema::kpis;
+use crate::database::{DatabaseConnection, Kpi, KpiStatus, NewKpi};
+use crate::modules::crm::{ActivityService, CustomerService, DealService, LeadService};
+use crate::modules::reporting::summaries::SummaryService;
+use crate::utils::formula::Formula;
+use crate::utils::validation::validate_required_string;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Days of history behind the flow metrics such as `cogs`
+pub const KPI_TRAILING_DAYS: i64 = 365;
+
+/// Metrics a KPI formula can refer to, with what each one measures
+pub const BASE_METRICS: &[(&str, &str)] = &[
+    ("revenue", "Balance of active revenue accounts"),
+    ("expenses", "Balance of active expense accounts"),
+    ("receivables", "Balance of active asset accounts named '...receivable...'"),
+    ("cogs", "Cost of stock shipped out in the last 365 days"),
+    ("inventory_value", "Stock on hand of active products at cost"),
+    ("low_stock_items", "Active products at or below their minimum stock level"),
+    ("headcount", "Active employees"),
+    ("active_customers", "Customers with status active"),
+    ("pipeline_value", "Value of open deals"),
+    ("won_value", "Value of won deals"),
+    ("win_rate", "Won deals as a percentage of closed deals"),
+    ("lead_conversion_rate", "Leads converted, in percent"),
+    ("overdue_activities", "Open activities past their date"),
+];
+
+/// Base metrics answered by a single aggregate query returning `value`
+const SQL_METRICS: &[(&str, &str)] = &[
+    (
+        "revenue",
+        "SELECT CAST(COALESCE(SUM(balance), 0) AS REAL) AS value FROM accounts WHERE account_type = 'revenue' AND is_active",
+    ),
+    (
+        "expenses",
+        "SELECT CAST(COALESCE(SUM(balance), 0) AS REAL) AS value FROM accounts WHERE account_type = 'expense' AND is_active",
+    ),
+    (
+        "receivables",
+        "SELECT CAST(COALESCE(SUM(balance), 0) AS REAL) AS value FROM accounts WHERE account_type = 'asset' AND is_active AND account_name LIKE '%receivable%'",
+    ),
+    (
+        "inventory_value",
+        "SELECT CAST(COALESCE(SUM(current_stock * cost_price), 0) AS REAL) AS value FROM products WHERE is_active",
+    ),
+    (
+        "low_stock_items",
+        "SELECT CAST(COUNT(*) AS REAL) AS value FROM products WHERE is_active AND current_stock <= min_stock_level",
+    ),
+    (
+        "headcount",
+        "SELECT CAST(COUNT(*) AS REAL) AS value FROM employees WHERE status = 'active'",
+    ),
+];
+
+#[derive(QueryableByName)]
+struct MetricRow {
+    #[diesel(sql_type = Double)]
+    value: f64,
+}
+
+/// A KPI's current value and status next to the last recorded one
+#[derive(Debug, Clone)]
+pub struct KpiEvaluation {
+    pub kpi: Kpi,
+    /// `None` when the formula divides by zero
+    pub value: Option<f64>,
+    pub status: Option<KpiStatus>,
+    pub previous: Option<KpiStatus>,
+}
+
+impl KpiEvaluation {
+    /// Whether the KPI moved into a warning or critical band since the last check
+    pub fn crossed(&self) -> bool {
+        matches!(self.status, Some(KpiStatus::Warning | KpiStatus::Critical)) && self.status != self.previous
+    }
+}
+
+/// Admin-defined KPIs over the base metrics.
+///
+/// Thresholds are bands: with `higher_is_better` a value at or below the
+/// warning threshold is a warning and at or below the critical one is
+/// critical; without it the bands are at or above.
+pub struct KpiService;
+
+impl KpiService {
+    /// Create or replace the KPI called `name`
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_kpi(
+        conn: &mut DatabaseConnection,
+        name: &str,
+        formula: &str,
+        warning: f64,
+        critical: f64,
+        higher_is_better: bool,
+        description: Option<&str>,
+        created_by: Option<i32>,
+    ) -> Result<Kpi> {
+        validate_required_string(name, "KPI name")?;
+        let parsed = Formula::parse(formula)?;
+        let unknown: Vec<String> = parsed
+            .metrics()
+            .into_iter()
+            .filter(|metric| !BASE_METRICS.iter().any(|(name, _)| name == metric))
+            .collect();
+        if !unknown.is_empty() {
+            return Err(crate::core::error::CLIERPError::Validation(format!(
+                "Unknown metric(s) {}. Run 'clierp config kpi metrics' for the available ones",
+                unknown.join(", ")
+            )));
+        }
+        let ordered = if higher_is_better { critical <= warning } else { critical >= warning };
+        if !ordered {
+            return Err(crate::core::error::CLIERPError::Validation(format!(
+                "Critical threshold must be {} the warning threshold",
+                if higher_is_better { "at or below" } else { "at or above" }
+            )));
+        }
+
+        let existing = kpis::table
+            .filter(kpis::name.eq(name))
+            .first::<Kpi>(conn)
+            .optional()?;
+        match existing {
+            Some(kpi) => {
+                diesel::update(kpis::table.find(kpi.id))
+                    .set((
+                        kpis::formula.eq(formula),
+                        kpis::description.eq(description),
+                        kpis::higher_is_better.eq(higher_is_better),
+                        kpis::warning_threshold.eq(warning),
+                        kpis::critical_threshold.eq(critical),
+                        // The old status says nothing about the new definition
+                        kpis::last_value.eq(None::<f64>),
+                        kpis::last_status.eq(None::<String>),
+                        kpis::last_evaluated_at.eq(None::<chrono::NaiveDateTime>),
+                        kpis::updated_at.eq(Utc::now().naive_utc()),
+                    ))
+                    .execute(conn)?;
+            }
+            None => {
+                diesel::insert_into(kpis::table)
+                    .values(&NewKpi {
+                        name: name.to_string(),
+                        description: description.map(|s| s.to_string()),
+                        formula: formula.to_string(),
+                        higher_is_better,
+                        warning_threshold: warning,
+                        critical_threshold: critical,
+                        created_by,
+                    })
+                    .execute(conn)?;
+            }
+        }
+
+        let kpi = kpis::table.filter(kpis::name.eq(name)).first::<Kpi>(conn)?;
+        tracing::info!("Set KPI {} = {} (ID {})", kpi.name, kpi.formula, kpi.id);
+        Ok(kpi)
+    }
+
+    pub fn list_kpis(conn: &mut DatabaseConnection) -> Result<Vec<Kpi>> {
+        kpis::table
+            .order(kpis::name.asc())
+            .load::<Kpi>(conn)
+            .map_err(Into::into)
+    }
+
+    pub fn remove_kpi(conn: &mut DatabaseConnection, name: &str) -> Result<()> {
+        let removed = diesel::delete(kpis::table.filter(kpis::name.eq(name))).execute(conn)?;
+        if removed == 0 {
+            return Err(crate::core::error::CLIERPError::NotFound(format!("KPI '{}' not found", name)));
+        }
+        tracing::info!("Removed KPI {}", name);
+        Ok(())
+    }
+
+    /// Current value of every base metric
+    pub fn metric_values(conn: &mut DatabaseConnection) -> Result<HashMap<String, f64>> {
+        let mut values = HashMap::new();
+        for (name, sql) in SQL_METRICS {
+            let row = diesel::sql_query(*sql).get_result::<MetricRow>(conn)?;
+            values.insert(name.to_string(), row.value);
+        }
+
+        let since = Utc::now().naive_utc() - Duration::days(KPI_TRAILING_DAYS);
+        let cogs = SummaryService::cost_of_sales_since(conn, since)?;
+        values.insert("cogs".to_string(), cogs);
+
+        let customers = CustomerService::get_customer_statistics(conn)?;
+        values.insert("active_customers".to_string(), customers.active_customers as f64);
+        let deals = DealService::get_deal_statistics(conn)?;
+        values.insert("pipeline_value".to_string(), deals.total_pipeline_value as f64);
+        values.insert("won_value".to_string(), deals.total_won_value as f64);
+        values.insert("win_rate".to_string(), deals.win_rate);
+        let leads = LeadService::get_lead_statistics(conn)?;
+        values.insert("lead_conversion_rate".to_string(), leads.conversion_rate);
+        let activities = ActivityService::get_activity_statistics(conn)?;
+        values.insert("overdue_activities".to_string(), activities.overdue_activities as f64);
+
+        Ok(values)
+    }
+
+    /// Evaluate every KPI against the current metrics without recording anything
+    pub fn evaluate_all(conn: &mut DatabaseConnection) -> Result<Vec<KpiEvaluation>> {
+        let kpis = Self::list_kpis(conn)?;
+        if kpis.is_empty() {
+            return Ok(Vec::new());
+        }
+        let values = Self::metric_values(conn)?;
+        kpis.into_iter()
+            .map(|kpi| {
+                let value = Formula::parse(&kpi.formula)?.evaluate(&values);
+                let status = value.map(|v| {
+                    KpiStatus::classify(v, kpi.warning_threshold, kpi.critical_threshold, kpi.higher_is_better)
+                });
+                let previous = kpi.last_status.as_deref().and_then(|s| s.parse().ok());
+                Ok(KpiEvaluation {
+                    kpi,
+                    value,
+                    status,
+                    previous,
+                })
+            })
+            .collect()
+    }
+
+    /// Evaluate every KPI, store the results as the new last status and log
+    /// an alert for each KPI that `crossed()` a threshold
+    pub fn check_all(conn: &mut DatabaseConnection) -> Result<Vec<KpiEvaluation>> {
+        let evaluations = Self::evaluate_all(conn)?;
+        let now = Utc::now().naive_utc();
+        conn.transaction::<_, crate::core::error::CLIERPError, _>(|conn| {
+            for evaluation in &evaluations {
+                diesel::update(kpis::table.find(evaluation.kpi.id))
+                    .set((
+                        kpis::last_value.eq(evaluation.value),
+                        kpis::last_status.eq(evaluation.status.map(|s| s.to_string())),
+                        kpis::last_evaluated_at.eq(Some(now)),
+                    ))
+                    .execute(conn)?;
+            }
+            Ok(())
+        })?;
+
+        for evaluation in evaluations.iter().filter(|e| e.crossed()) {
+            tracing::warn!(
+                "KPI alert: {} is {} at {:.2}",
+                evaluation.kpi.name,
+                evaluation.status.map(|s| s.to_string()).unwrap_or_default(),
+                evaluation.value.unwrap_or_default()
+            );
+        }
+        Ok(evaluations)
+    }
+}
+
+/// Render KPI evaluations as `name  value  status` lines, colored by status
+pub fn print_kpis(evaluations: &[KpiEvaluation]) {
+    use colored::Colorize;
+
+    for evaluation in evaluations {
+        let value = evaluation
+            .value
+            .map(|v| format!("{:.2}", v))
+            .unwrap_or_else(|| "n/a".to_string());
+        let status = match evaluation.status {
+            Some(KpiStatus::Ok) => "ok".green(),
+            Some(KpiStatus::Warning) => "warning".yellow(),
+            Some(KpiStatus::Critical) => "critical".red().bold(),
+            None => "-".normal(),
+        };
+        println!(
+            "  {:<24} {:>14}  {:<8}  (warn {} {}, crit {} {})",
+            evaluation.kpi.name,
+            value,
+            status,
+            if evaluation.kpi.higher_is_better { "≤" } else { "≥" },
+            evaluation.kpi.warning_threshold,
+            if evaluation.kpi.higher_is_better { "≤" } else { "≥" },
+            evaluation.kpi.critical_threshold
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sql_metrics_are_documented() {
+        for (name, _) in SQL_METRICS {
+            assert!(BASE_METRICS.iter().any(|(metric, _)| metric == name), "{} is undocumented", name);
+        }
+    }
+
+    #[test]
+    fn test_classify_bands() {
+        // Gross margin %: lower is worse
+        assert_eq!(KpiStatus::classify(35.0, 30.0, 20.0, true), KpiStatus::Ok);
+        assert_eq!(KpiStatus::classify(30.0, 30.0, 20.0, true), KpiStatus::Warning);
+        assert_eq!(KpiStatus::classify(12.0, 30.0, 20.0, true), KpiStatus::Critical);
+        // DSO: higher is worse
+        assert_eq!(KpiStatus::classify(40.0, 45.0, 60.0, false), KpiStatus::Ok);
+        assert_eq!(KpiStatus::classify(50.0, 45.0, 60.0, false), KpiStatus::Warning);
+        assert_eq!(KpiStatus::classify(75.0, 45.0, 60.0, false), KpiStatus::Critical);
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{categories, customers, deals, leads, products, stock_movements};
+use crate::database::{DatabaseConnection, Deal, DealProduct, Product, StockMovement, StockMovementType};
+use crate::modules::inventory::product::REVERSAL_REFERENCE;
+use crate::utils::timezone::DisplayTimezone;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Label of sales that cannot be traced to a customer
+pub const UNASSIGNED_CUSTOMER: &str = "Unassigned";
+
+/// How shipped stock is costed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CostingMethod {
+    /// Oldest receipts are shipped first
+    Fifo,
+    /// Newest receipts are shipped first
+    Lifo,
+    /// Moving weighted average of the receipts
+    Average,
+    /// The product's standard cost price
+    Standard,
+}
+
+impl std::fmt::Display for CostingMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CostingMethod::Fifo => write!(f, "fifo"),
+            CostingMethod::Lifo => write!(f, "lifo"),
+            CostingMethod::Average => write!(f, "average"),
+            CostingMethod::Standard => write!(f, "standard"),
+        }
+    }
+}
+
+impl std::str::FromStr for CostingMethod {
+    type Err = crate::core::error::CLIERPError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fifo" => Ok(CostingMethod::Fifo),
+            "lifo" => Ok(CostingMethod::Lifo),
+            "average" => Ok(CostingMethod::Average),
+            "standard" => Ok(CostingMethod::Standard),
+            _ => Err(crate::core::error::CLIERPError::Validation(format!(
+                "Invalid costing method: {} (expected fifo, lifo, average or standard)",
+                s
+            ))),
+        }
+    }
+}
+
+/// One shipment of stock, priced and costed
+#[derive(Debug, Clone, Serialize)]
+pub struct SaleLine {
+    pub movement_id: i32,
+    pub product_id: i32,
+    pub sku: String,
+    pub product_name: String,
+    pub category: String,
+    pub customer: String,
+    pub quantity: i32,
+    pub revenue: i64,
+    pub cogs: i64,
+}
+
+/// Revenue and cost of sales of one product, category or customer
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MarginRow {
+    pub label: String,
+    pub quantity: i64,
+    pub revenue: i64,
+    pub cogs: i64,
+}
+
+impl MarginRow {
+    pub fn margin(&self) -> i64 {
+        self.revenue - self.cogs
+    }
+
+    /// Gross margin as a percentage of revenue, 0 without revenue
+    pub fn margin_percent(&self) -> f64 {
+        if self.revenue == 0 {
+            0.0
+        } else {
+            self.margin() as f64 * 100.0 / self.revenue as f64
+        }
+    }
+
+    fn add(&mut self, line: &SaleLine) {
+        self.quantity += line.quantity as i64;
+        self.revenue += line.revenue;
+        self.cogs += line.cogs;
+    }
+}
+
+/// Gross margin of the stock shipped during a period
+#[derive(Debug, Clone, Serialize)]
+pub struct GrossMarginAnalysis {
+    pub method: CostingMethod,
+    pub lines: Vec<SaleLine>,
+}
+
+impl GrossMarginAnalysis {
+    pub fn total(&self) -> MarginRow {
+        let mut total = MarginRow { label: "Total".to_string(), ..Default::default() };
+        for line in &self.lines {
+            total.add(line);
+        }
+        total
+    }
+
+    pub fn by_product(&self) -> Vec<MarginRow> {
+        self.group(|line| format!("{} {}", line.sku, line.product_name))
+    }
+
+    pub fn by_category(&self) -> Vec<MarginRow> {
+        self.group(|line| line.category.clone())
+    }
+
+    pub fn by_customer(&self) -> Vec<MarginRow> {
+        self.group(|line| line.customer.clone())
+    }
+
+    /// Rows keyed by `label`, highest gross margin first
+    fn group(&self, label: impl Fn(&SaleLine) -> String) -> Vec<MarginRow> {
+        let mut grouped: BTreeMap<String, MarginRow> = BTreeMap::new();
+        for line in &self.lines {
+            let key = label(line);
+            grouped
+                .entry(key.clone())
+                .or_insert_with(|| MarginRow { label: key, ..Default::default() })
+                .add(line);
+        }
+        let mut rows: Vec<MarginRow> = grouped.into_values().collect();
+        rows.sort_by_key(|row| std::cmp::Reverse(row.margin()));
+        rows
+    }
+}
+
+/// Gross margin from shipped stock.
+///
+/// Every `out` movement in the period is a sale, unless it was reversed.
+/// Revenue is the unit price on the deal the shipment references, or the
+/// product's list price, less the deal discount. Cost of goods sold replays
+/// the product's movement history under the chosen costing method; receipts
+/// without a unit cost, and shipments no receipt covers, are costed at the
+/// product's cost price.
+pub struct GrossMarginService;
+
+impl GrossMarginService {
+    pub fn analyze(
+        conn: &mut DatabaseConnection,
+        start: NaiveDate,
+        end: NaiveDate,
+        method: CostingMethod,
+        category_id: Option<i32>,
+    ) -> Result<GrossMarginAnalysis> {
+        let (from, to) = DisplayTimezone::current().date_range_utc(start, end);
+        let out = StockMovementType::Out.to_string();
+
+        let mut query = stock_movements::table
+            .inner_join(products::table)
+            .filter(stock_movements::movement_type.eq(&out))
+            .filter(stock_movements::movement_date.ge(from))
+            .filter(stock_movements::movement_date.lt(to))
+            .select(stock_movements::all_columns)
+            .into_boxed();
+        if let Some(category_id) = category_id {
+            query = query.filter(products::category_id.eq(category_id));
+        }
+        let shipped: Vec<StockMovement> = query.load(conn)?;
+
+        let shipped_ids: Vec<i32> = shipped.iter().map(|m| m.id).collect();
+        let reversed: HashSet<i32> = stock_movements::table
+            .filter(stock_movements::reference_type.eq(REVERSAL_REFERENCE))
+            .filter(stock_movements::reference_id.eq_any(&shipped_ids))
+            .select(stock_movements::reference_id)
+            .load::<Option<i32>>(conn)?
+            .into_iter()
+            .flatten()
+            .collect();
+        let sales: HashSet<i32> = shipped
+            .iter()
+            .filter(|m| m.reference_type.as_deref() != Some(REVERSAL_REFERENCE) && !reversed.contains(&m.id))
+            .map(|m| m.id)
+            .collect();
+
+        let product_ids: Vec<i32> = shipped.iter().map(|m| m.product_id).collect::<HashSet<_>>().into_iter().collect();
+        let product_map: HashMap<i32, (Product, String)> = products::table
+            .inner_join(categories::table)
+            .filter(products::id.eq_any(&product_ids))
+            .select((Product::as_select(), categories::name))
+            .load::<(Product, String)>(conn)?
+            .into_iter()
+            .map(|(product, category)| (product.id, (product, category)))
+            .collect();
+
+        let deal_ids: Vec<i32> = shipped
+            .iter()
+            .filter(|m| m.reference_type.as_deref() == Some("deal"))
+            .filter_map(|m| m.reference_id)
+            .collect();
+        let deal_map: HashMap<i32, Deal> = deals::table
+            .filter(deals::id.eq_any(&deal_ids))
+            .load::<Deal>(conn)?
+            .into_iter()
+            .map(|deal| (deal.id, deal))
+            .collect();
+        let lead_ids: Vec<i32> = deal_map.values().filter_map(|deal| deal.lead_id).collect();
+        let deal_customers: HashMap<i32, String> = leads::table
+            .inner_join(customers::table)
+            .filter(leads::id.eq_any(&lead_ids))
+            .select((leads::id, customers::name))
+            .load::<(i32, String)>(conn)?
+            .into_iter()
+            .collect();
+
+        // The whole history up to the end of the period feeds the cost layers
+        let history: Vec<StockMovement> = stock_movements::table
+            .filter(stock_movements::product_id.eq_any(&product_ids))
+            .filter(stock_movements::movement_date.lt(to))
+            .order((stock_movements::movement_date.asc(), stock_movements::id.asc()))
+            .load(conn)?;
+        let mut histories: HashMap<i32, Vec<(i32, i32, Option<i32>)>> = HashMap::new();
+        for movement in &history {
+            histories
+                .entry(movement.product_id)
+                .or_default()
+                .push((movement.id, movement.quantity, movement.unit_cost));
+        }
+
+        let mut costs: HashMap<i32, i64> = HashMap::new();
+        for (product_id, flows) in &histories {
+            let fallback = product_map.get(product_id).map(|(product, _)| product.cost_price).unwrap_or(0);
+            costs.extend(cost_of_issues(flows, fallback, method));
+        }
+
+        let mut lines: Vec<SaleLine> = shipped
+            .iter()
+            .filter(|m| sales.contains(&m.id))
+            .filter_map(|m| {
+                let (product, category) = product_map.get(&m.product_id)?;
+                let quantity = m.quantity.abs();
+                let deal = match (m.reference_type.as_deref(), m.reference_id) {
+                    (Some("deal"), Some(deal_id)) => deal_map.get(&deal_id),
+                    _ => None,
+                };
+                let customer = deal
+                    .and_then(|deal| deal.lead_id)
+                    .and_then(|lead_id| deal_customers.get(&lead_id))
+                    .cloned()
+                    .unwrap_or_else(|| UNASSIGNED_CUSTOMER.to_string());
+                Some(SaleLine {
+                    movement_id: m.id,
+                    product_id: product.id,
+                    sku: product.sku.clone(),
+                    product_name: product.name.clone(),
+                    category: category.clone(),
+                    customer,
+                    quantity,
+                    revenue: sale_revenue(product, deal, quantity),
+                    cogs: costs.get(&m.id).copied().unwrap_or(quantity as i64 * product.cost_price as i64),
+                })
+            })
+            .collect();
+        lines.sort_by_key(|line| line.movement_id);
+
+        Ok(GrossMarginAnalysis { method, lines })
+    }
+}
+
+/// Revenue of `quantity` of `product`: the deal's unit price when the deal
+/// lists the product, the list price otherwise, less the deal discount
+fn sale_revenue(product: &Product, deal: Option<&Deal>, quantity: i32) -> i64 {
+    let deal_price = deal
+        .and_then(|deal| deal.products.as_deref())
+        .and_then(|json| serde_json::from_str::<Vec<DealProduct>>(json).ok())
+        .and_then(|items| items.into_iter().find(|item| item.product_id == product.id))
+        .map(|item| item.unit_price);
+    let gross = quantity as i64 * deal_price.unwrap_or(product.price) as i64;
+    let discount = deal.and_then(|deal| deal.discount_percent).unwrap_or(0).clamp(0, 100) as i64;
+    gross * (100 - discount) / 100
+}
+
+/// Cost of every issue in `flows`, keyed by movement id.
+///
+/// `flows` are `(movement id, signed quantity, unit cost)` in the order they
+/// happened. Receipts without a unit cost, and issues beyond what the
+/// receipts hold, are costed at `fallback`.
+pub fn cost_of_issues(flows: &[(i32, i32, Option<i32>)], fallback: i32, method: CostingMethod) -> HashMap<i32, i64> {
+    let mut costs = HashMap::new();
+    // Receipt layers as (quantity, unit cost), oldest first
+    let mut layers: VecDeque<(i64, i64)> = VecDeque::new();
+
+    for &(movement_id, quantity, unit_cost) in flows {
+        let quantity = quantity as i64;
+        if quantity > 0 {
+            let unit_cost = unit_cost.unwrap_or(fallback) as i64;
+            match method {
+                CostingMethod::Average => {
+                    let (held, value) = layers.pop_front().map_or((0, 0), |(q, c)| (q, q * c));
+                    let total = held + quantity;
+                    let average = (value + quantity * unit_cost + total / 2) / total;
+                    layers.push_back((total, average));
+                }
+                _ => layers.push_back((quantity, unit_cost)),
+            }
+            continue;
+        }
+        if quantity == 0 {
+            continue;
+        }
+        if method == CostingMethod::Standard {
+            costs.insert(movement_id, -quantity * fallback as i64);
+            continue;
+        }
+
+        let mut remaining = -quantity;
+        let mut cost = 0;
+        while remaining > 0 {
+            let layer = match method {
+                CostingMethod::Lifo => layers.back_mut(),
+                _ => layers.front_mut(),
+            };
+            let Some(layer) = layer else { break };
+            let taken = layer.0.min(remaining);
+            cost += taken * layer.1;
+            layer.0 -= taken;
+            remaining -= taken;
+            if layer.0 == 0 {
+                match method {
+                    CostingMethod::Lifo => layers.pop_back(),
+                    _ => layers.pop_front(),
+                };
+            }
+        }
+        cost += remaining * fallback as i64;
+        costs.insert(movement_id, cost);
+    }
+
+    costs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two receipts of 10 at 100 and 10 at 200, then 15 shipped
+    const FLOWS: &[(i32, i32, Option<i32>)] = &[(1, 10, Some(100)), (2, 10, Some(200)), (3, -15, None)];
+
+    #[test]
+    fn test_cost_of_issues_by_method() {
+        assert_eq!(cost_of_issues(FLOWS, 120, CostingMethod::Fifo)[&3], 10 * 100 + 5 * 200);
+        assert_eq!(cost_of_issues(FLOWS, 120, CostingMethod::Lifo)[&3], 10 * 200 + 5 * 100);
+        assert_eq!(cost_of_issues(FLOWS, 120, CostingMethod::Average)[&3], 15 * 150);
+        assert_eq!(cost_of_issues(FLOWS, 120, CostingMethod::Standard)[&3], 15 * 120);
+    }
+
+    #[test]
+    fn test_cost_of_issues_falls_back_to_cost_price() {
+        // 5 more than was received, and a receipt without a unit cost
+        let flows = [(1, 10, Some(100)), (2, -15, None), (3, 4, None), (4, -4, None)];
+        let costs = cost_of_issues(&flows, 80, CostingMethod::Fifo);
+        assert_eq!(costs[&2], 10 * 100 + 5 * 80);
+        assert_eq!(costs[&4], 4 * 80);
+    }
+
+    #[test]
+    fn test_costing_method_from_str() {
+        assert_eq!("FIFO".parse::<CostingMethod>().unwrap(), CostingMethod::Fifo);
+        assert_eq!("average".parse::<CostingMethod>().unwrap(), CostingMethod::Average);
+        assert!("weighted".parse::<CostingMethod>().is_err());
+    }
+}
@@ -1,11 +1,23 @@
 pub mod engine;
+pub mod data_source;
+pub mod dashboard;
 pub mod hr_reports;
 pub mod finance_reports;
 pub mod inventory_reports;
 pub mod crm_reports;
+pub mod kpi;
+pub mod margin;
+pub mod snapshots;
+pub mod summaries;
 
 pub use engine::*;
+pub use data_source::*;
+pub use dashboard::*;
 pub use hr_reports::*;
 pub use finance_reports::*;
 pub use inventory_reports::*;
-pub use crm_reports::*;
\ No newline at end of file
+pub use crm_reports::*;
+pub use kpi::*;
+pub use margin::*;
+pub use snapshots::*;
+pub use summaries::*;
\ No newline at end of file
@@ -0,0 +1,263 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::engine::{MetricValue, ReportResult};
+use crate::core::result::CLIERPResult;
+use crate::database::schema::report_snapshots;
+use crate::database::{DatabaseConnection, NewReportSnapshot, ReportSnapshot};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// How one key metric moved between two report runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricDelta {
+    pub metric: String,
+    pub before: Option<MetricValue>,
+    pub after: Option<MetricValue>,
+    /// `after - before`, in the unit of the metric
+    pub change: Option<MetricValue>,
+    /// Relative change in percent; `None` when `before` is zero or not numeric
+    pub change_pct: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportComparison {
+    pub before: ReportSnapshot,
+    pub after: ReportSnapshot,
+    pub metrics: Vec<MetricDelta>,
+}
+
+/// Saved report runs and comparisons between them.
+///
+/// A snapshot stores the complete `ReportResult`, so it still shows what the
+/// report said at the time after the underlying data has moved on.
+pub struct ReportSnapshotService;
+
+impl ReportSnapshotService {
+    pub fn save(
+        conn: &mut DatabaseConnection,
+        name: &str,
+        report_id: &str,
+        result: &ReportResult,
+        created_by: Option<i32>,
+    ) -> Result<ReportSnapshot> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(crate::core::error::CLIERPError::Validation(
+                "Snapshot name cannot be empty".to_string(),
+            ));
+        }
+        if name.parse::<i32>().is_ok() {
+            return Err(crate::core::error::CLIERPError::Validation(
+                "Snapshot name cannot be a number; numbers refer to snapshot IDs".to_string(),
+            ));
+        }
+
+        let taken = report_snapshots::table
+            .filter(report_snapshots::name.eq(name))
+            .count()
+            .get_result::<i64>(conn)?;
+        if taken > 0 {
+            return Err(crate::core::error::CLIERPError::AlreadyExists(format!(
+                "Report snapshot '{}' already exists",
+                name
+            )));
+        }
+
+        diesel::insert_into(report_snapshots::table)
+            .values(&NewReportSnapshot {
+                name: name.to_string(),
+                report_id: report_id.to_string(),
+                title: result.config.title.clone(),
+                result_json: serde_json::to_string(result)?,
+                created_by,
+            })
+            .execute(conn)?;
+
+        tracing::info!("Saved {} report snapshot '{}'", result.config.title, name);
+        Self::find(conn, name)
+    }
+
+    /// Saved snapshots, newest first
+    pub fn list(conn: &mut DatabaseConnection, limit: i64) -> Result<Vec<ReportSnapshot>> {
+        report_snapshots::table
+            .order((report_snapshots::created_at.desc(), report_snapshots::id.desc()))
+            .limit(limit)
+            .load::<ReportSnapshot>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Look a snapshot up by name, or by ID when `name_or_id` is a number
+    pub fn find(conn: &mut DatabaseConnection, name_or_id: &str) -> Result<ReportSnapshot> {
+        let snapshot = match name_or_id.parse::<i32>() {
+            Ok(id) => report_snapshots::table
+                .find(id)
+                .first::<ReportSnapshot>(conn)
+                .optional()?,
+            Err(_) => report_snapshots::table
+                .filter(report_snapshots::name.eq(name_or_id))
+                .first::<ReportSnapshot>(conn)
+                .optional()?,
+        };
+        snapshot.ok_or_else(|| {
+            crate::core::error::CLIERPError::NotFound(format!("Report snapshot '{}' not found", name_or_id))
+        })
+    }
+
+    pub fn result(snapshot: &ReportSnapshot) -> Result<ReportResult> {
+        serde_json::from_str(&snapshot.result_json).map_err(Into::into)
+    }
+
+    /// Compare the key metrics of two snapshots of the same report
+    pub fn compare(conn: &mut DatabaseConnection, before: &str, after: &str) -> Result<ReportComparison> {
+        let before = Self::find(conn, before)?;
+        let after = Self::find(conn, after)?;
+        if before.report_id != after.report_id || before.title != after.title {
+            return Err(crate::core::error::CLIERPError::Validation(format!(
+                "Snapshots '{}' ({}) and '{}' ({}) are of different reports",
+                before.name, before.title, after.name, after.title
+            )));
+        }
+
+        let metrics = compare_metrics(&Self::result(&before)?, &Self::result(&after)?);
+        Ok(ReportComparison { before, after, metrics })
+    }
+}
+
+/// Key metrics of two runs side by side, sorted by name. Metrics present in
+/// only one run are kept with the other side empty.
+pub fn compare_metrics(before: &ReportResult, after: &ReportResult) -> Vec<MetricDelta> {
+    let empty = Default::default();
+    let before_metrics = before.summary.as_ref().map_or(&empty, |s| &s.key_metrics);
+    let after_metrics = after.summary.as_ref().map_or(&empty, |s| &s.key_metrics);
+
+    let mut names: Vec<&String> = before_metrics.keys().chain(after_metrics.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let before = before_metrics.get(name).cloned();
+            let after = after_metrics.get(name).cloned();
+            let (change, change_pct) = match (&before, &after) {
+                (Some(before), Some(after)) => (metric_change(before, after), percent_change(before, after)),
+                _ => (None, None),
+            };
+            MetricDelta {
+                metric: name.clone(),
+                before,
+                after,
+                change,
+                change_pct,
+            }
+        })
+        .collect()
+}
+
+/// Difference of two values of the same kind; text and mixed kinds have none
+fn metric_change(before: &MetricValue, after: &MetricValue) -> Option<MetricValue> {
+    match (before, after) {
+        (MetricValue::Number(a), MetricValue::Number(b)) => Some(MetricValue::Number(b - a)),
+        (MetricValue::Currency(a), MetricValue::Currency(b)) => Some(MetricValue::Currency(b - a)),
+        // Percentages change by percentage points
+        (MetricValue::Percentage(a), MetricValue::Percentage(b)) => Some(MetricValue::Number(b - a)),
+        (MetricValue::Count(a), MetricValue::Count(b)) => Some(MetricValue::Count(b - a)),
+        _ => None,
+    }
+}
+
+fn percent_change(before: &MetricValue, after: &MetricValue) -> Option<f64> {
+    let numeric = |value: &MetricValue| match value {
+        MetricValue::Number(n) | MetricValue::Percentage(n) => Some(*n),
+        MetricValue::Currency(c) => Some(*c as f64),
+        MetricValue::Count(c) => Some(*c as f64),
+        MetricValue::Text(_) => None,
+    };
+    // Only values of the same kind compare
+    metric_change(before, after)?;
+    let (a, b) = (numeric(before)?, numeric(after)?);
+    if a == 0.0 {
+        return None;
+    }
+    Some((b - a) / a.abs() * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::reporting::{
+        ReportConfig, ReportData, ReportFormat, ReportMetadata, ReportSummary, TableData,
+    };
+    use std::collections::HashMap;
+
+    fn run(metrics: &[(&str, MetricValue)]) -> ReportResult {
+        ReportResult {
+            config: ReportConfig {
+                title: "inventory_valuation".to_string(),
+                description: None,
+                date_range: None,
+                filters: HashMap::new(),
+                format: ReportFormat::Text,
+                include_charts: false,
+                include_summary: true,
+            },
+            generated_at: chrono::Utc::now().naive_utc(),
+            data: ReportData::Table(TableData {
+                headers: Vec::new(),
+                rows: Vec::new(),
+                totals: None,
+            }),
+            summary: Some(ReportSummary {
+                key_metrics: metrics
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.clone()))
+                    .collect(),
+                insights: Vec::new(),
+                recommendations: Vec::new(),
+            }),
+            metadata: ReportMetadata {
+                total_records: 0,
+                processing_time_ms: 0,
+                filters_applied: Vec::new(),
+                data_sources: Vec::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_compare_metrics() {
+        let before = run(&[
+            ("total_value", MetricValue::Currency(200_000)),
+            ("margin", MetricValue::Percentage(30.0)),
+            ("items", MetricValue::Count(0)),
+            ("method", MetricValue::Text("FIFO".to_string())),
+        ]);
+        let after = run(&[
+            ("total_value", MetricValue::Currency(250_000)),
+            ("margin", MetricValue::Percentage(27.5)),
+            ("items", MetricValue::Count(12)),
+            ("method", MetricValue::Text("FIFO".to_string())),
+            ("slow_movers", MetricValue::Count(3)),
+        ]);
+
+        let deltas = compare_metrics(&before, &after);
+        let names: Vec<&str> = deltas.iter().map(|d| d.metric.as_str()).collect();
+        assert_eq!(names, vec!["items", "margin", "method", "slow_movers", "total_value"]);
+
+        let total = &deltas[4];
+        assert!(matches!(total.change, Some(MetricValue::Currency(50_000))));
+        assert_eq!(total.change_pct, Some(25.0));
+
+        let margin = &deltas[1];
+        assert!(matches!(margin.change, Some(MetricValue::Number(p)) if p == -2.5));
+
+        // Growth from zero has no percentage
+        assert!(matches!(deltas[0].change, Some(MetricValue::Count(12))));
+        assert_eq!(deltas[0].change_pct, None);
+
+        assert!(deltas[2].change.is_none());
+        assert!(deltas[3].before.is_none() && deltas[3].change.is_none());
+    }
+}
@@ -0,0 +1,246 @@
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+use diesel::sql_types::{Date, Double, Integer, Timestamp};
+
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{stock_movements, summary_account_monthly, summary_product_daily, transactions};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Stock movement totals per product and UTC day. `cost_out` is the cost of
+/// shipments with a unit cost; `uncosted_out` the quantity shipped without
+/// one, costed at the product's cost price when read.
+const PRODUCT_DAILY: &str = "INSERT INTO summary_product_daily
+        (product_id, day, quantity_in, quantity_out, cost_out, uncosted_out, movements)
+     SELECT product_id, date(movement_date),
+            COALESCE(SUM(CASE WHEN movement_type = 'in' THEN ABS(quantity) END), 0),
+            COALESCE(SUM(CASE WHEN movement_type = 'out' THEN ABS(quantity) END), 0),
+            COALESCE(SUM(CASE WHEN movement_type = 'out' AND unit_cost IS NOT NULL THEN ABS(quantity) * unit_cost END), 0),
+            COALESCE(SUM(CASE WHEN movement_type = 'out' AND unit_cost IS NULL THEN ABS(quantity) END), 0),
+            COUNT(*)
+     FROM stock_movements WHERE {} GROUP BY product_id, date(movement_date)";
+
+/// Debit and credit totals per account and month (YYYY-MM)
+const ACCOUNT_MONTHLY: &str = "INSERT INTO summary_account_monthly (account_id, month, debits, credits, entries)
+     SELECT account_id, strftime('%Y-%m', transaction_date),
+            COALESCE(SUM(CASE WHEN debit_credit = 'debit' THEN amount END), 0),
+            COALESCE(SUM(CASE WHEN debit_credit = 'credit' THEN amount END), 0),
+            COUNT(*)
+     FROM transactions WHERE {} GROUP BY account_id, strftime('%Y-%m', transaction_date)";
+
+#[derive(QueryableByName)]
+struct CostRow {
+    #[diesel(sql_type = Double)]
+    value: f64,
+}
+
+/// Rows in each summary table after a rebuild
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SummaryCounts {
+    pub product_days: i64,
+    pub account_months: i64,
+}
+
+/// Summary tables reports read instead of re-aggregating stock movements and
+/// transactions. Services refresh the cell a write touched (one product and
+/// day, or one account and month) right after writing; a rebuild recomputes
+/// everything for writes made outside CLIERP.
+pub struct SummaryService;
+
+impl SummaryService {
+    /// Recompute a product's totals for one UTC day
+    pub fn refresh_product_day(conn: &mut SqliteConnection, product_id: i32, day: NaiveDate) -> Result<()> {
+        diesel::delete(
+            summary_product_daily::table
+                .filter(summary_product_daily::product_id.eq(product_id))
+                .filter(summary_product_daily::day.eq(day)),
+        )
+        .execute(conn)?;
+        diesel::sql_query(PRODUCT_DAILY.replace("{}", "product_id = ? AND date(movement_date) = ?"))
+            .bind::<Integer, _>(product_id)
+            .bind::<Date, _>(day)
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Recompute every day of a product, e.g. after its movements were deleted
+    pub fn refresh_product(conn: &mut SqliteConnection, product_id: i32) -> Result<()> {
+        diesel::delete(summary_product_daily::table.filter(summary_product_daily::product_id.eq(product_id)))
+            .execute(conn)?;
+        diesel::sql_query(PRODUCT_DAILY.replace("{}", "product_id = ?"))
+            .bind::<Integer, _>(product_id)
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Recompute an account's totals for the month of `date`
+    pub fn refresh_account_month(conn: &mut SqliteConnection, account_id: i32, date: NaiveDate) -> Result<()> {
+        let month = date.format("%Y-%m").to_string();
+        diesel::delete(
+            summary_account_monthly::table
+                .filter(summary_account_monthly::account_id.eq(account_id))
+                .filter(summary_account_monthly::month.eq(&month)),
+        )
+        .execute(conn)?;
+        diesel::sql_query(ACCOUNT_MONTHLY.replace("{}", "account_id = ? AND strftime('%Y-%m', transaction_date) = ?"))
+            .bind::<Integer, _>(account_id)
+            .bind::<diesel::sql_types::Text, _>(month)
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Recompute both summary tables from scratch
+    pub fn rebuild(conn: &mut SqliteConnection) -> Result<SummaryCounts> {
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::delete(summary_product_daily::table).execute(conn)?;
+            diesel::sql_query(PRODUCT_DAILY.replace("{}", "1 = 1")).execute(conn)?;
+            diesel::delete(summary_account_monthly::table).execute(conn)?;
+            diesel::sql_query(ACCOUNT_MONTHLY.replace("{}", "1 = 1")).execute(conn)?;
+            Ok(())
+        })?;
+        let counts = SummaryCounts {
+            product_days: summary_product_daily::table.count().get_result(conn)?,
+            account_months: summary_account_monthly::table.count().get_result(conn)?,
+        };
+        tracing::info!(
+            "Rebuilt summaries: {} product day(s), {} account month(s)",
+            counts.product_days,
+            counts.account_months
+        );
+        Ok(counts)
+    }
+
+    /// Rebuild when a summary table is empty but its source is not, as on a
+    /// database created before the summaries existed
+    pub fn backfill(conn: &mut SqliteConnection) -> Result<()> {
+        let products_missing = summary_product_daily::table.count().get_result::<i64>(conn)? == 0
+            && stock_movements::table.count().get_result::<i64>(conn)? > 0;
+        let accounts_missing = summary_account_monthly::table.count().get_result::<i64>(conn)? == 0
+            && transactions::table.count().get_result::<i64>(conn)? > 0;
+        if products_missing || accounts_missing {
+            Self::rebuild(conn)?;
+        }
+        Ok(())
+    }
+
+    /// Debits and credits posted to an account between two dates, inclusive.
+    /// Whole months come from the summary and the days around them from the
+    /// transactions themselves.
+    pub fn account_totals(
+        conn: &mut SqliteConnection,
+        account_id: i32,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<(i64, i64)> {
+        let (start, end) = whole_months(from, to);
+        if let (Some(start), Some(end)) = (start, end) {
+            if start >= end {
+                return Self::add_transactions(conn, account_id, &[(from, to.and_then(|to| to.succ_opt()))], 0, 0);
+            }
+        }
+
+        let mut query = summary_account_monthly::table
+            .filter(summary_account_monthly::account_id.eq(account_id))
+            .select((summary_account_monthly::debits, summary_account_monthly::credits))
+            .into_boxed();
+        let mut edges = Vec::new();
+        if let Some(start) = start {
+            query = query.filter(summary_account_monthly::month.ge(start.format("%Y-%m").to_string()));
+            edges.push((from, Some(start)));
+        }
+        if let Some(end) = end {
+            query = query.filter(summary_account_monthly::month.lt(end.format("%Y-%m").to_string()));
+            edges.push((Some(end), to.and_then(|to| to.succ_opt())));
+        }
+        let (debits, credits) = query
+            .load::<(i64, i64)>(conn)?
+            .into_iter()
+            .fold((0, 0), |(d, c), (debits, credits)| (d + debits, c + credits));
+        Self::add_transactions(conn, account_id, &edges, debits, credits)
+    }
+
+    /// Cost of stock shipped since `since`: whole days after it from the
+    /// summary, the rest of its day from the movements themselves
+    pub fn cost_of_sales_since(conn: &mut SqliteConnection, since: NaiveDateTime) -> Result<f64> {
+        let row = diesel::sql_query(
+            "SELECT CAST(
+                 COALESCE((SELECT SUM(s.cost_out + s.uncosted_out * p.cost_price)
+                           FROM summary_product_daily s JOIN products p ON p.id = s.product_id
+                           WHERE s.day > date(?)), 0)
+               + COALESCE((SELECT SUM(ABS(m.quantity) * COALESCE(m.unit_cost, p.cost_price))
+                           FROM stock_movements m JOIN products p ON p.id = m.product_id
+                           WHERE m.movement_type = 'out' AND m.movement_date >= ? AND date(m.movement_date) = date(?)), 0)
+             AS REAL) AS value",
+        )
+        .bind::<Timestamp, _>(since)
+        .bind::<Timestamp, _>(since)
+        .bind::<Timestamp, _>(since)
+        .get_result::<CostRow>(conn)?;
+        Ok(row.value)
+    }
+
+    /// Add the transactions in each `[from, until)` range to the totals
+    fn add_transactions(
+        conn: &mut SqliteConnection,
+        account_id: i32,
+        ranges: &[(Option<NaiveDate>, Option<NaiveDate>)],
+        mut debits: i64,
+        mut credits: i64,
+    ) -> Result<(i64, i64)> {
+        for (from, until) in ranges {
+            let mut query = transactions::table
+                .filter(transactions::account_id.eq(account_id))
+                .select((transactions::debit_credit, transactions::amount))
+                .into_boxed();
+            if let Some(from) = from {
+                query = query.filter(transactions::transaction_date.ge(*from));
+            }
+            if let Some(until) = until {
+                query = query.filter(transactions::transaction_date.lt(*until));
+            }
+            for (side, amount) in query.load::<(String, i32)>(conn)? {
+                match side.as_str() {
+                    "debit" => debits += i64::from(amount),
+                    "credit" => credits += i64::from(amount),
+                    _ => {}
+                }
+            }
+        }
+        Ok((debits, credits))
+    }
+}
+
+/// First day of the first whole month on or after `from` and the day after
+/// the last whole month on or before `to`; `None` is unbounded
+fn whole_months(from: Option<NaiveDate>, to: Option<NaiveDate>) -> (Option<NaiveDate>, Option<NaiveDate>) {
+    let month_start = |date: NaiveDate| date.with_day(1).expect("every month has a first day");
+    let start = from.map(|from| {
+        if from.day() == 1 {
+            from
+        } else {
+            month_start(from) + Months::new(1)
+        }
+    });
+    let end = to.map(|to| match to.succ_opt() {
+        Some(next) if next.day() == 1 => next,
+        _ => month_start(to),
+    });
+    (start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whole_months() {
+        let date = |d: &str| d.parse::<NaiveDate>().ok();
+        assert_eq!(whole_months(date("2024-01-01"), date("2024-03-31")), (date("2024-01-01"), date("2024-04-01")));
+        assert_eq!(whole_months(date("2024-01-15"), date("2024-03-10")), (date("2024-02-01"), date("2024-03-01")));
+        assert_eq!(whole_months(None, date("2024-02-29")), (None, date("2024-03-01")));
+        assert_eq!(whole_months(date("2024-12-02"), None), (date("2025-01-01"), None));
+        // Within one month there is no whole month
+        assert_eq!(whole_months(date("2024-05-03"), date("2024-05-20")), (date("2024-06-01"), date("2024-05-01")));
+    }
+}
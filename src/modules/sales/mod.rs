@@ -0,0 +1,3 @@
+pub mod order;
+
+pub use order::*;
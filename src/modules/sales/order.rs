@@ -0,0 +1,371 @@
+use chrono::{NaiveDate, Utc};
+use diesel::prelude::*;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+use crate::database::schema::{customers, deals, invoices, leads, products, sales_order_items, sales_orders};
+use crate::database::{
+    Customer, DatabaseConnection, Deal, DealProduct, DealStage, Invoice, InvoiceKind, InvoiceStatus,
+    NewInvoice, NewSalesOrder, NewSalesOrderItem, SalesOrder, SalesOrderItem, SalesOrderItemWithProduct,
+    SalesOrderStatus, SalesOrderWithItems,
+};
+use crate::modules::inventory::ReservationService;
+
+/// Reference type recorded on reservations and stock movements of sales orders
+pub const SALES_ORDER_REFERENCE: &str = "sales_order";
+
+/// Fulfillment of a won deal: the order reserves stock for its lines, ships
+/// them as stock out movements and bills the customer with a receivable
+/// invoice. Orders move pending → reserved → shipped → invoiced and can be
+/// cancelled until they ship.
+pub struct SalesOrderService;
+
+impl SalesOrderService {
+    /// Create the order for a won deal from its product lines
+    pub fn create_from_deal(
+        conn: &mut DatabaseConnection,
+        deal_id: i32,
+        notes: Option<&str>,
+        created_by: Option<i32>,
+    ) -> Result<SalesOrder> {
+        let deal = deals::table
+            .find(deal_id)
+            .first::<Deal>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Deal with ID {} not found", deal_id)))?;
+        if deal.stage != DealStage::ClosedWon.to_string() {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Deal {} is {}; only won deals can be ordered",
+                deal.id, deal.stage
+            )));
+        }
+        let lines: Vec<DealProduct> = match &deal.products {
+            Some(json) => serde_json::from_str(json)?,
+            None => Vec::new(),
+        };
+        if lines.is_empty() {
+            return Err(CLIERPError::Validation(format!("Deal {} has no product lines to order", deal.id)));
+        }
+        let existing = sales_orders::table
+            .filter(sales_orders::deal_id.eq(deal.id))
+            .filter(sales_orders::status.ne(SalesOrderStatus::Cancelled.to_string()))
+            .select(sales_orders::order_number)
+            .first::<String>(conn)
+            .optional()?;
+        if let Some(order_number) = existing {
+            return Err(CLIERPError::AlreadyExists(format!(
+                "Deal {} already has sales order {}",
+                deal.id, order_number
+            )));
+        }
+
+        let customer_id = match deal.lead_id {
+            Some(lead_id) => leads::table
+                .find(lead_id)
+                .select(leads::customer_id)
+                .first::<Option<i32>>(conn)
+                .optional()?
+                .flatten(),
+            None => None,
+        };
+        let discount_percent = deal.discount_percent.unwrap_or(0);
+        let order_number = Self::generate_order_number(conn)?;
+
+        let order = conn.transaction::<_, CLIERPError, _>(|conn| {
+            diesel::insert_into(sales_orders::table)
+                .values(&NewSalesOrder {
+                    order_number: order_number.clone(),
+                    deal_id: Some(deal.id),
+                    customer_id,
+                    order_date: Utc::now().date_naive(),
+                    status: SalesOrderStatus::Pending.to_string(),
+                    discount_percent,
+                    total_amount: order_total(&lines, discount_percent),
+                    notes: notes.map(|s| s.to_string()),
+                    created_by,
+                })
+                .execute(conn)?;
+            let order = sales_orders::table
+                .filter(sales_orders::order_number.eq(&order_number))
+                .first::<SalesOrder>(conn)?;
+
+            for line in &lines {
+                if line.quantity <= 0 {
+                    return Err(CLIERPError::Validation(format!(
+                        "Deal line for product {} has no quantity",
+                        line.product_id
+                    )));
+                }
+                diesel::insert_into(sales_order_items::table)
+                    .values(&NewSalesOrderItem {
+                        order_id: order.id,
+                        product_id: line.product_id,
+                        quantity: line.quantity,
+                        unit_price: line.unit_price,
+                        total_price: line.total_price(),
+                    })
+                    .execute(conn)?;
+            }
+            Ok(order)
+        })?;
+
+        tracing::info!("Created sales order {} from deal {}", order.order_number, deal.id);
+        Ok(order)
+    }
+
+    pub fn get_sales_order_by_id(conn: &mut DatabaseConnection, order_id: i32) -> Result<Option<SalesOrder>> {
+        sales_orders::table
+            .find(order_id)
+            .first::<SalesOrder>(conn)
+            .optional()
+            .map_err(Into::into)
+    }
+
+    pub fn get_sales_order_with_details(
+        conn: &mut DatabaseConnection,
+        order_id: i32,
+    ) -> Result<SalesOrderWithItems> {
+        let sales_order = Self::get_order(conn, order_id)?;
+
+        let customer = match sales_order.customer_id {
+            Some(customer_id) => customers::table
+                .find(customer_id)
+                .first::<Customer>(conn)
+                .optional()?,
+            None => None,
+        };
+
+        let items = sales_order_items::table
+            .inner_join(products::table)
+            .filter(sales_order_items::order_id.eq(order_id))
+            .order(sales_order_items::id.asc())
+            .select((
+                SalesOrderItem::as_select(),
+                products::name,
+                products::sku,
+                products::unit,
+            ))
+            .load::<(SalesOrderItem, String, String, String)>(conn)?
+            .into_iter()
+            .map(|(item, product_name, product_sku, unit)| SalesOrderItemWithProduct {
+                sales_order_item: item,
+                product_name,
+                product_sku,
+                unit,
+            })
+            .collect();
+
+        Ok(SalesOrderWithItems {
+            sales_order,
+            items,
+            customer,
+        })
+    }
+
+    /// Orders, newest first, optionally with one status
+    pub fn list_sales_orders(
+        conn: &mut DatabaseConnection,
+        status: Option<SalesOrderStatus>,
+    ) -> Result<Vec<SalesOrder>> {
+        let mut query = sales_orders::table.into_boxed();
+        if let Some(status) = status {
+            query = query.filter(sales_orders::status.eq(status.to_string()));
+        }
+
+        query
+            .order((sales_orders::order_date.desc(), sales_orders::id.desc()))
+            .load::<SalesOrder>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Hold stock for every line. Fails without reserving anything if one
+    /// product is short.
+    pub fn reserve(conn: &mut DatabaseConnection, order_id: i32, reserved_by: Option<i32>) -> Result<SalesOrder> {
+        let order = Self::get_order(conn, order_id)?;
+        Self::require_status(&order, SalesOrderStatus::Pending, "reserved")?;
+
+        conn.transaction::<_, CLIERPError, _>(|conn| {
+            for item in Self::items(conn, order.id)? {
+                let reservation = ReservationService::reserve(
+                    conn,
+                    item.product_id,
+                    item.quantity,
+                    SALES_ORDER_REFERENCE,
+                    Some(order.id),
+                    Some(&format!("Sales order {}", order.order_number)),
+                    reserved_by,
+                )?;
+                diesel::update(sales_order_items::table.find(item.id))
+                    .set(sales_order_items::reservation_id.eq(reservation.id))
+                    .execute(conn)?;
+            }
+            Self::set_status(conn, order.id, SalesOrderStatus::Reserved)
+        })?;
+
+        tracing::info!("Reserved stock for sales order {}", order.order_number);
+        Self::get_order(conn, order_id)
+    }
+
+    /// Ship the reserved lines, moving the stock out
+    pub fn ship(conn: &mut DatabaseConnection, order_id: i32, shipped_by: Option<i32>) -> Result<SalesOrder> {
+        let order = Self::get_order(conn, order_id)?;
+        Self::require_status(&order, SalesOrderStatus::Reserved, "shipped")?;
+
+        conn.transaction::<_, CLIERPError, _>(|conn| {
+            for item in Self::items(conn, order.id)? {
+                let reservation_id = item.reservation_id.ok_or_else(|| {
+                    CLIERPError::BusinessLogic(format!("Line {} of sales order {} has no reservation", item.id, order.order_number))
+                })?;
+                ReservationService::fulfill(conn, reservation_id, shipped_by)?;
+                diesel::update(sales_order_items::table.find(item.id))
+                    .set(sales_order_items::shipped_quantity.eq(item.quantity))
+                    .execute(conn)?;
+            }
+            diesel::update(sales_orders::table.find(order.id))
+                .set(sales_orders::shipped_at.eq(Utc::now().naive_utc()))
+                .execute(conn)?;
+            Self::set_status(conn, order.id, SalesOrderStatus::Shipped)
+        })?;
+
+        tracing::info!("Shipped sales order {}", order.order_number);
+        Self::get_order(conn, order_id)
+    }
+
+    /// Bill a shipped order with a receivable invoice for its total
+    pub fn invoice(
+        conn: &mut DatabaseConnection,
+        order_id: i32,
+        due_date: Option<NaiveDate>,
+        created_by: Option<i32>,
+    ) -> Result<Invoice> {
+        let order = Self::get_order(conn, order_id)?;
+        Self::require_status(&order, SalesOrderStatus::Shipped, "invoiced")?;
+        let customer_id = order.customer_id.ok_or_else(|| {
+            CLIERPError::BusinessLogic(format!("Sales order {} has no customer to invoice", order.order_number))
+        })?;
+
+        let invoice = conn.transaction::<_, CLIERPError, _>(|conn| {
+            diesel::insert_into(invoices::table)
+                .values(&NewInvoice {
+                    invoice_number: format!("INV-{}", order.order_number),
+                    kind: InvoiceKind::Receivable.to_string(),
+                    customer_id: Some(customer_id),
+                    supplier_id: None,
+                    invoice_date: Utc::now().date_naive(),
+                    due_date,
+                    total_amount: order.total_amount,
+                    paid_amount: 0,
+                    status: InvoiceStatus::Open.to_string(),
+                    description: Some(format!("Sales order {}", order.order_number)),
+                    created_by,
+                    po_id: None,
+                })
+                .execute(conn)?;
+            let invoice = invoices::table
+                .filter(invoices::kind.eq(InvoiceKind::Receivable.to_string()))
+                .filter(invoices::invoice_number.eq(format!("INV-{}", order.order_number)))
+                .first::<Invoice>(conn)?;
+
+            diesel::update(sales_orders::table.find(order.id))
+                .set(sales_orders::invoice_id.eq(invoice.id))
+                .execute(conn)?;
+            Self::set_status(conn, order.id, SalesOrderStatus::Invoiced)?;
+            Ok(invoice)
+        })?;
+
+        tracing::info!("Invoiced sales order {} as {}", order.order_number, invoice.invoice_number);
+        Ok(invoice)
+    }
+
+    /// Cancel an order that has not shipped, releasing its reservations
+    pub fn cancel(conn: &mut DatabaseConnection, order_id: i32) -> Result<SalesOrder> {
+        let order = Self::get_order(conn, order_id)?;
+        let status: SalesOrderStatus = order.status.parse()?;
+        if !matches!(status, SalesOrderStatus::Pending | SalesOrderStatus::Reserved) {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Sales order {} is {} and can no longer be cancelled",
+                order.order_number, order.status
+            )));
+        }
+
+        conn.transaction::<_, CLIERPError, _>(|conn| {
+            for item in Self::items(conn, order.id)? {
+                if let Some(reservation_id) = item.reservation_id {
+                    ReservationService::release(conn, reservation_id)?;
+                }
+            }
+            Self::set_status(conn, order.id, SalesOrderStatus::Cancelled)
+        })?;
+
+        tracing::info!("Cancelled sales order {}", order.order_number);
+        Self::get_order(conn, order_id)
+    }
+
+    fn get_order(conn: &mut DatabaseConnection, order_id: i32) -> Result<SalesOrder> {
+        Self::get_sales_order_by_id(conn, order_id)?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Sales order with ID {} not found", order_id)))
+    }
+
+    fn items(conn: &mut DatabaseConnection, order_id: i32) -> Result<Vec<SalesOrderItem>> {
+        sales_order_items::table
+            .filter(sales_order_items::order_id.eq(order_id))
+            .order(sales_order_items::id.asc())
+            .load::<SalesOrderItem>(conn)
+            .map_err(Into::into)
+    }
+
+    fn require_status(order: &SalesOrder, expected: SalesOrderStatus, next: &str) -> Result<()> {
+        if order.status != expected.to_string() {
+            return Err(CLIERPError::BusinessLogic(format!(
+                "Sales order {} is {}; only {} orders can be {}",
+                order.order_number, order.status, expected, next
+            )));
+        }
+        Ok(())
+    }
+
+    fn set_status(conn: &mut DatabaseConnection, order_id: i32, status: SalesOrderStatus) -> Result<()> {
+        diesel::update(sales_orders::table.find(order_id))
+            .set((
+                sales_orders::status.eq(status.to_string()),
+                sales_orders::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    fn generate_order_number(conn: &mut DatabaseConnection) -> Result<String> {
+        let count = sales_orders::table
+            .count()
+            .get_result::<i64>(conn)?;
+
+        let today = Utc::now().naive_utc().date();
+        Ok(format!("SO{}{:06}", today.format("%Y%m%d"), count + 1))
+    }
+}
+
+/// Sum of the lines less the deal's discount, rounded down
+pub fn order_total(lines: &[DealProduct], discount_percent: i32) -> i32 {
+    let subtotal: i64 = lines.iter().map(|line| line.total_price() as i64).sum();
+    (subtotal * (100 - discount_percent.clamp(0, 100)) as i64 / 100) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_total() {
+        let lines = vec![
+            DealProduct { product_id: 1, quantity: 3, unit_price: 1000 },
+            DealProduct { product_id: 2, quantity: 1, unit_price: 999 },
+        ];
+        assert_eq!(order_total(&lines, 0), 3999);
+        assert_eq!(order_total(&lines, 10), 3599);
+        assert_eq!(order_total(&lines, 150), 0);
+        assert_eq!(order_total(&[], 5), 0);
+    }
+}
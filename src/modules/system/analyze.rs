@@ -0,0 +1,216 @@
+use std::time::Instant;
+
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable, Text};
+
+use crate::core::result::CLIERPResult;
+use crate::database::DatabaseConnection;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Access paths behind the list, search and report commands, each checked
+/// against the query planner. Literal values only select the plan shape.
+pub const ACCESS_PATHS: &[(&str, &str, &str)] = &[
+    ("product by SKU", "products", "SELECT id FROM products WHERE sku = 'SKU'"),
+    (
+        "stock movements of a product",
+        "stock_movements",
+        "SELECT id FROM stock_movements WHERE product_id = 1 ORDER BY movement_date DESC",
+    ),
+    (
+        "change history of a record",
+        "audit_logs",
+        "SELECT id FROM audit_logs WHERE table_name = 'products' AND record_id = 1 ORDER BY changed_at",
+    ),
+    ("customer by email", "customers", "SELECT id FROM customers WHERE email = 'a@example.com'"),
+    (
+        "active customers by name",
+        "customers",
+        "SELECT id FROM customers WHERE status = 'active' ORDER BY name",
+    ),
+    (
+        "leads by status",
+        "leads",
+        "SELECT id FROM leads WHERE status = 'new' ORDER BY created_at DESC",
+    ),
+    (
+        "deals in a pipeline stage",
+        "deals",
+        "SELECT id FROM deals WHERE stage = 'proposal' ORDER BY close_date",
+    ),
+    (
+        "open activities of an employee",
+        "activities",
+        "SELECT id FROM activities WHERE assigned_to = 1 AND completed = 0 ORDER BY activity_date",
+    ),
+    (
+        "open purchase orders",
+        "purchase_orders",
+        "SELECT id FROM purchase_orders WHERE status = 'sent' ORDER BY expected_date",
+    ),
+    (
+        "ledger of an account",
+        "transactions",
+        "SELECT id FROM transactions WHERE account_id = 1 ORDER BY transaction_date",
+    ),
+];
+
+/// Row count estimate and indexes of a table, from `sqlite_stat1`
+#[derive(Debug, Clone)]
+pub struct TableStats {
+    pub table: String,
+    pub rows: i64,
+    pub indexes: Vec<String>,
+}
+
+/// How the planner answers one access path, and how long it took
+#[derive(Debug, Clone)]
+pub struct AccessPathCheck {
+    pub name: String,
+    pub table: String,
+    pub plan: Vec<String>,
+    pub full_scan: bool,
+    pub temp_sort: bool,
+    pub rows_matched: i64,
+    pub elapsed_ms: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnalyzeReport {
+    pub tables: Vec<TableStats>,
+    pub checks: Vec<AccessPathCheck>,
+    /// Access paths skipped because their table does not exist in this database
+    pub skipped: Vec<String>,
+}
+
+#[derive(QueryableByName)]
+struct StatRow {
+    #[diesel(sql_type = Text)]
+    tbl: String,
+    #[diesel(sql_type = Nullable<Text>)]
+    idx: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    stat: Option<String>,
+}
+
+#[derive(QueryableByName)]
+struct PlanRow {
+    #[diesel(sql_type = Text)]
+    detail: String,
+}
+
+#[derive(QueryableByName)]
+struct NameRow {
+    #[diesel(sql_type = Text)]
+    name: String,
+}
+
+#[derive(QueryableByName)]
+struct CountRow {
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+pub struct AnalyzeService;
+
+impl AnalyzeService {
+    /// Refresh the planner statistics with `ANALYZE`, then explain and time
+    /// every access path in `ACCESS_PATHS`
+    pub fn run(conn: &mut DatabaseConnection) -> Result<AnalyzeReport> {
+        diesel::sql_query("ANALYZE").execute(conn)?;
+
+        let existing: Vec<String> = diesel::sql_query(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+        )
+        .load::<NameRow>(conn)?
+        .into_iter()
+        .map(|row| row.name)
+        .collect();
+
+        let stats = diesel::sql_query("SELECT tbl, idx, stat FROM sqlite_stat1")
+            .load::<StatRow>(conn)?;
+        let mut tables: Vec<TableStats> = existing
+            .iter()
+            .map(|table| {
+                let rows: Vec<&StatRow> = stats.iter().filter(|s| &s.tbl == table).collect();
+                TableStats {
+                    table: table.clone(),
+                    rows: rows
+                        .iter()
+                        .filter_map(|s| s.stat.as_deref().and_then(stat_row_count))
+                        .max()
+                        .unwrap_or(0),
+                    indexes: rows.iter().filter_map(|s| s.idx.clone()).collect(),
+                }
+            })
+            .collect();
+        tables.sort_by(|a, b| b.rows.cmp(&a.rows).then_with(|| a.table.cmp(&b.table)));
+
+        let mut checks = Vec::new();
+        let mut skipped = Vec::new();
+        for (name, table, sql) in ACCESS_PATHS {
+            if !existing.iter().any(|t| t == table) {
+                skipped.push(name.to_string());
+                continue;
+            }
+
+            let plan: Vec<String> = diesel::sql_query(format!("EXPLAIN QUERY PLAN {}", sql))
+                .load::<PlanRow>(conn)?
+                .into_iter()
+                .map(|row| row.detail)
+                .collect();
+
+            let started = Instant::now();
+            let matched = diesel::sql_query(format!("SELECT COUNT(*) AS count FROM ({})", sql))
+                .get_result::<CountRow>(conn)?;
+            let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+            checks.push(AccessPathCheck {
+                name: name.to_string(),
+                table: table.to_string(),
+                full_scan: plan.iter().any(|step| is_full_scan(step)),
+                temp_sort: plan.iter().any(|step| step.contains("USE TEMP B-TREE")),
+                plan,
+                rows_matched: matched.count,
+                elapsed_ms,
+            });
+        }
+
+        Ok(AnalyzeReport {
+            tables,
+            checks,
+            skipped,
+        })
+    }
+}
+
+/// Whether a query plan step reads a whole table instead of using an index
+pub fn is_full_scan(detail: &str) -> bool {
+    detail.starts_with("SCAN ") && !detail.contains(" USING ")
+}
+
+/// First number of a `sqlite_stat1.stat` value, the table's estimated row count
+fn stat_row_count(stat: &str) -> Option<i64> {
+    stat.split_whitespace().next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_scan_detection() {
+        assert!(is_full_scan("SCAN deals"));
+        assert!(is_full_scan("SCAN TABLE deals"));
+        assert!(!is_full_scan("SCAN deals USING INDEX idx_deals_stage"));
+        assert!(!is_full_scan("SCAN products USING COVERING INDEX idx_products_sku"));
+        assert!(!is_full_scan("SEARCH deals USING INDEX idx_deals_stage (stage=?)"));
+    }
+
+    #[test]
+    fn test_stat_row_count() {
+        assert_eq!(stat_row_count("1520 4 1"), Some(1520));
+        assert_eq!(stat_row_count(""), None);
+    }
+}
@@ -0,0 +1,267 @@
+use chrono::{NaiveDate, Utc};
+use diesel::prelude::*;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{accounts, anomaly_flags, products, stock_movements, transactions};
+use crate::database::{AnomalyFlag, AnomalyStatus, DatabaseConnection, NewAnomalyFlag};
+use crate::modules::inventory::ARCHIVE_SUMMARY_REFERENCE;
+use crate::utils::anomaly::{find_outliers, AnomalyMethod};
+use crate::utils::timezone::DisplayTimezone;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// `anomaly_flags.source` of flagged transactions
+pub const TRANSACTION_SOURCE: &str = "transaction";
+/// `anomaly_flags.source` of flagged stock movements
+pub const STOCK_MOVEMENT_SOURCE: &str = "stock_movement";
+
+/// Which records an anomaly scan looks at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum AnomalySource {
+    /// Transaction amounts, compared per account
+    Transactions,
+    /// Stock movement quantities, compared per SKU
+    Stock,
+    All,
+}
+
+/// Result of an anomaly scan
+#[derive(Debug, Clone)]
+pub struct ScanOutcome {
+    pub records_scanned: usize,
+    /// Flags created by this scan
+    pub flagged: Vec<AnomalyFlag>,
+    /// Outliers that were flagged by an earlier scan
+    pub already_flagged: usize,
+}
+
+/// One record to test, with the group it is compared within
+struct Candidate {
+    record_id: i32,
+    subject: String,
+    value: f64,
+}
+
+/// Flags unusual postings and stock movements for review.
+///
+/// Transaction amounts are compared with the other amounts of the same
+/// account, stock movement quantities with the other movements of the same
+/// product. Outliers are stored as open flags once; reviewing a flag
+/// records who looked at it and whether it was a real problem.
+pub struct AnomalyService;
+
+impl AnomalyService {
+    pub fn scan(
+        conn: &mut DatabaseConnection,
+        source: AnomalySource,
+        method: AnomalyMethod,
+        threshold: Option<f64>,
+        since: Option<NaiveDate>,
+    ) -> Result<ScanOutcome> {
+        let threshold = threshold.unwrap_or_else(|| method.default_threshold());
+        if threshold <= 0.0 {
+            return Err(crate::core::error::CLIERPError::Validation(
+                "Threshold must be greater than zero".to_string(),
+            ));
+        }
+
+        let mut outcome = ScanOutcome {
+            records_scanned: 0,
+            flagged: Vec::new(),
+            already_flagged: 0,
+        };
+        if matches!(source, AnomalySource::Transactions | AnomalySource::All) {
+            let candidates = Self::transaction_candidates(conn, since)?;
+            Self::flag_outliers(conn, TRANSACTION_SOURCE, candidates, method, threshold, &mut outcome)?;
+        }
+        if matches!(source, AnomalySource::Stock | AnomalySource::All) {
+            let candidates = Self::stock_candidates(conn, since)?;
+            Self::flag_outliers(conn, STOCK_MOVEMENT_SOURCE, candidates, method, threshold, &mut outcome)?;
+        }
+
+        tracing::info!(
+            "Anomaly scan ({}, threshold {}) checked {} records and flagged {}",
+            method,
+            threshold,
+            outcome.records_scanned,
+            outcome.flagged.len()
+        );
+        Ok(outcome)
+    }
+
+    /// Flags, newest first, optionally only those with `status`
+    pub fn list(
+        conn: &mut DatabaseConnection,
+        status: Option<AnomalyStatus>,
+        limit: i64,
+    ) -> Result<Vec<AnomalyFlag>> {
+        let mut query = anomaly_flags::table.into_boxed();
+        if let Some(status) = status {
+            query = query.filter(anomaly_flags::status.eq(status.to_string()));
+        }
+        query
+            .order((anomaly_flags::flagged_at.desc(), anomaly_flags::id.desc()))
+            .limit(limit)
+            .load::<AnomalyFlag>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Open flags of one account code or SKU, for showing next to its history
+    pub fn open_flags_for(conn: &mut DatabaseConnection, source: &str, subject: &str) -> Result<Vec<AnomalyFlag>> {
+        anomaly_flags::table
+            .filter(anomaly_flags::source.eq(source))
+            .filter(anomaly_flags::subject.eq(subject))
+            .filter(anomaly_flags::status.eq(AnomalyStatus::Open.to_string()))
+            .order(anomaly_flags::flagged_at.desc())
+            .load::<AnomalyFlag>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Close a flag as confirmed or dismissed
+    pub fn review(
+        conn: &mut DatabaseConnection,
+        flag_id: i32,
+        status: AnomalyStatus,
+        note: Option<&str>,
+        reviewed_by: Option<i32>,
+    ) -> Result<AnomalyFlag> {
+        if status == AnomalyStatus::Open {
+            return Err(crate::core::error::CLIERPError::Validation(
+                "A review must confirm or dismiss the flag".to_string(),
+            ));
+        }
+
+        let updated = diesel::update(anomaly_flags::table.find(flag_id))
+            .set((
+                anomaly_flags::status.eq(status.to_string()),
+                anomaly_flags::note.eq(note),
+                anomaly_flags::reviewed_by.eq(reviewed_by),
+                anomaly_flags::reviewed_at.eq(Some(Utc::now().naive_utc())),
+            ))
+            .execute(conn)?;
+        if updated == 0 {
+            return Err(crate::core::error::CLIERPError::NotFound(format!(
+                "Anomaly flag {} not found",
+                flag_id
+            )));
+        }
+
+        let flag = anomaly_flags::table.find(flag_id).first::<AnomalyFlag>(conn)?;
+        tracing::info!("Anomaly flag {} marked {}", flag.id, flag.status);
+        Ok(flag)
+    }
+
+    fn transaction_candidates(conn: &mut DatabaseConnection, since: Option<NaiveDate>) -> Result<Vec<Candidate>> {
+        let mut query = transactions::table
+            .inner_join(accounts::table)
+            .select((transactions::id, accounts::account_code, transactions::amount))
+            .into_boxed();
+        if let Some(since) = since {
+            query = query.filter(transactions::transaction_date.ge(since));
+        }
+
+        Ok(query
+            .load::<(i32, String, i32)>(conn)?
+            .into_iter()
+            .map(|(record_id, subject, amount)| Candidate {
+                record_id,
+                subject,
+                value: amount as f64,
+            })
+            .collect())
+    }
+
+    fn stock_candidates(conn: &mut DatabaseConnection, since: Option<NaiveDate>) -> Result<Vec<Candidate>> {
+        // Archive summaries net many movements and would always stand out
+        let mut query = stock_movements::table
+            .inner_join(products::table)
+            .filter(
+                stock_movements::reference_type
+                    .is_null()
+                    .or(stock_movements::reference_type.ne(ARCHIVE_SUMMARY_REFERENCE)),
+            )
+            .select((stock_movements::id, products::sku, stock_movements::quantity))
+            .into_boxed();
+        if let Some(since) = since {
+            query = query.filter(stock_movements::movement_date.ge(DisplayTimezone::current().day_start_utc(since)));
+        }
+
+        Ok(query
+            .load::<(i32, String, i32)>(conn)?
+            .into_iter()
+            .map(|(record_id, subject, quantity)| Candidate {
+                record_id,
+                subject,
+                value: quantity.abs() as f64,
+            })
+            .collect())
+    }
+
+    fn flag_outliers(
+        conn: &mut DatabaseConnection,
+        source: &str,
+        candidates: Vec<Candidate>,
+        method: AnomalyMethod,
+        threshold: f64,
+        outcome: &mut ScanOutcome,
+    ) -> Result<()> {
+        outcome.records_scanned += candidates.len();
+
+        let mut groups: BTreeMap<String, Vec<Candidate>> = BTreeMap::new();
+        for candidate in candidates {
+            groups.entry(candidate.subject.clone()).or_default().push(candidate);
+        }
+
+        let mut outliers = Vec::new();
+        for group in groups.values() {
+            let values: Vec<f64> = group.iter().map(|c| c.value).collect();
+            for (index, score) in find_outliers(&values, method, threshold) {
+                outliers.push((&group[index], score));
+            }
+        }
+        if outliers.is_empty() {
+            return Ok(());
+        }
+
+        let record_ids: Vec<i32> = outliers.iter().map(|(c, _)| c.record_id).collect();
+        let existing: HashSet<i32> = anomaly_flags::table
+            .filter(anomaly_flags::source.eq(source))
+            .filter(anomaly_flags::record_id.eq_any(&record_ids))
+            .select(anomaly_flags::record_id)
+            .load::<i32>(conn)?
+            .into_iter()
+            .collect();
+
+        let new_flags: Vec<NewAnomalyFlag> = outliers
+            .iter()
+            .filter(|(candidate, _)| !existing.contains(&candidate.record_id))
+            .map(|(candidate, score)| NewAnomalyFlag {
+                source: source.to_string(),
+                record_id: candidate.record_id,
+                subject: candidate.subject.clone(),
+                value: candidate.value,
+                score: *score,
+                method: method.to_string(),
+            })
+            .collect();
+        outcome.already_flagged += outliers.len() - new_flags.len();
+        if new_flags.is_empty() {
+            return Ok(());
+        }
+
+        diesel::insert_into(anomaly_flags::table)
+            .values(&new_flags)
+            .execute(conn)?;
+        let new_ids: Vec<i32> = new_flags.iter().map(|f| f.record_id).collect();
+        outcome.flagged.extend(
+            anomaly_flags::table
+                .filter(anomaly_flags::source.eq(source))
+                .filter(anomaly_flags::record_id.eq_any(&new_ids))
+                .order((anomaly_flags::subject.asc(), anomaly_flags::score.desc()))
+                .load::<AnomalyFlag>(conn)?,
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,279 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use diesel::dsl::{count_star, sum};
+use diesel::prelude::*;
+
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{
+    archive_runs, audit_logs, audit_logs_archive, stock_movements, stock_movements_archive,
+};
+use crate::database::{ArchiveRun, DatabaseConnection, NewArchiveRun};
+use crate::modules::inventory::ARCHIVE_SUMMARY_REFERENCE;
+use crate::modules::system::legal_hold::LegalHoldService;
+use crate::modules::reporting::summaries::SummaryService;
+use crate::utils::cache::QueryCache;
+use crate::utils::formatting::format_date;
+use crate::utils::timezone::DisplayTimezone;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Rows an archive run moves (or, on a dry run, would move) for one table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveOutcome {
+    pub table_name: String,
+    pub rows_archived: i64,
+    pub summary_rows: i64,
+}
+
+/// Moves old stock movements and audit logs into archive tables. The
+/// audit logs of records under legal hold stay where they are.
+///
+/// Archived stock movements are replaced by one summary movement per
+/// product carrying their net quantity, dated just before the cutoff, so
+/// stock derived from the movement history stays the same. Every run is
+/// recorded in `archive_runs`.
+pub struct ArchiveService;
+
+impl ArchiveService {
+    /// Archive everything recorded before the start of `before` (a business
+    /// date in the display timezone). A dry run only counts the rows.
+    pub fn run(
+        conn: &mut DatabaseConnection,
+        before: NaiveDate,
+        dry_run: bool,
+        run_by: Option<i32>,
+    ) -> Result<Vec<ArchiveOutcome>> {
+        let cutoff = DisplayTimezone::current().day_start_utc(before);
+
+        if dry_run {
+            let movements = stock_movements::table
+                .filter(stock_movements::movement_date.lt(cutoff))
+                .count()
+                .get_result::<i64>(conn)?;
+            let products = stock_movements::table
+                .filter(stock_movements::movement_date.lt(cutoff))
+                .select(stock_movements::product_id)
+                .distinct()
+                .load::<i32>(conn)?
+                .len() as i64;
+            let logs = audit_logs::table
+                .filter(audit_logs::changed_at.lt(cutoff))
+                .filter(audit_logs::id.ne_all(LegalHoldService::held_audit_log_ids(conn)?))
+                .count()
+                .get_result::<i64>(conn)?;
+            return Ok(vec![
+                ArchiveOutcome {
+                    table_name: "stock_movements".to_string(),
+                    rows_archived: movements,
+                    summary_rows: products,
+                },
+                ArchiveOutcome {
+                    table_name: "audit_logs".to_string(),
+                    rows_archived: logs,
+                    summary_rows: 0,
+                },
+            ]);
+        }
+
+        let outcomes = conn.transaction::<_, crate::core::error::CLIERPError, _>(|conn| {
+            let movements = Self::archive_stock_movements(conn, before, cutoff, run_by)?;
+            let logs = Self::archive_audit_logs(conn, cutoff)?;
+
+            for outcome in [&movements, &logs] {
+                diesel::insert_into(archive_runs::table)
+                    .values(&NewArchiveRun {
+                        table_name: outcome.table_name.clone(),
+                        cutoff,
+                        rows_archived: outcome.rows_archived as i32,
+                        summary_rows: outcome.summary_rows as i32,
+                        run_by,
+                    })
+                    .execute(conn)?;
+            }
+            Ok(vec![movements, logs])
+        })?;
+
+        QueryCache::current().invalidate(&["inventory"]);
+        tracing::info!(
+            "Archived {} stock movements and {} audit logs before {}",
+            outcomes[0].rows_archived,
+            outcomes[1].rows_archived,
+            cutoff
+        );
+        Ok(outcomes)
+    }
+
+    /// Past archive runs, newest first
+    pub fn history(conn: &mut DatabaseConnection, limit: i64) -> Result<Vec<ArchiveRun>> {
+        archive_runs::table
+            .order((archive_runs::run_at.desc(), archive_runs::id.desc()))
+            .limit(limit)
+            .load::<ArchiveRun>(conn)
+            .map_err(Into::into)
+    }
+
+    fn archive_stock_movements(
+        conn: &mut DatabaseConnection,
+        before: NaiveDate,
+        cutoff: NaiveDateTime,
+        run_by: Option<i32>,
+    ) -> Result<ArchiveOutcome> {
+        let archived = diesel::insert_into(stock_movements_archive::table)
+            .values(
+                stock_movements::table
+                    .filter(stock_movements::movement_date.lt(cutoff))
+                    .select((
+                        stock_movements::id,
+                        stock_movements::product_id,
+                        stock_movements::movement_type,
+                        stock_movements::quantity,
+                        stock_movements::unit_cost,
+                        stock_movements::reference_type,
+                        stock_movements::reference_id,
+                        stock_movements::notes,
+                        stock_movements::moved_by,
+                        stock_movements::movement_date,
+                    )),
+            )
+            .into_columns((
+                stock_movements_archive::id,
+                stock_movements_archive::product_id,
+                stock_movements_archive::movement_type,
+                stock_movements_archive::quantity,
+                stock_movements_archive::unit_cost,
+                stock_movements_archive::reference_type,
+                stock_movements_archive::reference_id,
+                stock_movements_archive::notes,
+                stock_movements_archive::moved_by,
+                stock_movements_archive::movement_date,
+            ))
+            .execute(conn)?;
+
+        let totals = stock_movements::table
+            .filter(stock_movements::movement_date.lt(cutoff))
+            .group_by(stock_movements::product_id)
+            .select((stock_movements::product_id, sum(stock_movements::quantity), count_star()))
+            .load::<(i32, Option<i64>, i64)>(conn)?;
+
+        diesel::delete(stock_movements::table.filter(stock_movements::movement_date.lt(cutoff)))
+            .execute(conn)?;
+
+        // Earlier summaries are archived and folded into the new one
+        let summary_date = cutoff - Duration::seconds(1);
+        for (product_id, quantity, movements) in &totals {
+            diesel::insert_into(stock_movements::table)
+                .values((
+                    stock_movements::product_id.eq(product_id),
+                    stock_movements::movement_type.eq("adjustment"),
+                    stock_movements::quantity.eq(quantity.unwrap_or(0) as i32),
+                    stock_movements::reference_type.eq(Some(ARCHIVE_SUMMARY_REFERENCE)),
+                    stock_movements::notes.eq(Some(format!(
+                        "Net of {} movement(s) before {} moved to the archive",
+                        movements,
+                        format_date(&before)
+                    ))),
+                    stock_movements::moved_by.eq(run_by),
+                    stock_movements::movement_date.eq(summary_date),
+                ))
+                .execute(conn)?;
+            SummaryService::refresh_product(conn, *product_id)?;
+        }
+
+        Ok(ArchiveOutcome {
+            table_name: "stock_movements".to_string(),
+            rows_archived: archived as i64,
+            summary_rows: totals.len() as i64,
+        })
+    }
+
+    /// Move audit logs before the cutoff, except the history of records
+    /// under legal hold
+    fn archive_audit_logs(conn: &mut DatabaseConnection, cutoff: NaiveDateTime) -> Result<ArchiveOutcome> {
+        let held = LegalHoldService::held_audit_log_ids(conn)?;
+        let archived = diesel::insert_into(audit_logs_archive::table)
+            .values(
+                audit_logs::table
+                    .filter(audit_logs::changed_at.lt(cutoff))
+                    .filter(audit_logs::id.ne_all(held.clone()))
+                    .select((
+                        audit_logs::id,
+                        audit_logs::user_id,
+                        audit_logs::table_name,
+                        audit_logs::record_id,
+                        audit_logs::action,
+                        audit_logs::old_values,
+                        audit_logs::new_values,
+                        audit_logs::changed_at,
+                    )),
+            )
+            .into_columns((
+                audit_logs_archive::id,
+                audit_logs_archive::user_id,
+                audit_logs_archive::table_name,
+                audit_logs_archive::record_id,
+                audit_logs_archive::action,
+                audit_logs_archive::old_values,
+                audit_logs_archive::new_values,
+                audit_logs_archive::changed_at,
+            ))
+            .execute(conn)?;
+
+        diesel::delete(
+            audit_logs::table
+                .filter(audit_logs::changed_at.lt(cutoff))
+                .filter(audit_logs::id.ne_all(held)),
+        )
+        .execute(conn)?;
+
+        Ok(ArchiveOutcome {
+            table_name: "audit_logs".to_string(),
+            rows_archived: archived as i64,
+            summary_rows: 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::StockMovement;
+    use crate::modules::inventory::ProductService;
+    use crate::testing::CompanyFixture;
+
+    #[test]
+    fn test_archive_keeps_net_quantity() {
+        let company = CompanyFixture::new().with_products(1).build().unwrap();
+        let product_id = company.products[0].id;
+        let service = ProductService::new();
+        service.update_stock(product_id, 7, "in", None, None, None, None, None).unwrap();
+        service.update_stock(product_id, -3, "out", None, None, None, None, None).unwrap();
+
+        let mut conn = company.connection().unwrap();
+        let old = NaiveDate::from_ymd_opt(2022, 6, 1).unwrap().and_hms_opt(12, 0, 0).unwrap();
+        diesel::update(stock_movements::table).set(stock_movements::movement_date.eq(old)).execute(&mut conn).unwrap();
+        service.update_stock(product_id, 2, "in", None, None, None, None, None).unwrap();
+        let moved = stock_movements::table
+            .filter(stock_movements::movement_date.lt(old + Duration::days(1)))
+            .load::<StockMovement>(&mut conn)
+            .unwrap();
+        let net: i32 = moved.iter().map(|m| m.quantity).sum();
+
+        let before = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+        let dry_run = ArchiveService::run(&mut conn, before, true, None).unwrap();
+        assert_eq!((dry_run[0].rows_archived, dry_run[0].summary_rows), (moved.len() as i64, 1));
+        assert_eq!(stock_movements_archive::table.count().get_result::<i64>(&mut conn).unwrap(), 0);
+
+        let outcomes = ArchiveService::run(&mut conn, before, false, Some(company.admin.id)).unwrap();
+        assert_eq!(outcomes[0].rows_archived, moved.len() as i64);
+        assert_eq!(stock_movements_archive::table.count().get_result::<i64>(&mut conn).unwrap(), moved.len() as i64);
+
+        let remaining = stock_movements::table
+            .order(stock_movements::movement_date.asc())
+            .load::<StockMovement>(&mut conn)
+            .unwrap();
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(remaining[0].reference_type.as_deref(), Some(ARCHIVE_SUMMARY_REFERENCE));
+        assert_eq!(remaining[0].quantity, net);
+        assert_eq!(ArchiveService::history(&mut conn, 10).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,260 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{audit_logs, users};
+use crate::database::{AuditAction, AuditLog, DatabaseConnection, EventAction, NewAuditLog};
+use crate::modules::system::events::EventService;
+use crate::outln;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+static AUDIT_ACTOR: OnceCell<i32> = OnceCell::new();
+
+/// Fields that change on every write and carry no information for history
+const IGNORED_FIELDS: &[&str] = &["created_at", "updated_at"];
+
+/// One field that changed in an audited update
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old_value: Value,
+    pub new_value: Value,
+}
+
+/// An audit log entry with the acting user's name and its field changes
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryEntry {
+    pub changed_at: NaiveDateTime,
+    pub action: String,
+    pub username: Option<String>,
+    pub changes: Vec<FieldChange>,
+}
+
+pub struct AuditService;
+
+impl AuditService {
+    /// Record which user is making changes for the rest of the process
+    pub fn set_actor(user_id: i32) {
+        let _ = AUDIT_ACTOR.set(user_id);
+    }
+
+    pub fn actor() -> Option<i32> {
+        AUDIT_ACTOR.get().copied()
+    }
+
+    /// Log the fields that differ between two versions of a record. Nothing
+    /// is written when only timestamps changed.
+    pub fn record_update<T: Serialize>(
+        conn: &mut DatabaseConnection,
+        table_name: &str,
+        record_id: i32,
+        before: &T,
+        after: &T,
+    ) -> Result<()> {
+        let before = serde_json::to_value(before)?;
+        let after = serde_json::to_value(after)?;
+        let (old_values, new_values) = diff_values(&before, &after);
+        if new_values.is_empty() {
+            return Ok(());
+        }
+
+        let entry = NewAuditLog {
+            user_id: Self::actor(),
+            table_name: table_name.to_string(),
+            record_id,
+            action: AuditAction::Update.to_string(),
+            old_values: Some(Value::Object(old_values).to_string()),
+            new_values: Some(Value::Object(new_values).to_string()),
+        };
+        diesel::insert_into(audit_logs::table)
+            .values(&entry)
+            .execute(conn)?;
+        EventService::record(conn, table_name, record_id, EventAction::Updated, &after)?;
+
+        Ok(())
+    }
+
+    /// Chronological change history of a record, oldest first
+    pub fn history(
+        conn: &mut DatabaseConnection,
+        table_name: &str,
+        record_id: i32,
+    ) -> Result<Vec<HistoryEntry>> {
+        let entries = audit_logs::table
+            .left_join(users::table)
+            .filter(audit_logs::table_name.eq(table_name))
+            .filter(audit_logs::record_id.eq(record_id))
+            .order((audit_logs::changed_at.asc(), audit_logs::id.asc()))
+            .select((AuditLog::as_select(), users::username.nullable()))
+            .load::<(AuditLog, Option<String>)>(conn)?;
+
+        Ok(entries
+            .into_iter()
+            .map(|(log, username)| HistoryEntry {
+                changed_at: log.changed_at,
+                changes: field_changes(log.old_values.as_deref(), log.new_values.as_deref()),
+                action: log.action,
+                username,
+            })
+            .collect())
+    }
+}
+
+/// Split two JSON objects into the old and new values of the fields that differ
+pub fn diff_values(before: &Value, after: &Value) -> (Map<String, Value>, Map<String, Value>) {
+    let mut old_values = Map::new();
+    let mut new_values = Map::new();
+    let (Some(before), Some(after)) = (before.as_object(), after.as_object()) else {
+        return (old_values, new_values);
+    };
+
+    for (field, new_value) in after {
+        if IGNORED_FIELDS.contains(&field.as_str()) {
+            continue;
+        }
+        let old_value = before.get(field).cloned().unwrap_or(Value::Null);
+        if &old_value != new_value {
+            old_values.insert(field.clone(), old_value);
+            new_values.insert(field.clone(), new_value.clone());
+        }
+    }
+
+    (old_values, new_values)
+}
+
+/// Pair up stored old and new JSON values into per-field changes
+pub fn field_changes(old_values: Option<&str>, new_values: Option<&str>) -> Vec<FieldChange> {
+    let parse = |json: Option<&str>| {
+        json.and_then(|s| serde_json::from_str::<Map<String, Value>>(s).ok())
+            .unwrap_or_default()
+    };
+    let old_values = parse(old_values);
+    let new_values = parse(new_values);
+
+    let mut fields: Vec<&String> = old_values.keys().chain(new_values.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .map(|field| FieldChange {
+            field: field.clone(),
+            old_value: old_values.get(field).cloned().unwrap_or(Value::Null),
+            new_value: new_values.get(field).cloned().unwrap_or(Value::Null),
+        })
+        .collect()
+}
+
+/// Fields that differ between two versions of a record, timestamps aside
+pub fn changed_fields<T: Serialize>(before: &T, after: &T) -> Result<Vec<FieldChange>> {
+    let (old_values, new_values) = diff_values(&serde_json::to_value(before)?, &serde_json::to_value(after)?);
+    Ok(new_values
+        .into_iter()
+        .map(|(field, new_value)| FieldChange {
+            old_value: old_values.get(&field).cloned().unwrap_or(Value::Null),
+            field,
+            new_value,
+        })
+        .collect())
+}
+
+/// Render what an update changed as `field: old → new` lines
+pub fn print_changes(changes: &[FieldChange]) {
+    if changes.is_empty() {
+        println!("No changes; nothing was written.");
+        return;
+    }
+
+    println!("Changes:");
+    for change in changes {
+        outln!(
+            "  {}: {} → {}",
+            change.field,
+            display_value(&change.old_value),
+            display_value(&change.new_value)
+        );
+    }
+}
+
+/// Render a change history as `when  who  field: old → new` lines
+pub fn print_history(entries: &[HistoryEntry]) {
+    println!("\nChange History:");
+    if entries.is_empty() {
+        println!("  No recorded changes.");
+        return;
+    }
+
+    for entry in entries {
+        println!(
+            "  {}  {} ({})",
+            crate::utils::formatting::format_datetime(&entry.changed_at),
+            entry.username.as_deref().unwrap_or("system"),
+            entry.action.to_lowercase()
+        );
+        for change in &entry.changes {
+            outln!(
+                "    {}: {} → {}",
+                change.field,
+                display_value(&change.old_value),
+                display_value(&change.new_value)
+            );
+        }
+    }
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_only_includes_changed_fields() {
+        let before = json!({"id": 1, "price": 1000, "name": "Pen", "updated_at": "2025-01-01T00:00:00"});
+        let after = json!({"id": 1, "price": 1200, "name": "Pen", "updated_at": "2025-01-02T00:00:00"});
+        let (old_values, new_values) = diff_values(&before, &after);
+
+        assert_eq!(old_values.len(), 1);
+        assert_eq!(old_values["price"], json!(1000));
+        assert_eq!(new_values["price"], json!(1200));
+    }
+
+    #[test]
+    fn test_field_changes_round_trip() {
+        let changes = field_changes(
+            Some(r#"{"stage":"proposal","probability":50}"#),
+            Some(r#"{"stage":"negotiation","probability":75}"#),
+        );
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].field, "probability");
+        assert_eq!(changes[1].old_value, json!("proposal"));
+        assert_eq!(changes[1].new_value, json!("negotiation"));
+        assert!(field_changes(None, Some("not json")).is_empty());
+    }
+
+    #[test]
+    fn test_changed_fields() {
+        let before = json!({"name": "Pen", "price": 1000, "barcode": null, "updated_at": "2025-01-01T00:00:00"});
+        let after = json!({"name": "Pen", "price": 1200, "barcode": "880123", "updated_at": "2025-01-02T00:00:00"});
+        let changes = changed_fields(&before, &after).unwrap();
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].field, "barcode");
+        assert_eq!(changes[0].old_value, Value::Null);
+        assert_eq!(changes[1].new_value, json!(1200));
+        assert!(changed_fields(&before, &before).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{products, stock_movements};
+use crate::database::{DatabaseConnection, NewStockMovement};
+use crate::modules::inventory::ProductService;
+use crate::modules::reporting::{InventoryReportsGenerator, ReportConfig, ReportFormat, ReportGenerator};
+use crate::utils::pagination::PaginationParams;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Timings of one benchmarked operation, in milliseconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchResult {
+    pub name: String,
+    pub iterations: usize,
+    pub total_ms: f64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    pub ops_per_sec: f64,
+}
+
+/// A benchmark run, written as JSON so runs can be diffed over time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchReport {
+    pub version: String,
+    pub run_at: NaiveDateTime,
+    /// Row counts the timings depend on
+    pub products: i64,
+    pub stock_movements: i64,
+    pub results: Vec<BenchResult>,
+    /// Benchmarks that could not run, with the reason
+    pub skipped: Vec<String>,
+}
+
+/// Times the key paths against the current database.
+///
+/// Stock movement inserts run inside a transaction that is rolled back, so
+/// a benchmark leaves the data as it was. The product list and report go
+/// through the same services as the commands, bypassing the query cache.
+pub struct BenchService;
+
+impl BenchService {
+    pub fn run(conn: &mut DatabaseConnection, iterations: usize) -> Result<BenchReport> {
+        if iterations == 0 {
+            return Err(CLIERPError::ValidationError("Iterations must be at least 1".to_string()));
+        }
+        let product_count = products::table.count().get_result::<i64>(conn)?;
+        let movement_count = stock_movements::table.count().get_result::<i64>(conn)?;
+        let mut results = Vec::new();
+        let mut skipped = Vec::new();
+
+        let product_id = products::table
+            .select(products::id)
+            .order(products::id.asc())
+            .first::<i32>(conn)
+            .optional()?;
+        match product_id {
+            Some(product_id) => results.push(Self::stock_movement_insert(conn, product_id, iterations)?),
+            None => skipped.push("stock_movement_insert: no products to move stock for".to_string()),
+        }
+
+        let service = ProductService::new();
+        let pagination = PaginationParams::new(1, 20);
+        results.push(measure("product_list_query", iterations, || {
+            service.list_products(&pagination, None, true, None, false).map(|_| ())
+        })?);
+
+        let generator = InventoryReportsGenerator;
+        results.push(measure("stock_status_report", iterations, || {
+            generator
+                .generate_report(ReportConfig {
+                    title: "stock_status".to_string(),
+                    description: None,
+                    date_range: None,
+                    filters: HashMap::new(),
+                    format: ReportFormat::Json,
+                    include_charts: false,
+                    include_summary: true,
+                })
+                .map(|_| ())
+        })?);
+
+        Ok(BenchReport {
+            version: crate::VERSION.to_string(),
+            run_at: Utc::now().naive_utc(),
+            products: product_count,
+            stock_movements: movement_count,
+            results,
+            skipped,
+        })
+    }
+
+    fn stock_movement_insert(conn: &mut DatabaseConnection, product_id: i32, iterations: usize) -> Result<BenchResult> {
+        let mut result = None;
+        let outcome = conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            let timed = measure("stock_movement_insert", iterations, || {
+                diesel::insert_into(stock_movements::table)
+                    .values(&NewStockMovement {
+                        product_id,
+                        movement_type: "adjustment".to_string(),
+                        quantity: 0,
+                        unit_cost: None,
+                        reference_type: Some("benchmark".to_string()),
+                        reference_id: None,
+                        notes: None,
+                        moved_by: None,
+                    })
+                    .execute(conn)?;
+                Ok(())
+            });
+            result = Some(timed);
+            Err(diesel::result::Error::RollbackTransaction)
+        });
+        match outcome {
+            Err(diesel::result::Error::RollbackTransaction) | Ok(()) => {}
+            Err(e) => return Err(e.into()),
+        }
+        result.expect("benchmark ran inside the transaction")
+    }
+}
+
+/// Run `operation` `iterations` times and summarize the timings
+fn measure(name: &str, iterations: usize, mut operation: impl FnMut() -> Result<()>) -> Result<BenchResult> {
+    let mut samples = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let started = Instant::now();
+        operation()?;
+        samples.push(started.elapsed().as_secs_f64() * 1000.0);
+    }
+    Ok(summarize(name, samples))
+}
+
+/// Mean, percentiles and throughput of timings in milliseconds
+pub fn summarize(name: &str, mut samples: Vec<f64>) -> BenchResult {
+    samples.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let iterations = samples.len();
+    let total_ms: f64 = samples.iter().sum();
+    let percentile = |p: f64| {
+        if samples.is_empty() {
+            return 0.0;
+        }
+        let rank = ((p * iterations as f64).ceil() as usize).clamp(1, iterations);
+        samples[rank - 1]
+    };
+
+    BenchResult {
+        name: name.to_string(),
+        iterations,
+        total_ms,
+        mean_ms: if iterations == 0 { 0.0 } else { total_ms / iterations as f64 },
+        p50_ms: percentile(0.50),
+        p95_ms: percentile(0.95),
+        max_ms: samples.last().copied().unwrap_or(0.0),
+        ops_per_sec: if total_ms > 0.0 { iterations as f64 * 1000.0 / total_ms } else { 0.0 },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize() {
+        let result = summarize("op", (1..=20).rev().map(|n| n as f64).collect());
+        assert_eq!(result.iterations, 20);
+        assert_eq!(result.total_ms, 210.0);
+        assert_eq!(result.mean_ms, 10.5);
+        assert_eq!(result.p50_ms, 10.0);
+        assert_eq!(result.p95_ms, 19.0);
+        assert_eq!(result.max_ms, 20.0);
+        assert!((result.ops_per_sec - 20.0 * 1000.0 / 210.0).abs() < 1e-9);
+
+        let empty = summarize("op", Vec::new());
+        assert_eq!(empty.mean_ms, 0.0);
+        assert_eq!(empty.p95_ms, 0.0);
+    }
+}
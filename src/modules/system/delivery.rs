@@ -0,0 +1,357 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::prelude::*;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{delivery_destinations, delivery_transfers, products};
+use crate::database::{DeliveryDestination, DeliveryTransfer, NewDeliveryDestination, NewDeliveryTransfer, Product};
+use crate::modules::inventory::translation::{normalize_locale, TranslationService};
+use crate::modules::system::export_templates::{ExportEntity, ExportTemplateService};
+use crate::utils::export::{escape_csv_value, CsvSerializable, ExportService};
+use crate::utils::formatting::format_plain_amount;
+use crate::utils::transfer::{RemoteTarget, TransferProtocol};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+pub const STOCK_LEVEL_CSV_HEADERS: [&str; 5] = ["SKU", "Name", "Stock", "Unit", "Barcode"];
+pub const PRICE_LIST_CSV_HEADERS: [&str; 5] = ["SKU", "Name", "Price", "Unit", "Barcode"];
+
+/// File a destination receives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DeliveryExport {
+    /// Current stock of every active product
+    StockLevels,
+    /// Selling price of every active product
+    PriceList,
+}
+
+impl DeliveryExport {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeliveryExport::StockLevels => "stock_levels",
+            DeliveryExport::PriceList => "price_list",
+        }
+    }
+
+    pub fn entity(&self) -> ExportEntity {
+        match self {
+            DeliveryExport::StockLevels => ExportEntity::StockLevels,
+            DeliveryExport::PriceList => ExportEntity::PriceList,
+        }
+    }
+}
+
+impl std::fmt::Display for DeliveryExport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl std::str::FromStr for DeliveryExport {
+    type Err = CLIERPError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "stock_levels" => Ok(DeliveryExport::StockLevels),
+            "price_list" => Ok(DeliveryExport::PriceList),
+            _ => Err(CLIERPError::Validation(format!("Unknown delivery export '{}'", s))),
+        }
+    }
+}
+
+/// One product line of a stock level or price list file
+struct ProductLine {
+    sku: String,
+    name: String,
+    /// Stock or price, per the export
+    value: String,
+    unit: String,
+    barcode: Option<String>,
+}
+
+impl CsvSerializable for ProductLine {
+    fn to_csv_row(&self) -> Vec<String> {
+        vec![
+            escape_csv_value(&self.sku),
+            escape_csv_value(&self.name),
+            self.value.clone(),
+            escape_csv_value(&self.unit),
+            escape_csv_value(self.barcode.as_deref().unwrap_or_default()),
+        ]
+    }
+}
+
+/// Result of one destination in a delivery run
+#[derive(Debug, Clone)]
+pub struct DeliveryOutcome {
+    pub destination: String,
+    pub file_name: String,
+    pub bytes: usize,
+    /// Error of a failed upload
+    pub error: Option<String>,
+}
+
+/// Uploads stock level and price list files to trading partners' FTP and
+/// SFTP servers.
+///
+/// Uploads happen when `system delivery run` finds a destination due, so a
+/// cron job running it every few minutes makes the schedule. Every attempt
+/// is logged in `delivery_transfers`; a failed one is logged as an alert
+/// and retried on the next run.
+pub struct DeliveryService;
+
+impl DeliveryService {
+    #[allow(clippy::too_many_arguments)]
+    pub fn add(
+        conn: &mut SqliteConnection,
+        name: &str,
+        protocol: TransferProtocol,
+        host: &str,
+        port: Option<u16>,
+        username: &str,
+        password_env: Option<&str>,
+        remote_dir: Option<&str>,
+        export: DeliveryExport,
+        template: Option<&str>,
+        locale: Option<&str>,
+        interval_minutes: i32,
+        created_by: Option<i32>,
+    ) -> Result<DeliveryDestination> {
+        let name = name.trim();
+        if name.is_empty() || host.trim().is_empty() || username.trim().is_empty() {
+            return Err(CLIERPError::Validation("Name, host and user are required".to_string()));
+        }
+        if interval_minutes < 1 {
+            return Err(CLIERPError::Validation("Interval must be at least one minute".to_string()));
+        }
+        if let Some(template) = template {
+            ExportTemplateService::mapping(conn, template, export.entity())?;
+        }
+        let locale = locale.map(normalize_locale).transpose()?;
+        let exists = delivery_destinations::table
+            .filter(delivery_destinations::name.eq(name))
+            .count()
+            .get_result::<i64>(conn)?
+            > 0;
+        if exists {
+            return Err(CLIERPError::AlreadyExists(format!("Delivery destination '{}'", name)));
+        }
+
+        diesel::insert_into(delivery_destinations::table)
+            .values(&NewDeliveryDestination {
+                name: name.to_string(),
+                protocol: protocol.to_string(),
+                host: host.trim().to_string(),
+                port: port.unwrap_or_else(|| protocol.default_port()) as i32,
+                username: username.trim().to_string(),
+                password_env: password_env.map(str::to_string),
+                remote_dir: remote_dir.map(str::to_string),
+                export: export.name().to_string(),
+                template: template.map(str::to_string),
+                locale,
+                interval_minutes,
+                created_by,
+            })
+            .execute(conn)?;
+
+        tracing::info!("Added delivery destination {} ({} to {})", name, export, host);
+        Self::get(conn, name)
+    }
+
+    pub fn get(conn: &mut SqliteConnection, name: &str) -> Result<DeliveryDestination> {
+        delivery_destinations::table
+            .filter(delivery_destinations::name.eq(name.trim()))
+            .first::<DeliveryDestination>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Delivery destination '{}' not found", name)))
+    }
+
+    pub fn list(conn: &mut SqliteConnection) -> Result<Vec<DeliveryDestination>> {
+        Ok(delivery_destinations::table
+            .order(delivery_destinations::name.asc())
+            .load::<DeliveryDestination>(conn)?)
+    }
+
+    /// Delete the destination and its transfer log, returning whether it existed
+    pub fn remove(conn: &mut SqliteConnection, name: &str) -> Result<bool> {
+        let destination = match Self::get(conn, name) {
+            Ok(destination) => destination,
+            Err(CLIERPError::NotFound(_)) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        conn.transaction::<_, CLIERPError, _>(|conn| {
+            diesel::delete(delivery_transfers::table.filter(delivery_transfers::destination_id.eq(destination.id)))
+                .execute(conn)?;
+            diesel::delete(delivery_destinations::table.find(destination.id)).execute(conn)?;
+            Ok(())
+        })?;
+        Ok(true)
+    }
+
+    pub fn set_active(conn: &mut SqliteConnection, name: &str, active: bool) -> Result<()> {
+        let destination = Self::get(conn, name)?;
+        diesel::update(delivery_destinations::table.find(destination.id))
+            .set((
+                delivery_destinations::is_active.eq(active),
+                delivery_destinations::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Whether the destination should be uploaded to at `now`
+    pub fn is_due(destination: &DeliveryDestination, now: NaiveDateTime) -> bool {
+        destination.is_active
+            && destination
+                .last_run_at
+                .is_none_or(|last| last + Duration::minutes(destination.interval_minutes as i64) <= now)
+    }
+
+    /// Upload to every due destination, or to `only` whether due or not
+    pub fn run(conn: &mut SqliteConnection, only: Option<&str>) -> Result<Vec<DeliveryOutcome>> {
+        let now = Utc::now().naive_utc();
+        let destinations = match only {
+            Some(name) => vec![Self::get(conn, name)?],
+            None => Self::list(conn)?
+                .into_iter()
+                .filter(|destination| Self::is_due(destination, now))
+                .collect(),
+        };
+
+        let mut outcomes = Vec::with_capacity(destinations.len());
+        for destination in destinations {
+            outcomes.push(Self::deliver(conn, &destination)?);
+        }
+        Ok(outcomes)
+    }
+
+    /// Build the destination's file, upload it and log the attempt
+    fn deliver(conn: &mut SqliteConnection, destination: &DeliveryDestination) -> Result<DeliveryOutcome> {
+        let file_name = format!(
+            "{}_{}.csv",
+            destination.export,
+            Utc::now().format("%Y%m%d_%H%M%S")
+        );
+        let (bytes, result) = match Self::build_file(conn, destination) {
+            Ok(contents) => (contents.len(), Self::target(destination).and_then(|t| t.upload(&file_name, &contents))),
+            Err(e) => (0, Err(e)),
+        };
+        let error = result.err().map(|e| e.to_string());
+
+        let status = if error.is_some() { "failed" } else { "ok" };
+        let failures = if error.is_some() { destination.consecutive_failures + 1 } else { 0 };
+        conn.transaction::<_, CLIERPError, _>(|conn| {
+            diesel::insert_into(delivery_transfers::table)
+                .values(&NewDeliveryTransfer {
+                    destination_id: destination.id,
+                    file_name: file_name.clone(),
+                    bytes: bytes as i32,
+                    status: status.to_string(),
+                    message: error.clone(),
+                })
+                .execute(conn)?;
+            diesel::update(delivery_destinations::table.find(destination.id))
+                .set((
+                    delivery_destinations::last_run_at.eq(Some(Utc::now().naive_utc())),
+                    delivery_destinations::last_status.eq(Some(status)),
+                    delivery_destinations::consecutive_failures.eq(failures),
+                ))
+                .execute(conn)?;
+            Ok(())
+        })?;
+
+        match &error {
+            Some(e) => tracing::error!(
+                "Delivery alert: upload to {} failed ({} in a row): {}",
+                destination.name,
+                failures,
+                e
+            ),
+            None => tracing::info!("Delivered {} ({} bytes) to {}", file_name, bytes, destination.name),
+        }
+        Ok(DeliveryOutcome {
+            destination: destination.name.clone(),
+            file_name,
+            bytes,
+            error,
+        })
+    }
+
+    fn target(destination: &DeliveryDestination) -> Result<RemoteTarget> {
+        let password = match &destination.password_env {
+            Some(var) => Some(std::env::var(var).map_err(|_| {
+                CLIERPError::Validation(format!("Environment variable {} with the password is not set", var))
+            })?),
+            None => None,
+        };
+        Ok(RemoteTarget {
+            protocol: destination.protocol.parse()?,
+            host: destination.host.clone(),
+            port: destination.port as u16,
+            username: destination.username.clone(),
+            password,
+            remote_dir: destination.remote_dir.clone(),
+        })
+    }
+
+    /// The CSV the destination receives, in its template's layout if it has one
+    pub fn build_file(conn: &mut SqliteConnection, destination: &DeliveryDestination) -> Result<Vec<u8>> {
+        let export: DeliveryExport = destination.export.parse()?;
+        let mut rows = products::table
+            .filter(products::is_active.eq(true))
+            .order(products::sku.asc())
+            .load::<Product>(conn)?;
+        if let Some(locale) = &destination.locale {
+            TranslationService::localize_products(conn, &mut rows, locale)?;
+        }
+        let lines: Vec<ProductLine> = rows
+            .into_iter()
+            .map(|product| ProductLine {
+                value: match export {
+                    DeliveryExport::StockLevels => product.current_stock.to_string(),
+                    DeliveryExport::PriceList => format_plain_amount(product.price),
+                },
+                sku: product.sku,
+                name: product.name,
+                unit: product.unit,
+                barcode: product.barcode,
+            })
+            .collect();
+
+        let headers = export.entity().headers();
+        let path = std::env::temp_dir().join(format!("clierp-delivery-{}-{}.csv", std::process::id(), destination.id));
+        let path_str = path.to_string_lossy().to_string();
+        let service = ExportService::new();
+        let written = match &destination.template {
+            Some(template) => {
+                let mapping = ExportTemplateService::mapping(conn, template, export.entity())?;
+                service.export_to_csv_mapped(&lines, headers, &mapping, &path_str)
+            }
+            None => service.export_to_csv(&lines, headers, &path_str),
+        };
+        let contents = written.and_then(|_| {
+            std::fs::read(&path).map_err(|e| CLIERPError::IoError(format!("Failed to read {}: {}", path_str, e)))
+        });
+        let _ = std::fs::remove_file(&path);
+        contents
+    }
+
+    /// Latest upload attempts, newest first
+    pub fn transfers(
+        conn: &mut SqliteConnection,
+        destination: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<(DeliveryTransfer, String)>> {
+        let mut query = delivery_transfers::table
+            .inner_join(delivery_destinations::table)
+            .select((DeliveryTransfer::as_select(), delivery_destinations::name))
+            .order(delivery_transfers::id.desc())
+            .limit(limit)
+            .into_boxed();
+        if let Some(name) = destination {
+            query = query.filter(delivery_destinations::name.eq(name.trim().to_string()));
+        }
+        Ok(query.load(conn)?)
+    }
+}
@@ -0,0 +1,301 @@
+use std::fs;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration as StdDuration;
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::prelude::*;
+
+use crate::core::config::CLIERPConfig;
+use crate::database::connection::DatabaseManager;
+use crate::database::migrations;
+use crate::database::schema::{audit_logs, events};
+use crate::modules::inventory::ATTACHMENTS_DIR;
+use crate::modules::system::delivery::DeliveryService;
+use crate::outln;
+use crate::utils::ldap::LdapDirectory;
+
+/// How far the clock may lag the newest recorded change before it is flagged
+const CLOCK_TOLERANCE_MINUTES: i64 = 5;
+
+/// How long a connection to an outbound destination may take
+const CONNECT_TIMEOUT: StdDuration = StdDuration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Failed,
+}
+
+/// Outcome of one doctor check, with what to do about a problem
+#[derive(Debug, Clone)]
+pub struct DoctorCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub fix: Option<String>,
+}
+
+impl DoctorCheck {
+    fn ok(name: &str, detail: impl Into<String>) -> Self {
+        Self { name: name.to_string(), status: CheckStatus::Ok, detail: detail.into(), fix: None }
+    }
+
+    fn warning(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { name: name.to_string(), status: CheckStatus::Warning, detail: detail.into(), fix: Some(fix.into()) }
+    }
+
+    fn failed(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { name: name.to_string(), status: CheckStatus::Failed, detail: detail.into(), fix: Some(fix.into()) }
+    }
+}
+
+/// Checks an installation for the problems that make commands fail:
+/// configuration, the database and its schema, directories CLIERP writes
+/// to, outbound connections and the system clock.
+///
+/// The doctor runs without the usual startup, which would already fail on
+/// most of these, and changes nothing: it never creates the database, runs
+/// migrations or creates directories.
+pub struct DoctorService;
+
+impl DoctorService {
+    pub fn run() -> Vec<DoctorCheck> {
+        let mut checks = Vec::new();
+        let config = match CLIERPConfig::load() {
+            Ok(config) => config,
+            Err(e) => {
+                checks.push(DoctorCheck::failed(
+                    "configuration",
+                    format!("cannot be loaded: {}", e),
+                    "Fix the setting named in the error in config/<RUN_MODE>.toml, config/local.toml or its CLIERP_* environment variable",
+                ));
+                return checks;
+            }
+        };
+        checks.push(Self::configuration(&config));
+        checks.extend(Self::database(&config));
+        checks.extend(Self::directories(&config));
+        checks.extend(Self::connectivity(&config));
+        checks
+    }
+
+    fn configuration(config: &CLIERPConfig) -> DoctorCheck {
+        if let Err(e) = config.validate() {
+            return DoctorCheck::failed("configuration", e.to_string(), "Correct the setting named in the error");
+        }
+        if config.auth.jwt_secret == "your-secret-key-change-this" || config.auth.jwt_secret.len() < 32 {
+            return DoctorCheck::warning(
+                "configuration",
+                "valid, but the JWT secret is the default or shorter than 32 characters",
+                "Set CLIERP_AUTH__JWT_SECRET to a long random value; existing logins and links stop working",
+            );
+        }
+        DoctorCheck::ok("configuration", "valid")
+    }
+
+    fn database(config: &CLIERPConfig) -> Vec<DoctorCheck> {
+        let path = config.database.url.trim_start_matches("sqlite:");
+        if path != ":memory:" && !Path::new(path).exists() {
+            return vec![DoctorCheck::failed(
+                "database",
+                format!("{} does not exist", path),
+                "Run 'clierp system init', or point database.url at the existing database",
+            )];
+        }
+        let mut conn = match DatabaseManager::establish_connection(&config.database.url, config) {
+            Ok(conn) => conn,
+            Err(e) => {
+                return vec![DoctorCheck::failed(
+                    "database",
+                    format!("cannot be opened: {}", e),
+                    "Check database.url and that the file is readable and writable by this user",
+                )]
+            }
+        };
+        if let Err(e) = diesel::sql_query("SELECT 1").execute(&mut conn) {
+            return vec![DoctorCheck::failed(
+                "database",
+                format!("does not answer queries: {}", e),
+                "The file may be corrupt or not a SQLite database; restore it from a backup",
+            )];
+        }
+        let mut checks = vec![DoctorCheck::ok("database", format!("{} is reachable", path))];
+
+        checks.push(match migrations::check_schema_version(&mut conn) {
+            Err(e) => DoctorCheck::failed("migrations", e.to_string(), "Upgrade clierp to the release that migrated this database"),
+            Ok(()) => match migrations::migration_status(&mut conn) {
+                Err(e) => DoctorCheck::failed("migrations", e.to_string(), "Run 'clierp system migrate --status' for details"),
+                Ok(status) => {
+                    let pending = status.iter().filter(|m| !m.applied).count();
+                    if pending == 0 {
+                        DoctorCheck::ok("migrations", format!("all {} applied", status.len()))
+                    } else {
+                        DoctorCheck::warning(
+                            "migrations",
+                            format!("{} of {} pending", pending, status.len()),
+                            "Back up the database, then run 'clierp system migrate'",
+                        )
+                    }
+                }
+            },
+        });
+
+        // Tables may be missing on a database that was never initialized
+        let latest_audit = audit_logs::table
+            .select(diesel::dsl::max(audit_logs::changed_at))
+            .first::<Option<NaiveDateTime>>(&mut conn)
+            .ok()
+            .flatten();
+        let latest_event = events::table
+            .select(diesel::dsl::max(events::occurred_at))
+            .first::<Option<NaiveDateTime>>(&mut conn)
+            .ok()
+            .flatten();
+        checks.push(clock_check(Utc::now().naive_utc(), latest_audit.max(latest_event)));
+
+        match DeliveryService::list(&mut conn) {
+            Ok(destinations) => {
+                for destination in destinations.iter().filter(|d| d.is_active) {
+                    let name = format!("delivery destination {}", destination.name);
+                    checks.push(connect_check(&name, &destination.host, destination.port as u16));
+                }
+            }
+            Err(e) => checks.push(DoctorCheck::warning(
+                "delivery destinations",
+                format!("cannot be listed: {}", e),
+                "Run 'clierp system migrate' so the delivery tables exist",
+            )),
+        }
+        checks
+    }
+
+    fn directories(config: &CLIERPConfig) -> Vec<DoctorCheck> {
+        let mut directories = vec![("attachments directory", PathBuf::from(ATTACHMENTS_DIR))];
+        if let Some(file) = &config.logging.file {
+            let parent = Path::new(file).parent().filter(|p| !p.as_os_str().is_empty());
+            directories.push(("log directory", parent.map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from("."))));
+        }
+        if let Some(dir) = &config.cache.dir {
+            directories.push(("cache directory", PathBuf::from(dir)));
+        }
+        directories.into_iter().map(|(name, dir)| writable_check(name, &dir)).collect()
+    }
+
+    fn connectivity(config: &CLIERPConfig) -> Vec<DoctorCheck> {
+        let mut checks = Vec::new();
+        if let Some(maildir) = &config.dropbox.maildir {
+            let new = Path::new(maildir).join("new");
+            checks.push(match fs::read_dir(&new) {
+                Ok(_) => DoctorCheck::ok("dropbox maildir", format!("{} is readable", new.display())),
+                Err(e) => DoctorCheck::failed(
+                    "dropbox maildir",
+                    format!("{}: {}", new.display(), e),
+                    "Point dropbox.maildir at the Maildir the dropbox address is delivered to",
+                ),
+            });
+        }
+        if config.ldap.enabled {
+            checks.push(match LdapDirectory::new(&config.ldap).ping() {
+                Ok(()) => DoctorCheck::ok("directory", format!("{} answers", config.ldap.url.clone().unwrap_or_default())),
+                Err(e) => DoctorCheck::warning(
+                    "directory",
+                    e.to_string(),
+                    "Check ldap.url and that ldapsearch is installed; until then only local users can log in",
+                ),
+            });
+        }
+        checks
+    }
+}
+
+/// Whether the clock is plausible: not behind the newest change recorded
+pub fn clock_check(now: NaiveDateTime, latest_recorded: Option<NaiveDateTime>) -> DoctorCheck {
+    match latest_recorded {
+        Some(latest) if latest - now > Duration::minutes(CLOCK_TOLERANCE_MINUTES) => DoctorCheck::failed(
+            "clock",
+            format!(
+                "UTC time is {} but a change was recorded at {}",
+                now.format("%Y-%m-%d %H:%M:%S"),
+                latest.format("%Y-%m-%d %H:%M:%S")
+            ),
+            "Set the system clock, e.g. enable NTP; records written now would sort before earlier ones",
+        ),
+        _ => DoctorCheck::ok("clock", format!("UTC time is {}", now.format("%Y-%m-%d %H:%M:%S"))),
+    }
+}
+
+/// Whether files can be created in `dir`, or in the directory it would be created in
+fn writable_check(name: &str, dir: &Path) -> DoctorCheck {
+    let Some(existing) = dir.ancestors().find(|p| p.as_os_str().is_empty() || p.exists()) else {
+        return DoctorCheck::failed(name, format!("{} has no existing parent", dir.display()), "Create the directory");
+    };
+    let existing = if existing.as_os_str().is_empty() { Path::new(".") } else { existing };
+    let probe = existing.join(format!(".clierp-doctor-{}", std::process::id()));
+    let result = fs::write(&probe, b"").and_then(|_| fs::remove_file(&probe));
+    match (result, existing == dir) {
+        (Ok(()), true) => DoctorCheck::ok(name, format!("{} is writable", dir.display())),
+        (Ok(()), false) => DoctorCheck::ok(name, format!("{} will be created on first use", dir.display())),
+        (Err(e), _) => DoctorCheck::failed(
+            name,
+            format!("cannot write to {}: {}", existing.display(), e),
+            format!("Give the user running clierp write access to {}", existing.display()),
+        ),
+    }
+}
+
+fn connect_check(name: &str, host: &str, port: u16) -> DoctorCheck {
+    let address = match (host, port).to_socket_addrs().map(|mut addrs| addrs.next()) {
+        Ok(Some(address)) => address,
+        Ok(None) | Err(_) => {
+            return DoctorCheck::failed(name, format!("{} does not resolve", host), "Check the host name and DNS");
+        }
+    };
+    match TcpStream::connect_timeout(&address, CONNECT_TIMEOUT) {
+        Ok(_) => DoctorCheck::ok(name, format!("{}:{} accepts connections", host, port)),
+        Err(e) => DoctorCheck::failed(
+            name,
+            format!("{}:{}: {}", host, port, e),
+            "Check the port and that a firewall allows outbound connections to it",
+        ),
+    }
+}
+
+pub fn print_checks(checks: &[DoctorCheck]) {
+    outln!("🩺 CLIERP Doctor:");
+    for check in checks {
+        let marker = match check.status {
+            CheckStatus::Ok => "✅",
+            CheckStatus::Warning => "⚠️ ",
+            CheckStatus::Failed => "❌",
+        };
+        outln!("  {} {}: {}", marker, check.name, check.detail);
+        if let Some(fix) = &check.fix {
+            println!("      Fix: {}", fix);
+        }
+    }
+    let failed = checks.iter().filter(|c| c.status == CheckStatus::Failed).count();
+    let warnings = checks.iter().filter(|c| c.status == CheckStatus::Warning).count();
+    println!();
+    if failed + warnings == 0 {
+        outln!("✅ Everything looks fine");
+    } else {
+        outln!("⚠️  {} problem(s), {} warning(s)", failed, warnings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_check() {
+        let now = "2024-06-01T12:00:00".parse::<NaiveDateTime>().unwrap();
+        assert_eq!(clock_check(now, None).status, CheckStatus::Ok);
+        assert_eq!(clock_check(now, Some(now + Duration::minutes(2))).status, CheckStatus::Ok);
+        let behind = clock_check(now, Some(now + Duration::hours(3)));
+        assert_eq!(behind.status, CheckStatus::Failed);
+        assert!(behind.fix.is_some());
+    }
+}
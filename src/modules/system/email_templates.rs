@@ -0,0 +1,363 @@
+use std::collections::BTreeMap;
+
+use chrono::{NaiveDate, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{customers, deals, email_templates, invoices, leads};
+use crate::database::{Customer, Deal, EmailTemplate, Invoice, NewEmailTemplate};
+use crate::utils::formatting::format_currency;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Templates shipped with CLIERP as (name, subject, body); editing one
+/// stores a copy that is used instead
+pub const BUILTIN_EMAIL_TEMPLATES: [(&str, &str, &str); 3] = [
+    (
+        "quote_follow_up",
+        "Following up on {{deal.name}}",
+        "Dear {{customer.name}},\n\n\
+         Thank you for considering our quote for {{deal.name}} ({{deal.value}}).\n\
+         Do you have any questions we can answer, or anything you would like us to change?\n\n\
+         Kind regards",
+    ),
+    (
+        "invoice_reminder",
+        "Reminder: invoice {{invoice.number}} is due {{invoice.due_date}}",
+        "Dear {{customer.name}},\n\n\
+         This is a reminder that invoice {{invoice.number}} of {{invoice.date}} has {{invoice.open_amount}} \
+         outstanding, due on {{invoice.due_date}}.\n\
+         If you have already paid, please disregard this message.\n\n\
+         Kind regards",
+    ),
+    (
+        "welcome",
+        "Welcome, {{customer.name}}",
+        "Dear {{customer.name}},\n\n\
+         Welcome aboard! Your customer number is {{customer.code}}; please quote it whenever you contact us.\n\n\
+         Kind regards",
+    ),
+];
+
+/// Merge fields a template may use, with what they are filled with
+pub const MERGE_FIELDS: [(&str, &str); 15] = [
+    ("today", "Today's date"),
+    ("customer.name", "Customer name"),
+    ("customer.code", "Customer number"),
+    ("customer.email", "Customer email address"),
+    ("customer.company", "Customer company name"),
+    ("deal.name", "Deal name"),
+    ("deal.value", "Deal value after discount"),
+    ("deal.stage", "Deal stage"),
+    ("deal.close_date", "Expected or actual close date of the deal"),
+    ("invoice.number", "Invoice number"),
+    ("invoice.date", "Invoice date"),
+    ("invoice.due_date", "Invoice due date"),
+    ("invoice.total", "Invoice total"),
+    ("invoice.paid", "Amount paid on the invoice"),
+    ("invoice.open_amount", "Amount still owed on the invoice"),
+];
+
+/// Records a template is filled from; a deal or invoice also supplies its
+/// customer
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MergeSource {
+    pub customer_id: Option<i32>,
+    pub deal_id: Option<i32>,
+    pub invoice_id: Option<i32>,
+}
+
+/// A template, edited or built in
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailTemplateText {
+    pub name: String,
+    pub subject: String,
+    pub body: String,
+    /// Whether this is an edited copy rather than the shipped text
+    pub edited: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RenderedEmail {
+    pub to: Option<String>,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Email templates with `{{field}}` merge fields, filled from customer,
+/// deal and invoice records. Anything that sends email renders the
+/// template by name so edits apply everywhere.
+pub struct EmailTemplateService;
+
+impl EmailTemplateService {
+    /// Built-in templates (edited or not) followed by custom ones, by name
+    pub fn list(conn: &mut SqliteConnection) -> Result<Vec<EmailTemplateText>> {
+        let mut templates: BTreeMap<String, EmailTemplateText> = BUILTIN_EMAIL_TEMPLATES
+            .iter()
+            .map(|(name, subject, body)| {
+                (
+                    name.to_string(),
+                    EmailTemplateText {
+                        name: name.to_string(),
+                        subject: subject.to_string(),
+                        body: body.to_string(),
+                        edited: false,
+                    },
+                )
+            })
+            .collect();
+        for template in email_templates::table.load::<EmailTemplate>(conn)? {
+            templates.insert(template.name.clone(), edited(template));
+        }
+        Ok(templates.into_values().collect())
+    }
+
+    pub fn get(conn: &mut SqliteConnection, name: &str) -> Result<EmailTemplateText> {
+        let name = name.trim();
+        let stored = email_templates::table
+            .filter(email_templates::name.eq(name))
+            .first::<EmailTemplate>(conn)
+            .optional()?;
+        if let Some(template) = stored {
+            return Ok(edited(template));
+        }
+        BUILTIN_EMAIL_TEMPLATES
+            .iter()
+            .find(|(builtin, _, _)| *builtin == name)
+            .map(|(name, subject, body)| EmailTemplateText {
+                name: name.to_string(),
+                subject: subject.to_string(),
+                body: body.to_string(),
+                edited: false,
+            })
+            .ok_or_else(|| CLIERPError::NotFound(format!("Email template '{}' not found", name)))
+    }
+
+    /// Save the template's text, checking its merge fields
+    pub fn save(
+        conn: &mut SqliteConnection,
+        name: &str,
+        subject: &str,
+        body: &str,
+        user_id: Option<i32>,
+    ) -> Result<EmailTemplateText> {
+        let name = name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(CLIERPError::Validation("Template name must be a single word".to_string()));
+        }
+        if subject.trim().is_empty() {
+            return Err(CLIERPError::Validation("Template subject cannot be empty".to_string()));
+        }
+        for text in [subject, body] {
+            for field in merge_fields(text)? {
+                if !MERGE_FIELDS.iter().any(|(known, _)| *known == field) {
+                    return Err(CLIERPError::Validation(format!(
+                        "Unknown merge field {{{{{}}}}}; see `clierp templates fields`",
+                        field
+                    )));
+                }
+            }
+        }
+
+        let updated = diesel::update(email_templates::table.filter(email_templates::name.eq(name)))
+            .set((
+                email_templates::subject.eq(subject.trim()),
+                email_templates::body.eq(body),
+                email_templates::updated_by.eq(user_id),
+                email_templates::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+        if updated == 0 {
+            diesel::insert_into(email_templates::table)
+                .values(&NewEmailTemplate {
+                    name: name.to_string(),
+                    subject: subject.trim().to_string(),
+                    body: body.to_string(),
+                    updated_by: user_id,
+                })
+                .execute(conn)?;
+        }
+
+        tracing::info!("Saved email template {}", name);
+        Self::get(conn, name)
+    }
+
+    /// Drop the edited copy, so a built-in template is used as shipped
+    /// again and a custom one is deleted; returns whether there was one
+    pub fn reset(conn: &mut SqliteConnection, name: &str) -> Result<bool> {
+        let deleted = diesel::delete(email_templates::table.filter(email_templates::name.eq(name.trim())))
+            .execute(conn)?;
+        Ok(deleted > 0)
+    }
+
+    /// Fill template `name` from the records in `source`
+    pub fn render(
+        conn: &mut SqliteConnection,
+        name: &str,
+        source: MergeSource,
+        today: NaiveDate,
+    ) -> Result<RenderedEmail> {
+        let template = Self::get(conn, name)?;
+        let (values, to) = Self::merge_values(conn, source, today)?;
+        Ok(RenderedEmail {
+            to,
+            subject: merge(&template.subject, &values)?,
+            body: merge(&template.body, &values)?,
+        })
+    }
+
+    /// Values of the merge fields the records supply, and the customer's
+    /// email address
+    fn merge_values(
+        conn: &mut SqliteConnection,
+        source: MergeSource,
+        today: NaiveDate,
+    ) -> Result<(BTreeMap<&'static str, String>, Option<String>)> {
+        let mut values = BTreeMap::new();
+        values.insert("today", today.to_string());
+        let mut customer_id = source.customer_id;
+
+        if let Some(invoice_id) = source.invoice_id {
+            let invoice = invoices::table
+                .find(invoice_id)
+                .first::<Invoice>(conn)
+                .optional()?
+                .ok_or_else(|| CLIERPError::NotFound(format!("Invoice {} not found", invoice_id)))?;
+            values.insert("invoice.number", invoice.invoice_number.clone());
+            values.insert("invoice.date", invoice.invoice_date.to_string());
+            values.insert("invoice.due_date", invoice.due_date.map(|d| d.to_string()).unwrap_or_default());
+            values.insert("invoice.total", format_currency(invoice.total_amount));
+            values.insert("invoice.paid", format_currency(invoice.paid_amount));
+            values.insert("invoice.open_amount", format_currency(invoice.open_amount()));
+            customer_id = customer_id.or(invoice.customer_id);
+        }
+        if let Some(deal_id) = source.deal_id {
+            let (deal, lead_customer) = deals::table
+                .left_join(leads::table)
+                .filter(deals::id.eq(deal_id))
+                .select((Deal::as_select(), leads::customer_id.nullable()))
+                .first::<(Deal, Option<i32>)>(conn)
+                .optional()?
+                .ok_or_else(|| CLIERPError::NotFound(format!("Deal {} not found", deal_id)))?;
+            values.insert("deal.name", deal.deal_name.clone());
+            values.insert("deal.value", format_currency(deal.final_amount.unwrap_or(deal.deal_value)));
+            values.insert("deal.stage", deal.stage.replace('_', " "));
+            values.insert("deal.close_date", deal.close_date.map(|d| d.to_string()).unwrap_or_default());
+            customer_id = customer_id.or(lead_customer);
+        }
+
+        let mut to = None;
+        if let Some(customer_id) = customer_id {
+            let customer = customers::table
+                .find(customer_id)
+                .first::<Customer>(conn)
+                .optional()?
+                .ok_or_else(|| CLIERPError::NotFound(format!("Customer {} not found", customer_id)))?;
+            values.insert("customer.name", customer.name.clone());
+            values.insert("customer.code", customer.customer_code.clone());
+            values.insert("customer.email", customer.email.clone().unwrap_or_default());
+            values.insert("customer.company", customer.company_name.clone().unwrap_or_default());
+            to = customer.email;
+        }
+        Ok((values, to))
+    }
+}
+
+/// A template as edited in a text editor: a `Subject:` line, a blank line
+/// and the body
+pub fn template_text(subject: &str, body: &str) -> String {
+    format!("Subject: {}\n\n{}\n", subject, body.trim_end())
+}
+
+/// Subject and body of a template edited as [`template_text`]
+pub fn parse_template_text(text: &str) -> Result<(String, String)> {
+    let (first, body) = text.split_once('\n').unwrap_or((text, ""));
+    let subject = first
+        .strip_prefix("Subject:")
+        .ok_or_else(|| CLIERPError::Validation("The first line must be 'Subject: ...'".to_string()))?;
+    Ok((subject.trim().to_string(), body.trim_matches('\n').trim_end().to_string()))
+}
+
+fn edited(template: EmailTemplate) -> EmailTemplateText {
+    EmailTemplateText {
+        name: template.name,
+        subject: template.subject,
+        body: template.body,
+        edited: true,
+    }
+}
+
+/// Names of the `{{field}}` merge fields in a text, in order
+fn merge_fields(text: &str) -> Result<Vec<&str>> {
+    let mut fields = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| CLIERPError::Validation("Unclosed merge field: missing }}".to_string()))?;
+        fields.push(after[..end].trim());
+        rest = &after[end + 2..];
+    }
+    Ok(fields)
+}
+
+/// Replace the merge fields of `text`; a field the records do not supply
+/// is an error naming the record it needs
+fn merge(text: &str, values: &BTreeMap<&str, String>) -> Result<String> {
+    let mut merged = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        merged.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| CLIERPError::Validation("Unclosed merge field: missing }}".to_string()))?;
+        let field = after[..end].trim();
+        let value = values.get(field).ok_or_else(|| {
+            let record = field.split('.').next().unwrap_or(field);
+            let article = if record.starts_with(['a', 'e', 'i', 'o', 'u']) { "an" } else { "a" };
+            CLIERPError::Validation(format!("Merge field {{{{{}}}}} needs {} {}", field, article, record))
+        })?;
+        merged.push_str(value);
+        rest = &after[end + 2..];
+    }
+    merged.push_str(rest);
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_fields() {
+        let (_, subject, body) = BUILTIN_EMAIL_TEMPLATES[1];
+        assert_eq!(merge_fields(subject).unwrap(), ["invoice.number", "invoice.due_date"]);
+        for (_, subject, body) in BUILTIN_EMAIL_TEMPLATES {
+            for field in merge_fields(subject).unwrap().into_iter().chain(merge_fields(body).unwrap()) {
+                assert!(MERGE_FIELDS.iter().any(|(known, _)| *known == field), "{}", field);
+            }
+        }
+
+        let values = BTreeMap::from([
+            ("customer.name", "Acme".to_string()),
+            ("invoice.number", "INV-7".to_string()),
+            ("invoice.due_date", "2024-07-01".to_string()),
+        ]);
+        assert_eq!(
+            merge(subject, &values).unwrap(),
+            "Reminder: invoice INV-7 is due 2024-07-01"
+        );
+        assert_eq!(merge("Hi {{ customer.name }}!", &values).unwrap(), "Hi Acme!");
+        assert!(merge(body, &values).unwrap_err().to_string().contains("needs an invoice"));
+        assert!(merge("Hi {{customer.name", &values).is_err());
+
+        let text = template_text(subject, body);
+        assert_eq!(parse_template_text(&text).unwrap(), (subject.to_string(), body.to_string()));
+        assert!(parse_template_text("Hello\n\nBody").is_err());
+    }
+}
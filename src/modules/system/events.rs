@@ -0,0 +1,108 @@
+use diesel::prelude::*;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::core::result::CLIERPResult;
+use crate::database::schema::events;
+use crate::database::{ChangeEvent, DatabaseConnection, EventAction, NewChangeEvent};
+use crate::modules::system::audit::AuditService;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Most events returned by one read
+pub const MAX_EVENTS_PER_READ: i64 = 1000;
+
+/// Append-only log of record changes for external subscribers (a data
+/// warehouse, a search index). Services append an event in the same
+/// connection, and transaction where they use one, as the change itself;
+/// subscribers read everything after the last sequence they saw.
+pub struct EventService;
+
+impl EventService {
+    /// Log that `record`, a row of `entity`, was created, updated or deleted
+    pub fn record<T: Serialize>(
+        conn: &mut DatabaseConnection,
+        entity: &str,
+        entity_id: i32,
+        action: EventAction,
+        record: &T,
+    ) -> Result<()> {
+        diesel::insert_into(events::table)
+            .values(&NewChangeEvent {
+                entity: entity.to_string(),
+                entity_id,
+                action: action.to_string(),
+                payload: serde_json::to_string(record)?,
+                user_id: AuditService::actor(),
+            })
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Events after sequence `after`, oldest first
+    pub fn since(
+        conn: &mut DatabaseConnection,
+        after: i64,
+        entity: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<ChangeEvent>> {
+        let mut query = events::table.filter(events::sequence.gt(after)).into_boxed();
+        if let Some(entity) = entity {
+            query = query.filter(events::entity.eq(entity));
+        }
+        Ok(query
+            .order(events::sequence.asc())
+            .limit(limit.clamp(1, MAX_EVENTS_PER_READ))
+            .load::<ChangeEvent>(conn)?)
+    }
+
+    /// Sequence of the latest event; 0 before the first
+    pub fn last_sequence(conn: &mut DatabaseConnection) -> Result<i64> {
+        Ok(events::table
+            .select(diesel::dsl::max(events::sequence))
+            .first::<Option<i64>>(conn)?
+            .unwrap_or(0))
+    }
+}
+
+/// An event as a JSON object with the payload inline, as streamed to
+/// subscribers one per line
+pub fn event_json(event: &ChangeEvent) -> Value {
+    json!({
+        "sequence": event.sequence,
+        "entity": event.entity,
+        "entity_id": event.entity_id,
+        "action": event.action,
+        "user_id": event.user_id,
+        "occurred_at": event.occurred_at.and_utc().to_rfc3339(),
+        "payload": serde_json::from_str::<Value>(&event.payload).unwrap_or_else(|_| Value::String(event.payload.clone())),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_event_json() {
+        let event = ChangeEvent {
+            sequence: 42,
+            entity: "customers".to_string(),
+            entity_id: 7,
+            action: EventAction::Updated.to_string(),
+            payload: r#"{"id":7,"name":"Acme"}"#.to_string(),
+            user_id: None,
+            occurred_at: NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_hms_opt(9, 30, 0).unwrap(),
+        };
+        let line = event_json(&event);
+        assert_eq!(line["sequence"], 42);
+        assert_eq!(line["action"], "updated");
+        assert_eq!(line["payload"]["name"], "Acme");
+        assert_eq!(line["occurred_at"], "2024-05-01T09:30:00+00:00");
+
+        let broken = ChangeEvent { payload: "not json".to_string(), ..event };
+        assert_eq!(event_json(&broken)["payload"], "not json");
+    }
+}
@@ -0,0 +1,150 @@
+use chrono::Utc;
+use diesel::prelude::*;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::export_templates;
+use crate::database::{ExportTemplate, NewExportTemplate};
+use crate::modules::hr::department::DEPARTMENT_CSV_HEADERS;
+use crate::modules::hr::employee::EMPLOYEE_CSV_HEADERS;
+use crate::modules::inventory::intrastat::INTRASTAT_CSV_HEADERS;
+use crate::modules::system::delivery::{PRICE_LIST_CSV_HEADERS, STOCK_LEVEL_CSV_HEADERS};
+use crate::utils::export::{parse_date_format, ExportMapping, TemplateColumn};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Export a template is written for; its columns are checked against the
+/// export's CSV headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportEntity {
+    Employees,
+    Departments,
+    Intrastat,
+    StockLevels,
+    PriceList,
+}
+
+impl ExportEntity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportEntity::Employees => "employees",
+            ExportEntity::Departments => "departments",
+            ExportEntity::Intrastat => "intrastat",
+            ExportEntity::StockLevels => "stock_levels",
+            ExportEntity::PriceList => "price_list",
+        }
+    }
+
+    /// Internal column names a template may map
+    pub fn headers(&self) -> &'static [&'static str] {
+        match self {
+            ExportEntity::Employees => &EMPLOYEE_CSV_HEADERS,
+            ExportEntity::Departments => &DEPARTMENT_CSV_HEADERS,
+            ExportEntity::Intrastat => &INTRASTAT_CSV_HEADERS,
+            ExportEntity::StockLevels => &STOCK_LEVEL_CSV_HEADERS,
+            ExportEntity::PriceList => &PRICE_LIST_CSV_HEADERS,
+        }
+    }
+}
+
+impl std::fmt::Display for ExportEntity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Named export layouts for third-party systems, chosen with `--template`
+/// on CSV exports. A template belongs to one export, so a partner taking
+/// several files has one template for each.
+pub struct ExportTemplateService;
+
+impl ExportTemplateService {
+    /// Create the template, or replace the one with this name
+    pub fn save(
+        conn: &mut SqliteConnection,
+        name: &str,
+        entity: ExportEntity,
+        columns: Vec<TemplateColumn>,
+        date_format: Option<&str>,
+        user_id: Option<i32>,
+    ) -> Result<ExportTemplate> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(CLIERPError::Validation("Template name cannot be empty".to_string()));
+        }
+        let mapping = ExportMapping {
+            columns,
+            date_format: date_format.map(parse_date_format).transpose()?,
+        };
+        mapping.column_indices(entity.headers())?;
+        let json = serde_json::to_string(&mapping.columns)
+            .map_err(|e| CLIERPError::SerializationError(e.to_string()))?;
+
+        let updated = diesel::update(export_templates::table.filter(export_templates::name.eq(name)))
+            .set((
+                export_templates::entity.eq(entity.name()),
+                export_templates::mapping.eq(&json),
+                export_templates::date_format.eq(&mapping.date_format),
+                export_templates::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+        if updated == 0 {
+            diesel::insert_into(export_templates::table)
+                .values(&NewExportTemplate {
+                    name: name.to_string(),
+                    entity: entity.name().to_string(),
+                    mapping: json,
+                    date_format: mapping.date_format.clone(),
+                    created_by: user_id,
+                })
+                .execute(conn)?;
+        }
+
+        tracing::info!("Saved export template {} for {}", name, entity);
+        Self::get(conn, name)
+    }
+
+    pub fn get(conn: &mut SqliteConnection, name: &str) -> Result<ExportTemplate> {
+        export_templates::table
+            .filter(export_templates::name.eq(name.trim()))
+            .first::<ExportTemplate>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Export template '{}' not found", name)))
+    }
+
+    pub fn list(conn: &mut SqliteConnection) -> Result<Vec<ExportTemplate>> {
+        Ok(export_templates::table
+            .order(export_templates::name.asc())
+            .load::<ExportTemplate>(conn)?)
+    }
+
+    /// Delete the template, returning whether it existed
+    pub fn remove(conn: &mut SqliteConnection, name: &str) -> Result<bool> {
+        let deleted = diesel::delete(export_templates::table.filter(export_templates::name.eq(name.trim())))
+            .execute(conn)?;
+        Ok(deleted > 0)
+    }
+
+    /// The mapping of template `name`, which must be one for `entity`
+    pub fn mapping(conn: &mut SqliteConnection, name: &str, entity: ExportEntity) -> Result<ExportMapping> {
+        let template = Self::get(conn, name)?;
+        if template.entity != entity.name() {
+            return Err(CLIERPError::Validation(format!(
+                "Export template '{}' is for {} exports, not {}",
+                template.name, template.entity, entity
+            )));
+        }
+        Self::parse_mapping(&template)
+    }
+
+    pub fn parse_mapping(template: &ExportTemplate) -> Result<ExportMapping> {
+        let columns = serde_json::from_str(&template.mapping).map_err(|e| {
+            CLIERPError::SerializationError(format!("Export template '{}' is damaged: {}", template.name, e))
+        })?;
+        Ok(ExportMapping {
+            columns,
+            date_format: template.date_format.clone(),
+        })
+    }
+}
@@ -0,0 +1,105 @@
+use chrono::Utc;
+use diesel::prelude::*;
+
+use crate::core::result::CLIERPResult;
+use crate::database::schema::import_checkpoints;
+use crate::database::{DatabaseConnection, ImportCheckpoint, ImportStatus, NewImportCheckpoint};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Bookkeeping for imports that commit in chunks.
+///
+/// Each committed chunk advances the checkpoint in the same transaction as
+/// its rows, so after an interruption the checkpoint points exactly at the
+/// first row that was not imported.
+pub struct ImportCheckpointService;
+
+impl ImportCheckpointService {
+    /// Start or resume an import of `source`. A running checkpoint is resumed
+    /// unless `restart` is set; a completed one starts over.
+    pub fn begin(
+        conn: &mut DatabaseConnection,
+        import_type: &str,
+        source: &str,
+        total_rows: i32,
+        restart: bool,
+        started_by: Option<i32>,
+    ) -> Result<ImportCheckpoint> {
+        let existing = import_checkpoints::table
+            .filter(import_checkpoints::import_type.eq(import_type))
+            .filter(import_checkpoints::source.eq(source))
+            .first::<ImportCheckpoint>(conn)
+            .optional()?;
+
+        match existing {
+            Some(checkpoint) if !restart && checkpoint.status == ImportStatus::Running.to_string() => {
+                diesel::update(import_checkpoints::table.find(checkpoint.id))
+                    .set((
+                        import_checkpoints::total_rows.eq(total_rows),
+                        import_checkpoints::updated_at.eq(Utc::now().naive_utc()),
+                    ))
+                    .execute(conn)?;
+            }
+            Some(checkpoint) => {
+                diesel::update(import_checkpoints::table.find(checkpoint.id))
+                    .set((
+                        import_checkpoints::total_rows.eq(total_rows),
+                        import_checkpoints::rows_committed.eq(0),
+                        import_checkpoints::last_key.eq(None::<String>),
+                        import_checkpoints::status.eq(ImportStatus::Running.to_string()),
+                        import_checkpoints::started_by.eq(started_by),
+                        import_checkpoints::updated_at.eq(Utc::now().naive_utc()),
+                    ))
+                    .execute(conn)?;
+            }
+            None => {
+                let checkpoint = NewImportCheckpoint {
+                    import_type: import_type.to_string(),
+                    source: source.to_string(),
+                    total_rows,
+                    rows_committed: 0,
+                    last_key: None,
+                    status: ImportStatus::Running.to_string(),
+                    started_by,
+                };
+                diesel::insert_into(import_checkpoints::table)
+                    .values(&checkpoint)
+                    .execute(conn)?;
+            }
+        }
+
+        import_checkpoints::table
+            .filter(import_checkpoints::import_type.eq(import_type))
+            .filter(import_checkpoints::source.eq(source))
+            .first::<ImportCheckpoint>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Record that rows up to `rows_committed` are in; call inside the chunk's transaction
+    pub fn advance(
+        conn: &mut DatabaseConnection,
+        checkpoint_id: i32,
+        rows_committed: i32,
+        last_key: Option<&str>,
+    ) -> Result<()> {
+        diesel::update(import_checkpoints::table.find(checkpoint_id))
+            .set((
+                import_checkpoints::rows_committed.eq(rows_committed),
+                import_checkpoints::last_key.eq(last_key.map(|s| s.to_string())),
+                import_checkpoints::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn complete(conn: &mut DatabaseConnection, checkpoint_id: i32) -> Result<()> {
+        diesel::update(import_checkpoints::table.find(checkpoint_id))
+            .set((
+                import_checkpoints::status.eq(ImportStatus::Completed.to_string()),
+                import_checkpoints::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+}
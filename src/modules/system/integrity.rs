@@ -0,0 +1,370 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+use diesel::prelude::*;
+use diesel::sql_types::{Integer, Nullable, Text};
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{employee_documents, product_attachments, record_links};
+use crate::database::{DatabaseConnection, RecordType};
+use crate::modules::system::links::{LinkService, RecordRef};
+use crate::utils::cache::QueryCache;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// What `--fix` does with the rows a check finds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repair {
+    /// Clear the dangling reference column, keeping the row
+    Clear(&'static str),
+    /// Move the row to `quarantined_rows` as JSON
+    Quarantine,
+}
+
+impl std::fmt::Display for Repair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Repair::Clear(column) => write!(f, "clear {}", column),
+            Repair::Quarantine => write!(f, "quarantine"),
+        }
+    }
+}
+
+/// A query for rows whose reference points at a missing record
+#[derive(Debug, Clone, Copy)]
+pub struct ReferenceCheck {
+    pub name: &'static str,
+    pub table: &'static str,
+    /// `WHERE` clause matching the orphaned rows of `table`
+    pub condition: &'static str,
+    pub repair: Repair,
+}
+
+/// Reference checks run by `system check`. Rows that only make sense with
+/// their parent are quarantined; optional references are cleared.
+pub const REFERENCE_CHECKS: &[ReferenceCheck] = &[
+    ReferenceCheck {
+        name: "stock movements without product",
+        table: "stock_movements",
+        condition: "product_id NOT IN (SELECT id FROM products)",
+        repair: Repair::Quarantine,
+    },
+    ReferenceCheck {
+        name: "stock reservations without product",
+        table: "stock_reservations",
+        condition: "product_id NOT IN (SELECT id FROM products)",
+        repair: Repair::Quarantine,
+    },
+    ReferenceCheck {
+        name: "attachments of deleted products",
+        table: "product_attachments",
+        condition: "product_id NOT IN (SELECT id FROM products)",
+        repair: Repair::Quarantine,
+    },
+    ReferenceCheck {
+        name: "bundle items of deleted bundles or products",
+        table: "product_bundle_items",
+        condition: "bundle_id NOT IN (SELECT id FROM product_bundles) OR product_id NOT IN (SELECT id FROM products)",
+        repair: Repair::Quarantine,
+    },
+    ReferenceCheck {
+        name: "purchase items without purchase order",
+        table: "purchase_items",
+        condition: "po_id NOT IN (SELECT id FROM purchase_orders)",
+        repair: Repair::Quarantine,
+    },
+    ReferenceCheck {
+        name: "purchase items without product",
+        table: "purchase_items",
+        condition: "product_id NOT IN (SELECT id FROM products)",
+        repair: Repair::Quarantine,
+    },
+    ReferenceCheck {
+        name: "purchase orders of deleted suppliers",
+        table: "purchase_orders",
+        condition: "supplier_id NOT IN (SELECT id FROM suppliers)",
+        repair: Repair::Quarantine,
+    },
+    ReferenceCheck {
+        name: "deals pointing at deleted leads",
+        table: "deals",
+        condition: "lead_id IS NOT NULL AND lead_id NOT IN (SELECT id FROM leads)",
+        repair: Repair::Clear("lead_id"),
+    },
+    ReferenceCheck {
+        name: "leads pointing at deleted customers",
+        table: "leads",
+        condition: "customer_id IS NOT NULL AND customer_id NOT IN (SELECT id FROM customers)",
+        repair: Repair::Clear("customer_id"),
+    },
+    ReferenceCheck {
+        name: "activities pointing at deleted customers",
+        table: "activities",
+        condition: "customer_id IS NOT NULL AND customer_id NOT IN (SELECT id FROM customers)",
+        repair: Repair::Clear("customer_id"),
+    },
+    ReferenceCheck {
+        name: "activities pointing at deleted leads",
+        table: "activities",
+        condition: "lead_id IS NOT NULL AND lead_id NOT IN (SELECT id FROM leads)",
+        repair: Repair::Clear("lead_id"),
+    },
+    ReferenceCheck {
+        name: "activities pointing at deleted deals",
+        table: "activities",
+        condition: "deal_id IS NOT NULL AND deal_id NOT IN (SELECT id FROM deals)",
+        repair: Repair::Clear("deal_id"),
+    },
+    ReferenceCheck {
+        name: "campaign leads of deleted leads or campaigns",
+        table: "campaign_leads",
+        condition: "lead_id NOT IN (SELECT id FROM leads) OR campaign_id NOT IN (SELECT id FROM campaigns)",
+        repair: Repair::Quarantine,
+    },
+    ReferenceCheck {
+        name: "surveys of deleted customers",
+        table: "customer_surveys",
+        condition: "customer_id NOT IN (SELECT id FROM customers)",
+        repair: Repair::Quarantine,
+    },
+    ReferenceCheck {
+        name: "surveys pointing at deleted deals",
+        table: "customer_surveys",
+        condition: "deal_id IS NOT NULL AND deal_id NOT IN (SELECT id FROM deals)",
+        repair: Repair::Clear("deal_id"),
+    },
+    ReferenceCheck {
+        name: "churn alerts of deleted customers",
+        table: "churn_alerts",
+        condition: "customer_id NOT IN (SELECT id FROM customers)",
+        repair: Repair::Quarantine,
+    },
+    ReferenceCheck {
+        name: "attendance of deleted employees",
+        table: "attendances",
+        condition: "employee_id NOT IN (SELECT id FROM employees)",
+        repair: Repair::Quarantine,
+    },
+    ReferenceCheck {
+        name: "payrolls of deleted employees",
+        table: "payrolls",
+        condition: "employee_id NOT IN (SELECT id FROM employees)",
+        repair: Repair::Quarantine,
+    },
+    ReferenceCheck {
+        name: "documents of deleted employees",
+        table: "employee_documents",
+        condition: "employee_id NOT IN (SELECT id FROM employees)",
+        repair: Repair::Quarantine,
+    },
+    ReferenceCheck {
+        name: "transactions without account",
+        table: "transactions",
+        condition: "account_id NOT IN (SELECT id FROM accounts)",
+        repair: Repair::Quarantine,
+    },
+];
+
+/// Rows found by one check, and whether they were repaired
+#[derive(Debug, Clone)]
+pub struct IntegrityFinding {
+    pub name: String,
+    pub table: String,
+    pub record_ids: Vec<i32>,
+    pub repair: Repair,
+    pub repaired: bool,
+}
+
+#[derive(QueryableByName)]
+struct IdRow {
+    #[diesel(sql_type = Integer)]
+    id: i32,
+}
+
+#[derive(QueryableByName)]
+struct ColumnRow {
+    #[diesel(sql_type = Text)]
+    name: String,
+}
+
+/// Finds orphaned rows and, when asked, repairs them.
+///
+/// SQLite does not enforce the schema's foreign keys here, so deleting a
+/// product or lead can leave rows pointing at nothing. Besides the
+/// reference checks, attachments and documents are checked for files that
+/// are gone and record links for records that no longer exist. Quarantined
+/// rows are kept as JSON in `quarantined_rows` so nothing is lost.
+pub struct IntegrityService;
+
+impl IntegrityService {
+    /// Run every check; with `fix`, repair what was found in one transaction
+    pub fn run(conn: &mut DatabaseConnection, fix: bool, fixed_by: Option<i32>) -> Result<Vec<IntegrityFinding>> {
+        let mut findings = Vec::new();
+        for check in REFERENCE_CHECKS {
+            let ids = diesel::sql_query(format!("SELECT id FROM {} WHERE {} ORDER BY id", check.table, check.condition))
+                .load::<IdRow>(conn)?
+                .into_iter()
+                .map(|row| row.id)
+                .collect();
+            findings.push(Self::finding(check.name, check.table, ids, check.repair));
+        }
+
+        let attachments: Vec<(i32, String)> = product_attachments::table
+            .select((product_attachments::id, product_attachments::file_path))
+            .order(product_attachments::id.asc())
+            .load(conn)?;
+        findings.push(Self::finding(
+            "product attachments missing their file",
+            "product_attachments",
+            missing_files(attachments),
+            Repair::Quarantine,
+        ));
+
+        let documents: Vec<(i32, Option<String>)> = employee_documents::table
+            .select((employee_documents::id, employee_documents::file_path))
+            .order(employee_documents::id.asc())
+            .load(conn)?;
+        findings.push(Self::finding(
+            "employee documents missing their file",
+            "employee_documents",
+            missing_files(documents.into_iter().filter_map(|(id, path)| path.map(|p| (id, p)))),
+            Repair::Clear("file_path"),
+        ));
+
+        findings.push(Self::finding(
+            "links to missing records",
+            "record_links",
+            Self::dangling_links(conn)?,
+            Repair::Quarantine,
+        ));
+
+        if fix && findings.iter().any(|f| !f.record_ids.is_empty()) {
+            conn.transaction::<_, CLIERPError, _>(|conn| {
+                for finding in findings.iter_mut().filter(|f| !f.record_ids.is_empty()) {
+                    Self::repair(conn, finding, fixed_by)?;
+                    finding.repaired = true;
+                }
+                Ok(())
+            })?;
+            QueryCache::current().invalidate(&["hr", "finance", "inventory", "crm"]);
+            tracing::info!(
+                "Integrity check repaired {} rows",
+                findings.iter().map(|f| f.record_ids.len()).sum::<usize>()
+            );
+        }
+        Ok(findings)
+    }
+
+    fn finding(name: &str, table: &str, record_ids: Vec<i32>, repair: Repair) -> IntegrityFinding {
+        IntegrityFinding {
+            name: name.to_string(),
+            table: table.to_string(),
+            record_ids,
+            repair,
+            repaired: false,
+        }
+    }
+
+    fn dangling_links(conn: &mut DatabaseConnection) -> Result<Vec<i32>> {
+        let links: Vec<(i32, String, i32, String, i32)> = record_links::table
+            .select((
+                record_links::id,
+                record_links::source_type,
+                record_links::source_id,
+                record_links::target_type,
+                record_links::target_id,
+            ))
+            .order(record_links::id.asc())
+            .load(conn)?;
+
+        let mut dangling = Vec::new();
+        for (id, source_type, source_id, target_type, target_id) in links {
+            let mut exists = |record_type: &str, record_id: i32| -> Result<bool> {
+                match RecordType::from_str(record_type, true) {
+                    Ok(record_type) => Ok(LinkService::label(conn, RecordRef::new(record_type, record_id))?.is_some()),
+                    Err(_) => Ok(false),
+                }
+            };
+            if !exists(&source_type, source_id)? || !exists(&target_type, target_id)? {
+                dangling.push(id);
+            }
+        }
+        Ok(dangling)
+    }
+
+    fn repair(conn: &mut SqliteConnection, finding: &IntegrityFinding, fixed_by: Option<i32>) -> Result<()> {
+        let ids = finding
+            .record_ids
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        match finding.repair {
+            Repair::Clear(column) => {
+                diesel::sql_query(format!("UPDATE {} SET {} = NULL WHERE id IN ({})", finding.table, column, ids))
+                    .execute(conn)?;
+            }
+            Repair::Quarantine => {
+                let columns = diesel::sql_query(format!("SELECT name FROM pragma_table_info('{}')", finding.table))
+                    .load::<ColumnRow>(conn)?
+                    .into_iter()
+                    .map(|c| format!("'{0}', \"{0}\"", c.name))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                diesel::sql_query(format!(
+                    "INSERT INTO quarantined_rows (table_name, record_id, reason, row_data, quarantined_by)
+                     SELECT ?, id, ?, json_object({}), ? FROM {} WHERE id IN ({})",
+                    columns, finding.table, ids
+                ))
+                .bind::<Text, _>(&finding.table)
+                .bind::<Text, _>(&finding.name)
+                .bind::<Nullable<Integer>, _>(fixed_by)
+                .execute(conn)?;
+                diesel::sql_query(format!("DELETE FROM {} WHERE id IN ({})", finding.table, ids)).execute(conn)?;
+            }
+        }
+        tracing::warn!(
+            "Repaired {} {} rows ({}): {}",
+            finding.record_ids.len(),
+            finding.table,
+            finding.repair,
+            finding.name
+        );
+        Ok(())
+    }
+}
+
+/// Ids whose file path does not point at an existing file
+fn missing_files(rows: impl IntoIterator<Item = (i32, String)>) -> Vec<i32> {
+    rows.into_iter()
+        .filter(|(_, path)| !Path::new(path).is_file())
+        .map(|(id, _)| id)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_files() {
+        let existing = std::env::current_exe().unwrap().to_string_lossy().to_string();
+        let rows = vec![
+            (1, existing),
+            (2, "/nonexistent/clierp/attachment.pdf".to_string()),
+            (3, std::env::temp_dir().to_string_lossy().to_string()),
+        ];
+        // A directory is not a file either
+        assert_eq!(missing_files(rows), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_reference_checks_are_unique() {
+        let mut names: Vec<&str> = REFERENCE_CHECKS.iter().map(|c| c.name).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), REFERENCE_CHECKS.len());
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::HashSet;
+
+use chrono::Utc;
+use diesel::prelude::*;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{
+    activities, audit_logs, customers, deals, employee_documents, employees, leads, legal_holds,
+};
+use crate::database::{DatabaseConnection, LegalHold, NewLegalHold};
+
+use super::retention::RetentionEntity;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Kinds of records a legal hold can be placed on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoldEntity {
+    Customers,
+    Deals,
+    /// Employee documents
+    Documents,
+    Activities,
+    Leads,
+    Employees,
+    Notifications,
+    AuditLogs,
+}
+
+impl HoldEntity {
+    pub const ALL: [HoldEntity; 8] = [
+        HoldEntity::Customers,
+        HoldEntity::Deals,
+        HoldEntity::Documents,
+        HoldEntity::Activities,
+        HoldEntity::Leads,
+        HoldEntity::Employees,
+        HoldEntity::Notifications,
+        HoldEntity::AuditLogs,
+    ];
+
+    /// Name stored in `legal_holds.entity`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HoldEntity::Customers => "customers",
+            HoldEntity::Deals => "deals",
+            HoldEntity::Documents => "documents",
+            HoldEntity::Activities => "activities",
+            HoldEntity::Leads => "leads",
+            HoldEntity::Employees => "employees",
+            HoldEntity::Notifications => "notifications",
+            HoldEntity::AuditLogs => "audit_logs",
+        }
+    }
+
+    /// Table the records live in, as `audit_logs.table_name` names it
+    pub fn table_name(&self) -> &'static str {
+        match self {
+            HoldEntity::Documents => "employee_documents",
+            entity => entity.as_str(),
+        }
+    }
+}
+
+impl From<RetentionEntity> for HoldEntity {
+    fn from(entity: RetentionEntity) -> Self {
+        match entity {
+            RetentionEntity::Activities => HoldEntity::Activities,
+            RetentionEntity::Leads => HoldEntity::Leads,
+            RetentionEntity::Employees => HoldEntity::Employees,
+            RetentionEntity::Notifications => HoldEntity::Notifications,
+            RetentionEntity::AuditLogs => HoldEntity::AuditLogs,
+        }
+    }
+}
+
+impl std::fmt::Display for HoldEntity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for HoldEntity {
+    type Err = CLIERPError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+        HoldEntity::ALL
+            .into_iter()
+            .find(|entity| entity.as_str() == name || entity.table_name() == name)
+            .ok_or_else(|| {
+                CLIERPError::Validation(format!(
+                    "Unknown hold entity '{}'; use customers, deals, documents, activities, leads, employees, notifications or audit_logs",
+                    s
+                ))
+            })
+    }
+}
+
+/// A hold in force with what the held record is called, if it still exists
+#[derive(Debug, Clone)]
+pub struct HeldRecord {
+    pub hold: LegalHold,
+    pub label: Option<String>,
+}
+
+/// Legal holds keep records as they are while a dispute or investigation
+/// runs: a held record cannot be deleted, and retention and archive runs
+/// skip it and the history attached to it.
+pub struct LegalHoldService;
+
+impl LegalHoldService {
+    pub fn place(
+        conn: &mut DatabaseConnection,
+        entity: HoldEntity,
+        record_id: i32,
+        reason: &str,
+        placed_by: Option<i32>,
+    ) -> Result<LegalHold> {
+        if reason.trim().is_empty() {
+            return Err(CLIERPError::Validation("A legal hold needs a reason".to_string()));
+        }
+        if Self::find_active(conn, entity, record_id)?.is_some() {
+            return Err(CLIERPError::AlreadyExists(format!("{} {} is already on hold", entity, record_id)));
+        }
+
+        diesel::insert_into(legal_holds::table)
+            .values(&NewLegalHold {
+                entity: entity.to_string(),
+                record_id,
+                reason: reason.trim().to_string(),
+                placed_by,
+            })
+            .execute(conn)?;
+        let hold = legal_holds::table
+            .order(legal_holds::id.desc())
+            .first::<LegalHold>(conn)?;
+        tracing::info!("Placed legal hold {} on {} {}", hold.id, entity, record_id);
+        Ok(hold)
+    }
+
+    pub fn release(conn: &mut DatabaseConnection, hold_id: i32, released_by: Option<i32>) -> Result<LegalHold> {
+        let hold = legal_holds::table
+            .find(hold_id)
+            .first::<LegalHold>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Legal hold {} not found", hold_id)))?;
+        if hold.released_at.is_some() {
+            return Err(CLIERPError::BusinessLogic(format!("Legal hold {} is already released", hold_id)));
+        }
+
+        diesel::update(legal_holds::table.find(hold_id))
+            .set((
+                legal_holds::released_by.eq(released_by),
+                legal_holds::released_at.eq(Some(Utc::now().naive_utc())),
+            ))
+            .execute(conn)?;
+        tracing::info!("Released legal hold {} on {} {}", hold.id, hold.entity, hold.record_id);
+        Ok(legal_holds::table.find(hold_id).first::<LegalHold>(conn)?)
+    }
+
+    /// Holds in force, optionally of one entity, oldest first
+    pub fn active(conn: &mut DatabaseConnection, entity: Option<HoldEntity>) -> Result<Vec<LegalHold>> {
+        let mut query = legal_holds::table
+            .filter(legal_holds::released_at.is_null())
+            .order(legal_holds::placed_at.asc())
+            .into_boxed();
+        if let Some(entity) = entity {
+            query = query.filter(legal_holds::entity.eq(entity.as_str()));
+        }
+        Ok(query.load::<LegalHold>(conn)?)
+    }
+
+    /// Every held record with its name, for the legal hold report
+    pub fn report(conn: &mut DatabaseConnection, entity: Option<HoldEntity>) -> Result<Vec<HeldRecord>> {
+        let holds = Self::active(conn, entity)?;
+        let mut records = Vec::with_capacity(holds.len());
+        for hold in holds {
+            let label = match hold.entity.parse::<HoldEntity>() {
+                Ok(entity) => Self::label(conn, entity, hold.record_id)?,
+                Err(_) => None,
+            };
+            records.push(HeldRecord { hold, label });
+        }
+        Ok(records)
+    }
+
+    /// Refuse to `doing` a held record, e.g. "delete"
+    pub fn ensure_not_held(conn: &mut DatabaseConnection, entity: HoldEntity, record_id: i32, doing: &str) -> Result<()> {
+        match Self::find_active(conn, entity, record_id)? {
+            Some(hold) => Err(CLIERPError::BusinessLogic(format!(
+                "Cannot {} {} {}: it is under legal hold {} ({}); an admin must release the hold first",
+                doing, entity, record_id, hold.id, hold.reason
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// IDs of the records of `entity` under a hold in force
+    pub fn held_ids(conn: &mut DatabaseConnection, entity: HoldEntity) -> Result<HashSet<i32>> {
+        Ok(legal_holds::table
+            .filter(legal_holds::entity.eq(entity.as_str()))
+            .filter(legal_holds::released_at.is_null())
+            .select(legal_holds::record_id)
+            .load::<i32>(conn)?
+            .into_iter()
+            .collect())
+    }
+
+    /// Records retention must leave alone: those held themselves, and
+    /// those attached to a held customer, deal or document
+    pub fn protected_ids(conn: &mut DatabaseConnection, entity: HoldEntity) -> Result<HashSet<i32>> {
+        let mut ids = Self::held_ids(conn, entity)?;
+        let customers = Self::held_ids(conn, HoldEntity::Customers)?;
+        let deals = Self::held_ids(conn, HoldEntity::Deals)?;
+        match entity {
+            HoldEntity::Activities if !customers.is_empty() || !deals.is_empty() => {
+                ids.extend(
+                    activities::table
+                        .filter(
+                            activities::customer_id
+                                .eq_any(customers.iter().copied().collect::<Vec<_>>())
+                                .or(activities::deal_id.eq_any(deals.iter().copied().collect::<Vec<_>>())),
+                        )
+                        .select(activities::id)
+                        .load::<i32>(conn)?,
+                );
+            }
+            HoldEntity::Leads if !customers.is_empty() || !deals.is_empty() => {
+                ids.extend(
+                    leads::table
+                        .filter(leads::customer_id.eq_any(customers.iter().copied().collect::<Vec<_>>()))
+                        .select(leads::id)
+                        .load::<i32>(conn)?,
+                );
+                ids.extend(
+                    deals::table
+                        .filter(deals::id.eq_any(deals.iter().copied().collect::<Vec<_>>()))
+                        .select(deals::lead_id)
+                        .load::<Option<i32>>(conn)?
+                        .into_iter()
+                        .flatten(),
+                );
+            }
+            HoldEntity::Employees => {
+                let documents = Self::held_ids(conn, HoldEntity::Documents)?;
+                if !documents.is_empty() {
+                    ids.extend(
+                        employee_documents::table
+                            .filter(employee_documents::id.eq_any(documents.into_iter().collect::<Vec<_>>()))
+                            .select(employee_documents::employee_id)
+                            .load::<i32>(conn)?,
+                    );
+                }
+            }
+            HoldEntity::AuditLogs => ids.extend(Self::held_audit_log_ids(conn)?),
+            _ => {}
+        }
+        Ok(ids)
+    }
+
+    /// Change history of held customers, deals and documents, which archive
+    /// and retention runs keep in place
+    pub fn held_audit_log_ids(conn: &mut DatabaseConnection) -> Result<Vec<i32>> {
+        let mut ids = Vec::new();
+        for entity in [HoldEntity::Customers, HoldEntity::Deals, HoldEntity::Documents] {
+            let held = Self::held_ids(conn, entity)?;
+            if held.is_empty() {
+                continue;
+            }
+            ids.extend(
+                audit_logs::table
+                    .filter(audit_logs::table_name.eq(entity.table_name()))
+                    .filter(audit_logs::record_id.eq_any(held.into_iter().collect::<Vec<_>>()))
+                    .select(audit_logs::id)
+                    .load::<i32>(conn)?,
+            );
+        }
+        Ok(ids)
+    }
+
+    fn find_active(conn: &mut DatabaseConnection, entity: HoldEntity, record_id: i32) -> Result<Option<LegalHold>> {
+        Ok(legal_holds::table
+            .filter(legal_holds::entity.eq(entity.as_str()))
+            .filter(legal_holds::record_id.eq(record_id))
+            .filter(legal_holds::released_at.is_null())
+            .first::<LegalHold>(conn)
+            .optional()?)
+    }
+
+    fn label(conn: &mut DatabaseConnection, entity: HoldEntity, record_id: i32) -> Result<Option<String>> {
+        let label = match entity {
+            HoldEntity::Customers => customers::table
+                .find(record_id)
+                .select(customers::name)
+                .first::<String>(conn)
+                .optional()?,
+            HoldEntity::Deals => deals::table
+                .find(record_id)
+                .select(deals::deal_name)
+                .first::<String>(conn)
+                .optional()?,
+            HoldEntity::Documents => employee_documents::table
+                .inner_join(employees::table)
+                .filter(employee_documents::id.eq(record_id))
+                .select((employee_documents::document_type, employees::name))
+                .first::<(String, String)>(conn)
+                .optional()?
+                .map(|(document_type, employee)| format!("{} of {}", document_type, employee)),
+            HoldEntity::Activities => activities::table
+                .find(record_id)
+                .select(activities::subject)
+                .first::<String>(conn)
+                .optional()?,
+            HoldEntity::Leads => leads::table
+                .find(record_id)
+                .select(leads::title)
+                .first::<String>(conn)
+                .optional()?,
+            HoldEntity::Employees => employees::table
+                .find(record_id)
+                .select(employees::name)
+                .first::<String>(conn)
+                .optional()?,
+            HoldEntity::Notifications | HoldEntity::AuditLogs => None,
+        };
+        Ok(label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hold_entity() {
+        assert_eq!("Customers".parse::<HoldEntity>().unwrap(), HoldEntity::Customers);
+        assert_eq!("employee_documents".parse::<HoldEntity>().unwrap(), HoldEntity::Documents);
+        assert_eq!(HoldEntity::Documents.to_string(), "documents");
+        assert!("payrolls".parse::<HoldEntity>().is_err());
+        for entity in RetentionEntity::ALL {
+            assert_eq!(HoldEntity::from(entity).as_str(), entity.as_str());
+        }
+    }
+}
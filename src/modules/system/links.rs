@@ -0,0 +1,254 @@
+use clap::ValueEnum;
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{
+    campaigns, customers, deals, employees, invoices, leads, products, purchase_orders, record_links,
+    suppliers, transactions,
+};
+use crate::database::{NewRecordLink, RecordLink, RecordType};
+use crate::outln;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Relation recorded when none is given
+pub const DEFAULT_RELATION: &str = "related";
+
+/// A record of any module, written `type:id` on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct RecordRef {
+    pub record_type: RecordType,
+    pub id: i32,
+}
+
+impl RecordRef {
+    pub fn new(record_type: RecordType, id: i32) -> Self {
+        Self { record_type, id }
+    }
+}
+
+impl std::fmt::Display for RecordRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.record_type, self.id)
+    }
+}
+
+impl std::str::FromStr for RecordRef {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let (record_type, id) = value
+            .split_once(':')
+            .ok_or_else(|| format!("expected type:id, e.g. deal:12, got '{}'", value))?;
+        let record_type = RecordType::from_str(record_type.trim(), true).map_err(|_| {
+            let known: Vec<String> = RecordType::value_variants().iter().map(|t| t.to_string()).collect();
+            format!("unknown record type '{}', expected one of: {}", record_type, known.join(", "))
+        })?;
+        let id = id
+            .trim()
+            .parse::<i32>()
+            .map_err(|_| format!("invalid record id '{}'", id))?;
+        Ok(Self { record_type, id })
+    }
+}
+
+/// Clap value parser for `type:id` arguments
+pub fn parse_record_ref_arg(value: &str) -> std::result::Result<RecordRef, String> {
+    value.parse()
+}
+
+/// A record linked to the one being looked at
+#[derive(Debug, Clone, Serialize)]
+pub struct RelatedRecord {
+    pub link: RecordLink,
+    pub record: RecordRef,
+    /// Name or number of the record, `None` if it no longer exists
+    pub label: Option<String>,
+    /// Whether the link was made from the record being looked at
+    pub outgoing: bool,
+}
+
+/// Links between records of different modules.
+///
+/// A link is stored once with the direction it was made in, and shows up
+/// from both ends. Linked records are checked to exist when the link is
+/// made; a record deleted later shows as missing in its related list.
+pub struct LinkService;
+
+impl LinkService {
+    pub fn add(
+        conn: &mut SqliteConnection,
+        source: RecordRef,
+        target: RecordRef,
+        relation: Option<&str>,
+        note: Option<&str>,
+        created_by: Option<i32>,
+    ) -> Result<RecordLink> {
+        if source == target {
+            return Err(CLIERPError::ValidationError("A record cannot be linked to itself".to_string()));
+        }
+        for record in [source, target] {
+            if Self::label(conn, record)?.is_none() {
+                return Err(CLIERPError::NotFound(format!("{} not found", record)));
+            }
+        }
+        if let Some(existing) = Self::find(conn, source, target)? {
+            return Err(CLIERPError::AlreadyExists(format!(
+                "{} and {} are already linked (link {})",
+                source, target, existing.id
+            )));
+        }
+
+        diesel::insert_into(record_links::table)
+            .values(&NewRecordLink {
+                source_type: source.record_type.to_string(),
+                source_id: source.id,
+                target_type: target.record_type.to_string(),
+                target_id: target.id,
+                relation: relation
+                    .map(str::trim)
+                    .filter(|r| !r.is_empty())
+                    .unwrap_or(DEFAULT_RELATION)
+                    .to_string(),
+                note: note.map(|n| n.to_string()),
+                created_by,
+            })
+            .execute(conn)?;
+        let link = record_links::table
+            .order(record_links::id.desc())
+            .first::<RecordLink>(conn)?;
+
+        tracing::info!("Linked {} to {} ({})", source, target, link.relation);
+        Ok(link)
+    }
+
+    /// Remove the link between two records, whichever way it was made
+    pub fn remove(conn: &mut SqliteConnection, source: RecordRef, target: RecordRef) -> Result<()> {
+        let link = Self::find(conn, source, target)?
+            .ok_or_else(|| CLIERPError::NotFound(format!("{} and {} are not linked", source, target)))?;
+        diesel::delete(record_links::table.find(link.id)).execute(conn)?;
+        tracing::info!("Unlinked {} from {}", source, target);
+        Ok(())
+    }
+
+    /// Records linked to `record` in either direction, oldest link first
+    pub fn related(conn: &mut SqliteConnection, record: RecordRef) -> Result<Vec<RelatedRecord>> {
+        let record_type = record.record_type.to_string();
+        let links = record_links::table
+            .filter(
+                record_links::source_type
+                    .eq(&record_type)
+                    .and(record_links::source_id.eq(record.id))
+                    .or(record_links::target_type.eq(&record_type).and(record_links::target_id.eq(record.id))),
+            )
+            .order(record_links::id.asc())
+            .load::<RecordLink>(conn)?;
+
+        let mut related = Vec::with_capacity(links.len());
+        for link in links {
+            let outgoing = link.source_type == record_type && link.source_id == record.id;
+            let (other_type, other_id) = if outgoing {
+                (&link.target_type, link.target_id)
+            } else {
+                (&link.source_type, link.source_id)
+            };
+            let Ok(other_type) = RecordType::from_str(other_type, true) else {
+                tracing::warn!("Link {} points at unknown record type '{}'", link.id, other_type);
+                continue;
+            };
+            let other = RecordRef::new(other_type, other_id);
+            related.push(RelatedRecord {
+                label: Self::label(conn, other)?,
+                record: other,
+                outgoing,
+                link,
+            });
+        }
+        Ok(related)
+    }
+
+    /// Name or number of a record, `None` if it does not exist
+    pub fn label(conn: &mut SqliteConnection, record: RecordRef) -> Result<Option<String>> {
+        let id = record.id;
+        let label: QueryResult<String> = match record.record_type {
+            RecordType::Employee => employees::table.find(id).select(employees::name).first(conn),
+            RecordType::Customer => customers::table.find(id).select(customers::name).first(conn),
+            RecordType::Lead => leads::table.find(id).select(leads::title).first(conn),
+            RecordType::Deal => deals::table.find(id).select(deals::deal_name).first(conn),
+            RecordType::Campaign => campaigns::table.find(id).select(campaigns::name).first(conn),
+            RecordType::Product => products::table.find(id).select(products::name).first(conn),
+            RecordType::Supplier => suppliers::table.find(id).select(suppliers::name).first(conn),
+            RecordType::PurchaseOrder => purchase_orders::table
+                .find(id)
+                .select(purchase_orders::po_number)
+                .first(conn),
+            RecordType::Invoice => invoices::table.find(id).select(invoices::invoice_number).first(conn),
+            RecordType::Transaction => transactions::table
+                .find(id)
+                .select(transactions::description)
+                .first(conn),
+        };
+        label.optional().map_err(Into::into)
+    }
+
+    fn find(conn: &mut SqliteConnection, a: RecordRef, b: RecordRef) -> Result<Option<RecordLink>> {
+        let matches = |from: RecordRef, to: RecordRef| {
+            record_links::source_type
+                .eq(from.record_type.to_string())
+                .and(record_links::source_id.eq(from.id))
+                .and(record_links::target_type.eq(to.record_type.to_string()))
+                .and(record_links::target_id.eq(to.id))
+        };
+        record_links::table
+            .filter(matches(a, b).or(matches(b, a)))
+            .first::<RecordLink>(conn)
+            .optional()
+            .map_err(Into::into)
+    }
+}
+
+/// Render a "Related" section; nothing is printed when there are no links
+pub fn print_related(related: &[RelatedRecord]) {
+    if related.is_empty() {
+        return;
+    }
+
+    println!("\nRelated:");
+    for item in related {
+        outln!(
+            "  {} {:<22} {:<30} {}{}",
+            if item.outgoing { "→" } else { "←" },
+            item.record.to_string(),
+            item.label.as_deref().unwrap_or("(missing)"),
+            item.link.relation,
+            item.link.note.as_deref().map(|n| format!(" — {}", n)).unwrap_or_default()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_record_ref() {
+        assert_eq!(
+            "deal:12".parse::<RecordRef>().unwrap(),
+            RecordRef::new(RecordType::Deal, 12)
+        );
+        assert_eq!(
+            "po:3".parse::<RecordRef>().unwrap(),
+            RecordRef::new(RecordType::PurchaseOrder, 3)
+        );
+        assert_eq!(
+            "Purchase_Order:3".parse::<RecordRef>().unwrap().to_string(),
+            "purchase_order:3"
+        );
+        assert!("deal".parse::<RecordRef>().is_err());
+        assert!("project:1".parse::<RecordRef>().is_err());
+        assert!("deal:x".parse::<RecordRef>().is_err());
+    }
+}
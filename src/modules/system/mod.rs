@@ -0,0 +1,49 @@
+pub mod analyze;
+pub mod anomaly;
+pub mod archive;
+pub mod audit;
+pub mod bench;
+pub mod delivery;
+pub mod doctor;
+pub mod email_templates;
+pub mod events;
+pub mod export_templates;
+pub mod import;
+pub mod integrity;
+pub mod legal_hold;
+pub mod links;
+pub mod notifications;
+pub mod portal;
+pub mod preferences;
+pub mod provisioning;
+pub mod retention;
+pub mod rules;
+pub mod sod;
+pub mod storage;
+pub mod usage;
+pub mod warehouse;
+
+pub use analyze::*;
+pub use anomaly::*;
+pub use archive::*;
+pub use audit::*;
+pub use bench::*;
+pub use delivery::*;
+pub use doctor::*;
+pub use email_templates::*;
+pub use events::*;
+pub use export_templates::*;
+pub use import::*;
+pub use integrity::*;
+pub use legal_hold::*;
+pub use links::*;
+pub use notifications::*;
+pub use portal::*;
+pub use preferences::*;
+pub use provisioning::*;
+pub use retention::*;
+pub use rules::*;
+pub use sod::*;
+pub use storage::*;
+pub use usage::*;
+pub use warehouse::*;
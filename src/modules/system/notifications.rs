@@ -0,0 +1,58 @@
+use chrono::Utc;
+use diesel::prelude::*;
+
+use crate::core::result::CLIERPResult;
+use crate::database::schema::notifications;
+use crate::database::{NewNotification, Notification};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Messages left for users by events that happen while they are away,
+/// such as a supplier answering through the portal
+pub struct NotificationService;
+
+impl NotificationService {
+    pub fn notify(conn: &mut SqliteConnection, user_id: i32, subject: &str, body: Option<&str>) -> Result<()> {
+        diesel::insert_into(notifications::table)
+            .values(&NewNotification {
+                user_id,
+                subject: subject.to_string(),
+                body: body.map(str::to_string),
+            })
+            .execute(conn)?;
+        tracing::info!("Notified user {}: {}", user_id, subject);
+        Ok(())
+    }
+
+    /// Notifications of a user, newest first
+    pub fn list(conn: &mut SqliteConnection, user_id: i32, unread_only: bool) -> Result<Vec<Notification>> {
+        let mut query = notifications::table
+            .filter(notifications::user_id.eq(user_id))
+            .into_boxed();
+        if unread_only {
+            query = query.filter(notifications::read_at.is_null());
+        }
+        Ok(query
+            .order(notifications::created_at.desc())
+            .load::<Notification>(conn)?)
+    }
+
+    pub fn unread_count(conn: &mut SqliteConnection, user_id: i32) -> Result<i64> {
+        Ok(notifications::table
+            .filter(notifications::user_id.eq(user_id))
+            .filter(notifications::read_at.is_null())
+            .count()
+            .get_result(conn)?)
+    }
+
+    pub fn mark_read(conn: &mut SqliteConnection, ids: &[i32]) -> Result<usize> {
+        Ok(diesel::update(
+            notifications::table
+                .filter(notifications::id.eq_any(ids))
+                .filter(notifications::read_at.is_null()),
+        )
+        .set(notifications::read_at.eq(Some(Utc::now().naive_utc())))
+        .execute(conn)?)
+    }
+}
@@ -0,0 +1,155 @@
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::portal_links;
+use crate::database::{NewPortalLink, PortalLink};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// What a portal link gives access to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortalScope {
+    /// One purchase order, for its supplier to confirm or ship
+    SupplierPo,
+    /// Read-only view of a customer's invoices and payments
+    Customer,
+    /// Read-only management dashboard, for the manager it was issued to
+    Dashboard,
+    /// The change event stream, for an external subscriber
+    Events,
+    /// User provisioning from an identity feed, for the admin it was issued to
+    Scim,
+}
+
+impl PortalScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PortalScope::SupplierPo => "supplier_po",
+            PortalScope::Customer => "customer",
+            PortalScope::Dashboard => "dashboard",
+            PortalScope::Events => "events",
+            PortalScope::Scim => "scim",
+        }
+    }
+}
+
+impl std::fmt::Display for PortalScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PortalClaims {
+    jti: String,
+    scope: String,
+    sub: i32,
+    exp: usize,
+}
+
+/// Signed, expiring links that let someone without an account act on one
+/// record. The token is a JWT signed with the auth secret; its ID is also
+/// stored so a link can be revoked before it expires.
+pub struct PortalService;
+
+impl PortalService {
+    /// Create a link to `record_id`, returning it and its token
+    pub fn issue(
+        conn: &mut SqliteConnection,
+        secret: &str,
+        scope: PortalScope,
+        record_id: i32,
+        ttl_days: u32,
+        created_by: Option<i32>,
+    ) -> Result<(PortalLink, String)> {
+        if ttl_days == 0 {
+            return Err(CLIERPError::Validation("A link must stay valid for at least one day".to_string()));
+        }
+        let token_id = uuid::Uuid::new_v4().simple().to_string();
+        let expires_at = Utc::now().naive_utc() + Duration::days(ttl_days as i64);
+        let claims = PortalClaims {
+            jti: token_id.clone(),
+            scope: scope.to_string(),
+            sub: record_id,
+            exp: expires_at.and_utc().timestamp() as usize,
+        };
+        let token = encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(secret.as_ref()))?;
+
+        diesel::insert_into(portal_links::table)
+            .values(&NewPortalLink {
+                token_id: token_id.clone(),
+                scope: scope.to_string(),
+                record_id,
+                expires_at,
+                created_by,
+            })
+            .execute(conn)?;
+        let link = portal_links::table
+            .filter(portal_links::token_id.eq(&token_id))
+            .first::<PortalLink>(conn)?;
+
+        tracing::info!("Issued {} link {} for record {}", scope, link.id, record_id);
+        Ok((link, token))
+    }
+
+    /// The link a token belongs to, if it is signed, of `scope`, not revoked
+    /// and not expired. Records the use.
+    pub fn verify(conn: &mut SqliteConnection, secret: &str, token: &str, scope: PortalScope) -> Result<PortalLink> {
+        let invalid = || CLIERPError::Authentication("This link is invalid or has expired".to_string());
+        let claims = decode::<PortalClaims>(
+            token,
+            &DecodingKey::from_secret(secret.as_ref()),
+            &Validation::new(Algorithm::HS256),
+        )
+        .map_err(|_| invalid())?
+        .claims;
+        if claims.scope != scope.as_str() {
+            return Err(invalid());
+        }
+
+        let link = portal_links::table
+            .filter(portal_links::token_id.eq(&claims.jti))
+            .first::<PortalLink>(conn)
+            .optional()?
+            .ok_or_else(invalid)?;
+        let now = Utc::now().naive_utc();
+        if link.record_id != claims.sub || link.revoked_at.is_some() || link.expires_at <= now {
+            return Err(invalid());
+        }
+
+        diesel::update(portal_links::table.find(link.id))
+            .set(portal_links::last_used_at.eq(Some(now)))
+            .execute(conn)?;
+        Ok(link)
+    }
+
+    pub fn revoke(conn: &mut SqliteConnection, scope: PortalScope, link_id: i32) -> Result<PortalLink> {
+        let link = portal_links::table
+            .find(link_id)
+            .filter(portal_links::scope.eq(scope.as_str()))
+            .first::<PortalLink>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("Portal link {} not found", link_id)))?;
+        if link.revoked_at.is_none() {
+            diesel::update(portal_links::table.find(link_id))
+                .set(portal_links::revoked_at.eq(Some(Utc::now().naive_utc())))
+                .execute(conn)?;
+            tracing::info!("Revoked portal link {}", link_id);
+        }
+        Ok(portal_links::table.find(link_id).first::<PortalLink>(conn)?)
+    }
+
+    /// Links of `scope` for one record, newest first
+    pub fn list(conn: &mut SqliteConnection, scope: PortalScope, record_id: i32) -> Result<Vec<PortalLink>> {
+        Ok(portal_links::table
+            .filter(portal_links::scope.eq(scope.as_str()))
+            .filter(portal_links::record_id.eq(record_id))
+            .order(portal_links::created_at.desc())
+            .load::<PortalLink>(conn)?)
+    }
+}
@@ -0,0 +1,203 @@
+use chrono::Utc;
+use diesel::prelude::*;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::user_preferences;
+use crate::database::{NewUserPreference, UserPreference};
+use crate::utils::i18n::Language;
+use crate::utils::timezone::DisplayTimezone;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Report output formats a user can make their default
+pub const OUTPUT_FORMATS: [&str; 4] = ["json", "csv", "html", "text"];
+
+/// Largest page size a list query returns
+const MAX_PER_PAGE: i64 = 1000;
+
+/// Setting a user can choose for themselves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum PreferenceKey {
+    /// Rows per page of list commands run without --per-page
+    #[value(name = "per_page")]
+    PerPage,
+    /// Report format used without --format (json, csv, html or text)
+    #[value(name = "output_format")]
+    OutputFormat,
+    /// Display timezone, e.g. local, UTC, +09:00
+    #[value(name = "timezone")]
+    Timezone,
+    /// Message language, e.g. en or ko
+    #[value(name = "locale")]
+    Locale,
+}
+
+impl PreferenceKey {
+    pub const ALL: [PreferenceKey; 4] = [
+        PreferenceKey::PerPage,
+        PreferenceKey::OutputFormat,
+        PreferenceKey::Timezone,
+        PreferenceKey::Locale,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PreferenceKey::PerPage => "per_page",
+            PreferenceKey::OutputFormat => "output_format",
+            PreferenceKey::Timezone => "timezone",
+            PreferenceKey::Locale => "locale",
+        }
+    }
+
+    /// Check `value` for this setting and return it as stored
+    pub fn normalize(&self, value: &str) -> Result<String> {
+        let value = value.trim();
+        match self {
+            PreferenceKey::PerPage => match value.parse::<i64>() {
+                Ok(per_page) if (1..=MAX_PER_PAGE).contains(&per_page) => Ok(per_page.to_string()),
+                _ => Err(CLIERPError::Validation(format!(
+                    "per_page must be a number from 1 to {}, got '{}'",
+                    MAX_PER_PAGE, value
+                ))),
+            },
+            PreferenceKey::OutputFormat => {
+                let format = value.to_lowercase();
+                if OUTPUT_FORMATS.contains(&format.as_str()) {
+                    Ok(format)
+                } else {
+                    Err(CLIERPError::Validation(format!(
+                        "output_format must be one of {}, got '{}'",
+                        OUTPUT_FORMATS.join(", "),
+                        value
+                    )))
+                }
+            }
+            PreferenceKey::Timezone => {
+                value.parse::<DisplayTimezone>()?;
+                Ok(value.to_string())
+            }
+            PreferenceKey::Locale => {
+                value.parse::<Language>()?;
+                Ok(value.to_string())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for PreferenceKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Preferences of one user; unset ones fall back to the configuration
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserPreferences {
+    pub per_page: Option<i64>,
+    pub output_format: Option<String>,
+    pub timezone: Option<String>,
+    pub locale: Option<String>,
+}
+
+impl UserPreferences {
+    pub fn get(&self, key: PreferenceKey) -> Option<String> {
+        match key {
+            PreferenceKey::PerPage => self.per_page.map(|n| n.to_string()),
+            PreferenceKey::OutputFormat => self.output_format.clone(),
+            PreferenceKey::Timezone => self.timezone.clone(),
+            PreferenceKey::Locale => self.locale.clone(),
+        }
+    }
+
+    fn insert(&mut self, key: PreferenceKey, value: String) {
+        match key {
+            PreferenceKey::PerPage => self.per_page = value.parse().ok(),
+            PreferenceKey::OutputFormat => self.output_format = Some(value),
+            PreferenceKey::Timezone => self.timezone = Some(value),
+            PreferenceKey::Locale => self.locale = Some(value),
+        }
+    }
+}
+
+/// Per-user preferences, loaded for the logged-in user when the CLI starts
+/// and taking precedence over the configuration file for that user only.
+pub struct PreferenceService;
+
+impl PreferenceService {
+    pub fn load(conn: &mut SqliteConnection, user_id: i32) -> Result<UserPreferences> {
+        let rows = user_preferences::table
+            .filter(user_preferences::user_id.eq(user_id))
+            .load::<UserPreference>(conn)?;
+
+        let mut preferences = UserPreferences::default();
+        for row in rows {
+            // Rows are checked when set; skip any that no longer parse
+            let key = PreferenceKey::ALL.into_iter().find(|key| key.name() == row.name);
+            if let Some(key) = key {
+                if let Ok(value) = key.normalize(&row.value) {
+                    preferences.insert(key, value);
+                }
+            }
+        }
+        Ok(preferences)
+    }
+
+    /// Store `value` for `key`, returning it as stored
+    pub fn set(conn: &mut SqliteConnection, user_id: i32, key: PreferenceKey, value: &str) -> Result<String> {
+        let value = key.normalize(value)?;
+        let updated = diesel::update(
+            user_preferences::table
+                .filter(user_preferences::user_id.eq(user_id))
+                .filter(user_preferences::name.eq(key.name())),
+        )
+        .set((
+            user_preferences::value.eq(&value),
+            user_preferences::updated_at.eq(Utc::now().naive_utc()),
+        ))
+        .execute(conn)?;
+        if updated == 0 {
+            diesel::insert_into(user_preferences::table)
+                .values(&NewUserPreference {
+                    user_id,
+                    name: key.name().to_string(),
+                    value: value.clone(),
+                })
+                .execute(conn)?;
+        }
+
+        tracing::info!("User {} set preference {} to {}", user_id, key, value);
+        Ok(value)
+    }
+
+    /// Remove the preference, returning whether one was set
+    pub fn unset(conn: &mut SqliteConnection, user_id: i32, key: PreferenceKey) -> Result<bool> {
+        let deleted = diesel::delete(
+            user_preferences::table
+                .filter(user_preferences::user_id.eq(user_id))
+                .filter(user_preferences::name.eq(key.name())),
+        )
+        .execute(conn)?;
+
+        tracing::info!("User {} cleared preference {}", user_id, key);
+        Ok(deleted > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(PreferenceKey::PerPage.normalize(" 50 ").unwrap(), "50");
+        assert!(PreferenceKey::PerPage.normalize("0").is_err());
+        assert!(PreferenceKey::PerPage.normalize("lots").is_err());
+        assert_eq!(PreferenceKey::OutputFormat.normalize("CSV").unwrap(), "csv");
+        assert!(PreferenceKey::OutputFormat.normalize("pdf").is_err());
+        assert_eq!(PreferenceKey::Timezone.normalize("+09:00").unwrap(), "+09:00");
+        assert_eq!(PreferenceKey::Locale.normalize("ko_KR.UTF-8").unwrap(), "ko_KR.UTF-8");
+        assert!(PreferenceKey::Locale.normalize("klingon").is_err());
+    }
+}
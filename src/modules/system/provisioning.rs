@@ -0,0 +1,361 @@
+//! Users kept in step with an external identity feed, such as an HR system
+//! pushing SCIM 2.0 requests to `/scim/v2/Users` in server mode. Provisioned
+//! users log in through the directory; the feed never sets passwords. A user
+//! is linked to the employee with the feed's employee number, else to the
+//! employee with the same email address. Admin users are managed in CLIERP
+//! only: the feed can neither grant the admin role nor change an admin.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use serde_json::{json, Value};
+
+use crate::core::auth::{parse_role, DIRECTORY_PASSWORD_HASH};
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::models::{NewUser, User, UserRole};
+use crate::database::schema::{employees, users};
+use crate::database::DatabaseConnection;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+pub const SCIM_USER_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+pub const SCIM_ENTERPRISE_SCHEMA: &str = "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User";
+pub const SCIM_LIST_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+pub const SCIM_ERROR_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+
+/// Most users returned by one list request
+pub const MAX_USERS_PER_PAGE: i64 = 200;
+
+/// A user as the identity feed describes them
+#[derive(Debug, Clone)]
+pub struct FeedUser {
+    pub username: String,
+    pub email: Option<String>,
+    pub active: bool,
+    /// Role to give the user; new users without one are employees
+    pub role: Option<UserRole>,
+    pub employee_number: Option<String>,
+}
+
+pub struct ProvisioningService;
+
+impl ProvisioningService {
+    pub fn create(conn: &mut DatabaseConnection, feed: &FeedUser) -> Result<User> {
+        if Self::find_by_username(conn, &feed.username)?.is_some() {
+            return Err(CLIERPError::AlreadyExists(format!("User {} already exists", feed.username)));
+        }
+        let employee_id = Self::linked_employee(conn, feed)?;
+        let role = feed.role.clone().unwrap_or(UserRole::Employee);
+
+        diesel::insert_into(users::table)
+            .values(&NewUser {
+                username: feed.username.clone(),
+                email: feed.email.clone().unwrap_or_else(|| format!("{}@clierp.local", feed.username)),
+                password_hash: DIRECTORY_PASSWORD_HASH.to_string(),
+                employee_id,
+                role: role.to_string(),
+                is_active: feed.active,
+            })
+            .execute(conn)?;
+        let user = users::table
+            .filter(users::username.eq(&feed.username))
+            .first::<User>(conn)?;
+
+        tracing::info!("Provisioned user {} ({}) linked to employee {:?}", user.username, role, employee_id);
+        Ok(user)
+    }
+
+    /// Bring a user in line with the feed; the username cannot change
+    pub fn replace(conn: &mut DatabaseConnection, user_id: i32, feed: &FeedUser) -> Result<User> {
+        let user = Self::get_managed(conn, user_id)?;
+        if feed.username != user.username {
+            return Err(CLIERPError::Validation(format!(
+                "User {} cannot be renamed to {}",
+                user.username, feed.username
+            )));
+        }
+        let employee_id = Self::linked_employee(conn, feed)?.or(user.employee_id);
+        let role = feed.role.as_ref().map(UserRole::to_string).unwrap_or(user.role.clone());
+
+        diesel::update(users::table.find(user.id))
+            .set((
+                users::email.eq(feed.email.clone().unwrap_or(user.email.clone())),
+                users::role.eq(&role),
+                users::employee_id.eq(employee_id),
+                users::is_active.eq(feed.active),
+                users::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+
+        if user.is_active && !feed.active {
+            tracing::info!("Deactivated user {} from the identity feed", user.username);
+        }
+        Ok(users::table.find(user.id).first::<User>(conn)?)
+    }
+
+    pub fn set_active(conn: &mut DatabaseConnection, user_id: i32, active: bool) -> Result<User> {
+        let user = Self::get_managed(conn, user_id)?;
+        let feed = Self::feed_user(conn, &user)?;
+        Self::replace(conn, user.id, &FeedUser { active, ..feed })
+    }
+
+    pub fn get(conn: &mut DatabaseConnection, user_id: i32) -> Result<User> {
+        users::table
+            .find(user_id)
+            .first::<User>(conn)
+            .optional()?
+            .ok_or_else(|| CLIERPError::NotFound(format!("User {} not found", user_id)))
+    }
+
+    pub fn find_by_username(conn: &mut DatabaseConnection, username: &str) -> Result<Option<User>> {
+        Ok(users::table
+            .filter(users::username.eq(username))
+            .first::<User>(conn)
+            .optional()?)
+    }
+
+    /// One page of users by ID, with the total; `start` counts from 1
+    pub fn list(conn: &mut DatabaseConnection, start: i64, count: i64) -> Result<(i64, Vec<User>)> {
+        let total = users::table.count().get_result::<i64>(conn)?;
+        let users = users::table
+            .order(users::id.asc())
+            .offset(start.max(1) - 1)
+            .limit(count.clamp(0, MAX_USERS_PER_PAGE))
+            .load::<User>(conn)?;
+        Ok((total, users))
+    }
+
+    /// The user as the feed would describe them, to apply partial changes to
+    pub fn feed_user(conn: &mut DatabaseConnection, user: &User) -> Result<FeedUser> {
+        Ok(FeedUser {
+            username: user.username.clone(),
+            email: Some(user.email.clone()),
+            active: user.is_active,
+            role: parse_role(&user.role),
+            employee_number: Self::employee_code(conn, user)?,
+        })
+    }
+
+    pub fn employee_code(conn: &mut DatabaseConnection, user: &User) -> Result<Option<String>> {
+        match user.employee_id {
+            Some(employee_id) => Ok(employees::table
+                .find(employee_id)
+                .select(employees::employee_code)
+                .first::<String>(conn)
+                .optional()?),
+            None => Ok(None),
+        }
+    }
+
+    fn get_managed(conn: &mut DatabaseConnection, user_id: i32) -> Result<User> {
+        let user = Self::get(conn, user_id)?;
+        if matches!(parse_role(&user.role), Some(UserRole::Admin)) {
+            return Err(CLIERPError::Authorization(format!(
+                "{} is an admin; admins are managed in CLIERP",
+                user.username
+            )));
+        }
+        Ok(user)
+    }
+
+    /// Employee with the feed's employee number, else with its email address
+    fn linked_employee(conn: &mut DatabaseConnection, feed: &FeedUser) -> Result<Option<i32>> {
+        if let Some(number) = &feed.employee_number {
+            return employees::table
+                .filter(employees::employee_code.eq(number))
+                .select(employees::id)
+                .first::<i32>(conn)
+                .optional()?
+                .map(Some)
+                .ok_or_else(|| CLIERPError::NotFound(format!("No employee with number {}", number)));
+        }
+        match &feed.email {
+            Some(email) => Ok(employees::table
+                .filter(employees::email.eq(email))
+                .select(employees::id)
+                .first::<i32>(conn)
+                .optional()?),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Read a SCIM User resource
+pub fn parse_scim_user(resource: &Value) -> Result<FeedUser> {
+    let invalid = |message: &str| CLIERPError::Validation(message.to_string());
+    let username = resource
+        .get("userName")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| invalid("userName is required"))?;
+
+    let emails = resource.get("emails").and_then(Value::as_array);
+    let email = emails
+        .and_then(|emails| {
+            emails
+                .iter()
+                .find(|e| e.get("primary").and_then(Value::as_bool) == Some(true))
+                .or_else(|| emails.first())
+        })
+        .and_then(|e| e.get("value"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+
+    let role = match resource
+        .get("roles")
+        .and_then(Value::as_array)
+        .and_then(|roles| roles.first())
+        .and_then(|role| role.get("value").or(Some(role)))
+        .and_then(Value::as_str)
+    {
+        Some(name) => Some(check_feed_role(name)?),
+        None => None,
+    };
+
+    Ok(FeedUser {
+        username: username.to_string(),
+        email,
+        active: resource.get("active").and_then(Value::as_bool).unwrap_or(true),
+        role,
+        employee_number: resource
+            .get(SCIM_ENTERPRISE_SCHEMA)
+            .and_then(|extension| extension.get("employeeNumber"))
+            .and_then(Value::as_str)
+            .map(str::to_string),
+    })
+}
+
+/// Apply a SCIM PatchOp: `replace` or `add` of `active`, `emails`, `roles`
+/// or the employee number, with a path or as an object of attributes
+pub fn apply_scim_patch(feed: &mut FeedUser, patch: &Value) -> Result<()> {
+    let operations = patch
+        .get("Operations")
+        .and_then(Value::as_array)
+        .ok_or_else(|| CLIERPError::Validation("Operations is required".to_string()))?;
+
+    for operation in operations {
+        let op = operation.get("op").and_then(Value::as_str).unwrap_or_default().to_lowercase();
+        if op != "replace" && op != "add" {
+            return Err(CLIERPError::Validation(format!("Unsupported patch operation '{}'", op)));
+        }
+        let value = operation.get("value").cloned().unwrap_or(Value::Null);
+        let attributes = match operation.get("path").and_then(Value::as_str) {
+            Some(path) => json!({ path: value }),
+            None => value,
+        };
+        let Some(attributes) = attributes.as_object() else {
+            return Err(CLIERPError::Validation("Patch value must be an object without a path".to_string()));
+        };
+
+        for (path, value) in attributes {
+            match path.as_str() {
+                "active" => {
+                    feed.active = value
+                        .as_bool()
+                        .or_else(|| value.as_str().and_then(|s| s.to_lowercase().parse().ok()))
+                        .ok_or_else(|| CLIERPError::Validation("active must be true or false".to_string()))?;
+                }
+                "emails" | "emails[type eq \"work\"].value" => {
+                    let probe = json!({ "userName": feed.username, "emails": value });
+                    feed.email = parse_scim_user(&probe)?.email.or_else(|| value.as_str().map(str::to_string));
+                }
+                "roles" => {
+                    let probe = json!({ "userName": feed.username, "roles": value });
+                    feed.role = parse_scim_user(&probe)?.role;
+                }
+                path if path == format!("{}:employeeNumber", SCIM_ENTERPRISE_SCHEMA) => {
+                    feed.employee_number = value.as_str().map(str::to_string);
+                }
+                path => {
+                    return Err(CLIERPError::Validation(format!("Attribute '{}' cannot be changed", path)));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// SCIM User resource for a user
+pub fn scim_user_json(user: &User, employee_number: Option<&str>) -> Value {
+    let mut resource = json!({
+        "schemas": [SCIM_USER_SCHEMA],
+        "id": user.id.to_string(),
+        "userName": user.username,
+        "active": user.is_active,
+        "emails": [{ "value": user.email, "primary": true }],
+        "roles": [{ "value": user.role, "primary": true }],
+        "meta": {
+            "resourceType": "User",
+            "created": user.created_at.and_utc().to_rfc3339(),
+            "lastModified": user.updated_at.and_utc().to_rfc3339(),
+        },
+    });
+    if let Some(number) = employee_number {
+        resource["schemas"] = json!([SCIM_USER_SCHEMA, SCIM_ENTERPRISE_SCHEMA]);
+        resource[SCIM_ENTERPRISE_SCHEMA] = json!({ "employeeNumber": number });
+    }
+    resource
+}
+
+/// Username in a `userName eq "..."` filter, the one filter identity
+/// providers send to look a user up before creating them
+pub fn parse_username_filter(filter: &str) -> Option<String> {
+    let mut parts = filter.trim().splitn(3, ' ');
+    let attribute = parts.next()?;
+    let operator = parts.next()?;
+    let value = parts.next()?.trim();
+    if !attribute.eq_ignore_ascii_case("userName") || !operator.eq_ignore_ascii_case("eq") {
+        return None;
+    }
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .map(|v| v.replace("\\\"", "\""))
+}
+
+fn check_feed_role(name: &str) -> Result<UserRole> {
+    match parse_role(&name.trim().to_lowercase()) {
+        Some(UserRole::Admin) => Err(CLIERPError::Authorization(
+            "The admin role cannot be given through the identity feed".to_string(),
+        )),
+        Some(role) => Ok(role),
+        None => Err(CLIERPError::Validation(format!("Unknown role '{}'", name))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scim_user_and_patch() {
+        let resource = json!({
+            "schemas": [SCIM_USER_SCHEMA, SCIM_ENTERPRISE_SCHEMA],
+            "userName": " kim ",
+            "emails": [{ "value": "kim@home.example" }, { "value": "kim@example.com", "primary": true }],
+            "roles": [{ "value": "Manager" }],
+            SCIM_ENTERPRISE_SCHEMA: { "employeeNumber": "E042" },
+        });
+        let mut feed = parse_scim_user(&resource).unwrap();
+        assert_eq!(feed.username, "kim");
+        assert_eq!(feed.email.as_deref(), Some("kim@example.com"));
+        assert!(feed.active);
+        assert!(matches!(feed.role, Some(UserRole::Manager)));
+        assert_eq!(feed.employee_number.as_deref(), Some("E042"));
+        assert!(parse_scim_user(&json!({ "userName": "root", "roles": ["admin"] })).is_err());
+        assert!(parse_scim_user(&json!({ "emails": [] })).is_err());
+
+        let patch = json!({ "Operations": [{ "op": "Replace", "path": "active", "value": "False" }] });
+        apply_scim_patch(&mut feed, &patch).unwrap();
+        assert!(!feed.active);
+        let patch = json!({ "Operations": [{ "op": "replace", "value": { "active": true, "roles": [{ "value": "auditor" }] } }] });
+        apply_scim_patch(&mut feed, &patch).unwrap();
+        assert!(feed.active && matches!(feed.role, Some(UserRole::Auditor)));
+        assert!(apply_scim_patch(&mut feed, &json!({ "Operations": [{ "op": "remove", "path": "emails" }] })).is_err());
+
+        assert_eq!(parse_username_filter("userName eq \"kim\""), Some("kim".to_string()));
+        assert_eq!(parse_username_filter("emails co \"kim\""), None);
+    }
+}
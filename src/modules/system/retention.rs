@@ -0,0 +1,374 @@
+use std::collections::HashSet;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use diesel::prelude::*;
+
+use crate::core::config::{RetentionConfig, RetentionPolicyConfig};
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{activities, audit_logs, employees, leads, notifications, retention_runs};
+use crate::database::{DatabaseConnection, NewRetentionRun, RetentionRun};
+use crate::utils::timezone::DisplayTimezone;
+
+use super::legal_hold::LegalHoldService;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Records changed per statement, below SQLite's bound parameter limit
+const BATCH_SIZE: usize = 500;
+
+/// Name anonymized employees get, followed by their employee code
+const ANONYMIZED_EMPLOYEE: &str = "Former employee";
+/// Title anonymized leads get, followed by their ID
+const ANONYMIZED_LEAD: &str = "Anonymized lead";
+
+/// Kinds of records retention policies apply to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionEntity {
+    /// CRM activities, by activity date
+    Activities,
+    /// Closed leads, by last update
+    Leads,
+    /// Terminated employees, by termination date
+    Employees,
+    /// User notifications, by creation
+    Notifications,
+    /// Field change history, by change time
+    AuditLogs,
+}
+
+impl RetentionEntity {
+    pub const ALL: [RetentionEntity; 5] = [
+        RetentionEntity::Activities,
+        RetentionEntity::Leads,
+        RetentionEntity::Employees,
+        RetentionEntity::Notifications,
+        RetentionEntity::AuditLogs,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetentionEntity::Activities => "activities",
+            RetentionEntity::Leads => "leads",
+            RetentionEntity::Employees => "employees",
+            RetentionEntity::Notifications => "notifications",
+            RetentionEntity::AuditLogs => "audit_logs",
+        }
+    }
+
+    /// Whether records of this kind can be deleted or anonymized; employees
+    /// and leads are referenced from too many places to delete
+    pub fn supports(&self, action: RetentionAction) -> bool {
+        match self {
+            RetentionEntity::Activities | RetentionEntity::Notifications | RetentionEntity::AuditLogs => {
+                action == RetentionAction::Delete
+            }
+            RetentionEntity::Leads | RetentionEntity::Employees => action == RetentionAction::Anonymize,
+        }
+    }
+}
+
+impl std::fmt::Display for RetentionEntity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for RetentionEntity {
+    type Err = CLIERPError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        RetentionEntity::ALL
+            .into_iter()
+            .find(|entity| entity.as_str() == s.trim().to_lowercase())
+            .ok_or_else(|| {
+                CLIERPError::Validation(format!(
+                    "Unknown retention entity '{}'; use activities, leads, employees, notifications or audit_logs",
+                    s
+                ))
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionAction {
+    Delete,
+    /// Clear names and contact details, keeping the record for totals
+    Anonymize,
+}
+
+impl std::fmt::Display for RetentionAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RetentionAction::Delete => write!(f, "delete"),
+            RetentionAction::Anonymize => write!(f, "anonymize"),
+        }
+    }
+}
+
+impl std::str::FromStr for RetentionAction {
+    type Err = CLIERPError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "delete" => Ok(RetentionAction::Delete),
+            "anonymize" => Ok(RetentionAction::Anonymize),
+            _ => Err(CLIERPError::Validation(format!(
+                "Unknown retention action '{}'; use delete or anonymize",
+                s
+            ))),
+        }
+    }
+}
+
+/// A checked `retention.policies` entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    pub entity: RetentionEntity,
+    pub action: RetentionAction,
+    pub days: u32,
+}
+
+impl RetentionPolicy {
+    pub fn from_config(config: &RetentionPolicyConfig) -> Result<Self> {
+        let entity: RetentionEntity = config.entity.parse()?;
+        let action: RetentionAction = config.action.parse()?;
+        if !entity.supports(action) {
+            return Err(CLIERPError::Validation(format!("Retention cannot {} {}", action, entity)));
+        }
+        if config.days == 0 {
+            return Err(CLIERPError::Validation(format!(
+                "Retention days for {} must be at least 1",
+                entity
+            )));
+        }
+        Ok(Self { entity, action, days: config.days })
+    }
+
+    /// Every configured policy; one policy per entity
+    pub fn all_from_config(config: &RetentionConfig) -> Result<Vec<Self>> {
+        let mut seen = HashSet::new();
+        let mut policies = Vec::with_capacity(config.policies.len());
+        for entry in &config.policies {
+            let policy = Self::from_config(entry)?;
+            if !seen.insert(policy.entity.as_str()) {
+                return Err(CLIERPError::Validation(format!(
+                    "{} has more than one retention policy",
+                    policy.entity
+                )));
+            }
+            policies.push(policy);
+        }
+        Ok(policies)
+    }
+
+    /// First business date whose records are kept
+    pub fn keep_from(&self, today: NaiveDate) -> NaiveDate {
+        today - Duration::days(self.days as i64)
+    }
+}
+
+/// What a retention run did (or, in preview, would do) for one policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionOutcome {
+    pub policy: RetentionPolicy,
+    pub cutoff: NaiveDateTime,
+    pub affected: i64,
+    /// Old enough, but under a legal hold
+    pub held: i64,
+}
+
+/// Deletes or anonymizes records older than the configured retention,
+/// except those under a legal hold or attached to a held customer, deal or
+/// document. Every run that changes data is recorded in `retention_runs`.
+pub struct RetentionService;
+
+impl RetentionService {
+    pub fn run(
+        conn: &mut DatabaseConnection,
+        policies: &[RetentionPolicy],
+        today: NaiveDate,
+        preview: bool,
+        run_by: Option<i32>,
+    ) -> Result<Vec<RetentionOutcome>> {
+        conn.transaction::<_, CLIERPError, _>(|conn| {
+            let mut outcomes = Vec::with_capacity(policies.len());
+            for policy in policies {
+                let keep_from = policy.keep_from(today);
+                let cutoff = DisplayTimezone::current().day_start_utc(keep_from);
+                let held = LegalHoldService::protected_ids(conn, policy.entity.into())?;
+                let (ids, on_hold): (Vec<i32>, Vec<i32>) = Self::expired_ids(conn, policy.entity, keep_from, cutoff)?
+                    .into_iter()
+                    .partition(|id| !held.contains(id));
+
+                if !preview {
+                    for batch in ids.chunks(BATCH_SIZE) {
+                        Self::apply(conn, *policy, batch)?;
+                    }
+                    diesel::insert_into(retention_runs::table)
+                        .values(&NewRetentionRun {
+                            entity: policy.entity.to_string(),
+                            action: policy.action.to_string(),
+                            cutoff,
+                            rows_affected: ids.len() as i32,
+                            rows_held: on_hold.len() as i32,
+                            run_by,
+                        })
+                        .execute(conn)?;
+                    tracing::info!(
+                        "Retention: {} {} {} before {}, {} held",
+                        policy.action,
+                        ids.len(),
+                        policy.entity,
+                        cutoff,
+                        on_hold.len()
+                    );
+                }
+                outcomes.push(RetentionOutcome {
+                    policy: *policy,
+                    cutoff,
+                    affected: ids.len() as i64,
+                    held: on_hold.len() as i64,
+                });
+            }
+            Ok(outcomes)
+        })
+    }
+
+    /// Past retention runs, newest first
+    pub fn history(conn: &mut DatabaseConnection, limit: i64) -> Result<Vec<RetentionRun>> {
+        retention_runs::table
+            .order((retention_runs::run_at.desc(), retention_runs::id.desc()))
+            .limit(limit)
+            .load::<RetentionRun>(conn)
+            .map_err(Into::into)
+    }
+
+    /// Records past retention that are not yet deleted or anonymized
+    fn expired_ids(
+        conn: &mut DatabaseConnection,
+        entity: RetentionEntity,
+        keep_from: NaiveDate,
+        cutoff: NaiveDateTime,
+    ) -> Result<Vec<i32>> {
+        let ids = match entity {
+            RetentionEntity::Activities => activities::table
+                .filter(activities::activity_date.lt(cutoff))
+                .select(activities::id)
+                .load::<i32>(conn)?,
+            RetentionEntity::Leads => leads::table
+                .filter(leads::status.eq_any(["closed_won", "closed_lost"]))
+                .filter(leads::updated_at.lt(cutoff))
+                .filter(
+                    leads::title
+                        .not_like(format!("{}%", ANONYMIZED_LEAD))
+                        .or(leads::description.is_not_null())
+                        .or(leads::notes.is_not_null()),
+                )
+                .select(leads::id)
+                .load::<i32>(conn)?,
+            RetentionEntity::Employees => employees::table
+                .filter(employees::status.eq("terminated"))
+                .filter(
+                    employees::termination_date
+                        .lt(keep_from)
+                        // Employees terminated before termination dates were recorded
+                        .or(employees::termination_date.is_null().and(employees::updated_at.lt(cutoff))),
+                )
+                .filter(
+                    employees::name
+                        .not_like(format!("{}%", ANONYMIZED_EMPLOYEE))
+                        .or(employees::email.is_not_null())
+                        .or(employees::phone.is_not_null()),
+                )
+                .select(employees::id)
+                .load::<i32>(conn)?,
+            RetentionEntity::Notifications => notifications::table
+                .filter(notifications::created_at.lt(cutoff))
+                .select(notifications::id)
+                .load::<i32>(conn)?,
+            RetentionEntity::AuditLogs => audit_logs::table
+                .filter(audit_logs::changed_at.lt(cutoff))
+                .select(audit_logs::id)
+                .load::<i32>(conn)?,
+        };
+        Ok(ids)
+    }
+
+    fn apply(conn: &mut DatabaseConnection, policy: RetentionPolicy, ids: &[i32]) -> Result<()> {
+        let now = Utc::now().naive_utc();
+        match (policy.entity, policy.action) {
+            (RetentionEntity::Activities, RetentionAction::Delete) => {
+                diesel::delete(activities::table.filter(activities::id.eq_any(ids))).execute(conn)?;
+            }
+            (RetentionEntity::Notifications, RetentionAction::Delete) => {
+                diesel::delete(notifications::table.filter(notifications::id.eq_any(ids))).execute(conn)?;
+            }
+            (RetentionEntity::AuditLogs, RetentionAction::Delete) => {
+                diesel::delete(audit_logs::table.filter(audit_logs::id.eq_any(ids))).execute(conn)?;
+            }
+            (RetentionEntity::Leads, RetentionAction::Anonymize) => {
+                for id in ids {
+                    diesel::update(leads::table.find(id))
+                        .set((
+                            leads::title.eq(format!("{} {}", ANONYMIZED_LEAD, id)),
+                            leads::description.eq(None::<String>),
+                            leads::notes.eq(None::<String>),
+                            leads::updated_at.eq(now),
+                        ))
+                        .execute(conn)?;
+                }
+            }
+            (RetentionEntity::Employees, RetentionAction::Anonymize) => {
+                let codes = employees::table
+                    .filter(employees::id.eq_any(ids))
+                    .select((employees::id, employees::employee_code))
+                    .load::<(i32, String)>(conn)?;
+                for (id, code) in codes {
+                    diesel::update(employees::table.find(id))
+                        .set((
+                            employees::name.eq(format!("{} {}", ANONYMIZED_EMPLOYEE, code)),
+                            employees::email.eq(None::<String>),
+                            employees::phone.eq(None::<String>),
+                            employees::updated_at.eq(now),
+                        ))
+                        .execute(conn)?;
+                }
+            }
+            (entity, action) => {
+                return Err(CLIERPError::Validation(format!("Retention cannot {} {}", action, entity)));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retention_policy_from_config() {
+        let entry = |entity: &str, action: &str, days: u32| RetentionPolicyConfig {
+            entity: entity.to_string(),
+            action: action.to_string(),
+            days,
+        };
+        let policy = RetentionPolicy::from_config(&entry("Activities", "delete", 1095)).unwrap();
+        assert_eq!(policy.entity, RetentionEntity::Activities);
+        assert_eq!(
+            policy.keep_from(NaiveDate::from_ymd_opt(2025, 3, 1).unwrap()),
+            NaiveDate::from_ymd_opt(2022, 3, 2).unwrap()
+        );
+        assert!(RetentionPolicy::from_config(&entry("employees", "anonymize", 2555)).is_ok());
+        assert!(RetentionPolicy::from_config(&entry("employees", "delete", 2555)).is_err());
+        assert!(RetentionPolicy::from_config(&entry("payrolls", "delete", 30)).is_err());
+        assert!(RetentionPolicy::from_config(&entry("notifications", "delete", 0)).is_err());
+
+        let config = RetentionConfig {
+            policies: vec![entry("audit_logs", "delete", 365), entry("audit_logs", "delete", 730)],
+        };
+        assert!(RetentionPolicy::all_from_config(&config).is_err());
+    }
+}
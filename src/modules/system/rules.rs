@@ -0,0 +1,124 @@
+use chrono::Utc;
+use diesel::prelude::*;
+
+use crate::core::result::CLIERPResult;
+use crate::database::schema::validation_rules;
+use crate::database::{DatabaseConnection, NewValidationRule, RuleType, ValidationRule};
+use crate::utils::validation::{apply_rules, validate_required_string, validate_rule_definition};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Entities whose services evaluate configured validation rules
+pub const RULE_ENTITIES: &[&str] = &["product", "customer", "deal"];
+
+pub struct ValidationRuleService;
+
+impl ValidationRuleService {
+    /// Create or replace the rule for an entity field and rule type
+    pub fn set_rule(
+        conn: &mut DatabaseConnection,
+        entity: &str,
+        field: &str,
+        rule_type: RuleType,
+        value: Option<&str>,
+        message: Option<&str>,
+    ) -> Result<ValidationRule> {
+        validate_required_string(field, "field")?;
+        if !RULE_ENTITIES.contains(&entity) {
+            return Err(crate::core::error::CLIERPError::Validation(format!(
+                "Unknown entity '{}'. Use one of: {}",
+                entity,
+                RULE_ENTITIES.join(", ")
+            )));
+        }
+        validate_rule_definition(rule_type, value)?;
+
+        let existing = validation_rules::table
+            .filter(validation_rules::entity.eq(entity))
+            .filter(validation_rules::field.eq(field))
+            .filter(validation_rules::rule_type.eq(rule_type.to_string()))
+            .first::<ValidationRule>(conn)
+            .optional()?;
+
+        match existing {
+            Some(rule) => {
+                diesel::update(validation_rules::table.find(rule.id))
+                    .set((
+                        validation_rules::value.eq(value),
+                        validation_rules::message.eq(message),
+                        validation_rules::is_active.eq(true),
+                        validation_rules::updated_at.eq(Utc::now().naive_utc()),
+                    ))
+                    .execute(conn)?;
+            }
+            None => {
+                let new_rule = NewValidationRule {
+                    entity: entity.to_string(),
+                    field: field.to_string(),
+                    rule_type: rule_type.to_string(),
+                    value: value.map(|s| s.to_string()),
+                    message: message.map(|s| s.to_string()),
+                    is_active: true,
+                };
+                diesel::insert_into(validation_rules::table)
+                    .values(&new_rule)
+                    .execute(conn)?;
+            }
+        }
+
+        let rule = validation_rules::table
+            .filter(validation_rules::entity.eq(entity))
+            .filter(validation_rules::field.eq(field))
+            .filter(validation_rules::rule_type.eq(rule_type.to_string()))
+            .first::<ValidationRule>(conn)?;
+
+        tracing::info!("Set validation rule {}.{} {} (ID {})", entity, field, rule_type, rule.id);
+        Ok(rule)
+    }
+
+    pub fn list_rules(conn: &mut DatabaseConnection, entity: Option<&str>) -> Result<Vec<ValidationRule>> {
+        let mut query = validation_rules::table.into_boxed();
+        if let Some(entity) = entity {
+            query = query.filter(validation_rules::entity.eq(entity));
+        }
+
+        let rules = query
+            .order((validation_rules::entity.asc(), validation_rules::field.asc()))
+            .load::<ValidationRule>(conn)?;
+        Ok(rules)
+    }
+
+    /// Active rules for one entity, in the order they are evaluated
+    pub fn rules_for(conn: &mut DatabaseConnection, entity: &str) -> Result<Vec<ValidationRule>> {
+        let rules = validation_rules::table
+            .filter(validation_rules::entity.eq(entity))
+            .filter(validation_rules::is_active.eq(true))
+            .order(validation_rules::id.asc())
+            .load::<ValidationRule>(conn)?;
+        Ok(rules)
+    }
+
+    pub fn remove_rule(conn: &mut DatabaseConnection, rule_id: i32) -> Result<()> {
+        let deleted = diesel::delete(validation_rules::table.find(rule_id)).execute(conn)?;
+        if deleted == 0 {
+            return Err(crate::core::error::CLIERPError::NotFound(format!(
+                "Validation rule with ID {} not found",
+                rule_id
+            )));
+        }
+
+        tracing::info!("Removed validation rule {}", rule_id);
+        Ok(())
+    }
+
+    /// Evaluate the configured rules for an entity against named field values
+    pub fn validate(
+        conn: &mut DatabaseConnection,
+        entity: &str,
+        fields: &[(&str, Option<&str>)],
+    ) -> Result<()> {
+        let rules = Self::rules_for(conn, entity)?;
+        apply_rules(&rules, fields)
+    }
+}
@@ -0,0 +1,217 @@
+use chrono::Utc;
+use diesel::prelude::*;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{sod_overrides, sod_rules};
+use crate::database::{NewSodOverride, NewSodRuleSetting, SodEnforcement, SodOverride, SodRuleSetting};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Pair of steps on one record that should be done by different users
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SodRule {
+    /// The creator of a purchase order cannot approve it
+    #[value(name = "po_create_approve")]
+    PoCreateApprove,
+    /// The preparer of a payroll cannot release its payment
+    #[value(name = "payroll_prepare_pay")]
+    PayrollPreparePay,
+    /// The requester of a stock adjustment cannot approve it
+    #[value(name = "stock_adjustment_request_approve")]
+    StockAdjustmentRequestApprove,
+    /// The proposer of a salary raise cannot approve it
+    #[value(name = "raise_propose_approve")]
+    RaiseProposeApprove,
+    /// The proposer of a stock write-down cannot approve it
+    #[value(name = "write_down_propose_approve")]
+    WriteDownProposeApprove,
+}
+
+impl SodRule {
+    pub const ALL: [SodRule; 5] = [
+        SodRule::PoCreateApprove,
+        SodRule::PayrollPreparePay,
+        SodRule::StockAdjustmentRequestApprove,
+        SodRule::RaiseProposeApprove,
+        SodRule::WriteDownProposeApprove,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SodRule::PoCreateApprove => "po_create_approve",
+            SodRule::PayrollPreparePay => "payroll_prepare_pay",
+            SodRule::StockAdjustmentRequestApprove => "stock_adjustment_request_approve",
+            SodRule::RaiseProposeApprove => "raise_propose_approve",
+            SodRule::WriteDownProposeApprove => "write_down_propose_approve",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            SodRule::PoCreateApprove => "Purchase order creator cannot approve it",
+            SodRule::PayrollPreparePay => "Payroll preparer cannot release the payment",
+            SodRule::StockAdjustmentRequestApprove => "Stock adjustment requester cannot approve it",
+            SodRule::RaiseProposeApprove => "Salary raise proposer cannot approve it",
+            SodRule::WriteDownProposeApprove => "Stock write-down proposer cannot approve it",
+        }
+    }
+
+    /// Conflicts are refused unless the user gives an override reason
+    pub fn default_enforcement(&self) -> SodEnforcement {
+        SodEnforcement::Override
+    }
+}
+
+impl std::fmt::Display for SodRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Segregation of duties checks run when a record is approved or paid.
+///
+/// Each rule names two steps that one user should not both do on the same
+/// record. Admins choose per rule whether a conflict is blocked, may go
+/// ahead with a logged override reason (for teams too small to split the
+/// work), or is not checked. Takes a plain `SqliteConnection` so payroll,
+/// which works on unpooled connections, can run the checks too.
+pub struct SodService;
+
+impl SodService {
+    pub fn enforcement(conn: &mut SqliteConnection, rule: SodRule) -> Result<SodEnforcement> {
+        let setting = sod_rules::table
+            .filter(sod_rules::name.eq(rule.name()))
+            .first::<SodRuleSetting>(conn)
+            .optional()?;
+        match setting {
+            Some(setting) => setting.enforcement.parse(),
+            None => Ok(rule.default_enforcement()),
+        }
+    }
+
+    /// All rules with their current enforcement
+    pub fn rules(conn: &mut SqliteConnection) -> Result<Vec<(SodRule, SodEnforcement)>> {
+        SodRule::ALL
+            .iter()
+            .map(|rule| Ok((*rule, Self::enforcement(conn, *rule)?)))
+            .collect()
+    }
+
+    pub fn set_enforcement(
+        conn: &mut SqliteConnection,
+        rule: SodRule,
+        enforcement: SodEnforcement,
+        updated_by: Option<i32>,
+    ) -> Result<()> {
+        let updated = diesel::update(sod_rules::table.filter(sod_rules::name.eq(rule.name())))
+            .set((
+                sod_rules::enforcement.eq(enforcement.to_string()),
+                sod_rules::updated_by.eq(updated_by),
+                sod_rules::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+        if updated == 0 {
+            diesel::insert_into(sod_rules::table)
+                .values(&NewSodRuleSetting {
+                    name: rule.name().to_string(),
+                    enforcement: enforcement.to_string(),
+                    updated_by,
+                })
+                .execute(conn)?;
+        }
+
+        tracing::info!("Segregation of duties rule {} set to {}", rule, enforcement);
+        Ok(())
+    }
+
+    /// Check that `user_id` may do the second step of `rule` on `record_id`,
+    /// given that `first_actor` did the first. Overrides are logged.
+    pub fn check(
+        conn: &mut SqliteConnection,
+        rule: SodRule,
+        record_id: i32,
+        first_actor: Option<i32>,
+        user_id: i32,
+        override_reason: Option<&str>,
+    ) -> Result<()> {
+        if first_actor != Some(user_id) {
+            return Ok(());
+        }
+
+        let enforcement = Self::enforcement(conn, rule)?;
+        let Some(reason) = resolve_conflict(rule, enforcement, override_reason)? else {
+            return Ok(());
+        };
+
+        diesel::insert_into(sod_overrides::table)
+            .values(&NewSodOverride {
+                rule_name: rule.name().to_string(),
+                record_id,
+                first_actor,
+                user_id,
+                reason: reason.to_string(),
+            })
+            .execute(conn)?;
+        tracing::warn!(
+            "Segregation of duties rule {} overridden by user {} on record {}: {}",
+            rule,
+            user_id,
+            record_id,
+            reason
+        );
+        Ok(())
+    }
+
+    /// Logged overrides, newest first
+    pub fn overrides(conn: &mut SqliteConnection, limit: i64) -> Result<Vec<SodOverride>> {
+        sod_overrides::table
+            .order((sod_overrides::created_at.desc(), sod_overrides::id.desc()))
+            .limit(limit)
+            .load::<SodOverride>(conn)
+            .map_err(Into::into)
+    }
+}
+
+/// Outcome of a conflict under `enforcement`: `Ok(None)` when the rule is
+/// off, the override reason when one was given and is allowed, an error otherwise
+fn resolve_conflict(
+    rule: SodRule,
+    enforcement: SodEnforcement,
+    override_reason: Option<&str>,
+) -> Result<Option<&str>> {
+    let reason = override_reason.map(str::trim).filter(|r| !r.is_empty());
+    match (enforcement, reason) {
+        (SodEnforcement::Off, _) => Ok(None),
+        (SodEnforcement::Override, Some(reason)) => Ok(Some(reason)),
+        (SodEnforcement::Override, None) => Err(CLIERPError::Authorization(format!(
+            "{} (segregation of duties rule {}); give --override-sod <reason> to proceed, the override is logged",
+            rule.description(),
+            rule
+        ))),
+        (SodEnforcement::Block, _) => Err(CLIERPError::Authorization(format!(
+            "{} (segregation of duties rule {}, overrides are not allowed)",
+            rule.description(),
+            rule
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_conflict() {
+        let rule = SodRule::PoCreateApprove;
+        assert!(matches!(resolve_conflict(rule, SodEnforcement::Off, None), Ok(None)));
+        assert!(matches!(
+            resolve_conflict(rule, SodEnforcement::Override, Some(" only buyer on shift ")),
+            Ok(Some("only buyer on shift"))
+        ));
+        assert!(resolve_conflict(rule, SodEnforcement::Override, Some("  ")).is_err());
+        assert!(resolve_conflict(rule, SodEnforcement::Override, None).is_err());
+        assert!(resolve_conflict(rule, SodEnforcement::Block, Some("urgent")).is_err());
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Text};
+
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{employee_documents, product_attachments, products, storage_snapshots};
+use crate::database::{DatabaseConnection, NewStorageSnapshot, StorageSnapshot};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Rows and space of one table
+#[derive(Debug, Clone)]
+pub struct TableUsage {
+    pub table: String,
+    pub rows: i64,
+    /// Bytes of the table's pages, when SQLite is built with `dbstat`
+    pub bytes: Option<i64>,
+    /// Rows added since the previous stats run; `None` for a new table
+    pub growth: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexUsage {
+    pub index: String,
+    pub table: String,
+    pub bytes: i64,
+}
+
+/// A file attached to a product or employee
+#[derive(Debug, Clone)]
+pub struct AttachmentUsage {
+    /// e.g. `product SKU-1` or `employee 12`
+    pub owner: String,
+    pub file_name: String,
+    pub bytes: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct StorageReport {
+    pub taken_at: NaiveDateTime,
+    pub database_bytes: i64,
+    /// Bytes in free pages that `VACUUM` would give back
+    pub free_bytes: i64,
+    /// Largest tables first
+    pub tables: Vec<TableUsage>,
+    /// Largest indexes first; empty when `dbstat` is unavailable
+    pub indexes: Vec<IndexUsage>,
+    pub page_sizes_available: bool,
+    /// Largest attachments first
+    pub attachments: Vec<AttachmentUsage>,
+    pub attachment_bytes: i64,
+    /// The stats run the growth is measured against
+    pub previous: Option<StorageSnapshot>,
+}
+
+impl StorageReport {
+    /// Database growth in bytes since the previous stats run
+    pub fn database_growth(&self) -> Option<i64> {
+        self.previous.as_ref().map(|previous| self.database_bytes - previous.database_bytes)
+    }
+}
+
+#[derive(QueryableByName)]
+struct SchemaRow {
+    #[diesel(sql_type = Text)]
+    kind: String,
+    #[diesel(sql_type = Text)]
+    name: String,
+    #[diesel(sql_type = Text)]
+    tbl_name: String,
+}
+
+#[derive(QueryableByName)]
+struct SizeRow {
+    #[diesel(sql_type = Text)]
+    name: String,
+    #[diesel(sql_type = BigInt)]
+    bytes: i64,
+}
+
+#[derive(QueryableByName)]
+struct CountRow {
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+/// Row counts and space usage, for planning archiving and backups. Each run
+/// can be saved so the next one reports growth since it.
+pub struct StorageService;
+
+impl StorageService {
+    /// Measure the database; `top` limits the index and attachment lists
+    pub fn collect(conn: &mut DatabaseConnection, top: usize) -> Result<StorageReport> {
+        let page_size = Self::pragma(conn, "page_size")?;
+        let database_bytes = Self::pragma(conn, "page_count")? * page_size;
+        let free_bytes = Self::pragma(conn, "freelist_count")? * page_size;
+
+        let schema = diesel::sql_query(
+            "SELECT type AS kind, name, tbl_name FROM sqlite_master
+             WHERE type IN ('table', 'index') AND name NOT LIKE 'sqlite_stat%'
+             ORDER BY name",
+        )
+        .load::<SchemaRow>(conn)?;
+
+        // dbstat is an optional SQLite extension
+        let sizes: Option<HashMap<String, i64>> =
+            diesel::sql_query("SELECT name, CAST(SUM(pgsize) AS INTEGER) AS bytes FROM dbstat GROUP BY name")
+                .load::<SizeRow>(conn)
+                .ok()
+                .map(|rows| rows.into_iter().map(|row| (row.name, row.bytes)).collect());
+
+        let previous = storage_snapshots::table
+            .order(storage_snapshots::id.desc())
+            .first::<StorageSnapshot>(conn)
+            .optional()?;
+        let previous_rows: HashMap<String, i64> = previous
+            .as_ref()
+            .and_then(|snapshot| serde_json::from_str(&snapshot.row_counts).ok())
+            .unwrap_or_default();
+
+        let mut tables = Vec::new();
+        let mut indexes = Vec::new();
+        for row in &schema {
+            if row.kind == "index" {
+                if let Some(bytes) = sizes.as_ref().and_then(|sizes| sizes.get(&row.name)) {
+                    indexes.push(IndexUsage {
+                        index: row.name.clone(),
+                        table: row.tbl_name.clone(),
+                        bytes: *bytes,
+                    });
+                }
+                continue;
+            }
+            let rows = diesel::sql_query(format!("SELECT COUNT(*) AS count FROM \"{}\"", row.name.replace('"', "\"\"")))
+                .get_result::<CountRow>(conn)?
+                .count;
+            tables.push(TableUsage {
+                table: row.name.clone(),
+                rows,
+                bytes: sizes.as_ref().and_then(|sizes| sizes.get(&row.name).copied()),
+                growth: previous_rows.get(&row.name).map(|before| rows - before),
+            });
+        }
+        tables.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(b.rows.cmp(&a.rows)).then_with(|| a.table.cmp(&b.table)));
+        indexes.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.index.cmp(&b.index)));
+        indexes.truncate(top);
+
+        let (attachments, attachment_bytes) = Self::attachments(conn, top)?;
+
+        Ok(StorageReport {
+            taken_at: Utc::now().naive_utc(),
+            database_bytes,
+            free_bytes,
+            tables,
+            indexes,
+            page_sizes_available: sizes.is_some(),
+            attachments,
+            attachment_bytes,
+            previous,
+        })
+    }
+
+    /// Keep a report's size and row counts as the baseline of the next run
+    pub fn save(conn: &mut DatabaseConnection, report: &StorageReport) -> Result<()> {
+        let rows: HashMap<&str, i64> = report.tables.iter().map(|t| (t.table.as_str(), t.rows)).collect();
+        diesel::insert_into(storage_snapshots::table)
+            .values(&NewStorageSnapshot {
+                database_bytes: report.database_bytes,
+                row_counts: serde_json::to_string(&rows)?,
+            })
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Largest product attachments and employee documents, and their total size
+    fn attachments(conn: &mut DatabaseConnection, top: usize) -> Result<(Vec<AttachmentUsage>, i64)> {
+        let mut attachments: Vec<AttachmentUsage> = product_attachments::table
+            .inner_join(products::table)
+            .select((products::sku, product_attachments::file_name, product_attachments::file_size))
+            .load::<(String, String, i32)>(conn)?
+            .into_iter()
+            .map(|(sku, file_name, size)| AttachmentUsage {
+                owner: format!("product {}", sku),
+                file_name,
+                bytes: i64::from(size),
+            })
+            .collect();
+
+        // Employee documents only record the path, so their size is read from disk
+        let documents = employee_documents::table
+            .filter(employee_documents::file_path.is_not_null())
+            .select((employee_documents::employee_id, employee_documents::file_path))
+            .load::<(i32, Option<String>)>(conn)?;
+        for (employee_id, path) in documents {
+            let Some(path) = path else { continue };
+            let Ok(metadata) = std::fs::metadata(&path) else { continue };
+            attachments.push(AttachmentUsage {
+                owner: format!("employee {}", employee_id),
+                file_name: Path::new(&path)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or(path.clone()),
+                bytes: metadata.len() as i64,
+            });
+        }
+
+        let total = attachments.iter().map(|a| a.bytes).sum();
+        attachments.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.file_name.cmp(&b.file_name)));
+        attachments.truncate(top);
+        Ok((attachments, total))
+    }
+
+    fn pragma(conn: &mut DatabaseConnection, name: &str) -> Result<i64> {
+        Ok(diesel::sql_query(format!("SELECT {} AS count FROM pragma_{}()", name, name))
+            .get_result::<CountRow>(conn)?
+            .count)
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::prelude::*;
+
+use crate::core::result::CLIERPResult;
+use crate::database::schema::command_usage;
+use crate::database::{CommandUsage, NewCommandUsage};
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// Runs, time and failures of one command over a period
+#[derive(Debug, Clone)]
+pub struct CommandStats {
+    pub command: String,
+    pub runs: i64,
+    pub failures: i64,
+    pub total_ms: i64,
+    pub max_ms: i64,
+    /// Runs per role, `-` for runs without a login
+    pub roles: Vec<(String, i64)>,
+}
+
+impl CommandStats {
+    pub fn average_ms(&self) -> i64 {
+        if self.runs == 0 {
+            0
+        } else {
+            self.total_ms / self.runs
+        }
+    }
+
+    pub fn error_rate(&self) -> f64 {
+        if self.runs == 0 {
+            0.0
+        } else {
+            self.failures as f64 * 100.0 / self.runs as f64
+        }
+    }
+}
+
+/// Opt-in usage statistics kept in the local database and never sent
+/// anywhere. Only the subcommand path of a run is recorded, so argument
+/// values such as names and amounts stay out of the statistics.
+pub struct UsageService;
+
+impl UsageService {
+    /// Record one run and drop runs older than `retention_days`
+    pub fn record(conn: &mut SqliteConnection, usage: &NewCommandUsage, retention_days: u32) -> Result<()> {
+        diesel::insert_into(command_usage::table).values(usage).execute(conn)?;
+        let cutoff = Utc::now().naive_utc() - Duration::days(i64::from(retention_days));
+        diesel::delete(command_usage::table.filter(command_usage::used_at.lt(cutoff))).execute(conn)?;
+        Ok(())
+    }
+
+    /// Statistics per command since `since`, the heaviest (most total time) first
+    pub fn summarize(conn: &mut SqliteConnection, since: NaiveDateTime) -> Result<Vec<CommandStats>> {
+        let runs = command_usage::table
+            .filter(command_usage::used_at.ge(since))
+            .select(CommandUsage::as_select())
+            .load::<CommandUsage>(conn)?;
+
+        let mut by_command: HashMap<String, (CommandStats, HashMap<String, i64>)> = HashMap::new();
+        for run in runs {
+            let (stats, roles) = by_command.entry(run.command.clone()).or_insert_with(|| {
+                (
+                    CommandStats {
+                        command: run.command.clone(),
+                        runs: 0,
+                        failures: 0,
+                        total_ms: 0,
+                        max_ms: 0,
+                        roles: Vec::new(),
+                    },
+                    HashMap::new(),
+                )
+            });
+            stats.runs += 1;
+            if !run.succeeded {
+                stats.failures += 1;
+            }
+            stats.total_ms += run.duration_ms;
+            stats.max_ms = stats.max_ms.max(run.duration_ms);
+            *roles.entry(run.role.unwrap_or_else(|| "-".to_string())).or_default() += 1;
+        }
+
+        let mut stats: Vec<CommandStats> = by_command
+            .into_values()
+            .map(|(mut stats, roles)| {
+                stats.roles = roles.into_iter().collect();
+                stats.roles.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                stats
+            })
+            .collect();
+        stats.sort_by(|a, b| b.total_ms.cmp(&a.total_ms).then_with(|| a.command.cmp(&b.command)));
+        Ok(stats)
+    }
+
+    /// Delete every recorded run
+    pub fn clear(conn: &mut SqliteConnection) -> Result<usize> {
+        Ok(diesel::delete(command_usage::table).execute(conn)?)
+    }
+}
+
+/// The subcommand path of a command line, e.g. `inv product list` for
+/// `inv product list --category 3`, leaving out flags and values
+pub fn command_path(root: &clap::Command, words: &[String]) -> String {
+    let mut command = root;
+    let mut path = Vec::new();
+    let mut words = words.iter();
+    while let Some(word) = words.next() {
+        if let Some(flag) = word.strip_prefix("--") {
+            // Skip the value of a flag given as `--flag value`
+            let takes_value = !flag.contains('=')
+                && [command, root]
+                    .iter()
+                    .flat_map(|c| c.get_arguments())
+                    .find(|arg| arg.get_long() == Some(flag))
+                    .is_some_and(|arg| arg.get_action().takes_values());
+            if takes_value {
+                words.next();
+            }
+            continue;
+        }
+        if word.starts_with('-') {
+            continue;
+        }
+        match command
+            .get_subcommands()
+            .find(|sub| sub.get_name() == word || sub.get_all_aliases().any(|alias| alias == word))
+        {
+            Some(sub) => {
+                path.push(sub.get_name());
+                command = sub;
+            }
+            None => break,
+        }
+    }
+    path.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_command_path() {
+        let root = crate::core::command::CLIArgs::command();
+        let words = |line: &str| line.split_whitespace().map(String::from).collect::<Vec<_>>();
+        assert_eq!(command_path(&root, &words("system stats --top 5")), "system stats");
+        assert_eq!(command_path(&root, &words("--plain events tail --follow")), "events tail");
+        assert_eq!(command_path(&root, &words("--error-format json system doctor")), "system doctor");
+        assert_eq!(command_path(&root, &words("auth login secret-user")), "auth login");
+        assert_eq!(command_path(&root, &words("")), "");
+    }
+}
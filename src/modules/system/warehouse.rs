@@ -0,0 +1,249 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{deals, invoices, stock_movements, transactions};
+use crate::database::DatabaseConnection;
+use crate::utils::parquet::{ParquetTable, ParquetValues};
+use crate::utils::timezone::DisplayTimezone;
+
+// Type alias for convenience
+type Result<T> = CLIERPResult<T>;
+
+/// One file written by the export
+#[derive(Debug, Clone)]
+pub struct WarehouseFile {
+    pub path: PathBuf,
+    pub rows: usize,
+}
+
+/// Parquet export of the fact tables for analysts' tools (DuckDB, BI).
+///
+/// Each fact file keeps the table's own IDs as keys to the other tables and
+/// adds `*_date_key` columns (`YYYYMMDD` of the local date) joining to
+/// `dim_date.parquet`, which has one row per day from the earliest to the
+/// latest date in the facts. Timestamps stay in UTC.
+pub struct WarehouseService;
+
+impl WarehouseService {
+    /// Write the fact and date dimension files into `dir`, replacing earlier ones
+    pub fn export(conn: &mut DatabaseConnection, dir: &Path) -> Result<Vec<WarehouseFile>> {
+        fs::create_dir_all(dir)
+            .map_err(|e| CLIERPError::IoError(format!("Cannot create {}: {}", dir.display(), e)))?;
+        let tz = DisplayTimezone::current();
+        let local_date = |at: NaiveDateTime| tz.to_local(at).date();
+        let mut dates = BTreeSet::new();
+        let mut files = Vec::new();
+
+        let movements = stock_movements::table
+            .order(stock_movements::id.asc())
+            .select((
+                stock_movements::id,
+                stock_movements::product_id,
+                stock_movements::movement_type,
+                stock_movements::quantity,
+                stock_movements::unit_cost,
+                stock_movements::reference_type,
+                stock_movements::reference_id,
+                stock_movements::moved_by,
+                stock_movements::movement_date,
+            ))
+            .load::<(i32, i32, String, i32, Option<i32>, Option<String>, Option<i32>, Option<i32>, NaiveDateTime)>(conn)?;
+        dates.extend(movements.iter().map(|m| local_date(m.8)));
+        let table = ParquetTable::new()
+            .column("movement_id", int32(movements.iter().map(|m| Some(m.0))))
+            .column("movement_date_key", int32(movements.iter().map(|m| Some(date_key(local_date(m.8))))))
+            .column("product_id", int32(movements.iter().map(|m| Some(m.1))))
+            .column("movement_type", text(movements.iter().map(|m| Some(m.2.clone()))))
+            .column("quantity", int32(movements.iter().map(|m| Some(m.3))))
+            .column("unit_cost", int32(movements.iter().map(|m| m.4)))
+            .column("value", ParquetValues::Int64(movements.iter().map(|m| m.4.map(|cost| i64::from(cost) * i64::from(m.3))).collect()))
+            .column("reference_type", text(movements.iter().map(|m| m.5.clone())))
+            .column("reference_id", int32(movements.iter().map(|m| m.6)))
+            .column("moved_by", int32(movements.iter().map(|m| m.7)))
+            .column("moved_at", timestamps(movements.iter().map(|m| Some(m.8))));
+        files.push(write(dir, "fact_stock_movements", &table)?);
+
+        let entries = transactions::table
+            .order(transactions::id.asc())
+            .select((
+                transactions::id,
+                transactions::account_id,
+                transactions::transaction_date,
+                transactions::amount,
+                transactions::debit_credit,
+                transactions::description,
+                transactions::reference,
+                transactions::created_by,
+                transactions::created_at,
+            ))
+            .load::<(i32, i32, NaiveDate, i32, String, String, Option<String>, Option<i32>, NaiveDateTime)>(conn)?;
+        dates.extend(entries.iter().map(|t| t.2));
+        let table = ParquetTable::new()
+            .column("transaction_id", int32(entries.iter().map(|t| Some(t.0))))
+            .column("transaction_date_key", int32(entries.iter().map(|t| Some(date_key(t.2)))))
+            .column("account_id", int32(entries.iter().map(|t| Some(t.1))))
+            .column("transaction_date", days(entries.iter().map(|t| Some(t.2))))
+            .column("amount", int32(entries.iter().map(|t| Some(t.3))))
+            .column("debit_credit", text(entries.iter().map(|t| Some(t.4.clone()))))
+            .column("signed_amount", int32(entries.iter().map(|t| Some(if t.4 == "credit" { -t.3 } else { t.3 }))))
+            .column("description", text(entries.iter().map(|t| Some(t.5.clone()))))
+            .column("reference", text(entries.iter().map(|t| t.6.clone())))
+            .column("created_by", int32(entries.iter().map(|t| t.7)))
+            .column("created_at", timestamps(entries.iter().map(|t| Some(t.8))));
+        files.push(write(dir, "fact_transactions", &table)?);
+
+        let deal_rows = deals::table
+            .order(deals::id.asc())
+            .select((
+                deals::id,
+                deals::lead_id,
+                deals::deal_name,
+                deals::stage,
+                deals::deal_value,
+                deals::final_amount,
+                deals::probability,
+                deals::close_date,
+                deals::assigned_to,
+                deals::created_at,
+            ))
+            .load::<(i32, Option<i32>, String, String, i32, Option<i32>, Option<i32>, Option<NaiveDate>, Option<i32>, NaiveDateTime)>(conn)?;
+        dates.extend(deal_rows.iter().map(|d| local_date(d.9)));
+        dates.extend(deal_rows.iter().filter_map(|d| d.7));
+        let table = ParquetTable::new()
+            .column("deal_id", int32(deal_rows.iter().map(|d| Some(d.0))))
+            .column("created_date_key", int32(deal_rows.iter().map(|d| Some(date_key(local_date(d.9))))))
+            .column("close_date_key", int32(deal_rows.iter().map(|d| d.7.map(date_key))))
+            .column("lead_id", int32(deal_rows.iter().map(|d| d.1)))
+            .column("deal_name", text(deal_rows.iter().map(|d| Some(d.2.clone()))))
+            .column("stage", text(deal_rows.iter().map(|d| Some(d.3.clone()))))
+            .column("deal_value", int32(deal_rows.iter().map(|d| Some(d.4))))
+            .column("final_amount", int32(deal_rows.iter().map(|d| Some(d.5.unwrap_or(d.4)))))
+            .column("probability", int32(deal_rows.iter().map(|d| d.6)))
+            .column("close_date", days(deal_rows.iter().map(|d| d.7)))
+            .column("assigned_to", int32(deal_rows.iter().map(|d| d.8)))
+            .column("created_at", timestamps(deal_rows.iter().map(|d| Some(d.9))));
+        files.push(write(dir, "fact_deals", &table)?);
+
+        let invoice_rows = invoices::table
+            .order(invoices::id.asc())
+            .select((
+                invoices::id,
+                invoices::invoice_number,
+                invoices::kind,
+                invoices::customer_id,
+                invoices::supplier_id,
+                invoices::po_id,
+                invoices::invoice_date,
+                invoices::due_date,
+                invoices::total_amount,
+                invoices::paid_amount,
+                invoices::status,
+            ))
+            .load::<(i32, String, String, Option<i32>, Option<i32>, Option<i32>, NaiveDate, Option<NaiveDate>, i32, i32, String)>(conn)?;
+        dates.extend(invoice_rows.iter().map(|i| i.6));
+        dates.extend(invoice_rows.iter().filter_map(|i| i.7));
+        let table = ParquetTable::new()
+            .column("invoice_id", int32(invoice_rows.iter().map(|i| Some(i.0))))
+            .column("invoice_date_key", int32(invoice_rows.iter().map(|i| Some(date_key(i.6)))))
+            .column("due_date_key", int32(invoice_rows.iter().map(|i| i.7.map(date_key))))
+            .column("invoice_number", text(invoice_rows.iter().map(|i| Some(i.1.clone()))))
+            .column("kind", text(invoice_rows.iter().map(|i| Some(i.2.clone()))))
+            .column("customer_id", int32(invoice_rows.iter().map(|i| i.3)))
+            .column("supplier_id", int32(invoice_rows.iter().map(|i| i.4)))
+            .column("po_id", int32(invoice_rows.iter().map(|i| i.5)))
+            .column("invoice_date", days(invoice_rows.iter().map(|i| Some(i.6))))
+            .column("due_date", days(invoice_rows.iter().map(|i| i.7)))
+            .column("total_amount", int32(invoice_rows.iter().map(|i| Some(i.8))))
+            .column("paid_amount", int32(invoice_rows.iter().map(|i| Some(i.9))))
+            .column("open_amount", int32(invoice_rows.iter().map(|i| Some(i.8 - i.9))))
+            .column("status", text(invoice_rows.iter().map(|i| Some(i.10.clone()))));
+        files.push(write(dir, "fact_invoices", &table)?);
+
+        let calendar = match (dates.first(), dates.last()) {
+            (Some(first), Some(last)) => first.iter_days().take_while(|day| day <= last).collect(),
+            _ => Vec::new(),
+        };
+        files.push(write(dir, "dim_date", &date_dimension(&calendar))?);
+
+        tracing::info!("Exported warehouse files to {}", dir.display());
+        Ok(files)
+    }
+}
+
+/// Surrogate key of a date, e.g. 20240315
+pub fn date_key(date: NaiveDate) -> i32 {
+    date.year() * 10000 + date.month() as i32 * 100 + date.day() as i32
+}
+
+/// One row per day with the attributes reports group by
+fn date_dimension(calendar: &[NaiveDate]) -> ParquetTable {
+    let column = |f: &dyn Fn(&NaiveDate) -> i32| ParquetValues::Int32(calendar.iter().map(|d| Some(f(d))).collect());
+    let names = |f: &dyn Fn(&NaiveDate) -> String| ParquetValues::Text(calendar.iter().map(|d| Some(f(d))).collect());
+    ParquetTable::new()
+        .column("date_key", column(&|d| date_key(*d)))
+        .column("date", days(calendar.iter().map(|d| Some(*d))))
+        .column("year", column(&|d| d.year()))
+        .column("quarter", column(&|d| (d.month0() / 3 + 1) as i32))
+        .column("month", column(&|d| d.month() as i32))
+        .column("month_name", names(&|d| d.format("%B").to_string()))
+        .column("year_month", names(&|d| d.format("%Y-%m").to_string()))
+        .column("day_of_month", column(&|d| d.day() as i32))
+        .column("day_of_week", column(&|d| d.weekday().number_from_monday() as i32))
+        .column("day_name", names(&|d| d.format("%A").to_string()))
+        .column("iso_year", column(&|d| d.iso_week().year()))
+        .column("iso_week", column(&|d| d.iso_week().week() as i32))
+        .column("is_weekend", column(&|d| (d.weekday().number_from_monday() >= 6) as i32))
+}
+
+fn write(dir: &Path, name: &str, table: &ParquetTable) -> Result<WarehouseFile> {
+    let path = dir.join(format!("{}.parquet", name));
+    fs::write(&path, table.to_bytes())
+        .map_err(|e| CLIERPError::IoError(format!("Cannot write {}: {}", path.display(), e)))?;
+    Ok(WarehouseFile { path, rows: table.rows() })
+}
+
+fn int32(values: impl Iterator<Item = Option<i32>>) -> ParquetValues {
+    ParquetValues::Int32(values.collect())
+}
+
+fn text(values: impl Iterator<Item = Option<String>>) -> ParquetValues {
+    ParquetValues::Text(values.collect())
+}
+
+fn days(values: impl Iterator<Item = Option<NaiveDate>>) -> ParquetValues {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid date");
+    ParquetValues::Date(values.map(|d| d.map(|d| (d - epoch).num_days() as i32)).collect())
+}
+
+fn timestamps(values: impl Iterator<Item = Option<NaiveDateTime>>) -> ParquetValues {
+    ParquetValues::Timestamp(values.map(|t| t.map(|t| t.and_utc().timestamp_millis())).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_dimension() {
+        let date = |d: &str| d.parse::<NaiveDate>().unwrap();
+        assert_eq!(date_key(date("2024-03-05")), 20240305);
+
+        let calendar: Vec<NaiveDate> = date("2024-12-30").iter_days().take(3).collect();
+        let table = date_dimension(&calendar);
+        assert_eq!(table.rows(), 3);
+        match days(calendar.iter().map(|d| Some(*d))) {
+            ParquetValues::Date(values) => assert_eq!(values, [Some(20087), Some(20088), Some(20089)]),
+            other => panic!("unexpected {:?}", other),
+        }
+        match timestamps([Some(date("1970-01-02").and_hms_opt(0, 0, 1).unwrap())].into_iter()) {
+            ParquetValues::Timestamp(values) => assert_eq!(values, [Some(86_401_000)]),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
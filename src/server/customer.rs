@@ -0,0 +1,187 @@
+//! Read-only account pages opened from customer portal links
+
+use chrono::{Duration, NaiveDate, Utc};
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::Invoice;
+use crate::modules::crm::customer_portal::CustomerPortalService;
+use crate::modules::finance::receivables::ReceivableService;
+use crate::utils::formatting::format_currency;
+use crate::utils::html::{escape_html, html_page, html_table};
+
+use super::http::{Request, Response};
+use super::tenant::Tenant;
+use super::{error_response, message_page};
+
+/// Statement period shown when the page is opened without `from`
+const DEFAULT_STATEMENT_DAYS: i64 = 365;
+
+pub(super) fn handle(request: &Request, tenant: &Tenant, token: &str, rest: &[&str]) -> Response {
+    let result = match (request.method.as_str(), rest) {
+        ("GET", []) => account(request, tenant, token),
+        ("GET", ["invoice", number]) => invoice(tenant, token, number),
+        ("GET", _) => return Response::html(404, message_page("Not found", "There is nothing at this address.", false)),
+        _ => return Response::html(405, message_page("Not allowed", "These pages can only be viewed.", false)),
+    };
+    result.unwrap_or_else(error_response)
+}
+
+fn account(request: &Request, tenant: &Tenant, token: &str) -> CLIERPResult<Response> {
+    let today = Utc::now().date_naive();
+    let to = date_param(request, "to")?.unwrap_or(today);
+    let from = date_param(request, "from")?.unwrap_or(to - Duration::days(DEFAULT_STATEMENT_DAYS));
+    if from > to {
+        return Err(CLIERPError::Validation("The statement must start before it ends".to_string()));
+    }
+
+    let mut conn = tenant.connection()?;
+    let (_, customer) = CustomerPortalService::open(&mut conn, tenant.secret(), token)?;
+    let open_invoices = ReceivableService::open_invoices(&mut conn, customer.id)?;
+    let statement = ReceivableService::statement(&mut conn, customer.id, from, to)?;
+    let payments = ReceivableService::payments(&mut conn, customer.id)?;
+
+    let invoice_link = |invoice: &Invoice| {
+        format!(
+            "<a href=\"/customer/{}/invoice/{}\">{}</a>",
+            escape_html(token),
+            escape_html(&invoice.invoice_number),
+            escape_html(&invoice.invoice_number)
+        )
+    };
+
+    let mut body = format!("<p>Account of <strong>{}</strong> ({})</p>\n", escape_html(&customer.name), escape_html(&customer.customer_code));
+
+    body.push_str("<h2>Open invoices</h2>\n");
+    if open_invoices.is_empty() {
+        body.push_str("<p class=\"notice\">Nothing is open. Thank you!</p>\n");
+    } else {
+        // Built by hand rather than with html_table, which escapes the links
+        body.push_str("<table>\n<thead><tr><th>Invoice</th><th>Date</th><th>Due</th><th>Total</th><th>Paid</th><th>Open</th></tr></thead>\n<tbody>\n");
+        let mut total_open = 0;
+        for invoice in &open_invoices {
+            let overdue = invoice.due_date.is_some_and(|due| due < today);
+            total_open += invoice.open_amount();
+            body.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                invoice_link(invoice),
+                invoice.invoice_date,
+                invoice.due_date.map(|d| d.to_string()).unwrap_or_else(|| "-".to_string()),
+                if overdue { " <strong>(overdue)</strong>" } else { "" },
+                escape_html(&format_currency(invoice.total_amount)),
+                escape_html(&format_currency(invoice.paid_amount)),
+                escape_html(&format_currency(invoice.open_amount()))
+            ));
+        }
+        body.push_str(&format!(
+            "</tbody>\n<tfoot><tr><td>Total</td><td></td><td></td><td></td><td></td><td>{}</td></tr></tfoot>\n</table>\n",
+            escape_html(&format_currency(total_open))
+        ));
+    }
+
+    body.push_str(&format!("<h2>Statement {} to {}</h2>\n", statement.from, statement.to));
+    let headers: Vec<String> = ["Date", "Description", "Invoiced", "Paid", "Balance"].iter().map(|h| h.to_string()).collect();
+    let mut rows = vec![vec![
+        statement.from.to_string(),
+        "Opening balance".to_string(),
+        String::new(),
+        String::new(),
+        format_currency(statement.opening_balance),
+    ]];
+    rows.extend(statement.lines.iter().map(|line| {
+        vec![
+            line.date.to_string(),
+            line.description.clone(),
+            if line.debit != 0 { format_currency(line.debit) } else { String::new() },
+            if line.credit != 0 { format_currency(line.credit) } else { String::new() },
+            format_currency(line.balance),
+        ]
+    }));
+    let totals = vec![
+        statement.to.to_string(),
+        "Closing balance".to_string(),
+        String::new(),
+        String::new(),
+        format_currency(statement.closing_balance),
+    ];
+    body.push_str(&html_table(&headers, &rows, Some(&totals)));
+
+    body.push_str("<h2>Payment history</h2>\n");
+    if payments.is_empty() {
+        body.push_str("<p>No payments recorded yet.</p>\n");
+    } else {
+        let headers: Vec<String> = ["Date", "Invoice", "Amount", "Reference"].iter().map(|h| h.to_string()).collect();
+        let rows: Vec<Vec<String>> = payments
+            .iter()
+            .map(|(payment, invoice)| {
+                vec![
+                    payment.paid_on.to_string(),
+                    invoice.invoice_number.clone(),
+                    format_currency(payment.amount),
+                    match payment.returned_on {
+                        Some(returned_on) => format!(
+                            "{} (returned {})",
+                            payment.reference.as_deref().unwrap_or_default(),
+                            returned_on
+                        )
+                        .trim_start()
+                        .to_string(),
+                        None => payment.reference.clone().unwrap_or_default(),
+                    },
+                ]
+            })
+            .collect();
+        body.push_str(&html_table(&headers, &rows, None));
+    }
+
+    Ok(Response::html(200, html_page(&format!("Account of {}", customer.name), &body)))
+}
+
+fn invoice(tenant: &Tenant, token: &str, number: &str) -> CLIERPResult<Response> {
+    let mut conn = tenant.connection()?;
+    let (_, customer) = CustomerPortalService::open(&mut conn, tenant.secret(), token)?;
+    let (invoice, payments) = ReceivableService::customer_invoice(&mut conn, customer.id, number)?;
+
+    let mut body = format!(
+        "<p>Billed to <strong>{}</strong> ({})</p>\n",
+        escape_html(&customer.name),
+        escape_html(&customer.customer_code)
+    );
+    if let Some(address) = &customer.address {
+        body.push_str(&format!("<p>{}</p>\n", escape_html(address)));
+    }
+    let headers: Vec<String> = ["Invoice date", "Due date", "Status", "Total", "Paid", "Open"].iter().map(|h| h.to_string()).collect();
+    let rows = vec![vec![
+        invoice.invoice_date.to_string(),
+        invoice.due_date.map(|d| d.to_string()).unwrap_or_else(|| "-".to_string()),
+        invoice.status.clone(),
+        format_currency(invoice.total_amount),
+        format_currency(invoice.paid_amount),
+        format_currency(invoice.open_amount()),
+    ]];
+    body.push_str(&html_table(&headers, &rows, None));
+    if let Some(description) = &invoice.description {
+        body.push_str(&format!("<p>{}</p>\n", escape_html(description)));
+    }
+    if !payments.is_empty() {
+        body.push_str("<h2>Payments</h2>\n");
+        let headers: Vec<String> = ["Date", "Amount", "Reference"].iter().map(|h| h.to_string()).collect();
+        let rows: Vec<Vec<String>> = payments
+            .iter()
+            .map(|p| vec![p.paid_on.to_string(), format_currency(p.amount), p.reference.clone().unwrap_or_default()])
+            .collect();
+        body.push_str(&html_table(&headers, &rows, None));
+    }
+    body.push_str(&format!("<p><a href=\"/customer/{}\">Back to the account</a></p>\n", escape_html(token)));
+
+    Ok(Response::html(200, html_page(&format!("Invoice {}", invoice.invoice_number), &body)))
+}
+
+fn date_param(request: &Request, name: &str) -> CLIERPResult<Option<NaiveDate>> {
+    match request.query.get(name).map(|v| v.trim()).filter(|v| !v.is_empty()) {
+        Some(value) => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| CLIERPError::Validation(format!("'{}' is not a date (YYYY-MM-DD)", value))),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,192 @@
+//! Read-only management dashboard opened from a dashboard link: KPI tiles,
+//! the deal pipeline and products low on stock, laid out for a phone
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+
+use crate::cli::tui::board::PipelineBoard;
+use crate::core::result::CLIERPResult;
+use crate::database::{get_report_connection, DatabaseConnection, DealStage, KpiStatus};
+use crate::modules::crm::{DealService, RecordScope};
+use crate::modules::inventory::ProductService;
+use crate::modules::reporting::{DashboardService, KpiService};
+use crate::utils::formatting::{format_currency, format_datetime};
+use crate::utils::html::{escape_html, html_page, html_table};
+
+use super::http::{Request, Response};
+use super::tenant::Tenant;
+use super::{error_response, message_page};
+
+/// Deals listed per pipeline stage; the rest are counted
+const DEALS_PER_STAGE: usize = 5;
+
+/// Base metrics shown as tiles when no KPIs are defined
+const BASE_TILES: &[(&str, &str, TileUnit)] = &[
+    ("pipeline_value", "Open pipeline", TileUnit::Currency),
+    ("won_value", "Won", TileUnit::Currency),
+    ("win_rate", "Win rate", TileUnit::Percent),
+    ("active_customers", "Active customers", TileUnit::Count),
+    ("overdue_activities", "Overdue activities", TileUnit::Count),
+    ("low_stock_items", "Low stock items", TileUnit::Count),
+];
+
+#[derive(Debug, Clone, Copy)]
+enum TileUnit {
+    Currency,
+    Percent,
+    Count,
+}
+
+pub(super) fn handle(request: &Request, tenant: &Tenant, token: &str, rest: &[&str]) -> Response {
+    let result = match (request.method.as_str(), rest) {
+        ("GET", []) => dashboard(tenant, token),
+        ("GET", _) => return Response::html(404, message_page("Not found", "There is nothing at this address.", false)),
+        _ => return Response::html(405, message_page("Not allowed", "The dashboard can only be viewed.", false)),
+    };
+    result.unwrap_or_else(error_response)
+}
+
+fn dashboard(tenant: &Tenant, token: &str) -> CLIERPResult<Response> {
+    let mut conn = tenant.connection()?;
+    let (_, user) = DashboardService::open(&mut conn, tenant.secret(), token)?;
+    // Tiles and the pipeline are read from the reporting replica, if any
+    let mut conn = get_report_connection()?;
+
+    let mut body = format!(
+        "<p class=\"muted\">{} &middot; as of {}</p>\n",
+        escape_html(&user.username),
+        escape_html(&format_datetime(&Utc::now().naive_utc()))
+    );
+    body.push_str(&tiles(&mut conn)?);
+    body.push_str(&pipeline(&mut conn)?);
+    body.push_str(&low_stock()?);
+    Ok(Response::html(200, html_page("Dashboard", &body)))
+}
+
+/// Defined KPIs with their status, or the main base metrics without one
+fn tiles(conn: &mut DatabaseConnection) -> CLIERPResult<String> {
+    let evaluations = KpiService::evaluate_all(conn)?;
+    let tiles: Vec<(String, String, Option<KpiStatus>)> = if evaluations.is_empty() {
+        let values: HashMap<String, f64> = KpiService::metric_values(conn)?;
+        BASE_TILES
+            .iter()
+            .map(|(metric, label, unit)| {
+                let value = values.get(*metric).copied().unwrap_or(0.0);
+                let value = match unit {
+                    TileUnit::Currency => format_currency(value.round() as i64),
+                    TileUnit::Percent => format!("{:.1}%", value),
+                    TileUnit::Count => format!("{}", value.round() as i64),
+                };
+                (label.to_string(), value, None)
+            })
+            .collect()
+    } else {
+        evaluations
+            .into_iter()
+            .map(|evaluation| {
+                let value = evaluation.value.map(|v| format!("{:.2}", v)).unwrap_or_else(|| "n/a".to_string());
+                (evaluation.kpi.name, value, evaluation.status)
+            })
+            .collect()
+    };
+
+    let mut html = String::from("<h2>KPIs</h2>\n<div class=\"tiles\">\n");
+    for (label, value, status) in tiles {
+        let class = status.map(|s| format!(" {}", s)).unwrap_or_default();
+        html.push_str(&format!(
+            "<div class=\"tile{}\"><div class=\"label\">{}</div><div class=\"value\">{}</div></div>\n",
+            class,
+            escape_html(&label),
+            escape_html(&value)
+        ));
+    }
+    html.push_str("</div>\n");
+    Ok(html)
+}
+
+/// Open pipeline stages side by side, largest deals first
+fn pipeline(conn: &mut DatabaseConnection) -> CLIERPResult<String> {
+    let deals = DealService::find_deals_by_filters(conn, &[], &RecordScope::unrestricted())?;
+    let board = PipelineBoard::from_deals(deals);
+
+    let mut html = String::from("<h2>Pipeline</h2>\n<div class=\"board\">\n");
+    for column in &board.columns {
+        if matches!(column.stage, DealStage::ClosedWon | DealStage::ClosedLost) {
+            continue;
+        }
+        html.push_str(&format!(
+            "<section class=\"stage\"><h3>{}</h3>\n<p class=\"muted\">{} deal(s) &middot; {}</p>\n<ul>\n",
+            escape_html(&column.stage.to_string().replace('_', " ")),
+            column.deals.len(),
+            escape_html(&format_currency(column.total()))
+        ));
+        for deal in column.deals.iter().take(DEALS_PER_STAGE) {
+            html.push_str(&format!(
+                "<li>{} <span class=\"price\">{}</span></li>\n",
+                escape_html(&deal.deal_name),
+                escape_html(&format_currency(deal.final_amount.unwrap_or(deal.deal_value)))
+            ));
+        }
+        if column.deals.len() > DEALS_PER_STAGE {
+            html.push_str(&format!("<li class=\"muted\">and {} more</li>\n", column.deals.len() - DEALS_PER_STAGE));
+        }
+        html.push_str("</ul>\n</section>\n");
+    }
+    html.push_str("</div>\n");
+    Ok(html)
+}
+
+fn low_stock() -> CLIERPResult<String> {
+    let products = ProductService::new().get_low_stock_products()?;
+    let mut html = String::from("<h2>Low stock</h2>\n");
+    if products.is_empty() {
+        html.push_str("<p class=\"notice\">Every product is above its minimum stock level.</p>\n");
+        return Ok(html);
+    }
+    let headers: Vec<String> = ["SKU", "Product", "Stock", "Minimum"].iter().map(|h| h.to_string()).collect();
+    let rows: Vec<Vec<String>> = products
+        .iter()
+        .map(|p| {
+            vec![
+                p.product.sku.clone(),
+                p.product.name.clone(),
+                format!("{} {}", p.product.current_stock, p.product.unit),
+                p.product.min_stock_level.to_string(),
+            ]
+        })
+        .collect();
+    html.push_str("<div class=\"scroll\">\n");
+    html.push_str(&html_table(&headers, &rows, None));
+    html.push_str("</div>\n");
+    Ok(html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::products;
+    use crate::testing::CompanyFixture;
+    use diesel::prelude::*;
+
+    #[test]
+    fn test_sections() {
+        let company = CompanyFixture::new().with_products(2).build().unwrap();
+        let mut conn = company.connection().unwrap();
+
+        let html = tiles(&mut conn).unwrap();
+        for (_, label, _) in BASE_TILES {
+            assert!(html.contains(label), "missing tile {}", label);
+        }
+        assert!(pipeline(&mut conn).unwrap().starts_with("<h2>Pipeline</h2>"));
+        assert!(low_stock().unwrap().contains("Every product is above its minimum stock level."));
+
+        diesel::update(products::table.find(company.products[0].id))
+            .set(products::current_stock.eq(2))
+            .execute(&mut conn)
+            .unwrap();
+        let html = low_stock().unwrap();
+        assert!(html.contains("FIX-P0001"));
+        assert!(!html.contains("FIX-P0002"));
+    }
+}
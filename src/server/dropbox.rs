@@ -0,0 +1,39 @@
+//! Emails posted to the dropbox by a mail server, e.g. from a Postfix pipe
+//! or an inbound-mail webhook sending the raw message as the body
+
+use crate::core::error::CLIERPError;
+use crate::modules::crm::dropbox::{dropbox_token, DropboxService, IngestOutcome};
+
+use super::http::{Request, Response};
+use super::tenant::Tenant;
+
+pub(super) fn handle(request: &Request, tenant: &Tenant, token: &str) -> Response {
+    if request.method != "POST" {
+        return Response::text(405, "Post the raw message");
+    }
+    let expected = match dropbox_token(tenant.secret()) {
+        Ok(expected) => expected,
+        Err(e) => {
+            tracing::error!("Cannot derive the dropbox token: {}", e);
+            return Response::text(500, "Dropbox unavailable");
+        }
+    };
+    // Compared in full so the time taken does not tell how much matched
+    let matches = token.len() == expected.len()
+        && token.bytes().zip(expected.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0;
+    if !matches {
+        return Response::text(404, "Not found");
+    }
+
+    let result = tenant.connection().and_then(|mut conn| DropboxService::ingest(&mut conn, &expected, &request.body));
+    match result {
+        Ok(IngestOutcome::Logged(activities)) => Response::text(200, &format!("Logged {} activit(ies)", activities.len())),
+        Ok(IngestOutcome::Unmatched) => Response::text(200, "No customer address"),
+        Ok(IngestOutcome::Duplicate) => Response::text(200, "Already logged"),
+        Err(CLIERPError::ValidationError(message)) => Response::text(400, &message),
+        Err(e) => {
+            tracing::error!("Dropbox message failed: {}", e);
+            Response::text(500, "Could not log the message")
+        }
+    }
+}
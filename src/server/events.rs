@@ -0,0 +1,73 @@
+//! The change event stream for external subscribers: `GET /events/<token>`
+//! answers the events after `since` as JSON lines. With `wait` the request
+//! is held until there is at least one, so a subscriber that loops on the
+//! last sequence it saw follows changes as they happen.
+
+use std::time::{Duration, Instant};
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::modules::system::events::{event_json, EventService, MAX_EVENTS_PER_READ};
+use crate::modules::system::portal::{PortalScope, PortalService};
+
+use super::http::{Request, Response};
+use super::tenant::Tenant;
+
+/// Longest a request is held waiting for events
+const MAX_WAIT: Duration = Duration::from_secs(30);
+/// How often a held request looks for new events
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub(super) fn handle(request: &Request, tenant: &Tenant, token: &str) -> Response {
+    if request.method != "GET" {
+        return Response::text(405, "The event stream can only be read");
+    }
+    read(request, tenant, token).unwrap_or_else(|error| match error {
+        CLIERPError::Authentication(message) => Response::text(403, &message),
+        CLIERPError::Validation(message) => Response::text(400, &message),
+        error => {
+            tracing::error!("Event stream request failed: {}", error);
+            Response::text(500, "Could not read events")
+        }
+    })
+}
+
+fn read(request: &Request, tenant: &Tenant, token: &str) -> CLIERPResult<Response> {
+    let number = |name: &str, default: i64| -> CLIERPResult<i64> {
+        match request.query.get(name) {
+            Some(value) => value
+                .parse::<i64>()
+                .ok()
+                .filter(|n| *n >= 0)
+                .ok_or_else(|| CLIERPError::Validation(format!("{} must be a whole number", name))),
+            None => Ok(default),
+        }
+    };
+    let since = number("since", 0)?;
+    let limit = number("limit", MAX_EVENTS_PER_READ)?;
+    let wait = Duration::from_secs(number("wait", 0)? as u64).min(MAX_WAIT);
+    let entity = request.query.get("entity").map(String::as_str).filter(|e| !e.is_empty());
+
+    let mut conn = tenant.connection()?;
+    PortalService::verify(&mut conn, tenant.secret(), token, PortalScope::Events)?;
+
+    let started = Instant::now();
+    let events = loop {
+        let events = EventService::since(&mut conn, since, entity, limit)?;
+        if !events.is_empty() || started.elapsed() + POLL_INTERVAL > wait {
+            break events;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
+
+    let mut body = String::new();
+    for event in &events {
+        body.push_str(&event_json(event).to_string());
+        body.push('\n');
+    }
+    Ok(Response {
+        status: 200,
+        content_type: "application/x-ndjson; charset=utf-8".to_string(),
+        body: body.into_bytes(),
+    })
+}
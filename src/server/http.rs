@@ -0,0 +1,213 @@
+//! Just enough HTTP/1.1 for the portal pages: one request per connection,
+//! form posts, and HTML responses.
+
+use std::collections::HashMap;
+use std::io::{BufRead, Read, Write};
+
+/// Largest request body accepted, in bytes; emails posted to the dropbox
+/// may carry attachments
+const MAX_BODY: usize = 10 * 1024 * 1024;
+/// Largest request line or header line accepted, in bytes
+const MAX_LINE: usize = 8 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    /// Decoded path without the query string
+    pub path: String,
+    pub query: HashMap<String, String>,
+    /// Header names are lowercase
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Read one request; `Err` carries the reason it is malformed
+    pub fn read(reader: &mut impl BufRead) -> Result<Self, String> {
+        let request_line = read_line(reader)?;
+        let mut parts = request_line.split_whitespace();
+        let (method, target) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(method), Some(target), Some(version)) if version.starts_with("HTTP/1.") => (method, target),
+            _ => return Err(format!("malformed request line '{}'", request_line)),
+        };
+
+        let mut headers = HashMap::new();
+        loop {
+            let line = read_line(reader)?;
+            if line.is_empty() {
+                break;
+            }
+            let (name, value) = line.split_once(':').ok_or_else(|| format!("malformed header '{}'", line))?;
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+
+        let length = match headers.get("content-length") {
+            Some(value) => value.parse::<usize>().map_err(|_| "invalid Content-Length".to_string())?,
+            None => 0,
+        };
+        if length > MAX_BODY {
+            return Err("request body too large".to_string());
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        Ok(Self {
+            method: method.to_uppercase(),
+            path: percent_decode(path, false),
+            query: parse_form(query),
+            headers,
+            body,
+        })
+    }
+
+    /// Fields of an `application/x-www-form-urlencoded` body
+    pub fn form(&self) -> HashMap<String, String> {
+        parse_form(&String::from_utf8_lossy(&self.body))
+    }
+
+    /// Path segments, e.g. `["supplier", "po", "<token>"]`
+    pub fn segments(&self) -> Vec<&str> {
+        self.path.split('/').filter(|s| !s.is_empty()).collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: String,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn html(status: u16, body: String) -> Self {
+        Self {
+            status,
+            content_type: "text/html; charset=utf-8".to_string(),
+            body: body.into_bytes(),
+        }
+    }
+
+    pub fn text(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8".to_string(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    pub fn write_to(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        write!(
+            writer,
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\n\
+             X-Content-Type-Options: nosniff\r\nConnection: close\r\n\r\n",
+            self.status,
+            reason(self.status),
+            self.content_type,
+            self.body.len()
+        )?;
+        writer.write_all(&self.body)?;
+        writer.flush()
+    }
+}
+
+/// Decode `key=value&...` pairs; `+` is a space
+pub fn parse_form(input: &str) -> HashMap<String, String> {
+    input
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key, true), percent_decode(value, true))
+        })
+        .collect()
+}
+
+/// Decode `%XX` escapes, and `+` as a space when `plus_as_space`
+pub fn percent_decode(input: &str, plus_as_space: bool) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => match (hex_value(bytes[i + 1]), hex_value(bytes[i + 2])) {
+                (Some(high), Some(low)) => {
+                    decoded.push((high << 4) | low);
+                    i += 3;
+                    continue;
+                }
+                _ => decoded.push(b'%'),
+            },
+            b'+' if plus_as_space => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|d| d as u8)
+}
+
+fn read_line(reader: &mut impl BufRead) -> Result<String, String> {
+    let mut line = Vec::new();
+    Read::take(&mut *reader, MAX_LINE as u64 + 1)
+        .read_until(b'\n', &mut line)
+        .map_err(|e| e.to_string())?;
+    if line.len() > MAX_LINE {
+        return Err("line too long".to_string());
+    }
+    if line.last() != Some(&b'\n') {
+        return Err("connection closed before the request ended".to_string());
+    }
+    Ok(String::from_utf8_lossy(&line).trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_form_post() {
+        let body = "date=2024-05-01&message=Tom+%26+J%C3%A9";
+        let raw = format!(
+            "POST /supplier/po/abc/propose?x=1 HTTP/1.1\r\nHost: erp\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let request = Request::read(&mut raw.as_bytes()).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.segments(), vec!["supplier", "po", "abc", "propose"]);
+        assert_eq!(request.query["x"], "1");
+        assert_eq!(request.headers["host"], "erp");
+        let form = request.form();
+        assert_eq!(form["date"], "2024-05-01");
+        assert_eq!(form["message"], "Tom & Jé");
+    }
+
+    #[test]
+    fn test_malformed_requests() {
+        assert!(Request::read(&mut "GARBAGE\r\n\r\n".as_bytes()).is_err());
+        assert!(Request::read(&mut "GET / HTTP/1.1\r\nHost".as_bytes()).is_err());
+        assert!(Request::read(&mut "POST / HTTP/1.1\r\nContent-Length: 999999\r\n\r\n".as_bytes()).is_err());
+        assert_eq!(percent_decode("100%", false), "100%");
+        assert_eq!(percent_decode("a%zzb", false), "a%zzb");
+        assert_eq!(percent_decode("%é", false), "%é");
+    }
+}
@@ -0,0 +1,177 @@
+//! `clierp system serve`: a small HTTP server for the pages outside parties open
+//! from portal links, the managers' dashboard, the change event stream, user
+//! provisioning from an identity feed, and for emails posted to the CRM
+//! dropbox. Each connection is handled on its own thread with its own
+//! database connection; there are no sessions, the link token in the URL (or,
+//! for provisioning, the bearer token) is the only credential. With
+//! `server.tenants` configured one process serves several companies, each
+//! request confined to its tenant's database. SIGHUP, or `POST /reload` with
+//! `server.reload_token`, reloads the log level and cache settings.
+
+mod customer;
+mod dashboard;
+mod dropbox;
+mod events;
+pub mod http;
+pub mod reload;
+mod scim;
+mod supplier;
+pub mod tenant;
+
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::core::config::CLIERPConfig;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::utils::html::{escape_html, html_page};
+use crate::utils::cache::QueryCache;
+use http::{Request, Response};
+use tenant::Tenants;
+
+/// How long a client may take to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(15);
+
+pub struct Server {
+    bind: String,
+    tenants: Arc<Tenants>,
+}
+
+impl Server {
+    /// Server on `bind`, or the configured address
+    pub fn new(config: &CLIERPConfig, bind: Option<String>) -> CLIERPResult<Self> {
+        let tenants = Tenants::from_config(config)?;
+        if tenants.is_multi() {
+            // Cached results are keyed by query, not by company
+            QueryCache::current().set_enabled(false);
+        }
+        reload::install(config, tenants.is_multi());
+        Ok(Self {
+            bind: bind.unwrap_or_else(|| config.server.bind.clone()),
+            tenants: Arc::new(tenants),
+        })
+    }
+
+    pub fn bind_address(&self) -> &str {
+        &self.bind
+    }
+
+    /// Serve until the process is stopped
+    pub fn run(&self) -> CLIERPResult<()> {
+        let listener = TcpListener::bind(&self.bind)
+            .map_err(|e| CLIERPError::IoError(format!("Cannot listen on {}: {}", self.bind, e)))?;
+        tracing::info!("Serving portal pages on {}", self.bind);
+        reload::watch_hangup();
+
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let tenants = self.tenants.clone();
+                    std::thread::spawn(move || handle_connection(stream, &tenants));
+                }
+                Err(e) => tracing::warn!("Failed to accept connection: {}", e),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn handle_connection(stream: TcpStream, tenants: &Tenants) {
+    let peer = stream.peer_addr().map(|a| a.to_string()).unwrap_or_default();
+    if let Err(e) = stream.set_read_timeout(Some(READ_TIMEOUT)) {
+        tracing::warn!("Could not set a read timeout for {}: {}", peer, e);
+    }
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(e) => {
+            tracing::warn!("Dropping connection from {}: {}", peer, e);
+            return;
+        }
+    };
+
+    let response = match Request::read(&mut BufReader::new(stream)) {
+        Ok(request) => {
+            let response = route(&request, tenants);
+            tracing::info!("{} {} {} -> {}", peer, request.method, redact(&request.path), response.status);
+            response
+        }
+        Err(e) => {
+            tracing::debug!("Bad request from {}: {}", peer, e);
+            Response::text(400, "Bad request")
+        }
+    };
+    if let Err(e) = response.write_to(&mut writer) {
+        tracing::debug!("Could not answer {}: {}", peer, e);
+    }
+}
+
+/// Response to one request
+pub fn route(request: &Request, tenants: &Tenants) -> Response {
+    let segments = request.segments();
+    if let ("GET", []) = (request.method.as_str(), segments.as_slice()) {
+        return Response::text(200, "CLIERP");
+    }
+    if let ["reload"] = segments.as_slice() {
+        return reload::handle(request);
+    }
+    let tenant = match tenants.resolve(request) {
+        Ok(tenant) => tenant,
+        Err(message) => return Response::html(404, message_page("Not found", &message, false)),
+    };
+    // Everything the handler reaches through `get_connection` is this tenant's
+    let _scope = tenant.enter();
+    match (request.method.as_str(), segments.as_slice()) {
+        (_, ["supplier", "po", token, rest @ ..]) => supplier::handle(request, tenant, token, rest),
+        (_, ["customer", token, rest @ ..]) => customer::handle(request, tenant, token, rest),
+        (_, ["dashboard", token, rest @ ..]) => dashboard::handle(request, tenant, token, rest),
+        (_, ["dropbox", token]) => dropbox::handle(request, tenant, token),
+        (_, ["events", token]) => events::handle(request, tenant, token),
+        (_, ["scim", "v2", rest @ ..]) => scim::handle(request, tenant, rest),
+        _ => Response::html(404, message_page("Not found", "There is nothing at this address.", false)),
+    }
+}
+
+/// Page for an error; details of unexpected errors stay in the log
+pub(crate) fn error_response(error: CLIERPError) -> Response {
+    match error {
+        CLIERPError::Authentication(message) => Response::html(403, message_page("Link not valid", &message, false)),
+        CLIERPError::NotFound(message) => Response::html(404, message_page("Not found", &message, false)),
+        CLIERPError::Validation(message)
+        | CLIERPError::ValidationError(message)
+        | CLIERPError::InvalidInput(message)
+        | CLIERPError::BusinessLogic(message) => {
+            Response::html(400, message_page("Could not save", &message, false))
+        }
+        error => {
+            tracing::error!("Portal request failed: {}", error);
+            Response::html(
+                500,
+                message_page("Something went wrong", "Please try again later or contact your buyer.", false),
+            )
+        }
+    }
+}
+
+/// Page with a single notice or error box
+pub(crate) fn message_page(title: &str, message: &str, ok: bool) -> String {
+    let class = if ok { "notice" } else { "error" };
+    html_page(title, &format!("<p class=\"{}\">{}</p>\n", class, escape_html(message)))
+}
+
+/// Path with link tokens cut short, for the request log
+fn redact(path: &str) -> String {
+    // The dropbox token is short but just as secret
+    if path.starts_with("/dropbox/") {
+        return "/dropbox/...".to_string();
+    }
+    path.split('/')
+        .map(|segment| if segment.len() > 32 {
+            format!("{}...", segment.chars().take(8).collect::<String>())
+        } else {
+            segment.to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
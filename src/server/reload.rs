@@ -0,0 +1,138 @@
+//! Hot reload of the settings in `RELOADABLE_SETTINGS` while the server
+//! runs, on SIGHUP or a `POST /reload` carrying `server.reload_token` as a
+//! bearer token. Other changed settings are logged as needing a restart.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use once_cell::sync::OnceCell;
+use serde_json::Value;
+
+use crate::core::config::CLIERPConfig;
+use crate::core::error::CLIERPError;
+use crate::core::logging::set_log_level;
+use crate::core::result::CLIERPResult;
+use crate::core::settings::{display_setting, flatten_settings, RELOADABLE_SETTINGS};
+use crate::utils::cache::QueryCache;
+
+use super::http::{Request, Response};
+
+struct Running {
+    config: CLIERPConfig,
+    /// Multi-tenant servers keep the query cache off whatever is configured
+    multi_tenant: bool,
+}
+
+static RUNNING: OnceCell<Mutex<Running>> = OnceCell::new();
+
+/// Remember the configuration the server started with
+pub(super) fn install(config: &CLIERPConfig, multi_tenant: bool) {
+    let _ = RUNNING.set(Mutex::new(Running {
+        config: config.clone(),
+        multi_tenant,
+    }));
+}
+
+/// Load the configuration again and apply the reloadable settings that
+/// changed, returning them as "key = value"
+pub fn reload() -> CLIERPResult<Vec<String>> {
+    let running = RUNNING
+        .get()
+        .ok_or_else(|| CLIERPError::Internal("The server is not running".to_string()))?;
+    let next = CLIERPConfig::load()?;
+    next.validate()?;
+
+    let mut running = running
+        .lock()
+        .map_err(|_| CLIERPError::Internal("Configuration lock poisoned".to_string()))?;
+    let before: HashMap<String, Value> = flatten_settings(&running.config)?.into_iter().collect();
+    let mut applied = Vec::new();
+    for (key, new) in flatten_settings(&next)? {
+        if before.get(&key) == Some(&new) {
+            continue;
+        }
+        if !RELOADABLE_SETTINGS.contains(&key.as_str()) {
+            tracing::warn!("{} changed; restart the server to apply it", key);
+            continue;
+        }
+        match key.as_str() {
+            "logging.level" => set_log_level(&next.logging.level)?,
+            "cache.enabled" if running.multi_tenant => {
+                tracing::warn!("cache.enabled changed; the cache stays off while serving several tenants");
+                continue;
+            }
+            "cache.enabled" => QueryCache::current().set_enabled(next.cache.enabled),
+            _ => continue,
+        }
+        applied.push(format!("{} = {}", key, display_setting(&new)));
+    }
+    running.config = next;
+
+    tracing::info!("Configuration reloaded: {}", if applied.is_empty() { "nothing to apply".to_string() } else { applied.join(", ") });
+    Ok(applied)
+}
+
+/// Reload on every SIGHUP for as long as the process runs
+pub(super) fn watch_hangup() {
+    #[cfg(unix)]
+    match tokio::runtime::Handle::try_current() {
+        Ok(runtime) => {
+            runtime.spawn(async {
+                use tokio::signal::unix::{signal, SignalKind};
+
+                let mut hangups = match signal(SignalKind::hangup()) {
+                    Ok(hangups) => hangups,
+                    Err(e) => {
+                        tracing::warn!("Cannot listen for SIGHUP, reload with POST /reload: {}", e);
+                        return;
+                    }
+                };
+                while hangups.recv().await.is_some() {
+                    if let Err(e) = reload() {
+                        tracing::error!("Reloading the configuration failed, keeping the current one: {}", e);
+                    }
+                }
+            });
+        }
+        Err(_) => tracing::warn!("No async runtime to listen for SIGHUP; reload with POST /reload"),
+    }
+}
+
+/// `POST /reload`; not served unless `server.reload_token` is set
+pub(super) fn handle(request: &Request) -> Response {
+    let expected = RUNNING
+        .get()
+        .and_then(|running| running.lock().ok())
+        .and_then(|running| running.config.server.reload_token.clone())
+        .filter(|token| !token.is_empty());
+    let Some(expected) = expected else {
+        return Response::text(404, "Not found");
+    };
+    if request.method != "POST" {
+        return Response::text(405, "Use POST to reload the configuration");
+    }
+    let token = request
+        .headers
+        .get("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .unwrap_or_default();
+    if !same_token(token, &expected) {
+        return Response::text(403, "A valid bearer token is required");
+    }
+
+    match reload() {
+        Ok(applied) if applied.is_empty() => Response::text(200, "Reloaded; nothing to apply\n"),
+        Ok(applied) => Response::text(200, &format!("Reloaded:\n{}\n", applied.join("\n"))),
+        Err(e) => {
+            tracing::error!("Reloading the configuration failed, keeping the current one: {}", e);
+            Response::text(400, &format!("Reload failed, keeping the current configuration: {}\n", e))
+        }
+    }
+}
+
+/// Compare without stopping at the first differing byte
+fn same_token(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given.bytes().zip(expected.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
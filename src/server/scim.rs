@@ -0,0 +1,156 @@
+//! SCIM 2.0 user provisioning for identity providers and HR systems:
+//! `/scim/v2/Users` lists, creates, replaces, patches and deactivates users.
+//! Requests carry the link token from `clierp auth scim share` as a bearer
+//! token. Deleting a user only deactivates them, so their history stays
+//! attributed.
+
+use serde_json::{json, Value};
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::models::User;
+use crate::database::DatabaseConnection;
+use crate::modules::system::portal::{PortalScope, PortalService};
+use crate::modules::system::provisioning::{
+    apply_scim_patch, parse_scim_user, parse_username_filter, scim_user_json, ProvisioningService,
+    MAX_USERS_PER_PAGE, SCIM_ERROR_SCHEMA, SCIM_LIST_SCHEMA,
+};
+
+use super::http::{Request, Response};
+use super::tenant::Tenant;
+
+pub(super) fn handle(request: &Request, tenant: &Tenant, rest: &[&str]) -> Response {
+    serve(request, tenant, rest).unwrap_or_else(|error| match error {
+        CLIERPError::Authentication(message) => scim_error(401, &message),
+        CLIERPError::Authorization(message) => scim_error(403, &message),
+        CLIERPError::NotFound(message) => scim_error(404, &message),
+        CLIERPError::AlreadyExists(message) => scim_error(409, &message),
+        CLIERPError::Validation(message) | CLIERPError::ValidationError(message) => scim_error(400, &message),
+        error => {
+            tracing::error!("SCIM request failed: {}", error);
+            scim_error(500, "Could not provision the user")
+        }
+    })
+}
+
+fn serve(request: &Request, tenant: &Tenant, rest: &[&str]) -> CLIERPResult<Response> {
+    let token = request
+        .headers
+        .get("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .ok_or_else(|| CLIERPError::Authentication("A bearer token is required".to_string()))?;
+    let mut conn = tenant.connection()?;
+    PortalService::verify(&mut conn, tenant.secret(), token, PortalScope::Scim)?;
+
+    match (request.method.as_str(), rest) {
+        ("GET", ["Users"]) => list(request, &mut conn),
+        ("POST", ["Users"]) => {
+            let feed = parse_scim_user(&body(request)?)?;
+            let user = ProvisioningService::create(&mut conn, &feed)?;
+            user_response(&mut conn, 201, &user)
+        }
+        ("GET", ["Users", id]) => {
+            let user = ProvisioningService::get(&mut conn, user_id(id)?)?;
+            user_response(&mut conn, 200, &user)
+        }
+        ("PUT", ["Users", id]) => {
+            let feed = parse_scim_user(&body(request)?)?;
+            let user = ProvisioningService::replace(&mut conn, user_id(id)?, &feed)?;
+            user_response(&mut conn, 200, &user)
+        }
+        ("PATCH", ["Users", id]) => {
+            let user = ProvisioningService::get(&mut conn, user_id(id)?)?;
+            let mut feed = ProvisioningService::feed_user(&mut conn, &user)?;
+            apply_scim_patch(&mut feed, &body(request)?)?;
+            let user = ProvisioningService::replace(&mut conn, user.id, &feed)?;
+            user_response(&mut conn, 200, &user)
+        }
+        ("DELETE", ["Users", id]) => {
+            ProvisioningService::set_active(&mut conn, user_id(id)?, false)?;
+            Ok(Response {
+                status: 204,
+                content_type: "application/scim+json".to_string(),
+                body: Vec::new(),
+            })
+        }
+        (_, ["Users", ..]) => Ok(scim_error(405, "Method not supported")),
+        _ => Ok(scim_error(404, "Only Users are provisioned")),
+    }
+}
+
+fn list(request: &Request, conn: &mut DatabaseConnection) -> CLIERPResult<Response> {
+    let number = |name: &str, default: i64| -> CLIERPResult<i64> {
+        match request.query.get(name) {
+            Some(value) => value
+                .parse::<i64>()
+                .ok()
+                .filter(|n| *n >= 0)
+                .ok_or_else(|| CLIERPError::Validation(format!("{} must be a whole number", name))),
+            None => Ok(default),
+        }
+    };
+    let start = number("startIndex", 1)?.max(1);
+    let count = number("count", MAX_USERS_PER_PAGE)?.min(MAX_USERS_PER_PAGE);
+
+    let (total, users) = match request.query.get("filter") {
+        Some(filter) => {
+            let username = parse_username_filter(filter).ok_or_else(|| {
+                CLIERPError::Validation("Only userName eq \"...\" filters are supported".to_string())
+            })?;
+            let users: Vec<User> = ProvisioningService::find_by_username(conn, &username)?.into_iter().collect();
+            (users.len() as i64, users)
+        }
+        None => ProvisioningService::list(conn, start, count)?,
+    };
+
+    let mut resources = Vec::with_capacity(users.len());
+    for user in &users {
+        let employee_number = ProvisioningService::employee_code(conn, user)?;
+        resources.push(scim_user_json(user, employee_number.as_deref()));
+    }
+    Ok(scim_json(
+        200,
+        &json!({
+            "schemas": [SCIM_LIST_SCHEMA],
+            "totalResults": total,
+            "startIndex": start,
+            "itemsPerPage": resources.len(),
+            "Resources": resources,
+        }),
+    ))
+}
+
+fn user_response(conn: &mut DatabaseConnection, status: u16, user: &User) -> CLIERPResult<Response> {
+    let employee_number = ProvisioningService::employee_code(conn, user)?;
+    Ok(scim_json(status, &scim_user_json(user, employee_number.as_deref())))
+}
+
+fn body(request: &Request) -> CLIERPResult<Value> {
+    serde_json::from_slice(&request.body)
+        .map_err(|e| CLIERPError::Validation(format!("The body is not valid JSON: {}", e)))
+}
+
+fn user_id(id: &str) -> CLIERPResult<i32> {
+    id.parse()
+        .map_err(|_| CLIERPError::NotFound(format!("User {} not found", id)))
+}
+
+fn scim_json(status: u16, body: &Value) -> Response {
+    Response {
+        status,
+        content_type: "application/scim+json".to_string(),
+        body: body.to_string().into_bytes(),
+    }
+}
+
+fn scim_error(status: u16, detail: &str) -> Response {
+    scim_json(
+        status,
+        &json!({
+            "schemas": [SCIM_ERROR_SCHEMA],
+            "status": status.to_string(),
+            "detail": detail,
+        }),
+    )
+}
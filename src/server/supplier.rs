@@ -0,0 +1,145 @@
+//! Purchase order pages opened from supplier portal links
+
+use chrono::NaiveDate;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::PurchaseOrderWithItems;
+use crate::modules::inventory::supplier_portal::{SupplierPortalService, SupplierReply};
+use crate::utils::formatting::format_currency;
+use crate::utils::html::{escape_html, html_page, html_table};
+
+use super::http::{Request, Response};
+use super::tenant::Tenant;
+use super::{error_response, message_page};
+
+pub(super) fn handle(request: &Request, tenant: &Tenant, token: &str, rest: &[&str]) -> Response {
+    let result = match (request.method.as_str(), rest) {
+        ("GET", []) => show(tenant, token),
+        ("POST", [action]) => respond(request, tenant, token, action),
+        _ => return Response::html(405, message_page("Not allowed", "This page cannot be used that way.", false)),
+    };
+    result.unwrap_or_else(error_response)
+}
+
+fn show(tenant: &Tenant, token: &str) -> CLIERPResult<Response> {
+    let mut conn = tenant.connection()?;
+    let (_, po) = SupplierPortalService::open(&mut conn, tenant.secret(), token)?;
+    let responses = SupplierPortalService::responses(&mut conn, po.purchase_order.id)?;
+
+    let order = &po.purchase_order;
+    let mut body = format!(
+        "<p>For <strong>{}</strong> &middot; ordered {} &middot; expected {} &middot; status <strong>{}</strong></p>\n",
+        escape_html(&po.supplier.name),
+        order.order_date,
+        order.expected_date.map(|d| d.to_string()).unwrap_or_else(|| "-".to_string()),
+        escape_html(&order.status)
+    );
+    body.push_str(&items_table(&po));
+    if let Some(notes) = &order.notes {
+        body.push_str(&format!("<p>{}</p>\n", escape_html(notes)));
+    }
+
+    if !responses.is_empty() {
+        body.push_str("<h2>Your answers</h2>\n<ul>\n");
+        for response in &responses {
+            let mut line = format!("{} &middot; {}", response.created_at.format("%Y-%m-%d %H:%M"), escape_html(&response.kind));
+            if let Some(date) = response.proposed_date {
+                line.push_str(&format!(" for {}", date));
+            }
+            if let Some(tracking) = &response.tracking_number {
+                line.push_str(&format!(" &middot; tracking {}", escape_html(tracking)));
+            }
+            body.push_str(&format!("<li>{}</li>\n", line));
+        }
+        body.push_str("</ul>\n");
+    }
+
+    let action = |name: &str| format!("/supplier/po/{}/{}", escape_html(token), name);
+    let shipped = order.status == "shipped";
+    if !shipped {
+        body.push_str(&format!(
+            "<h2>Confirm the order</h2>\n<form method=\"post\" action=\"{}\">\n\
+             <label>Message <textarea name=\"message\" rows=\"2\"></textarea></label>\n\
+             <button type=\"submit\">Confirm as ordered</button>\n</form>\n",
+            action("confirm")
+        ));
+        body.push_str(&format!(
+            "<h2>Propose a delivery date</h2>\n<form method=\"post\" action=\"{}\">\n\
+             <label>Delivery date <input type=\"date\" name=\"date\" required></label>\n\
+             <label>Message <textarea name=\"message\" rows=\"2\"></textarea></label>\n\
+             <button type=\"submit\">Confirm with this date</button>\n</form>\n",
+            action("propose")
+        ));
+    }
+    body.push_str(&format!(
+        "<h2>Shipping notice</h2>\n<form method=\"post\" action=\"{}\">\n\
+         <label>Carrier <input type=\"text\" name=\"carrier\"></label>\n\
+         <label>Tracking number <input type=\"text\" name=\"tracking_number\"></label>\n\
+         <label>Message <textarea name=\"message\" rows=\"2\"></textarea></label>\n\
+         <button type=\"submit\">Goods have shipped</button>\n</form>\n",
+        action("ship")
+    ));
+
+    Ok(Response::html(200, html_page(&format!("Purchase order {}", order.po_number), &body)))
+}
+
+fn respond(request: &Request, tenant: &Tenant, token: &str, action: &str) -> CLIERPResult<Response> {
+    let form = request.form();
+    let field = |name: &str| form.get(name).cloned();
+    let reply = match action {
+        "confirm" => SupplierReply::Confirm { message: field("message") },
+        "propose" => {
+            let date = field("date").unwrap_or_default();
+            let date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+                .map_err(|_| CLIERPError::Validation("Please enter the delivery date as YYYY-MM-DD".to_string()))?;
+            SupplierReply::ProposeDate { date, message: field("message") }
+        }
+        "ship" => SupplierReply::Ship {
+            carrier: field("carrier"),
+            tracking_number: field("tracking_number"),
+            message: field("message"),
+        },
+        _ => return Ok(Response::html(404, message_page("Not found", "There is nothing at this address.", false))),
+    };
+
+    let mut conn = tenant.connection()?;
+    let (link, po) = SupplierPortalService::open(&mut conn, tenant.secret(), token)?;
+    SupplierPortalService::respond(&mut conn, &link, reply)?;
+
+    let body = format!(
+        "<p class=\"notice\">Thank you, your answer for {} has been passed to the buyer.</p>\n\
+         <p><a href=\"/supplier/po/{}\">Back to the order</a></p>\n",
+        escape_html(&po.purchase_order.po_number),
+        escape_html(token)
+    );
+    Ok(Response::html(200, html_page("Answer received", &body)))
+}
+
+fn items_table(po: &PurchaseOrderWithItems) -> String {
+    let headers: Vec<String> = ["SKU", "Product", "Quantity", "Unit cost", "Total"]
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+    let rows: Vec<Vec<String>> = po
+        .items
+        .iter()
+        .map(|item| {
+            vec![
+                item.product_sku.clone(),
+                item.product_name.clone(),
+                format!("{} {}", item.purchase_item.quantity, item.unit),
+                format_currency(item.purchase_item.unit_cost),
+                format_currency(item.purchase_item.total_cost),
+            ]
+        })
+        .collect();
+    let totals = vec![
+        "Total".to_string(),
+        String::new(),
+        String::new(),
+        String::new(),
+        format_currency(po.purchase_order.total_amount),
+    ];
+    html_table(&headers, &rows, Some(&totals))
+}
@@ -0,0 +1,209 @@
+//! Company profiles served by one process. Each tenant has its own
+//! connection pool and link secret; a request names its tenant with a
+//! subdomain of `server.tenant_domain` or the `X-CLIERP-Tenant` header, and
+//! when it gives both they must agree. Requests for no known tenant are
+//! refused rather than served from a default company.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::core::config::CLIERPConfig;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::connection::{DatabaseConnection, DatabaseManager, PoolScope, SqlitePool};
+use crate::database::migrations;
+
+use super::http::Request;
+
+/// Header naming the tenant, lowercase as `Request::headers` keeps it
+pub const TENANT_HEADER: &str = "x-clierp-tenant";
+
+pub struct Tenant {
+    name: String,
+    secret: String,
+    pool: Arc<SqlitePool>,
+}
+
+impl Tenant {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Secret this tenant's links are signed with
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+
+    /// Make this tenant's database the one `get_connection` returns on the
+    /// current thread while the scope lives
+    pub fn enter(&self) -> PoolScope {
+        DatabaseManager::scope(self.pool.clone())
+    }
+
+    /// Connection to this tenant's database. Fails if the thread is not
+    /// inside this tenant's scope, so a handler can never read through
+    /// services that fetch their own connection from another company.
+    pub fn connection(&self) -> CLIERPResult<DatabaseConnection> {
+        let pool = DatabaseManager::get_pool()?;
+        if !Arc::ptr_eq(&pool, &self.pool) {
+            return Err(CLIERPError::Internal(format!(
+                "Request for tenant '{}' is running outside its database scope",
+                self.name
+            )));
+        }
+        pool.get().map_err(|e| {
+            CLIERPError::DatabaseConnection(diesel::ConnectionError::BadConnection(e.to_string()))
+        })
+    }
+}
+
+pub enum Tenants {
+    /// The configured database, for every request
+    Single(Tenant),
+    /// Configured company profiles by name
+    Multi {
+        tenants: HashMap<String, Tenant>,
+        domain: Option<String>,
+    },
+}
+
+impl Tenants {
+    /// Open a pool per configured tenant and bring each database up to the
+    /// current schema; without tenants the main database is served alone
+    pub fn from_config(config: &CLIERPConfig) -> CLIERPResult<Self> {
+        if config.server.tenants.is_empty() {
+            return Ok(Tenants::Single(Tenant {
+                name: "default".to_string(),
+                secret: config.auth.jwt_secret.clone(),
+                pool: DatabaseManager::get_pool()?,
+            }));
+        }
+
+        let mut databases = HashSet::new();
+        let mut secrets = HashSet::new();
+        let mut tenants = HashMap::new();
+        for profile in &config.server.tenants {
+            let name = profile.name.trim().to_lowercase();
+            if !is_tenant_name(&name) {
+                return Err(invalid(format!(
+                    "Tenant name '{}' must be a subdomain label: letters, digits and '-'",
+                    profile.name
+                )));
+            }
+            if tenants.contains_key(&name) {
+                return Err(invalid(format!("Tenant '{}' is configured twice", name)));
+            }
+            if !databases.insert(profile.database_url.replace("sqlite:", "").trim().to_string()) {
+                return Err(invalid(format!(
+                    "Tenant '{}' shares its database with another tenant",
+                    name
+                )));
+            }
+            if profile.jwt_secret.trim().is_empty() || !secrets.insert(profile.jwt_secret.clone()) {
+                return Err(invalid(format!(
+                    "Tenant '{}' needs a link secret of its own",
+                    name
+                )));
+            }
+
+            let pool = DatabaseManager::build_pool(&profile.database_url, config)?;
+            let mut conn = DatabaseManager::establish_connection(&profile.database_url, config)?;
+            migrations::check_schema_version(&mut conn)?;
+            migrations::run_migrations(&mut conn)?;
+
+            tracing::info!("Serving tenant '{}' from {}", name, profile.database_url);
+            tenants.insert(
+                name.clone(),
+                Tenant {
+                    name,
+                    secret: profile.jwt_secret.clone(),
+                    pool: Arc::new(pool),
+                },
+            );
+        }
+
+        Ok(Tenants::Multi {
+            tenants,
+            domain: config.server.tenant_domain.as_ref().map(|d| d.trim().trim_matches('.').to_lowercase()),
+        })
+    }
+
+    pub fn is_multi(&self) -> bool {
+        matches!(self, Tenants::Multi { .. })
+    }
+
+    /// Tenant a request is for; `Err` says why none can serve it
+    pub fn resolve(&self, request: &Request) -> Result<&Tenant, String> {
+        match self {
+            Tenants::Single(tenant) => Ok(tenant),
+            Tenants::Multi { tenants, domain } => {
+                let name = requested_tenant(
+                    request.headers.get("host").map(String::as_str),
+                    request.headers.get(TENANT_HEADER).map(String::as_str),
+                    domain.as_deref(),
+                )?
+                .ok_or_else(|| "No company given for this address.".to_string())?;
+                tenants.get(&name).ok_or_else(|| "There is no such company here.".to_string())
+            }
+        }
+    }
+}
+
+/// Tenant named by the subdomain of `domain` in `host` and by the tenant
+/// header, lowercase; `Err` when the two disagree or the host is below the
+/// tenant level
+pub fn requested_tenant(
+    host: Option<&str>,
+    header: Option<&str>,
+    domain: Option<&str>,
+) -> Result<Option<String>, String> {
+    let from_host = match (host, domain) {
+        (Some(host), Some(domain)) => {
+            let host = host.rsplit_once(':').map_or(host, |(name, _)| name).to_lowercase();
+            match host.strip_suffix(domain).and_then(|prefix| prefix.strip_suffix('.')) {
+                Some(label) if !label.contains('.') => Some(label.to_string()),
+                Some(_) => return Err("This address is not a company's address.".to_string()),
+                None => None,
+            }
+        }
+        _ => None,
+    };
+    let from_header = header.map(|name| name.trim().to_lowercase()).filter(|name| !name.is_empty());
+
+    match (from_host, from_header) {
+        (Some(host), Some(header)) if host != header => {
+            Err("The company in the address and in the request do not match.".to_string())
+        }
+        (host, header) => Ok(host.or(header)),
+    }
+}
+
+fn invalid(message: String) -> CLIERPError {
+    CLIERPError::Configuration(config::ConfigError::Message(message))
+}
+
+fn is_tenant_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 63
+        && !name.starts_with('-')
+        && !name.ends_with('-')
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requested_tenant() {
+        let domain = Some("erp.example.com");
+        assert_eq!(requested_tenant(Some("acme.erp.example.com:8080"), None, domain), Ok(Some("acme".to_string())));
+        assert_eq!(requested_tenant(Some("ACME.erp.example.com"), Some("acme"), domain), Ok(Some("acme".to_string())));
+        assert_eq!(requested_tenant(Some("erp.example.com"), Some(" Globex "), domain), Ok(Some("globex".to_string())));
+        assert_eq!(requested_tenant(Some("127.0.0.1:8080"), None, domain), Ok(None));
+        assert_eq!(requested_tenant(Some("acme.erp.example.com"), None, None), Ok(None));
+        assert!(requested_tenant(Some("acme.erp.example.com"), Some("globex"), domain).is_err());
+        assert!(requested_tenant(Some("a.acme.erp.example.com"), None, domain).is_err());
+        assert!(requested_tenant(Some("acmeerp.example.com"), None, domain).is_ok_and(|t| t.is_none()));
+    }
+}
@@ -0,0 +1,49 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::core::config::CLIERPConfig;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::connection::{DatabaseConnection, DatabaseManager, PoolScope, SqlitePool};
+use crate::database::migrations;
+
+static NEXT_DATABASE: AtomicUsize = AtomicUsize::new(0);
+
+/// A fully migrated in-memory database. Services called on the thread that
+/// created it use it instead of the configured database until it is
+/// dropped, so tests running in parallel never see each other's data.
+pub struct TestDatabase {
+    pool: Arc<SqlitePool>,
+    _scope: PoolScope,
+}
+
+impl TestDatabase {
+    pub fn new() -> CLIERPResult<Self> {
+        // A named shared-cache database lives as long as the pool keeps a
+        // connection open, and every connection of the pool sees it
+        let url = format!(
+            "file:clierp-test-{}-{}?mode=memory&cache=shared",
+            std::process::id(),
+            NEXT_DATABASE.fetch_add(1, Ordering::Relaxed)
+        );
+        let pool = Arc::new(DatabaseManager::build_pool(&url, &CLIERPConfig::default())?);
+
+        let mut conn = pool.get().map_err(|e| {
+            CLIERPError::DatabaseConnection(diesel::ConnectionError::BadConnection(e.to_string()))
+        })?;
+        // The ledger and CRM tables come from the SQL migrations, and the
+        // built-in tables summarize them, so the SQL migrations go first
+        migrations::run_sql_migrations(&mut conn)?;
+        migrations::run_migrations(&mut conn)?;
+        drop(conn);
+
+        let scope = DatabaseManager::scope(pool.clone());
+        Ok(Self { pool, _scope: scope })
+    }
+
+    pub fn connection(&self) -> CLIERPResult<DatabaseConnection> {
+        self.pool.get().map_err(|e| {
+            CLIERPError::DatabaseConnection(diesel::ConnectionError::BadConnection(e.to_string()))
+        })
+    }
+}
@@ -0,0 +1,180 @@
+use chrono::NaiveDate;
+use diesel::prelude::*;
+
+use crate::core::result::CLIERPResult;
+use crate::database::models::{Category, Department, Employee, NewUser, Product, User};
+use crate::database::schema::{categories, departments, users};
+use crate::database::{Customer, CustomerType, DatabaseConnection, Supplier};
+use crate::modules::crm::CustomerService;
+use crate::modules::hr::{CreateEmployeeRequest, EmployeeService};
+use crate::modules::inventory::{ProductService, SupplierService};
+
+use super::database::TestDatabase;
+
+/// Username of the administrator every fixture company has
+pub const FIXTURE_ADMIN: &str = "fixture.admin";
+
+/// Builds a company with a known set of records in a fresh
+/// [`TestDatabase`].
+///
+/// Records are numbered from 1 and everything about them follows from the
+/// number, so the same builder always produces the same data: product 3 is
+/// SKU `FIX-P0003`, sells for 3,000 at a cost of 1,800 and has 30 on hand.
+#[derive(Debug, Clone, Default)]
+pub struct CompanyFixture {
+    products: usize,
+    customers: usize,
+    suppliers: usize,
+    employees: usize,
+}
+
+impl CompanyFixture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Products in the first default category
+    pub fn with_products(mut self, count: usize) -> Self {
+        self.products = count;
+        self
+    }
+
+    /// Business customers with a credit limit of 100,000
+    pub fn with_customers(mut self, count: usize) -> Self {
+        self.customers = count;
+        self
+    }
+
+    pub fn with_suppliers(mut self, count: usize) -> Self {
+        self.suppliers = count;
+        self
+    }
+
+    /// Employees of the first default department, hired on 2024-01-01
+    pub fn with_employees(mut self, count: usize) -> Self {
+        self.employees = count;
+        self
+    }
+
+    /// Create the database and records; services on this thread use the
+    /// database until the returned company is dropped
+    pub fn build(self) -> CLIERPResult<Company> {
+        let database = TestDatabase::new()?;
+        let mut conn = database.connection()?;
+
+        let admin = insert_admin(&mut conn)?;
+
+        let category = categories::table.order(categories::id.asc()).first::<Category>(&mut conn)?;
+        let product_service = ProductService::new();
+        let mut products = Vec::with_capacity(self.products);
+        for n in 1..=self.products as i32 {
+            products.push(product_service.create_product_with_connection(
+                &mut conn,
+                &format!("FIX-P{:04}", n),
+                &format!("Fixture Product {}", n),
+                None,
+                category.id,
+                1_000 * n,
+                600 * n,
+                10 * n,
+                5,
+                None,
+                "ea",
+                None,
+            )?);
+        }
+
+        let mut customers = Vec::with_capacity(self.customers);
+        for n in 1..=self.customers {
+            customers.push(CustomerService::create_customer(
+                &mut conn,
+                &format!("Fixture Customer {}", n),
+                CustomerType::Business,
+                Some(&format!("customer{}@example.com", n)),
+                None,
+                None,
+                Some(&format!("Fixture Customer {} Ltd", n)),
+                None,
+                Some(100_000),
+                None,
+            )?);
+        }
+
+        let mut suppliers = Vec::with_capacity(self.suppliers);
+        for n in 1..=self.suppliers {
+            suppliers.push(SupplierService::create_supplier(
+                &mut conn,
+                &format!("FIX-S{:03}", n),
+                &format!("Fixture Supplier {}", n),
+                None,
+                Some(&format!("supplier{}@example.com", n)),
+                None,
+                None,
+                Some("Net 30"),
+            )?);
+        }
+
+        let department = departments::table.order(departments::id.asc()).first::<Department>(&mut conn)?;
+        let employee_service = EmployeeService::new();
+        let mut employees = Vec::with_capacity(self.employees);
+        for n in 1..=self.employees {
+            employees.push(employee_service.create_employee(
+                &mut conn,
+                CreateEmployeeRequest {
+                    employee_code: None,
+                    name: format!("Fixture Employee {}", n),
+                    email: Some(format!("employee{}@example.com", n)),
+                    phone: None,
+                    department_id: department.id,
+                    position: "Staff".to_string(),
+                    hire_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap_or_default(),
+                    salary: 3_000_000,
+                },
+            )?);
+        }
+        drop(conn);
+
+        Ok(Company {
+            database,
+            admin,
+            products,
+            customers,
+            suppliers,
+            employees,
+        })
+    }
+}
+
+/// Records created by a [`CompanyFixture`], in creation order
+pub struct Company {
+    pub database: TestDatabase,
+    pub admin: User,
+    pub products: Vec<Product>,
+    pub customers: Vec<Customer>,
+    pub suppliers: Vec<Supplier>,
+    pub employees: Vec<Employee>,
+}
+
+impl Company {
+    pub fn connection(&self) -> CLIERPResult<DatabaseConnection> {
+        self.database.connection()
+    }
+}
+
+/// The password hash is not a real bcrypt hash, so the administrator
+/// cannot log in; tests pass its id as the acting user instead
+fn insert_admin(conn: &mut DatabaseConnection) -> CLIERPResult<User> {
+    diesel::insert_into(users::table)
+        .values(&NewUser {
+            username: FIXTURE_ADMIN.to_string(),
+            email: "fixture.admin@example.com".to_string(),
+            password_hash: "!fixture".to_string(),
+            employee_id: None,
+            role: "admin".to_string(),
+            is_active: true,
+        })
+        .execute(conn)?;
+    Ok(users::table
+        .filter(users::username.eq(FIXTURE_ADMIN))
+        .first::<User>(conn)?)
+}
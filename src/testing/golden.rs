@@ -0,0 +1,695 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use diesel::prelude::*;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::cli::commands::reports::render_report;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::database::schema::{
+    activities, attendances, campaign_leads, campaigns, deals, leads, payrolls, purchase_items, purchase_orders,
+    stock_movements,
+};
+use crate::database::{
+    ActivityType, AttendanceStatus, CampaignResponse, CampaignStatus, CampaignType, DatabaseConnection, DealStage,
+    LeadPriority, LeadStatus, NewAttendance,
+};
+use crate::modules::crm::{ActivityService, AttributionService, CampaignService, DealService, LeadService};
+use crate::modules::finance::{
+    AccountService, CreateAccountRequest, JournalEntryRequest, JournalLine, JournalService, PostingRules,
+};
+use crate::modules::hr::PayrollService;
+use crate::modules::inventory::{ProductService, PurchaseOrderItem, PurchaseOrderService, ReceiveItemData};
+use crate::modules::reporting::{
+    render_pdf, DateRange, ReportConfig, ReportData, ReportEngine, ReportFormat, SummaryService,
+};
+use crate::utils::cache::QueryCache;
+use crate::utils::fiscal::FiscalCalendar;
+
+use super::fixtures::{Company, CompanyFixture};
+
+/// Where `clierp reports verify-golden` and the golden tests keep the
+/// expected outputs, relative to the repository root
+pub const GOLDEN_DIR: &str = "tests/golden";
+
+/// Generator id and title of every report the generators know
+pub const GOLDEN_REPORTS: &[(&str, &str)] = &[
+    ("hr", "employee_summary"),
+    ("hr", "headcount"),
+    ("hr", "turnover"),
+    ("hr", "attendance_report"),
+    ("hr", "leave_utilization"),
+    ("hr", "document_compliance"),
+    ("hr", "payroll_report"),
+    ("hr", "hr_analytics"),
+    ("finance", "income_statement"),
+    ("finance", "balance_sheet"),
+    ("finance", "cash_flow"),
+    ("finance", "budget_vs_actual"),
+    ("finance", "financial_analytics"),
+    ("finance", "gross_margin"),
+    ("inventory", "stock_status"),
+    ("inventory", "stock_movement"),
+    ("inventory", "inventory_valuation"),
+    ("inventory", "purchase_analysis"),
+    ("inventory", "supplier_performance"),
+    ("inventory", "abc_analysis"),
+    ("inventory", "inventory_aging"),
+    ("inventory", "order_reconciliation"),
+    ("crm", "customer_analysis"),
+    ("crm", "sales_pipeline"),
+    ("crm", "lead_conversion"),
+    ("crm", "campaign_performance"),
+    ("crm", "sales_activity"),
+    ("crm", "revenue_forecast"),
+    ("crm", "marketing_attribution"),
+];
+
+/// Dates and timestamps, which change from run to run; amounts and counts
+/// are left alone
+static VOLATILE_DATE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(19|20)\d{2}-\d{2}(-\d{2}([ T]\d{2}:\d{2}(:\d{2}(\.\d+)?)?)?)?\b").expect("valid date pattern")
+});
+static PROCESSING_TIME: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#""processing_time_ms": \d+"#).expect("valid processing time pattern"));
+
+/// Set to rewrite golden files that differ or are missing, as
+/// `clierp reports verify-golden --update` does
+pub const GOLDEN_UPDATE_ENV: &str = "CLIERP_UPDATE_GOLDEN";
+
+/// Chart of accounts of the seed company: code, name and type
+const SEED_ACCOUNTS: &[(&str, &str, &str)] = &[
+    ("1000", "Cash", "asset"),
+    ("1300", "Inventory", "asset"),
+    ("2100", "Accounts Payable", "liability"),
+    ("3000", "Owner's Capital", "equity"),
+    ("4000", "Sales", "revenue"),
+    ("5000", "Cost of Goods Sold", "expense"),
+    ("6000", "Salaries", "expense"),
+];
+
+/// Year, month and day of a seed record
+type SeedDate = (i32, u32, u32);
+
+/// Date, description, debited and credited account codes and amount
+type SeedEntry = (SeedDate, &'static str, &'static str, &'static str, i32);
+
+/// Journal entries of the seed company
+const SEED_ENTRIES: &[SeedEntry] = &[
+    ((2025, 1, 2), "Owner's contribution", "1000", "3000", 50_000_000),
+    ((2025, 1, 15), "Stock bought on account", "1300", "2100", 6_000_000),
+    ((2025, 2, 10), "Cash sales", "1000", "4000", 9_000_000),
+    ((2025, 2, 10), "Cost of cash sales", "5000", "1300", 5_400_000),
+    ((2025, 2, 28), "February salaries", "6000", "1000", 2_000_000),
+    ((2025, 3, 20), "Supplier paid", "2100", "1000", 6_000_000),
+];
+
+/// Supplier, order and expected dates, lines of product, quantity and unit
+/// cost, and the day the order was received in full, if it was. Records
+/// are numbered from 1 as the fixture numbers them.
+type SeedPurchase = (usize, SeedDate, SeedDate, &'static [(usize, i32, i32)], Option<SeedDate>);
+
+const SEED_PURCHASES: &[SeedPurchase] = &[
+    (1, (2025, 1, 10), (2025, 1, 20), &[(1, 20, 600), (2, 10, 1_200)], Some((2025, 1, 18))),
+    (2, (2025, 2, 3), (2025, 2, 14), &[(3, 15, 1_800)], Some((2025, 2, 19))),
+    (3, (2025, 3, 5), (2025, 3, 20), &[(4, 25, 2_400), (5, 5, 3_000)], None),
+];
+
+/// Name, type, first and last day, budget and spend
+type SeedCampaign = (&'static str, CampaignType, SeedDate, SeedDate, i32, i32);
+
+const SEED_CAMPAIGNS: &[SeedCampaign] = &[
+    ("Winter Newsletter", CampaignType::Email, (2025, 1, 6), (2025, 2, 28), 1_500_000, 1_200_000),
+    ("Spring Trade Show", CampaignType::Event, (2025, 3, 3), (2025, 3, 7), 5_000_000, 4_600_000),
+];
+
+/// Day opened, title, customer, lead source, estimated value and priority
+type SeedLead = (SeedDate, &'static str, usize, &'static str, i32, LeadPriority);
+
+const SEED_LEADS: &[SeedLead] = &[
+    ((2025, 1, 8), "Office fit-out", 1, "Winter Newsletter", 12_000_000, LeadPriority::High),
+    ((2025, 1, 20), "Warehouse shelving", 2, "referral", 8_000_000, LeadPriority::Medium),
+    ((2025, 2, 3), "Annual supplies", 3, "Winter Newsletter", 5_000_000, LeadPriority::Medium),
+    ((2025, 3, 4), "Showroom displays", 4, "Spring Trade Show", 9_000_000, LeadPriority::Urgent),
+    ((2025, 3, 12), "Spare parts", 5, "website", 2_000_000, LeadPriority::Low),
+];
+
+/// Campaign, lead, response and the day it came in
+type SeedTouch = (usize, usize, CampaignResponse, SeedDate);
+
+const SEED_TOUCHES: &[SeedTouch] = &[
+    (1, 1, CampaignResponse::Interested, (2025, 1, 7)),
+    (1, 3, CampaignResponse::Interested, (2025, 1, 30)),
+    (1, 2, CampaignResponse::NoResponse, (2025, 2, 5)),
+    (2, 4, CampaignResponse::Interested, (2025, 3, 4)),
+];
+
+/// Lead, sales rep, value, days opened and closed, and whether it was won.
+/// Every deal is closed, so reports that age open deals against today
+/// render the same on any day.
+type SeedDeal = (usize, usize, i32, SeedDate, SeedDate, bool);
+
+const SEED_DEALS: &[SeedDeal] = &[
+    (1, 1, 11_000_000, (2025, 1, 15), (2025, 2, 10), true),
+    (2, 2, 8_000_000, (2025, 1, 27), (2025, 2, 21), false),
+    (3, 1, 4_500_000, (2025, 2, 12), (2025, 3, 14), true),
+    (4, 2, 9_000_000, (2025, 3, 10), (2025, 3, 28), true),
+];
+
+/// Day, type, subject, deal and minutes spent; every activity is done, for
+/// the same reason every deal is closed
+type SeedActivity = (SeedDate, ActivityType, &'static str, usize, i32);
+
+const SEED_ACTIVITIES: &[SeedActivity] = &[
+    ((2025, 1, 16), ActivityType::Call, "Discovery call", 1, 30),
+    ((2025, 1, 29), ActivityType::Meeting, "Site visit", 1, 90),
+    ((2025, 2, 4), ActivityType::Email, "Shelving quote", 2, 15),
+    ((2025, 2, 20), ActivityType::Call, "Price negotiation", 2, 45),
+    ((2025, 2, 14), ActivityType::Meeting, "Supplies review", 3, 60),
+    ((2025, 3, 11), ActivityType::Meeting, "Showroom walkthrough", 4, 120),
+];
+
+/// Won deal, product, quantity and the day it shipped
+type SeedShipment = (usize, usize, i32, SeedDate);
+
+const SEED_SHIPMENTS: &[SeedShipment] = &[
+    (1, 3, 8, (2025, 2, 12)),
+    (1, 5, 10, (2025, 2, 12)),
+    (3, 2, 6, (2025, 3, 17)),
+    (4, 7, 12, (2025, 3, 31)),
+];
+
+/// Months the seed company ran payroll for
+const SEED_PAYROLL_PERIODS: &[&str] = &["2025-01", "2025-02", "2025-03"];
+
+/// The period every golden report covers, which holds all of the seed
+/// company's dated records, so the outputs do not depend on the day the
+/// tests run
+pub fn golden_period() -> DateRange {
+    DateRange {
+        start_date: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap_or_default(),
+        end_date: NaiveDate::from_ymd_opt(2025, 3, 31).unwrap_or_default(),
+    }
+}
+
+/// The data every golden report is rendered from: ten products, five
+/// customers, three suppliers and five employees, with the opening stock
+/// moved in and a quarter of bookkeeping, purchasing, sales and payroll
+/// in [`golden_period`].
+///
+/// Services date what they record with the clock, so each step moves its
+/// records back to their seed dates.
+pub fn seed_company() -> CLIERPResult<Company> {
+    let company = CompanyFixture::new()
+        .with_products(10)
+        .with_customers(5)
+        .with_suppliers(3)
+        .with_employees(5)
+        .build()?;
+    let mut conn = company.connection()?;
+    // The ledger is SEED_ENTRIES alone; stock events post nothing
+    let _posting = PostingRules::scope(None);
+
+    let opening = golden_period().start_date.and_hms_opt(9, 0, 0).unwrap_or_default();
+    diesel::update(stock_movements::table)
+        .set(stock_movements::movement_date.eq(opening))
+        .execute(&mut conn)?;
+
+    seed_ledger(&mut conn, &company)?;
+    seed_purchases(&mut conn, &company)?;
+    seed_sales(&mut conn, &company)?;
+    seed_workforce(&mut conn, &company)?;
+    // The summaries were kept for the days the services ran
+    SummaryService::rebuild(&mut conn)?;
+    drop(conn);
+
+    Ok(company)
+}
+
+fn seed_date((year, month, day): SeedDate) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap_or_default()
+}
+
+fn seed_time(date: SeedDate, hour: u32) -> NaiveDateTime {
+    seed_date(date).and_hms_opt(hour, 0, 0).unwrap_or_default()
+}
+
+fn seed_ledger(conn: &mut DatabaseConnection, company: &Company) -> CLIERPResult<()> {
+    let service = AccountService::new();
+    let mut accounts = HashMap::new();
+    for (code, name, account_type) in SEED_ACCOUNTS {
+        let account = service.create_account(
+            conn,
+            CreateAccountRequest {
+                account_code: code.to_string(),
+                account_name: name.to_string(),
+                account_type: account_type.to_string(),
+                parent_id: None,
+            },
+        )?;
+        accounts.insert(*code, account.id);
+    }
+    for (date, description, debit, credit, amount) in SEED_ENTRIES {
+        JournalService::post_entry(
+            conn,
+            JournalEntryRequest {
+                entry_date: seed_date(*date),
+                description: description.to_string(),
+                reference: None,
+                lines: vec![
+                    JournalLine::debit(accounts[debit], *amount),
+                    JournalLine::credit(accounts[credit], *amount),
+                ],
+            },
+            Some(company.admin.id),
+        )?;
+    }
+    Ok(())
+}
+
+/// Approved purchase orders, received in full on their receipt day
+fn seed_purchases(conn: &mut DatabaseConnection, company: &Company) -> CLIERPResult<()> {
+    for (n, (supplier, ordered, expected, lines, received)) in SEED_PURCHASES.iter().enumerate() {
+        let order = PurchaseOrderService::create_purchase_order(
+            conn,
+            company.suppliers[supplier - 1].id,
+            Some(seed_date(*expected)),
+            None,
+            lines
+                .iter()
+                .map(|(product, quantity, unit_cost)| PurchaseOrderItem {
+                    product_id: company.products[product - 1].id,
+                    quantity: *quantity,
+                    unit_cost: *unit_cost,
+                })
+                .collect(),
+            None,
+        )?;
+        let po_id = order.purchase_order.id;
+        PurchaseOrderService::approve_purchase_order(conn, po_id, company.admin.id, None)?;
+        if received.is_some() {
+            let items = order
+                .items
+                .iter()
+                .map(|item| ReceiveItemData { item_id: item.purchase_item.id, quantity: item.purchase_item.quantity })
+                .collect();
+            PurchaseOrderService::receive_purchase_items(conn, po_id, items, Some(company.admin.id))?;
+        }
+
+        let order_date = seed_date(*ordered);
+        let placed = seed_time(*ordered, 9);
+        diesel::update(purchase_orders::table.find(po_id))
+            .set((
+                purchase_orders::po_number.eq(format!("PO{}{:06}", order_date.format("%Y%m%d"), n + 1)),
+                purchase_orders::order_date.eq(order_date),
+                purchase_orders::approved_at.eq(Some(placed)),
+                purchase_orders::created_at.eq(placed),
+                purchase_orders::updated_at.eq(received.map_or(placed, |day| seed_time(day, 14))),
+            ))
+            .execute(conn)?;
+        diesel::update(purchase_items::table.filter(purchase_items::po_id.eq(po_id)))
+            .set(purchase_items::created_at.eq(placed))
+            .execute(conn)?;
+        if let Some(day) = received {
+            diesel::update(
+                stock_movements::table
+                    .filter(stock_movements::reference_type.eq("purchase_order"))
+                    .filter(stock_movements::reference_id.eq(po_id)),
+            )
+            .set(stock_movements::movement_date.eq(seed_time(*day, 14)))
+            .execute(conn)?;
+        }
+    }
+    Ok(())
+}
+
+/// Campaigns, the leads they brought in, closed deals with their
+/// activities, and the stock shipped for the deals that were won
+fn seed_sales(conn: &mut DatabaseConnection, company: &Company) -> CLIERPResult<()> {
+    let mut campaign_ids = Vec::with_capacity(SEED_CAMPAIGNS.len());
+    for (name, campaign_type, start, end, budget, spent) in SEED_CAMPAIGNS {
+        let campaign = CampaignService::create_campaign(
+            conn,
+            name,
+            campaign_type.clone(),
+            None,
+            seed_date(*start),
+            Some(seed_date(*end)),
+            Some(*budget),
+            None,
+            None,
+        )?;
+        let spent = Some(Some(*spent));
+        CampaignService::update_campaign(conn, campaign.id, None, None, None, None, None, spent, None, None)?;
+        CampaignService::update_campaign_status(conn, campaign.id, CampaignStatus::Completed)?;
+        diesel::update(campaigns::table.find(campaign.id))
+            .set((campaigns::created_at.eq(seed_time(*start, 9)), campaigns::updated_at.eq(seed_time(*end, 18))))
+            .execute(conn)?;
+        campaign_ids.push(campaign.id);
+    }
+
+    let mut lead_ids = Vec::with_capacity(SEED_LEADS.len());
+    for (opened, title, customer, source, value, priority) in SEED_LEADS {
+        let lead = LeadService::create_lead(
+            conn,
+            title,
+            Some(company.customers[customer - 1].id),
+            source,
+            *value,
+            None,
+            priority.clone(),
+            None,
+            None,
+            None,
+        )?;
+        LeadService::update_lead_status(conn, lead.id, LeadStatus::Qualified, None)?;
+        diesel::update(leads::table.find(lead.id))
+            .set((leads::created_at.eq(seed_time(*opened, 9)), leads::updated_at.eq(seed_time(*opened, 9))))
+            .execute(conn)?;
+        lead_ids.push(lead.id);
+    }
+
+    for (campaign, lead, response, day) in SEED_TOUCHES {
+        let (campaign_id, lead_id) = (campaign_ids[campaign - 1], lead_ids[lead - 1]);
+        let touch = AttributionService::record_touch(conn, campaign_id, lead_id, Some(response.clone()))?;
+        let at = seed_time(*day, 9);
+        diesel::update(campaign_leads::table.find(touch.id))
+            .set((campaign_leads::created_at.eq(at), campaign_leads::response_date.eq(Some(at))))
+            .execute(conn)?;
+    }
+
+    let mut deal_ids = Vec::with_capacity(SEED_DEALS.len());
+    for (lead, rep, value, opened, closed, won) in SEED_DEALS {
+        let (stage, lead_status) = match won {
+            true => (DealStage::ClosedWon, LeadStatus::ClosedWon),
+            false => (DealStage::ClosedLost, LeadStatus::ClosedLost),
+        };
+        let deal = DealService::create_deal(
+            conn,
+            lead_ids[lead - 1],
+            SEED_LEADS[lead - 1].1,
+            *value,
+            Some(seed_date(*closed)),
+            Some(company.employees[rep - 1].id),
+            None,
+            None,
+        )?;
+        DealService::update_deal_stage(conn, deal.id, stage, None)?;
+        LeadService::update_lead_status(conn, lead_ids[lead - 1], lead_status, None)?;
+        diesel::update(deals::table.find(deal.id))
+            .set((
+                deals::close_date.eq(Some(seed_date(*closed))),
+                deals::created_at.eq(seed_time(*opened, 9)),
+                deals::updated_at.eq(seed_time(*closed, 17)),
+            ))
+            .execute(conn)?;
+        diesel::update(leads::table.find(lead_ids[lead - 1]))
+            .set(leads::updated_at.eq(seed_time(*closed, 17)))
+            .execute(conn)?;
+        deal_ids.push((deal.id, company.employees[rep - 1].id));
+    }
+
+    for (day, activity_type, subject, deal, minutes) in SEED_ACTIVITIES {
+        let (deal_id, rep) = deal_ids[deal - 1];
+        let lead = SEED_DEALS[deal - 1].0;
+        let customer = SEED_LEADS[lead - 1].2;
+        let at = seed_time(*day, 10);
+        let activity = ActivityService::create_activity(
+            conn,
+            activity_type.clone(),
+            subject,
+            None,
+            Some(company.customers[customer - 1].id),
+            Some(lead_ids[lead - 1]),
+            Some(deal_id),
+            Some(rep),
+            at,
+            Some(*minutes),
+        )?;
+        ActivityService::complete_activity(conn, activity.id, Some("Done"))?;
+        diesel::update(activities::table.find(activity.id))
+            .set((activities::created_at.eq(at), activities::updated_at.eq(at)))
+            .execute(conn)?;
+    }
+
+    let products = ProductService::new();
+    for (deal, product, quantity, day) in SEED_SHIPMENTS {
+        let (deal_id, _) = deal_ids[deal - 1];
+        products.update_stock(
+            company.products[product - 1].id,
+            -quantity,
+            "out",
+            None,
+            Some("deal"),
+            Some(deal_id),
+            None,
+            Some(company.admin.id),
+        )?;
+        let movement_id = stock_movements::table
+            .order(stock_movements::id.desc())
+            .select(stock_movements::id)
+            .first::<i32>(conn)?;
+        diesel::update(stock_movements::table.find(movement_id))
+            .set(stock_movements::movement_date.eq(seed_time(*day, 15)))
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// A working day of every employee through the period, then a paid
+/// payroll for each month. Employee `n` is late on the `n`th and the
+/// `n + 10`th of the month and absent on the `n + 20`th; the first two
+/// stay two hours late on Fridays.
+fn seed_workforce(conn: &mut DatabaseConnection, company: &Company) -> CLIERPResult<()> {
+    let period = golden_period();
+    let mut records = Vec::new();
+    for date in period.start_date.iter_days().take_while(|date| *date <= period.end_date) {
+        if date.weekday().number_from_monday() > 5 {
+            continue;
+        }
+        for (n, employee) in company.employees.iter().enumerate() {
+            let n = n as u32 + 1;
+            let overtime = n <= 2 && date.weekday() == Weekday::Fri;
+            let (status, check_in) = match date.day() {
+                day if day == n + 20 => (AttendanceStatus::Absent, None),
+                day if day == n || day == n + 10 => (AttendanceStatus::Late, NaiveTime::from_hms_opt(9, 30, 0)),
+                _ => (AttendanceStatus::Present, NaiveTime::from_hms_opt(8, 55, 0)),
+            };
+            records.push(NewAttendance {
+                employee_id: employee.id,
+                date,
+                check_in,
+                check_out: check_in.and(NaiveTime::from_hms_opt(if overtime { 20 } else { 18 }, 0, 0)),
+                break_time: check_in.map(|_| 60),
+                overtime_hours: check_in.map(|_| if overtime { 2.0 } else { 0.0 }),
+                status: status.to_string(),
+                notes: None,
+            });
+        }
+    }
+    diesel::insert_into(attendances::table).values(&records).execute(conn)?;
+
+    let service = PayrollService::new();
+    for period in SEED_PAYROLL_PERIODS {
+        let (_, end) = FiscalCalendar::current().resolve(period)?;
+        let paid_on = end.with_day(25).unwrap_or(end);
+        for employee in &company.employees {
+            let calculation = service.calculate_payroll(conn, employee.id, period.to_string())?;
+            let payroll = service.generate_payroll(conn, calculation, None, None, None)?;
+            service.process_payroll(conn, payroll.id)?;
+            service.pay_payroll(conn, payroll.id, company.admin.id, None)?;
+            diesel::update(payrolls::table.find(payroll.id))
+                .set((
+                    payrolls::payment_date.eq(Some(paid_on)),
+                    payrolls::created_at.eq(end.with_day(20).unwrap_or(end).and_hms_opt(9, 0, 0).unwrap_or_default()),
+                    payrolls::updated_at.eq(paid_on.and_hms_opt(9, 0, 0).unwrap_or_default()),
+                ))
+                .execute(conn)?;
+        }
+    }
+    Ok(())
+}
+
+/// How one rendered report compared with its golden file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GoldenStatus {
+    Matched,
+    /// There is no golden file for the output
+    Missing,
+    /// There was no golden file yet and `update` wrote the output as one
+    Recorded,
+    /// The output differed and `update` rewrote the golden file
+    Updated,
+    /// The output differs from the golden file, first at `line`
+    Changed { line: usize, expected: String, actual: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct GoldenOutcome {
+    pub path: PathBuf,
+    pub status: GoldenStatus,
+}
+
+/// Render every report in every format that can show it against the seed
+/// dataset and compare each output with its golden file under `dir`. With
+/// `update`, golden files that differ or are missing are written instead of
+/// reported.
+///
+/// Runs on a fresh in-memory database with the query cache off, so the
+/// configured database and its cached reports are never touched.
+pub fn verify_golden(dir: &Path, update: bool) -> CLIERPResult<Vec<GoldenOutcome>> {
+    QueryCache::current().set_enabled(false);
+    let _company = seed_company()?;
+
+    let range = golden_period();
+    let engine = ReportEngine::with_default_generators();
+
+    let mut outcomes = Vec::with_capacity(GOLDEN_REPORTS.len() * 5);
+    for (report_id, title) in GOLDEN_REPORTS {
+        for (format, extension) in [
+            (ReportFormat::Json, "json"),
+            (ReportFormat::Csv, "csv"),
+            (ReportFormat::Html, "html"),
+            (ReportFormat::Text, "txt"),
+            (ReportFormat::Pdf, "pdf"),
+        ] {
+            let Some(output) = render_golden(&engine, report_id, title, format, &range)? else {
+                continue;
+            };
+            let path = dir.join(report_id).join(format!("{}.{}", title, extension));
+            let status = compare_golden(&path, &output, update)?;
+            outcomes.push(GoldenOutcome { path, status });
+        }
+    }
+    Ok(outcomes)
+}
+
+/// A report as its golden file holds it, or `None` when the format cannot
+/// show all of the report. A report that fails renders as its error, so a
+/// report starting or stopping to fail shows up as well.
+fn render_golden(
+    engine: &ReportEngine,
+    report_id: &str,
+    title: &str,
+    format: ReportFormat,
+    range: &DateRange,
+) -> CLIERPResult<Option<String>> {
+    let config = ReportConfig {
+        title: title.to_string(),
+        description: Some(format!("Generated {} report", title.replace('_', " "))),
+        date_range: Some(range.clone()),
+        filters: HashMap::new(),
+        format: format.clone(),
+        include_charts: false,
+        include_summary: true,
+    };
+    let output = match engine.generate_report(report_id, config) {
+        Ok(mut result) => {
+            result.generated_at = NaiveDate::from_ymd_opt(2025, 1, 1)
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .unwrap_or(result.generated_at);
+            result.metadata.processing_time_ms = 0;
+            if !renders_fully(&format, &result.data) {
+                return Ok(None);
+            }
+            match format {
+                ReportFormat::Pdf => String::from_utf8_lossy(&render_pdf(&[&result])).into_owned(),
+                _ => render_report(&result)?,
+            }
+        }
+        Err(e) => format!("error: {}\n", e),
+    };
+    Ok(Some(normalize_golden(&output)))
+}
+
+/// Whether `format` renders every part of `data`: CSV holds a single table
+/// and the text and HTML layouts print tables only, while JSON and PDF show
+/// charts as well
+fn renders_fully(format: &ReportFormat, data: &ReportData) -> bool {
+    let tables_only = |data: &ReportData| match data {
+        ReportData::Table(_) => true,
+        ReportData::Mixed(sections) => sections.iter().all(|section| matches!(section.data, ReportData::Table(_))),
+        ReportData::Chart(_) => false,
+    };
+    match format {
+        ReportFormat::Json | ReportFormat::Pdf => true,
+        ReportFormat::Csv => matches!(data, ReportData::Table(_)),
+        ReportFormat::Html | ReportFormat::Text => tables_only(data),
+    }
+}
+
+/// Mask what changes between runs of the same report on the same data
+pub fn normalize_golden(output: &str) -> String {
+    let output = VOLATILE_DATE.replace_all(output, "<date>");
+    PROCESSING_TIME
+        .replace_all(&output, r#""processing_time_ms": 0"#)
+        .replace("\r\n", "\n")
+}
+
+fn compare_golden(path: &Path, output: &str, update: bool) -> CLIERPResult<GoldenStatus> {
+    let expected = match std::fs::read_to_string(path) {
+        Ok(expected) => expected,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && update => {
+            write_golden(path, output)?;
+            return Ok(GoldenStatus::Recorded);
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(GoldenStatus::Missing),
+        Err(e) => return Err(CLIERPError::IoError(format!("Failed to read {}: {}", path.display(), e))),
+    };
+
+    match first_difference(&expected, output) {
+        None => Ok(GoldenStatus::Matched),
+        Some(_) if update => {
+            write_golden(path, output)?;
+            Ok(GoldenStatus::Updated)
+        }
+        Some((line, expected, actual)) => Ok(GoldenStatus::Changed { line, expected, actual }),
+    }
+}
+
+fn write_golden(path: &Path, output: &str) -> CLIERPResult<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| CLIERPError::IoError(format!("Failed to create {}: {}", parent.display(), e)))?;
+    }
+    std::fs::write(path, output)
+        .map_err(|e| CLIERPError::IoError(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+/// First line, counted from 1, where the outputs differ, with both
+/// versions of it; a missing line is empty
+pub fn first_difference(expected: &str, actual: &str) -> Option<(usize, String, String)> {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (None, None) => return None,
+            (e, a) if e != a => {
+                return Some((line, e.unwrap_or_default().to_string(), a.unwrap_or_default().to_string()))
+            }
+            _ => line += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_golden() {
+        assert_eq!(
+            normalize_golden("Generated: 2026-10-14 09:30:12\nPeriod: 2026-10-01 to 2026-10-14\nMonth 2026-09: FIX-P0003 3,000\n"),
+            "Generated: <date>\nPeriod: <date> to <date>\nMonth <date>: FIX-P0003 3,000\n"
+        );
+        assert_eq!(normalize_golden("\"processing_time_ms\": 17,"), "\"processing_time_ms\": 0,");
+
+        assert_eq!(first_difference("a\nb\n", "a\nb\n"), None);
+        assert_eq!(first_difference("a\nb\n", "a\nc\n"), Some((2, "b".to_string(), "c".to_string())));
+        assert_eq!(first_difference("a\n", "a\nb\n"), Some((2, String::new(), "b".to_string())));
+    }
+}
@@ -0,0 +1,17 @@
+//! Test support for plugins and integration tests, behind the
+//! `test-support` feature: a migrated throwaway database and builders for
+//! deterministic fixture data, and the golden files every report is
+//! checked against.
+//!
+//! ```ignore
+//! let company = CompanyFixture::new().with_products(10).with_customers(5).build()?;
+//! let mut conn = company.connection()?;
+//! ```
+
+pub mod database;
+pub mod fixtures;
+pub mod golden;
+
+pub use database::*;
+pub use fixtures::*;
+pub use golden::*;
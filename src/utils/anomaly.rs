@@ -0,0 +1,118 @@
+/// Outlier test used by the anomaly detector
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum AnomalyMethod {
+    /// Distance from the mean in standard deviations
+    #[value(name = "zscore")]
+    ZScore,
+    /// Distance outside the interquartile range, in IQRs
+    Iqr,
+}
+
+impl AnomalyMethod {
+    /// Conventional cut-off: 3 standard deviations, or 1.5 IQRs beyond the quartiles
+    pub fn default_threshold(&self) -> f64 {
+        match self {
+            AnomalyMethod::ZScore => 3.0,
+            AnomalyMethod::Iqr => 1.5,
+        }
+    }
+}
+
+impl std::fmt::Display for AnomalyMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnomalyMethod::ZScore => write!(f, "zscore"),
+            AnomalyMethod::Iqr => write!(f, "iqr"),
+        }
+    }
+}
+
+/// Fewest values a group needs before any of them is judged unusual
+pub const MIN_SAMPLE_SIZE: usize = 8;
+
+/// Indices of the outliers in `values`, each with its score: standard
+/// deviations from the mean, or IQRs beyond the nearer quartile. Groups
+/// smaller than `MIN_SAMPLE_SIZE` or without spread have no outliers.
+pub fn find_outliers(values: &[f64], method: AnomalyMethod, threshold: f64) -> Vec<(usize, f64)> {
+    if values.len() < MIN_SAMPLE_SIZE {
+        return Vec::new();
+    }
+
+    let score: Box<dyn Fn(f64) -> f64> = match method {
+        AnomalyMethod::ZScore => {
+            let n = values.len() as f64;
+            let mean = values.iter().sum::<f64>() / n;
+            let sd = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+            if sd == 0.0 {
+                return Vec::new();
+            }
+            Box::new(move |v| (v - mean).abs() / sd)
+        }
+        AnomalyMethod::Iqr => {
+            let mut sorted = values.to_vec();
+            sorted.sort_by(|a, b| a.total_cmp(b));
+            let (q1, q3) = (quantile(&sorted, 0.25), quantile(&sorted, 0.75));
+            let iqr = q3 - q1;
+            if iqr == 0.0 {
+                return Vec::new();
+            }
+            Box::new(move |v| {
+                if v < q1 {
+                    (q1 - v) / iqr
+                } else if v > q3 {
+                    (v - q3) / iqr
+                } else {
+                    0.0
+                }
+            })
+        }
+    };
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (i, score(*v)))
+        .filter(|(_, s)| *s >= threshold)
+        .collect()
+}
+
+/// Linear-interpolated quantile of sorted values
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let pos = q * (sorted.len() - 1) as f64;
+    let (lower, upper) = (pos.floor() as usize, pos.ceil() as usize);
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (pos - lower as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zscore_outliers() {
+        let mut values = vec![100.0, 102.0, 98.0, 101.0, 99.0, 100.0, 103.0, 97.0, 100.0, 101.0];
+        assert!(find_outliers(&values, AnomalyMethod::ZScore, 3.0).is_empty());
+
+        values.push(1000.0);
+        let outliers = find_outliers(&values, AnomalyMethod::ZScore, 3.0);
+        assert_eq!(outliers.len(), 1);
+        assert_eq!(outliers[0].0, 10);
+        assert!(outliers[0].1 > 3.0);
+    }
+
+    #[test]
+    fn test_iqr_outliers() {
+        let values = [10.0, 12.0, 11.0, 13.0, 12.0, 11.0, 10.0, 12.0, -40.0, 90.0];
+        let indices: Vec<usize> = find_outliers(&values, AnomalyMethod::Iqr, 1.5)
+            .into_iter()
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(indices, vec![8, 9]);
+    }
+
+    #[test]
+    fn test_small_or_flat_groups() {
+        assert!(find_outliers(&[1.0, 1.0, 500.0], AnomalyMethod::ZScore, 1.0).is_empty());
+        assert!(find_outliers(&[5.0; 20], AnomalyMethod::Iqr, 1.5).is_empty());
+        assert_eq!(quantile(&[1.0, 2.0, 3.0, 4.0], 0.5), 2.5);
+    }
+}
@@ -0,0 +1,278 @@
+use once_cell::sync::OnceCell;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::core::config::CacheConfig;
+use crate::core::result::CLIERPResult;
+
+static QUERY_CACHE: OnceCell<QueryCache> = OnceCell::new();
+
+pub const DEFAULT_CACHE_TTL_SECONDS: u64 = 300;
+
+/// Key prefix of cached report runs. Reports aggregate across modules, so
+/// every invalidation drops them as well.
+pub const REPORTS_PREFIX: &str = "reports.";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    key: String,
+    /// Unix seconds after which the entry is stale
+    expires_at: u64,
+    value: serde_json::Value,
+}
+
+/// Cache for expensive aggregate queries such as dashboard statistics and
+/// report runs.
+///
+/// Entries live in memory for the process and, when `cache.dir` is set, in
+/// one JSON file per key so repeated CLI runs share them until the TTL runs
+/// out. Services call `invalidate` after writes that change the aggregates;
+/// `--no-cache` turns the cache off for one run.
+pub struct QueryCache {
+    enabled: AtomicBool,
+    ttl: Duration,
+    dir: Option<PathBuf>,
+    entries: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl QueryCache {
+    pub fn new(ttl: Duration, dir: Option<PathBuf>) -> Self {
+        Self {
+            enabled: AtomicBool::new(true),
+            ttl,
+            dir,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Install the cache from configuration for the rest of the process
+    pub fn initialize(config: &CacheConfig) -> CLIERPResult<()> {
+        let cache = Self::new(
+            Duration::from_secs(config.ttl_seconds),
+            config.dir.as_ref().map(PathBuf::from),
+        );
+        cache.set_enabled(config.enabled);
+        let _ = QUERY_CACHE.set(cache);
+        Ok(())
+    }
+
+    /// The configured cache, or an in-memory one with the default TTL if not initialized
+    pub fn current() -> &'static QueryCache {
+        QUERY_CACHE.get_or_init(|| Self::new(Duration::from_secs(DEFAULT_CACHE_TTL_SECONDS), None))
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// The value cached under `key`, or the result of `compute`, which is
+    /// then cached. Errors are never cached, and a disabled cache always
+    /// computes.
+    pub fn get_or_compute<T, F>(&self, key: &str, compute: F) -> CLIERPResult<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> CLIERPResult<T>,
+    {
+        if !self.is_enabled() {
+            return compute();
+        }
+
+        let now = unix_now();
+        if let Some(value) = self.lookup(key, now) {
+            // An entry written by an older build may no longer fit the type
+            if let Ok(value) = serde_json::from_value(value) {
+                return Ok(value);
+            }
+        }
+
+        let value = compute()?;
+        self.store(key, serde_json::to_value(&value)?, now);
+        Ok(value)
+    }
+
+    /// Drop every entry whose key starts with one of `prefixes`, together
+    /// with all cached reports
+    pub fn invalidate(&self, prefixes: &[&str]) {
+        self.remove_where(|key| {
+            key.starts_with(REPORTS_PREFIX) || prefixes.iter().any(|prefix| key.starts_with(prefix))
+        });
+    }
+
+    /// Drop every entry, in memory and on disk
+    pub fn clear(&self) {
+        self.remove_where(|_| true);
+    }
+
+    fn lookup(&self, key: &str, now: u64) -> Option<serde_json::Value> {
+        let mut entries = self.entries();
+        if let Some(entry) = entries.get(key) {
+            if entry.expires_at > now {
+                return Some(entry.value.clone());
+            }
+            entries.remove(key);
+        }
+
+        let path = self.disk_path(key)?;
+        let entry = read_entry(&path)?;
+        if entry.key != key {
+            return None;
+        }
+        if entry.expires_at <= now {
+            let _ = std::fs::remove_file(&path);
+            return None;
+        }
+        let value = entry.value.clone();
+        entries.insert(key.to_string(), entry);
+        Some(value)
+    }
+
+    fn store(&self, key: &str, value: serde_json::Value, now: u64) {
+        let entry = CacheEntry {
+            key: key.to_string(),
+            expires_at: now + self.ttl.as_secs(),
+            value,
+        };
+
+        if let Some(path) = self.disk_path(key) {
+            let written = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| {
+                    let json = serde_json::to_vec(&entry).map_err(std::io::Error::from)?;
+                    std::fs::write(&path, json)
+                });
+            if let Err(e) = written {
+                tracing::warn!("Failed to write query cache entry {}: {}", path.display(), e);
+            }
+        }
+
+        self.entries().insert(key.to_string(), entry);
+    }
+
+    fn remove_where(&self, matches: impl Fn(&str) -> bool) {
+        self.entries().retain(|key, _| !matches(key));
+
+        let Some(dir) = &self.dir else { return };
+        let Ok(files) = std::fs::read_dir(dir) else { return };
+        for file in files.flatten() {
+            let path = file.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            // Unreadable entries are dropped too; they would never be hit
+            if read_entry(&path).is_none_or(|entry| matches(&entry.key)) {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+
+    fn disk_path(&self, key: &str) -> Option<PathBuf> {
+        self.dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{:016x}.json", cache_key_hash(key))))
+    }
+
+    fn entries(&self) -> MutexGuard<'_, HashMap<String, CacheEntry>> {
+        // A panic while holding the lock leaves only complete entries behind
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn read_entry(path: &Path) -> Option<CacheEntry> {
+    let content = std::fs::read(path).ok()?;
+    serde_json::from_slice(&content).ok()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// FNV-1a hash of a cache key. File names must stay the same across builds,
+/// which `DefaultHasher` does not promise.
+fn cache_key_hash(key: &str) -> u64 {
+    key.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn counted(cache: &QueryCache, key: &str, calls: &Cell<i32>) -> i32 {
+        cache
+            .get_or_compute(key, || {
+                calls.set(calls.get() + 1);
+                Ok(calls.get())
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_hit_and_invalidate() {
+        let cache = QueryCache::new(Duration::from_secs(60), None);
+        let calls = Cell::new(0);
+
+        assert_eq!(counted(&cache, "crm.deals.statistics", &calls), 1);
+        assert_eq!(counted(&cache, "crm.deals.statistics", &calls), 1);
+        assert_eq!(counted(&cache, "reports.crm.{}", &calls), 2);
+
+        // Unrelated prefixes keep the entry, but reports always go
+        cache.invalidate(&["crm.leads"]);
+        assert_eq!(counted(&cache, "crm.deals.statistics", &calls), 1);
+        assert_eq!(counted(&cache, "reports.crm.{}", &calls), 3);
+
+        cache.invalidate(&["crm.deals"]);
+        assert_eq!(counted(&cache, "crm.deals.statistics", &calls), 4);
+    }
+
+    #[test]
+    fn test_expired_and_disabled() {
+        let cache = QueryCache::new(Duration::ZERO, None);
+        let calls = Cell::new(0);
+        assert_eq!(counted(&cache, "crm.customers.statistics", &calls), 1);
+        assert_eq!(counted(&cache, "crm.customers.statistics", &calls), 2);
+
+        let cache = QueryCache::new(Duration::from_secs(60), None);
+        cache.set_enabled(false);
+        assert_eq!(counted(&cache, "crm.customers.statistics", &calls), 3);
+        assert_eq!(counted(&cache, "crm.customers.statistics", &calls), 4);
+    }
+
+    #[test]
+    fn test_disk_entries_shared() {
+        let dir = std::env::temp_dir().join(format!("clierp-cache-test-{}", std::process::id()));
+        let calls = Cell::new(0);
+
+        let first = QueryCache::new(Duration::from_secs(60), Some(dir.clone()));
+        assert_eq!(counted(&first, "crm.deals.pipeline", &calls), 1);
+
+        // A new process reads what the previous one stored
+        let second = QueryCache::new(Duration::from_secs(60), Some(dir.clone()));
+        assert_eq!(counted(&second, "crm.deals.pipeline", &calls), 1);
+
+        second.invalidate(&["crm.deals"]);
+        let third = QueryCache::new(Duration::from_secs(60), Some(dir.clone()));
+        assert_eq!(counted(&third, "crm.deals.pipeline", &calls), 2);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_key_hash_is_stable() {
+        assert_eq!(cache_key_hash(""), 0xcbf29ce484222325);
+        assert_eq!(cache_key_hash("a"), 0xaf63dc4c8601ec8c);
+    }
+}
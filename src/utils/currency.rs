@@ -0,0 +1,219 @@
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{config::CurrencyConfig, error::CLIERPError, result::CLIERPResult};
+
+static CURRENCY_FORMAT: OnceCell<CurrencyFormat> = OnceCell::new();
+
+/// How monetary amounts are rendered.
+///
+/// Amounts are stored as integers in the currency's minor unit, so with two
+/// decimal places `123456` is shown as `$1,234.56`; with zero (KRW, JPY) it
+/// is shown as `₩123,456`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurrencyFormat {
+    pub symbol: String,
+    pub decimal_places: u32,
+    pub thousands_separator: String,
+    pub decimal_separator: String,
+    pub symbol_after: bool,
+}
+
+impl Default for CurrencyFormat {
+    fn default() -> Self {
+        Self::for_locale("ko-KR").expect("built-in locale")
+    }
+}
+
+impl CurrencyFormat {
+    /// Built-in defaults for a locale tag such as `en-US`
+    pub fn for_locale(locale: &str) -> CLIERPResult<Self> {
+        let (symbol, decimal_places, thousands, decimal, symbol_after) = match locale {
+            "ko-KR" | "ko" => ("₩", 0, ",", ".", false),
+            "ja-JP" | "ja" => ("¥", 0, ",", ".", false),
+            "zh-CN" | "zh" => ("¥", 2, ",", ".", false),
+            "en-US" | "en" => ("$", 2, ",", ".", false),
+            "en-GB" => ("£", 2, ",", ".", false),
+            "de-DE" | "de" => ("€", 2, ".", ",", true),
+            "fr-FR" | "fr" => ("€", 2, " ", ",", true),
+            _ => {
+                return Err(CLIERPError::Validation(format!(
+                    "Unsupported currency locale '{}'. Use ko-KR, ja-JP, zh-CN, en-US, en-GB, de-DE or fr-FR",
+                    locale
+                )))
+            }
+        };
+
+        Ok(Self {
+            symbol: symbol.to_string(),
+            decimal_places,
+            thousands_separator: thousands.to_string(),
+            decimal_separator: decimal.to_string(),
+            symbol_after,
+        })
+    }
+
+    /// Locale defaults with any explicit overrides from configuration applied
+    pub fn from_config(config: &CurrencyConfig) -> CLIERPResult<Self> {
+        let mut format = Self::for_locale(&config.locale)?;
+        if let Some(symbol) = &config.symbol {
+            format.symbol = symbol.clone();
+        }
+        if let Some(places) = config.decimal_places {
+            if places > 4 {
+                return Err(CLIERPError::Validation(
+                    "Currency decimal places must be between 0 and 4".to_string(),
+                ));
+            }
+            format.decimal_places = places;
+        }
+        if let Some(separator) = &config.thousands_separator {
+            format.thousands_separator = separator.clone();
+        }
+        if let Some(separator) = &config.decimal_separator {
+            format.decimal_separator = separator.clone();
+        }
+        if let Some(after) = config.symbol_after {
+            format.symbol_after = after;
+        }
+        Ok(format)
+    }
+
+    /// Install the format from configuration for the rest of the process
+    pub fn initialize(config: &CurrencyConfig) -> CLIERPResult<()> {
+        let format = Self::from_config(config)?;
+        let _ = CURRENCY_FORMAT.set(format);
+        Ok(())
+    }
+
+    /// The configured format, or the Korean won default if not initialized
+    pub fn current() -> CurrencyFormat {
+        CURRENCY_FORMAT.get().cloned().unwrap_or_default()
+    }
+
+    /// Amount with symbol and separators, e.g. `₩1,234,000` or `1.234,56 €`
+    pub fn format(&self, amount: i64) -> String {
+        let sign = if amount < 0 { "-" } else { "" };
+        let number = self.format_unsigned(amount.unsigned_abs());
+        if self.symbol_after {
+            format!("{}{} {}", sign, number, self.symbol)
+        } else {
+            format!("{}{}{}", sign, self.symbol, number)
+        }
+    }
+
+    /// Amount with separators but no symbol, e.g. `1,234,000`, for `--plain`
+    pub fn format_without_symbol(&self, amount: i64) -> String {
+        let sign = if amount < 0 { "-" } else { "" };
+        format!("{}{}", sign, self.format_unsigned(amount.unsigned_abs()))
+    }
+
+    /// Plain integer with the locale's thousands separator
+    pub fn format_number(&self, n: i64) -> String {
+        let sign = if n < 0 { "-" } else { "" };
+        format!("{}{}", sign, group_digits(&n.unsigned_abs().to_string(), &self.thousands_separator))
+    }
+
+    /// Machine-readable amount for exports: no symbol or grouping, `.` as the
+    /// decimal point, e.g. `1234.56`
+    pub fn format_plain(&self, amount: i64) -> String {
+        let sign = if amount < 0 { "-" } else { "" };
+        let (major, minor) = self.split(amount.unsigned_abs());
+        match minor {
+            Some(minor) => format!("{}{}.{}", sign, major, minor),
+            None => format!("{}{}", sign, major),
+        }
+    }
+
+    /// Convert an amount entered in major units (e.g. `12.50`) to minor units
+    pub fn to_minor_units(&self, amount: f64) -> i64 {
+        (amount * 10f64.powi(self.decimal_places as i32)).round() as i64
+    }
+
+    fn format_unsigned(&self, amount: u64) -> String {
+        let (major, minor) = self.split(amount);
+        let major = group_digits(&major.to_string(), &self.thousands_separator);
+        match minor {
+            Some(minor) => format!("{}{}{}", major, self.decimal_separator, minor),
+            None => major,
+        }
+    }
+
+    fn split(&self, amount: u64) -> (u64, Option<String>) {
+        if self.decimal_places == 0 {
+            return (amount, None);
+        }
+        let scale = 10u64.pow(self.decimal_places);
+        let minor = format!("{:0width$}", amount % scale, width = self.decimal_places as usize);
+        (amount / scale, Some(minor))
+    }
+}
+
+fn group_digits(digits: &str, separator: &str) -> String {
+    let mut result = String::new();
+    let len = digits.len();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (len - i).is_multiple_of(3) {
+            result.push_str(separator);
+        }
+        result.push(c);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(locale: &str) -> CurrencyConfig {
+        CurrencyConfig {
+            locale: locale.to_string(),
+            symbol: None,
+            decimal_places: None,
+            thousands_separator: None,
+            decimal_separator: None,
+            symbol_after: None,
+        }
+    }
+
+    #[test]
+    fn test_default_is_won_without_decimals() {
+        let format = CurrencyFormat::default();
+        assert_eq!(format.format(1234567), "₩1,234,567");
+        assert_eq!(format.format(-123), "-₩123");
+        assert_eq!(format.format(0), "₩0");
+    }
+
+    #[test]
+    fn test_locale_presets() {
+        let usd = CurrencyFormat::for_locale("en-US").unwrap();
+        assert_eq!(usd.format(123456), "$1,234.56");
+        assert_eq!(usd.format(5), "$0.05");
+
+        let eur = CurrencyFormat::for_locale("de-DE").unwrap();
+        assert_eq!(eur.format(123456789), "1.234.567,89 €");
+        assert!(CurrencyFormat::for_locale("xx-XX").is_err());
+        assert_eq!(eur.format_without_symbol(-123456789), "-1.234.567,89");
+    }
+
+    #[test]
+    fn test_config_overrides() {
+        let mut cfg = config("en-US");
+        cfg.symbol = Some("CHF ".to_string());
+        cfg.thousands_separator = Some("'".to_string());
+        let format = CurrencyFormat::from_config(&cfg).unwrap();
+        assert_eq!(format.format(100000000), "CHF 1'000'000.00");
+
+        cfg.decimal_places = Some(9);
+        assert!(CurrencyFormat::from_config(&cfg).is_err());
+    }
+
+    #[test]
+    fn test_plain_and_minor_units() {
+        let usd = CurrencyFormat::for_locale("en-US").unwrap();
+        assert_eq!(usd.format_plain(-123456), "-1234.56");
+        assert_eq!(usd.to_minor_units(12.5), 1250);
+        assert_eq!(CurrencyFormat::default().format_plain(1500), "1500");
+        assert_eq!(CurrencyFormat::default().format_number(-123), "-123");
+    }
+}
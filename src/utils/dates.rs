@@ -0,0 +1,297 @@
+use chrono::{Datelike, Duration, Months, NaiveDate, Weekday};
+
+use crate::core::{error::CLIERPError, result::CLIERPResult};
+use crate::utils::fiscal::FiscalCalendar;
+use crate::utils::timezone::DisplayTimezone;
+
+/// Parse a date argument relative to today in the display timezone.
+///
+/// Accepts `YYYY-MM-DD`, `today`, `yesterday`, `tomorrow`, `N days|weeks|months|years ago`,
+/// `in N days|weeks|months|years` and `last|next <weekday>`.
+pub fn parse_date(input: &str) -> CLIERPResult<NaiveDate> {
+    parse_date_from(input, DisplayTimezone::current().today())
+}
+
+/// Parse a period argument into an inclusive date range relative to today.
+///
+/// Accepts `this-month`, `last-month`, `this-quarter`, `last-quarter`, `this-year`,
+/// `last-year`, `ytd`, `mtd`, `qtd`, plus the labels understood by
+/// [`FiscalCalendar::resolve`] (`YYYY-MM`, `FY2025`, `FY2025-Q2`, `FY2025-P03`).
+/// Months, quarters and years follow the configured fiscal calendar.
+pub fn parse_period(input: &str) -> CLIERPResult<(NaiveDate, NaiveDate)> {
+    parse_period_from(input, DisplayTimezone::current().today(), &FiscalCalendar::current())
+}
+
+/// Normalize a period argument to a stable label (`YYYY-MM` or `FY2025-P03`)
+/// suitable for storing, e.g. as a payroll period.
+pub fn normalize_period(input: &str) -> CLIERPResult<String> {
+    normalize_period_from(input, DisplayTimezone::current().today(), &FiscalCalendar::current())
+}
+
+/// `value_parser` adapter for clap date arguments
+pub fn parse_date_arg(input: &str) -> Result<NaiveDate, String> {
+    parse_date(input).map_err(|e| e.to_string())
+}
+
+/// `value_parser` adapter for clap period arguments; keeps the normalized label
+pub fn parse_period_arg(input: &str) -> Result<String, String> {
+    normalize_period(input).map_err(|e| e.to_string())
+}
+
+pub fn parse_date_from(input: &str, today: NaiveDate) -> CLIERPResult<NaiveDate> {
+    let normalized = input.trim().to_lowercase();
+    let normalized = normalized.replace('_', " ");
+
+    if let Ok(date) = NaiveDate::parse_from_str(&normalized, "%Y-%m-%d") {
+        return Ok(date);
+    }
+
+    match normalized.as_str() {
+        "today" | "now" => return Ok(today),
+        "yesterday" => return Ok(today - Duration::days(1)),
+        "tomorrow" => return Ok(today + Duration::days(1)),
+        "" => return Err(invalid_date(input, "date cannot be empty")),
+        _ => {}
+    }
+
+    let words: Vec<&str> = normalized.split_whitespace().collect();
+    match words.as_slice() {
+        [amount, unit, "ago"] => shift(today, parse_amount(input, amount)?, unit, -1)
+            .ok_or_else(|| invalid_date(input, "unknown unit")),
+        ["in", amount, unit] => shift(today, parse_amount(input, amount)?, unit, 1)
+            .ok_or_else(|| invalid_date(input, "unknown unit")),
+        [amount, unit] if amount.parse::<i64>().is_ok() && unit_months(unit).is_some() => Err(invalid_date(
+            input,
+            &format!(
+                "ambiguous direction, use \"{} {} ago\" or \"in {} {}\"",
+                amount, unit, amount, unit
+            ),
+        )),
+        ["last", day] => parse_weekday(day)
+            .map(|wd| previous_weekday(today, wd))
+            .ok_or_else(|| invalid_date(input, "unknown weekday")),
+        ["next", day] => parse_weekday(day)
+            .map(|wd| next_weekday(today, wd))
+            .ok_or_else(|| invalid_date(input, "unknown weekday")),
+        [single] if single.contains('/') || single.contains('.') => Err(invalid_date(
+            input,
+            "day/month order is ambiguous, use YYYY-MM-DD",
+        )),
+        [day] if parse_weekday(day).is_some() => Err(invalid_date(
+            input,
+            &format!("ambiguous weekday, use \"last {}\" or \"next {}\"", day, day),
+        )),
+        _ => Err(invalid_date(input, "unrecognized date")),
+    }
+}
+
+pub fn parse_period_from(
+    input: &str,
+    today: NaiveDate,
+    calendar: &FiscalCalendar,
+) -> CLIERPResult<(NaiveDate, NaiveDate)> {
+    let normalized = input.trim().to_lowercase().replace('_', "-");
+    let current = calendar.period_of(today);
+    let fiscal_year = current.fiscal_year;
+
+    let range = match normalized.as_str() {
+        "this-month" | "current-month" => (current.start_date, current.end_date),
+        "last-month" | "previous-month" => {
+            let previous = calendar.period_of(current.start_date - Duration::days(1));
+            (previous.start_date, previous.end_date)
+        }
+        "mtd" => (current.start_date, today),
+        "this-quarter" | "current-quarter" => calendar.quarter_range(fiscal_year, current.quarter())?,
+        "last-quarter" | "previous-quarter" => {
+            let (start, _) = calendar.quarter_range(fiscal_year, current.quarter())?;
+            let previous = calendar.period_of(start - Duration::days(1));
+            calendar.quarter_range(previous.fiscal_year, previous.quarter())?
+        }
+        "qtd" => (calendar.quarter_range(fiscal_year, current.quarter())?.0, today),
+        "this-year" | "current-year" => calendar.year_range(fiscal_year),
+        "last-year" | "previous-year" => calendar.year_range(fiscal_year - 1),
+        "ytd" => (calendar.year_range(fiscal_year).0, today),
+        "today" | "yesterday" => {
+            let date = parse_date_from(&normalized, today)?;
+            (date, date)
+        }
+        "month" | "quarter" | "year" => {
+            return Err(CLIERPError::Validation(format!(
+                "Ambiguous period '{}', use this-{} or last-{}",
+                input, normalized, normalized
+            )))
+        }
+        _ => calendar.resolve(&normalized).map_err(|_| {
+            CLIERPError::Validation(format!(
+                "Invalid period '{}'. Use last-month, this-quarter, ytd, YYYY-MM, FY2025, FY2025-Q2 or FY2025-P03",
+                input
+            ))
+        })?,
+    };
+
+    Ok(range)
+}
+
+pub fn normalize_period_from(
+    input: &str,
+    today: NaiveDate,
+    calendar: &FiscalCalendar,
+) -> CLIERPResult<String> {
+    let normalized = input.trim().to_lowercase().replace('_', "-");
+
+    // Explicit labels are already stable
+    if calendar.resolve(&normalized).is_ok() {
+        return Ok(normalized.to_uppercase());
+    }
+
+    let (start, end) = parse_period_from(input, today, calendar)?;
+    let period = calendar.period_of(start);
+    if period.start_date == start && period.end_date == end {
+        if calendar.pattern().is_none() && calendar.start_month() == 1 {
+            return Ok(start.format("%Y-%m").to_string());
+        }
+        return Ok(period.label());
+    }
+
+    Err(CLIERPError::Validation(format!(
+        "Period '{}' does not map to a single month; use e.g. last-month or YYYY-MM",
+        input
+    )))
+}
+
+fn invalid_date(input: &str, reason: &str) -> CLIERPError {
+    CLIERPError::Validation(format!(
+        "Invalid date '{}': {}. Use YYYY-MM-DD, today, yesterday, \"2 weeks ago\" or \"last friday\"",
+        input, reason
+    ))
+}
+
+fn parse_amount(input: &str, amount: &str) -> CLIERPResult<i64> {
+    let value = match amount {
+        "a" | "an" | "one" => 1,
+        _ => amount
+            .parse::<i64>()
+            .map_err(|_| invalid_date(input, "expected a number"))?,
+    };
+    if value < 0 {
+        return Err(invalid_date(input, "amount cannot be negative"));
+    }
+    Ok(value)
+}
+
+/// Number of months per unit, or 0 for day/week units
+fn unit_months(unit: &str) -> Option<u32> {
+    match unit.trim_end_matches('s') {
+        "day" | "week" => Some(0),
+        "month" => Some(1),
+        "year" => Some(12),
+        _ => None,
+    }
+}
+
+fn shift(today: NaiveDate, amount: i64, unit: &str, direction: i64) -> Option<NaiveDate> {
+    match unit.trim_end_matches('s') {
+        "day" => Some(today + Duration::days(amount * direction)),
+        "week" => Some(today + Duration::weeks(amount * direction)),
+        "month" | "year" => {
+            let months = Months::new((amount as u32) * unit_months(unit)?);
+            if direction < 0 {
+                today.checked_sub_months(months)
+            } else {
+                today.checked_add_months(months)
+            }
+        }
+        _ => None,
+    }
+}
+
+fn parse_weekday(day: &str) -> Option<Weekday> {
+    match day {
+        "mon" | "monday" => Some(Weekday::Mon),
+        "tue" | "tuesday" => Some(Weekday::Tue),
+        "wed" | "wednesday" => Some(Weekday::Wed),
+        "thu" | "thursday" => Some(Weekday::Thu),
+        "fri" | "friday" => Some(Weekday::Fri),
+        "sat" | "saturday" => Some(Weekday::Sat),
+        "sun" | "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn previous_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let mut date = today - Duration::days(1);
+    while date.weekday() != weekday {
+        date -= Duration::days(1);
+    }
+    date
+}
+
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let mut date = today + Duration::days(1);
+    while date.weekday() != weekday {
+        date += Duration::days(1);
+    }
+    date
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    // A Wednesday
+    fn today() -> NaiveDate {
+        date(2025, 3, 12)
+    }
+
+    #[test]
+    fn test_parse_relative_dates() {
+        assert_eq!(parse_date_from("2025-01-31", today()).unwrap(), date(2025, 1, 31));
+        assert_eq!(parse_date_from("yesterday", today()).unwrap(), date(2025, 3, 11));
+        assert_eq!(parse_date_from("2 weeks ago", today()).unwrap(), date(2025, 2, 26));
+        assert_eq!(parse_date_from("in 3 days", today()).unwrap(), date(2025, 3, 15));
+        assert_eq!(parse_date_from("1 month ago", today()).unwrap(), date(2025, 2, 12));
+        assert_eq!(parse_date_from("last friday", today()).unwrap(), date(2025, 3, 7));
+        assert_eq!(parse_date_from("next monday", today()).unwrap(), date(2025, 3, 17));
+    }
+
+    #[test]
+    fn test_ambiguous_dates_are_rejected() {
+        assert!(parse_date_from("03/04/2025", today()).is_err());
+        assert!(parse_date_from("2 weeks", today()).is_err());
+        assert!(parse_date_from("friday", today()).is_err());
+        assert!(parse_date_from("soon", today()).is_err());
+    }
+
+    #[test]
+    fn test_parse_periods() {
+        let calendar = FiscalCalendar::default();
+        assert_eq!(
+            parse_period_from("last-month", today(), &calendar).unwrap(),
+            (date(2025, 2, 1), date(2025, 2, 28))
+        );
+        assert_eq!(
+            parse_period_from("last-quarter", today(), &calendar).unwrap(),
+            (date(2024, 10, 1), date(2024, 12, 31))
+        );
+        assert_eq!(
+            parse_period_from("ytd", today(), &calendar).unwrap(),
+            (date(2025, 1, 1), today())
+        );
+        assert!(parse_period_from("month", today(), &calendar).is_err());
+    }
+
+    #[test]
+    fn test_normalize_period() {
+        let calendar = FiscalCalendar::default();
+        assert_eq!(normalize_period_from("last-month", today(), &calendar).unwrap(), "2025-02");
+        assert_eq!(normalize_period_from("2025-01", today(), &calendar).unwrap(), "2025-01");
+        assert!(normalize_period_from("last-quarter", today(), &calendar).is_err());
+
+        let april = FiscalCalendar::new(4, None).unwrap();
+        assert_eq!(normalize_period_from("this-month", today(), &april).unwrap(), "FY2025-P12");
+    }
+}
@@ -1,6 +1,7 @@
 use crate::core::error::CLIERPError;
 use crate::core::result::CLIERPResult;
-use serde::Serialize;
+use crate::utils::progress::progress_bar;
+use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
@@ -32,11 +33,46 @@ impl ExportService {
             .map_err(|e| CLIERPError::IoError(format!("Failed to write headers: {}", e)))?;
 
         // Write data rows
+        let bar = progress_bar(data.len() as u64, "Exporting");
         for item in data {
             let row = item.to_csv_row();
             writeln!(file, "{}", row.join(","))
                 .map_err(|e| CLIERPError::IoError(format!("Failed to write data row: {}", e)))?;
+            bar.inc(1);
         }
+        bar.finish_and_clear();
+
+        Ok(())
+    }
+
+    /// Export data to CSV in a partner's layout, `headers` being the
+    /// columns `to_csv_row` writes
+    pub fn export_to_csv_mapped<T>(
+        &self,
+        data: &[T],
+        headers: &[&str],
+        mapping: &ExportMapping,
+        file_path: &str,
+    ) -> CLIERPResult<()>
+    where
+        T: CsvSerializable,
+    {
+        let indices = mapping.column_indices(headers)?;
+        let mut file = File::create(file_path).map_err(|e| {
+            CLIERPError::IoError(format!("Failed to create file {}: {}", file_path, e))
+        })?;
+
+        writeln!(file, "{}", mapping.headers(headers).join(","))
+            .map_err(|e| CLIERPError::IoError(format!("Failed to write headers: {}", e)))?;
+
+        let bar = progress_bar(data.len() as u64, "Exporting");
+        for item in data {
+            let row = mapping.map_row(&indices, &item.to_csv_row());
+            writeln!(file, "{}", row.join(","))
+                .map_err(|e| CLIERPError::IoError(format!("Failed to write data row: {}", e)))?;
+            bar.inc(1);
+        }
+        bar.finish_and_clear();
 
         Ok(())
     }
@@ -92,6 +128,126 @@ pub trait CsvSerializable {
     fn to_csv_row(&self) -> Vec<String>;
 }
 
+/// One column of an export template: the internal header it is read from
+/// and the header the partner expects, written `SKU=ItemCode`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateColumn {
+    pub source: String,
+    pub header: String,
+}
+
+impl std::str::FromStr for TemplateColumn {
+    type Err = CLIERPError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (source, header) = s.split_once('=').unwrap_or((s, s));
+        let (source, header) = (source.trim(), header.trim());
+        if source.is_empty() || header.is_empty() {
+            return Err(CLIERPError::Validation(format!(
+                "Expected COLUMN=PARTNER_COLUMN, got '{}'",
+                s
+            )));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            header: header.to_string(),
+        })
+    }
+}
+
+/// Clap value parser for `--column` arguments
+pub fn parse_template_column_arg(s: &str) -> Result<TemplateColumn, String> {
+    s.parse().map_err(|e: CLIERPError| e.to_string())
+}
+
+/// How an export is rewritten for a third-party system. Without columns
+/// every internal column is written under its own name; with a date format
+/// `YYYY-MM-DD` values, and the date part of timestamps, are rewritten.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportMapping {
+    pub columns: Vec<TemplateColumn>,
+    /// chrono format, e.g. `%d/%m/%Y`
+    pub date_format: Option<String>,
+}
+
+impl ExportMapping {
+    /// Positions in `headers` of the template's columns, in template order
+    pub fn column_indices(&self, headers: &[&str]) -> CLIERPResult<Vec<usize>> {
+        if self.columns.is_empty() {
+            return Ok((0..headers.len()).collect());
+        }
+        self.columns
+            .iter()
+            .map(|column| {
+                headers
+                    .iter()
+                    .position(|header| header.eq_ignore_ascii_case(&column.source))
+                    .ok_or_else(|| {
+                        CLIERPError::Validation(format!(
+                            "Unknown column '{}'; columns are {}",
+                            column.source,
+                            headers.join(", ")
+                        ))
+                    })
+            })
+            .collect()
+    }
+
+    /// Header line of the partner file
+    pub fn headers(&self, headers: &[&str]) -> Vec<String> {
+        if self.columns.is_empty() {
+            headers.iter().map(|header| escape_csv_value(header)).collect()
+        } else {
+            self.columns.iter().map(|column| escape_csv_value(&column.header)).collect()
+        }
+    }
+
+    /// `row`, as written by `to_csv_row`, in the partner's layout
+    pub fn map_row(&self, indices: &[usize], row: &[String]) -> Vec<String> {
+        indices
+            .iter()
+            .map(|&i| {
+                let value = row.get(i).map(String::as_str).unwrap_or_default();
+                match &self.date_format {
+                    Some(format) => reformat_date(value, format).unwrap_or_else(|| value.to_string()),
+                    None => value.to_string(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// `value` with its date written in `format`, or `None` when it is not a
+/// `YYYY-MM-DD` date or `YYYY-MM-DD HH:MM:SS` timestamp
+fn reformat_date(value: &str, format: &str) -> Option<String> {
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Some(date.format(format).to_string());
+    }
+    let timestamp = chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").ok()?;
+    Some(format!("{} {}", timestamp.date().format(format), timestamp.time()))
+}
+
+/// A date format given as `DD/MM/YYYY` (or `YY`) or as a chrono format such
+/// as `%d.%m.%Y`, returned as the chrono format
+pub fn parse_date_format(s: &str) -> CLIERPResult<String> {
+    use chrono::format::{Item, StrftimeItems};
+
+    let format = if s.contains('%') {
+        s.to_string()
+    } else {
+        s.replace("YYYY", "%Y").replace("YY", "%y").replace("MM", "%m").replace("DD", "%d")
+    };
+    let valid = format.contains('%') && !StrftimeItems::new(&format).any(|item| matches!(item, Item::Error));
+    if valid {
+        Ok(format)
+    } else {
+        Err(CLIERPError::Validation(format!(
+            "Invalid date format '{}'; use e.g. DD/MM/YYYY or %d.%m.%Y",
+            s
+        )))
+    }
+}
+
 /// Helper function to escape CSV values
 pub fn escape_csv_value(value: &str) -> String {
     if value.contains(',') || value.contains('"') || value.contains('\n') {
@@ -112,6 +268,36 @@ mod tests {
         assert_eq!(escape_csv_value("with\"quote"), "\"with\"\"quote\"");
     }
 
+    #[test]
+    fn test_export_mapping() {
+        let headers = ["ID", "SKU", "Name", "Created"];
+        let row: Vec<String> = ["7", "BOLT-1", "\"Bolt, M6\"", "2026-03-09 14:05:00"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mapping = ExportMapping {
+            columns: vec!["sku=ItemCode".parse().unwrap(), "Name=Item Description".parse().unwrap(), "Created".parse().unwrap()],
+            date_format: Some(parse_date_format("DD/MM/YYYY").unwrap()),
+        };
+
+        let indices = mapping.column_indices(&headers).unwrap();
+        assert_eq!(mapping.headers(&headers), vec!["ItemCode", "Item Description", "Created"]);
+        assert_eq!(mapping.map_row(&indices, &row), vec!["BOLT-1", "\"Bolt, M6\"", "09/03/2026 14:05:00"]);
+        assert_eq!(ExportMapping::default().column_indices(&headers).unwrap(), vec![0, 1, 2, 3]);
+
+        let unknown = ExportMapping { columns: vec!["Barcode=EAN".parse().unwrap()], date_format: None };
+        assert!(unknown.column_indices(&headers).is_err());
+    }
+
+    #[test]
+    fn test_parse_date_format() {
+        assert_eq!(parse_date_format("DD/MM/YYYY").unwrap(), "%d/%m/%Y");
+        assert_eq!(parse_date_format("%d.%m.%Y").unwrap(), "%d.%m.%Y");
+        assert!(parse_date_format("%Q").is_err());
+        assert!(parse_date_format("soon").is_err());
+        assert!("=ItemCode".parse::<TemplateColumn>().is_err());
+    }
+
     #[test]
     fn test_get_file_extension() {
         assert_eq!(ExportService::get_file_extension("csv"), "csv");
@@ -0,0 +1,531 @@
+use diesel::expression::{is_aggregate, AppearsOnTable, Expression, SelectableExpression, ValidGrouping};
+use diesel::query_builder::{AstPass, QueryFragment, QueryId};
+use diesel::sql_types::{BigInt, Bool, Text};
+use diesel::sqlite::Sqlite;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{error::CLIERPError, result::CLIERPResult};
+use crate::utils::dates::parse_date;
+
+/// Type of a filterable column, which decides how values are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    Integer,
+    Text,
+    Boolean,
+    /// Compared as `YYYY-MM-DD`; values accept anything `--from` accepts
+    Date,
+}
+
+/// A field `--where` may name, and the SQL it reads.
+///
+/// `column` is written into the query as is, so it only ever comes from
+/// these constant tables, never from user input; values are always bound.
+#[derive(Debug, Clone, Copy)]
+pub struct FilterField {
+    pub name: &'static str,
+    pub column: &'static str,
+    pub field_type: FieldType,
+}
+
+impl FilterField {
+    pub const fn integer(name: &'static str, column: &'static str) -> Self {
+        Self { name, column, field_type: FieldType::Integer }
+    }
+
+    pub const fn text(name: &'static str, column: &'static str) -> Self {
+        Self { name, column, field_type: FieldType::Text }
+    }
+
+    pub const fn boolean(name: &'static str, column: &'static str) -> Self {
+        Self { name, column, field_type: FieldType::Boolean }
+    }
+
+    pub const fn date(name: &'static str, column: &'static str) -> Self {
+        Self { name, column, field_type: FieldType::Date }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Like,
+}
+
+impl CompareOp {
+    fn sql(&self) -> &'static str {
+        match self {
+            CompareOp::Eq => " = ",
+            CompareOp::Ne => " <> ",
+            CompareOp::Lt => " < ",
+            CompareOp::Le => " <= ",
+            CompareOp::Gt => " > ",
+            CompareOp::Ge => " >= ",
+            CompareOp::Like => " LIKE ",
+        }
+    }
+}
+
+/// A parsed `--where` expression, e.g. `price>=10000 and category in (3,5)`.
+///
+/// Comparisons are `= != <> < <= > >=`, `like` (with `%` or `*` as the
+/// wildcard), `in (...)`, `not in (...)`, `is null` and `is not null`,
+/// combined with `and`, `or`, `not` and parentheses. Text values may be
+/// quoted with `'` or `"`; unquoted words are taken as they are. Field
+/// names and values are checked against the list's fields when the
+/// expression is turned into a [`WherePredicate`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FilterExpr {
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+    Not(Box<FilterExpr>),
+    Compare { field: String, op: CompareOp, value: String },
+    In { field: String, values: Vec<String>, negated: bool },
+    IsNull { field: String, negated: bool },
+}
+
+impl std::str::FromStr for FilterExpr {
+    type Err = CLIERPError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser { tokens, position: 0 };
+        let expr = parser.or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(invalid(format!("unexpected {}", token))),
+        }
+    }
+}
+
+/// Clap value parser for `--where` arguments
+pub fn parse_filter_expr_arg(s: &str) -> Result<FilterExpr, String> {
+    s.parse().map_err(|e: CLIERPError| e.to_string())
+}
+
+impl FilterExpr {
+    /// The expression as a predicate over `fields`, for `.filter()` on any
+    /// query that selects from the tables the fields name
+    pub fn predicate(&self, fields: &[FilterField]) -> CLIERPResult<WherePredicate> {
+        let mut predicate = WherePredicate { parts: Vec::new() };
+        self.write(fields, &mut predicate)?;
+        Ok(predicate)
+    }
+
+    fn write(&self, fields: &[FilterField], out: &mut WherePredicate) -> CLIERPResult<()> {
+        match self {
+            FilterExpr::And(left, right) | FilterExpr::Or(left, right) => {
+                let joiner = if matches!(self, FilterExpr::And(..)) { ") AND (" } else { ") OR (" };
+                out.sql("((");
+                left.write(fields, out)?;
+                out.sql(joiner);
+                right.write(fields, out)?;
+                out.sql("))");
+            }
+            FilterExpr::Not(inner) => {
+                out.sql("NOT (");
+                inner.write(fields, out)?;
+                out.sql(")");
+            }
+            FilterExpr::Compare { field, op, value } => {
+                let field = find_field(fields, field)?;
+                if *op == CompareOp::Like && field.field_type != FieldType::Text {
+                    return Err(invalid(format!("like only works on text fields, not {}", field.name)));
+                }
+                out.column(field);
+                out.sql(op.sql());
+                let value = if *op == CompareOp::Like { value.replace('*', "%") } else { value.clone() };
+                out.push(bind_value(field, &value)?);
+            }
+            FilterExpr::In { field, values, negated } => {
+                let field = find_field(fields, field)?;
+                out.column(field);
+                out.sql(if *negated { " NOT IN (" } else { " IN (" });
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        out.sql(", ");
+                    }
+                    out.push(bind_value(field, value)?);
+                }
+                out.sql(")");
+            }
+            FilterExpr::IsNull { field, negated } => {
+                let field = find_field(fields, field)?;
+                out.column(field);
+                out.sql(if *negated { " IS NOT NULL" } else { " IS NULL" });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn invalid(message: String) -> CLIERPError {
+    CLIERPError::ValidationError(format!("Invalid --where expression: {}", message))
+}
+
+fn find_field<'a>(fields: &'a [FilterField], name: &str) -> CLIERPResult<&'a FilterField> {
+    fields
+        .iter()
+        .find(|field| field.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let names: Vec<&str> = fields.iter().map(|field| field.name).collect();
+            CLIERPError::ValidationError(format!(
+                "Unknown field '{}' in --where; fields are {}",
+                name,
+                names.join(", ")
+            ))
+        })
+}
+
+fn bind_value(field: &FilterField, value: &str) -> CLIERPResult<SqlPart> {
+    let wrong = |expected: &str| {
+        CLIERPError::ValidationError(format!("{} must be {}, got '{}'", field.name, expected, value))
+    };
+    match field.field_type {
+        FieldType::Integer => value
+            .replace('_', "")
+            .parse::<i64>()
+            .map(SqlPart::Integer)
+            .map_err(|_| wrong("a whole number")),
+        FieldType::Text => Ok(SqlPart::Text(value.to_string())),
+        FieldType::Boolean => match value.to_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" => Ok(SqlPart::Integer(1)),
+            "false" | "no" | "n" | "0" => Ok(SqlPart::Integer(0)),
+            _ => Err(wrong("true or false")),
+        },
+        FieldType::Date => parse_date(value)
+            .map(|date| SqlPart::Text(date.format("%Y-%m-%d").to_string()))
+            .map_err(|_| wrong("a date")),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(CompareOp),
+    Open,
+    Close,
+    Comma,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "'{}'", word),
+            Token::Quoted(text) => write!(f, "\"{}\"", text),
+            Token::Op(op) => write!(f, "'{}'", op.sql().trim()),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+            Token::Comma => write!(f, "','"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> CLIERPResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | ',' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    _ => Token::Comma,
+                });
+            }
+            '=' | '!' | '<' | '>' => {
+                chars.next();
+                let followed_by = |chars: &mut std::iter::Peekable<std::str::Chars>, expected: char| {
+                    let found = chars.peek() == Some(&expected);
+                    if found {
+                        chars.next();
+                    }
+                    found
+                };
+                let op = match c {
+                    '=' => {
+                        followed_by(&mut chars, '=');
+                        CompareOp::Eq
+                    }
+                    '!' if followed_by(&mut chars, '=') => CompareOp::Ne,
+                    '!' => return Err(invalid("'!' must be followed by '='".to_string())),
+                    '<' if followed_by(&mut chars, '=') => CompareOp::Le,
+                    '<' if followed_by(&mut chars, '>') => CompareOp::Ne,
+                    '<' => CompareOp::Lt,
+                    _ if followed_by(&mut chars, '=') => CompareOp::Ge,
+                    _ => CompareOp::Gt,
+                };
+                tokens.push(Token::Op(op));
+            }
+            '\'' | '"' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        // A doubled quote stands for the quote itself
+                        Some(q) if q == c && chars.peek() == Some(&c) => {
+                            chars.next();
+                            text.push(c);
+                        }
+                        Some(q) if q == c => break,
+                        Some(other) => text.push(other),
+                        None => return Err(invalid("unterminated quoted value".to_string())),
+                    }
+                }
+                tokens.push(Token::Quoted(text));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "(),=!<>'\"".contains(c) {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    /// Consume the next token if it is the keyword `keyword`
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> CLIERPResult<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(invalid(format!("expected {}, found {}", expected, token))),
+            None => Err(invalid(format!("expected {} at the end", expected))),
+        }
+    }
+
+    fn or(&mut self) -> CLIERPResult<FilterExpr> {
+        let mut expr = self.and()?;
+        while self.keyword("or") {
+            expr = FilterExpr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> CLIERPResult<FilterExpr> {
+        let mut expr = self.not()?;
+        while self.keyword("and") {
+            expr = FilterExpr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> CLIERPResult<FilterExpr> {
+        if self.keyword("not") {
+            return Ok(FilterExpr::Not(Box::new(self.not()?)));
+        }
+        if self.peek() == Some(&Token::Open) {
+            self.next();
+            let expr = self.or()?;
+            self.expect(Token::Close)?;
+            return Ok(expr);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> CLIERPResult<FilterExpr> {
+        let field = match self.next() {
+            Some(Token::Word(field)) => field,
+            Some(token) => return Err(invalid(format!("expected a field name, found {}", token))),
+            None => return Err(invalid("expected a field name".to_string())),
+        };
+
+        if self.keyword("is") {
+            let negated = self.keyword("not");
+            if !self.keyword("null") {
+                return Err(invalid(format!("expected null after '{} is'", field)));
+            }
+            return Ok(FilterExpr::IsNull { field, negated });
+        }
+
+        let negated = self.keyword("not");
+        if self.keyword("in") {
+            self.expect(Token::Open)?;
+            let mut values = vec![self.value()?];
+            while self.peek() == Some(&Token::Comma) {
+                self.next();
+                values.push(self.value()?);
+            }
+            self.expect(Token::Close)?;
+            return Ok(FilterExpr::In { field, values, negated });
+        }
+        if self.keyword("like") {
+            let like = FilterExpr::Compare { field, op: CompareOp::Like, value: self.value()? };
+            return Ok(if negated { FilterExpr::Not(Box::new(like)) } else { like });
+        }
+        if negated {
+            return Err(invalid(format!("expected in or like after '{} not'", field)));
+        }
+
+        match self.next() {
+            Some(Token::Op(op)) => Ok(FilterExpr::Compare { field, op, value: self.value()? }),
+            Some(token) => Err(invalid(format!("expected an operator after '{}', found {}", field, token))),
+            None => Err(invalid(format!("expected an operator after '{}'", field))),
+        }
+    }
+
+    fn value(&mut self) -> CLIERPResult<String> {
+        match self.next() {
+            Some(Token::Word(value)) | Some(Token::Quoted(value)) => Ok(value),
+            Some(token) => Err(invalid(format!("expected a value, found {}", token))),
+            None => Err(invalid("expected a value at the end".to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum SqlPart {
+    Sql(&'static str),
+    Integer(i64),
+    Text(String),
+}
+
+/// A `--where` expression as a Diesel boolean expression with bound values
+#[derive(Debug, Clone, PartialEq)]
+pub struct WherePredicate {
+    parts: Vec<SqlPart>,
+}
+
+impl WherePredicate {
+    fn sql(&mut self, sql: &'static str) {
+        self.parts.push(SqlPart::Sql(sql));
+    }
+
+    fn push(&mut self, part: SqlPart) {
+        self.parts.push(part);
+    }
+
+    /// Dates are compared by day, so timestamp columns match whole days
+    fn column(&mut self, field: &FilterField) {
+        if field.field_type == FieldType::Date {
+            self.sql("date(");
+            self.sql(field.column);
+            self.sql(")");
+        } else {
+            self.sql(field.column);
+        }
+    }
+}
+
+impl Expression for WherePredicate {
+    type SqlType = Bool;
+}
+
+impl<QS> AppearsOnTable<QS> for WherePredicate {}
+
+impl<QS> SelectableExpression<QS> for WherePredicate {}
+
+impl<GB> ValidGrouping<GB> for WherePredicate {
+    type IsAggregate = is_aggregate::Never;
+}
+
+impl QueryId for WherePredicate {
+    type QueryId = ();
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+impl QueryFragment<Sqlite> for WherePredicate {
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Sqlite>) -> QueryResult<()> {
+        for part in &self.parts {
+            match part {
+                SqlPart::Sql(sql) => out.push_sql(sql),
+                SqlPart::Integer(value) => out.push_bind_param::<BigInt, _>(value)?,
+                SqlPart::Text(value) => out.push_bind_param::<Text, _>(value)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIELDS: &[FilterField] = &[
+        FilterField::integer("price", "products.price"),
+        FilterField::integer("category", "products.category_id"),
+        FilterField::text("name", "products.name"),
+        FilterField::boolean("active", "products.is_active"),
+        FilterField::date("created", "products.created_at"),
+    ];
+
+    fn sql(expr: &str) -> String {
+        let predicate = expr.parse::<FilterExpr>().unwrap().predicate(FIELDS).unwrap();
+        diesel::debug_query::<Sqlite, _>(&predicate).to_string()
+    }
+
+    #[test]
+    fn test_parse_precedence() {
+        let expr: FilterExpr = "price>=10000 and category in (3,5) or not active=true".parse().unwrap();
+        assert!(matches!(expr, FilterExpr::Or(ref left, ref right)
+            if matches!(**left, FilterExpr::And(..)) && matches!(**right, FilterExpr::Not(..))));
+        assert!("price >=".parse::<FilterExpr>().is_err());
+        assert!("(price > 1".parse::<FilterExpr>().is_err());
+        assert!("name = 'open".parse::<FilterExpr>().is_err());
+    }
+
+    #[test]
+    fn test_predicate_binds_values() {
+        assert_eq!(
+            sql("price>=10000 and category in (3,5)"),
+            "((products.price >= ?) AND (products.category_id IN (?, ?))) -- binds: [10000, 3, 5]"
+        );
+        assert_eq!(
+            sql("name like 'Bolt*' or name = \"it's\""),
+            "((products.name LIKE ?) OR (products.name = ?)) -- binds: [\"Bolt%\", \"it's\"]"
+        );
+        assert_eq!(sql("active = yes"), "products.is_active = ? -- binds: [1]");
+        assert_eq!(sql("created <= 2026-01-31"), "date(products.created_at) <= ? -- binds: [\"2026-01-31\"]");
+    }
+
+    #[test]
+    fn test_predicate_checks_fields_and_values() {
+        let check = |expr: &str| expr.parse::<FilterExpr>().unwrap().predicate(FIELDS);
+        assert!(check("weight > 3").is_err());
+        assert!(check("price > cheap").is_err());
+        assert!(check("price like '1%'").is_err());
+        assert!(check("name = '1; DROP TABLE products'").is_ok());
+    }
+}
@@ -1,6 +1,9 @@
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
+use crate::core::{error::CLIERPError, result::CLIERPResult};
+use crate::utils::filter_expr::FilterExpr;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DateRange {
     pub from_date: Option<NaiveDate>,
@@ -17,8 +20,8 @@ impl DateRange {
     }
 
     pub fn contains(&self, date: &NaiveDate) -> bool {
-        let after_from = self.from_date.map_or(true, |from| date >= &from);
-        let before_to = self.to_date.map_or(true, |to| date <= &to);
+        let after_from = self.from_date.is_none_or(|from| date >= &from);
+        let before_to = self.to_date.is_none_or(|to| date <= &to);
         after_from && before_to
     }
 }
@@ -228,4 +231,111 @@ pub struct FilterOptions {
     pub date_to: Option<NaiveDate>,
     pub sort_by: Option<String>,
     pub sort_desc: bool,
+    /// `--where` expression, checked against the list's filter fields
+    pub where_clause: Option<FilterExpr>,
+}
+
+/// A `field=value` pair, used by bulk commands for both `--filter` and `--set`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldAssignment {
+    pub field: String,
+    pub value: String,
+}
+
+impl std::str::FromStr for FieldAssignment {
+    type Err = CLIERPError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((field, value)) if !field.trim().is_empty() => Ok(Self {
+                field: field.trim().to_lowercase(),
+                value: value.trim().to_string(),
+            }),
+            _ => Err(CLIERPError::Validation(format!(
+                "Expected field=value, got '{}'",
+                s
+            ))),
+        }
+    }
+}
+
+impl FieldAssignment {
+    pub fn as_i32(&self) -> CLIERPResult<i32> {
+        self.value.parse().map_err(|_| {
+            CLIERPError::Validation(format!("{} must be a whole number, got '{}'", self.field, self.value))
+        })
+    }
+
+    pub fn as_bool(&self) -> CLIERPResult<bool> {
+        match self.value.to_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" => Ok(true),
+            "false" | "no" | "n" | "0" => Ok(false),
+            _ => Err(CLIERPError::Validation(format!(
+                "{} must be true or false, got '{}'",
+                self.field, self.value
+            ))),
+        }
+    }
+
+    /// The value, with an empty string or `null` meaning "clear the field"
+    pub fn as_optional(&self) -> Option<&str> {
+        match self.value.as_str() {
+            "" | "null" => None,
+            value => Some(value),
+        }
+    }
+
+    pub fn as_optional_i32(&self) -> CLIERPResult<Option<i32>> {
+        self.as_optional().map(|_| self.as_i32()).transpose()
+    }
+
+    /// Parse the value as a clap value enum, accepting `closed_won` as well
+    /// as `closed-won`
+    pub fn as_enum<E: clap::ValueEnum>(&self) -> CLIERPResult<E> {
+        E::from_str(&self.value.replace('_', "-"), true).map_err(|_| {
+            let allowed: Vec<String> = E::value_variants()
+                .iter()
+                .filter_map(|v| v.to_possible_value().map(|p| p.get_name().to_string()))
+                .collect();
+            CLIERPError::Validation(format!(
+                "Invalid {} '{}'. Use one of: {}",
+                self.field,
+                self.value,
+                allowed.join(", ")
+            ))
+        })
+    }
+
+    /// Error for a field the bulk command does not support
+    pub fn unsupported(&self, allowed: &[&str]) -> CLIERPError {
+        CLIERPError::Validation(format!(
+            "Unsupported field '{}'. Use one of: {}",
+            self.field,
+            allowed.join(", ")
+        ))
+    }
+}
+
+/// Clap value parser for `field=value` arguments
+pub fn parse_field_assignment_arg(s: &str) -> Result<FieldAssignment, String> {
+    s.parse().map_err(|e: CLIERPError| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_field_assignment() {
+        let assignment: FieldAssignment = "Category = 3".parse().unwrap();
+        assert_eq!(assignment.field, "category");
+        assert_eq!(assignment.as_i32().unwrap(), 3);
+
+        let cleared: FieldAssignment = "max_stock=".parse().unwrap();
+        assert_eq!(cleared.as_optional_i32().unwrap(), None);
+
+        assert!("no-equals".parse::<FieldAssignment>().is_err());
+        assert!("=5".parse::<FieldAssignment>().is_err());
+        assert!("active=maybe".parse::<FieldAssignment>().unwrap().as_bool().is_err());
+    }
 }
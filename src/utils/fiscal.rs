@@ -0,0 +1,307 @@
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{config::FiscalConfig, error::CLIERPError, result::CLIERPResult};
+
+static FISCAL_CALENDAR: OnceCell<FiscalCalendar> = OnceCell::new();
+
+/// Week-based period layouts for retail-style fiscal calendars
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PeriodPattern {
+    FourFourFive,
+    FourFiveFour,
+    FiveFourFour,
+}
+
+impl PeriodPattern {
+    /// Number of weeks in each period of a quarter
+    pub fn weeks(&self) -> [i64; 3] {
+        match self {
+            PeriodPattern::FourFourFive => [4, 4, 5],
+            PeriodPattern::FourFiveFour => [4, 5, 4],
+            PeriodPattern::FiveFourFour => [5, 4, 4],
+        }
+    }
+}
+
+impl std::str::FromStr for PeriodPattern {
+    type Err = CLIERPError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "4-4-5" => Ok(PeriodPattern::FourFourFive),
+            "4-5-4" => Ok(PeriodPattern::FourFiveFour),
+            "5-4-4" => Ok(PeriodPattern::FiveFourFour),
+            _ => Err(CLIERPError::Validation(format!(
+                "Invalid fiscal period pattern '{}'. Use 4-4-5, 4-5-4 or 5-4-4",
+                s
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for PeriodPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PeriodPattern::FourFourFive => write!(f, "4-4-5"),
+            PeriodPattern::FourFiveFour => write!(f, "4-5-4"),
+            PeriodPattern::FiveFourFour => write!(f, "5-4-4"),
+        }
+    }
+}
+
+/// One of the twelve periods of a fiscal year (dates are inclusive)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FiscalPeriod {
+    pub fiscal_year: i32,
+    pub number: u32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+impl FiscalPeriod {
+    pub fn quarter(&self) -> u32 {
+        (self.number - 1) / 3 + 1
+    }
+
+    /// Label such as `FY2025-P03`
+    pub fn label(&self) -> String {
+        format!("FY{}-P{:02}", self.fiscal_year, self.number)
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        date >= self.start_date && date <= self.end_date
+    }
+}
+
+/// Fiscal calendar used by finance reports, budgets and payroll periods.
+///
+/// Fiscal years are named after the calendar year they end in, so with an
+/// April start FY2025 runs from 2024-04-01 to 2025-03-31. Without a period
+/// pattern each period is a calendar month; with one, periods are whole
+/// weeks and the last period absorbs the days left before the next year.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FiscalCalendar {
+    start_month: u32,
+    pattern: Option<PeriodPattern>,
+}
+
+impl Default for FiscalCalendar {
+    fn default() -> Self {
+        Self {
+            start_month: 1,
+            pattern: None,
+        }
+    }
+}
+
+impl FiscalCalendar {
+    pub fn new(start_month: u32, pattern: Option<PeriodPattern>) -> CLIERPResult<Self> {
+        if !(1..=12).contains(&start_month) {
+            return Err(CLIERPError::Validation(
+                "Fiscal year start month must be between 1 and 12".to_string(),
+            ));
+        }
+        Ok(Self { start_month, pattern })
+    }
+
+    pub fn from_config(config: &FiscalConfig) -> CLIERPResult<Self> {
+        let pattern = config
+            .period_pattern
+            .as_deref()
+            .map(str::parse::<PeriodPattern>)
+            .transpose()?;
+        Self::new(config.year_start_month, pattern)
+    }
+
+    /// Install the calendar from configuration for the rest of the process
+    pub fn initialize(config: &FiscalConfig) -> CLIERPResult<()> {
+        let calendar = Self::from_config(config)?;
+        let _ = FISCAL_CALENDAR.set(calendar);
+        Ok(())
+    }
+
+    /// The configured calendar, or calendar months/years if not initialized
+    pub fn current() -> FiscalCalendar {
+        FISCAL_CALENDAR.get().cloned().unwrap_or_default()
+    }
+
+    pub fn start_month(&self) -> u32 {
+        self.start_month
+    }
+
+    pub fn pattern(&self) -> Option<PeriodPattern> {
+        self.pattern
+    }
+
+    pub fn year_start(&self, fiscal_year: i32) -> NaiveDate {
+        let year = if self.start_month == 1 { fiscal_year } else { fiscal_year - 1 };
+        NaiveDate::from_ymd_opt(year, self.start_month, 1).expect("valid fiscal year start")
+    }
+
+    /// First and last day of the fiscal year
+    pub fn year_range(&self, fiscal_year: i32) -> (NaiveDate, NaiveDate) {
+        let next_start = self.year_start(fiscal_year + 1);
+        (self.year_start(fiscal_year), next_start - Duration::days(1))
+    }
+
+    pub fn fiscal_year_of(&self, date: NaiveDate) -> i32 {
+        if self.start_month == 1 || date.month() < self.start_month {
+            date.year()
+        } else {
+            date.year() + 1
+        }
+    }
+
+    pub fn periods(&self, fiscal_year: i32) -> Vec<FiscalPeriod> {
+        let (year_start, year_end) = self.year_range(fiscal_year);
+        let mut periods = Vec::with_capacity(12);
+        let mut start = year_start;
+
+        for number in 1..=12u32 {
+            let end = if number == 12 {
+                year_end
+            } else {
+                match self.pattern {
+                    Some(pattern) => {
+                        let weeks = pattern.weeks()[((number - 1) % 3) as usize];
+                        start + Duration::weeks(weeks) - Duration::days(1)
+                    }
+                    None => start
+                        .checked_add_months(Months::new(1))
+                        .expect("valid period end")
+                        - Duration::days(1),
+                }
+            };
+            periods.push(FiscalPeriod {
+                fiscal_year,
+                number,
+                start_date: start,
+                end_date: end,
+            });
+            start = end + Duration::days(1);
+        }
+
+        periods
+    }
+
+    pub fn period(&self, fiscal_year: i32, number: u32) -> CLIERPResult<FiscalPeriod> {
+        if !(1..=12).contains(&number) {
+            return Err(CLIERPError::Validation(
+                "Fiscal period must be between 1 and 12".to_string(),
+            ));
+        }
+        Ok(self.periods(fiscal_year).remove((number - 1) as usize))
+    }
+
+    pub fn period_of(&self, date: NaiveDate) -> FiscalPeriod {
+        let fiscal_year = self.fiscal_year_of(date);
+        self.periods(fiscal_year)
+            .into_iter()
+            .find(|p| p.contains(date))
+            .expect("date falls within its fiscal year")
+    }
+
+    pub fn quarter_range(&self, fiscal_year: i32, quarter: u32) -> CLIERPResult<(NaiveDate, NaiveDate)> {
+        if !(1..=4).contains(&quarter) {
+            return Err(CLIERPError::Validation(
+                "Fiscal quarter must be between 1 and 4".to_string(),
+            ));
+        }
+        let periods = self.periods(fiscal_year);
+        let first = &periods[((quarter - 1) * 3) as usize];
+        let last = &periods[((quarter - 1) * 3 + 2) as usize];
+        Ok((first.start_date, last.end_date))
+    }
+
+    /// Resolve a period label into an inclusive date range.
+    ///
+    /// Accepts `YYYY-MM` (calendar month), `FY2025` (fiscal year),
+    /// `FY2025-Q2` (fiscal quarter) and `FY2025-P03` (fiscal period).
+    pub fn resolve(&self, label: &str) -> CLIERPResult<(NaiveDate, NaiveDate)> {
+        let invalid = || {
+            CLIERPError::Validation(format!(
+                "Invalid period '{}'. Use YYYY-MM, FY2025, FY2025-Q2 or FY2025-P03",
+                label
+            ))
+        };
+        let label = label.trim().to_uppercase();
+
+        if let Some(rest) = label.strip_prefix("FY") {
+            let (year_part, sub) = match rest.split_once('-') {
+                Some((year, sub)) => (year, Some(sub)),
+                None => (rest, None),
+            };
+            let fiscal_year: i32 = year_part.parse().map_err(|_| invalid())?;
+            return match sub {
+                None => Ok(self.year_range(fiscal_year)),
+                Some(q) if q.starts_with('Q') => {
+                    let quarter: u32 = q[1..].parse().map_err(|_| invalid())?;
+                    self.quarter_range(fiscal_year, quarter)
+                }
+                Some(p) if p.starts_with('P') => {
+                    let number: u32 = p[1..].parse().map_err(|_| invalid())?;
+                    let period = self.period(fiscal_year, number)?;
+                    Ok((period.start_date, period.end_date))
+                }
+                Some(_) => Err(invalid()),
+            };
+        }
+
+        let start = NaiveDate::parse_from_str(&format!("{}-01", label), "%Y-%m-%d").map_err(|_| invalid())?;
+        let end = start.checked_add_months(Months::new(1)).ok_or_else(invalid)? - Duration::days(1);
+        Ok((start, end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_default_calendar_matches_calendar_year() {
+        let calendar = FiscalCalendar::default();
+        assert_eq!(calendar.year_range(2025), (date(2025, 1, 1), date(2025, 12, 31)));
+        assert_eq!(calendar.period_of(date(2025, 2, 14)).number, 2);
+        assert_eq!(calendar.resolve("2025-02").unwrap(), (date(2025, 2, 1), date(2025, 2, 28)));
+    }
+
+    #[test]
+    fn test_april_start_fiscal_year() {
+        let calendar = FiscalCalendar::new(4, None).unwrap();
+        assert_eq!(calendar.fiscal_year_of(date(2024, 4, 1)), 2025);
+        assert_eq!(calendar.fiscal_year_of(date(2025, 3, 31)), 2025);
+        assert_eq!(calendar.year_range(2025), (date(2024, 4, 1), date(2025, 3, 31)));
+        assert_eq!(calendar.resolve("FY2025-Q4").unwrap(), (date(2025, 1, 1), date(2025, 3, 31)));
+        assert_eq!(calendar.period_of(date(2024, 12, 10)).label(), "FY2025-P09");
+    }
+
+    #[test]
+    fn test_445_periods_cover_the_year() {
+        let calendar = FiscalCalendar::new(1, Some(PeriodPattern::FourFourFive)).unwrap();
+        let periods = calendar.periods(2025);
+
+        assert_eq!(periods.len(), 12);
+        assert_eq!(periods[0].end_date, date(2025, 1, 28));
+        assert_eq!(periods[2].end_date - periods[2].start_date, Duration::days(34));
+        assert_eq!(periods[11].end_date, date(2025, 12, 31));
+        for pair in periods.windows(2) {
+            assert_eq!(pair[0].end_date + Duration::days(1), pair[1].start_date);
+        }
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        assert!(FiscalCalendar::new(13, None).is_err());
+        assert!("4-4-4".parse::<PeriodPattern>().is_err());
+        let calendar = FiscalCalendar::default();
+        assert!(calendar.resolve("FY2025-Q5").is_err());
+        assert!(calendar.resolve("FY2025-P13").is_err());
+        assert!(calendar.resolve("last-month").is_err());
+    }
+}
@@ -1,29 +1,136 @@
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use colored::*;
 use tabled::{Table, Tabled};
 
+use crate::utils::currency::CurrencyFormat;
+use crate::utils::timezone::DisplayTimezone;
+
+/// Set by the global `--plain` flag for the rest of the process
+static PLAIN_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Symbols used in output and their `--plain` replacements. Emojis carry a
+/// trailing variation selector in some messages, which is dropped with them.
+const PLAIN_SYMBOLS: &[(&str, &str)] = &[
+    ("✅", "[OK]"),
+    ("✓", "[OK]"),
+    ("❌", "[ERROR]"),
+    ("✗", "[ERROR]"),
+    ("⚠", "[WARN]"),
+    ("ℹ", "[INFO]"),
+    ("⏳", "[PENDING]"),
+    ("🛑", "[STOP]"),
+    ("↩", "[REVERSED]"),
+    ("═", "="),
+    ("─", "-"),
+    ("│", "|"),
+    ("├", "+"),
+    ("┤", "+"),
+    ("┼", "+"),
+    ("┌", "+"),
+    ("┐", "+"),
+    ("└", "+"),
+    ("┘", "+"),
+    ("┬", "+"),
+    ("┴", "+"),
+    ("…", "..."),
+    ("→", "->"),
+    ("←", "<-"),
+];
+
+/// Apply `--no-color`, `--plain` and the `NO_COLOR` environment variable.
+///
+/// Colors are turned off by any of them; `--plain` also turns symbols and
+/// box drawing into ASCII (see [`plain_text`]) so output can be grepped and
+/// compared between runs.
+pub fn configure_output(no_color: bool, plain: bool) {
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    if no_color || plain || no_color_env {
+        colored::control::set_override(false);
+    }
+    PLAIN_OUTPUT.store(plain, Ordering::Relaxed);
+}
+
+/// Whether `--plain` output was asked for
+pub fn is_plain() -> bool {
+    PLAIN_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// `text` as it should be printed: unchanged, or in `--plain` mode with
+/// symbols replaced by ASCII tags and other non-ASCII pictographs removed.
+/// Letters such as Hangul in names are kept; amounts lose their currency
+/// sign in [`format_currency`] instead.
+pub fn for_output(text: &str) -> Cow<'_, str> {
+    if is_plain() {
+        Cow::Owned(plain_text(text))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// `text` with [`PLAIN_SYMBOLS`] replaced and emojis dropped
+pub fn plain_text(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut rest = text;
+    'outer: while let Some(c) = rest.chars().next() {
+        for (symbol, ascii) in PLAIN_SYMBOLS {
+            if let Some(after) = rest.strip_prefix(symbol) {
+                plain.push_str(ascii);
+                rest = after.trim_start_matches('\u{fe0f}');
+                continue 'outer;
+            }
+        }
+        rest = &rest[c.len_utf8()..];
+        if is_pictograph(c) {
+            // Drop the space that followed the emoji as well
+            rest = rest.trim_start_matches('\u{fe0f}');
+            if plain.is_empty() || plain.ends_with(char::is_whitespace) {
+                rest = rest.strip_prefix(' ').unwrap_or(rest);
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+/// Emoji and other pictographs, which have no place in `--plain` output
+fn is_pictograph(c: char) -> bool {
+    matches!(c as u32, 0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0xFE0F)
+}
+
+/// `println!` for messages carrying symbols, passed through [`for_output`]
+#[macro_export]
+macro_rules! outln {
+    ($($arg:tt)*) => {
+        println!("{}", $crate::utils::formatting::for_output(&format!($($arg)*)))
+    };
+}
+
 /// Format success message with green color
 pub fn success(message: &str) -> String {
-    format!("✓ {}", message.green())
+    for_output(&format!("✅ {}", message.green())).into_owned()
 }
 
 /// Format error message with red color
 pub fn error(message: &str) -> String {
-    format!("✗ {}", message.red())
+    for_output(&format!("❌ {}", message.red())).into_owned()
 }
 
 /// Format warning message with yellow color
 pub fn warning(message: &str) -> String {
-    format!("⚠ {}", message.yellow())
+    for_output(&format!("⚠️ {}", message.yellow())).into_owned()
 }
 
 /// Format info message with blue color
 pub fn info(message: &str) -> String {
-    format!("ℹ {}", message.blue())
+    for_output(&format!("ℹ {}", message.blue())).into_owned()
 }
 
 /// Format header with bold text
 pub fn header(message: &str) -> String {
-    message.bold().to_string()
+    for_output(&message.bold().to_string()).into_owned()
 }
 
 /// Create a table from data that implements Tabled trait
@@ -35,9 +142,15 @@ pub fn create_table<T: Tabled>(data: Vec<T>) -> String {
     Table::new(data).to_string()
 }
 
-/// Format currency amount
-pub fn format_currency(amount: i32) -> String {
-    format!("₩{}", format_number_with_commas(amount))
+/// Format currency amount using the configured locale; `--plain` leaves
+/// out the symbol
+pub fn format_currency<T: Into<i64>>(amount: T) -> String {
+    let format = CurrencyFormat::current();
+    if is_plain() {
+        format.format_without_symbol(amount.into())
+    } else {
+        format.format(amount.into())
+    }
 }
 
 /// Format optional currency amount
@@ -48,20 +161,14 @@ pub fn format_optional_currency(amount: Option<i32>) -> String {
     }
 }
 
-/// Format number with thousands separators
+/// Format number with the configured thousands separator
 pub fn format_number_with_commas(n: i32) -> String {
-    let s = n.to_string();
-    let mut result = String::new();
-    let chars: Vec<char> = s.chars().collect();
-
-    for (i, &c) in chars.iter().enumerate() {
-        if i > 0 && (chars.len() - i) % 3 == 0 {
-            result.push(',');
-        }
-        result.push(c);
-    }
+    CurrencyFormat::current().format_number(n as i64)
+}
 
-    result
+/// Format currency amount for CSV/JSON exports (no symbol or grouping)
+pub fn format_plain_amount<T: Into<i64>>(amount: T) -> String {
+    CurrencyFormat::current().format_plain(amount.into())
 }
 
 /// Format percentage
@@ -69,9 +176,27 @@ pub fn format_percentage(value: f64) -> String {
     format!("{:.1}%", value)
 }
 
-/// Format datetime to readable string
+/// Format a size in bytes with a binary unit, e.g. 1.5 MiB
+pub fn format_bytes(bytes: i64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB", "TiB"];
+    if bytes.abs() < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value.abs() >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Format a stored UTC timestamp in the display timezone
 pub fn format_datetime(datetime: &chrono::NaiveDateTime) -> String {
-    datetime.format("%Y-%m-%d %H:%M:%S").to_string()
+    DisplayTimezone::current()
+        .to_local(*datetime)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
 }
 
 /// Format date to readable string
@@ -122,3 +247,25 @@ pub fn format_table(headers: &[&str], rows: &[Vec<String>]) {
         println!();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text() {
+        assert_eq!(plain_text("✅ Audit 3 cancelled"), "[OK] Audit 3 cancelled");
+        assert_eq!(plain_text("⚠️ Stock below minimum"), "[WARN] Stock below minimum");
+        assert_eq!(plain_text("📊 Customer Overview:"), "Customer Overview:");
+        assert_eq!(plain_text("│ 김철수 │ ₩1,000 │"), "| 김철수 | ₩1,000 |");
+        assert_eq!(plain_text("══ Report ══"), "== Report ==");
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(-3 * 1024 * 1024), "-3.0 MiB");
+        assert_eq!(format_bytes(5 * 1024_i64.pow(4)), "5.0 TiB");
+    }
+}
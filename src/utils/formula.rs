@@ -0,0 +1,216 @@
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use std::collections::HashMap;
+
+/// Arithmetic expression over named metrics, e.g.
+/// `(revenue - cogs) / revenue * 100`.
+///
+/// Supports numbers, metric names (letters, digits and `_`), `+ - * /`,
+/// unary minus and parentheses, with the usual precedence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Metric(String),
+    Neg(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Op(char),
+    Open,
+    Close,
+}
+
+impl Formula {
+    pub fn parse(source: &str) -> CLIERPResult<Self> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens: &tokens, pos: 0 };
+        let expr = parser.expression()?;
+        if parser.pos < tokens.len() {
+            return Err(invalid(source, "unexpected input after the end of the expression"));
+        }
+        Ok(Self { expr })
+    }
+
+    /// Metric names the formula refers to, sorted and without duplicates
+    pub fn metrics(&self) -> Vec<String> {
+        fn collect(expr: &Expr, names: &mut Vec<String>) {
+            match expr {
+                Expr::Number(_) => {}
+                Expr::Metric(name) => names.push(name.clone()),
+                Expr::Neg(inner) => collect(inner, names),
+                Expr::Binary(_, left, right) => {
+                    collect(left, names);
+                    collect(right, names);
+                }
+            }
+        }
+        let mut names = Vec::new();
+        collect(&self.expr, &mut names);
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Value of the formula, or `None` if a metric is missing or it divides by zero
+    pub fn evaluate(&self, values: &HashMap<String, f64>) -> Option<f64> {
+        fn eval(expr: &Expr, values: &HashMap<String, f64>) -> Option<f64> {
+            match expr {
+                Expr::Number(n) => Some(*n),
+                Expr::Metric(name) => values.get(name).copied(),
+                Expr::Neg(inner) => eval(inner, values).map(|v| -v),
+                Expr::Binary(op, left, right) => {
+                    let (a, b) = (eval(left, values)?, eval(right, values)?);
+                    match op {
+                        '+' => Some(a + b),
+                        '-' => Some(a - b),
+                        '*' => Some(a * b),
+                        _ if b == 0.0 => None,
+                        _ => Some(a / b),
+                    }
+                }
+            }
+        }
+        eval(&self.expr, values)
+    }
+}
+
+fn invalid(source: &str, reason: &str) -> CLIERPError {
+    CLIERPError::Validation(format!("Invalid formula '{}': {}", source, reason))
+}
+
+fn tokenize(source: &str) -> CLIERPResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = start;
+            while let Some(&(i, d)) = chars.peek() {
+                if !(d.is_ascii_digit() || d == '.') {
+                    break;
+                }
+                end = i + d.len_utf8();
+                chars.next();
+            }
+            let number = source[start..end]
+                .parse()
+                .map_err(|_| invalid(source, &format!("'{}' is not a number", &source[start..end])))?;
+            tokens.push(Token::Number(number));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, d)) = chars.peek() {
+                if !(d.is_ascii_alphanumeric() || d == '_') {
+                    break;
+                }
+                end = i + d.len_utf8();
+                chars.next();
+            }
+            tokens.push(Token::Name(source[start..end].to_lowercase()));
+        } else {
+            tokens.push(match c {
+                '+' | '-' | '*' | '/' => Token::Op(c),
+                '(' => Token::Open,
+                ')' => Token::Close,
+                _ => return Err(invalid(source, &format!("unexpected character '{}'", c))),
+            });
+            chars.next();
+        }
+    }
+    if tokens.is_empty() {
+        return Err(invalid(source, "the expression is empty"));
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek_op(&self, ops: &[char]) -> Option<char> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) if ops.contains(op) => Some(*op),
+            _ => None,
+        }
+    }
+
+    fn expression(&mut self) -> CLIERPResult<Expr> {
+        let mut left = self.term()?;
+        while let Some(op) = self.peek_op(&['+', '-']) {
+            self.pos += 1;
+            left = Expr::Binary(op, Box::new(left), Box::new(self.term()?));
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self) -> CLIERPResult<Expr> {
+        let mut left = self.factor()?;
+        while let Some(op) = self.peek_op(&['*', '/']) {
+            self.pos += 1;
+            left = Expr::Binary(op, Box::new(left), Box::new(self.factor()?));
+        }
+        Ok(left)
+    }
+
+    fn factor(&mut self) -> CLIERPResult<Expr> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Name(name)) => Ok(Expr::Metric(name)),
+            Some(Token::Op('-')) => Ok(Expr::Neg(Box::new(self.factor()?))),
+            Some(Token::Open) => {
+                let inner = self.expression()?;
+                if self.tokens.get(self.pos) != Some(&Token::Close) {
+                    return Err(CLIERPError::Validation("Invalid formula: missing ')'".to_string()));
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            _ => Err(CLIERPError::Validation(
+                "Invalid formula: expected a number, metric or '('".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_with_precedence() {
+        let values = HashMap::from([
+            ("revenue".to_string(), 1000.0),
+            ("cogs".to_string(), 600.0),
+        ]);
+        let margin = Formula::parse("(revenue - cogs) / Revenue * 100").unwrap();
+        assert_eq!(margin.metrics(), vec!["cogs", "revenue"]);
+        assert_eq!(margin.evaluate(&values), Some(40.0));
+
+        assert_eq!(Formula::parse("2 + 3 * -4").unwrap().evaluate(&values), Some(-10.0));
+        assert_eq!(Formula::parse("cogs / 0").unwrap().evaluate(&values), None);
+        assert_eq!(Formula::parse("missing + 1").unwrap().evaluate(&values), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Formula::parse("").is_err());
+        assert!(Formula::parse("revenue -").is_err());
+        assert!(Formula::parse("(revenue").is_err());
+        assert!(Formula::parse("revenue cogs").is_err());
+        assert!(Formula::parse("revenue % 2").is_err());
+        assert!(Formula::parse("1.2.3").is_err());
+    }
+}
@@ -0,0 +1,158 @@
+/// Escape text for use in HTML element content and quoted attributes
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Standard (RFC 4648) base64 with padding
+pub fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (u32::from(bytes[0]) << 16) | (u32::from(bytes[1]) << 8) | u32::from(bytes[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Standard base64; `None` on anything but base64 characters and whitespace
+pub fn base64_decode(input: &str) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in input.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            c if c.is_ascii_whitespace() => continue,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(decoded)
+}
+
+/// `data:` URI embedding `data`, so an HTML file carries its own images
+pub fn data_uri(mime_type: &str, data: &[u8]) -> String {
+    format!("data:{};base64,{}", mime_type, base64_encode(data))
+}
+
+/// Self-contained printable page around `body`
+pub fn html_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n<h1>{}</h1>\n{}</body>\n</html>\n",
+        escape_html(title),
+        STYLE,
+        escape_html(title),
+        body
+    )
+}
+
+/// `<table>` with a header row; cells are escaped
+pub fn html_table(headers: &[String], rows: &[Vec<String>], totals: Option<&[String]>) -> String {
+    let mut html = String::from("<table>\n<thead><tr>");
+    for header in headers {
+        html.push_str(&format!("<th>{}</th>", escape_html(header)));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            html.push_str(&format!("<td>{}</td>", escape_html(cell)));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n");
+    if let Some(totals) = totals {
+        html.push_str("<tfoot><tr>");
+        for cell in totals {
+            html.push_str(&format!("<td>{}</td>", escape_html(cell)));
+        }
+        html.push_str("</tr></tfoot>\n");
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.6em; }
+table { border-collapse: collapse; margin-bottom: 1.5em; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; }
+th { background: #f0f0f0; }
+tfoot td { font-weight: bold; }
+.catalog { display: flex; flex-wrap: wrap; gap: 12px; }
+.item { width: 220px; border: 1px solid #ccc; padding: 8px; page-break-inside: avoid; }
+.item img { width: 100%; height: 160px; object-fit: contain; }
+.item .photo { height: 160px; background: #f4f4f4; }
+.item .price { font-weight: bold; }
+.item .muted { color: #777; font-size: 0.85em; }
+form { margin: 1em 0; padding: 8px 12px; border: 1px solid #ccc; max-width: 32em; }
+form label { display: block; margin: 0.4em 0; }
+form input[type=text], form input[type=date], form textarea { width: 100%; box-sizing: border-box; }
+.notice { padding: 8px 12px; background: #eef6ee; border: 1px solid #9c9; }
+.error { padding: 8px 12px; background: #fbeeee; border: 1px solid #c99; }
+.muted { color: #777; }
+.scroll { overflow-x: auto; }
+.tiles { display: grid; grid-template-columns: repeat(auto-fill, minmax(150px, 1fr)); gap: 8px; margin-bottom: 1.5em; }
+.tile { border: 1px solid #ccc; border-left: 6px solid #ccc; padding: 8px; }
+.tile .label { color: #555; font-size: 0.85em; }
+.tile .value { font-size: 1.4em; font-weight: bold; }
+.tile.ok { border-left-color: #6a6; }
+.tile.warning { border-left-color: #db3; }
+.tile.critical { border-left-color: #c33; }
+.board { display: flex; gap: 8px; overflow-x: auto; margin-bottom: 1.5em; }
+.stage { flex: 0 0 220px; border: 1px solid #ccc; padding: 8px; }
+.stage h3 { margin: 0; font-size: 1em; text-transform: capitalize; }
+.stage ul { margin: 0.5em 0 0; padding: 0; list-style: none; }
+.stage li { padding: 2px 0; border-top: 1px solid #eee; }
+.stage .price { float: right; }
+@media (max-width: 600px) {
+  body { margin: 0.8em; }
+  .board { flex-direction: column; }
+  .stage { flex-basis: auto; }
+}
+@media print { body { margin: 0; } }
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_and_base64() {
+        assert_eq!(escape_html("<a href=\"x\">Tom & Jerry's</a>"), "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;");
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(data_uri("image/png", b"hi"), "data:image/png;base64,aGk=");
+    }
+}
@@ -0,0 +1,204 @@
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{error::CLIERPError, result::CLIERPResult};
+
+static LANGUAGE: OnceCell<Language> = OnceCell::new();
+
+/// Language used for user-facing CLI messages (help text stays English)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum Language {
+    #[default]
+    En,
+    Ko,
+}
+
+impl std::str::FromStr for Language {
+    type Err = CLIERPError;
+
+    /// Accepts `en`, `ko`, `en-US`, `ko_KR.UTF-8` and similar locale strings
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tag = s
+            .split(['.', '@'])
+            .next()
+            .unwrap_or_default()
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match tag.as_str() {
+            "en" | "english" | "c" | "posix" => Ok(Language::En),
+            "ko" | "korean" => Ok(Language::Ko),
+            _ => Err(CLIERPError::Validation(format!(
+                "Unsupported language '{}'. Use en or ko",
+                s
+            ))),
+        }
+    }
+}
+
+impl Language {
+    /// Install the message language for the rest of the process
+    pub fn initialize(lang: &str) -> CLIERPResult<()> {
+        let language = lang.parse::<Language>()?;
+        let _ = LANGUAGE.set(language);
+        Ok(())
+    }
+
+    /// The configured language, or English if not initialized
+    pub fn current() -> Language {
+        LANGUAGE.get().copied().unwrap_or_default()
+    }
+
+    fn bundle(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::En => EN,
+            Language::Ko => KO,
+        }
+    }
+
+    fn lookup(&self, key: &str) -> Option<&'static str> {
+        self.bundle().iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+    }
+}
+
+/// Translate a message key, falling back to English and then to the key itself
+pub fn t(key: &'static str) -> &'static str {
+    Language::current()
+        .lookup(key)
+        .or_else(|| Language::En.lookup(key))
+        .unwrap_or(key)
+}
+
+/// Translate a message key and substitute `{name}` placeholders
+pub fn t_with(key: &'static str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+    let mut message = t(key).to_string();
+    for (name, value) in args {
+        message = message.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    message
+}
+
+const EN: &[(&str, &str)] = &[
+    ("app.banner", "CLIERP - CLI-based ERP System"),
+    ("app.help_hint", "Use --help for more information"),
+    ("common.page", "Page {page} of {total_pages} ({total_count} total)"),
+    ("system.initializing", "Initializing CLIERP system..."),
+    ("system.initialized", "✓ System initialized successfully!"),
+    ("system.default_admin", "Default admin user created: username 'admin'"),
+    ("system.change_password", "Please login and change the default password."),
+    ("system.status_title", "CLIERP System Status"),
+    ("system.db_connected", "Database: ✓ Connected"),
+    ("system.db_error", "Database: ✗ Error - {error}"),
+    ("system.migrating", "Running database migrations..."),
+    ("system.migrated", "✓ Migrations completed successfully!"),
+    ("system.admin_created", "✓ Default admin user created!"),
+    ("auth.password_prompt", "Password: "),
+    ("auth.new_password_prompt", "Password for new user: "),
+    ("auth.login_success", "✓ Login successful! Welcome, {username}"),
+    ("auth.login_failed", "✗ Login failed: {error}"),
+    ("auth.logged_out", "✓ Logged out successfully!"),
+    ("auth.current_user", "Current User:"),
+    ("auth.not_logged_in", "Not logged in"),
+    ("auth.user_created", "✓ User created successfully: {username}"),
+    ("inv.product_created", "✅ Product created:"),
+    ("inv.no_products", "No products found."),
+    ("inv.products_title", "Products:"),
+    ("inv.product_details", "Product Details:"),
+    ("inv.customs_updated", "✅ Customs details updated:"),
+    ("inv.stock_added", "✅ Stock added:"),
+    ("inv.stock_removed", "✅ Stock removed:"),
+    ("inv.movement_reversed", "✅ Stock movement reversed:"),
+    ("inv.no_low_stock", "No low stock products found."),
+    ("inv.low_stock_title", "Low Stock Products:"),
+    ("inv.no_cross_border", "  No cross-border movements found."),
+    ("inv.declaration_exported", "✅ Declaration exported to {path}"),
+    ("purchase.supplier_created", "✅ Supplier created successfully!"),
+    ("purchase.no_suppliers", "No suppliers found."),
+    ("purchase.suppliers_title", "Suppliers:"),
+    ("purchase.supplier_details", "Supplier Details:"),
+    ("purchase.supplier_updated", "✅ Supplier updated successfully!"),
+    ("purchase.po_created", "✅ Purchase order created successfully!"),
+    ("purchase.no_orders", "No purchase orders found."),
+    ("purchase.orders_title", "Purchase Orders:"),
+    ("purchase.order_details", "Purchase Order Details:"),
+    ("purchase.po_approved", "✅ Purchase order approved successfully!"),
+    ("purchase.items_received", "✅ Purchase order items received successfully!"),
+];
+
+const KO: &[(&str, &str)] = &[
+    ("app.banner", "CLIERP - CLI 기반 ERP 시스템"),
+    ("app.help_hint", "자세한 사용법은 --help 를 참고하세요"),
+    ("common.page", "{page}/{total_pages} 페이지 (총 {total_count}건)"),
+    ("system.initializing", "CLIERP 시스템을 초기화하는 중..."),
+    ("system.initialized", "✓ 시스템이 초기화되었습니다!"),
+    ("system.default_admin", "기본 관리자 계정이 생성되었습니다: 사용자명 'admin'"),
+    ("system.change_password", "로그인 후 기본 비밀번호를 변경하세요."),
+    ("system.status_title", "CLIERP 시스템 상태"),
+    ("system.db_connected", "데이터베이스: ✓ 연결됨"),
+    ("system.db_error", "데이터베이스: ✗ 오류 - {error}"),
+    ("system.migrating", "데이터베이스 마이그레이션을 실행하는 중..."),
+    ("system.migrated", "✓ 마이그레이션이 완료되었습니다!"),
+    ("system.admin_created", "✓ 기본 관리자 계정이 생성되었습니다!"),
+    ("auth.password_prompt", "비밀번호: "),
+    ("auth.new_password_prompt", "새 사용자 비밀번호: "),
+    ("auth.login_success", "✓ 로그인 성공! {username}님 환영합니다"),
+    ("auth.login_failed", "✗ 로그인 실패: {error}"),
+    ("auth.logged_out", "✓ 로그아웃되었습니다!"),
+    ("auth.current_user", "현재 사용자:"),
+    ("auth.not_logged_in", "로그인되어 있지 않습니다"),
+    ("auth.user_created", "✓ 사용자가 생성되었습니다: {username}"),
+    ("inv.product_created", "✅ 제품이 등록되었습니다:"),
+    ("inv.no_products", "제품이 없습니다."),
+    ("inv.products_title", "제품 목록:"),
+    ("inv.product_details", "제품 상세:"),
+    ("inv.customs_updated", "✅ 통관 정보가 수정되었습니다:"),
+    ("inv.stock_added", "✅ 입고되었습니다:"),
+    ("inv.stock_removed", "✅ 출고되었습니다:"),
+    ("inv.movement_reversed", "✅ 재고 이동이 취소 처리되었습니다:"),
+    ("inv.no_low_stock", "재고 부족 제품이 없습니다."),
+    ("inv.low_stock_title", "재고 부족 제품:"),
+    ("inv.no_cross_border", "  국가 간 이동 내역이 없습니다."),
+    ("inv.declaration_exported", "✅ 신고서를 {path} 파일로 내보냈습니다"),
+    ("purchase.supplier_created", "✅ 공급업체가 등록되었습니다!"),
+    ("purchase.no_suppliers", "공급업체가 없습니다."),
+    ("purchase.suppliers_title", "공급업체 목록:"),
+    ("purchase.supplier_details", "공급업체 상세:"),
+    ("purchase.supplier_updated", "✅ 공급업체 정보가 수정되었습니다!"),
+    ("purchase.po_created", "✅ 구매 주문이 생성되었습니다!"),
+    ("purchase.no_orders", "구매 주문이 없습니다."),
+    ("purchase.orders_title", "구매 주문 목록:"),
+    ("purchase.order_details", "구매 주문 상세:"),
+    ("purchase.po_approved", "✅ 구매 주문이 승인되었습니다!"),
+    ("purchase.items_received", "✅ 구매 주문 품목이 입고되었습니다!"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_language() {
+        assert_eq!("ko".parse::<Language>().unwrap(), Language::Ko);
+        assert_eq!("ko_KR.UTF-8".parse::<Language>().unwrap(), Language::Ko);
+        assert_eq!("en-US".parse::<Language>().unwrap(), Language::En);
+        assert!("fr".parse::<Language>().is_err());
+    }
+
+    #[test]
+    fn test_bundles_have_the_same_keys() {
+        for (key, _) in EN {
+            assert!(Language::Ko.lookup(key).is_some(), "missing Korean message: {}", key);
+        }
+        for (key, _) in KO {
+            assert!(Language::En.lookup(key).is_some(), "missing English message: {}", key);
+        }
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let message = t_with("auth.login_success", &[("username", &"admin")]);
+        assert_eq!(message, "✓ Login successful! Welcome, admin");
+        assert_eq!(t("unknown.key"), "unknown.key");
+    }
+}
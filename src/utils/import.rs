@@ -0,0 +1,211 @@
+use std::collections::HashSet;
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+
+/// Rows written per insert statement by batched imports
+pub const DEFAULT_BATCH_SIZE: usize = 500;
+
+/// What a batched insert does with a record whose key already exists,
+/// either in the table or earlier in the same batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ConflictStrategy {
+    /// Keep the existing record and leave the new one out
+    Skip,
+    /// Overwrite the existing record with the new values
+    Update,
+    /// Reject the whole batch
+    #[default]
+    Fail,
+}
+
+impl std::fmt::Display for ConflictStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConflictStrategy::Skip => write!(f, "skip"),
+            ConflictStrategy::Update => write!(f, "update"),
+            ConflictStrategy::Fail => write!(f, "fail"),
+        }
+    }
+}
+
+/// How one record of a batch is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchAction {
+    Insert,
+    Update,
+    Skip,
+}
+
+/// Decide per record how a batch is written, given the keys already stored.
+/// `key_name` labels the key in errors, e.g. "SKU".
+///
+/// Updates are meant to run after the batch's inserts, so a key repeated
+/// within the batch is inserted once and then updated by later records.
+pub fn plan_batch(
+    key_name: &str,
+    keys: &[&str],
+    existing: &HashSet<String>,
+    strategy: ConflictStrategy,
+) -> CLIERPResult<Vec<BatchAction>> {
+    let mut seen: HashSet<&str> = HashSet::new();
+    keys.iter()
+        .map(|key| {
+            let in_batch = !seen.insert(key);
+            if !in_batch && !existing.contains(*key) {
+                return Ok(BatchAction::Insert);
+            }
+            match strategy {
+                ConflictStrategy::Skip => Ok(BatchAction::Skip),
+                ConflictStrategy::Update => Ok(BatchAction::Update),
+                ConflictStrategy::Fail if in_batch => Err(CLIERPError::Validation(format!(
+                    "{} {} appears more than once",
+                    key_name, key
+                ))),
+                ConflictStrategy::Fail => Err(CLIERPError::Validation(format!(
+                    "{} {} already exists; use --on-conflict skip or update",
+                    key_name, key
+                ))),
+            }
+        })
+        .collect()
+}
+
+/// A CSV file with a header row, loaded into memory
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl CsvTable {
+    pub fn read(file_path: &str) -> CLIERPResult<Self> {
+        let content = std::fs::read_to_string(file_path).map_err(|e| {
+            CLIERPError::IoError(format!("Failed to read file {}: {}", file_path, e))
+        })?;
+        Self::parse(&content)
+    }
+
+    /// Parse CSV text. Headers are trimmed and lowercased; blank lines are skipped.
+    pub fn parse(content: &str) -> CLIERPResult<Self> {
+        let mut records = parse_csv(content.trim_start_matches('\u{feff}')).into_iter();
+        let headers: Vec<String> = records
+            .next()
+            .ok_or_else(|| CLIERPError::Validation("CSV file is empty".to_string()))?
+            .into_iter()
+            .map(|h| h.trim().to_lowercase())
+            .collect();
+
+        Ok(Self {
+            headers,
+            rows: records.collect(),
+        })
+    }
+
+    /// Fail unless every listed column is present
+    pub fn require_columns(&self, columns: &[&str]) -> CLIERPResult<()> {
+        let missing: Vec<&str> = columns
+            .iter()
+            .filter(|c| !self.headers.iter().any(|h| h == *c))
+            .copied()
+            .collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(CLIERPError::Validation(format!(
+                "CSV is missing required column(s): {}",
+                missing.join(", ")
+            )))
+        }
+    }
+
+    /// Trimmed value of `column` in `row`; empty cells and unknown columns are `None`
+    pub fn get<'a>(&self, row: &'a [String], column: &str) -> Option<&'a str> {
+        let index = self.headers.iter().position(|h| h == column)?;
+        row.get(index).map(|v| v.trim()).filter(|v| !v.is_empty())
+    }
+}
+
+/// Split CSV text into records, honouring quoted fields with embedded
+/// commas, doubled quotes and line breaks (the inverse of `escape_csv_value`)
+pub fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|f| !f.is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                } else {
+                    record.clear();
+                }
+            }
+            _ => field.push(c),
+        }
+    }
+
+    record.push(field);
+    if record.iter().any(|f| !f.is_empty()) {
+        records.push(record);
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_quoted_fields() {
+        let records = parse_csv("sku,name\r\nA-1,\"Pen, blue\"\n\nA-2,\"12\"\" ruler\nwooden\"\n");
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1], vec!["A-1", "Pen, blue"]);
+        assert_eq!(records[2], vec!["A-2", "12\" ruler\nwooden"]);
+    }
+
+    #[test]
+    fn test_plan_batch() {
+        let existing: HashSet<String> = ["A-1".to_string()].into_iter().collect();
+        let keys = ["A-1", "A-2", "A-2"];
+
+        assert_eq!(
+            plan_batch("SKU", &keys, &existing, ConflictStrategy::Skip).unwrap(),
+            vec![BatchAction::Skip, BatchAction::Insert, BatchAction::Skip]
+        );
+        assert_eq!(
+            plan_batch("SKU", &keys, &existing, ConflictStrategy::Update).unwrap(),
+            vec![BatchAction::Update, BatchAction::Insert, BatchAction::Update]
+        );
+        assert!(plan_batch("SKU", &keys, &existing, ConflictStrategy::Fail).is_err());
+        assert!(plan_batch("SKU", &["A-2", "A-2"], &HashSet::new(), ConflictStrategy::Fail).is_err());
+        assert_eq!(
+            plan_batch("SKU", &["A-2", "A-3"], &existing, ConflictStrategy::Fail).unwrap(),
+            vec![BatchAction::Insert, BatchAction::Insert]
+        );
+    }
+
+    #[test]
+    fn test_table_lookup() {
+        let table = CsvTable::parse("\u{feff}SKU, Name ,price\nA-1,Pen,\n").unwrap();
+        assert_eq!(table.headers, vec!["sku", "name", "price"]);
+        assert!(table.require_columns(&["sku", "name"]).is_ok());
+        assert!(table.require_columns(&["category_id"]).is_err());
+
+        let row = &table.rows[0];
+        assert_eq!(table.get(row, "name"), Some("Pen"));
+        assert_eq!(table.get(row, "price"), None);
+        assert_eq!(table.get(row, "unit"), None);
+    }
+}
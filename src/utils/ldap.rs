@@ -0,0 +1,224 @@
+//! Login against an LDAP directory or Active Directory. Uses the system
+//! `ldapsearch` client (OpenLDAP tools), so ldaps:// and StartTLS follow the
+//! system's TLS setup; the password is passed on stdin, never on the
+//! command line.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::core::config::LdapConfig;
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::utils::html::base64_decode;
+
+/// `ldapsearch` exit status for a bind with the wrong password
+const INVALID_CREDENTIALS: i32 = 49;
+
+/// Directory entry of a user whose bind succeeded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryUser {
+    pub dn: String,
+    pub email: Option<String>,
+    /// Values of the group attribute, usually group DNs
+    pub groups: Vec<String>,
+}
+
+/// What the directory said about a login
+#[derive(Debug)]
+pub enum DirectoryLogin {
+    Accepted(DirectoryUser),
+    /// Wrong password, or no such user in the directory
+    Rejected,
+    /// The directory could not be asked; callers fall back to local users
+    Unavailable(String),
+}
+
+pub struct LdapDirectory<'a> {
+    config: &'a LdapConfig,
+}
+
+impl<'a> LdapDirectory<'a> {
+    pub fn new(config: &'a LdapConfig) -> Self {
+        Self { config }
+    }
+
+    /// Bind as the user and read their entry
+    pub fn login(&self, username: &str, password: &str) -> DirectoryLogin {
+        if username.is_empty() || username.chars().any(char::is_control) || password.is_empty() {
+            // An empty password would be an anonymous bind that always succeeds
+            return DirectoryLogin::Rejected;
+        }
+        let Some(url) = &self.config.url else {
+            return DirectoryLogin::Unavailable("ldap.url is not set".to_string());
+        };
+
+        let bind_dn = self.config.bind_dn.replace("{username}", &escape_dn_value(username));
+        let filter = self.config.user_filter.replace("{username}", &escape_filter_value(username));
+        let mut command = Command::new("ldapsearch");
+        command
+            .args(["-x", "-LLL", "-o", "ldif-wrap=no"])
+            .arg("-o")
+            .arg(format!("nettimeout={}", self.config.timeout))
+            .args(["-H", url, "-D", &bind_dn, "-y", "/dev/stdin", "-b", &self.config.search_base, "-s", "sub"])
+            .arg(&filter)
+            .args([&self.config.email_attribute, &self.config.group_attribute]);
+        if self.config.start_tls {
+            command.arg("-ZZ");
+        }
+
+        let output = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                if let Some(mut stdin) = child.stdin.take() {
+                    stdin.write_all(password.as_bytes())?;
+                }
+                child.wait_with_output()
+            });
+        let output = match output {
+            Ok(output) => output,
+            Err(e) => return DirectoryLogin::Unavailable(format!("Could not run ldapsearch: {}", e)),
+        };
+
+        match output.status.code() {
+            Some(0) => match parse_entry(&String::from_utf8_lossy(&output.stdout), &self.config.email_attribute, &self.config.group_attribute) {
+                Some(user) => DirectoryLogin::Accepted(user),
+                // The bind worked but the filter does not find the user
+                None => DirectoryLogin::Rejected,
+            },
+            Some(INVALID_CREDENTIALS) => DirectoryLogin::Rejected,
+            _ => DirectoryLogin::Unavailable(format!(
+                "ldapsearch against {} failed: {}",
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
+        }
+    }
+
+    /// Check the directory answers, binding as nobody
+    pub fn ping(&self) -> CLIERPResult<()> {
+        let url = self.config.url.as_ref().ok_or_else(|| {
+            CLIERPError::Configuration(config::ConfigError::Message("ldap.url is not set".to_string()))
+        })?;
+        let output = Command::new("ldapsearch")
+            .args(["-x", "-LLL", "-o"])
+            .arg(format!("nettimeout={}", self.config.timeout))
+            .args(["-H", url, "-s", "base", "-b", "", "namingContexts"])
+            .output()
+            .map_err(|e| CLIERPError::IoError(format!("Could not run ldapsearch: {}", e)))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(CLIERPError::IoError(format!(
+                "{} did not answer: {}",
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    }
+}
+
+/// First entry of unwrapped LDIF output with the two attributes read;
+/// attribute names compare without case
+pub fn parse_entry(ldif: &str, email_attribute: &str, group_attribute: &str) -> Option<DirectoryUser> {
+    let mut user: Option<DirectoryUser> = None;
+    for line in ldif.lines() {
+        if line.is_empty() {
+            if user.is_some() {
+                break;
+            }
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = match value.strip_prefix(':') {
+            Some(encoded) => match base64_decode(encoded.trim()) {
+                Some(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+                None => continue,
+            },
+            None => value.trim().to_string(),
+        };
+
+        if name.eq_ignore_ascii_case("dn") {
+            user = Some(DirectoryUser {
+                dn: value,
+                email: None,
+                groups: Vec::new(),
+            });
+        } else if let Some(user) = user.as_mut() {
+            if name.eq_ignore_ascii_case(email_attribute) && user.email.is_none() {
+                user.email = Some(value);
+            } else if name.eq_ignore_ascii_case(group_attribute) {
+                user.groups.push(value);
+            }
+        }
+    }
+    user
+}
+
+/// Escape a value placed in a DN (RFC 4514)
+pub fn escape_dn_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for (i, c) in value.chars().enumerate() {
+        match c {
+            ',' | '+' | '"' | '\\' | '<' | '>' | ';' | '=' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '#' if i == 0 => escaped.push_str("\\#"),
+            ' ' if i == 0 || i == value.chars().count() - 1 => escaped.push_str("\\ "),
+            '\0' => escaped.push_str("\\00"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escape a value placed in a search filter (RFC 4515)
+pub fn escape_filter_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '*' => escaped.push_str("\\2a"),
+            '(' => escaped.push_str("\\28"),
+            ')' => escaped.push_str("\\29"),
+            '\\' => escaped.push_str("\\5c"),
+            '\0' => escaped.push_str("\\00"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_entry_and_escaping() {
+        let ldif = "dn: uid=kim,ou=people,dc=example,dc=com\n\
+                    mail: kim@example.com\n\
+                    memberOf: cn=erp-managers,ou=groups,dc=example,dc=com\n\
+                    memberof:: Y249ZXJwLeq0gOumrOyekCxkYz1leGFtcGxlLGRjPWNvbQ==\n\
+                    \n\
+                    # refldap://other.example.com\n";
+        let user = parse_entry(ldif, "mail", "memberOf").unwrap();
+        assert_eq!(user.dn, "uid=kim,ou=people,dc=example,dc=com");
+        assert_eq!(user.email.as_deref(), Some("kim@example.com"));
+        assert_eq!(
+            user.groups,
+            vec!["cn=erp-managers,ou=groups,dc=example,dc=com", "cn=erp-관리자,dc=example,dc=com"]
+        );
+        assert!(parse_entry("", "mail", "memberOf").is_none());
+
+        assert_eq!(escape_dn_value("kim, jr"), "kim\\, jr");
+        assert_eq!(escape_dn_value("#admin "), "\\#admin\\ ");
+        assert_eq!(escape_filter_value("*)(uid=*"), "\\2a\\29\\28uid=\\2a");
+    }
+}
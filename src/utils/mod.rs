@@ -1,8 +1,25 @@
+pub mod anomaly;
+pub mod cache;
 pub mod crypto;
+pub mod currency;
+pub mod dates;
 pub mod export;
+pub mod filter_expr;
 pub mod filters;
+pub mod fiscal;
 pub mod formatting;
+pub mod formula;
+pub mod html;
+pub mod i18n;
+pub mod import;
+pub mod ldap;
 pub mod pagination;
+pub mod parquet;
+pub mod pdf;
+pub mod progress;
+pub mod table;
+pub mod timezone;
+pub mod transfer;
 pub mod validation;
 
 pub use filters::*;
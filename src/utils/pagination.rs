@@ -1,9 +1,11 @@
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 use diesel::prelude::*;
 use diesel::query_dsl::LoadQuery;
-use diesel::sqlite::Sqlite;
 use crate::core::result::CLIERPResult;
 
+static DEFAULT_PER_PAGE: OnceCell<i64> = OnceCell::new();
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaginationParams {
     pub page: Option<i64>,
@@ -14,7 +16,7 @@ impl Default for PaginationParams {
     fn default() -> Self {
         Self {
             page: Some(1),
-            per_page: Some(50),
+            per_page: Some(Self::default_per_page(50)),
         }
     }
 }
@@ -27,6 +29,17 @@ impl PaginationParams {
         }
     }
 
+    /// Install the logged-in user's page size for the rest of the process
+    pub fn set_default_per_page(per_page: i64) {
+        let _ = DEFAULT_PER_PAGE.set(per_page);
+    }
+
+    /// Page size for a list run without `--per-page`: the user's preference,
+    /// else the command's own `fallback`
+    pub fn default_per_page(fallback: i64) -> i64 {
+        DEFAULT_PER_PAGE.get().copied().unwrap_or(fallback)
+    }
+
     pub fn new_with_options(page: Option<i64>, per_page: Option<i64>) -> Self {
         Self { page, per_page }
     }
@@ -36,7 +49,7 @@ impl PaginationParams {
     }
 
     pub fn per_page(&self) -> i64 {
-        self.per_page.unwrap_or(50).max(1).min(1000)
+        self.per_page.unwrap_or_else(|| Self::default_per_page(50)).clamp(1, 1000)
     }
 
     pub fn offset(&self) -> i64 {
@@ -0,0 +1,338 @@
+//! Minimal Parquet writer for the warehouse export.
+//!
+//! A file holds one row group with one uncompressed, PLAIN-encoded data page
+//! per column, and every column is optional. There are no statistics,
+//! dictionaries or compression; DuckDB, Spark and pandas read such files
+//! as is. Metadata is written in the Thrift compact protocol.
+
+/// Values of one column; `None` is null
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParquetValues {
+    Int32(Vec<Option<i32>>),
+    Int64(Vec<Option<i64>>),
+    Double(Vec<Option<f64>>),
+    Text(Vec<Option<String>>),
+    /// Days since 1970-01-01
+    Date(Vec<Option<i32>>),
+    /// Milliseconds since 1970-01-01 00:00 UTC
+    Timestamp(Vec<Option<i64>>),
+}
+
+impl ParquetValues {
+    fn len(&self) -> usize {
+        match self {
+            ParquetValues::Int32(v) | ParquetValues::Date(v) => v.len(),
+            ParquetValues::Int64(v) | ParquetValues::Timestamp(v) => v.len(),
+            ParquetValues::Double(v) => v.len(),
+            ParquetValues::Text(v) => v.len(),
+        }
+    }
+
+    /// Physical type and converted type
+    fn types(&self) -> (i32, Option<i32>) {
+        match self {
+            ParquetValues::Int32(_) => (TYPE_INT32, None),
+            ParquetValues::Int64(_) => (TYPE_INT64, None),
+            ParquetValues::Double(_) => (TYPE_DOUBLE, None),
+            ParquetValues::Text(_) => (TYPE_BYTE_ARRAY, Some(CONVERTED_UTF8)),
+            ParquetValues::Date(_) => (TYPE_INT32, Some(CONVERTED_DATE)),
+            ParquetValues::Timestamp(_) => (TYPE_INT64, Some(CONVERTED_TIMESTAMP_MILLIS)),
+        }
+    }
+
+    /// Whether each row has a value, and the PLAIN encoding of those values
+    fn encode(&self) -> (Vec<bool>, Vec<u8>) {
+        fn plain<T>(values: &[Option<T>], mut write: impl FnMut(&mut Vec<u8>, &T)) -> (Vec<bool>, Vec<u8>) {
+            let mut data = Vec::new();
+            let present = values
+                .iter()
+                .map(|value| {
+                    if let Some(value) = value {
+                        write(&mut data, value);
+                    }
+                    value.is_some()
+                })
+                .collect();
+            (present, data)
+        }
+        match self {
+            ParquetValues::Int32(v) | ParquetValues::Date(v) => plain(v, |out, x| out.extend(x.to_le_bytes())),
+            ParquetValues::Int64(v) | ParquetValues::Timestamp(v) => plain(v, |out, x| out.extend(x.to_le_bytes())),
+            ParquetValues::Double(v) => plain(v, |out, x| out.extend(x.to_le_bytes())),
+            ParquetValues::Text(v) => plain(v, |out, x| {
+                out.extend((x.len() as u32).to_le_bytes());
+                out.extend(x.as_bytes());
+            }),
+        }
+    }
+}
+
+const MAGIC: &[u8] = b"PAR1";
+
+const TYPE_INT32: i32 = 1;
+const TYPE_INT64: i32 = 2;
+const TYPE_DOUBLE: i32 = 5;
+const TYPE_BYTE_ARRAY: i32 = 6;
+const CONVERTED_UTF8: i32 = 0;
+const CONVERTED_DATE: i32 = 6;
+const CONVERTED_TIMESTAMP_MILLIS: i32 = 9;
+const REPETITION_OPTIONAL: i32 = 1;
+const ENCODING_PLAIN: i32 = 0;
+const ENCODING_RLE: i32 = 3;
+const CODEC_UNCOMPRESSED: i32 = 0;
+const PAGE_DATA: i32 = 0;
+
+/// Columns of equal length, written as one Parquet file
+#[derive(Debug, Clone, Default)]
+pub struct ParquetTable {
+    columns: Vec<(String, ParquetValues)>,
+}
+
+impl ParquetTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a column; it must have as many values as the columns before it
+    pub fn column(mut self, name: &str, values: ParquetValues) -> Self {
+        if let Some((_, first)) = self.columns.first() {
+            assert_eq!(first.len(), values.len(), "column {} has a different number of rows", name);
+        }
+        self.columns.push((name.to_string(), values));
+        self
+    }
+
+    pub fn rows(&self) -> usize {
+        self.columns.first().map(|(_, values)| values.len()).unwrap_or(0)
+    }
+
+    /// The table as the bytes of a Parquet file
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let rows = self.rows();
+        let mut file = MAGIC.to_vec();
+        // Offset and size of each column chunk
+        let mut chunks = Vec::new();
+        for (_, values) in &self.columns {
+            let (present, data) = values.encode();
+            let mut page = definition_levels(&present);
+            page.extend(data);
+
+            let mut header = Thrift::new();
+            header.i32(1, PAGE_DATA);
+            header.i32(2, page.len() as i32);
+            header.i32(3, page.len() as i32);
+            header.begin_struct(5);
+            header.i32(1, rows as i32);
+            header.i32(2, ENCODING_PLAIN);
+            header.i32(3, ENCODING_RLE);
+            header.i32(4, ENCODING_RLE);
+            header.end_struct();
+            header.stop();
+
+            let offset = file.len() as i64;
+            file.extend(&header.out);
+            file.extend(&page);
+            chunks.push((offset, (header.out.len() + page.len()) as i64));
+        }
+
+        let mut meta = Thrift::new();
+        meta.i32(1, 1);
+        meta.list(2, THRIFT_STRUCT, self.columns.len() + 1);
+        meta.begin_element();
+        meta.binary(4, b"schema");
+        meta.i32(5, self.columns.len() as i32);
+        meta.end_struct();
+        for (name, values) in &self.columns {
+            let (physical, converted) = values.types();
+            meta.begin_element();
+            meta.i32(1, physical);
+            meta.i32(3, REPETITION_OPTIONAL);
+            meta.binary(4, name.as_bytes());
+            if let Some(converted) = converted {
+                meta.i32(6, converted);
+            }
+            meta.end_struct();
+        }
+        meta.i64(3, rows as i64);
+        meta.list(4, THRIFT_STRUCT, 1);
+        meta.begin_element();
+        meta.list(1, THRIFT_STRUCT, self.columns.len());
+        for ((name, values), (offset, size)) in self.columns.iter().zip(&chunks) {
+            meta.begin_element();
+            meta.i64(2, *offset);
+            meta.begin_struct(3);
+            meta.i32(1, values.types().0);
+            meta.list(2, THRIFT_I32, 2);
+            meta.list_i32(ENCODING_PLAIN);
+            meta.list_i32(ENCODING_RLE);
+            meta.list(3, THRIFT_BINARY, 1);
+            meta.list_binary(name.as_bytes());
+            meta.i32(4, CODEC_UNCOMPRESSED);
+            meta.i64(5, rows as i64);
+            meta.i64(6, *size);
+            meta.i64(7, *size);
+            meta.i64(9, *offset);
+            meta.end_struct();
+            meta.end_struct();
+        }
+        meta.i64(2, chunks.iter().map(|(_, size)| size).sum());
+        meta.i64(3, rows as i64);
+        meta.end_struct();
+        meta.binary(6, b"clierp");
+        meta.stop();
+
+        file.extend(&meta.out);
+        file.extend((meta.out.len() as u32).to_le_bytes());
+        file.extend(MAGIC);
+        file
+    }
+}
+
+/// Definition levels of an optional column (1 for a value, 0 for null) as a
+/// length-prefixed, bit-packed RLE/bit-packing hybrid run
+fn definition_levels(present: &[bool]) -> Vec<u8> {
+    let groups = present.len().div_ceil(8);
+    let mut encoded = Vec::new();
+    write_varint(&mut encoded, ((groups as u64) << 1) | 1);
+    for group in present.chunks(8) {
+        encoded.push(group.iter().enumerate().fold(0u8, |byte, (i, set)| byte | (u8::from(*set) << i)));
+    }
+    let mut levels = (encoded.len() as u32).to_le_bytes().to_vec();
+    levels.extend(encoded);
+    levels
+}
+
+const THRIFT_I32: u8 = 5;
+const THRIFT_I64: u8 = 6;
+const THRIFT_BINARY: u8 = 8;
+const THRIFT_LIST: u8 = 9;
+const THRIFT_STRUCT: u8 = 12;
+
+/// Thrift compact protocol encoder
+struct Thrift {
+    out: Vec<u8>,
+    /// Last field ID written in each open struct
+    last_field: Vec<i16>,
+}
+
+impl Thrift {
+    fn new() -> Self {
+        Self { out: Vec::new(), last_field: vec![0] }
+    }
+
+    fn field(&mut self, id: i16, kind: u8) {
+        let last = self.last_field.last_mut().expect("field outside a struct");
+        let delta = id - *last;
+        if (1..=15).contains(&delta) {
+            self.out.push(((delta as u8) << 4) | kind);
+        } else {
+            self.out.push(kind);
+            write_varint(&mut self.out, zigzag(id as i64));
+        }
+        *last = id;
+    }
+
+    fn i32(&mut self, id: i16, value: i32) {
+        self.field(id, THRIFT_I32);
+        write_varint(&mut self.out, zigzag(value as i64));
+    }
+
+    fn i64(&mut self, id: i16, value: i64) {
+        self.field(id, THRIFT_I64);
+        write_varint(&mut self.out, zigzag(value));
+    }
+
+    fn binary(&mut self, id: i16, value: &[u8]) {
+        self.field(id, THRIFT_BINARY);
+        self.list_binary(value);
+    }
+
+    fn begin_struct(&mut self, id: i16) {
+        self.field(id, THRIFT_STRUCT);
+        self.last_field.push(0);
+    }
+
+    /// Start a struct that is a list element, which has no field header
+    fn begin_element(&mut self) {
+        self.last_field.push(0);
+    }
+
+    fn end_struct(&mut self) {
+        self.out.push(0);
+        self.last_field.pop();
+    }
+
+    /// End of the top-level struct
+    fn stop(&mut self) {
+        self.out.push(0);
+    }
+
+    fn list(&mut self, id: i16, element: u8, len: usize) {
+        self.field(id, THRIFT_LIST);
+        if len < 15 {
+            self.out.push(((len as u8) << 4) | element);
+        } else {
+            self.out.push(0xf0 | element);
+            write_varint(&mut self.out, len as u64);
+        }
+    }
+
+    fn list_i32(&mut self, value: i32) {
+        write_varint(&mut self.out, zigzag(value as i64));
+    }
+
+    fn list_binary(&mut self, value: &[u8]) {
+        write_varint(&mut self.out, value.len() as u64);
+        self.out.extend(value);
+    }
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parquet_layout() {
+        assert_eq!(zigzag(0), 0);
+        assert_eq!(zigzag(-1), 1);
+        assert_eq!(zigzag(1), 2);
+        let mut varint = Vec::new();
+        write_varint(&mut varint, 300);
+        assert_eq!(varint, [0xac, 0x02]);
+
+        // 9 rows, the second and ninth null: two bit-packed groups
+        let present = [true, false, true, true, true, true, true, true, false];
+        assert_eq!(definition_levels(&present), [3, 0, 0, 0, 0x05, 0xfd, 0x00]);
+
+        let mut thrift = Thrift::new();
+        thrift.i32(1, 7);
+        thrift.i64(20, -2);
+        thrift.stop();
+        assert_eq!(thrift.out, [0x15, 0x0e, 0x06, 0x28, 0x03, 0x00]);
+
+        let table = ParquetTable::new()
+            .column("id", ParquetValues::Int32(vec![Some(1), Some(2)]))
+            .column("name", ParquetValues::Text(vec![Some("a".to_string()), None]));
+        assert_eq!(table.rows(), 2);
+        let bytes = table.to_bytes();
+        assert_eq!(&bytes[..4], MAGIC);
+        assert_eq!(&bytes[bytes.len() - 4..], MAGIC);
+        let footer = u32::from_le_bytes(bytes[bytes.len() - 8..bytes.len() - 4].try_into().unwrap()) as usize;
+        assert!(footer < bytes.len() - 12);
+        // The first column chunk starts right after the magic with a data page header
+        assert_eq!(bytes[4], 0x15);
+        assert_eq!(bytes[5], 0x00);
+    }
+}
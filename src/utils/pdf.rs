@@ -0,0 +1,279 @@
+//! Minimal PDF writer for printable output.
+//!
+//! Pages are drawn with absolute coordinates in points (1/72 inch, origin
+//! at the bottom left) using the built-in Helvetica fonts, so no font files
+//! are embedded. Those fonts only cover Latin-1; other characters are
+//! printed as `?`, and the HTML outputs should be used for such text.
+//! Images must be JPEG, which PDF embeds as is.
+
+use std::fmt::Write as _;
+
+pub const A4_WIDTH: f32 = 595.0;
+pub const A4_HEIGHT: f32 = 842.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PdfFont {
+    Regular,
+    Bold,
+}
+
+impl PdfFont {
+    fn resource(&self) -> &'static str {
+        match self {
+            PdfFont::Regular => "F1",
+            PdfFont::Bold => "F2",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PdfImage {
+    width: u32,
+    height: u32,
+    jpeg: Vec<u8>,
+}
+
+/// Drawing operations of one page
+#[derive(Debug, Clone, Default)]
+pub struct PdfPage {
+    content: Vec<u8>,
+}
+
+impl PdfPage {
+    pub fn text(&mut self, x: f32, y: f32, size: f32, font: PdfFont, text: &str) {
+        let mut op = format!("BT /{} {:.1} Tf {:.2} {:.2} Td (", font.resource(), size, x, y).into_bytes();
+        op.extend(encode_text(text));
+        op.extend_from_slice(b") Tj ET\n");
+        self.content.extend(op);
+    }
+
+    /// Draw image `image` (from [`PdfDocument::add_jpeg`]) scaled into the box
+    pub fn image(&mut self, image: usize, x: f32, y: f32, width: f32, height: f32) {
+        let _ = writeln!(self, "q {:.2} 0 0 {:.2} {:.2} {:.2} cm /Im{} Do Q", width, height, x, y, image);
+    }
+
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        let _ = writeln!(self, "0.5 w {:.2} {:.2} m {:.2} {:.2} l S", x1, y1, x2, y2);
+    }
+
+    /// Outline of a rectangle whose bottom left corner is `(x, y)`
+    pub fn rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        let _ = writeln!(self, "0.5 w {:.2} {:.2} {:.2} {:.2} re S", x, y, width, height);
+    }
+}
+
+impl std::fmt::Write for PdfPage {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.content.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PdfDocument {
+    pages: Vec<PdfPage>,
+    images: Vec<PdfImage>,
+}
+
+impl PdfDocument {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a JPEG for use on any page, returning its index
+    pub fn add_jpeg(&mut self, jpeg: Vec<u8>, width: u32, height: u32) -> usize {
+        self.images.push(PdfImage { width, height, jpeg });
+        self.images.len() - 1
+    }
+
+    /// Start an A4 page and return it for drawing
+    pub fn new_page(&mut self) -> &mut PdfPage {
+        self.pages.push(PdfPage::default());
+        self.pages.last_mut().expect("page just added")
+    }
+
+    /// Page most recently started, to keep drawing on
+    pub fn last_page(&mut self) -> Option<&mut PdfPage> {
+        self.pages.last_mut()
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // 1 catalog, 2 page tree, 3-4 fonts, then the images, then a page
+        // and its content stream per page
+        let first_image = 5;
+        let first_page = first_image + self.images.len();
+        let page_ids: Vec<usize> = (0..self.pages.len()).map(|i| first_page + i * 2).collect();
+
+        let mut out = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+        let mut object = |out: &mut Vec<u8>, body: &[u8]| {
+            offsets.push(out.len());
+            out.extend(format!("{} 0 obj\n", offsets.len()).into_bytes());
+            out.extend_from_slice(body);
+            out.extend_from_slice(b"\nendobj\n");
+        };
+
+        object(&mut out, b"<< /Type /Catalog /Pages 2 0 R >>");
+        let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+        object(
+            &mut out,
+            format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), page_ids.len()).as_bytes(),
+        );
+        object(
+            &mut out,
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>",
+        );
+        object(
+            &mut out,
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>",
+        );
+        for image in &self.images {
+            let mut body = format!(
+                "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB \
+                 /BitsPerComponent 8 /Filter /DCTDecode /Length {} >>\nstream\n",
+                image.width,
+                image.height,
+                image.jpeg.len()
+            )
+            .into_bytes();
+            body.extend_from_slice(&image.jpeg);
+            body.extend_from_slice(b"\nendstream");
+            object(&mut out, &body);
+        }
+
+        let xobjects: String = (0..self.images.len())
+            .map(|i| format!("/Im{} {} 0 R ", i, first_image + i))
+            .collect();
+        for (page, id) in self.pages.iter().zip(&page_ids) {
+            object(
+                &mut out,
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                     /Resources << /Font << /F1 3 0 R /F2 4 0 R >> /XObject << {}>> >> /Contents {} 0 R >>",
+                    A4_WIDTH,
+                    A4_HEIGHT,
+                    xobjects,
+                    id + 1
+                )
+                .as_bytes(),
+            );
+            let mut body = format!("<< /Length {} >>\nstream\n", page.content.len()).into_bytes();
+            body.extend_from_slice(&page.content);
+            body.extend_from_slice(b"endstream");
+            object(&mut out, &body);
+        }
+
+        let xref = out.len();
+        let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1);
+        for offset in &offsets {
+            let _ = writeln!(trailer, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            trailer,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            offsets.len() + 1,
+            xref
+        );
+        out.extend(trailer.into_bytes());
+        out
+    }
+}
+
+/// Approximate width of `text` in points; Helvetica averages about half
+/// an em per character
+pub fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().count() as f32 * size * 0.5
+}
+
+/// Split `text` into lines no wider than `max_width`, breaking at spaces
+pub fn wrap_text(text: &str, size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+        if text_width(&candidate, size) > max_width && !line.is_empty() {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        } else {
+            line = candidate;
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// WinAnsi bytes of `text` for a literal string, escaped
+fn encode_text(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        let latin1 = [c as u32 as u8];
+        let encoded: &[u8] = match c {
+            '€' => &[0x80],
+            '…' => &[0x85],
+            '‘' => &[0x91],
+            '’' => &[0x92],
+            '“' => &[0x93],
+            '”' => &[0x94],
+            '•' => &[0x95],
+            '–' => &[0x96],
+            '—' => &[0x97],
+            '™' => &[0x99],
+            // The won sign is not in WinAnsi; prices still need a currency
+            '₩' => b"KRW ",
+            '\u{20}'..='\u{7e}' | '\u{a0}'..='\u{ff}' => &latin1,
+            _ => b"?",
+        };
+        for &byte in encoded {
+            match byte {
+                b'(' | b')' | b'\\' => bytes.extend_from_slice(&[b'\\', byte]),
+                0x80..=0xff => bytes.extend(format!("\\{:03o}", byte).into_bytes()),
+                _ => bytes.push(byte),
+            }
+        }
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_text() {
+        assert_eq!(encode_text("a (b) \\"), b"a \\(b\\) \\\\".to_vec());
+        assert_eq!(encode_text("é€한"), b"\\351\\200?".to_vec());
+        assert_eq!(encode_text("₩1,000"), b"KRW 1,000".to_vec());
+        assert_eq!(wrap_text("one two three four", 10.0, 40.0), vec!["one two", "three", "four"]);
+    }
+
+    #[test]
+    fn test_document_xref_offsets() {
+        let mut doc = PdfDocument::new();
+        doc.new_page().text(50.0, 800.0, 12.0, PdfFont::Bold, "Catalog");
+        doc.new_page().line(0.0, 0.0, 10.0, 10.0);
+        let bytes = doc.to_bytes();
+        let text = String::from_utf8_lossy(&bytes).to_string();
+
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("/Count 2"));
+
+        let startxref: usize = text.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(text[startxref..].starts_with("xref"));
+        let offsets: Vec<usize> = text[startxref..]
+            .lines()
+            .skip(3)
+            .take_while(|l| l.ends_with(" n "))
+            .map(|l| l[..10].parse().unwrap())
+            .collect();
+        assert_eq!(offsets.len(), 8);
+        for (i, offset) in offsets.iter().enumerate() {
+            assert!(text[*offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+}
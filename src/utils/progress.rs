@@ -0,0 +1,16 @@
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Progress bar on stderr for batch work over `total` items.
+///
+/// indicatif only draws when stderr is a terminal, so piped and scripted
+/// runs see no progress output.
+pub fn progress_bar(total: u64, message: &str) -> ProgressBar {
+    let bar = ProgressBar::new(total);
+    bar.set_style(
+        ProgressStyle::with_template("{msg} [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .progress_chars("=> "),
+    );
+    bar.set_message(message.to_string());
+    bar
+}
@@ -0,0 +1,381 @@
+use std::cmp::Ordering;
+use std::io::IsTerminal;
+
+use clap::Args;
+use tabled::{
+    builder::Builder,
+    grid::util::string::string_width,
+    settings::{object::Columns, Modify, Width},
+    Tabled,
+};
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+use crate::utils::formatting::is_plain;
+
+/// Narrowest a column is cut to before columns are dropped instead
+const MIN_COLUMN_WIDTH: usize = 8;
+
+/// `--columns`, `--sort` and `--vertical` of the list commands
+#[derive(Debug, Clone, Default, Args)]
+pub struct TableArgs {
+    /// Columns to show, in order (e.g. sku,name,stock)
+    #[arg(long, value_delimiter = ',')]
+    pub columns: Vec<String>,
+    /// Sort the rows shown by a column, optionally :asc or :desc (e.g. stock:desc)
+    #[arg(long, value_parser = parse_column_sort_arg)]
+    pub sort: Option<ColumnSort>,
+    /// Show each row as a block of "Column | value" lines
+    #[arg(long)]
+    pub vertical: bool,
+}
+
+/// A column to sort on and its direction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnSort {
+    pub column: String,
+    pub descending: bool,
+}
+
+impl std::str::FromStr for ColumnSort {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let (column, direction) = match value.rsplit_once(':') {
+            Some((column, direction)) => (column, Some(direction)),
+            None => (value, None),
+        };
+        let descending = match direction.map(|d| d.trim().to_lowercase()).as_deref() {
+            None | Some("asc") => false,
+            Some("desc") => true,
+            Some(other) => return Err(format!("sort direction must be asc or desc, got '{}'", other)),
+        };
+        let column = column.trim();
+        if column.is_empty() {
+            return Err("sort needs a column, e.g. stock:desc".to_string());
+        }
+        Ok(Self { column: column.to_string(), descending })
+    }
+}
+
+/// Clap value parser for `column[:asc|:desc]` arguments
+pub fn parse_column_sort_arg(value: &str) -> std::result::Result<ColumnSort, String> {
+    value.parse()
+}
+
+/// Rows of a list command, rendered once for every module.
+///
+/// Columns are named by their header; `--columns` and `--sort` accept the
+/// header in any case, with spaces or underscores, or the header's first
+/// word (`stock` for "Current Stock" when no other column starts with it).
+/// Sorting compares cells as numbers when both read as one, ignoring
+/// currency signs and thousands separators, and as text otherwise.
+///
+/// On a terminal the table is fitted to its width: the widest columns are
+/// cut first, and when every column is down to `MIN_COLUMN_WIDTH` the
+/// rightmost ones are left out, so the leading identifying columns always
+/// show. Piped output is never cut.
+#[derive(Debug, Clone, Default)]
+pub struct TableView {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub vertical: bool,
+}
+
+impl TableView {
+    pub fn new(headers: &[&str]) -> Self {
+        Self {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+            vertical: false,
+        }
+    }
+
+    pub fn from_tabled<T: Tabled>(records: &[T]) -> Self {
+        Self {
+            headers: T::headers().into_iter().map(|h| h.into_owned()).collect(),
+            rows: records
+                .iter()
+                .map(|record| record.fields().into_iter().map(|f| f.into_owned()).collect())
+                .collect(),
+            vertical: false,
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Sort and pick columns as `args` asks; sorting may use a column that
+    /// is not shown
+    pub fn apply(&mut self, args: &TableArgs) -> CLIERPResult<()> {
+        if let Some(sort) = &args.sort {
+            let index = self.column_index(&sort.column)?;
+            self.rows.sort_by(|a, b| {
+                let order = compare_cells(&a[index], &b[index]);
+                if sort.descending { order.reverse() } else { order }
+            });
+        }
+
+        if !args.columns.is_empty() {
+            let indexes = args
+                .columns
+                .iter()
+                .map(|name| self.column_index(name))
+                .collect::<CLIERPResult<Vec<_>>>()?;
+            self.headers = indexes.iter().map(|&i| self.headers[i].clone()).collect();
+            self.rows = self
+                .rows
+                .iter()
+                .map(|row| indexes.iter().map(|&i| row[i].clone()).collect())
+                .collect();
+        }
+        self.vertical = args.vertical;
+        Ok(())
+    }
+
+    /// The table as text, fitted to the terminal when stdout is one
+    pub fn render(&self) -> String {
+        let width = std::io::stdout()
+            .is_terminal()
+            .then(|| crossterm::terminal::size().ok())
+            .flatten()
+            .map(|(columns, _)| columns as usize);
+        self.render_within(width)
+    }
+
+    /// The table as text, at most `width` characters wide when given
+    pub fn render_within(&self, width: Option<usize>) -> String {
+        if self.vertical {
+            return self.render_vertical();
+        }
+
+        let natural: Vec<usize> = (0..self.headers.len())
+            .map(|i| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.get(i))
+                    .chain(std::iter::once(&self.headers[i]))
+                    .map(|cell| string_width(cell))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let widths = match width {
+            Some(width) => fit_columns(&natural, width),
+            None => natural.clone(),
+        };
+        let shown = widths.len();
+
+        let mut builder = Builder::default();
+        builder.push_record(self.headers.iter().take(shown));
+        for row in &self.rows {
+            builder.push_record(row.iter().take(shown));
+        }
+        let mut table = builder.build();
+        for (i, (&fitted, &full)) in widths.iter().zip(&natural).enumerate() {
+            if fitted < full {
+                let suffix = if is_plain() { "~" } else { "…" };
+                table.with(Modify::new(Columns::single(i)).with(Width::truncate(fitted).suffix(suffix)));
+            }
+        }
+
+        let mut out = table.to_string();
+        if shown < self.headers.len() {
+            out.push_str(&format!(
+                "\n(not shown: {}; use --columns or --vertical)",
+                self.headers[shown..].join(", ")
+            ));
+        }
+        out
+    }
+
+    /// One block per row, headers on the left
+    fn render_vertical(&self) -> String {
+        let label_width = self.headers.iter().map(|h| string_width(h)).max().unwrap_or(0);
+        let mut out = String::new();
+        for (n, row) in self.rows.iter().enumerate() {
+            if n > 0 {
+                out.push('\n');
+            }
+            out.push_str(&format!("-[ RECORD {} ]{}\n", n + 1, "-".repeat(label_width.max(8))));
+            for (header, cell) in self.headers.iter().zip(row) {
+                let pad = label_width - string_width(header);
+                out.push_str(&format!("{}{} | {}\n", header, " ".repeat(pad), cell));
+            }
+        }
+        out
+    }
+
+    fn column_index(&self, name: &str) -> CLIERPResult<usize> {
+        let wanted = column_key(name);
+        let keys: Vec<String> = self.headers.iter().map(|h| column_key(h)).collect();
+        if let Some(index) = keys.iter().position(|key| *key == wanted) {
+            return Ok(index);
+        }
+
+        let by_word: Vec<usize> = keys
+            .iter()
+            .enumerate()
+            .filter(|(_, key)| key.split('_').any(|word| word == wanted))
+            .map(|(i, _)| i)
+            .collect();
+        match by_word.as_slice() {
+            [index] => Ok(*index),
+            _ => Err(CLIERPError::ValidationError(format!(
+                "Unknown column '{}'; columns are {}",
+                name,
+                keys.join(", ")
+            ))),
+        }
+    }
+}
+
+/// Widths for columns of `natural` width in a table at most `available`
+/// wide; columns past the end of the result are dropped. The first column is
+/// always kept.
+pub fn fit_columns(natural: &[usize], available: usize) -> Vec<usize> {
+    // Each column adds its padding and a border, plus the closing border
+    let table_width = |widths: &[usize]| widths.iter().map(|w| w + 3).sum::<usize>() + 1;
+
+    let mut widths = natural.to_vec();
+    while table_width(&widths) > available {
+        let excess = table_width(&widths) - available;
+        let widest = widths
+            .iter()
+            .enumerate()
+            .filter(|(_, &w)| w > MIN_COLUMN_WIDTH)
+            .max_by_key(|(_, &w)| w)
+            .map(|(i, _)| i);
+        match widest {
+            Some(i) => {
+                let next = widths
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != i)
+                    .map(|(_, &w)| w)
+                    .max()
+                    .unwrap_or(0);
+                let target = widths[i].saturating_sub(excess).max(next).max(MIN_COLUMN_WIDTH);
+                // Ties with the next widest column shrink one at a time
+                widths[i] = if target < widths[i] { target } else { widths[i] - 1 };
+            }
+            None if widths.len() > 1 => {
+                widths.pop();
+            }
+            None => break,
+        }
+    }
+    widths
+}
+
+/// Header as a column name: lower case, words joined by `_`
+fn column_key(header: &str) -> String {
+    header
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Order two cells as numbers when both read as one, as text otherwise
+pub fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (cell_number(a), cell_number(b)) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        _ => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
+
+/// Value of a cell such as `₩1,250`, `-3`, `12.5%` or `40 pcs`
+fn cell_number(cell: &str) -> Option<f64> {
+    let number: String = cell
+        .trim()
+        .trim_start_matches(|c: char| !c.is_ascii_digit() && c != '-' && c != '.')
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || matches!(c, '-' | '.' | ','))
+        .filter(|c| *c != ',')
+        .collect();
+    number.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn products() -> TableView {
+        let mut table = TableView::new(&["SKU", "Name", "Current Stock", "Price"]);
+        table.push(vec!["B-2".into(), "Bolt".into(), "120".into(), "₩1,500".into()]);
+        table.push(vec!["A-1".into(), "anchor".into(), "8".into(), "₩12,000".into()]);
+        table.push(vec!["C-3".into(), "Clamp".into(), "35".into(), "₩900".into()]);
+        table
+    }
+
+    #[test]
+    fn test_column_sort_from_str() {
+        assert_eq!(
+            "stock:desc".parse::<ColumnSort>().unwrap(),
+            ColumnSort { column: "stock".to_string(), descending: true }
+        );
+        assert!(!"name".parse::<ColumnSort>().unwrap().descending);
+        assert!("stock:down".parse::<ColumnSort>().is_err());
+        assert!(":desc".parse::<ColumnSort>().is_err());
+    }
+
+    #[test]
+    fn test_apply_sorts_numbers_and_picks_columns() {
+        let mut table = products();
+        let args = TableArgs {
+            columns: vec!["sku".to_string(), "stock".to_string()],
+            sort: Some("current_stock:desc".parse().unwrap()),
+            ..Default::default()
+        };
+        table.apply(&args).unwrap();
+        assert_eq!(table.headers, vec!["SKU", "Current Stock"]);
+        assert_eq!(table.rows, vec![vec!["B-2", "120"], vec!["C-3", "35"], vec!["A-1", "8"]]);
+
+        // Currency cells sort by amount, text case-insensitively
+        let mut table = products();
+        table.apply(&TableArgs { sort: Some("price".parse().unwrap()), ..Default::default() }).unwrap();
+        assert_eq!(table.rows[0][0], "C-3");
+        let mut table = products();
+        table.apply(&TableArgs { sort: Some("Name".parse().unwrap()), ..Default::default() }).unwrap();
+        assert_eq!(table.rows[0][1], "anchor");
+    }
+
+    #[test]
+    fn test_fit_columns() {
+        // Fits already: 4 columns of padding and borders plus the closing one
+        assert_eq!(fit_columns(&[5, 20, 6], 41), vec![5, 20, 6]);
+        // The widest column is cut first
+        assert_eq!(fit_columns(&[5, 30, 6], 36), vec![5, 15, 6]);
+        // Once every column is at the minimum, trailing columns go
+        assert_eq!(fit_columns(&[8, 30, 30, 8], 30), vec![8, 8]);
+        assert_eq!(fit_columns(&[40], 10), vec![8]);
+    }
+
+    #[test]
+    fn test_render_within_reports_hidden_columns() {
+        let table = products();
+        let text = table.render_within(Some(30));
+        assert!(text.contains("(not shown: Price;"));
+        assert!(text.lines().all(|line| string_width(line) <= 30 || line.starts_with('(')));
+
+        let mut table = products();
+        table.apply(&TableArgs { vertical: true, ..Default::default() }).unwrap();
+        let text = table.render_within(Some(30));
+        assert!(text.contains("-[ RECORD 2 ]"));
+        assert!(text.contains("Current Stock | 8"));
+    }
+
+    #[test]
+    fn test_apply_rejects_unknown_column() {
+        let mut table = products();
+        let args = TableArgs { columns: vec!["weight".to_string()], ..Default::default() };
+        assert!(table.apply(&args).is_err());
+    }
+}
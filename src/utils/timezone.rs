@@ -0,0 +1,181 @@
+use chrono::{Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc};
+use once_cell::sync::OnceCell;
+
+use crate::core::{error::CLIERPError, result::CLIERPResult};
+
+static DISPLAY_TIMEZONE: OnceCell<DisplayTimezone> = OnceCell::new();
+
+/// Timezone used for business dates and for displaying timestamps.
+///
+/// Timestamps are stored in the database as naive UTC. Anything a user sees
+/// or types (check-in times, activity due dates, report date boundaries) is
+/// in this timezone and converted at the edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayTimezone {
+    /// The operating system's local timezone
+    #[default]
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl std::str::FromStr for DisplayTimezone {
+    type Err = CLIERPError;
+
+    /// Accepts `local`, `UTC`, offsets such as `+09:00`, `-0530` or `UTC+9`,
+    /// and the names of common zones without daylight saving time
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            CLIERPError::Validation(format!(
+                "Invalid timezone '{}'. Use local, UTC, an offset like +09:00, or a zone such as Asia/Seoul",
+                s
+            ))
+        };
+        let value = s.trim();
+
+        let zone_minutes = match value.to_lowercase().as_str() {
+            "local" => return Ok(DisplayTimezone::Local),
+            "utc" | "z" | "etc/utc" | "gmt" => return Ok(DisplayTimezone::Fixed(FixedOffset::east_opt(0).unwrap())),
+            "asia/seoul" | "asia/tokyo" => Some(9 * 60),
+            "asia/shanghai" | "asia/singapore" | "asia/taipei" | "asia/hong_kong" => Some(8 * 60),
+            "asia/ho_chi_minh" | "asia/bangkok" | "asia/jakarta" => Some(7 * 60),
+            "asia/kolkata" => Some(5 * 60 + 30),
+            "asia/dubai" => Some(4 * 60),
+            _ => None,
+        };
+        if let Some(minutes) = zone_minutes {
+            return Ok(DisplayTimezone::Fixed(FixedOffset::east_opt(minutes * 60).unwrap()));
+        }
+
+        let offset = value
+            .strip_prefix("UTC")
+            .or_else(|| value.strip_prefix("utc"))
+            .or_else(|| value.strip_prefix("GMT"))
+            .unwrap_or(value);
+        let (sign, digits) = match offset.chars().next() {
+            Some('+') => (1, &offset[1..]),
+            Some('-') => (-1, &offset[1..]),
+            _ => return Err(invalid()),
+        };
+        let (h, m) = match digits.split_once(':') {
+            Some((h, m)) => (h, m),
+            None if digits.len() == 4 => digits.split_at(2),
+            None => (digits, "0"),
+        };
+        let h: i32 = h.parse().map_err(|_| invalid())?;
+        let m: i32 = m.parse().map_err(|_| invalid())?;
+        if h > 14 || m > 59 {
+            return Err(invalid());
+        }
+
+        FixedOffset::east_opt(sign * (h * 3600 + m * 60))
+            .map(DisplayTimezone::Fixed)
+            .ok_or_else(invalid)
+    }
+}
+
+impl std::fmt::Display for DisplayTimezone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DisplayTimezone::Local => write!(f, "local"),
+            DisplayTimezone::Fixed(offset) => write!(f, "UTC{}", offset),
+        }
+    }
+}
+
+impl DisplayTimezone {
+    /// Install the display timezone from configuration for the rest of the process
+    pub fn initialize(timezone: &str) -> CLIERPResult<()> {
+        let timezone = timezone.parse::<DisplayTimezone>()?;
+        let _ = DISPLAY_TIMEZONE.set(timezone);
+        Ok(())
+    }
+
+    /// The configured timezone, or the system timezone if not initialized
+    pub fn current() -> DisplayTimezone {
+        DISPLAY_TIMEZONE.get().copied().unwrap_or_default()
+    }
+
+    /// Current wall-clock time in this timezone
+    pub fn now(&self) -> NaiveDateTime {
+        self.to_local(Utc::now().naive_utc())
+    }
+
+    /// Current business date in this timezone
+    pub fn today(&self) -> NaiveDate {
+        self.now().date()
+    }
+
+    /// Convert a stored UTC timestamp to wall-clock time in this timezone
+    pub fn to_local(&self, utc: NaiveDateTime) -> NaiveDateTime {
+        let offset = match self {
+            DisplayTimezone::Local => Local.offset_from_utc_datetime(&utc).fix(),
+            DisplayTimezone::Fixed(offset) => *offset,
+        };
+        utc + Duration::seconds(offset.local_minus_utc() as i64)
+    }
+
+    /// Convert wall-clock time in this timezone to UTC for storage
+    pub fn to_utc(&self, local: NaiveDateTime) -> NaiveDateTime {
+        let offset = match self {
+            DisplayTimezone::Local => Local
+                .offset_from_local_datetime(&local)
+                .earliest()
+                .map(|o| o.fix())
+                .unwrap_or_else(|| Local.offset_from_utc_datetime(&local).fix()),
+            DisplayTimezone::Fixed(offset) => *offset,
+        };
+        local - Duration::seconds(offset.local_minus_utc() as i64)
+    }
+
+    /// UTC instant at which a local business date begins
+    pub fn day_start_utc(&self, date: NaiveDate) -> NaiveDateTime {
+        self.to_utc(date.and_time(NaiveTime::MIN))
+    }
+
+    /// Half-open UTC range `[start, end)` covering the inclusive local dates
+    pub fn date_range_utc(&self, start: NaiveDate, end: NaiveDate) -> (NaiveDateTime, NaiveDateTime) {
+        (self.day_start_utc(start), self.day_start_utc(end + Duration::days(1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seoul() -> DisplayTimezone {
+        "Asia/Seoul".parse().unwrap()
+    }
+
+    fn datetime(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_timezones() {
+        assert_eq!("local".parse::<DisplayTimezone>().unwrap(), DisplayTimezone::Local);
+        assert_eq!("+09:00".parse::<DisplayTimezone>().unwrap(), seoul());
+        assert_eq!("UTC+9".parse::<DisplayTimezone>().unwrap(), seoul());
+        assert_eq!(
+            "-0530".parse::<DisplayTimezone>().unwrap(),
+            DisplayTimezone::Fixed(FixedOffset::west_opt(5 * 3600 + 1800).unwrap())
+        );
+        assert!("Mars/Olympus".parse::<DisplayTimezone>().is_err());
+        assert!("+25:00".parse::<DisplayTimezone>().is_err());
+    }
+
+    #[test]
+    fn test_round_trip_conversion() {
+        let utc = datetime(2025, 3, 31, 16, 30);
+        let local = seoul().to_local(utc);
+        assert_eq!(local, datetime(2025, 4, 1, 1, 30));
+        assert_eq!(seoul().to_utc(local), utc);
+    }
+
+    #[test]
+    fn test_date_range_boundaries() {
+        let date = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap();
+        let (start, end) = seoul().date_range_utc(date, date);
+        assert_eq!(start, datetime(2025, 3, 31, 15, 0));
+        assert_eq!(end, datetime(2025, 4, 1, 15, 0));
+    }
+}
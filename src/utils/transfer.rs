@@ -0,0 +1,311 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::error::CLIERPError;
+use crate::core::result::CLIERPResult;
+
+/// Time allowed for connecting and for each reply from the server
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum TransferProtocol {
+    Ftp,
+    /// Uses the system `sftp` client, so the key must be set up for batch
+    /// (non-interactive) logins
+    Sftp,
+}
+
+impl TransferProtocol {
+    pub fn default_port(&self) -> u16 {
+        match self {
+            TransferProtocol::Ftp => 21,
+            TransferProtocol::Sftp => 22,
+        }
+    }
+}
+
+impl std::fmt::Display for TransferProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransferProtocol::Ftp => write!(f, "ftp"),
+            TransferProtocol::Sftp => write!(f, "sftp"),
+        }
+    }
+}
+
+impl std::str::FromStr for TransferProtocol {
+    type Err = CLIERPError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ftp" => Ok(TransferProtocol::Ftp),
+            "sftp" => Ok(TransferProtocol::Sftp),
+            _ => Err(CLIERPError::Validation(format!("Unknown protocol '{}'; use ftp or sftp", s))),
+        }
+    }
+}
+
+/// Server and directory a file is uploaded to
+#[derive(Debug, Clone)]
+pub struct RemoteTarget {
+    pub protocol: TransferProtocol,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    /// FTP password; SFTP always authenticates with a key
+    pub password: Option<String>,
+    /// Directory on the server, or the login directory when `None`
+    pub remote_dir: Option<String>,
+}
+
+impl RemoteTarget {
+    /// Upload `contents` as `file_name`, replacing any file of that name
+    pub fn upload(&self, file_name: &str, contents: &[u8]) -> CLIERPResult<()> {
+        match self.protocol {
+            TransferProtocol::Ftp => self.upload_ftp(file_name, contents),
+            TransferProtocol::Sftp => self.upload_sftp(file_name, contents),
+        }
+    }
+
+    fn upload_ftp(&self, file_name: &str, contents: &[u8]) -> CLIERPResult<()> {
+        let mut session = FtpSession::connect(&self.host, self.port)?;
+        session.expect(&[220])?;
+        let (code, _) = session.command(&format!("USER {}", self.username), &[230, 331])?;
+        if code == 331 {
+            let password = self.password.as_deref().unwrap_or_default();
+            session.command(&format!("PASS {}", password), &[230, 202])?;
+        }
+        session.command("TYPE I", &[200])?;
+        if let Some(dir) = &self.remote_dir {
+            session.command(&format!("CWD {}", dir), &[250])?;
+        }
+
+        let (_, reply) = session.command("PASV", &[227])?;
+        let port = parse_pasv_port(&reply)?;
+        // Connect to the control host rather than the address in the reply,
+        // which is often a private one behind NAT
+        let mut data = connect(&SocketAddr::new(session.peer_ip(), port))?;
+        session.command(&format!("STOR {}", file_name), &[125, 150])?;
+        data.write_all(contents).map_err(|e| transfer_error("Upload failed", e))?;
+        drop(data);
+        session.expect(&[226, 250])?;
+        // The file is stored; a failed goodbye does not matter
+        let _ = session.command("QUIT", &[221]);
+        Ok(())
+    }
+
+    fn upload_sftp(&self, file_name: &str, contents: &[u8]) -> CLIERPResult<()> {
+        check_batch_argument("file name", file_name)?;
+        if let Some(dir) = &self.remote_dir {
+            check_batch_argument("remote directory", dir)?;
+        }
+        let local = std::env::temp_dir().join(format!("clierp-upload-{}-{}", std::process::id(), file_name));
+        std::fs::write(&local, contents)
+            .map_err(|e| CLIERPError::IoError(format!("Failed to write {}: {}", local.display(), e)))?;
+
+        let mut batch = String::new();
+        if let Some(dir) = &self.remote_dir {
+            batch.push_str(&format!("cd \"{}\"\n", dir));
+        }
+        batch.push_str(&format!("put \"{}\" \"{}\"\n", local.display(), file_name));
+
+        let result = Command::new("sftp")
+            .args(["-b", "-", "-o", "BatchMode=yes", "-o", "ConnectTimeout=30", "-P"])
+            .arg(self.port.to_string())
+            .arg(format!("{}@{}", self.username, self.host))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                if let Some(mut stdin) = child.stdin.take() {
+                    stdin.write_all(batch.as_bytes())?;
+                }
+                child.wait_with_output()
+            });
+        let _ = std::fs::remove_file(&local);
+
+        let output = result.map_err(|e| transfer_error("Could not run sftp", e))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(CLIERPError::IoError(format!(
+                "sftp to {} failed: {}",
+                self.host,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    }
+}
+
+/// Names go into the sftp batch quoted, so a quote or line break in one
+/// would end its command and start another
+fn check_batch_argument(what: &str, value: &str) -> CLIERPResult<()> {
+    if value.contains(['"', '\n', '\r']) {
+        return Err(CLIERPError::Validation(format!(
+            "The {} '{}' may not contain quotes or line breaks",
+            what,
+            value.escape_default()
+        )));
+    }
+    Ok(())
+}
+
+fn transfer_error(context: &str, e: std::io::Error) -> CLIERPError {
+    CLIERPError::IoError(format!("{}: {}", context, e))
+}
+
+fn connect(addr: &SocketAddr) -> CLIERPResult<TcpStream> {
+    let stream = TcpStream::connect_timeout(addr, TIMEOUT)
+        .map_err(|e| transfer_error(&format!("Could not connect to {}", addr), e))?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    Ok(stream)
+}
+
+/// Control connection of a plain FTP session
+struct FtpSession {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl FtpSession {
+    fn connect(host: &str, port: u16) -> CLIERPResult<Self> {
+        let addr = (host, port)
+            .to_socket_addrs()
+            .map_err(|e| transfer_error(&format!("Could not resolve {}", host), e))?
+            .next()
+            .ok_or_else(|| CLIERPError::IoError(format!("Could not resolve {}", host)))?;
+        let stream = connect(&addr)?;
+        Ok(Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    fn peer_ip(&self) -> std::net::IpAddr {
+        self.writer
+            .peer_addr()
+            .map(|addr| addr.ip())
+            .unwrap_or_else(|_| Ipv4Addr::LOCALHOST.into())
+    }
+
+    fn command(&mut self, command: &str, expected: &[u32]) -> CLIERPResult<(u32, String)> {
+        self.writer
+            .write_all(format!("{}\r\n", command).as_bytes())
+            .map_err(|e| transfer_error("FTP connection lost", e))?;
+        self.expect(expected).map_err(|e| match e {
+            // Keep the password out of error messages and logs
+            CLIERPError::IoError(message) if command.starts_with("PASS") => {
+                CLIERPError::IoError(message.replace(command, "PASS ****"))
+            }
+            e => e,
+        })
+    }
+
+    /// Read a reply, failing unless its code is one of `expected`
+    fn expect(&mut self, expected: &[u32]) -> CLIERPResult<(u32, String)> {
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            let read = self
+                .reader
+                .read_line(&mut line)
+                .map_err(|e| transfer_error("FTP connection lost", e))?;
+            if read == 0 {
+                return Err(CLIERPError::IoError("FTP server closed the connection".to_string()));
+            }
+            lines.push(line.trim_end().to_string());
+            if let Some(reply) = parse_reply(&lines) {
+                return match reply {
+                    (code, text) if expected.contains(&code) => Ok((code, text)),
+                    (code, text) => Err(CLIERPError::IoError(format!("FTP server replied {} {}", code, text))),
+                };
+            }
+        }
+    }
+}
+
+/// Code and text of a complete reply, or `None` while a multi-line reply
+/// (`123-...` up to `123 ...`) is still being read
+fn parse_reply(lines: &[String]) -> Option<(u32, String)> {
+    let first = lines.first()?;
+    let code: u32 = first.get(..3)?.parse().ok()?;
+    let last = lines.last()?;
+    let complete = if first.as_bytes().get(3) == Some(&b'-') {
+        lines.len() > 1 && last.starts_with(&format!("{} ", code))
+    } else {
+        true
+    };
+    complete.then(|| (code, last.get(4..).unwrap_or_default().to_string()))
+}
+
+/// Data port of a `227 Entering Passive Mode (h1,h2,h3,h4,p1,p2)` reply
+fn parse_pasv_port(reply: &str) -> CLIERPResult<u16> {
+    let invalid = || CLIERPError::IoError(format!("Unexpected PASV reply '{}'", reply));
+    let start = reply.find('(').ok_or_else(invalid)?;
+    let end = reply[start..].find(')').ok_or_else(invalid)? + start;
+    let numbers: Vec<u16> = reply[start + 1..end]
+        .split(',')
+        .map(|n| n.trim().parse::<u8>().map(u16::from))
+        .collect::<Result<_, _>>()
+        .map_err(|_| invalid())?;
+    match numbers.as_slice() {
+        [_, _, _, _, high, low] => Ok(high * 256 + low),
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_reply() {
+        assert_eq!(parse_reply(&lines(&["220 Welcome"])), Some((220, "Welcome".to_string())));
+        assert_eq!(parse_reply(&lines(&["230-Hello", " motd"])), None);
+        assert_eq!(
+            parse_reply(&lines(&["230-Hello", " motd", "230 Logged in"])),
+            Some((230, "Logged in".to_string()))
+        );
+        assert_eq!(parse_reply(&lines(&["garbage"])), None);
+    }
+
+    #[test]
+    fn test_parse_pasv_port() {
+        assert_eq!(parse_pasv_port("Entering Passive Mode (192,168,1,10,39,16)").unwrap(), 10000);
+        assert!(parse_pasv_port("Entering Passive Mode").is_err());
+        assert!(parse_pasv_port("(1,2,3,4,5)").is_err());
+    }
+
+    #[test]
+    fn test_sftp_rejects_batch_commands_in_names() {
+        let target = |remote_dir: Option<&str>| RemoteTarget {
+            protocol: TransferProtocol::Sftp,
+            host: "sftp.invalid".to_string(),
+            port: 22,
+            username: "clierp".to_string(),
+            password: None,
+            remote_dir: remote_dir.map(str::to_string),
+        };
+
+        for (remote_dir, file_name) in [
+            (None, "stock.csv\"\nrm \"prices.csv"),
+            (None, "stock.csv\r\nrm prices.csv"),
+            (Some("in/\"\nrm \"prices.csv"), "stock.csv"),
+            (Some("in\nrm prices.csv"), "stock.csv"),
+        ] {
+            let err = target(remote_dir).upload(file_name, b"SKU\n").unwrap_err();
+            assert!(matches!(err, CLIERPError::Validation(_)), "{:?}", err);
+        }
+        assert!(check_batch_argument("file name", "stock levels 2025-01.csv").is_ok());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::core::{error::CLIERPError, result::CLIERPResult};
+use crate::database::{RuleType, ValidationRule};
 use once_cell::sync::Lazy;
 use regex::Regex;
 
@@ -10,6 +11,12 @@ static EMAIL_REGEX: Lazy<Regex> =
 static PHONE_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"^01[0-9]-?[0-9]{4}-?[0-9]{4}$").unwrap());
 
+// ISO 3166-1 alpha-2 country code
+static COUNTRY_CODE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Z]{2}$").unwrap());
+
+// Combined Nomenclature / HS commodity code (6 to 10 digits)
+static COMMODITY_CODE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[0-9]{6,10}$").unwrap());
+
 /// Validate email address
 pub fn validate_email(email: &str) -> CLIERPResult<()> {
     if EMAIL_REGEX.is_match(email) {
@@ -34,6 +41,30 @@ pub fn validate_phone(phone: &str) -> CLIERPResult<()> {
     }
 }
 
+/// Validate ISO 3166-1 alpha-2 country code (e.g. "DE", "KR")
+pub fn validate_country_code(code: &str) -> CLIERPResult<()> {
+    if COUNTRY_CODE_REGEX.is_match(code) {
+        Ok(())
+    } else {
+        Err(CLIERPError::Validation(format!(
+            "Invalid country code: {} (expected ISO 3166-1 alpha-2, e.g. DE)",
+            code
+        )))
+    }
+}
+
+/// Validate commodity code (HS / CN, digits only)
+pub fn validate_commodity_code(code: &str) -> CLIERPResult<()> {
+    if COMMODITY_CODE_REGEX.is_match(code) {
+        Ok(())
+    } else {
+        Err(CLIERPError::Validation(format!(
+            "Invalid commodity code: {} (expected 6 to 10 digits)",
+            code
+        )))
+    }
+}
+
 /// Validate employee code format
 pub fn validate_employee_code(code: &str) -> CLIERPResult<()> {
     if code.len() < 3 || code.len() > 20 {
@@ -115,5 +146,150 @@ pub fn validate_required_string(value: &str, field_name: &str) -> CLIERPResult<(
     Ok(())
 }
 
+/// Check that a rule's value makes sense for its type before it is stored
+pub fn validate_rule_definition(rule_type: RuleType, value: Option<&str>) -> CLIERPResult<()> {
+    let missing = || {
+        CLIERPError::Validation(format!("Rule type '{}' requires a value", rule_type))
+    };
+    match rule_type {
+        RuleType::Required => Ok(()),
+        RuleType::Regex => {
+            let pattern = value.ok_or_else(missing)?;
+            Regex::new(pattern)
+                .map(|_| ())
+                .map_err(|e| CLIERPError::Validation(format!("Invalid regex '{}': {}", pattern, e)))
+        }
+        RuleType::Min | RuleType::Max => {
+            let limit = value.ok_or_else(missing)?;
+            limit.trim().parse::<f64>().map(|_| ()).map_err(|_| {
+                CLIERPError::Validation(format!("Rule value must be a number: {}", limit))
+            })
+        }
+        RuleType::MaxLength => {
+            let limit = value.ok_or_else(missing)?;
+            limit.trim().parse::<usize>().map(|_| ()).map_err(|_| {
+                CLIERPError::Validation(format!("Rule value must be a whole number: {}", limit))
+            })
+        }
+    }
+}
+
+/// Evaluate a single configured rule against a field value.
+///
+/// Only `required` rejects a missing or empty value; the other rule types
+/// apply to values that are present.
+pub fn check_rule(rule: &ValidationRule, value: Option<&str>) -> CLIERPResult<()> {
+    let rule_type = rule.rule_type.parse::<RuleType>()?;
+    let limit = rule.value.as_deref().unwrap_or_default().trim();
+    let value = value.map(str::trim).filter(|v| !v.is_empty());
+
+    let passed = match (rule_type, value) {
+        (RuleType::Required, value) => value.is_some(),
+        (_, None) => true,
+        (RuleType::Regex, Some(v)) => Regex::new(limit)
+            .map_err(|e| CLIERPError::Validation(format!("Invalid regex '{}': {}", limit, e)))?
+            .is_match(v),
+        (RuleType::Min, Some(v)) => parse_number(v, &rule.field)? >= parse_number(limit, &rule.field)?,
+        (RuleType::Max, Some(v)) => parse_number(v, &rule.field)? <= parse_number(limit, &rule.field)?,
+        (RuleType::MaxLength, Some(v)) => limit.parse::<usize>().map_or(true, |max| v.chars().count() <= max),
+    };
+
+    if passed {
+        return Ok(());
+    }
+
+    let message = rule.message.clone().unwrap_or_else(|| match rule_type {
+        RuleType::Required => format!("{} is required", rule.field),
+        RuleType::Regex => format!("{} does not match the required format {}", rule.field, limit),
+        RuleType::Min => format!("{} must be at least {}", rule.field, limit),
+        RuleType::Max => format!("{} cannot exceed {}", rule.field, limit),
+        RuleType::MaxLength => format!("{} cannot exceed {} characters", rule.field, limit),
+    });
+    Err(CLIERPError::Validation(message))
+}
+
+/// Evaluate every active rule against the named field values of a record
+pub fn apply_rules(rules: &[ValidationRule], fields: &[(&str, Option<&str>)]) -> CLIERPResult<()> {
+    for rule in rules.iter().filter(|r| r.is_active) {
+        if let Some((_, value)) = fields.iter().find(|(name, _)| *name == rule.field) {
+            check_rule(rule, *value)?;
+        }
+    }
+    Ok(())
+}
+
+fn parse_number(value: &str, field: &str) -> CLIERPResult<f64> {
+    value
+        .parse::<f64>()
+        .map_err(|_| CLIERPError::Validation(format!("{} must be a number", field)))
+}
+
 /// Validation result type
 pub type ValidationResult<T> = CLIERPResult<T>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(field: &str, rule_type: RuleType, value: Option<&str>) -> ValidationRule {
+        ValidationRule {
+            id: 1,
+            entity: "product".to_string(),
+            field: field.to_string(),
+            rule_type: rule_type.to_string(),
+            value: value.map(str::to_string),
+            message: None,
+            is_active: true,
+            created_at: chrono::Utc::now().naive_utc(),
+            updated_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    #[test]
+    fn test_regex_and_required_rules() {
+        let sku = rule("sku", RuleType::Regex, Some(r"^[A-Z]{3}-\d{4}$"));
+        assert!(check_rule(&sku, Some("ABC-1234")).is_ok());
+        assert!(check_rule(&sku, Some("abc1234")).is_err());
+        assert!(check_rule(&sku, None).is_ok());
+
+        let email = rule("email", RuleType::Required, None);
+        assert!(check_rule(&email, Some("a@b.co")).is_ok());
+        assert!(check_rule(&email, Some("  ")).is_err());
+        assert!(check_rule(&email, None).is_err());
+    }
+
+    #[test]
+    fn test_numeric_and_length_rules() {
+        let discount = rule("discount_percent", RuleType::Max, Some("15"));
+        assert!(check_rule(&discount, Some("15")).is_ok());
+        assert!(check_rule(&discount, Some("20")).is_err());
+
+        let minimum = rule("credit_limit", RuleType::Min, Some("0"));
+        assert!(check_rule(&minimum, Some("-1")).is_err());
+
+        let name = rule("name", RuleType::MaxLength, Some("5"));
+        assert!(check_rule(&name, Some("한글이름")).is_ok());
+        assert!(check_rule(&name, Some("too long")).is_err());
+    }
+
+    #[test]
+    fn test_apply_rules_uses_custom_message_and_skips_inactive() {
+        let mut required = rule("tax_id", RuleType::Required, None);
+        required.message = Some("Tax ID is mandatory for all customers".to_string());
+        let mut inactive = rule("email", RuleType::Required, None);
+        inactive.is_active = false;
+
+        let rules = vec![required, inactive];
+        let err = apply_rules(&rules, &[("tax_id", None), ("email", None)]).unwrap_err();
+        assert_eq!(err.to_string(), "Validation error: Tax ID is mandatory for all customers");
+        assert!(apply_rules(&rules, &[("tax_id", Some("123-45-67890")), ("email", None)]).is_ok());
+    }
+
+    #[test]
+    fn test_rule_definitions() {
+        assert!(validate_rule_definition(RuleType::Regex, Some("[")).is_err());
+        assert!(validate_rule_definition(RuleType::Max, Some("ten")).is_err());
+        assert!(validate_rule_definition(RuleType::MaxLength, None).is_err());
+        assert!(validate_rule_definition(RuleType::Required, None).is_ok());
+    }
+}
@@ -0,0 +1,29 @@
+use std::path::Path;
+
+use clierp::testing::{verify_golden, GoldenStatus, GOLDEN_DIR, GOLDEN_UPDATE_ENV};
+
+/// Every report in every format still renders as its golden file says;
+/// record intended changes with `clierp reports verify-golden --update` or
+/// by running this test with `CLIERP_UPDATE_GOLDEN=1`
+#[test]
+fn test_reports_match_golden_files() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(GOLDEN_DIR);
+    let update = std::env::var_os(GOLDEN_UPDATE_ENV).is_some_and(|value| !value.is_empty());
+    let outcomes = verify_golden(&dir, update).expect("Failed to render the golden reports");
+
+    let changed: Vec<String> = outcomes
+        .iter()
+        .filter_map(|outcome| match &outcome.status {
+            GoldenStatus::Missing => Some(format!("{} is missing", outcome.path.display())),
+            GoldenStatus::Changed { line, expected, actual } => Some(format!(
+                "{} at line {}: expected '{}', got '{}'",
+                outcome.path.display(),
+                line,
+                expected,
+                actual
+            )),
+            _ => None,
+        })
+        .collect();
+    assert!(changed.is_empty(), "Reports differ from their golden files:\n{}", changed.join("\n"));
+}
@@ -2,16 +2,18 @@ mod common;
 
 use common::setup_test_db;
 use clierp::database::connection::get_connection;
-use clierp::modules::hr::{EmployeeService, DepartmentService, PayrollService};
-use clierp::modules::finance::{AccountService, TransactionService};
+use clierp::database::{CustomerType, DealStage, LeadPriority};
+use clierp::modules::hr::{EmployeeService, DepartmentService, PayrollService, CreateEmployeeRequest};
+use clierp::modules::finance::{AccountService, CreateAccountRequest, JournalService, JournalEntryRequest, JournalLine};
 use clierp::modules::inventory::{ProductService, CategoryService, SupplierService, PurchaseOrderService};
-use clierp::modules::crm::{CustomerService, LeadService, DealService, ActivityService};
+use clierp::modules::inventory::{PurchaseOrderItem, ReceiveItemData};
+use clierp::modules::crm::{CustomerService, LeadService, DealService};
 use clierp::utils::pagination::PaginationParams;
-use chrono::{NaiveDate, Utc};
+use chrono::NaiveDate;
 
 #[test]
 fn test_employee_purchase_workflow() {
-    setup_test_db();
+    let company = setup_test_db();
 
     let mut conn = get_connection().expect("Failed to get connection");
 
@@ -19,29 +21,30 @@ fn test_employee_purchase_workflow() {
     let dept_service = DepartmentService::new();
     let employee_service = EmployeeService::new();
     let category_service = CategoryService::new();
-    let supplier_service = SupplierService::new();
     let product_service = ProductService::new();
-    let po_service = PurchaseOrderService::new();
 
     // 1. Create department and employee
     let department = dept_service.create_department(
         &mut conn,
-        "Purchasing Department",
-        Some("Handles all procurement activities"),
+        "Purchasing Department".to_string(),
+        Some("Handles all procurement activities".to_string()),
         None,
     ).unwrap();
 
     let employee = employee_service.create_employee(
         &mut conn,
-        "PURCH001",
-        "John Purchaser",
-        Some("purchaser@company.com"),
-        Some("555-1234"),
-        department.id,
-        "Purchasing Manager",
-        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
-        60000, // $600.00 salary
-    ).unwrap();
+        CreateEmployeeRequest {
+            employee_code: Some("PURCH001".to_string()),
+            name: "John Purchaser".to_string(),
+            email: Some("purchaser@company.com".to_string()),
+            phone: Some("555-1234".to_string()),
+            department_id: department.id,
+            position: "Purchasing Manager".to_string(),
+            hire_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            salary: 60000, // $600.00 salary
+        },
+    ).unwrap();
+    assert_eq!(employee.department_id, department.id);
 
     // 2. Create product and supplier
     let category = category_service.create_category(
@@ -50,7 +53,8 @@ fn test_employee_purchase_workflow() {
         None,
     ).unwrap();
 
-    let supplier = supplier_service.create_supplier(
+    let supplier = SupplierService::create_supplier(
+        &mut conn,
         "OFFICE001",
         "Office Supply Co.",
         Some("Sales Rep"),
@@ -74,28 +78,33 @@ fn test_employee_purchase_workflow() {
         None,
     ).unwrap();
 
-    // 3. Create purchase order (employee creating PO)
-    let order_date = NaiveDate::from_ymd_opt(2024, 10, 1).unwrap();
+    // 3. Create purchase order for the purchasing department
     let expected_date = NaiveDate::from_ymd_opt(2024, 10, 15).unwrap();
 
-    let po = po_service.create_purchase_order(
+    let po = PurchaseOrderService::create_purchase_order(
+        &mut conn,
         supplier.id,
-        order_date,
         Some(expected_date),
         Some("Requested by purchasing department"),
-        vec![(product.id, 10, 40000)], // 10 desks at $400 each
+        vec![PurchaseOrderItem { product_id: product.id, quantity: 10, unit_cost: 40000 }], // 10 desks at $400 each
+        None,
     ).unwrap();
 
-    // 4. Employee approves PO
-    let approved_po = po_service.approve_purchase_order(po.id, Some(employee.id)).unwrap();
+    // 4. Approve the PO
+    let approved_po = PurchaseOrderService::approve_purchase_order(
+        &mut conn,
+        po.purchase_order.id,
+        company.admin.id,
+        None,
+    ).unwrap();
     assert_eq!(approved_po.status, "approved");
 
-    // 5. Receive the goods (employee receives)
-    let received_po = po_service.receive_purchase_order(
-        po.id,
-        vec![(product.id, 10)], // All 10 received
-        Some("All items received in good condition"),
-        Some(employee.id),
+    // 5. Receive the goods
+    let received_po = PurchaseOrderService::receive_purchase_items(
+        &mut conn,
+        po.purchase_order.id,
+        vec![ReceiveItemData { item_id: po.items[0].purchase_item.id, quantity: 10 }], // All 10 received
+        Some(company.admin.id),
     ).unwrap();
     assert_eq!(received_po.status, "received");
 
@@ -103,35 +112,32 @@ fn test_employee_purchase_workflow() {
     let updated_product = product_service.get_product_by_id(product.id).unwrap();
     assert_eq!(updated_product.current_stock, 10);
 
-    // 7. Verify stock movement was recorded with employee reference
+    // 7. Verify stock movement was recorded with the receiving user
     let pagination = PaginationParams::new(1, 10);
     let movements = product_service.get_stock_movements(product.id, &pagination).unwrap();
     assert!(!movements.data.is_empty());
 
-    // Should have movement with employee reference
+    // Should have movement with user reference
     let receiving_movement = movements.data.iter().find(|m| m.movement_type == "in");
     assert!(receiving_movement.is_some());
-    assert_eq!(receiving_movement.unwrap().moved_by, Some(employee.id));
+    assert_eq!(receiving_movement.unwrap().moved_by, Some(company.admin.id));
 }
 
 #[test]
 fn test_sales_to_finance_integration() {
-    setup_test_db();
+    let company = setup_test_db();
+    let employee_id = company.employees[0].id;
 
     let mut conn = get_connection().expect("Failed to get connection");
 
     // Services
-    let customer_service = CustomerService::new();
-    let lead_service = LeadService::new();
-    let deal_service = DealService::new();
     let account_service = AccountService::new();
-    let transaction_service = TransactionService::new();
 
     // 1. Create customer and lead
-    let customer = customer_service.create_customer(
+    let customer = CustomerService::create_customer(
         &mut conn,
         "Big Corporation",
-        crate::database::models::CustomerType::Business,
+        CustomerType::Business,
         Some("finance@bigcorp.com"),
         None,
         None,
@@ -141,92 +147,89 @@ fn test_sales_to_finance_integration() {
         None,
     ).unwrap();
 
-    let lead = lead_service.create_lead(
+    let lead = LeadService::create_lead(
         &mut conn,
         "Software License Deal",
         Some(customer.id),
         "Direct Sales",
         75000, // $750.00
         Some(NaiveDate::from_ymd_opt(2024, 12, 31).unwrap()),
-        crate::database::models::LeadPriority::High,
-        Some(1),
+        LeadPriority::High,
+        Some(employee_id),
         Some("Annual software license"),
         None,
     ).unwrap();
 
     // 2. Convert to deal and close as won
-    let deal = deal_service.create_deal_from_lead(
+    let deal = DealService::create_deal(
         &mut conn,
         lead.id,
         "Software License Agreement",
         80000, // Negotiated up to $800.00
         Some(NaiveDate::from_ymd_opt(2024, 11, 30).unwrap()),
+        Some(employee_id),
+        None,
         Some("Final proposal accepted"),
     ).unwrap();
 
-    let closed_deal = deal_service.close_deal(
+    let closed_deal = DealService::update_deal_stage(
         &mut conn,
         deal.id,
-        crate::database::models::DealStage::ClosedWon,
-        Some(NaiveDate::from_ymd_opt(2024, 10, 25).unwrap()),
+        DealStage::ClosedWon,
         Some("Contract signed, payment received"),
-        Some(1),
     ).unwrap();
 
-    // 3. Create revenue account if it doesn't exist
+    // 3. Create revenue and receivables accounts
     let revenue_account = account_service.create_account(
-        "4100",
-        "Software Revenue",
-        crate::database::models::AccountType::Revenue,
-        None,
+        &mut conn,
+        CreateAccountRequest {
+            account_code: "4100".to_string(),
+            account_name: "Software Revenue".to_string(),
+            account_type: "revenue".to_string(),
+            parent_id: None,
+        },
     ).unwrap();
 
     let receivables_account = account_service.create_account(
-        "1200",
-        "Accounts Receivable",
-        crate::database::models::AccountType::Asset,
-        None,
-    ).unwrap();
-
-    // 4. Record the sale in accounting
-    let transaction_date = NaiveDate::from_ymd_opt(2024, 10, 25).unwrap();
-
-    // Debit Accounts Receivable
-    let receivable_transaction = transaction_service.create_transaction(
-        receivables_account.id,
-        transaction_date,
-        80000, // $800.00
-        "debit",
-        &format!("Sale to {} - Deal #{}", customer.name, closed_deal.id),
-        Some(&format!("DEAL-{}", closed_deal.id)),
-        Some(1), // Created by employee
+        &mut conn,
+        CreateAccountRequest {
+            account_code: "1200".to_string(),
+            account_name: "Accounts Receivable".to_string(),
+            account_type: "asset".to_string(),
+            parent_id: None,
+        },
     ).unwrap();
 
-    // Credit Revenue
-    let revenue_transaction = transaction_service.create_transaction(
-        revenue_account.id,
-        transaction_date,
-        80000, // $800.00
-        "credit",
-        &format!("Revenue from {} - Deal #{}", customer.name, closed_deal.id),
-        Some(&format!("DEAL-{}", closed_deal.id)),
-        Some(1),
+    // 4. Record the sale in accounting: debit receivables, credit revenue
+    let sale_entry = JournalService::post_entry(
+        &mut conn,
+        JournalEntryRequest {
+            entry_date: NaiveDate::from_ymd_opt(2024, 10, 25).unwrap(),
+            description: format!("Sale to {} - Deal #{}", customer.name, closed_deal.id),
+            reference: Some(format!("DEAL-{}", closed_deal.id)),
+            lines: vec![
+                JournalLine::debit(receivables_account.id, 80000), // $800.00
+                JournalLine::credit(revenue_account.id, 80000),
+            ],
+        },
+        Some(company.admin.id),
     ).unwrap();
 
     // 5. Verify the accounting entries
+    let receivable_transaction = &sale_entry.lines[0];
+    let revenue_transaction = &sale_entry.lines[1];
     assert_eq!(receivable_transaction.amount, 80000);
     assert_eq!(receivable_transaction.debit_credit, "debit");
     assert_eq!(revenue_transaction.amount, 80000);
     assert_eq!(revenue_transaction.debit_credit, "credit");
 
     // 6. Verify account balances
-    let updated_receivables = account_service.get_account_by_id(receivables_account.id).unwrap();
-    let updated_revenue = account_service.get_account_by_id(revenue_account.id).unwrap();
+    let updated_receivables = account_service.get_account_by_id(&mut conn, receivables_account.id).unwrap().unwrap();
+    let updated_revenue = account_service.get_account_by_id(&mut conn, revenue_account.id).unwrap().unwrap();
 
-    // Asset accounts increase with debits
+    // Debits add to the balance and credits subtract from it
     assert_eq!(updated_receivables.balance, 80000);
-    // Revenue accounts increase with credits (stored as positive)
-    assert_eq!(updated_revenue.balance, 80000);
+    assert_eq!(updated_revenue.balance, -80000);
 
     // 7. Verify we can trace back from transaction to deal
     assert!(receivable_transaction.reference.is_some());
@@ -235,16 +238,14 @@ fn test_sales_to_finance_integration() {
 
 #[test]
 fn test_inventory_to_sales_integration() {
-    setup_test_db();
+    let company = setup_test_db();
+    let employee_id = company.employees[0].id;
 
     let mut conn = get_connection().expect("Failed to get connection");
 
     // Services
     let category_service = CategoryService::new();
     let product_service = ProductService::new();
-    let customer_service = CustomerService::new();
-    let lead_service = LeadService::new();
-    let deal_service = DealService::new();
 
     // 1. Create product with stock
     let category = category_service.create_category(
@@ -268,10 +269,10 @@ fn test_inventory_to_sales_integration() {
     ).unwrap();
 
     // 2. Create customer and sales process
-    let customer = customer_service.create_customer(
+    let customer = CustomerService::create_customer(
         &mut conn,
         "Tech Startup Inc.",
-        crate::database::models::CustomerType::Business,
+        CustomerType::Business,
         Some("admin@techstartup.com"),
         None,
         None,
@@ -281,36 +282,36 @@ fn test_inventory_to_sales_integration() {
         None,
     ).unwrap();
 
-    let lead = lead_service.create_lead(
+    let lead = LeadService::create_lead(
         &mut conn,
         "ERP Implementation Project",
         Some(customer.id),
         "Website Inquiry",
         600000, // 5 licenses @ $1200 each = $6000
         Some(NaiveDate::from_ymd_opt(2024, 12, 31).unwrap()),
-        crate::database::models::LeadPriority::High,
-        Some(1),
+        LeadPriority::High,
+        Some(employee_id),
         Some("Startup needs ERP solution"),
         None,
     ).unwrap();
 
-    let deal = deal_service.create_deal_from_lead(
+    let deal = DealService::create_deal(
         &mut conn,
         lead.id,
         "ERP License Sale",
         600000,
         Some(NaiveDate::from_ymd_opt(2024, 11, 15).unwrap()),
+        Some(employee_id),
+        None,
         None,
     ).unwrap();
 
     // 3. Close deal and fulfill from inventory
-    let _closed_deal = deal_service.close_deal(
+    let _closed_deal = DealService::update_deal_stage(
         &mut conn,
         deal.id,
-        crate::database::models::DealStage::ClosedWon,
-        Some(NaiveDate::from_ymd_opt(2024, 10, 20).unwrap()),
+        DealStage::ClosedWon,
         Some("Contract executed, fulfilling licenses"),
-        Some(1),
     ).unwrap();
 
     // 4. Fulfill the order by reducing inventory
@@ -322,7 +323,7 @@ fn test_inventory_to_sales_integration() {
         Some("sale"),
         Some(deal.id),
         Some(&format!("Fulfillment for deal #{} - {}", deal.id, customer.name)),
-        Some(1),
+        Some(company.admin.id),
     ).unwrap();
 
     // 5. Verify inventory was reduced
@@ -334,7 +335,7 @@ fn test_inventory_to_sales_integration() {
 
     let sale_movement = movements.data.iter().find(|m| {
         m.movement_type == "out" &&
-        m.reference_type.as_ref().map_or(false, |rt| rt == "sale")
+        m.reference_type.as_ref().is_some_and(|rt| rt == "sale")
     });
 
     assert!(sale_movement.is_some());
@@ -358,7 +359,7 @@ fn test_inventory_to_sales_integration() {
         Some("sale"),
         None,
         Some("Additional sales - bulk order"),
-        Some(1),
+        Some(company.admin.id),
     ).unwrap();
 
     // Now check low stock again
@@ -369,7 +370,7 @@ fn test_inventory_to_sales_integration() {
 
 #[test]
 fn test_employee_payroll_to_finance_integration() {
-    setup_test_db();
+    let company = setup_test_db();
 
     let mut conn = get_connection().expect("Failed to get connection");
 
@@ -378,119 +379,129 @@ fn test_employee_payroll_to_finance_integration() {
     let employee_service = EmployeeService::new();
     let payroll_service = PayrollService::new();
     let account_service = AccountService::new();
-    let transaction_service = TransactionService::new();
 
     // 1. Create department and employees
     let department = dept_service.create_department(
         &mut conn,
-        "Sales Department",
-        Some("Sales and marketing team"),
+        "Sales Department".to_string(),
+        Some("Sales and marketing team".to_string()),
         None,
     ).unwrap();
 
     let employee1 = employee_service.create_employee(
         &mut conn,
-        "SALES001",
-        "Alice Salesperson",
-        Some("alice@company.com"),
-        None,
-        department.id,
-        "Sales Representative",
-        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
-        50000, // $500.00/month
+        CreateEmployeeRequest {
+            employee_code: Some("SALES001".to_string()),
+            name: "Alice Salesperson".to_string(),
+            email: Some("alice@company.com".to_string()),
+            phone: None,
+            department_id: department.id,
+            position: "Sales Representative".to_string(),
+            hire_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            salary: 50000, // $500.00/month
+        },
     ).unwrap();
 
     let employee2 = employee_service.create_employee(
         &mut conn,
-        "SALES002",
-        "Bob Manager",
-        Some("bob@company.com"),
-        None,
-        department.id,
-        "Sales Manager",
-        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
-        75000, // $750.00/month
-    ).unwrap();
-
-    // 2. Create expense accounts for payroll
+        CreateEmployeeRequest {
+            employee_code: Some("SALES002".to_string()),
+            name: "Bob Manager".to_string(),
+            email: Some("bob@company.com".to_string()),
+            phone: None,
+            department_id: department.id,
+            position: "Sales Manager".to_string(),
+            hire_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            salary: 75000, // $750.00/month
+        },
+    ).unwrap();
+
+    // 2. Create expense and liability accounts for payroll
     let salary_expense_account = account_service.create_account(
-        "5100",
-        "Salary Expense",
-        crate::database::models::AccountType::Expense,
-        None,
+        &mut conn,
+        CreateAccountRequest {
+            account_code: "5100".to_string(),
+            account_name: "Salary Expense".to_string(),
+            account_type: "expense".to_string(),
+            parent_id: None,
+        },
     ).unwrap();
 
     let payroll_liability_account = account_service.create_account(
-        "2100",
-        "Salaries Payable",
-        crate::database::models::AccountType::Liability,
-        None,
+        &mut conn,
+        CreateAccountRequest {
+            account_code: "2100".to_string(),
+            account_name: "Salaries Payable".to_string(),
+            account_type: "liability".to_string(),
+            parent_id: None,
+        },
     ).unwrap();
 
-    // 3. Process payroll for the month
+    let withholding_account = account_service.create_account(
+        &mut conn,
+        CreateAccountRequest {
+            account_code: "2200".to_string(),
+            account_name: "Taxes Withheld".to_string(),
+            account_type: "liability".to_string(),
+            parent_id: None,
+        },
+    ).unwrap();
+
+    // 3. Process payroll for the month; base salaries are taxed at 10%
     let payroll_period = "2024-10";
 
-    let payroll1 = payroll_service.calculate_payroll(
+    let calculation1 = payroll_service.calculate_payroll(&mut conn, employee1.id, payroll_period.to_string()).unwrap();
+    let payroll1 = payroll_service.generate_payroll(
         &mut conn,
-        employee1.id,
-        payroll_period,
-        None, // Use base salary
-        0,    // No overtime
-        0,    // No bonuses
-        5000, // $50.00 deductions (taxes, etc.)
+        calculation1,
+        None, // No bonuses
+        None, // No deductions beyond tax
+        Some(company.admin.id),
     ).unwrap();
 
-    let payroll2 = payroll_service.calculate_payroll(
+    let calculation2 = payroll_service.calculate_payroll(&mut conn, employee2.id, payroll_period.to_string()).unwrap();
+    let payroll2 = payroll_service.generate_payroll(
         &mut conn,
-        employee2.id,
-        payroll_period,
+        calculation2,
+        Some(10000), // $100.00 bonus for manager
         None,
-        0,
-        10000, // $100.00 bonus for manager
-        7500,  // $75.00 deductions
+        Some(company.admin.id),
     ).unwrap();
 
     // 4. Create accounting entries for payroll
-    let total_gross_pay = payroll1.base_salary + payroll1.bonuses + payroll2.base_salary + payroll2.bonuses;
+    let total_gross_pay = payroll1.base_salary + payroll1.bonuses.unwrap_or(0) + payroll2.base_salary + payroll2.bonuses.unwrap_or(0);
     let total_net_pay = payroll1.net_salary + payroll2.net_salary;
-    let total_deductions = payroll1.deductions + payroll2.deductions;
-
-    let payroll_date = NaiveDate::from_ymd_opt(2024, 10, 31).unwrap();
+    let total_deductions = payroll1.deductions.unwrap_or(0) + payroll2.deductions.unwrap_or(0);
 
-    // Debit Salary Expense for gross pay
-    let expense_transaction = transaction_service.create_transaction(
-        salary_expense_account.id,
-        payroll_date,
-        total_gross_pay,
-        "debit",
-        &format!("Payroll expense for {}", payroll_period),
-        Some(&format!("PAYROLL-{}", payroll_period)),
-        Some(1),
-    ).unwrap();
-
-    // Credit Salaries Payable for net pay (what we owe employees)
-    let liability_transaction = transaction_service.create_transaction(
-        payroll_liability_account.id,
-        payroll_date,
-        total_net_pay,
-        "credit",
-        &format!("Salaries payable for {}", payroll_period),
-        Some(&format!("PAYROLL-{}", payroll_period)),
-        Some(1),
+    // Debit Salary Expense for gross pay, credit Salaries Payable for net
+    // pay (what we owe employees) and the withholding for the deductions
+    let payroll_entry = JournalService::post_entry(
+        &mut conn,
+        JournalEntryRequest {
+            entry_date: NaiveDate::from_ymd_opt(2024, 10, 31).unwrap(),
+            description: format!("Payroll for {}", payroll_period),
+            reference: Some(format!("PAYROLL-{}", payroll_period)),
+            lines: vec![
+                JournalLine::debit(salary_expense_account.id, total_gross_pay),
+                JournalLine::credit(payroll_liability_account.id, total_net_pay),
+                JournalLine::credit(withholding_account.id, total_deductions),
+            ],
+        },
+        Some(company.admin.id),
     ).unwrap();
 
     // 5. Verify accounting entries
-    assert_eq!(expense_transaction.amount, total_gross_pay);
-    assert_eq!(liability_transaction.amount, total_net_pay);
+    assert_eq!(payroll_entry.lines[0].amount, total_gross_pay);
+    assert_eq!(payroll_entry.lines[1].amount, total_net_pay);
 
     // 6. Verify account balances
-    let updated_expense_account = account_service.get_account_by_id(salary_expense_account.id).unwrap();
-    let updated_liability_account = account_service.get_account_by_id(payroll_liability_account.id).unwrap();
+    let updated_expense_account = account_service.get_account_by_id(&mut conn, salary_expense_account.id).unwrap().unwrap();
+    let updated_liability_account = account_service.get_account_by_id(&mut conn, payroll_liability_account.id).unwrap().unwrap();
 
     // Expense accounts increase with debits
     assert_eq!(updated_expense_account.balance, total_gross_pay);
-    // Liability accounts increase with credits
-    assert_eq!(updated_liability_account.balance, total_net_pay);
+    // Liability accounts increase with credits, which are subtracted
+    assert_eq!(updated_liability_account.balance, -total_net_pay);
 
     // 7. Verify payroll records exist and are linked
     assert_eq!(payroll1.period, payroll_period);
@@ -500,7 +511,7 @@ fn test_employee_payroll_to_finance_integration() {
 
     // 8. Calculate total expected vs actual
     let expected_total_gross = 50000 + 75000 + 10000; // Alice + Bob + Bob's bonus
-    let expected_total_deductions = 5000 + 7500;       // Alice + Bob deductions
+    let expected_total_deductions = 5000 + 7500;       // Alice + Bob tax
     let expected_total_net = expected_total_gross - expected_total_deductions;
 
     assert_eq!(total_gross_pay, expected_total_gross);
@@ -510,61 +521,60 @@ fn test_employee_payroll_to_finance_integration() {
 
 #[test]
 fn test_complete_business_workflow() {
-    setup_test_db();
+    let company = setup_test_db();
 
     let mut conn = get_connection().expect("Failed to get connection");
 
-    // Initialize all services
+    // Initialize the services
     let dept_service = DepartmentService::new();
     let employee_service = EmployeeService::new();
-    let customer_service = CustomerService::new();
-    let lead_service = LeadService::new();
-    let deal_service = DealService::new();
     let category_service = CategoryService::new();
     let product_service = ProductService::new();
-    let supplier_service = SupplierService::new();
-    let po_service = PurchaseOrderService::new();
     let account_service = AccountService::new();
-    let transaction_service = TransactionService::new();
 
     // 1. Setup company structure
     let sales_dept = dept_service.create_department(
         &mut conn,
-        "Sales Department",
-        Some("Sales and customer relations"),
+        "Sales Department".to_string(),
+        Some("Sales and customer relations".to_string()),
         None,
     ).unwrap();
 
     let procurement_dept = dept_service.create_department(
         &mut conn,
-        "Procurement Department",
-        Some("Purchasing and inventory management"),
+        "Procurement Department".to_string(),
+        Some("Purchasing and inventory management".to_string()),
         None,
     ).unwrap();
 
     let sales_rep = employee_service.create_employee(
         &mut conn,
-        "REP001",
-        "Sarah Sales",
-        Some("sarah@company.com"),
-        None,
-        sales_dept.id,
-        "Senior Sales Representative",
-        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
-        60000,
+        CreateEmployeeRequest {
+            employee_code: Some("REP001".to_string()),
+            name: "Sarah Sales".to_string(),
+            email: Some("sarah@company.com".to_string()),
+            phone: None,
+            department_id: sales_dept.id,
+            position: "Senior Sales Representative".to_string(),
+            hire_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            salary: 60000,
+        },
     ).unwrap();
 
     let procurement_manager = employee_service.create_employee(
         &mut conn,
-        "PROC001",
-        "Paul Procurement",
-        Some("paul@company.com"),
-        None,
-        procurement_dept.id,
-        "Procurement Manager",
-        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
-        70000,
-    ).unwrap();
+        CreateEmployeeRequest {
+            employee_code: Some("PROC001".to_string()),
+            name: "Paul Procurement".to_string(),
+            email: Some("paul@company.com".to_string()),
+            phone: None,
+            department_id: procurement_dept.id,
+            position: "Procurement Manager".to_string(),
+            hire_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            salary: 70000,
+        },
+    ).unwrap();
+    assert_eq!(procurement_manager.department_id, procurement_dept.id);
 
     // 2. Setup products and suppliers
     let tech_category = category_service.create_category(
@@ -573,7 +583,8 @@ fn test_complete_business_workflow() {
         None,
     ).unwrap();
 
-    let supplier = supplier_service.create_supplier(
+    let supplier = SupplierService::create_supplier(
+        &mut conn,
         "TECH001",
         "TechSupplier Corp",
         Some("Tech Sales"),
@@ -598,27 +609,34 @@ fn test_complete_business_workflow() {
     ).unwrap();
 
     // 3. Procurement workflow - order inventory
-    let po = po_service.create_purchase_order(
+    let po = PurchaseOrderService::create_purchase_order(
+        &mut conn,
         supplier.id,
-        NaiveDate::from_ymd_opt(2024, 10, 1).unwrap(),
         Some(NaiveDate::from_ymd_opt(2024, 10, 15).unwrap()),
         Some("Initial stock order"),
-        vec![(product.id, 20, 120000)], // 20 laptops at $1200 each
+        vec![PurchaseOrderItem { product_id: product.id, quantity: 20, unit_cost: 120000 }], // 20 laptops at $1200 each
+        None,
     ).unwrap();
 
-    let approved_po = po_service.approve_purchase_order(po.id, Some(procurement_manager.id)).unwrap();
-    let received_po = po_service.receive_purchase_order(
-        po.id,
-        vec![(product.id, 20)],
-        Some("All items received"),
-        Some(procurement_manager.id),
+    let approved_po = PurchaseOrderService::approve_purchase_order(
+        &mut conn,
+        po.purchase_order.id,
+        company.admin.id,
+        None,
+    ).unwrap();
+    let received_po = PurchaseOrderService::receive_purchase_items(
+        &mut conn,
+        po.purchase_order.id,
+        vec![ReceiveItemData { item_id: po.items[0].purchase_item.id, quantity: 20 }],
+        Some(company.admin.id),
     ).unwrap();
+    assert_eq!(received_po.status, "received");
 
     // 4. Sales workflow - customer inquiry to closed deal
-    let customer = customer_service.create_customer(
+    let customer = CustomerService::create_customer(
         &mut conn,
         "Enterprise Client Ltd.",
-        crate::database::models::CustomerType::Business,
+        CustomerType::Business,
         Some("purchasing@enterprise.com"),
         None,
         None,
@@ -628,35 +646,35 @@ fn test_complete_business_workflow() {
         None,
     ).unwrap();
 
-    let lead = lead_service.create_lead(
+    let lead = LeadService::create_lead(
         &mut conn,
         "Laptop Procurement for Office",
         Some(customer.id),
         "Email Inquiry",
         750000, // 5 laptops @ $1500 = $7500
         Some(NaiveDate::from_ymd_opt(2024, 11, 30).unwrap()),
-        crate::database::models::LeadPriority::High,
+        LeadPriority::High,
         Some(sales_rep.id),
         Some("Customer needs 5 laptops for new office"),
         None,
     ).unwrap();
 
-    let deal = deal_service.create_deal_from_lead(
+    let deal = DealService::create_deal(
         &mut conn,
         lead.id,
         "Enterprise Laptop Order",
         750000,
         Some(NaiveDate::from_ymd_opt(2024, 11, 15).unwrap()),
+        Some(sales_rep.id),
+        None,
         None,
     ).unwrap();
 
-    let closed_deal = deal_service.close_deal(
+    let closed_deal = DealService::update_deal_stage(
         &mut conn,
         deal.id,
-        crate::database::models::DealStage::ClosedWon,
-        Some(NaiveDate::from_ymd_opt(2024, 10, 30).unwrap()),
+        DealStage::ClosedWon,
         Some("Purchase order received"),
-        Some(sales_rep.id),
     ).unwrap();
 
     // 5. Fulfill order from inventory
@@ -668,81 +686,57 @@ fn test_complete_business_workflow() {
         Some("sale"),
         Some(deal.id),
         Some(&format!("Order fulfillment for {}", customer.name)),
-        Some(sales_rep.id),
+        Some(company.admin.id),
     ).unwrap();
 
     // 6. Create accounting entries
-    let revenue_account = account_service.create_account(
-        "4000",
-        "Product Sales Revenue",
-        crate::database::models::AccountType::Revenue,
-        None,
-    ).unwrap();
-
-    let cogs_account = account_service.create_account(
-        "5000",
-        "Cost of Goods Sold",
-        crate::database::models::AccountType::Expense,
-        None,
-    ).unwrap();
-
-    let inventory_account = account_service.create_account(
-        "1300",
-        "Inventory Asset",
-        crate::database::models::AccountType::Asset,
-        None,
-    ).unwrap();
-
-    let receivables_account = account_service.create_account(
-        "1200",
-        "Accounts Receivable",
-        crate::database::models::AccountType::Asset,
-        None,
-    ).unwrap();
+    let create_account = |conn: &mut _, code: &str, name: &str, account_type: &str| {
+        account_service.create_account(
+            conn,
+            CreateAccountRequest {
+                account_code: code.to_string(),
+                account_name: name.to_string(),
+                account_type: account_type.to_string(),
+                parent_id: None,
+            },
+        ).unwrap()
+    };
+    let revenue_account = create_account(&mut conn, "4000", "Product Sales Revenue", "revenue");
+    let cogs_account = create_account(&mut conn, "5000", "Cost of Goods Sold", "expense");
+    let inventory_account = create_account(&mut conn, "1300", "Inventory Asset", "asset");
+    let receivables_account = create_account(&mut conn, "1200", "Accounts Receivable", "asset");
 
     let sale_date = NaiveDate::from_ymd_opt(2024, 10, 30).unwrap();
 
     // Record the sale
-    let _receivable_entry = transaction_service.create_transaction(
-        receivables_account.id,
-        sale_date,
-        750000, // Sale amount
-        "debit",
-        &format!("Sale to {} - Deal #{}", customer.name, deal.id),
-        Some(&format!("SALE-{}", deal.id)),
-        Some(sales_rep.id),
-    ).unwrap();
-
-    let _revenue_entry = transaction_service.create_transaction(
-        revenue_account.id,
-        sale_date,
-        750000,
-        "credit",
-        &format!("Revenue from {} - Deal #{}", customer.name, deal.id),
-        Some(&format!("SALE-{}", deal.id)),
-        Some(sales_rep.id),
+    let _sale_entry = JournalService::post_entry(
+        &mut conn,
+        JournalEntryRequest {
+            entry_date: sale_date,
+            description: format!("Sale to {} - Deal #{}", customer.name, deal.id),
+            reference: Some(format!("SALE-{}", deal.id)),
+            lines: vec![
+                JournalLine::debit(receivables_account.id, 750000), // Sale amount
+                JournalLine::credit(revenue_account.id, 750000),
+            ],
+        },
+        Some(company.admin.id),
     ).unwrap();
 
     // Record cost of goods sold
     let cogs_amount = 5 * 120000; // 5 laptops at $1200 cost each
-    let _cogs_entry = transaction_service.create_transaction(
-        cogs_account.id,
-        sale_date,
-        cogs_amount,
-        "debit",
-        &format!("COGS for sale to {} - Deal #{}", customer.name, deal.id),
-        Some(&format!("COGS-{}", deal.id)),
-        Some(sales_rep.id),
-    ).unwrap();
-
-    let _inventory_reduction = transaction_service.create_transaction(
-        inventory_account.id,
-        sale_date,
-        cogs_amount,
-        "credit",
-        &format!("Inventory reduction for sale - Deal #{}", deal.id),
-        Some(&format!("INV-{}", deal.id)),
-        Some(sales_rep.id),
+    let _cogs_entry = JournalService::post_entry(
+        &mut conn,
+        JournalEntryRequest {
+            entry_date: sale_date,
+            description: format!("COGS for sale to {} - Deal #{}", customer.name, deal.id),
+            reference: Some(format!("COGS-{}", deal.id)),
+            lines: vec![
+                JournalLine::debit(cogs_account.id, cogs_amount),
+                JournalLine::credit(inventory_account.id, cogs_amount),
+            ],
+        },
+        Some(company.admin.id),
     ).unwrap();
 
     // 7. Verify all integrations worked correctly
@@ -752,7 +746,7 @@ fn test_complete_business_workflow() {
 
     // Check deal was closed
     assert_eq!(closed_deal.stage, "closed_won");
-    assert_eq!(closed_deal.value, 750000);
+    assert_eq!(closed_deal.deal_value, 750000);
 
     // Check stock movement
     let pagination = PaginationParams::new(1, 10);
@@ -764,49 +758,47 @@ fn test_complete_business_workflow() {
     assert!(sale_movement.is_some());
 
     // Check accounting balances
-    let updated_revenue = account_service.get_account_by_id(revenue_account.id).unwrap();
-    let updated_cogs = account_service.get_account_by_id(cogs_account.id).unwrap();
-    let updated_receivables = account_service.get_account_by_id(receivables_account.id).unwrap();
+    let updated_revenue = account_service.get_account_by_id(&mut conn, revenue_account.id).unwrap().unwrap();
+    let updated_cogs = account_service.get_account_by_id(&mut conn, cogs_account.id).unwrap().unwrap();
+    let updated_receivables = account_service.get_account_by_id(&mut conn, receivables_account.id).unwrap().unwrap();
 
-    assert_eq!(updated_revenue.balance, 750000);
+    assert_eq!(updated_revenue.balance, -750000);
     assert_eq!(updated_cogs.balance, 600000); // 5 * 120000
     assert_eq!(updated_receivables.balance, 750000);
 
     // Check profit margin
-    let gross_profit = 750000 - 600000; // Revenue - COGS
+    let gross_profit = -updated_revenue.balance - updated_cogs.balance; // Revenue - COGS
     assert_eq!(gross_profit, 150000); // $1500 profit on $7500 sale = 20% margin
 
-    // 8. Verify employee actions are tracked
-    assert_eq!(approved_po.approved_by, Some(procurement_manager.id));
+    // 8. Verify user and employee actions are tracked
+    assert_eq!(approved_po.approved_by, Some(company.admin.id));
     assert_eq!(closed_deal.assigned_to, Some(sales_rep.id));
 
     let receiving_movement = movements.data.iter().find(|m| m.movement_type == "in");
-    assert_eq!(receiving_movement.unwrap().moved_by, Some(procurement_manager.id));
+    assert_eq!(receiving_movement.unwrap().moved_by, Some(company.admin.id));
 
     let shipping_movement = movements.data.iter().find(|m| m.movement_type == "out");
-    assert_eq!(shipping_movement.unwrap().moved_by, Some(sales_rep.id));
+    assert_eq!(shipping_movement.unwrap().moved_by, Some(company.admin.id));
 }
 
 #[test]
 fn test_cross_module_data_consistency() {
-    setup_test_db();
+    let company = setup_test_db();
+    let employee_id = company.employees[0].id;
 
     let mut conn = get_connection().expect("Failed to get connection");
 
     // This test verifies that data remains consistent across module boundaries
 
     // Services
-    let customer_service = CustomerService::new();
-    let lead_service = LeadService::new();
-    let deal_service = DealService::new();
     let product_service = ProductService::new();
     let category_service = CategoryService::new();
 
     // 1. Create interconnected data
-    let customer = customer_service.create_customer(
+    let customer = CustomerService::create_customer(
         &mut conn,
         "Consistency Test Corp",
-        crate::database::models::CustomerType::Business,
+        CustomerType::Business,
         Some("test@consistency.com"),
         None,
         None,
@@ -836,56 +828,57 @@ fn test_cross_module_data_consistency() {
         None,
     ).unwrap();
 
-    let lead = lead_service.create_lead(
+    let lead = LeadService::create_lead(
         &mut conn,
         "Consistency Test Lead",
         Some(customer.id),
         "Test",
         50000,
         None,
-        crate::database::models::LeadPriority::Medium,
-        Some(1),
+        LeadPriority::Medium,
+        Some(employee_id),
         None,
         None,
     ).unwrap();
 
-    let deal = deal_service.create_deal_from_lead(
+    let deal = DealService::create_deal(
         &mut conn,
         lead.id,
         "Consistency Test Deal",
         60000,
         None,
+        Some(employee_id),
+        None,
         None,
     ).unwrap();
 
     // 2. Test cascading updates
 
     // Update customer information
-    let updated_customer = customer_service.update_customer(
+    let updated_customer = CustomerService::update_customer(
         &mut conn,
         customer.id,
         Some("Updated Consistency Corp"),
-        None, // Don't change type
         None, // Don't change email
         None, // Don't change phone
         None, // Don't change address
         None, // Don't change company name
         None, // Don't change tax ID
         Some(50000), // Update credit limit
-        None, // Don't change notes
         None, // Don't change status
+        None, // Don't change notes
     ).unwrap();
 
     assert_eq!(updated_customer.name, "Updated Consistency Corp");
-    assert_eq!(updated_customer.credit_limit, 50000);
+    assert_eq!(updated_customer.credit_limit, Some(50000));
 
     // Verify lead still references correct customer
-    let retrieved_lead = lead_service.get_lead_by_id(&mut conn, lead.id).unwrap().unwrap();
+    let retrieved_lead = LeadService::get_lead_by_id(&mut conn, lead.id).unwrap().unwrap();
     assert_eq!(retrieved_lead.customer_id, Some(customer.id));
 
     // Verify deal still references correct lead
-    let retrieved_deal = deal_service.get_deal_by_id(&mut conn, deal.id).unwrap().unwrap();
-    assert_eq!(retrieved_deal.lead_id, lead.id);
+    let retrieved_deal = DealService::get_deal_by_id(&mut conn, deal.id).unwrap().unwrap();
+    assert_eq!(retrieved_deal.lead_id, Some(lead.id));
 
     // 3. Test stock consistency across multiple operations
     let initial_stock = product.current_stock;
@@ -899,7 +892,7 @@ fn test_cross_module_data_consistency() {
         Some("purchase"),
         None,
         Some("Test restock"),
-        Some(1),
+        Some(company.admin.id),
     ).unwrap();
 
     let _updated2 = product_service.update_stock(
@@ -910,7 +903,7 @@ fn test_cross_module_data_consistency() {
         Some("sale"),
         Some(deal.id),
         Some("Test sale"),
-        Some(1),
+        Some(company.admin.id),
     ).unwrap();
 
     let final_product = product_service.get_product_by_id(product.id).unwrap();
@@ -926,6 +919,7 @@ fn test_cross_module_data_consistency() {
     // Calculate total from movements
     let total_movement: i32 = movements.data.iter().map(|m| m.quantity).sum();
     let expected_total = initial_stock + 10 - 5; // Initial + in - out
+    assert_eq!(total_movement, expected_total);
     assert_eq!(final_product.current_stock, expected_total);
 
     // 5. Test referential integrity
@@ -933,15 +927,15 @@ fn test_cross_module_data_consistency() {
     // Movement should reference our deal
     let sale_movement = movements.data.iter().find(|m|
         m.movement_type == "out" &&
-        m.reference_type.as_ref().map_or(false, |rt| rt == "sale")
+        m.reference_type.as_ref().is_some_and(|rt| rt == "sale")
     ).unwrap();
     assert_eq!(sale_movement.reference_id, Some(deal.id));
 
     // Deal should still exist and reference correct lead
-    let final_deal = deal_service.get_deal_by_id(&mut conn, deal.id).unwrap().unwrap();
-    assert_eq!(final_deal.lead_id, lead.id);
+    let final_deal = DealService::get_deal_by_id(&mut conn, deal.id).unwrap().unwrap();
+    assert_eq!(final_deal.lead_id, Some(lead.id));
 
     // Lead should still reference correct customer
-    let final_lead = lead_service.get_lead_by_id(&mut conn, lead.id).unwrap().unwrap();
+    let final_lead = LeadService::get_lead_by_id(&mut conn, lead.id).unwrap().unwrap();
     assert_eq!(final_lead.customer_id, Some(customer.id));
-}
\ No newline at end of file
+}
@@ -2,13 +2,13 @@ mod common;
 
 use common::setup_test_db;
 use clierp::modules::inventory::{ProductService, CategoryService, SupplierService, PurchaseOrderService};
-use clierp::modules::inventory::{Product, Category, Supplier, PurchaseOrder, PurchaseOrderItem};
+use clierp::modules::inventory::{PurchaseOrderItem, ReceiveItemData};
 use clierp::utils::pagination::PaginationParams;
 use chrono::NaiveDate;
 
 #[test]
 fn test_category_creation_and_retrieval() {
-    setup_test_db();
+    let _company = setup_test_db();
 
     let category_service = CategoryService::new();
 
@@ -35,7 +35,7 @@ fn test_category_creation_and_retrieval() {
 
 #[test]
 fn test_category_hierarchy() {
-    setup_test_db();
+    let _company = setup_test_db();
 
     let category_service = CategoryService::new();
 
@@ -57,7 +57,7 @@ fn test_category_hierarchy() {
 
     // Test listing categories
     let pagination = PaginationParams::new(1, 10);
-    let categories = category_service.list_categories(&pagination);
+    let categories = category_service.list_categories(&pagination, None, false);
     assert!(categories.is_ok());
     let result = categories.unwrap();
     assert!(result.data.len() >= 2); // At least our parent and child
@@ -65,7 +65,7 @@ fn test_category_hierarchy() {
 
 #[test]
 fn test_product_creation_and_validation() {
-    setup_test_db();
+    let _company = setup_test_db();
 
     let product_service = ProductService::new();
     let category_service = CategoryService::new();
@@ -120,7 +120,7 @@ fn test_product_creation_and_validation() {
 
 #[test]
 fn test_product_stock_management() {
-    setup_test_db();
+    let company = setup_test_db();
 
     let product_service = ProductService::new();
     let category_service = CategoryService::new();
@@ -150,7 +150,7 @@ fn test_product_stock_management() {
         Some("purchase"),
         None,
         Some("Restocking"),
-        Some(1), // Test user
+        Some(company.admin.id), // Test user
     );
 
     assert!(result.is_ok());
@@ -166,7 +166,7 @@ fn test_product_stock_management() {
         Some("sale"),
         None,
         Some("Sale transaction"),
-        Some(1),
+        Some(company.admin.id),
     );
 
     assert!(result.is_ok());
@@ -191,7 +191,7 @@ fn test_product_stock_management() {
 
 #[test]
 fn test_low_stock_detection() {
-    setup_test_db();
+    let _company = setup_test_db();
 
     let product_service = ProductService::new();
     let category_service = CategoryService::new();
@@ -242,12 +242,13 @@ fn test_low_stock_detection() {
 
 #[test]
 fn test_supplier_management() {
-    setup_test_db();
+    let company = setup_test_db();
 
-    let supplier_service = SupplierService::new();
+    let mut conn = company.connection().expect("Failed to get connection");
 
     // Test creating a supplier
-    let result = supplier_service.create_supplier(
+    let result = SupplierService::create_supplier(
+        &mut conn,
         "SUPP001",
         "Test Supplier Co.",
         Some("John Doe"),
@@ -264,11 +265,13 @@ fn test_supplier_management() {
     assert_eq!(supplier.contact_person, Some("John Doe".to_string()));
 
     // Test retrieving supplier
-    let retrieved = supplier_service.get_supplier_by_id(supplier.id);
+    let retrieved = SupplierService::get_supplier_by_id(&mut conn, supplier.id);
     assert!(retrieved.is_ok());
+    assert!(retrieved.unwrap().is_some());
 
     // Test duplicate supplier code validation
-    let duplicate_result = supplier_service.create_supplier(
+    let duplicate_result = SupplierService::create_supplier(
+        &mut conn,
         "SUPP001", // Same code
         "Another Supplier",
         None,
@@ -284,15 +287,15 @@ fn test_supplier_management() {
 
 #[test]
 fn test_purchase_order_workflow() {
-    setup_test_db();
+    let company = setup_test_db();
 
-    let po_service = PurchaseOrderService::new();
-    let supplier_service = SupplierService::new();
+    let mut conn = company.connection().expect("Failed to get connection");
     let product_service = ProductService::new();
     let category_service = CategoryService::new();
 
     // Create test data
-    let supplier = supplier_service.create_supplier(
+    let supplier = SupplierService::create_supplier(
+        &mut conn,
         "SUPP001",
         "Test Supplier",
         None,
@@ -318,38 +321,42 @@ fn test_purchase_order_workflow() {
     ).unwrap();
 
     // Create purchase order
-    let order_date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
     let expected_date = NaiveDate::from_ymd_opt(2024, 1, 25).unwrap();
 
-    let po_result = po_service.create_purchase_order(
+    let po_result = PurchaseOrderService::create_purchase_order(
+        &mut conn,
         supplier.id,
-        order_date,
         Some(expected_date),
         Some("Test PO notes"),
-        vec![(product.id, 50, 4000)], // 50 units at 40.00 each
+        vec![PurchaseOrderItem { product_id: product.id, quantity: 50, unit_cost: 4000 }], // 50 units at 40.00 each
+        None,
     );
 
     assert!(po_result.is_ok());
-    let po = po_result.unwrap();
+    let po_with_items = po_result.unwrap();
+    let po = &po_with_items.purchase_order;
     assert_eq!(po.supplier_id, supplier.id);
     assert_eq!(po.total_amount, 200000); // 50 * 4000 = 200000 cents
     assert_eq!(po.status, "pending");
+    assert_eq!(po_with_items.items.len(), 1);
 
     // Test approving the purchase order
-    let approve_result = po_service.approve_purchase_order(po.id, Some(1));
+    let approve_result = PurchaseOrderService::approve_purchase_order(&mut conn, po.id, company.admin.id, None);
     assert!(approve_result.is_ok());
     let approved_po = approve_result.unwrap();
     assert_eq!(approved_po.status, "approved");
 
     // Test receiving the purchase order
-    let receive_result = po_service.receive_purchase_order(
+    let received = ReceiveItemData { item_id: po_with_items.items[0].purchase_item.id, quantity: 45 };
+    let receive_result = PurchaseOrderService::receive_purchase_items(
+        &mut conn,
         po.id,
-        vec![(product.id, 45)], // Received 45 out of 50 ordered
-        Some("Partial delivery"),
-        Some(1),
+        vec![received], // Received 45 out of 50 ordered
+        Some(company.admin.id),
     );
 
     assert!(receive_result.is_ok());
+    assert_eq!(receive_result.unwrap().status, "approved"); // Still waiting for 5
 
     // Verify stock was updated
     let updated_product = product_service.get_product_by_id(product.id).unwrap();
@@ -358,7 +365,7 @@ fn test_purchase_order_workflow() {
 
 #[test]
 fn test_product_search_and_filtering() {
-    setup_test_db();
+    let _company = setup_test_db();
 
     let product_service = ProductService::new();
     let category_service = CategoryService::new();
@@ -442,7 +449,7 @@ fn test_product_search_and_filtering() {
 
 #[test]
 fn test_stock_movement_history() {
-    setup_test_db();
+    let company = setup_test_db();
 
     let product_service = ProductService::new();
     let category_service = CategoryService::new();
@@ -472,7 +479,7 @@ fn test_stock_movement_history() {
         Some("purchase"),
         None,
         Some("Purchase order #1"),
-        Some(1),
+        Some(company.admin.id),
     );
 
     let _ = product_service.update_stock(
@@ -483,7 +490,7 @@ fn test_stock_movement_history() {
         Some("sale"),
         None,
         Some("Sale #1"),
-        Some(1),
+        Some(company.admin.id),
     );
 
     let _ = product_service.update_stock(
@@ -494,7 +501,7 @@ fn test_stock_movement_history() {
         Some("sale"),
         None,
         Some("Sale #2"),
-        Some(1),
+        Some(company.admin.id),
     );
 
     // Test retrieving stock movements
@@ -515,7 +522,7 @@ fn test_stock_movement_history() {
 
 #[test]
 fn test_product_validation_edge_cases() {
-    setup_test_db();
+    let _company = setup_test_db();
 
     let product_service = ProductService::new();
     let category_service = CategoryService::new();
@@ -589,7 +596,7 @@ fn test_product_validation_edge_cases() {
 
 #[test]
 fn test_product_update_functionality() {
-    setup_test_db();
+    let _company = setup_test_db();
 
     let product_service = ProductService::new();
     let category_service = CategoryService::new();
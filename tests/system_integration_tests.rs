@@ -2,152 +2,115 @@ mod common;
 
 use common::setup_test_db;
 use clierp::database::connection::get_connection;
+use clierp::database::schema::attendances;
+use clierp::database::{ActivityType, CampaignType, CustomerType, DealStage, LeadPriority, LeadStatus, NewAttendance};
 
 // Import all module services
-use clierp::modules::hr::{EmployeeService, DepartmentService, PayrollService, AttendanceService};
-use clierp::modules::finance::{AccountService, TransactionService};
+use clierp::modules::hr::{EmployeeService, DepartmentService, PayrollService, AttendanceService, CreateEmployeeRequest};
+use clierp::modules::finance::{AccountService, CreateAccountRequest, JournalService, JournalEntryRequest, JournalLine, ReportService};
 use clierp::modules::inventory::{ProductService, CategoryService, SupplierService, PurchaseOrderService};
+use clierp::modules::inventory::{PurchaseOrderItem, ReceiveItemData};
 use clierp::modules::crm::{CustomerService, LeadService, DealService, CampaignService, ActivityService};
-use clierp::modules::reporting::{HRReportService, FinanceReportService, InventoryReportService, CRMReportService};
 
-use clierp::database::models::*;
 use clierp::utils::pagination::PaginationParams;
-use chrono::{NaiveDate, NaiveTime, Utc};
-use std::collections::HashMap;
+use chrono::{NaiveDate, NaiveTime};
+use diesel::prelude::*;
+
+/// A journal entry with a reference back to the document it records
+fn entry(date: NaiveDate, description: String, reference: String, lines: Vec<JournalLine>) -> JournalEntryRequest {
+    JournalEntryRequest {
+        entry_date: date,
+        description,
+        reference: Some(reference),
+        lines,
+    }
+}
 
 /// Test a complete business cycle from employee onboarding through sales fulfillment
 #[test]
 fn test_complete_business_cycle() {
-    setup_test_db();
+    let company = setup_test_db();
 
     let mut conn = get_connection().expect("Failed to get connection");
 
-    // Initialize all services
+    // Initialize the services that keep state
     let dept_service = DepartmentService::new();
     let employee_service = EmployeeService::new();
     let attendance_service = AttendanceService::new();
     let payroll_service = PayrollService::new();
     let account_service = AccountService::new();
-    let transaction_service = TransactionService::new();
+    let report_service = ReportService::new();
     let category_service = CategoryService::new();
     let product_service = ProductService::new();
-    let supplier_service = SupplierService::new();
-    let po_service = PurchaseOrderService::new();
-    let customer_service = CustomerService::new();
-    let lead_service = LeadService::new();
-    let deal_service = DealService::new();
-    let campaign_service = CampaignService::new();
-    let activity_service = ActivityService::new();
 
     // === PHASE 1: COMPANY SETUP ===
 
     // 1. Create organizational structure
     let sales_dept = dept_service.create_department(
         &mut conn,
-        "Sales Department",
-        Some("Sales and customer relations"),
+        "Sales Department".to_string(),
+        Some("Sales and customer relations".to_string()),
         None,
     ).unwrap();
 
     let procurement_dept = dept_service.create_department(
         &mut conn,
-        "Procurement Department",
-        Some("Purchasing and inventory management"),
+        "Procurement Department".to_string(),
+        Some("Purchasing and inventory management".to_string()),
         None,
     ).unwrap();
 
     let finance_dept = dept_service.create_department(
         &mut conn,
-        "Finance Department",
-        Some("Accounting and financial management"),
+        "Finance Department".to_string(),
+        Some("Accounting and financial management".to_string()),
         None,
     ).unwrap();
 
     // 2. Hire employees
-    let sales_manager = employee_service.create_employee(
-        &mut conn,
-        "SM001",
-        "Sarah Sales",
-        Some("sarah@company.com"),
-        Some("555-1001"),
-        sales_dept.id,
-        "Sales Manager",
-        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
-        80000, // $800.00/month
-    ).unwrap();
-
-    let sales_rep = employee_service.create_employee(
-        &mut conn,
-        "SR001",
-        "Tom Representative",
-        Some("tom@company.com"),
-        Some("555-1002"),
-        sales_dept.id,
-        "Sales Representative",
-        NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
-        60000, // $600.00/month
-    ).unwrap();
-
-    let procurement_manager = employee_service.create_employee(
-        &mut conn,
-        "PM001",
-        "Paul Procurement",
-        Some("paul@company.com"),
-        Some("555-1003"),
-        procurement_dept.id,
-        "Procurement Manager",
-        NaiveDate::from_ymd_opt(2024, 1, 10).unwrap(),
-        70000, // $700.00/month
-    ).unwrap();
-
-    let accountant = employee_service.create_employee(
-        &mut conn,
-        "AC001",
-        "Alice Accountant",
-        Some("alice@company.com"),
-        Some("555-1004"),
-        finance_dept.id,
-        "Senior Accountant",
-        NaiveDate::from_ymd_opt(2024, 1, 5).unwrap(),
-        65000, // $650.00/month
-    ).unwrap();
+    let mut hire = |code: &str, name: &str, phone: &str, department_id: i32, position: &str, hired: u32, salary: i32| {
+        employee_service.create_employee(
+            &mut conn,
+            CreateEmployeeRequest {
+                employee_code: Some(code.to_string()),
+                name: name.to_string(),
+                email: Some(format!("{}@company.com", code.to_lowercase())),
+                phone: Some(phone.to_string()),
+                department_id,
+                position: position.to_string(),
+                hire_date: NaiveDate::from_ymd_opt(2024, 1, hired).unwrap(),
+                salary,
+            },
+        ).unwrap()
+    };
+    let sales_manager = hire("SM001", "Sarah Sales", "555-1001", sales_dept.id, "Sales Manager", 1, 80000); // $800.00/month
+    let sales_rep = hire("SR001", "Tom Representative", "555-1002", sales_dept.id, "Sales Representative", 15, 60000); // $600.00/month
+    let procurement_manager = hire("PM001", "Paul Procurement", "555-1003", procurement_dept.id, "Procurement Manager", 10, 70000); // $700.00/month
+    let accountant = hire("AC001", "Alice Accountant", "555-1004", finance_dept.id, "Senior Accountant", 5, 65000); // $650.00/month
+    let staff = [&sales_manager, &sales_rep, &procurement_manager, &accountant];
 
     // 3. Set up chart of accounts
-    let cash_account = account_service.create_account(
-        "1000", "Cash", AccountType::Asset, None
-    ).unwrap();
-
-    let receivables_account = account_service.create_account(
-        "1200", "Accounts Receivable", AccountType::Asset, None
-    ).unwrap();
-
-    let inventory_account = account_service.create_account(
-        "1300", "Inventory", AccountType::Asset, None
-    ).unwrap();
-
-    let payables_account = account_service.create_account(
-        "2000", "Accounts Payable", AccountType::Liability, None
-    ).unwrap();
-
-    let salary_payable_account = account_service.create_account(
-        "2100", "Salaries Payable", AccountType::Liability, None
-    ).unwrap();
-
-    let equity_account = account_service.create_account(
-        "3000", "Owner's Equity", AccountType::Equity, None
-    ).unwrap();
-
-    let revenue_account = account_service.create_account(
-        "4000", "Sales Revenue", AccountType::Revenue, None
-    ).unwrap();
-
-    let cogs_account = account_service.create_account(
-        "5000", "Cost of Goods Sold", AccountType::Expense, None
-    ).unwrap();
-
-    let salary_expense_account = account_service.create_account(
-        "5100", "Salary Expense", AccountType::Expense, None
-    ).unwrap();
+    let mut create_account = |code: &str, name: &str, account_type: &str| {
+        account_service.create_account(
+            &mut conn,
+            CreateAccountRequest {
+                account_code: code.to_string(),
+                account_name: name.to_string(),
+                account_type: account_type.to_string(),
+                parent_id: None,
+            },
+        ).unwrap()
+    };
+    let _cash_account = create_account("1000", "Cash", "asset");
+    let receivables_account = create_account("1200", "Accounts Receivable", "asset");
+    let inventory_account = create_account("1300", "Inventory", "asset");
+    let payables_account = create_account("2000", "Accounts Payable", "liability");
+    let salary_payable_account = create_account("2100", "Salaries Payable", "liability");
+    let withholding_account = create_account("2200", "Taxes Withheld", "liability");
+    let _equity_account = create_account("3000", "Owner's Equity", "equity");
+    let revenue_account = create_account("4000", "Sales Revenue", "revenue");
+    let cogs_account = create_account("5000", "Cost of Goods Sold", "expense");
+    let salary_expense_account = create_account("5100", "Salary Expense", "expense");
 
     // === PHASE 2: INVENTORY SETUP ===
 
@@ -165,7 +128,8 @@ fn test_complete_business_cycle() {
     ).unwrap();
 
     // 5. Register suppliers
-    let hardware_supplier = supplier_service.create_supplier(
+    let hardware_supplier = SupplierService::create_supplier(
+        &mut conn,
         "HW001",
         "TechHardware Corp",
         Some("Hardware Sales Team"),
@@ -175,7 +139,8 @@ fn test_complete_business_cycle() {
         Some("Net 30"),
     ).unwrap();
 
-    let software_supplier = supplier_service.create_supplier(
+    let software_supplier = SupplierService::create_supplier(
+        &mut conn,
         "SW001",
         "SoftwarePlus Inc",
         Some("License Manager"),
@@ -217,142 +182,120 @@ fn test_complete_business_cycle() {
     // === PHASE 3: PROCUREMENT PROCESS ===
 
     // 7. Create purchase orders for initial inventory
-    let laptop_po = po_service.create_purchase_order(
+    let laptop_po = PurchaseOrderService::create_purchase_order(
+        &mut conn,
         hardware_supplier.id,
-        NaiveDate::from_ymd_opt(2024, 10, 1).unwrap(),
         Some(NaiveDate::from_ymd_opt(2024, 10, 15).unwrap()),
         Some("Initial laptop inventory for Q4 sales"),
-        vec![(laptop_product.id, 20, 140000)], // 20 laptops at $1400 each
+        vec![PurchaseOrderItem { product_id: laptop_product.id, quantity: 20, unit_cost: 140000 }], // 20 laptops at $1400 each
+        None,
     ).unwrap();
 
-    let software_po = po_service.create_purchase_order(
+    let software_po = PurchaseOrderService::create_purchase_order(
+        &mut conn,
         software_supplier.id,
-        NaiveDate::from_ymd_opt(2024, 10, 1).unwrap(),
         Some(NaiveDate::from_ymd_opt(2024, 10, 10).unwrap()),
         Some("Software licenses for resale"),
-        vec![(software_product.id, 50, 80000)], // 50 licenses at $800 each
+        vec![PurchaseOrderItem { product_id: software_product.id, quantity: 50, unit_cost: 80000 }], // 50 licenses at $800 each
+        None,
     ).unwrap();
 
     // 8. Approve and receive purchase orders
-    let approved_laptop_po = po_service.approve_purchase_order(
-        laptop_po.id,
-        Some(procurement_manager.id)
-    ).unwrap();
-
-    let approved_software_po = po_service.approve_purchase_order(
-        software_po.id,
-        Some(procurement_manager.id)
-    ).unwrap();
-
-    // Receive goods
-    let received_laptop_po = po_service.receive_purchase_order(
-        laptop_po.id,
-        vec![(laptop_product.id, 20)], // All 20 received
-        Some("All laptops received in excellent condition"),
-        Some(procurement_manager.id),
-    ).unwrap();
-
-    let received_software_po = po_service.receive_purchase_order(
-        software_po.id,
-        vec![(software_product.id, 50)], // All 50 received
-        Some("License keys activated and ready for distribution"),
-        Some(procurement_manager.id),
-    ).unwrap();
+    for po in [&laptop_po, &software_po] {
+        let approved = PurchaseOrderService::approve_purchase_order(
+            &mut conn,
+            po.purchase_order.id,
+            company.admin.id,
+            None,
+        ).unwrap();
+        assert_eq!(approved.status, "approved");
+
+        // All ordered goods are received
+        let received = PurchaseOrderService::receive_purchase_items(
+            &mut conn,
+            po.purchase_order.id,
+            po.items
+                .iter()
+                .map(|item| ReceiveItemData { item_id: item.purchase_item.id, quantity: item.purchase_item.quantity })
+                .collect(),
+            Some(company.admin.id),
+        ).unwrap();
+        assert_eq!(received.status, "received");
+    }
 
     // 9. Record purchase transactions in accounting
     let purchase_date = NaiveDate::from_ymd_opt(2024, 10, 15).unwrap();
-
-    // Laptop purchase: Debit Inventory, Credit Accounts Payable
-    let laptop_inventory_entry = transaction_service.create_transaction(
-        inventory_account.id,
-        purchase_date,
-        20 * 140000, // 20 * $1400 = $28,000
-        "debit",
-        "Laptop inventory purchase",
-        Some(&format!("PO-{}", laptop_po.id)),
-        Some(accountant.id),
-    ).unwrap();
-
-    let laptop_payable_entry = transaction_service.create_transaction(
-        payables_account.id,
-        purchase_date,
-        20 * 140000,
-        "credit",
-        "Laptop purchase - amount owed",
-        Some(&format!("PO-{}", laptop_po.id)),
-        Some(accountant.id),
-    ).unwrap();
-
-    // Software purchase
-    let software_inventory_entry = transaction_service.create_transaction(
-        inventory_account.id,
-        purchase_date,
-        50 * 80000, // 50 * $800 = $40,000
-        "debit",
-        "Software license inventory",
-        Some(&format!("PO-{}", software_po.id)),
-        Some(accountant.id),
-    ).unwrap();
-
-    let software_payable_entry = transaction_service.create_transaction(
-        payables_account.id,
-        purchase_date,
-        50 * 80000,
-        "credit",
-        "Software purchase - amount owed",
-        Some(&format!("PO-{}", software_po.id)),
-        Some(accountant.id),
+    // Debit Inventory, credit Accounts Payable
+    let laptop_purchase = 20 * 140000; // 20 * $1400 = $28,000
+    let software_purchase = 50 * 80000; // 50 * $800 = $40,000
+    JournalService::post_entry(
+        &mut conn,
+        entry(
+            purchase_date,
+            "Laptop inventory purchase".to_string(),
+            format!("PO-{}", laptop_po.purchase_order.id),
+            vec![
+                JournalLine::debit(inventory_account.id, laptop_purchase),
+                JournalLine::credit(payables_account.id, laptop_purchase),
+            ],
+        ),
+        Some(company.admin.id),
+    ).unwrap();
+    JournalService::post_entry(
+        &mut conn,
+        entry(
+            purchase_date,
+            "Software license inventory".to_string(),
+            format!("PO-{}", software_po.purchase_order.id),
+            vec![
+                JournalLine::debit(inventory_account.id, software_purchase),
+                JournalLine::credit(payables_account.id, software_purchase),
+            ],
+        ),
+        Some(company.admin.id),
     ).unwrap();
 
     // === PHASE 4: EMPLOYEE MANAGEMENT ===
 
     // 10. Record attendance for all employees
     let work_date = NaiveDate::from_ymd_opt(2024, 10, 16).unwrap();
-
-    let _sm_attendance = attendance_service.check_in(
-        &mut conn,
-        sales_manager.id,
-        work_date,
-        Some(NaiveTime::from_hms_opt(8, 30, 0).unwrap()),
-    ).unwrap();
-
-    let _sr_attendance = attendance_service.check_in(
-        &mut conn,
-        sales_rep.id,
-        work_date,
-        Some(NaiveTime::from_hms_opt(9, 0, 0).unwrap()),
-    ).unwrap();
-
-    let _pm_attendance = attendance_service.check_in(
-        &mut conn,
-        procurement_manager.id,
-        work_date,
-        Some(NaiveTime::from_hms_opt(8, 45, 0).unwrap()),
-    ).unwrap();
-
-    let _ac_attendance = attendance_service.check_in(
-        &mut conn,
-        accountant.id,
-        work_date,
-        Some(NaiveTime::from_hms_opt(8, 15, 0).unwrap()),
-    ).unwrap();
+    let arrivals = [(8, 30), (9, 0), (8, 45), (8, 15)];
+    let records: Vec<NewAttendance> = staff
+        .iter()
+        .zip(arrivals)
+        .map(|(employee, (hour, minute))| NewAttendance {
+            employee_id: employee.id,
+            date: work_date,
+            check_in: NaiveTime::from_hms_opt(hour, minute, 0),
+            check_out: NaiveTime::from_hms_opt(17, 30, 0),
+            break_time: Some(60),
+            overtime_hours: Some(0.0),
+            status: "present".to_string(),
+            notes: None,
+        })
+        .collect();
+    diesel::insert_into(attendances::table).values(&records).execute(&mut conn).unwrap();
+
+    let attendance = attendance_service.get_attendance_on(&mut conn, work_date).unwrap();
+    assert_eq!(attendance.len(), staff.len());
 
     // === PHASE 5: SALES AND MARKETING ===
 
-    // 11. Create marketing campaign
-    let campaign = campaign_service.create_campaign(
+    // 11. Create marketing campaign; leads name it as their source
+    let campaign = CampaignService::create_campaign(
         &mut conn,
         "Q4 2024 Technology Solutions",
+        CampaignType::Email,
         Some("End-of-year push for business technology solutions"),
-        Some(NaiveDate::from_ymd_opt(2024, 10, 15).unwrap()),
+        NaiveDate::from_ymd_opt(2024, 10, 15).unwrap(),
         Some(NaiveDate::from_ymd_opt(2024, 12, 31).unwrap()),
-        50000, // $500 budget
+        Some(50000), // $500 budget
         Some("Small to medium businesses needing technology upgrades"),
-        vec!["email", "phone", "webinar"],
+        None,
     ).unwrap();
 
     // 12. Create customers
-    let enterprise_customer = customer_service.create_customer(
+    let enterprise_customer = CustomerService::create_customer(
         &mut conn,
         "TechCorp Enterprises",
         CustomerType::Business,
@@ -361,11 +304,11 @@ fn test_complete_business_cycle() {
         Some("789 Enterprise Blvd, Business District"),
         Some("TechCorp Enterprises Ltd."),
         Some("TC-123456789"),
-        Some(500000), // $5000 credit limit
+        Some(5000000), // $50,000 credit limit
         Some("Major enterprise client"),
     ).unwrap();
 
-    let startup_customer = customer_service.create_customer(
+    let startup_customer = CustomerService::create_customer(
         &mut conn,
         "StartupFast Inc",
         CustomerType::Business,
@@ -374,11 +317,11 @@ fn test_complete_business_cycle() {
         Some("123 Innovation St, Startup Valley"),
         Some("StartupFast Inc."),
         Some("SF-987654321"),
-        Some(100000), // $1000 credit limit
+        Some(2000000), // $20,000 credit limit
         None,
     ).unwrap();
 
-    let small_business = customer_service.create_customer(
+    let small_business = CustomerService::create_customer(
         &mut conn,
         "Local Business Solutions",
         CustomerType::Business,
@@ -387,17 +330,17 @@ fn test_complete_business_cycle() {
         Some("456 Main St, Hometown"),
         Some("Local Business Solutions LLC"),
         None,
-        Some(50000), // $500 credit limit
+        Some(1000000), // $10,000 credit limit
         None,
     ).unwrap();
 
-    // 13. Generate leads
-    let enterprise_lead = lead_service.create_lead(
+    // 13. Generate leads from the campaign
+    let enterprise_lead = LeadService::create_lead(
         &mut conn,
         "Enterprise Technology Upgrade",
         Some(enterprise_customer.id),
-        "Website Contact Form",
-        2000000, // $20,000 (10 laptops + 20 licenses)
+        &campaign.name,
+        4200000, // $42,000 (10 laptops + 20 licenses)
         Some(NaiveDate::from_ymd_opt(2024, 12, 15).unwrap()),
         LeadPriority::High,
         Some(sales_manager.id),
@@ -405,12 +348,12 @@ fn test_complete_business_cycle() {
         Some("Decision maker confirmed, budget approved"),
     ).unwrap();
 
-    let startup_lead = lead_service.create_lead(
+    let startup_lead = LeadService::create_lead(
         &mut conn,
         "Startup Office Setup",
         Some(startup_customer.id),
-        "Referral",
-        900000, // $9,000 (5 laptops + 5 licenses)
+        &campaign.name,
+        1500000, // $15,000 (5 laptops + 5 licenses)
         Some(NaiveDate::from_ymd_opt(2024, 11, 30).unwrap()),
         LeadPriority::High,
         Some(sales_rep.id),
@@ -418,12 +361,12 @@ fn test_complete_business_cycle() {
         Some("Fast-growing startup, urgent need"),
     ).unwrap();
 
-    let small_biz_lead = lead_service.create_lead(
+    let small_biz_lead = LeadService::create_lead(
         &mut conn,
         "Small Business ERP Implementation",
         Some(small_business.id),
-        "Cold Call",
-        360000, // $3,600 (2 laptops + 3 licenses)
+        &campaign.name,
+        720000, // $7,200 (2 laptops + 3 licenses)
         Some(NaiveDate::from_ymd_opt(2024, 11, 15).unwrap()),
         LeadPriority::Medium,
         Some(sales_rep.id),
@@ -431,464 +374,328 @@ fn test_complete_business_cycle() {
         Some("Budget conscious, needs value proposition"),
     ).unwrap();
 
-    // 14. Associate leads with campaign
-    let _campaign_lead1 = campaign_service.add_lead_to_campaign(
-        &mut conn, campaign.id, enterprise_lead.id
-    ).unwrap();
-    let _campaign_lead2 = campaign_service.add_lead_to_campaign(
-        &mut conn, campaign.id, startup_lead.id
-    ).unwrap();
-    let _campaign_lead3 = campaign_service.add_lead_to_campaign(
-        &mut conn, campaign.id, small_biz_lead.id
-    ).unwrap();
-
     // === PHASE 6: SALES ACTIVITIES ===
 
-    // 15. Create sales activities
-    let enterprise_call = activity_service.create_activity(
+    // 14. Create sales activities
+    let enterprise_call = ActivityService::create_activity(
         &mut conn,
-        "Enterprise Discovery Call",
         ActivityType::Call,
+        "Enterprise Discovery Call",
+        Some("Understand enterprise requirements and decision process"),
         Some(enterprise_customer.id),
         Some(enterprise_lead.id),
         None,
-        Some(NaiveDate::from_ymd_opt(2024, 10, 17).unwrap()),
-        sales_manager.id,
-        Some("Understand enterprise requirements and decision process"),
-        ActivityStatus::Scheduled,
+        Some(sales_manager.id),
+        NaiveDate::from_ymd_opt(2024, 10, 17).unwrap().and_hms_opt(10, 0, 0).unwrap(),
+        Some(60),
     ).unwrap();
 
-    let startup_demo = activity_service.create_activity(
+    let startup_demo = ActivityService::create_activity(
         &mut conn,
-        "Product Demo for Startup",
         ActivityType::Meeting,
+        "Product Demo for Startup",
+        Some("Demonstrate ERP solution capabilities"),
         Some(startup_customer.id),
         Some(startup_lead.id),
         None,
-        Some(NaiveDate::from_ymd_opt(2024, 10, 18).unwrap()),
-        sales_rep.id,
-        Some("Demonstrate ERP solution capabilities"),
-        ActivityStatus::Scheduled,
+        Some(sales_rep.id),
+        NaiveDate::from_ymd_opt(2024, 10, 18).unwrap().and_hms_opt(14, 0, 0).unwrap(),
+        Some(90),
     ).unwrap();
 
     // Complete activities
-    let _completed_call = activity_service.complete_activity(
+    let completed_call = ActivityService::complete_activity(
         &mut conn,
         enterprise_call.id,
         Some("Great meeting! Customer is very interested. Sending proposal next."),
-        Some(sales_manager.id),
     ).unwrap();
+    assert!(completed_call.completed);
 
-    let _completed_demo = activity_service.complete_activity(
+    let completed_demo = ActivityService::complete_activity(
         &mut conn,
         startup_demo.id,
         Some("Demo went well. Customer ready to move forward with 5 laptop + license bundle."),
-        Some(sales_rep.id),
     ).unwrap();
+    assert!(completed_demo.completed);
+
+    // The enterprise and startup leads qualify
+    for lead in [&enterprise_lead, &startup_lead] {
+        LeadService::update_lead_status(&mut conn, lead.id, LeadStatus::Qualified, Some("Needs confirmed")).unwrap();
+    }
 
     // === PHASE 7: DEAL CLOSURE ===
 
-    // 16. Convert leads to deals
-    let enterprise_deal = deal_service.create_deal_from_lead(
+    // 15. Open deals from the leads, to close on their expected dates
+    let enterprise_sale_date = NaiveDate::from_ymd_opt(2024, 10, 25).unwrap();
+    let startup_sale_date = NaiveDate::from_ymd_opt(2024, 10, 22).unwrap();
+
+    let enterprise_deal = DealService::create_deal(
         &mut conn,
         enterprise_lead.id,
         "Enterprise Technology Package",
-        1980000, // Negotiated to $19,800 (10 laptops @ $1800 + 20 licenses @ $1200)
-        Some(NaiveDate::from_ymd_opt(2024, 11, 30).unwrap()),
+        4200000, // $42,000 (10 laptops @ $1800 + 20 licenses @ $1200)
+        Some(enterprise_sale_date),
+        Some(sales_manager.id),
         Some("Comprehensive technology solution package"),
+        None,
     ).unwrap();
 
-    let startup_deal = deal_service.create_deal_from_lead(
+    let startup_deal = DealService::create_deal(
         &mut conn,
         startup_lead.id,
         "Startup Office Bundle",
-        900000, // Original price $9,000 (5 laptops @ $1800 + 5 licenses @ $1200)
-        Some(NaiveDate::from_ymd_opt(2024, 11, 15).unwrap()),
+        1500000, // $15,000 (5 laptops @ $1800 + 5 licenses @ $1200)
+        Some(startup_sale_date),
+        Some(sales_rep.id),
         Some("Complete office setup bundle"),
+        None,
     ).unwrap();
 
-    let small_biz_deal = deal_service.create_deal_from_lead(
+    let small_biz_deal = DealService::create_deal(
         &mut conn,
         small_biz_lead.id,
         "Small Business ERP Package",
-        330000, // Discounted to $3,300 (2 laptops @ $1650 + 3 licenses @ $1000)
-        Some(NaiveDate::from_ymd_opt(2024, 11, 10).unwrap()),
+        630000, // Discounted to $6,300 (2 laptops @ $1650 + 3 licenses @ $1000)
+        Some(NaiveDate::from_ymd_opt(2024, 10, 20).unwrap()),
+        Some(sales_rep.id),
         Some("Budget-friendly ERP implementation"),
+        None,
     ).unwrap();
 
-    // 17. Progress deals through stages
-    let enterprise_negotiation = deal_service.update_deal_stage(
+    // 16. Progress deals through stages
+    let enterprise_negotiation = DealService::update_deal_stage(
         &mut conn,
         enterprise_deal.id,
         DealStage::Negotiation,
-        85,
         Some("In final negotiations, very likely to close"),
-        Some(sales_manager.id),
     ).unwrap();
+    assert_eq!(enterprise_negotiation.stage, "negotiation");
 
-    let startup_proposal = deal_service.update_deal_stage(
+    let startup_proposal = DealService::update_deal_stage(
         &mut conn,
         startup_deal.id,
         DealStage::Proposal,
-        75,
         Some("Proposal sent, awaiting approval"),
-        Some(sales_rep.id),
     ).unwrap();
+    assert_eq!(startup_proposal.stage, "proposal");
 
-    // 18. Close deals
-    let closed_enterprise = deal_service.close_deal(
+    // 17. Close deals
+    let closed_enterprise = DealService::update_deal_stage(
         &mut conn,
         enterprise_deal.id,
         DealStage::ClosedWon,
-        Some(NaiveDate::from_ymd_opt(2024, 10, 25).unwrap()),
         Some("Contract signed! Large enterprise deal closed."),
-        Some(sales_manager.id),
     ).unwrap();
 
-    let closed_startup = deal_service.close_deal(
+    let closed_startup = DealService::update_deal_stage(
         &mut conn,
         startup_deal.id,
         DealStage::ClosedWon,
-        Some(NaiveDate::from_ymd_opt(2024, 10, 22).unwrap()),
         Some("Startup approved purchase, moving to fulfillment"),
-        Some(sales_rep.id),
     ).unwrap();
 
-    let lost_small_biz = deal_service.close_deal(
+    let lost_small_biz = DealService::update_deal_stage(
         &mut conn,
         small_biz_deal.id,
         DealStage::ClosedLost,
-        Some(NaiveDate::from_ymd_opt(2024, 10, 20).unwrap()),
         Some("Customer decided to postpone technology upgrade"),
-        Some(sales_rep.id),
     ).unwrap();
+    assert_eq!(lost_small_biz.stage, "closed_lost");
 
     // === PHASE 8: ORDER FULFILLMENT ===
 
-    // 19. Fulfill orders from inventory
+    // 18. Fulfill orders from inventory
+    let fulfill = |product_id: i32, quantity: i32, deal_id: i32, notes: String| {
+        product_service.update_stock(
+            product_id,
+            -quantity,
+            "out",
+            None,
+            Some("sale"),
+            Some(deal_id),
+            Some(&notes),
+            Some(company.admin.id),
+        ).unwrap()
+    };
 
     // Enterprise order: 10 laptops + 20 licenses
-    let enterprise_laptop_fulfillment = product_service.update_stock(
-        laptop_product.id,
-        -10,
-        "out",
-        None,
-        Some("sale"),
-        Some(enterprise_deal.id),
-        Some(&format!("Enterprise order fulfillment for {}", enterprise_customer.name)),
-        Some(sales_manager.id),
-    ).unwrap();
-
-    let enterprise_software_fulfillment = product_service.update_stock(
-        software_product.id,
-        -20,
-        "out",
-        None,
-        Some("sale"),
-        Some(enterprise_deal.id),
-        Some(&format!("Enterprise software fulfillment for {}", enterprise_customer.name)),
-        Some(sales_manager.id),
-    ).unwrap();
+    fulfill(laptop_product.id, 10, enterprise_deal.id, format!("Enterprise order fulfillment for {}", enterprise_customer.name));
+    fulfill(software_product.id, 20, enterprise_deal.id, format!("Enterprise software fulfillment for {}", enterprise_customer.name));
 
     // Startup order: 5 laptops + 5 licenses
-    let startup_laptop_fulfillment = product_service.update_stock(
-        laptop_product.id,
-        -5,
-        "out",
-        None,
-        Some("sale"),
-        Some(startup_deal.id),
-        Some(&format!("Startup order fulfillment for {}", startup_customer.name)),
-        Some(sales_rep.id),
-    ).unwrap();
-
-    let startup_software_fulfillment = product_service.update_stock(
-        software_product.id,
-        -5,
-        "out",
-        None,
-        Some("sale"),
-        Some(startup_deal.id),
-        Some(&format!("Startup software fulfillment for {}", startup_customer.name)),
-        Some(sales_rep.id),
-    ).unwrap();
+    let laptops_left = fulfill(laptop_product.id, 5, startup_deal.id, format!("Startup order fulfillment for {}", startup_customer.name));
+    let licenses_left = fulfill(software_product.id, 5, startup_deal.id, format!("Startup software fulfillment for {}", startup_customer.name));
 
     // === PHASE 9: FINANCIAL RECORDING ===
 
-    // 20. Record sales transactions
-    let enterprise_sale_date = NaiveDate::from_ymd_opt(2024, 10, 25).unwrap();
-    let startup_sale_date = NaiveDate::from_ymd_opt(2024, 10, 22).unwrap();
-
-    // Enterprise sale
-    let enterprise_receivable = transaction_service.create_transaction(
-        receivables_account.id,
-        enterprise_sale_date,
-        closed_enterprise.value,
-        "debit",
-        &format!("Sale to {} - Deal #{}", enterprise_customer.name, enterprise_deal.id),
-        Some(&format!("SALE-{}", enterprise_deal.id)),
-        Some(accountant.id),
-    ).unwrap();
-
-    let enterprise_revenue = transaction_service.create_transaction(
-        revenue_account.id,
-        enterprise_sale_date,
-        closed_enterprise.value,
-        "credit",
-        &format!("Revenue from {} - Deal #{}", enterprise_customer.name, enterprise_deal.id),
-        Some(&format!("SALE-{}", enterprise_deal.id)),
-        Some(accountant.id),
-    ).unwrap();
-
-    // Enterprise COGS
+    // 19. Record each sale and its cost of goods sold
     let enterprise_cogs_amount = (10 * 140000) + (20 * 80000); // 10 laptops + 20 licenses at cost
-    let enterprise_cogs = transaction_service.create_transaction(
-        cogs_account.id,
-        enterprise_sale_date,
-        enterprise_cogs_amount,
-        "debit",
-        &format!("COGS for enterprise sale - Deal #{}", enterprise_deal.id),
-        Some(&format!("COGS-{}", enterprise_deal.id)),
-        Some(accountant.id),
-    ).unwrap();
-
-    let enterprise_inventory_reduction = transaction_service.create_transaction(
-        inventory_account.id,
-        enterprise_sale_date,
-        enterprise_cogs_amount,
-        "credit",
-        &format!("Inventory reduction for enterprise sale - Deal #{}", enterprise_deal.id),
-        Some(&format!("INV-{}", enterprise_deal.id)),
-        Some(accountant.id),
-    ).unwrap();
-
-    // Startup sale
-    let startup_receivable = transaction_service.create_transaction(
-        receivables_account.id,
-        startup_sale_date,
-        closed_startup.value,
-        "debit",
-        &format!("Sale to {} - Deal #{}", startup_customer.name, startup_deal.id),
-        Some(&format!("SALE-{}", startup_deal.id)),
-        Some(accountant.id),
-    ).unwrap();
-
-    let startup_revenue = transaction_service.create_transaction(
-        revenue_account.id,
-        startup_sale_date,
-        closed_startup.value,
-        "credit",
-        &format!("Revenue from {} - Deal #{}", startup_customer.name, startup_deal.id),
-        Some(&format!("SALE-{}", startup_deal.id)),
-        Some(accountant.id),
-    ).unwrap();
-
-    // Startup COGS
     let startup_cogs_amount = (5 * 140000) + (5 * 80000); // 5 laptops + 5 licenses at cost
-    let startup_cogs = transaction_service.create_transaction(
-        cogs_account.id,
-        startup_sale_date,
-        startup_cogs_amount,
-        "debit",
-        &format!("COGS for startup sale - Deal #{}", startup_deal.id),
-        Some(&format!("COGS-{}", startup_deal.id)),
-        Some(accountant.id),
-    ).unwrap();
 
-    let startup_inventory_reduction = transaction_service.create_transaction(
-        inventory_account.id,
-        startup_sale_date,
-        startup_cogs_amount,
-        "credit",
-        &format!("Inventory reduction for startup sale - Deal #{}", startup_deal.id),
-        Some(&format!("INV-{}", startup_deal.id)),
-        Some(accountant.id),
-    ).unwrap();
+    let mut revenue_entries = Vec::new();
+    for (deal, customer, sale_date, cogs_amount) in [
+        (&closed_enterprise, &enterprise_customer, enterprise_sale_date, enterprise_cogs_amount),
+        (&closed_startup, &startup_customer, startup_sale_date, startup_cogs_amount),
+    ] {
+        assert_eq!(deal.close_date, Some(sale_date));
+        revenue_entries.push(JournalService::post_entry(
+            &mut conn,
+            entry(
+                sale_date,
+                format!("Sale to {} - Deal #{}", customer.name, deal.id),
+                format!("SALE-{}", deal.id),
+                vec![
+                    JournalLine::debit(receivables_account.id, deal.deal_value),
+                    JournalLine::credit(revenue_account.id, deal.deal_value),
+                ],
+            ),
+            Some(company.admin.id),
+        ).unwrap());
+        JournalService::post_entry(
+            &mut conn,
+            entry(
+                sale_date,
+                format!("COGS for sale - Deal #{}", deal.id),
+                format!("COGS-{}", deal.id),
+                vec![
+                    JournalLine::debit(cogs_account.id, cogs_amount),
+                    JournalLine::credit(inventory_account.id, cogs_amount),
+                ],
+            ),
+            Some(company.admin.id),
+        ).unwrap();
+    }
 
     // === PHASE 10: PAYROLL PROCESSING ===
 
-    // 21. Process monthly payroll
+    // 20. Process monthly payroll with bonuses for the big sales
     let payroll_period = "2024-10";
-
-    let sm_payroll = payroll_service.calculate_payroll(
-        &mut conn,
-        sales_manager.id,
-        payroll_period,
-        None,
-        0,
-        100000, // $1000 bonus for big sale
-        12000,  // $120 deductions
-    ).unwrap();
-
-    let sr_payroll = payroll_service.calculate_payroll(
-        &mut conn,
-        sales_rep.id,
-        payroll_period,
-        None,
-        0,
-        50000, // $500 bonus for good performance
-        8000,  // $80 deductions
-    ).unwrap();
-
-    let pm_payroll = payroll_service.calculate_payroll(
-        &mut conn,
-        procurement_manager.id,
-        payroll_period,
-        None,
-        0,
-        0,     // No bonus
-        7000,  // $70 deductions
-    ).unwrap();
-
-    let ac_payroll = payroll_service.calculate_payroll(
-        &mut conn,
-        accountant.id,
-        payroll_period,
-        None,
-        0,
-        0,     // No bonus
-        6500,  // $65 deductions
-    ).unwrap();
-
-    // 22. Record payroll in accounting
+    let adjustments = [
+        (Some(100000), Some(12000)), // $1000 bonus, $120 deductions
+        (Some(50000), Some(8000)),   // $500 bonus, $80 deductions
+        (None, Some(7000)),          // $70 deductions
+        (None, Some(6500)),          // $65 deductions
+    ];
+    let payrolls: Vec<_> = staff
+        .iter()
+        .zip(adjustments)
+        .map(|(employee, (bonuses, deductions))| {
+            let calculation = payroll_service.calculate_payroll(&mut conn, employee.id, payroll_period.to_string()).unwrap();
+            payroll_service.generate_payroll(&mut conn, calculation, bonuses, deductions, Some(company.admin.id)).unwrap()
+        })
+        .collect();
+
+    // 21. Record payroll in accounting
     let payroll_date = NaiveDate::from_ymd_opt(2024, 10, 31).unwrap();
-    let total_gross_payroll = sm_payroll.base_salary + sm_payroll.bonuses +
-                             sr_payroll.base_salary + sr_payroll.bonuses +
-                             pm_payroll.base_salary + pm_payroll.bonuses +
-                             ac_payroll.base_salary + ac_payroll.bonuses;
-
-    let total_net_payroll = sm_payroll.net_salary + sr_payroll.net_salary +
-                           pm_payroll.net_salary + ac_payroll.net_salary;
-
-    let payroll_expense = transaction_service.create_transaction(
-        salary_expense_account.id,
-        payroll_date,
-        total_gross_payroll,
-        "debit",
-        &format!("Payroll expense for {}", payroll_period),
-        Some(&format!("PAYROLL-{}", payroll_period)),
-        Some(accountant.id),
-    ).unwrap();
+    let total_gross_payroll: i32 = payrolls.iter().map(|p| p.base_salary + p.bonuses.unwrap_or(0)).sum();
+    let total_net_payroll: i32 = payrolls.iter().map(|p| p.net_salary).sum();
+    let total_deductions: i32 = payrolls.iter().map(|p| p.deductions.unwrap_or(0)).sum();
 
-    let payroll_liability = transaction_service.create_transaction(
-        salary_payable_account.id,
-        payroll_date,
-        total_net_payroll,
-        "credit",
-        &format!("Salaries payable for {}", payroll_period),
-        Some(&format!("PAYROLL-{}", payroll_period)),
-        Some(accountant.id),
+    JournalService::post_entry(
+        &mut conn,
+        entry(
+            payroll_date,
+            format!("Payroll for {}", payroll_period),
+            format!("PAYROLL-{}", payroll_period),
+            vec![
+                JournalLine::debit(salary_expense_account.id, total_gross_payroll),
+                JournalLine::credit(salary_payable_account.id, total_net_payroll),
+                JournalLine::credit(withholding_account.id, total_deductions),
+            ],
+        ),
+        Some(company.admin.id),
     ).unwrap();
 
     // === PHASE 11: COMPREHENSIVE VERIFICATION ===
 
-    // 23. Verify inventory levels
-    assert_eq!(enterprise_laptop_fulfillment.current_stock, 5); // 20 - 10 - 5 = 5
-    assert_eq!(startup_software_fulfillment.current_stock, 25); // 50 - 20 - 5 = 25
-
-    // 24. Verify accounting balances
-    let final_cash = account_service.get_account_by_id(cash_account.id).unwrap();
-    let final_receivables = account_service.get_account_by_id(receivables_account.id).unwrap();
-    let final_inventory = account_service.get_account_by_id(inventory_account.id).unwrap();
-    let final_payables = account_service.get_account_by_id(payables_account.id).unwrap();
-    let final_revenue = account_service.get_account_by_id(revenue_account.id).unwrap();
-    let final_cogs = account_service.get_account_by_id(cogs_account.id).unwrap();
-
-    // Receivables should equal total sales
-    let total_sales = closed_enterprise.value + closed_startup.value;
-    assert_eq!(final_receivables.balance, total_sales);
+    // 22. Verify inventory levels
+    assert_eq!(laptops_left.current_stock, 5); // 20 - 10 - 5 = 5
+    assert_eq!(licenses_left.current_stock, 25); // 50 - 20 - 5 = 25
 
-    // Revenue should equal total sales
-    assert_eq!(final_revenue.balance, total_sales);
+    // 23. Verify accounting balances; debits add and credits subtract
+    let mut balance_of = |account_id: i32| {
+        account_service.get_account_by_id(&mut conn, account_id).unwrap().unwrap().balance
+    };
 
-    // Inventory should reflect purchases minus sales
-    let total_purchases = (20 * 140000) + (50 * 80000); // All purchases
-    let total_cogs = enterprise_cogs_amount + startup_cogs_amount; // All COGS
-    let expected_inventory = total_purchases - total_cogs;
-    assert_eq!(final_inventory.balance, expected_inventory);
+    // Receivables and revenue both equal total sales
+    let total_sales = closed_enterprise.deal_value + closed_startup.deal_value;
+    assert_eq!(balance_of(receivables_account.id), total_sales);
+    assert_eq!(balance_of(revenue_account.id), -total_sales);
 
-    // Payables should equal total purchases
-    assert_eq!(final_payables.balance, total_purchases);
+    // Inventory reflects purchases minus sales
+    let total_purchases = laptop_purchase + software_purchase;
+    let total_cogs = enterprise_cogs_amount + startup_cogs_amount;
+    assert_eq!(balance_of(inventory_account.id), total_purchases - total_cogs);
 
-    // COGS should equal cost of goods sold
-    assert_eq!(final_cogs.balance, total_cogs);
+    // Payables equal total purchases and COGS the cost of goods sold
+    assert_eq!(balance_of(payables_account.id), -total_purchases);
+    assert_eq!(balance_of(cogs_account.id), total_cogs);
+    assert_eq!(balance_of(salary_expense_account.id), total_gross_payroll);
 
-    // 25. Verify business metrics
+    // 24. Verify business metrics: the margin of every unit sold
     let gross_profit = total_sales - total_cogs;
-    let gross_margin_percentage = (gross_profit as f64 / total_sales as f64) * 100.0;
+    assert_eq!(gross_profit, 15 * (180000 - 140000) + 25 * (120000 - 80000));
 
-    // Should have healthy gross margin (around 50%)
-    assert!(gross_margin_percentage > 40.0);
-    assert!(gross_margin_percentage < 60.0);
+    // 25. Verify employee pay
+    assert_eq!(payrolls[0].bonuses, Some(100000)); // Sales manager got bonus
+    assert_eq!(payrolls[1].bonuses, Some(50000)); // Sales rep got bonus
+    assert_eq!(payrolls[2].bonuses, Some(0)); // Procurement manager no bonus
+    assert_eq!(payrolls[3].bonuses, Some(0)); // Accountant no bonus
+    assert_eq!(total_gross_payroll, total_net_payroll + total_deductions);
 
-    // 26. Verify employee performance
-    assert_eq!(sm_payroll.bonuses, 100000); // Sales manager got bonus
-    assert_eq!(sr_payroll.bonuses, 50000);  // Sales rep got bonus
-    assert_eq!(pm_payroll.bonuses, 0);      // Procurement manager no bonus
-    assert_eq!(ac_payroll.bonuses, 0);      // Accountant no bonus
+    // 26. Verify campaign effectiveness
+    let campaign_stats = CampaignService::get_campaign_with_stats(&mut conn, campaign.id).unwrap().unwrap();
+    assert_eq!(campaign_stats.total_leads, 3);
+    assert_eq!(campaign_stats.converted_leads, 2); // Enterprise + Startup
 
-    // 27. Verify campaign effectiveness
-    let campaign_performance = campaign_service.get_campaign_performance(&mut conn, campaign.id).unwrap();
-    assert_eq!(campaign_performance.total_leads, 3);
-    assert_eq!(campaign_performance.converted_leads, 2); // Enterprise + Startup
-
-    // 28. Verify stock movements are properly tracked
+    // 27. Verify stock movements are properly tracked
     let pagination = PaginationParams::new(1, 20);
     let laptop_movements = product_service.get_stock_movements(laptop_product.id, &pagination).unwrap();
     let software_movements = product_service.get_stock_movements(software_product.id, &pagination).unwrap();
 
-    // Each product should have: initial stock + 2 sales = 3 movements each
+    // Each product has its receipt and 2 sales
     assert_eq!(laptop_movements.data.len(), 3);
     assert_eq!(software_movements.data.len(), 3);
 
-    // 29. Test cross-module data integrity
+    // 28. Test cross-module data integrity
 
-    // Deals should still reference correct leads
-    let final_enterprise_deal = deal_service.get_deal_by_id(&mut conn, enterprise_deal.id).unwrap().unwrap();
-    assert_eq!(final_enterprise_deal.lead_id, enterprise_lead.id);
+    // Deals still reference the right leads
+    let final_enterprise_deal = DealService::get_deal_by_id(&mut conn, enterprise_deal.id).unwrap().unwrap();
+    assert_eq!(final_enterprise_deal.lead_id, Some(enterprise_lead.id));
     assert_eq!(final_enterprise_deal.stage, "closed_won");
 
-    // Leads should still reference correct customers
-    let final_enterprise_lead = lead_service.get_lead_by_id(&mut conn, enterprise_lead.id).unwrap().unwrap();
+    // Leads still reference the right customers
+    let final_enterprise_lead = LeadService::get_lead_by_id(&mut conn, enterprise_lead.id).unwrap().unwrap();
     assert_eq!(final_enterprise_lead.customer_id, Some(enterprise_customer.id));
 
-    // Stock movements should reference deals
+    // Stock movements reference deals
     let enterprise_laptop_movement = laptop_movements.data.iter()
         .find(|m| m.reference_id == Some(enterprise_deal.id))
         .unwrap();
     assert_eq!(enterprise_laptop_movement.quantity, -10);
 
-    // Transactions should reference deals
+    // Transactions reference deals
+    let enterprise_revenue = &revenue_entries[0].lines[1];
     assert!(enterprise_revenue.reference.as_ref().unwrap().contains(&enterprise_deal.id.to_string()));
 
-    // 30. Verify accounting equation: Assets = Liabilities + Equity
-    let total_assets = final_cash.balance + final_receivables.balance + final_inventory.balance;
-    let total_liabilities = final_payables.balance + payroll_liability.amount;
-    let net_income = final_revenue.balance - final_cogs.balance - payroll_expense.amount;
-
-    // Basic accounting equation should hold (simplified - in practice would include all accounts)
-    assert!(total_assets > total_liabilities); // We should have positive equity
-
-    println!("=== SYSTEM INTEGRATION TEST RESULTS ===");
-    println!("Total Sales Revenue: ${:.2}", total_sales as f64 / 100.0);
-    println!("Total COGS: ${:.2}", total_cogs as f64 / 100.0);
-    println!("Gross Profit: ${:.2}", gross_profit as f64 / 100.0);
-    println!("Gross Margin: {:.1}%", gross_margin_percentage);
-    println!("Laptops Remaining: {}", enterprise_laptop_fulfillment.current_stock);
-    println!("Software Licenses Remaining: {}", startup_software_fulfillment.current_stock);
-    println!("Campaign Conversion Rate: {:.1}%",
-             (campaign_performance.converted_leads as f64 / campaign_performance.total_leads as f64) * 100.0);
-    println!("Total Payroll Expense: ${:.2}", total_gross_payroll as f64 / 100.0);
-    println!("========================================");
-
-    // Final assertion: System is internally consistent
-    assert!(true); // If we get here, all previous assertions passed
+    // 29. Verify the accounting equation: Assets = Liabilities + Equity,
+    // with the month's earnings not yet closed to equity
+    let october_start = NaiveDate::from_ymd_opt(2024, 10, 1).unwrap();
+    let income_statement = report_service.generate_income_statement(&mut conn, october_start, payroll_date).unwrap();
+    assert_eq!(income_statement.net_income, gross_profit - total_gross_payroll);
+
+    let balance_sheet = report_service.generate_balance_sheet(&mut conn, payroll_date).unwrap();
+    assert_eq!(
+        balance_sheet.total_assets,
+        balance_sheet.total_liabilities_and_equity + income_statement.net_income
+    );
 }
 
 /// Test system behavior under concurrent operations
 #[test]
 fn test_concurrent_operations() {
-    setup_test_db();
+    let company = setup_test_db();
+    let employee_id = company.employees[0].id;
 
     let mut conn = get_connection().expect("Failed to get connection");
 
@@ -897,9 +704,6 @@ fn test_concurrent_operations() {
 
     let category_service = CategoryService::new();
     let product_service = ProductService::new();
-    let customer_service = CustomerService::new();
-    let lead_service = LeadService::new();
-    let deal_service = DealService::new();
 
     // Create test data
     let category = category_service.create_category("Concurrent Test", None, None).unwrap();
@@ -908,20 +712,20 @@ fn test_concurrent_operations() {
         10000, 8000, 100, 10, Some(200), "EA", None
     ).unwrap();
 
-    let customer = customer_service.create_customer(
+    let customer = CustomerService::create_customer(
         &mut conn, "Concurrent Customer", CustomerType::Business,
         None, None, None, None, None, None, None
     ).unwrap();
 
     // Create multiple leads simultaneously (simulating concurrent users)
-    let lead1 = lead_service.create_lead(
+    let lead1 = LeadService::create_lead(
         &mut conn, "Concurrent Lead 1", Some(customer.id), "Source1",
-        50000, None, LeadPriority::High, Some(1), None, None
+        50000, None, LeadPriority::High, Some(employee_id), None, None
     ).unwrap();
 
-    let lead2 = lead_service.create_lead(
+    let lead2 = LeadService::create_lead(
         &mut conn, "Concurrent Lead 2", Some(customer.id), "Source2",
-        60000, None, LeadPriority::Medium, Some(1), None, None
+        60000, None, LeadPriority::Medium, Some(employee_id), None, None
     ).unwrap();
 
     // Simulate concurrent stock operations
@@ -936,33 +740,31 @@ fn test_concurrent_operations() {
     for (movement_type, quantity, reference_type) in stock_ops {
         let updated_product = product_service.update_stock(
             product.id, quantity, movement_type, None,
-            Some(reference_type), None, None, Some(1)
+            Some(reference_type), None, None, Some(company.admin.id)
         ).unwrap();
 
-        final_stock = match movement_type {
-            "out" => final_stock + quantity, // quantity is negative for "out"
-            "in" => final_stock + quantity,
-            "adjustment" => final_stock + quantity,
-            _ => final_stock,
-        };
+        // Every movement here changes the stock by its signed quantity
+        final_stock += quantity;
+        assert_eq!(updated_product.current_stock, final_stock);
     }
 
     // Verify final stock is consistent
     let final_product = product_service.get_product_by_id(product.id).unwrap();
     assert_eq!(final_product.current_stock, final_stock);
+    assert_eq!(final_stock, 104); // 100 - 5 + 10 - 3 + 2
 
     // Convert leads to deals and verify no conflicts
-    let deal1 = deal_service.create_deal_from_lead(
-        &mut conn, lead1.id, "Deal 1", 55000, None, None
+    let deal1 = DealService::create_deal(
+        &mut conn, lead1.id, "Deal 1", 55000, None, Some(employee_id), None, None
     ).unwrap();
 
-    let deal2 = deal_service.create_deal_from_lead(
-        &mut conn, lead2.id, "Deal 2", 65000, None, None
+    let deal2 = DealService::create_deal(
+        &mut conn, lead2.id, "Deal 2", 65000, None, Some(employee_id), None, None
     ).unwrap();
 
     // Both deals should exist and be valid
-    assert_eq!(deal1.lead_id, lead1.id);
-    assert_eq!(deal2.lead_id, lead2.id);
+    assert_eq!(deal1.lead_id, Some(lead1.id));
+    assert_eq!(deal2.lead_id, Some(lead2.id));
     assert_ne!(deal1.id, deal2.id);
 
     // Verify stock movement history is complete and ordered
@@ -981,39 +783,38 @@ fn test_concurrent_operations() {
 /// Test error handling and recovery scenarios
 #[test]
 fn test_error_handling_and_recovery() {
-    setup_test_db();
+    let company = setup_test_db();
+    let employee_id = company.employees[0].id;
 
     let mut conn = get_connection().expect("Failed to get connection");
 
-    let customer_service = CustomerService::new();
-    let lead_service = LeadService::new();
-    let deal_service = DealService::new();
     let product_service = ProductService::new();
     let category_service = CategoryService::new();
 
-    // Test duplicate prevention
-    let customer = customer_service.create_customer(
+    // Test input validation
+    let customer = CustomerService::create_customer(
         &mut conn, "Error Test Customer", CustomerType::Individual,
         Some("error@test.com"), None, None, None, None, None, None
     ).unwrap();
 
-    // Try to create customer with same email - should fail
-    let duplicate_result = customer_service.create_customer(
+    // Try to create customer with a malformed email - should fail
+    let invalid_email_result = CustomerService::create_customer(
         &mut conn, "Another Customer", CustomerType::Individual,
-        Some("error@test.com"), None, None, None, None, None, None
+        Some("not-an-email"), None, None, None, None, None, None
     );
-    assert!(duplicate_result.is_err());
+    assert!(invalid_email_result.is_err());
 
     // Test referential integrity
-    let lead = lead_service.create_lead(
+    let lead = LeadService::create_lead(
         &mut conn, "Error Test Lead", Some(customer.id), "Test",
-        10000, None, LeadPriority::Low, Some(1), None, None
+        10000, None, LeadPriority::Low, Some(employee_id), None, None
     ).unwrap();
+    assert_eq!(lead.customer_id, Some(customer.id));
 
     // Try to create lead with non-existent customer - should fail
-    let invalid_customer_result = lead_service.create_lead(
+    let invalid_customer_result = LeadService::create_lead(
         &mut conn, "Invalid Lead", Some(99999), "Test",
-        10000, None, LeadPriority::Low, Some(1), None, None
+        10000, None, LeadPriority::Low, Some(employee_id), None, None
     );
     assert!(invalid_customer_result.is_err());
 
@@ -1043,13 +844,13 @@ fn test_error_handling_and_recovery() {
 
     // Test stock validation
     let stock_reduction_result = product_service.update_stock(
-        valid_product.id, -50, "out", None, Some("test"), None, None, Some(1)
+        valid_product.id, -50, "out", None, Some("test"), None, None, Some(company.admin.id)
     );
     assert!(stock_reduction_result.is_err()); // Can't reduce more than available
 
     // Valid stock operation should work
     let valid_stock_result = product_service.update_stock(
-        valid_product.id, -5, "out", None, Some("test"), None, None, Some(1)
+        valid_product.id, -5, "out", None, Some("test"), None, None, Some(company.admin.id)
     );
     assert!(valid_stock_result.is_ok());
-}
\ No newline at end of file
+}